@@ -22,6 +22,34 @@ fn replacement_with_slash(updated_version: &str) -> String {
     format!(r#"$1/{}"#, updated_version)
 }
 
+pub mod types_derive {
+    use super::*;
+
+    pub static DEPENDENT_FILES: Lazy<Vec<DependentFile>> = Lazy::new(|| {
+        vec![
+            DependentFile::new(
+                "types/Cargo.toml",
+                Regex::new(r#"(?m)(^casper-types-derive = \{[^\}]*version = )"(?:[^"]+)"#)
+                    .unwrap(),
+                replacement,
+            ),
+            DependentFile::new(
+                "types_derive/Cargo.toml",
+                MANIFEST_VERSION_REGEX.clone(),
+                replacement,
+            ),
+            DependentFile::new(
+                "types_derive/src/lib.rs",
+                Regex::new(
+                    r#"(?m)(#!\[doc\(html_root_url = "https://docs.rs/casper-types-derive)/(?:[^"]+)"#,
+                )
+                .unwrap(),
+                replacement_with_slash,
+            ),
+        ]
+    });
+}
+
 pub mod types {
     use super::*;
 
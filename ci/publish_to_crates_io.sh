@@ -76,6 +76,7 @@ check_python_has_toml
 
 # These are the subdirs of casper-node which contain packages for publishing.  They should remain ordered from
 # least-dependent to most.
+publish types_derive
 publish types
 publish hashing
 publish execution_engine
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use lmdb::DatabaseFlags;
+use tempfile::TempDir;
+
+use casper_execution_engine::{
+    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+    storage::{
+        global_state::{lmdb::LmdbGlobalState, CommitProvider, StateProvider, StateReader},
+        transaction_source::lmdb::LmdbEnvironment,
+        trie_store::lmdb::LmdbTrieStore,
+    },
+};
+use casper_hashing::Digest;
+use casper_types::{CLValue, Key, StoredValue};
+
+const MAX_DB_SIZE: usize = 1024 * 1024 * 1024; // 1 GiB
+const MAX_READERS: u32 = 512;
+
+/// Number of keys the global state is populated with before benchmarking.
+const FIXTURE_KEY_COUNT: u64 = 10_000;
+/// Number of keys written by a single commit in the commit and flush benchmarks.
+const BATCH_SIZE: u64 = 100;
+/// Lengths of the prefix shared by all keys, which determine the depth of the trie.
+const SHARED_PREFIX_LENGTHS: [usize; 4] = [0, 8, 16, 24];
+
+/// Returns the key with the given index, whose first `prefix_length` address bytes are zero.
+fn key(prefix_length: usize, index: u64) -> Key {
+    let mut addr = Digest::hash(index.to_le_bytes()).value();
+    addr[..prefix_length].fill(0);
+    Key::Hash(addr)
+}
+
+fn value(index: u64) -> StoredValue {
+    StoredValue::CLValue(CLValue::from_t(index).unwrap())
+}
+
+/// Returns the effects writing the keys with indices in `indices`.
+fn writes(prefix_length: usize, indices: impl Iterator<Item = u64>) -> AdditiveMap<Key, Transform> {
+    indices
+        .map(|index| (key(prefix_length, index), Transform::Write(value(index))))
+        .collect()
+}
+
+/// An LMDB-backed global state populated with `FIXTURE_KEY_COUNT` keys.
+struct Fixture {
+    _temp_dir: TempDir,
+    global_state: LmdbGlobalState,
+    root_hash: Digest,
+    prefix_length: usize,
+}
+
+impl Fixture {
+    fn new(prefix_length: usize) -> Self {
+        let temp_dir = tempfile::tempdir().expect("should create temp dir");
+        let environment = Arc::new(
+            LmdbEnvironment::new(temp_dir.path(), MAX_DB_SIZE, MAX_READERS, true)
+                .expect("should create environment"),
+        );
+        let trie_store = Arc::new(
+            LmdbTrieStore::new(&environment, None, DatabaseFlags::empty())
+                .expect("should create trie store"),
+        );
+        let global_state =
+            LmdbGlobalState::empty(environment, trie_store).expect("should create global state");
+        let root_hash = global_state
+            .commit(
+                CorrelationId::new(),
+                global_state.empty_root(),
+                writes(prefix_length, 0..FIXTURE_KEY_COUNT),
+            )
+            .expect("should populate global state");
+        Fixture {
+            _temp_dir: temp_dir,
+            global_state,
+            root_hash,
+            prefix_length,
+        }
+    }
+
+    /// Returns the effects writing `count` keys not yet in the global state.
+    fn new_writes(&self, count: u64) -> AdditiveMap<Key, Transform> {
+        writes(
+            self.prefix_length,
+            FIXTURE_KEY_COUNT..FIXTURE_KEY_COUNT + count,
+        )
+    }
+}
+
+fn global_state_bench(c: &mut Criterion) {
+    for prefix_length in SHARED_PREFIX_LENGTHS {
+        let fixture = Fixture::new(prefix_length);
+        let id = BenchmarkId::from_parameter(prefix_length);
+
+        let mut group = c.benchmark_group("trie_read");
+        let view = fixture
+            .global_state
+            .checkout(fixture.root_hash)
+            .unwrap()
+            .expect("should have root");
+        let existing_key = key(prefix_length, FIXTURE_KEY_COUNT / 2);
+        group.bench_function(id.clone(), |b| {
+            b.iter(|| view.read(CorrelationId::new(), black_box(&existing_key)))
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group("trie_insert");
+        let insert = fixture.new_writes(1);
+        group.bench_function(id.clone(), |b| {
+            b.iter(|| {
+                fixture
+                    .global_state
+                    .commit(CorrelationId::new(), fixture.root_hash, insert.clone())
+                    .unwrap()
+            })
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group("trie_commit");
+        group.throughput(Throughput::Elements(BATCH_SIZE));
+        let batch = fixture.new_writes(BATCH_SIZE);
+        group.bench_function(id.clone(), |b| {
+            b.iter(|| {
+                fixture
+                    .global_state
+                    .commit(CorrelationId::new(), fixture.root_hash, batch.clone())
+                    .unwrap()
+            })
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group("scratch_flush");
+        group.throughput(Throughput::Elements(BATCH_SIZE));
+        group.bench_function(id, |b| {
+            b.iter_batched(
+                || {
+                    let scratch = fixture.global_state.create_scratch();
+                    scratch
+                        .commit(CorrelationId::new(), fixture.root_hash, batch.clone())
+                        .unwrap();
+                    scratch
+                },
+                |scratch| {
+                    fixture
+                        .global_state
+                        .put_stored_values(
+                            CorrelationId::new(),
+                            fixture.root_hash,
+                            scratch.into_inner(),
+                        )
+                        .unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, global_state_bench);
+criterion_main!(benches);
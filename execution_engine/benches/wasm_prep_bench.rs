@@ -0,0 +1,78 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use parity_wasm::{
+    builder,
+    elements::{BlockType, Instruction, Instructions},
+};
+
+use casper_execution_engine::shared::{wasm_config::WasmConfig, wasm_prep};
+
+/// Numbers of functions in the benchmarked modules.
+const FUNCTION_COUNTS: [u32; 3] = [10, 100, 1_000];
+/// Number of metered blocks in each function.
+const BLOCKS_PER_FUNCTION: i32 = 16;
+
+/// Returns a function body of `BLOCKS_PER_FUNCTION` conditional blocks doing some arithmetic.
+fn function_body() -> Instructions {
+    let mut instructions = Vec::new();
+    for index in 0..BLOCKS_PER_FUNCTION {
+        instructions.extend([
+            Instruction::I32Const(index),
+            Instruction::If(BlockType::NoResult),
+            Instruction::I32Const(index),
+            Instruction::I32Const(2),
+            Instruction::I32Mul,
+            Instruction::Drop,
+            Instruction::End,
+        ]);
+    }
+    instructions.push(Instruction::End);
+    Instructions::new(instructions)
+}
+
+/// Returns the bytes of a module with `function_count` functions, the first of which is exported
+/// as `call`.
+fn module_bytes(function_count: u32) -> Vec<u8> {
+    let mut module = builder::module();
+    for _ in 0..function_count {
+        module = module
+            .function()
+            .signature()
+            .build()
+            .body()
+            .with_instructions(function_body())
+            .build()
+            .build();
+    }
+    let module = module
+        .export()
+        .field("call")
+        .internal()
+        .func(0)
+        .build()
+        .memory()
+        .build()
+        .build();
+    parity_wasm::serialize(module).expect("should serialize")
+}
+
+fn wasm_prep_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wasm_preprocess");
+    for function_count in FUNCTION_COUNTS {
+        let module_bytes = module_bytes(function_count);
+        group.throughput(Throughput::Bytes(module_bytes.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(function_count),
+            &module_bytes,
+            |b, module_bytes| {
+                b.iter(|| {
+                    wasm_prep::preprocess(WasmConfig::default(), black_box(module_bytes))
+                        .expect("should preprocess")
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, wasm_prep_bench);
+criterion_main!(benches);
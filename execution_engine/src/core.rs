@@ -1,4 +1,5 @@
 //! The core of the smart contract execution logic.
+pub mod backend;
 pub mod engine_state;
 pub mod execution;
 pub mod resolvers;
@@ -0,0 +1,156 @@
+//! Abstraction over the engines able to execute Wasm.
+//!
+//! The runtime only talks to a Wasm engine through the traits defined here: an [`ExecutionBackend`]
+//! instantiates a preprocessed module into a [`WasmInstance`], whose exports are called with the
+//! host functions implemented by the runtime, and whose linear memory is exposed as a
+//! [`WasmMemory`].
+//!
+//! wasmi is the default backend.  An experimental wasmtime backend is available behind the
+//! `wasmtime-backend` feature.
+mod wasmi_backend;
+#[cfg(feature = "wasmtime-backend")]
+mod wasmtime_backend;
+
+use std::fmt::{self, Display, Formatter};
+
+use parity_wasm::elements::Module;
+use wasmi::{RuntimeArgs, RuntimeValue, Trap};
+
+use casper_types::ProtocolVersion;
+
+use crate::{
+    core::{execution::Error, resolvers::v1_function_index::FunctionIndex},
+    shared::{gas_profile, wasm_config::WasmConfig, wasm_prep},
+};
+
+/// The engine used to execute Wasm.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExecutionBackendKind {
+    /// The wasmi interpreter.
+    Wasmi,
+    /// The wasmtime compiler.  Experimental.
+    #[cfg(feature = "wasmtime-backend")]
+    Wasmtime,
+    /// Executes session code with both wasmi and wasmtime, and panics if their effects differ.
+    ///
+    /// The effects of the wasmi execution are the ones committed.  Only meant to be used in tests.
+    #[cfg(feature = "wasmtime-backend")]
+    Differential,
+}
+
+impl Default for ExecutionBackendKind {
+    fn default() -> Self {
+        ExecutionBackendKind::Wasmi
+    }
+}
+
+impl ExecutionBackendKind {
+    fn backend(self) -> &'static dyn ExecutionBackend {
+        match self {
+            ExecutionBackendKind::Wasmi => &wasmi_backend::WasmiBackend,
+            #[cfg(feature = "wasmtime-backend")]
+            ExecutionBackendKind::Wasmtime => &wasmtime_backend::WasmtimeBackend,
+            // The effects of a differential execution are the ones of its wasmi execution.
+            #[cfg(feature = "wasmtime-backend")]
+            ExecutionBackendKind::Differential => &wasmi_backend::WasmiBackend,
+        }
+    }
+}
+
+/// An error accessing the linear memory of a Wasm instance.
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryError(String);
+
+impl MemoryError {
+    pub(crate) fn new<T: Into<String>>(message: T) -> Self {
+        MemoryError(message.into())
+    }
+}
+
+impl Display for MemoryError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl From<MemoryError> for String {
+    fn from(error: MemoryError) -> Self {
+        error.0
+    }
+}
+
+impl From<MemoryError> for Error {
+    fn from(error: MemoryError) -> Self {
+        Error::Interpreter(error.into())
+    }
+}
+
+/// The linear memory of a Wasm instance.
+pub(crate) trait WasmMemory {
+    /// Returns a copy of `size` bytes of memory starting at `offset`.
+    fn get(&self, offset: u32, size: usize) -> Result<Vec<u8>, MemoryError>;
+
+    /// Copies `value` into memory starting at `offset`.
+    fn set(&self, offset: u32, value: &[u8]) -> Result<(), MemoryError>;
+}
+
+/// The host functions imported by a Wasm instance.
+pub(crate) trait HostFunctions {
+    /// Calls the host function `function` with `args`.
+    ///
+    /// The trap returned on error aborts the execution of the instance.
+    fn call_host_function(
+        &mut self,
+        function: FunctionIndex,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap>;
+
+    /// Charges `amount` of gas for the Wasm executed since the previous charge.
+    ///
+    /// This is the hook called by the metering injected during preprocessing.
+    fn charge_wasm_gas(&mut self, amount: u32) -> Result<(), Trap>;
+}
+
+/// An instantiated Wasm module.
+pub(crate) trait WasmInstance {
+    /// Returns a handle to the instance's linear memory.
+    fn memory(&self) -> Box<dyn WasmMemory>;
+
+    /// Calls the export `name`, which takes no arguments and returns nothing.
+    ///
+    /// Errors raised by `host` are returned unchanged, including the [`Error::Ret`] used to return
+    /// early from the instance.
+    fn call_export(&mut self, name: &str, host: &mut dyn HostFunctions) -> Result<(), Error>;
+}
+
+/// An engine able to execute Wasm.
+pub(crate) trait ExecutionBackend {
+    /// Instantiates `module`, resolving its imports to the host functions and memory of the given
+    /// protocol version.
+    ///
+    /// Modules with a "start" section are rejected, as running it is not supported.
+    fn instantiate(
+        &self,
+        module: Module,
+        protocol_version: ProtocolVersion,
+        wasm_config: &WasmConfig,
+    ) -> Result<Box<dyn WasmInstance>, Error>;
+}
+
+/// Instantiates `module` with the backend of the given kind.
+///
+/// If gas usage is being profiled, the module is first instrumented to report its opcodes.
+pub(crate) fn instantiate(
+    kind: ExecutionBackendKind,
+    module: Module,
+    protocol_version: ProtocolVersion,
+    wasm_config: &WasmConfig,
+) -> Result<Box<dyn WasmInstance>, Error> {
+    let module = if gas_profile::is_active() {
+        wasm_prep::inject_opcode_profiling(module)
+    } else {
+        module
+    };
+    kind.backend()
+        .instantiate(module, protocol_version, wasm_config)
+}
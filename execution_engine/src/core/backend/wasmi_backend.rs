@@ -0,0 +1,111 @@
+//! The wasmi interpreter backend.
+use std::convert::TryFrom;
+
+use parity_wasm::elements::Module;
+use wasmi::{
+    Externals, ImportsBuilder, MemoryInstance, MemoryRef, ModuleInstance, ModuleRef, RuntimeArgs,
+    RuntimeValue, Trap,
+};
+
+use casper_types::ProtocolVersion;
+
+use super::{ExecutionBackend, HostFunctions, MemoryError, WasmInstance, WasmMemory};
+use crate::{
+    core::{
+        execution::Error,
+        resolvers::{self, memory_resolver::MemoryResolver, v1_function_index::FunctionIndex},
+    },
+    shared::wasm_config::WasmConfig,
+};
+
+/// Executes Wasm with the wasmi interpreter.
+pub(super) struct WasmiBackend;
+
+impl ExecutionBackend for WasmiBackend {
+    /// Creates a wasmi module instance and a memory instance.
+    ///
+    /// This ensures that a memory instance is properly resolved into a pre-allocated memory area,
+    /// and a host function resolver is attached to the module.
+    fn instantiate(
+        &self,
+        module: Module,
+        protocol_version: ProtocolVersion,
+        wasm_config: &WasmConfig,
+    ) -> Result<Box<dyn WasmInstance>, Error> {
+        let module = wasmi::Module::from_parity_wasm_module(module)?;
+        let resolver = resolvers::create_module_resolver(protocol_version, wasm_config)?;
+        let mut imports = ImportsBuilder::new();
+        imports.push_resolver("env", &resolver);
+        let not_started_module = ModuleInstance::new(&module, &imports)?;
+        if not_started_module.has_start() {
+            return Err(Error::UnsupportedWasmStart);
+        }
+        let instance = not_started_module.not_started_instance().clone();
+        let memory = resolver.memory_ref()?;
+        Ok(Box::new(WasmiInstance { instance, memory }))
+    }
+}
+
+struct WasmiInstance {
+    instance: ModuleRef,
+    memory: MemoryRef,
+}
+
+impl WasmInstance for WasmiInstance {
+    fn memory(&self) -> Box<dyn WasmMemory> {
+        Box::new(self.memory.clone())
+    }
+
+    fn call_export(&mut self, name: &str, host: &mut dyn HostFunctions) -> Result<(), Error> {
+        let error = match self
+            .instance
+            .invoke_export(name, &[], &mut HostExternals(host))
+        {
+            Ok(_) => return Ok(()),
+            Err(error) => error,
+        };
+
+        if let Some(host_error) = error.as_host_error() {
+            return match host_error.downcast_ref::<Error>() {
+                Some(error) => Err(error.clone()),
+                None => Err(Error::Interpreter(host_error.to_string())),
+            };
+        }
+        Err(Error::Interpreter(error.into()))
+    }
+}
+
+impl WasmMemory for MemoryRef {
+    fn get(&self, offset: u32, size: usize) -> Result<Vec<u8>, MemoryError> {
+        MemoryInstance::get(self, offset, size).map_err(MemoryError::from)
+    }
+
+    fn set(&self, offset: u32, value: &[u8]) -> Result<(), MemoryError> {
+        MemoryInstance::set(self, offset, value).map_err(MemoryError::from)
+    }
+}
+
+impl From<wasmi::Error> for MemoryError {
+    fn from(error: wasmi::Error) -> Self {
+        MemoryError::new(error)
+    }
+}
+
+/// Dispatches the host function calls of a wasmi instance to `HostFunctions`.
+struct HostExternals<'a>(&'a mut dyn HostFunctions);
+
+impl<'a> Externals for HostExternals<'a> {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        let function = FunctionIndex::try_from(index).expect("unknown function index");
+        if function == FunctionIndex::GasFuncIndex {
+            let amount: u32 = args.nth_checked(0)?;
+            self.0.charge_wasm_gas(amount)?;
+            return Ok(None);
+        }
+        self.0.call_host_function(function, args)
+    }
+}
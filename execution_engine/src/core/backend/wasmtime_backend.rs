@@ -0,0 +1,429 @@
+//! The experimental wasmtime backend.
+//!
+//! Imports are resolved through the same resolver as the wasmi backend, so both backends accept
+//! the same modules and expose the same host functions with the same signatures.
+use std::{cell::Cell, convert::TryFrom, mem, ptr::NonNull, rc::Rc, slice};
+
+use once_cell::sync::Lazy;
+use parity_wasm::elements::{self, External, FunctionType, Module, Type};
+use wasmi::{
+    nan_preserving_float::{F32, F64},
+    ModuleImportResolver, RuntimeArgs, RuntimeValue, Signature, TrapKind, ValueType,
+};
+use wasmtime::{
+    Caller, Config, Engine, FuncType, Instance, Linker, Memory, MemoryType, Store, Trap, Val,
+    ValType,
+};
+
+use casper_types::ProtocolVersion;
+
+use super::{ExecutionBackend, HostFunctions, MemoryError, WasmInstance, WasmMemory};
+use crate::{
+    core::{
+        execution::Error,
+        resolvers::{self, v1_function_index::FunctionIndex},
+    },
+    shared::wasm_config::WasmConfig,
+};
+
+/// The name of the only module whose imports can be resolved.
+const HOST_MODULE_NAME: &str = "env";
+/// The name of the only memory which can be imported.
+const MEMORY_NAME: &str = "memory";
+
+/// The engine shared by all wasmtime instances.
+static ENGINE: Lazy<Engine> = Lazy::new(|| {
+    let mut config = Config::new();
+    // Only accept the Wasm features accepted by wasmi, and canonicalize NaNs so that floating point
+    // results are deterministic.
+    config
+        .wasm_bulk_memory(false)
+        .wasm_reference_types(false)
+        .wasm_simd(false)
+        .wasm_multi_value(false)
+        .cranelift_nan_canonicalization(true);
+    Engine::new(&config).expect("should create wasmtime engine")
+});
+
+/// Executes Wasm with the wasmtime compiler.
+pub(super) struct WasmtimeBackend;
+
+impl ExecutionBackend for WasmtimeBackend {
+    fn instantiate(
+        &self,
+        module: Module,
+        protocol_version: ProtocolVersion,
+        wasm_config: &WasmConfig,
+    ) -> Result<Box<dyn WasmInstance>, Error> {
+        if module.start_section().is_some() {
+            return Err(Error::UnsupportedWasmStart);
+        }
+
+        let view = Rc::new(Cell::new(None));
+        let mut store = Store::new(
+            &ENGINE,
+            HostState {
+                host: None,
+                memory: None,
+                view: Rc::clone(&view),
+                host_error: None,
+            },
+        );
+        let mut linker = Linker::new(&ENGINE);
+        let resolver = resolvers::create_module_resolver(protocol_version, wasm_config)?;
+
+        let function_types = module
+            .type_section()
+            .map(|section| section.types())
+            .unwrap_or_default();
+        let imports = module
+            .import_section()
+            .map(|section| section.entries())
+            .unwrap_or_default();
+        for import in imports {
+            if import.module() != HOST_MODULE_NAME {
+                return Err(Error::Interpreter(format!(
+                    "Module {} not found",
+                    import.module()
+                )));
+            }
+            let name = import.field();
+            match import.external() {
+                External::Function(type_index) => {
+                    let Type::Function(function_type) =
+                        function_types.get(*type_index as usize).ok_or_else(|| {
+                            Error::Interpreter(format!("Type {} not found", type_index))
+                        })?;
+                    let signature = wasmi_signature(function_type)?;
+                    // The resolver is the source of truth for the signatures of host functions.
+                    let func_ref = resolver.resolve_func(name, &signature)?;
+                    if *func_ref.signature() != signature {
+                        return Err(Error::Interpreter(format!(
+                            "Export {} has different signature {:?}",
+                            name,
+                            func_ref.signature()
+                        )));
+                    }
+                    let function = function_index(name)
+                        .ok_or_else(|| Error::Interpreter(format!("Export {} not found", name)))?;
+                    linker
+                        .func_new(
+                            HOST_MODULE_NAME,
+                            name,
+                            wasmtime_func_type(function_type)?,
+                            move |caller, params, results| {
+                                call_host_function(caller, function, params, results)
+                            },
+                        )
+                        .map_err(|error| Error::Interpreter(error.to_string()))?;
+                }
+                External::Memory(memory_type) if name == MEMORY_NAME => {
+                    if store.data().memory.is_some() {
+                        return Err(Error::Interpreter("Memory is already instantiated".into()));
+                    }
+                    let limits = memory_type.limits();
+                    let maximum = limits.maximum().unwrap_or(wasm_config.max_memory);
+                    // Checks if wasm's memory entry has too much initial memory or non-default max
+                    // memory pages exceeds the limit.
+                    if limits.initial() > maximum || maximum > wasm_config.max_memory {
+                        return Err(Error::Interpreter(
+                            "Module requested too much memory".into(),
+                        ));
+                    }
+                    let memory = Memory::new(
+                        &mut store,
+                        MemoryType::new(limits.initial(), limits.maximum()),
+                    )
+                    .map_err(|error| Error::Interpreter(error.to_string()))?;
+                    linker
+                        .define(HOST_MODULE_NAME, name, memory)
+                        .map_err(|error| Error::Interpreter(error.to_string()))?;
+                    store.data_mut().memory = Some(memory);
+                }
+                _ => {
+                    return Err(Error::Interpreter(format!(
+                        "host module doesn't export {} with name {}",
+                        external_kind(import.external()),
+                        name
+                    )));
+                }
+            }
+        }
+
+        let bytes =
+            elements::serialize(module).map_err(|error| Error::Interpreter(error.to_string()))?;
+        let module = wasmtime::Module::new(&ENGINE, bytes)
+            .map_err(|error| Error::Interpreter(error.to_string()))?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(|error| Error::Interpreter(error.to_string()))?;
+
+        Ok(Box::new(WasmtimeInstance {
+            store,
+            instance,
+            view,
+        }))
+    }
+}
+
+/// The data of the store of a wasmtime instance.
+struct HostState {
+    /// The host functions of the export being called, if any.
+    host: Option<NonNull<dyn HostFunctions>>,
+    /// The imported memory, if any.
+    memory: Option<Memory>,
+    /// The view of `memory` while a host function is being called.
+    view: Rc<Cell<Option<(NonNull<u8>, usize)>>>,
+    /// The error raised by the last host function call which trapped.
+    host_error: Option<Error>,
+}
+
+struct WasmtimeInstance {
+    store: Store<HostState>,
+    instance: Instance,
+    view: Rc<Cell<Option<(NonNull<u8>, usize)>>>,
+}
+
+impl WasmInstance for WasmtimeInstance {
+    fn memory(&self) -> Box<dyn WasmMemory> {
+        Box::new(WasmtimeMemory {
+            view: Rc::clone(&self.view),
+        })
+    }
+
+    fn call_export(&mut self, name: &str, host: &mut dyn HostFunctions) -> Result<(), Error> {
+        let func = self
+            .instance
+            .get_func(&mut self.store, name)
+            .ok_or_else(|| Error::Interpreter(format!("Export {} not found", name)))?;
+
+        let host: NonNull<dyn HostFunctions + '_> = NonNull::from(host);
+        // SAFETY: this only erases the lifetime of `host`.  The pointer is cleared before returning,
+        // so it is never dereferenced after the borrow it was created from ends.
+        let host: NonNull<dyn HostFunctions> = unsafe { mem::transmute(host) };
+        self.store.data_mut().host = Some(host);
+        self.store.data_mut().host_error = None;
+        let result = func.call(&mut self.store, &[], &mut []);
+        self.store.data_mut().host = None;
+
+        match (result, self.store.data_mut().host_error.take()) {
+            (Ok(()), _) => Ok(()),
+            (Err(_), Some(error)) => Err(error),
+            (Err(error), None) => Err(Error::Interpreter(error.to_string())),
+        }
+    }
+}
+
+/// Calls `function` on the host functions of the export being called by `caller`.
+fn call_host_function(
+    mut caller: Caller<'_, HostState>,
+    function: FunctionIndex,
+    params: &[Val],
+    results: &mut [Val],
+) -> Result<(), Trap> {
+    let host = caller
+        .data()
+        .host
+        .ok_or_else(|| Trap::new("host function called outside of an export call"))?;
+    let args = params
+        .iter()
+        .map(runtime_value)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The memory can only be accessed by the host while it's not accessed by the instance, i.e.
+    // while the host function is being called.
+    let view = Rc::clone(&caller.data().view);
+    if let Some(memory) = caller.data().memory {
+        let data = memory.data_mut(&mut caller);
+        view.set(NonNull::new(data.as_mut_ptr()).map(|data_ptr| (data_ptr, data.len())));
+    }
+    // SAFETY: `host` was set by `call_export` from a mutable borrow which outlives the call of the
+    // export, and is only dereferenced by the host function being called.
+    let host = unsafe { &mut *host.as_ptr() };
+    let result = if function == FunctionIndex::GasFuncIndex {
+        RuntimeArgs::from(args.as_slice())
+            .nth_checked(0)
+            .and_then(|amount| host.charge_wasm_gas(amount))
+            .map(|()| None)
+    } else {
+        host.call_host_function(function, RuntimeArgs::from(args.as_slice()))
+    };
+    view.set(None);
+
+    match result {
+        Ok(Some(value)) => {
+            if let Some(result) = results.first_mut() {
+                *result = wasmtime_value(value);
+            }
+            Ok(())
+        }
+        Ok(None) => Ok(()),
+        Err(trap) => {
+            if let TrapKind::Host(host_error) = trap.kind() {
+                if let Some(error) = host_error.downcast_ref::<Error>() {
+                    caller.data_mut().host_error = Some(error.clone());
+                }
+            }
+            Err(Trap::new(format!("{:?}", trap)))
+        }
+    }
+}
+
+/// The linear memory of a wasmtime instance.
+///
+/// The memory is only accessible while a host function is being called.
+struct WasmtimeMemory {
+    view: Rc<Cell<Option<(NonNull<u8>, usize)>>>,
+}
+
+impl WasmtimeMemory {
+    fn region(&self, offset: u32, size: usize) -> Result<(NonNull<u8>, usize), MemoryError> {
+        let (data_ptr, length) = self
+            .view
+            .get()
+            .ok_or_else(|| MemoryError::new("memory accessed outside of a host function call"))?;
+        let start = offset as usize;
+        match start.checked_add(size) {
+            Some(end) if end <= length => Ok((data_ptr, start)),
+            _ => Err(MemoryError::new(format!(
+                "trying to access region [{}..{}] in memory [0..{}]",
+                start,
+                start.saturating_add(size),
+                length
+            ))),
+        }
+    }
+}
+
+impl WasmMemory for WasmtimeMemory {
+    fn get(&self, offset: u32, size: usize) -> Result<Vec<u8>, MemoryError> {
+        let (data_ptr, start) = self.region(offset, size)?;
+        // SAFETY: the region is within the memory's bounds, and the view is only set while a host
+        // function is being called, during which the memory can't be accessed by the instance.
+        let region = unsafe { slice::from_raw_parts(data_ptr.as_ptr().add(start), size) };
+        Ok(region.to_vec())
+    }
+
+    fn set(&self, offset: u32, value: &[u8]) -> Result<(), MemoryError> {
+        let (data_ptr, start) = self.region(offset, value.len())?;
+        // SAFETY: the region is within the memory's bounds, and the view is only set while a host
+        // function is being called, during which the memory can't be accessed by the instance.
+        let region =
+            unsafe { slice::from_raw_parts_mut(data_ptr.as_ptr().add(start), value.len()) };
+        region.copy_from_slice(value);
+        Ok(())
+    }
+}
+
+/// Returns the host function imported as `name`.
+fn function_index(name: &str) -> Option<FunctionIndex> {
+    (0..)
+        .map_while(|index| FunctionIndex::try_from(index).ok())
+        .find(|function| function.import_name() == name)
+}
+
+fn external_kind(external: &External) -> &'static str {
+    match external {
+        External::Function(_) => "function",
+        External::Table(_) => "table",
+        External::Memory(_) => "memory",
+        External::Global(_) => "global",
+    }
+}
+
+fn wasmi_value_type(value_type: elements::ValueType) -> Result<ValueType, Error> {
+    match value_type {
+        elements::ValueType::I32 => Ok(ValueType::I32),
+        elements::ValueType::I64 => Ok(ValueType::I64),
+        elements::ValueType::F32 => Ok(ValueType::F32),
+        elements::ValueType::F64 => Ok(ValueType::F64),
+        #[allow(unreachable_patterns)]
+        other => Err(Error::Interpreter(format!(
+            "unsupported value type {:?}",
+            other
+        ))),
+    }
+}
+
+fn wasmi_signature(function_type: &FunctionType) -> Result<Signature, Error> {
+    let params = function_type
+        .params()
+        .iter()
+        .map(|param| wasmi_value_type(*param))
+        .collect::<Result<Vec<_>, _>>()?;
+    let return_type = function_type
+        .return_type()
+        .map(wasmi_value_type)
+        .transpose()?;
+    Ok(Signature::new(params, return_type))
+}
+
+fn wasmtime_func_type(function_type: &FunctionType) -> Result<FuncType, Error> {
+    let to_val_type = |value_type| {
+        wasmi_value_type(value_type).map(|value_type| match value_type {
+            ValueType::I32 => ValType::I32,
+            ValueType::I64 => ValType::I64,
+            ValueType::F32 => ValType::F32,
+            ValueType::F64 => ValType::F64,
+        })
+    };
+    let params = function_type
+        .params()
+        .iter()
+        .map(|param| to_val_type(*param))
+        .collect::<Result<Vec<_>, _>>()?;
+    let results = function_type.return_type().map(to_val_type).transpose()?;
+    Ok(FuncType::new(params, results))
+}
+
+fn runtime_value(value: &Val) -> Result<RuntimeValue, Trap> {
+    match value {
+        Val::I32(value) => Ok(RuntimeValue::I32(*value)),
+        Val::I64(value) => Ok(RuntimeValue::I64(*value)),
+        Val::F32(bits) => Ok(RuntimeValue::F32(F32::from_bits(*bits))),
+        Val::F64(bits) => Ok(RuntimeValue::F64(F64::from_bits(*bits))),
+        other => Err(Trap::new(format!("unsupported value {:?}", other))),
+    }
+}
+
+fn wasmtime_value(value: RuntimeValue) -> Val {
+    match value {
+        RuntimeValue::I32(value) => Val::I32(value),
+        RuntimeValue::I64(value) => Val::I64(value),
+        RuntimeValue::F32(value) => Val::F32(value.to_bits()),
+        RuntimeValue::F64(value) => Val::F64(value.to_bits()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_access_memory_within_bounds_during_host_calls() {
+        let mut data = vec![0u8; 8];
+        let view = Rc::new(Cell::new(None));
+        let memory = WasmtimeMemory {
+            view: Rc::clone(&view),
+        };
+        assert!(memory.get(0, 1).is_err());
+
+        view.set(Some((NonNull::new(data.as_mut_ptr()).unwrap(), data.len())));
+        memory.set(6, &[1, 2]).expect("should write within bounds");
+        assert_eq!(
+            memory.get(6, 2).expect("should read within bounds"),
+            vec![1, 2]
+        );
+        assert!(memory.get(7, 2).is_err());
+        assert!(memory.set(u32::MAX, &[1]).is_err());
+
+        view.set(None);
+        assert!(memory.get(6, 2).is_err());
+    }
+
+    #[test]
+    fn should_find_function_index_by_import_name() {
+        assert_eq!(function_index("gas"), Some(FunctionIndex::GasFuncIndex));
+        assert_eq!(function_index("unknown"), None);
+    }
+}
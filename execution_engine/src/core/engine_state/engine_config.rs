@@ -1,6 +1,9 @@
 //! Support for runtime configuration of the execution engine - as an integral property of the
 //! `EngineState` instance.
-use crate::shared::{system_config::SystemConfig, wasm_config::WasmConfig};
+use crate::{
+    core::{backend::ExecutionBackendKind, engine_state::module_cache::DEFAULT_MODULE_CACHE_SIZE},
+    shared::{system_config::SystemConfig, wasm_config::WasmConfig},
+};
 
 /// Default value for a maximum query depth configuration option.
 pub const DEFAULT_MAX_QUERY_DEPTH: u64 = 5;
@@ -37,6 +40,15 @@ pub struct EngineConfig {
     vesting_schedule_period_millis: u64,
     wasm_config: WasmConfig,
     system_config: SystemConfig,
+    /// The backend used to execute Wasm.
+    execution_backend: ExecutionBackendKind,
+    /// Maximum number of parsed Wasm modules of stored contracts kept in memory.
+    module_cache_size: usize,
+    /// Whether transfers are recorded as `TransferV2`s, with their initiator, memo and phase.
+    record_transfer_metadata: bool,
+    /// Whether auction callers may be authorized by account hashes derived via
+    /// [`AccountHashVersion::V2`](casper_types::account::AccountHashVersion::V2).
+    allow_account_hash_v2: bool,
 }
 
 impl Default for EngineConfig {
@@ -50,6 +62,10 @@ impl Default for EngineConfig {
             vesting_schedule_period_millis: DEFAULT_VESTING_SCHEDULE_LENGTH_MILLIS,
             wasm_config: WasmConfig::default(),
             system_config: SystemConfig::default(),
+            execution_backend: ExecutionBackendKind::default(),
+            module_cache_size: DEFAULT_MODULE_CACHE_SIZE,
+            record_transfer_metadata: false,
+            allow_account_hash_v2: false,
         }
     }
 }
@@ -76,9 +92,42 @@ impl EngineConfig {
             vesting_schedule_period_millis,
             wasm_config,
             system_config,
+            execution_backend: ExecutionBackendKind::default(),
+            module_cache_size: DEFAULT_MODULE_CACHE_SIZE,
+            record_transfer_metadata: false,
+            allow_account_hash_v2: false,
         }
     }
 
+    /// Returns a copy of this config using the given backend to execute Wasm.
+    pub fn with_execution_backend(mut self, execution_backend: ExecutionBackendKind) -> Self {
+        self.execution_backend = execution_backend;
+        self
+    }
+
+    /// Returns a copy of this config caching up to `module_cache_size` parsed Wasm modules of
+    /// stored contracts.  A size of 0 disables the cache.
+    pub fn with_module_cache_size(mut self, module_cache_size: usize) -> Self {
+        self.module_cache_size = module_cache_size;
+        self
+    }
+
+    /// Returns a copy of this config recording transfers as `TransferV2`s, with their initiator,
+    /// memo and phase, if `record_transfer_metadata` is `true`.  Otherwise legacy `Transfer`s are
+    /// recorded and the "memo" argument of transfers is ignored.
+    pub fn with_record_transfer_metadata(mut self, record_transfer_metadata: bool) -> Self {
+        self.record_transfer_metadata = record_transfer_metadata;
+        self
+    }
+
+    /// Returns a copy of this config authorizing auction callers by account hashes derived via
+    /// `AccountHashVersion::V2` as well as `V1` if `allow_account_hash_v2` is `true`.  Otherwise
+    /// only `V1` account hashes are accepted.
+    pub fn with_allow_account_hash_v2(mut self, allow_account_hash_v2: bool) -> Self {
+        self.allow_account_hash_v2 = allow_account_hash_v2;
+        self
+    }
+
     /// Returns the current max associated keys config.
     pub fn max_associated_keys(&self) -> u32 {
         self.max_associated_keys
@@ -113,4 +162,24 @@ impl EngineConfig {
     pub fn vesting_schedule_period_millis(&self) -> u64 {
         self.vesting_schedule_period_millis
     }
+
+    /// Returns the backend used to execute Wasm.
+    pub fn execution_backend(&self) -> ExecutionBackendKind {
+        self.execution_backend
+    }
+
+    /// Returns the maximum number of parsed Wasm modules kept in memory.
+    pub fn module_cache_size(&self) -> usize {
+        self.module_cache_size
+    }
+
+    /// Returns `true` if transfers are recorded as `TransferV2`s.
+    pub fn record_transfer_metadata(&self) -> bool {
+        self.record_transfer_metadata
+    }
+
+    /// Returns `true` if auction callers may be authorized by `V2` account hashes.
+    pub fn allow_account_hash_v2(&self) -> bool {
+        self.allow_account_hash_v2
+    }
 }
@@ -7,7 +7,7 @@ use casper_types::{ProtocolVersion, PublicKey, SecretKey};
 use super::deploy_item::DeployItem;
 
 /// Represents an execution request that can contain multiple deploys.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ExecuteRequest {
     /// State root hash of the global state in which the deploys will be executed.
     pub parent_state_hash: Digest,
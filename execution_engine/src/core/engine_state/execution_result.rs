@@ -3,7 +3,8 @@
 use std::collections::VecDeque;
 
 use casper_types::{
-    bytesrepr::FromBytes, CLTyped, CLValue, Gas, Key, Motes, StoredValue, TransferAddr,
+    bytesrepr::FromBytes, CLTyped, CLValue, ExecutionResultV2, FailureReason, Gas, Key, Motes,
+    StoredValue, TransferAddr,
 };
 
 use super::error;
@@ -396,6 +397,32 @@ impl From<ExecutionResult> for casper_types::ExecutionResult {
     }
 }
 
+impl From<&ExecutionResult> for ExecutionResultV2 {
+    fn from(ee_execution_result: &ExecutionResult) -> Self {
+        let mut execution_result =
+            ExecutionResultV2::from(casper_types::ExecutionResult::from(ee_execution_result));
+        if let (ExecutionResult::Failure { error, .. }, Some(failure)) =
+            (ee_execution_result, execution_result.failure.as_mut())
+        {
+            failure.reason = failure_reason(error);
+        }
+        execution_result
+    }
+}
+
+/// Returns the category of the failure caused by `error`.
+fn failure_reason(error: &error::Error) -> FailureReason {
+    match error {
+        error::Error::Exec(ExecError::Revert(api_error)) => FailureReason::Revert {
+            error_code: (*api_error).into(),
+        },
+        error::Error::Exec(ExecError::GasLimit) => FailureReason::GasLimit,
+        error::Error::InsufficientPayment => FailureReason::InsufficientPayment,
+        error::Error::Finalization => FailureReason::Finalization,
+        _ => FailureReason::Other,
+    }
+}
+
 /// Represents error conditions of an execution result builder.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ExecutionResultBuilderError {
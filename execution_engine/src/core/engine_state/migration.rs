@@ -0,0 +1,97 @@
+//! Support for typed global state migrations run as part of a protocol upgrade.
+use thiserror::Error;
+
+use casper_types::{Key, KeyTag, StoredValue};
+
+use crate::{
+    core::{execution, tracking_copy::TrackingCopy},
+    shared::newtypes::CorrelationId,
+    storage::global_state::StateReader,
+};
+
+/// A single migration of the global state, applied at the upgrade point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Migration {
+    /// Writes the value stored under `from` to `to`.
+    ///
+    /// As global state entries cannot be deleted, the value under `from` is left in place.
+    RewriteKey {
+        /// The key the value is currently stored under.
+        from: Key,
+        /// The key the value is written to.
+        to: Key,
+    },
+    /// Reads every value stored under a key with the given tag and writes it back, re-encoding it
+    /// in the current serialization format.
+    ReencodeValues {
+        /// The tag of the keys whose values are re-encoded.
+        key_tag: KeyTag,
+    },
+    /// Writes `value` under `key` unless a value is already stored there, e.g. to seed a new
+    /// system registry.
+    SeedValue {
+        /// The key to seed.
+        key: Key,
+        /// The initial value.
+        value: StoredValue,
+    },
+}
+
+/// Represents outcomes of a failed global state migration.
+#[derive(Clone, Error, Debug)]
+pub enum MigrationError {
+    /// The value to be migrated is absent from global state.
+    #[error("Missing value under key {0}")]
+    MissingValue(Key),
+    /// Error accessing global state.
+    #[error("Global state error: {0}")]
+    GlobalState(String),
+}
+
+fn global_state_error<E: Into<execution::Error>>(error: E) -> MigrationError {
+    MigrationError::GlobalState(error.into().to_string())
+}
+
+/// Applies `migrations` to the tracking copy, in order.
+pub(crate) fn apply_migrations<R>(
+    correlation_id: CorrelationId,
+    tracking_copy: &mut TrackingCopy<R>,
+    migrations: &[Migration],
+) -> Result<(), MigrationError>
+where
+    R: StateReader<Key, StoredValue>,
+    R::Error: Into<execution::Error>,
+{
+    for migration in migrations {
+        match migration {
+            Migration::RewriteKey { from, to } => {
+                let value = tracking_copy
+                    .read(correlation_id, from)
+                    .map_err(global_state_error)?
+                    .ok_or(MigrationError::MissingValue(*from))?;
+                tracking_copy.write(*to, value);
+            }
+            Migration::ReencodeValues { key_tag } => {
+                let keys = tracking_copy
+                    .get_keys(correlation_id, key_tag)
+                    .map_err(global_state_error)?;
+                for key in keys {
+                    let value = tracking_copy
+                        .read(correlation_id, &key)
+                        .map_err(global_state_error)?
+                        .ok_or(MigrationError::MissingValue(key))?;
+                    tracking_copy.write(key, value);
+                }
+            }
+            Migration::SeedValue { key, value } => {
+                let existing = tracking_copy
+                    .read(correlation_id, key)
+                    .map_err(global_state_error)?;
+                if existing.is_none() {
+                    tracking_copy.write(*key, value.clone());
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -11,7 +11,10 @@ pub mod execution_effect;
 pub mod execution_result;
 pub mod genesis;
 pub mod get_bids;
+pub mod migration;
+pub mod module_cache;
 pub mod op;
+pub mod optimistic;
 pub mod query;
 pub mod run_genesis_request;
 pub mod step;
@@ -24,6 +27,7 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
     rc::Rc,
+    sync::Arc,
 };
 
 use num::Zero;
@@ -38,10 +42,10 @@ use casper_types::{
     contracts::NamedKeys,
     system::{
         auction::{
-            EraValidators, ARG_ERA_END_TIMESTAMP_MILLIS, ARG_EVICTED_VALIDATORS,
-            ARG_REWARD_FACTORS, ARG_VALIDATOR_PUBLIC_KEYS, AUCTION_DELAY_KEY,
-            LOCKED_FUNDS_PERIOD_KEY, SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY, UNBONDING_DELAY_KEY,
-            VALIDATOR_SLOTS_KEY,
+            EraValidators, SeigniorageRecipientsSnapshot, ARG_ERA_END_TIMESTAMP_MILLIS,
+            ARG_EVICTED_VALIDATORS, ARG_REWARD_FACTORS, ARG_VALIDATOR_PUBLIC_KEYS,
+            AUCTION_DELAY_KEY, LOCKED_FUNDS_PERIOD_KEY, SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY,
+            UNBONDING_DELAY_KEY, VALIDATOR_SLOTS_KEY,
         },
         handle_payment,
         mint::{self, ROUND_SEIGNIORAGE_RATE_KEY},
@@ -64,6 +68,8 @@ pub use self::{
     execution_result::{ExecutionResult, ForcedTransferResult},
     genesis::{ExecConfig, GenesisAccount, GenesisConfig, GenesisSuccess},
     get_bids::{GetBidsRequest, GetBidsResult},
+    migration::Migration,
+    module_cache::{ModuleCache, ModuleCacheStats},
     query::{QueryRequest, QueryResult},
     run_genesis_request::RunGenesisRequest,
     step::{RewardItem, SlashItem, StepError, StepRequest, StepSuccess},
@@ -75,6 +81,7 @@ use crate::{
     core::{
         engine_state::{
             executable_deploy_item::ExecutionKind,
+            execution_effect::ExecutionEffect,
             execution_result::{ExecutionResultBuilder, ExecutionResults},
             genesis::GenesisInstaller,
             upgrade::{ProtocolUpgradeError, SystemUpgrader},
@@ -83,10 +90,14 @@ use crate::{
         runtime::RuntimeStack,
         tracking_copy::{TrackingCopy, TrackingCopyExt},
     },
-    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+    shared::{
+        additive_map::AdditiveMap, execution_journal::ExecutionJournal, newtypes::CorrelationId,
+        transform::Transform,
+    },
     storage::{
         global_state::{
             lmdb::LmdbGlobalState, scratch::ScratchGlobalState, CommitProvider, StateProvider,
+            StateReader,
         },
         trie::{TrieOrChunk, TrieOrChunkId},
     },
@@ -106,6 +117,52 @@ pub static MAX_PAYMENT: Lazy<U512> = Lazy::new(|| U512::from(MAX_PAYMENT_AMOUNT)
 /// pay.
 pub const WASMLESS_TRANSFER_FIXED_GAS_PRICE: u64 = 1;
 
+/// A deploy executed up to, but not including, the finalization of its payment.
+struct PendingFinalization {
+    execution_result_builder: ExecutionResultBuilder,
+    handle_payment_args: RuntimeArgs,
+    /// The payment purse, if the handle payment contract is to be looked up again before
+    /// finalizing.
+    payment_purse_key: Option<Key>,
+    proposer_purse: URef,
+    authorization_keys: BTreeSet<AccountHash>,
+    blocktime: BlockTime,
+    deploy_hash: DeployHash,
+    protocol_version: ProtocolVersion,
+    gas_limit: Gas,
+    spending_limit: U512,
+}
+
+/// How far a deploy has been executed.
+#[allow(clippy::large_enum_variant)]
+enum DeployExecution {
+    /// The deploy was executed in full.
+    Finished(ExecutionResult),
+    /// The deploy was executed up to the finalization of its payment.
+    Pending {
+        /// The effects of the deploy so far.
+        journal: ExecutionJournal,
+        /// What is left to finalize the payment.
+        finalization: PendingFinalization,
+    },
+}
+
+impl DeployExecution {
+    /// Returns the result of a deploy whose finalization was not deferred.
+    fn into_finished(self) -> ExecutionResult {
+        match self {
+            DeployExecution::Finished(execution_result) => execution_result,
+            DeployExecution::Pending { .. } => unreachable!("finalization was not deferred"),
+        }
+    }
+}
+
+impl From<ExecutionResult> for DeployExecution {
+    fn from(execution_result: ExecutionResult) -> Self {
+        DeployExecution::Finished(execution_result)
+    }
+}
+
 /// Main implementation of an execution engine state.
 ///
 /// Takes an engine's configuration and a provider of a state (aka the global state) to operate on.
@@ -115,6 +172,8 @@ pub const WASMLESS_TRANSFER_FIXED_GAS_PRICE: u64 = 1;
 pub struct EngineState<S> {
     config: EngineConfig,
     state: S,
+    /// Parsed Wasm modules of stored contracts, shared with scratch engine states.
+    module_cache: Arc<ModuleCache>,
 }
 
 impl EngineState<ScratchGlobalState> {
@@ -143,6 +202,7 @@ impl EngineState<LmdbGlobalState> {
         EngineState {
             config: self.config,
             state: self.state.create_scratch(),
+            module_cache: Arc::clone(&self.module_cache),
         }
     }
 
@@ -166,7 +226,12 @@ where
 {
     /// Creates new engine state.
     pub fn new(state: S, config: EngineConfig) -> EngineState<S> {
-        EngineState { config, state }
+        let module_cache = Arc::new(ModuleCache::new(config.module_cache_size()));
+        EngineState {
+            config,
+            state,
+            module_cache,
+        }
     }
 
     /// Returns engine config.
@@ -175,10 +240,23 @@ where
     }
 
     /// Updates current engine config with a new instance.
+    ///
+    /// The cache of parsed Wasm modules is cleared if its size or the Wasm config changes, as
+    /// cached modules may not conform to the new Wasm config.
     pub fn update_config(&mut self, new_config: EngineConfig) {
+        if new_config.module_cache_size() != self.config.module_cache_size()
+            || new_config.wasm_config() != self.config.wasm_config()
+        {
+            self.module_cache = Arc::new(ModuleCache::new(new_config.module_cache_size()));
+        }
         self.config = new_config
     }
 
+    /// Returns statistics about the use of the cache of parsed Wasm modules.
+    pub fn module_cache_stats(&self) -> ModuleCacheStats {
+        self.module_cache.stats()
+    }
+
     /// Commits genesis process.
     ///
     /// This process is run only once per network to initiate the system. By definition users are
@@ -248,6 +326,42 @@ where
         correlation_id: CorrelationId,
         upgrade_config: UpgradeConfig,
     ) -> Result<UpgradeSuccess, Error> {
+        let execution_effect = self.upgrade_effect(correlation_id, &upgrade_config)?;
+
+        // commit
+        let post_state_hash = self
+            .state
+            .commit(
+                correlation_id,
+                upgrade_config.pre_state_hash(),
+                execution_effect.transforms.to_owned(),
+            )
+            .map_err(Into::into)?;
+
+        // return result and effects
+        Ok(UpgradeSuccess {
+            post_state_hash,
+            execution_effect,
+        })
+    }
+
+    /// Runs an upgrade without committing it.
+    ///
+    /// Returns the effects the upgrade, including all of its global state migrations, would have
+    /// if committed.
+    pub fn dry_run_upgrade(
+        &self,
+        correlation_id: CorrelationId,
+        upgrade_config: &UpgradeConfig,
+    ) -> Result<ExecutionEffect, Error> {
+        self.upgrade_effect(correlation_id, upgrade_config)
+    }
+
+    fn upgrade_effect(
+        &self,
+        correlation_id: CorrelationId,
+        upgrade_config: &UpgradeConfig,
+    ) -> Result<ExecutionEffect, Error> {
         // per specification:
         // https://casperlabs.atlassian.net/wiki/spaces/EN/pages/139854367/Upgrading+System+Contracts+Specification
 
@@ -428,23 +542,15 @@ where
             tracking_copy.borrow_mut().write(*key, value.clone());
         }
 
-        let execution_effect = tracking_copy.borrow().effect();
-
-        // commit
-        let post_state_hash = self
-            .state
-            .commit(
-                correlation_id,
-                pre_state_hash,
-                execution_effect.transforms.to_owned(),
-            )
-            .map_err(Into::into)?;
+        // apply the typed migrations, as part of the same commit
+        migration::apply_migrations(
+            correlation_id,
+            &mut *tracking_copy.borrow_mut(),
+            upgrade_config.migrations(),
+        )
+        .map_err(|error| Error::ProtocolUpgrade(ProtocolUpgradeError::Migration(error)))?;
 
-        // return result and effects
-        Ok(UpgradeSuccess {
-            post_state_hash,
-            execution_effect,
-        })
+        Ok(tracking_copy.borrow().effect())
     }
 
     /// Creates a new tracking copy instance.
@@ -495,7 +601,8 @@ where
         correlation_id: CorrelationId,
         mut exec_request: ExecuteRequest,
     ) -> Result<ExecutionResults, Error> {
-        let executor = Executor::new(*self.config());
+        let executor =
+            Executor::new(*self.config()).with_module_cache(Arc::clone(&self.module_cache));
 
         let deploys = exec_request.take_deploys();
         let mut results = ExecutionResults::with_capacity(deploys.len());
@@ -599,8 +706,35 @@ where
         deploy_item: DeployItem,
         proposer: PublicKey,
     ) -> Result<ExecutionResult, Error> {
+        self.execute_transfer(
+            correlation_id,
+            executor,
+            protocol_version,
+            prestate_hash,
+            blocktime,
+            deploy_item,
+            proposer,
+            false,
+        )
+        .map(DeployExecution::into_finished)
+    }
+
+    /// Executes a native transfer, stopping short of finalizing its payment if
+    /// `defer_finalization` is set.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_transfer(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        protocol_version: ProtocolVersion,
+        prestate_hash: Digest,
+        blocktime: BlockTime,
+        deploy_item: DeployItem,
+        proposer: PublicKey,
+        defer_finalization: bool,
+    ) -> Result<DeployExecution, Error> {
         let tracking_copy = match self.tracking_copy(prestate_hash) {
-            Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+            Err(error) => return Ok(ExecutionResult::precondition_failure(error).into()),
             Ok(None) => return Err(Error::RootNotFound(prestate_hash)),
             Ok(Some(tracking_copy)) => Rc::new(RefCell::new(tracking_copy)),
         };
@@ -610,9 +744,9 @@ where
         let account_hash = match base_key.into_account() {
             Some(account_addr) => account_addr,
             None => {
-                return Ok(ExecutionResult::precondition_failure(
-                    error::Error::Authorization,
-                ));
+                return Ok(
+                    ExecutionResult::precondition_failure(error::Error::Authorization).into(),
+                );
             }
         };
 
@@ -625,7 +759,7 @@ where
             Rc::clone(&tracking_copy),
         ) {
             Ok(account) => account,
-            Err(e) => return Ok(ExecutionResult::precondition_failure(e)),
+            Err(e) => return Ok(ExecutionResult::precondition_failure(e).into()),
         };
 
         let proposer_addr = proposer.to_account_hash();
@@ -634,7 +768,9 @@ where
             .get_account(correlation_id, proposer_addr)
         {
             Ok(proposer) => proposer,
-            Err(error) => return Ok(ExecutionResult::precondition_failure(Error::Exec(error))),
+            Err(error) => {
+                return Ok(ExecutionResult::precondition_failure(Error::Exec(error)).into())
+            }
         };
 
         let system_contract_registry = tracking_copy
@@ -654,7 +790,7 @@ where
         {
             Ok(contract) => contract,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return Ok(ExecutionResult::precondition_failure(error.into()).into());
             }
         };
 
@@ -673,9 +809,9 @@ where
         ) {
             Some(motes) => motes,
             None => {
-                return Ok(ExecutionResult::precondition_failure(
-                    Error::GasConversionOverflow,
-                ))
+                return Ok(
+                    ExecutionResult::precondition_failure(Error::GasConversionOverflow).into(),
+                )
             }
         };
 
@@ -687,7 +823,9 @@ where
                 .get_purse_balance_key(correlation_id, proposer_main_purse.into())
             {
                 Ok(balance_key) => balance_key,
-                Err(error) => return Ok(ExecutionResult::precondition_failure(Error::Exec(error))),
+                Err(error) => {
+                    return Ok(ExecutionResult::precondition_failure(Error::Exec(error)).into())
+                }
             }
         };
 
@@ -700,7 +838,9 @@ where
             .get_purse_balance_key(correlation_id, account_main_purse.into())
         {
             Ok(balance_key) => balance_key,
-            Err(error) => return Ok(ExecutionResult::precondition_failure(Error::Exec(error))),
+            Err(error) => {
+                return Ok(ExecutionResult::precondition_failure(Error::Exec(error)).into())
+            }
         };
 
         let account_main_purse_balance = match tracking_copy
@@ -708,15 +848,15 @@ where
             .get_purse_balance(correlation_id, account_main_purse_balance_key)
         {
             Ok(balance_key) => balance_key,
-            Err(error) => return Ok(ExecutionResult::precondition_failure(Error::Exec(error))),
+            Err(error) => {
+                return Ok(ExecutionResult::precondition_failure(Error::Exec(error)).into())
+            }
         };
 
         if account_main_purse_balance < wasmless_transfer_motes {
             // We don't have minimum balance to operate and therefore we can't charge for user
             // errors.
-            return Ok(ExecutionResult::precondition_failure(
-                Error::InsufficientPayment,
-            ));
+            return Ok(ExecutionResult::precondition_failure(Error::InsufficientPayment).into());
         }
 
         // Function below creates an ExecutionResult with precomputed effects of "finalize_payment".
@@ -736,7 +876,8 @@ where
         // Any error that occurs in logic below this point would result in a charge for user error.
 
         let mut runtime_args_builder =
-            TransferRuntimeArgsBuilder::new(deploy_item.session.args().clone());
+            TransferRuntimeArgsBuilder::new(deploy_item.session.args().clone())
+                .with_memo(self.config.record_transfer_metadata());
 
         match runtime_args_builder.transfer_target_mode(correlation_id, Rc::clone(&tracking_copy)) {
             Ok(mode) => match mode {
@@ -775,18 +916,18 @@ where
                             let error = execution_result
                                 .take_error()
                                 .unwrap_or(Error::InsufficientPayment);
-                            return Ok(make_charged_execution_failure(error));
+                            return Ok(make_charged_execution_failure(error).into());
                         }
                     }
                 }
             },
-            Err(error) => return Ok(make_charged_execution_failure(error)),
+            Err(error) => return Ok(make_charged_execution_failure(error).into()),
         }
 
         let transfer_args =
             match runtime_args_builder.build(&account, correlation_id, Rc::clone(&tracking_copy)) {
                 Ok(transfer_args) => transfer_args,
-                Err(error) => return Ok(make_charged_execution_failure(error)),
+                Err(error) => return Ok(make_charged_execution_failure(error).into()),
             };
 
         let payment_uref;
@@ -801,7 +942,9 @@ where
                     .get_purse_balance_key(correlation_id, Key::URef(source_uref))
                 {
                     Ok(purse_balance_key) => purse_balance_key,
-                    Err(error) => return Ok(make_charged_execution_failure(Error::Exec(error))),
+                    Err(error) => {
+                        return Ok(make_charged_execution_failure(Error::Exec(error)).into())
+                    }
                 };
 
                 match tracking_copy
@@ -809,7 +952,9 @@ where
                     .get_purse_balance(correlation_id, source_purse_balance_key)
                 {
                     Ok(purse_balance) => purse_balance,
-                    Err(error) => return Ok(make_charged_execution_failure(Error::Exec(error))),
+                    Err(error) => {
+                        return Ok(make_charged_execution_failure(Error::Exec(error)).into())
+                    }
                 }
             } else {
                 // If source purse is main purse then we already have the balance.
@@ -822,11 +967,11 @@ where
                 Some(total_amount) if source_purse_balance < total_amount => {
                     // We can't continue if the minimum funds in source purse are lower than the
                     // required cost.
-                    return Ok(make_charged_execution_failure(Error::InsufficientPayment));
+                    return Ok(make_charged_execution_failure(Error::InsufficientPayment).into());
                 }
                 None => {
                     // When trying to send too much that could cause an overflow.
-                    return Ok(make_charged_execution_failure(Error::InsufficientPayment));
+                    return Ok(make_charged_execution_failure(Error::InsufficientPayment).into());
                 }
                 Some(_) => {}
             }
@@ -852,11 +997,13 @@ where
 
             payment_uref = match maybe_payment_uref {
                 Some(payment_uref) => payment_uref,
-                None => return Ok(make_charged_execution_failure(Error::InsufficientPayment)),
+                None => {
+                    return Ok(make_charged_execution_failure(Error::InsufficientPayment).into())
+                }
             };
 
             if let Some(error) = get_payment_purse_result.take_error() {
-                return Ok(make_charged_execution_failure(error));
+                return Ok(make_charged_execution_failure(error).into());
             }
 
             // Create a new arguments to transfer cost of wasmless transfer into the payment purse.
@@ -867,11 +1014,14 @@ where
                 payment_uref,
                 wasmless_transfer_motes.value(),
                 transfer_args.arg_id(),
+                None,
             );
 
             let runtime_args = match RuntimeArgs::try_from(new_transfer_args) {
                 Ok(runtime_args) => runtime_args,
-                Err(error) => return Ok(make_charged_execution_failure(Error::Exec(error.into()))),
+                Err(error) => {
+                    return Ok(make_charged_execution_failure(Error::Exec(error.into())).into())
+                }
             };
 
             let transfer_to_payment_purse_stack = self.get_new_system_call_stack();
@@ -895,7 +1045,7 @@ where
                 );
 
             if let Some(error) = payment_result.as_error().cloned() {
-                return Ok(make_charged_execution_failure(error));
+                return Ok(make_charged_execution_failure(error).into());
             }
 
             let transfer_result = match actual_result {
@@ -908,9 +1058,9 @@ where
             };
 
             if let Err(error) = transfer_result {
-                return Ok(make_charged_execution_failure(Error::Exec(
-                    ExecError::Revert(error),
-                )));
+                return Ok(
+                    make_charged_execution_failure(Error::Exec(ExecError::Revert(error))).into(),
+                );
             }
 
             let payment_purse_balance = {
@@ -919,7 +1069,9 @@ where
                     .get_purse_balance_key(correlation_id, Key::URef(payment_uref))
                 {
                     Ok(payment_purse_balance_key) => payment_purse_balance_key,
-                    Err(error) => return Ok(make_charged_execution_failure(Error::Exec(error))),
+                    Err(error) => {
+                        return Ok(make_charged_execution_failure(Error::Exec(error)).into())
+                    }
                 };
 
                 match tracking_copy
@@ -927,7 +1079,9 @@ where
                     .get_purse_balance(correlation_id, payment_purse_balance_key)
                 {
                     Ok(payment_purse_balance) => payment_purse_balance,
-                    Err(error) => return Ok(make_charged_execution_failure(Error::Exec(error))),
+                    Err(error) => {
+                        return Ok(make_charged_execution_failure(Error::Exec(error)).into())
+                    }
                 }
             };
 
@@ -940,7 +1094,9 @@ where
                 match Gas::from_motes(payment_purse_balance, WASMLESS_TRANSFER_FIXED_GAS_PRICE) {
                     Some(gas) => gas,
                     None => {
-                        return Ok(make_charged_execution_failure(Error::GasConversionOverflow))
+                        return Ok(
+                            make_charged_execution_failure(Error::GasConversionOverflow).into()
+                        )
                     }
                 };
 
@@ -951,12 +1107,10 @@ where
             payment_result.with_cost(payment_gas)
         };
 
-        let runtime_args = match RuntimeArgs::try_from(transfer_args) {
+        let runtime_args = match RuntimeArgs::try_from(transfer_args.clone()) {
             Ok(runtime_args) => runtime_args,
             Err(error) => {
-                return Ok(make_charged_execution_failure(
-                    ExecError::from(error).into(),
-                ))
+                return Ok(make_charged_execution_failure(ExecError::from(error).into()).into())
             }
         };
 
@@ -984,68 +1138,6 @@ where
         // charge for anything that happens while calling transfer entrypoint.
         session_result = session_result.with_cost(Gas::default());
 
-        let finalize_result = {
-            let handle_payment_args = {
-                // Gas spent during payment code execution
-                let finalize_cost_motes = {
-                    // A case where payment_result.cost() is different than wasmless transfer cost
-                    // is considered a programming error.
-                    debug_assert_eq!(payment_result.cost(), wasmless_transfer_gas_cost);
-                    wasmless_transfer_motes
-                };
-
-                let account = deploy_item.address;
-                let maybe_runtime_args = RuntimeArgs::try_new(|args| {
-                    args.insert(handle_payment::ARG_AMOUNT, finalize_cost_motes.value())?;
-                    args.insert(handle_payment::ARG_ACCOUNT, account)?;
-                    args.insert(handle_payment::ARG_TARGET, proposer_purse)?;
-                    Ok(())
-                });
-
-                match maybe_runtime_args {
-                    Ok(runtime_args) => runtime_args,
-                    Err(error) => {
-                        let exec_error = ExecError::from(error);
-                        return Ok(ExecutionResult::precondition_failure(exec_error.into()));
-                    }
-                }
-            };
-
-            let system_account = Account::new(
-                PublicKey::System.to_account_hash(),
-                Default::default(),
-                URef::new(Default::default(), AccessRights::READ_ADD_WRITE),
-                Default::default(),
-                Default::default(),
-            );
-
-            let tc = tracking_copy.borrow();
-            let finalization_tc = Rc::new(RefCell::new(tc.fork()));
-
-            let finalize_payment_stack = self.get_new_system_call_stack();
-            handle_payment_access_rights.extend(&[payment_uref, proposer_purse]);
-
-            let (_ret, finalize_result): (Option<()>, ExecutionResult) = executor
-                .call_system_contract(
-                    DirectSystemContractCall::FinalizePayment,
-                    handle_payment_args,
-                    &system_account,
-                    authorization_keys,
-                    blocktime,
-                    deploy_item.deploy_hash,
-                    gas_limit,
-                    protocol_version,
-                    correlation_id,
-                    finalization_tc,
-                    Phase::FinalizePayment,
-                    finalize_payment_stack,
-                    // Spending limit is cost of wasmless execution.
-                    U512::from(self.config().system_config().wasmless_transfer_cost()),
-                );
-
-            finalize_result
-        };
-
         // Create + persist deploy info.
         {
             let transfers = session_result.transfers();
@@ -1067,16 +1159,62 @@ where
             session_result = session_result.with_journal(tracking_copy.borrow().execution_journal())
         }
 
+        let handle_payment_args = {
+            // Gas spent during payment code execution
+            let finalize_cost_motes = {
+                // A case where payment_result.cost() is different than wasmless transfer cost
+                // is considered a programming error.
+                debug_assert_eq!(payment_result.cost(), wasmless_transfer_gas_cost);
+                wasmless_transfer_motes
+            };
+
+            let account = deploy_item.address;
+            let maybe_runtime_args = RuntimeArgs::try_new(|args| {
+                args.insert(handle_payment::ARG_AMOUNT, finalize_cost_motes.value())?;
+                args.insert(handle_payment::ARG_ACCOUNT, account)?;
+                args.insert(handle_payment::ARG_TARGET, proposer_purse)?;
+                Ok(())
+            });
+
+            match maybe_runtime_args {
+                Ok(runtime_args) => runtime_args,
+                Err(error) => {
+                    let exec_error = ExecError::from(error);
+                    return Ok(ExecutionResult::precondition_failure(exec_error.into()).into());
+                }
+            }
+        };
+
+        handle_payment_access_rights.extend(&[payment_uref, proposer_purse]);
+
         let mut execution_result_builder = ExecutionResultBuilder::new();
         execution_result_builder.set_payment_execution_result(payment_result);
         execution_result_builder.set_session_execution_result(session_result);
-        execution_result_builder.set_finalize_execution_result(finalize_result);
 
-        let execution_result = execution_result_builder
-            .build()
-            .expect("ExecutionResultBuilder not initialized properly");
+        let finalization = PendingFinalization {
+            execution_result_builder,
+            handle_payment_args,
+            payment_purse_key: None,
+            proposer_purse,
+            authorization_keys,
+            blocktime,
+            deploy_hash: deploy_item.deploy_hash,
+            protocol_version,
+            gas_limit,
+            // Spending limit is cost of wasmless execution.
+            spending_limit: U512::from(self.config().system_config().wasmless_transfer_cost()),
+        };
+
+        if defer_finalization {
+            let journal = tracking_copy.borrow().execution_journal();
+            return Ok(DeployExecution::Pending {
+                journal,
+                finalization,
+            });
+        }
 
-        Ok(execution_result)
+        self.finalize_payment(correlation_id, executor, &*tracking_copy, finalization)
+            .map(DeployExecution::Finished)
     }
 
     /// Executes a deploy.
@@ -1100,13 +1238,39 @@ where
         deploy_item: DeployItem,
         proposer: PublicKey,
     ) -> Result<ExecutionResult, Error> {
+        self.execute_deploy(
+            correlation_id,
+            executor,
+            protocol_version,
+            prestate_hash,
+            blocktime,
+            deploy_item,
+            proposer,
+            false,
+        )
+        .map(DeployExecution::into_finished)
+    }
+
+    /// Executes a deploy, stopping short of finalizing its payment if `defer_finalization` is set.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_deploy(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        protocol_version: ProtocolVersion,
+        prestate_hash: Digest,
+        blocktime: BlockTime,
+        deploy_item: DeployItem,
+        proposer: PublicKey,
+        defer_finalization: bool,
+    ) -> Result<DeployExecution, Error> {
         // spec: https://casperlabs.atlassian.net/wiki/spaces/EN/pages/123404576/Payment+code+execution+specification
 
         // Create tracking copy (which functions as a deploy context)
         // validation_spec_2: prestate_hash check
         // do this second; as there is no reason to proceed if the prestate hash is invalid
         let tracking_copy = match self.tracking_copy(prestate_hash) {
-            Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+            Err(error) => return Ok(ExecutionResult::precondition_failure(error).into()),
             Ok(None) => return Err(Error::RootNotFound(prestate_hash)),
             Ok(Some(tracking_copy)) => Rc::new(RefCell::new(tracking_copy)),
         };
@@ -1127,7 +1291,7 @@ where
                 Rc::clone(&tracking_copy),
             ) {
                 Ok(account) => account,
-                Err(e) => return Ok(ExecutionResult::precondition_failure(e)),
+                Err(e) => return Ok(ExecutionResult::precondition_failure(e).into()),
             }
         };
 
@@ -1150,7 +1314,7 @@ where
         ) {
             Ok(execution_kind) => execution_kind,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error));
+                return Ok(ExecutionResult::precondition_failure(error).into());
             }
         };
 
@@ -1164,7 +1328,7 @@ where
             {
                 Ok(key) => key,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return Ok(ExecutionResult::precondition_failure(error.into()).into());
                 }
             }
         };
@@ -1176,7 +1340,7 @@ where
             .get_purse_balance(correlation_id, account_main_purse_balance_key)
         {
             Ok(balance) => balance,
-            Err(error) => return Ok(ExecutionResult::precondition_failure(error.into())),
+            Err(error) => return Ok(ExecutionResult::precondition_failure(error.into()).into()),
         };
 
         let max_payment_cost = Motes::new(*MAX_PAYMENT);
@@ -1184,21 +1348,9 @@ where
         // Enforce minimum main purse balance validation
         // validation_spec_5: account main purse minimum balance
         if account_main_purse_balance < max_payment_cost {
-            return Ok(ExecutionResult::precondition_failure(
-                Error::InsufficientPayment,
-            ));
+            return Ok(ExecutionResult::precondition_failure(Error::InsufficientPayment).into());
         }
 
-        // Finalization is executed by system account (currently genesis account)
-        // payment_code_spec_5: system executes finalization
-        let system_account = Account::new(
-            PublicKey::System.to_account_hash(),
-            Default::default(),
-            URef::new(Default::default(), AccessRights::READ_ADD_WRITE),
-            Default::default(),
-            Default::default(),
-        );
-
         // [`ExecutionResultBuilder`] handles merging of multiple execution results
         let mut execution_result_builder = execution_result::ExecutionResultBuilder::new();
 
@@ -1209,9 +1361,9 @@ where
             let payment_gas_limit = match Gas::from_motes(max_payment_cost, deploy_item.gas_price) {
                 Some(gas) => gas,
                 None => {
-                    return Ok(ExecutionResult::precondition_failure(
-                        Error::GasConversionOverflow,
-                    ))
+                    return Ok(
+                        ExecutionResult::precondition_failure(Error::GasConversionOverflow).into(),
+                    )
                 }
             };
 
@@ -1260,7 +1412,7 @@ where
                 ) {
                     Ok(execution_kind) => execution_kind,
                     Err(error) => {
-                        return Ok(ExecutionResult::precondition_failure(error));
+                        return Ok(ExecutionResult::precondition_failure(error).into());
                     }
                 };
                 executor.exec(
@@ -1292,7 +1444,7 @@ where
             {
                 Ok(account) => account,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return Ok(ExecutionResult::precondition_failure(error.into()).into());
                 }
             };
             proposer_account.main_purse()
@@ -1307,7 +1459,7 @@ where
             {
                 Ok(key) => key,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return Ok(ExecutionResult::precondition_failure(error.into()).into());
                 }
             }
         };
@@ -1327,8 +1479,8 @@ where
                 account_main_purse_balance_key,
                 proposer_main_purse_balance_key,
             ) {
-                Ok(execution_result) => return Ok(execution_result),
-                Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+                Ok(execution_result) => return Ok(execution_result.into()),
+                Err(error) => return Ok(ExecutionResult::precondition_failure(error).into()),
             }
         }
 
@@ -1355,7 +1507,7 @@ where
         {
             Ok(contract) => contract,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return Ok(ExecutionResult::precondition_failure(error.into()).into());
             }
         };
 
@@ -1366,7 +1518,7 @@ where
             .get(handle_payment::PAYMENT_PURSE_KEY)
         {
             Some(key) => *key,
-            None => return Ok(ExecutionResult::precondition_failure(Error::Deploy)),
+            None => return Ok(ExecutionResult::precondition_failure(Error::Deploy).into()),
         };
         let purse_balance_key = match tracking_copy
             .borrow_mut()
@@ -1374,7 +1526,7 @@ where
         {
             Ok(key) => key,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return Ok(ExecutionResult::precondition_failure(error.into()).into());
             }
         };
         let payment_purse_balance: Motes = {
@@ -1384,7 +1536,7 @@ where
             {
                 Ok(balance) => balance,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return Ok(ExecutionResult::precondition_failure(error.into()).into());
                 }
             }
         };
@@ -1405,9 +1557,9 @@ where
             let gas_cost = match Gas::from_motes(max_payment_cost, deploy_item.gas_price) {
                 Some(gas) => gas,
                 None => {
-                    return Ok(ExecutionResult::precondition_failure(
-                        Error::GasConversionOverflow,
-                    ))
+                    return Ok(
+                        ExecutionResult::precondition_failure(Error::GasConversionOverflow).into(),
+                    )
                 }
             };
 
@@ -1419,8 +1571,8 @@ where
                 account_main_purse_balance_key,
                 proposer_main_purse_balance_key,
             ) {
-                Ok(execution_result) => return Ok(execution_result),
-                Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+                Ok(execution_result) => return Ok(execution_result.into()),
+                Err(error) => return Ok(ExecutionResult::precondition_failure(error).into()),
             }
         };
 
@@ -1454,7 +1606,8 @@ where
                     None => {
                         return Ok(ExecutionResult::precondition_failure(
                             Error::GasConversionOverflow,
-                        ))
+                        )
+                        .into())
                     }
                 };
 
@@ -1514,8 +1667,8 @@ where
                 account_main_purse_balance_key,
                 proposer_main_purse_balance_key,
             ) {
-                Ok(execution_result) => return Ok(execution_result),
-                Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+                Ok(execution_result) => return Ok(execution_result.into()),
+                Err(error) => return Ok(ExecutionResult::precondition_failure(error).into()),
             }
         }
 
@@ -1533,71 +1686,138 @@ where
         // results) is enforced in execution_result_builder.build()
         execution_result_builder.set_session_execution_result(session_result);
 
-        // payment_code_spec_5: run finalize process
-        let finalize_result: ExecutionResult = {
-            let post_session_tc = post_session_rc.borrow();
-            let finalization_tc = Rc::new(RefCell::new(post_session_tc.fork()));
-
-            let handle_payment_args = {
-                //((gas spent during payment code execution) + (gas spent during session code execution)) * gas_price
-                let finalize_cost_motes = match Motes::from_gas(
-                    execution_result_builder.total_cost(),
-                    deploy_item.gas_price,
-                ) {
+        //((gas spent during payment code execution) + (gas spent during session code execution)) * gas_price
+        let handle_payment_args = {
+            let finalize_cost_motes =
+                match Motes::from_gas(execution_result_builder.total_cost(), deploy_item.gas_price)
+                {
                     Some(motes) => motes,
                     None => {
                         return Ok(ExecutionResult::precondition_failure(
                             Error::GasConversionOverflow,
-                        ))
+                        )
+                        .into())
                     }
                 };
 
-                let maybe_runtime_args = RuntimeArgs::try_new(|args| {
-                    args.insert(handle_payment::ARG_AMOUNT, finalize_cost_motes.value())?;
-                    args.insert(handle_payment::ARG_ACCOUNT, account.account_hash())?;
-                    args.insert(handle_payment::ARG_TARGET, proposer_purse)?;
-                    Ok(())
-                });
-                match maybe_runtime_args {
-                    Ok(runtime_args) => runtime_args,
-                    Err(error) => {
-                        let exec_error = ExecError::from(error);
-                        return Ok(ExecutionResult::precondition_failure(exec_error.into()));
-                    }
+            let maybe_runtime_args = RuntimeArgs::try_new(|args| {
+                args.insert(handle_payment::ARG_AMOUNT, finalize_cost_motes.value())?;
+                args.insert(handle_payment::ARG_ACCOUNT, account.account_hash())?;
+                args.insert(handle_payment::ARG_TARGET, proposer_purse)?;
+                Ok(())
+            });
+            match maybe_runtime_args {
+                Ok(runtime_args) => runtime_args,
+                Err(error) => {
+                    let exec_error = ExecError::from(error);
+                    return Ok(ExecutionResult::precondition_failure(exec_error.into()).into());
                 }
-            };
+            }
+        };
 
-            // The Handle Payment keys may have changed because of effects during payment and/or
-            // session, so we need to look them up again from the tracking copy
-            let system_contract_registry = finalization_tc
-                .borrow_mut()
-                .get_system_contracts(correlation_id)?;
+        let finalization = PendingFinalization {
+            execution_result_builder,
+            handle_payment_args,
+            payment_purse_key: Some(payment_purse_key),
+            proposer_purse,
+            authorization_keys,
+            blocktime,
+            deploy_hash,
+            protocol_version,
+            gas_limit: Gas::new(U512::MAX),
+            spending_limit: U512::zero(),
+        };
 
-            let handle_payment_contract_hash = system_contract_registry
-                .get(HANDLE_PAYMENT)
-                .ok_or_else(|| {
-                    error!("Missing system handle payment contract hash");
-                    Error::MissingSystemContractHash(HANDLE_PAYMENT.to_string())
-                })?;
+        if defer_finalization {
+            let mut journal = tracking_copy.borrow().execution_journal();
+            journal.extend(post_session_rc.borrow().execution_journal());
+            return Ok(DeployExecution::Pending {
+                journal,
+                finalization,
+            });
+        }
 
-            let handle_payment_contract = match finalization_tc
-                .borrow_mut()
-                .get_contract(correlation_id, *handle_payment_contract_hash)
-            {
-                Ok(info) => info,
-                Err(error) => return Ok(ExecutionResult::precondition_failure(error.into())),
-            };
+        // payment_code_spec_5: run finalize process
+        // NOTE: payment_code_spec_5_a is enforced in execution_result_builder.build()
+        // payment_code_spec_6: return properly combined set of transforms and
+        // appropriate error
+        self.finalize_payment(correlation_id, executor, &*post_session_rc, finalization)
+            .map(DeployExecution::Finished)
+    }
+
+    /// Finalizes the payment of a deploy executed up to that point on `tracking_copy`.
+    ///
+    /// The handle payment contract is looked up again first if the payment purse is given, as the
+    /// payment and session code may have changed its keys.
+    fn finalize_payment<R>(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        tracking_copy: &RefCell<TrackingCopy<R>>,
+        finalization: PendingFinalization,
+    ) -> Result<ExecutionResult, Error>
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
+        let PendingFinalization {
+            mut execution_result_builder,
+            handle_payment_args,
+            payment_purse_key,
+            proposer_purse,
+            authorization_keys,
+            blocktime,
+            deploy_hash,
+            protocol_version,
+            gas_limit,
+            spending_limit,
+        } = finalization;
+
+        // Finalization is executed by system account (currently genesis account)
+        // payment_code_spec_5: system executes finalization
+        let system_account = Account::new(
+            PublicKey::System.to_account_hash(),
+            Default::default(),
+            URef::new(Default::default(), AccessRights::READ_ADD_WRITE),
+            Default::default(),
+            Default::default(),
+        );
+
+        let finalize_result: ExecutionResult = {
+            let post_session_tc = tracking_copy.borrow();
+            let finalization_tc = Rc::new(RefCell::new(post_session_tc.fork()));
+
+            if let Some(payment_purse_key) = payment_purse_key {
+                // The Handle Payment keys may have changed because of effects during payment
+                // and/or session, so we need to look them up again from the tracking copy
+                let system_contract_registry = finalization_tc
+                    .borrow_mut()
+                    .get_system_contracts(correlation_id)?;
+
+                let handle_payment_contract_hash = system_contract_registry
+                    .get(HANDLE_PAYMENT)
+                    .ok_or_else(|| {
+                        error!("Missing system handle payment contract hash");
+                        Error::MissingSystemContractHash(HANDLE_PAYMENT.to_string())
+                    })?;
 
-            let mut handle_payment_access_rights =
-                handle_payment_contract.extract_access_rights(*handle_payment_contract_hash);
-            handle_payment_access_rights.extend(&[
-                payment_purse_key
-                    .into_uref()
-                    .ok_or(Error::InvalidKeyVariant)?,
-                proposer_purse,
-            ]);
+                let handle_payment_contract = match finalization_tc
+                    .borrow_mut()
+                    .get_contract(correlation_id, *handle_payment_contract_hash)
+                {
+                    Ok(info) => info,
+                    Err(error) => return Ok(ExecutionResult::precondition_failure(error.into())),
+                };
 
-            let gas_limit = Gas::new(U512::MAX);
+                let mut handle_payment_access_rights =
+                    handle_payment_contract.extract_access_rights(*handle_payment_contract_hash);
+                handle_payment_access_rights.extend(&[
+                    payment_purse_key
+                        .into_uref()
+                        .ok_or(Error::InvalidKeyVariant)?,
+                    proposer_purse,
+                ]);
+            }
 
             let handle_payment_stack = self.get_new_system_call_stack();
 
@@ -1615,7 +1835,7 @@ where
                     finalization_tc,
                     Phase::FinalizePayment,
                     handle_payment_stack,
-                    U512::zero(),
+                    spending_limit,
                 );
 
             finalize_result
@@ -1628,9 +1848,6 @@ where
             .build()
             .expect("ExecutionResultBuilder not initialized properly");
 
-        // NOTE: payment_code_spec_5_a is enforced in execution_result_builder.build()
-        // payment_code_spec_6: return properly combined set of transforms and
-        // appropriate error
         Ok(ret)
     }
 
@@ -1675,6 +1892,21 @@ where
         Ok(self.state.get_trie_full(correlation_id, &trie_key)?)
     }
 
+    /// Puts a trie, returning its key.
+    ///
+    /// Unlike [`Self::put_trie_and_find_missing_descendant_trie_keys`] this doesn't check the
+    /// trie's descendants, so it's suited to copying whole tries in descendant-first order.
+    pub fn put_trie(
+        &self,
+        correlation_id: CorrelationId,
+        trie_bytes: &[u8],
+    ) -> Result<Digest, Error>
+    where
+        Error: From<S::Error>,
+    {
+        Ok(self.state.put_trie(correlation_id, trie_bytes)?)
+    }
+
     /// Puts a trie and finds missing descendant trie keys.
     pub fn put_trie_and_find_missing_descendant_trie_keys(
         &self,
@@ -1716,6 +1948,24 @@ where
         system_contract_registry: Option<SystemContractRegistry>,
         get_era_validators_request: GetEraValidatorsRequest,
     ) -> Result<EraValidators, GetEraValidatorsError> {
+        let snapshot = self.get_seigniorage_recipients_snapshot(
+            correlation_id,
+            system_contract_registry,
+            get_era_validators_request,
+        )?;
+        let era_validators_result = auction::detail::era_validators_from_snapshot(snapshot);
+        Ok(era_validators_result)
+    }
+
+    /// Obtains the auction's snapshot of seigniorage recipients, i.e. the stakes and delegation
+    /// rates of the validators and delegators of the current and all already determined upcoming
+    /// eras.
+    pub fn get_seigniorage_recipients_snapshot(
+        &self,
+        correlation_id: CorrelationId,
+        system_contract_registry: Option<SystemContractRegistry>,
+        get_era_validators_request: GetEraValidatorsRequest,
+    ) -> Result<SeigniorageRecipientsSnapshot, GetEraValidatorsError> {
         let state_root_hash = get_era_validators_request.state_hash();
 
         let system_contract_registry = match system_contract_registry {
@@ -1770,8 +2020,7 @@ where
             }
         };
 
-        let era_validators_result = auction::detail::era_validators_from_snapshot(snapshot);
-        Ok(era_validators_result)
+        Ok(snapshot)
     }
 
     /// Gets current bids from the auction system.
@@ -2131,6 +2380,8 @@ fn should_charge_for_errors_in_wasm(execution_result: &ExecutionResult) -> bool
                 | ExecError::RemoveKeyFailure(_)
                 | ExecError::UpdateKeyFailure(_)
                 | ExecError::SetThresholdFailure(_)
+                | ExecError::KeyManagementFailure(_)
+                | ExecError::MessageEmissionFailure(_)
                 | ExecError::SystemContract(_)
                 | ExecError::DeploymentAuthorizationFailure
                 | ExecError::ExpectedReturnValue
@@ -2154,7 +2405,8 @@ fn should_charge_for_errors_in_wasm(execution_result: &ExecutionResult) -> bool
                 | ExecError::RuntimeStackOverflow
                 | ExecError::ValueTooLarge
                 | ExecError::MissingRuntimeStack
-                | ExecError::DisabledContract(_) => false,
+                | ExecError::DisabledContract(_)
+                | ExecError::RestrictedURef(_) => false,
             },
             Error::WasmPreprocessing(_) => true,
             Error::WasmSerialization(_) => true,
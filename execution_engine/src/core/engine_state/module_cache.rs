@@ -0,0 +1,223 @@
+//! A cache of the Wasm modules of stored contracts.
+//!
+//! The Wasm of a stored contract is preprocessed once when the contract is installed, but has to be
+//! parsed again on every call.  The cache keeps the parsed modules of the most recently called
+//! contracts, so that frequently called contracts are only parsed once per protocol version.
+//!
+//! Parsing does not consume gas, hence gas accounting is the same whether a module is cached or
+//! not.
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use linked_hash_map::LinkedHashMap;
+use parity_wasm::elements::Module;
+
+use casper_types::{ContractHash, ContractWasmHash, ProtocolVersion};
+
+use crate::core::execution::Error;
+
+/// Default maximum number of modules held by the cache.
+pub const DEFAULT_MODULE_CACHE_SIZE: usize = 256;
+
+/// Cached modules, along with the hash of the Wasm they were parsed from, in order of use.
+type Modules = LinkedHashMap<(ContractHash, ProtocolVersion), (ContractWasmHash, Module)>;
+
+/// Statistics about the use of a [`ModuleCache`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ModuleCacheStats {
+    /// Number of lookups which found the module in the cache.
+    pub hits: u64,
+    /// Number of lookups which had to parse the module.
+    pub misses: u64,
+    /// Number of modules currently cached.
+    pub entries: usize,
+}
+
+/// A least-recently-used cache of parsed Wasm modules, keyed by contract hash and protocol version.
+pub struct ModuleCache {
+    capacity: usize,
+    modules: Mutex<Modules>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ModuleCache {
+    /// Creates a cache holding up to `capacity` modules.  A capacity of 0 disables caching.
+    pub fn new(capacity: usize) -> Self {
+        ModuleCache {
+            capacity,
+            modules: Mutex::new(LinkedHashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the module of the given contract, parsing it with `parse` if it is not cached.
+    ///
+    /// A cached module is only returned if it was parsed from the Wasm with the given hash.
+    pub(crate) fn get_or_parse<F>(
+        &self,
+        contract_hash: ContractHash,
+        contract_wasm_hash: ContractWasmHash,
+        protocol_version: ProtocolVersion,
+        parse: F,
+    ) -> Result<Module, Error>
+    where
+        F: FnOnce() -> Result<Module, Error>,
+    {
+        if self.capacity == 0 {
+            return parse();
+        }
+
+        let key = (contract_hash, protocol_version);
+        if let Some((wasm_hash, module)) = self.lock().get_refresh(&key) {
+            if *wasm_hash == contract_wasm_hash {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(module.clone());
+            }
+        }
+
+        // Parse without holding the lock, so that other threads executing deploys are not blocked.
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let module = parse()?;
+
+        let mut modules = self.lock();
+        modules.insert(key, (contract_wasm_hash, module.clone()));
+        while modules.len() > self.capacity {
+            modules.pop_front();
+        }
+        Ok(module)
+    }
+
+    /// Returns statistics about the use of the cache.
+    pub fn stats(&self) -> ModuleCacheStats {
+        ModuleCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.lock().len(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Modules> {
+        // A panic while holding the lock cannot leave the map in an inconsistent state.
+        self.modules
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Debug for ModuleCache {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("ModuleCache")
+            .field("capacity", &self.capacity)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parity_wasm::builder;
+
+    use super::*;
+
+    fn module(functions: usize) -> Module {
+        let mut module = builder::module();
+        for _ in 0..functions {
+            module = module.function().signature().build().body().build().build();
+        }
+        module.build()
+    }
+
+    fn parse(functions: usize) -> impl FnOnce() -> Result<Module, Error> {
+        move || Ok(module(functions))
+    }
+
+    #[test]
+    fn should_evict_least_recently_used_module() {
+        let cache = ModuleCache::new(2);
+        let wasm_hash = ContractWasmHash::new([0; 32]);
+        let version = ProtocolVersion::V1_0_0;
+        let first = ContractHash::new([1; 32]);
+        let second = ContractHash::new([2; 32]);
+        let third = ContractHash::new([3; 32]);
+
+        cache
+            .get_or_parse(first, wasm_hash, version, parse(1))
+            .unwrap();
+        cache
+            .get_or_parse(second, wasm_hash, version, parse(2))
+            .unwrap();
+        // Using `first` again makes `second` the least recently used module.
+        let cached = cache
+            .get_or_parse(first, wasm_hash, version, || panic!("should be cached"))
+            .unwrap();
+        assert_eq!(cached, module(1));
+        cache
+            .get_or_parse(third, wasm_hash, version, parse(3))
+            .unwrap();
+
+        let reparsed = cache
+            .get_or_parse(second, wasm_hash, version, parse(2))
+            .unwrap();
+        assert_eq!(reparsed, module(2));
+        assert_eq!(
+            cache.stats(),
+            ModuleCacheStats {
+                hits: 1,
+                misses: 4,
+                entries: 2
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_return_module_of_other_wasm_or_protocol_version() {
+        let cache = ModuleCache::new(8);
+        let contract_hash = ContractHash::new([1; 32]);
+        let wasm_hash = ContractWasmHash::new([0; 32]);
+
+        cache
+            .get_or_parse(contract_hash, wasm_hash, ProtocolVersion::V1_0_0, parse(1))
+            .unwrap();
+        let other_wasm = cache
+            .get_or_parse(
+                contract_hash,
+                ContractWasmHash::new([9; 32]),
+                ProtocolVersion::V1_0_0,
+                parse(2),
+            )
+            .unwrap();
+        assert_eq!(other_wasm, module(2));
+        let other_version = cache
+            .get_or_parse(
+                contract_hash,
+                wasm_hash,
+                ProtocolVersion::from_parts(1, 1, 0),
+                parse(3),
+            )
+            .unwrap();
+        assert_eq!(other_version, module(3));
+        assert_eq!(cache.stats().hits, 0);
+    }
+
+    #[test]
+    fn should_not_cache_with_zero_capacity() {
+        let cache = ModuleCache::new(0);
+        let contract_hash = ContractHash::new([1; 32]);
+        let wasm_hash = ContractWasmHash::new([0; 32]);
+
+        for _ in 0..2 {
+            cache
+                .get_or_parse(contract_hash, wasm_hash, ProtocolVersion::V1_0_0, parse(1))
+                .unwrap();
+        }
+        assert_eq!(cache.stats(), ModuleCacheStats::default());
+    }
+}
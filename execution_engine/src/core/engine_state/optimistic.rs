@@ -0,0 +1,301 @@
+//! Optimistic parallel execution of deploys against a scratch global state.
+//!
+//! All deploys of a batch are first executed concurrently against the state as it is before any of
+//! them, recording the values each execution reads.  The finalization of the payment of a deploy
+//! is left out, as every finalization reads and writes the proposer's purse.  The results are then
+//! handed out in order: the effects of a deploy are used as they are if none of the values it read
+//! was changed by the deploys before it, in which case its payment is finalized on top of them.
+//! Otherwise the deploy is executed again against the state as it is by then.  Provided every
+//! result is committed before the next one is taken, the end state is the same as if the deploys
+//! had been executed one after another.
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread, vec,
+};
+
+use casper_types::{BlockTime, Key};
+
+use super::{
+    execution_result::{ExecutionResult, ExecutionResults},
+    DeployExecution, EngineState, Error, ExecutableDeployItem, ExecuteRequest, PendingFinalization,
+};
+use crate::{
+    core::execution::Executor,
+    shared::{execution_journal::ExecutionJournal, newtypes::CorrelationId, transform::Transform},
+    storage::global_state::{
+        scratch::{ReadSet, ScratchGlobalState},
+        StateProvider,
+    },
+};
+
+/// How far a request was executed against the state before the batch.
+#[allow(clippy::large_enum_variant)]
+enum Speculated {
+    /// All deploys of the request were executed in full.
+    Executed(ExecutionResults),
+    /// The only deploy of the request was executed up to the finalization of its payment.
+    Pending {
+        journal: ExecutionJournal,
+        finalization: PendingFinalization,
+    },
+}
+
+/// The outcome of executing a request against the state before the batch.
+struct Speculation {
+    result: Result<Speculated, Error>,
+    read_set: ReadSet,
+}
+
+impl EngineState<ScratchGlobalState> {
+    /// Executes the given requests on up to `threads` threads, returning their results in order.
+    ///
+    /// The requests are executed speculatively before this method returns.  Each result taken from
+    /// the returned iterator must be committed to this state before the next one is taken, as the
+    /// remaining results are checked against, and if need be re-executed on top of, the effects
+    /// committed so far.
+    ///
+    /// With a single thread, or a single request, every request is simply executed when its result
+    /// is taken.
+    pub fn execute_optimistically(
+        &self,
+        correlation_id: CorrelationId,
+        requests: Vec<ExecuteRequest>,
+        threads: usize,
+    ) -> OptimisticExecution<'_> {
+        let speculations = if threads > 1 && requests.len() > 1 {
+            self.speculate(correlation_id, &requests, threads)
+        } else {
+            requests.iter().map(|_| None).collect()
+        };
+        OptimisticExecution {
+            engine_state: self,
+            correlation_id,
+            requests: requests.into_iter(),
+            speculations: speculations.into_iter(),
+            written: HashSet::new(),
+            reexecuted: 0,
+        }
+    }
+
+    /// Executes all requests concurrently against the current state, recording their reads.
+    fn speculate(
+        &self,
+        correlation_id: CorrelationId,
+        requests: &[ExecuteRequest],
+        threads: usize,
+    ) -> Vec<Option<Speculation>> {
+        let next_index = AtomicUsize::new(0);
+        let speculations: Mutex<Vec<Option<Speculation>>> =
+            Mutex::new(requests.iter().map(|_| None).collect());
+
+        thread::scope(|scope| {
+            for _ in 0..threads.min(requests.len()) {
+                scope.spawn(|| loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    let request = match requests.get(index) {
+                        Some(request) => request.clone(),
+                        None => break,
+                    };
+                    let engine_state = EngineState {
+                        config: self.config,
+                        state: self.state.recording_reads(),
+                        module_cache: Arc::clone(&self.module_cache),
+                    };
+                    let result = engine_state.execute_until_finalization(correlation_id, request);
+                    let speculation = Speculation {
+                        result,
+                        read_set: engine_state.state.take_read_set(),
+                    };
+                    speculations.lock().unwrap()[index] = Some(speculation);
+                });
+            }
+        });
+
+        speculations.into_inner().unwrap()
+    }
+
+    /// Executes a request, leaving out the finalization of the payment of its deploy if it has
+    /// only one.
+    fn execute_until_finalization(
+        &self,
+        correlation_id: CorrelationId,
+        mut request: ExecuteRequest,
+    ) -> Result<Speculated, Error> {
+        if request.deploys.len() != 1 {
+            return self
+                .run_execute(correlation_id, request)
+                .map(Speculated::Executed);
+        }
+        let executor =
+            Executor::new(*self.config()).with_module_cache(Arc::clone(&self.module_cache));
+        let deploy_item = request.take_deploys().remove(0);
+        let execution = match deploy_item.session {
+            ExecutableDeployItem::Transfer { .. } => self.execute_transfer(
+                correlation_id,
+                &executor,
+                request.protocol_version,
+                request.parent_state_hash,
+                BlockTime::new(request.block_time),
+                deploy_item,
+                request.proposer,
+                true,
+            )?,
+            _ => self.execute_deploy(
+                correlation_id,
+                &executor,
+                request.protocol_version,
+                request.parent_state_hash,
+                BlockTime::new(request.block_time),
+                deploy_item,
+                request.proposer,
+                true,
+            )?,
+        };
+        Ok(match execution {
+            DeployExecution::Finished(execution_result) => {
+                let mut results = ExecutionResults::with_capacity(1);
+                results.push_back(execution_result);
+                Speculated::Executed(results)
+            }
+            DeployExecution::Pending {
+                journal,
+                finalization,
+            } => Speculated::Pending {
+                journal,
+                finalization,
+            },
+        })
+    }
+
+    /// Applies the effects of a deploy executed up to the finalization of its payment to the state
+    /// as it is now, and finalizes its payment on top of them.
+    ///
+    /// Returns `Ok(None)` if the effects cannot be applied.
+    fn finalize_speculation(
+        &self,
+        correlation_id: CorrelationId,
+        request: &ExecuteRequest,
+        journal: ExecutionJournal,
+        finalization: PendingFinalization,
+    ) -> Result<Option<ExecutionResult>, Error> {
+        let tracking_copy = match self.tracking_copy(request.parent_state_hash)? {
+            Some(tracking_copy) => RefCell::new(tracking_copy),
+            None => return Err(Error::RootNotFound(request.parent_state_hash)),
+        };
+        let applied = tracking_copy
+            .borrow_mut()
+            .apply_journal(correlation_id, journal)
+            .map_err(|error| Error::Exec(error.into()))?;
+        if !applied {
+            return Ok(None);
+        }
+        let executor =
+            Executor::new(*self.config()).with_module_cache(Arc::clone(&self.module_cache));
+        self.finalize_payment(correlation_id, &executor, &tracking_copy, finalization)
+            .map(Some)
+    }
+}
+
+/// The results of executing a batch of requests optimistically, in the order of the requests.
+///
+/// Returned by [`EngineState::execute_optimistically`].
+pub struct OptimisticExecution<'a> {
+    engine_state: &'a EngineState<ScratchGlobalState>,
+    correlation_id: CorrelationId,
+    requests: vec::IntoIter<ExecuteRequest>,
+    speculations: vec::IntoIter<Option<Speculation>>,
+    /// The keys written by the results taken so far.
+    written: HashSet<Key>,
+    reexecuted: usize,
+}
+
+impl<'a> OptimisticExecution<'a> {
+    /// Returns the number of speculative results which had to be discarded because they conflicted
+    /// with the results before them.
+    pub fn reexecuted(&self) -> usize {
+        self.reexecuted
+    }
+
+    /// Returns the results of the request from its speculation, or `None` if the speculation is
+    /// stale.
+    fn complete(
+        &self,
+        request: &ExecuteRequest,
+        speculation: Speculation,
+    ) -> Option<Result<ExecutionResults, Error>> {
+        // Engine errors are re-checked as well, as they may be caused by a stale read.
+        let speculated = speculation.result.ok()?;
+        let current = self
+            .engine_state
+            .state
+            .checkout(request.parent_state_hash)
+            .ok()??;
+        let stale = speculation
+            .read_set
+            .conflicts_with(self.correlation_id, &self.written, &current)
+            .ok()?;
+        if stale {
+            return None;
+        }
+        match speculated {
+            Speculated::Executed(results) => Some(Ok(results)),
+            Speculated::Pending {
+                journal,
+                finalization,
+            } => match self.engine_state.finalize_speculation(
+                self.correlation_id,
+                request,
+                journal,
+                finalization,
+            ) {
+                Ok(Some(execution_result)) => {
+                    let mut results = ExecutionResults::with_capacity(1);
+                    results.push_back(execution_result);
+                    Some(Ok(results))
+                }
+                Ok(None) => None,
+                Err(error) => Some(Err(error)),
+            },
+        }
+    }
+}
+
+impl<'a> Iterator for OptimisticExecution<'a> {
+    type Item = Result<ExecutionResults, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let request = self.requests.next()?;
+        let result = match self.speculations.next().flatten() {
+            None => self.engine_state.run_execute(self.correlation_id, request),
+            Some(speculation) => match self.complete(&request, speculation) {
+                Some(result) => result,
+                None => {
+                    self.reexecuted += 1;
+                    self.engine_state.run_execute(self.correlation_id, request)
+                }
+            },
+        };
+        if let Ok(results) = &result {
+            for execution_result in results {
+                // Reads are no changes, so other speculations may have read the same values.
+                self.written.extend(
+                    execution_result
+                        .execution_journal()
+                        .iter()
+                        .filter(|(_key, transform)| !matches!(transform, Transform::Identity))
+                        .map(|(key, _transform)| key.normalize()),
+                );
+            }
+        }
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.requests.size_hint()
+    }
+}
@@ -4,7 +4,7 @@ use casper_types::{
     account::{Account, AccountHash},
     system::mint,
     AccessRights, ApiError, CLType, CLValueError, Key, PublicKey, RuntimeArgs, StoredValue, URef,
-    U512,
+    TRANSFER_MEMO_MAX_LENGTH, U512,
 };
 
 use crate::{
@@ -32,13 +32,14 @@ pub enum TransferTargetMode {
 /// Mint's transfer arguments.
 ///
 /// A struct has a benefit of static typing, which is helpful while resolving the arguments.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct TransferArgs {
     to: Option<AccountHash>,
     source: URef,
     target: URef,
     amount: U512,
     arg_id: Option<u64>,
+    memo: Option<String>,
 }
 
 impl TransferArgs {
@@ -49,6 +50,7 @@ impl TransferArgs {
         target: URef,
         amount: U512,
         arg_id: Option<u64>,
+        memo: Option<String>,
     ) -> Self {
         Self {
             to,
@@ -56,6 +58,7 @@ impl TransferArgs {
             target,
             amount,
             arg_id,
+            memo,
         }
     }
 
@@ -78,6 +81,11 @@ impl TransferArgs {
     pub fn amount(&self) -> U512 {
         self.amount
     }
+
+    /// Returns `memo` field.
+    pub fn memo(&self) -> Option<&str> {
+        self.memo.as_deref()
+    }
 }
 
 impl TryFrom<TransferArgs> for RuntimeArgs {
@@ -91,6 +99,10 @@ impl TryFrom<TransferArgs> for RuntimeArgs {
         runtime_args.insert(mint::ARG_TARGET, transfer_args.target)?;
         runtime_args.insert(mint::ARG_AMOUNT, transfer_args.amount)?;
         runtime_args.insert(mint::ARG_ID, transfer_args.arg_id)?;
+        // The memo is optional, so it is only passed on if it was given.
+        if let Some(memo) = transfer_args.memo {
+            runtime_args.insert(mint::ARG_MEMO, Some(memo))?;
+        }
 
         Ok(runtime_args)
     }
@@ -105,6 +117,7 @@ pub struct TransferRuntimeArgsBuilder {
     inner: RuntimeArgs,
     transfer_target_mode: TransferTargetMode,
     to: Option<AccountHash>,
+    accept_memo: bool,
 }
 
 impl TransferRuntimeArgsBuilder {
@@ -116,9 +129,16 @@ impl TransferRuntimeArgsBuilder {
             inner: imputed_runtime_args,
             transfer_target_mode: TransferTargetMode::Unknown,
             to: None,
+            accept_memo: false,
         }
     }
 
+    /// Sets whether the optional "memo" argument is resolved.  If not, it is ignored.
+    pub fn with_memo(mut self, accept_memo: bool) -> TransferRuntimeArgsBuilder {
+        self.accept_memo = accept_memo;
+        self
+    }
+
     /// Checks if a purse exists.
     fn purse_exists<R>(
         &self,
@@ -314,6 +334,23 @@ impl TransferRuntimeArgsBuilder {
         Ok(id)
     }
 
+    fn resolve_memo(&self) -> Result<Option<String>, Error> {
+        if !self.accept_memo {
+            return Ok(None);
+        }
+        let memo: Option<String> = match self.inner.get(mint::ARG_MEMO) {
+            Some(memo_value) => memo_value.clone().into_t().map_err(Error::reverter)?,
+            None => None,
+        };
+        if memo
+            .as_ref()
+            .map_or(false, |memo| memo.len() > TRANSFER_MEMO_MAX_LENGTH)
+        {
+            return Err(Error::reverter(ApiError::InvalidArgument));
+        }
+        Ok(memo)
+    }
+
     /// Returns a resolved [`TransferTargetMode`].
     pub(crate) fn transfer_target_mode<R>(
         &mut self,
@@ -369,12 +406,15 @@ impl TransferRuntimeArgsBuilder {
 
         let id = self.resolve_id()?;
 
+        let memo = self.resolve_memo()?;
+
         Ok(TransferArgs {
             to,
             source: source_uref,
             target: target_uref,
             amount,
             arg_id: id,
+            memo,
         })
     }
 }
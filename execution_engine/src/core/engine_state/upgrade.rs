@@ -13,7 +13,11 @@ use casper_types::{
 
 use crate::{
     core::{
-        engine_state::{execution_effect::ExecutionEffect, ChainspecRegistry},
+        engine_state::{
+            execution_effect::ExecutionEffect,
+            migration::{Migration, MigrationError},
+            ChainspecRegistry,
+        },
         tracking_copy::TrackingCopy,
     },
     shared::newtypes::CorrelationId,
@@ -53,6 +57,7 @@ pub struct UpgradeConfig {
     new_unbonding_delay: Option<u64>,
     global_state_update: BTreeMap<Key, StoredValue>,
     chainspec_registry: ChainspecRegistry,
+    migrations: Vec<Migration>,
 }
 
 impl UpgradeConfig {
@@ -83,6 +88,7 @@ impl UpgradeConfig {
             new_unbonding_delay,
             global_state_update,
             chainspec_registry,
+            migrations: Vec::new(),
         }
     }
 
@@ -141,10 +147,20 @@ impl UpgradeConfig {
         &self.chainspec_registry
     }
 
+    /// Returns the global state migrations to be applied, in order.
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
     /// Sets new pre state hash.
     pub fn with_pre_state_hash(&mut self, pre_state_hash: Digest) {
         self.pre_state_hash = pre_state_hash;
     }
+
+    /// Sets the global state migrations to be applied, in order.
+    pub fn with_migrations(&mut self, migrations: Vec<Migration>) {
+        self.migrations = migrations;
+    }
 }
 
 /// Represents outcomes of a failed protocol upgrade.
@@ -168,6 +184,9 @@ pub enum ProtocolUpgradeError {
     /// Failed to create system contract registry.
     #[error("Failed to insert system contract registry")]
     FailedToCreateSystemRegistry,
+    /// Failed to apply a global state migration.
+    #[error("Failed to apply global state migration: {0}")]
+    Migration(MigrationError),
 }
 
 impl From<bytesrepr::Error> for ProtocolUpgradeError {
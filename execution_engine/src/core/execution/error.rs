@@ -3,9 +3,14 @@ use parity_wasm::elements;
 use thiserror::Error;
 
 use casper_types::{
-    account::{AddKeyFailure, RemoveKeyFailure, SetThresholdFailure, UpdateKeyFailure},
-    bytesrepr, system, AccessRights, ApiError, CLType, CLValueError, ContractHash,
-    ContractPackageHash, ContractVersionKey, ContractWasmHash, Key, StoredValueTypeMismatch, URef,
+    account::{
+        AddKeyFailure, KeyManagementFailure, RemoveKeyFailure, SetThresholdFailure,
+        UpdateKeyFailure,
+    },
+    bytesrepr,
+    contract_messages::MessageEmissionFailure,
+    system, AccessRights, ApiError, CLType, CLValueError, ContractHash, ContractPackageHash,
+    ContractVersionKey, ContractWasmHash, Key, StoredValueTypeMismatch, URef,
 };
 
 use crate::{
@@ -84,6 +89,12 @@ pub enum Error {
     /// Error setting threshold on associated key.
     #[error("{}", _0)]
     SetThresholdFailure(SetThresholdFailure),
+    /// Error applying a batch of associated key and threshold changes.
+    #[error("{}", _0)]
+    KeyManagementFailure(KeyManagementFailure),
+    /// Error emitting a contract message.
+    #[error("{}", _0)]
+    MessageEmissionFailure(MessageEmissionFailure),
     /// Error executing system contract.
     #[error("{}", _0)]
     SystemContract(system::Error),
@@ -173,6 +184,9 @@ pub enum Error {
     /// Contract is disabled.
     #[error("Contract is disabled")]
     DisabledContract(ContractHash),
+    /// A [`URef`] was used with access rights exceeding those its issuer restricted it to.
+    #[error("URef access rights restricted by its issuer: {}", _0)]
+    RestrictedURef(URef),
 }
 
 impl From<wasm_prep::PreprocessingError> for Error {
@@ -259,6 +273,18 @@ impl From<SetThresholdFailure> for Error {
     }
 }
 
+impl From<KeyManagementFailure> for Error {
+    fn from(err: KeyManagementFailure) -> Self {
+        Error::KeyManagementFailure(err)
+    }
+}
+
+impl From<MessageEmissionFailure> for Error {
+    fn from(err: MessageEmissionFailure) -> Self {
+        Error::MessageEmissionFailure(err)
+    }
+}
+
 impl From<system::Error> for Error {
     fn from(error: system::Error) -> Self {
         Error::SystemContract(error)
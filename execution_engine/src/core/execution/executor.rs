@@ -1,4 +1,4 @@
-use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc, sync::Arc};
 
 use casper_types::{
     account::{Account, AccountHash},
@@ -9,11 +9,13 @@ use casper_types::{
     ProtocolVersion, RuntimeArgs, StoredValue, U512,
 };
 
+#[cfg(feature = "wasmtime-backend")]
+use crate::core::backend::ExecutionBackendKind;
 use crate::{
     core::{
         engine_state::{
-            executable_deploy_item::ExecutionKind, execution_result::ExecutionResult, EngineConfig,
-            ExecError,
+            executable_deploy_item::ExecutionKind, execution_result::ExecutionResult,
+            module_cache::ModuleCache, EngineConfig, ExecError,
         },
         execution::{address_generator::AddressGenerator, Error},
         runtime::{Runtime, RuntimeStack},
@@ -35,12 +37,25 @@ fn try_get_amount(runtime_args: &RuntimeArgs) -> Result<U512, ExecError> {
 /// Executor object deals with execution of WASM modules.
 pub struct Executor {
     config: EngineConfig,
+    module_cache: Arc<ModuleCache>,
 }
 
 impl Executor {
     /// Creates new executor object.
+    ///
+    /// The executor does not cache parsed Wasm modules unless given a cache via
+    /// [`Executor::with_module_cache`].
     pub fn new(config: EngineConfig) -> Self {
-        Executor { config }
+        Executor {
+            config,
+            module_cache: Arc::new(ModuleCache::new(0)),
+        }
+    }
+
+    /// Returns this executor using `module_cache` for the parsed Wasm modules of stored contracts.
+    pub fn with_module_cache(mut self, module_cache: Arc<ModuleCache>) -> Self {
+        self.module_cache = module_cache;
+        self
     }
 
     /// Executes a WASM module.
@@ -65,6 +80,85 @@ impl Executor {
         phase: Phase,
         stack: RuntimeStack,
     ) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<Error>,
+    {
+        #[cfg(feature = "wasmtime-backend")]
+        if self.config.execution_backend() == ExecutionBackendKind::Differential {
+            let wasmtime_result = self.exec_with_wasmtime(
+                execution_kind.clone(),
+                args.clone(),
+                account,
+                named_keys.clone(),
+                access_rights.clone(),
+                authorization_keys.clone(),
+                blocktime,
+                deploy_hash,
+                gas_limit,
+                protocol_version,
+                correlation_id,
+                &tracking_copy,
+                phase,
+                stack.clone(),
+            );
+            let wasmi_result = self.exec_wasm(
+                execution_kind,
+                args,
+                account,
+                named_keys,
+                access_rights,
+                authorization_keys,
+                blocktime,
+                deploy_hash,
+                gas_limit,
+                protocol_version,
+                correlation_id,
+                tracking_copy,
+                phase,
+                stack,
+            );
+            assert_same_effects(&wasmi_result, &wasmtime_result);
+            return wasmi_result;
+        }
+
+        self.exec_wasm(
+            execution_kind,
+            args,
+            account,
+            named_keys,
+            access_rights,
+            authorization_keys,
+            blocktime,
+            deploy_hash,
+            gas_limit,
+            protocol_version,
+            correlation_id,
+            tracking_copy,
+            phase,
+            stack,
+        )
+    }
+
+    /// Executes a WASM module with the configured backend.
+    #[allow(clippy::too_many_arguments)]
+    fn exec_wasm<R>(
+        &self,
+        execution_kind: ExecutionKind,
+        args: RuntimeArgs,
+        account: &Account,
+        named_keys: &mut NamedKeys,
+        access_rights: ContextAccessRights,
+        authorization_keys: BTreeSet<AccountHash>,
+        blocktime: BlockTime,
+        deploy_hash: DeployHash,
+        gas_limit: Gas,
+        protocol_version: ProtocolVersion,
+        correlation_id: CorrelationId,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+        phase: Phase,
+        stack: RuntimeStack,
+    ) -> ExecutionResult
     where
         R: StateReader<Key, StoredValue>,
         R::Error: Into<Error>,
@@ -100,7 +194,7 @@ impl Executor {
             spending_limit,
         );
 
-        let mut runtime = Runtime::new(self.config, context);
+        let mut runtime = Runtime::new(self.config, Arc::clone(&self.module_cache), context);
 
         let result = match execution_kind {
             ExecutionKind::Module(module_bytes) => {
@@ -132,6 +226,55 @@ impl Executor {
         }
     }
 
+    /// Executes a WASM module with the wasmtime backend against a fork of `tracking_copy`, leaving
+    /// `tracking_copy` unchanged.
+    #[cfg(feature = "wasmtime-backend")]
+    #[allow(clippy::too_many_arguments)]
+    fn exec_with_wasmtime<R>(
+        &self,
+        execution_kind: ExecutionKind,
+        args: RuntimeArgs,
+        account: &Account,
+        mut named_keys: NamedKeys,
+        access_rights: ContextAccessRights,
+        authorization_keys: BTreeSet<AccountHash>,
+        blocktime: BlockTime,
+        deploy_hash: DeployHash,
+        gas_limit: Gas,
+        protocol_version: ProtocolVersion,
+        correlation_id: CorrelationId,
+        tracking_copy: &Rc<RefCell<TrackingCopy<R>>>,
+        phase: Phase,
+        stack: RuntimeStack,
+    ) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<Error>,
+    {
+        let tracking_copy = tracking_copy.borrow();
+        let fork = Rc::new(RefCell::new(tracking_copy.fork()));
+        Executor::new(
+            self.config
+                .with_execution_backend(ExecutionBackendKind::Wasmtime),
+        )
+        .exec_wasm(
+            execution_kind,
+            args,
+            account,
+            &mut named_keys,
+            access_rights,
+            authorization_keys,
+            blocktime,
+            deploy_hash,
+            gas_limit,
+            protocol_version,
+            correlation_id,
+            fork,
+            phase,
+            stack,
+        )
+    }
+
     /// Executes standard payment code natively.
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn exec_standard_payment<R>(
@@ -190,7 +333,8 @@ impl Executor {
 
         // Standard payment is executed in the calling account's context; the stack already
         // captures that.
-        let mut runtime = Runtime::new(self.config, runtime_context);
+        let mut runtime =
+            Runtime::new(self.config, Arc::clone(&self.module_cache), runtime_context);
 
         match runtime.call_host_standard_payment(stack) {
             Ok(()) => ExecutionResult::Success {
@@ -305,7 +449,8 @@ impl Executor {
             remaining_spending_limit,
         );
 
-        let mut runtime = Runtime::new(self.config, runtime_context);
+        let mut runtime =
+            Runtime::new(self.config, Arc::clone(&self.module_cache), runtime_context);
 
         // DO NOT alter this logic to call a system contract directly (such as via mint_internal,
         // etc). Doing so would bypass necessary context based security checks in some use cases. It
@@ -424,3 +569,29 @@ impl DirectSystemContractCall {
         }
     }
 }
+
+/// Panics if the effects of executing the same WASM with wasmi and with wasmtime differ.
+#[cfg(feature = "wasmtime-backend")]
+fn assert_same_effects(wasmi_result: &ExecutionResult, wasmtime_result: &ExecutionResult) {
+    assert_eq!(
+        wasmi_result.is_success(),
+        wasmtime_result.is_success(),
+        "wasmtime execution outcome differs from wasmi: {:?} vs {:?}",
+        wasmi_result.as_error(),
+        wasmtime_result.as_error()
+    );
+    assert!(
+        wasmi_result.execution_journal() == wasmtime_result.execution_journal(),
+        "wasmtime execution journal differs from wasmi"
+    );
+    assert_eq!(
+        wasmi_result.transfers(),
+        wasmtime_result.transfers(),
+        "wasmtime transfers differ from wasmi"
+    );
+    assert_eq!(
+        wasmi_result.cost(),
+        wasmtime_result.cost(),
+        "wasmtime cost differs from wasmi"
+    );
+}
@@ -59,6 +59,79 @@ pub(crate) enum FunctionIndex {
     LoadAuthorizationKeys,
     RandomBytes,
     DictionaryReadFuncIndex,
+    LoadNamedKeysPageFuncIndex,
+    RestrictURefFuncIndex,
+    ManageAssociatedKeysFuncIndex,
+    EmitMessageFuncIndex,
+}
+
+impl FunctionIndex {
+    /// Returns the name under which the host function is imported.
+    pub(crate) fn import_name(self) -> &'static str {
+        match self {
+            FunctionIndex::WriteFuncIndex => "casper_write",
+            FunctionIndex::ReadFuncIndex => "casper_read_value",
+            FunctionIndex::AddFuncIndex => "casper_add",
+            FunctionIndex::NewFuncIndex => "casper_new_uref",
+            FunctionIndex::RetFuncIndex => "casper_ret",
+            FunctionIndex::CallContractFuncIndex => "casper_call_contract",
+            FunctionIndex::GetKeyFuncIndex => "casper_get_key",
+            FunctionIndex::GasFuncIndex => "gas",
+            FunctionIndex::HasKeyFuncIndex => "casper_has_key",
+            FunctionIndex::PutKeyFuncIndex => "casper_put_key",
+            FunctionIndex::IsValidURefFnIndex => "casper_is_valid_uref",
+            FunctionIndex::RevertFuncIndex => "casper_revert",
+            FunctionIndex::AddAssociatedKeyFuncIndex => "casper_add_associated_key",
+            FunctionIndex::RemoveAssociatedKeyFuncIndex => "casper_remove_associated_key",
+            FunctionIndex::UpdateAssociatedKeyFuncIndex => "casper_update_associated_key",
+            FunctionIndex::SetActionThresholdFuncIndex => "casper_set_action_threshold",
+            FunctionIndex::LoadNamedKeysFuncIndex => "casper_load_named_keys",
+            FunctionIndex::RemoveKeyFuncIndex => "casper_remove_key",
+            FunctionIndex::GetCallerIndex => "casper_get_caller",
+            FunctionIndex::GetBlocktimeIndex => "casper_get_blocktime",
+            FunctionIndex::CreatePurseIndex => "casper_create_purse",
+            FunctionIndex::TransferToAccountIndex => "casper_transfer_to_account",
+            FunctionIndex::TransferFromPurseToAccountIndex => {
+                "casper_transfer_from_purse_to_account"
+            }
+            FunctionIndex::TransferFromPurseToPurseIndex => "casper_transfer_from_purse_to_purse",
+            FunctionIndex::GetBalanceIndex => "casper_get_balance",
+            FunctionIndex::GetPhaseIndex => "casper_get_phase",
+            FunctionIndex::GetSystemContractIndex => "casper_get_system_contract",
+            FunctionIndex::GetMainPurseIndex => "casper_get_main_purse",
+            FunctionIndex::ReadHostBufferIndex => "casper_read_host_buffer",
+            FunctionIndex::CreateContractPackageAtHash => "casper_create_contract_package_at_hash",
+            FunctionIndex::AddContractVersion => "casper_add_contract_version",
+            FunctionIndex::DisableContractVersion => "casper_disable_contract_version",
+            FunctionIndex::CallVersionedContract => "casper_call_versioned_contract",
+            FunctionIndex::CreateContractUserGroup => "casper_create_contract_user_group",
+            #[cfg(feature = "test-support")]
+            FunctionIndex::PrintIndex => "casper_print",
+            FunctionIndex::GetRuntimeArgsizeIndex => "casper_get_named_arg_size",
+            FunctionIndex::GetRuntimeArgIndex => "casper_get_named_arg",
+            FunctionIndex::RemoveContractUserGroupIndex => "casper_remove_contract_user_group",
+            FunctionIndex::ExtendContractUserGroupURefsIndex => {
+                "casper_provision_contract_user_group_uref"
+            }
+            FunctionIndex::RemoveContractUserGroupURefsIndex => {
+                "casper_remove_contract_user_group_urefs"
+            }
+            FunctionIndex::Blake2b => "casper_blake2b",
+            FunctionIndex::RecordTransfer => "casper_record_transfer",
+            FunctionIndex::RecordEraInfo => "casper_record_era_info",
+            FunctionIndex::NewDictionaryFuncIndex => "casper_new_dictionary",
+            FunctionIndex::DictionaryGetFuncIndex => "casper_dictionary_get",
+            FunctionIndex::DictionaryPutFuncIndex => "casper_dictionary_put",
+            FunctionIndex::LoadCallStack => "casper_load_call_stack",
+            FunctionIndex::LoadAuthorizationKeys => "casper_load_authorization_keys",
+            FunctionIndex::RandomBytes => "casper_random_bytes",
+            FunctionIndex::DictionaryReadFuncIndex => "casper_dictionary_read",
+            FunctionIndex::LoadNamedKeysPageFuncIndex => "casper_load_named_keys_page",
+            FunctionIndex::RestrictURefFuncIndex => "casper_restrict_uref",
+            FunctionIndex::ManageAssociatedKeysFuncIndex => "casper_manage_associated_keys",
+            FunctionIndex::EmitMessageFuncIndex => "casper_emit_message",
+        }
+    }
 }
 
 impl From<FunctionIndex> for usize {
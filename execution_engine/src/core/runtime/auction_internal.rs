@@ -140,6 +140,10 @@ where
     fn vesting_schedule_period_millis(&self) -> u64 {
         self.config.vesting_schedule_period_millis()
     }
+
+    fn allow_account_hash_v2(&self) -> bool {
+        self.config.allow_account_hash_v2()
+    }
 }
 
 impl<'a, R> MintProvider for Runtime<'a, R>
@@ -1,6 +1,6 @@
-use std::{collections::BTreeSet, convert::TryFrom};
+use std::collections::BTreeSet;
 
-use wasmi::{Externals, RuntimeArgs, RuntimeValue, Trap};
+use wasmi::{RuntimeArgs, RuntimeValue, Trap};
 
 use casper_types::{
     account::AccountHash,
@@ -15,23 +15,62 @@ use casper_types::{
 
 use super::{args::Args, Error, Runtime};
 use crate::{
-    core::resolvers::v1_function_index::FunctionIndex,
-    shared::host_function_costs::{Cost, HostFunction, DEFAULT_HOST_FUNCTION_NEW_DICTIONARY},
+    core::{backend::HostFunctions, resolvers::v1_function_index::FunctionIndex},
+    shared::{
+        gas_profile,
+        host_function_costs::{Cost, HostFunction, DEFAULT_HOST_FUNCTION_NEW_DICTIONARY},
+    },
     storage::global_state::StateReader,
 };
 
-impl<'a, R> Externals for Runtime<'a, R>
+impl<'a, R> HostFunctions for Runtime<'a, R>
 where
     R: StateReader<Key, StoredValue>,
     R::Error: Into<Error>,
 {
-    fn invoke_index(
+    fn call_host_function(
         &mut self,
-        index: usize,
+        func: FunctionIndex,
         args: RuntimeArgs,
     ) -> Result<Option<RuntimeValue>, Trap> {
-        let func = FunctionIndex::try_from(index).expect("unknown function index");
+        if func == FunctionIndex::GasFuncIndex || !gas_profile::is_active() {
+            return self.invoke_host_function(func, args);
+        }
+
+        gas_profile::enter_host_function();
+        let gas_before = self.gas_counter();
+        let result = self.invoke_host_function(func, args);
+        let gas = self
+            .gas_counter()
+            .checked_sub(gas_before)
+            .unwrap_or_default();
+        gas_profile::exit_host_function(func.import_name(), gas);
+        result
+    }
+
+    fn charge_wasm_gas(&mut self, amount: u32) -> Result<(), Trap> {
+        // Gas is special cased internal host function and for accounting purposes it isn't
+        // represented in protocol data.
+        if gas_profile::record_opcode(amount, &self.config.wasm_config().opcode_costs()) {
+            return Ok(());
+        }
+        let gas = Gas::new(amount.into());
+        self.gas(gas)?;
+        gas_profile::record_wasm_gas(gas);
+        Ok(())
+    }
+}
 
+impl<'a, R> Runtime<'a, R>
+where
+    R: StateReader<Key, StoredValue>,
+    R::Error: Into<Error>,
+{
+    fn invoke_host_function(
+        &mut self,
+        func: FunctionIndex,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
         let host_function_costs = self.config.wasm_config().take_host_function_costs();
 
         match func {
@@ -187,9 +226,7 @@ where
 
             FunctionIndex::GasFuncIndex => {
                 let (gas_arg,): (u32,) = Args::parse(args)?;
-                // Gas is special cased internal host function and for accounting purposes it isn't
-                // represented in protocol data.
-                self.gas(Gas::new(gas_arg.into()))?;
+                self.charge_wasm_gas(gas_arg)?;
                 Ok(None)
             }
 
@@ -920,7 +957,7 @@ where
                 let target: URef = self.t_from_mem(target_ptr, target_size)?;
                 let amount: U512 = self.t_from_mem(amount_ptr, amount_size)?;
                 let id: Option<u64> = self.t_from_mem(id_ptr, id_size)?;
-                self.record_transfer(maybe_to, source, target, amount, id)?;
+                self.record_transfer(maybe_to, source, target, amount, id, None)?;
                 Ok(Some(RuntimeValue::I32(0)))
             }
 
@@ -1060,6 +1097,73 @@ where
 
                 Ok(Some(RuntimeValue::I32(0)))
             }
+
+            FunctionIndex::LoadNamedKeysPageFuncIndex => {
+                // args(0) = index of the first named key of the page
+                // args(1) = maximum number of named keys in the page
+                // args(2) = pointer to total amount of keys (output)
+                // args(3) = pointer to amount of serialized bytes (output)
+                let (offset, count, total_keys_ptr, result_size_ptr) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.load_named_keys,
+                    [total_keys_ptr, result_size_ptr],
+                )?;
+                let ret =
+                    self.load_named_keys_page(offset, count, total_keys_ptr, result_size_ptr)?;
+                Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
+            }
+
+            FunctionIndex::RestrictURefFuncIndex => {
+                // args(0) = pointer to the URef to restrict
+                // args(1) = size of the URef
+                // args(2) = maximum access rights of the URef in other contexts
+                let (uref_ptr, uref_size, access_rights) = Args::parse(args)?;
+                // TODO: add cost table entry once we can upgrade safely
+                self.charge_host_function_call(
+                    &HostFunction::fixed(10_000),
+                    [uref_ptr, uref_size, access_rights],
+                )?;
+                let ret = self.restrict_uref(uref_ptr, uref_size, access_rights)?;
+                Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
+            }
+
+            FunctionIndex::ManageAssociatedKeysFuncIndex => {
+                // args(0) = pointer to the serialized list of key management operations
+                // args(1) = size of the serialized list
+                let (operations_ptr, operations_size): (u32, u32) = Args::parse(args)?;
+                // TODO: add cost table entry once we can upgrade safely
+                self.charge_host_function_call(
+                    &HostFunction::fixed(10_000),
+                    [operations_ptr, operations_size],
+                )?;
+                let value =
+                    self.manage_associated_keys(operations_ptr, operations_size as usize)?;
+                Ok(Some(RuntimeValue::I32(value)))
+            }
+
+            FunctionIndex::EmitMessageFuncIndex => {
+                // args(0) = pointer to the UTF-8 bytes of the topic name
+                // args(1) = size of the topic name
+                // args(2) = pointer to the message payload
+                // args(3) = size of the message payload
+                let (topic_name_ptr, topic_name_size, payload_ptr, payload_size): (
+                    u32,
+                    u32,
+                    u32,
+                    u32,
+                ) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.emit_message,
+                    [topic_name_ptr, topic_name_size, payload_ptr, payload_size],
+                )?;
+                let value = self.emit_message(
+                    topic_name_ptr,
+                    topic_name_size as usize,
+                    payload_ptr,
+                    payload_size as usize,
+                )?;
+                Ok(Some(RuntimeValue::I32(value)))
+            }
         }
     }
 }
@@ -153,8 +153,9 @@ where
         target: URef,
         amount: U512,
         id: Option<u64>,
+        memo: Option<String>,
     ) -> Result<(), Error> {
-        let result = Runtime::record_transfer(self, maybe_to, source, target, amount, id);
+        let result = Runtime::record_transfer(self, maybe_to, source, target, amount, id, memo);
         result.map_err(|exec_error| {
             <Option<Error>>::from(exec_error).unwrap_or(Error::RecordTransferFailure)
         })
@@ -14,15 +14,17 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     convert::{TryFrom, TryInto},
     iter::FromIterator,
+    sync::Arc,
 };
 
 use parity_wasm::elements::Module;
 use tracing::error;
-use wasmi::{MemoryRef, Trap, TrapKind};
+use wasmi::{Trap, TrapKind};
 
 use casper_types::{
-    account::{Account, AccountHash, ActionType, Weight},
+    account::{Account, AccountHash, ActionType, KeyManagementOperation, Weight},
     bytesrepr::{self, Bytes, FromBytes, ToBytes},
+    contract_messages::{MessageEmissionFailure, MAX_MESSAGE_PAYLOAD_SIZE, MAX_TOPIC_NAME_SIZE},
     contracts::{
         self, Contract, ContractPackage, ContractPackageStatus, ContractVersion, ContractVersions,
         DisabledVersions, EntryPoint, EntryPointAccess, EntryPoints, Group, Groups, NamedKeys,
@@ -37,12 +39,13 @@ use casper_types::{
     AccessRights, ApiError, CLTyped, CLValue, ContextAccessRights, ContractHash,
     ContractPackageHash, ContractVersionKey, ContractWasm, DeployHash, EntryPointType, EraId, Gas,
     GrantedAccess, Key, NamedArg, Parameter, Phase, PublicKey, RuntimeArgs, StoredValue, Transfer,
-    TransferResult, TransferredTo, URef, DICTIONARY_ITEM_KEY_MAX_LENGTH, U512,
+    TransferResult, TransferV2, TransferredTo, URef, DICTIONARY_ITEM_KEY_MAX_LENGTH, U512,
 };
 
 use crate::{
     core::{
-        engine_state::EngineConfig,
+        backend::{self, WasmMemory},
+        engine_state::{module_cache::ModuleCache, EngineConfig},
         execution::{self, Error},
         runtime::host_function_flag::HostFunctionFlag,
         runtime_context::{self, RuntimeContext},
@@ -73,7 +76,8 @@ enum CallContractIdentifier {
 /// Represents the runtime properties of a WASM execution.
 pub struct Runtime<'a, R> {
     config: EngineConfig,
-    memory: Option<MemoryRef>,
+    module_cache: Arc<ModuleCache>,
+    memory: Option<Box<dyn WasmMemory>>,
     module: Option<Module>,
     host_buffer: Option<CLValue>,
     context: RuntimeContext<'a, R>,
@@ -87,9 +91,14 @@ where
     R::Error: Into<Error>,
 {
     /// Creates a new runtime instance.
-    pub(crate) fn new(config: EngineConfig, context: RuntimeContext<'a, R>) -> Self {
+    pub(crate) fn new(
+        config: EngineConfig,
+        module_cache: Arc<ModuleCache>,
+        context: RuntimeContext<'a, R>,
+    ) -> Self {
         Runtime {
             config,
+            module_cache,
             memory: None,
             module: None,
             host_buffer: None,
@@ -99,17 +108,19 @@ where
         }
     }
 
-    /// Creates a new runtime instance by cloning the config, and host function flag from `self`.
+    /// Creates a new runtime instance by cloning the config, module cache and host function flag
+    /// from `self`.
     fn new_invocation_runtime(
         &self,
         context: RuntimeContext<'a, R>,
         module: Module,
-        memory: MemoryRef,
+        memory: Box<dyn WasmMemory>,
         stack: RuntimeStack,
     ) -> Self {
         Self::check_preconditions(&stack);
         Runtime {
             config: self.config,
+            module_cache: Arc::clone(&self.module_cache),
             memory: Some(memory),
             module: Some(module),
             host_buffer: None,
@@ -128,6 +139,7 @@ where
         Self::check_preconditions(&stack);
         Runtime {
             config: self.config,
+            module_cache: Arc::clone(&self.module_cache),
             memory: None,
             module: None,
             host_buffer: None,
@@ -656,8 +668,14 @@ where
                 let target: URef = Self::get_named_argument(runtime_args, mint::ARG_TARGET)?;
                 let amount: U512 = Self::get_named_argument(runtime_args, mint::ARG_AMOUNT)?;
                 let id: Option<u64> = Self::get_named_argument(runtime_args, mint::ARG_ID)?;
+                let memo: Option<String> = match runtime_args.get(mint::ARG_MEMO) {
+                    Some(_) if self.config.record_transfer_metadata() => {
+                        Self::get_named_argument(runtime_args, mint::ARG_MEMO)?
+                    }
+                    _ => None,
+                };
                 let result: Result<(), mint::Error> =
-                    mint_runtime.transfer(maybe_to, source, target, amount, id);
+                    mint_runtime.transfer(maybe_to, source, target, amount, id, memo);
                 CLValue::from_t(result).map_err(Self::reverter)
             })(),
             // Type: `fn read_base_round_reward() -> Result<U512, Error>`
@@ -1028,9 +1046,13 @@ where
         let protocol_version = self.context.protocol_version();
         let wasm_config = self.config.wasm_config();
         let module = wasm_prep::preprocess(*wasm_config, module_bytes)?;
-        let (instance, memory) =
-            utils::instance_and_memory(module.clone(), protocol_version, wasm_config)?;
-        self.memory = Some(memory);
+        let mut instance = backend::instantiate(
+            self.config.execution_backend(),
+            module.clone(),
+            protocol_version,
+            wasm_config,
+        )?;
+        self.memory = Some(instance.memory());
         self.module = Some(module);
         self.stack = Some(stack);
         self.context.set_args(utils::attenuate_uref_in_args(
@@ -1039,33 +1061,22 @@ where
             AccessRights::WRITE,
         )?);
 
-        let result = instance.invoke_export(DEFAULT_ENTRY_POINT_NAME, &[], self);
+        let result = instance.call_export(DEFAULT_ENTRY_POINT_NAME, self);
 
-        let error = match result {
-            Err(error) => error,
+        match result {
             // If `Ok` and the `host_buffer` is `None`, the contract's execution succeeded but did
             // not explicitly call `runtime::ret()`.  Treat as though the execution
             // returned the unit type `()` as per Rust functions which don't specify a
             // return value.
-            Ok(_) => {
-                return Ok(self.take_host_buffer().unwrap_or(CLValue::from_t(())?));
-            }
-        };
-
-        if let Some(host_error) = error.as_host_error() {
+            Ok(()) => Ok(self.take_host_buffer().unwrap_or(CLValue::from_t(())?)),
             // If the "error" was in fact a trap caused by calling `ret` then
             // this is normal operation and we should return the value captured
             // in the Runtime result field.
-            let downcasted_error = host_error.downcast_ref::<Error>();
-            match downcasted_error {
-                Some(Error::Ret(ref _ret_urefs)) => {
-                    return self.take_host_buffer().ok_or(Error::ExpectedReturnValue);
-                }
-                Some(error) => return Err(error.clone()),
-                None => return Err(Error::Interpreter(host_error.to_string())),
+            Err(Error::Ret(_ret_urefs)) => {
+                self.take_host_buffer().ok_or(Error::ExpectedReturnValue)
             }
+            Err(error) => Err(error),
         }
-        Err(Error::Interpreter(error.into()))
     }
 
     /// Calls contract living under a `key`, with supplied `args`.
@@ -1119,8 +1130,8 @@ where
         }
     }
 
-    fn try_get_memory(&self) -> Result<&MemoryRef, Error> {
-        self.memory.as_ref().ok_or(Error::WasmPreprocessing(
+    fn try_get_memory(&self) -> Result<&dyn WasmMemory, Error> {
+        self.memory.as_deref().ok_or(Error::WasmPreprocessing(
             PreprocessingError::MissingMemorySection,
         ))
     }
@@ -1333,13 +1344,20 @@ where
         let module: Module = {
             let wasm_key = contract.contract_wasm_key();
 
+            // The Wasm is read even if its module is cached, so that the read is tracked the same
+            // way either way.
             let contract_wasm: ContractWasm = match self.context.read_gs(&wasm_key)? {
                 Some(StoredValue::ContractWasm(contract_wasm)) => contract_wasm,
                 Some(_) => return Err(Error::InvalidContractWasm(contract.contract_wasm_hash())),
                 None => return Err(Error::KeyNotFound(context_key)),
             };
 
-            parity_wasm::deserialize_buffer(contract_wasm.bytes())?
+            self.module_cache.get_or_parse(
+                contract_hash,
+                contract.contract_wasm_hash(),
+                self.context.protocol_version(),
+                || Ok(parity_wasm::deserialize_buffer(contract_wasm.bytes())?),
+            )?
         };
 
         let context = self.context.new_from_self(
@@ -1350,14 +1368,16 @@ where
             context_args,
         );
         let protocol_version = self.context.protocol_version();
-        let (instance, memory) = utils::instance_and_memory(
+        let mut instance = backend::instantiate(
+            self.config.execution_backend(),
             module.clone(),
             protocol_version,
             self.config.wasm_config(),
         )?;
+        let memory = instance.memory();
         let runtime = &mut Runtime::new_invocation_runtime(self, context, module, memory, stack);
 
-        let result = instance.invoke_export(entry_point.name(), &[], runtime);
+        let result = instance.call_export(entry_point.name(), runtime);
 
         // The `runtime`'s context was initialized with our counter from before the call and any gas
         // charged by the sub-call was added to its counter - so let's copy the correct value of the
@@ -1369,12 +1389,11 @@ where
             *transfers = runtime.context.transfers().to_owned();
         }
 
-        let error = match result {
-            Err(error) => error,
+        match result {
             // If `Ok` and the `host_buffer` is `None`, the contract's execution succeeded but did
             // not explicitly call `runtime::ret()`.  Treat as though the execution returned the
             // unit type `()` as per Rust functions which don't specify a return value.
-            Ok(_) => {
+            Ok(()) => {
                 if self.context.entry_point_type() == EntryPointType::Session
                     && runtime.context.entry_point_type() == EntryPointType::Session
                 {
@@ -1384,39 +1403,30 @@ where
                 }
                 self.context
                     .set_remaining_spending_limit(runtime.context.remaining_spending_limit());
-                return Ok(runtime.take_host_buffer().unwrap_or(CLValue::from_t(())?));
+                Ok(runtime.take_host_buffer().unwrap_or(CLValue::from_t(())?))
             }
-        };
-
-        if let Some(host_error) = error.as_host_error() {
             // If the "error" was in fact a trap caused by calling `ret` then this is normal
             // operation and we should return the value captured in the Runtime result field.
-            let downcasted_error = host_error.downcast_ref::<Error>();
-            match downcasted_error {
-                Some(Error::Ret(ref ret_urefs)) => {
-                    // Insert extra urefs returned from call.
-                    // Those returned URef's are guaranteed to be valid as they were already
-                    // validated in the `ret` call inside context we ret from.
-                    self.context.access_rights_extend(ret_urefs);
-
-                    if self.context.entry_point_type() == EntryPointType::Session
-                        && runtime.context.entry_point_type() == EntryPointType::Session
-                    {
-                        // Overwrites parent's named keys with child's new named keys but only when
-                        // running session code.
-                        *self.context.named_keys_mut() = runtime.context.named_keys().clone();
-                    }
+            Err(Error::Ret(ret_urefs)) => {
+                // Insert extra urefs returned from call.
+                // Those returned URef's are guaranteed to be valid as they were already
+                // validated in the `ret` call inside context we ret from.
+                self.context.access_rights_extend(&ret_urefs);
 
-                    // Stored contracts are expected to always call a `ret` function, otherwise it's
-                    // an error.
-                    return runtime.take_host_buffer().ok_or(Error::ExpectedReturnValue);
+                if self.context.entry_point_type() == EntryPointType::Session
+                    && runtime.context.entry_point_type() == EntryPointType::Session
+                {
+                    // Overwrites parent's named keys with child's new named keys but only when
+                    // running session code.
+                    *self.context.named_keys_mut() = runtime.context.named_keys().clone();
                 }
-                Some(error) => return Err(error.clone()),
-                None => return Err(Error::Interpreter(host_error.to_string())),
+
+                // Stored contracts are expected to always call a `ret` function, otherwise it's
+                // an error.
+                runtime.take_host_buffer().ok_or(Error::ExpectedReturnValue)
             }
+            Err(error) => Err(error),
         }
-
-        Err(Error::Interpreter(error.into()))
     }
 
     fn call_contract_host_buffer(
@@ -1541,6 +1551,85 @@ where
         Ok(Ok(()))
     }
 
+    /// Loads the named keys of the current context in name order, skipping the first `offset` and
+    /// returning at most `count` of them, along with the total number of named keys.
+    fn load_named_keys_page(
+        &mut self,
+        offset: u32,
+        count: u32,
+        total_keys_ptr: u32,
+        result_size_ptr: u32,
+    ) -> Result<Result<(), ApiError>, Trap> {
+        if !self.can_write_to_host_buffer() {
+            // Exit early if the host buffer is already occupied
+            return Ok(Err(ApiError::HostBufferFull));
+        }
+
+        let total_keys: u32 = match self.context.named_keys().len().try_into() {
+            Ok(value) => value,
+            Err(_) => return Ok(Err(ApiError::OutOfMemory)),
+        };
+
+        let total_keys_bytes = total_keys.to_le_bytes();
+        if let Err(error) = self
+            .try_get_memory()?
+            .set(total_keys_ptr, &total_keys_bytes)
+        {
+            return Err(Error::Interpreter(error.into()).into());
+        }
+
+        let page: NamedKeys = self
+            .context
+            .named_keys()
+            .iter()
+            .skip(offset as usize)
+            .take(count as usize)
+            .map(|(name, key)| (name.clone(), *key))
+            .collect();
+
+        let length: u32 = if page.is_empty() {
+            // No need to write to the host buffer, we leave it empty.
+            0
+        } else {
+            let page = CLValue::from_t(page).map_err(Error::CLValue)?;
+            let length = match page.inner_bytes().len().try_into() {
+                Ok(value) => value,
+                Err(_) => return Ok(Err(ApiError::BufferTooSmall)),
+            };
+            if let Err(error) = self.write_host_buffer(page) {
+                return Ok(Err(error));
+            }
+            length
+        };
+
+        let length_bytes = length.to_le_bytes();
+        if let Err(error) = self.try_get_memory()?.set(result_size_ptr, &length_bytes) {
+            return Err(Error::Interpreter(error.into()).into());
+        }
+
+        Ok(Ok(()))
+    }
+
+    /// Restricts the access rights with which a [`URef`] held by the current context can be used
+    /// by any other context.
+    fn restrict_uref(
+        &mut self,
+        uref_ptr: u32,
+        uref_size: u32,
+        access_rights: u32,
+    ) -> Result<Result<(), ApiError>, Trap> {
+        let uref: URef = self.t_from_mem(uref_ptr, uref_size)?;
+        let max_rights = match u8::try_from(access_rights)
+            .ok()
+            .and_then(AccessRights::from_bits)
+        {
+            Some(max_rights) => max_rights,
+            None => return Ok(Err(ApiError::InvalidArgument)),
+        };
+        self.context.restrict_uref(uref, max_rights)?;
+        Ok(Ok(()))
+    }
+
     fn create_contract_package(
         &mut self,
         is_locked: ContractPackageStatus,
@@ -1809,6 +1898,7 @@ where
         target: URef,
         amount: U512,
         id: Option<u64>,
+        memo: Option<String>,
     ) -> Result<(), Error> {
         if self.context.base_key() != Key::from(self.context.get_system_contract(MINT)?) {
             return Err(Error::InvalidContext);
@@ -1818,19 +1908,50 @@ where
             return Ok(());
         }
 
+        if let Some(memo) = memo.as_ref() {
+            // The memo is stored alongside the transfer, so charge for it as for any other stored
+            // bytes.
+            let storage_costs = self.config.wasm_config().storage_costs();
+            self.context
+                .charge_gas(storage_costs.calculate_gas_cost(memo.len()))?;
+        }
+
         let transfer_addr = self.context.new_transfer_addr()?;
-        let transfer = {
-            let deploy_hash: DeployHash = self.context.get_deploy_hash();
-            let from: AccountHash = self.context.account().account_hash();
-            let fee: U512 = U512::zero(); // TODO
-            Transfer::new(deploy_hash, from, maybe_to, source, target, amount, fee, id)
-        };
+        let deploy_hash: DeployHash = self.context.get_deploy_hash();
+        let from: AccountHash = self.context.account().account_hash();
+        let fee: U512 = U512::zero(); // TODO
+        let transfer = Transfer::new(deploy_hash, from, maybe_to, source, target, amount, fee, id);
         {
             let transfers = self.context.transfers_mut();
             transfers.push(transfer_addr);
         }
+        if !self.config.record_transfer_metadata() {
+            self.context
+                .write_transfer(Key::Transfer(transfer_addr), transfer);
+            return Ok(());
+        }
+
+        let transfer = {
+            let initiator = match self.get_immediate_caller() {
+                Some(CallStackElement::StoredContract { .. }) => None,
+                Some(CallStackElement::Session { account_hash })
+                | Some(CallStackElement::StoredSession { account_hash, .. })
+                    if *account_hash != PublicKey::System.to_account_hash() =>
+                {
+                    Some(*account_hash)
+                }
+                // Native transfers are executed by the system on behalf of the deploy's account.
+                _ => Some(from),
+            };
+            TransferV2 {
+                initiator,
+                memo,
+                phase: self.context.phase(),
+                ..TransferV2::from(transfer)
+            }
+        };
         self.context
-            .write_transfer(Key::Transfer(transfer_addr), transfer);
+            .write_transfer_v2(Key::Transfer(transfer_addr), transfer);
         Ok(())
     }
 
@@ -2002,6 +2123,51 @@ where
         }
     }
 
+    fn manage_associated_keys(
+        &mut self,
+        operations_ptr: u32,
+        operations_size: usize,
+    ) -> Result<i32, Trap> {
+        let operations: Vec<KeyManagementOperation> = {
+            let operations_serialized = self.bytes_from_mem(operations_ptr, operations_size)?;
+            bytesrepr::deserialize(operations_serialized).map_err(Error::BytesRepr)?
+        };
+
+        match self.context.manage_associated_keys(&operations) {
+            Ok(_) => Ok(0),
+            Err(Error::KeyManagementFailure(e)) => Ok(e as i32),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn emit_message(
+        &mut self,
+        topic_name_ptr: u32,
+        topic_name_size: usize,
+        payload_ptr: u32,
+        payload_size: usize,
+    ) -> Result<i32, Trap> {
+        if topic_name_size > MAX_TOPIC_NAME_SIZE {
+            return Ok(MessageEmissionFailure::InvalidTopicName as i32);
+        }
+        if payload_size > MAX_MESSAGE_PAYLOAD_SIZE {
+            return Ok(MessageEmissionFailure::PayloadTooLarge as i32);
+        }
+
+        let topic_name =
+            match String::from_utf8(self.bytes_from_mem(topic_name_ptr, topic_name_size)?) {
+                Ok(topic_name) => topic_name,
+                Err(_) => return Ok(MessageEmissionFailure::InvalidTopicName as i32),
+            };
+        let payload = self.bytes_from_mem(payload_ptr, payload_size)?;
+
+        match self.context.emit_message(&topic_name, &payload) {
+            Ok(_) => Ok(0),
+            Err(Error::MessageEmissionFailure(e)) => Ok(e as i32),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     /// Looks up the public mint contract key in the context's protocol data.
     ///
     /// Returned URef is already attenuated depending on the calling account.
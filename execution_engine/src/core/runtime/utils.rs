@@ -1,47 +1,11 @@
 use std::collections::BTreeMap;
 
-use parity_wasm::elements::Module;
-use wasmi::{ImportsBuilder, MemoryRef, ModuleInstance, ModuleRef};
-
 use casper_types::{
-    contracts::NamedKeys, AccessRights, CLType, CLValue, Key, ProtocolVersion, PublicKey,
-    RuntimeArgs, URef, URefAddr, U128, U256, U512,
-};
-
-use crate::{
-    core::{
-        execution::Error,
-        resolvers::{self, memory_resolver::MemoryResolver},
-    },
-    shared::wasm_config::WasmConfig,
+    contracts::NamedKeys, AccessRights, CLType, CLValue, Key, PublicKey, RuntimeArgs, URef,
+    URefAddr, U128, U256, U512,
 };
 
-/// Creates an WASM module instance and a memory instance.
-///
-/// This ensures that a memory instance is properly resolved into a pre-allocated memory area, and a
-/// host function resolver is attached to the module.
-///
-/// The WASM module is also validated to not have a "start" section as we currently don't support
-/// running it.
-///
-/// Both [`ModuleRef`] and a [`MemoryRef`] are ready to be executed.
-pub(super) fn instance_and_memory(
-    parity_module: Module,
-    protocol_version: ProtocolVersion,
-    wasm_config: &WasmConfig,
-) -> Result<(ModuleRef, MemoryRef), Error> {
-    let module = wasmi::Module::from_parity_wasm_module(parity_module)?;
-    let resolver = resolvers::create_module_resolver(protocol_version, wasm_config)?;
-    let mut imports = ImportsBuilder::new();
-    imports.push_resolver("env", &resolver);
-    let not_started_module = ModuleInstance::new(&module, &imports)?;
-    if not_started_module.has_start() {
-        return Err(Error::UnsupportedWasmStart);
-    }
-    let instance = not_started_module.not_started_instance().clone();
-    let memory = resolver.memory_ref()?;
-    Ok((instance, memory))
-}
+use crate::core::execution::Error;
 
 /// Removes `rights_to_disable` from all urefs in `args` matching the address `uref_addr`.
 pub(super) fn attenuate_uref_in_args(
@@ -11,16 +11,22 @@ use tracing::error;
 
 use casper_types::{
     account::{
-        Account, AccountHash, ActionType, AddKeyFailure, RemoveKeyFailure, SetThresholdFailure,
-        UpdateKeyFailure, Weight,
+        Account, AccountHash, ActionType, AddKeyFailure, KeyManagementFailure,
+        KeyManagementOperation, RemoveKeyFailure, SetThresholdFailure, UpdateKeyFailure, Weight,
+    },
+    bytesrepr::{Bytes, ToBytes},
+    contract_messages::{
+        self, Message, MessageAddr, MessageEmissionFailure, MessageTopicSummary,
+        MAX_MESSAGE_PAYLOAD_SIZE, MAX_TOPIC_NAME_SIZE,
     },
-    bytesrepr::ToBytes,
     contracts::NamedKeys,
+    crypto,
     system::auction::EraInfo,
     AccessRights, BlockTime, CLType, CLValue, ContextAccessRights, Contract, ContractHash,
     ContractPackage, ContractPackageHash, DeployHash, DeployInfo, EntryPointAccess, EntryPointType,
     Gas, GrantedAccess, Key, KeyTag, Phase, ProtocolVersion, PublicKey, RuntimeArgs, StoredValue,
-    Transfer, TransferAddr, URef, URefAddr, DICTIONARY_ITEM_KEY_MAX_LENGTH, KEY_HASH_LENGTH, U512,
+    StoredValueTypeMismatch, Transfer, TransferAddr, TransferV2, URef, URefAddr,
+    DICTIONARY_ITEM_KEY_MAX_LENGTH, KEY_HASH_LENGTH, U512,
 };
 
 use crate::{
@@ -41,6 +47,19 @@ mod tests;
 /// Number of bytes returned from the `random_bytes` function.
 pub const RANDOM_BYTES_COUNT: usize = 32;
 
+/// Domain separator of the keys under which [`URef`] access rights restrictions are stored.
+const UREF_RESTRICTION_PREFIX: &[u8] = b"uref-restriction";
+
+/// Returns the key under which the access rights restriction of `uref` is stored.
+///
+/// As a [`Key::Hash`] can't be written to by contracts, the restriction can only be changed via
+/// [`RuntimeContext::restrict_uref`].
+fn uref_restriction_key(uref: &URef) -> Key {
+    let mut preimage = UREF_RESTRICTION_PREFIX.to_vec();
+    preimage.extend_from_slice(&uref.addr());
+    Key::Hash(crypto::blake2b(preimage))
+}
+
 /// Validates an entry point access with a special validator callback.
 ///
 /// If the passed `access` object is a `Groups` variant, then this function will return a
@@ -338,6 +357,10 @@ where
                 error!("should not remove the deploy approvals root hash key");
                 Err(Error::RemoveKeyFailure(RemoveKeyFailure::PermissionDenied))
             }
+            Key::Message(_) => {
+                error!("should not remove a message key");
+                Err(Error::RemoveKeyFailure(RemoveKeyFailure::PermissionDenied))
+            }
         }
     }
 
@@ -595,6 +618,19 @@ where
         }
     }
 
+    /// Write a transfer instance, along with its initiator, memo and phase, to the global state.
+    pub fn write_transfer_v2(&mut self, key: Key, value: TransferV2) {
+        if let Key::Transfer(_) = key {
+            // The memo is limited in length, so writing a `TransferV2` will not exceed write size
+            // limit either.
+            self.tracking_copy
+                .borrow_mut()
+                .write(key, StoredValue::TransferV2(value));
+        } else {
+            panic!("Do not use this function for writing non-transfer keys")
+        }
+    }
+
     /// Write an era info instance to the global state.
     pub fn write_era_info(&mut self, key: Key, value: EraInfo) {
         if let Key::EraInfo(_) = key {
@@ -718,6 +754,9 @@ where
             StoredValue::Bid(_) => Ok(()),
             StoredValue::Withdraw(_) => Ok(()),
             StoredValue::Unbonding(_) => Ok(()),
+            StoredValue::MessageTopic(_) => Ok(()),
+            StoredValue::Message(_) => Ok(()),
+            StoredValue::TransferV2(_) => Ok(()),
         }
     }
 
@@ -735,13 +774,82 @@ where
 
     /// Validate [`URef`] access rights.
     ///
-    /// Returns unit if [`URef`]s address exists in the context, and has correct access rights bit
-    /// set.
+    /// Returns unit if [`URef`]s address exists in the context, has correct access rights bit
+    /// set, and its access rights don't exceed those it was restricted to by another context.
     pub(crate) fn validate_uref(&self, uref: &URef) -> Result<(), Error> {
-        if self.access_rights.has_access_rights_to_uref(uref) {
-            Ok(())
-        } else {
-            Err(Error::ForgedReference(*uref))
+        if !self.access_rights.has_access_rights_to_uref(uref) {
+            return Err(Error::ForgedReference(*uref));
+        }
+        match self.read_uref_restriction(uref)? {
+            Some((issuer, max_rights))
+                if issuer != self.base_key() && !max_rights.contains(uref.access_rights()) =>
+            {
+                // System contracts only receive URefs which were already validated in the context
+                // of their caller.
+                if self.is_system_context()? {
+                    Ok(())
+                } else {
+                    Err(Error::RestrictedURef(*uref))
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Restricts the access rights with which `uref` can be used by any other context to
+    /// `max_rights`.
+    ///
+    /// The current context must hold `uref` with full access rights, and an existing restriction
+    /// can only be changed by the context which issued it.  Restricting to
+    /// [`AccessRights::READ_ADD_WRITE`] lifts the restriction, while restricting to
+    /// [`AccessRights::NONE`] revokes `uref` entirely.
+    pub(crate) fn restrict_uref(
+        &mut self,
+        uref: URef,
+        max_rights: AccessRights,
+    ) -> Result<(), Error> {
+        if uref.access_rights() != AccessRights::READ_ADD_WRITE {
+            return Err(Error::InvalidAccess {
+                required: AccessRights::READ_ADD_WRITE,
+            });
+        }
+        self.validate_uref(&uref)?;
+
+        let issuer = self.base_key();
+        if let Some((existing_issuer, _)) = self.read_uref_restriction(&uref)? {
+            if existing_issuer != issuer {
+                return Err(Error::RestrictedURef(uref));
+            }
+        }
+
+        let restriction = CLValue::from_t((issuer, max_rights.bits()))?;
+        self.metered_write_gs_unsafe(uref_restriction_key(&uref), restriction)
+    }
+
+    /// Reads the issuer and maximum access rights of the restriction placed on `uref`, if any.
+    ///
+    /// The read is not recorded in the execution journal.
+    fn read_uref_restriction(&self, uref: &URef) -> Result<Option<(Key, AccessRights)>, Error> {
+        let maybe_stored_value = self
+            .tracking_copy
+            .borrow_mut()
+            .get(self.correlation_id, &uref_restriction_key(uref))
+            .map_err(Into::into)?;
+        match maybe_stored_value {
+            Some(StoredValue::CLValue(cl_value)) => {
+                let (issuer, max_rights): (Key, u8) = cl_value.into_t()?;
+                Ok(Some((issuer, AccessRights::from_bits_truncate(max_rights))))
+            }
+            Some(_) => Err(Error::UnexpectedStoredValueVariant),
+            None => Ok(None),
+        }
+    }
+
+    /// Checks if the current context is a system contract.
+    fn is_system_context(&self) -> Result<bool, Error> {
+        match self.base_key().into_hash() {
+            Some(hash) => self.is_system_contract(&ContractHash::new(hash)),
+            None => Ok(false),
         }
     }
 
@@ -796,6 +904,7 @@ where
             Key::ChainspecRegistry => true,
             Key::BlockEffectsRootHash { .. } => true,
             Key::DeployApprovalsRootHash { .. } => true,
+            Key::Message(_) => true,
         }
     }
 
@@ -820,6 +929,7 @@ where
             Key::ChainspecRegistry => false,
             Key::BlockEffectsRootHash { .. } => false,
             Key::DeployApprovalsRootHash { .. } => false,
+            Key::Message(_) => false,
         }
     }
 
@@ -844,6 +954,7 @@ where
             Key::ChainspecRegistry => false,
             Key::BlockEffectsRootHash { .. } => false,
             Key::DeployApprovalsRootHash { .. } => false,
+            Key::Message(_) => false,
         }
     }
 
@@ -1149,6 +1260,112 @@ where
         Ok(())
     }
 
+    /// Applies a batch of associated key and action threshold changes as a single unit.
+    ///
+    /// Only the resulting account is validated against the action thresholds and the maximum
+    /// number of associated keys, so an account can be reconfigured without passing through
+    /// intermediate states which would be rejected.
+    pub(crate) fn manage_associated_keys(
+        &mut self,
+        operations: &[KeyManagementOperation],
+    ) -> Result<(), Error> {
+        // Check permission to modify associated keys
+        if !self.is_valid_context() {
+            // Exit early with error to avoid mutations
+            return Err(KeyManagementFailure::PermissionDenied.into());
+        }
+
+        if !self
+            .account()
+            .can_manage_keys_with(&self.authorization_keys)
+        {
+            // Exit early if authorization keys weight doesn't exceed required
+            // key management threshold
+            return Err(KeyManagementFailure::PermissionDenied.into());
+        }
+
+        // Converts an account's public key into a URef
+        let key = Key::Account(self.account().account_hash());
+
+        // Take an account out of the global state
+        let mut account: Account = self.read_gs_typed(&key)?;
+
+        // Exit early in case of error without updating global state
+        account
+            .apply_key_management_operations(operations)
+            .map_err(Error::from)?;
+
+        if account.associated_keys().len() > (self.engine_config.max_associated_keys() as usize) {
+            return Err(KeyManagementFailure::MaxKeysLimit.into());
+        }
+
+        let account_value = self.account_to_validated_value(account)?;
+
+        self.metered_write_gs_unsafe(key, account_value)?;
+
+        Ok(())
+    }
+
+    /// Emits a message on the named topic of the current contract.
+    ///
+    /// The topic is registered by the first message emitted on it.  Only the checksum of the
+    /// payload is written to global state, under the next index of the topic; the payload itself is
+    /// recorded in the execution effects.
+    pub(crate) fn emit_message(&mut self, topic_name: &str, payload: &[u8]) -> Result<(), Error> {
+        let entity_addr = match self.base_key() {
+            Key::Hash(contract_hash) if self.entry_point_type() == EntryPointType::Contract => {
+                contract_hash
+            }
+            _ => return Err(MessageEmissionFailure::InvalidContext.into()),
+        };
+
+        if topic_name.is_empty() || topic_name.len() > MAX_TOPIC_NAME_SIZE {
+            return Err(MessageEmissionFailure::InvalidTopicName.into());
+        }
+        if payload.len() > MAX_MESSAGE_PAYLOAD_SIZE {
+            return Err(MessageEmissionFailure::PayloadTooLarge.into());
+        }
+
+        let topic_name_hash = contract_messages::topic_name_hash(topic_name);
+        let topic_key = Key::Message(MessageAddr::new_topic_addr(entity_addr, topic_name_hash));
+        let message_index = match self.read_gs_direct(&topic_key)? {
+            Some(StoredValue::MessageTopic(summary)) => summary.message_count(),
+            Some(other) => {
+                return Err(Error::TypeMismatch(StoredValueTypeMismatch::new(
+                    "MessageTopic".to_string(),
+                    other.type_name(),
+                )))
+            }
+            None => 0,
+        };
+        let message_count = message_index
+            .checked_add(1)
+            .ok_or(MessageEmissionFailure::TopicFull)?;
+
+        let message_key = Key::Message(MessageAddr::new_message_addr(
+            entity_addr,
+            topic_name_hash,
+            message_index,
+        ));
+        let message = Message::new(Bytes::from(payload));
+        // Charge for the payload as well as the checksum, as both are kept in execution results.
+        self.charge_gas_storage(
+            StoredValue::Message(message.checksum()).serialized_length() + payload.len(),
+        )?;
+        self.tracking_copy
+            .borrow_mut()
+            .write_message(message_key, message);
+        self.metered_write_gs_unsafe(
+            topic_key,
+            StoredValue::MessageTopic(MessageTopicSummary::new(
+                message_count,
+                self.get_blocktime(),
+            )),
+        )?;
+
+        Ok(())
+    }
+
     /// Creates validated instance of `StoredValue` from `account`.
     fn account_to_validated_value(&self, account: Account) -> Result<StoredValue, Error> {
         let value = StoredValue::Account(account);
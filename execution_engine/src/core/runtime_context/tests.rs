@@ -10,10 +10,14 @@ use rand::RngCore;
 
 use casper_types::{
     account::{
-        Account, AccountHash, ActionType, AddKeyFailure, AssociatedKeys, RemoveKeyFailure,
-        SetThresholdFailure, Weight, ACCOUNT_HASH_LENGTH,
+        Account, AccountHash, ActionType, AddKeyFailure, AssociatedKeys, KeyManagementFailure,
+        KeyManagementOperation, RemoveKeyFailure, SetThresholdFailure, Weight, ACCOUNT_HASH_LENGTH,
+    },
+    bytesrepr::{Bytes, ToBytes},
+    contract_messages::{
+        self, Message, MessageAddr, MessageChecksum, MessageEmissionFailure, MessageTopicSummary,
+        MAX_MESSAGE_PAYLOAD_SIZE,
     },
-    bytesrepr::ToBytes,
     contracts::NamedKeys,
     system::{AUCTION, HANDLE_PAYMENT, MINT, STANDARD_PAYMENT},
     AccessRights, BlockTime, CLValue, ContextAccessRights, Contract, ContractHash, DeployHash,
@@ -21,7 +25,7 @@ use casper_types::{
     SecretKey, StoredValue, URef, KEY_HASH_LENGTH, U256, U512,
 };
 
-use super::{Error, RuntimeContext};
+use super::{uref_restriction_key, Error, RuntimeContext};
 use crate::{
     core::{
         engine_state::{EngineConfig, SystemContractRegistry},
@@ -725,6 +729,61 @@ fn action_thresholds_management() {
     let _ = build_runtime_context_and_execute(named_keys, query);
 }
 
+#[test]
+fn batch_associated_keys_management() {
+    let named_keys = NamedKeys::new();
+    let query = |mut runtime_context: RuntimeContext<InMemoryGlobalStateView>| {
+        let key_1 = AccountHash::new([42; 32]);
+        let key_2 = AccountHash::new([43; 32]);
+
+        // The thresholds are raised before the keys which satisfy them are added.
+        runtime_context
+            .manage_associated_keys(&[
+                KeyManagementOperation::SetKeyManagementThreshold(Weight::new(3)),
+                KeyManagementOperation::SetDeploymentThreshold(Weight::new(2)),
+                KeyManagementOperation::AddKey {
+                    account_hash: key_1,
+                    weight: Weight::new(1),
+                },
+                KeyManagementOperation::AddKey {
+                    account_hash: key_2,
+                    weight: Weight::new(1),
+                },
+            ])
+            .expect("Unable to manage associated keys");
+
+        let effect = runtime_context.effect();
+        let transform = effect.transforms.get(&runtime_context.base_key()).unwrap();
+        let mutated_account = match transform {
+            Transform::Write(StoredValue::Account(account)) => account.clone(),
+            _ => panic!("Invalid transform operation found"),
+        };
+
+        assert_eq!(mutated_account.associated_keys().len(), 3);
+        assert_eq!(
+            mutated_account.action_thresholds().deployment(),
+            &Weight::new(2)
+        );
+        assert_eq!(
+            mutated_account.action_thresholds().key_management(),
+            &Weight::new(3)
+        );
+
+        let err = runtime_context
+            .manage_associated_keys(&[KeyManagementOperation::RemoveKey {
+                account_hash: key_2,
+            }])
+            .expect_err("Shouldn't be able to fall below the key management threshold");
+        match err {
+            Error::KeyManagementFailure(KeyManagementFailure::InsufficientTotalWeight) => {}
+            e => panic!("Invalid error variant: {:?}", e),
+        }
+
+        Ok(())
+    };
+    let _ = build_runtime_context_and_execute(named_keys, query);
+}
+
 #[test]
 fn should_verify_ownership_before_adding_key() {
     // Testing a valid case only - successfully added a key, and successfully removed,
@@ -938,6 +997,56 @@ fn validate_valid_purse_of_an_account() {
     assert!(runtime_context.validate_uref(&purse).is_err());
 }
 
+#[test]
+fn validate_restricted_uref() {
+    let issued_uref = URef::new([42u8; 32], AccessRights::READ_ADD_WRITE);
+    let received_uref = URef::new([43u8; 32], AccessRights::READ_ADD_WRITE);
+
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert("issued".to_string(), Key::from(issued_uref));
+    named_keys.insert("received".to_string(), Key::from(received_uref));
+
+    let deploy_hash = [1u8; 32];
+    let (base_key, account) =
+        new_account_with_purse(AccountHash::new([0u8; 32]), [0u8; 32], named_keys.clone());
+    let access_rights = account.extract_access_rights();
+
+    let address_generator = AddressGenerator::new(&deploy_hash, Phase::Session);
+    let mut runtime_context = new_runtime_context(
+        &account,
+        base_key,
+        &mut named_keys,
+        access_rights,
+        address_generator,
+    );
+
+    // The issuer of a restriction isn't bound by it.
+    runtime_context
+        .restrict_uref(issued_uref, AccessRights::NONE)
+        .expect("should restrict uref");
+    assert!(runtime_context.validate_uref(&issued_uref).is_ok());
+
+    // Any other context may only use the URef within the restricted access rights.
+    let other_issuer = Key::Hash([7u8; 32]);
+    let restriction = CLValue::from_t((other_issuer, AccessRights::READ.bits())).unwrap();
+    runtime_context
+        .metered_write_gs_unsafe(uref_restriction_key(&received_uref), restriction)
+        .unwrap();
+    assert!(runtime_context
+        .validate_uref(&received_uref.into_read())
+        .is_ok());
+    assert!(matches!(
+        runtime_context.validate_uref(&received_uref),
+        Err(Error::RestrictedURef(uref)) if uref == received_uref
+    ));
+
+    // Only the issuer may change the restriction.
+    assert!(matches!(
+        runtime_context.restrict_uref(received_uref, AccessRights::READ_ADD_WRITE),
+        Err(Error::RestrictedURef(_))
+    ));
+}
+
 #[test]
 fn should_meter_for_gas_storage_write() {
     // Test fixture
@@ -1033,3 +1142,95 @@ fn associated_keys_add_full() {
         Error::AddKeyFailure(AddKeyFailure::MaxKeysLimit)
     ));
 }
+
+#[test]
+fn should_emit_messages_from_contract_context() {
+    let account_hash = AccountHash::new([2; 32]);
+    let (account_key, account) = new_account(account_hash, NamedKeys::new());
+    let contract_addr = [3; KEY_HASH_LENGTH];
+    let mut named_keys = NamedKeys::new();
+    let tracking_copy = new_tracking_copy(account_key, account.clone());
+    let mut runtime_context = RuntimeContext::new(
+        Rc::new(RefCell::new(tracking_copy)),
+        EntryPointType::Contract,
+        &mut named_keys,
+        account.extract_access_rights(),
+        RuntimeArgs::new(),
+        BTreeSet::from_iter(vec![account_hash]),
+        &account,
+        Key::Hash(contract_addr),
+        BlockTime::new(5),
+        DeployHash::new(DEPLOY_HASH),
+        Gas::new(U512::from(GAS_LIMIT)),
+        Gas::default(),
+        Rc::new(RefCell::new(AddressGenerator::new(&DEPLOY_HASH, PHASE))),
+        ProtocolVersion::V1_0_0,
+        CorrelationId::new(),
+        PHASE,
+        EngineConfig::default(),
+        Vec::default(),
+        U512::MAX,
+    );
+
+    runtime_context
+        .emit_message("transfers", b"first")
+        .expect("should emit first message");
+    runtime_context
+        .emit_message("transfers", b"second")
+        .expect("should emit second message");
+
+    let topic_name_hash = contract_messages::topic_name_hash("transfers");
+    let effect = runtime_context.effect();
+    let topic_key = Key::Message(MessageAddr::new_topic_addr(contract_addr, topic_name_hash));
+    assert_eq!(
+        effect.transforms.get(&topic_key),
+        Some(&Transform::Write(StoredValue::MessageTopic(
+            MessageTopicSummary::new(2, BlockTime::new(5))
+        )))
+    );
+    for (index, payload) in [&b"first"[..], &b"second"[..]].iter().enumerate() {
+        let message_key = Key::Message(MessageAddr::new_message_addr(
+            contract_addr,
+            topic_name_hash,
+            index as u32,
+        ));
+        assert_eq!(
+            effect.transforms.get(&message_key),
+            Some(&Transform::WriteMessage(Message::new(Bytes::from(
+                *payload
+            ))))
+        );
+        assert_eq!(
+            runtime_context.read_gs_direct(&message_key).unwrap(),
+            Some(StoredValue::Message(MessageChecksum::from_payload(payload)))
+        );
+    }
+
+    let result = runtime_context.emit_message("", b"payload");
+    assert!(matches!(
+        result,
+        Err(Error::MessageEmissionFailure(
+            MessageEmissionFailure::InvalidTopicName
+        ))
+    ));
+    let result = runtime_context.emit_message("transfers", &[0; MAX_MESSAGE_PAYLOAD_SIZE + 1][..]);
+    assert!(matches!(
+        result,
+        Err(Error::MessageEmissionFailure(
+            MessageEmissionFailure::PayloadTooLarge
+        ))
+    ));
+}
+
+#[test]
+fn should_not_emit_messages_from_session_context() {
+    let result = build_runtime_context_and_execute(NamedKeys::new(), |mut rc| {
+        rc.emit_message("transfers", b"payload")
+    });
+    assert!(matches!(
+        result,
+        Err(Error::MessageEmissionFailure(
+            MessageEmissionFailure::InvalidContext
+        ))
+    ));
+}
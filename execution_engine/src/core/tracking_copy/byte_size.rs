@@ -44,6 +44,9 @@ impl ByteSize for StoredValue {
                 StoredValue::Bid(bid) => bid.serialized_length(),
                 StoredValue::Withdraw(withdraw_purses) => withdraw_purses.serialized_length(),
                 StoredValue::Unbonding(unbonding_purses) => unbonding_purses.serialized_length(),
+                StoredValue::MessageTopic(summary) => summary.serialized_length(),
+                StoredValue::Message(checksum) => checksum.serialized_length(),
+                StoredValue::TransferV2(transfer) => transfer.serialized_length(),
             }
     }
 }
@@ -19,6 +19,7 @@ use thiserror::Error;
 use casper_hashing::Digest;
 use casper_types::{
     bytesrepr::{self},
+    contract_messages::Message,
     CLType, CLValue, CLValueError, Key, KeyTag, StoredValue, StoredValueTypeMismatch, Tagged, U512,
 };
 
@@ -353,6 +354,16 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         self.journal.push((normalized_key, Transform::Write(value)));
     }
 
+    /// Writes the checksum of `message` under `key`, recording the message's payload in the
+    /// journal.  As with [`TrackingCopy::write`], the write is only cached.
+    pub fn write_message(&mut self, key: Key, message: Message) {
+        let normalized_key = key.normalize();
+        self.cache
+            .insert_write(normalized_key, StoredValue::Message(message.checksum()));
+        self.journal
+            .push((normalized_key, Transform::WriteMessage(message)));
+    }
+
     /// Ok(None) represents missing key to which we want to "add" some value.
     /// Ok(Some(unit)) represents successful operation.
     /// Err(error) is reserved for unexpected errors when accessing global
@@ -429,6 +440,41 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         }
     }
 
+    /// Applies the transforms of a journal produced against an earlier version of the same state,
+    /// as if the operations which produced them were executed on this instance.
+    ///
+    /// Reads are not replayed, and additions are applied to the values currently stored.  Returns
+    /// `false`, leaving this instance partially modified, if a transform cannot be applied.
+    pub fn apply_journal(
+        &mut self,
+        correlation_id: CorrelationId,
+        journal: ExecutionJournal,
+    ) -> Result<bool, R::Error> {
+        for (key, transform) in journal {
+            match transform {
+                Transform::Identity => {}
+                Transform::Write(value) => self.write(key, value),
+                Transform::WriteMessage(message) => self.write_message(key, message),
+                Transform::Failure(_) => return Ok(false),
+                transform => {
+                    let normalized_key = key.normalize();
+                    let current_value = match self.get(correlation_id, &normalized_key)? {
+                        Some(current_value) => current_value,
+                        None => return Ok(false),
+                    };
+                    match transform.clone().apply(current_value) {
+                        Ok(new_value) => {
+                            self.cache.insert_write(normalized_key, new_value);
+                            self.journal.push((normalized_key, transform));
+                        }
+                        Err(_) => return Ok(false),
+                    }
+                }
+            }
+        }
+        Ok(true)
+    }
+
     /// Returns the execution effects cached by this instance.
     pub fn effect(&self) -> ExecutionEffect {
         ExecutionEffect::from(self.journal.clone())
@@ -559,6 +605,15 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
                 StoredValue::Unbonding(_) => {
                     return Ok(query.into_not_found_result("UnbondingPurses value found."));
                 }
+                StoredValue::MessageTopic(_) => {
+                    return Ok(query.into_not_found_result("MessageTopic value found."));
+                }
+                StoredValue::Message(_) => {
+                    return Ok(query.into_not_found_result("Message value found."));
+                }
+                StoredValue::TransferV2(_) => {
+                    return Ok(query.into_not_found_result("TransferV2 value found."));
+                }
             }
         }
     }
@@ -656,8 +711,18 @@ impl From<bytesrepr::Error> for ValidationError {
     }
 }
 
+/// Returns whether a query continues from `value` to the key it holds, without consuming a path
+/// component.
+fn is_key_value(value: &StoredValue) -> bool {
+    matches!(value, StoredValue::CLValue(cl_value) if cl_value.cl_type() == &CLType::Key)
+}
+
 /// Validates proof of the query.
 ///
+/// As when querying, a value holding a [`Key`] is followed to that key without consuming a path
+/// component, so the proofs should number one more than the path components and such values.
+/// Likewise, a dictionary item is expected as its value, unwrapped from its `DictionaryValue`.
+///
 /// Returns [`ValidationError`] for any of
 pub fn validate_query_proof(
     hash: &Digest,
@@ -666,11 +731,19 @@ pub fn validate_query_proof(
     path: &[String],
     expected_value: &StoredValue,
 ) -> Result<(), ValidationError> {
-    if proofs.len() != path.len() + 1 {
+    let key_values = match proofs.split_last() {
+        Some((_, followed_proofs)) => followed_proofs
+            .iter()
+            .filter(|proof| is_key_value(proof.value()))
+            .count(),
+        None => 0,
+    };
+    if proofs.len() != path.len() + key_values + 1 {
         return Err(ValidationError::PathLengthDifferentThanProofLessOne);
     }
 
     let mut proofs_iter = proofs.iter();
+    let mut path_iter = path.iter();
 
     // length check above means we are safe to unwrap here
     let first_proof = proofs_iter.next().unwrap();
@@ -685,16 +758,26 @@ pub fn validate_query_proof(
 
     let mut proof_value = first_proof.value();
 
-    for (proof, path_component) in proofs_iter.zip(path.iter()) {
-        let named_keys = match proof_value {
-            StoredValue::Account(account) => account.named_keys(),
-            StoredValue::Contract(contract) => contract.named_keys(),
-            _ => return Err(ValidationError::PathCold),
-        };
-
-        let key = match named_keys.get(path_component) {
-            Some(key) => key,
-            None => return Err(ValidationError::PathCold),
+    for proof in proofs_iter {
+        let key = if is_key_value(proof_value) {
+            let cl_value: CLValue = proof_value
+                .clone()
+                .try_into()
+                .map_err(|_| ValidationError::ValueToCLValueConversion)?;
+            cl_value.into_t::<Key>()?
+        } else {
+            let named_keys = match proof_value {
+                StoredValue::Account(account) => account.named_keys(),
+                StoredValue::Contract(contract) => contract.named_keys(),
+                _ => return Err(ValidationError::PathCold),
+            };
+            let path_component = path_iter
+                .next()
+                .ok_or(ValidationError::PathLengthDifferentThanProofLessOne)?;
+            match named_keys.get(path_component) {
+                Some(key) => *key,
+                None => return Err(ValidationError::PathCold),
+            }
         };
 
         if proof.key() != &key.normalize() {
@@ -708,7 +791,12 @@ pub fn validate_query_proof(
         proof_value = proof.value();
     }
 
-    if proof_value != expected_value {
+    // As when querying, dictionary values are expected without their `DictionaryValue` wrapper.
+    // The length check above means we are safe to unwrap here.
+    let last_key = proofs.last().unwrap().key();
+    let proof_value = dictionary::handle_stored_value(*last_key, proof_value.clone())?;
+
+    if &proof_value != expected_value {
         return Err(ValidationError::UnexpectedValue);
     }
 
@@ -6,10 +6,11 @@ use proptest::prelude::*;
 use casper_hashing::Digest;
 use casper_types::{
     account::{Account, AccountHash, AssociatedKeys, Weight, ACCOUNT_HASH_LENGTH},
+    contract_messages::{topic_name_hash, MessageChecksum, MessageTopicSummary},
     contracts::NamedKeys,
     gens::*,
-    AccessRights, CLValue, Contract, EntryPoints, HashAddr, Key, KeyTag, ProtocolVersion,
-    StoredValue, URef, U256, U512,
+    AccessRights, BlockTime, CLValue, Contract, EntryPoints, HashAddr, Key, KeyTag,
+    ProtocolVersion, StoredValue, URef, U256, U512,
 };
 
 use super::{
@@ -304,6 +305,40 @@ fn tracking_copy_aw() {
     );
 }
 
+#[test]
+fn tracking_copy_apply_journal() {
+    let correlation_id = CorrelationId::new();
+    let five = StoredValue::CLValue(CLValue::from_t(5_i32).unwrap());
+    let mut tc = TrackingCopy::new(CountingDb::new_init(five));
+    let k = Key::Hash([0u8; 32]);
+    let written = Key::Hash([1u8; 32]);
+    let value = StoredValue::CLValue(CLValue::from_t(7_i32).unwrap());
+
+    // Reads are skipped and additions apply to the value currently stored.
+    let journal = ExecutionJournal::new(vec![
+        (k, Transform::Identity),
+        (k, Transform::AddInt32(3)),
+        (written, Transform::Write(value.clone())),
+    ]);
+    assert_matches!(tc.apply_journal(correlation_id, journal), Ok(true));
+    assert_eq!(
+        tc.journal,
+        ExecutionJournal::new(vec![
+            (k, Transform::AddInt32(3)),
+            (written, Transform::Write(value.clone()))
+        ])
+    );
+    assert_eq!(
+        tc.read(correlation_id, &k).unwrap(),
+        Some(StoredValue::CLValue(CLValue::from_t(8_i32).unwrap()))
+    );
+    assert_eq!(tc.read(correlation_id, &written).unwrap(), Some(value));
+
+    // Adding to a value of another type fails.
+    let journal = ExecutionJournal::new(vec![(k, Transform::AddKeys(NamedKeys::new()))]);
+    assert_matches!(tc.apply_journal(correlation_id, journal), Ok(false));
+}
+
 proptest! {
     #[test]
     fn query_empty_path(k in key_arb(), missing_key in key_arb(), v in stored_value_arb()) {
@@ -821,6 +856,102 @@ fn validate_query_proof_should_work() {
     );
 }
 
+#[test]
+fn validate_query_proof_should_follow_key_values() {
+    // create account
+    let account_hash = AccountHash::new([3; 32]);
+    let fake_purse = URef::new([4; 32], AccessRights::READ_ADD_WRITE);
+    let account_value = StoredValue::Account(Account::create(
+        account_hash,
+        NamedKeys::default(),
+        fake_purse,
+    ));
+    let account_key = Key::Account(account_hash);
+
+    // create a uref holding the account's key
+    let uref_value = StoredValue::CLValue(CLValue::from_t(account_key).expect("should convert"));
+    let uref_key = Key::URef(URef::new([8; 32], AccessRights::READ_ADD_WRITE));
+
+    // create account that refers to that uref
+    let account_hash = AccountHash::new([7; 32]);
+    let fake_purse = URef::new([6; 32], AccessRights::READ_ADD_WRITE);
+    let uref_name = "uref".to_string();
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(uref_name.clone(), uref_key);
+    let main_account_value =
+        StoredValue::Account(Account::create(account_hash, named_keys, fake_purse));
+    let main_account_key = Key::Account(account_hash);
+
+    let correlation_id = CorrelationId::new();
+    let (global_state, root_hash) = InMemoryGlobalState::from_pairs(
+        correlation_id,
+        &[
+            (account_key, account_value.clone()),
+            (uref_key, uref_value.clone()),
+            (main_account_key, main_account_value),
+        ],
+    )
+    .unwrap();
+    let view = global_state
+        .checkout(root_hash)
+        .expect("should checkout")
+        .expect("should have view");
+    let tracking_copy = TrackingCopy::new(view);
+
+    // Following the uref's key to the account once more names are left to visit.
+    let path = &[uref_name, "unused".to_string()];
+    let result = tracking_copy
+        .query(
+            correlation_id,
+            &EngineConfig::default(),
+            main_account_key,
+            &path[..1],
+        )
+        .expect("should query");
+    let proofs = if let TrackingCopyQueryResult::Success { proofs, .. } = result {
+        proofs
+    } else {
+        panic!("query was not successful: {:?}", result)
+    };
+    crate::core::validate_query_proof(
+        &root_hash,
+        &proofs,
+        &main_account_key,
+        &path[..1],
+        &uref_value,
+    )
+    .expect("should validate query ending at a key value");
+
+    let mut followed_proofs = proofs;
+    let account_proof = tracking_copy
+        .query(correlation_id, &EngineConfig::default(), account_key, &[])
+        .expect("should query");
+    if let TrackingCopyQueryResult::Success { mut proofs, .. } = account_proof {
+        followed_proofs.append(&mut proofs);
+    } else {
+        panic!("query was not successful: {:?}", account_proof)
+    }
+    crate::core::validate_query_proof(
+        &root_hash,
+        &followed_proofs,
+        &main_account_key,
+        &path[..1],
+        &account_value,
+    )
+    .expect("should validate query following a key value");
+
+    assert_eq!(
+        crate::core::validate_query_proof(
+            &root_hash,
+            &followed_proofs,
+            &main_account_key,
+            path,
+            &account_value
+        ),
+        Err(ValidationError::PathLengthDifferentThanProofLessOne)
+    );
+}
+
 #[test]
 fn get_keys_should_return_keys_in_the_account_keyspace() {
     // account 1
@@ -1143,3 +1274,47 @@ fn query_with_large_depth_with_urefs_should_fail() {
         result
     );
 }
+
+#[test]
+fn query_message_topic_and_messages() {
+    let correlation_id = CorrelationId::new();
+    let entity_addr = [1; 32];
+    let topic_name_hash = topic_name_hash("events");
+    let topic_key = Key::message_topic(entity_addr, topic_name_hash);
+    let summary = MessageTopicSummary::new(2, BlockTime::new(1_000));
+    let checksum = MessageChecksum::from_payload(b"second message");
+
+    let (gs, root_hash) = InMemoryGlobalState::from_pairs(
+        correlation_id,
+        &[
+            (topic_key, StoredValue::MessageTopic(summary)),
+            (
+                Key::message(entity_addr, topic_name_hash, 1),
+                StoredValue::Message(checksum),
+            ),
+        ],
+    )
+    .unwrap();
+    let view = gs.checkout(root_hash).unwrap().unwrap();
+    let tc = TrackingCopy::new(view);
+    let engine_config = EngineConfig::default();
+
+    let result = tc.query(correlation_id, &engine_config, topic_key, &[]);
+    assert_matches!(
+        result,
+        Ok(TrackingCopyQueryResult::Success { value: StoredValue::MessageTopic(value), .. })
+            if value == summary
+    );
+
+    let message_key = Key::message(entity_addr, topic_name_hash, 1);
+    let result = tc.query(correlation_id, &engine_config, message_key, &[]);
+    assert_matches!(
+        result,
+        Ok(TrackingCopyQueryResult::Success { value: StoredValue::Message(value), .. })
+            if value == checksum
+    );
+
+    let missing_key = Key::message(entity_addr, topic_name_hash, 2);
+    let result = tc.query(correlation_id, &engine_config, missing_key, &[]);
+    assert_matches!(result, Ok(TrackingCopyQueryResult::ValueNotFound(_)));
+}
@@ -0,0 +1,403 @@
+//! Profiling of gas usage, for tracking the costs of contracts across engine changes and for
+//! showing contract authors which host functions dominate the cost of their deploys.
+//!
+//! Profiling is enabled per thread via [`start`], and the profile of everything executed on that
+//! thread since is returned by [`finish`].  It is intended for tests and for speculative execution
+//! only: while profiling, Wasm modules are instrumented to report each executed opcode, which slows
+//! down execution considerably.
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+use parity_wasm::elements::Instruction;
+use pwasm_utils::rules::InstructionType;
+use serde::{Serialize, Serializer};
+
+use casper_types::Gas;
+
+use super::opcode_costs::OpcodeCosts;
+
+/// Set on arguments of the `gas` function which mark the execution of an opcode rather than
+/// charging gas.
+const OPCODE_MARKER: u32 = 0x8000_0000;
+
+thread_local! {
+    static PROFILER: RefCell<Option<Profiler>> = RefCell::new(None);
+}
+
+struct Profiler {
+    profile: GasProfile,
+    /// The host functions currently being called, innermost last.
+    host_function_calls: Vec<HostFunctionCall>,
+}
+
+/// A host function call in progress.
+struct HostFunctionCall {
+    /// When the call started.
+    started: Instant,
+    /// The gas charged by nested Wasm and host function calls so far.
+    nested_gas: Gas,
+    /// The wall-clock time spent in nested host function calls so far.
+    nested_time: Duration,
+}
+
+/// A group of Wasm opcodes sharing a cost in [`OpcodeCosts`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum OpcodeGroup {
+    /// Bit operations.
+    Bit,
+    /// Arithmetic add operations.
+    Add,
+    /// Mul operations.
+    Mul,
+    /// Div operations.
+    Div,
+    /// Memory load operations.
+    Load,
+    /// Memory store operations.
+    Store,
+    /// Const operations.
+    Const,
+    /// Local operations.
+    Local,
+    /// Global operations.
+    Global,
+    /// Control flow operations.
+    ControlFlow,
+    /// Integer comparison operations.
+    IntegerComparison,
+    /// Conversion operations.
+    Conversion,
+    /// Unreachable operations.
+    Unreachable,
+    /// Nop operations.
+    Nop,
+    /// Get current memory operations.
+    CurrentMemory,
+    /// Grow memory operations.
+    GrowMemory,
+    /// All other operations.
+    Regular,
+}
+
+impl OpcodeGroup {
+    /// Returns the group of the given instruction.
+    pub fn of(instruction: &Instruction) -> Self {
+        match InstructionType::op(instruction) {
+            InstructionType::Bit => OpcodeGroup::Bit,
+            InstructionType::Add => OpcodeGroup::Add,
+            InstructionType::Mul => OpcodeGroup::Mul,
+            InstructionType::Div => OpcodeGroup::Div,
+            InstructionType::Load => OpcodeGroup::Load,
+            InstructionType::Store => OpcodeGroup::Store,
+            InstructionType::Const => OpcodeGroup::Const,
+            InstructionType::Local => OpcodeGroup::Local,
+            InstructionType::Global => OpcodeGroup::Global,
+            InstructionType::ControlFlow => OpcodeGroup::ControlFlow,
+            InstructionType::IntegerComparison => OpcodeGroup::IntegerComparison,
+            InstructionType::Conversion => OpcodeGroup::Conversion,
+            InstructionType::Unreachable => OpcodeGroup::Unreachable,
+            InstructionType::Nop => OpcodeGroup::Nop,
+            InstructionType::CurrentMemory => OpcodeGroup::CurrentMemory,
+            InstructionType::GrowMemory => OpcodeGroup::GrowMemory,
+            _ => OpcodeGroup::Regular,
+        }
+    }
+
+    /// Returns the cost of a single opcode of this group.
+    pub fn cost(self, opcode_costs: &OpcodeCosts) -> u32 {
+        match self {
+            OpcodeGroup::Bit => opcode_costs.bit,
+            OpcodeGroup::Add => opcode_costs.add,
+            OpcodeGroup::Mul => opcode_costs.mul,
+            OpcodeGroup::Div => opcode_costs.div,
+            OpcodeGroup::Load => opcode_costs.load,
+            OpcodeGroup::Store => opcode_costs.store,
+            OpcodeGroup::Const => opcode_costs.op_const,
+            OpcodeGroup::Local => opcode_costs.local,
+            OpcodeGroup::Global => opcode_costs.global,
+            OpcodeGroup::ControlFlow => opcode_costs.control_flow,
+            OpcodeGroup::IntegerComparison => opcode_costs.integer_comparison,
+            OpcodeGroup::Conversion => opcode_costs.conversion,
+            OpcodeGroup::Unreachable => opcode_costs.unreachable,
+            OpcodeGroup::Nop => opcode_costs.nop,
+            OpcodeGroup::CurrentMemory => opcode_costs.current_memory,
+            OpcodeGroup::GrowMemory => opcode_costs.grow_memory,
+            OpcodeGroup::Regular => opcode_costs.regular,
+        }
+    }
+
+    /// Returns the argument of the `gas` function marking the execution of an opcode of this
+    /// group.
+    pub(crate) fn marker(self) -> u32 {
+        OPCODE_MARKER | self as u32
+    }
+
+    fn from_marker(marker: u32) -> Option<Self> {
+        if marker & OPCODE_MARKER == 0 {
+            return None;
+        }
+        let group = match marker & !OPCODE_MARKER {
+            0 => OpcodeGroup::Bit,
+            1 => OpcodeGroup::Add,
+            2 => OpcodeGroup::Mul,
+            3 => OpcodeGroup::Div,
+            4 => OpcodeGroup::Load,
+            5 => OpcodeGroup::Store,
+            6 => OpcodeGroup::Const,
+            7 => OpcodeGroup::Local,
+            8 => OpcodeGroup::Global,
+            9 => OpcodeGroup::ControlFlow,
+            10 => OpcodeGroup::IntegerComparison,
+            11 => OpcodeGroup::Conversion,
+            12 => OpcodeGroup::Unreachable,
+            13 => OpcodeGroup::Nop,
+            14 => OpcodeGroup::CurrentMemory,
+            15 => OpcodeGroup::GrowMemory,
+            16 => OpcodeGroup::Regular,
+            _ => return None,
+        };
+        Some(group)
+    }
+}
+
+/// The number of opcodes of a group executed, and their cost.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct OpcodeGroupUsage {
+    /// The number of opcodes executed.
+    pub count: u64,
+    /// The total cost of the opcodes executed.
+    #[serde(serialize_with = "serialize_gas")]
+    pub gas: Gas,
+}
+
+/// The number of calls to a host function, and the gas charged by and time spent in them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct HostFunctionUsage {
+    /// The number of calls.
+    pub calls: u64,
+    /// The gas charged by the calls, excluding that charged by any Wasm they execute.
+    #[serde(serialize_with = "serialize_gas")]
+    pub gas: Gas,
+    /// The wall-clock time spent in the calls, excluding that spent in nested host function calls
+    /// but including that spent executing Wasm, e.g. of a called contract.
+    #[serde(rename = "time_ns", serialize_with = "serialize_duration")]
+    pub time: Duration,
+}
+
+/// A breakdown of the gas used during execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GasProfile {
+    /// The gas charged by the metering injected into Wasm, for executing opcodes and growing
+    /// memory.
+    #[serde(serialize_with = "serialize_gas")]
+    pub wasm: Gas,
+    /// The opcodes executed by group.
+    ///
+    /// Metering charges for each block of opcodes on entering it, and for each page of memory
+    /// grown, so the total cost of the opcodes can differ from `wasm` if execution stops partway
+    /// through a block or memory is grown.
+    pub opcodes: BTreeMap<OpcodeGroup, OpcodeGroupUsage>,
+    /// The host functions called by name.
+    pub host_functions: BTreeMap<&'static str, HostFunctionUsage>,
+}
+
+impl GasProfile {
+    /// Returns the total gas charged by Wasm metering and host functions.
+    pub fn total(&self) -> Gas {
+        self.host_functions
+            .values()
+            .fold(self.wasm, |total, usage| total + usage.gas)
+    }
+}
+
+fn serialize_gas<S: Serializer>(gas: &Gas, serializer: S) -> Result<S::Ok, S::Error> {
+    gas.value().serialize(serializer)
+}
+
+fn serialize_duration<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    u64::try_from(duration.as_nanos())
+        .unwrap_or(u64::MAX)
+        .serialize(serializer)
+}
+
+/// Starts profiling execution on the current thread, discarding any profile in progress.
+pub fn start() {
+    PROFILER.with(|profiler| {
+        *profiler.borrow_mut() = Some(Profiler {
+            profile: GasProfile::default(),
+            host_function_calls: Vec::new(),
+        })
+    });
+}
+
+/// Stops profiling execution on the current thread, returning the profile if it was started.
+pub fn finish() -> Option<GasProfile> {
+    PROFILER.with(|profiler| {
+        profiler
+            .borrow_mut()
+            .take()
+            .map(|profiler| profiler.profile)
+    })
+}
+
+/// Runs `f`, returning the gas profile of its execution if `enabled` is set.
+///
+/// Any profile already in progress on the current thread is discarded.
+pub fn profiled<T>(enabled: bool, f: impl FnOnce() -> T) -> (T, Option<GasProfile>) {
+    if !enabled {
+        return (f(), None);
+    }
+    start();
+    let result = f();
+    (result, finish())
+}
+
+/// Returns whether execution on the current thread is being profiled.
+pub(crate) fn is_active() -> bool {
+    PROFILER.with(|profiler| profiler.borrow().is_some())
+}
+
+/// If execution is being profiled and `gas_arg` marks the execution of an opcode, records it and
+/// returns `true`.
+pub(crate) fn record_opcode(gas_arg: u32, opcode_costs: &OpcodeCosts) -> bool {
+    if gas_arg & OPCODE_MARKER == 0 {
+        return false;
+    }
+    PROFILER.with(|profiler| {
+        let mut profiler = profiler.borrow_mut();
+        let (profiler, group) = match (profiler.as_mut(), OpcodeGroup::from_marker(gas_arg)) {
+            (Some(profiler), Some(group)) => (profiler, group),
+            _ => return false,
+        };
+        let usage = profiler.profile.opcodes.entry(group).or_default();
+        usage.count += 1;
+        usage.gas += Gas::from(group.cost(opcode_costs));
+        true
+    })
+}
+
+/// Records gas charged by Wasm metering.
+pub(crate) fn record_wasm_gas(amount: Gas) {
+    PROFILER.with(|profiler| {
+        if let Some(profiler) = profiler.borrow_mut().as_mut() {
+            profiler.profile.wasm += amount;
+            if let Some(call) = profiler.host_function_calls.last_mut() {
+                call.nested_gas += amount;
+            }
+        }
+    });
+}
+
+/// Records the start of a host function call.
+pub(crate) fn enter_host_function() {
+    PROFILER.with(|profiler| {
+        if let Some(profiler) = profiler.borrow_mut().as_mut() {
+            profiler.host_function_calls.push(HostFunctionCall {
+                started: Instant::now(),
+                nested_gas: Gas::default(),
+                nested_time: Duration::default(),
+            });
+        }
+    });
+}
+
+/// Records the end of a host function call which charged `gas` in total, including any charged by
+/// Wasm it executed.
+pub(crate) fn exit_host_function(name: &'static str, gas: Gas) {
+    PROFILER.with(|profiler| {
+        if let Some(profiler) = profiler.borrow_mut().as_mut() {
+            let (nested_gas, elapsed, nested_time) = match profiler.host_function_calls.pop() {
+                Some(call) => (call.nested_gas, call.started.elapsed(), call.nested_time),
+                None => (Gas::default(), Duration::default(), Duration::default()),
+            };
+            let usage = profiler.profile.host_functions.entry(name).or_default();
+            usage.calls += 1;
+            usage.gas += gas.checked_sub(nested_gas).unwrap_or_default();
+            usage.time += elapsed.checked_sub(nested_time).unwrap_or_default();
+            if let Some(parent) = profiler.host_function_calls.last_mut() {
+                parent.nested_gas += gas;
+                parent.nested_time += elapsed;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_opcode_markers() {
+        for group in [
+            OpcodeGroup::Bit,
+            OpcodeGroup::Const,
+            OpcodeGroup::ControlFlow,
+            OpcodeGroup::Regular,
+        ] {
+            assert_eq!(OpcodeGroup::from_marker(group.marker()), Some(group));
+        }
+        assert_eq!(OpcodeGroup::from_marker(1_000), None);
+        assert_eq!(OpcodeGroup::from_marker(OPCODE_MARKER | 17), None);
+    }
+
+    #[test]
+    fn should_exclude_nested_gas_from_host_functions() {
+        start();
+        assert!(is_active());
+
+        enter_host_function();
+        record_wasm_gas(Gas::from(30u64));
+        enter_host_function();
+        exit_host_function("casper_write", Gas::from(5u64));
+        exit_host_function("casper_call_contract", Gas::from(100u64));
+        record_wasm_gas(Gas::from(7u64));
+        assert!(record_opcode(
+            OpcodeGroup::Const.marker(),
+            &OpcodeCosts::default()
+        ));
+        assert!(!record_opcode(42, &OpcodeCosts::default()));
+
+        let profile = finish().expect("should have profile");
+        assert!(!is_active());
+        assert_eq!(profile.wasm, Gas::from(37u64));
+        let call_contract = profile.host_functions["casper_call_contract"];
+        assert_eq!(call_contract.calls, 1);
+        assert_eq!(call_contract.gas, Gas::from(65u64));
+        let write = profile.host_functions["casper_write"];
+        assert_eq!(write.calls, 1);
+        assert_eq!(write.gas, Gas::from(5u64));
+        assert_eq!(profile.opcodes[&OpcodeGroup::Const].count, 1);
+        assert_eq!(profile.total(), Gas::from(107u64));
+    }
+
+    #[test]
+    fn should_exclude_nested_time_from_host_functions() {
+        let ((), profile) = profiled(true, || {
+            enter_host_function();
+            enter_host_function();
+            std::thread::sleep(Duration::from_millis(20));
+            exit_host_function("casper_read_value", Gas::default());
+            exit_host_function("casper_call_contract", Gas::default());
+        });
+        assert!(!is_active());
+
+        let profile = profile.expect("should have profile");
+        let read_value = profile.host_functions["casper_read_value"];
+        let call_contract = profile.host_functions["casper_call_contract"];
+        assert!(read_value.time >= Duration::from_millis(20));
+        assert!(call_contract.time < read_value.time);
+
+        let (active, profile) = profiled(false, is_active);
+        assert!(!active);
+        assert!(profile.is_none());
+    }
+}
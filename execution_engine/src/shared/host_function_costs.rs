@@ -26,6 +26,7 @@ const DEFAULT_CALL_CONTRACT_COST: u32 = 4_500;
 const DEFAULT_CALL_CONTRACT_ARGS_SIZE_WEIGHT: u32 = 420;
 
 const DEFAULT_CREATE_PURSE_COST: u32 = 2_500_000_000;
+const DEFAULT_EMIT_MESSAGE_COST: u32 = 10_000;
 const DEFAULT_GET_BALANCE_COST: u32 = 3_800;
 const DEFAULT_GET_BLOCKTIME_COST: u32 = 330;
 const DEFAULT_GET_CALLER_COST: u32 = 380;
@@ -286,6 +287,8 @@ pub struct HostFunctionCosts {
     pub blake2b: HostFunction<[Cost; 4]>,
     /// Cost of calling the `next address` host function.
     pub random_bytes: HostFunction<[Cost; 2]>,
+    /// Cost of calling the `emit_message` host function.
+    pub emit_message: HostFunction<[Cost; 4]>,
 }
 
 impl Default for HostFunctionCosts {
@@ -404,6 +407,7 @@ impl Default for HostFunctionCosts {
             ),
             blake2b: HostFunction::default(),
             random_bytes: HostFunction::default(),
+            emit_message: HostFunction::fixed(DEFAULT_EMIT_MESSAGE_COST),
         }
     }
 }
@@ -454,6 +458,7 @@ impl ToBytes for HostFunctionCosts {
         ret.append(&mut self.print.to_bytes()?);
         ret.append(&mut self.blake2b.to_bytes()?);
         ret.append(&mut self.random_bytes.to_bytes()?);
+        ret.append(&mut self.emit_message.to_bytes()?);
         Ok(ret)
     }
 
@@ -501,6 +506,7 @@ impl ToBytes for HostFunctionCosts {
             + self.print.serialized_length()
             + self.blake2b.serialized_length()
             + self.random_bytes.serialized_length()
+            + self.emit_message.serialized_length()
     }
 }
 
@@ -549,6 +555,7 @@ impl FromBytes for HostFunctionCosts {
         let (print, rem) = FromBytes::from_bytes(rem)?;
         let (blake2b, rem) = FromBytes::from_bytes(rem)?;
         let (random_bytes, rem) = FromBytes::from_bytes(rem)?;
+        let (emit_message, rem) = FromBytes::from_bytes(rem)?;
         Ok((
             HostFunctionCosts {
                 read_value,
@@ -594,6 +601,7 @@ impl FromBytes for HostFunctionCosts {
                 print,
                 blake2b,
                 random_bytes,
+                emit_message,
             },
             rem,
         ))
@@ -646,6 +654,7 @@ impl Distribution<HostFunctionCosts> for Standard {
             print: rng.gen(),
             blake2b: rng.gen(),
             random_bytes: rng.gen(),
+            emit_message: rng.gen(),
         }
     }
 }
@@ -706,6 +715,7 @@ pub mod gens {
             print in host_function_cost_arb(),
             blake2b in host_function_cost_arb(),
             random_bytes in host_function_cost_arb(),
+            emit_message in host_function_cost_arb(),
         ) -> HostFunctionCosts {
             HostFunctionCosts {
                 read_value,
@@ -751,6 +761,7 @@ pub mod gens {
                 print,
                 blake2b,
                 random_bytes,
+                emit_message,
             }
         }
     }
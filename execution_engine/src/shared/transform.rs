@@ -12,6 +12,7 @@ use num::traits::{AsPrimitive, WrappingAdd};
 
 use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
+    contract_messages::Message,
     contracts::NamedKeys,
     CLType, CLTyped, CLValue, CLValueError, StoredValue, StoredValueTypeMismatch, U128, U256, U512,
 };
@@ -67,6 +68,10 @@ pub enum Transform {
     Identity,
     /// Writes a new value in the global state.
     Write(StoredValue),
+    /// Writes the checksum of a contract message in the global state.
+    ///
+    /// The message's payload is carried only so that it can be included in execution results.
+    WriteMessage(Message),
     /// A wrapping addition of an `i32` to an existing numeric value (not necessarily an `i32`) in
     /// the global state.
     AddInt32(i32),
@@ -172,6 +177,7 @@ impl Transform {
         match self {
             Transform::Identity => Ok(stored_value),
             Transform::Write(new_value) => Ok(new_value),
+            Transform::WriteMessage(message) => Ok(StoredValue::Message(message.checksum())),
             Transform::AddInt32(to_add) => wrapping_addition(stored_value, to_add),
             Transform::AddUInt64(to_add) => wrapping_addition(stored_value, to_add),
             Transform::AddUInt128(to_add) => wrapping_addition(stored_value, to_add),
@@ -231,6 +237,21 @@ impl Transform {
                     let found = "Unbonding".to_string();
                     Err(StoredValueTypeMismatch::new(expected, found).into())
                 }
+                StoredValue::MessageTopic(_) => {
+                    let expected = "Contract or Account".to_string();
+                    let found = "MessageTopic".to_string();
+                    Err(StoredValueTypeMismatch::new(expected, found).into())
+                }
+                StoredValue::Message(_) => {
+                    let expected = "Contract or Account".to_string();
+                    let found = "Message".to_string();
+                    Err(StoredValueTypeMismatch::new(expected, found).into())
+                }
+                StoredValue::TransferV2(_) => {
+                    let expected = "Contract or Account".to_string();
+                    let found = "TransferV2".to_string();
+                    Err(StoredValueTypeMismatch::new(expected, found).into())
+                }
             },
             Transform::Failure(error) => Err(error),
         }
@@ -274,7 +295,13 @@ impl Add for Transform {
             (Transform::Identity, b) => b,
             (a @ Transform::Failure(_), _) => a,
             (_, b @ Transform::Failure(_)) => b,
-            (_, b @ Transform::Write(_)) => b,
+            (_, b @ Transform::Write(_)) | (_, b @ Transform::WriteMessage(_)) => b,
+            (Transform::WriteMessage(message), b) => {
+                match b.apply(StoredValue::Message(message.checksum())) {
+                    Err(error) => Transform::Failure(error),
+                    Ok(new_value) => Transform::Write(new_value),
+                }
+            }
             (Transform::Write(v), b) => {
                 // second transform changes value being written
                 match b.apply(v) {
@@ -359,6 +386,9 @@ impl From<&Transform> for casper_types::Transform {
             Transform::Write(StoredValue::Transfer(transfer)) => {
                 casper_types::Transform::WriteTransfer(*transfer)
             }
+            Transform::Write(StoredValue::TransferV2(transfer)) => {
+                casper_types::Transform::WriteTransferV2(transfer.clone())
+            }
             Transform::Write(StoredValue::DeployInfo(deploy_info)) => {
                 casper_types::Transform::WriteDeployInfo(deploy_info.clone())
             }
@@ -374,6 +404,15 @@ impl From<&Transform> for casper_types::Transform {
             Transform::Write(StoredValue::Withdraw(_)) => casper_types::Transform::Failure(
                 "withdraw purses should not be be written to global state".to_string(),
             ),
+            Transform::Write(StoredValue::MessageTopic(summary)) => {
+                casper_types::Transform::WriteMessageTopic(*summary)
+            }
+            Transform::Write(StoredValue::Message(_)) => casper_types::Transform::Failure(
+                "messages should be written to global state with their payload".to_string(),
+            ),
+            Transform::WriteMessage(message) => {
+                casper_types::Transform::WriteMessage(message.clone())
+            }
             Transform::AddInt32(value) => casper_types::Transform::AddInt32(*value),
             Transform::AddUInt64(value) => casper_types::Transform::AddUInt64(*value),
             Transform::AddUInt128(value) => casper_types::Transform::AddUInt128(*value),
@@ -868,4 +907,26 @@ mod tests {
         assert_eq!(ZERO_U512, add(MAX_U512, ONE_U512));
         assert_eq!(MAX_U512 - 1, add(MAX_U512, MAX_U512));
     }
+
+    #[test]
+    fn write_message_should_store_checksum_and_keep_payload() {
+        let message = Message::new(Bytes::from(b"payload".as_ref()));
+        let checksum = message.checksum();
+        let transform = Transform::WriteMessage(message.clone());
+
+        assert_eq!(
+            transform
+                .clone()
+                .apply(StoredValue::Message(Default::default())),
+            Ok(StoredValue::Message(checksum))
+        );
+        assert_eq!(
+            Transform::Identity + transform.clone(),
+            Transform::WriteMessage(message.clone())
+        );
+        assert_eq!(
+            casper_types::Transform::from(&transform),
+            casper_types::Transform::WriteMessage(message)
+        );
+    }
 }
@@ -5,7 +5,7 @@ use parity_wasm::elements::{
 use pwasm_utils::{self, stack_height};
 use thiserror::Error;
 
-use super::wasm_config::WasmConfig;
+use super::{gas_profile::OpcodeGroup, wasm_config::WasmConfig};
 
 const DEFAULT_GAS_MODULE_NAME: &str = "env";
 /// Name of the internal gas function injected by [`pwasm_utils::inject_gas_counter`].
@@ -402,6 +402,162 @@ pub fn preprocess(
     Ok(module)
 }
 
+/// Instruments a preprocessed module to report each executed opcode to the
+/// [`gas_profile`](super::gas_profile) via its `gas` function.
+///
+/// Instructions injected by the preprocessor for metering and stack height limiting are not
+/// reported, and calls to the function injected to meter `grow_memory` are reported as
+/// `grow_memory` opcodes.  The module is returned unchanged if it doesn't import the `gas` function.
+pub(crate) fn inject_opcode_profiling(mut module: Module) -> Module {
+    let mut function_imports = 0;
+    let mut gas_function = None;
+    let mut global_imports = 0;
+    for entry in module
+        .import_section()
+        .map(|section| section.entries())
+        .unwrap_or_default()
+    {
+        match entry.external() {
+            External::Function(_) => {
+                if entry.module() == DEFAULT_GAS_MODULE_NAME
+                    && entry.field() == INTERNAL_GAS_FUNCTION_NAME
+                {
+                    gas_function = Some(function_imports);
+                }
+                function_imports += 1;
+            }
+            External::Global(_) => global_imports += 1,
+            _ => (),
+        }
+    }
+    let gas_function = match gas_function {
+        Some(gas_function) => gas_function,
+        None => return module,
+    };
+    // The stack height limiter declares its global last.
+    let stack_height_global = module
+        .global_section()
+        .and_then(|section| section.entries().len().checked_sub(1))
+        .map(|index| (global_imports + index) as u32);
+
+    if let Some(code_section) = module.code_section_mut() {
+        let grow_memory_function = code_section
+            .bodies()
+            .iter()
+            .position(|body| is_grow_memory_counter(body.code().elements(), gas_function))
+            .map(|index| function_imports + index as u32);
+
+        for body in code_section.bodies_mut() {
+            if is_grow_memory_counter(body.code().elements(), gas_function)
+                || is_stack_height_thunk(body.code().elements(), gas_function, stack_height_global)
+            {
+                continue;
+            }
+            let instructions = body.code_mut().elements_mut();
+            let original = std::mem::take(instructions);
+            let mut index = 0;
+            while index < original.len() {
+                let uninstrumented =
+                    injected_instructions(&original[index..], gas_function, stack_height_global);
+                if uninstrumented > 0 {
+                    instructions.extend_from_slice(&original[index..index + uninstrumented]);
+                    index += uninstrumented;
+                    continue;
+                }
+                let instruction = &original[index];
+                let group = match instruction {
+                    Instruction::End | Instruction::Else => None,
+                    Instruction::Call(function) if Some(*function) == grow_memory_function => {
+                        Some(OpcodeGroup::GrowMemory)
+                    }
+                    _ => Some(OpcodeGroup::of(instruction)),
+                };
+                if let Some(group) = group {
+                    instructions.push(Instruction::I32Const(group.marker() as i32));
+                    instructions.push(Instruction::Call(gas_function));
+                }
+                instructions.push(instruction.clone());
+                index += 1;
+            }
+        }
+    }
+    module
+}
+
+/// Returns whether `instructions` are the body of the function injected by the preprocessor to
+/// meter `grow_memory`, which charges for the requested pages before growing the memory.
+fn is_grow_memory_counter(instructions: &[Instruction], gas_function: u32) -> bool {
+    use Instruction::*;
+
+    matches!(
+        instructions,
+        [GetLocal(0), GetLocal(0), I32Const(_), I32Mul, Call(function), GrowMemory(0), End]
+            if *function == gas_function
+    )
+}
+
+/// Returns whether `instructions` are the body of a thunk injected by the stack height limiter,
+/// which forwards its arguments to the function it wraps.
+fn is_stack_height_thunk(
+    instructions: &[Instruction],
+    gas_function: u32,
+    stack_height_global: Option<u32>,
+) -> bool {
+    let arguments = instructions
+        .iter()
+        .enumerate()
+        .take_while(|(index, instruction)| **instruction == Instruction::GetLocal(*index as u32))
+        .count();
+    let rest = &instructions[arguments..];
+    let preamble = injected_instructions(rest, gas_function, stack_height_global);
+    if preamble != 10 {
+        return false;
+    }
+    match &rest[preamble..] {
+        [Instruction::Call(_), postamble @ ..] => {
+            injected_instructions(postamble, gas_function, stack_height_global) == 4
+                && postamble[4..] == [Instruction::End]
+        }
+        _ => false,
+    }
+}
+
+/// Returns the number of instructions at the start of `instructions` which were injected by the
+/// preprocessor, or zero if they start with an instruction from the original module.
+fn injected_instructions(
+    instructions: &[Instruction],
+    gas_function: u32,
+    stack_height_global: Option<u32>,
+) -> usize {
+    use Instruction::*;
+
+    match instructions {
+        // Metering.
+        [I32Const(_), Call(function), ..] if *function == gas_function => 2,
+        [Call(function), ..] if *function == gas_function => 1,
+        // Stack height limiting before a call.
+        [GetGlobal(get), I32Const(_), I32Add, SetGlobal(set), rest @ ..]
+            if Some(*get) == stack_height_global && get == set =>
+        {
+            match rest {
+                [GetGlobal(check), I32Const(_), I32GtU, If(_), Unreachable, End, ..]
+                    if check == get =>
+                {
+                    10
+                }
+                _ => 0,
+            }
+        }
+        // Stack height limiting after a call.
+        [GetGlobal(get), I32Const(_), I32Sub, SetGlobal(set), ..]
+            if Some(*get) == stack_height_global && get == set =>
+        {
+            4
+        }
+        _ => 0,
+    }
+}
+
 /// Returns a parity Module from the given bytes without making modifications or checking limits.
 pub fn deserialize(module_bytes: &[u8]) -> Result<Module, PreprocessingError> {
     parity_wasm::deserialize_buffer::<Module>(module_bytes).map_err(Into::into)
@@ -586,4 +742,73 @@ mod tests {
             error,
         );
     }
+
+    #[test]
+    fn should_inject_opcode_profiling() {
+        let module = builder::module()
+            .function()
+            .signature()
+            .build()
+            .body()
+            .with_instructions(Instructions::new(vec![
+                Instruction::I32Const(1),
+                Instruction::I32Const(2),
+                Instruction::I32Add,
+                Instruction::Drop,
+                Instruction::Nop,
+                Instruction::End,
+            ]))
+            .build()
+            .build()
+            .export()
+            .field(DEFAULT_ENTRY_POINT_NAME)
+            .internal()
+            .func(0)
+            .build()
+            // Memory section is mandatory
+            .memory()
+            .build()
+            .build();
+        let module_bytes = parity_wasm::serialize(module).expect("should serialize");
+        let module = preprocess(WasmConfig::default(), &module_bytes).expect("should preprocess");
+
+        let gas_calls = |module: &Module| -> (Vec<OpcodeGroup>, usize) {
+            let mut markers = Vec::new();
+            let mut metering_calls = 0;
+            for body in module.code_section().expect("should have code").bodies() {
+                for pair in body.code().elements().windows(2) {
+                    if let [Instruction::I32Const(value), Instruction::Call(_)] = pair {
+                        let value = *value as u32;
+                        match [
+                            OpcodeGroup::Const,
+                            OpcodeGroup::Add,
+                            OpcodeGroup::Nop,
+                            OpcodeGroup::ControlFlow,
+                            OpcodeGroup::Regular,
+                        ]
+                        .iter()
+                        .find(|group| group.marker() == value)
+                        {
+                            Some(group) => markers.push(*group),
+                            None if value & 0x8000_0000 == 0 => metering_calls += 1,
+                            None => (),
+                        }
+                    }
+                }
+            }
+            (markers, metering_calls)
+        };
+
+        let (markers, metering_calls) = gas_calls(&module);
+        assert!(markers.is_empty());
+        assert!(metering_calls > 0);
+
+        let profiled_module = inject_opcode_profiling(module);
+        let (markers, profiled_metering_calls) = gas_calls(&profiled_module);
+        assert_eq!(profiled_metering_calls, metering_calls);
+        let count = |group| markers.iter().filter(|marker| **marker == group).count();
+        assert_eq!(count(OpcodeGroup::Const), 2);
+        assert_eq!(count(OpcodeGroup::Add), 1);
+        assert_eq!(count(OpcodeGroup::Nop), 1);
+    }
 }
@@ -1,12 +1,17 @@
 use std::{
     collections::{HashMap, HashSet},
+    convert::TryFrom,
     ops::Deref,
     sync::{Arc, RwLock},
 };
 
 use casper_hashing::{ChunkWithProof, Digest};
-use casper_types::{bytesrepr::Bytes, Key, StoredValue};
-use tracing::trace;
+use casper_types::{
+    bytesrepr::{self, Bytes},
+    Key, StoredValue,
+};
+use lmdb::Cursor;
+use tracing::{debug, trace, warn};
 
 use crate::{
     shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
@@ -44,6 +49,15 @@ pub struct LmdbGlobalState {
     digests_without_missing_descendants: RwLock<HashSet<Digest>>,
 }
 
+/// The outcome of pruning tries unreachable from a set of retained state roots.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PruneTriesResult {
+    /// The number of tries deleted.
+    pub pruned_tries: u64,
+    /// The total size of the deleted tries in bytes.
+    pub reclaimed_bytes: u64,
+}
+
 /// Represents a "view" of global state at a particular root hash.
 pub struct LmdbGlobalStateView {
     /// Environment for LMDB.
@@ -130,6 +144,205 @@ impl LmdbGlobalState {
     pub fn trie_store(&self) -> &LmdbTrieStore {
         &self.trie_store
     }
+
+    /// Deletes all tries which are not reachable from any of `retained_roots`.
+    ///
+    /// Reachable tries are marked within a single read transaction, and only tries present at
+    /// that point are candidates for deletion.  The candidates are then deleted in batches of
+    /// `batch_size`, each in its own write transaction, so that writers are not blocked for long.
+    ///
+    /// Since tries are content-addressed, a trie written after marking, e.g. by block execution or
+    /// trie sync, may be identical to a candidate, and is then referred to by a state root which
+    /// wasn't marked.  Deleting it would corrupt that state, so no trie may be written while a
+    /// batch is deleted: every batch first calls `pause_writers`, which has to wait for any
+    /// writers to finish and keep new ones from starting for as long as the returned guard is
+    /// held.  With writers paused, the batch then retains the tries reachable from the roots
+    /// returned by `current_roots`, which have to include every state root written since marking.
+    ///
+    /// Retained roots missing from the store are ignored.  If none of them is present, nothing is
+    /// deleted.
+    pub fn prune_unreachable_tries<P, G, F>(
+        &self,
+        retained_roots: Vec<Digest>,
+        pause_writers: P,
+        current_roots: F,
+        batch_size: usize,
+    ) -> Result<PruneTriesResult, error::Error>
+    where
+        P: Fn() -> G,
+        F: Fn() -> Vec<Digest>,
+    {
+        let db = self.trie_store.get_db();
+        let (mut reachable, candidates) = {
+            let txn = self.environment.create_read_txn()?;
+
+            let mut present_roots = Vec::with_capacity(retained_roots.len() + 1);
+            for root in retained_roots {
+                match lmdb::Transaction::get(&txn, db, &root) {
+                    Ok(_) => present_roots.push(root),
+                    Err(lmdb::Error::NotFound) => (),
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            if present_roots.is_empty() {
+                warn!("none of the retained state roots is present, not pruning tries");
+                return Ok(PruneTriesResult::default());
+            }
+            present_roots.push(self.empty_root_hash);
+
+            let reachable = descendant_trie_keys::<
+                Key,
+                StoredValue,
+                lmdb::RoTransaction,
+                LmdbTrieStore,
+                error::Error,
+            >(
+                &txn,
+                self.trie_store.deref(),
+                present_roots,
+                &HashSet::new(),
+            )?;
+
+            let mut candidates = Vec::new();
+            let mut cursor = lmdb::Transaction::open_ro_cursor(&txn, db)?;
+            for (raw_key, raw_value) in cursor.iter() {
+                match Digest::try_from(raw_key) {
+                    Ok(trie_key) if !reachable.contains(&trie_key) => {
+                        candidates.push((trie_key, raw_value.len() as u64));
+                    }
+                    Ok(_) => (),
+                    Err(_) => warn!(?raw_key, "skipping malformed trie key"),
+                }
+            }
+            drop(cursor);
+            (reachable, candidates)
+        };
+
+        debug!(
+            reachable = reachable.len(),
+            unreachable = candidates.len(),
+            "marked tries for pruning"
+        );
+
+        let mut result = PruneTriesResult::default();
+        for batch in candidates.chunks(batch_size.max(1)) {
+            let _paused_writers = pause_writers();
+            let mut txn = self.environment.create_read_write_txn()?;
+            let newly_reachable =
+                descendant_trie_keys::<
+                    Key,
+                    StoredValue,
+                    lmdb::RwTransaction,
+                    LmdbTrieStore,
+                    error::Error,
+                >(&txn, self.trie_store.deref(), current_roots(), &reachable)?;
+            reachable.extend(newly_reachable);
+
+            for (trie_key, size) in batch {
+                if reachable.contains(trie_key) {
+                    continue;
+                }
+                match txn.del(db, trie_key, None) {
+                    Ok(()) => {
+                        result.pruned_tries += 1;
+                        result.reclaimed_bytes += size;
+                    }
+                    Err(lmdb::Error::NotFound) => (),
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            txn.commit()?;
+        }
+
+        // Pruned tries must not be reported as complete anymore.
+        self.digests_without_missing_descendants
+            .write()
+            .expect("digest cache write lock")
+            .retain(|digest| reachable.contains(digest));
+
+        Ok(result)
+    }
+
+    /// Calls `visit` with the serialized form of every trie reachable from `state_root_hash`,
+    /// returning the number of tries visited.
+    ///
+    /// All tries are read within a single read transaction, so they form a consistent snapshot of
+    /// the global state even while new state is being committed.  Each trie is visited once, parents
+    /// before their descendants.  Tries missing from the store are skipped, so completeness should
+    /// be checked beforehand using `missing_trie_keys`.
+    pub fn visit_tries<F, E>(&self, state_root_hash: Digest, mut visit: F) -> Result<u64, E>
+    where
+        F: FnMut(&[u8]) -> Result<(), E>,
+        E: From<error::Error>,
+    {
+        let txn = self
+            .environment
+            .create_read_txn()
+            .map_err(error::Error::from)?;
+        let mut visited = HashSet::new();
+        let mut trie_keys_to_visit = vec![state_root_hash];
+        while let Some(trie_key) = trie_keys_to_visit.pop() {
+            if !visited.insert(trie_key) {
+                continue;
+            }
+            let trie_bytes = match Store::<Digest, Trie<Key, StoredValue>>::get_raw(
+                &*self.trie_store,
+                &txn,
+                &trie_key,
+            )? {
+                Some(trie_bytes) => trie_bytes,
+                None => continue,
+            };
+            visit(&trie_bytes)?;
+
+            // Leaves have no descendants, so there's no need to deserialize them.
+            if trie_bytes.first() == Some(&Trie::<Key, StoredValue>::LEAF_TAG) {
+                continue;
+            }
+            let trie: Trie<Key, StoredValue> =
+                bytesrepr::deserialize_from_slice(&*trie_bytes).map_err(error::Error::from)?;
+            match trie {
+                Trie::Leaf { .. } => (),
+                Trie::Node { pointer_block } => trie_keys_to_visit.extend(
+                    pointer_block
+                        .as_indexed_pointers()
+                        .map(|(_, pointer)| pointer.into_hash()),
+                ),
+                Trie::Extension { pointer, .. } => trie_keys_to_visit.push(pointer.into_hash()),
+            }
+        }
+        txn.commit().map_err(error::Error::from)?;
+        Ok(visited.len() as u64)
+    }
+
+    /// Writes the given serialized tries within a single write transaction, returning their hashes
+    /// in the same order.
+    ///
+    /// As with `put_trie`, each trie is stored under the hash of its serialized form, but writing
+    /// many tries at once avoids the cost of a transaction per trie, e.g. when importing a snapshot.
+    pub fn put_tries<T>(
+        &self,
+        correlation_id: CorrelationId,
+        tries: &[T],
+    ) -> Result<Vec<Digest>, error::Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let mut txn = self.environment.create_read_write_txn()?;
+        let trie_hashes = tries
+            .iter()
+            .map(|trie| {
+                put_trie::<Key, StoredValue, lmdb::RwTransaction, LmdbTrieStore, error::Error>(
+                    correlation_id,
+                    &mut txn,
+                    &self.trie_store,
+                    trie.as_ref(),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        txn.commit()?;
+        Ok(trie_hashes)
+    }
 }
 
 impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
@@ -570,6 +783,102 @@ mod tests {
         );
     }
 
+    #[test]
+    fn prunes_tries_unreachable_from_retained_roots() {
+        let correlation_id = CorrelationId::new();
+        let test_pairs_updated = create_test_pairs_updated();
+
+        let (state, root_hash) = create_test_state(create_test_pairs);
+
+        let effects: AdditiveMap<Key, Transform> = {
+            let mut tmp = AdditiveMap::new();
+            for TestPair { key, value } in &test_pairs_updated {
+                tmp.insert(*key, Transform::Write(value.to_owned()));
+            }
+            tmp
+        };
+        let updated_hash = state.commit(correlation_id, root_hash, effects).unwrap();
+
+        let result = state
+            .prune_unreachable_tries(vec![updated_hash], || (), Vec::new, 1)
+            .unwrap();
+        assert!(result.pruned_tries > 0);
+        assert!(result.reclaimed_bytes > 0);
+
+        assert!(state.checkout(root_hash).unwrap().is_none());
+        let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
+        for TestPair { key, value } in test_pairs_updated.iter().cloned() {
+            assert_eq!(
+                Some(value),
+                updated_checkout.read(correlation_id, &key).unwrap()
+            );
+        }
+        assert!(state
+            .missing_trie_keys(correlation_id, vec![updated_hash])
+            .unwrap()
+            .is_empty());
+
+        // Pruning again finds nothing left to delete.
+        let result = state
+            .prune_unreachable_tries(vec![updated_hash], || (), Vec::new, 1)
+            .unwrap();
+        assert_eq!(result, PruneTriesResult::default());
+    }
+
+    #[test]
+    fn does_not_prune_without_any_retained_root_present() {
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let fake_hash: Digest = Digest::hash(&[1u8; 32]);
+
+        let result = state
+            .prune_unreachable_tries(vec![fake_hash], || (), Vec::new, 10)
+            .unwrap();
+        assert_eq!(result, PruneTriesResult::default());
+        assert!(state.checkout(root_hash).unwrap().is_some());
+    }
+
+    #[test]
+    fn copies_all_tries_under_state_root() {
+        let correlation_id = CorrelationId::new();
+        let (source, root_hash) = create_test_state(create_test_pairs);
+
+        let mut tries = Vec::new();
+        let visited = source
+            .visit_tries::<_, error::Error>(root_hash, |trie_bytes| {
+                tries.push(trie_bytes.to_vec());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(visited, tries.len() as u64);
+
+        let temp_dir = tempdir().unwrap();
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                &temp_dir.path(),
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS,
+                true,
+            )
+            .unwrap(),
+        );
+        let trie_store =
+            Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+        let target = LmdbGlobalState::empty(environment, trie_store).unwrap();
+        assert!(target.checkout(root_hash).unwrap().is_none());
+
+        let trie_hashes = target.put_tries(correlation_id, &tries).unwrap();
+        assert_eq!(trie_hashes[0], root_hash);
+        assert!(target
+            .missing_trie_keys(correlation_id, vec![root_hash])
+            .unwrap()
+            .is_empty());
+
+        let checkout = target.checkout(root_hash).unwrap().unwrap();
+        for TestPair { key, value } in create_test_pairs().iter().cloned() {
+            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+        }
+    }
+
     #[test]
     fn returns_trie_or_chunk() {
         let correlation_id = CorrelationId::new();
@@ -1,14 +1,17 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     mem,
     ops::Deref,
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
 };
 
 use tracing::error;
 
 use casper_hashing::{ChunkWithProof, Digest};
-use casper_types::{bytesrepr::Bytes, Key, StoredValue};
+use casper_types::{
+    bytesrepr::{Bytes, ToBytes},
+    Key, StoredValue,
+};
 
 use crate::{
     shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
@@ -17,7 +20,10 @@ use crate::{
         global_state::{CommitError, CommitProvider, StateProvider, StateReader},
         store::Store,
         transaction_source::{lmdb::LmdbEnvironment, Transaction, TransactionSource},
-        trie::{merkle_proof::TrieMerkleProof, Trie, TrieOrChunk, TrieOrChunkId},
+        trie::{
+            merkle_proof::TrieMerkleProof, operations::create_hashed_empty_trie, Trie, TrieOrChunk,
+            TrieOrChunkId,
+        },
         trie_store::{
             lmdb::LmdbTrieStore,
             operations::{
@@ -62,10 +68,64 @@ impl Cache {
     }
 }
 
+/// The keys, along with the values first read under them, and the key prefixes read through the
+/// views of a [`ScratchGlobalState`].
+#[derive(Debug, Default)]
+pub struct ReadSet {
+    values: HashMap<Key, Option<StoredValue>>,
+    prefixes: Vec<Vec<u8>>,
+}
+
+impl ReadSet {
+    /// Returns `true` if any of the given keys matches a prefix which was listed, or was read and
+    /// holds a different value in `current` than the one read.
+    pub fn conflicts_with<R>(
+        &self,
+        correlation_id: CorrelationId,
+        written: &HashSet<Key>,
+        current: &R,
+    ) -> Result<bool, R::Error>
+    where
+        R: StateReader<Key, StoredValue>,
+    {
+        for key in written {
+            if let Some(value_read) = self.values.get(key) {
+                if current.read(correlation_id, key)? != *value_read {
+                    return Ok(true);
+                }
+            }
+        }
+        if self.prefixes.is_empty() {
+            return Ok(false);
+        }
+        Ok(written.iter().any(|key| {
+            // A key which cannot be serialized cannot have been listed either.
+            key.to_bytes().map_or(false, |bytes| {
+                self.prefixes.iter().any(|prefix| bytes.starts_with(prefix))
+            })
+        }))
+    }
+}
+
+type SharedReadSet = Arc<Mutex<ReadSet>>;
+
+fn record_read(read_set: &Option<SharedReadSet>, key: &Key, value: Option<&StoredValue>) {
+    if let Some(read_set) = read_set {
+        read_set
+            .lock()
+            .unwrap()
+            .values
+            .entry(key.normalize())
+            .or_insert_with(|| value.cloned());
+    }
+}
+
 /// Global state implemented against LMDB as a backing data store.
 pub struct ScratchGlobalState {
     /// Underlying, cached stored values.
     cache: SharedCache,
+    /// The keys read through the views of this state, if they are being recorded.
+    read_set: Option<SharedReadSet>,
     /// Environment for LMDB.
     pub(crate) environment: Arc<LmdbEnvironment>,
     /// Trie store held within LMDB.
@@ -78,6 +138,7 @@ pub struct ScratchGlobalState {
 /// Represents a "view" of global state at a particular root hash.
 pub struct ScratchGlobalStateView {
     cache: SharedCache,
+    read_set: Option<SharedReadSet>,
     /// Environment for LMDB.
     pub(crate) environment: Arc<LmdbEnvironment>,
     /// Trie store held within LMDB.
@@ -96,12 +157,51 @@ impl ScratchGlobalState {
     ) -> Self {
         ScratchGlobalState {
             cache: Arc::new(RwLock::new(Cache::new())),
+            read_set: None,
             environment,
             trie_store,
             empty_root_hash,
         }
     }
 
+    /// Creates a state over an existing environment and store, neither of which is written to.
+    ///
+    /// The environment may be read-only, in which case all changes are held in the cache.
+    pub fn open(
+        environment: Arc<LmdbEnvironment>,
+        trie_store: Arc<LmdbTrieStore>,
+    ) -> Result<Self, error::Error> {
+        let (empty_root_hash, _empty_root) = create_hashed_empty_trie::<Key, StoredValue>()?;
+        Ok(ScratchGlobalState::new(
+            environment,
+            trie_store,
+            empty_root_hash,
+        ))
+    }
+
+    /// Returns a state sharing this state's cache which records the keys read through its views.
+    ///
+    /// The returned state is meant for executing against this state's current contents and
+    /// finding out what the execution depended on; its effects should be committed to `self`.
+    pub fn recording_reads(&self) -> Self {
+        ScratchGlobalState {
+            cache: Arc::clone(&self.cache),
+            read_set: Some(Arc::default()),
+            environment: Arc::clone(&self.environment),
+            trie_store: Arc::clone(&self.trie_store),
+            empty_root_hash: self.empty_root_hash,
+        }
+    }
+
+    /// Returns the reads made so far and starts recording afresh, or an empty set if this state
+    /// does not record its reads.
+    pub fn take_read_set(&self) -> ReadSet {
+        self.read_set
+            .as_ref()
+            .map(|read_set| mem::take(&mut *read_set.lock().unwrap()))
+            .unwrap_or_default()
+    }
+
     /// Consume self and return inner cache.
     pub fn into_inner(self) -> HashMap<Key, StoredValue> {
         let cache = mem::replace(&mut *self.cache.write().unwrap(), Cache::new());
@@ -117,8 +217,10 @@ impl StateReader<Key, StoredValue> for ScratchGlobalStateView {
         correlation_id: CorrelationId,
         key: &Key,
     ) -> Result<Option<StoredValue>, Self::Error> {
-        if let Some(value) = self.cache.read().unwrap().get(key) {
-            return Ok(Some(value.clone()));
+        let cached = self.cache.read().unwrap().get(key).cloned();
+        if let Some(value) = cached {
+            record_read(&self.read_set, key, Some(&value));
+            return Ok(Some(value));
         }
         let txn = self.environment.create_read_txn()?;
         let ret = match read::<Key, StoredValue, lmdb::RoTransaction, LmdbTrieStore, Self::Error>(
@@ -136,6 +238,7 @@ impl StateReader<Key, StoredValue> for ScratchGlobalStateView {
             ReadResult::RootNotFound => panic!("ScratchGlobalState has invalid root"),
         };
         txn.commit()?;
+        record_read(&self.read_set, key, ret.as_ref());
         Ok(ret)
     }
 
@@ -163,6 +266,11 @@ impl StateReader<Key, StoredValue> for ScratchGlobalStateView {
             ReadResult::RootNotFound => panic!("LmdbWithCacheGlobalState has invalid root"),
         };
         txn.commit()?;
+        record_read(
+            &self.read_set,
+            key,
+            ret.as_ref().map(TrieMerkleProof::value),
+        );
         Ok(ret)
     }
 
@@ -171,6 +279,9 @@ impl StateReader<Key, StoredValue> for ScratchGlobalStateView {
         correlation_id: CorrelationId,
         prefix: &[u8],
     ) -> Result<Vec<Key>, Self::Error> {
+        if let Some(read_set) = &self.read_set {
+            read_set.lock().unwrap().prefixes.push(prefix.to_vec());
+        }
         let txn = self.environment.create_read_txn()?;
         let keys_iter = keys_with_prefix::<Key, StoredValue, _, _>(
             correlation_id,
@@ -271,6 +382,7 @@ impl StateProvider for ScratchGlobalState {
         let maybe_root: Option<Trie<Key, StoredValue>> = self.trie_store.get(&txn, &state_hash)?;
         let maybe_state = maybe_root.map(|_| ScratchGlobalStateView {
             cache: Arc::clone(&self.cache),
+            read_set: self.read_set.clone(),
             environment: Arc::clone(&self.environment),
             trie_store: Arc::clone(&self.trie_store),
             root_hash: state_hash,
@@ -363,7 +475,7 @@ mod tests {
     use tempfile::tempdir;
 
     use casper_hashing::Digest;
-    use casper_types::{account::AccountHash, CLValue};
+    use casper_types::{account::AccountHash, CLValue, KeyTag};
 
     use super::*;
     use crate::storage::{
@@ -638,4 +750,73 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn recording_state_records_reads_and_shares_cache() {
+        let correlation_id = CorrelationId::new();
+        let test_pairs = create_test_pairs();
+        let test_pairs_updated = create_test_pairs_updated();
+
+        let TestState { state, root_hash } = create_test_state();
+
+        let scratch = state.create_scratch();
+        let recording = scratch.recording_reads();
+
+        let checkout = recording.checkout(root_hash).unwrap().unwrap();
+        assert_eq!(
+            Some(test_pairs[0].value.clone()),
+            checkout.read(correlation_id, &test_pairs[0].key).unwrap()
+        );
+        // Reads of missing keys are dependencies too.
+        assert_eq!(
+            None,
+            checkout
+                .read(correlation_id, &test_pairs_updated[2].key)
+                .unwrap()
+        );
+
+        let read_set = recording.take_read_set();
+        let written = |key: Key| -> HashSet<Key> { std::iter::once(key).collect() };
+        let current = scratch.checkout(root_hash).unwrap().unwrap();
+        let conflicts = |read_set: &ReadSet, key: Key| {
+            read_set
+                .conflicts_with(correlation_id, &written(key), &current)
+                .unwrap()
+        };
+        // Writing the values which were read is no conflict.
+        assert!(!conflicts(&read_set, test_pairs[0].key));
+        assert!(!conflicts(&read_set, test_pairs_updated[2].key));
+
+        // Listing keys depends on every key with the given prefix.
+        let _ = checkout
+            .keys_with_prefix(correlation_id, &[KeyTag::Account as u8])
+            .unwrap();
+        let prefix_read_set = recording.take_read_set();
+        assert!(conflicts(&prefix_read_set, test_pairs[1].key));
+        assert!(!conflicts(&prefix_read_set, Key::Hash([1; 32])));
+
+        // Neither the plain scratch state nor its other views record anything, but writes to it are
+        // visible through the recording state.
+        let effects: AdditiveMap<Key, Transform> = {
+            let mut tmp = AdditiveMap::new();
+            for TestPair { key, value } in &test_pairs_updated {
+                tmp.insert(*key, Transform::Write(value.to_owned()));
+            }
+            tmp
+        };
+        scratch.commit(correlation_id, root_hash, effects).unwrap();
+        assert!(!conflicts(&scratch.take_read_set(), test_pairs[0].key));
+
+        // Changing the values which were read, including creating a missing one, is.
+        assert!(conflicts(&read_set, test_pairs[0].key));
+        assert!(conflicts(&read_set, test_pairs_updated[2].key));
+        assert!(!conflicts(&read_set, test_pairs[1].key));
+        assert!(!conflicts(&recording.take_read_set(), test_pairs[0].key));
+        assert_eq!(
+            Some(test_pairs_updated[2].value.clone()),
+            checkout
+                .read(correlation_id, &test_pairs_updated[2].key)
+                .unwrap()
+        );
+    }
 }
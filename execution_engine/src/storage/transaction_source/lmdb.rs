@@ -146,6 +146,26 @@ impl LmdbEnvironment {
         })
     }
 
+    /// Opens an existing environment, such as a node's global state or a copy of one, read-only.
+    ///
+    /// The map size is taken from the existing database.  Any attempt to write to the environment
+    /// fails.
+    pub fn open_read_only<P: AsRef<Path>>(path: P, max_readers: u32) -> Result<Self, error::Error> {
+        let env = Environment::new()
+            .set_flags(
+                EnvironmentFlags::NO_SUB_DIR
+                    | EnvironmentFlags::NO_READAHEAD
+                    | EnvironmentFlags::READ_ONLY,
+            )
+            .set_max_dbs(MAX_DBS)
+            .set_max_readers(max_readers)
+            .open(&path.as_ref().join(EE_DB_FILENAME))?;
+        Ok(LmdbEnvironment {
+            env,
+            manual_sync_enabled: false,
+        })
+    }
+
     /// Returns a reference to the wrapped `Environment`.
     pub fn env(&self) -> &Environment {
         &self.env
@@ -64,8 +64,6 @@ pub trait Auction:
         delegation_rate: DelegationRate,
         amount: U512,
     ) -> Result<U512, ApiError> {
-        let provided_account_hash = AccountHash::from_public_key(&public_key, |x| self.blake2b(x));
-
         if amount.is_zero() {
             return Err(Error::BondTooSmall.into());
         }
@@ -74,7 +72,7 @@ pub trait Auction:
             return Err(Error::DelegationRateTooLarge.into());
         }
 
-        if !self.is_allowed_session_caller(&provided_account_hash) {
+        if !self.is_allowed_session_caller_for_key(&public_key) {
             return Err(Error::InvalidContext.into());
         }
 
@@ -150,7 +148,7 @@ pub trait Auction:
     fn withdraw_bid(&mut self, public_key: PublicKey, amount: U512) -> Result<U512, Error> {
         let provided_account_hash = AccountHash::from_public_key(&public_key, |x| self.blake2b(x));
 
-        if !self.is_allowed_session_caller(&provided_account_hash) {
+        if !self.is_allowed_session_caller_for_key(&public_key) {
             return Err(Error::InvalidContext);
         }
 
@@ -209,14 +207,11 @@ pub trait Auction:
         amount: U512,
         minimum_delegation_amount: u64,
     ) -> Result<U512, ApiError> {
-        let provided_account_hash =
-            AccountHash::from_public_key(&delegator_public_key, |x| self.blake2b(x));
-
         if amount.is_zero() {
             return Err(Error::BondTooSmall.into());
         }
 
-        if !self.is_allowed_session_caller(&provided_account_hash) {
+        if !self.is_allowed_session_caller_for_key(&delegator_public_key) {
             return Err(Error::InvalidContext.into());
         }
 
@@ -253,10 +248,7 @@ pub trait Auction:
         validator_public_key: PublicKey,
         amount: U512,
     ) -> Result<U512, Error> {
-        let provided_account_hash =
-            AccountHash::from_public_key(&delegator_public_key, |x| self.blake2b(x));
-
-        if !self.is_allowed_session_caller(&provided_account_hash) {
+        if !self.is_allowed_session_caller_for_key(&delegator_public_key) {
             return Err(Error::InvalidContext);
         }
 
@@ -312,10 +304,7 @@ pub trait Auction:
         new_validator: PublicKey,
         minimum_delegation_amount: u64,
     ) -> Result<U512, Error> {
-        let provided_account_hash =
-            AccountHash::from_public_key(&delegator_public_key, |x| self.blake2b(x));
-
-        if !self.is_allowed_session_caller(&provided_account_hash) {
+        if !self.is_allowed_session_caller_for_key(&delegator_public_key) {
             return Err(Error::InvalidContext);
         }
 
@@ -633,7 +622,7 @@ pub trait Auction:
         let provided_account_hash =
             AccountHash::from_public_key(&validator_public_key, |x| self.blake2b(x));
 
-        if !self.is_allowed_session_caller(&provided_account_hash) {
+        if !self.is_allowed_session_caller_for_key(&validator_public_key) {
             return Err(Error::InvalidContext);
         }
 
@@ -1,13 +1,13 @@
 use std::collections::BTreeSet;
 
 use casper_types::{
-    account::AccountHash,
+    account::{AccountHash, AccountHashVersion},
     bytesrepr::{FromBytes, ToBytes},
     system::{
         auction::{Bid, EraInfo, Error, UnbondingPurse},
         mint,
     },
-    CLTyped, EraId, Key, KeyTag, URef, BLAKE2B_DIGEST_LENGTH, U512,
+    CLTyped, EraId, Key, KeyTag, PublicKey, URef, BLAKE2B_DIGEST_LENGTH, U512,
 };
 
 /// Provider of runtime host functionality.
@@ -18,6 +18,27 @@ pub trait RuntimeProvider {
     /// Checks if account_hash matches the active session's account.
     fn is_allowed_session_caller(&self, account_hash: &AccountHash) -> bool;
 
+    /// Checks if the account hash of `public_key`, derived via any supported
+    /// [`AccountHashVersion`], matches the active session's account.
+    ///
+    /// [`AccountHashVersion::V2`] is only supported once enabled by the chainspec.
+    fn is_allowed_session_caller_for_key(&self, public_key: &PublicKey) -> bool {
+        AccountHashVersion::ALL
+            .iter()
+            .filter(|version| **version != AccountHashVersion::V2 || self.allow_account_hash_v2())
+            .any(|version| {
+                let account_hash =
+                    AccountHash::from_public_key_with_version(public_key, *version, |x| {
+                        self.blake2b(x)
+                    });
+                self.is_allowed_session_caller(&account_hash)
+            })
+    }
+
+    /// Returns `true` if callers may be authorized by account hashes derived via
+    /// [`AccountHashVersion::V2`].
+    fn allow_account_hash_v2(&self) -> bool;
+
     /// Gets named key under a `name`.
     fn named_keys_get(&self, name: &str) -> Option<Key>;
 
@@ -11,7 +11,7 @@ use casper_types::{
         mint::{Error, ROUND_SEIGNIORAGE_RATE_KEY, TOTAL_SUPPLY_KEY},
         CallStackElement,
     },
-    Key, Phase, PublicKey, URef, U512,
+    Key, Phase, PublicKey, URef, TRANSFER_MEMO_MAX_LENGTH, U512,
 };
 
 use crate::system::mint::{
@@ -100,6 +100,7 @@ pub trait Mint: RuntimeProvider + StorageProvider + SystemProvider {
         target: URef,
         amount: U512,
         id: Option<u64>,
+        memo: Option<String>,
     ) -> Result<(), Error> {
         if let (Phase::Session, Some(&CallStackElement::StoredSession { .. })) =
             (self.get_phase(), self.get_immediate_caller())
@@ -108,6 +109,13 @@ pub trait Mint: RuntimeProvider + StorageProvider + SystemProvider {
             return Err(Error::InvalidContext);
         }
 
+        if memo
+            .as_ref()
+            .map_or(false, |memo| memo.len() > TRANSFER_MEMO_MAX_LENGTH)
+        {
+            return Err(Error::MemoTooLong);
+        }
+
         if !source.is_readable() {
             return Err(Error::InvalidAccessRights);
         }
@@ -136,7 +144,7 @@ pub trait Mint: RuntimeProvider + StorageProvider + SystemProvider {
         self.write_balance(source, source_balance - amount)?;
         self.add_balance(target, amount)?;
 
-        self.record_transfer(maybe_to, source, target, amount, id)?;
+        self.record_transfer(maybe_to, source, target, amount, id, memo)?;
         Ok(())
     }
 
@@ -10,5 +10,6 @@ pub trait SystemProvider {
         target: URef,
         amount: U512,
         id: Option<u64>,
+        memo: Option<String>,
     ) -> Result<(), Error>;
 }
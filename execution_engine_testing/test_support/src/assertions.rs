@@ -0,0 +1,427 @@
+//! Assertions on the transfers, emitted events and balance changes of execs.
+//!
+//! The transfer and event assertions inspect the execution journals of the last exec, so can be
+//! made before or after committing it.  Balance changes are measured between a [`BalanceSnapshot`]
+//! and the builder's current post-state, so are only visible once the exec has been committed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use casper_engine_test_support::{
+//!     ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+//!     PRODUCTION_RUN_GENESIS_REQUEST,
+//! };
+//! use casper_types::{account::AccountHash, runtime_args, system::mint, RuntimeArgs, U512};
+//!
+//! let target = AccountHash::new([1; 32]);
+//! let amount = U512::from(5_000_000_000_u64);
+//!
+//! let mut builder = InMemoryWasmTestBuilder::default();
+//! builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+//! let balances = builder.balance_snapshot([*DEFAULT_ACCOUNT_ADDR, target]);
+//!
+//! let transfer_request = ExecuteRequestBuilder::transfer(
+//!     *DEFAULT_ACCOUNT_ADDR,
+//!     runtime_args! {
+//!         mint::ARG_TARGET => target,
+//!         mint::ARG_AMOUNT => amount,
+//!         mint::ARG_ID => <Option<u64>>::None,
+//!     },
+//! )
+//! .build();
+//! builder.exec(transfer_request).expect_success().commit();
+//!
+//! builder.assert_transfer(*DEFAULT_ACCOUNT_ADDR, target, amount);
+//! balances
+//!     .assert_increased_by(&builder, target, amount)
+//!     .assert_decreased_by_at_least(&builder, *DEFAULT_ACCOUNT_ADDR, amount);
+//! ```
+
+use std::collections::BTreeMap;
+
+use casper_execution_engine::{
+    core::{engine_state, execution},
+    shared::transform::Transform,
+    storage::global_state::{CommitProvider, StateProvider},
+};
+use casper_types::{
+    account::AccountHash,
+    contract_messages::topic_name_hash,
+    ContractHash, EmittedEvent, Key, StoredValue, TransferV2, URef, URefAddr, U512,
+};
+
+use crate::WasmTestBuilder;
+
+/// The holder of a balance: either a purse, or an account's main purse.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BalanceHolder {
+    /// A purse.
+    Purse(URef),
+    /// The main purse of an account.
+    Account(AccountHash),
+}
+
+impl From<URef> for BalanceHolder {
+    fn from(purse: URef) -> Self {
+        BalanceHolder::Purse(purse)
+    }
+}
+
+impl From<AccountHash> for BalanceHolder {
+    fn from(account_hash: AccountHash) -> Self {
+        BalanceHolder::Account(account_hash)
+    }
+}
+
+/// The change in a balance since a [`BalanceSnapshot`] was taken.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BalanceDelta {
+    /// The balance increased by the given amount.
+    Increased(U512),
+    /// The balance decreased by the given amount.
+    Decreased(U512),
+    /// The balance is unchanged.
+    Unchanged,
+}
+
+impl BalanceDelta {
+    fn new(before: U512, after: U512) -> Self {
+        if after > before {
+            BalanceDelta::Increased(after - before)
+        } else if after < before {
+            BalanceDelta::Decreased(before - after)
+        } else {
+            BalanceDelta::Unchanged
+        }
+    }
+}
+
+/// The balances of a set of purses at the time the snapshot was taken, against which later
+/// balances are compared.
+///
+/// Taken via [`WasmTestBuilder::balance_snapshot`].
+#[derive(Clone, Debug)]
+pub struct BalanceSnapshot {
+    /// The purses of the holders given when taking the snapshot.
+    purses: BTreeMap<BalanceHolder, URef>,
+    /// Balances keyed by purse address.
+    balances: BTreeMap<URefAddr, U512>,
+}
+
+impl BalanceSnapshot {
+    /// Returns the balance of `holder` when the snapshot was taken.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `holder` was not included in the snapshot.
+    pub fn balance<H: Into<BalanceHolder>>(&self, holder: H) -> U512 {
+        let holder = holder.into();
+        let purse = self.purse(holder);
+        self.balances[&purse.addr()]
+    }
+
+    /// Returns the change in the balance of `holder` between the snapshot and the builder's
+    /// current post-state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `holder` was not included in the snapshot.
+    pub fn delta<S, H>(&self, builder: &WasmTestBuilder<S>, holder: H) -> BalanceDelta
+    where
+        S: StateProvider + CommitProvider,
+        engine_state::Error: From<S::Error>,
+        S::Error: Into<execution::Error>,
+        H: Into<BalanceHolder>,
+    {
+        let holder = holder.into();
+        let purse = self.purse(holder);
+        let before = self.balances[&purse.addr()];
+        let after = builder.get_purse_balance(purse);
+        BalanceDelta::new(before, after)
+    }
+
+    /// Asserts that the balance of `holder` increased by exactly `amount`.
+    pub fn assert_increased_by<S, H>(
+        &self,
+        builder: &WasmTestBuilder<S>,
+        holder: H,
+        amount: U512,
+    ) -> &Self
+    where
+        S: StateProvider + CommitProvider,
+        engine_state::Error: From<S::Error>,
+        S::Error: Into<execution::Error>,
+        H: Into<BalanceHolder>,
+    {
+        self.assert_delta(builder, holder.into(), BalanceDelta::Increased(amount))
+    }
+
+    /// Asserts that the balance of `holder` decreased by exactly `amount`.
+    pub fn assert_decreased_by<S, H>(
+        &self,
+        builder: &WasmTestBuilder<S>,
+        holder: H,
+        amount: U512,
+    ) -> &Self
+    where
+        S: StateProvider + CommitProvider,
+        engine_state::Error: From<S::Error>,
+        S::Error: Into<execution::Error>,
+        H: Into<BalanceHolder>,
+    {
+        self.assert_delta(builder, holder.into(), BalanceDelta::Decreased(amount))
+    }
+
+    /// Asserts that the balance of `holder` decreased by at least `amount`.
+    ///
+    /// This is useful for an account which also paid for the exec, where the exact cost is not of
+    /// interest.
+    pub fn assert_decreased_by_at_least<S, H>(
+        &self,
+        builder: &WasmTestBuilder<S>,
+        holder: H,
+        amount: U512,
+    ) -> &Self
+    where
+        S: StateProvider + CommitProvider,
+        engine_state::Error: From<S::Error>,
+        S::Error: Into<execution::Error>,
+        H: Into<BalanceHolder>,
+    {
+        let holder = holder.into();
+        match self.delta(builder, holder) {
+            BalanceDelta::Decreased(decrease) if decrease >= amount => self,
+            delta => panic!(
+                "expected balance of {:?} to decrease by at least {}, but got {:?}",
+                holder, amount, delta
+            ),
+        }
+    }
+
+    /// Asserts that the balance of `holder` is unchanged.
+    pub fn assert_unchanged<S, H>(&self, builder: &WasmTestBuilder<S>, holder: H) -> &Self
+    where
+        S: StateProvider + CommitProvider,
+        engine_state::Error: From<S::Error>,
+        S::Error: Into<execution::Error>,
+        H: Into<BalanceHolder>,
+    {
+        self.assert_delta(builder, holder.into(), BalanceDelta::Unchanged)
+    }
+
+    fn assert_delta<S>(
+        &self,
+        builder: &WasmTestBuilder<S>,
+        holder: BalanceHolder,
+        expected: BalanceDelta,
+    ) -> &Self
+    where
+        S: StateProvider + CommitProvider,
+        engine_state::Error: From<S::Error>,
+        S::Error: Into<execution::Error>,
+    {
+        let actual = self.delta(builder, holder);
+        assert_eq!(
+            actual, expected,
+            "unexpected change in balance of {:?}",
+            holder
+        );
+        self
+    }
+
+    /// Returns the purse of `holder`, which may also be given as the main purse of an account
+    /// included in the snapshot.
+    fn purse(&self, holder: BalanceHolder) -> URef {
+        match (self.purses.get(&holder), holder) {
+            (Some(purse), _) => *purse,
+            (None, BalanceHolder::Purse(purse)) if self.balances.contains_key(&purse.addr()) => {
+                purse
+            }
+            (None, _) => panic!("{:?} should be included in the balance snapshot", holder),
+        }
+    }
+}
+
+impl<S> WasmTestBuilder<S>
+where
+    S: StateProvider + CommitProvider,
+    engine_state::Error: From<S::Error>,
+    S::Error: Into<execution::Error>,
+{
+    /// Returns the transfers recorded by the last exec, in order.
+    ///
+    /// Transfers recorded without their initiator, memo and phase are converted to `TransferV2`s.
+    pub fn last_exec_transfers(&self) -> Vec<TransferV2> {
+        self.last_exec_transforms()
+            .filter_map(|(key, transform)| match (key, transform) {
+                (Key::Transfer(_), Transform::Write(StoredValue::Transfer(transfer))) => {
+                    Some(TransferV2::from(transfer))
+                }
+                (Key::Transfer(_), Transform::Write(StoredValue::TransferV2(transfer))) => {
+                    Some(transfer)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the contract messages emitted by the last exec, in order.
+    pub fn last_exec_events(&self) -> Vec<EmittedEvent> {
+        self.last_exec_transforms()
+            .filter_map(|(key, transform)| match (key, transform) {
+                (Key::Message(message_addr), Transform::WriteMessage(message)) => {
+                    Some(EmittedEvent {
+                        message_addr,
+                        checksum: message.checksum(),
+                        payload: message.payload().clone(),
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Asserts that the last exec transferred `amount` motes from the account `from` to the main
+    /// purse of the account `to`.
+    pub fn assert_transfer(&self, from: AccountHash, to: AccountHash, amount: U512) -> &Self {
+        self.assert_transfer_matching(
+            |transfer| {
+                transfer.from == from && transfer.to == Some(to) && transfer.amount == amount
+            },
+            || format!("{} motes from {} to {}", amount, from, to),
+        )
+    }
+
+    /// Asserts that the last exec transferred `amount` motes from the account `from` to the purse
+    /// `target`.
+    pub fn assert_transfer_to_purse(&self, from: AccountHash, target: URef, amount: U512) -> &Self {
+        self.assert_transfer_matching(
+            |transfer| {
+                transfer.from == from
+                    && transfer.target.addr() == target.addr()
+                    && transfer.amount == amount
+            },
+            || format!("{} motes from {} to {}", amount, from, target),
+        )
+    }
+
+    /// Asserts that the last exec recorded no transfers.
+    pub fn assert_no_transfers(&self) -> &Self {
+        let transfers = self.last_exec_transfers();
+        assert!(
+            transfers.is_empty(),
+            "expected no transfers, but got {:?}",
+            transfers
+        );
+        self
+    }
+
+    /// Asserts that the last exec emitted a message on the topic `topic` of the contract
+    /// `contract`.
+    pub fn assert_event_emitted(&self, contract: ContractHash, topic: &str) -> &Self {
+        let count = self.count_events(contract, topic, None);
+        assert!(
+            count > 0,
+            "expected {} to emit an event on topic {:?}, but got {:?}",
+            contract,
+            topic,
+            self.last_exec_events()
+        );
+        self
+    }
+
+    /// Asserts that the last exec emitted a message with the given payload on the topic `topic` of
+    /// the contract `contract`.
+    pub fn assert_event_emitted_with_payload(
+        &self,
+        contract: ContractHash,
+        topic: &str,
+        payload: &[u8],
+    ) -> &Self {
+        let count = self.count_events(contract, topic, Some(payload));
+        assert!(
+            count > 0,
+            "expected {} to emit an event with payload {:?} on topic {:?}, but got {:?}",
+            contract,
+            payload,
+            topic,
+            self.last_exec_events()
+        );
+        self
+    }
+
+    /// Asserts that the last exec emitted no messages on the topic `topic` of the contract
+    /// `contract`.
+    pub fn assert_no_event_emitted(&self, contract: ContractHash, topic: &str) -> &Self {
+        let count = self.count_events(contract, topic, None);
+        assert_eq!(
+            count, 0,
+            "expected {} to emit no events on topic {:?}",
+            contract, topic
+        );
+        self
+    }
+
+    /// Takes a snapshot of the current balances of the given purses or accounts' main purses,
+    /// against which later balances can be compared.
+    pub fn balance_snapshot<I, H>(&self, holders: I) -> BalanceSnapshot
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<BalanceHolder>,
+    {
+        let mut purses = BTreeMap::new();
+        let mut balances = BTreeMap::new();
+        for holder in holders {
+            let holder = holder.into();
+            let purse = match holder {
+                BalanceHolder::Purse(purse) => purse,
+                BalanceHolder::Account(account_hash) => {
+                    self.get_expected_account(account_hash).main_purse()
+                }
+            };
+            balances.insert(purse.addr(), self.get_purse_balance(purse));
+            purses.insert(holder, purse);
+        }
+        BalanceSnapshot { purses, balances }
+    }
+
+    /// Returns the transforms of all results of the last exec, in order.
+    fn last_exec_transforms(&self) -> impl Iterator<Item = (Key, Transform)> {
+        self.get_last_exec_results()
+            .expect("should have exec results")
+            .into_iter()
+            .flat_map(|exec_result| exec_result.execution_journal().clone())
+    }
+
+    fn assert_transfer_matching<F, D>(&self, matches: F, describe: D) -> &Self
+    where
+        F: Fn(&TransferV2) -> bool,
+        D: FnOnce() -> String,
+    {
+        let transfers = self.last_exec_transfers();
+        assert!(
+            transfers.iter().any(matches),
+            "expected a transfer of {}, but got {:?}",
+            describe(),
+            transfers
+        );
+        self
+    }
+
+    fn count_events(
+        &self,
+        contract: ContractHash,
+        topic: &str,
+        payload: Option<&[u8]>,
+    ) -> usize {
+        let topic_name_hash = topic_name_hash(topic);
+        self.last_exec_events()
+            .iter()
+            .filter(|event| {
+                event.message_addr.entity_addr() == contract.value()
+                    && event.message_addr.topic_name_hash() == topic_name_hash
+                    && payload.map_or(true, |payload| event.payload.as_slice() == payload)
+            })
+            .count()
+    }
+}
@@ -0,0 +1,396 @@
+use std::{path::Path, time::Duration};
+
+use num_rational::Ratio;
+
+use casper_execution_engine::{
+    core::engine_state::{
+        run_genesis_request::RunGenesisRequest, EngineConfig, ExecConfig, GenesisAccount,
+        DEFAULT_MAX_QUERY_DEPTH,
+    },
+    shared::{
+        host_function_costs::HostFunctionCosts,
+        opcode_costs::OpcodeCosts,
+        storage_costs::StorageCosts,
+        system_config::{
+            auction_costs::AuctionCosts, handle_payment_costs::HandlePaymentCosts,
+            mint_costs::MintCosts, standard_payment_costs::StandardPaymentCosts, SystemConfig,
+        },
+        wasm_config::WasmConfig,
+    },
+};
+use casper_types::ProtocolVersion;
+
+use crate::{
+    chainspec_config::{ChainspecConfig, Error, PRODUCTION_PATH},
+    DEFAULT_CHAINSPEC_REGISTRY, DEFAULT_GENESIS_CONFIG_HASH, DEFAULT_GENESIS_TIMESTAMP_MILLIS,
+};
+
+/// Builds the engine and genesis configuration of a test from a chainspec, with individual
+/// parameters overridden.
+///
+/// ```
+/// # use casper_engine_test_support::ChainspecBuilder;
+/// let chainspec = ChainspecBuilder::production()
+///     .with_unbonding_delay(2)
+///     .with_max_associated_keys(10);
+/// let engine_config = chainspec.engine_config();
+/// assert_eq!(engine_config.max_associated_keys(), 10);
+/// ```
+#[derive(Clone)]
+pub struct ChainspecBuilder {
+    chainspec_config: ChainspecConfig,
+    locked_funds_period: Duration,
+    vesting_schedule_period: Duration,
+}
+
+impl ChainspecBuilder {
+    /// Returns a new `ChainspecBuilder` starting from the chainspec at the given path.
+    pub fn from_chainspec_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let chainspec_config = ChainspecConfig::from_chainspec_path(path)?;
+        let locked_funds_period =
+            humantime::parse_duration(&chainspec_config.core_config.locked_funds_period)
+                .map_err(|_| Error::FailedToParseLockedFundsPeriod)?;
+        let vesting_schedule_period =
+            humantime::parse_duration(&chainspec_config.core_config.vesting_schedule_period)
+                .map_err(|_| Error::FailedToParseVestingSchedulePeriod)?;
+        Ok(ChainspecBuilder {
+            chainspec_config,
+            locked_funds_period,
+            vesting_schedule_period,
+        })
+    }
+
+    /// Returns a new `ChainspecBuilder` starting from the production chainspec.
+    pub fn production() -> Self {
+        Self::from_chainspec_path(&*PRODUCTION_PATH).expect("must load production chainspec")
+    }
+
+    /// Sets the number of validator slots in the auction.
+    pub fn with_validator_slots(mut self, validator_slots: u32) -> Self {
+        self.chainspec_config.core_config.validator_slots = validator_slots;
+        self
+    }
+
+    /// Sets the auction delay in eras.
+    pub fn with_auction_delay(mut self, auction_delay: u64) -> Self {
+        self.chainspec_config.core_config.auction_delay = auction_delay;
+        self
+    }
+
+    /// Sets the period after genesis during which a genesis validator's bid is locked.
+    pub fn with_locked_funds_period(mut self, locked_funds_period: Duration) -> Self {
+        self.locked_funds_period = locked_funds_period;
+        self
+    }
+
+    /// Sets the period over which a genesis validator's bid is released.
+    pub fn with_vesting_schedule_period(mut self, vesting_schedule_period: Duration) -> Self {
+        self.vesting_schedule_period = vesting_schedule_period;
+        self
+    }
+
+    /// Sets the delay in eras before unbonded funds are paid out.
+    pub fn with_unbonding_delay(mut self, unbonding_delay: u64) -> Self {
+        self.chainspec_config.core_config.unbonding_delay = unbonding_delay;
+        self
+    }
+
+    /// Sets the round seigniorage rate.
+    pub fn with_round_seigniorage_rate(mut self, round_seigniorage_rate: Ratio<u64>) -> Self {
+        self.chainspec_config.core_config.round_seigniorage_rate = round_seigniorage_rate;
+        self
+    }
+
+    /// Sets the maximum number of associated keys of a single account.
+    pub fn with_max_associated_keys(mut self, max_associated_keys: u32) -> Self {
+        self.chainspec_config.core_config.max_associated_keys = max_associated_keys;
+        self
+    }
+
+    /// Sets the maximum height of the contract runtime call stack.
+    pub fn with_max_runtime_call_stack_height(
+        mut self,
+        max_runtime_call_stack_height: u32,
+    ) -> Self {
+        self.chainspec_config
+            .core_config
+            .max_runtime_call_stack_height = max_runtime_call_stack_height;
+        self
+    }
+
+    /// Sets the minimum amount of motes that can be delegated to a validator.
+    pub fn with_minimum_delegation_amount(mut self, minimum_delegation_amount: u64) -> Self {
+        self.chainspec_config.core_config.minimum_delegation_amount = minimum_delegation_amount;
+        self
+    }
+
+    /// Sets whether the arguments of contract calls are checked strictly.
+    pub fn with_strict_argument_checking(mut self, strict_argument_checking: bool) -> Self {
+        self.chainspec_config.core_config.strict_argument_checking = strict_argument_checking;
+        self
+    }
+
+    /// Sets whether transfers are recorded with their initiator, memo and phase.
+    pub fn with_record_transfer_metadata(mut self, record_transfer_metadata: bool) -> Self {
+        self.chainspec_config.core_config.record_transfer_metadata = record_transfer_metadata;
+        self
+    }
+
+    /// Sets whether auction callers may be authorized by `V2` account hashes.
+    pub fn with_allow_account_hash_v2(mut self, allow_account_hash_v2: bool) -> Self {
+        self.chainspec_config.core_config.allow_account_hash_v2 = allow_account_hash_v2;
+        self
+    }
+
+    /// Sets the maximum amount of heap memory, in 64kB pages, each contract can use.
+    pub fn with_max_memory(mut self, max_memory: u32) -> Self {
+        self.chainspec_config.wasm_config.max_memory = max_memory;
+        self
+    }
+
+    /// Sets the maximum height of the Wasm stack.
+    pub fn with_max_stack_height(mut self, max_stack_height: u32) -> Self {
+        self.chainspec_config.wasm_config.max_stack_height = max_stack_height;
+        self
+    }
+
+    /// Sets the Wasm opcode costs.
+    pub fn with_opcode_costs(mut self, opcode_costs: OpcodeCosts) -> Self {
+        let wasm_config = self.chainspec_config.wasm_config;
+        self.chainspec_config.wasm_config = WasmConfig::new(
+            wasm_config.max_memory,
+            wasm_config.max_stack_height,
+            opcode_costs,
+            wasm_config.storage_costs(),
+            wasm_config.take_host_function_costs(),
+        );
+        self
+    }
+
+    /// Sets the storage costs.
+    pub fn with_storage_costs(mut self, storage_costs: StorageCosts) -> Self {
+        let wasm_config = self.chainspec_config.wasm_config;
+        self.chainspec_config.wasm_config = WasmConfig::new(
+            wasm_config.max_memory,
+            wasm_config.max_stack_height,
+            wasm_config.opcode_costs(),
+            storage_costs,
+            wasm_config.take_host_function_costs(),
+        );
+        self
+    }
+
+    /// Sets the host function costs.
+    pub fn with_host_function_costs(mut self, host_function_costs: HostFunctionCosts) -> Self {
+        let wasm_config = self.chainspec_config.wasm_config;
+        self.chainspec_config.wasm_config = WasmConfig::new(
+            wasm_config.max_memory,
+            wasm_config.max_stack_height,
+            wasm_config.opcode_costs(),
+            wasm_config.storage_costs(),
+            host_function_costs,
+        );
+        self
+    }
+
+    /// Sets the whole Wasm configuration, replacing any limits or costs set before.
+    pub fn with_wasm_config(mut self, wasm_config: WasmConfig) -> Self {
+        self.chainspec_config.wasm_config = wasm_config;
+        self
+    }
+
+    /// Sets the cost of a wasmless transfer.
+    pub fn with_wasmless_transfer_cost(mut self, wasmless_transfer_cost: u32) -> Self {
+        let system_config = self.chainspec_config.system_costs_config;
+        self.chainspec_config.system_costs_config = SystemConfig::new(
+            wasmless_transfer_cost,
+            *system_config.auction_costs(),
+            *system_config.mint_costs(),
+            *system_config.handle_payment_costs(),
+            *system_config.standard_payment_costs(),
+        );
+        self
+    }
+
+    /// Sets the costs of the auction contract's entry points.
+    pub fn with_auction_costs(mut self, auction_costs: AuctionCosts) -> Self {
+        let system_config = self.chainspec_config.system_costs_config;
+        self.chainspec_config.system_costs_config = SystemConfig::new(
+            system_config.wasmless_transfer_cost(),
+            auction_costs,
+            *system_config.mint_costs(),
+            *system_config.handle_payment_costs(),
+            *system_config.standard_payment_costs(),
+        );
+        self
+    }
+
+    /// Sets the costs of the mint contract's entry points.
+    pub fn with_mint_costs(mut self, mint_costs: MintCosts) -> Self {
+        let system_config = self.chainspec_config.system_costs_config;
+        self.chainspec_config.system_costs_config = SystemConfig::new(
+            system_config.wasmless_transfer_cost(),
+            *system_config.auction_costs(),
+            mint_costs,
+            *system_config.handle_payment_costs(),
+            *system_config.standard_payment_costs(),
+        );
+        self
+    }
+
+    /// Sets the costs of the handle payment contract's entry points.
+    pub fn with_handle_payment_costs(mut self, handle_payment_costs: HandlePaymentCosts) -> Self {
+        let system_config = self.chainspec_config.system_costs_config;
+        self.chainspec_config.system_costs_config = SystemConfig::new(
+            system_config.wasmless_transfer_cost(),
+            *system_config.auction_costs(),
+            *system_config.mint_costs(),
+            handle_payment_costs,
+            *system_config.standard_payment_costs(),
+        );
+        self
+    }
+
+    /// Sets the costs of the standard payment contract.
+    pub fn with_standard_payment_costs(
+        mut self,
+        standard_payment_costs: StandardPaymentCosts,
+    ) -> Self {
+        let system_config = self.chainspec_config.system_costs_config;
+        self.chainspec_config.system_costs_config = SystemConfig::new(
+            system_config.wasmless_transfer_cost(),
+            *system_config.auction_costs(),
+            *system_config.mint_costs(),
+            *system_config.handle_payment_costs(),
+            standard_payment_costs,
+        );
+        self
+    }
+
+    /// Sets the whole system contract costs configuration, replacing any costs set before.
+    pub fn with_system_config(mut self, system_config: SystemConfig) -> Self {
+        self.chainspec_config.system_costs_config = system_config;
+        self
+    }
+
+    /// Returns the [`EngineConfig`] for the configured chainspec.
+    pub fn engine_config(&self) -> EngineConfig {
+        let core_config = &self.chainspec_config.core_config;
+        EngineConfig::new(
+            DEFAULT_MAX_QUERY_DEPTH,
+            core_config.max_associated_keys,
+            core_config.max_runtime_call_stack_height,
+            core_config.minimum_delegation_amount,
+            core_config.strict_argument_checking,
+            self.vesting_schedule_period.as_millis() as u64,
+            self.chainspec_config.wasm_config,
+            self.chainspec_config.system_costs_config,
+        )
+        .with_record_transfer_metadata(core_config.record_transfer_metadata)
+        .with_allow_account_hash_v2(core_config.allow_account_hash_v2)
+    }
+
+    /// Returns the [`ExecConfig`] for the configured chainspec with the given genesis accounts.
+    pub fn exec_config(&self, genesis_accounts: Vec<GenesisAccount>) -> ExecConfig {
+        let core_config = &self.chainspec_config.core_config;
+        ExecConfig::new(
+            genesis_accounts,
+            self.chainspec_config.wasm_config,
+            self.chainspec_config.system_costs_config,
+            core_config.validator_slots,
+            core_config.auction_delay,
+            self.locked_funds_period.as_millis() as u64,
+            core_config.round_seigniorage_rate,
+            core_config.unbonding_delay,
+            DEFAULT_GENESIS_TIMESTAMP_MILLIS,
+        )
+    }
+
+    /// Returns a [`RunGenesisRequest`] for the configured chainspec with the given genesis
+    /// accounts and protocol version.
+    pub fn genesis_request(
+        &self,
+        genesis_accounts: Vec<GenesisAccount>,
+        protocol_version: ProtocolVersion,
+    ) -> RunGenesisRequest {
+        RunGenesisRequest::new(
+            *DEFAULT_GENESIS_CONFIG_HASH,
+            protocol_version,
+            self.exec_config(genesis_accounts),
+            DEFAULT_CHAINSPEC_REGISTRY.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use num_rational::Ratio;
+
+    use casper_execution_engine::shared::{
+        opcode_costs::OpcodeCosts, system_config::mint_costs::MintCosts,
+    };
+
+    use super::ChainspecBuilder;
+    use crate::{DEFAULT_ACCOUNTS, DEFAULT_PROTOCOL_VERSION};
+
+    #[test]
+    fn should_match_production_chainspec_without_overrides() {
+        let production = ChainspecBuilder::production();
+        assert_eq!(
+            production.exec_config(DEFAULT_ACCOUNTS.clone()),
+            *crate::PRODUCTION_RUN_GENESIS_REQUEST.ee_config()
+        );
+    }
+
+    #[test]
+    fn should_override_individual_parameters() {
+        let production = ChainspecBuilder::production();
+        let opcode_costs = OpcodeCosts {
+            add: 1_000,
+            ..production.engine_config().wasm_config().opcode_costs()
+        };
+        let mint_costs = MintCosts {
+            transfer: 7,
+            ..*production.engine_config().system_config().mint_costs()
+        };
+        let chainspec = production
+            .clone()
+            .with_unbonding_delay(2)
+            .with_validator_slots(3)
+            .with_locked_funds_period(Duration::from_secs(60))
+            .with_round_seigniorage_rate(Ratio::new(1, 10))
+            .with_max_associated_keys(7)
+            .with_max_memory(10)
+            .with_opcode_costs(opcode_costs)
+            .with_wasmless_transfer_cost(42)
+            .with_mint_costs(mint_costs);
+
+        let genesis_request = chainspec.genesis_request(Vec::new(), *DEFAULT_PROTOCOL_VERSION);
+        let exec_config = genesis_request.ee_config();
+        assert_eq!(exec_config.unbonding_delay(), 2);
+        assert_eq!(exec_config.validator_slots(), 3);
+        assert_eq!(exec_config.locked_funds_period_millis(), 60_000);
+        assert_eq!(exec_config.round_seigniorage_rate(), Ratio::new(1, 10));
+        assert_eq!(
+            exec_config.auction_delay(),
+            production.exec_config(Vec::new()).auction_delay()
+        );
+
+        let engine_config = chainspec.engine_config();
+        assert_eq!(engine_config.max_associated_keys(), 7);
+        assert_eq!(engine_config.wasm_config().max_memory, 10);
+        assert_eq!(engine_config.wasm_config().opcode_costs(), opcode_costs);
+        assert_eq!(
+            engine_config.wasm_config().storage_costs(),
+            production.engine_config().wasm_config().storage_costs()
+        );
+        assert_eq!(engine_config.system_config().wasmless_transfer_cost(), 42);
+        assert_eq!(engine_config.system_config().mint_costs(), &mint_costs);
+        assert_eq!(
+            engine_config.system_config().auction_costs(),
+            production.engine_config().system_config().auction_costs()
+        );
+    }
+}
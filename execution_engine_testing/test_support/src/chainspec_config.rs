@@ -41,6 +41,7 @@ pub enum Error {
     FailedToParseChainspec(toml::de::Error),
     FailedToCreateExecConfig,
     FailedToParseLockedFundsPeriod,
+    FailedToParseVestingSchedulePeriod,
     FailedToCreateGenesisRequest,
 }
 
@@ -68,6 +69,12 @@ pub struct CoreConfig {
     pub(crate) minimum_delegation_amount: u64,
     /// Enables strict arguments checking when calling a contract.
     pub(crate) strict_argument_checking: bool,
+    /// Records transfers with their initiator, memo and phase.
+    #[serde(default)]
+    pub(crate) record_transfer_metadata: bool,
+    /// Authorizes auction callers by `V2` account hashes as well as `V1`.
+    #[serde(default)]
+    pub(crate) allow_account_hash_v2: bool,
 }
 
 /// This struct can be parsed from a TOML-encoded chainspec file.  It means that as the
@@ -1,4 +1,4 @@
-use std::{collections::BTreeSet, path::Path};
+use std::{cell::Cell, cmp, collections::BTreeSet, env, path::Path, thread};
 
 use rand::Rng;
 
@@ -13,6 +13,104 @@ use casper_types::{
 
 use crate::{utils, DEFAULT_GAS_PRICE};
 
+/// The env var holding the test seed, as printed when a test fails.
+const CASPER_TEST_SEED: &str = "CASPER_TEST_SEED";
+
+/// A seed from which the deploy hashes of a test are derived.
+type TestSeed = [u8; 16];
+
+thread_local! {
+    /// The seed and index of the next deploy hash, if deploy hashes are pinned on this thread.
+    static PINNED_DEPLOY_HASHES: Cell<Option<(u64, u64)>> = Cell::new(None);
+    /// The test seed and index of the next deploy hash used while deploy hashes are not pinned,
+    /// initialized on first use.
+    static SEEDED_DEPLOY_HASHES: Cell<Option<(TestSeed, u64)>> = Cell::new(None);
+    /// Whether the test seed has been reported on this thread.
+    static SEED_REPORTED: Cell<bool> = Cell::new(false);
+}
+
+/// Pins the deploy hashes generated on the current thread for as long as it is alive.
+///
+/// Deploys built without an explicit hash by [`DeployItemBuilder`] or `ExecuteRequestBuilder` are
+/// normally given a random one.  While a `PinnedDeployHashes` exists, they are instead given a
+/// sequence of hashes derived from its seed, so that execution results depending on deploy hashes,
+/// e.g. the addresses of newly created purses, are reproducible.
+///
+/// Pins can be nested, and dropping one restores the sequence of the enclosing one, if any.
+#[derive(Debug)]
+pub struct PinnedDeployHashes {
+    previous: Option<(u64, u64)>,
+}
+
+impl PinnedDeployHashes {
+    /// Pins the deploy hashes generated on the current thread to the sequence derived from `seed`.
+    pub fn pin(seed: u64) -> Self {
+        let previous = PINNED_DEPLOY_HASHES.with(|pinned| pinned.replace(Some((seed, 0))));
+        PinnedDeployHashes { previous }
+    }
+}
+
+impl Drop for PinnedDeployHashes {
+    fn drop(&mut self) {
+        PINNED_DEPLOY_HASHES.with(|pinned| pinned.set(self.previous));
+    }
+}
+
+/// Returns the test seed given by the env var `CASPER_TEST_SEED`, or a random one if unset.
+fn test_seed_from_env() -> TestSeed {
+    let mut seed = TestSeed::default();
+    match env::var(CASPER_TEST_SEED) {
+        Ok(seed_as_hex) => {
+            base16::decode_slice(&seed_as_hex, &mut seed).unwrap_or_else(|error| {
+                panic!("can't parse '{}' as a test seed: {}", seed_as_hex, error)
+            });
+        }
+        Err(_) => rand::thread_rng().fill(&mut seed),
+    }
+    seed
+}
+
+/// Seeds the deploy hashes generated on the current thread while they are not pinned, restarting
+/// their sequence.
+///
+/// Deploy hashes are otherwise seeded by the env var `CASPER_TEST_SEED` if set, or randomly.
+pub(crate) fn seed_deploy_hashes(seed: TestSeed) {
+    SEEDED_DEPLOY_HASHES.with(|seeded| seeded.set(Some((seed, 0))));
+}
+
+/// Prints the test seed of the current thread, if deploy hashes were derived from it and it has
+/// not been printed yet, so that a failing test can be replayed.
+pub(crate) fn report_test_seed() {
+    let seed = match SEEDED_DEPLOY_HASHES.with(Cell::get) {
+        Some((seed, _)) if !SEED_REPORTED.with(|reported| reported.replace(true)) => seed,
+        _ => return,
+    };
+    let line_1 = format!("Thread: {}", thread::current().name().unwrap_or("unnamed"));
+    let line_2 = "To reproduce failure, try running with env var:";
+    let line_3 = format!("{}={}", CASPER_TEST_SEED, base16::encode_lower(&seed));
+    let border = "=".repeat(cmp::max(line_1.len(), line_2.len()));
+    println!(
+        "\n{}\n{}\n{}\n{}\n{}\n",
+        border, line_1, line_2, line_3, border
+    );
+}
+
+/// Returns the next pinned deploy hash if deploy hashes are pinned, or the next one derived from
+/// the test seed otherwise.
+pub(crate) fn random_deploy_hash() -> [u8; 32] {
+    PINNED_DEPLOY_HASHES.with(|pinned| match pinned.get() {
+        Some((seed, index)) => {
+            pinned.set(Some((seed, index + 1)));
+            Digest::hash([seed.to_le_bytes(), index.to_le_bytes()].concat()).value()
+        }
+        None => SEEDED_DEPLOY_HASHES.with(|seeded| {
+            let (seed, index) = seeded.get().unwrap_or_else(|| (test_seed_from_env(), 0));
+            seeded.set(Some((seed, index + 1)));
+            Digest::hash([&seed[..], &index.to_le_bytes()].concat()).value()
+        }),
+    })
+}
+
 #[derive(Default)]
 struct DeployItemData {
     pub address: Option<AccountHash>,
@@ -281,7 +379,7 @@ impl DeployItemBuilder {
             deploy_hash: self
                 .deploy_item
                 .deploy_hash
-                .unwrap_or_else(|| rand::thread_rng().gen()),
+                .unwrap_or_else(|| DeployHash::new(random_deploy_hash())),
         }
     }
 }
@@ -311,4 +409,39 @@ mod tests {
             .build();
         assert_ne!(deploy.deploy_hash, DeployHash::default());
     }
+
+    #[test]
+    fn should_generate_pinned_deploy_hashes() {
+        let hashes = |seed| {
+            let _pinned = PinnedDeployHashes::pin(seed);
+            (0..3).map(|_| random_deploy_hash()).collect::<Vec<_>>()
+        };
+
+        let first_hashes = hashes(1);
+        assert_eq!(first_hashes, hashes(1));
+        assert_ne!(first_hashes, hashes(2));
+        assert_ne!(first_hashes[0], first_hashes[1]);
+        assert_ne!(random_deploy_hash(), random_deploy_hash());
+    }
+
+    #[test]
+    fn should_replay_seeded_deploy_hashes() {
+        let hashes = |seed| {
+            seed_deploy_hashes(seed);
+            (0..3).map(|_| random_deploy_hash()).collect::<Vec<_>>()
+        };
+
+        let first_hashes = hashes([1; 16]);
+        assert_eq!(first_hashes, hashes([1; 16]));
+        assert_ne!(first_hashes, hashes([2; 16]));
+
+        // Pinned hashes take precedence without disturbing the seeded sequence.
+        seed_deploy_hashes([1; 16]);
+        let first_hash = random_deploy_hash();
+        {
+            let _pinned = PinnedDeployHashes::pin(1);
+            assert_ne!(random_deploy_hash(), first_hashes[1]);
+        }
+        assert_eq!([first_hash, random_deploy_hash()], first_hashes[..2]);
+    }
 }
@@ -1,7 +1,5 @@
 use std::convert::TryInto;
 
-use rand::Rng;
-
 use casper_execution_engine::core::engine_state::{
     deploy_item::DeployItem, execute_request::ExecuteRequest,
 };
@@ -10,7 +8,10 @@ use casper_types::{
     ProtocolVersion, RuntimeArgs,
 };
 
-use crate::{DeployItemBuilder, DEFAULT_BLOCK_TIME, DEFAULT_PAYMENT, DEFAULT_PROPOSER_PUBLIC_KEY};
+use crate::{
+    deploy_item_builder::random_deploy_hash, DeployItemBuilder, DEFAULT_BLOCK_TIME,
+    DEFAULT_PAYMENT, DEFAULT_PROPOSER_PUBLIC_KEY,
+};
 
 const ARG_AMOUNT: &str = "amount";
 
@@ -72,8 +73,7 @@ impl ExecuteRequestBuilder {
         session_file: &str,
         session_args: RuntimeArgs,
     ) -> Self {
-        let mut rng = rand::thread_rng();
-        let deploy_hash: [u8; 32] = rng.gen();
+        let deploy_hash = random_deploy_hash();
 
         let deploy = DeployItemBuilder::new()
             .with_address(account_hash)
@@ -94,8 +94,7 @@ impl ExecuteRequestBuilder {
         module_bytes: Vec<u8>,
         session_args: RuntimeArgs,
     ) -> Self {
-        let mut rng = rand::thread_rng();
-        let deploy_hash: [u8; 32] = rng.gen();
+        let deploy_hash = random_deploy_hash();
 
         let deploy = DeployItemBuilder::new()
             .with_address(account_hash)
@@ -117,8 +116,7 @@ impl ExecuteRequestBuilder {
         entry_point: &str,
         args: RuntimeArgs,
     ) -> Self {
-        let mut rng = rand::thread_rng();
-        let deploy_hash = rng.gen();
+        let deploy_hash = random_deploy_hash();
 
         let deploy = DeployItemBuilder::new()
             .with_address(sender)
@@ -138,8 +136,7 @@ impl ExecuteRequestBuilder {
         entry_point: &str,
         args: RuntimeArgs,
     ) -> Self {
-        let mut rng = rand::thread_rng();
-        let deploy_hash = rng.gen();
+        let deploy_hash = random_deploy_hash();
 
         let deploy = DeployItemBuilder::new()
             .with_address(sender)
@@ -160,8 +157,7 @@ impl ExecuteRequestBuilder {
         entry_point_name: &str,
         args: RuntimeArgs,
     ) -> Self {
-        let mut rng = rand::thread_rng();
-        let deploy_hash = rng.gen();
+        let deploy_hash = random_deploy_hash();
 
         let deploy = DeployItemBuilder::new()
             .with_address(sender)
@@ -187,8 +183,7 @@ impl ExecuteRequestBuilder {
         entry_point_name: &str,
         args: RuntimeArgs,
     ) -> Self {
-        let mut rng = rand::thread_rng();
-        let deploy_hash = rng.gen();
+        let deploy_hash = random_deploy_hash();
 
         let deploy = DeployItemBuilder::new()
             .with_address(sender)
@@ -203,8 +198,7 @@ impl ExecuteRequestBuilder {
 
     /// Returns an [`ExecuteRequest`] for a native transfer.
     pub fn transfer(sender: AccountHash, transfer_args: RuntimeArgs) -> Self {
-        let mut rng = rand::thread_rng();
-        let deploy_hash = rng.gen();
+        let deploy_hash = random_deploy_hash();
 
         let deploy_item = DeployItemBuilder::new()
             .with_address(sender)
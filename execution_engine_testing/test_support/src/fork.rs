@@ -0,0 +1,359 @@
+//! Global state forked from a running node.
+//!
+//! [`ForkedGlobalState`] is backed by a local LMDB database acting as a cache.  Whenever a trie
+//! needed to read or write global state is missing from the cache, it is fetched from the node via
+//! the `state_get_trie` JSON-RPC method, verified against its hash and stored.  Since tries are
+//! addressed by their hash, the cache stays valid across runs and across forks at different
+//! blocks.
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
+
+use log::debug;
+use reqwest::blocking::Client;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use casper_execution_engine::{
+    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+    storage::{
+        error,
+        global_state::{
+            lmdb::{LmdbGlobalState, LmdbGlobalStateView},
+            CommitProvider, StateProvider, StateReader,
+        },
+        trie::{merkle_proof::TrieMerkleProof, Trie, TrieOrChunk, TrieOrChunkId},
+    },
+};
+use casper_hashing::Digest;
+use casper_types::{
+    bytesrepr::{self, Bytes, ToBytes},
+    Key, StoredValue,
+};
+
+/// The block of a running node from which global state is forked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForkBlock {
+    /// The node's highest block.
+    Latest,
+    /// The block with the given hash.
+    Hash(Digest),
+    /// The block at the given height.
+    Height(u64),
+}
+
+/// A minimal blocking JSON-RPC client for a node's RPC server.
+struct NodeClient {
+    client: Client,
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct GetStateRootHashResult {
+    state_root_hash: Option<Digest>,
+}
+
+#[derive(Deserialize)]
+struct GetTrieResult {
+    maybe_trie_bytes: Option<Bytes>,
+}
+
+impl NodeClient {
+    fn new(address: &str) -> Self {
+        NodeClient {
+            client: Client::new(),
+            address: address.to_string(),
+        }
+    }
+
+    fn call<T: DeserializeOwned>(&self, method: &str, params: Option<Value>) -> T {
+        let mut request = json!({ "jsonrpc": "2.0", "id": 1, "method": method });
+        if let Some(params) = params {
+            request["params"] = params;
+        }
+        let response: RpcResponse<T> = self
+            .client
+            .post(&self.address)
+            .json(&request)
+            .send()
+            .and_then(|response| response.json())
+            .unwrap_or_else(|error| {
+                panic!("should call {} on {}: {}", method, self.address, error)
+            });
+        if let Some(RpcError { code, message }) = response.error {
+            panic!(
+                "{} on {} failed with code {}: {}",
+                method, self.address, code, message
+            );
+        }
+        response
+            .result
+            .unwrap_or_else(|| panic!("{} on {} returned no result", method, self.address))
+    }
+
+    fn state_root_hash(&self, block: ForkBlock) -> Digest {
+        let params = match block {
+            ForkBlock::Latest => None,
+            ForkBlock::Hash(block_hash) => {
+                Some(json!({ "block_identifier": { "Hash": block_hash } }))
+            }
+            ForkBlock::Height(height) => Some(json!({ "block_identifier": { "Height": height } })),
+        };
+        self.call::<GetStateRootHashResult>("chain_get_state_root_hash", params)
+            .state_root_hash
+            .unwrap_or_else(|| panic!("{} has no state root hash for {:?}", self.address, block))
+    }
+
+    fn trie(&self, trie_key: Digest) -> Option<Bytes> {
+        self.call::<GetTrieResult>("state_get_trie", Some(json!({ "trie_key": trie_key })))
+            .maybe_trie_bytes
+    }
+}
+
+/// The state shared by a [`ForkedGlobalState`] and its views.
+struct Fork {
+    global_state: LmdbGlobalState,
+    node: NodeClient,
+}
+
+impl Fork {
+    /// Returns the trie under `trie_key`, fetching it from the node if it is not cached.
+    ///
+    /// Returns `None` if the node does not have the trie either.
+    fn trie(&self, trie_key: Digest) -> Result<Option<Trie<Key, StoredValue>>, error::Error> {
+        let correlation_id = CorrelationId::new();
+        if let Some(trie_bytes) = self.global_state.get_trie_full(correlation_id, &trie_key)? {
+            return Ok(Some(bytesrepr::deserialize_from_slice(trie_bytes)?));
+        }
+        let trie_bytes = match self.node.trie(trie_key) {
+            Some(trie_bytes) => trie_bytes,
+            None => return Ok(None),
+        };
+        let trie = bytesrepr::deserialize_from_slice(&trie_bytes)?;
+        let stored_key = self.global_state.put_trie(correlation_id, &trie_bytes)?;
+        assert_eq!(
+            stored_key, trie_key,
+            "trie fetched from {} does not match its key",
+            self.node.address
+        );
+        debug!("fetched trie {} from {}", trie_key, self.node.address);
+        Ok(Some(trie))
+    }
+
+    /// Ensures all tries on the path from `root` towards `path` are cached.
+    ///
+    /// Returns the key of the trie at the end of the path, if the path exists.
+    fn fetch_path(&self, root: Digest, path: &[u8]) -> Result<Option<Digest>, error::Error> {
+        let mut trie_key = root;
+        let mut depth = 0;
+        loop {
+            let trie = match self.trie(trie_key)? {
+                Some(trie) => trie,
+                None => return Ok(None),
+            };
+            if depth >= path.len() {
+                return Ok(Some(trie_key));
+            }
+            match trie {
+                Trie::Leaf { .. } => return Ok(Some(trie_key)),
+                Trie::Node { pointer_block } => match pointer_block[usize::from(path[depth])] {
+                    Some(pointer) => {
+                        trie_key = pointer.into_hash();
+                        depth += 1;
+                    }
+                    None => return Ok(None),
+                },
+                Trie::Extension { affix, pointer } => {
+                    let remaining = &path[depth..];
+                    let affix_len = affix.len().min(remaining.len());
+                    if affix[..affix_len] != remaining[..affix_len] {
+                        return Ok(None);
+                    }
+                    trie_key = pointer.into_hash();
+                    depth += affix.len();
+                }
+            }
+        }
+    }
+
+    /// Ensures the trie under `trie_key` and all of its descendants are cached.
+    fn fetch_subtrie(&self, trie_key: Digest) -> Result<(), error::Error> {
+        let mut pending = vec![trie_key];
+        while let Some(trie_key) = pending.pop() {
+            if let Some(trie) = self.trie(trie_key)? {
+                pending.extend(trie.iter_descendants());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Global state forked from a running node, fetching tries lazily and caching them in LMDB.
+///
+/// # Panics
+///
+/// Reading or writing global state panics if a missing trie cannot be fetched from the node.
+pub struct ForkedGlobalState {
+    fork: Arc<Fork>,
+}
+
+/// A view of [`ForkedGlobalState`] at a particular root hash.
+pub struct ForkedGlobalStateView {
+    fork: Arc<Fork>,
+    view: LmdbGlobalStateView,
+    root_hash: Digest,
+}
+
+impl ForkedGlobalState {
+    /// Creates a global state forked from the node with the given RPC address, e.g.
+    /// `http://localhost:7777/rpc`, caching the fetched tries in `cache`.
+    pub(crate) fn new(cache: LmdbGlobalState, node_address: &str) -> Self {
+        ForkedGlobalState {
+            fork: Arc::new(Fork {
+                global_state: cache,
+                node: NodeClient::new(node_address),
+            }),
+        }
+    }
+
+    /// Returns the state root hash of the given block of the node.
+    pub(crate) fn state_root_hash(&self, block: ForkBlock) -> Digest {
+        self.fork.node.state_root_hash(block)
+    }
+}
+
+impl Debug for ForkedGlobalState {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("ForkedGlobalState")
+            .field("node_address", &self.fork.node.address)
+            .finish()
+    }
+}
+
+impl StateReader<Key, StoredValue> for ForkedGlobalStateView {
+    type Error = error::Error;
+
+    fn read(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, Self::Error> {
+        self.fork.fetch_path(self.root_hash, &key.to_bytes()?)?;
+        self.view.read(correlation_id, key)
+    }
+
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        self.fork.fetch_path(self.root_hash, &key.to_bytes()?)?;
+        self.view.read_with_proof(correlation_id, key)
+    }
+
+    fn keys_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+    ) -> Result<Vec<Key>, Self::Error> {
+        if let Some(trie_key) = self.fork.fetch_path(self.root_hash, prefix)? {
+            self.fork.fetch_subtrie(trie_key)?;
+        }
+        self.view.keys_with_prefix(correlation_id, prefix)
+    }
+}
+
+impl StateProvider for ForkedGlobalState {
+    type Error = error::Error;
+
+    type Reader = ForkedGlobalStateView;
+
+    fn checkout(&self, state_hash: Digest) -> Result<Option<Self::Reader>, Self::Error> {
+        if self.fork.trie(state_hash)?.is_none() {
+            return Ok(None);
+        }
+        Ok(self
+            .fork
+            .global_state
+            .checkout(state_hash)?
+            .map(|view| ForkedGlobalStateView {
+                fork: Arc::clone(&self.fork),
+                view,
+                root_hash: state_hash,
+            }))
+    }
+
+    fn empty_root(&self) -> Digest {
+        self.fork.global_state.empty_root()
+    }
+
+    fn get_trie(
+        &self,
+        correlation_id: CorrelationId,
+        trie_or_chunk_id: TrieOrChunkId,
+    ) -> Result<Option<TrieOrChunk>, Self::Error> {
+        self.fork.trie(*trie_or_chunk_id.digest())?;
+        self.fork
+            .global_state
+            .get_trie(correlation_id, trie_or_chunk_id)
+    }
+
+    fn get_trie_full(
+        &self,
+        correlation_id: CorrelationId,
+        trie_key: &Digest,
+    ) -> Result<Option<Bytes>, Self::Error> {
+        self.fork.trie(*trie_key)?;
+        self.fork
+            .global_state
+            .get_trie_full(correlation_id, trie_key)
+    }
+
+    fn put_trie(&self, correlation_id: CorrelationId, trie: &[u8]) -> Result<Digest, Self::Error> {
+        self.fork.global_state.put_trie(correlation_id, trie)
+    }
+
+    /// Returns the keys of the tries which are missing from the local cache.
+    ///
+    /// Nothing is fetched from the node, as missing tries are fetched on demand anyway.
+    fn missing_trie_keys(
+        &self,
+        correlation_id: CorrelationId,
+        trie_keys: Vec<Digest>,
+    ) -> Result<Vec<Digest>, Self::Error> {
+        self.fork
+            .global_state
+            .missing_trie_keys(correlation_id, trie_keys)
+    }
+}
+
+impl CommitProvider for ForkedGlobalState {
+    fn commit(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Digest,
+        effects: AdditiveMap<Key, Transform>,
+    ) -> Result<Digest, Self::Error> {
+        for key in effects.keys() {
+            self.fork.fetch_path(state_hash, &key.to_bytes()?)?;
+        }
+        self.fork
+            .global_state
+            .commit(correlation_id, state_hash, effects)
+    }
+}
@@ -0,0 +1,129 @@
+//! A process-wide cache of genesis results shared by all `WasmTestBuilder`s.
+//!
+//! Running genesis is the most expensive part of setting up most tests, yet nearly all of them run
+//! one of a handful of identical genesis requests.  The first builder to run a given request
+//! records the resulting tries, and later builders copy those tries into their own, isolated
+//! global state rather than executing genesis again.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use once_cell::sync::Lazy;
+
+use casper_execution_engine::{
+    core::engine_state::{EngineState, Error, RunGenesisRequest, SystemContractRegistry},
+    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+    storage::{global_state::StateProvider, trie::Trie},
+};
+use casper_hashing::Digest;
+use casper_types::{
+    account::Account,
+    bytesrepr::{self, Bytes},
+    Key, StoredValue,
+};
+
+/// Cached genesis results, keyed by the hash of the genesis request.
+static CACHE: Lazy<Mutex<HashMap<Digest, Arc<CachedGenesis>>>> = Lazy::new(Default::default);
+
+/// The results of running a genesis request, along with all tries under its post state hash.
+pub(crate) struct CachedGenesis {
+    pub(crate) post_state_hash: Digest,
+    pub(crate) transforms: AdditiveMap<Key, Transform>,
+    pub(crate) genesis_account: Account,
+    pub(crate) system_contract_registry: SystemContractRegistry,
+    /// Serialized tries, each preceded by all of its descendants.
+    tries: Vec<Bytes>,
+}
+
+impl CachedGenesis {
+    /// Collects the tries under `post_state_hash` from `engine_state`.
+    pub(crate) fn new<S>(
+        engine_state: &EngineState<S>,
+        post_state_hash: Digest,
+        transforms: AdditiveMap<Key, Transform>,
+        genesis_account: Account,
+        system_contract_registry: SystemContractRegistry,
+    ) -> Self
+    where
+        S: StateProvider,
+        Error: From<S::Error>,
+    {
+        let correlation_id = CorrelationId::new();
+        let mut tries = Vec::new();
+        let mut visited = HashSet::new();
+        // Depth-first, post-order traversal so that descendants are written before their parents.
+        // A trie is pushed a second time, with its bytes, once its descendants have been pushed.
+        let mut stack: Vec<(Digest, Option<Bytes>)> = vec![(post_state_hash, None)];
+        while let Some((trie_key, maybe_trie_bytes)) = stack.pop() {
+            if let Some(trie_bytes) = maybe_trie_bytes {
+                tries.push(trie_bytes);
+                continue;
+            }
+            if !visited.insert(trie_key) {
+                continue;
+            }
+            let trie_bytes = engine_state
+                .get_trie_full(correlation_id, trie_key)
+                .expect("should read trie")
+                .unwrap_or_else(|| panic!("genesis trie {} should exist", trie_key));
+            let trie: Trie<Key, StoredValue> =
+                bytesrepr::deserialize(trie_bytes.inner_bytes().clone())
+                    .expect("should parse trie");
+            stack.push((trie_key, Some(trie_bytes)));
+            stack.extend(
+                trie.iter_descendants()
+                    .filter(|descendant| !visited.contains(descendant))
+                    .map(|descendant| (descendant, None)),
+            );
+        }
+
+        CachedGenesis {
+            post_state_hash,
+            transforms,
+            genesis_account,
+            system_contract_registry,
+            tries,
+        }
+    }
+
+    /// Copies the cached tries into the global state of `engine_state`.
+    pub(crate) fn copy_tries_into<S>(&self, engine_state: &EngineState<S>)
+    where
+        S: StateProvider,
+        Error: From<S::Error>,
+    {
+        let correlation_id = CorrelationId::new();
+        for trie_bytes in &self.tries {
+            engine_state
+                .put_trie(correlation_id, trie_bytes)
+                .expect("should put trie");
+        }
+    }
+}
+
+/// Returns the hash identifying `run_genesis_request` in the cache.
+pub(crate) fn request_hash(run_genesis_request: &RunGenesisRequest) -> Digest {
+    let serialized =
+        serde_json::to_vec(run_genesis_request).expect("should serialize genesis request");
+    Digest::hash(serialized)
+}
+
+/// Returns the cached results of the genesis request with the given hash, if any.
+pub(crate) fn get(request_hash: &Digest) -> Option<Arc<CachedGenesis>> {
+    CACHE
+        .lock()
+        .expect("genesis cache lock should not be poisoned")
+        .get(request_hash)
+        .cloned()
+}
+
+/// Caches the results of the genesis request with the given hash.
+pub(crate) fn insert(request_hash: Digest, cached_genesis: CachedGenesis) {
+    CACHE
+        .lock()
+        .expect("genesis cache lock should not be poisoned")
+        .entry(request_hash)
+        .or_insert_with(|| Arc::new(cached_genesis));
+}
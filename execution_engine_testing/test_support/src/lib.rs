@@ -8,15 +8,22 @@
 )]
 #![warn(missing_docs)]
 mod additive_map_diff;
+pub mod assertions;
 /// Utility methods for running the auction in a test or bench context.
 pub mod auction;
+mod chainspec_builder;
 mod chainspec_config;
 mod deploy_item_builder;
 mod execute_request_builder;
+#[cfg(feature = "fork")]
+mod fork;
+mod genesis_cache;
+pub mod snapshot;
 mod step_request_builder;
 /// Utilities for running transfers in a test or bench context.
 pub mod transfer;
 mod upgrade_request_builder;
+pub mod upgrade_scenario;
 pub mod utils;
 mod wasm_test_builder;
 
@@ -34,12 +41,20 @@ use casper_types::{account::AccountHash, Motes, ProtocolVersion, PublicKey, Secr
 
 use crate::chainspec_config::PRODUCTION_PATH;
 pub use additive_map_diff::AdditiveMapDiff;
+pub use assertions::{BalanceDelta, BalanceHolder, BalanceSnapshot};
+pub use chainspec_builder::ChainspecBuilder;
 pub use chainspec_config::ChainspecConfig;
-pub use deploy_item_builder::DeployItemBuilder;
+pub use deploy_item_builder::{DeployItemBuilder, PinnedDeployHashes};
 pub use execute_request_builder::ExecuteRequestBuilder;
+#[cfg(feature = "fork")]
+pub use fork::{ForkBlock, ForkedGlobalState, ForkedGlobalStateView};
 pub use step_request_builder::StepRequestBuilder;
 pub use upgrade_request_builder::UpgradeRequestBuilder;
-pub use wasm_test_builder::{InMemoryWasmTestBuilder, LmdbWasmTestBuilder, WasmTestBuilder};
+#[cfg(feature = "fork")]
+pub use wasm_test_builder::ForkedWasmTestBuilder;
+pub use wasm_test_builder::{
+    InMemoryWasmTestBuilder, LmdbWasmTestBuilder, SnapshotWasmTestBuilder, WasmTestBuilder,
+};
 
 const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
 
@@ -0,0 +1,155 @@
+//! Golden-file snapshots of execution results.
+//!
+//! The results of an exec, i.e. its effects, transfers, gas costs, failure and emitted events,
+//! are rendered as pretty-printed JSON and compared against a snapshot file committed alongside
+//! the tests, under `snapshots/` in the directory of the crate running them.
+//!
+//! When a snapshot does not match, or does not exist yet, the actual results are written next to
+//! it with a `.new.json` extension and the assertion fails.  Once the change is reviewed, the
+//! snapshots can be updated by running the tests again with the environment variable
+//! [`UPDATE_SNAPSHOTS_ENV_VAR`] set.
+//!
+//! Snapshots are only reproducible if the execs are, so deploys should be built with a fixed
+//! deploy hash, e.g. via `DeployItemBuilder::with_deploy_hash`, or with deploy hashes pinned via
+//! [`PinnedDeployHashes`](crate::PinnedDeployHashes).
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use casper_types::ExecutionResultV2;
+
+/// The environment variable which, if set, causes mismatching or missing snapshots to be
+/// overwritten with the actual results rather than failing.
+pub const UPDATE_SNAPSHOTS_ENV_VAR: &str = "CASPER_UPDATE_SNAPSHOTS";
+
+/// The directory holding the snapshots, relative to the directory of the crate running the tests.
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// The extension of the files holding snapshots.
+const SNAPSHOT_EXTENSION: &str = "json";
+
+/// The extension of the files holding actual results which do not match their snapshot.
+const NEW_SNAPSHOT_EXTENSION: &str = "new.json";
+
+/// Renders `results` in the canonical form used for snapshots.
+fn render(results: &[ExecutionResultV2]) -> String {
+    let mut rendered =
+        serde_json::to_string_pretty(results).expect("should serialize execution results");
+    rendered.push('\n');
+    rendered
+}
+
+/// Returns the path of the snapshot called `name`.
+fn snapshot_path(name: &str) -> PathBuf {
+    let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+        .expect("snapshots require CARGO_MANIFEST_DIR to be set, e.g. by cargo test");
+    Path::new(&manifest_dir)
+        .join(SNAPSHOTS_DIR)
+        .join(format!("{}.{}", name, SNAPSHOT_EXTENSION))
+}
+
+/// Asserts that `results` match the snapshot called `name`.
+///
+/// # Panics
+///
+/// Panics if the snapshot does not match or does not exist, unless [`UPDATE_SNAPSHOTS_ENV_VAR`]
+/// is set.
+pub(crate) fn assert_snapshot(name: &str, results: &[ExecutionResultV2]) {
+    let update = env::var_os(UPDATE_SNAPSHOTS_ENV_VAR).is_some();
+    if let Err(message) = check_snapshot(&snapshot_path(name), &render(results), update) {
+        panic!("{}", message);
+    }
+}
+
+/// Compares `actual` with the snapshot at `path`, overwriting it instead if `update` is set.
+///
+/// On a mismatch, `actual` is written next to the snapshot and a description of the mismatch is
+/// returned.
+fn check_snapshot(path: &Path, actual: &str, update: bool) -> Result<(), String> {
+    let new_path = path.with_extension(NEW_SNAPSHOT_EXTENSION);
+    let write = |target: &Path| {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .unwrap_or_else(|error| panic!("should create {}: {}", parent.display(), error));
+        }
+        fs::write(target, actual)
+            .unwrap_or_else(|error| panic!("should write {}: {}", target.display(), error));
+    };
+
+    let expected = fs::read_to_string(path).ok();
+    if expected.as_deref() == Some(actual) {
+        let _ = fs::remove_file(&new_path);
+        return Ok(());
+    }
+    if update {
+        write(path);
+        let _ = fs::remove_file(&new_path);
+        return Ok(());
+    }
+
+    write(&new_path);
+    let mismatch = match expected {
+        None => "does not exist".to_string(),
+        Some(expected) => {
+            let line = expected
+                .lines()
+                .zip(actual.lines())
+                .position(|(expected_line, actual_line)| expected_line != actual_line)
+                .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+            format!("differs from the actual results from line {}", line + 1)
+        }
+    };
+    Err(format!(
+        "snapshot {} {}\nactual results written to {}\nreview the changes and rerun with {} set \
+        to accept them",
+        path.display(),
+        mismatch,
+        new_path.display(),
+        UPDATE_SNAPSHOTS_ENV_VAR
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::check_snapshot;
+
+    #[test]
+    fn should_fail_on_missing_snapshot_unless_updating() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/exec.json");
+        let new_path = dir.path().join("nested/exec.new.json");
+
+        let error = check_snapshot(&path, "actual\n", false).unwrap_err();
+        assert!(error.contains("does not exist"), "{}", error);
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(&new_path).unwrap(), "actual\n");
+
+        check_snapshot(&path, "actual\n", true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "actual\n");
+        assert!(!new_path.exists());
+    }
+
+    #[test]
+    fn should_report_first_mismatching_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exec.json");
+        fs::write(&path, "a\nb\nc\n").unwrap();
+
+        check_snapshot(&path, "a\nb\nc\n", false).unwrap();
+
+        let error = check_snapshot(&path, "a\nx\nc\n", false).unwrap_err();
+        assert!(error.contains("from line 2"), "{}", error);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\nc\n");
+
+        let error = check_snapshot(&path, "a\nb\n", false).unwrap_err();
+        assert!(error.contains("from line 3"), "{}", error);
+
+        check_snapshot(&path, "a\nx\nc\n", true).unwrap();
+        check_snapshot(&path, "a\nx\nc\n", false).unwrap();
+        assert!(!dir.path().join("exec.new.json").exists());
+    }
+}
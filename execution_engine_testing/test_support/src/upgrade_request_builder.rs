@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use num_rational::Ratio;
 
-use casper_execution_engine::core::engine_state::{ChainspecRegistry, UpgradeConfig};
+use casper_execution_engine::core::engine_state::{ChainspecRegistry, Migration, UpgradeConfig};
 use casper_hashing::Digest;
 use casper_types::{EraId, Key, ProtocolVersion, StoredValue};
 
@@ -19,6 +19,7 @@ pub struct UpgradeRequestBuilder {
     new_unbonding_delay: Option<u64>,
     global_state_update: BTreeMap<Key, StoredValue>,
     chainspec_registry: ChainspecRegistry,
+    migrations: Vec<Migration>,
 }
 
 impl UpgradeRequestBuilder {
@@ -99,9 +100,15 @@ impl UpgradeRequestBuilder {
         self
     }
 
+    /// Sets the global state migrations to be applied, in order.
+    pub fn with_migrations(mut self, migrations: Vec<Migration>) -> Self {
+        self.migrations = migrations;
+        self
+    }
+
     /// Consumes the `UpgradeRequestBuilder` and returns an [`UpgradeConfig`].
     pub fn build(self) -> UpgradeConfig {
-        UpgradeConfig::new(
+        let mut upgrade_config = UpgradeConfig::new(
             self.pre_state_hash,
             self.current_protocol_version,
             self.new_protocol_version,
@@ -113,7 +120,9 @@ impl UpgradeRequestBuilder {
             self.new_unbonding_delay,
             self.global_state_update,
             self.chainspec_registry,
-        )
+        );
+        upgrade_config.with_migrations(self.migrations);
+        upgrade_config
     }
 }
 
@@ -131,6 +140,7 @@ impl Default for UpgradeRequestBuilder {
             new_unbonding_delay: None,
             global_state_update: Default::default(),
             chainspec_registry: ChainspecRegistry::new_with_optional_global_state(&[], None),
+            migrations: Vec::new(),
         }
     }
 }
@@ -0,0 +1,208 @@
+//! A harness for testing protocol upgrades end to end.
+//!
+//! An [`UpgradeScenario`] runs genesis under one protocol version, executes a workload, upgrades
+//! to another protocol version applying global state migrations, and asserts that the state of
+//! the tracked accounts and keys, including the balances of the accounts' main purses, survived
+//! the upgrade.  Since workloads are closures taking the protocol version to execute under,
+//! the same scenario can be run over any number of version pairs.
+//!
+//! ```no_run
+//! use casper_engine_test_support::upgrade_scenario::UpgradeScenario;
+//! use casper_types::ProtocolVersion;
+//!
+//! const VERSION_PAIRS: [(ProtocolVersion, ProtocolVersion); 2] = [
+//!     (ProtocolVersion::V1_0_0, ProtocolVersion::from_parts(1, 0, 1)),
+//!     (ProtocolVersion::V1_0_0, ProtocolVersion::from_parts(1, 1, 0)),
+//! ];
+//!
+//! for (from, to) in VERSION_PAIRS {
+//!     UpgradeScenario::new(from, to)
+//!         .with_workload(|_builder, _protocol_version| {
+//!             // Execute requests built with `_protocol_version` against `_builder`.
+//!         })
+//!         .run();
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use casper_execution_engine::core::engine_state::Migration;
+use casper_types::{
+    account::{Account, AccountHash},
+    EraId, Key, ProtocolVersion, StoredValue, U512,
+};
+
+use crate::{
+    ChainspecBuilder, InMemoryWasmTestBuilder, UpgradeRequestBuilder, DEFAULT_ACCOUNTS,
+    DEFAULT_ACCOUNT_ADDR, DEFAULT_PROPOSER_ADDR,
+};
+
+/// The default activation point of the upgrade.
+const DEFAULT_ACTIVATION_POINT: EraId = EraId::new(1);
+
+type Workload = Box<dyn Fn(&mut InMemoryWasmTestBuilder, ProtocolVersion)>;
+
+/// A protocol upgrade scenario, run by [`UpgradeScenario::run`].
+pub struct UpgradeScenario {
+    chainspec: ChainspecBuilder,
+    from: ProtocolVersion,
+    to: ProtocolVersion,
+    activation_point: EraId,
+    migrations: Vec<Migration>,
+    workloads: Vec<Workload>,
+    post_upgrade_workloads: Vec<Workload>,
+    tracked_accounts: Vec<AccountHash>,
+    tracked_keys: Vec<Key>,
+}
+
+/// The tracked state, recorded before and after the upgrade.
+#[derive(Debug, PartialEq)]
+struct TrackedState {
+    accounts: BTreeMap<AccountHash, Option<(Account, U512)>>,
+    keys: BTreeMap<Key, Option<StoredValue>>,
+}
+
+impl UpgradeScenario {
+    /// Returns a scenario upgrading the production chainspec from protocol version `from` to
+    /// protocol version `to`.
+    ///
+    /// The default genesis accounts, i.e. [`DEFAULT_ACCOUNT_ADDR`] and [`DEFAULT_PROPOSER_ADDR`],
+    /// are tracked.
+    pub fn new(from: ProtocolVersion, to: ProtocolVersion) -> Self {
+        UpgradeScenario {
+            chainspec: ChainspecBuilder::production(),
+            from,
+            to,
+            activation_point: DEFAULT_ACTIVATION_POINT,
+            migrations: Vec::new(),
+            workloads: Vec::new(),
+            post_upgrade_workloads: Vec::new(),
+            tracked_accounts: vec![*DEFAULT_ACCOUNT_ADDR, *DEFAULT_PROPOSER_ADDR],
+            tracked_keys: Vec::new(),
+        }
+    }
+
+    /// Sets the chainspec used for genesis and the upgrade.
+    pub fn with_chainspec(mut self, chainspec: ChainspecBuilder) -> Self {
+        self.chainspec = chainspec;
+        self
+    }
+
+    /// Sets the activation point of the upgrade.
+    pub fn with_activation_point(mut self, activation_point: EraId) -> Self {
+        self.activation_point = activation_point;
+        self
+    }
+
+    /// Sets the global state migrations to be applied by the upgrade, in order.
+    pub fn with_migrations(mut self, migrations: Vec<Migration>) -> Self {
+        self.migrations = migrations;
+        self
+    }
+
+    /// Adds a workload run after genesis and before the upgrade.
+    ///
+    /// The workload is passed the protocol version before the upgrade, with which it should build
+    /// its execute requests.
+    pub fn with_workload(
+        mut self,
+        workload: impl Fn(&mut InMemoryWasmTestBuilder, ProtocolVersion) + 'static,
+    ) -> Self {
+        self.workloads.push(Box::new(workload));
+        self
+    }
+
+    /// Adds a workload run after the upgrade and after the tracked state has been checked.
+    ///
+    /// The workload is passed the protocol version after the upgrade, and can also be used to make
+    /// further assertions about the upgraded state.
+    pub fn with_post_upgrade_workload(
+        mut self,
+        workload: impl Fn(&mut InMemoryWasmTestBuilder, ProtocolVersion) + 'static,
+    ) -> Self {
+        self.post_upgrade_workloads.push(Box::new(workload));
+        self
+    }
+
+    /// Tracks an account, asserting that it and the balance of its main purse survive the
+    /// upgrade.
+    pub fn track_account(mut self, account_hash: AccountHash) -> Self {
+        self.tracked_accounts.push(account_hash);
+        self
+    }
+
+    /// Tracks a key, asserting that the value under it survives the upgrade.
+    ///
+    /// Keys written or rewritten by the migrations should not be tracked.
+    pub fn track_key(mut self, key: Key) -> Self {
+        self.tracked_keys.push(key);
+        self
+    }
+
+    /// Runs the scenario, returning the builder holding the upgraded state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the upgrade fails or if the tracked state does not survive it.
+    pub fn run(self) -> InMemoryWasmTestBuilder {
+        let mut builder = InMemoryWasmTestBuilder::new_with_config(self.chainspec.engine_config());
+        builder.run_genesis(
+            &self
+                .chainspec
+                .genesis_request(DEFAULT_ACCOUNTS.clone(), self.from),
+        );
+
+        for workload in &self.workloads {
+            workload(&mut builder, self.from);
+        }
+
+        let state_before = self.tracked_state(&builder);
+
+        let mut upgrade_request = UpgradeRequestBuilder::new()
+            .with_current_protocol_version(self.from)
+            .with_new_protocol_version(self.to)
+            .with_activation_point(self.activation_point)
+            .with_migrations(self.migrations.clone())
+            .build();
+        builder.upgrade_with_upgrade_request(self.chainspec.engine_config(), &mut upgrade_request);
+        if let Some(Err(error)) = builder.get_upgrade_result(0) {
+            panic!(
+                "upgrade from {} to {} failed: {:?}",
+                self.from, self.to, error
+            );
+        }
+
+        let state_after = self.tracked_state(&builder);
+        assert_eq!(
+            state_before, state_after,
+            "tracked state did not survive the upgrade from {} to {}",
+            self.from, self.to
+        );
+
+        for workload in &self.post_upgrade_workloads {
+            workload(&mut builder, self.to);
+        }
+
+        builder
+    }
+
+    fn tracked_state(&self, builder: &InMemoryWasmTestBuilder) -> TrackedState {
+        let accounts = self
+            .tracked_accounts
+            .iter()
+            .map(|account_hash| {
+                let account = builder.get_account(*account_hash).map(|account| {
+                    let balance = builder.get_purse_balance(account.main_purse());
+                    (account, balance)
+                });
+                (*account_hash, account)
+            })
+            .collect();
+        let keys = self
+            .tracked_keys
+            .iter()
+            .map(|key| (*key, builder.query(None, *key, &[]).ok()))
+            .collect();
+        TrackedState { accounts, keys }
+    }
+}
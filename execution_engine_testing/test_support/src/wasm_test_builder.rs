@@ -7,6 +7,7 @@ use std::{
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
+    thread,
 };
 
 use filesize::PathExt;
@@ -17,22 +18,25 @@ use num_traits::CheckedMul;
 
 use casper_execution_engine::{
     core::{
+        self,
         engine_state::{
             self,
             era_validators::GetEraValidatorsRequest,
             execute_request::ExecuteRequest,
+            execution_effect::ExecutionEffect,
             execution_result::ExecutionResult,
             run_genesis_request::RunGenesisRequest,
             step::{StepRequest, StepSuccess},
             BalanceResult, EngineConfig, EngineState, Error, GenesisSuccess, GetBidsRequest,
             QueryRequest, QueryResult, RewardItem, StepError, SystemContractRegistry,
-            UpgradeConfig, UpgradeSuccess, DEFAULT_MAX_QUERY_DEPTH,
+            UpgradeConfig, UpgradeSuccess,
         },
         execution,
     },
     shared::{
         additive_map::AdditiveMap,
         execution_journal::ExecutionJournal,
+        gas_profile::{self, GasProfile},
         logging::{self, Settings, Style},
         newtypes::CorrelationId,
         system_config::{
@@ -67,13 +71,15 @@ use casper_types::{
         AUCTION, HANDLE_PAYMENT, MINT, STANDARD_PAYMENT,
     },
     CLTyped, CLValue, Contract, ContractHash, ContractPackage, ContractPackageHash, ContractWasm,
-    DeployHash, DeployInfo, EraId, Gas, Key, KeyTag, ProtocolVersion, PublicKey, RuntimeArgs,
-    StoredValue, Transfer, TransferAddr, URef, U512,
+    DeployHash, DeployInfo, EraId, ExecutionResultV2, Gas, Key, KeyTag, ProtocolVersion, PublicKey,
+    RuntimeArgs, StoredValue, Transfer, TransferAddr, TransferV2, URef, U512,
 };
 
+#[cfg(feature = "fork")]
+use crate::fork::{ForkBlock, ForkedGlobalState};
 use crate::{
-    chainspec_config::{ChainspecConfig, PRODUCTION_PATH},
-    utils, ExecuteRequestBuilder, StepRequestBuilder, DEFAULT_PROPOSER_ADDR,
+    chainspec_config::PRODUCTION_PATH, deploy_item_builder, genesis_cache, snapshot, utils,
+    ChainspecBuilder, ExecuteRequestBuilder, StepRequestBuilder, DEFAULT_PROPOSER_ADDR,
     DEFAULT_PROTOCOL_VERSION, SYSTEM_ADDR,
 };
 
@@ -92,6 +98,12 @@ const GLOBAL_STATE_DIR: &str = "global_state";
 pub type InMemoryWasmTestBuilder = WasmTestBuilder<InMemoryGlobalState>;
 /// Wasm test builder where state is held in LMDB.
 pub type LmdbWasmTestBuilder = WasmTestBuilder<LmdbGlobalState>;
+/// Wasm test builder where state is read from an existing LMDB global state opened read-only, and
+/// all changes are held in memory.
+pub type SnapshotWasmTestBuilder = WasmTestBuilder<ScratchGlobalState>;
+/// Wasm test builder where state is fetched lazily from a running node and cached in LMDB.
+#[cfg(feature = "fork")]
+pub type ForkedWasmTestBuilder = WasmTestBuilder<ForkedGlobalState>;
 
 /// Builder for simple WASM test
 pub struct WasmTestBuilder<S> {
@@ -117,6 +129,16 @@ pub struct WasmTestBuilder<S> {
     system_contract_registry: Option<SystemContractRegistry>,
     /// Global state dir, for implementations that define one.
     global_state_dir: Option<PathBuf>,
+    /// Whether subsequent execs are profiled.
+    gas_profiling: bool,
+    /// Gas profiles of execs, i.e. `gas_profiles[0]` is for first exec call etc.
+    gas_profiles: Vec<Option<GasProfile>>,
+    /// Whether the Merkle proofs of query and balance results are validated.
+    proof_validation: bool,
+    /// Whether genesis results are shared with other builders via the genesis cache.
+    genesis_cache: bool,
+    /// Number of deploys re-executed by the last optimistic exec due to conflicts.
+    optimistic_reexecutions: usize,
 }
 
 impl<S> WasmTestBuilder<S> {
@@ -148,6 +170,19 @@ impl<S> Clone for WasmTestBuilder<S> {
             scratch_engine_state: None,
             system_contract_registry: self.system_contract_registry.clone(),
             global_state_dir: self.global_state_dir.clone(),
+            gas_profiling: self.gas_profiling,
+            gas_profiles: self.gas_profiles.clone(),
+            proof_validation: self.proof_validation,
+            genesis_cache: self.genesis_cache,
+            optimistic_reexecutions: self.optimistic_reexecutions,
+        }
+    }
+}
+
+impl<S> Drop for WasmTestBuilder<S> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            deploy_item_builder::report_test_seed();
         }
     }
 }
@@ -175,6 +210,11 @@ impl InMemoryWasmTestBuilder {
             scratch_engine_state: None,
             system_contract_registry: None,
             global_state_dir: None,
+            gas_profiling: false,
+            gas_profiles: Vec::new(),
+            proof_validation: true,
+            genesis_cache: true,
+            optimistic_reexecutions: 0,
         }
     }
 
@@ -198,6 +238,11 @@ impl InMemoryWasmTestBuilder {
             scratch_engine_state: None,
             system_contract_registry: None,
             global_state_dir: None,
+            gas_profiling: false,
+            gas_profiles: Vec::new(),
+            proof_validation: true,
+            genesis_cache: true,
+            optimistic_reexecutions: 0,
         }
     }
 
@@ -206,24 +251,9 @@ impl InMemoryWasmTestBuilder {
         chainspec_path: P,
         post_state_hash: Option<Digest>,
     ) -> Self {
-        let chainspec_config = ChainspecConfig::from_chainspec_path(chainspec_path)
-            .expect("must build chainspec configuration");
-
-        let vesting_schedule_period_millis =
-            humantime::parse_duration(&chainspec_config.core_config.vesting_schedule_period)
-                .expect("should parse a vesting schedule period")
-                .as_millis() as u64;
-
-        let engine_config = EngineConfig::new(
-            DEFAULT_MAX_QUERY_DEPTH,
-            chainspec_config.core_config.max_associated_keys,
-            chainspec_config.core_config.max_runtime_call_stack_height,
-            chainspec_config.core_config.minimum_delegation_amount,
-            chainspec_config.core_config.strict_argument_checking,
-            vesting_schedule_period_millis,
-            chainspec_config.wasm_config,
-            chainspec_config.system_costs_config,
-        );
+        let engine_config = ChainspecBuilder::from_chainspec_path(chainspec_path)
+            .expect("must build chainspec configuration")
+            .engine_config();
 
         let global_state = InMemoryGlobalState::empty().expect("should create global state");
 
@@ -270,6 +300,11 @@ impl LmdbWasmTestBuilder {
             scratch_engine_state: None,
             system_contract_registry: None,
             global_state_dir: Some(global_state_dir),
+            gas_profiling: false,
+            gas_profiles: Vec::new(),
+            proof_validation: true,
+            genesis_cache: true,
+            optimistic_reexecutions: 0,
         }
     }
 
@@ -279,23 +314,9 @@ impl LmdbWasmTestBuilder {
         data_dir: &T,
         chainspec_path: P,
     ) -> Self {
-        let chainspec_config = ChainspecConfig::from_chainspec_path(chainspec_path)
-            .expect("must build chainspec configuration");
-        let vesting_schedule_period_millis =
-            humantime::parse_duration(&chainspec_config.core_config.vesting_schedule_period)
-                .expect("should parse a vesting schedule period")
-                .as_millis() as u64;
-
-        let engine_config = EngineConfig::new(
-            DEFAULT_MAX_QUERY_DEPTH,
-            chainspec_config.core_config.max_associated_keys,
-            chainspec_config.core_config.max_runtime_call_stack_height,
-            chainspec_config.core_config.minimum_delegation_amount,
-            chainspec_config.core_config.strict_argument_checking,
-            vesting_schedule_period_millis,
-            chainspec_config.wasm_config,
-            chainspec_config.system_costs_config,
-        );
+        let engine_config = ChainspecBuilder::from_chainspec_path(chainspec_path)
+            .expect("must build chainspec configuration")
+            .engine_config();
 
         Self::new_with_config(data_dir, engine_config)
     }
@@ -366,6 +387,11 @@ impl LmdbWasmTestBuilder {
             scratch_engine_state: None,
             system_contract_registry: None,
             global_state_dir: Some(global_state_dir.as_ref().to_path_buf()),
+            gas_profiling: false,
+            gas_profiles: Vec::new(),
+            proof_validation: true,
+            genesis_cache: true,
+            optimistic_reexecutions: 0,
         }
     }
 
@@ -415,7 +441,9 @@ impl LmdbWasmTestBuilder {
 
         let mut exec_results = Vec::new();
         // First execute the request against our scratch global state.
-        let maybe_exec_results = cached_state.run_execute(CorrelationId::new(), exec_request);
+        let (maybe_exec_results, gas_profile) = gas_profile::profiled(self.gas_profiling, || {
+            cached_state.run_execute(CorrelationId::new(), exec_request)
+        });
         for execution_result in maybe_exec_results.unwrap() {
             let journal = execution_result.execution_journal().clone();
             let transforms: AdditiveMap<Key, Transform> = journal.clone().into();
@@ -432,9 +460,64 @@ impl LmdbWasmTestBuilder {
             exec_results.push(Rc::new(execution_result))
         }
         self.exec_results.push(exec_results);
+        self.gas_profiles.push(gas_profile);
+        self
+    }
+
+    /// Executes the given requests optimistically on up to `threads` threads and commits their
+    /// transforms into a scratch global state, in the order of the requests.
+    /// You MUST call write_scratch_to_db to flush these changes to LmdbGlobalState.
+    ///
+    /// Gas is not profiled for these execs.
+    pub fn scratch_exec_and_commit_optimistically(
+        &mut self,
+        exec_requests: Vec<ExecuteRequest>,
+        threads: usize,
+    ) -> &mut Self {
+        if self.scratch_engine_state.is_none() {
+            self.scratch_engine_state = Some(self.engine_state.get_scratch_engine_state());
+        }
+
+        let cached_state = self
+            .scratch_engine_state
+            .as_ref()
+            .expect("scratch state should exist");
+        let post_state_hash = self.post_state_hash.expect("expected post_state_hash");
+
+        let exec_requests = exec_requests
+            .into_iter()
+            .map(|mut exec_request| {
+                exec_request.parent_state_hash = post_state_hash;
+                exec_request
+            })
+            .collect();
+
+        let mut execution =
+            cached_state.execute_optimistically(CorrelationId::new(), exec_requests, threads);
+        for maybe_exec_results in execution.by_ref() {
+            let mut exec_results = Vec::new();
+            for execution_result in maybe_exec_results.unwrap() {
+                let journal = execution_result.execution_journal().clone();
+                let transforms: AdditiveMap<Key, Transform> = journal.clone().into();
+                let _post_state_hash = cached_state
+                    .apply_effect(CorrelationId::new(), post_state_hash, transforms)
+                    .expect("should commit");
+
+                self.transforms.push(journal);
+                exec_results.push(Rc::new(execution_result))
+            }
+            self.exec_results.push(exec_results);
+            self.gas_profiles.push(None);
+        }
+        self.optimistic_reexecutions = execution.reexecuted();
         self
     }
 
+    /// Returns the number of deploys re-executed by the last optimistic exec due to conflicts.
+    pub fn get_optimistic_reexecutions(&self) -> usize {
+        self.optimistic_reexecutions
+    }
+
     /// Commit scratch to global state, and reset the scratch cache.
     pub fn write_scratch_to_db(&mut self) -> &mut Self {
         let prestate_hash = self.post_state_hash.expect("Should have genesis hash");
@@ -466,6 +549,160 @@ impl LmdbWasmTestBuilder {
     }
 }
 
+impl SnapshotWasmTestBuilder {
+    /// Returns a [`SnapshotWasmTestBuilder`] over the global state at `state_root` in an existing
+    /// LMDB database, such as a node's data directory or a copy of one.
+    ///
+    /// `global_state_dir` is the directory containing the `data.lmdb` file.  The database is opened
+    /// read-only and is never modified: the effects of executed deploys are committed to in-memory
+    /// scratch state layered over it, so regression tests can replay deploys against real state.
+    pub fn from_lmdb_snapshot<T: AsRef<Path>>(global_state_dir: T, state_root: Digest) -> Self {
+        Self::from_lmdb_snapshot_with_config(global_state_dir, EngineConfig::default(), state_root)
+    }
+
+    /// Returns a [`SnapshotWasmTestBuilder`] over the global state at `state_root` in an existing
+    /// LMDB database, using the supplied engine configuration.
+    ///
+    /// See [`from_lmdb_snapshot`](Self::from_lmdb_snapshot) for details.
+    pub fn from_lmdb_snapshot_with_config<T: AsRef<Path>>(
+        global_state_dir: T,
+        engine_config: EngineConfig,
+        state_root: Digest,
+    ) -> Self {
+        Self::initialize_logging();
+        let global_state_dir = global_state_dir.as_ref();
+        let environment = Arc::new(
+            LmdbEnvironment::open_read_only(global_state_dir, DEFAULT_MAX_READERS).unwrap_or_else(
+                |error| {
+                    panic!(
+                        "should open LmdbEnvironment at {}: {}",
+                        global_state_dir.display(),
+                        error
+                    )
+                },
+            ),
+        );
+        let trie_store =
+            Arc::new(LmdbTrieStore::open(&environment, None).expect("should open LmdbTrieStore"));
+
+        let global_state = ScratchGlobalState::open(environment, trie_store)
+            .expect("should create ScratchGlobalState");
+        let engine_state = EngineState::new(global_state, engine_config);
+        let system_contract_registry = engine_state
+            .get_system_contract_registry(CorrelationId::new(), state_root)
+            .unwrap_or_else(|error| {
+                panic!(
+                    "should read system contract registry under {}: {}",
+                    state_root, error
+                )
+            });
+        WasmTestBuilder {
+            engine_state: Rc::new(engine_state),
+            exec_results: Vec::new(),
+            upgrade_results: Vec::new(),
+            genesis_hash: None,
+            post_state_hash: Some(state_root),
+            transforms: Vec::new(),
+            genesis_account: None,
+            genesis_transforms: None,
+            scratch_engine_state: None,
+            system_contract_registry: Some(system_contract_registry),
+            global_state_dir: Some(global_state_dir.to_path_buf()),
+            gas_profiling: false,
+            gas_profiles: Vec::new(),
+            // Values written to the scratch state are not hashed into tries, so can't be proven.
+            proof_validation: false,
+            // Tries can't be copied into the read-only snapshot.
+            genesis_cache: false,
+            optimistic_reexecutions: 0,
+        }
+    }
+}
+
+#[cfg(feature = "fork")]
+impl ForkedWasmTestBuilder {
+    /// Returns a [`ForkedWasmTestBuilder`] over the global state of `block` on the node whose
+    /// JSON-RPC server is at `node_address`, e.g. `http://localhost:7777/rpc`.
+    ///
+    /// Global state is fetched from the node lazily, one trie at a time, as it is read or
+    /// written.  Fetched tries are cached in an LMDB database under `data_dir`, so subsequent
+    /// runs using the same `data_dir` only fetch tries they have not seen before.  All changes are
+    /// written to the local database only.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node cannot be reached, or does not have the requested block.
+    pub fn fork_from_node<T: AsRef<OsStr> + ?Sized>(
+        data_dir: &T,
+        node_address: &str,
+        block: ForkBlock,
+    ) -> Self {
+        Self::fork_from_node_with_config(data_dir, node_address, block, EngineConfig::default())
+    }
+
+    /// Returns a [`ForkedWasmTestBuilder`] over the global state of `block` on the node whose
+    /// JSON-RPC server is at `node_address`, using the supplied engine configuration.
+    ///
+    /// See [`fork_from_node`](Self::fork_from_node) for details.
+    pub fn fork_from_node_with_config<T: AsRef<OsStr> + ?Sized>(
+        data_dir: &T,
+        node_address: &str,
+        block: ForkBlock,
+        engine_config: EngineConfig,
+    ) -> Self {
+        Self::initialize_logging();
+        let page_size = *OS_PAGE_SIZE;
+        let global_state_dir = LmdbWasmTestBuilder::global_state_dir(data_dir);
+        LmdbWasmTestBuilder::create_global_state_dir(&global_state_dir);
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                &global_state_dir,
+                page_size * DEFAULT_LMDB_PAGES,
+                DEFAULT_MAX_READERS,
+                true,
+            )
+            .expect("should create LmdbEnvironment"),
+        );
+        let trie_store = Arc::new(
+            LmdbTrieStore::new(&environment, None, DatabaseFlags::empty())
+                .expect("should create LmdbTrieStore"),
+        );
+        let cache =
+            LmdbGlobalState::empty(environment, trie_store).expect("should create LmdbGlobalState");
+
+        let global_state = ForkedGlobalState::new(cache, node_address);
+        let state_root = global_state.state_root_hash(block);
+        let engine_state = EngineState::new(global_state, engine_config);
+        let system_contract_registry = engine_state
+            .get_system_contract_registry(CorrelationId::new(), state_root)
+            .unwrap_or_else(|error| {
+                panic!(
+                    "should read system contract registry under {}: {}",
+                    state_root, error
+                )
+            });
+        WasmTestBuilder {
+            engine_state: Rc::new(engine_state),
+            exec_results: Vec::new(),
+            upgrade_results: Vec::new(),
+            genesis_hash: None,
+            post_state_hash: Some(state_root),
+            transforms: Vec::new(),
+            genesis_account: None,
+            genesis_transforms: None,
+            scratch_engine_state: None,
+            system_contract_registry: Some(system_contract_registry),
+            global_state_dir: Some(global_state_dir),
+            gas_profiling: false,
+            gas_profiles: Vec::new(),
+            proof_validation: true,
+            // The forked state starts from a block on the node rather than from genesis.
+            genesis_cache: false,
+            optimistic_reexecutions: 0,
+        }
+    }
+}
+
 impl<S> WasmTestBuilder<S>
 where
     S: StateProvider + CommitProvider,
@@ -473,7 +710,41 @@ where
     S::Error: Into<execution::Error>,
 {
     /// Takes a [`RunGenesisRequest`], executes the request and returns Self.
+    ///
+    /// Unless disabled via [`Self::with_genesis_cache`], the results of a request which has
+    /// already been run by another builder in this process are copied from the genesis cache
+    /// rather than executed again.
     pub fn run_genesis(&mut self, run_genesis_request: &RunGenesisRequest) -> &mut Self {
+        if !self.genesis_cache {
+            return self.execute_genesis(run_genesis_request);
+        }
+
+        let request_hash = genesis_cache::request_hash(run_genesis_request);
+        if let Some(cached_genesis) = genesis_cache::get(&request_hash) {
+            cached_genesis.copy_tries_into(&self.engine_state);
+            self.system_contract_registry = Some(cached_genesis.system_contract_registry.clone());
+            self.genesis_hash = Some(cached_genesis.post_state_hash);
+            self.post_state_hash = Some(cached_genesis.post_state_hash);
+            self.genesis_account = Some(cached_genesis.genesis_account.clone());
+            self.genesis_transforms = Some(cached_genesis.transforms.clone());
+            return self;
+        }
+
+        self.execute_genesis(run_genesis_request);
+        let cached_genesis = genesis_cache::CachedGenesis::new(
+            &self.engine_state,
+            self.get_post_state_hash(),
+            self.get_genesis_transforms().clone(),
+            self.get_genesis_account().clone(),
+            self.system_contract_registry
+                .clone()
+                .expect("genesis should set the system contract registry"),
+        );
+        genesis_cache::insert(request_hash, cached_genesis);
+        self
+    }
+
+    fn execute_genesis(&mut self, run_genesis_request: &RunGenesisRequest) -> &mut Self {
         let system_account = Key::Account(PublicKey::System.to_account_hash());
 
         let GenesisSuccess {
@@ -535,13 +806,37 @@ where
             .run_query(CorrelationId::new(), query_request)
             .expect("should get query response");
 
-        if let QueryResult::Success { value, .. } = query_result {
+        if let QueryResult::Success { value, proofs } = query_result {
+            self.validate_query_proofs(post_state, base_key, path, &value, &proofs);
             return Ok(value.deref().clone());
         }
 
         Err(format!("{:?}", query_result))
     }
 
+    /// Panics if proof validation is enabled and `proofs` don't prove `value` to be the result of
+    /// querying `base_key` and `path` under `state_root_hash`.
+    fn validate_query_proofs(
+        &self,
+        state_root_hash: Digest,
+        base_key: Key,
+        path: &[String],
+        value: &StoredValue,
+        proofs: &[TrieMerkleProof<Key, StoredValue>],
+    ) {
+        if !self.proof_validation {
+            return;
+        }
+        if let Err(error) =
+            core::validate_query_proof(&state_root_hash, proofs, &base_key, path, value)
+        {
+            panic!(
+                "invalid proofs of query of {} with path {:?} under state root hash {}: {}",
+                base_key, path, state_root_hash, error
+            );
+        }
+    }
+
     /// Queries state for a dictionary item.
     pub fn query_dictionary_item(
         &self,
@@ -576,6 +871,7 @@ where
             .expect("should get query response");
 
         if let QueryResult::Success { value, proofs } = query_result {
+            self.validate_query_proofs(post_state, base_key, path, &value, &proofs);
             return Ok((value.deref().clone(), proofs));
         }
 
@@ -659,9 +955,10 @@ where
             exec_request
         };
 
-        let maybe_exec_results = self
-            .engine_state
-            .run_execute(CorrelationId::new(), exec_request);
+        let (maybe_exec_results, gas_profile) = gas_profile::profiled(self.gas_profiling, || {
+            self.engine_state
+                .run_execute(CorrelationId::new(), exec_request)
+        });
         assert!(maybe_exec_results.is_ok());
         // Parse deploy results
         let execution_results = maybe_exec_results.as_ref().unwrap();
@@ -678,6 +975,7 @@ where
                 .map(Rc::new)
                 .collect(),
         );
+        self.gas_profiles.push(gas_profile);
         self
     }
 
@@ -740,6 +1038,20 @@ where
         self
     }
 
+    /// Runs an upgrade against the current post state hash without committing it.
+    ///
+    /// Returns the effects the upgrade, including its global state migrations, would have.
+    pub fn dry_run_upgrade(
+        &self,
+        upgrade_config: &mut UpgradeConfig,
+    ) -> Result<ExecutionEffect, engine_state::Error> {
+        let pre_state_hash = self.post_state_hash.expect("should have state hash");
+        upgrade_config.with_pre_state_hash(pre_state_hash);
+
+        self.engine_state
+            .dry_run_upgrade(CorrelationId::new(), upgrade_config)
+    }
+
     /// Executes a request to call the system auction contract.
     pub fn run_auction(
         &mut self,
@@ -948,6 +1260,89 @@ where
         self.exec_results.len()
     }
 
+    /// Asserts that the results of the exec at `index` match the snapshot called `name`.
+    ///
+    /// See the [`snapshot`](crate::snapshot) module for where snapshots are stored and how to
+    /// update them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no exec at `index`, or if its results do not match the snapshot.
+    pub fn assert_exec_snapshot(&self, index: usize, name: &str) -> &Self {
+        let exec_results = self
+            .get_exec_result(index)
+            .unwrap_or_else(|| panic!("should have exec result at index {}", index));
+        let results: Vec<ExecutionResultV2> = exec_results
+            .iter()
+            .map(|exec_result| ExecutionResultV2::from(exec_result.as_ref()))
+            .collect();
+        snapshot::assert_snapshot(name, &results);
+        self
+    }
+
+    /// Asserts that the results of the last exec match the snapshot called `name`.
+    ///
+    /// See [`assert_exec_snapshot`](Self::assert_exec_snapshot) for details.
+    pub fn assert_last_exec_snapshot(&self, name: &str) -> &Self {
+        let index = self
+            .exec_results
+            .len()
+            .checked_sub(1)
+            .expect("should have exec results");
+        self.assert_exec_snapshot(index, name)
+    }
+
+    /// Seeds the hashes given to deploys built on this thread without an explicit hash, restarting
+    /// their sequence.
+    ///
+    /// Unless pinned via [`PinnedDeployHashes`](crate::PinnedDeployHashes), these hashes are
+    /// otherwise derived from the env var `CASPER_TEST_SEED` if set, or from a random seed which
+    /// is printed if the test fails.  Passing the printed seed here replays the failing test's
+    /// deploy hashes.
+    pub fn with_test_seed(&mut self, seed: [u8; 16]) -> &mut Self {
+        deploy_item_builder::seed_deploy_hashes(seed);
+        self
+    }
+
+    /// Enables or disables validation of the Merkle proofs returned for queries and balance
+    /// requests against the state root hash they were made under.
+    ///
+    /// Validation is enabled by default, so that every test using this builder's query methods
+    /// panics on a malformed proof.  It is disabled by default for a [`SnapshotWasmTestBuilder`],
+    /// as values committed to its scratch state are not hashed into tries.
+    pub fn with_proof_validation(&mut self, enabled: bool) -> &mut Self {
+        self.proof_validation = enabled;
+        self
+    }
+
+    /// Enables or disables sharing genesis results with other builders via the genesis cache.
+    ///
+    /// Enabled by default for in-memory and LMDB builders.
+    pub fn with_genesis_cache(&mut self, enabled: bool) -> &mut Self {
+        self.genesis_cache = enabled;
+        self
+    }
+
+    /// Enables or disables gas profiling of subsequent execs.
+    ///
+    /// While enabled, a [`GasProfile`] breaking down the gas used by host function and by Wasm
+    /// opcode group is recorded for each exec.  Profiling instruments executed Wasm, so is
+    /// considerably slower than normal execution.
+    pub fn with_gas_profiling(&mut self, enabled: bool) -> &mut Self {
+        self.gas_profiling = enabled;
+        self
+    }
+
+    /// Returns the gas profile of a specific exec, if it was profiled.
+    pub fn get_gas_profile(&self, index: usize) -> Option<&GasProfile> {
+        self.gas_profiles.get(index)?.as_ref()
+    }
+
+    /// Returns the gas profile of the last exec, if it was profiled.
+    pub fn get_last_gas_profile(&self) -> Option<&GasProfile> {
+        self.gas_profiles.last()?.as_ref()
+    }
+
     /// Returns a `Result` containing an [`UpgradeSuccess`].
     pub fn get_upgrade_result(
         &self,
@@ -995,9 +1390,23 @@ where
     pub fn get_purse_balance_result(&self, purse: URef) -> BalanceResult {
         let correlation_id = CorrelationId::new();
         let state_root_hash: Digest = self.post_state_hash.expect("should have post_state_hash");
-        self.engine_state
+        let balance_result = self
+            .engine_state
             .get_purse_balance(correlation_id, state_root_hash, purse)
-            .expect("should get purse balance")
+            .expect("should get purse balance");
+        if let BalanceResult::Success { motes, proof } = &balance_result {
+            if self.proof_validation {
+                if let Err(error) =
+                    core::validate_balance_proof(&state_root_hash, proof, Key::URef(purse), motes)
+                {
+                    panic!(
+                        "invalid proof of balance of {} under state root hash {}: {}",
+                        purse, state_root_hash, error
+                    );
+                }
+            }
+        }
+        balance_result
     }
 
     /// Returns a `BalanceResult` for a purse using a `PublicKey`.
@@ -1088,6 +1497,19 @@ where
         }
     }
 
+    /// Queries for a transfer recorded with its initiator, memo and phase by `TransferAddr`.
+    pub fn get_transfer_v2(&self, transfer: TransferAddr) -> Option<TransferV2> {
+        let transfer_value: StoredValue = self
+            .query(None, Key::Transfer(transfer), &[])
+            .expect("should have transfer value");
+
+        if let StoredValue::TransferV2(transfer) = transfer_value {
+            Some(transfer)
+        } else {
+            None
+        }
+    }
+
     /// Queries for deploy info by `DeployHash`.
     pub fn get_deploy_info(&self, deploy_hash: DeployHash) -> Option<DeployInfo> {
         let deploy_info_value: StoredValue = self
@@ -0,0 +1,525 @@
+//! This executable measures the wall-clock cost of Wasm opcode groups and host functions against
+//! the real engine, and proposes chainspec costs proportional to the measurements.
+//!
+//! Each measured item is run as a pair of generated session modules looping over the same
+//! instructions, with and without the measured opcode or host function call.  The difference in
+//! execution time between the two, divided by the number of operations, is taken as the item's
+//! cost in nanoseconds.
+
+use std::{
+    env,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use clap::{crate_version, App, Arg};
+use toml::Value;
+
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    DEFAULT_RUN_GENESIS_REQUEST,
+};
+use casper_engine_tests::profiling;
+use casper_types::{contracts::DEFAULT_ENTRY_POINT_NAME, RuntimeArgs};
+
+const ABOUT: &str =
+    "Measures the execution time of Wasm opcode groups and host functions, and writes a proposed \
+     chainspec cost table along with a report of its deviations from the current one.";
+
+const CHAINSPEC_ARG_NAME: &str = "chainspec";
+const CHAINSPEC_ARG_SHORT: &str = "c";
+const CHAINSPEC_ARG_VALUE_NAME: &str = "PATH";
+const CHAINSPEC_ARG_HELP: &str = "Path to the chainspec holding the current costs [default: \
+                                  resources/production/chainspec.toml]";
+
+const ITERATIONS_ARG_NAME: &str = "iterations";
+const ITERATIONS_ARG_SHORT: &str = "i";
+const ITERATIONS_ARG_DEFAULT: &str = "10000";
+const ITERATIONS_ARG_VALUE_NAME: &str = "NUM";
+const ITERATIONS_ARG_HELP: &str = "Number of loop iterations in each generated module";
+
+const RUNS_ARG_NAME: &str = "runs";
+const RUNS_ARG_SHORT: &str = "r";
+const RUNS_ARG_DEFAULT: &str = "5";
+const RUNS_ARG_VALUE_NAME: &str = "NUM";
+const RUNS_ARG_HELP: &str =
+    "Number of executions of each generated module, of which the median time is taken";
+
+const GAS_PER_NS_ARG_NAME: &str = "gas-per-ns";
+const GAS_PER_NS_ARG_VALUE_NAME: &str = "GAS";
+const GAS_PER_NS_ARG_HELP: &str =
+    "Gas charged per nanosecond of execution time.  If unspecified, the median ratio of current \
+     cost to measured time across all opcode groups is used";
+
+const OUTPUT_DIR_ARG_NAME: &str = "output-dir";
+const OUTPUT_DIR_ARG_SHORT: &str = "o";
+const OUTPUT_DIR_ARG_VALUE_NAME: &str = "DIR";
+const OUTPUT_DIR_ARG_HELP: &str =
+    "Path to output directory.  It will be created if it doesn't exist.  If unspecified, the \
+    current working directory will be used";
+
+const PROPOSED_COSTS_FILE_NAME: &str = "proposed_costs.toml";
+const DEVIATIONS_FILE_NAME: &str = "deviations.csv";
+const CSV_HEADER: &str = "table,name,measured_ns,current_cost,proposed_cost,deviation_percent";
+
+/// Number of times the measured body is repeated within each loop iteration, to reduce the share
+/// of the loop's own overhead in the measurement.
+const UNROLL: usize = 10;
+/// Memory offset of a UTF-8 name which is not a named key or runtime arg.
+const NAME_OFFSET: u32 = 256;
+const NAME: &str = "missing_key";
+/// Memory offset of 33 bytes serializing both a `Key::Hash` and a `URef`, neither of which exist.
+const KEY_OFFSET: u32 = 512;
+const KEY_SIZE: u32 = 33;
+
+/// An item whose cost is measured.
+struct Benchmark {
+    /// The name of the chainspec entry holding the item's cost.
+    name: &'static str,
+    /// Import declarations required by the bodies.
+    imports: String,
+    /// Instructions executing the measured item.
+    body: String,
+    /// Instructions doing the same as `body`, but without the measured item.
+    reference: String,
+    /// Number of measured operations in `body`.
+    ops: usize,
+}
+
+impl Benchmark {
+    fn new(name: &'static str, body: &str, reference: &str, ops: usize) -> Self {
+        Benchmark {
+            name,
+            imports: String::new(),
+            body: body.to_string(),
+            reference: reference.to_string(),
+            ops,
+        }
+    }
+
+    /// Returns a benchmark of the host function imported as `casper_<name>`, called with `args`,
+    /// with its result dropped if `has_result`.
+    fn host_function(name: &'static str, args: &[u32], has_result: bool) -> Self {
+        let params = vec!["i32"; args.len()].join(" ");
+        let result = if has_result { "(result i32)" } else { "" };
+        let imports = format!(
+            r#"(import "env" "casper_{name}" (func ${name} (param {params}) {result}))"#,
+            name = name,
+            params = params,
+            result = result
+        );
+        let push_args: String = args
+            .iter()
+            .map(|arg| format!("i32.const {}\n", arg))
+            .collect();
+        let body = format!(
+            "{}call ${}\n{}",
+            push_args,
+            name,
+            if has_result { "drop\n" } else { "" }
+        );
+        let reference = format!("{}{}", push_args, "drop\n".repeat(args.len()));
+        Benchmark {
+            name,
+            imports,
+            body,
+            reference,
+            ops: 1,
+        }
+    }
+}
+
+fn opcode_benchmarks() -> Vec<Benchmark> {
+    let binary = |name, op| {
+        Benchmark::new(
+            name,
+            &format!("i32.const 7\ni32.const 3\n{}\ndrop\n", op),
+            "i32.const 7\ni32.const 3\ndrop\ndrop\n",
+            1,
+        )
+    };
+    vec![
+        binary("bit", "i32.and"),
+        binary("add", "i32.add"),
+        binary("mul", "i32.mul"),
+        binary("div", "i32.div_u"),
+        binary("integer_comparison", "i32.lt_u"),
+        Benchmark::new(
+            "conversion",
+            "i64.const 7\ni32.wrap_i64\ndrop\n",
+            "i64.const 7\ndrop\n",
+            1,
+        ),
+        Benchmark::new("const", "i32.const 7\ndrop\n", "", 1),
+        Benchmark::new("local", "local.get $x\nlocal.set $x\n", "", 2),
+        Benchmark::new("global", "global.get $g\nglobal.set $g\n", "", 2),
+        Benchmark::new(
+            "load",
+            "i32.const 0\ni32.load\ndrop\n",
+            "i32.const 0\ndrop\n",
+            1,
+        ),
+        Benchmark::new(
+            "store",
+            "i32.const 0\ni32.const 7\ni32.store\n",
+            "i32.const 0\ni32.const 7\ndrop\ndrop\n",
+            1,
+        ),
+        Benchmark::new("control_flow", "block\nbr 0\nend\n", "", 2),
+        Benchmark::new("nop", "nop\n", "", 1),
+        Benchmark::new(
+            "current_memory",
+            "memory.size\ndrop\n",
+            "i32.const 0\ndrop\n",
+            1,
+        ),
+    ]
+}
+
+fn host_function_benchmarks() -> Vec<Benchmark> {
+    let name_size = NAME.len() as u32;
+    vec![
+        Benchmark::host_function("get_blocktime", &[0], false),
+        Benchmark::host_function("get_phase", &[0], false),
+        Benchmark::host_function("get_main_purse", &[0], false),
+        Benchmark::host_function("get_caller", &[0], true),
+        Benchmark::host_function("blake2b", &[KEY_OFFSET, KEY_SIZE, 0, 32], true),
+        Benchmark::host_function("random_bytes", &[0, 32], true),
+        Benchmark::host_function("has_key", &[NAME_OFFSET, name_size], true),
+        Benchmark::host_function("get_key", &[NAME_OFFSET, name_size, 0, 64, 128], true),
+        Benchmark::host_function("load_named_keys", &[0, 4], true),
+        Benchmark::host_function("get_named_arg_size", &[NAME_OFFSET, name_size, 0], true),
+        Benchmark::host_function("read_value", &[KEY_OFFSET, KEY_SIZE, 0], true),
+        Benchmark::host_function("is_valid_uref", &[KEY_OFFSET, KEY_SIZE], true),
+    ]
+}
+
+/// Returns a session module running `body` `UNROLL` times in each of `iterations` loop iterations.
+fn module_bytes(imports: &str, body: &str, iterations: usize) -> Vec<u8> {
+    let key_bytes: String = "\\01".to_string() + &"\\00".repeat(KEY_SIZE as usize - 1);
+    let wat = format!(
+        r#"(module
+            {imports}
+            (global $g (mut i32) (i32.const 0))
+            (memory 1)
+            (data (i32.const {name_offset}) "{name}")
+            (data (i32.const {key_offset}) "{key_bytes}")
+            (func (export "{entry_point}")
+                (local $i i32)
+                (local $x i32)
+                (local.set $i (i32.const {iterations}))
+                (block $done
+                    (loop $next
+                        (br_if $done (i32.eqz (local.get $i)))
+                        {body}
+                        (local.set $i (i32.sub (local.get $i) (i32.const 1)))
+                        (br $next)))))"#,
+        imports = imports,
+        name_offset = NAME_OFFSET,
+        name = NAME,
+        key_offset = KEY_OFFSET,
+        key_bytes = key_bytes,
+        entry_point = DEFAULT_ENTRY_POINT_NAME,
+        iterations = iterations,
+        body = body.repeat(UNROLL),
+    );
+    wat::parse_str(&wat).unwrap_or_else(|error| panic!("should parse wat: {}\n{}", error, wat))
+}
+
+/// Returns the median wall-clock time of executing `module_bytes` `runs` times.
+fn median_exec_time(
+    builder: &mut InMemoryWasmTestBuilder,
+    module_bytes: &[u8],
+    runs: usize,
+) -> Duration {
+    let mut times: Vec<Duration> = (0..runs)
+        .map(|_| {
+            let exec_request = ExecuteRequestBuilder::module_bytes(
+                *DEFAULT_ACCOUNT_ADDR,
+                module_bytes.to_vec(),
+                RuntimeArgs::default(),
+            )
+            .build();
+            let start = Instant::now();
+            builder.exec(exec_request);
+            let elapsed = start.elapsed();
+            builder.expect_success();
+            elapsed
+        })
+        .collect();
+    times.sort();
+    times[times.len() / 2]
+}
+
+/// A measured item and its costs.
+struct Measurement {
+    table: &'static str,
+    name: &'static str,
+    nanos: f64,
+    current_cost: Option<u64>,
+}
+
+impl Measurement {
+    fn proposed_cost(&self, gas_per_ns: f64) -> u64 {
+        (self.nanos * gas_per_ns).round() as u64
+    }
+
+    fn deviation_percent(&self, gas_per_ns: f64) -> Option<f64> {
+        let current_cost = self.current_cost.filter(|cost| *cost > 0)? as f64;
+        Some((self.proposed_cost(gas_per_ns) as f64 - current_cost) / current_cost * 100.0)
+    }
+}
+
+fn measure(
+    builder: &mut InMemoryWasmTestBuilder,
+    table: &'static str,
+    benchmark: &Benchmark,
+    current_costs: &Value,
+    iterations: usize,
+    runs: usize,
+) -> Measurement {
+    let body = module_bytes(&benchmark.imports, &benchmark.body, iterations);
+    let reference = module_bytes(&benchmark.imports, &benchmark.reference, iterations);
+    let body_time = median_exec_time(builder, &body, runs);
+    let reference_time = median_exec_time(builder, &reference, runs);
+    let ops = (iterations * UNROLL * benchmark.ops) as f64;
+    let nanos = body_time.saturating_sub(reference_time).as_nanos() as f64 / ops;
+
+    let current_cost = match current_costs.get(benchmark.name) {
+        Some(Value::Table(host_function)) => host_function.get("cost"),
+        other => other,
+    }
+    .and_then(Value::as_integer)
+    .map(|cost| cost as u64);
+
+    println!(
+        "{}.{}: {:.2} ns (current cost {})",
+        table,
+        benchmark.name,
+        nanos,
+        current_cost.map_or_else(|| "n/a".to_string(), |cost| cost.to_string())
+    );
+    Measurement {
+        table,
+        name: benchmark.name,
+        nanos,
+        current_cost,
+    }
+}
+
+/// Returns the median ratio of current cost to measured time of the opcode measurements.
+fn derive_gas_per_ns(measurements: &[Measurement]) -> f64 {
+    let mut ratios: Vec<f64> = measurements
+        .iter()
+        .filter(|measurement| measurement.table == "opcode_costs" && measurement.nanos > 0.0)
+        .filter_map(|measurement| Some(measurement.current_cost? as f64 / measurement.nanos))
+        .collect();
+    assert!(
+        !ratios.is_empty(),
+        "no opcode group took measurable time; try increasing --{}",
+        ITERATIONS_ARG_NAME
+    );
+    ratios.sort_by(|a, b| a.partial_cmp(b).expect("ratios should not be NaN"));
+    ratios[ratios.len() / 2]
+}
+
+/// Returns the current cost tables with the measured entries replaced by the proposed costs.
+fn proposed_costs(chainspec: &Value, measurements: &[Measurement], gas_per_ns: f64) -> Value {
+    let mut wasm = toml::value::Table::new();
+    for table in ["opcode_costs", "host_function_costs"] {
+        let mut costs = chainspec["wasm"]
+            .get(table)
+            .cloned()
+            .unwrap_or_else(|| Value::Table(Default::default()));
+        for measurement in measurements.iter().filter(|m| m.table == table) {
+            let cost = Value::Integer(measurement.proposed_cost(gas_per_ns) as i64);
+            match costs.get_mut(measurement.name) {
+                Some(Value::Table(host_function)) => {
+                    host_function.insert("cost".to_string(), cost);
+                }
+                Some(entry) => *entry = cost,
+                None => {}
+            }
+        }
+        wasm.insert(table.to_string(), costs);
+    }
+    let mut root = toml::value::Table::new();
+    root.insert("wasm".to_string(), Value::Table(wasm));
+    Value::Table(root)
+}
+
+fn write_deviations(path: &Path, measurements: &[Measurement], gas_per_ns: f64) {
+    let mut file =
+        File::create(path).unwrap_or_else(|_| panic!("should create {}", path.display()));
+    writeln!(file, "{}", CSV_HEADER)
+        .unwrap_or_else(|_| panic!("should write to {}", path.display()));
+    for measurement in measurements {
+        writeln!(
+            file,
+            "{},{},{:.3},{},{},{}",
+            measurement.table,
+            measurement.name,
+            measurement.nanos,
+            measurement
+                .current_cost
+                .map_or_else(String::new, |cost| cost.to_string()),
+            measurement.proposed_cost(gas_per_ns),
+            measurement
+                .deviation_percent(gas_per_ns)
+                .map_or_else(String::new, |deviation| format!("{:.1}", deviation)),
+        )
+        .unwrap_or_else(|_| panic!("should write to {}", path.display()));
+    }
+}
+
+fn chainspec_arg() -> Arg<'static, 'static> {
+    Arg::with_name(CHAINSPEC_ARG_NAME)
+        .long(CHAINSPEC_ARG_NAME)
+        .short(CHAINSPEC_ARG_SHORT)
+        .value_name(CHAINSPEC_ARG_VALUE_NAME)
+        .help(CHAINSPEC_ARG_HELP)
+}
+
+fn iterations_arg() -> Arg<'static, 'static> {
+    Arg::with_name(ITERATIONS_ARG_NAME)
+        .long(ITERATIONS_ARG_NAME)
+        .short(ITERATIONS_ARG_SHORT)
+        .default_value(ITERATIONS_ARG_DEFAULT)
+        .value_name(ITERATIONS_ARG_VALUE_NAME)
+        .help(ITERATIONS_ARG_HELP)
+}
+
+fn runs_arg() -> Arg<'static, 'static> {
+    Arg::with_name(RUNS_ARG_NAME)
+        .long(RUNS_ARG_NAME)
+        .short(RUNS_ARG_SHORT)
+        .default_value(RUNS_ARG_DEFAULT)
+        .value_name(RUNS_ARG_VALUE_NAME)
+        .help(RUNS_ARG_HELP)
+}
+
+fn gas_per_ns_arg() -> Arg<'static, 'static> {
+    Arg::with_name(GAS_PER_NS_ARG_NAME)
+        .long(GAS_PER_NS_ARG_NAME)
+        .value_name(GAS_PER_NS_ARG_VALUE_NAME)
+        .help(GAS_PER_NS_ARG_HELP)
+}
+
+fn output_dir_arg() -> Arg<'static, 'static> {
+    Arg::with_name(OUTPUT_DIR_ARG_NAME)
+        .long(OUTPUT_DIR_ARG_NAME)
+        .short(OUTPUT_DIR_ARG_SHORT)
+        .value_name(OUTPUT_DIR_ARG_VALUE_NAME)
+        .help(OUTPUT_DIR_ARG_HELP)
+}
+
+#[derive(Debug)]
+struct Args {
+    chainspec: PathBuf,
+    iterations: usize,
+    runs: usize,
+    gas_per_ns: Option<f64>,
+    output_dir: PathBuf,
+}
+
+impl Args {
+    fn new() -> Self {
+        let exe_name = profiling::exe_name();
+        let arg_matches = App::new(&exe_name)
+            .version(crate_version!())
+            .about(ABOUT)
+            .arg(chainspec_arg())
+            .arg(iterations_arg())
+            .arg(runs_arg())
+            .arg(gas_per_ns_arg())
+            .arg(output_dir_arg())
+            .get_matches();
+        let chainspec = match arg_matches.value_of(CHAINSPEC_ARG_NAME) {
+            Some(path) => PathBuf::from_str(path).expect("Expected a valid unicode path"),
+            None => Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../../resources/production/chainspec.toml"),
+        };
+        let iterations = arg_matches
+            .value_of(ITERATIONS_ARG_NAME)
+            .map(profiling::parse_count)
+            .expect("should have iterations");
+        let runs = arg_matches
+            .value_of(RUNS_ARG_NAME)
+            .map(profiling::parse_count)
+            .expect("should have runs");
+        let gas_per_ns = arg_matches.value_of(GAS_PER_NS_ARG_NAME).map(|value| {
+            value
+                .parse()
+                .expect("Expected a floating point gas per nanosecond")
+        });
+        let output_dir = match arg_matches.value_of(OUTPUT_DIR_ARG_NAME) {
+            Some(dir) => PathBuf::from_str(dir).expect("Expected a valid unicode path"),
+            None => env::current_dir().expect("Expected to be able to access current working dir"),
+        };
+        Args {
+            chainspec,
+            iterations,
+            runs,
+            gas_per_ns,
+            output_dir,
+        }
+    }
+}
+
+fn main() {
+    let args = Args::new();
+
+    let chainspec: Value = toml::from_str(
+        &fs::read_to_string(&args.chainspec)
+            .unwrap_or_else(|_| panic!("should read {}", args.chainspec.display())),
+    )
+    .unwrap_or_else(|error| panic!("should parse {}: {}", args.chainspec.display(), error));
+
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let mut measurements = Vec::new();
+    for (table, benchmarks) in [
+        ("opcode_costs", opcode_benchmarks()),
+        ("host_function_costs", host_function_benchmarks()),
+    ] {
+        let current_costs = &chainspec["wasm"][table];
+        for benchmark in &benchmarks {
+            measurements.push(measure(
+                &mut builder,
+                table,
+                benchmark,
+                current_costs,
+                args.iterations,
+                args.runs,
+            ));
+        }
+    }
+
+    let gas_per_ns = args
+        .gas_per_ns
+        .unwrap_or_else(|| derive_gas_per_ns(&measurements));
+    println!("using {:.3} gas per nanosecond", gas_per_ns);
+
+    let output_dir = &args.output_dir;
+    fs::create_dir_all(output_dir)
+        .unwrap_or_else(|_| panic!("should create {}", output_dir.display()));
+
+    let proposed_costs_path = output_dir.join(PROPOSED_COSTS_FILE_NAME);
+    let proposed_costs = toml::to_string(&proposed_costs(&chainspec, &measurements, gas_per_ns))
+        .expect("should serialize proposed costs");
+    fs::write(&proposed_costs_path, proposed_costs)
+        .unwrap_or_else(|_| panic!("should write {}", proposed_costs_path.display()));
+
+    let deviations_path = output_dir.join(DEVIATIONS_FILE_NAME);
+    write_deviations(&deviations_path, &measurements, gas_per_ns);
+
+    println!(
+        "wrote {} and {}",
+        proposed_costs_path.display(),
+        deviations_path.display()
+    );
+}
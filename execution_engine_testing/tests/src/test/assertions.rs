@@ -0,0 +1,81 @@
+use casper_engine_test_support::{
+    BalanceDelta, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_types::{
+    account::AccountHash, bytesrepr::ToBytes, runtime_args, system::mint, CLTyped, ContractHash,
+    RuntimeArgs, U512,
+};
+
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1u8; 32]);
+const TRANSFER_AMOUNT: u64 = 5_000_000_000;
+const TOPIC_NAME: &str = "topic";
+
+/// Transfers `TRANSFER_AMOUNT` from the default account to `target`, an account or a purse.
+fn transfer_to<T: CLTyped + ToBytes>(builder: &mut InMemoryWasmTestBuilder, target: T) {
+    let runtime_args = runtime_args! {
+        mint::ARG_TARGET => target,
+        mint::ARG_AMOUNT => U512::from(TRANSFER_AMOUNT),
+        mint::ARG_ID => <Option<u64>>::None,
+    };
+    let transfer_request =
+        ExecuteRequestBuilder::transfer(*DEFAULT_ACCOUNT_ADDR, runtime_args).build();
+    builder.exec(transfer_request).expect_success().commit();
+}
+
+fn setup() -> InMemoryWasmTestBuilder {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+    builder
+}
+
+#[ignore]
+#[test]
+fn should_assert_transfers_and_balance_deltas() {
+    let mut builder = setup();
+    let amount = U512::from(TRANSFER_AMOUNT);
+
+    transfer_to(&mut builder, ACCOUNT_1_ADDR);
+    builder
+        .assert_transfer(*DEFAULT_ACCOUNT_ADDR, ACCOUNT_1_ADDR, amount)
+        .assert_no_event_emitted(ContractHash::new([2; 32]), TOPIC_NAME);
+
+    let account_1_purse = builder.get_expected_account(ACCOUNT_1_ADDR).main_purse();
+    let balances = builder.balance_snapshot([*DEFAULT_ACCOUNT_ADDR, ACCOUNT_1_ADDR]);
+    assert_eq!(balances.balance(account_1_purse), amount);
+
+    transfer_to(&mut builder, account_1_purse);
+    builder.assert_transfer_to_purse(*DEFAULT_ACCOUNT_ADDR, account_1_purse, amount);
+    balances
+        .assert_increased_by(&builder, ACCOUNT_1_ADDR, amount)
+        .assert_decreased_by_at_least(&builder, *DEFAULT_ACCOUNT_ADDR, amount);
+    assert!(matches!(
+        balances.delta(&builder, *DEFAULT_ACCOUNT_ADDR),
+        BalanceDelta::Decreased(decrease) if decrease > amount
+    ));
+}
+
+#[ignore]
+#[test]
+#[should_panic(expected = "expected a transfer of")]
+fn should_fail_to_assert_transfer_of_different_amount() {
+    let mut builder = setup();
+
+    transfer_to(&mut builder, ACCOUNT_1_ADDR);
+    builder.assert_transfer(
+        *DEFAULT_ACCOUNT_ADDR,
+        ACCOUNT_1_ADDR,
+        U512::from(TRANSFER_AMOUNT + 1),
+    );
+}
+
+#[ignore]
+#[test]
+#[should_panic(expected = "unexpected change in balance")]
+fn should_fail_to_assert_unchanged_balance() {
+    let mut builder = setup();
+    let balances = builder.balance_snapshot([*DEFAULT_ACCOUNT_ADDR]);
+
+    transfer_to(&mut builder, ACCOUNT_1_ADDR);
+    balances.assert_unchanged(&builder, *DEFAULT_ACCOUNT_ADDR);
+}
@@ -6,10 +6,12 @@ use casper_engine_test_support::{
     ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
     PRODUCTION_RUN_GENESIS_REQUEST,
 };
-use casper_execution_engine::shared::system_config::DEFAULT_WASMLESS_TRANSFER_COST;
+use casper_execution_engine::{
+    core::engine_state::EngineConfig, shared::system_config::DEFAULT_WASMLESS_TRANSFER_COST,
+};
 use casper_types::{
     account::AccountHash, runtime_args, system::mint, AccessRights, Contract, ContractHash,
-    DeployHash, PublicKey, RuntimeArgs, SecretKey, Transfer, TransferAddr, U512,
+    DeployHash, Phase, PublicKey, RuntimeArgs, SecretKey, Transfer, TransferAddr, TransferV2, U512,
 };
 
 const CONTRACT_TRANSFER_PURSE_TO_ACCOUNT: &str = "transfer_purse_to_account.wasm";
@@ -116,6 +118,53 @@ fn should_record_wasmless_transfer() {
     assert_eq!(transfer.id, id);
 }
 
+#[ignore]
+#[test]
+fn should_record_wasmless_transfer_metadata_if_enabled() {
+    let engine_config = EngineConfig::default().with_record_transfer_metadata(true);
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let id = Some(0);
+    let memo = Some("invoice 42".to_string());
+
+    let transfer_request = ExecuteRequestBuilder::transfer(
+        *DEFAULT_ACCOUNT_ADDR,
+        runtime_args! {
+            TRANSFER_ARG_TARGET => *ALICE_ADDR,
+            TRANSFER_ARG_AMOUNT => *TRANSFER_AMOUNT_1,
+            TRANSFER_ARG_ID => id,
+            mint::ARG_MEMO => memo.clone()
+        },
+    )
+    .build();
+    let deploy_hash = transfer_request.deploys()[0].deploy_hash;
+
+    builder.exec(transfer_request).commit().expect_success();
+
+    let default_account = builder
+        .get_account(*DEFAULT_ACCOUNT_ADDR)
+        .expect("should have default account");
+    let deploy_info = builder
+        .get_deploy_info(deploy_hash)
+        .expect("should have deploy info");
+    assert_eq!(deploy_info.transfers.len(), 1);
+
+    assert!(builder.get_transfer(deploy_info.transfers[0]).is_none());
+    let transfer: TransferV2 = builder
+        .get_transfer_v2(deploy_info.transfers[0])
+        .expect("should have transfer");
+
+    assert_eq!(transfer.from, *DEFAULT_ACCOUNT_ADDR);
+    assert_eq!(transfer.to, Some(*ALICE_ADDR));
+    assert_eq!(transfer.source, default_account.main_purse());
+    assert_eq!(transfer.amount, *TRANSFER_AMOUNT_1);
+    assert_eq!(transfer.id, id);
+    assert_eq!(transfer.initiator, Some(*DEFAULT_ACCOUNT_ADDR));
+    assert_eq!(transfer.memo, memo);
+    assert_eq!(transfer.phase, Phase::Session);
+}
+
 #[ignore]
 #[test]
 fn should_record_wasm_transfer() {
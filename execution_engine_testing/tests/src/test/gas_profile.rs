@@ -0,0 +1,139 @@
+use parity_wasm::{
+    builder,
+    elements::{Instruction, Instructions},
+};
+
+use casper_engine_test_support::{
+    DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, ARG_AMOUNT,
+    DEFAULT_ACCOUNT_ADDR, DEFAULT_PAYMENT, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::{
+    core::engine_state::ExecuteRequest, shared::gas_profile::OpcodeGroup,
+};
+use casper_types::{
+    account::AccountHash, contracts::DEFAULT_ENTRY_POINT_NAME, runtime_args, Gas, RuntimeArgs, U512,
+};
+
+const CONTRACT_TRANSFER_TO_ACCOUNT: &str = "transfer_to_account_u512.wasm";
+const ARG_TARGET: &str = "target";
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1u8; 32]);
+const TRANSFER_AMOUNT: u64 = 2_500_000_000;
+
+fn session_code_request(instructions: Vec<Instruction>) -> ExecuteRequest {
+    let module = builder::module()
+        .function()
+        .signature()
+        .build()
+        .body()
+        .with_instructions(Instructions::new(instructions))
+        .build()
+        .build()
+        .export()
+        .field(DEFAULT_ENTRY_POINT_NAME)
+        .build()
+        .memory()
+        .build()
+        .build();
+    let session_bytes = parity_wasm::serialize(module).expect("should serialize");
+
+    let deploy_item = DeployItemBuilder::new()
+        .with_address(*DEFAULT_ACCOUNT_ADDR)
+        .with_session_bytes(session_bytes, RuntimeArgs::default())
+        .with_empty_payment_bytes(runtime_args! {
+            ARG_AMOUNT => *DEFAULT_PAYMENT
+        })
+        .with_authorization_keys(&[*DEFAULT_ACCOUNT_ADDR])
+        .with_deploy_hash([42; 32])
+        .build();
+    ExecuteRequestBuilder::from_deploy_item(deploy_item).build()
+}
+
+#[ignore]
+#[test]
+fn should_profile_opcodes() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let exec_request = session_code_request(vec![
+        Instruction::I32Const(1),
+        Instruction::I32Const(2),
+        Instruction::I32Add,
+        Instruction::Drop,
+        Instruction::Nop,
+        Instruction::End,
+    ]);
+    builder
+        .with_gas_profiling(true)
+        .exec(exec_request)
+        .expect_success()
+        .commit();
+
+    let profile = builder
+        .get_last_gas_profile()
+        .expect("should have gas profile");
+    let opcode_costs = builder
+        .get_engine_state()
+        .config()
+        .wasm_config()
+        .opcode_costs();
+    let count = |group| {
+        profile
+            .opcodes
+            .get(&group)
+            .map(|usage| usage.count)
+            .unwrap_or_default()
+    };
+    assert_eq!(count(OpcodeGroup::Const), 2);
+    assert_eq!(count(OpcodeGroup::Add), 1);
+    assert_eq!(count(OpcodeGroup::Nop), 1);
+    assert_eq!(
+        profile.opcodes[&OpcodeGroup::Const].gas,
+        Gas::from(2 * opcode_costs.op_const)
+    );
+    assert!(profile.wasm > Gas::default());
+    assert!(profile.host_functions.is_empty());
+}
+
+#[ignore]
+#[test]
+fn should_profile_host_functions() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let transfer_request = || {
+        ExecuteRequestBuilder::standard(
+            *DEFAULT_ACCOUNT_ADDR,
+            CONTRACT_TRANSFER_TO_ACCOUNT,
+            runtime_args! {
+                ARG_TARGET => ACCOUNT_1_ADDR,
+                ARG_AMOUNT => U512::from(TRANSFER_AMOUNT),
+            },
+        )
+        .build()
+    };
+
+    builder.exec(transfer_request()).expect_success().commit();
+    assert!(builder.get_last_gas_profile().is_none());
+
+    builder
+        .with_gas_profiling(true)
+        .exec(transfer_request())
+        .expect_success()
+        .commit();
+    let profile = builder
+        .get_last_gas_profile()
+        .expect("should have gas profile")
+        .clone();
+
+    let transfer = profile.host_functions["casper_transfer_to_account"];
+    assert_eq!(transfer.calls, 1);
+    assert!(transfer.gas > Gas::default());
+    assert!(profile.host_functions.contains_key("casper_get_named_arg"));
+    assert!(!profile.host_functions.contains_key("gas"));
+
+    let cost = builder.last_exec_gas_cost();
+    assert!(profile.total() <= cost);
+
+    let report = serde_json::to_value(&profile).expect("should serialize");
+    assert!(report["host_functions"]["casper_transfer_to_account"]["gas"].is_string());
+}
@@ -0,0 +1,54 @@
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, LmdbWasmTestBuilder, SnapshotWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    MINIMUM_ACCOUNT_CREATION_BALANCE, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::core::engine_state::EngineConfig;
+use casper_types::{account::AccountHash, runtime_args, system::mint, RuntimeArgs};
+use tempfile::TempDir;
+
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1u8; 32]);
+const GLOBAL_STATE_DIR: &str = "global_state";
+
+#[ignore]
+#[test]
+fn should_replay_deploys_against_snapshot_without_modifying_it() {
+    let data_dir = TempDir::new().expect("should create temp dir");
+
+    let state_root = {
+        let mut builder = LmdbWasmTestBuilder::new(data_dir.path());
+        builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+        builder.flush_environment();
+        builder.get_post_state_hash()
+    };
+
+    {
+        let mut builder = SnapshotWasmTestBuilder::from_lmdb_snapshot(
+            data_dir.path().join(GLOBAL_STATE_DIR),
+            state_root,
+        );
+        assert!(builder.get_account(*DEFAULT_ACCOUNT_ADDR).is_some());
+
+        let transfer = ExecuteRequestBuilder::transfer(
+            *DEFAULT_ACCOUNT_ADDR,
+            runtime_args! {
+                mint::ARG_TARGET => ACCOUNT_1_ADDR,
+                mint::ARG_AMOUNT => MINIMUM_ACCOUNT_CREATION_BALANCE,
+                mint::ARG_ID => Some(1u64),
+            },
+        )
+        .build();
+        builder.exec(transfer).expect_success().commit();
+
+        assert!(
+            builder.get_account(ACCOUNT_1_ADDR).is_some(),
+            "account should exist in scratch state"
+        );
+    }
+
+    let builder = LmdbWasmTestBuilder::open(data_dir.path(), EngineConfig::default(), state_root);
+    assert!(builder.get_account(*DEFAULT_ACCOUNT_ADDR).is_some());
+    assert!(
+        builder.get_account(ACCOUNT_1_ADDR).is_none(),
+        "snapshot should not have been modified"
+    );
+}
@@ -1,3 +1,4 @@
+mod assertions;
 mod bulk_update_with_scratch_trie;
 mod chainspec_registry;
 mod check_transfer_success;
@@ -6,10 +7,14 @@ mod contract_context;
 mod deploy;
 mod explorer;
 mod gas_counter;
+mod gas_profile;
 mod get_balance;
 mod groups;
 mod host_function_costs;
+mod lmdb_snapshot;
 mod manage_groups;
+mod module_cache;
+mod optimistic_execution;
 mod regression;
 mod step;
 mod storage_costs;
@@ -17,4 +22,6 @@ mod system_contracts;
 mod system_costs;
 mod tutorial;
 mod upgrade;
+mod upgrade_migrations;
+mod upgrade_scenarios;
 mod wasmless_transfer;
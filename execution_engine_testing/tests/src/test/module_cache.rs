@@ -0,0 +1,62 @@
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_types::{Gas, RuntimeArgs};
+
+const CONTRACT_NAME: &str = "do_nothing_stored.wasm";
+const CONTRACT_HASH_NAMED_KEY: &str = "do_nothing_hash";
+const ENTRY_POINT_NAME: &str = "delegate";
+const CALL_COUNT: usize = 3;
+
+/// Installs the contract and calls it repeatedly, returning the gas cost of each call.
+fn call_stored_contract(builder: &mut InMemoryWasmTestBuilder) -> Vec<Gas> {
+    builder
+        .run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST)
+        .commit();
+
+    let install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, CONTRACT_NAME, RuntimeArgs::new())
+            .build();
+    builder.exec(install_request).expect_success().commit();
+
+    (0..CALL_COUNT)
+        .map(|_| {
+            let call_request = ExecuteRequestBuilder::contract_call_by_name(
+                *DEFAULT_ACCOUNT_ADDR,
+                CONTRACT_HASH_NAMED_KEY,
+                ENTRY_POINT_NAME,
+                RuntimeArgs::new(),
+            )
+            .build();
+            builder.exec(call_request).expect_success().commit();
+            builder.last_exec_gas_cost()
+        })
+        .collect()
+}
+
+#[ignore]
+#[test]
+fn should_charge_same_gas_with_and_without_module_cache() {
+    let mut cached_builder = InMemoryWasmTestBuilder::default();
+    let cached_costs = call_stored_contract(&mut cached_builder);
+
+    // Apart from the cache, the engine is configured the same as by the production chainspec.
+    let engine_config = cached_builder
+        .get_engine_state()
+        .config()
+        .with_module_cache_size(0);
+    let mut uncached_builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    let uncached_costs = call_stored_contract(&mut uncached_builder);
+
+    assert_eq!(cached_costs, uncached_costs);
+
+    // The first call parses the module, every further call finds it in the cache.
+    let stats = cached_builder.get_engine_state().module_cache_stats();
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.hits, CALL_COUNT as u64 - 1);
+    assert_eq!(stats.entries, 1);
+
+    let stats = uncached_builder.get_engine_state().module_cache_stats();
+    assert_eq!(stats.hits + stats.misses, 0);
+}
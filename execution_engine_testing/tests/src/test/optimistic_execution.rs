@@ -0,0 +1,203 @@
+use casper_engine_test_support::{
+    DeployItemBuilder, ExecuteRequestBuilder, LmdbWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::core::engine_state::{ExecuteRequest, MAX_PAYMENT_AMOUNT};
+use casper_hashing::Digest;
+use casper_types::{
+    account::AccountHash,
+    runtime_args,
+    system::mint::{ARG_AMOUNT, ARG_ID, ARG_TARGET},
+    RuntimeArgs, U512,
+};
+use tempfile::TempDir;
+
+const TRANSFER_COST: u64 = 100_000_000;
+const THREAD_COUNTS: [usize; 3] = [2, 4, 16];
+
+/// Accounts funded with enough for a single transfer of `MAX_PAYMENT_AMOUNT` each.
+const FUNDED_ACCOUNT_COUNT: u8 = 8;
+
+fn account(index: u8) -> AccountHash {
+    AccountHash::new([index; 32])
+}
+
+fn transfer(sender: AccountHash, target: AccountHash, amount: U512, seed: u8) -> ExecuteRequest {
+    let deploy = DeployItemBuilder::new()
+        .with_address(sender)
+        .with_empty_payment_bytes(runtime_args! {})
+        .with_transfer_args(runtime_args! {
+            ARG_TARGET => target,
+            ARG_AMOUNT => amount,
+            ARG_ID => Some(u64::from(seed)),
+        })
+        .with_authorization_keys(&[sender])
+        .with_deploy_hash([seed; 32])
+        .build();
+    ExecuteRequestBuilder::new().push_deploy(deploy).build()
+}
+
+/// Returns a builder in which the funded accounts hold enough for a transfer and its fee.
+fn setup() -> (TempDir, LmdbWasmTestBuilder) {
+    let data_dir = TempDir::new().expect("should create temp dir");
+    let mut builder = LmdbWasmTestBuilder::new(data_dir.path());
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    for index in 1..=FUNDED_ACCOUNT_COUNT {
+        let request = transfer(
+            *DEFAULT_ACCOUNT_ADDR,
+            account(index),
+            U512::from(MAX_PAYMENT_AMOUNT + TRANSFER_COST),
+            index,
+        );
+        builder.exec(request).expect_success().commit();
+    }
+
+    (data_dir, builder)
+}
+
+/// Executes the requests optimistically on the given number of threads, returning the post state
+/// hash, whether each request succeeded and how many requests were re-executed.
+fn run_optimistically(requests: &[ExecuteRequest], threads: usize) -> (Digest, Vec<bool>, usize) {
+    let (_data_dir, mut builder) = setup();
+    let first_exec = builder.get_exec_results_count();
+
+    builder.scratch_exec_and_commit_optimistically(requests.to_vec(), threads);
+    let reexecutions = builder.get_optimistic_reexecutions();
+    let successes = (first_exec..builder.get_exec_results_count())
+        .map(|index| {
+            builder
+                .get_exec_result_owned(index)
+                .expect("should have exec result")
+                .iter()
+                .all(|result| result.is_success())
+        })
+        .collect();
+
+    builder.write_scratch_to_db();
+    (builder.get_post_state_hash(), successes, reexecutions)
+}
+
+/// Asserts that executing the requests on several threads ends in the same state, with the same
+/// outcomes, as executing them one after another.
+fn assert_equivalent_to_sequential(requests: &[ExecuteRequest]) -> usize {
+    let (expected_root, expected_successes, sequential_reexecutions) =
+        run_optimistically(requests, 1);
+    assert_eq!(sequential_reexecutions, 0);
+
+    let mut most_reexecutions = 0;
+    for threads in THREAD_COUNTS {
+        let (root, successes, reexecutions) = run_optimistically(requests, threads);
+        assert_eq!(
+            root, expected_root,
+            "state root on {} threads should match sequential execution",
+            threads
+        );
+        assert_eq!(successes, expected_successes);
+        most_reexecutions = most_reexecutions.max(reexecutions);
+    }
+    most_reexecutions
+}
+
+#[ignore]
+#[test]
+fn should_match_sequential_execution_of_independent_transfers() {
+    let requests: Vec<_> = (1..=FUNDED_ACCOUNT_COUNT)
+        .map(|index| {
+            transfer(
+                account(index),
+                account(100 + index),
+                U512::from(MAX_PAYMENT_AMOUNT),
+                100 + index,
+            )
+        })
+        .collect();
+
+    // The transfers only share the payment purse and the proposer's purse, neither of which
+    // makes them conflict.
+    assert_eq!(assert_equivalent_to_sequential(&requests), 0);
+}
+
+#[ignore]
+#[test]
+fn should_match_sequential_execution_of_transfers_from_one_account() {
+    // Every account can afford exactly one transfer, so the second transfer of each sender only
+    // fails if it sees the effects of the first.
+    let requests: Vec<_> = (1..=FUNDED_ACCOUNT_COUNT)
+        .flat_map(|index| {
+            (0..2).map(move |attempt| {
+                transfer(
+                    account(index),
+                    account(100 + index),
+                    U512::from(MAX_PAYMENT_AMOUNT),
+                    100 + index + attempt * FUNDED_ACCOUNT_COUNT,
+                )
+            })
+        })
+        .collect();
+
+    let reexecutions = assert_equivalent_to_sequential(&requests);
+    assert!(
+        reexecutions > 0,
+        "double spends should have been re-executed"
+    );
+
+    let (_root, successes, _reexecutions) = run_optimistically(&requests, 4);
+    let expected: Vec<_> = (1..=FUNDED_ACCOUNT_COUNT)
+        .flat_map(|_| vec![true, false])
+        .collect();
+    assert_eq!(successes, expected);
+}
+
+#[ignore]
+#[test]
+fn should_match_sequential_execution_of_chained_transfers() {
+    // Each transfer is only funded by the one before it.
+    let mut requests = vec![transfer(
+        *DEFAULT_ACCOUNT_ADDR,
+        account(200),
+        U512::from(FUNDED_ACCOUNT_COUNT) * U512::from(MAX_PAYMENT_AMOUNT + TRANSFER_COST),
+        200,
+    )];
+    for index in 200..200 + FUNDED_ACCOUNT_COUNT {
+        let remaining = U512::from(200 + FUNDED_ACCOUNT_COUNT - index - 1);
+        requests.push(transfer(
+            account(index),
+            account(index + 1),
+            remaining * U512::from(MAX_PAYMENT_AMOUNT + TRANSFER_COST)
+                + U512::from(MAX_PAYMENT_AMOUNT),
+            index + 1,
+        ));
+    }
+
+    let reexecutions = assert_equivalent_to_sequential(&requests);
+    assert!(reexecutions > 0);
+
+    let (_root, successes, _reexecutions) = run_optimistically(&requests, 4);
+    assert!(successes.into_iter().all(|success| success));
+}
+
+#[ignore]
+#[test]
+fn should_match_sequential_execution_of_mixed_transfers() {
+    // Independent transfers interleaved with ones paying into the accounts making them.
+    let mut requests = vec![];
+    for index in 1..=FUNDED_ACCOUNT_COUNT {
+        requests.push(transfer(
+            account(index),
+            account(index % FUNDED_ACCOUNT_COUNT + 1),
+            U512::from(MAX_PAYMENT_AMOUNT / 2),
+            50 + index,
+        ));
+        if index % 2 == 0 {
+            requests.push(transfer(
+                *DEFAULT_ACCOUNT_ADDR,
+                account(index),
+                U512::from(TRANSFER_COST),
+                150 + index,
+            ));
+        }
+    }
+
+    assert_equivalent_to_sequential(&requests);
+}
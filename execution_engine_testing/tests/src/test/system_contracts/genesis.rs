@@ -1,19 +1,23 @@
 use num_traits::Zero;
 use once_cell::sync::Lazy;
+use tempfile::TempDir;
 
 use casper_engine_test_support::{
-    ChainspecConfig, InMemoryWasmTestBuilder, DEFAULT_AUCTION_DELAY, DEFAULT_CHAINSPEC_REGISTRY,
+    ChainspecConfig, ExecuteRequestBuilder, InMemoryWasmTestBuilder, LmdbWasmTestBuilder,
+    DEFAULT_ACCOUNT_ADDR, DEFAULT_AUCTION_DELAY, DEFAULT_CHAINSPEC_REGISTRY,
     DEFAULT_GENESIS_TIMESTAMP_MILLIS, DEFAULT_LOCKED_FUNDS_PERIOD_MILLIS,
     DEFAULT_ROUND_SEIGNIORAGE_RATE, DEFAULT_SYSTEM_CONFIG, DEFAULT_UNBONDING_DELAY,
-    DEFAULT_VALIDATOR_SLOTS, DEFAULT_WASM_CONFIG,
+    DEFAULT_VALIDATOR_SLOTS, DEFAULT_WASM_CONFIG, PRODUCTION_RUN_GENESIS_REQUEST,
 };
 use casper_execution_engine::core::engine_state::{
     genesis::{ExecConfig, GenesisAccount, GenesisValidator},
     run_genesis_request::RunGenesisRequest,
 };
 use casper_types::{
-    account::AccountHash, system::auction::DelegationRate, Motes, ProtocolVersion, PublicKey,
-    SecretKey, StoredValue, U512,
+    account::AccountHash,
+    runtime_args,
+    system::{auction::DelegationRate, mint},
+    Motes, ProtocolVersion, PublicKey, RuntimeArgs, SecretKey, StoredValue, U512,
 };
 
 const GENESIS_CONFIG_HASH: [u8; 32] = [127; 32];
@@ -165,3 +169,46 @@ fn should_track_total_token_supply_in_mint() {
         "unexpected total supply"
     )
 }
+
+#[ignore]
+#[test]
+fn should_copy_cached_genesis_into_isolated_global_states() {
+    let mut uncached_builder = InMemoryWasmTestBuilder::default();
+    uncached_builder
+        .with_genesis_cache(false)
+        .run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let mut in_memory_builder = InMemoryWasmTestBuilder::default();
+    in_memory_builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let data_dir = TempDir::new().expect("should create temp dir");
+    let mut lmdb_builder = LmdbWasmTestBuilder::new_with_production_chainspec(data_dir.path());
+    lmdb_builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let genesis_hash = uncached_builder.get_genesis_hash();
+    assert_eq!(in_memory_builder.get_genesis_hash(), genesis_hash);
+    assert_eq!(lmdb_builder.get_genesis_hash(), genesis_hash);
+    assert_eq!(
+        in_memory_builder.get_system_auction_hash(),
+        uncached_builder.get_system_auction_hash()
+    );
+
+    // Changes to one builder's global state are not visible to the others.
+    let transfer_request = ExecuteRequestBuilder::transfer(
+        *DEFAULT_ACCOUNT_ADDR,
+        runtime_args! {
+            mint::ARG_TARGET => *ACCOUNT_1_ADDR,
+            mint::ARG_AMOUNT => U512::from(ACCOUNT_1_BALANCE),
+            mint::ARG_ID => <Option<u64>>::None,
+        },
+    )
+    .build();
+    in_memory_builder
+        .exec(transfer_request)
+        .expect_success()
+        .commit();
+
+    assert!(in_memory_builder.get_account(*ACCOUNT_1_ADDR).is_some());
+    assert!(lmdb_builder.get_account(*ACCOUNT_1_ADDR).is_none());
+    assert!(uncached_builder.get_account(*ACCOUNT_1_ADDR).is_none());
+}
@@ -0,0 +1,127 @@
+use casper_engine_test_support::{
+    InMemoryWasmTestBuilder, UpgradeRequestBuilder, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::{
+    core::engine_state::{Migration, UpgradeConfig},
+    shared::transform::Transform,
+};
+use casper_types::{CLValue, EraId, Key, KeyTag, ProtocolVersion, StoredValue};
+
+const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V1_0_0;
+const DEFAULT_ACTIVATION_POINT: EraId = EraId::new(1);
+const SEEDED_KEY: Key = Key::Hash([42; 32]);
+const REWRITTEN_KEY: Key = Key::Hash([43; 32]);
+
+fn seeded_value() -> StoredValue {
+    StoredValue::CLValue(CLValue::from_t(1_u64).unwrap())
+}
+
+fn upgrade_request(migrations: Vec<Migration>) -> UpgradeConfig {
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+
+    UpgradeRequestBuilder::new()
+        .with_current_protocol_version(PROTOCOL_VERSION)
+        .with_new_protocol_version(new_protocol_version)
+        .with_activation_point(DEFAULT_ACTIVATION_POINT)
+        .with_migrations(migrations)
+        .build()
+}
+
+#[ignore]
+#[test]
+fn should_apply_migrations_in_order() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let mut upgrade_request = upgrade_request(vec![
+        Migration::SeedValue {
+            key: SEEDED_KEY,
+            value: seeded_value(),
+        },
+        Migration::RewriteKey {
+            from: SEEDED_KEY,
+            to: REWRITTEN_KEY,
+        },
+        Migration::ReencodeValues {
+            key_tag: KeyTag::Bid,
+        },
+    ]);
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    assert_eq!(builder.query(None, SEEDED_KEY, &[]), Ok(seeded_value()));
+    assert_eq!(builder.query(None, REWRITTEN_KEY, &[]), Ok(seeded_value()));
+}
+
+#[ignore]
+#[test]
+fn should_not_overwrite_existing_value_when_seeding() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let registry = builder
+        .query(None, Key::SystemContractRegistry, &[])
+        .expect("should have system contract registry");
+
+    let mut upgrade_request = upgrade_request(vec![Migration::SeedValue {
+        key: Key::SystemContractRegistry,
+        value: seeded_value(),
+    }]);
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    assert_eq!(
+        builder.query(None, Key::SystemContractRegistry, &[]),
+        Ok(registry)
+    );
+}
+
+#[ignore]
+#[test]
+fn should_fail_upgrade_when_rewriting_missing_key() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let mut upgrade_request = upgrade_request(vec![Migration::RewriteKey {
+        from: SEEDED_KEY,
+        to: REWRITTEN_KEY,
+    }]);
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request);
+
+    assert!(builder
+        .get_upgrade_result(0)
+        .expect("should have upgrade result")
+        .is_err());
+}
+
+#[ignore]
+#[test]
+fn should_dry_run_migrations_without_committing() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+    let pre_state_hash = builder.get_post_state_hash();
+
+    let mut upgrade_request = upgrade_request(vec![Migration::SeedValue {
+        key: SEEDED_KEY,
+        value: seeded_value(),
+    }]);
+
+    let execution_effect = builder
+        .dry_run_upgrade(&mut upgrade_request)
+        .expect("dry run should succeed");
+
+    assert_eq!(
+        execution_effect.transforms.get(&SEEDED_KEY),
+        Some(&Transform::Write(seeded_value()))
+    );
+    assert_eq!(builder.get_post_state_hash(), pre_state_hash);
+    assert!(builder.query(None, SEEDED_KEY, &[]).is_err());
+}
@@ -0,0 +1,99 @@
+use casper_engine_test_support::{
+    upgrade_scenario::UpgradeScenario, ExecuteRequestBuilder, InMemoryWasmTestBuilder,
+    DEFAULT_ACCOUNT_ADDR, MINIMUM_ACCOUNT_CREATION_BALANCE,
+};
+use casper_execution_engine::core::engine_state::Migration;
+use casper_types::{
+    account::AccountHash, runtime_args, system::mint, CLValue, Key, ProtocolVersion, RuntimeArgs,
+    StoredValue, U512,
+};
+
+const VERSION_PAIRS: [(ProtocolVersion, ProtocolVersion); 3] = [
+    (
+        ProtocolVersion::V1_0_0,
+        ProtocolVersion::from_parts(1, 0, 1),
+    ),
+    (
+        ProtocolVersion::V1_0_0,
+        ProtocolVersion::from_parts(1, 1, 0),
+    ),
+    (
+        ProtocolVersion::from_parts(1, 4, 0),
+        ProtocolVersion::from_parts(1, 5, 0),
+    ),
+];
+
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1; 32]);
+const SEEDED_KEY: Key = Key::Hash([42; 32]);
+
+fn transfer_to_account_1(
+    builder: &mut InMemoryWasmTestBuilder,
+    protocol_version: ProtocolVersion,
+    amount: U512,
+) {
+    let exec_request = ExecuteRequestBuilder::transfer(
+        *DEFAULT_ACCOUNT_ADDR,
+        runtime_args! {
+            mint::ARG_TARGET => ACCOUNT_1_ADDR,
+            mint::ARG_AMOUNT => amount,
+            mint::ARG_ID => <Option<u64>>::None
+        },
+    )
+    .with_protocol_version(protocol_version)
+    .build();
+
+    builder.exec(exec_request).expect_success().commit();
+}
+
+fn account_1_balance(builder: &InMemoryWasmTestBuilder) -> U512 {
+    let account = builder.get_expected_account(ACCOUNT_1_ADDR);
+    builder.get_purse_balance(account.main_purse())
+}
+
+fn seeded_value() -> StoredValue {
+    StoredValue::CLValue(CLValue::from_t(1_u64).unwrap())
+}
+
+#[ignore]
+#[test]
+fn should_preserve_balances_across_upgrades() {
+    let amount = U512::from(MINIMUM_ACCOUNT_CREATION_BALANCE);
+
+    for (from, to) in VERSION_PAIRS {
+        let builder = UpgradeScenario::new(from, to)
+            .with_workload(move |builder, protocol_version| {
+                transfer_to_account_1(builder, protocol_version, amount)
+            })
+            .track_account(ACCOUNT_1_ADDR)
+            .with_post_upgrade_workload(move |builder, protocol_version| {
+                transfer_to_account_1(builder, protocol_version, amount)
+            })
+            .run();
+
+        assert_eq!(account_1_balance(&builder), amount * 2);
+    }
+}
+
+#[ignore]
+#[test]
+fn should_apply_migrations_across_upgrades() {
+    for (from, to) in VERSION_PAIRS {
+        let builder = UpgradeScenario::new(from, to)
+            .with_workload(|builder, protocol_version| {
+                transfer_to_account_1(
+                    builder,
+                    protocol_version,
+                    U512::from(MINIMUM_ACCOUNT_CREATION_BALANCE),
+                )
+            })
+            .track_account(ACCOUNT_1_ADDR)
+            .track_key(Key::SystemContractRegistry)
+            .with_migrations(vec![Migration::SeedValue {
+                key: SEEDED_KEY,
+                value: seeded_value(),
+            }])
+            .run();
+
+        assert_eq!(builder.query(None, SEEDED_KEY, &[]), Ok(seeded_value()));
+    }
+}
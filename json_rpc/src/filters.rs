@@ -7,7 +7,9 @@
 mod tests;
 
 use bytes::Bytes;
+use futures::{stream, StreamExt};
 use http::{header::CONTENT_TYPE, HeaderMap, StatusCode};
+use serde::Serialize;
 use serde_json::{json, Map, Value};
 use tracing::{debug, trace, warn};
 use warp::{
@@ -28,6 +30,36 @@ use crate::{
 
 const CONTENT_TYPE_VALUE: &str = "application/json";
 
+/// The limits applied to batch requests, i.e. arrays of JSON-RPC requests sent in a single HTTP
+/// request.
+#[derive(Clone, Copy, Debug)]
+pub struct BatchLimits {
+    max_batch_size: u32,
+    max_concurrency: u32,
+}
+
+impl BatchLimits {
+    /// Returns new batch limits.
+    ///
+    /// `max_batch_size` is the maximum number of requests in a batch; batches are rejected if it is
+    /// `0`.  `max_concurrency` is the maximum number of requests of a single batch handled
+    /// concurrently; it is treated as `1` if given as `0`.
+    pub fn new(max_batch_size: u32, max_concurrency: u32) -> Self {
+        BatchLimits {
+            max_batch_size,
+            max_concurrency: max_concurrency.max(1),
+        }
+    }
+}
+
+/// The body of an HTTP response: a single JSON-RPC response, or the responses to a batch.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ResponseBody {
+    Single(Response),
+    Batch(Vec<Response>),
+}
+
 /// Returns a boxed warp filter which handles the initial setup.
 ///
 /// This includes:
@@ -66,12 +98,106 @@ pub fn base_filter<P: AsRef<str>>(path: P, max_body_bytes: u32) -> BoxedFilter<(
         .boxed()
 }
 
-/// Handles parsing a JSON-RPC request from the given HTTP body, executing it using the appropriate
-/// handler, and providing a JSON-RPC response (which could be a success or failure).
+/// Validates the given JSON-RPC request, executes it using the appropriate handler, and provides a
+/// JSON-RPC response (which could be a success or failure).
 ///
 /// Returns an `Err(Rejection)` only if the request is a Notification as per the JSON-RPC
-/// specification, i.e. the request doesn't contain an "id" field.  In this case, no JSON-RPC
-/// response is sent to the client.
+/// specification, i.e. the request doesn't contain an "id" field.
+async fn handle_request(
+    unvalidated_request: Map<String, Value>,
+    handlers: &RequestHandlers,
+    allow_unknown_fields: bool,
+) -> Result<Response, Rejection> {
+    match Request::new(unvalidated_request, allow_unknown_fields) {
+        Ok(request) => Ok(handlers.handle_request(request).await),
+        Err(ErrorOrRejection::Error { id, error }) => {
+            debug!(?error, "got an invalid request");
+            Ok(Response::new_failure(id, error))
+        }
+        Err(ErrorOrRejection::Rejection(rejection)) => {
+            debug!(?rejection, "rejecting an invalid request");
+            Err(rejection)
+        }
+    }
+}
+
+/// Handles a batch of JSON-RPC requests, returning the responses in the order of the requests.
+///
+/// Up to `batch_limits.max_concurrency` requests are handled concurrently.  As per the JSON-RPC
+/// specification, Notifications in the batch are not responded to.  If the batch consists of
+/// Notifications only, the request is rejected as for a single Notification.
+async fn handle_batch(
+    batch: Vec<Value>,
+    handlers: RequestHandlers,
+    allow_unknown_fields: bool,
+    batch_limits: BatchLimits,
+) -> Result<ResponseBody, Rejection> {
+    if batch.is_empty() {
+        debug!("got an empty batch");
+        let error = Error::new(ReservedErrorCode::InvalidRequest, "Empty batch");
+        return Ok(ResponseBody::Single(Response::new_failure(
+            Value::Null,
+            error,
+        )));
+    }
+    if batch.len() > batch_limits.max_batch_size as usize {
+        debug!(batch_size = batch.len(), "got a batch exceeding the limit");
+        let error = Error::new(
+            ReservedErrorCode::InvalidRequest,
+            format!(
+                "Batch of {} requests exceeds the limit of {}",
+                batch.len(),
+                batch_limits.max_batch_size
+            ),
+        );
+        return Ok(ResponseBody::Single(Response::new_failure(
+            Value::Null,
+            error,
+        )));
+    }
+
+    let handlers = &handlers;
+    let results: Vec<Result<Response, Rejection>> = stream::iter(batch)
+        .map(|unvalidated_request| async move {
+            match unvalidated_request {
+                Value::Object(unvalidated_request) => {
+                    handle_request(unvalidated_request, handlers, allow_unknown_fields).await
+                }
+                _ => {
+                    debug!("got a batch element which is not an object");
+                    let error = Error::new(
+                        ReservedErrorCode::InvalidRequest,
+                        "Expected batch element to be an Object",
+                    );
+                    Ok(Response::new_failure(Value::Null, error))
+                }
+            }
+        })
+        .buffered(batch_limits.max_concurrency as usize)
+        .collect()
+        .await;
+
+    let mut responses = Vec::with_capacity(results.len());
+    let mut maybe_rejection = None;
+    for result in results {
+        match result {
+            Ok(response) => responses.push(response),
+            Err(rejection) => maybe_rejection = Some(rejection),
+        }
+    }
+    match maybe_rejection {
+        Some(rejection) if responses.is_empty() => Err(rejection),
+        _ => Ok(ResponseBody::Batch(responses)),
+    }
+}
+
+/// Handles parsing a JSON-RPC request or batch of requests from the given HTTP body, executing
+/// them using the appropriate handler, and providing a JSON-RPC response, or an array of responses
+/// (which could be successes or failures).
+///
+/// Returns an `Err(Rejection)` only if the request is a Notification as per the JSON-RPC
+/// specification, i.e. the request doesn't contain an "id" field, or if a batch consists of
+/// Notifications only.  In this case, no JSON-RPC response is sent to the client.
 ///
 /// If `allow_unknown_fields` is `false`, requests with unknown fields will cause the server to
 /// respond with an error.
@@ -79,26 +205,29 @@ async fn handle_body(
     body: Bytes,
     handlers: RequestHandlers,
     allow_unknown_fields: bool,
-) -> Result<Response, Rejection> {
-    let response = match serde_json::from_slice::<Map<String, Value>>(&*body) {
-        Ok(unvalidated_request) => match Request::new(unvalidated_request, allow_unknown_fields) {
-            Ok(request) => handlers.handle_request(request).await,
-            Err(ErrorOrRejection::Error { id, error }) => {
-                debug!(?error, "got an invalid request");
-                Response::new_failure(id, error)
-            }
-            Err(ErrorOrRejection::Rejection(rejection)) => {
-                debug!(?rejection, "rejecting an invalid request");
-                return Err(rejection);
-            }
-        },
+    batch_limits: BatchLimits,
+) -> Result<ResponseBody, Rejection> {
+    let response = match serde_json::from_slice::<Value>(&*body) {
+        Ok(Value::Array(batch)) => {
+            return handle_batch(batch, handlers, allow_unknown_fields, batch_limits).await;
+        }
+        Ok(Value::Object(unvalidated_request)) => {
+            handle_request(unvalidated_request, &handlers, allow_unknown_fields).await?
+        }
+        Ok(other) => {
+            let error = serde_json::from_value::<Map<String, Value>>(other)
+                .expect_err("should fail to parse non-object as map");
+            debug!(%error, "got bad json");
+            let error = Error::new(ReservedErrorCode::ParseError, error.to_string());
+            Response::new_failure(Value::Null, error)
+        }
         Err(error) => {
             debug!(%error, "got bad json");
             let error = Error::new(ReservedErrorCode::ParseError, error.to_string());
             Response::new_failure(Value::Null, error)
         }
     };
-    Ok(response)
+    Ok(ResponseBody::Single(response))
 }
 
 /// Returns a boxed warp filter which handles parsing a JSON-RPC request from the given HTTP body,
@@ -112,14 +241,17 @@ async fn handle_body(
 ///
 /// If `allow_unknown_fields` is `false`, requests with unknown fields will cause the server to
 /// respond with an error.
+///
+/// Batch requests are handled as per the JSON-RPC specification, subject to `batch_limits`.
 pub fn main_filter(
     handlers: RequestHandlers,
     allow_unknown_fields: bool,
+    batch_limits: BatchLimits,
 ) -> BoxedFilter<(WithStatus<reply::Json>,)> {
     body::bytes()
         .and_then(move |body| {
             let handlers = handlers.clone();
-            async move { handle_body(body, handlers, allow_unknown_fields).await }
+            async move { handle_body(body, handlers, allow_unknown_fields, batch_limits).await }
         })
         .map(|response| reply::with_status(reply::json(&response), StatusCode::OK))
         .boxed()
@@ -11,11 +11,12 @@ use warp::{filters::BoxedFilter, Filter, Reply};
 use super::ResponseBodyOnRejection;
 use crate::{
     filters::{handle_rejection, main_filter},
-    Error, Params, RequestHandlersBuilder, ReservedErrorCode, Response,
+    BatchLimits, Error, Params, RequestHandlersBuilder, ReservedErrorCode, Response,
 };
 
 const GET_GOOD_THING: &str = "get good thing";
 const GET_BAD_THING: &str = "get bad thing";
+const MAX_BATCH_SIZE: u32 = 3;
 
 #[derive(PartialEq, Eq, Serialize, Deserialize, Debug)]
 struct GoodThing {
@@ -55,7 +56,7 @@ fn main_filter_with_recovery() -> BoxedFilter<(impl Reply,)> {
     handlers.register_handler(GET_BAD_THING, Arc::new(get_bad_thing));
     let handlers = handlers.build();
 
-    main_filter(handlers, false)
+    main_filter(handlers, false, BatchLimits::new(MAX_BATCH_SIZE, 2))
         .recover(handle_rejection)
         .boxed()
 }
@@ -318,3 +319,120 @@ async fn should_handle_invalid_json() {
         )
     );
 }
+
+async fn batch_from_http_response(response: http::Response<hyper::Body>) -> Vec<Response> {
+    let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body_bytes).unwrap()
+}
+
+#[tokio::test]
+async fn should_handle_batch_request() {
+    let _ = env_logger::try_init();
+
+    let filter = main_filter_with_recovery();
+
+    // The responses should be returned in the order of the requests, while the Notification (the
+    // request without an ID) shouldn't be responded to.
+    let http_response = warp::test::request()
+        .body(
+            r#"[
+                {"jsonrpc":"2.0","id":"a","method":"get good thing","params":["one"]},
+                {"jsonrpc":"2.0","method":"get good thing","params":["two"]},
+                {"jsonrpc":"2.0","id":2,"method":"get good thing"}
+            ]"#,
+        )
+        .filter(&filter)
+        .await
+        .unwrap()
+        .into_response();
+
+    assert_eq!(http_response.status(), StatusCode::OK);
+    let rpc_responses = batch_from_http_response(http_response).await;
+    assert_eq!(rpc_responses.len(), 2);
+    assert_eq!(rpc_responses[0].id(), "a");
+    assert_eq!(
+        rpc_responses[0].result(),
+        Some(GoodThing {
+            good_thing: "one".to_string()
+        })
+    );
+    assert_eq!(rpc_responses[1].id(), 2);
+    assert_eq!(
+        rpc_responses[1].error().unwrap(),
+        &Error::new(ReservedErrorCode::InvalidParams, "no params")
+    );
+}
+
+#[tokio::test]
+async fn should_handle_batch_with_invalid_element() {
+    let _ = env_logger::try_init();
+
+    let filter = main_filter_with_recovery();
+
+    let http_response = warp::test::request()
+        .body(r#"[1,{"jsonrpc":"2.0","id":"a","method":"get good thing","params":["one"]}]"#)
+        .filter(&filter)
+        .await
+        .unwrap()
+        .into_response();
+
+    assert_eq!(http_response.status(), StatusCode::OK);
+    let rpc_responses = batch_from_http_response(http_response).await;
+    assert_eq!(rpc_responses.len(), 2);
+    assert_eq!(rpc_responses[0].id(), &Value::Null);
+    assert_eq!(
+        rpc_responses[0].error().unwrap(),
+        &Error::new(
+            ReservedErrorCode::InvalidRequest,
+            "Expected batch element to be an Object"
+        )
+    );
+    assert_eq!(rpc_responses[1].id(), "a");
+    assert!(rpc_responses[1].is_success());
+}
+
+#[tokio::test]
+async fn should_reject_empty_or_oversized_batch() {
+    let _ = env_logger::try_init();
+
+    let filter = main_filter_with_recovery();
+
+    let http_response = warp::test::request()
+        .body("[]")
+        .filter(&filter)
+        .await
+        .unwrap()
+        .into_response();
+
+    assert_eq!(http_response.status(), StatusCode::OK);
+    let rpc_response = from_http_response(http_response).await;
+    assert_eq!(rpc_response.id(), &Value::Null);
+    assert_eq!(
+        rpc_response.error().unwrap(),
+        &Error::new(ReservedErrorCode::InvalidRequest, "Empty batch")
+    );
+
+    let request = r#"{"jsonrpc":"2.0","id":1,"method":"get good thing","params":["one"]}"#;
+    let batch = vec![request; MAX_BATCH_SIZE as usize + 1].join(",");
+    let http_response = warp::test::request()
+        .body(format!("[{}]", batch))
+        .filter(&filter)
+        .await
+        .unwrap()
+        .into_response();
+
+    assert_eq!(http_response.status(), StatusCode::OK);
+    let rpc_response = from_http_response(http_response).await;
+    assert_eq!(rpc_response.id(), &Value::Null);
+    assert_eq!(
+        rpc_response.error().unwrap(),
+        &Error::new(
+            ReservedErrorCode::InvalidRequest,
+            format!(
+                "Batch of {} requests exceeds the limit of {}",
+                MAX_BATCH_SIZE + 1,
+                MAX_BATCH_SIZE
+            )
+        )
+    );
+}
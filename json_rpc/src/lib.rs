@@ -12,7 +12,7 @@
 //! # Example
 //!
 //! ```no_run
-//! use casper_json_rpc::{Error, Params, RequestHandlersBuilder};
+//! use casper_json_rpc::{BatchLimits, Error, Params, RequestHandlersBuilder};
 //! use std::{convert::Infallible, sync::Arc};
 //!
 //! # #[allow(unused)]
@@ -40,7 +40,14 @@
 //!     let path = "rpc";
 //!     let max_body_bytes = 1024;
 //!     let allow_unknown_fields = false;
-//!     let route = casper_json_rpc::route(path, max_body_bytes, handlers, allow_unknown_fields);
+//!     let batch_limits = BatchLimits::new(20, 4);
+//!     let route = casper_json_rpc::route(
+//!         path,
+//!         max_body_bytes,
+//!         handlers,
+//!         allow_unknown_fields,
+//!         batch_limits,
+//!     );
 //!
 //!     // Convert it into a `Service` and run it.
 //!     let make_svc = hyper::service::make_service_fn(move |_| {
@@ -89,6 +96,7 @@ use http::{header::CONTENT_TYPE, Method};
 use warp::{filters::BoxedFilter, Filter, Reply};
 
 pub use error::{Error, ErrorCodeT, ReservedErrorCode};
+pub use filters::BatchLimits;
 pub use request::Params;
 pub use request_handlers::{RequestHandlers, RequestHandlersBuilder};
 pub use response::Response;
@@ -110,6 +118,9 @@ const JSON_RPC_VERSION: &str = "2.0";
 /// If `allow_unknown_fields` is `false`, requests with unknown fields will cause the server to
 /// respond with an error.
 ///
+/// `batch_limits` sets the maximum number of requests in a batch request, and how many of them are
+/// handled concurrently.
+///
 /// Note that this is a convenience function combining the lower-level functions in [`filters`]
 /// along with [a warp CORS filter](https://docs.rs/warp/latest/warp/filters/cors/index.html) which
 ///   * allows any origin
@@ -122,9 +133,14 @@ pub fn route<P: AsRef<str>>(
     max_body_bytes: u32,
     handlers: RequestHandlers,
     allow_unknown_fields: bool,
+    batch_limits: BatchLimits,
 ) -> BoxedFilter<(impl Reply,)> {
     filters::base_filter(path, max_body_bytes)
-        .and(filters::main_filter(handlers, allow_unknown_fields))
+        .and(filters::main_filter(
+            handlers,
+            allow_unknown_fields,
+            batch_limits,
+        ))
         .recover(filters::handle_rejection)
         .with(
             warp::cors()
@@ -17,7 +17,8 @@ use casper_node::{cli::Cli, MAX_THREAD_COUNT};
 
 /// Aborting panic hook.
 ///
-/// Will exit the application using `abort` when an error occurs. Always shows a backtrace.
+/// Will exit the application using `abort` when an error occurs. Always shows a backtrace, and
+/// writes a crash dump and reports the panic as an incident, if configured.
 fn panic_hook(info: &PanicInfo) {
     let backtrace = Backtrace::new();
 
@@ -33,6 +34,9 @@ fn panic_hook(info: &PanicInfo) {
         eprintln!("{}", info);
     }
 
+    casper_node::write_crash_dump(&format!("panic: {}", info));
+    casper_node::report_panic(info);
+
     // Abort after a panic, even if only a worker thread panicked.
     process::abort()
 }
@@ -0,0 +1,358 @@
+//! Append-only audit log of administrative actions.
+//!
+//! Diagnostics port commands, config reloads, peer bans and validator mode changes are recorded as
+//! lines of JSON in the configured file, along with the time and the source of the action.  Every
+//! entry contains the hash of the line preceding it, so modifying, removing or reordering any
+//! entry breaks the chain of hashes from that point on, which `casper-node verify-audit-log`
+//! detects.  The node refuses to start with a broken audit log.
+
+use std::{
+    borrow::Cow,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use datasize::DataSize;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{error, info};
+
+use casper_hashing::Digest;
+use casper_types::Timestamp;
+
+use crate::{
+    components::consensus::ValidatorMode, effect::diagnostics_port::ConfigReloadReport,
+    types::NodeId,
+};
+
+/// The audit log of the running node, if enabled.
+static AUDIT_LOG: OnceCell<Mutex<AuditLog>> = OnceCell::new();
+
+/// Audit log configuration.
+#[derive(Clone, DataSize, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AuditLogConfig {
+    /// Path of the audit log file, absolute or relative to the config file.
+    ///
+    /// If not set, administrative actions are not recorded.
+    #[serde(default)]
+    pub(crate) path: Option<PathBuf>,
+}
+
+/// The origin of an administrative action.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Source {
+    /// A client of the diagnostics port, authenticated by the credentials of its process.
+    ///
+    /// The credentials are missing if the operating system could not provide them.
+    DiagnosticsPort {
+        client_id: u64,
+        uid: Option<u32>,
+        gid: Option<u32>,
+        pid: Option<i32>,
+    },
+    /// A client of the diagnostics port connected via TCP, authenticated by its TLS certificate.
+    RemoteDiagnosticsPort {
+        client_id: u64,
+        addr: SocketAddr,
+        subject: String,
+        fingerprint: String,
+    },
+    /// A signal sent to the node process.
+    Signal { signal: &'static str },
+    /// The node itself, acting on its own detection of misbehavior.
+    Node,
+}
+
+/// An administrative action.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Action {
+    /// A command sent to the diagnostics port.
+    DiagnosticsCommand { command: String },
+    /// A reload of the config file.
+    ConfigReload {
+        applied: Vec<String>,
+        requires_restart: Vec<String>,
+        error: Option<String>,
+    },
+    /// A peer address being blocked.
+    PeerBan {
+        peer_id: Option<NodeId>,
+        addr: SocketAddr,
+        reason: String,
+    },
+    /// A change of the validator mode.
+    ValidatorModeChange {
+        mode: ValidatorMode,
+        error: Option<String>,
+    },
+}
+
+impl Action {
+    /// Creates the action recording the outcome of a config reload.
+    pub(crate) fn config_reload(result: &Result<ConfigReloadReport, Cow<'static, str>>) -> Self {
+        match result {
+            Ok(report) => Action::ConfigReload {
+                applied: report.applied.clone(),
+                requires_restart: report.requires_restart.clone(),
+                error: None,
+            },
+            Err(error) => Action::ConfigReload {
+                applied: vec![],
+                requires_restart: vec![],
+                error: Some(error.to_string()),
+            },
+        }
+    }
+}
+
+/// An entry of the audit log, as written to a single line of the file.
+#[derive(Serialize)]
+struct Entry<'a> {
+    /// Position of the entry in the log, starting at 0.
+    sequence: u64,
+    timestamp: Timestamp,
+    source: &'a Source,
+    action: &'a Action,
+    /// Hash of the preceding line, or the all-zero digest for the first entry.
+    prev_hash: Digest,
+}
+
+/// The fields of an entry required to verify the chain of hashes.
+#[derive(Deserialize)]
+struct EntryLink {
+    sequence: u64,
+    prev_hash: Digest,
+}
+
+/// The end of a verified audit log.
+#[derive(Debug, Default)]
+pub(crate) struct Head {
+    /// The number of entries in the log.
+    pub(crate) entries: u64,
+    /// The hash of the last line, to be referenced by the next entry.
+    pub(crate) last_hash: Digest,
+}
+
+/// Error reading or verifying an audit log.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// The audit log could not be read or opened.
+    #[error("could not access audit log {}: {error}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        error: io::Error,
+    },
+    /// An entry could not be parsed.
+    #[error("entry {sequence} of the audit log is malformed: {error}")]
+    Malformed {
+        sequence: u64,
+        #[source]
+        error: serde_json::Error,
+    },
+    /// An entry does not reference the entry preceding it.
+    #[error(
+        "entry {sequence} of the audit log does not follow the previous entry, expected sequence \
+         number {sequence} and previous hash {expected_prev_hash}"
+    )]
+    BrokenChain {
+        sequence: u64,
+        expected_prev_hash: Digest,
+    },
+    /// The last line of the log is not terminated by a newline.
+    #[error("the audit log ends with an incomplete entry")]
+    IncompleteEntry,
+    /// The audit log has been initialized before.
+    #[error("audit log already initialized")]
+    AlreadyInitialized,
+}
+
+/// An open audit log.
+struct AuditLog {
+    file: File,
+    path: PathBuf,
+    head: Head,
+}
+
+impl AuditLog {
+    /// Opens the audit log at `path` for appending, verifying any existing entries.
+    fn open(path: &Path) -> Result<Self, Error> {
+        let head = if path.exists() {
+            verify(path)?
+        } else {
+            Head::default()
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|error| Error::Io {
+                path: path.to_owned(),
+                error,
+            })?;
+        Ok(AuditLog {
+            file,
+            path: path.to_owned(),
+            head,
+        })
+    }
+
+    /// Appends a new entry, flushing it to disk before returning.
+    fn append(&mut self, source: &Source, action: &Action) -> io::Result<()> {
+        let entry = Entry {
+            sequence: self.head.entries,
+            timestamp: Timestamp::now(),
+            source,
+            action,
+            prev_hash: self.head.last_hash,
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        let hash = Digest::hash(&line);
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+        self.head = Head {
+            entries: self.head.entries + 1,
+            last_hash: hash,
+        };
+        Ok(())
+    }
+}
+
+/// Opens the audit log at `path`, to which all subsequent actions are recorded.
+pub(crate) fn init(path: &Path) -> Result<(), Error> {
+    let audit_log = AuditLog::open(path)?;
+    info!(
+        path = %path.display(),
+        entries = audit_log.head.entries,
+        last_hash = %audit_log.head.last_hash,
+        "opened audit log"
+    );
+    AUDIT_LOG
+        .set(Mutex::new(audit_log))
+        .map_err(|_| Error::AlreadyInitialized)
+}
+
+/// Records an administrative action in the audit log, if enabled.
+///
+/// Failures to write the entry are logged, but do not affect the action itself.
+pub(crate) fn record(source: &Source, action: Action) {
+    let audit_log = match AUDIT_LOG.get() {
+        Some(audit_log) => audit_log,
+        None => return,
+    };
+    let mut audit_log = audit_log
+        .lock()
+        .expect("audit log lock should not be poisoned");
+    if let Err(err) = audit_log.append(source, &action) {
+        error!(
+            path = %audit_log.path.display(),
+            %err,
+            ?source,
+            ?action,
+            "failed to write to audit log"
+        );
+    }
+}
+
+/// Verifies the chain of hashes of the audit log at `path`.
+pub(crate) fn verify(path: &Path) -> Result<Head, Error> {
+    let contents = fs::read(path).map_err(|error| Error::Io {
+        path: path.to_owned(),
+        error,
+    })?;
+    if !contents.is_empty() && !contents.ends_with(b"\n") {
+        return Err(Error::IncompleteEntry);
+    }
+
+    let mut head = Head::default();
+    for line in contents.split_terminator(|byte| *byte == b'\n') {
+        let link: EntryLink = serde_json::from_slice(line).map_err(|error| Error::Malformed {
+            sequence: head.entries,
+            error,
+        })?;
+        if link.sequence != head.entries || link.prev_hash != head.last_hash {
+            return Err(Error::BrokenChain {
+                sequence: head.entries,
+                expected_prev_hash: head.last_hash,
+            });
+        }
+        head = Head {
+            entries: head.entries + 1,
+            last_hash: Digest::hash(line),
+        };
+    }
+    Ok(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_actions(path: &Path, count: u64) {
+        let mut audit_log = AuditLog::open(path).unwrap();
+        for client_id in 0..count {
+            let source = Source::DiagnosticsPort {
+                client_id,
+                uid: Some(1000),
+                gid: Some(1000),
+                pid: Some(4242),
+            };
+            let action = Action::DiagnosticsCommand {
+                command: "reload-config".to_string(),
+            };
+            audit_log.append(&source, &action).unwrap();
+        }
+    }
+
+    #[test]
+    fn should_chain_entries_across_reopening() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("audit.log");
+
+        record_actions(&path, 2);
+        record_actions(&path, 3);
+
+        let head = verify(&path).unwrap();
+        assert_eq!(head.entries, 5);
+        let contents = fs::read(&path).unwrap();
+        let last_line = contents.split_terminator(|byte| *byte == b'\n').last();
+        assert_eq!(head.last_hash, Digest::hash(last_line.unwrap()));
+    }
+
+    #[test]
+    fn should_detect_tampering() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("audit.log");
+        record_actions(&path, 3);
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+
+        // Modifying an entry breaks the link from the entry following it.
+        let modified = contents.replacen("\"client_id\":1", "\"client_id\":7", 1);
+        fs::write(&path, modified).unwrap();
+        assert!(matches!(
+            verify(&path),
+            Err(Error::BrokenChain { sequence: 2, .. })
+        ));
+
+        // So does removing an entry.
+        fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert!(matches!(
+            verify(&path),
+            Err(Error::BrokenChain { sequence: 1, .. })
+        ));
+
+        // A truncated entry is rejected, and no further entries can be appended.
+        fs::write(&path, &contents[..contents.len() - 10]).unwrap();
+        assert!(matches!(verify(&path), Err(Error::IncompleteEntry)));
+        assert!(AuditLog::open(&path).is_err());
+    }
+}
@@ -0,0 +1,349 @@
+//! Offline checks of a chainspec, run by operators via the `chainspec-check` subcommand before an
+//! upgrade window.
+//!
+//! On top of the checks the node itself runs when loading a chainspec, this reports problems which
+//! would only surface at genesis or at the activation point, such as inconsistent genesis accounts
+//! or an upgrade which doesn't follow on from the previous chainspec.
+
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use casper_types::{
+    system::auction::{DelegationRate, DELEGATION_RATE_DENOMINATOR},
+    ProtocolVersion, PublicKey, TimeDiff,
+};
+
+use crate::types::{ActivationPoint, Chainspec};
+
+/// A problem found in a chainspec.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub(crate) enum Problem {
+    /// The chainspec failed the node's own validity checks.
+    #[error("chainspec failed validation; see the logged errors for details")]
+    Invalid,
+
+    /// A Wasm opcode is free to execute.
+    #[error("wasm opcode cost `{0}` is zero")]
+    ZeroOpcodeCost(&'static str),
+
+    /// Storage is free.
+    #[error("wasm storage cost per byte is zero")]
+    ZeroStorageCost,
+
+    /// Contracts can't allocate any memory.
+    #[error("wasm max_memory is zero")]
+    ZeroMaxMemory,
+
+    /// Contracts can't use the stack.
+    #[error("wasm max_stack_height is zero")]
+    ZeroMaxStackHeight,
+
+    /// Neither an era duration nor a minimum era height is set, so every block ends an era.
+    #[error("era_duration and minimum_era_height are both zero")]
+    ZeroEraLength,
+
+    /// The era duration can't be reached with the minimum era height at the minimum round length.
+    #[error(
+        "era_duration {era_duration} is shorter than minimum_era_height {minimum_era_height} \
+        rounds of {round_length}"
+    )]
+    EraDurationTooShort {
+        /// The configured era duration.
+        era_duration: TimeDiff,
+        /// The configured minimum era height.
+        minimum_era_height: u64,
+        /// The minimum round length.
+        round_length: TimeDiff,
+    },
+
+    /// An account is listed more than once in `accounts.toml`.
+    #[error("account {0} is listed more than once")]
+    DuplicatedAccount(PublicKey),
+
+    /// There are more genesis validators than validator slots.
+    #[error("{validators} genesis validators exceed {validator_slots} validator slots")]
+    TooManyGenesisValidators {
+        /// The number of genesis validators.
+        validators: usize,
+        /// The configured number of validator slots.
+        validator_slots: u32,
+    },
+
+    /// A genesis validator has a zero bond.
+    #[error("genesis validator {0} has a zero bonded amount")]
+    ZeroBondedAmount(PublicKey),
+
+    /// A genesis validator's delegation rate is out of range.
+    #[error(
+        "genesis validator {public_key} has delegation rate {delegation_rate} above {}",
+        DELEGATION_RATE_DENOMINATOR
+    )]
+    InvalidDelegationRate {
+        /// The validator's public key.
+        public_key: PublicKey,
+        /// The configured delegation rate.
+        delegation_rate: DelegationRate,
+    },
+
+    /// A delegator delegates to an account which isn't a genesis validator.
+    #[error("delegator {delegator} delegates to {validator}, which is not a genesis validator")]
+    OrphanedDelegator {
+        /// The delegator's public key.
+        delegator: PublicKey,
+        /// The public key delegated to.
+        validator: PublicKey,
+    },
+
+    /// A delegator delegates a zero amount.
+    #[error("delegator {0} has a zero delegated amount")]
+    ZeroDelegatedAmount(PublicKey),
+
+    /// The protocol version doesn't increase relative to the previous chainspec.
+    #[error("protocol version {version} is not greater than the previous {previous}")]
+    ProtocolVersionNotIncreasing {
+        /// The protocol version of the checked chainspec.
+        version: ProtocolVersion,
+        /// The protocol version of the previous chainspec.
+        previous: ProtocolVersion,
+    },
+
+    /// The network name differs from the previous chainspec.
+    #[error("network name {name} differs from the previous {previous}")]
+    NetworkNameChanged {
+        /// The network name of the checked chainspec.
+        name: String,
+        /// The network name of the previous chainspec.
+        previous: String,
+    },
+
+    /// The activation point isn't after the previous chainspec's one.
+    #[error("{activation_point} is not after the previous {previous}")]
+    ActivationPointNotIncreasing {
+        /// The activation point of the checked chainspec.
+        activation_point: ActivationPoint,
+        /// The activation point of the previous chainspec.
+        previous: ActivationPoint,
+    },
+}
+
+/// Checks `chainspec` and, if given, that it is a valid upgrade of `maybe_previous`.
+///
+/// Returns all problems found, which is empty if the chainspec is fine.
+pub(crate) fn check_chainspec(
+    chainspec: &Chainspec,
+    maybe_previous: Option<&Chainspec>,
+) -> Vec<Problem> {
+    let mut problems = vec![];
+    if !chainspec.is_valid() {
+        problems.push(Problem::Invalid);
+    }
+    check_costs(chainspec, &mut problems);
+    check_era_length(chainspec, &mut problems);
+    check_accounts(chainspec, &mut problems);
+    if let Some(previous) = maybe_previous {
+        check_upgrade(chainspec, previous, &mut problems);
+    }
+    problems
+}
+
+fn check_costs(chainspec: &Chainspec, problems: &mut Vec<Problem>) {
+    let wasm_config = &chainspec.wasm_config;
+    let opcode_costs = wasm_config.opcode_costs();
+    let named_opcode_costs = [
+        ("bit", opcode_costs.bit),
+        ("add", opcode_costs.add),
+        ("mul", opcode_costs.mul),
+        ("div", opcode_costs.div),
+        ("load", opcode_costs.load),
+        ("store", opcode_costs.store),
+        ("const", opcode_costs.op_const),
+        ("local", opcode_costs.local),
+        ("global", opcode_costs.global),
+        ("control_flow", opcode_costs.control_flow),
+        ("integer_comparison", opcode_costs.integer_comparison),
+        ("conversion", opcode_costs.conversion),
+        ("unreachable", opcode_costs.unreachable),
+        ("nop", opcode_costs.nop),
+        ("current_memory", opcode_costs.current_memory),
+        ("grow_memory", opcode_costs.grow_memory),
+        ("regular", opcode_costs.regular),
+    ];
+    problems.extend(
+        named_opcode_costs
+            .iter()
+            .filter(|(_, cost)| *cost == 0)
+            .map(|(name, _)| Problem::ZeroOpcodeCost(*name)),
+    );
+    if wasm_config.storage_costs().gas_per_byte() == 0 {
+        problems.push(Problem::ZeroStorageCost);
+    }
+    if wasm_config.max_memory == 0 {
+        problems.push(Problem::ZeroMaxMemory);
+    }
+    if wasm_config.max_stack_height == 0 {
+        problems.push(Problem::ZeroMaxStackHeight);
+    }
+}
+
+fn check_era_length(chainspec: &Chainspec, problems: &mut Vec<Problem>) {
+    let era_duration = chainspec.core_config.era_duration;
+    let minimum_era_height = chainspec.core_config.minimum_era_height;
+    if era_duration.millis() == 0 && minimum_era_height == 0 {
+        problems.push(Problem::ZeroEraLength);
+        return;
+    }
+    let round_length = TimeDiff::from(1u64 << chainspec.highway_config.minimum_round_exponent);
+    if era_duration.millis() > 0
+        && era_duration.millis() < minimum_era_height.saturating_mul(round_length.millis())
+    {
+        problems.push(Problem::EraDurationTooShort {
+            era_duration,
+            minimum_era_height,
+            round_length,
+        });
+    }
+}
+
+fn check_accounts(chainspec: &Chainspec, problems: &mut Vec<Problem>) {
+    let accounts_config = &chainspec.network_config.accounts_config;
+
+    let mut public_keys = BTreeSet::new();
+    let mut validators = BTreeSet::new();
+    for account in accounts_config.accounts() {
+        let public_key = account.public_key();
+        if !public_keys.insert(public_key.clone()) {
+            problems.push(Problem::DuplicatedAccount(public_key.clone()));
+        }
+        if let Some(validator) = account.validator() {
+            if validator.bonded_amount().value().is_zero() {
+                problems.push(Problem::ZeroBondedAmount(public_key.clone()));
+            }
+            if validator.delegation_rate() > DELEGATION_RATE_DENOMINATOR {
+                problems.push(Problem::InvalidDelegationRate {
+                    public_key: public_key.clone(),
+                    delegation_rate: validator.delegation_rate(),
+                });
+            }
+            validators.insert(public_key);
+        }
+    }
+
+    let validator_slots = chainspec.core_config.validator_slots;
+    if validators.len() > validator_slots as usize {
+        problems.push(Problem::TooManyGenesisValidators {
+            validators: validators.len(),
+            validator_slots,
+        });
+    }
+
+    for delegator in accounts_config.delegators() {
+        if !validators.contains(delegator.validator_public_key()) {
+            problems.push(Problem::OrphanedDelegator {
+                delegator: delegator.delegator_public_key().clone(),
+                validator: delegator.validator_public_key().clone(),
+            });
+        }
+        if delegator.delegated_amount().value().is_zero() {
+            problems.push(Problem::ZeroDelegatedAmount(
+                delegator.delegator_public_key().clone(),
+            ));
+        }
+    }
+}
+
+fn check_upgrade(chainspec: &Chainspec, previous: &Chainspec, problems: &mut Vec<Problem>) {
+    let version = chainspec.protocol_version();
+    if !version.is_upgrade_of(&previous.protocol_version()) {
+        problems.push(Problem::ProtocolVersionNotIncreasing {
+            version,
+            previous: previous.protocol_version(),
+        });
+    }
+
+    let name = &chainspec.network_config.name;
+    if *name != previous.network_config.name {
+        problems.push(Problem::NetworkNameChanged {
+            name: name.clone(),
+            previous: previous.network_config.name.clone(),
+        });
+    }
+
+    let activation_point = chainspec.protocol_config.activation_point;
+    let previous_activation_point = previous.protocol_config.activation_point;
+    if !activation_point.is_after(&previous_activation_point) {
+        problems.push(Problem::ActivationPointNotIncreasing {
+            activation_point,
+            previous: previous_activation_point,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{EraId, Timestamp};
+
+    use super::*;
+    use crate::{
+        types::ChainspecRawBytes,
+        utils::{Loadable, RESOURCES_PATH},
+    };
+
+    fn production_chainspec() -> Chainspec {
+        let (chainspec, _) =
+            <(Chainspec, ChainspecRawBytes)>::from_path(RESOURCES_PATH.join("production")).unwrap();
+        chainspec
+    }
+
+    #[test]
+    fn production_chainspec_should_pass() {
+        assert_eq!(check_chainspec(&production_chainspec(), None), vec![]);
+    }
+
+    #[test]
+    fn should_detect_era_duration_too_short() {
+        let mut chainspec = production_chainspec();
+        chainspec.core_config.era_duration = TimeDiff::from(1);
+        assert!(matches!(
+            check_chainspec(&chainspec, None).as_slice(),
+            [Problem::EraDurationTooShort { .. }]
+        ));
+    }
+
+    #[test]
+    fn should_detect_non_monotonic_upgrade() {
+        let previous = production_chainspec();
+        let mut chainspec = production_chainspec();
+        chainspec.network_config.name = "other-network".to_string();
+
+        let problems = check_chainspec(&chainspec, Some(&previous));
+        assert!(problems.contains(&Problem::ProtocolVersionNotIncreasing {
+            version: chainspec.protocol_version(),
+            previous: previous.protocol_version(),
+        }));
+        assert!(problems.contains(&Problem::NetworkNameChanged {
+            name: "other-network".to_string(),
+            previous: previous.network_config.name.clone(),
+        }));
+        assert!(problems.contains(&Problem::ActivationPointNotIncreasing {
+            activation_point: chainspec.protocol_config.activation_point,
+            previous: previous.protocol_config.activation_point,
+        }));
+    }
+
+    #[test]
+    fn should_compare_activation_points() {
+        let genesis = ActivationPoint::Genesis(Timestamp::from(1_000));
+        assert!(ActivationPoint::EraId(EraId::from(1)).is_after(&genesis));
+        assert!(!ActivationPoint::EraId(EraId::from(0)).is_after(&genesis));
+        assert!(!genesis.is_after(&ActivationPoint::EraId(EraId::from(1))));
+        assert!(ActivationPoint::Timestamp {
+            timestamp: Timestamp::from(2_000)
+        }
+        .is_after(&genesis));
+        assert!(!ActivationPoint::Timestamp {
+            timestamp: Timestamp::from(1_000)
+        }
+        .is_after(&genesis));
+    }
+}
@@ -6,12 +6,13 @@ pub mod arglang;
 
 use std::{
     alloc::System,
-    env, fs,
+    env, fs, io,
     path::{Path, PathBuf},
     str::FromStr,
 };
 
 use anyhow::{self, Context};
+use once_cell::sync::OnceCell;
 use prometheus::Registry;
 use regex::Regex;
 use stats_alloc::{StatsAlloc, INSTRUMENTED_SYSTEM};
@@ -19,12 +20,21 @@ use structopt::StructOpt;
 use toml::{value::Table, Value};
 use tracing::{error, info};
 
+use casper_hashing::Digest;
+use casper_types::{PublicKey, SecretKey};
+
 use crate::{
-    logging,
-    reactor::{initializer, joiner, participating, ReactorExit, Runner},
+    audit_log,
+    components::{
+        clock_skew,
+        small_network::NetworkKeyEndorsement,
+        storage::{check::check_storage, migrate_global_state},
+    },
+    incident, logging,
+    reactor::{initializer, joiner, participating, ReactorExit, Runner, Supervisor},
     setup_signal_hooks,
-    types::ExitCode,
-    utils::WithDir,
+    types::{BlockHash, Chainspec, ChainspecRawBytes, ExitCode},
+    utils::{Loadable, WithDir},
 };
 
 // We override the standard allocator to gather metrics and tune the allocator via th MALLOC_CONF
@@ -32,6 +42,9 @@ use crate::{
 #[global_allocator]
 static ALLOC: &StatsAlloc<System> = &INSTRUMENTED_SYSTEM;
 
+/// The config file and overrides the running validator was started with.
+static CONFIG_SOURCE: OnceCell<ConfigSource> = OnceCell::new();
+
 // Note: The docstring on `Cli` is the help shown when calling the binary with `--help`.
 #[derive(Debug, StructOpt)]
 #[structopt(version = crate::VERSION_STRING_COLOR.as_str())]
@@ -74,9 +87,147 @@ pub enum Cli {
         #[structopt(long)]
         new_config: PathBuf,
     },
+    /// Check a chainspec before using it.
+    ///
+    /// Loads and validates the chainspec in the given directory, prints its hash and any problems
+    /// found, and exits with a non-zero exit code if there were any.
+    ChainspecCheck {
+        /// Path to the directory containing the chainspec.toml to check.
+        chainspec_dir: PathBuf,
+        /// Path to the directory containing the chainspec.toml of the previous protocol version, to
+        /// check the upgrade against.
+        #[structopt(long)]
+        previous_chainspec_dir: Option<PathBuf>,
+    },
+    /// Import a global state snapshot.
+    ///
+    /// Verifies that the snapshot is of the trusted block and that the block has sufficient valid
+    /// finality signatures, then writes the snapshot's global state to the node's storage and
+    /// exits.  The node should then be started with `node.trusted_hash` set to the same block hash,
+    /// so that it joins the network from that block without downloading its global state.
+    ImportSnapshot {
+        /// Path to configuration file.
+        config: PathBuf,
+        /// Path to the snapshot file.
+        snapshot: PathBuf,
+        /// Hex-encoded hash of the block the snapshot was taken at.  Defaults to the config file's
+        /// `node.trusted_hash`.
+        #[structopt(long)]
+        trusted_hash: Option<String>,
+    },
+    /// Export a global state snapshot.
+    ///
+    /// Writes the global state of a finalized block, along with the block's header, its finality
+    /// signatures and the headers linking it to the previous era's switch block, to a new snapshot
+    /// file and exits.  Other nodes can bootstrap from the snapshot using `import-snapshot`.  The
+    /// node may keep running while the snapshot is exported.
+    ExportSnapshot {
+        /// Path to configuration file.
+        config: PathBuf,
+        /// Path to the snapshot file to create.
+        snapshot: PathBuf,
+        /// Hex-encoded hash of the block to take the snapshot at.  Defaults to the highest block of
+        /// the latest era with sufficient finality signatures.
+        #[structopt(long)]
+        block_hash: Option<String>,
+    },
+    /// Check the node's storage for consistency.
+    ///
+    /// Opens the storage configured in the given config file read-only, verifies the chain of block
+    /// hashes, the deploys of complete blocks against their bodies and the presence of their global
+    /// state, then prints a JSON report and exits with a non-zero exit code if there were any
+    /// problems.  The node must not be running.
+    StorageCheck {
+        /// Path to configuration file.
+        config: PathBuf,
+    },
+    /// Move the global state to the folder configured as `storage.global_state_path`.
+    ///
+    /// Moves the global state database out of the storage folder, or out of the folder given by
+    /// `--from`, e.g. after changing `storage.global_state_path`.  Files are copied if they cannot
+    /// be renamed, e.g. when moving to a different disk.  The node must not be running.
+    MigrateGlobalState {
+        /// Path to configuration file.
+        config: PathBuf,
+        /// Folder currently containing the global state, defaults to the storage folder.
+        #[structopt(long)]
+        from: Option<PathBuf>,
+    },
+    /// Generate a key pair backed up by a BIP-39 mnemonic.
+    ///
+    /// Writes secret_key.pem, public_key.pem and public_key_hex to the output directory and prints
+    /// the mnemonic, from which the same keys can be restored using `--restore`.  The mnemonic
+    /// should be written down and kept as safe as the secret key itself.
+    Keygen {
+        /// Directory to write the keys to.
+        output_dir: PathBuf,
+        /// Key algorithm, either 'ed25519', 'secp256k1' or 'secp256r1'.
+        #[structopt(long, default_value = "ed25519")]
+        algorithm: String,
+        /// Number of words of the generated mnemonic: 12, 15, 18, 21 or 24.
+        #[structopt(long, default_value = "24")]
+        words: usize,
+        /// Restore the keys from a mnemonic read from stdin instead of generating new ones.
+        #[structopt(long)]
+        restore: bool,
+        /// Optional passphrase used together with the mnemonic.
+        #[structopt(
+            long,
+            env = "CASPER_MNEMONIC_PASSPHRASE",
+            hide_env_values = true,
+            default_value = ""
+        )]
+        passphrase: String,
+    },
+    /// Endorse a network key with a validator key.
+    ///
+    /// Signs the given network public key with the validator secret key and writes the resulting
+    /// endorsement to the output file.  A node configured with the network secret key and this
+    /// endorsement under `network.network_key` signs its handshakes with the network key, so the
+    /// validator secret key does not need to be present for networking.
+    EndorseNetworkKey {
+        /// Path to the validator's secret key.
+        #[structopt(long)]
+        validator_secret_key: PathBuf,
+        /// Path to the network public key to endorse.
+        #[structopt(long)]
+        network_public_key: PathBuf,
+        /// Path of the endorsement file to write.
+        #[structopt(long)]
+        output: PathBuf,
+    },
+    /// Verify the chain of hashes of an audit log.
+    ///
+    /// Prints the number of entries and the hash of the last entry, or the first entry found to be
+    /// modified, removed or reordered, in which case the exit code is non-zero.  The printed hash
+    /// can be kept elsewhere to later prove that the log has not been truncated or rewritten.
+    VerifyAuditLog {
+        /// Path to the audit log.
+        audit_log: PathBuf,
+    },
+    /// Print the effective configuration.
+    ///
+    /// Validates the configuration file, applies any overrides given via `-C` and prints the
+    /// resulting configuration as TOML, including the default values of all options not set
+    /// explicitly.
+    DumpConfig {
+        /// Path to configuration file.
+        config: PathBuf,
+
+        #[structopt(
+            short = "C",
+            long,
+            env = "NODE_CONFIG",
+            use_delimiter(true),
+            value_delimiter(";")
+        )]
+        /// Overrides and extensions for configuration file entries in the form
+        /// <SECTION>.<KEY>=<VALUE>.  For example, '-C=node.chainspec_config_path=chainspec.toml'
+        config_ext: Vec<ConfigExt>,
+    },
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 /// Command line extension to be applied to TOML-based config file values.
 pub struct ConfigExt {
     section: String,
@@ -140,14 +291,34 @@ impl FromStr for ConfigExt {
 impl Cli {
     /// Executes selected CLI command.
     pub async fn run(self) -> anyhow::Result<i32> {
+        let result = self.run_command().await;
+        // Export any spans still buffered before the process exits.  This blocks until done, so
+        // must not run on the async runtime's threads.
+        let _ = tokio::task::spawn_blocking(logging::shutdown).await;
+        result
+    }
+
+    async fn run_command(self) -> anyhow::Result<i32> {
         match self {
             Cli::Validator { config, config_ext } => {
                 // Setup UNIX signal hooks.
                 setup_signal_hooks();
 
-                let validator_config = Self::init(&config, config_ext)?;
+                let validator_config = Self::init(&config, config_ext.clone())?;
                 info!(version = %crate::VERSION_STRING.as_str(), "node starting up");
 
+                if let Some(path) = &validator_config.value().audit_log.path {
+                    audit_log::init(&validator_config.with_dir(path.clone()))?;
+                }
+                incident::init(
+                    &validator_config.map_ref(|config| config.incident_reporting.clone()),
+                )?;
+                clock_skew::check_at_startup(&validator_config.value().clock_skew).await?;
+
+                // Keep track of where the config came from, so it can be reloaded on request.
+                let config_source = ConfigSource::new(config.clone(), config_ext)?;
+                let _ = CONFIG_SOURCE.set(config_source);
+
                 // We use a `ChaCha20Rng` for the production node. For one, we want to completely
                 // eliminate any chance of runtime failures, regardless of how small (these
                 // exist with `OsRng`). Additionally, we want to limit the number of syscalls for
@@ -157,12 +328,19 @@ impl Cli {
                 // The metrics are shared across all reactors.
                 let registry = Registry::new();
 
+                // The supervisor watches all reactors in turn.
+                let supervisor = Supervisor::spawn(
+                    &validator_config.map_ref(|config| config.supervisor.clone()),
+                    &registry,
+                )?;
+
                 let mut initializer_runner = Runner::<initializer::Reactor>::with_metrics(
                     validator_config,
                     &mut rng,
                     &registry,
                 )
                 .await?;
+                initializer_runner.set_supervisor(supervisor.clone());
 
                 match initializer_runner.run(&mut rng).await {
                     ReactorExit::ProcessShouldExit(exit_code) => return Ok(exit_code as i32),
@@ -180,6 +358,7 @@ impl Cli {
                     &registry,
                 )
                 .await?;
+                joiner_runner.set_supervisor(supervisor.clone());
                 match joiner_runner.run(&mut rng).await {
                     ReactorExit::ProcessShouldExit(exit_code) => return Ok(exit_code as i32),
                     ReactorExit::ProcessShouldContinue => info!("finished joining"),
@@ -191,6 +370,7 @@ impl Cli {
                 let mut participating_runner =
                     Runner::<participating::Reactor>::with_metrics(config, &mut rng, &registry)
                         .await?;
+                participating_runner.set_supervisor(supervisor);
 
                 match participating_runner.run(&mut rng).await {
                     ReactorExit::ProcessShouldExit(exit_code) => Ok(exit_code as i32),
@@ -244,6 +424,236 @@ impl Cli {
                 )?;
                 Ok(ExitCode::Success as i32)
             }
+            Cli::ChainspecCheck {
+                chainspec_dir,
+                previous_chainspec_dir,
+            } => {
+                logging::init()?;
+
+                let load = |dir: &Path| {
+                    <(Chainspec, ChainspecRawBytes)>::from_path(dir)
+                        .map(|(chainspec, _)| chainspec)
+                        .with_context(|| format!("could not load chainspec from {}", dir.display()))
+                };
+                let chainspec = load(&chainspec_dir)?;
+                let maybe_previous = previous_chainspec_dir.as_deref().map(load).transpose()?;
+
+                println!("chainspec hash: {}", chainspec.hash());
+                let problems =
+                    crate::chainspec_check::check_chainspec(&chainspec, maybe_previous.as_ref());
+                if problems.is_empty() {
+                    println!("no problems found");
+                    return Ok(ExitCode::Success as i32);
+                }
+                for problem in &problems {
+                    println!("problem: {}", problem);
+                }
+                Ok(ExitCode::Abort as i32)
+            }
+            Cli::ImportSnapshot {
+                config,
+                snapshot,
+                trusted_hash,
+            } => {
+                let validator_config = Self::init(&config, vec![])?;
+                let maybe_trusted_hash = trusted_hash
+                    .map(|hash| {
+                        Digest::from_hex(&hash)
+                            .map(BlockHash::new)
+                            .map_err(|error| anyhow::anyhow!("invalid trusted hash: {}", error))
+                    })
+                    .transpose()?;
+
+                info!(version = %env!("CARGO_PKG_VERSION"), "importing snapshot");
+                let block_hash = crate::snapshot_import::import_snapshot(
+                    validator_config,
+                    &snapshot,
+                    maybe_trusted_hash,
+                )?;
+                println!(
+                    "imported snapshot; start the node with node.trusted_hash = '{}'",
+                    block_hash.inner()
+                );
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::ExportSnapshot {
+                config,
+                snapshot,
+                block_hash,
+            } => {
+                let validator_config = Self::init(&config, vec![])?;
+                let maybe_block_hash = block_hash
+                    .map(|hash| {
+                        Digest::from_hex(&hash)
+                            .map(BlockHash::new)
+                            .map_err(|error| anyhow::anyhow!("invalid block hash: {}", error))
+                    })
+                    .transpose()?;
+
+                info!(version = %env!("CARGO_PKG_VERSION"), "exporting snapshot");
+                let (block_hash, trie_count) = crate::snapshot_export::export_snapshot(
+                    validator_config,
+                    &snapshot,
+                    maybe_block_hash,
+                )?;
+                println!(
+                    "exported snapshot of block {} with {} tries",
+                    block_hash.inner(),
+                    trie_count
+                );
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::StorageCheck { config } => {
+                // Logging is not initialized, so that only the report is written to stdout.
+                let root = config
+                    .parent()
+                    .map(|path| path.to_owned())
+                    .unwrap_or_else(|| "/".into());
+                let participating_config = load_config(&config, &[])?;
+                let chainspec = <(Chainspec, ChainspecRawBytes)>::from_path(&root)
+                    .with_context(|| format!("could not load chainspec from {}", root.display()))?
+                    .0;
+
+                let report = check_storage(
+                    &WithDir::new(root, participating_config.storage),
+                    &chainspec.network_config.name,
+                )?;
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                if report.is_ok() {
+                    Ok(ExitCode::Success as i32)
+                } else {
+                    Ok(ExitCode::Abort as i32)
+                }
+            }
+            Cli::MigrateGlobalState { config, from } => {
+                let root = config
+                    .parent()
+                    .map(|path| path.to_owned())
+                    .unwrap_or_else(|| "/".into());
+                let participating_config = load_config(&config, &[])?;
+                let chainspec = <(Chainspec, ChainspecRawBytes)>::from_path(&root)
+                    .with_context(|| format!("could not load chainspec from {}", root.display()))?
+                    .0;
+                let network_name = &chainspec.network_config.name;
+                let storage_config = WithDir::new(root, participating_config.storage);
+
+                let source = from.unwrap_or_else(|| {
+                    storage_config
+                        .with_dir(storage_config.value().path.clone())
+                        .join(network_name)
+                });
+                let dest = migrate_global_state(&storage_config, network_name, &source)?;
+                println!(
+                    "moved global state from {} to {}",
+                    source.display(),
+                    dest.display()
+                );
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::Keygen {
+                output_dir,
+                algorithm,
+                words,
+                restore,
+                passphrase,
+            } => {
+                let secret_key_path = output_dir.join("secret_key.pem");
+                if secret_key_path.exists() {
+                    anyhow::bail!("{} already exists", secret_key_path.display());
+                }
+
+                let (secret_key, maybe_mnemonic) = if restore {
+                    let mut mnemonic = String::new();
+                    io::stdin()
+                        .read_line(&mut mnemonic)
+                        .context("could not read mnemonic from stdin")?;
+                    let secret_key = match algorithm.as_str() {
+                        "ed25519" => SecretKey::ed25519_from_mnemonic(mnemonic.trim(), &passphrase),
+                        "secp256k1" => {
+                            SecretKey::secp256k1_from_mnemonic(mnemonic.trim(), &passphrase)
+                        }
+                        "secp256r1" => {
+                            SecretKey::secp256r1_from_mnemonic(mnemonic.trim(), &passphrase)
+                        }
+                        other => anyhow::bail!("unknown key algorithm '{}'", other),
+                    }?;
+                    (secret_key, None)
+                } else {
+                    let (secret_key, mnemonic) = match algorithm.as_str() {
+                        "ed25519" => SecretKey::generate_ed25519_with_mnemonic(words, &passphrase),
+                        "secp256k1" => {
+                            SecretKey::generate_secp256k1_with_mnemonic(words, &passphrase)
+                        }
+                        "secp256r1" => {
+                            SecretKey::generate_secp256r1_with_mnemonic(words, &passphrase)
+                        }
+                        other => anyhow::bail!("unknown key algorithm '{}'", other),
+                    }?;
+                    (secret_key, Some(mnemonic))
+                };
+
+                let public_key = PublicKey::from(&secret_key);
+                fs::create_dir_all(&output_dir)
+                    .with_context(|| format!("could not create {}", output_dir.display()))?;
+                secret_key.to_file(&secret_key_path)?;
+                public_key.to_file(output_dir.join("public_key.pem"))?;
+                fs::write(output_dir.join("public_key_hex"), public_key.to_hex())
+                    .context("could not write public_key_hex")?;
+
+                println!("wrote keys to {}", output_dir.display());
+                println!("public key: {}", public_key.to_hex());
+                if let Some(mnemonic) = maybe_mnemonic {
+                    println!("mnemonic: {}", mnemonic);
+                }
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::EndorseNetworkKey {
+                validator_secret_key,
+                network_public_key,
+                output,
+            } => {
+                let secret_key =
+                    SecretKey::from_file(&validator_secret_key).with_context(|| {
+                        format!(
+                            "could not load validator secret key from {}",
+                            validator_secret_key.display()
+                        )
+                    })?;
+                let public_key = PublicKey::from_file(&network_public_key).with_context(|| {
+                    format!(
+                        "could not load network public key from {}",
+                        network_public_key.display()
+                    )
+                })?;
+                NetworkKeyEndorsement::create(public_key, &secret_key)
+                    .to_file(&output)
+                    .with_context(|| format!("could not write {}", output.display()))?;
+                println!("wrote network key endorsement to {}", output.display());
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::VerifyAuditLog { audit_log } => match audit_log::verify(&audit_log) {
+                Ok(head) => {
+                    println!(
+                        "verified {} entries, last hash {}",
+                        head.entries, head.last_hash
+                    );
+                    Ok(ExitCode::Success as i32)
+                }
+                Err(error) => {
+                    println!("{}", error);
+                    Ok(ExitCode::Abort as i32)
+                }
+            },
+            Cli::DumpConfig { config, config_ext } => {
+                // Logging is not initialized, so that only the configuration is written to stdout.
+                let participating_config = load_config(&config, &config_ext)?;
+
+                // Converting to a TOML value first ensures plain values precede any sub-tables.
+                let config_table = Value::try_from(&participating_config)
+                    .context("could not encode configuration")?;
+                print!("{}", toml::to_string_pretty(&config_table)?);
+                Ok(ExitCode::Success as i32)
+            }
         }
     }
 
@@ -259,24 +669,88 @@ impl Cli {
             .map(|path| path.to_owned())
             .unwrap_or_else(|| "/".into());
 
-        // The app supports running without a config file, using default values.
+        // Create participating config, including any overridden values.
+        let participating_config = load_config(config, &config_ext)?;
+        logging::init_with_config(&participating_config.logging)?;
+        casper_types::bech32::set_json_output(participating_config.node.bech32_addresses);
+        casper_types::json_decimal::set_json_output(
+            participating_config.node.json_decimal_integers,
+        );
+
+        Ok(WithDir::new(root, participating_config))
+    }
+}
+
+/// Reads the config file as a TOML table and applies the given command line overrides.
+fn load_config_table(config: &Path, config_ext: &[ConfigExt]) -> anyhow::Result<Value> {
+    // The app supports running without a config file, using default values.
+    let encoded_config = fs::read_to_string(&config)
+        .context("could not read configuration file")
+        .with_context(|| config.display().to_string())?;
+
+    // Get the TOML table version of the config indicated from CLI args, or from a new
+    // defaulted config instance if one is not provided.
+    let mut config_table: Value = toml::from_str(&encoded_config)?;
+
+    // If any command line overrides to the config values are passed, apply them.
+    for item in config_ext {
+        item.update_toml_table(&mut config_table)?;
+    }
+
+    Ok(config_table)
+}
+
+/// Reads and validates the config file, applying the given command line overrides.
+///
+/// Errors name the offending key and, if it is set in the config file, its position in the file.
+fn load_config(config: &Path, config_ext: &[ConfigExt]) -> anyhow::Result<participating::Config> {
+    let config_table = load_config_table(config, config_ext)?;
+    config_table.try_into().or_else(|error| {
+        // The TOML table no longer knows where its values came from, so parse the file on its own
+        // to find out whether it or one of the overrides is invalid.
         let encoded_config = fs::read_to_string(&config)
             .context("could not read configuration file")
             .with_context(|| config.display().to_string())?;
-
-        // Get the TOML table version of the config indicated from CLI args, or from a new
-        // defaulted config instance if one is not provided.
-        let mut config_table: Value = toml::from_str(&encoded_config)?;
-
-        // If any command line overrides to the config values are passed, apply them.
-        for item in config_ext {
-            item.update_toml_table(&mut config_table)?;
+        match toml::from_str::<participating::Config>(&encoded_config) {
+            Err(file_error) => Err(anyhow::Error::new(file_error))
+                .with_context(|| format!("invalid configuration file {}", config.display())),
+            Ok(_) => Err(anyhow::Error::new(error)).context("invalid configuration override"),
         }
+    })
+}
 
-        // Create participating config, including any overridden values.
-        let participating_config: participating::Config = config_table.try_into()?;
-        logging::init_with_config(&participating_config.logging)?;
+/// The config file and command line overrides of the running validator.
+struct ConfigSource {
+    path: PathBuf,
+    config_ext: Vec<ConfigExt>,
+    /// The config table the validator was started with.
+    initial_table: Value,
+}
 
-        Ok(WithDir::new(root, participating_config))
+impl ConfigSource {
+    fn new(path: PathBuf, config_ext: Vec<ConfigExt>) -> anyhow::Result<Self> {
+        let initial_table = load_config_table(&path, &config_ext)?;
+        Ok(ConfigSource {
+            path,
+            config_ext,
+            initial_table,
+        })
     }
 }
+
+/// Returns the config table the running validator was started with, or `None` if the node was not
+/// started via the `validator` subcommand.
+pub(crate) fn initial_config_table() -> Option<Value> {
+    CONFIG_SOURCE
+        .get()
+        .map(|config_source| config_source.initial_table.clone())
+}
+
+/// Reads the running validator's config file again, applying the same command line overrides as on
+/// startup.
+pub(crate) fn reload_config_table() -> anyhow::Result<Value> {
+    let config_source = CONFIG_SOURCE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("node was not started from a config file"))?;
+    load_config_table(&config_source.path, &config_source.config_ext)
+}
@@ -48,12 +48,14 @@ pub(crate) mod block_proposer;
 pub(crate) mod block_validator;
 pub(crate) mod chain_synchronizer;
 pub(crate) mod chainspec_loader;
+pub(crate) mod clock_skew;
 pub(crate) mod consensus;
 pub mod contract_runtime;
 pub(crate) mod deploy_acceptor;
 pub(crate) mod diagnostics_port;
 pub(crate) mod event_stream_server;
 pub(crate) mod fetcher;
+pub(crate) mod finality_watchdog;
 pub(crate) mod gossiper;
 pub(crate) mod linear_chain;
 pub(crate) mod rest_server;
@@ -5,6 +5,7 @@
 
 mod cached_state;
 mod config;
+mod deploy_blocklist;
 mod deploy_sets;
 mod event;
 mod metrics;
@@ -12,8 +13,10 @@ mod metrics;
 mod tests;
 
 use std::{
+    cmp::Reverse,
     collections::{BTreeSet, HashMap, HashSet},
     convert::Infallible,
+    mem,
     sync::Arc,
     time::Duration,
 };
@@ -23,7 +26,7 @@ use futures::join;
 use prometheus::{self, Registry};
 use tracing::{debug, error, info, warn};
 
-use casper_types::{PublicKey, Timestamp};
+use casper_types::{account::AccountHash, Gas, PublicKey, Timestamp};
 
 use crate::{
     components::{
@@ -45,9 +48,78 @@ use crate::{
 };
 use cached_state::CachedState;
 pub use config::Config;
+use config::DeployOrdering;
+pub(crate) use deploy_blocklist::{DeployBlocklist, DeployBlocklistUpdate};
 use deploy_sets::{BlockProposerDeploySets, PendingDeployInfo, PruneResult};
 pub(crate) use event::{DeployInfo, Event};
-use metrics::Metrics;
+use metrics::{EvictionReason, Metrics, Observations, ProposalFill};
+
+/// Returns `true` if a deploy described by `replacement` may replace the pending `replaced` one
+/// with the same replacement tag, i.e. if it offers a strictly higher gas price, or the same gas
+/// price and a strictly higher payment amount.
+fn outbids(replacement: &DeployInfo, replaced: &DeployInfo) -> bool {
+    (replacement.header.gas_price(), replacement.payment_amount)
+        > (replaced.header.gas_price(), replaced.payment_amount)
+}
+
+/// Returns `true` if an untagged deploy described by `replacement` replaces the pending `replaced`
+/// one with identical session code and dependencies, i.e. if it offers a strictly higher payment
+/// amount at no lower gas price.  Otherwise the account is taken to run the same session code
+/// again.
+fn pays_more(replacement: &DeployInfo, replaced: &DeployInfo) -> bool {
+    replacement.payment_amount > replaced.payment_amount
+        && replacement.header.gas_price() >= replaced.header.gas_price()
+}
+
+/// Returns the number of unexpired deploys and transfers signed by the key of `account` among the
+/// pending ones last put to storage by a block proposer.
+///
+/// Used by reactors which don't run a block proposer to enforce the pending deploy quota.
+pub(crate) async fn load_pending_count_for_account<REv>(
+    effect_builder: EffectBuilder<REv>,
+    account: &PublicKey,
+) -> usize
+where
+    REv: From<StateStoreRequest>,
+{
+    let now = Timestamp::now();
+    effect_builder
+        .load_state::<CachedState>(STATE_KEY.into())
+        .await
+        .map_or(0, |cached_state| {
+            cached_state
+                .pending_deploys
+                .values()
+                .chain(cached_state.pending_transfers.values())
+                .filter(|pending_deploy_info| {
+                    let header = &pending_deploy_info.info.header;
+                    pending_deploy_info.info.signed_by_account
+                        && header.account() == account
+                        && !header.expired(now)
+                })
+                .count()
+        })
+}
+
+/// Returns whether the given deploy or account is on the deploy blocklist last put to storage by a
+/// block proposer.
+///
+/// Used by reactors which don't run a block proposer to enforce the blocklist.
+pub(crate) async fn load_blocklist_contains<REv>(
+    effect_builder: EffectBuilder<REv>,
+    deploy_hash: &DeployHash,
+    account_hash: &AccountHash,
+) -> bool
+where
+    REv: From<StateStoreRequest>,
+{
+    effect_builder
+        .load_state::<DeployBlocklist>(BLOCKLIST_STATE_KEY.into())
+        .await
+        .map_or(false, |blocklist| {
+            blocklist.contains(deploy_hash, account_hash)
+        })
+}
 
 /// Block proposer component.
 #[derive(DataSize, Debug)]
@@ -61,10 +133,17 @@ pub(crate) struct BlockProposer {
 
 const STATE_KEY: &[u8] = b"block proposer";
 
+/// Key under which the operator's deploy blocklist is put to storage.
+const BLOCKLIST_STATE_KEY: &[u8] = b"block proposer deploy blocklist";
+
 /// Interval after which a pruning of the internal sets is triggered.
 // TODO: Make configurable.
 const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
 
+/// Interval after which the pending deploys are persisted to storage, if they changed since the
+/// last snapshot. This bounds how many recently received deploys are lost if the node restarts.
+const PERSIST_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Experimentally, deploys are in the range of 270-280 bytes, we use this to determine if we are
 /// within a threshold to break iteration of `pending` early.
 const DEPLOY_APPROX_MIN_SIZE: usize = 300;
@@ -90,6 +169,8 @@ enum BlockProposerState {
         pending: Vec<Event>,
         /// The deploy config from the current chainspec.
         deploy_config: DeployConfig,
+        /// The fixed cost of a wasm-less transfer from the current chainspec.
+        wasmless_transfer_cost: u32,
         /// The configuration, containing local settings for deploy selection.
         local_config: Config,
     },
@@ -114,14 +195,16 @@ impl BlockProposer {
         let effects = async move {
             join!(
                 effect_builder.get_finalized_blocks(max_ttl),
-                effect_builder.load_state::<CachedState>(STATE_KEY.into())
+                effect_builder.load_state::<CachedState>(STATE_KEY.into()),
+                effect_builder.load_state::<DeployBlocklist>(BLOCKLIST_STATE_KEY.into())
             )
         }
         .event(
-            move |(finalized_blocks, maybe_cached_state)| Event::Loaded {
+            move |(finalized_blocks, maybe_cached_state, maybe_blocklist)| Event::Loaded {
                 finalized_blocks,
                 next_finalized_block,
                 cached_state: maybe_cached_state.unwrap_or_default(),
+                blocklist: maybe_blocklist.unwrap_or_default(),
             },
         );
 
@@ -129,6 +212,7 @@ impl BlockProposer {
             state: BlockProposerState::Initializing {
                 pending: Vec::new(),
                 deploy_config: chainspec.deploy_config,
+                wasmless_transfer_cost: chainspec.system_costs_config.wasmless_transfer_cost(),
                 local_config,
             },
             metrics: Metrics::new(registry)?,
@@ -136,6 +220,26 @@ impl BlockProposer {
 
         Ok((block_proposer, effects))
     }
+
+    /// Returns the estimated heap memory usage of the deploy pool and related state.
+    pub(crate) fn estimate_heap_size_breakdown(&self) -> Vec<(String, usize)> {
+        match &self.state {
+            BlockProposerState::Initializing { pending, .. } => {
+                vec![("pending_events".to_string(), pending.estimate_heap_size())]
+            }
+            BlockProposerState::Ready(ready) => vec![
+                ("deploy_sets".to_string(), ready.sets.estimate_heap_size()),
+                (
+                    "request_queue".to_string(),
+                    ready.request_queue.estimate_heap_size(),
+                ),
+                (
+                    "blocklist".to_string(),
+                    ready.blocklist.estimate_heap_size(),
+                ),
+            ],
+        }
+    }
 }
 
 impl<REv> Component<REv> for BlockProposer
@@ -166,31 +270,44 @@ where
                 BlockProposerState::Initializing {
                     ref mut pending,
                     deploy_config,
+                    wasmless_transfer_cost,
                     local_config,
                 },
                 Event::Loaded {
                     finalized_blocks,
                     next_finalized_block,
                     cached_state,
+                    blocklist,
                 },
             ) => {
-                let (sets, pruned_hashes) = BlockProposerDeploySets::new(
+                let (mut sets, pruned_hashes) = BlockProposerDeploySets::new(
                     finalized_blocks,
                     next_finalized_block,
                     cached_state,
                     deploy_config.max_ttl,
                 );
+                let blocked_count = sets.remove_blocked(&blocklist).len();
+                if blocked_count > 0 {
+                    info!(%blocked_count, "dropped blocked deploys from buffer on loading");
+                }
 
                 let mut new_ready_state = BlockProposerReady {
                     sets,
                     deploy_config: *deploy_config,
+                    wasmless_transfer_cost: *wasmless_transfer_cost,
                     request_queue: Default::default(),
                     local_config: local_config.clone(),
+                    state_dirty: false,
+                    observations: Observations::default(),
+                    blocklist,
                 };
 
                 // Announce pruned hashes.
                 let pruned_count = pruned_hashes.total_pruned;
+                let restored_count = new_ready_state.sets.pending_deploys.len()
+                    + new_ready_state.sets.pending_transfers.len();
                 debug!(%pruned_count, "pruned deploys from buffer on loading");
+                info!(%restored_count, "restored pending deploys from storage");
                 effects.extend(
                     effect_builder
                         .announce_expired_deploys(pruned_hashes.expired_hashes_to_be_announced)
@@ -217,6 +334,13 @@ where
                         .set_timeout(PRUNE_INTERVAL)
                         .event(|_| Event::Prune),
                 );
+
+                // Start persisting changes to the pending deploys.
+                effects.extend(
+                    effect_builder
+                        .set_timeout(PERSIST_INTERVAL)
+                        .event(|_| Event::PersistState),
+                );
             }
             (
                 BlockProposerState::Initializing {
@@ -232,9 +356,10 @@ where
                 effects.extend(ready_state.handle_event(effect_builder, event));
 
                 // Update metrics after the effects have been applied.
-                self.metrics.pending_deploys.set(
-                    ready_state.sets.pending_deploys.len() as i64
-                        + ready_state.sets.pending_transfers.len() as i64,
+                self.metrics.update(
+                    &ready_state.sets,
+                    mem::take(&mut ready_state.observations),
+                    Timestamp::now(),
                 );
             }
         };
@@ -251,10 +376,18 @@ struct BlockProposerReady {
     sets: BlockProposerDeploySets,
     /// We don't need the whole Chainspec here, just the deploy config.
     deploy_config: DeployConfig,
+    /// The fixed cost of a wasm-less transfer, counting towards the transfer gas limit.
+    wasmless_transfer_cost: u32,
     /// The queue of requests awaiting being handled.
     request_queue: RequestQueue,
     /// The block proposer configuration, containing local settings for selecting deploys.
     local_config: Config,
+    /// Whether the pending deploys changed since the last snapshot was saved to storage.
+    state_dirty: bool,
+    /// Evictions and proposals not yet recorded in the metrics.
+    observations: Observations,
+    /// Deploys and accounts blocked by the node operator, which are never buffered or proposed.
+    blocklist: DeployBlocklist,
 }
 
 impl BlockProposerReady {
@@ -264,7 +397,8 @@ impl BlockProposerReady {
         event: Event,
     ) -> Effects<Event>
     where
-        REv: Send + From<StateStoreRequest> + From<BlockProposerAnnouncement>,
+        REv:
+            Send + From<StateStoreRequest> + From<StorageRequest> + From<BlockProposerAnnouncement>,
     {
         match event {
             Event::Request(BlockProposerRequest::RequestBlockPayload(request)) => {
@@ -291,6 +425,50 @@ impl BlockProposerReady {
                         .ignore()
                 }
             }
+            Event::Request(BlockProposerRequest::GetUnresolvableDependencies {
+                deploy_hash,
+                responder,
+            }) => responder
+                .respond(self.unresolvable_dependencies(&deploy_hash))
+                .ignore(),
+            Event::Request(BlockProposerRequest::CheckPendingDeployQuota {
+                account,
+                responder,
+            }) => responder
+                .respond(self.within_pending_deploy_quota(&account))
+                .ignore(),
+            Event::Request(BlockProposerRequest::CheckDeployReplacement {
+                hash,
+                deploy_info,
+                responder,
+            }) => responder
+                .respond(self.underbid_replacement(&hash, &deploy_info))
+                .ignore(),
+            Event::Request(BlockProposerRequest::CheckDeployBlocklist {
+                deploy_hash,
+                account_hash,
+                responder,
+            }) => responder
+                .respond(self.blocklist.contains(&deploy_hash, &account_hash))
+                .ignore(),
+            Event::Request(BlockProposerRequest::UpdateDeployBlocklist { update, responder }) => {
+                let mut effects = Effects::new();
+                let changed = self.blocklist.apply(update);
+                if changed {
+                    info!(%update, "updated deploy blocklist");
+                    self.remove_blocked();
+                    effects.extend(
+                        effect_builder
+                            .save_state(BLOCKLIST_STATE_KEY.into(), self.blocklist.clone())
+                            .ignore(),
+                    );
+                }
+                effects.extend(responder.respond(Some(changed)).ignore());
+                effects
+            }
+            Event::Request(BlockProposerRequest::GetDeployBlocklist { responder }) => {
+                responder.respond(Some(self.blocklist.clone())).ignore()
+            }
             Event::BufferDeploy {
                 hash,
                 approvals,
@@ -316,14 +494,62 @@ impl BlockProposerReady {
                 );
 
                 // After pruning, we store a state snapshot.
-                effects.extend(
-                    effect_builder
-                        .save_state(STATE_KEY.into(), CachedState::from(&self.sets))
-                        .ignore(),
-                );
+                effects.extend(self.save_state(effect_builder));
 
                 effects
             }
+            Event::PersistState => {
+                // Re-trigger timer after `PERSIST_INTERVAL`.
+                let mut effects = effect_builder
+                    .set_timeout(PERSIST_INTERVAL)
+                    .event(|_| Event::PersistState);
+                if self.state_dirty {
+                    effects.extend(self.save_state(effect_builder));
+                }
+                effects
+            }
+            Event::BlockExecuted(block) => {
+                self.sets
+                    .add_executed_block(&block, self.deploy_config.max_ttl);
+                Effects::new()
+            }
+            Event::OrphanedDeploys(deploy_hashes) => {
+                let missing = self.return_orphaned_deploys(deploy_hashes, Timestamp::now());
+                if missing.is_empty() {
+                    return Effects::new();
+                }
+                effect_builder
+                    .get_deploys_from_storage(missing)
+                    .event(|deploys| {
+                        Event::OrphanedDeploysLoaded(
+                            deploys
+                                .into_iter()
+                                .flatten()
+                                .map(|deploy| deploy.into_naive())
+                                .collect(),
+                        )
+                    })
+            }
+            Event::OrphanedDeploysLoaded(deploys) => {
+                let eligible_since =
+                    Timestamp::now().saturating_sub(self.local_config.deploy_delay);
+                for deploy in deploys {
+                    let deploy_info = match deploy.deploy_info() {
+                        Ok(deploy_info) => deploy_info,
+                        Err(error) => {
+                            error!(%error, deploy_hash = %deploy.id(), "invalid orphaned deploy");
+                            continue;
+                        }
+                    };
+                    self.add_deploy(
+                        eligible_since,
+                        deploy.deploy_or_transfer_hash(),
+                        deploy.approvals().clone(),
+                        deploy_info,
+                    );
+                }
+                Effects::new()
+            }
             Event::Loaded { .. } => {
                 // This should never happen, but we can just ignore the event and carry on.
                 error!("got loaded event for block proposer state during ready state");
@@ -362,6 +588,60 @@ impl BlockProposerReady {
         approvals: BTreeSet<Approval>,
         deploy_info: DeployInfo,
     ) {
+        let account_hash = deploy_info.header.account().to_account_hash();
+        if self.blocklist.contains(hash.deploy_hash(), &account_hash) {
+            info!(%hash, "blocked deploy rejected from the buffer");
+            return;
+        }
+
+        if !self.contains_finalized(hash.deploy_hash()) {
+            if let Some(replaced) = self.underbid_replacement(&hash, &deploy_info) {
+                info!(
+                    %hash, %replaced,
+                    "deploy does not outbid the pending deploy with the same replacement tag; \
+                    rejected"
+                );
+                return;
+            }
+            if let Some((replaced, _)) = self.sets.find_replaceable(&hash, &deploy_info).filter(
+                |(_, pending_deploy_info)| {
+                    deploy_info.replacement_tag.is_some()
+                        || pays_more(&deploy_info, &pending_deploy_info.info)
+                },
+            ) {
+                self.observations.evictions.push(EvictionReason::Replaced);
+                self.sets.replace_pending(
+                    &hash,
+                    replaced,
+                    PendingDeployInfo {
+                        approvals,
+                        info: deploy_info,
+                        timestamp: current_instant,
+                    },
+                );
+                self.state_dirty = true;
+                info!(%hash, %replaced, "replaced pending deploy in the buffer");
+                return;
+            }
+        }
+
+        if self.is_stale_in_sequence(&deploy_info) {
+            info!(
+                %hash, sequence_number = ?deploy_info.sequence_number,
+                "deploy sequence number already finalized; rejected from the buffer"
+            );
+            return;
+        }
+
+        if !self.sets.is_pending(hash.deploy_hash())
+            && !self.within_pending_deploy_quota(deploy_info.header.account())
+        {
+            warn!(
+                %hash, account = %deploy_info.header.account(),
+                "account reached its pending deploy quota; deploy rejected from the buffer"
+            );
+            return;
+        }
         if hash.is_transfer() {
             // only add the transfer if it isn't contained in a finalized block
             if self
@@ -374,14 +654,15 @@ impl BlockProposerReady {
                     .add_finalized_transfer(*hash.deploy_hash(), deploy_info.header.expires());
                 return;
             }
-            self.sets.pending_transfers.insert(
-                *hash.deploy_hash(),
+            self.sets.add_pending(
+                &hash,
                 PendingDeployInfo {
                     approvals,
                     info: deploy_info,
                     timestamp: current_instant,
                 },
             );
+            self.state_dirty = true;
             info!(%hash, "added transfer to the buffer");
         } else {
             // only add the deploy if it isn't contained in a finalized block
@@ -391,35 +672,80 @@ impl BlockProposerReady {
                     .add_finalized_deploy(*hash.deploy_hash(), deploy_info.header.expires());
                 return;
             }
-            self.sets.pending_deploys.insert(
-                *hash.deploy_hash(),
+            self.sets.add_pending(
+                &hash,
                 PendingDeployInfo {
                     approvals,
                     info: deploy_info,
                     timestamp: current_instant,
                 },
             );
+            self.state_dirty = true;
             info!(%hash, "added deploy to the buffer");
         }
     }
 
+    /// Returns the hash of the pending deploy with the same replacement tag as the given one, if
+    /// the latter fails to outbid it.
+    fn underbid_replacement(
+        &self,
+        hash: &DeployOrTransferHash,
+        deploy_info: &DeployInfo,
+    ) -> Option<DeployHash> {
+        deploy_info.replacement_tag?;
+        let (replaced, pending_deploy_info) = self.sets.find_replaceable(hash, deploy_info)?;
+        if outbids(deploy_info, &pending_deploy_info.info) {
+            None
+        } else {
+            Some(replaced)
+        }
+    }
+
     /// Handles finalization of a block.
     fn handle_finalized_block(&mut self, block: &FinalizedBlock) -> Effects<Event> {
+        let now = Timestamp::now();
         for deploy_hash in block.deploy_hashes() {
-            let expiry = match self.sets.pending_deploys.remove(deploy_hash) {
-                Some(pending_deploy_info) => pending_deploy_info.info.header.expires(),
+            let expiry = match self.sets.remove_pending(deploy_hash, false) {
+                Some(pending_deploy_info) => {
+                    self.state_dirty = true;
+                    self.observations
+                        .ages_at_finalization
+                        .push(now.saturating_diff(pending_deploy_info.timestamp));
+                    self.record_finalized_sequence_number(&pending_deploy_info.info);
+                    pending_deploy_info.info.header.expires()
+                }
                 None => block.timestamp().saturating_add(self.deploy_config.max_ttl),
             };
             self.sets.add_finalized_deploy(*deploy_hash, expiry);
         }
         for transfer_hash in block.transfer_hashes() {
-            let expiry = match self.sets.pending_transfers.remove(transfer_hash) {
-                Some(pending_deploy_info) => pending_deploy_info.info.header.expires(),
+            let expiry = match self.sets.remove_pending(transfer_hash, true) {
+                Some(pending_deploy_info) => {
+                    self.state_dirty = true;
+                    self.observations
+                        .ages_at_finalization
+                        .push(now.saturating_diff(pending_deploy_info.timestamp));
+                    self.record_finalized_sequence_number(&pending_deploy_info.info);
+                    pending_deploy_info.info.header.expires()
+                }
                 None => block.timestamp().saturating_add(self.deploy_config.max_ttl),
             };
             self.sets.add_finalized_transfer(*transfer_hash, expiry);
         }
 
+        for hash in block.deploy_hashes().iter().chain(block.transfer_hashes()) {
+            if let Some(replacement) = self.sets.remove_replacement_of(hash) {
+                self.state_dirty = true;
+                self.observations
+                    .evictions
+                    .push(EvictionReason::ReplacedDeployFinalized);
+                info!(
+                    %hash, %replacement,
+                    "replaced deploy was finalized; dropped its replacement"
+                );
+            }
+        }
+
         self.sets.next_finalized = self.sets.next_finalized.max(block.height() + 1);
         if let Some(requests) = self.request_queue.remove(&self.sets.next_finalized) {
             info!(height = %self.sets.next_finalized, "handling queued requests");
@@ -442,12 +768,185 @@ impl BlockProposerReady {
         }
     }
 
-    /// Checks if a deploy's dependencies are satisfied, so the deploy is eligible for inclusion.
-    fn deps_resolved(&self, header: &DeployHeader, past_deploys: &HashSet<DeployHash>) -> bool {
+    /// Makes the deploys of proposals orphaned by an era change eligible for proposal again
+    /// without waiting for the deploy delay. Returns the hashes of the deploys which are neither
+    /// pending nor finalized, and need to be retrieved from storage and buffered anew.
+    fn return_orphaned_deploys(
+        &mut self,
+        deploy_hashes: Vec<DeployHash>,
+        current_instant: Timestamp,
+    ) -> Vec<DeployHash> {
+        let eligible_since = current_instant.saturating_sub(self.local_config.deploy_delay);
+        let mut missing = Vec::new();
+        for hash in deploy_hashes {
+            let maybe_pending = match self.sets.pending_deploys.get_mut(&hash) {
+                Some(pending) => Some(pending),
+                None => self.sets.pending_transfers.get_mut(&hash),
+            };
+            if let Some(pending) = maybe_pending {
+                if pending.timestamp > eligible_since {
+                    pending.timestamp = eligible_since;
+                    self.state_dirty = true;
+                }
+                debug!(%hash, "orphaned deploy is still pending");
+            } else if !self.contains_finalized(&hash) {
+                missing.push(hash);
+            }
+        }
+        if !missing.is_empty() {
+            info!(
+                missing_count = missing.len(),
+                "re-buffering orphaned deploys"
+            );
+        }
+        missing
+    }
+
+    /// Drops the pending deploys and transfers which are on the blocklist.
+    fn remove_blocked(&mut self) {
+        for hash in self.sets.remove_blocked(&self.blocklist) {
+            self.state_dirty = true;
+            self.observations.evictions.push(EvictionReason::Blocked);
+            info!(%hash, "dropped blocked deploy from the buffer");
+        }
+    }
+
+    /// Records the sequence number of a finalized deploy, if strict account ordering is enabled.
+    ///
+    /// Deploys from peers are not checked against the account's authorization, so only deploys
+    /// signed by the account's own key are recorded.  Otherwise anyone could make the account's
+    /// pending deploys stale by getting a deploy with a high sequence number finalized.
+    fn record_finalized_sequence_number(&mut self, info: &DeployInfo) {
+        if let (true, true, Some(sequence_number)) = (
+            self.deploy_config.strict_account_ordering,
+            info.signed_by_account,
+            info.sequence_number,
+        ) {
+            let finalized = self
+                .sets
+                .finalized_sequence_numbers
+                .entry(info.header.account().clone())
+                .or_insert(sequence_number);
+            *finalized = (*finalized).max(sequence_number);
+        }
+    }
+
+    /// Returns `true` if strict account ordering is enabled and a deploy of the same account with
+    /// an equal or higher sequence number has already been finalized.
+    fn is_stale_in_sequence(&self, info: &DeployInfo) -> bool {
+        match (
+            self.deploy_config.strict_account_ordering,
+            info.sequence_number,
+        ) {
+            (true, Some(sequence_number)) => self
+                .sets
+                .finalized_sequence_numbers
+                .get(info.header.account())
+                .map_or(false, |finalized| sequence_number <= *finalized),
+            _ => false,
+        }
+    }
+
+    /// Checks if a deploy is next in its account's sequence, if strict account ordering is
+    /// enabled: every pending deploy of the same account with a lower sequence number that is not
+    /// stale has to be included already, as determined by `is_included`.
+    fn sequence_resolved<F>(&self, info: &DeployInfo, is_included: F) -> bool
+    where
+        F: Fn(&DeployHash) -> bool,
+    {
+        let sequence_number = match (
+            self.deploy_config.strict_account_ordering,
+            info.sequence_number,
+        ) {
+            (true, Some(sequence_number)) => sequence_number,
+            _ => return true,
+        };
+        if self.is_stale_in_sequence(info) {
+            return false;
+        }
+        self.sets
+            .pending_deploys
+            .iter()
+            .chain(&self.sets.pending_transfers)
+            .all(|(hash, pending_deploy_info)| {
+                let other = &pending_deploy_info.info;
+                other.header.account() != info.header.account()
+                    || other
+                        .sequence_number
+                        .map_or(true, |other_number| other_number >= sequence_number)
+                    || self.is_stale_in_sequence(other)
+                    || is_included(hash)
+            })
+    }
+
+    /// Returns `true` if another deploy from the given account can be buffered without exceeding
+    /// the configured quota of pending deploys per account.
+    fn within_pending_deploy_quota(&self, account: &PublicKey) -> bool {
+        let max_pending = self.local_config.max_pending_deploys_per_account;
+        max_pending == 0 || self.sets.pending_count_for_account(account) < max_pending as usize
+    }
+
+    /// Checks if a deploy's dependencies are satisfied, so the deploy is eligible for inclusion:
+    /// each dependency has to be executed already, or be included earlier in the same block.
+    fn deps_resolved(&self, header: &DeployHeader, included: &HashSet<DeployHash>) -> bool {
+        header
+            .dependencies()
+            .iter()
+            .all(|dep| included.contains(dep) || self.sets.executed_deploys.contains_key(dep))
+    }
+
+    /// Returns the dependencies of a pending deploy or transfer which are neither executed nor
+    /// pending in this block proposer, so they can't be resolved by proposing any known deploys.
+    ///
+    /// Returns `None` if the deploy is not pending.
+    fn unresolvable_dependencies(&self, deploy_hash: &DeployHash) -> Option<Vec<DeployHash>> {
+        let pending_deploy_info = self
+            .sets
+            .pending_deploys
+            .get(deploy_hash)
+            .or_else(|| self.sets.pending_transfers.get(deploy_hash))?;
+        Some(self.unresolvable_dependencies_of(&pending_deploy_info.info.header))
+    }
+
+    fn unresolvable_dependencies_of(&self, header: &DeployHeader) -> Vec<DeployHash> {
         header
             .dependencies()
             .iter()
-            .all(|dep| past_deploys.contains(dep) || self.contains_finalized(dep))
+            .filter(|dep| {
+                !self.sets.executed_deploys.contains_key(dep)
+                    && !self.contains_finalized(dep)
+                    && !self.sets.is_pending(dep)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Returns the given pending deploys or transfers in the order in which they should be
+    /// considered for inclusion into a block, according to the configured `DeployOrdering`.
+    ///
+    /// Ties are broken by arrival time and then by hash, so the order is deterministic.
+    fn ordered_pending<'a>(
+        &self,
+        pending: &'a HashMap<DeployHash, PendingDeployInfo>,
+    ) -> Vec<(&'a DeployHash, &'a PendingDeployInfo)> {
+        let mut ordered: Vec<_> = pending.iter().collect();
+        match self.local_config.deploy_ordering {
+            DeployOrdering::Fifo => {
+                ordered.sort_by_key(|(hash, pending_deploy_info)| {
+                    (pending_deploy_info.timestamp, **hash)
+                });
+            }
+            DeployOrdering::GasPrice => {
+                ordered.sort_by_key(|(hash, pending_deploy_info)| {
+                    (
+                        Reverse(pending_deploy_info.info.header.gas_price()),
+                        pending_deploy_info.timestamp,
+                        **hash,
+                    )
+                });
+            }
+        }
+        ordered
     }
 
     /// Returns a list of candidates for inclusion into a block.
@@ -458,7 +957,7 @@ impl BlockProposerReady {
         accusations: Vec<PublicKey>,
         random_bit: bool,
     ) -> Arc<BlockPayload> {
-        let past_deploys = context
+        let past_deploys: HashSet<DeployHash> = context
             .ancestor_values()
             .iter()
             .flat_map(|block_payload| block_payload.deploys_and_transfers_iter())
@@ -466,90 +965,183 @@ impl BlockProposerReady {
             .take_while(|hash| !self.contains_finalized(hash))
             .collect();
         let block_timestamp = context.timestamp();
-        let mut appendable_block = AppendableBlock::new(deploy_config, block_timestamp);
-
-        // We prioritize transfers over deploys, so we try to include them first.
-        for (hash, pending_deploy_info) in &self.sets.pending_transfers {
-            if !self.deps_resolved(&pending_deploy_info.info.header, &past_deploys)
-                || past_deploys.contains(hash)
-                || self.contains_finalized(hash)
-                || block_timestamp.saturating_diff(pending_deploy_info.timestamp)
-                    < self.local_config.deploy_delay
-            {
-                continue;
-            }
+        let mut appendable_block = AppendableBlock::new(
+            deploy_config,
+            Gas::from(self.wasmless_transfer_cost),
+            block_timestamp,
+        );
 
-            if let Err(err) = appendable_block.add_transfer(
-                DeployWithApprovals::new(*hash, pending_deploy_info.approvals.clone()),
-                &pending_deploy_info.info,
-            ) {
-                match err {
-                    // We added the maximum number of transfers.
-                    AddError::TransferCount | AddError::GasLimit | AddError::BlockSize => break,
-                    // This transfer would exceed the approval count, but another one with fewer
-                    // approvals might not.
-                    AddError::ApprovalCount if pending_deploy_info.approvals.len() > 1 => (),
-                    AddError::ApprovalCount => break,
-                    // The deploy is not valid in this block, but might be valid in another.
-                    AddError::InvalidDeploy => (),
-                    // These errors should never happen when adding a transfer.
-                    AddError::InvalidGasAmount | AddError::DeployCount | AddError::Duplicate => {
-                        error!(?err, "unexpected error when adding transfer")
+        // We prioritize transfers over deploys, so we try to include them first.  Transfers
+        // depending on others in the same block are only added after those, so we keep iterating
+        // for as long as new transfers get added.
+        let mut included_transfers = HashSet::new();
+        let mut added_any = true;
+        'transfers: while added_any {
+            added_any = false;
+            for (hash, pending_deploy_info) in self.ordered_pending(&self.sets.pending_transfers) {
+                if included_transfers.contains(hash)
+                    || !self.deps_resolved(&pending_deploy_info.info.header, &included_transfers)
+                    || !self.sequence_resolved(&pending_deploy_info.info, |other| {
+                        past_deploys.contains(other) || included_transfers.contains(other)
+                    })
+                    || past_deploys.contains(hash)
+                    || self.contains_finalized(hash)
+                    || block_timestamp.saturating_diff(pending_deploy_info.timestamp)
+                        < self.local_config.deploy_delay
+                {
+                    continue;
+                }
+
+                match appendable_block.add_transfer(
+                    DeployWithApprovals::new(*hash, pending_deploy_info.approvals.clone()),
+                    &pending_deploy_info.info,
+                ) {
+                    Ok(()) => {
+                        included_transfers.insert(*hash);
+                        added_any = true;
                     }
+                    Err(err) => match err {
+                        // We added the maximum number of transfers, or as many as their gas limit
+                        // allows: all transfers have the same cost.
+                        AddError::TransferCount
+                        | AddError::TransferGasLimit
+                        | AddError::GasLimit
+                        | AddError::BlockSize => break 'transfers,
+                        // This transfer would exceed the approval count, but another one with fewer
+                        // approvals might not.
+                        AddError::ApprovalCount if pending_deploy_info.approvals.len() > 1 => (),
+                        AddError::ApprovalCount => break 'transfers,
+                        // The deploy is not valid in this block, but might be valid in another.
+                        AddError::InvalidDeploy => (),
+                        // These errors should never happen when adding a transfer.
+                        AddError::InvalidGasAmount
+                        | AddError::DeployCount
+                        | AddError::Duplicate => {
+                            error!(?err, "unexpected error when adding transfer")
+                        }
+                    },
                 }
             }
         }
 
-        // Now we try to add other deploys to the block.
-        for (hash, pending_deploy_info) in &self.sets.pending_deploys {
-            if !self.deps_resolved(&pending_deploy_info.info.header, &past_deploys)
-                || past_deploys.contains(hash)
-                || self.contains_finalized(hash)
-                || block_timestamp.saturating_diff(pending_deploy_info.timestamp)
-                    < self.local_config.deploy_delay
-            {
-                continue;
-            }
+        // Now we try to add other deploys to the block, again in as many rounds as it takes to
+        // add deploys depending on others in the same block.
+        let mut included_deploys = HashSet::new();
+        let mut added_any = true;
+        'deploys: while added_any {
+            added_any = false;
+            for (hash, pending_deploy_info) in self.ordered_pending(&self.sets.pending_deploys) {
+                if included_deploys.contains(hash)
+                    || !self.deps_resolved(&pending_deploy_info.info.header, &included_deploys)
+                    || !self.sequence_resolved(&pending_deploy_info.info, |other| {
+                        past_deploys.contains(other)
+                            || included_transfers.contains(other)
+                            || included_deploys.contains(other)
+                    })
+                    || past_deploys.contains(hash)
+                    || self.contains_finalized(hash)
+                    || block_timestamp.saturating_diff(pending_deploy_info.timestamp)
+                        < self.local_config.deploy_delay
+                {
+                    continue;
+                }
 
-            if let Err(err) = appendable_block.add_deploy(
-                DeployWithApprovals::new(*hash, pending_deploy_info.approvals.clone()),
-                &pending_deploy_info.info,
-            ) {
-                match err {
-                    // We added the maximum number of deploys.
-                    AddError::DeployCount => break,
-                    AddError::BlockSize => {
-                        if appendable_block.total_size() + DEPLOY_APPROX_MIN_SIZE
-                            > deploy_config.block_gas_limit as usize
-                        {
-                            break; // Probably no deploy will fit in this block anymore.
-                        }
-                    }
-                    // This deploy would exceed the approval count, but another one with fewer
-                    // approvals might not.
-                    AddError::ApprovalCount if pending_deploy_info.approvals.len() > 1 => (),
-                    AddError::ApprovalCount => break,
-                    // The deploy is not valid in this block, but might be valid in another.
-                    // TODO: Do something similar to DEPLOY_APPROX_MIN_SIZE for gas.
-                    AddError::InvalidDeploy | AddError::GasLimit => (),
-                    // These errors should never happen when adding a deploy.
-                    AddError::TransferCount | AddError::Duplicate => {
-                        error!(?err, "unexpected error when adding deploy")
-                    }
-                    AddError::InvalidGasAmount => {
-                        error!("payment_amount couldn't be converted from motes to gas")
+                match appendable_block.add_deploy(
+                    DeployWithApprovals::new(*hash, pending_deploy_info.approvals.clone()),
+                    &pending_deploy_info.info,
+                ) {
+                    Ok(()) => {
+                        included_deploys.insert(*hash);
+                        added_any = true;
                     }
+                    Err(err) => match err {
+                        // We added the maximum number of deploys.
+                        AddError::DeployCount => break 'deploys,
+                        AddError::BlockSize => {
+                            if appendable_block.total_size() + DEPLOY_APPROX_MIN_SIZE
+                                > deploy_config.block_gas_limit as usize
+                            {
+                                // Probably no deploy will fit in this block anymore.
+                                break 'deploys;
+                            }
+                        }
+                        // This deploy would exceed the approval count, but another one with fewer
+                        // approvals might not.
+                        AddError::ApprovalCount if pending_deploy_info.approvals.len() > 1 => (),
+                        AddError::ApprovalCount => break 'deploys,
+                        // The deploy is not valid in this block, but might be valid in another.
+                        // TODO: Do something similar to DEPLOY_APPROX_MIN_SIZE for gas.
+                        AddError::InvalidDeploy | AddError::GasLimit => (),
+                        // These errors should never happen when adding a deploy.
+                        AddError::TransferCount
+                        | AddError::TransferGasLimit
+                        | AddError::Duplicate => {
+                            error!(?err, "unexpected error when adding deploy")
+                        }
+                        AddError::InvalidGasAmount => {
+                            error!("payment_amount couldn't be converted from motes to gas")
+                        }
+                    },
                 }
             }
         }
 
-        Arc::new(appendable_block.into_block_payload(accusations, random_bit))
+        self.observations.proposals.push(ProposalFill {
+            gas: appendable_block.total_gas().value().as_u64() as f64
+                / deploy_config.block_gas_limit.max(1) as f64,
+            deploy_count: appendable_block.deploy_count() as f64
+                / deploy_config.block_max_deploy_count.max(1) as f64,
+            transfer_count: appendable_block.transfer_count() as f64
+                / deploy_config.block_max_transfer_count.max(1) as f64,
+        });
+
+        Arc::new(
+            appendable_block
+                .into_block_payload(accusations, random_bit)
+                .with_signals(self.local_config.signals),
+        )
+    }
+
+    /// Stores a snapshot of the pending deploys, including their arrival times, so they survive a
+    /// restart of the node.
+    fn save_state<REv>(&mut self, effect_builder: EffectBuilder<REv>) -> Effects<Event>
+    where
+        REv: From<StateStoreRequest> + Send,
+    {
+        self.state_dirty = false;
+        effect_builder
+            .save_state(STATE_KEY.into(), CachedState::from(&self.sets))
+            .ignore()
     }
 
     /// Prunes expired deploy information from the BlockProposer, returns the hashes of deploys
     /// pruned.
     fn prune(&mut self, current_instant: Timestamp) -> PruneResult {
-        self.sets.prune(current_instant)
+        for (hash, pending_deploy_info) in self
+            .sets
+            .pending_deploys
+            .iter()
+            .chain(&self.sets.pending_transfers)
+            .filter(|(_, pending_deploy_info)| {
+                pending_deploy_info.info.header.expired(current_instant)
+            })
+        {
+            let unresolvable = self.unresolvable_dependencies_of(&pending_deploy_info.info.header);
+            if !unresolvable.is_empty() {
+                warn!(
+                    %hash, ?unresolvable,
+                    "deploy expired with unresolvable dependencies"
+                );
+            }
+        }
+        let prune_result = self.sets.prune(current_instant);
+        self.observations.evictions.extend(
+            prune_result
+                .expired_hashes_to_be_announced
+                .iter()
+                .map(|_| EvictionReason::Expired),
+        );
+        prune_result
     }
 
     fn contains_finalized(&self, hash: &DeployHash) -> bool {
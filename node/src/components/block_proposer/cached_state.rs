@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use casper_types::PublicKey;
+
 use super::{deploy_sets::PendingDeployInfo, BlockProposerDeploySets};
 use crate::types::DeployHash;
 
@@ -11,6 +13,7 @@ use crate::types::DeployHash;
 pub(crate) struct CachedState {
     pub(super) pending_deploys: HashMap<DeployHash, PendingDeployInfo>,
     pub(super) pending_transfers: HashMap<DeployHash, PendingDeployInfo>,
+    pub(super) finalized_sequence_numbers: HashMap<PublicKey, u64>,
 }
 
 impl From<&BlockProposerDeploySets> for CachedState {
@@ -18,6 +21,7 @@ impl From<&BlockProposerDeploySets> for CachedState {
         CachedState {
             pending_deploys: sets.pending_deploys.clone(),
             pending_transfers: sets.pending_transfers.clone(),
+            finalized_sequence_numbers: sets.finalized_sequence_numbers.clone(),
         }
     }
 }
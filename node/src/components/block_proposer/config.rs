@@ -13,12 +13,27 @@ pub struct Config {
     /// other nodes, and don't have to be requested from the proposer afterwards.
     #[serde(default = "default_deploy_delay")]
     pub deploy_delay: TimeDiff,
+    /// The order in which pending deploys and transfers are considered for inclusion in a block.
+    #[serde(default)]
+    pub deploy_ordering: DeployOrdering,
+    /// The maximum number of pending deploys and transfers from a single account to hold.  Further
+    /// deploys from that account are rejected until some of them are included or expire.  `0`
+    /// means no limit.
+    #[serde(default)]
+    pub max_pending_deploys_per_account: u32,
+    /// Signaling bits set in the block payloads this node proposes.  Their meaning is agreed on
+    /// off-chain, e.g. to indicate readiness for an upcoming upgrade.
+    #[serde(default)]
+    pub signals: u8,
 }
 
 impl Default for Config {
     fn default() -> Config {
         Config {
             deploy_delay: default_deploy_delay(),
+            deploy_ordering: DeployOrdering::default(),
+            max_pending_deploys_per_account: 0,
+            signals: 0,
         }
     }
 }
@@ -26,3 +41,22 @@ impl Default for Config {
 fn default_deploy_delay() -> TimeDiff {
     "1min".parse().unwrap()
 }
+
+/// Policy for ordering pending deploys when proposing a block.
+///
+/// Defaults to "gas_price".
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployOrdering {
+    /// Deploys are considered in the order they were received.
+    Fifo,
+    /// Deploys offering a higher gas price are considered first; ties are broken by the order
+    /// they were received.
+    GasPrice,
+}
+
+impl Default for DeployOrdering {
+    fn default() -> Self {
+        DeployOrdering::GasPrice
+    }
+}
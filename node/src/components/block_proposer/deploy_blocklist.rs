@@ -0,0 +1,90 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use casper_types::account::AccountHash;
+
+use crate::types::DeployHash;
+
+/// Deploys and accounts blocked by the node operator at runtime.
+///
+/// Blocked deploys are rejected by the deploy acceptor and never proposed by the block proposer.
+/// The blocklist is put to storage on every change and loaded on initialization.
+#[derive(Clone, DataSize, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct DeployBlocklist {
+    /// Individually blocked deploys.
+    deploy_hashes: BTreeSet<DeployHash>,
+    /// Accounts all of whose deploys are blocked.
+    accounts: BTreeSet<AccountHash>,
+}
+
+/// A change to the `DeployBlocklist`.
+#[derive(Clone, Copy, DataSize, Debug, Serialize)]
+pub(crate) enum DeployBlocklistUpdate {
+    /// Blocks a deploy.
+    BlockDeploy(DeployHash),
+    /// Unblocks a deploy.
+    UnblockDeploy(DeployHash),
+    /// Blocks all deploys from an account.
+    BlockAccount(AccountHash),
+    /// Unblocks the deploys from an account.
+    UnblockAccount(AccountHash),
+}
+
+impl DeployBlocklist {
+    /// Returns `true` if the deploy or its account is blocked.
+    pub(crate) fn contains(&self, deploy_hash: &DeployHash, account_hash: &AccountHash) -> bool {
+        self.deploy_hashes.contains(deploy_hash) || self.accounts.contains(account_hash)
+    }
+
+    /// Applies the update, returning `true` if the blocklist changed.
+    pub(crate) fn apply(&mut self, update: DeployBlocklistUpdate) -> bool {
+        match update {
+            DeployBlocklistUpdate::BlockDeploy(deploy_hash) => {
+                self.deploy_hashes.insert(deploy_hash)
+            }
+            DeployBlocklistUpdate::UnblockDeploy(deploy_hash) => {
+                self.deploy_hashes.remove(&deploy_hash)
+            }
+            DeployBlocklistUpdate::BlockAccount(account_hash) => self.accounts.insert(account_hash),
+            DeployBlocklistUpdate::UnblockAccount(account_hash) => {
+                self.accounts.remove(&account_hash)
+            }
+        }
+    }
+}
+
+impl Display for DeployBlocklist {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "blocked deploys: {}", self.deploy_hashes.len())?;
+        for deploy_hash in &self.deploy_hashes {
+            writeln!(f, "  {}", deploy_hash)?;
+        }
+        write!(f, "blocked accounts: {}", self.accounts.len())?;
+        for account_hash in &self.accounts {
+            write!(f, "\n  {}", account_hash.to_formatted_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DeployBlocklistUpdate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DeployBlocklistUpdate::BlockDeploy(deploy_hash) => write!(f, "block {}", deploy_hash),
+            DeployBlocklistUpdate::UnblockDeploy(deploy_hash) => {
+                write!(f, "unblock {}", deploy_hash)
+            }
+            DeployBlocklistUpdate::BlockAccount(account_hash) => {
+                write!(f, "block {}", account_hash.to_formatted_string())
+            }
+            DeployBlocklistUpdate::UnblockAccount(account_hash) => {
+                write!(f, "unblock {}", account_hash.to_formatted_string())
+            }
+        }
+    }
+}
@@ -8,10 +8,11 @@ use datasize::DataSize;
 use itertools::{Either, Itertools};
 use serde::{Deserialize, Serialize};
 
-use casper_types::{TimeDiff, Timestamp};
+use casper_hashing::Digest;
+use casper_types::{PublicKey, TimeDiff, Timestamp};
 
-use super::{BlockHeight, CachedState, DeployInfo, FinalizationQueue};
-use crate::types::{Approval, Block, DeployHash};
+use super::{BlockHeight, CachedState, DeployBlocklist, DeployInfo, FinalizationQueue};
+use crate::types::{Approval, Block, DeployHash, DeployOrTransferHash};
 
 pub(crate) struct PruneResult {
     pub(crate) total_pruned: usize,
@@ -27,6 +28,30 @@ impl PruneResult {
     }
 }
 
+/// Identifies which pending deploys or transfers of an account a new one may replace.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Hash)]
+enum ReplacementKey {
+    /// The replacement tag chosen by the account.
+    Tag(u64),
+    /// The session code and dependencies of an untagged deploy.
+    Session {
+        session_hash: Digest,
+        dependencies: Vec<DeployHash>,
+    },
+}
+
+impl ReplacementKey {
+    fn of(deploy_info: &DeployInfo) -> Self {
+        match deploy_info.replacement_tag {
+            Some(tag) => ReplacementKey::Tag(tag),
+            None => ReplacementKey::Session {
+                session_hash: deploy_info.session_hash,
+                dependencies: deploy_info.header.dependencies().clone(),
+            },
+        }
+    }
+}
+
 #[derive(Clone, DataSize, Debug, Serialize, Deserialize)]
 pub(super) struct PendingDeployInfo {
     pub(super) approvals: BTreeSet<Approval>,
@@ -49,6 +74,10 @@ pub(super) struct BlockProposerDeploySets {
     /// The transfers that have already been included in a finalized block, and their earliest
     /// known expiry date.
     pub(super) finalized_transfers: HashMap<DeployHash, Timestamp>,
+    /// The deploys and transfers that have already been executed as part of a block added to the
+    /// linear chain, and their earliest known expiry date.  Only these satisfy dependencies of
+    /// deploys proposed in a later block.
+    pub(super) executed_deploys: HashMap<DeployHash, Timestamp>,
     /// The next block height we expect to be finalized.
     /// If we receive a notification of finalization of a later block, we will store it in
     /// finalization_queue.
@@ -58,6 +87,24 @@ pub(super) struct BlockProposerDeploySets {
     /// The queue of finalized block contents awaiting inclusion in `self.finalized_deploys` and
     /// `self.finalized_transfers`.
     pub(super) finalization_queue: FinalizationQueue,
+    /// Deploys and transfers which were replaced by a pending one offering a higher price, mapped
+    /// to their pending replacement.  If a replaced deploy is finalized anyway, its replacement
+    /// must not be proposed.
+    pub(super) replaced_deploys: HashMap<DeployHash, DeployHash>,
+    /// The highest sequence number of each account's finalized deploys signed by the account's
+    /// own key, if the chainspec enables strict account ordering.  Only deploys which were pending
+    /// here are taken into account.
+    pub(super) finalized_sequence_numbers: HashMap<PublicKey, u64>,
+    /// The number of pending deploys and transfers signed by each account's own key.  Deploys
+    /// from peers are not checked against the account's authorization, so any others don't count
+    /// towards the account's quota.  Kept in step with
+    /// `pending_deploys` and `pending_transfers`, which must only be added to or removed from via
+    /// `add_pending` and `remove_pending`, or be followed by `reindex_pending`.
+    pending_count_per_account: HashMap<PublicKey, usize>,
+    /// A pending deploy or transfer signed by the account's own key for each account and
+    /// replacement key, which a new one with the same account and replacement key may replace.
+    /// Kept in step like `pending_count_per_account`.
+    replaceable: HashMap<(PublicKey, ReplacementKey), DeployHash>,
 }
 
 impl BlockProposerDeploySets {
@@ -71,6 +118,7 @@ impl BlockProposerDeploySets {
     ) -> (BlockProposerDeploySets, PruneResult) {
         let mut finalized_deploys = HashMap::<DeployHash, Timestamp>::new();
         let mut finalized_transfers = HashMap::<DeployHash, Timestamp>::new();
+        let mut executed_deploys = HashMap::<DeployHash, Timestamp>::new();
         for block in finalized_blocks {
             let expiry = block.header().timestamp().saturating_add(max_ttl);
             for hash in block.body().deploy_hashes() {
@@ -79,11 +127,16 @@ impl BlockProposerDeploySets {
             for hash in block.body().transfer_hashes() {
                 finalized_transfers.insert(*hash, expiry);
             }
+            // Blocks in storage have already been executed.
+            for hash in block.deploy_hashes().iter().chain(block.transfer_hashes()) {
+                executed_deploys.insert(*hash, expiry);
+            }
         }
 
         let CachedState {
             mut pending_deploys,
             mut pending_transfers,
+            finalized_sequence_numbers,
         } = cached_state;
         pending_deploys.retain(|hash, _| !finalized_deploys.contains_key(hash));
         pending_transfers.retain(|hash, _| !finalized_transfers.contains_key(hash));
@@ -93,9 +146,12 @@ impl BlockProposerDeploySets {
             pending_transfers,
             finalized_deploys,
             finalized_transfers,
+            executed_deploys,
             next_finalized: next_finalized_height,
+            finalized_sequence_numbers,
             ..Default::default()
         };
+        sets.reindex_pending();
         let prune_result = sets.prune(Timestamp::now());
         (sets, prune_result)
     }
@@ -105,6 +161,9 @@ impl BlockProposerDeploySets {
     pub(super) fn prune(&mut self, current_instant: Timestamp) -> PruneResult {
         let pending_deploys = prune_pending_deploys(&mut self.pending_deploys, current_instant);
         let pending_transfers = prune_pending_deploys(&mut self.pending_transfers, current_instant);
+        if !pending_deploys.is_empty() || !pending_transfers.is_empty() {
+            self.reindex_pending();
+        }
 
         // We prune from finalized deploys and transfers collections because expired ones can never
         // be proposed again. This makes the collections smaller for later iterations.
@@ -117,6 +176,18 @@ impl BlockProposerDeploySets {
                 *expiry < current_instant
             });
 
+        hashmap_drain_filter_in_place(&mut self.executed_deploys, |expiry| {
+            *expiry < current_instant
+        });
+
+        // Replacements which are no longer pending can't be proposed anymore anyway.
+        let (pending_deploys_map, pending_transfers_map) =
+            (&self.pending_deploys, &self.pending_transfers);
+        self.replaced_deploys.retain(|_, replacement| {
+            pending_deploys_map.contains_key(replacement)
+                || pending_transfers_map.contains_key(replacement)
+        });
+
         // We return a total of pruned deploys, but for the deploys pruned
         // from the `finalized` collection we don't want to send
         // the expiration event.
@@ -129,6 +200,205 @@ impl BlockProposerDeploySets {
         )
     }
 
+    /// Returns whether the given deploy or transfer is pending.
+    pub(super) fn is_pending(&self, hash: &DeployHash) -> bool {
+        self.pending_deploys.contains_key(hash) || self.pending_transfers.contains_key(hash)
+    }
+
+    /// Adds a pending deploy or transfer, or updates it if already pending.
+    pub(super) fn add_pending(
+        &mut self,
+        hash: &DeployOrTransferHash,
+        pending_deploy_info: PendingDeployInfo,
+    ) {
+        let account = pending_deploy_info.info.header.account().clone();
+        let replacement_key = ReplacementKey::of(&pending_deploy_info.info);
+        let signed_by_account = pending_deploy_info.info.signed_by_account;
+        let pending = if hash.is_transfer() {
+            &mut self.pending_transfers
+        } else {
+            &mut self.pending_deploys
+        };
+        if let Some(previous) = pending.insert(*hash.deploy_hash(), pending_deploy_info) {
+            self.decrement_pending_count(&previous.info);
+        }
+        if signed_by_account {
+            *self
+                .pending_count_per_account
+                .entry(account.clone())
+                .or_default() += 1;
+            self.replaceable
+                .insert((account, replacement_key), *hash.deploy_hash());
+        }
+    }
+
+    /// Removes a pending deploy, or a pending transfer if `is_transfer` is true.  Returns its
+    /// information if it was pending.
+    pub(super) fn remove_pending(
+        &mut self,
+        hash: &DeployHash,
+        is_transfer: bool,
+    ) -> Option<PendingDeployInfo> {
+        let pending = if is_transfer {
+            &mut self.pending_transfers
+        } else {
+            &mut self.pending_deploys
+        };
+        let removed = pending.remove(hash)?;
+        self.decrement_pending_count(&removed.info);
+        let index_key = (
+            removed.info.header.account().clone(),
+            ReplacementKey::of(&removed.info),
+        );
+        if self.replaceable.get(&index_key) == Some(hash) {
+            self.replaceable.remove(&index_key);
+        }
+        Some(removed)
+    }
+
+    fn decrement_pending_count(&mut self, deploy_info: &DeployInfo) {
+        if !deploy_info.signed_by_account {
+            return;
+        }
+        let account = deploy_info.header.account();
+        if let Some(count) = self.pending_count_per_account.get_mut(account) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.pending_count_per_account.remove(account);
+            }
+        }
+    }
+
+    /// Recounts and reindexes the pending deploys and transfers of each account after bulk
+    /// changes.
+    fn reindex_pending(&mut self) {
+        let mut pending_count_per_account = HashMap::new();
+        let mut replaceable = HashMap::new();
+        for (hash, pending_deploy_info) in self
+            .pending_deploys
+            .iter()
+            .chain(self.pending_transfers.iter())
+        {
+            if !pending_deploy_info.info.signed_by_account {
+                continue;
+            }
+            let account = pending_deploy_info.info.header.account();
+            *pending_count_per_account
+                .entry(account.clone())
+                .or_default() += 1;
+            replaceable.insert(
+                (
+                    account.clone(),
+                    ReplacementKey::of(&pending_deploy_info.info),
+                ),
+                *hash,
+            );
+        }
+        self.pending_count_per_account = pending_count_per_account;
+        self.replaceable = replaceable;
+    }
+
+    /// Returns the pending deploy or transfer which the given one may replace, i.e. one of the
+    /// same kind, from the same account, with the same replacement tag or, if neither is tagged,
+    /// with identical session code and dependencies.  Both have to be signed by the account's own
+    /// key, so that no one else can evict the account's deploys.
+    pub(super) fn find_replaceable(
+        &self,
+        hash: &DeployOrTransferHash,
+        deploy_info: &DeployInfo,
+    ) -> Option<(DeployHash, &PendingDeployInfo)> {
+        if !deploy_info.signed_by_account {
+            return None;
+        }
+        let index_key = (
+            deploy_info.header.account().clone(),
+            ReplacementKey::of(deploy_info),
+        );
+        let replaceable = *self.replaceable.get(&index_key)?;
+        if replaceable == *hash.deploy_hash() {
+            return None;
+        }
+        let pending = if hash.is_transfer() {
+            &self.pending_transfers
+        } else {
+            &self.pending_deploys
+        };
+        pending
+            .get(&replaceable)
+            .map(|pending_deploy_info| (replaceable, pending_deploy_info))
+    }
+
+    /// Replaces the pending deploy or transfer `replaced` with `replacement`.
+    pub(super) fn replace_pending(
+        &mut self,
+        hash: &DeployOrTransferHash,
+        replaced: DeployHash,
+        pending_deploy_info: PendingDeployInfo,
+    ) {
+        self.remove_pending(&replaced, hash.is_transfer());
+        self.add_pending(hash, pending_deploy_info);
+
+        // Deploys replaced earlier are now replaced by the new one.
+        for replacement in self.replaced_deploys.values_mut() {
+            if *replacement == replaced {
+                *replacement = *hash.deploy_hash();
+            }
+        }
+        self.replaced_deploys.insert(replaced, *hash.deploy_hash());
+    }
+
+    /// Removes the pending replacement of the given deploy or transfer, if any, as the latter has
+    /// been finalized.  Returns the hash of the removed replacement.
+    pub(super) fn remove_replacement_of(&mut self, finalized: &DeployHash) -> Option<DeployHash> {
+        let replacement = self.replaced_deploys.remove(finalized)?;
+        if self.remove_pending(&replacement, false).is_none() {
+            self.remove_pending(&replacement, true);
+        }
+        Some(replacement)
+    }
+
+    /// Removes the pending deploys and transfers on the given blocklist.  Returns the hashes of the
+    /// removed deploys and transfers.
+    pub(super) fn remove_blocked(&mut self, blocklist: &DeployBlocklist) -> Vec<DeployHash> {
+        let is_blocked = |hash: &DeployHash, pending_deploy_info: &PendingDeployInfo| {
+            let account_hash = pending_deploy_info.info.header.account().to_account_hash();
+            blocklist.contains(hash, &account_hash)
+        };
+        let mut removed = vec![];
+        for pending in [&mut self.pending_deploys, &mut self.pending_transfers] {
+            pending.retain(|hash, pending_deploy_info| {
+                if is_blocked(hash, pending_deploy_info) {
+                    removed.push(*hash);
+                    return false;
+                }
+                true
+            });
+        }
+        if !removed.is_empty() {
+            self.reindex_pending();
+        }
+        removed
+    }
+
+    /// Returns the number of pending deploys and transfers signed by the given account's own key.
+    pub(super) fn pending_count_for_account(&self, account: &PublicKey) -> usize {
+        self.pending_count_per_account
+            .get(account)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Adds the deploys and transfers of an executed block.
+    pub(super) fn add_executed_block(&mut self, block: &Block, max_ttl: TimeDiff) {
+        let new_expiry = block.header().timestamp().saturating_add(max_ttl);
+        for hash in block.deploy_hashes().iter().chain(block.transfer_hashes()) {
+            self.executed_deploys
+                .entry(*hash)
+                .and_modify(|expiry| *expiry = new_expiry.min(*expiry))
+                .or_insert(new_expiry);
+        }
+    }
+
     /// Adds a finalized deploy hash.
     pub(super) fn add_finalized_deploy(&mut self, hash: DeployHash, new_expiry: Timestamp) {
         self.finalized_deploys
@@ -8,12 +8,15 @@ use derive_more::From;
 use fmt::Display;
 use serde::{Deserialize, Serialize};
 
+use casper_hashing::Digest;
 use casper_types::Motes;
 
-use super::{BlockHeight, CachedState};
+use super::{BlockHeight, CachedState, DeployBlocklist};
 use crate::{
     effect::requests::BlockProposerRequest,
-    types::{Approval, Block, DeployHeader, DeployOrTransferHash, FinalizedBlock},
+    types::{
+        Approval, Block, Deploy, DeployHash, DeployHeader, DeployOrTransferHash, FinalizedBlock,
+    },
 };
 
 /// Information about a deploy.
@@ -22,6 +25,17 @@ pub struct DeployInfo {
     pub header: DeployHeader,
     pub payment_amount: Motes,
     pub size: usize,
+    /// The hash of the serialized session code, used to identify replacement deploys.
+    pub session_hash: Digest,
+    /// The deploy's replacement tag, if any, used to identify replacement deploys.
+    pub replacement_tag: Option<u64>,
+    /// The deploy's sequence number, used to order the deploys of an account if the chainspec
+    /// enables strict account ordering.
+    pub sequence_number: Option<u64>,
+    /// Whether the deploy is approved by the account's own key.  Deploys received from peers are
+    /// not checked against the account's authorization, so only such deploys may replace, or be
+    /// replaced by, another deploy of the account.
+    pub signed_by_account: bool,
 }
 
 /// An event for when using the block proposer as a component.
@@ -38,6 +52,8 @@ pub(crate) enum Event {
         next_finalized_block: BlockHeight,
         /// The cached state retrieved from storage.
         cached_state: CachedState,
+        /// The deploy blocklist retrieved from storage.
+        blocklist: DeployBlocklist,
     },
     /// A new deploy has been received by this node and stored: it should be retrieved from storage
     /// and buffered here.
@@ -48,8 +64,18 @@ pub(crate) enum Event {
     },
     /// The block proposer has been asked to prune stale deploys.
     Prune,
+    /// The block proposer has been asked to persist its pending deploys, if they changed.
+    PersistState,
     /// A block has been finalized. We should never propose its deploys again.
     FinalizedBlock(Box<FinalizedBlock>),
+    /// A block has been executed and added to the linear chain. Its deploys now satisfy the
+    /// dependencies of other deploys.
+    BlockExecuted(Box<Block>),
+    /// Deploys were proposed in an era that ended without finalizing them. They should be
+    /// proposed again.
+    OrphanedDeploys(Vec<DeployHash>),
+    /// Orphaned deploys that were not pending anymore have been retrieved from storage.
+    OrphanedDeploysLoaded(Vec<Deploy>),
 }
 
 impl Display for Event {
@@ -66,9 +92,23 @@ impl Display for Event {
             ),
             Event::BufferDeploy { hash, .. } => write!(f, "block-proposer add {}", hash),
             Event::Prune => write!(f, "block-proposer prune"),
+            Event::PersistState => write!(f, "block-proposer persist state"),
             Event::FinalizedBlock(block) => {
                 write!(f, "block-proposer finalized block {}", block)
             }
+            Event::BlockExecuted(block) => {
+                write!(f, "block-proposer executed block {}", block.hash())
+            }
+            Event::OrphanedDeploys(deploy_hashes) => {
+                write!(f, "block-proposer {} orphaned deploys", deploy_hashes.len())
+            }
+            Event::OrphanedDeploysLoaded(deploys) => {
+                write!(
+                    f,
+                    "block-proposer loaded {} orphaned deploys",
+                    deploys.len()
+                )
+            }
         }
     }
 }
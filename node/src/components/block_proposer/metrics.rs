@@ -1,14 +1,104 @@
 use datasize::DataSize;
-use prometheus::{self, IntGauge, Registry};
+use prometheus::{self, Histogram, IntCounter, IntGauge, Registry};
 
-use crate::unregister_metric;
+use casper_types::{TimeDiff, Timestamp};
+
+use super::deploy_sets::BlockProposerDeploySets;
+use crate::{unregister_metric, utils};
+
+const DEPLOY_AGE_AT_FINALIZATION_NAME: &str = "block_proposer_deploy_age_at_finalization";
+const DEPLOY_AGE_AT_FINALIZATION_HELP: &str =
+    "time in seconds a deploy was pending in the block proposer before being finalized";
+const PROPOSAL_GAS_FILL_NAME: &str = "block_proposer_proposal_gas_fill";
+const PROPOSAL_GAS_FILL_HELP: &str = "fraction of the block gas limit used by each proposed block";
+const PROPOSAL_DEPLOY_COUNT_FILL_NAME: &str = "block_proposer_proposal_deploy_count_fill";
+const PROPOSAL_DEPLOY_COUNT_FILL_HELP: &str =
+    "fraction of the maximum deploy count used by each proposed block";
+const PROPOSAL_TRANSFER_COUNT_FILL_NAME: &str = "block_proposer_proposal_transfer_count_fill";
+const PROPOSAL_TRANSFER_COUNT_FILL_HELP: &str =
+    "fraction of the maximum transfer count used by each proposed block";
+
+/// Value of upper bound of the first age bucket.  In seconds.
+const AGE_BUCKET_START: f64 = 1.0;
+
+/// Multiplier of previous upper bound for next bound.
+const AGE_BUCKET_FACTOR: f64 = 2.0;
+
+/// Age bucket count, with the last bucket going to +Inf which will not be included in the results.
+const AGE_BUCKET_COUNT: usize = 14;
+
+/// Fill ratio buckets: `0.0`, i.e. an empty block, then steps of 10% up to a full block.
+const FILL_BUCKET_WIDTH: f64 = 0.1;
+const FILL_BUCKET_COUNT: usize = 11;
+
+/// The reason a pending deploy was dropped from the block proposer without being finalized.
+#[derive(Clone, Copy, DataSize, Debug, Eq, PartialEq)]
+pub(super) enum EvictionReason {
+    /// The deploy expired.
+    Expired,
+    /// The deploy was replaced by one offering a higher price.
+    Replaced,
+    /// The deploy was a replacement, but the deploy it replaced got finalized.
+    ReplacedDeployFinalized,
+    /// The deploy or its account was blocked by the node operator.
+    Blocked,
+}
+
+/// How much of the capacity of a proposed block was used, as fractions between 0 and 1.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq)]
+pub(super) struct ProposalFill {
+    pub(super) gas: f64,
+    pub(super) deploy_count: f64,
+    pub(super) transfer_count: f64,
+}
+
+/// Events observed by the ready block proposer which are not yet reflected in the metrics.
+#[derive(DataSize, Debug, Default)]
+pub(super) struct Observations {
+    /// Pending deploys evicted without being finalized.
+    pub(super) evictions: Vec<EvictionReason>,
+    /// The time finalized deploys were pending for.
+    pub(super) ages_at_finalization: Vec<TimeDiff>,
+    /// The fill ratios of proposed blocks.
+    pub(super) proposals: Vec<ProposalFill>,
+}
 
 /// Metrics for the block proposer.
 #[derive(DataSize, Debug, Clone)]
 pub(super) struct Metrics {
     /// Amount of pending deploys
     #[data_size(skip)]
-    pub(super) pending_deploys: IntGauge,
+    pending_deploys: IntGauge,
+    /// Total serialized size of the pending deploys, in bytes.
+    #[data_size(skip)]
+    pending_deploy_bytes: IntGauge,
+    /// Age of the oldest pending deploy, in seconds.
+    #[data_size(skip)]
+    oldest_pending_deploy_age: IntGauge,
+    /// Time deploys were pending before being finalized.
+    #[data_size(skip)]
+    deploy_age_at_finalization: Histogram,
+    /// Number of pending deploys evicted due to expiry.
+    #[data_size(skip)]
+    evicted_expired: IntCounter,
+    /// Number of pending deploys evicted due to being replaced.
+    #[data_size(skip)]
+    evicted_replaced: IntCounter,
+    /// Number of pending replacement deploys evicted as the deploy they replaced was finalized.
+    #[data_size(skip)]
+    evicted_replaced_deploy_finalized: IntCounter,
+    /// Number of pending deploys evicted due to being blocked by the node operator.
+    #[data_size(skip)]
+    evicted_blocked: IntCounter,
+    /// Fraction of the block gas limit used by proposed blocks.
+    #[data_size(skip)]
+    proposal_gas_fill: Histogram,
+    /// Fraction of the maximum deploy count used by proposed blocks.
+    #[data_size(skip)]
+    proposal_deploy_count_fill: Histogram,
+    /// Fraction of the maximum transfer count used by proposed blocks.
+    #[data_size(skip)]
+    proposal_transfer_count_fill: Histogram,
     /// Registry stored to allow deregistration later.
     #[data_size(skip)]
     registry: Registry,
@@ -18,16 +108,136 @@ impl Metrics {
     /// Creates a new instance of the block proposer metrics.
     pub fn new(registry: Registry) -> Result<Self, prometheus::Error> {
         let pending_deploys = IntGauge::new("pending_deploy", "the number of pending deploys")?;
+        let pending_deploy_bytes = IntGauge::new(
+            "block_proposer_pending_deploy_bytes",
+            "total serialized size in bytes of the pending deploys",
+        )?;
+        let oldest_pending_deploy_age = IntGauge::new(
+            "block_proposer_oldest_pending_deploy_age",
+            "age in seconds of the oldest pending deploy",
+        )?;
+        let evicted_expired = IntCounter::new(
+            "block_proposer_evicted_expired_deploys",
+            "number of pending deploys evicted due to expiry",
+        )?;
+        let evicted_replaced = IntCounter::new(
+            "block_proposer_evicted_replaced_deploys",
+            "number of pending deploys evicted due to being replaced by a higher-priced deploy",
+        )?;
+        let evicted_replaced_deploy_finalized = IntCounter::new(
+            "block_proposer_evicted_replacement_deploys",
+            "number of pending replacement deploys evicted as the replaced deploy was finalized",
+        )?;
+        let evicted_blocked = IntCounter::new(
+            "block_proposer_evicted_blocked_deploys",
+            "number of pending deploys evicted due to being blocked by the node operator",
+        )?;
         registry.register(Box::new(pending_deploys.clone()))?;
+        registry.register(Box::new(pending_deploy_bytes.clone()))?;
+        registry.register(Box::new(oldest_pending_deploy_age.clone()))?;
+        registry.register(Box::new(evicted_expired.clone()))?;
+        registry.register(Box::new(evicted_replaced.clone()))?;
+        registry.register(Box::new(evicted_replaced_deploy_finalized.clone()))?;
+        registry.register(Box::new(evicted_blocked.clone()))?;
+
+        let age_buckets =
+            prometheus::exponential_buckets(AGE_BUCKET_START, AGE_BUCKET_FACTOR, AGE_BUCKET_COUNT)?;
+        let fill_buckets = prometheus::linear_buckets(0.0, FILL_BUCKET_WIDTH, FILL_BUCKET_COUNT)?;
+
         Ok(Metrics {
             pending_deploys,
+            pending_deploy_bytes,
+            oldest_pending_deploy_age,
+            deploy_age_at_finalization: utils::register_histogram_metric(
+                &registry,
+                DEPLOY_AGE_AT_FINALIZATION_NAME,
+                DEPLOY_AGE_AT_FINALIZATION_HELP,
+                age_buckets,
+            )?,
+            evicted_expired,
+            evicted_replaced,
+            evicted_replaced_deploy_finalized,
+            evicted_blocked,
+            proposal_gas_fill: utils::register_histogram_metric(
+                &registry,
+                PROPOSAL_GAS_FILL_NAME,
+                PROPOSAL_GAS_FILL_HELP,
+                fill_buckets.clone(),
+            )?,
+            proposal_deploy_count_fill: utils::register_histogram_metric(
+                &registry,
+                PROPOSAL_DEPLOY_COUNT_FILL_NAME,
+                PROPOSAL_DEPLOY_COUNT_FILL_HELP,
+                fill_buckets.clone(),
+            )?,
+            proposal_transfer_count_fill: utils::register_histogram_metric(
+                &registry,
+                PROPOSAL_TRANSFER_COUNT_FILL_NAME,
+                PROPOSAL_TRANSFER_COUNT_FILL_HELP,
+                fill_buckets,
+            )?,
             registry,
         })
     }
+
+    /// Updates the metrics from the current pending deploys and the events observed since the last
+    /// update.
+    pub(super) fn update(
+        &self,
+        sets: &BlockProposerDeploySets,
+        observations: Observations,
+        now: Timestamp,
+    ) {
+        let pending = sets
+            .pending_deploys
+            .values()
+            .chain(sets.pending_transfers.values());
+        let (mut count, mut bytes, mut oldest) = (0, 0, now);
+        for pending_deploy_info in pending {
+            count += 1;
+            bytes += pending_deploy_info.info.size;
+            oldest = oldest.min(pending_deploy_info.timestamp);
+        }
+        self.pending_deploys.set(count);
+        self.pending_deploy_bytes.set(bytes as i64);
+        self.oldest_pending_deploy_age
+            .set((now.saturating_diff(oldest).millis() / 1000) as i64);
+
+        for reason in observations.evictions {
+            match reason {
+                EvictionReason::Expired => self.evicted_expired.inc(),
+                EvictionReason::Replaced => self.evicted_replaced.inc(),
+                EvictionReason::ReplacedDeployFinalized => {
+                    self.evicted_replaced_deploy_finalized.inc()
+                }
+                EvictionReason::Blocked => self.evicted_blocked.inc(),
+            }
+        }
+        for age in observations.ages_at_finalization {
+            self.deploy_age_at_finalization
+                .observe(age.millis() as f64 / 1000.0);
+        }
+        for fill in observations.proposals {
+            self.proposal_gas_fill.observe(fill.gas);
+            self.proposal_deploy_count_fill.observe(fill.deploy_count);
+            self.proposal_transfer_count_fill
+                .observe(fill.transfer_count);
+        }
+    }
 }
 
 impl Drop for Metrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.pending_deploys);
+        unregister_metric!(self.registry, self.pending_deploy_bytes);
+        unregister_metric!(self.registry, self.oldest_pending_deploy_age);
+        unregister_metric!(self.registry, self.deploy_age_at_finalization);
+        unregister_metric!(self.registry, self.evicted_expired);
+        unregister_metric!(self.registry, self.evicted_replaced);
+        unregister_metric!(self.registry, self.evicted_replaced_deploy_finalized);
+        unregister_metric!(self.registry, self.evicted_blocked);
+        unregister_metric!(self.registry, self.proposal_gas_fill);
+        unregister_metric!(self.registry, self.proposal_deploy_count_fill);
+        unregister_metric!(self.registry, self.proposal_transfer_count_fill);
     }
 }
@@ -3,13 +3,17 @@ use std::{convert::TryInto, time::Duration};
 use itertools::Itertools;
 
 use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+use casper_hashing::Digest;
 use casper_types::{
     bytesrepr::Bytes, runtime_args, system::standard_payment::ARG_AMOUNT, testing::TestRng, EraId,
-    Gas, PublicKey, RuntimeArgs, SecretKey, TimeDiff,
+    Gas, ProtocolVersion, PublicKey, RuntimeArgs, SecretKey, TimeDiff, U512,
 };
 
 use super::*;
-use crate::types::{BlockPayload, Deploy, DeployHash, FinalizedBlock};
+use crate::types::{
+    Block, BlockHash, BlockPayload, Deploy, DeployHash, FinalizedBlock, ARG_REPLACEMENT_TAG,
+    ARG_SEQUENCE_NUMBER,
+};
 
 const DEFAULT_TEST_GAS_PRICE: u64 = 1;
 
@@ -88,9 +92,75 @@ fn generate_deploy(
     )
 }
 
+/// Generates a deploy signed by the given key, so that several deploys can share an account.
+fn generate_deploy_signed_by(
+    secret_key: &SecretKey,
+    timestamp: Timestamp,
+    ttl: TimeDiff,
+    gas_price: u64,
+    payment_args: RuntimeArgs,
+    session_args: RuntimeArgs,
+) -> Deploy {
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: payment_args,
+    };
+    let session = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: session_args,
+    };
+
+    Deploy::new(
+        timestamp,
+        ttl,
+        gas_price,
+        vec![],
+        "chain".to_string(),
+        payment,
+        session,
+        secret_key,
+        None,
+    )
+}
+
+/// Generates a deploy signed by the given key, carrying the given sequence number.
+fn generate_sequenced_deploy(
+    secret_key: &SecretKey,
+    sequence_number: u64,
+    gas_price: u64,
+) -> Deploy {
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: runtime_args! {
+            ARG_AMOUNT => default_gas_payment().value(),
+            ARG_SEQUENCE_NUMBER => sequence_number
+        },
+    };
+    // Distinct session code, so the deploys don't replace each other.
+    let session = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: runtime_args! { "sequence" => sequence_number },
+    };
+
+    Deploy::new(
+        Timestamp::from(0),
+        TimeDiff::from(10000),
+        gas_price,
+        vec![],
+        "chain".to_string(),
+        payment,
+        session,
+        secret_key,
+        None,
+    )
+}
+
 fn create_test_proposer(deploy_delay: TimeDiff) -> BlockProposerReady {
     BlockProposerReady {
-        local_config: Config { deploy_delay },
+        local_config: Config {
+            deploy_delay,
+            ..Default::default()
+        },
         ..Default::default()
     }
 }
@@ -357,6 +427,13 @@ fn should_successfully_prune() {
 
     assert_eq!(proposer.sets.pending_deploys.len(), 1); // deploy4 is still valid
     assert_eq!(proposer.sets.finalized_deploys.len(), 0);
+
+    // Only the expired pending deploys count as evicted.
+    assert_eq!(
+        proposer.observations.evictions,
+        vec![EvictionReason::Expired, EvictionReason::Expired]
+    );
+    assert_eq!(proposer.observations.ages_at_finalization.len(), 1);
 }
 
 #[test]
@@ -661,14 +738,33 @@ fn test_proposer_with(
     proposer
 }
 
+#[test]
+fn should_record_proposal_fill_ratios() {
+    let proposer = test_proposer_with(TestArgs {
+        deploy_count: 15,
+        payment_amount: default_gas_payment(),
+        block_gas_limit: 5,
+        max_deploy_count: 15,
+        proposed_count: 5,
+        remaining_pending_count: 10,
+        ..Default::default()
+    });
+    assert_eq!(
+        proposer.observations.proposals,
+        vec![ProposalFill {
+            gas: 1.0,
+            deploy_count: 5.0 / 15.0,
+            transfer_count: 0.0,
+        }]
+    );
+}
+
 #[test]
 fn should_return_deploy_dependencies() {
     let mut rng = crate::new_rng();
     let creation_time = Timestamp::from(100);
     let ttl = TimeDiff::from(Duration::from_millis(100));
     let block_time = Timestamp::from(120);
-    let era1 = EraId::from(1);
-    let pub_key = PublicKey::from(&SecretKey::random_secp256k1(&mut rng));
 
     let deploy1 = generate_deploy(
         &mut rng,
@@ -722,14 +818,67 @@ fn should_return_deploy_dependencies() {
         vec![],
         true,
     );
-    let deploys: Vec<DeployOrTransferHash> = block.deploys_and_transfers_iter().collect();
-    // only deploy1 should be returned, as it has no dependencies
-    assert_eq!(deploys.len(), 1);
-    assert!(deploys.contains(&deploy1.deploy_or_transfer_hash()));
+    // both deploys should be returned, with deploy2 ordered after its dependency
+    assert_eq!(
+        vec![deploy1.id(), deploy2.id()],
+        block.deploy_hashes().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn should_hold_deploy_until_dependency_is_executed() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+    let block_time = Timestamp::from(120);
+    let era1 = EraId::from(1);
+    let pub_key = PublicKey::from(&SecretKey::random_secp256k1(&mut rng));
+
+    let deploy1 = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let deploy2 = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![*deploy1.id()],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+
+    let mut proposer = create_test_proposer(0.into());
+    proposer.add_deploy(
+        creation_time,
+        deploy2.deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        deploy2.deploy_info().unwrap(),
+    );
 
-    // the deploy will be included in block 1
-    let finalized_block = FinalizedBlock::new((*block).clone(), None, block_time, era1, 1, pub_key);
+    // deploy1 is unknown, so deploy2's dependency can't be resolved
+    assert_eq!(
+        Some(vec![*deploy1.id()]),
+        proposer.unresolvable_dependencies(deploy2.id())
+    );
+    assert_eq!(None, proposer.unresolvable_dependencies(deploy1.id()));
+
+    // deploy1 gets finalized in block 1, but is not executed yet
+    let block = BlockPayload::new(
+        vec![DeployWithApprovals::from(&deploy1)],
+        vec![],
+        vec![],
+        false,
+    );
+    let finalized_block = FinalizedBlock::new(block, None, block_time, era1, 1, pub_key);
     proposer.handle_finalized_block(&finalized_block);
+    assert_eq!(
+        Some(vec![]),
+        proposer.unresolvable_dependencies(deploy2.id())
+    );
 
     let block = proposer.propose_block_payload(
         DeployConfig::default(),
@@ -737,9 +886,32 @@ fn should_return_deploy_dependencies() {
         vec![],
         true,
     );
-    // `blocks` contains a block that contains deploy1 now, so we should get deploy2
-    assert_eq!(block.deploys().len(), 1);
-    assert!(block.deploy_hashes().any(|hash| hash == deploy2.id()));
+    assert!(block.deploys().is_empty());
+
+    // once block 1 is executed, deploy2 can be proposed
+    let executed_block = Block::new(
+        BlockHash::new(Digest::hash([1u8; 32])),
+        Digest::hash([2u8; 32]),
+        Digest::hash([3u8; 32]),
+        finalized_block,
+        None,
+        ProtocolVersion::V1_0_0,
+    )
+    .unwrap();
+    proposer
+        .sets
+        .add_executed_block(&executed_block, TimeDiff::from(Duration::from_secs(60)));
+
+    let block = proposer.propose_block_payload(
+        DeployConfig::default(),
+        BlockContext::new(block_time, vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(
+        vec![deploy2.id()],
+        block.deploy_hashes().collect::<Vec<_>>()
+    );
 }
 
 #[test]
@@ -780,3 +952,767 @@ fn should_respect_deploy_delay() {
     );
     assert_eq!(vec![deploy.id()], block.deploy_hashes().collect::<Vec<_>>());
 }
+
+#[test]
+fn should_return_orphaned_deploys_without_deploy_delay() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(0);
+    let ttl = TimeDiff::from(10000);
+    let deploy_config = DeployConfig::default();
+    let pending = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let missing = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let mut proposer = create_test_proposer(10.into()); // Deploy delay: 10 milliseconds
+    proposer.add_deploy(
+        100.into(),
+        pending.deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        pending.deploy_info().unwrap(),
+    );
+
+    // The pending deploy is made eligible immediately, the other one must be loaded from storage.
+    let to_load = proposer.return_orphaned_deploys(vec![*pending.id(), *missing.id()], 100.into());
+    assert_eq!(vec![*missing.id()], to_load);
+    assert!(proposer.state_dirty);
+    let block = proposer.propose_block_payload(
+        deploy_config,
+        BlockContext::new(100.into(), vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(
+        vec![pending.id()],
+        block.deploy_hashes().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn should_hold_future_dated_deploy_until_its_timestamp() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(1000);
+    let ttl = TimeDiff::from(10000);
+    let deploy_config = DeployConfig::default();
+    let deploy = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let mut proposer = create_test_proposer(0.into());
+
+    // The deploy is received at time 100, but is dated 1000: it is held until then.
+    proposer.add_deploy(
+        100.into(),
+        deploy.deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        deploy.deploy_info().unwrap(),
+    );
+    let block = proposer.propose_block_payload(
+        deploy_config,
+        BlockContext::new(999.into(), vec![]),
+        vec![],
+        true,
+    );
+    assert!(block.deploys().is_empty());
+    assert!(proposer.sets.is_pending(deploy.id()));
+    let block = proposer.propose_block_payload(
+        deploy_config,
+        BlockContext::new(1000.into(), vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(vec![deploy.id()], block.deploy_hashes().collect::<Vec<_>>());
+}
+
+fn proposed_with_ordering(deploy_ordering: DeployOrdering) -> (Vec<DeployHash>, Vec<Deploy>) {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(0);
+    let ttl = TimeDiff::from(10000);
+    let mut deploy_config = DeployConfig::default();
+    deploy_config.block_max_deploy_count = 2;
+
+    let mut proposer = create_test_proposer(0.into());
+    proposer.local_config.deploy_ordering = deploy_ordering;
+
+    // Deploys arrive in order with gas prices 1, 3 and 2, but only two fit in the block.
+    let deploys: Vec<_> = [1, 3, 2]
+        .iter()
+        .enumerate()
+        .map(|(index, gas_price)| {
+            let deploy = generate_deploy(
+                &mut rng,
+                creation_time,
+                ttl,
+                vec![],
+                default_gas_payment(),
+                *gas_price,
+            );
+            proposer.add_deploy(
+                (index as u64 + 1).into(),
+                deploy.deploy_or_transfer_hash(),
+                BTreeSet::new(),
+                deploy.deploy_info().unwrap(),
+            );
+            deploy
+        })
+        .collect();
+
+    let block = proposer.propose_block_payload(
+        deploy_config,
+        BlockContext::new(100.into(), vec![]),
+        vec![],
+        true,
+    );
+    let proposed = block.deploy_hashes().copied().collect();
+    (proposed, deploys)
+}
+
+#[test]
+fn should_prefer_deploys_with_higher_gas_price() {
+    let (proposed, deploys) = proposed_with_ordering(DeployOrdering::GasPrice);
+    assert_eq!(vec![*deploys[1].id(), *deploys[2].id()], proposed);
+}
+
+#[test]
+fn should_propose_deploys_in_arrival_order_with_fifo_ordering() {
+    let (proposed, deploys) = proposed_with_ordering(DeployOrdering::Fifo);
+    assert_eq!(vec![*deploys[0].id(), *deploys[1].id()], proposed);
+}
+
+#[test]
+fn should_mark_state_dirty_when_pending_deploys_change() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(10000);
+    let deploy = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let mut proposer = create_test_proposer(0.into());
+    assert!(!proposer.state_dirty);
+
+    proposer.add_deploy(
+        creation_time,
+        deploy.deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        deploy.deploy_info().unwrap(),
+    );
+    assert!(proposer.state_dirty);
+
+    // Finalizing the deploy removes it from the pending set, which needs to be persisted as well.
+    proposer.state_dirty = false;
+    let block = BlockPayload::new(
+        vec![DeployWithApprovals::from(&deploy)],
+        vec![],
+        vec![],
+        false,
+    );
+    let finalized_block = FinalizedBlock::new(
+        block,
+        None,
+        creation_time,
+        EraId::from(0),
+        0,
+        PublicKey::System,
+    );
+    proposer.handle_finalized_block(&finalized_block);
+    assert!(proposer.state_dirty);
+    assert!(proposer.sets.pending_deploys.is_empty());
+}
+
+#[test]
+fn should_respect_pending_deploy_quota_per_account() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(10000);
+    let mut proposer = create_test_proposer(0.into());
+    proposer.local_config.max_pending_deploys_per_account = 2;
+
+    // `generate_deploy` uses a new random account for each deploy, so sign these with a shared key.
+    let secret_key = SecretKey::random(&mut rng);
+    let deploys: Vec<_> = (0..3)
+        .map(|index| {
+            Deploy::new(
+                creation_time,
+                ttl,
+                DEFAULT_TEST_GAS_PRICE + index,
+                vec![],
+                "chain".to_string(),
+                ExecutableDeployItem::ModuleBytes {
+                    module_bytes: Bytes::new(),
+                    args: runtime_args! { ARG_AMOUNT => default_gas_payment().value() },
+                },
+                ExecutableDeployItem::ModuleBytes {
+                    module_bytes: Bytes::new(),
+                    args: RuntimeArgs::new(),
+                },
+                &secret_key,
+                None,
+            )
+        })
+        .collect();
+    let account = PublicKey::from(&secret_key);
+
+    for deploy in &deploys {
+        proposer.add_deploy(
+            creation_time,
+            deploy.deploy_or_transfer_hash(),
+            BTreeSet::new(),
+            deploy.deploy_info().unwrap(),
+        );
+    }
+    assert_eq!(proposer.sets.pending_deploys.len(), 2);
+    assert!(!proposer.sets.pending_deploys.contains_key(deploys[2].id()));
+    assert!(!proposer.within_pending_deploy_quota(&account));
+
+    // Another account is unaffected.
+    let other_deploy = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    proposer.add_deploy(
+        creation_time,
+        other_deploy.deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        other_deploy.deploy_info().unwrap(),
+    );
+    assert_eq!(proposer.sets.pending_deploys.len(), 3);
+}
+
+#[test]
+fn should_not_count_deploys_not_signed_by_the_account_towards_its_quota() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(10000);
+    let mut proposer = create_test_proposer(0.into());
+    proposer.local_config.max_pending_deploys_per_account = 1;
+
+    let account_secret_key = SecretKey::random(&mut rng);
+    let account = PublicKey::from(&account_secret_key);
+    let other_secret_key = SecretKey::random(&mut rng);
+    let deploys: Vec<_> = [&other_secret_key, &account_secret_key]
+        .iter()
+        .map(|secret_key| {
+            Deploy::new(
+                creation_time,
+                ttl,
+                DEFAULT_TEST_GAS_PRICE,
+                vec![],
+                "chain".to_string(),
+                ExecutableDeployItem::ModuleBytes {
+                    module_bytes: Bytes::new(),
+                    args: runtime_args! { ARG_AMOUNT => default_gas_payment().value() },
+                },
+                ExecutableDeployItem::ModuleBytes {
+                    module_bytes: Bytes::new(),
+                    args: RuntimeArgs::new(),
+                },
+                secret_key,
+                Some(account.clone()),
+            )
+        })
+        .collect();
+
+    // A deploy on behalf of the account, but signed by another key, doesn't use up its quota.
+    for deploy in &deploys {
+        proposer.add_deploy(
+            creation_time,
+            deploy.deploy_or_transfer_hash(),
+            deploy.approvals().clone(),
+            deploy.deploy_info().unwrap(),
+        );
+    }
+    assert_eq!(proposer.sets.pending_deploys.len(), 2);
+    assert_eq!(proposer.sets.pending_count_for_account(&account), 1);
+    assert!(!proposer.within_pending_deploy_quota(&account));
+
+    proposer.sets.remove_pending(deploys[0].id(), false);
+    assert_eq!(proposer.sets.pending_count_for_account(&account), 1);
+}
+
+#[test]
+fn should_replace_pending_deploy_with_higher_gas_price() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(10000);
+    let mut proposer = create_test_proposer(0.into());
+    let secret_key = SecretKey::random(&mut rng);
+    let account = PublicKey::from(&secret_key);
+    // Deploys with the same replacement tag may replace each other, whatever their session code.
+    let add_deploy_with_gas_price = |proposer: &mut BlockProposerReady, gas_price| {
+        let deploy = generate_deploy_signed_by(
+            &secret_key,
+            creation_time,
+            ttl,
+            gas_price,
+            runtime_args! {
+                ARG_AMOUNT => default_gas_payment().value(),
+                ARG_REPLACEMENT_TAG => 7u64
+            },
+            runtime_args! { "gas_price" => gas_price },
+        );
+        proposer.add_deploy(
+            creation_time,
+            deploy.deploy_or_transfer_hash(),
+            BTreeSet::new(),
+            deploy.deploy_info().unwrap(),
+        );
+        *deploy.id()
+    };
+
+    let original = add_deploy_with_gas_price(&mut proposer, 2);
+
+    // A deploy with the same replacement tag not offering a higher price is rejected.
+    let underpriced = add_deploy_with_gas_price(&mut proposer, 1);
+    assert!(!proposer.sets.pending_deploys.contains_key(&underpriced));
+    assert!(proposer.sets.pending_deploys.contains_key(&original));
+
+    // A deploy offering a higher price replaces the original one.
+    let replacement = add_deploy_with_gas_price(&mut proposer, 3);
+    assert_eq!(
+        vec![replacement],
+        proposer
+            .sets
+            .pending_deploys
+            .keys()
+            .copied()
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(proposer.sets.pending_count_for_account(&account), 1);
+
+    // If the original deploy gets finalized regardless, its replacement must not be proposed.
+    let block = BlockPayload::new(
+        vec![DeployWithApprovals::new(original, BTreeSet::new())],
+        vec![],
+        vec![],
+        false,
+    );
+    let finalized_block = FinalizedBlock::new(
+        block,
+        None,
+        creation_time,
+        EraId::from(0),
+        0,
+        PublicKey::System,
+    );
+    proposer.handle_finalized_block(&finalized_block);
+    assert!(proposer.sets.pending_deploys.is_empty());
+    assert_eq!(proposer.sets.pending_count_for_account(&account), 0);
+    assert_eq!(
+        proposer.observations.evictions,
+        vec![
+            EvictionReason::Replaced,
+            EvictionReason::ReplacedDeployFinalized
+        ]
+    );
+}
+
+#[test]
+fn should_not_replace_pending_deploy_with_one_not_signed_by_the_account() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(10000);
+    let mut proposer = create_test_proposer(0.into());
+    let account_secret_key = SecretKey::random(&mut rng);
+    let account = PublicKey::from(&account_secret_key);
+    let other_secret_key = SecretKey::random(&mut rng);
+    let add_deploy_signed_by =
+        |proposer: &mut BlockProposerReady, secret_key: &SecretKey, gas_price| {
+            let payment = ExecutableDeployItem::ModuleBytes {
+                module_bytes: Bytes::new(),
+                args: runtime_args! {
+                    ARG_AMOUNT => default_gas_payment().value(),
+                    ARG_REPLACEMENT_TAG => 7u64
+                },
+            };
+            let session = ExecutableDeployItem::ModuleBytes {
+                module_bytes: Bytes::new(),
+                args: runtime_args! { "gas_price" => gas_price },
+            };
+            let deploy = Deploy::new(
+                creation_time,
+                ttl,
+                gas_price,
+                vec![],
+                "chain".to_string(),
+                payment,
+                session,
+                secret_key,
+                Some(account.clone()),
+            );
+            proposer.add_deploy(
+                creation_time,
+                deploy.deploy_or_transfer_hash(),
+                deploy.approvals().clone(),
+                deploy.deploy_info().unwrap(),
+            );
+            *deploy.id()
+        };
+
+    // A deploy on behalf of the account, but signed by another key, neither replaces the
+    // account's own deploy, nor blocks the account's deploys with the same replacement tag.
+    let original = add_deploy_signed_by(&mut proposer, &account_secret_key, 2);
+    let unauthorized = add_deploy_signed_by(&mut proposer, &other_secret_key, 3);
+    assert!(proposer.sets.pending_deploys.contains_key(&original));
+    assert!(proposer.sets.pending_deploys.contains_key(&unauthorized));
+    assert!(proposer.sets.replaced_deploys.is_empty());
+
+    let replacement = add_deploy_signed_by(&mut proposer, &account_secret_key, 4);
+    assert!(!proposer.sets.pending_deploys.contains_key(&original));
+    assert!(proposer.sets.pending_deploys.contains_key(&unauthorized));
+    assert!(proposer.sets.pending_deploys.contains_key(&replacement));
+    assert_eq!(proposer.sets.pending_count_for_account(&account), 2);
+}
+
+#[test]
+fn should_replace_pending_deploy_with_same_session_and_higher_payment() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(10000);
+    let mut proposer = create_test_proposer(0.into());
+    let secret_key = SecretKey::random(&mut rng);
+    let account = PublicKey::from(&secret_key);
+    // Untagged deploys with identical session code only differ in their price.
+    let add_deploy = |proposer: &mut BlockProposerReady, gas_price, payment_amount: u64| {
+        let deploy = generate_deploy_signed_by(
+            &secret_key,
+            creation_time,
+            ttl,
+            gas_price,
+            runtime_args! { ARG_AMOUNT => U512::from(payment_amount) },
+            RuntimeArgs::new(),
+        );
+        proposer.add_deploy(
+            creation_time,
+            deploy.deploy_or_transfer_hash(),
+            BTreeSet::new(),
+            deploy.deploy_info().unwrap(),
+        );
+        *deploy.id()
+    };
+
+    let original = add_deploy(&mut proposer, 2, 10);
+
+    // A resubmission with the same payment amount is taken as running the session code again.
+    let resubmission = add_deploy(&mut proposer, 3, 10);
+    assert!(proposer.sets.pending_deploys.contains_key(&original));
+    assert!(proposer.sets.pending_deploys.contains_key(&resubmission));
+
+    // So is one paying more at a lower gas price.
+    let cheaper = add_deploy(&mut proposer, 1, 20);
+    assert!(proposer.sets.pending_deploys.contains_key(&cheaper));
+    assert_eq!(proposer.sets.pending_count_for_account(&account), 3);
+
+    // A deploy paying more at no lower gas price replaces the latest of them.
+    let replacement = add_deploy(&mut proposer, 3, 30);
+    assert!(proposer.sets.pending_deploys.contains_key(&replacement));
+    assert!(!proposer.sets.pending_deploys.contains_key(&cheaper));
+    assert_eq!(proposer.sets.pending_count_for_account(&account), 3);
+    assert_eq!(proposer.sets.replaced_deploys.len(), 1);
+    assert_eq!(
+        proposer.observations.evictions,
+        vec![EvictionReason::Replaced]
+    );
+}
+
+#[test]
+fn should_propose_deploys_in_sequence_order_with_strict_account_ordering() {
+    let mut rng = crate::new_rng();
+    let mut deploy_config = DeployConfig::default();
+    deploy_config.strict_account_ordering = true;
+    let mut proposer = create_test_proposer(0.into());
+    proposer.deploy_config = deploy_config;
+    proposer.local_config.deploy_ordering = DeployOrdering::GasPrice;
+    let secret_key = SecretKey::random(&mut rng);
+
+    // Higher sequence numbers offer higher gas prices, but must not be proposed first.
+    let deploys: Vec<_> = (1..=3)
+        .map(|sequence_number| {
+            let deploy = generate_sequenced_deploy(&secret_key, sequence_number, sequence_number);
+            proposer.add_deploy(
+                1.into(),
+                deploy.deploy_or_transfer_hash(),
+                BTreeSet::new(),
+                deploy.deploy_info().unwrap(),
+            );
+            deploy
+        })
+        .collect();
+
+    deploy_config.block_max_deploy_count = 1;
+    let block = proposer.propose_block_payload(
+        deploy_config,
+        BlockContext::new(100.into(), vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(
+        vec![deploys[0].id()],
+        block.deploy_hashes().collect::<Vec<_>>()
+    );
+
+    deploy_config.block_max_deploy_count = 10;
+    let block = proposer.propose_block_payload(
+        deploy_config,
+        BlockContext::new(100.into(), vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(
+        deploys.iter().map(Deploy::id).collect::<Vec<_>>(),
+        block.deploy_hashes().collect::<Vec<_>>()
+    );
+
+    // Once a deploy is finalized, another one reusing its sequence number is rejected.
+    let block = BlockPayload::new(
+        vec![DeployWithApprovals::from(&deploys[0])],
+        vec![],
+        vec![],
+        false,
+    );
+    let finalized_block = FinalizedBlock::new(
+        block,
+        None,
+        100.into(),
+        EraId::from(0),
+        0,
+        PublicKey::System,
+    );
+    proposer.handle_finalized_block(&finalized_block);
+    let duplicate = generate_sequenced_deploy(&secret_key, 1, 5);
+    proposer.add_deploy(
+        101.into(),
+        duplicate.deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        duplicate.deploy_info().unwrap(),
+    );
+    assert!(!proposer.sets.is_pending(duplicate.id()));
+
+    // The finalized sequence numbers survive a restart.
+    let (sets, _) = BlockProposerDeploySets::new(
+        vec![],
+        proposer.sets.next_finalized,
+        CachedState::from(&proposer.sets),
+        deploy_config.max_ttl,
+    );
+    proposer.sets = sets;
+    proposer.add_deploy(
+        101.into(),
+        duplicate.deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        duplicate.deploy_info().unwrap(),
+    );
+    assert!(!proposer.sets.is_pending(duplicate.id()));
+}
+
+#[test]
+fn should_not_advance_sequence_for_deploy_not_signed_by_the_account() {
+    let mut rng = crate::new_rng();
+    let mut proposer = create_test_proposer(0.into());
+    proposer.deploy_config.strict_account_ordering = true;
+    let secret_key = SecretKey::random(&mut rng);
+    let account = PublicKey::from(&secret_key);
+
+    // A deploy on behalf of the account with a high sequence number, signed by another key.
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: runtime_args! {
+            ARG_AMOUNT => default_gas_payment().value(),
+            ARG_SEQUENCE_NUMBER => 100u64
+        },
+    };
+    let session = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: RuntimeArgs::new(),
+    };
+    let unauthorized = Deploy::new(
+        Timestamp::from(0),
+        TimeDiff::from(10000),
+        DEFAULT_TEST_GAS_PRICE,
+        vec![],
+        "chain".to_string(),
+        payment,
+        session,
+        &SecretKey::random(&mut rng),
+        Some(account),
+    );
+    proposer.add_deploy(
+        1.into(),
+        unauthorized.deploy_or_transfer_hash(),
+        unauthorized.approvals().clone(),
+        unauthorized.deploy_info().unwrap(),
+    );
+    let block = BlockPayload::new(
+        vec![DeployWithApprovals::from(&unauthorized)],
+        vec![],
+        vec![],
+        false,
+    );
+    let finalized_block = FinalizedBlock::new(
+        block,
+        None,
+        100.into(),
+        EraId::from(0),
+        0,
+        PublicKey::System,
+    );
+    proposer.handle_finalized_block(&finalized_block);
+    assert!(proposer.sets.finalized_sequence_numbers.is_empty());
+
+    // So the account's own deploys are still accepted.
+    let deploy = generate_sequenced_deploy(&secret_key, 1, DEFAULT_TEST_GAS_PRICE);
+    proposer.add_deploy(
+        101.into(),
+        deploy.deploy_or_transfer_hash(),
+        deploy.approvals().clone(),
+        deploy.deploy_info().unwrap(),
+    );
+    assert!(proposer.sets.is_pending(deploy.id()));
+}
+
+#[test]
+fn should_respect_separate_gas_limits_for_transfers_and_deploys() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+    let mut proposer = create_test_proposer(0.into());
+    proposer.wasmless_transfer_cost = 10;
+    let mut deploy_config = proposer.deploy_config;
+    deploy_config.block_gas_limit = 3;
+    deploy_config.block_max_transfer_gas = 30;
+
+    for _ in 0..5 {
+        let transfer =
+            generate_transfer(&mut rng, creation_time, ttl, vec![], default_gas_payment());
+        proposer.add_deploy(
+            creation_time,
+            transfer.deploy_or_transfer_hash(),
+            BTreeSet::new(),
+            transfer.deploy_info().unwrap(),
+        );
+    }
+    for _ in 0..5 {
+        let deploy = generate_deploy(
+            &mut rng,
+            creation_time,
+            ttl,
+            vec![],
+            default_gas_payment(),
+            DEFAULT_TEST_GAS_PRICE,
+        );
+        proposer.add_deploy(
+            creation_time,
+            deploy.deploy_or_transfer_hash(),
+            BTreeSet::new(),
+            deploy.deploy_info().unwrap(),
+        );
+    }
+
+    // Three transfers exhaust the transfer gas limit, but don't use up any of the deploys' gas.
+    let block = proposer.propose_block_payload(
+        deploy_config,
+        BlockContext::new(120.into(), vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(block.transfer_hashes().count(), 3);
+    assert_eq!(block.deploy_hashes().count(), 3);
+}
+
+#[test]
+fn should_drop_and_reject_blocked_deploys() {
+    let mut rng = crate::new_rng();
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(10000);
+    let mut proposer = create_test_proposer(0.into());
+
+    let deploys: Vec<_> = (0..3)
+        .map(|_| {
+            generate_deploy(
+                &mut rng,
+                creation_time,
+                ttl,
+                vec![],
+                default_gas_payment(),
+                DEFAULT_TEST_GAS_PRICE,
+            )
+        })
+        .collect();
+    for deploy in &deploys[..2] {
+        proposer.add_deploy(
+            creation_time,
+            deploy.deploy_or_transfer_hash(),
+            BTreeSet::new(),
+            deploy.deploy_info().unwrap(),
+        );
+    }
+    assert_eq!(proposer.sets.pending_deploys.len(), 2);
+
+    // Blocking a pending deploy drops it.
+    assert!(proposer
+        .blocklist
+        .apply(DeployBlocklistUpdate::BlockDeploy(*deploys[0].id())));
+    proposer.remove_blocked();
+    assert_eq!(proposer.sets.pending_deploys.len(), 1);
+    assert!(!proposer.sets.pending_deploys.contains_key(deploys[0].id()));
+    assert_eq!(
+        proposer.observations.evictions,
+        vec![EvictionReason::Blocked]
+    );
+
+    // Blocking an account drops its pending deploys and rejects new ones.
+    let account_hash = deploys[1].header().account().to_account_hash();
+    assert!(proposer
+        .blocklist
+        .apply(DeployBlocklistUpdate::BlockAccount(account_hash)));
+    proposer.remove_blocked();
+    assert!(proposer.sets.pending_deploys.is_empty());
+    proposer.add_deploy(
+        creation_time,
+        deploys[1].deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        deploys[1].deploy_info().unwrap(),
+    );
+    assert!(proposer.sets.pending_deploys.is_empty());
+
+    // Other deploys are unaffected, and unblocked deploys are accepted again.
+    proposer.add_deploy(
+        creation_time,
+        deploys[2].deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        deploys[2].deploy_info().unwrap(),
+    );
+    assert!(proposer
+        .blocklist
+        .apply(DeployBlocklistUpdate::UnblockDeploy(*deploys[0].id())));
+    proposer.add_deploy(
+        creation_time,
+        deploys[0].deploy_or_transfer_hash(),
+        BTreeSet::new(),
+        deploys[0].deploy_info().unwrap(),
+    );
+    assert_eq!(proposer.sets.pending_deploys.len(), 2);
+}
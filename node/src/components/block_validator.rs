@@ -25,7 +25,7 @@ use itertools::Itertools;
 use smallvec::{smallvec, SmallVec};
 use tracing::{info, warn};
 
-use casper_types::Timestamp;
+use casper_types::{Gas, Timestamp};
 
 use crate::{
     components::{
@@ -293,8 +293,14 @@ where
                         ));
                         let block_timestamp = entry.key().timestamp();
                         let deploy_config = self.chainspec.deploy_config;
+                        let transfer_cost =
+                            Gas::from(self.chainspec.system_costs_config.wasmless_transfer_cost());
                         entry.insert(BlockValidationState {
-                            appendable_block: AppendableBlock::new(deploy_config, block_timestamp),
+                            appendable_block: AppendableBlock::new(
+                                deploy_config,
+                                transfer_cost,
+                                block_timestamp,
+                            ),
                             missing_deploys: block_deploys,
                             responders: smallvec![responder],
                             sources: VecDeque::new(), /* This is empty b/c we create the first
@@ -45,7 +45,7 @@ impl MockReactor {
     }
 
     async fn expect_block_validator_event(&self) -> Event {
-        let ((_ancestor, reactor_event), _) = self.scheduler.pop().await;
+        let ((_metadata, reactor_event), _) = self.scheduler.pop().await;
         if let ReactorEvent::BlockValidator(event) = reactor_event {
             event
         } else {
@@ -57,7 +57,7 @@ impl MockReactor {
     where
         T: Into<Option<Deploy>>,
     {
-        let ((_ancestor, reactor_event), _) = self.scheduler.pop().await;
+        let ((_metadata, reactor_event), _) = self.scheduler.pop().await;
         if let ReactorEvent::Fetcher(FetcherRequest {
             id,
             peer,
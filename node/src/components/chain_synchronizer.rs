@@ -4,8 +4,9 @@ mod event;
 mod metrics;
 mod operations;
 mod progress;
+mod trie_peers;
 
-use std::{convert::Infallible, fmt::Debug, marker::PhantomData, sync::Arc};
+use std::{convert::Infallible, fmt::Debug, marker::PhantomData, sync::Arc, time::Duration};
 
 use datasize::DataSize;
 use prometheus::Registry;
@@ -24,7 +25,7 @@ use crate::{
         },
         requests::{
             ChainspecLoaderRequest, ContractRuntimeRequest, FetcherRequest,
-            MarkBlockCompletedRequest, NetworkInfoRequest, NodeStateRequest,
+            MarkBlockCompletedRequest, NetworkInfoRequest, NodeStateRequest, StateStoreRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
@@ -42,8 +43,11 @@ pub(crate) use error::Error;
 pub(crate) use event::Event;
 pub(crate) use metrics::Metrics;
 pub(crate) use operations::KeyBlockInfo;
-pub(crate) use progress::Progress;
 use progress::ProgressHolder;
+pub(crate) use progress::{Progress, SyncStatus};
+
+/// How often the sync progress is announced while syncing.
+const SYNC_PROGRESS_REPORT_INTERVAL: Duration = Duration::from_secs(10);
 
 #[derive(DataSize, Debug)]
 pub(crate) enum JoiningOutcome {
@@ -79,6 +83,7 @@ where
     REv: From<StorageRequest>
         + From<NetworkInfoRequest>
         + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>
         + From<ChainspecLoaderRequest>
         + From<FetcherRequest<Block>>
         + From<FetcherRequest<BlockHeader>>
@@ -91,6 +96,7 @@ where
         + From<BlocklistAnnouncement>
         + From<ControlAnnouncement>
         + From<MarkBlockCompletedRequest>
+        + From<ChainSynchronizerAnnouncement>
         + Send,
 {
     /// Constructs a new `ChainSynchronizer` suitable for use in the joiner reactor to perform the
@@ -107,13 +113,14 @@ where
         let progress = ProgressHolder::new_fast_sync();
         let node_state = NodeState::Joining(progress.progress());
 
-        let effects = operations::run_fast_sync_task(
+        let mut effects = operations::run_fast_sync_task(
             effect_builder,
             config.clone(),
             metrics.clone(),
             progress.clone(),
         )
         .event(|result| Event::FastSyncResult(Box::new(result)));
+        effects.extend(schedule_sync_progress_report(effect_builder));
 
         let synchronizer = ChainSynchronizer {
             config,
@@ -178,6 +185,7 @@ where
         + From<FetcherRequest<BlockSignatures>>
         + From<FetcherRequest<BlockHeadersBatch>>
         + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>
         + From<BlocklistAnnouncement>
         + From<MarkBlockCompletedRequest>
         + From<ChainSynchronizerAnnouncement>
@@ -209,13 +217,14 @@ where
                 _phantom: PhantomData,
             };
 
-            let effects = operations::run_sync_to_genesis_task(
+            let mut effects = operations::run_sync_to_genesis_task(
                 effect_builder,
                 synchronizer.config.clone(),
                 synchronizer.metrics.clone(),
                 progress,
             )
             .event(|result| Event::SyncToGenesisResult(Box::new(result)));
+            effects.extend(schedule_sync_progress_report(effect_builder));
 
             return Ok((synchronizer, effects));
         }
@@ -239,6 +248,32 @@ where
     }
 }
 
+impl<REv> ChainSynchronizer<REv>
+where
+    REv: From<ChainSynchronizerAnnouncement> + Send,
+{
+    /// Announces the current sync progress and schedules the next report, unless syncing has
+    /// finished.
+    fn handle_report_sync_progress(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        if self.progress.progress().is_finished() {
+            return Effects::new();
+        }
+        let sync_status = self.progress.sync_status(self.config.min_round_length());
+        let mut effects = effect_builder.announce_sync_progress(sync_status).ignore();
+        effects.extend(schedule_sync_progress_report(effect_builder));
+        effects
+    }
+}
+
+fn schedule_sync_progress_report<REv: Send>(effect_builder: EffectBuilder<REv>) -> Effects<Event> {
+    effect_builder
+        .set_timeout(SYNC_PROGRESS_REPORT_INTERVAL)
+        .event(|_| Event::ReportSyncProgress)
+}
+
 impl<REv> ChainSynchronizer<REv> {
     fn handle_get_node_state_request(&mut self, request: NodeStateRequest) -> Effects<Event> {
         self.node_state = match self.node_state {
@@ -254,7 +289,17 @@ impl<REv> ChainSynchronizer<REv> {
             NodeState::Participating => NodeState::Participating,
         };
 
-        request.0.respond(self.node_state.clone()).ignore()
+        let maybe_sync_status = match self.node_state {
+            NodeState::Joining(_) | NodeState::ParticipatingAndSyncingToGenesis { .. } => {
+                Some(self.progress.sync_status(self.config.min_round_length()))
+            }
+            NodeState::Participating => None,
+        };
+
+        request
+            .0
+            .respond((self.node_state.clone(), maybe_sync_status))
+            .ignore()
     }
 }
 
@@ -263,6 +308,7 @@ where
     REv: From<StorageRequest>
         + From<NetworkInfoRequest>
         + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>
         + From<ChainspecLoaderRequest>
         + From<FetcherRequest<Block>>
         + From<FetcherRequest<BlockHeader>>
@@ -276,6 +322,7 @@ where
         + From<BlocklistAnnouncement>
         + From<ControlAnnouncement>
         + From<MarkBlockCompletedRequest>
+        + From<ChainSynchronizerAnnouncement>
         + Send,
 {
     type Event = Event;
@@ -308,6 +355,7 @@ where
             }
             Event::FastSyncResult(result) => self.handle_fast_sync_result(effect_builder, *result),
             Event::GetNodeState(request) => self.handle_get_node_state_request(request),
+            Event::ReportSyncProgress => self.handle_report_sync_progress(effect_builder),
         }
     }
 }
@@ -20,6 +20,16 @@ pub(super) struct Config {
     max_parallel_deploy_fetches: u32,
     /// Maximum number of trie nodes to fetch in parallel.
     max_parallel_trie_fetches: u32,
+    /// Maximum number of trie and trie chunk requests in flight to a single peer.
+    max_trie_fetches_per_peer: u32,
+    /// The maximum duration to wait for a peer to deliver a trie or trie chunk.
+    trie_fetch_timeout: Duration,
+    /// The maximum number of failed requests for a single trie or trie chunk before giving up.
+    max_trie_fetch_attempts: u32,
+    /// The score a peer loses each time it fails to deliver a trie or trie chunk in time.
+    trie_peer_failure_penalty: u32,
+    /// The score below which a peer is no longer asked for tries until all peers are exhausted.
+    trie_peer_min_score: i64,
     /// Maximum number of blocks to fetch in parallel.
     max_parallel_block_fetches: u32,
     /// The maximum number of retries of fetch operations during the chain synchronization process.
@@ -57,6 +67,11 @@ impl Config {
             trusted_hash: node_config.trusted_hash,
             max_parallel_deploy_fetches: node_config.max_parallel_deploy_fetches,
             max_parallel_trie_fetches: node_config.max_parallel_trie_fetches,
+            max_trie_fetches_per_peer: node_config.max_trie_fetches_per_peer,
+            trie_fetch_timeout: Duration::from_millis(node_config.trie_fetch_timeout.millis()),
+            max_trie_fetch_attempts: node_config.max_trie_fetch_attempts,
+            trie_peer_failure_penalty: node_config.trie_peer_failure_penalty,
+            trie_peer_min_score: node_config.trie_peer_min_score,
             max_parallel_block_fetches: node_config.max_parallel_block_fetches,
             max_sync_fetch_attempts: node_config.max_sync_fetch_attempts,
             retry_interval: Duration::from_millis(node_config.retry_interval.millis()),
@@ -94,6 +109,10 @@ impl Config {
         self.chainspec.highway_config.finality_threshold_fraction
     }
 
+    pub(super) fn is_signing_context_active(&self, era_id: EraId) -> bool {
+        self.chainspec.core_config.is_signing_context_active(era_id)
+    }
+
     pub(super) fn deploy_max_ttl(&self) -> TimeDiff {
         self.chainspec.deploy_config.max_ttl
     }
@@ -114,6 +133,26 @@ impl Config {
         self.max_parallel_trie_fetches as usize
     }
 
+    pub(super) fn max_trie_fetches_per_peer(&self) -> u32 {
+        self.max_trie_fetches_per_peer
+    }
+
+    pub(super) fn trie_fetch_timeout(&self) -> Duration {
+        self.trie_fetch_timeout
+    }
+
+    pub(super) fn max_trie_fetch_attempts(&self) -> usize {
+        self.max_trie_fetch_attempts as usize
+    }
+
+    pub(super) fn trie_peer_failure_penalty(&self) -> u32 {
+        self.trie_peer_failure_penalty
+    }
+
+    pub(super) fn trie_peer_min_score(&self) -> i64 {
+        self.trie_peer_min_score
+    }
+
     pub(super) fn max_parallel_block_fetches(&self) -> usize {
         self.max_parallel_block_fetches as usize
     }
@@ -16,6 +16,8 @@ pub(crate) enum Event {
     /// A request to provide the node state.
     #[from]
     GetNodeState(NodeStateRequest),
+    /// The timer for announcing the sync progress has fired.
+    ReportSyncProgress,
 }
 
 impl Display for Event {
@@ -28,6 +30,7 @@ impl Display for Event {
                 write!(formatter, "sync to genesis result: {:?}", result)
             }
             Event::GetNodeState(_) => write!(formatter, "get node state"),
+            Event::ReportSyncProgress => write!(formatter, "report sync progress"),
         }
     }
 }
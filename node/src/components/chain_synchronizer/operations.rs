@@ -1,10 +1,11 @@
 use std::{
+    borrow::Cow,
     cmp,
     collections::{BTreeMap, VecDeque},
     mem,
     sync::{
         atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
-        Arc, RwLock,
+        Arc, Mutex, MutexGuard, RwLock,
     },
 };
 
@@ -19,17 +20,18 @@ use prometheus::IntGauge;
 use quanta::Instant;
 use serde::Serialize;
 use thiserror::Error;
-use tokio::sync::Semaphore;
+use tokio::sync::{Notify, Semaphore};
 use tracing::{debug, error, info, trace, warn};
 
 use casper_execution_engine::storage::trie::{TrieOrChunk, TrieOrChunkId};
-use casper_hashing::Digest;
+use casper_hashing::{ChunkWithProof, Digest};
 use casper_types::{bytesrepr::Bytes, EraId, PublicKey, TimeDiff, Timestamp, U512};
 
 use crate::{
     components::{
         chain_synchronizer::{
             error::{Error, FetchBlockHeadersBatchError, FetchTrieError},
+            trie_peers::{Assignment, TriePeers},
             Config, Metrics, ProgressHolder,
         },
         contract_runtime::{BlockAndExecutionEffects, ExecutionPreState},
@@ -42,15 +44,16 @@ use crate::{
         },
         requests::{
             ContractRuntimeRequest, FetcherRequest, MarkBlockCompletedRequest, NetworkInfoRequest,
+            StateStoreRequest,
         },
         EffectBuilder,
     },
     storage::StorageRequest,
     types::{
         AvailableBlockRange, Block, BlockAndDeploys, BlockHash, BlockHeader,
-        BlockHeaderWithMetadata, BlockHeadersBatch, BlockHeadersBatchId, BlockSignatures,
-        BlockWithMetadata, Deploy, DeployHash, FinalizedApprovals, FinalizedApprovalsWithId,
-        FinalizedBlock, Item, NodeId,
+        BlockHeaderWithMetadata, BlockHeadersBatch, BlockHeadersBatchId, BlockHeight,
+        BlockSignatures, BlockWithMetadata, Deploy, DeployHash, FinalizedApprovals,
+        FinalizedApprovalsWithId, FinalizedBlock, Item, NodeId,
     },
     utils::work_queue::WorkQueue,
 };
@@ -100,6 +103,10 @@ where
     /// A range of blocks for which we already have all required data stored locally.
     locally_available_block_range_on_start: AvailableBlockRange,
     trie_fetch_limit: Semaphore,
+    /// The peers tries and trie chunks are fetched from.
+    trie_peers: Mutex<TriePeers>,
+    /// Notified whenever a trie fetch request to one of `trie_peers` finishes.
+    trie_request_finished: Notify,
 }
 
 impl<'a, REv> ChainSyncContext<'a, REv>
@@ -132,6 +139,12 @@ where
             filter_count: AtomicI64::new(0),
             locally_available_block_range_on_start,
             trie_fetch_limit: Semaphore::new(config.max_parallel_trie_fetches()),
+            trie_peers: Mutex::new(TriePeers::new(
+                config.max_trie_fetches_per_peer(),
+                config.trie_peer_failure_penalty(),
+                config.trie_peer_min_score(),
+            )),
+            trie_request_finished: Notify::new(),
         };
 
         // The config may contain the hash of a block that is known to be on the correct chain. We
@@ -155,7 +168,10 @@ where
                     config_header
                 } else {
                     if let Some(stored_header_at_same_height) = effect_builder
-                        .get_block_header_at_height_from_storage(config_header.height(), false)
+                        .get_block_header_at_height_from_storage(
+                            BlockHeight::new(config_header.height()),
+                            false,
+                        )
                         .await
                     {
                         if stored_header_at_same_height != config_header {
@@ -181,7 +197,7 @@ where
             return Err(Error::TrustedHeaderTooEarly {
                 trusted_header: Box::new(trusted_block_header),
                 current_protocol_version: config.protocol_version(),
-                activation_point: config.chainspec().protocol_config.activation_point.era_id(),
+                activation_point: config.chainspec().protocol_config.activation_era(),
             });
         }
 
@@ -221,10 +237,19 @@ where
             filter_count: AtomicI64::new(0),
             locally_available_block_range_on_start,
             trie_fetch_limit: Semaphore::new(config.max_parallel_trie_fetches()),
+            trie_peers: Mutex::new(TriePeers::new(
+                config.max_trie_fetches_per_peer(),
+                config.trie_peer_failure_penalty(),
+                config.trie_peer_min_score(),
+            )),
+            trie_request_finished: Notify::new(),
         };
 
         let trusted_block_header = match effect_builder
-            .get_block_header_at_height_from_storage(highest_available_block_height, true)
+            .get_block_header_at_height_from_storage(
+                BlockHeight::new(highest_available_block_height),
+                true,
+            )
             .await
         {
             Some(block_header) => block_header,
@@ -309,6 +334,11 @@ impl<'a, REv> ChainSyncContext<'a, REv> {
         }
     }
 
+    /// Locks and returns the peers tries and trie chunks are fetched from.
+    fn trie_peers(&self) -> MutexGuard<'_, TriePeers> {
+        self.trie_peers.lock().expect("trie peers lock poisoned")
+    }
+
     /// Clears the list of bad peers.
     fn redeem_all(&self) {
         let mut bad_peer_list = self
@@ -504,32 +534,220 @@ where
     None
 }
 
+/// A trie fetch request assigned to a peer by `TriePeers`.
+///
+/// If dropped before its outcome is recorded, e.g. because the fetch was cancelled, the request is
+/// released without affecting the peer's score.
+struct TriePeerRequest<'a, 'b, REv: 'static> {
+    ctx: &'a ChainSyncContext<'b, REv>,
+    peer: NodeId,
+    finished: bool,
+}
+
+impl<'a, 'b, REv: 'static> TriePeerRequest<'a, 'b, REv> {
+    fn new(ctx: &'a ChainSyncContext<'b, REv>, peer: NodeId) -> Self {
+        TriePeerRequest {
+            ctx,
+            peer,
+            finished: false,
+        }
+    }
+
+    fn completed(mut self) {
+        self.ctx.trie_peers().completed(self.peer);
+        self.finished = true;
+    }
+
+    fn failed(mut self) {
+        self.ctx.trie_peers().failed(self.peer);
+        self.finished = true;
+    }
+}
+
+impl<'a, 'b, REv: 'static> Drop for TriePeerRequest<'a, 'b, REv> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.ctx.trie_peers().cancelled(self.peer);
+        }
+        self.ctx.trie_request_finished.notify_waiters();
+    }
+}
+
+/// Fetches a trie or a trie chunk from the best available of the context's trie peers.
+///
+/// Each request is bounded by the configured trie fetch timeout.  Peers which fail to deliver in
+/// time are penalized and the item is requested from the next best peer, until the configured
+/// number of attempts is exhausted.  While every usable peer already has the maximum number of
+/// requests in flight, this waits for one of them to finish without using up an attempt.
+async fn fetch_trie_or_chunk<REv>(
+    ctx: &ChainSyncContext<'_, REv>,
+    id: TrieOrChunkId,
+) -> Result<FetchedData<TrieOrChunk>, FetchWithRetryError<TrieOrChunk>>
+where
+    REv: From<FetcherRequest<TrieOrChunk>> + From<NetworkInfoRequest>,
+{
+    let mut attempts = 0;
+    loop {
+        // Register interest before assigning, so a request finishing in between isn't missed.
+        let request_finished = ctx.trie_request_finished.notified();
+        let maybe_peer = ctx.trie_peers().assign();
+        match maybe_peer {
+            Assignment::Peer(peer) => {
+                let request = TriePeerRequest::new(ctx, peer);
+                let fetch = ctx.effect_builder.fetch::<TrieOrChunk>(id, peer);
+                match tokio::time::timeout(ctx.config.trie_fetch_timeout(), fetch).await {
+                    Ok(Ok(fetched_data)) => {
+                        request.completed();
+                        return Ok(fetched_data);
+                    }
+                    Ok(Err(error @ FetcherError::CouldNotConstructGetRequest { .. })) => {
+                        return Err(error.into())
+                    }
+                    Ok(Err(error)) => {
+                        warn!(%error, ?peer, "failed to fetch trie or chunk; trying another peer");
+                        request.failed();
+                    }
+                    Err(_) => {
+                        warn!(?id, ?peer, "trie fetch timed out; trying another peer");
+                        request.failed();
+                    }
+                }
+            }
+            Assignment::Busy => {
+                // Also check again after a retry interval, in case new peers have connected.
+                let _ = tokio::time::timeout(ctx.config.retry_interval(), request_finished).await;
+                continue;
+            }
+            Assignment::NoUsablePeers => {
+                let peers = get_peers(TrieOrChunk::can_use_syncing_nodes(), ctx).await;
+                let redeemed = {
+                    let mut trie_peers = ctx.trie_peers();
+                    trie_peers.update_peers(peers);
+                    trie_peers.redeem()
+                };
+                if redeemed > 0 {
+                    warn!(redeemed, "all trie peers exhausted; redeeming them");
+                }
+                if attempts % 100 == 0 {
+                    warn!(
+                        attempts,
+                        ?id,
+                        "failed to attempt to fetch trie or chunk due to no usable peers"
+                    );
+                }
+                tokio::time::sleep(ctx.config.retry_interval()).await;
+            }
+        }
+
+        attempts += 1;
+        if attempts >= ctx.config.max_trie_fetch_attempts() {
+            error!(attempts, ?id, "trie fetch attempts exhausted");
+            return Err(FetchWithRetryError::AttemptsExhausted {
+                id,
+                total_attempts: attempts,
+                attempts_after_bootstrapped: attempts,
+            });
+        }
+    }
+}
+
+/// Prefix of the state store keys under which verified chunks of a partially downloaded trie are
+/// kept, allowing the download to resume after a restart.
+const TRIE_CHUNK_STATE_KEY_PREFIX: &[u8] = b"chain_sync_trie_chunk";
+
+/// Returns the state store key of the chunk at `index` of the trie under `trie_key`.
+fn trie_chunk_state_key(trie_key: Digest, index: u64) -> Cow<'static, [u8]> {
+    let mut key = TRIE_CHUNK_STATE_KEY_PREFIX.to_vec();
+    key.extend_from_slice(trie_key.as_ref());
+    key.extend_from_slice(&index.to_le_bytes());
+    Cow::Owned(key)
+}
+
+/// Fetches a trie or a trie chunk, preferring a chunk persisted by an earlier, interrupted download.
+///
+/// Chunks fetched from peers are persisted until the whole trie has been stored, see
+/// [`delete_trie_chunks`].  Returns `None` if the trie is already present in the trie store.
+async fn fetch_resumable_trie_or_chunk<REv>(
+    ctx: &ChainSyncContext<'_, REv>,
+    id: TrieOrChunkId,
+) -> Result<Option<TrieOrChunk>, FetchTrieError>
+where
+    REv: From<FetcherRequest<TrieOrChunk>> + From<NetworkInfoRequest> + From<StateStoreRequest>,
+{
+    let TrieOrChunkId(index, trie_key) = id;
+    let state_key = trie_chunk_state_key(trie_key, index);
+    if let Some(chunk_with_proof) = ctx
+        .effect_builder
+        .load_state::<ChunkWithProof>(state_key.clone())
+        .await
+    {
+        let proof = chunk_with_proof.proof();
+        if proof.index() == index
+            && proof.root_hash() == trie_key
+            && chunk_with_proof.verify().is_ok()
+        {
+            trace!(?id, "using trie chunk persisted by an earlier download");
+            return Ok(Some(TrieOrChunk::ChunkWithProof(chunk_with_proof)));
+        }
+        warn!(?id, "discarding invalid persisted trie chunk");
+    }
+
+    match fetch_trie_or_chunk(ctx, id).await? {
+        FetchedData::FromStorage { .. } => Ok(None),
+        FetchedData::FromPeer { item, .. } => {
+            if let TrieOrChunk::ChunkWithProof(chunk_with_proof) = &*item {
+                ctx.effect_builder
+                    .save_state(state_key, chunk_with_proof)
+                    .await;
+            }
+            Ok(Some(*item))
+        }
+    }
+}
+
+/// Removes the persisted chunks of a trie once they are no longer needed.
+async fn delete_trie_chunks<REv>(ctx: &ChainSyncContext<'_, REv>, trie_key: Digest, count: u64)
+where
+    REv: From<StateStoreRequest>,
+{
+    for index in 0..count {
+        ctx.effect_builder
+            .delete_state(trie_chunk_state_key(trie_key, index))
+            .await;
+    }
+}
+
 enum TrieAlreadyPresentOrDownloaded {
     AlreadyPresent,
     Downloaded(Bytes),
+    /// The trie was assembled from `count` chunks, which stay persisted until it has been stored.
+    DownloadedInChunks {
+        trie_bytes: Bytes,
+        count: u64,
+    },
 }
 
+/// Fetches a trie, downloading it chunk by chunk if it is too large to be sent in one message.
+///
+/// Every chunk is verified against the trie key by its Merkle proof and persisted, so an
+/// interrupted download only needs to fetch the chunks which are still missing.
 async fn fetch_trie_with_retries<REv>(
     id: Digest,
     ctx: &ChainSyncContext<'_, REv>,
 ) -> Result<TrieAlreadyPresentOrDownloaded, FetchTrieError>
 where
-    REv: From<FetcherRequest<TrieOrChunk>> + From<NetworkInfoRequest>,
+    REv: From<FetcherRequest<TrieOrChunk>> + From<NetworkInfoRequest> + From<StateStoreRequest>,
 {
-    let trie_or_chunk =
-        match fetch_with_retries::<_, TrieOrChunk>(ctx, TrieOrChunkId(0, id)).await? {
-            FetchedData::FromStorage { .. } => {
-                return Ok(TrieAlreadyPresentOrDownloaded::AlreadyPresent)
-            }
-            FetchedData::FromPeer {
-                item: trie_or_chunk,
-                ..
-            } => *trie_or_chunk,
-        };
-
-    let chunk_with_proof = match trie_or_chunk {
-        TrieOrChunk::Trie(trie) => return Ok(TrieAlreadyPresentOrDownloaded::Downloaded(trie)),
-        TrieOrChunk::ChunkWithProof(chunk_with_proof) => chunk_with_proof,
+    // Pick up any newly connected peers.
+    let peers = get_peers(TrieOrChunk::can_use_syncing_nodes(), ctx).await;
+    ctx.trie_peers().update_peers(peers);
+
+    let chunk_with_proof = match fetch_resumable_trie_or_chunk(ctx, TrieOrChunkId(0, id)).await? {
+        None => return Ok(TrieAlreadyPresentOrDownloaded::AlreadyPresent),
+        Some(TrieOrChunk::Trie(trie)) => {
+            return Ok(TrieAlreadyPresentOrDownloaded::Downloaded(trie))
+        }
+        Some(TrieOrChunk::ChunkWithProof(chunk_with_proof)) => chunk_with_proof,
     };
 
     debug_assert!(
@@ -544,22 +762,18 @@ where
     // Build a map of the chunks.
     let chunk_map_result = futures::stream::iter(1..count)
         .map(|index| async move {
-            match fetch_with_retries::<_, TrieOrChunk>(ctx, TrieOrChunkId(index, id)).await? {
-                FetchedData::FromStorage { .. } => {
-                    Err(FetchTrieError::TrieBeingFetchByChunksSomehowFetchedFromStorage)
+            match fetch_resumable_trie_or_chunk(ctx, TrieOrChunkId(index, id)).await? {
+                None => Err(FetchTrieError::TrieBeingFetchByChunksSomehowFetchedFromStorage),
+                Some(TrieOrChunk::Trie(_)) => Err(
+                    FetchTrieError::TrieBeingFetchedByChunksSomehowFetchWholeFromPeer {
+                        digest: id,
+                    },
+                ),
+                Some(TrieOrChunk::ChunkWithProof(chunk_with_proof)) => {
+                    let index = chunk_with_proof.proof().index();
+                    let chunk = chunk_with_proof.into_chunk();
+                    Ok((index, chunk))
                 }
-                FetchedData::FromPeer { item, .. } => match *item {
-                    TrieOrChunk::Trie(_) => Err(
-                        FetchTrieError::TrieBeingFetchedByChunksSomehowFetchWholeFromPeer {
-                            digest: id,
-                        },
-                    ),
-                    TrieOrChunk::ChunkWithProof(chunk_with_proof) => {
-                        let index = chunk_with_proof.proof().index();
-                        let chunk = chunk_with_proof.into_chunk();
-                        Ok((index, chunk))
-                    }
-                },
             }
         })
         // Do not try to fetch all of the trie chunks at once; only fetch at most
@@ -575,6 +789,7 @@ where
         Ok(chunk_map) => chunk_map,
         Err(FetchTrieError::TrieBeingFetchByChunksSomehowFetchedFromStorage) => {
             // Trie must have been downloaded by a parallel process...
+            delete_trie_chunks(ctx, id, count).await;
             return Ok(TrieAlreadyPresentOrDownloaded::AlreadyPresent);
         }
         Err(error) => {
@@ -585,7 +800,7 @@ where
 
     // Concatenate all of the chunks into a trie
     let trie_bytes = chunk_map.into_values().flat_map(Vec::<u8>::from).collect();
-    Ok(TrieAlreadyPresentOrDownloaded::Downloaded(trie_bytes))
+    Ok(TrieAlreadyPresentOrDownloaded::DownloadedInChunks { trie_bytes, count })
 }
 
 /// Fetches and stores a block header from the network.
@@ -726,6 +941,11 @@ impl KeyBlockInfo {
         self.era_id
     }
 
+    /// Returns the time the era started, i.e. the timestamp of the key block.
+    pub(crate) fn era_start(&self) -> Timestamp {
+        self.era_start
+    }
+
     /// Returns the hash of the key block, i.e. the last block before `era_id`.
     pub(crate) fn block_hash(&self) -> &BlockHash {
         &self.key_block_hash
@@ -945,7 +1165,10 @@ where
                     Ok(_) => (),
                 }
 
-                if let Err(error) = item.block_signatures().verify() {
+                let signing_context_active = ctx
+                    .config
+                    .is_signing_context_active(item.block_signatures().era_id);
+                if let Err(error) = item.block_signatures().verify(signing_context_active) {
                     warn!(
                         ?error,
                         ?peer,
@@ -1005,8 +1228,10 @@ async fn fetch_and_store_trie<REv>(
     ctx: &ChainSyncContext<'_, REv>,
 ) -> Result<Vec<Digest>, Error>
 where
-    REv:
-        From<FetcherRequest<TrieOrChunk>> + From<NetworkInfoRequest> + From<ContractRuntimeRequest>,
+    REv: From<FetcherRequest<TrieOrChunk>>
+        + From<NetworkInfoRequest>
+        + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>,
 {
     let fetched_trie = fetch_trie_with_retries(trie_key, ctx).await?;
     match fetched_trie {
@@ -1018,6 +1243,14 @@ where
             .effect_builder
             .put_trie_and_find_missing_descendant_trie_keys(trie_bytes)
             .await?),
+        TrieAlreadyPresentOrDownloaded::DownloadedInChunks { trie_bytes, count } => {
+            let missing_descendants = ctx
+                .effect_builder
+                .put_trie_and_find_missing_descendant_trie_keys(trie_bytes)
+                .await?;
+            delete_trie_chunks(ctx, trie_key, count).await;
+            Ok(missing_descendants)
+        }
     }
 }
 
@@ -1076,8 +1309,10 @@ async fn sync_trie_store_worker<REv>(
     ctx: &ChainSyncContext<'_, REv>,
 ) -> Result<(), Error>
 where
-    REv:
-        From<FetcherRequest<TrieOrChunk>> + From<NetworkInfoRequest> + From<ContractRuntimeRequest>,
+    REv: From<FetcherRequest<TrieOrChunk>>
+        + From<NetworkInfoRequest>
+        + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>,
 {
     while let Some(job) = queue.next_job().await {
         let permit = match ctx.trie_fetch_limit.acquire().await {
@@ -1098,6 +1333,7 @@ where
                 err
             })?;
         trace!(?child_jobs, trie_key = %job.inner(), "downloaded trie node");
+        ctx.progress.record_trie_fetched();
         if abort.load(Ordering::Relaxed) {
             return Ok(()); // Another task failed and sent an error.
         }
@@ -1118,8 +1354,10 @@ async fn sync_trie_store<REv>(
     ctx: &ChainSyncContext<'_, REv>,
 ) -> Result<(), Error>
 where
-    REv:
-        From<FetcherRequest<TrieOrChunk>> + From<NetworkInfoRequest> + From<ContractRuntimeRequest>,
+    REv: From<FetcherRequest<TrieOrChunk>>
+        + From<NetworkInfoRequest>
+        + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>,
 {
     let block_height = block_header.height();
     debug_assert!(ctx.progress.is_fetching_tries(block_height));
@@ -1202,6 +1440,7 @@ where
     REv: From<FetcherRequest<TrieOrChunk>>
         + From<NetworkInfoRequest>
         + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>
         + From<StorageRequest>
         + From<BlocklistAnnouncement>
         + From<FetcherRequest<Block>>
@@ -1315,6 +1554,8 @@ where
 
         if let Some(higher_block_header_with_metadata) = maybe_fetched_block {
             highest_synced_block_header = higher_block_header_with_metadata.block_header;
+            ctx.progress
+                .record_synced_block(&highest_synced_block_header);
 
             // If the new block is a switch block, update the validator weights, etc...
             if let Some(key_block_info) =
@@ -1416,6 +1657,7 @@ where
         + From<FetcherRequest<BlockSignatures>>
         + From<FetcherRequest<BlockHeadersBatch>>
         + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>
         + From<BlocklistAnnouncement>
         + From<MarkBlockCompletedRequest>
         + From<ChainSynchronizerAnnouncement>
@@ -1538,6 +1780,7 @@ where
         + From<FetcherRequest<BlockSignatures>>
         + From<NetworkInfoRequest>
         + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>
         + From<BlocklistAnnouncement>
         + From<MarkBlockCompletedRequest>
         + Send,
@@ -1556,6 +1799,8 @@ where
     // choose to "re-sync" from Genesis, even if it means we will go through thousands of blocks
     // that we already have. Hopefully, local checks will be fast enough.
     let latest_height_requested: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+    ctx.progress
+        .set_sync_forward_target_height(ctx.trusted_block_header().height());
 
     let mut workers: FuturesUnordered<_> = (0..ctx.config.max_parallel_block_fetches())
         .map(|worker_id| fetch_block_worker(worker_id, latest_height_requested.clone(), ctx))
@@ -1581,6 +1826,7 @@ where
         + From<FetcherRequest<BlockSignatures>>
         + From<NetworkInfoRequest>
         + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>
         + From<BlocklistAnnouncement>
         + From<MarkBlockCompletedRequest>
         + Send,
@@ -1595,7 +1841,7 @@ where
 
         let block_header = ctx
             .effect_builder
-            .get_block_header_at_height_from_storage(block_height, false)
+            .get_block_header_at_height_from_storage(BlockHeight::new(block_height), false)
             .await
             .ok_or(Error::NoSuchBlockHeight(block_height))?;
 
@@ -1909,6 +2155,7 @@ where
     REv: From<StorageRequest>
         + From<NetworkInfoRequest>
         + From<ContractRuntimeRequest>
+        + From<StateStoreRequest>
         + From<FetcherRequest<Block>>
         + From<FetcherRequest<BlockHeader>>
         + From<FetcherRequest<BlockAndDeploys>>
@@ -2181,6 +2428,8 @@ where
             .await;
 
         highest_synced_block_header = block.take_header();
+        ctx.progress
+            .record_synced_block(&highest_synced_block_header);
         execution_pre_state = ExecutionPreState::from_block_header(&highest_synced_block_header);
 
         if let Some(new_key_block_info) =
@@ -6,8 +6,9 @@ use serde::{Deserialize, Serialize};
 use tracing::error;
 
 use casper_hashing::Digest;
+use casper_types::{TimeDiff, Timestamp};
 
-use crate::types::BlockHash;
+use crate::types::{BlockHash, BlockHeader};
 
 /// The reason for syncing the trie store under a given state root hash.
 //
@@ -153,11 +154,183 @@ impl Progress {
             Progress::SyncToGenesis(progress) => *progress == SyncToGenesis::Finished,
         }
     }
+
+    /// Returns the number of tries still queued for fetching across all ongoing tasks.
+    fn num_tries_to_fetch(&self) -> usize {
+        match self {
+            Progress::FastSync(FastSync::FetchingTries {
+                num_tries_to_fetch, ..
+            }) => *num_tries_to_fetch,
+            Progress::SyncToGenesis(SyncToGenesis::SyncingForwardFromGenesis(tasks)) => tasks
+                .iter()
+                .map(|task| match task.fetching {
+                    SyncBlockFetching::Tries {
+                        num_tries_to_fetch, ..
+                    } => num_tries_to_fetch,
+                    SyncBlockFetching::BlockAndDeploys | SyncBlockFetching::BlockSignatures => 0,
+                })
+                .sum(),
+            _ => 0,
+        }
+    }
+}
+
+/// The phase of chain synchronization, common to fast-sync and sync-to-genesis.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DataSize, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPhase {
+    /// Syncing has not started yet.
+    NotYetStarted,
+    /// Fetching block headers, either towards the tip or back towards genesis.
+    FetchingBlockHeaders,
+    /// Fetching the global state under the highest synced block.
+    FetchingTries,
+    /// Fetching and executing blocks to catch up with the tip of the chain.
+    ExecutingBlocks,
+    /// Syncing blocks, global state and finality signatures forwards from genesis.
+    SyncingBlocks,
+    /// Syncing has finished.
+    Finished,
+}
+
+/// A summary of the progress of chain synchronization, including estimates of the remaining work.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, DataSize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SyncStatus {
+    /// The current phase of chain synchronization.
+    pub phase: SyncPhase,
+    /// The height of the highest block synced so far.
+    pub highest_synced_block_height: Option<u64>,
+    /// The estimated number of blocks still to be synced, if known.
+    pub estimated_blocks_remaining: Option<u64>,
+    /// The number of tries fetched so far.
+    pub tries_fetched: u64,
+    /// The estimated total number of tries to fetch.  This rises as more tries are discovered.
+    pub estimated_total_tries: u64,
+    /// The estimated time until the current phase is finished, if known.
+    pub eta: Option<TimeDiff>,
+}
+
+/// Counters used to derive the [`SyncStatus`].
+#[derive(DataSize, Debug)]
+struct SyncStats {
+    phase: SyncPhase,
+    phase_started: Timestamp,
+    blocks_synced_at_phase_start: u64,
+    tries_fetched_at_phase_start: u64,
+    blocks_synced: u64,
+    tries_fetched: u64,
+    highest_synced_block_height: Option<u64>,
+    highest_synced_block_timestamp: Option<Timestamp>,
+    lowest_block_height: Option<u64>,
+    sync_forward_target_height: Option<u64>,
+}
+
+impl SyncStats {
+    fn new() -> Self {
+        SyncStats {
+            phase: SyncPhase::NotYetStarted,
+            phase_started: Timestamp::now(),
+            blocks_synced_at_phase_start: 0,
+            tries_fetched_at_phase_start: 0,
+            blocks_synced: 0,
+            tries_fetched: 0,
+            highest_synced_block_height: None,
+            highest_synced_block_timestamp: None,
+            lowest_block_height: None,
+            sync_forward_target_height: None,
+        }
+    }
+
+    fn enter_phase(&mut self, phase: SyncPhase) {
+        if self.phase != phase {
+            self.phase = phase;
+            self.phase_started = Timestamp::now();
+            self.blocks_synced_at_phase_start = self.blocks_synced;
+            self.tries_fetched_at_phase_start = self.tries_fetched;
+        }
+    }
+
+    fn record_synced_block_height(&mut self, block_height: u64) {
+        self.blocks_synced += 1;
+        self.highest_synced_block_height = Some(
+            self.highest_synced_block_height
+                .map_or(block_height, |height| height.max(block_height)),
+        );
+    }
+
+    /// Estimates the remaining blocks to sync.  While fast syncing, this is based on the time
+    /// elapsed since the highest synced block, assuming blocks are produced at the minimum round
+    /// length.
+    fn estimated_blocks_remaining(
+        &self,
+        min_round_length: TimeDiff,
+        now: Timestamp,
+    ) -> Option<u64> {
+        match self.phase {
+            SyncPhase::NotYetStarted | SyncPhase::Finished => None,
+            SyncPhase::SyncingBlocks => Some(
+                self.sync_forward_target_height?
+                    .saturating_sub(self.highest_synced_block_height.unwrap_or(0)),
+            ),
+            SyncPhase::FetchingBlockHeaders
+            | SyncPhase::FetchingTries
+            | SyncPhase::ExecutingBlocks => {
+                if let Some(lowest_block_height) = self.lowest_block_height {
+                    return Some(lowest_block_height);
+                }
+                let time_behind = now.saturating_diff(self.highest_synced_block_timestamp?);
+                Some(time_behind.millis() / min_round_length.millis().max(1))
+            }
+        }
+    }
+
+    fn sync_status(
+        &self,
+        num_tries_to_fetch: usize,
+        min_round_length: TimeDiff,
+        now: Timestamp,
+    ) -> SyncStatus {
+        let estimated_blocks_remaining = self.estimated_blocks_remaining(min_round_length, now);
+        let estimated_total_tries = self.tries_fetched + num_tries_to_fetch as u64;
+        let (done, remaining) = match self.phase {
+            SyncPhase::NotYetStarted | SyncPhase::Finished => (0, None),
+            SyncPhase::FetchingTries => (
+                self.tries_fetched - self.tries_fetched_at_phase_start,
+                Some(num_tries_to_fetch as u64),
+            ),
+            SyncPhase::FetchingBlockHeaders
+            | SyncPhase::ExecutingBlocks
+            | SyncPhase::SyncingBlocks => (
+                self.blocks_synced - self.blocks_synced_at_phase_start,
+                estimated_blocks_remaining,
+            ),
+        };
+        let eta = match remaining {
+            Some(remaining) if done > 0 => {
+                let elapsed = now.saturating_diff(self.phase_started);
+                Some(TimeDiff::from(
+                    (u128::from(elapsed.millis()) * u128::from(remaining) / u128::from(done))
+                        .min(u128::from(u64::MAX)) as u64,
+                ))
+            }
+            _ => None,
+        };
+        SyncStatus {
+            phase: self.phase,
+            highest_synced_block_height: self.highest_synced_block_height,
+            estimated_blocks_remaining,
+            tries_fetched: self.tries_fetched,
+            estimated_total_tries,
+            eta,
+        }
+    }
 }
 
 #[derive(Clone, DataSize, Debug)]
 pub(super) struct ProgressHolder {
     inner: Arc<Mutex<Progress>>,
+    stats: Arc<Mutex<SyncStats>>,
 }
 
 /// This impl is specific to fast-sync progress.
@@ -165,12 +338,14 @@ impl ProgressHolder {
     pub(super) fn new_fast_sync() -> Self {
         ProgressHolder {
             inner: Arc::new(Mutex::new(Progress::FastSync(FastSync::NotYetStarted))),
+            stats: Arc::new(Mutex::new(SyncStats::new())),
         }
     }
 
     pub(super) fn start_fetching_trusted_block_header(&self, trusted_hash: BlockHash) {
         let mut inner = self.get_inner_while_fast_syncing("fetching_trusted_block_header");
         *inner = Progress::FastSync(FastSync::FetchingTrustedBlockHeader(trusted_hash));
+        self.enter_phase(SyncPhase::FetchingBlockHeaders);
     }
 
     pub(super) fn start_fetching_tries_for_fast_sync(
@@ -185,11 +360,13 @@ impl ProgressHolder {
             reason: FetchingTriesReason::FastSync,
             num_tries_to_fetch: 0,
         });
+        self.enter_phase(SyncPhase::FetchingTries);
     }
 
     pub(super) fn start_fetching_block_and_deploys_to_execute(&self, block_height: u64) {
         let mut inner = self.get_inner_while_fast_syncing("fetching_block_and_deploys_to_execute");
         *inner = Progress::FastSync(FastSync::FetchingBlockAndDeploysToExecute(block_height));
+        self.enter_phase(SyncPhase::ExecutingBlocks);
     }
 
     pub(super) fn start_executing_block(&self, block_height: u64) {
@@ -205,6 +382,16 @@ impl ProgressHolder {
         });
     }
 
+    /// Records that the given block header has been synced while fast syncing, either by fetching
+    /// it or by executing its block.
+    pub(super) fn record_synced_block(&self, block_header: &BlockHeader) {
+        let mut stats = self.stats.lock().expect("lock poisoned");
+        stats.record_synced_block_height(block_header.height());
+        if stats.highest_synced_block_height == Some(block_header.height()) {
+            stats.highest_synced_block_timestamp = Some(block_header.timestamp());
+        }
+    }
+
     fn get_inner_while_fast_syncing(&self, new_state: &str) -> MutexGuard<Progress> {
         let inner = self.inner.lock().expect("lock poisoned");
         match *inner {
@@ -228,6 +415,7 @@ impl ProgressHolder {
             inner: Arc::new(Mutex::new(Progress::SyncToGenesis(
                 SyncToGenesis::NotYetStarted,
             ))),
+            stats: Arc::new(Mutex::new(SyncStats::new())),
         }
     }
 
@@ -235,7 +423,20 @@ impl ProgressHolder {
         let inner = &mut *self.inner.lock().expect("lock poisoned");
         *inner = Progress::SyncToGenesis(SyncToGenesis::FetchingHeadersBackToGenesis {
             lowest_block_height,
-        })
+        });
+        self.enter_phase(SyncPhase::FetchingBlockHeaders);
+        let mut stats = self.stats.lock().expect("lock poisoned");
+        if let Some(previous_lowest) = stats.lowest_block_height {
+            stats.blocks_synced += previous_lowest.saturating_sub(lowest_block_height);
+        }
+        stats.lowest_block_height = Some(lowest_block_height);
+    }
+
+    /// Sets the height up to which blocks will be synced forwards from genesis.
+    pub(super) fn set_sync_forward_target_height(&self, target_height: u64) {
+        let mut stats = self.stats.lock().expect("lock poisoned");
+        stats.lowest_block_height = None;
+        stats.sync_forward_target_height = Some(target_height);
     }
 
     pub(super) fn start_syncing_block_for_sync_forward(&self, block_height: u64) {
@@ -244,7 +445,8 @@ impl ProgressHolder {
             inner,
             Progress::SyncToGenesis(SyncToGenesis::SyncingForwardFromGenesis(_))
         ) {
-            *inner = Progress::SyncToGenesis(SyncToGenesis::SyncingForwardFromGenesis(Vec::new()))
+            *inner = Progress::SyncToGenesis(SyncToGenesis::SyncingForwardFromGenesis(Vec::new()));
+            self.enter_phase(SyncPhase::SyncingBlocks);
         }
 
         let tasks =
@@ -397,6 +599,10 @@ impl ProgressHolder {
         match tasks.binary_search_by(|task| task.block_height.cmp(&block_height)) {
             Ok(index) => {
                 let existing_progress = tasks.remove(index);
+                self.stats
+                    .lock()
+                    .expect("lock poisoned")
+                    .record_synced_block_height(block_height);
                 if !matches!(
                     existing_progress.fetching,
                     SyncBlockFetching::BlockSignatures
@@ -426,6 +632,12 @@ impl ProgressHolder {
             Progress::FastSync(progress) => *progress = FastSync::Starting,
             Progress::SyncToGenesis(progress) => *progress = SyncToGenesis::Starting,
         }
+        self.enter_phase(SyncPhase::FetchingBlockHeaders);
+    }
+
+    /// Records that a trie or trie chunk has been fetched.
+    pub(super) fn record_trie_fetched(&self) {
+        self.stats.lock().expect("lock poisoned").tries_fetched += 1;
     }
 
     pub(super) fn set_num_tries_to_fetch(&self, block_height: u64, num_tries: usize) {
@@ -472,11 +684,31 @@ impl ProgressHolder {
             Progress::FastSync(progress) => *progress = FastSync::Finished,
             Progress::SyncToGenesis(progress) => *progress = SyncToGenesis::Finished,
         }
+        self.enter_phase(SyncPhase::Finished);
     }
 
     pub(super) fn progress(&self) -> Progress {
         self.inner.lock().expect("lock poisoned").clone()
     }
+
+    /// Returns a summary of the sync progress, estimating the remaining blocks to sync while fast
+    /// syncing from the given minimum round length.
+    pub(super) fn sync_status(&self, min_round_length: TimeDiff) -> SyncStatus {
+        let num_tries_to_fetch = self
+            .inner
+            .lock()
+            .expect("lock poisoned")
+            .num_tries_to_fetch();
+        self.stats.lock().expect("lock poisoned").sync_status(
+            num_tries_to_fetch,
+            min_round_length,
+            Timestamp::now(),
+        )
+    }
+
+    fn enter_phase(&self, phase: SyncPhase) {
+        self.stats.lock().expect("lock poisoned").enter_phase(phase);
+    }
 }
 
 /// This impl is specific to functionality used for `debug_assert`s.
@@ -527,3 +759,43 @@ impl ProgressHolder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_estimate_remaining_blocks_and_eta() {
+        let min_round_length = TimeDiff::from(1_000);
+        let mut stats = SyncStats::new();
+        let start = stats.phase_started;
+        stats.enter_phase(SyncPhase::FetchingBlockHeaders);
+        stats.phase_started = start;
+
+        // Before any block is synced nothing can be estimated.
+        let status = stats.sync_status(0, min_round_length, start);
+        assert_eq!(status.phase, SyncPhase::FetchingBlockHeaders);
+        assert_eq!(status.estimated_blocks_remaining, None);
+        assert_eq!(status.eta, None);
+
+        // Ten blocks synced in ten seconds, with the highest one 90 rounds behind.
+        let now = start + TimeDiff::from(10_000);
+        for height in 1..=10 {
+            stats.record_synced_block_height(height);
+        }
+        stats.highest_synced_block_timestamp = Some(now.saturating_sub(TimeDiff::from(90_000)));
+        let status = stats.sync_status(0, min_round_length, now);
+        assert_eq!(status.highest_synced_block_height, Some(10));
+        assert_eq!(status.estimated_blocks_remaining, Some(90));
+        assert_eq!(status.eta, Some(TimeDiff::from(90_000)));
+
+        // Entering a new phase restarts the ETA calculation.
+        stats.enter_phase(SyncPhase::FetchingTries);
+        stats.phase_started = now;
+        stats.tries_fetched = 25;
+        let status = stats.sync_status(75, min_round_length, now + TimeDiff::from(5_000));
+        assert_eq!(status.tries_fetched, 25);
+        assert_eq!(status.estimated_total_tries, 100);
+        assert_eq!(status.eta, Some(TimeDiff::from(15_000)));
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use crate::types::NodeId;
+
+/// The outcome of assigning a trie fetch request to a peer.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Assignment {
+    /// The request should be sent to the given peer.
+    Peer(NodeId),
+    /// All usable peers already have the maximum number of requests in flight.
+    Busy,
+    /// There are no usable peers.
+    NoUsablePeers,
+}
+
+#[derive(Debug, Default)]
+struct PeerState {
+    /// The number of requests in flight to the peer.
+    in_flight: u32,
+    /// The peer's score; starts at zero and only drops below it after failed requests.
+    score: i64,
+}
+
+/// Tracks the peers tries and trie chunks are being fetched from during fast sync.
+///
+/// Each request is sent to the usable peer with the fewest requests in flight, preferring peers
+/// with a higher score, so that concurrent fetches are spread across all available peers rather
+/// than queueing up on a few of them.  Peers lose `failure_penalty` points for each failed or timed
+/// out request and regain a point for each delivered item, up to zero.  Peers whose score falls
+/// below `min_score` are not asked again until they are redeemed.
+#[derive(Debug)]
+pub(super) struct TriePeers {
+    peers: HashMap<NodeId, PeerState>,
+    max_in_flight_per_peer: u32,
+    failure_penalty: i64,
+    min_score: i64,
+}
+
+impl TriePeers {
+    pub(super) fn new(max_in_flight_per_peer: u32, failure_penalty: u32, min_score: i64) -> Self {
+        TriePeers {
+            peers: HashMap::new(),
+            max_in_flight_per_peer,
+            failure_penalty: i64::from(failure_penalty),
+            min_score,
+        }
+    }
+
+    /// Adds newly connected peers, and forgets peers which are no longer connected and have no
+    /// requests in flight.
+    pub(super) fn update_peers<I: IntoIterator<Item = NodeId>>(&mut self, peers: I) {
+        let connected: HashSet<NodeId> = peers.into_iter().collect();
+        self.peers
+            .retain(|peer, state| state.in_flight > 0 || connected.contains(peer));
+        for peer in connected {
+            self.peers.entry(peer).or_default();
+        }
+    }
+
+    /// Returns the best usable peer which is below its limit of requests in flight and records a
+    /// new request to it.
+    pub(super) fn assign(&mut self) -> Assignment {
+        let min_score = self.min_score;
+        let max_in_flight_per_peer = self.max_in_flight_per_peer;
+        let mut any_usable = false;
+        let best = self
+            .peers
+            .iter_mut()
+            .filter(|(_, state)| state.score >= min_score)
+            .inspect(|_| any_usable = true)
+            .filter(|(_, state)| state.in_flight < max_in_flight_per_peer)
+            .min_by_key(|(_, state)| (state.in_flight, Reverse(state.score)));
+        match best {
+            Some((peer, state)) => {
+                state.in_flight += 1;
+                Assignment::Peer(*peer)
+            }
+            None if any_usable => Assignment::Busy,
+            None => Assignment::NoUsablePeers,
+        }
+    }
+
+    /// Records that a request to `peer` delivered the requested item.
+    pub(super) fn completed(&mut self, peer: NodeId) {
+        if let Some(state) = self.release(peer) {
+            state.score = (state.score + 1).min(0);
+        }
+    }
+
+    /// Records that a request to `peer` failed or timed out.
+    pub(super) fn failed(&mut self, peer: NodeId) {
+        let failure_penalty = self.failure_penalty;
+        if let Some(state) = self.release(peer) {
+            state.score = state.score.saturating_sub(failure_penalty);
+        }
+    }
+
+    /// Records that a request to `peer` was abandoned without an outcome.
+    pub(super) fn cancelled(&mut self, peer: NodeId) {
+        let _ = self.release(peer);
+    }
+
+    /// Resets the score of all peers which are no longer usable, returning how many were redeemed.
+    pub(super) fn redeem(&mut self) -> usize {
+        let min_score = self.min_score;
+        let mut redeemed = 0;
+        for state in self.peers.values_mut() {
+            if state.score < min_score {
+                state.score = 0;
+                redeemed += 1;
+            }
+        }
+        redeemed
+    }
+
+    fn release(&mut self, peer: NodeId) -> Option<&mut PeerState> {
+        let state = self.peers.get_mut(&peer)?;
+        state.in_flight = state.in_flight.saturating_sub(1);
+        Some(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn should_spread_requests_across_peers() {
+        let mut rng = TestRng::new();
+        let peers: Vec<_> = (0..3).map(|_| NodeId::random(&mut rng)).collect();
+        let mut trie_peers = TriePeers::new(2, 10, -50);
+        trie_peers.update_peers(peers.clone());
+
+        let assigned: HashSet<_> = (0..3)
+            .filter_map(|_| match trie_peers.assign() {
+                Assignment::Peer(peer) => Some(peer),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(assigned, peers.iter().copied().collect());
+
+        // Once a request completes, its peer is the least busy one again.
+        trie_peers.completed(peers[1]);
+        assert_eq!(trie_peers.assign(), Assignment::Peer(peers[1]));
+
+        // Once every peer has two requests in flight, further requests have to wait.
+        for _ in 0..3 {
+            assert!(matches!(trie_peers.assign(), Assignment::Peer(_)));
+        }
+        assert_eq!(trie_peers.assign(), Assignment::Busy);
+        trie_peers.cancelled(peers[2]);
+        assert_eq!(trie_peers.assign(), Assignment::Peer(peers[2]));
+    }
+
+    #[test]
+    fn should_exclude_and_redeem_failing_peers() {
+        let mut rng = TestRng::new();
+        let peer_a = NodeId::random(&mut rng);
+        let peer_b = NodeId::random(&mut rng);
+        let mut trie_peers = TriePeers::new(1, 10, -15);
+        trie_peers.update_peers(vec![peer_a, peer_b]);
+
+        // A single failure lowers the peer's preference, but keeps it usable.
+        let first = match trie_peers.assign() {
+            Assignment::Peer(peer) => peer,
+            assignment => panic!("unexpected assignment {:?}", assignment),
+        };
+        let second = if first == peer_a { peer_b } else { peer_a };
+        trie_peers.failed(first);
+        assert_eq!(trie_peers.assign(), Assignment::Peer(second));
+        trie_peers.completed(second);
+        assert_eq!(trie_peers.assign(), Assignment::Peer(second));
+        trie_peers.failed(second);
+
+        // A second failure takes each peer below the minimum score.
+        for _ in 0..2 {
+            match trie_peers.assign() {
+                Assignment::Peer(peer) => trie_peers.failed(peer),
+                assignment => panic!("unexpected assignment {:?}", assignment),
+            }
+        }
+        assert_eq!(trie_peers.assign(), Assignment::NoUsablePeers);
+
+        assert_eq!(trie_peers.redeem(), 2);
+        assert!(matches!(trie_peers.assign(), Assignment::Peer(_)));
+    }
+}
@@ -50,7 +50,10 @@ use crate::{
     fatal,
     reactor::ReactorExit,
     types::{
-        chainspec::{ChainspecRawBytes, Error, ProtocolConfig, CHAINSPEC_FILENAME},
+        chainspec::{
+            apply_overlays, validate_upgrade_files, verify_upgrade_signature, ChainspecRawBytes,
+            Error, ProtocolConfig, CHAINSPEC_FILENAME,
+        },
         ActivationPoint, BlockHeader, BlockPayload, Chainspec, ChainspecInfo, ExitCode,
         FinalizedBlock,
     },
@@ -66,6 +69,9 @@ pub(crate) enum Event {
     /// The result of getting the highest block from storage.
     Initialize {
         maybe_highest_block_header: Option<Box<BlockHeader>>,
+        /// The first era of the current protocol version, if the activation point is given as a
+        /// timestamp and the era could be determined from storage.
+        maybe_activation_era: Option<EraId>,
     },
     /// The result of contract runtime running the genesis process.
     CommitGenesisResult(#[serde(skip_serializing)] Result<GenesisSuccess, engine_state::Error>),
@@ -92,13 +98,15 @@ impl Display for Event {
         match self {
             Event::Initialize {
                 maybe_highest_block_header,
+                maybe_activation_era,
             } => {
                 write!(
                     formatter,
-                    "initialize(maybe_highest_block_header: {})",
+                    "initialize(maybe_highest_block_header: {}, maybe_activation_era: {:?})",
                     maybe_highest_block_header
                         .as_ref()
-                        .map_or_else(|| "None".to_string(), |header| header.to_string())
+                        .map_or_else(|| "None".to_string(), |header| header.to_string()),
+                    maybe_activation_era
                 )
             }
             Event::CommitGenesisResult(result) => {
@@ -148,6 +156,10 @@ impl NextUpgrade {
     pub(crate) fn activation_point(&self) -> ActivationPoint {
         self.activation_point
     }
+
+    pub(crate) fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
 }
 
 impl From<ProtocolConfig> for NextUpgrade {
@@ -163,9 +175,8 @@ impl Display for NextUpgrade {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         write!(
             formatter,
-            "next upgrade to {} at start of era {}",
-            self.protocol_version,
-            self.activation_point.era_id()
+            "next upgrade to {} at {}",
+            self.protocol_version, self.activation_point
         )
     }
 }
@@ -177,6 +188,9 @@ pub(crate) struct ChainspecLoader {
     /// The path to the folder where all chainspec and upgrade_point files will be stored in
     /// subdirs corresponding to their versions.
     root_dir: PathBuf,
+    /// The keys of which one must have signed a staged upgrade for it to be activated.  If empty,
+    /// staged upgrades are not required to be signed.
+    trusted_release_keys: Arc<Vec<PublicKey>>,
     reactor_exit: Option<ReactorExit>,
     next_upgrade: Option<NextUpgrade>,
     maybe_immediate_switch_block_data: Option<BlockAndExecutionEffects>,
@@ -185,6 +199,7 @@ pub(crate) struct ChainspecLoader {
 impl ChainspecLoader {
     pub(crate) fn new<P, REv>(
         chainspec_dir: P,
+        trusted_release_keys: Vec<PublicKey>,
         effect_builder: EffectBuilder<REv>,
     ) -> Result<(Self, Effects<Event>), Error>
     where
@@ -197,6 +212,7 @@ impl ChainspecLoader {
             Arc::new(chainspec),
             Arc::new(chainspec_raw_bytes),
             chainspec_dir,
+            trusted_release_keys,
             effect_builder,
         ))
     }
@@ -214,6 +230,7 @@ impl ChainspecLoader {
             chainspec,
             chainspec_raw_bytes,
             &RESOURCES_PATH.join("local"),
+            vec![],
             effect_builder,
         )
     }
@@ -222,6 +239,7 @@ impl ChainspecLoader {
         chainspec: Arc<Chainspec>,
         chainspec_raw_bytes: Arc<ChainspecRawBytes>,
         chainspec_dir: P,
+        trusted_release_keys: Vec<PublicKey>,
         effect_builder: EffectBuilder<REv>,
     ) -> (Self, Effects<Event>)
     where
@@ -242,6 +260,7 @@ impl ChainspecLoader {
                 chainspec,
                 chainspec_raw_bytes,
                 root_dir,
+                trusted_release_keys: Arc::new(trusted_release_keys),
                 reactor_exit: Some(ReactorExit::ProcessShouldExit(ExitCode::Abort)),
                 next_upgrade: None,
                 maybe_immediate_switch_block_data: None,
@@ -249,7 +268,13 @@ impl ChainspecLoader {
             return (chainspec_loader, Effects::new());
         }
 
-        let next_upgrade = next_upgrade(root_dir.clone(), chainspec.protocol_config.version);
+        let trusted_release_keys = Arc::new(trusted_release_keys);
+        let next_upgrade = next_upgrade(
+            root_dir.clone(),
+            chainspec.protocol_config.version,
+            chainspec.protocol_config.activation_point,
+            &trusted_release_keys,
+        );
 
         // If the next activation point is the same as the current chainspec one, we've installed
         // two new versions, where the first which we're currently running should be immediately
@@ -266,11 +291,29 @@ impl ChainspecLoader {
         let mut effects = if should_stop {
             Effects::new()
         } else {
-            effect_builder
-                .get_highest_block_header_from_storage()
-                .event(|highest_block_header| Event::Initialize {
+            let protocol_config = chainspec.protocol_config.clone();
+            async move {
+                let maybe_highest_block_header =
+                    effect_builder.get_highest_block_header_from_storage().await;
+                let maybe_activation_era = match maybe_highest_block_header.as_ref() {
+                    Some(highest_block_header) => {
+                        activation_era_from_storage(
+                            effect_builder,
+                            &protocol_config,
+                            highest_block_header,
+                        )
+                        .await
+                    }
+                    None => None,
+                };
+                (maybe_highest_block_header, maybe_activation_era)
+            }
+            .event(
+                |(highest_block_header, maybe_activation_era)| Event::Initialize {
                     maybe_highest_block_header: highest_block_header.map(Box::new),
-                })
+                    maybe_activation_era,
+                },
+            )
         };
 
         // Start regularly checking for the next upgrade.
@@ -286,6 +329,7 @@ impl ChainspecLoader {
             chainspec,
             chainspec_raw_bytes,
             root_dir,
+            trusted_release_keys,
             reactor_exit,
             next_upgrade,
             maybe_immediate_switch_block_data: None,
@@ -298,10 +342,18 @@ impl ChainspecLoader {
         &mut self,
         effect_builder: EffectBuilder<REv>,
         maybe_highest_block_header: Option<Box<BlockHeader>>,
+        maybe_activation_era: Option<EraId>,
     ) -> Effects<Event>
     where
         REv: From<Event> + From<ContractRuntimeRequest> + Send,
     {
+        if let Some(activation_era) = maybe_activation_era {
+            info!(%activation_era, "determined activation era from storage");
+            Arc::make_mut(&mut self.chainspec)
+                .protocol_config
+                .effective_activation_era = Some(activation_era);
+        }
+
         // Check if we're not running a version that's already outdated - if it is, we should exit
         // and upgrade.
         if Self::should_exit_for_upgrade(
@@ -383,11 +435,11 @@ impl ChainspecLoader {
             chainspec_raw_bytes.chainspec_bytes(),
             chainspec_raw_bytes.maybe_global_state_bytes(),
         );
-        let upgrade_config = UpgradeConfig::new(
+        let mut upgrade_config = UpgradeConfig::new(
             *upgrade_block_header.state_root_hash(),
             upgrade_block_header.protocol_version(),
             self.chainspec.protocol_version(),
-            Some(self.chainspec.protocol_config.activation_point.era_id()),
+            Some(upgrade_block_header.next_block_era_id()),
             Some(self.chainspec.core_config.validator_slots),
             Some(self.chainspec.core_config.auction_delay),
             Some(self.chainspec.core_config.locked_funds_period.millis()),
@@ -396,6 +448,7 @@ impl ChainspecLoader {
             global_state_update,
             chainspec_registry,
         );
+        upgrade_config.with_migrations(self.chainspec.protocol_config.get_migrations()?);
         Ok(Box::new(upgrade_config))
     }
 
@@ -405,12 +458,20 @@ impl ChainspecLoader {
     ) -> bool {
         maybe_highest_block_header.map_or(false, |highest_block_header| {
             maybe_next_upgrade_activation_point.map_or(false, |next_upgrade_activation_point| {
-                if highest_block_header.next_block_era_id()
-                    >= next_upgrade_activation_point.era_id()
-                {
-                    // This is an invalid run as the highest block era ID >= next activation
-                    // point, so we're running an outdated version.  Exit with success to
-                    // indicate we should upgrade.
+                let is_past_activation_point = match next_upgrade_activation_point.era_id() {
+                    Some(era_id) => highest_block_header.next_block_era_id() >= era_id,
+                    None => {
+                        highest_block_header.is_switch_block()
+                            && next_upgrade_activation_point.should_upgrade(
+                                &highest_block_header.era_id(),
+                                highest_block_header.timestamp(),
+                            )
+                    }
+                };
+                if is_past_activation_point {
+                    // This is an invalid run as the highest block is at or after the next
+                    // activation point, so we're running an outdated version.  Exit with success
+                    // to indicate we should upgrade.
                     warn!(
                         %next_upgrade_activation_point,
                         %highest_block_header,
@@ -643,7 +704,7 @@ impl ChainspecLoader {
         self.chainspec
             .protocol_config
             .hard_reset
-            .then(|| self.chainspec.protocol_config.activation_point.era_id())
+            .then(|| self.chainspec.protocol_config.activation_era())
     }
 
     fn new_chainspec_info(&self) -> ChainspecInfo {
@@ -659,14 +720,22 @@ impl ChainspecLoader {
     {
         let root_dir = self.root_dir.clone();
         let current_version = self.chainspec.protocol_config.version;
+        let current_activation_point = self.chainspec.protocol_config.activation_point;
+        let trusted_release_keys = Arc::clone(&self.trusted_release_keys);
         let mut effects = async move {
-            let maybe_next_upgrade =
-                task::spawn_blocking(move || next_upgrade(root_dir, current_version))
-                    .await
-                    .unwrap_or_else(|error| {
-                        warn!(%error, "failed to join tokio task");
-                        None
-                    });
+            let maybe_next_upgrade = task::spawn_blocking(move || {
+                next_upgrade(
+                    root_dir,
+                    current_version,
+                    current_activation_point,
+                    &trusted_release_keys,
+                )
+            })
+            .await
+            .unwrap_or_else(|error| {
+                warn!(%error, "failed to join tokio task");
+                None
+            });
             if let Some(next_upgrade) = maybe_next_upgrade {
                 effect_builder
                     .announce_upgrade_activation_point_read(next_upgrade)
@@ -723,7 +792,12 @@ where
         match event {
             Event::Initialize {
                 maybe_highest_block_header,
-            } => self.handle_initialize(effect_builder, maybe_highest_block_header),
+                maybe_activation_era,
+            } => self.handle_initialize(
+                effect_builder,
+                maybe_highest_block_header,
+                maybe_activation_era,
+            ),
             Event::CommitGenesisResult(result) => {
                 self.handle_commit_genesis_result(effect_builder, result)
             }
@@ -756,14 +830,62 @@ struct UpgradePoint {
 }
 
 impl UpgradePoint {
-    /// Parses a chainspec file at the given path as an `UpgradePoint`.
+    /// Parses a chainspec file at the given path, with any overlays applied, as an `UpgradePoint`.
     fn from_chainspec_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        let bytes = file_utils::read_file(path.as_ref().join(&CHAINSPEC_FILENAME))
-            .map_err(Error::LoadUpgradePoint)?;
+        let bytes = apply_overlays(
+            path.as_ref(),
+            file_utils::read_file(path.as_ref().join(&CHAINSPEC_FILENAME))
+                .map_err(Error::LoadUpgradePoint)?,
+        )?;
         Ok(toml::from_slice(&bytes)?)
     }
 }
 
+/// Determines the first era of the current protocol version from storage, if its activation point
+/// is given as a timestamp.
+///
+/// If the highest block is the last one before the upgrade, the activation era is the next one.  If
+/// it already belongs to the current protocol version, this searches for the earliest switch block
+/// of the current version, whose era is the activation era.
+async fn activation_era_from_storage<REv>(
+    effect_builder: EffectBuilder<REv>,
+    protocol_config: &ProtocolConfig,
+    highest_block_header: &BlockHeader,
+) -> Option<EraId>
+where
+    REv: From<StorageRequest>,
+{
+    if protocol_config.activation_point.era_id().is_some() {
+        return None;
+    }
+    if protocol_config.is_last_block_before_activation(highest_block_header) {
+        return Some(highest_block_header.next_block_era_id());
+    }
+    if highest_block_header.protocol_version() != protocol_config.version {
+        return None;
+    }
+
+    // Switch blocks of the current version form a suffix of the eras up to the highest one.  The
+    // highest era itself belongs to the current version even if it hasn't ended yet.
+    let mut low = 0;
+    let mut high = highest_block_header.era_id().value();
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let is_current_version = effect_builder
+            .get_switch_block_header_at_era_id_from_storage(EraId::from(mid))
+            .await
+            .map_or(false, |header| {
+                header.protocol_version() == protocol_config.version
+            });
+        if is_current_version {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Some(EraId::from(low))
+}
+
 fn dir_name_from_version(version: &ProtocolVersion) -> PathBuf {
     PathBuf::from(version.to_string().replace('.', "_"))
 }
@@ -776,11 +898,7 @@ fn next_installed_version(
     dir: &Path,
     current_version: &ProtocolVersion,
 ) -> Result<ProtocolVersion, Error> {
-    let max_version =
-        ProtocolVersion::from_parts(u32::max_value(), u32::max_value(), u32::max_value());
-
-    let mut next_version = max_version;
-    let mut read_version = false;
+    let mut installed_versions = vec![];
     for entry in fs::read_dir(dir).map_err(|error| Error::ReadDir {
         dir: dir.to_path_buf(),
         error,
@@ -806,29 +924,32 @@ fn next_installed_version(
             }
         };
 
-        if version > *current_version && version < next_version {
-            next_version = version;
-        }
-        read_version = true;
+        installed_versions.push(version);
     }
 
-    if !read_version {
+    if installed_versions.is_empty() {
         return Err(Error::NoVersionSubdirFound {
             dir: dir.to_path_buf(),
         });
     }
 
-    if next_version == max_version {
-        next_version = *current_version;
-    }
-
-    Ok(next_version)
+    Ok(current_version
+        .ordered_upgrades(installed_versions)
+        .next()
+        .unwrap_or(*current_version))
 }
 
 /// Uses `next_installed_version()` to find the next versioned subdir.  If it exists, reads the
 /// UpgradePoint file from there and returns its version and activation point.  Returns `None` if
-/// there is no greater version available, or if any step errors.
-fn next_upgrade(dir: PathBuf, current_version: ProtocolVersion) -> Option<NextUpgrade> {
+/// there is no greater version available, if the upgrade isn't signed by one of
+/// `trusted_release_keys` (unless that is empty), if it would activate before
+/// `current_activation_point`, if its upgrade files fail to parse, or if any step errors.
+fn next_upgrade(
+    dir: PathBuf,
+    current_version: ProtocolVersion,
+    current_activation_point: ActivationPoint,
+    trusted_release_keys: &[PublicKey],
+) -> Option<NextUpgrade> {
     let next_version = match next_installed_version(&dir, &current_version) {
         Ok(version) => version,
         Err(error) => {
@@ -837,7 +958,7 @@ fn next_upgrade(dir: PathBuf, current_version: ProtocolVersion) -> Option<NextUp
         }
     };
 
-    if next_version <= current_version {
+    if !next_version.is_upgrade_of(&current_version) {
         return None;
     }
 
@@ -859,11 +980,46 @@ fn next_upgrade(dir: PathBuf, current_version: ProtocolVersion) -> Option<NextUp
         return None;
     }
 
+    if let Err(error) = verify_upgrade_signature(&subdir, trusted_release_keys) {
+        error!(
+            subdir=%subdir.display(),
+            %error,
+            "refusing to activate upgrade which failed signature verification"
+        );
+        return None;
+    }
+
+    // The staged activation point may equal the current one if two new versions were installed,
+    // in which case the current version is immediately replaced.
+    let next_activation_point = upgrade_point.protocol_config.activation_point;
+    if next_activation_point != current_activation_point
+        && !next_activation_point.is_after(&current_activation_point)
+    {
+        error!(
+            subdir=%subdir.display(),
+            %next_activation_point,
+            %current_activation_point,
+            "refusing to activate upgrade which doesn't follow the current activation point"
+        );
+        return None;
+    }
+
+    if let Err(error) = validate_upgrade_files(&subdir) {
+        error!(
+            subdir=%subdir.display(),
+            %error,
+            "refusing to activate upgrade with invalid upgrade files"
+        );
+        return None;
+    }
+
     Some(NextUpgrade::from(upgrade_point.protocol_config))
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::Rng;
+
     use casper_types::testing::TestRng;
 
     use super::*;
@@ -962,6 +1118,43 @@ mod tests {
             highest_block_header.as_deref(),
             next_upgrade_activation_point
         ));
+
+        // With a timestamp activation point, only a switch block at or after it ends the last era
+        // of the current version.
+        let timestamp = highest_block_header.as_ref().unwrap().timestamp();
+        let next_upgrade_activation_point = Some(ActivationPoint::Timestamp { timestamp });
+        assert!(ChainspecLoader::should_exit_for_upgrade(
+            highest_block_header.as_deref(),
+            next_upgrade_activation_point
+        ));
+
+        let next_upgrade_activation_point = Some(ActivationPoint::Timestamp {
+            timestamp: timestamp + 1.into(),
+        });
+        assert!(!ChainspecLoader::should_exit_for_upgrade(
+            highest_block_header.as_deref(),
+            next_upgrade_activation_point
+        ));
+
+        let highest_block_header = Some(Box::new(
+            Block::random_with_specifics(
+                &mut rng,
+                EraId::from(2),
+                HEIGHT,
+                PROTOCOL_VERSION,
+                IS_NOT_SWITCH,
+                None,
+            )
+            .header()
+            .clone(),
+        ));
+        let next_upgrade_activation_point = Some(ActivationPoint::Timestamp {
+            timestamp: highest_block_header.as_ref().unwrap().timestamp(),
+        });
+        assert!(!ChainspecLoader::should_exit_for_upgrade(
+            highest_block_header.as_deref(),
+            next_upgrade_activation_point
+        ));
     }
 
     #[test]
@@ -1047,6 +1240,11 @@ mod tests {
         );
     }
 
+    /// The activation point of the version currently running in the upgrade tests.
+    fn genesis() -> ActivationPoint {
+        ActivationPoint::Genesis(Timestamp::zero())
+    }
+
     /// Creates the appropriate subdir in `root_dir`, and adds a random chainspec.toml with the
     /// protocol_config.version field set to `version`.
     fn install_chainspec(
@@ -1056,6 +1254,8 @@ mod tests {
     ) -> Chainspec {
         let mut chainspec = Chainspec::random(rng);
         chainspec.protocol_config.version = *version;
+        chainspec.protocol_config.activation_point =
+            ActivationPoint::EraId(EraId::from(rng.gen_range(1..100)));
 
         let subdir = root_dir.join(dir_name_from_version(version));
         fs::create_dir(&subdir).unwrap();
@@ -1074,7 +1274,13 @@ mod tests {
         let tempdir = tempfile::tempdir().expect("should create temp dir");
 
         let next_point = |current_version: &ProtocolVersion| {
-            next_upgrade(tempdir.path().to_path_buf(), *current_version).unwrap()
+            next_upgrade(
+                tempdir.path().to_path_buf(),
+                *current_version,
+                genesis(),
+                &[],
+            )
+            .unwrap()
         };
 
         let mut rng = crate::new_rng();
@@ -1101,7 +1307,12 @@ mod tests {
         let tempdir = tempfile::tempdir().expect("should create temp dir");
 
         let maybe_next_point = |current_version: &ProtocolVersion| {
-            next_upgrade(tempdir.path().to_path_buf(), *current_version)
+            next_upgrade(
+                tempdir.path().to_path_buf(),
+                *current_version,
+                genesis(),
+                &[],
+            )
         };
 
         let mut rng = crate::new_rng();
@@ -1146,4 +1357,76 @@ mod tests {
         fs::remove_file(&path_v1_0_0).unwrap();
         assert!(maybe_next_point(&current).is_none());
     }
+
+    #[test]
+    fn should_not_get_unsigned_upgrade_if_release_keys_are_trusted() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let current = ProtocolVersion::from_parts(0, 9, 9);
+        let v1_0_0 = ProtocolVersion::from_parts(1, 0, 0);
+        install_chainspec(&mut rng, tempdir.path(), &v1_0_0);
+
+        let trusted_release_keys = vec![PublicKey::random(&mut rng)];
+        assert!(next_upgrade(
+            tempdir.path().to_path_buf(),
+            current,
+            genesis(),
+            &trusted_release_keys
+        )
+        .is_none());
+        assert!(next_upgrade(tempdir.path().to_path_buf(), current, genesis(), &[]).is_some());
+    }
+
+    #[test]
+    fn should_not_get_upgrade_activating_before_current_one() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let current = ProtocolVersion::from_parts(0, 9, 9);
+        let v1_0_0 = ProtocolVersion::from_parts(1, 0, 0);
+        let chainspec = install_chainspec(&mut rng, tempdir.path(), &v1_0_0);
+        let next_activation_point = chainspec.protocol_config.activation_point;
+        let next_era_id = next_activation_point.era_id().unwrap();
+
+        // The same activation point is accepted, as the current version is then replaced at once.
+        let maybe_next_point = |current_activation_point| {
+            next_upgrade(
+                tempdir.path().to_path_buf(),
+                current,
+                current_activation_point,
+                &[],
+            )
+        };
+        assert!(maybe_next_point(next_activation_point).is_some());
+        assert!(maybe_next_point(ActivationPoint::EraId(next_era_id.successor())).is_none());
+    }
+
+    #[test]
+    fn should_not_get_upgrade_with_invalid_migrations() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let current = ProtocolVersion::from_parts(0, 9, 9);
+        let v1_0_0 = ProtocolVersion::from_parts(1, 0, 0);
+        install_chainspec(&mut rng, tempdir.path(), &v1_0_0);
+        let migrations_path = tempdir
+            .path()
+            .join(dir_name_from_version(&v1_0_0))
+            .join("migrations.toml");
+
+        fs::write(
+            &migrations_path,
+            "[[migrations]]\ntype = \"reencode_values\"\nkey_tag = \"bid\"",
+        )
+        .unwrap();
+        assert!(next_upgrade(tempdir.path().to_path_buf(), current, genesis(), &[]).is_some());
+
+        fs::write(
+            &migrations_path,
+            "[[migrations]]\ntype = \"reencode_values\"\nkey_tag = \"bids\"",
+        )
+        .unwrap();
+        assert!(next_upgrade(tempdir.path().to_path_buf(), current, genesis(), &[]).is_none());
+    }
 }
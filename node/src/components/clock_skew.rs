@@ -0,0 +1,468 @@
+//! Clock skew monitor component.
+//!
+//! A node whose clock is off creates blocks and accepts deploys with timestamps its peers consider
+//! to lie in the past or future, which leads to rejected deploys and consensus trouble.  The clock
+//! skew monitor periodically queries the configured NTP servers via SNTP, exports the measured
+//! offset of the local clock as the `clock_skew_seconds` metric, and warns when it exceeds the
+//! configured maximum.  The same measurement is taken once at startup, where an excessive skew can
+//! optionally prevent the node from starting.
+
+use std::{
+    convert::TryInto,
+    fmt::{self, Display, Formatter},
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use datasize::DataSize;
+use futures::future;
+use prometheus::{Gauge, Registry};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::net::{lookup_host, UdpSocket};
+use tracing::{debug, info, warn};
+
+use casper_types::{TimeDiff, Timestamp};
+
+use super::Component;
+use crate::{
+    effect::{EffectBuilder, EffectExt, Effects},
+    types::NodeRng,
+    unregister_metric,
+    utils::deserialize_nonzero_time_diff,
+};
+
+/// The length of an SNTP packet without extension fields.
+const PACKET_LENGTH: usize = 48;
+/// The first byte of a request: no leap second warning, protocol version 4, client mode.
+const REQUEST_HEADER: u8 = 0b00_100_011;
+/// The mode of a server's response.
+const SERVER_MODE: u8 = 4;
+/// Seconds between the NTP epoch, 1900-01-01, and the Unix epoch.
+const NTP_TO_UNIX_EPOCH_SECS: i64 = 2_208_988_800;
+
+/// Clock skew monitor configuration.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    /// The NTP servers to compare the local clock against, as `host:port`.  If empty, the clock
+    /// skew is not measured.
+    pub(crate) ntp_servers: Vec<String>,
+    /// How often the clock skew is measured.
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
+    pub(crate) check_interval: TimeDiff,
+    /// Timeout for each query to an NTP server.
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
+    pub(crate) query_timeout: TimeDiff,
+    /// The maximum clock skew tolerated without a warning.
+    pub(crate) max_skew: TimeDiff,
+    /// Whether the node refuses to start if the clock skew measured at startup exceeds
+    /// `max_skew`.
+    pub(crate) refuse_to_start: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            ntp_servers: vec![],
+            check_interval: TimeDiff::from_seconds(600),
+            query_timeout: TimeDiff::from_seconds(5),
+            max_skew: TimeDiff::from_seconds(1),
+            refuse_to_start: false,
+        }
+    }
+}
+
+impl Config {
+    /// Returns whether the given skew in milliseconds exceeds the maximum.
+    fn is_excessive(&self, skew_millis: i64) -> bool {
+        skew_millis.unsigned_abs() > self.max_skew.millis()
+    }
+}
+
+/// Error querying an NTP server.
+#[derive(Debug, Error)]
+enum SntpError {
+    /// Failed to resolve the server's address or to exchange packets with it.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The server's host name did not resolve to any address.
+    #[error("no address found")]
+    NoAddress,
+    /// The server did not respond in time.
+    #[error("timed out")]
+    Timeout,
+    /// The response was not a valid SNTP server response.
+    #[error("malformed response")]
+    Malformed,
+    /// The server declined to provide the time.
+    #[error("kiss-of-death response")]
+    KissOfDeath,
+    /// The response does not belong to the request.
+    #[error("response does not match request")]
+    Mismatch,
+}
+
+/// Error returned if the node refuses to start due to excessive clock skew.
+#[derive(Debug, Error)]
+#[error(
+    "local clock is off by {skew_millis} ms from the NTP servers' time, more than the maximum of \
+    {max_skew}; fix the clock or set `clock_skew.refuse_to_start` to false"
+)]
+pub(crate) struct ExcessiveClockSkew {
+    skew_millis: i64,
+    max_skew: TimeDiff,
+}
+
+/// Measures the clock skew once at startup.
+///
+/// Returns an error if the skew is excessive and the node is configured to refuse to start in that
+/// case, otherwise only warns.
+pub(crate) async fn check_at_startup(config: &Config) -> Result<(), ExcessiveClockSkew> {
+    if config.ntp_servers.is_empty() {
+        return Ok(());
+    }
+    let skew_millis =
+        match measure_skew(config.ntp_servers.clone(), config.query_timeout.into()).await {
+            Some(skew_millis) => skew_millis,
+            None => return Ok(()),
+        };
+    if !config.is_excessive(skew_millis) {
+        info!(skew_millis, "local clock is in sync");
+        return Ok(());
+    }
+    if config.refuse_to_start {
+        return Err(ExcessiveClockSkew {
+            skew_millis,
+            max_skew: config.max_skew,
+        });
+    }
+    warn!(
+        skew_millis,
+        max_skew = %config.max_skew,
+        "local clock skew exceeds the maximum"
+    );
+    Ok(())
+}
+
+/// Clock skew monitor event.
+#[derive(Debug, Serialize)]
+pub(crate) enum Event {
+    /// The timer for the next measurement has fired.
+    Check,
+    /// The clock skew was measured, in milliseconds, if any NTP server responded.
+    Measured(Option<i64>),
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Check => write!(formatter, "check clock skew"),
+            Event::Measured(Some(skew_millis)) => {
+                write!(formatter, "measured clock skew of {} ms", skew_millis)
+            }
+            Event::Measured(None) => write!(formatter, "failed to measure clock skew"),
+        }
+    }
+}
+
+/// Metrics for the clock skew monitor.
+#[derive(Debug)]
+struct Metrics {
+    /// The measured offset of the local clock.
+    clock_skew: Gauge,
+    /// Prometheus registry used to publish metrics.
+    registry: Registry,
+}
+
+impl Metrics {
+    fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let clock_skew = Gauge::new(
+            "clock_skew_seconds",
+            "offset in seconds of the local clock from the configured NTP servers' time, positive \
+            if the local clock is ahead",
+        )?;
+        registry.register(Box::new(clock_skew.clone()))?;
+        Ok(Metrics {
+            clock_skew,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl Drop for Metrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.clock_skew);
+    }
+}
+
+/// Clock skew monitor component.
+#[derive(DataSize, Debug)]
+pub(crate) struct ClockSkewMonitor {
+    config: Config,
+    /// Whether the last measured skew exceeded the maximum.
+    excessive: bool,
+    #[data_size(skip)]
+    metrics: Metrics,
+}
+
+impl ClockSkewMonitor {
+    /// Creates a new clock skew monitor, returning the effect taking its first measurement.
+    pub(crate) fn new(
+        config: Config,
+        registry: &Registry,
+    ) -> Result<(Self, Effects<Event>), prometheus::Error> {
+        let metrics = Metrics::new(registry)?;
+        let monitor = ClockSkewMonitor {
+            config,
+            excessive: false,
+            metrics,
+        };
+        let effects = if monitor.config.ntp_servers.is_empty() {
+            Effects::new()
+        } else {
+            monitor.measure()
+        };
+        Ok((monitor, effects))
+    }
+
+    fn measure(&self) -> Effects<Event> {
+        measure_skew(
+            self.config.ntp_servers.clone(),
+            self.config.query_timeout.into(),
+        )
+        .event(Event::Measured)
+    }
+}
+
+impl<REv: Send> Component<REv> for ClockSkewMonitor {
+    type Event = Event;
+    type ConstructionError = prometheus::Error;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::Check => self.measure(),
+            Event::Measured(maybe_skew_millis) => {
+                if let Some(skew_millis) = maybe_skew_millis {
+                    self.metrics.clock_skew.set(skew_millis as f64 / 1000.0);
+                    let excessive = self.config.is_excessive(skew_millis);
+                    if excessive {
+                        warn!(
+                            skew_millis,
+                            max_skew = %self.config.max_skew,
+                            "local clock skew exceeds the maximum"
+                        );
+                    } else if self.excessive {
+                        info!(skew_millis, "local clock is back in sync");
+                    }
+                    self.excessive = excessive;
+                }
+                effect_builder
+                    .set_timeout(self.config.check_interval.into())
+                    .event(|_| Event::Check)
+            }
+        }
+    }
+}
+
+/// Measures the offset of the local clock from the NTP servers' time in milliseconds, positive if
+/// the local clock is ahead.
+///
+/// The servers are queried concurrently, and the median of their offsets is returned, or `None` if
+/// no server responded.
+async fn measure_skew(ntp_servers: Vec<String>, query_timeout: Duration) -> Option<i64> {
+    let queries = ntp_servers.iter().map(|server| async move {
+        let result = tokio::time::timeout(query_timeout, query_skew(server))
+            .await
+            .unwrap_or(Err(SntpError::Timeout));
+        match result {
+            Ok(skew_millis) => {
+                debug!(%server, skew_millis, "queried NTP server");
+                Some(skew_millis)
+            }
+            Err(error) => {
+                debug!(%server, %error, "failed to query NTP server");
+                None
+            }
+        }
+    });
+    let mut skews: Vec<i64> = future::join_all(queries)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    if skews.is_empty() {
+        warn!(
+            ?ntp_servers,
+            "failed to measure clock skew: no NTP server responded"
+        );
+        return None;
+    }
+    skews.sort_unstable();
+    Some(skews[skews.len() / 2])
+}
+
+/// Queries a single NTP server, returning the offset of the local clock in milliseconds.
+async fn query_skew(server: &str) -> Result<i64, SntpError> {
+    let server_address = lookup_host(server)
+        .await?
+        .next()
+        .ok_or(SntpError::NoAddress)?;
+    let local_address = if server_address.is_ipv4() {
+        SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+    } else {
+        SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+    };
+    let socket = UdpSocket::bind(local_address).await?;
+    socket.connect(server_address).await?;
+
+    let sent_at = Timestamp::now();
+    let request = request_packet(sent_at);
+    socket.send(&request).await?;
+    let mut response = [0; PACKET_LENGTH];
+    let length = socket.recv(&mut response).await?;
+    let received_at = Timestamp::now();
+
+    skew_from_response(&request, &response[..length], sent_at, received_at)
+}
+
+/// Returns an SNTP request sent at the given time.
+fn request_packet(sent_at: Timestamp) -> [u8; PACKET_LENGTH] {
+    let mut request = [0; PACKET_LENGTH];
+    request[0] = REQUEST_HEADER;
+    // The server copies the transmit timestamp into its response's originate timestamp.
+    request[40..48].copy_from_slice(&to_ntp_timestamp(sent_at));
+    request
+}
+
+/// Computes the offset of the local clock in milliseconds from a server's response to the request.
+fn skew_from_response(
+    request: &[u8; PACKET_LENGTH],
+    response: &[u8],
+    sent_at: Timestamp,
+    received_at: Timestamp,
+) -> Result<i64, SntpError> {
+    if response.len() < PACKET_LENGTH || response[0] & 0b111 != SERVER_MODE {
+        return Err(SntpError::Malformed);
+    }
+    if response[1] == 0 {
+        return Err(SntpError::KissOfDeath);
+    }
+    if response[24..32] != request[40..48] {
+        return Err(SntpError::Mismatch);
+    }
+    let server_received_at = from_ntp_timestamp(&response[32..40]);
+    let server_sent_at = from_ntp_timestamp(&response[40..48]);
+    let sent_at = sent_at.millis() as i64;
+    let received_at = received_at.millis() as i64;
+    // The standard SNTP offset is the server's time minus the local time, assuming symmetric
+    // network delays.
+    let offset = ((server_received_at - sent_at) + (server_sent_at - received_at)) / 2;
+    Ok(-offset)
+}
+
+/// Converts a timestamp to the 64-bit NTP timestamp format.
+fn to_ntp_timestamp(timestamp: Timestamp) -> [u8; 8] {
+    let millis = timestamp.millis();
+    // Seconds wrap around at the end of each 136-year NTP era.
+    let secs = ((millis / 1000) as i64 + NTP_TO_UNIX_EPOCH_SECS) as u32;
+    let fraction = (((millis % 1000) << 32) / 1000) as u32;
+    let mut bytes = [0; 8];
+    bytes[..4].copy_from_slice(&secs.to_be_bytes());
+    bytes[4..].copy_from_slice(&fraction.to_be_bytes());
+    bytes
+}
+
+/// Converts a 64-bit NTP timestamp to milliseconds since the Unix epoch.
+fn from_ntp_timestamp(bytes: &[u8]) -> i64 {
+    let secs = i64::from(u32::from_be_bytes(bytes[..4].try_into().unwrap()));
+    let fraction = i64::from(u32::from_be_bytes(bytes[4..8].try_into().unwrap()));
+    // Timestamps in the lower half of the range belong to NTP era 1, which starts in 2036.
+    let secs = if secs < 1 << 31 {
+        secs + (1 << 32)
+    } else {
+        secs
+    };
+    (secs - NTP_TO_UNIX_EPOCH_SECS) * 1000 + ((fraction * 1000) >> 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response_to(
+        request: &[u8; PACKET_LENGTH],
+        server_received_at: Timestamp,
+        server_sent_at: Timestamp,
+    ) -> [u8; PACKET_LENGTH] {
+        let mut response = [0; PACKET_LENGTH];
+        response[0] = 0b00_100_000 | SERVER_MODE;
+        response[1] = 2;
+        response[24..32].copy_from_slice(&request[40..48]);
+        response[32..40].copy_from_slice(&to_ntp_timestamp(server_received_at));
+        response[40..48].copy_from_slice(&to_ntp_timestamp(server_sent_at));
+        response
+    }
+
+    #[test]
+    fn should_convert_ntp_timestamps() {
+        let timestamp = Timestamp::from(1_700_000_000_123);
+        assert_eq!(
+            from_ntp_timestamp(&to_ntp_timestamp(timestamp)),
+            timestamp.millis() as i64
+        );
+        // Timestamps after the NTP seconds wrap around in 2036.
+        let timestamp = Timestamp::from(2_200_000_000_999);
+        assert_eq!(
+            from_ntp_timestamp(&to_ntp_timestamp(timestamp)),
+            timestamp.millis() as i64
+        );
+    }
+
+    #[test]
+    fn should_compute_skew() {
+        // The local clock is 5 seconds ahead, and each way takes 100 ms.
+        let sent_at = Timestamp::from(1_700_000_010_000);
+        let received_at = Timestamp::from(1_700_000_010_250);
+        let request = request_packet(sent_at);
+        let response = response_to(
+            &request,
+            Timestamp::from(1_700_000_005_100),
+            Timestamp::from(1_700_000_005_150),
+        );
+        let skew = skew_from_response(&request, &response, sent_at, received_at).unwrap();
+        assert_eq!(skew, 5_000);
+    }
+
+    #[test]
+    fn should_reject_invalid_responses() {
+        let sent_at = Timestamp::from(1_700_000_000_000);
+        let request = request_packet(sent_at);
+        let response = response_to(&request, sent_at, sent_at);
+
+        assert!(matches!(
+            skew_from_response(&request, &response[..40], sent_at, sent_at),
+            Err(SntpError::Malformed)
+        ));
+
+        let mut kiss_of_death = response;
+        kiss_of_death[1] = 0;
+        assert!(matches!(
+            skew_from_response(&request, &kiss_of_death, sent_at, sent_at),
+            Err(SntpError::KissOfDeath)
+        ));
+
+        let other_request = request_packet(Timestamp::from(1_700_000_000_001));
+        assert!(matches!(
+            skew_from_response(&other_request, &response, sent_at, sent_at),
+            Err(SntpError::Mismatch)
+        ));
+    }
+}
@@ -9,6 +9,7 @@ mod era_supervisor;
 #[macro_use]
 mod highway_core;
 pub(crate) mod error;
+mod keystore;
 mod metrics;
 mod protocols;
 #[cfg(test)]
@@ -36,7 +37,7 @@ use crate::{
     components::Component,
     effect::{
         announcements::{BlocklistAnnouncement, ConsensusAnnouncement},
-        diagnostics_port::DumpConsensusStateRequest,
+        diagnostics_port::{DumpConsensusStateRequest, ValidatorModeRequest},
         incoming::ConsensusMessageIncoming,
         requests::{
             BlockProposerRequest, BlockValidationRequest, ChainspecLoaderRequest, ConsensusRequest,
@@ -51,7 +52,7 @@ use crate::{
 };
 
 pub(crate) use cl_context::ClContext;
-pub(crate) use config::{ChainspecConsensusExt, Config};
+pub(crate) use config::{ChainspecConsensusExt, Config, LoadKeysError, ValidatorMode};
 pub(crate) use consensus_protocol::{BlockContext, EraReport, ProposedBlock};
 pub(crate) use era_supervisor::{debug::EraDump, EraSupervisor};
 pub(crate) use protocols::highway::HighwayProtocol;
@@ -132,6 +133,9 @@ pub(crate) enum Event {
     /// Dump state for debugging purposes.
     #[from]
     DumpState(DumpConsensusStateRequest),
+    /// A request to show or change the validator mode.
+    #[from]
+    ValidatorMode(ValidatorModeRequest),
 }
 
 impl Debug for ConsensusMessage {
@@ -233,6 +237,7 @@ impl Display for Event {
                 write!(f, "new upgrade activation point: {:?}", activation_point)
             }
             Event::DumpState(req) => Display::fmt(req, f),
+            Event::ValidatorMode(req) => Display::fmt(req, f),
         }
     }
 }
@@ -346,6 +351,12 @@ where
                     Err(err) => req.answer(Err(err)).ignore(),
                 }
             }
+            Event::ValidatorMode(ValidatorModeRequest { update, responder }) => {
+                if let Some(mode) = update {
+                    self.set_next_validator_mode(mode);
+                }
+                responder.respond(Ok(self.validator_mode_status())).ignore()
+            }
         }
     }
 }
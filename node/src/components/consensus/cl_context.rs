@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use casper_hashing::Digest;
-use casper_types::{crypto, PublicKey, SecretKey, Signature};
+use casper_types::{crypto, PublicKey, SecretKey, Signature, SigningContext};
 
 use crate::{
     components::consensus::traits::{ConsensusValueT, Context, ValidatorSecret},
@@ -16,13 +16,20 @@ use crate::{
 pub struct Keypair {
     secret_key: Arc<SecretKey>,
     public_key: PublicKey,
+    /// Whether to sign in the consensus signing context.
+    signing_context_active: bool,
 }
 
 impl Keypair {
-    pub(crate) fn new(secret_key: Arc<SecretKey>, public_key: PublicKey) -> Self {
+    pub(crate) fn new(
+        secret_key: Arc<SecretKey>,
+        public_key: PublicKey,
+        signing_context_active: bool,
+    ) -> Self {
         Self {
             secret_key,
             public_key,
+            signing_context_active,
         }
     }
 }
@@ -30,7 +37,7 @@ impl Keypair {
 impl From<Arc<SecretKey>> for Keypair {
     fn from(secret_key: Arc<SecretKey>) -> Self {
         let public_key: PublicKey = secret_key.as_ref().into();
-        Self::new(secret_key, public_key)
+        Self::new(secret_key, public_key, true)
     }
 }
 
@@ -39,7 +46,13 @@ impl ValidatorSecret for Keypair {
     type Signature = Signature;
 
     fn sign(&self, hash: &Digest) -> Signature {
-        crypto::sign(hash, self.secret_key.as_ref(), &self.public_key)
+        crypto::sign_with_context_if_active(
+            hash,
+            SigningContext::Consensus,
+            self.signing_context_active,
+            self.secret_key.as_ref(),
+            &self.public_key,
+        )
     }
 }
 
@@ -65,8 +78,19 @@ impl Context for ClContext {
         Digest::hash(data)
     }
 
-    fn verify_signature(hash: &Digest, public_key: &PublicKey, signature: &Signature) -> bool {
-        if let Err(error) = crypto::verify(hash, signature, public_key) {
+    fn verify_signature(
+        hash: &Digest,
+        public_key: &PublicKey,
+        signature: &Signature,
+        signing_context_active: bool,
+    ) -> bool {
+        if let Err(error) = crypto::verify_with_context_if_active(
+            hash,
+            SigningContext::Consensus,
+            signing_context_active,
+            signature,
+            public_key,
+        ) {
             info!(%error, %signature, %public_key, %hash, "failed to validate signature");
             return false;
         }
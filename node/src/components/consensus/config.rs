@@ -1,46 +1,185 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    env,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
 use datasize::DataSize;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use casper_types::{PublicKey, SecretKey};
+use casper_types::{crypto, PublicKey, SecretKey};
 
 use crate::{
     components::consensus::{
-        era_supervisor::PAST_OPEN_ERAS, protocols::highway::config::Config as HighwayConfig, EraId,
+        era_supervisor::PAST_OPEN_ERAS,
+        keystore::{Keystore, KeystoreError},
+        protocols::highway::config::Config as HighwayConfig,
+        EraId,
     },
     types::Chainspec,
-    utils::{External, LoadError, Loadable},
+    utils::{External, LoadError},
 };
 
 /// Consensus configuration.
-#[derive(DataSize, Debug, Deserialize, Clone)]
+#[derive(DataSize, Debug, Deserialize, Serialize, Clone)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
 pub(crate) struct Config {
     /// Path to secret key file.
+    #[serde(default)]
     pub(crate) secret_key_path: External,
+    /// Where to read the passphrase from if the secret key file is encrypted.
+    #[serde(default)]
+    pub(crate) secret_key_passphrase: Option<PassphraseSource>,
+    /// Keystore to load the secret key from instead of `secret_key_path`.
+    #[serde(default)]
+    pub(crate) keystore: Option<Keystore>,
     /// Highway-specific node configuration.
     pub(crate) highway: HighwayConfig,
+    /// Whether to take part in consensus in eras in which this node is a validator.
+    #[serde(default)]
+    pub(crate) validator_mode: ValidatorMode,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             secret_key_path: External::Missing,
+            secret_key_passphrase: None,
+            keystore: None,
             highway: HighwayConfig::default(),
+            validator_mode: ValidatorMode::default(),
+        }
+    }
+}
+
+/// Whether a node with a validator key takes part in consensus.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ValidatorMode {
+    /// Create units and sign finalized blocks in eras in which we are a validator.
+    Active,
+    /// Only follow the chain, e.g. as the standby of a redundant validator setup.
+    Observer,
+}
+
+impl Default for ValidatorMode {
+    fn default() -> Self {
+        ValidatorMode::Active
+    }
+}
+
+impl Display for ValidatorMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidatorMode::Active => f.write_str("active"),
+            ValidatorMode::Observer => f.write_str("observer"),
+        }
+    }
+}
+
+impl FromStr for ValidatorMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "active" => Ok(ValidatorMode::Active),
+            "observer" => Ok(ValidatorMode::Observer),
+            _ => Err("invalid validator mode, must be one of 'active', 'observer'"),
         }
     }
 }
 
+/// The source of the passphrase of an encrypted secret key file.
+#[derive(Clone, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PassphraseSource {
+    /// Read the passphrase from a file, ignoring any trailing newline.
+    File(PathBuf),
+    /// Read the passphrase from the given environment variable.
+    EnvVar(String),
+    /// Prompt for the passphrase on the terminal.
+    Prompt,
+}
+
+impl PassphraseSource {
+    /// Reads the passphrase, resolving a relative file path from `root`.
+    pub(crate) fn read(&self, root: &Path) -> Result<String, PassphraseError> {
+        match self {
+            PassphraseSource::File(path) => {
+                let full_path = root.join(path);
+                let contents =
+                    fs::read_to_string(&full_path).map_err(|error| PassphraseError::File {
+                        path: full_path,
+                        error,
+                    })?;
+                Ok(contents.trim_end_matches(&['\r', '\n'][..]).to_string())
+            }
+            PassphraseSource::EnvVar(name) => {
+                env::var(name).map_err(|error| PassphraseError::EnvVar {
+                    name: name.clone(),
+                    error,
+                })
+            }
+            PassphraseSource::Prompt => {
+                rpassword::read_password_from_tty(Some("Secret key passphrase: "))
+                    .map_err(PassphraseError::Prompt)
+            }
+        }
+    }
+}
+
+/// Error reading the passphrase of an encrypted secret key file.
+#[derive(Debug, Error)]
+pub enum PassphraseError {
+    /// Failed to read the passphrase file.
+    #[error("could not read passphrase file {}: {error}", .path.display())]
+    File { path: PathBuf, error: io::Error },
+    /// Failed to read the passphrase environment variable.
+    #[error("could not read passphrase environment variable {name}: {error}")]
+    EnvVar { name: String, error: env::VarError },
+    /// Failed to prompt for the passphrase.
+    #[error("could not prompt for passphrase: {0}")]
+    Prompt(io::Error),
+}
+
+/// Error loading the consensus keys.
+#[derive(Debug, Error)]
+pub enum LoadKeysError {
+    /// Failed to load the secret key.
+    #[error(transparent)]
+    SecretKey(#[from] LoadError<crypto::ErrorExt>),
+    /// Failed to read the passphrase of the secret key.
+    #[error(transparent)]
+    Passphrase(#[from] PassphraseError),
+    /// Failed to load the secret key from the keystore.
+    #[error(transparent)]
+    Keystore(#[from] KeystoreError),
+}
+
 impl Config {
-    /// Loads the secret key from the configuration file and derives the public key.
-    #[allow(clippy::type_complexity)]
+    /// Loads the secret key from the keystore if configured, otherwise from the secret key file,
+    /// decrypting it if a passphrase source is configured, and derives the public key.
     pub(crate) fn load_keys<P: AsRef<Path>>(
         &self,
         root: P,
-    ) -> Result<(Arc<SecretKey>, PublicKey), LoadError<<Arc<SecretKey> as Loadable>::Error>> {
-        let secret_signing_key: Arc<SecretKey> = self.secret_key_path.clone().load(root)?;
+    ) -> Result<(Arc<SecretKey>, PublicKey), LoadKeysError> {
+        let root = root.as_ref();
+        let secret_signing_key: Arc<SecretKey> = match (&self.keystore, &self.secret_key_passphrase)
+        {
+            (Some(keystore), _) => Arc::new(keystore.load_secret_key(root)?),
+            (None, None) => self.secret_key_path.clone().load(root)?,
+            (None, Some(source)) => {
+                let passphrase = source.read(root)?;
+                self.secret_key_path.clone().load_with(root, |path| {
+                    SecretKey::from_encrypted_file(path, &passphrase).map(Arc::new)
+                })?
+            }
+        };
         let public_key: PublicKey = PublicKey::from(secret_signing_key.as_ref());
         Ok((secret_signing_key, public_key))
     }
@@ -63,7 +202,7 @@ pub trait ChainspecConsensusExt {
 
 impl ChainspecConsensusExt for Chainspec {
     fn activation_era(&self) -> EraId {
-        self.protocol_config.activation_point.era_id()
+        self.protocol_config.activation_era()
     }
 
     fn earliest_open_era(&self, current_era: EraId) -> EraId {
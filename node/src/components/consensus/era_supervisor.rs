@@ -24,7 +24,7 @@ use futures::FutureExt;
 use itertools::Itertools;
 use prometheus::Registry;
 use rand::Rng;
-use tracing::{debug, error, info, trace, warn};
+use tracing::{debug, debug_span, error, info, trace, warn};
 
 use casper_hashing::Digest;
 use casper_types::{AsymmetricType, EraId, PublicKey, SecretKey, TimeDiff, Timestamp, U512};
@@ -40,12 +40,13 @@ use crate::{
             metrics::Metrics,
             validator_change::{ValidatorChange, ValidatorChanges},
             ActionId, ChainspecConsensusExt, Config, ConsensusMessage, Event, NewBlockPayload,
-            ReactorEventT, ResolveValidity, TimerId,
+            ReactorEventT, ResolveValidity, TimerId, ValidatorMode,
         },
         storage::Storage,
     },
     effect::{
         announcements::ControlAnnouncement,
+        diagnostics_port::ValidatorModeStatus,
         requests::{BlockValidationRequest, ContractRuntimeRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects, Responder,
     },
@@ -75,6 +76,7 @@ pub(super) const PAST_OPEN_ERAS: u64 = 2 * PAST_EVIDENCE_ERAS;
 
 type ConsensusConstructor = dyn Fn(
         Digest,                    // the era's unique instance ID
+        EraId,                     // the era's ID
         BTreeMap<PublicKey, U512>, // validator weights
         &HashSet<PublicKey>,       /* faulty validators that are banned in
                                     * this era */
@@ -130,6 +132,10 @@ pub struct EraSupervisor {
     next_upgrade_activation_point: Option<ActivationPoint>,
     /// The era that was current when this node joined the network.
     era_where_we_joined: EraId,
+    /// Whether we take part in consensus in the current era, if we are one of its validators.
+    validator_mode: ValidatorMode,
+    /// The validator mode to switch to when the next era begins.
+    next_validator_mode: ValidatorMode,
 }
 
 impl Debug for EraSupervisor {
@@ -172,6 +178,7 @@ impl EraSupervisor {
         #[allow(clippy::integer_arithmetic)] // Block height should never reach u64::MAX.
         let next_height = latest_block_header.height() + 1;
 
+        let validator_mode = config.validator_mode;
         let mut era_supervisor = Self {
             open_eras: Default::default(),
             secret_signing_key,
@@ -186,6 +193,8 @@ impl EraSupervisor {
             next_upgrade_activation_point,
             next_executed_height: next_height,
             era_where_we_joined: current_era,
+            validator_mode,
+            next_validator_mode: validator_mode,
         };
 
         // Collect the information needed to initialize all open eras.
@@ -331,9 +340,15 @@ impl EraSupervisor {
         rng: &mut NodeRng,
         switch_blocks: &[BlockHeader],
     ) -> Effects<Event> {
+        let prev_era = self.current_era;
         match self.create_new_era(switch_blocks) {
             Ok((era_id, outcomes)) => {
-                self.handle_consensus_outcomes(effect_builder, rng, era_id, outcomes)
+                let mut effects =
+                    self.handle_consensus_outcomes(effect_builder, rng, era_id, outcomes);
+                if self.current_era > prev_era {
+                    effects.extend(self.announce_orphaned_deploys(effect_builder, prev_era));
+                }
+                effects
             }
             Err(err) => fatal!(
                 effect_builder,
@@ -344,6 +359,30 @@ impl EraSupervisor {
         }
     }
 
+    /// Announces the deploys that were proposed in the given era but never finalized, so they can
+    /// be proposed again in the next one.
+    fn announce_orphaned_deploys<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+    ) -> Effects<Event> {
+        let orphaned: Vec<DeployHash> = match self.open_eras.get_mut(&era_id) {
+            Some(era) => era.proposed_deploys.drain().collect(),
+            None => return Effects::new(),
+        };
+        if orphaned.is_empty() {
+            return Effects::new();
+        }
+        info!(
+            era = era_id.value(),
+            count = orphaned.len(),
+            "deploys of unfinalized proposals orphaned by era change"
+        );
+        effect_builder
+            .announce_orphaned_deploys(era_id, orphaned)
+            .ignore()
+    }
+
     /// Initializes a new era. The switch blocks must contain the most recent `auction_delay + 1`
     /// ones, in order, but at most as far back as to the last activation point.
     fn create_new_era(
@@ -452,6 +491,7 @@ impl EraSupervisor {
         // Create and insert the new era instance.
         let (consensus, mut outcomes) = (self.new_consensus)(
             instance_id,
+            era_id,
             validators.clone(),
             &faulty,
             &inactive,
@@ -485,12 +525,28 @@ impl EraSupervisor {
             self.current_era = era_id;
             self.metrics.current_era.set(era_id.value() as i64);
             self.next_block_height = self.next_block_height.max(start_height);
+            if self.validator_mode != self.next_validator_mode {
+                info!(
+                    era = era_id.value(),
+                    mode = %self.next_validator_mode,
+                    "switching validator mode"
+                );
+                self.validator_mode = self.next_validator_mode;
+            }
             outcomes.extend(self.era_mut(era_id).consensus.handle_is_current(now));
             if !self.era(era_id).validators().contains_key(&our_id) {
                 info!(era = era_id.value(), %our_id, "not voting; not a validator");
+            } else if self.validator_mode == ValidatorMode::Observer {
+                info!(era = era_id.value(), %our_id, "not voting; observer mode");
             } else {
                 info!(era = era_id.value(), %our_id, "start voting");
-                let secret = Keypair::new(self.secret_signing_key.clone(), our_id.clone());
+                let signing_context_active =
+                    self.chainspec.core_config.is_signing_context_active(era_id);
+                let secret = Keypair::new(
+                    self.secret_signing_key.clone(),
+                    our_id.clone(),
+                    signing_context_active,
+                );
                 let unit_hash_file = self.unit_file(&instance_id);
                 outcomes.extend(self.era_mut(era_id).consensus.activate_validator(
                     our_id,
@@ -575,6 +631,7 @@ impl EraSupervisor {
                 Effects::new()
             }
             Some(era) => {
+                let _era_span = debug_span!("consensus", era = era_id.value()).entered();
                 let outcomes = f(&mut *era.consensus, rng);
                 self.handle_consensus_outcomes(effect_builder, rng, era_id, outcomes)
             }
@@ -657,6 +714,7 @@ impl EraSupervisor {
             warn!(era = era_id.value(), "new block payload in outdated era");
             return Effects::new();
         }
+        self.era_mut(era_id).add_proposed_deploys(&block_payload);
         let proposed_block = ProposedBlock::new(block_payload, block_context);
         self.delegate_to_era(effect_builder, rng, era_id, move |consensus, _| {
             consensus.propose(proposed_block, Timestamp::now())
@@ -672,13 +730,16 @@ impl EraSupervisor {
         let our_sk = self.secret_signing_key.clone();
         let era_id = block_header.era_id();
         self.executed_block(&block_header);
-        let mut effects = if self.is_validator_in(&our_pk, era_id) {
+        let mut effects = if self.validator_mode == ValidatorMode::Active
+            && self.is_validator_in(&our_pk, era_id)
+        {
             effect_builder
                 .announce_created_finality_signature(FinalitySignature::new(
                     block_header.hash(),
                     era_id,
                     &our_sk,
                     our_pk,
+                    self.chainspec.core_config.is_signing_context_active(era_id),
                 ))
                 .ignore()
         } else {
@@ -705,7 +766,7 @@ impl EraSupervisor {
                 error!(era = era_id.value(), %block_header, "executed block in uninitialized era");
             }
             // If it's not the last block before an upgrade, initialize the next era.
-            if !self.should_upgrade_after(&era_id) {
+            if !self.should_upgrade_after(&era_id, block_header.timestamp()) {
                 let new_era_id = era_id.successor();
                 let effect = get_switch_blocks(self.chainspec.clone(), effect_builder, new_era_id)
                     .event(move |switch_blocks| Event::CreateNewEra { switch_blocks });
@@ -765,6 +826,11 @@ impl EraSupervisor {
             );
             effects.extend(self.disconnect(effect_builder, sender));
         }
+        if let Some(era) = self.open_eras.get_mut(&era_id) {
+            if valid {
+                era.add_proposed_deploys(proposed_block.value());
+            }
+        }
         if self
             .open_eras
             .get_mut(&era_id)
@@ -915,6 +981,7 @@ impl EraSupervisor {
                     inactive_validators: tbd.inactive_validators,
                 });
                 let proposed_block = Arc::try_unwrap(value).unwrap_or_else(|arc| (*arc).clone());
+                era.remove_finalized_deploys(&proposed_block);
                 let finalized_approvals: HashMap<_, _> = proposed_block
                     .deploys()
                     .iter()
@@ -1088,10 +1155,30 @@ impl EraSupervisor {
             .ignore()
     }
 
-    pub(super) fn should_upgrade_after(&self, era_id: &EraId) -> bool {
+    pub(super) fn should_upgrade_after(&self, era_id: &EraId, era_end: Timestamp) -> bool {
         match self.next_upgrade_activation_point {
             None => false,
-            Some(upgrade_point) => upgrade_point.should_upgrade(era_id),
+            Some(upgrade_point) => upgrade_point.should_upgrade(era_id, era_end),
+        }
+    }
+
+    /// Sets the validator mode to switch to when the next era begins.
+    pub(super) fn set_next_validator_mode(&mut self, mode: ValidatorMode) {
+        info!(
+            current_era = self.current_era.value(),
+            %mode,
+            "validator mode will change with the next era"
+        );
+        self.next_validator_mode = mode;
+    }
+
+    /// Returns the current validator mode and the one to switch to when the next era begins.
+    pub(super) fn validator_mode_status(&self) -> ValidatorModeStatus {
+        ValidatorModeStatus {
+            current_era: self.current_era,
+            is_validator: self.is_validator_in(&self.public_signing_key, self.current_era),
+            mode: self.validator_mode,
+            next_era_mode: self.next_validator_mode,
         }
     }
 
@@ -1104,6 +1191,14 @@ impl EraSupervisor {
     pub(crate) fn current_era(&self) -> EraId {
         self.current_era
     }
+
+    /// Returns the estimated heap memory usage of each open era.
+    pub(crate) fn estimate_heap_size_breakdown(&self) -> Vec<(String, usize)> {
+        self.open_eras
+            .iter()
+            .map(|(era_id, era)| (format!("era_{}", era_id.value()), era.estimate_heap_size()))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -10,10 +10,13 @@ use tracing::{debug, warn};
 
 use casper_types::{PublicKey, Timestamp, U512};
 
-use crate::components::consensus::{
-    cl_context::ClContext,
-    consensus_protocol::{ConsensusProtocol, ProposedBlock},
-    protocols::highway::HighwayProtocol,
+use crate::{
+    components::consensus::{
+        cl_context::ClContext,
+        consensus_protocol::{ConsensusProtocol, ProposedBlock},
+        protocols::highway::HighwayProtocol,
+    },
+    types::{BlockPayload, DeployHash},
 };
 
 const CASPER_ENABLE_DETAILED_CONSENSUS_METRICS_ENV_VAR: &str =
@@ -61,6 +64,8 @@ pub struct Era {
     pub(crate) accusations: HashSet<PublicKey>,
     /// The validator weights.
     pub(crate) validators: BTreeMap<PublicKey, U512>,
+    /// Deploys contained in valid proposals in this era that have not been finalized yet.
+    pub(crate) proposed_deploys: HashSet<DeployHash>,
 }
 
 impl Era {
@@ -81,6 +86,7 @@ impl Era {
             cannot_propose,
             accusations: HashSet::new(),
             validators,
+            proposed_deploys: HashSet::new(),
         }
     }
 
@@ -134,6 +140,25 @@ impl Era {
         self.validation_states.remove(proposed_block).is_some()
     }
 
+    /// Records the deploys and transfers of a valid proposal.
+    pub(crate) fn add_proposed_deploys(&mut self, block_payload: &BlockPayload) {
+        self.proposed_deploys.extend(
+            block_payload
+                .deploy_hashes()
+                .chain(block_payload.transfer_hashes()),
+        );
+    }
+
+    /// Forgets the deploys and transfers of a finalized block.
+    pub(crate) fn remove_finalized_deploys(&mut self, block_payload: &BlockPayload) {
+        for deploy_hash in block_payload
+            .deploy_hashes()
+            .chain(block_payload.transfer_hashes())
+        {
+            self.proposed_deploys.remove(deploy_hash);
+        }
+    }
+
     /// Adds new accusations from a finalized block.
     pub(crate) fn add_accusations(&mut self, accusations: &[PublicKey]) {
         for pub_key in accusations {
@@ -176,6 +201,7 @@ impl DataSize for Era {
             cannot_propose,
             accusations,
             validators,
+            proposed_deploys,
         } = self;
 
         // `DataSize` cannot be made object safe due its use of associated constants. We implement
@@ -212,5 +238,6 @@ impl DataSize for Era {
             .saturating_add(cannot_propose.estimate_heap_size())
             .saturating_add(accusations.estimate_heap_size())
             .saturating_add(validators.estimate_heap_size())
+            .saturating_add(proposed_deploys.estimate_heap_size())
     }
 }
@@ -7,7 +7,7 @@ use std::{
 };
 
 use datasize::DataSize;
-use tracing::{error, info, trace, warn};
+use tracing::{error, info, info_span, trace, warn};
 
 use casper_types::{TimeDiff, Timestamp};
 
@@ -190,6 +190,8 @@ impl<C: Context> ActiveValidator<C> {
         let r_exp = self.round_exp(state, timestamp);
         let r_id = state::round_id(timestamp, r_exp);
         let r_len = state::round_len(r_exp);
+        let _round_span =
+            info_span!("consensus_round", round_id = %r_id, round_len = %r_len).entered();
         // Only create new units if enough validators are online.
         if !self.paused && self.enough_validators_online(state, timestamp) {
             if timestamp == r_id && state.leader(r_id) == self.vidx {
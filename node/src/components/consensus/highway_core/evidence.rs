@@ -90,7 +90,7 @@ impl<C: Context> Evidence<C> {
     ) -> Result<(), EvidenceError> {
         match self {
             Evidence::Equivocation(unit1, unit2) => {
-                Self::validate_equivocation(unit1, unit2, instance_id, validators)
+                Self::validate_equivocation(unit1, unit2, instance_id, validators, params)
             }
             Evidence::Endorsements {
                 endorsement1,
@@ -121,10 +121,20 @@ impl<C: Context> Evidence<C> {
                     swimlane2.last().unwrap_or(unit2),
                     instance_id,
                     validators,
+                    params,
                 )?;
-                if !C::verify_signature(&endorsement1.hash(), v_id, endorsement1.signature())
-                    || !C::verify_signature(&endorsement2.hash(), v_id, endorsement2.signature())
-                {
+                let signing_context_active = params.signing_context_active();
+                if !C::verify_signature(
+                    &endorsement1.hash(),
+                    v_id,
+                    endorsement1.signature(),
+                    signing_context_active,
+                ) || !C::verify_signature(
+                    &endorsement2.hash(),
+                    v_id,
+                    endorsement2.signature(),
+                    signing_context_active,
+                ) {
                     return Err(EvidenceError::Signature);
                 }
                 Ok(())
@@ -137,6 +147,7 @@ impl<C: Context> Evidence<C> {
         unit2: &SignedWireUnit<C>,
         instance_id: &C::InstanceId,
         validators: &Validators<C::ValidatorId>,
+        params: &Params,
     ) -> Result<(), EvidenceError> {
         let wunit1 = unit1.wire_unit();
         let wunit2 = unit2.wire_unit();
@@ -155,9 +166,18 @@ impl<C: Context> Evidence<C> {
         if unit1 == unit2 {
             return Err(EvidenceError::EquivocationSameUnit);
         }
-        if !C::verify_signature(&unit1.hash(), v_id, &unit1.signature)
-            || !C::verify_signature(&unit2.hash(), v_id, &unit2.signature)
-        {
+        let signing_context_active = params.signing_context_active();
+        if !C::verify_signature(
+            &unit1.hash(),
+            v_id,
+            &unit1.signature,
+            signing_context_active,
+        ) || !C::verify_signature(
+            &unit2.hash(),
+            v_id,
+            &unit2.signature,
+            signing_context_active,
+        ) {
             return Err(EvidenceError::Signature);
         }
         Ok(())
@@ -561,6 +561,7 @@ impl<C: Context> Highway<C> {
     /// Performs initial validation and returns an error if `vertex` is invalid. (See
     /// `PreValidatedVertex` and `validate_vertex`.)
     fn do_pre_validate_vertex(&self, vertex: &Vertex<C>) -> Result<(), VertexError> {
+        let signing_context_active = self.state.params().signing_context_active();
         match vertex {
             Vertex::Unit(unit) => {
                 let creator = unit.wire_unit().creator;
@@ -568,7 +569,8 @@ impl<C: Context> Highway<C> {
                 if unit.wire_unit().instance_id != self.instance_id {
                     return Err(UnitError::InstanceId.into());
                 }
-                if !C::verify_signature(&unit.hash(), v_id, &unit.signature) {
+                if !C::verify_signature(&unit.hash(), v_id, &unit.signature, signing_context_active)
+                {
                     return Err(UnitError::Signature.into());
                 }
                 Ok(self.state.pre_validate_unit(unit)?)
@@ -590,13 +592,20 @@ impl<C: Context> Highway<C> {
                         return Err(EndorsementError::Banned.into());
                     }
                     let endorsement: Endorsement<C> = Endorsement::new(unit, *creator);
-                    if !C::verify_signature(&endorsement.hash(), v_id, signature) {
+                    if !C::verify_signature(
+                        &endorsement.hash(),
+                        v_id,
+                        signature,
+                        signing_context_active,
+                    ) {
                         return Err(EndorsementError::Signature.into());
                     }
                 }
                 Ok(())
             }
-            Vertex::Ping(ping) => ping.validate(&self.validators, &self.instance_id),
+            Vertex::Ping(ping) => {
+                ping.validate(&self.validators, &self.instance_id, signing_context_active)
+            }
         }
     }
 
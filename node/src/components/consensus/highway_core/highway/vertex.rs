@@ -366,6 +366,7 @@ impl<C: Context> Ping<C> {
         &self,
         validators: &Validators<C::ValidatorId>,
         our_instance_id: &C::InstanceId,
+        signing_context_active: bool,
     ) -> Result<(), VertexError> {
         let Ping {
             creator,
@@ -378,7 +379,7 @@ impl<C: Context> Ping<C> {
         }
         let v_id = validators.id(self.creator).ok_or(PingError::Creator)?;
         let hash = Self::hash(*creator, *timestamp, *instance_id);
-        if !C::verify_signature(&hash, v_id, signature) {
+        if !C::verify_signature(&hash, v_id, signature, signing_context_active) {
             return Err(PingError::Signature.into());
         }
         Ok(())
@@ -721,6 +721,7 @@ fn test_params() -> Params {
         Timestamp::zero(),
         Timestamp::zero(), // Length depends only on block number.
         TEST_ENDORSEMENT_EVIDENCE_LIMIT,
+        true, // signing context active
     )
 }
 
@@ -1054,6 +1055,7 @@ impl Context for TestContext {
         hash: &Self::Hash,
         public_key: &Self::ValidatorId,
         signature: &<Self::ValidatorSecret as ValidatorSecret>::Signature,
+        _signing_context_active: bool,
     ) -> bool {
         let computed_signature = hash.0 + public_key.0;
         computed_signature == signature.0
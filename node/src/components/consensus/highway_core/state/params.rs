@@ -16,6 +16,7 @@ pub(crate) struct Params {
     start_timestamp: Timestamp,
     end_timestamp: Timestamp,
     endorsement_evidence_limit: u64,
+    signing_context_active: bool,
 }
 
 impl Params {
@@ -37,6 +38,10 @@ impl Params {
     /// * `end_height`, `end_timestamp`: The last block will be the first one that has at least the
     ///   specified height _and_ is no earlier than the specified timestamp. No children of this
     ///   block can be proposed.
+    /// * `endorsement_evidence_limit`: The maximum number of additional units included in evidence
+    ///   for conflicting endorsements.
+    /// * `signing_context_active`: Whether units, endorsements and pings must be signed in the
+    ///   consensus signing context.
     #[allow(clippy::too_many_arguments)] // FIXME
     pub(crate) fn new(
         seed: u64,
@@ -49,6 +54,7 @@ impl Params {
         start_timestamp: Timestamp,
         end_timestamp: Timestamp,
         endorsement_evidence_limit: u64,
+        signing_context_active: bool,
     ) -> Params {
         assert!(
             reduced_block_reward <= block_reward,
@@ -65,6 +71,7 @@ impl Params {
             start_timestamp,
             end_timestamp,
             endorsement_evidence_limit,
+            signing_context_active,
         }
     }
 
@@ -132,6 +139,12 @@ impl Params {
     pub(crate) fn endorsement_evidence_limit(&self) -> u64 {
         self.endorsement_evidence_limit
     }
+
+    /// Returns whether signatures must be in the consensus signing context. Otherwise signatures
+    /// without a context are accepted as well, and our own are created without one.
+    pub(crate) fn signing_context_active(&self) -> bool {
+        self.signing_context_active
+    }
 }
 
 #[cfg(test)]
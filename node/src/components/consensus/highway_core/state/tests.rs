@@ -82,6 +82,7 @@ impl Context for TestContext {
         hash: &Self::Hash,
         public_key: &Self::ValidatorId,
         signature: &<Self::ValidatorSecret as ValidatorSecret>::Signature,
+        _signing_context_active: bool,
     ) -> bool {
         let computed_signature = hash + u64::from(*public_key);
         computed_signature == *signature
@@ -131,6 +132,7 @@ pub(crate) fn test_params(seed: u64) -> Params {
         Timestamp::from(0),
         Timestamp::from(0),
         TEST_ENDORSEMENT_EVIDENCE_LIMIT,
+        true,
     )
 }
 
@@ -249,6 +251,7 @@ fn ban_and_mark_faulty() -> Result<(), AddUnitError<TestContext>> {
         Timestamp::zero(),
         Timestamp::from(u64::MAX),
         TEST_ENDORSEMENT_EVIDENCE_LIMIT,
+        true,
     );
     // Everyone already knows Alice is faulty, so she is banned.
     let mut state = State::new(WEIGHTS, params, vec![ALICE], vec![]);
@@ -0,0 +1,172 @@
+//! Keystores the validator's secret key can be loaded from instead of a PEM file on disk.
+//!
+//! The OS keychain backend is only available if the node is built with the `os-keychain` feature,
+//! as it links against the platform's keychain libraries.
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use age::secrecy::Secret;
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_types::{crypto, SecretKey};
+
+use super::config::{PassphraseError, PassphraseSource};
+
+/// A keystore holding the validator's PEM-encoded secret key.
+#[derive(Clone, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub(crate) enum Keystore {
+    /// An entry of the OS keychain: the Secret Service on Linux, or the Keychain on macOS.
+    OsKeychain {
+        /// The service name of the entry.
+        service: String,
+        /// The account name of the entry.
+        account: String,
+    },
+    /// A file encrypted with a passphrase using `age`, which derives the key via scrypt.
+    AgeFile {
+        /// Path to the encrypted file, absolute or relative to the config file.
+        path: PathBuf,
+        /// Where to read the passphrase from.
+        passphrase: PassphraseSource,
+    },
+}
+
+/// Error loading the secret key from a keystore.
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    /// Failed to read the keystore file.
+    #[error("could not read keystore file {}: {error}", .path.display())]
+    File { path: PathBuf, error: io::Error },
+    /// The keystore file is not encrypted with a passphrase, but e.g. for a recipient's key.
+    #[error("keystore file {} is not encrypted with a passphrase", .path.display())]
+    NotPassphraseEncrypted { path: PathBuf },
+    /// Failed to decrypt the keystore file.
+    #[error("could not decrypt keystore file {}: {error}", .path.display())]
+    Decrypt {
+        path: PathBuf,
+        error: age::DecryptError,
+    },
+    /// Failed to read the passphrase of the keystore file.
+    #[error(transparent)]
+    Passphrase(#[from] PassphraseError),
+    /// Failed to read the entry from the OS keychain.
+    #[cfg(feature = "os-keychain")]
+    #[error("could not read entry {service}/{account} from the OS keychain: {error}")]
+    Keychain {
+        service: String,
+        account: String,
+        error: keyring::Error,
+    },
+    /// The node was built without support for the OS keychain.
+    #[cfg(not(feature = "os-keychain"))]
+    #[error("the OS keychain keystore requires building with the `os-keychain` feature")]
+    KeychainUnsupported,
+    /// The keystore does not hold a valid secret key.
+    #[error("invalid secret key in keystore: {0}")]
+    SecretKey(crypto::ErrorExt),
+}
+
+impl Keystore {
+    /// Loads the secret key, resolving relative paths from `root`.
+    pub(crate) fn load_secret_key(&self, root: &Path) -> Result<SecretKey, KeystoreError> {
+        let pem = match self {
+            Keystore::OsKeychain { service, account } => read_os_keychain(service, account)?,
+            Keystore::AgeFile { path, passphrase } => {
+                let passphrase = passphrase.read(root)?;
+                decrypt_age_file(&root.join(path), passphrase)?
+            }
+        };
+        SecretKey::from_pem(pem).map_err(KeystoreError::SecretKey)
+    }
+}
+
+/// Reads the password stored in an OS keychain entry.
+#[cfg(feature = "os-keychain")]
+fn read_os_keychain(service: &str, account: &str) -> Result<Vec<u8>, KeystoreError> {
+    keyring::Entry::new(service, account)
+        .get_password()
+        .map(String::into_bytes)
+        .map_err(|error| KeystoreError::Keychain {
+            service: service.to_string(),
+            account: account.to_string(),
+            error,
+        })
+}
+
+#[cfg(not(feature = "os-keychain"))]
+fn read_os_keychain(_service: &str, _account: &str) -> Result<Vec<u8>, KeystoreError> {
+    Err(KeystoreError::KeychainUnsupported)
+}
+
+/// Decrypts a passphrase-encrypted `age` file.
+fn decrypt_age_file(path: &Path, passphrase: String) -> Result<Vec<u8>, KeystoreError> {
+    let file_error = |error| KeystoreError::File {
+        path: path.to_owned(),
+        error,
+    };
+    let decrypt_error = |error| KeystoreError::Decrypt {
+        path: path.to_owned(),
+        error,
+    };
+
+    let encrypted = fs::read(path).map_err(file_error)?;
+    let decryptor = match age::Decryptor::new(&encrypted[..]).map_err(decrypt_error)? {
+        age::Decryptor::Passphrase(decryptor) => decryptor,
+        age::Decryptor::Recipients(_) => {
+            return Err(KeystoreError::NotPassphraseEncrypted {
+                path: path.to_owned(),
+            })
+        }
+    };
+    let mut reader = decryptor
+        .decrypt(&Secret::new(passphrase), None)
+        .map_err(decrypt_error)?;
+    let mut decrypted = Vec::new();
+    reader.read_to_end(&mut decrypted).map_err(file_error)?;
+    Ok(decrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use casper_types::testing::TestRng;
+
+    #[test]
+    fn should_load_secret_key_from_age_file() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let tempdir = tempfile::tempdir().unwrap();
+
+        let mut encrypted = Vec::new();
+        let encryptor = age::Encryptor::with_user_passphrase(Secret::new("hunter2".to_string()));
+        let mut writer = encryptor.wrap_output(&mut encrypted).unwrap();
+        writer
+            .write_all(secret_key.to_pem().unwrap().as_bytes())
+            .unwrap();
+        writer.finish().unwrap();
+        fs::write(tempdir.path().join("secret_key.age"), encrypted).unwrap();
+        fs::write(tempdir.path().join("passphrase"), "hunter2\n").unwrap();
+
+        let keystore = Keystore::AgeFile {
+            path: "secret_key.age".into(),
+            passphrase: PassphraseSource::File("passphrase".into()),
+        };
+        let loaded = keystore.load_secret_key(tempdir.path()).unwrap();
+        assert_eq!(loaded.to_pem().unwrap(), secret_key.to_pem().unwrap());
+
+        fs::write(tempdir.path().join("passphrase"), "hunter3\n").unwrap();
+        assert!(matches!(
+            keystore.load_secret_key(tempdir.path()),
+            Err(KeystoreError::Decrypt { .. })
+        ));
+    }
+}
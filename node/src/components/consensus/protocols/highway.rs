@@ -19,7 +19,7 @@ use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, trace, warn};
 
-use casper_types::{system::auction::BLOCK_REWARD, TimeDiff, Timestamp, U512};
+use casper_types::{system::auction::BLOCK_REWARD, EraId, TimeDiff, Timestamp, U512};
 
 use crate::{
     components::consensus::{
@@ -94,6 +94,7 @@ impl<C: Context + 'static> HighwayProtocol<C> {
     #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     pub(crate) fn new_boxed(
         instance_id: C::InstanceId,
+        era_id: EraId,
         validator_stakes: BTreeMap<C::ValidatorId, U512>,
         faulty: &HashSet<C::ValidatorId>,
         inactive: &HashSet<C::ValidatorId>,
@@ -187,6 +188,7 @@ impl<C: Context + 'static> HighwayProtocol<C> {
             era_start_time,
             era_start_time + chainspec.core_config.era_duration,
             endorsement_evidence_limit,
+            chainspec.core_config.is_signing_context_active(era_id),
         );
 
         let outcomes = Self::initialize_timers(now, era_start_time, &config.highway);
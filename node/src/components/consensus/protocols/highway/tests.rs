@@ -1,6 +1,6 @@
 use std::{collections::BTreeSet, sync::Arc};
 
-use casper_types::{testing::TestRng, PublicKey, TimeDiff, Timestamp, U512};
+use casper_types::{testing::TestRng, EraId, PublicKey, TimeDiff, Timestamp, U512};
 
 use crate::{
     components::consensus::{
@@ -44,6 +44,7 @@ where
         0.into(),
         Timestamp::from(u64::MAX),
         highway_testing::TEST_ENDORSEMENT_EVIDENCE_LIMIT,
+        true,
     );
     let weights = weights.into_iter().map(|w| w.into()).collect::<Vec<_>>();
     state::State::new(weights, params, vec![], vec![])
@@ -75,11 +76,13 @@ where
             max_execution_delay: 3,
             ..HighwayConfig::default()
         },
+        ..Default::default()
     };
     // Timestamp of the genesis era start and test start.
     let start_timestamp: Timestamp = 0.into();
     let (hw_proto, outcomes) = HighwayProtocol::<ClContext>::new_boxed(
         ClContext::hash(INSTANCE_ID_DATA),
+        EraId::new(0),
         weights.into_iter().collect(),
         &init_faulty.into_iter().collect(),
         &None.into_iter().collect(),
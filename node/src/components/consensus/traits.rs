@@ -75,9 +75,12 @@ pub trait Context: Clone + DataSize + Debug + Eq + Ord + Hash + Send {
 
     fn hash(data: &[u8]) -> Self::Hash;
 
+    /// Verifies the signature of the hash. If `signing_context_active` is `false`, signatures
+    /// created without a signing context are accepted as well.
     fn verify_signature(
         hash: &Self::Hash,
         public_key: &Self::ValidatorId,
         signature: &<Self::ValidatorSecret as ValidatorSecret>::Signature,
+        signing_context_active: bool,
     ) -> bool;
 }
@@ -2,6 +2,7 @@
 
 mod config;
 mod error;
+mod execution_pool;
 mod metrics;
 mod operations;
 mod types;
@@ -10,17 +11,20 @@ use std::{
     collections::BTreeMap,
     fmt::{self, Debug, Display, Formatter},
     path::Path,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     time::Instant,
 };
 
 use datasize::DataSize;
 use derive_more::From;
 use lmdb::DatabaseFlags;
-use once_cell::sync::Lazy;
 use prometheus::Registry;
 use serde::Serialize;
 use thiserror::Error;
+use tokio::sync::RwLock;
 use tracing::{debug, error, info, trace};
 
 use casper_execution_engine::{
@@ -31,21 +35,23 @@ use casper_execution_engine::{
     },
     shared::{newtypes::CorrelationId, system_config::SystemConfig, wasm_config::WasmConfig},
     storage::{
-        global_state::lmdb::LmdbGlobalState,
+        global_state::lmdb::{LmdbGlobalState, PruneTriesResult},
         transaction_source::lmdb::LmdbEnvironment,
         trie::{TrieOrChunk, TrieOrChunkId},
         trie_store::lmdb::LmdbTrieStore,
     },
 };
 use casper_hashing::Digest;
-use casper_types::{bytesrepr::Bytes, ProtocolVersion, Timestamp};
+use casper_types::{bytesrepr::Bytes, ProtocolVersion, TimeDiff, Timestamp};
 
 use crate::{
     components::{contract_runtime::types::StepEffectAndUpcomingEraValidators, Component},
     effect::{
         announcements::{ContractRuntimeAnnouncement, ControlAnnouncement},
         incoming::{TrieDemand, TrieRequest, TrieRequestIncoming},
-        requests::{ContractRuntimeRequest, MarkBlockCompletedRequest, NetworkRequest},
+        requests::{
+            ContractRuntimeRequest, MarkBlockCompletedRequest, NetworkRequest, StorageRequest,
+        },
         EffectBuilder, EffectExt, Effects,
     },
     fatal,
@@ -55,6 +61,7 @@ use crate::{
 };
 pub(crate) use config::Config;
 pub(crate) use error::{BlockExecutionError, ConfigError};
+use execution_pool::{ExecutionPool, TaskKind};
 use metrics::Metrics;
 pub use operations::execute_finalized_block;
 pub(crate) use types::{BlockAndExecutionEffects, EraValidatorsRequest};
@@ -63,6 +70,13 @@ use self::operations::execute_only;
 
 use super::fetcher::FetchedOrNotFound;
 
+/// The maximum number of blocks pruned from storage per request, to keep the storage component
+/// responsive.
+const MAX_PRUNED_BLOCKS_PER_REQUEST: u64 = 100;
+
+/// The number of unreachable tries deleted per write transaction of global state.
+const PRUNED_TRIES_PER_TRANSACTION: usize = 10_000;
+
 /// An enum that represents all possible error conditions of a `contract_runtime` component.
 #[derive(Debug, Error, From)]
 pub(crate) enum ContractRuntimeError {
@@ -74,32 +88,6 @@ pub(crate) enum ContractRuntimeError {
     FailedToRetrieveTrieById(#[source] engine_state::Error),
 }
 
-/// Maximum number of resource intensive tasks that can be run in parallel.
-///
-/// TODO: Fine tune this constant to the machine executing the node.
-const MAX_PARALLEL_INTENSIVE_TASKS: usize = 4;
-
-/// Semaphore enforcing maximum number of parallel resource intensive tasks.
-static INTENSIVE_TASKS_SEMAPHORE: Lazy<tokio::sync::Semaphore> =
-    Lazy::new(|| tokio::sync::Semaphore::new(MAX_PARALLEL_INTENSIVE_TASKS));
-
-/// Asynchronously runs a resource intensive task.
-/// At most `MAX_PARALLEL_INTENSIVE_TASKS` are being run in parallel at any time.
-///
-/// The task is a closure that takes no arguments and returns a value.
-/// This function returns a future for that value.
-async fn run_intensive_task<T, V>(task: T) -> V
-where
-    T: 'static + Send + FnOnce() -> V,
-    V: 'static + Send,
-{
-    // This will never panic since the semaphore is never closed.
-    let _permit = INTENSIVE_TASKS_SEMAPHORE.acquire().await.unwrap();
-    tokio::task::spawn_blocking(task)
-        .await
-        .expect("task panicked")
-}
-
 #[derive(DataSize, Debug, Clone, Serialize)]
 /// Wrapper for speculative execution prestate.
 pub struct SpeculativeExecutionState {
@@ -172,6 +160,9 @@ pub(crate) enum Event {
 
     #[from]
     TrieDemand(TrieDemand),
+
+    /// Prune history outside the retention window.
+    PruneHistory,
 }
 
 impl Display for Event {
@@ -182,6 +173,7 @@ impl Display for Event {
             }
             Event::TrieRequestIncoming(req) => write!(f, "trie request incoming: {}", req),
             Event::TrieDemand(demand) => write!(f, "trie demand: {}", demand),
+            Event::PruneHistory => write!(f, "prune history"),
         }
     }
 }
@@ -198,6 +190,23 @@ pub(crate) struct ContractRuntime {
     exec_queue: ExecQueue,
     /// Cached instance of a [`SystemContractRegistry`].
     system_contract_registry: Option<SystemContractRegistry>,
+    /// The number of finalized blocks currently being executed.
+    #[data_size(skip)]
+    blocks_in_flight: Arc<AtomicUsize>,
+    /// Whether the node is shutting down, in which case no further blocks are executed.
+    is_draining: bool,
+    /// The threads executing blocks and deploys.
+    #[data_size(skip)]
+    execution_pool: Arc<ExecutionPool>,
+    /// The number of threads executing the deploys of a single block.
+    deploy_execution_threads: usize,
+    /// How often history outside the retention window is pruned, if pruning was started.
+    pruning_interval: Option<TimeDiff>,
+    /// Held for reading while tries are written, i.e. while a block is executed until its state
+    /// root becomes the execution pre-state, or while a synced trie is stored, and for writing
+    /// while a batch of unreachable tries is deleted.
+    #[data_size(skip)]
+    trie_writes: Arc<RwLock<()>>,
 }
 
 impl Debug for ContractRuntime {
@@ -213,6 +222,7 @@ where
         + From<ControlAnnouncement>
         + From<NetworkRequest<Message>>
         + From<MarkBlockCompletedRequest>
+        + From<StorageRequest>
         + Send,
 {
     type Event = Event;
@@ -232,11 +242,89 @@ where
                 self.handle_trie_request(effect_builder, request)
             }
             Event::TrieDemand(demand) => self.handle_trie_demand(demand),
+            Event::PruneHistory => self.handle_prune_history(effect_builder),
         }
     }
 }
 
 impl ContractRuntime {
+    /// Prunes history outside the retention window, then schedules the next run.
+    ///
+    /// Storage is pruned first, in small batches.  Once it has caught up, all tries unreachable
+    /// from the state roots of the retained blocks and the current pre-state are deleted in the
+    /// background, pausing block execution and trie sync while each batch is deleted.
+    fn handle_prune_history<REv>(&self, effect_builder: EffectBuilder<REv>) -> Effects<Event>
+    where
+        REv: From<StorageRequest> + Send,
+    {
+        let pruning_interval = match self.pruning_interval {
+            Some(pruning_interval) => pruning_interval,
+            None => {
+                error!("pruning history without a pruning interval");
+                return Effects::new();
+            }
+        };
+        let engine_state = Arc::clone(&self.engine_state);
+        let metrics = Arc::clone(&self.metrics);
+        let execution_pre_state = Arc::clone(&self.execution_pre_state);
+        let trie_writes = Arc::clone(&self.trie_writes);
+
+        async move {
+            let maybe_retained_state_roots = loop {
+                let pruned_history = match effect_builder
+                    .prune_history(MAX_PRUNED_BLOCKS_PER_REQUEST)
+                    .await
+                {
+                    Some(pruned_history) => pruned_history,
+                    None => break None,
+                };
+                metrics.pruned_blocks.inc_by(pruned_history.pruned_blocks);
+                metrics.pruned_deploys.inc_by(pruned_history.pruned_deploys);
+                if pruned_history.retained_state_roots.is_some() {
+                    break pruned_history.retained_state_roots;
+                }
+            };
+
+            if let Some(mut retained_state_roots) = maybe_retained_state_roots {
+                // Blocks hold `trie_writes` until their state root is the new pre-state, so with
+                // writers paused, the pre-state root is the latest state root written.
+                let pause_writers =
+                    move || futures::executor::block_on(Arc::clone(&trie_writes).write_owned());
+                let current_roots =
+                    move || vec![execution_pre_state.lock().unwrap().pre_state_root_hash];
+                retained_state_roots.extend(current_roots());
+
+                let start = Instant::now();
+                let result = tokio::task::spawn_blocking(move || {
+                    engine_state.get_state().prune_unreachable_tries(
+                        retained_state_roots,
+                        pause_writers,
+                        current_roots,
+                        PRUNED_TRIES_PER_TRANSACTION,
+                    )
+                })
+                .await;
+                metrics.prune_tries.observe(start.elapsed().as_secs_f64());
+
+                match result {
+                    Ok(Ok(PruneTriesResult {
+                        pruned_tries,
+                        reclaimed_bytes,
+                    })) => {
+                        metrics.pruned_tries.inc_by(pruned_tries);
+                        metrics.pruned_trie_bytes.inc_by(reclaimed_bytes);
+                        info!(pruned_tries, reclaimed_bytes, "pruned unreachable tries");
+                    }
+                    Ok(Err(error)) => error!(%error, "failed to prune unreachable tries"),
+                    Err(error) => error!(%error, "pruning unreachable tries panicked"),
+                }
+            }
+
+            effect_builder.set_timeout(pruning_interval.into()).await
+        }
+        .event(|_| Event::PruneHistory)
+    }
+
     /// Handles an incoming request to get a trie.
     fn handle_trie_request<REv>(
         &self,
@@ -333,9 +421,13 @@ impl ContractRuntime {
                 async move {
                     let correlation_id = CorrelationId::new();
                     let start = Instant::now();
+                    #[cfg(test)]
+                    let request = query_request.clone();
                     let result = engine_state.run_query(correlation_id, query_request);
                     metrics.run_query.observe(start.elapsed().as_secs_f64());
                     trace!(?result, "query result");
+                    #[cfg(test)]
+                    crate::testing::assert_valid_query_proofs(&request, &result);
                     responder.respond(result).await
                 }
                 .ignore()
@@ -357,6 +449,8 @@ impl ContractRuntime {
                     );
                     metrics.get_balance.observe(start.elapsed().as_secs_f64());
                     trace!(?result, "balance result");
+                    #[cfg(test)]
+                    crate::testing::assert_valid_balance_proof(&balance_request, &result);
                     responder.respond(result).await
                 }
                 .ignore()
@@ -452,13 +546,16 @@ impl ContractRuntime {
                 trace!(?trie_bytes, "put_trie request");
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
+                let trie_writes = Arc::clone(&self.trie_writes);
                 async move {
                     let correlation_id = CorrelationId::new();
                     let start = Instant::now();
+                    let trie_write = trie_writes.read_owned().await;
                     let result = engine_state.put_trie_and_find_missing_descendant_trie_keys(
                         correlation_id,
                         &*trie_bytes,
                     );
+                    drop(trie_write);
                     // PERF: this *could* be called only periodically.
                     if let Err(lmdb_error) = engine_state.flush_environment() {
                         fatal!(
@@ -492,19 +589,26 @@ impl ContractRuntime {
                 );
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
+                let execution_pool = Arc::clone(&self.execution_pool);
+                let deploy_execution_threads = self.deploy_execution_threads;
+                let trie_writes = Arc::clone(&self.trie_writes);
                 async move {
-                    let result = run_intensive_task(move || {
-                        execute_finalized_block(
-                            engine_state.as_ref(),
-                            Some(metrics),
-                            protocol_version,
-                            execution_pre_state,
-                            finalized_block,
-                            deploys,
-                            transfers,
-                        )
-                    })
-                    .await;
+                    let trie_write = trie_writes.read_owned().await;
+                    let result = execution_pool
+                        .run(TaskKind::Block, move || {
+                            execute_finalized_block(
+                                engine_state.as_ref(),
+                                Some(metrics),
+                                deploy_execution_threads,
+                                protocol_version,
+                                execution_pre_state,
+                                finalized_block,
+                                deploys,
+                                transfers,
+                            )
+                        })
+                        .await;
+                    drop(trie_write);
                     trace!(?result, "execute block response");
                     responder.respond(result).await
                 }
@@ -521,22 +625,43 @@ impl ContractRuntime {
                 let metrics = Arc::clone(&self.metrics);
                 let exec_queue = Arc::clone(&self.exec_queue);
                 let execution_pre_state = Arc::clone(&self.execution_pre_state);
+                let execution_pool = Arc::clone(&self.execution_pool);
+                let deploy_execution_threads = self.deploy_execution_threads;
                 let protocol_version = self.protocol_version;
-                if self.execution_pre_state.lock().unwrap().next_block_height
+                let trie_writes = Arc::clone(&self.trie_writes);
+                if self.is_draining {
+                    info!(
+                        height = finalized_block.height(),
+                        "not executing finalized block while shutting down"
+                    );
+                    exec_queue.lock().unwrap().insert(
+                        finalized_block.height(),
+                        (finalized_block, deploys, transfers),
+                    );
+                } else if self.execution_pre_state.lock().unwrap().next_block_height
                     == finalized_block.height()
                 {
+                    let blocks_in_flight = Arc::clone(&self.blocks_in_flight);
+                    blocks_in_flight.fetch_add(1, Ordering::SeqCst);
                     effects.extend(
-                        Self::execute_finalized_block_or_requeue(
-                            engine_state,
-                            metrics,
-                            exec_queue,
-                            execution_pre_state,
-                            effect_builder,
-                            protocol_version,
-                            finalized_block,
-                            deploys,
-                            transfers,
-                        )
+                        async move {
+                            Self::execute_finalized_block_or_requeue(
+                                engine_state,
+                                metrics,
+                                exec_queue,
+                                execution_pre_state,
+                                execution_pool,
+                                deploy_execution_threads,
+                                trie_writes,
+                                effect_builder,
+                                protocol_version,
+                                finalized_block,
+                                deploys,
+                                transfers,
+                            )
+                            .await;
+                            blocks_in_flight.fetch_sub(1, Ordering::SeqCst);
+                        }
                         .ignore(),
                     )
                 } else {
@@ -585,15 +710,23 @@ impl ContractRuntime {
             }
             ContractRuntimeRequest::SpeculativeDeployExecution {
                 execution_prestate,
-                deploy,
+                transaction,
+                profile_gas,
                 responder,
             } => {
                 let engine_state = Arc::clone(&self.engine_state);
+                let execution_pool = Arc::clone(&self.execution_pool);
                 async move {
-                    let result = run_intensive_task(move || {
-                        execute_only(engine_state.as_ref(), execution_prestate, (*deploy).into())
-                    })
-                    .await;
+                    let result = execution_pool
+                        .run(TaskKind::Speculative, move || {
+                            execute_only(
+                                engine_state.as_ref(),
+                                execution_prestate,
+                                (*transaction).into(),
+                                profile_gas,
+                            )
+                        })
+                        .await;
                     responder.respond(result).await
                 }
                 .ignore()
@@ -602,6 +735,27 @@ impl ContractRuntime {
     }
 }
 
+/// Opens the global state stored in `storage_dir`.
+pub(crate) fn open_global_state(
+    storage_dir: &Path,
+    contract_runtime_config: &Config,
+) -> Result<LmdbGlobalState, ConfigError> {
+    let environment = Arc::new(LmdbEnvironment::new(
+        storage_dir,
+        contract_runtime_config.max_global_state_size(),
+        contract_runtime_config.max_readers(),
+        contract_runtime_config.manual_sync_enabled(),
+    )?);
+
+    let trie_store = Arc::new(LmdbTrieStore::new(
+        &environment,
+        None,
+        DatabaseFlags::empty(),
+    )?);
+
+    Ok(LmdbGlobalState::empty(environment, trie_store)?)
+}
+
 impl ContractRuntime {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
@@ -614,6 +768,8 @@ impl ContractRuntime {
         max_runtime_call_stack_height: u32,
         minimum_delegation_amount: u64,
         strict_argument_checking: bool,
+        record_transfer_metadata: bool,
+        allow_account_hash_v2: bool,
         vesting_schedule_period_millis: u64,
         registry: &Registry,
     ) -> Result<Self, ConfigError> {
@@ -625,20 +781,7 @@ impl ContractRuntime {
             parent_seed: Default::default(),
         }));
 
-        let environment = Arc::new(LmdbEnvironment::new(
-            storage_dir,
-            contract_runtime_config.max_global_state_size(),
-            contract_runtime_config.max_readers(),
-            contract_runtime_config.manual_sync_enabled(),
-        )?);
-
-        let trie_store = Arc::new(LmdbTrieStore::new(
-            &environment,
-            None,
-            DatabaseFlags::empty(),
-        )?);
-
-        let global_state = LmdbGlobalState::empty(environment, trie_store)?;
+        let global_state = open_global_state(storage_dir, contract_runtime_config)?;
         let engine_config = EngineConfig::new(
             contract_runtime_config.max_query_depth(),
             max_associated_keys,
@@ -648,12 +791,21 @@ impl ContractRuntime {
             vesting_schedule_period_millis,
             wasm_config,
             system_config,
-        );
+        )
+        .with_module_cache_size(contract_runtime_config.module_cache_size())
+        .with_record_transfer_metadata(record_transfer_metadata)
+        .with_allow_account_hash_v2(allow_account_hash_v2);
 
         let engine_state = Arc::new(EngineState::new(global_state, engine_config));
 
         let metrics = Arc::new(Metrics::new(registry)?);
 
+        let execution_pool = Arc::new(ExecutionPool::new(
+            contract_runtime_config.execution_threads(),
+            contract_runtime_config.execution_queue_policy(),
+            contract_runtime_config.execution_cores(),
+        )?);
+
         Ok(ContractRuntime {
             execution_pre_state,
             engine_state,
@@ -661,9 +813,47 @@ impl ContractRuntime {
             protocol_version,
             exec_queue: Arc::new(Mutex::new(BTreeMap::new())),
             system_contract_registry: None,
+            blocks_in_flight: Arc::new(AtomicUsize::new(0)),
+            is_draining: false,
+            execution_pool,
+            deploy_execution_threads: contract_runtime_config.deploy_execution_threads(),
+            pruning_interval: None,
+            trie_writes: Arc::new(RwLock::new(())),
         })
     }
 
+    /// Starts pruning history outside the retention window every `pruning_interval`.
+    pub(crate) fn start_pruning_history<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        pruning_interval: TimeDiff,
+    ) -> Effects<Event>
+    where
+        REv: Send,
+    {
+        self.pruning_interval = Some(pruning_interval);
+        effect_builder
+            .set_timeout(pruning_interval.into())
+            .event(|_| Event::PruneHistory)
+    }
+
+    /// Stops executing newly finalized blocks ahead of a shutdown.
+    ///
+    /// Blocks already being executed are finished.
+    pub(crate) fn begin_drain(&mut self) {
+        self.is_draining = true;
+    }
+
+    /// Returns `true` if no block is currently being executed.
+    pub(crate) fn is_drained(&self) -> bool {
+        self.blocks_in_flight.load(Ordering::SeqCst) == 0
+    }
+
+    /// Flushes global state to disk.
+    pub(crate) fn flush(&self) -> Result<(), lmdb::Error> {
+        self.engine_state.get_state().environment().sync()
+    }
+
     /// Commits a genesis request.
     fn commit_genesis(
         &self,
@@ -769,6 +959,9 @@ impl ContractRuntime {
         metrics: Arc<Metrics>,
         exec_queue: ExecQueue,
         execution_pre_state: Arc<Mutex<ExecutionPreState>>,
+        execution_pool: Arc<ExecutionPool>,
+        deploy_execution_threads: usize,
+        trie_writes: Arc<RwLock<()>>,
         effect_builder: EffectBuilder<REv>,
         protocol_version: ProtocolVersion,
         finalized_block: FinalizedBlock,
@@ -781,23 +974,26 @@ impl ContractRuntime {
             + From<MarkBlockCompletedRequest>
             + Send,
     {
+        let trie_write = trie_writes.read_owned().await;
         let current_execution_pre_state = execution_pre_state.lock().unwrap().clone();
         let BlockAndExecutionEffects {
             block,
             execution_results,
             maybe_step_effect_and_upcoming_era_validators,
-        } = match run_intensive_task(move || {
-            execute_finalized_block(
-                engine_state.as_ref(),
-                Some(metrics),
-                protocol_version,
-                current_execution_pre_state,
-                finalized_block,
-                deploys,
-                transfers,
-            )
-        })
-        .await
+        } = match execution_pool
+            .run(TaskKind::Block, move || {
+                execute_finalized_block(
+                    engine_state.as_ref(),
+                    Some(metrics),
+                    deploy_execution_threads,
+                    protocol_version,
+                    current_execution_pre_state,
+                    finalized_block,
+                    deploys,
+                    transfers,
+                )
+            })
+            .await
         {
             Ok(block_and_execution_effects) => block_and_execution_effects,
             Err(error) => return fatal!(effect_builder, "{}", error).await,
@@ -805,6 +1001,7 @@ impl ContractRuntime {
 
         let new_execution_pre_state = ExecutionPreState::from_block_header(block.header());
         *execution_pre_state.lock().unwrap() = new_execution_pre_state.clone();
+        drop(trie_write);
 
         let current_era_id = block.header().era_id();
 
@@ -818,10 +1015,17 @@ impl ContractRuntime {
         if let Some(StepEffectAndUpcomingEraValidators {
             step_execution_journal,
             upcoming_era_validators,
+            era_rewards,
+            era_stakes,
         }) = maybe_step_effect_and_upcoming_era_validators
         {
             effect_builder
-                .announce_commit_step_success(current_era_id, step_execution_journal)
+                .announce_commit_step_success(
+                    current_era_id,
+                    step_execution_journal,
+                    era_rewards,
+                    era_stakes,
+                )
                 .await;
 
             effect_builder
@@ -861,6 +1065,8 @@ impl ContractRuntime {
     ) -> Result<Option<TrieOrChunk>, engine_state::Error> {
         let correlation_id = CorrelationId::new();
         let start = Instant::now();
+        #[cfg(any(feature = "fault-injection", test))]
+        crate::fault_injection::delay_trie_read();
         let result = engine_state.get_trie(correlation_id, trie_or_chunk_id);
         metrics.get_trie.observe(start.elapsed().as_secs_f64());
         result
@@ -873,6 +1079,8 @@ impl ContractRuntime {
     ) -> Result<Option<Bytes>, engine_state::Error> {
         let correlation_id = CorrelationId::new();
         let start = Instant::now();
+        #[cfg(any(feature = "fault-injection", test))]
+        crate::fault_injection::delay_trie_read();
         let result = engine_state.get_trie_full(correlation_id, trie_key);
         metrics.get_trie.observe(start.elapsed().as_secs_f64());
         result
@@ -3,13 +3,19 @@ use serde::{Deserialize, Serialize};
 
 use casper_execution_engine::shared::utils;
 
+use super::execution_pool::ExecutionQueuePolicy;
+
 const DEFAULT_MAX_GLOBAL_STATE_SIZE: usize = 805_306_368_000; // 750 GiB
 const DEFAULT_MAX_READERS: u32 = 512;
 const DEFAULT_MAX_QUERY_DEPTH: u64 = 5;
 const DEFAULT_MANUAL_SYNC_ENABLED: bool = true;
+const DEFAULT_EXECUTION_THREADS: usize = 4;
+const DEFAULT_EXECUTION_QUEUE_POLICY: ExecutionQueuePolicy = ExecutionQueuePolicy::BlocksFirst;
+const DEFAULT_DEPLOY_EXECUTION_THREADS: usize = 1;
+const DEFAULT_MODULE_CACHE_SIZE: usize = 256;
 
 /// Contract runtime configuration.
-#[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
 pub struct Config {
@@ -31,6 +37,31 @@ pub struct Config {
     ///
     /// Defaults to `false`.
     enable_manual_sync: Option<bool>,
+    /// The number of threads executing contracts, i.e. the maximum number of blocks or deploys
+    /// being executed in parallel.
+    ///
+    /// Defaults to 4.
+    execution_threads: Option<usize>,
+    /// The order in which queued executions are run.
+    ///
+    /// Defaults to `blocks_first`.
+    execution_queue_policy: Option<ExecutionQueuePolicy>,
+    /// The CPU cores to pin the execution threads to.
+    ///
+    /// Defaults to no pinning.
+    execution_cores: Option<Vec<usize>>,
+    /// The number of threads executing the deploys of a single block in parallel.
+    ///
+    /// Deploys are executed optimistically, and re-executed in order if they read any state written
+    /// by a deploy before them, so the results are the same as with sequential execution.
+    ///
+    /// Defaults to 1, i.e. sequential execution.
+    deploy_execution_threads: Option<usize>,
+    /// The maximum number of parsed Wasm modules of stored contracts kept in memory, so that
+    /// frequently called contracts are not parsed again on every call.  0 disables the cache.
+    ///
+    /// Defaults to 256.
+    module_cache_size: Option<usize>,
 }
 
 impl Config {
@@ -54,6 +85,29 @@ impl Config {
         self.enable_manual_sync
             .unwrap_or(DEFAULT_MANUAL_SYNC_ENABLED)
     }
+
+    pub(crate) fn execution_threads(&self) -> usize {
+        self.execution_threads.unwrap_or(DEFAULT_EXECUTION_THREADS)
+    }
+
+    pub(crate) fn execution_queue_policy(&self) -> ExecutionQueuePolicy {
+        self.execution_queue_policy
+            .unwrap_or(DEFAULT_EXECUTION_QUEUE_POLICY)
+    }
+
+    pub(crate) fn execution_cores(&self) -> Option<Vec<usize>> {
+        self.execution_cores.clone()
+    }
+
+    pub(crate) fn deploy_execution_threads(&self) -> usize {
+        self.deploy_execution_threads
+            .unwrap_or(DEFAULT_DEPLOY_EXECUTION_THREADS)
+            .max(1)
+    }
+
+    pub(crate) fn module_cache_size(&self) -> usize {
+        self.module_cache_size.unwrap_or(DEFAULT_MODULE_CACHE_SIZE)
+    }
 }
 
 impl Default for Config {
@@ -63,6 +117,11 @@ impl Default for Config {
             max_readers: Some(DEFAULT_MAX_READERS),
             max_query_depth: Some(DEFAULT_MAX_QUERY_DEPTH),
             enable_manual_sync: Some(DEFAULT_MANUAL_SYNC_ENABLED),
+            execution_threads: Some(DEFAULT_EXECUTION_THREADS),
+            execution_queue_policy: Some(DEFAULT_EXECUTION_QUEUE_POLICY),
+            execution_cores: None,
+            deploy_execution_threads: Some(DEFAULT_DEPLOY_EXECUTION_THREADS),
+            module_cache_size: Some(DEFAULT_MODULE_CACHE_SIZE),
         }
     }
 }
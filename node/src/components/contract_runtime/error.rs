@@ -1,5 +1,7 @@
 //! Errors that the contract runtime component may raise.
 
+use std::io;
+
 use serde::Serialize;
 use thiserror::Error;
 
@@ -26,6 +28,9 @@ pub(crate) enum ConfigError {
     /// Error initializing execution engine.
     #[error("failed to initialize execution engine: {0}")]
     EngineState(#[from] EngineStateError),
+    /// Error starting the contract execution threads.
+    #[error("failed to start contract execution threads: {0}")]
+    ExecutionThreads(#[from] io::Error),
 }
 
 /// An error during block execution.
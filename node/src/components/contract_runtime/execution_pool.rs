@@ -0,0 +1,236 @@
+//! Dedicated thread pool for contract execution.
+//!
+//! Executing deploys can take a long time and is CPU-bound, so it is kept off the tokio runtime
+//! entirely: a fixed number of worker threads, optionally pinned to a set of CPU cores, take tasks
+//! from a shared queue.  This way long-running executions cannot starve networking and consensus
+//! tasks of runtime threads, and at most `execution_threads` executions run in parallel.
+
+use std::{
+    collections::VecDeque,
+    io,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use tracing::{debug, warn};
+
+/// The order in which queued tasks are picked up by the execution threads.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ExecutionQueuePolicy {
+    /// Tasks are executed in the order they were queued.
+    Fifo,
+    /// Execution of finalized blocks takes precedence over speculative execution of deploys.
+    BlocksFirst,
+}
+
+/// The kind of an execution task, used to order the queue.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum TaskKind {
+    /// Execution of a finalized block.
+    Block,
+    /// Speculative execution of a single deploy.
+    Speculative,
+}
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+#[derive(Default)]
+struct Queues {
+    blocks: VecDeque<Job>,
+    speculative: VecDeque<Job>,
+    /// Tasks of either kind in the order they were queued, used by the FIFO policy.
+    fifo: VecDeque<Job>,
+    shutting_down: bool,
+}
+
+struct Shared {
+    queues: Mutex<Queues>,
+    available: Condvar,
+    policy: ExecutionQueuePolicy,
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Queues> {
+        // Jobs never run while the lock is held, so the queues cannot be left inconsistent.
+        self.queues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A pool of threads executing contracts.
+///
+/// The worker threads exit once the pool is dropped and the queue has been emptied.
+pub(super) struct ExecutionPool {
+    shared: Arc<Shared>,
+}
+
+impl ExecutionPool {
+    /// Starts `threads` execution threads, pinned to the given CPU cores if any.
+    pub(super) fn new(
+        threads: usize,
+        policy: ExecutionQueuePolicy,
+        cores: Option<Vec<usize>>,
+    ) -> io::Result<Self> {
+        let shared = Arc::new(Shared {
+            queues: Mutex::new(Queues::default()),
+            available: Condvar::new(),
+            policy,
+        });
+
+        for idx in 0..threads.max(1) {
+            let shared = Arc::clone(&shared);
+            let cores = cores.clone();
+            thread::Builder::new()
+                .name(format!("contract-exec-{}", idx))
+                .spawn(move || {
+                    if let Some(cores) = cores {
+                        if let Err(error) = pin_to_cores(&cores) {
+                            warn!(%error, ?cores, "could not pin execution thread to cores");
+                        }
+                    }
+                    worker(&shared)
+                })?;
+        }
+        debug!(
+            threads,
+            ?policy,
+            ?cores,
+            "started contract execution threads"
+        );
+
+        Ok(ExecutionPool { shared })
+    }
+
+    /// Queues the task for execution and returns its result once it has been executed.
+    pub(super) async fn run<T, V>(&self, kind: TaskKind, task: T) -> V
+    where
+        T: 'static + Send + FnOnce() -> V,
+        V: 'static + Send,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            // The receiver might be gone if the requester was cancelled, which is fine.
+            let _ = sender.send(task());
+        });
+
+        {
+            let mut queues = self.shared.lock();
+            match (self.shared.policy, kind) {
+                (ExecutionQueuePolicy::Fifo, _) => queues.fifo.push_back(job),
+                (ExecutionQueuePolicy::BlocksFirst, TaskKind::Block) => {
+                    queues.blocks.push_back(job)
+                }
+                (ExecutionQueuePolicy::BlocksFirst, TaskKind::Speculative) => {
+                    queues.speculative.push_back(job)
+                }
+            }
+        }
+        self.shared.available.notify_one();
+
+        // The sender is only dropped without sending if the task panicked.
+        receiver.await.expect("task panicked")
+    }
+}
+
+impl Drop for ExecutionPool {
+    fn drop(&mut self) {
+        self.shared.lock().shutting_down = true;
+        self.shared.available.notify_all();
+    }
+}
+
+/// Runs queued jobs until the pool is shut down.
+fn worker(shared: &Shared) {
+    loop {
+        let job = {
+            let mut queues = shared.lock();
+            loop {
+                if let Some(job) = queues
+                    .fifo
+                    .pop_front()
+                    .or_else(|| queues.blocks.pop_front())
+                    .or_else(|| queues.speculative.pop_front())
+                {
+                    break job;
+                }
+                if queues.shutting_down {
+                    return;
+                }
+                queues = shared
+                    .available
+                    .wait(queues)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+        };
+
+        // A panicking task drops its result sender, which is reported to the requester; the thread
+        // itself keeps serving the queue.
+        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+    }
+}
+
+/// Restricts the calling thread to the given CPU cores.
+#[cfg(target_os = "linux")]
+fn pin_to_cores(cores: &[usize]) -> io::Result<()> {
+    // Safe, as `cpu_set_t` is a plain bit set and the size passed matches the set.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &core in cores {
+            if core >= libc::CPU_SETSIZE as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid core index {}", core),
+                ));
+            }
+            libc::CPU_SET(core, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Restricts the calling thread to the given CPU cores.
+#[cfg(not(target_os = "linux"))]
+fn pin_to_cores(_cores: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "pinning threads to cores is only supported on Linux",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn should_run_block_execution_first() {
+        let pool = ExecutionPool::new(1, ExecutionQueuePolicy::BlocksFirst, None).unwrap();
+
+        // Occupy the only thread until all other tasks are queued.
+        let (unblock_sender, unblock_receiver) = mpsc::channel::<()>();
+        let blocker = pool.run(TaskKind::Block, move || unblock_receiver.recv().unwrap());
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str| {
+            let order = Arc::clone(&order);
+            move || order.lock().unwrap().push(name)
+        };
+        let speculative = pool.run(TaskKind::Speculative, record("speculative"));
+        let block = pool.run(TaskKind::Block, record("block"));
+
+        tokio::join!(blocker, speculative, block, async move {
+            unblock_sender.send(()).unwrap()
+        });
+        assert_eq!(*order.lock().unwrap(), vec!["block", "speculative"]);
+    }
+}
@@ -1,4 +1,6 @@
-use prometheus::{self, Gauge, Histogram, IntGauge, Registry};
+use prometheus::{self, Gauge, Histogram, IntCounter, IntGauge, Registry};
+
+use casper_execution_engine::core::engine_state::ModuleCacheStats;
 
 use crate::{unregister_metric, utils};
 
@@ -61,6 +63,38 @@ const EXEC_BLOCK_HELP: &str = "time in seconds to execute all deploys in a block
 const LATEST_COMMIT_STEP_NAME: &str = "contract_runtime_latest_commit_step";
 const LATEST_COMMIT_STEP_HELP: &str = "duration in seconds of latest commit step at era end";
 
+const PRUNED_BLOCKS_NAME: &str = "contract_runtime_pruned_blocks";
+const PRUNED_BLOCKS_HELP: &str =
+    "number of blocks whose deploys and execution results were pruned from storage";
+
+const PRUNED_DEPLOYS_NAME: &str = "contract_runtime_pruned_deploys";
+const PRUNED_DEPLOYS_HELP: &str =
+    "number of deploys and their execution results pruned from storage";
+
+const PRUNED_TRIES_NAME: &str = "contract_runtime_pruned_tries";
+const PRUNED_TRIES_HELP: &str = "number of tries pruned from global state";
+
+const PRUNED_TRIE_BYTES_NAME: &str = "contract_runtime_pruned_trie_bytes";
+const PRUNED_TRIE_BYTES_HELP: &str = "total size in bytes of the tries pruned from global state";
+
+const REEXECUTED_DEPLOYS_NAME: &str = "contract_runtime_reexecuted_deploys";
+const REEXECUTED_DEPLOYS_HELP: &str =
+    "number of deploys executed again because they conflicted with an earlier deploy of a block";
+
+const MODULE_CACHE_HITS_NAME: &str = "contract_runtime_module_cache_hits";
+const MODULE_CACHE_HITS_HELP: &str =
+    "number of calls of stored contracts whose parsed Wasm module was found in the cache";
+
+const MODULE_CACHE_MISSES_NAME: &str = "contract_runtime_module_cache_misses";
+const MODULE_CACHE_MISSES_HELP: &str =
+    "number of calls of stored contracts whose Wasm module had to be parsed";
+
+const MODULE_CACHE_ENTRIES_NAME: &str = "contract_runtime_module_cache_entries";
+const MODULE_CACHE_ENTRIES_HELP: &str = "number of parsed Wasm modules in the cache";
+
+const PRUNE_TRIES_NAME: &str = "contract_runtime_prune_tries";
+const PRUNE_TRIES_HELP: &str = "time in seconds to prune unreachable tries from global state";
+
 /// Metrics for the contract runtime component.
 #[derive(Debug)]
 pub struct Metrics {
@@ -79,6 +113,15 @@ pub struct Metrics {
     pub(super) chain_height: IntGauge,
     pub(super) exec_block: Histogram,
     pub(super) latest_commit_step: Gauge,
+    pub(super) pruned_blocks: IntCounter,
+    pub(super) pruned_deploys: IntCounter,
+    pub(super) pruned_tries: IntCounter,
+    pub(super) pruned_trie_bytes: IntCounter,
+    pub(super) prune_tries: Histogram,
+    pub(super) reexecuted_deploys: IntCounter,
+    module_cache_hits: IntCounter,
+    module_cache_misses: IntCounter,
+    module_cache_entries: IntGauge,
     registry: Registry,
 }
 
@@ -103,6 +146,32 @@ impl Metrics {
         let latest_commit_step = Gauge::new(LATEST_COMMIT_STEP_NAME, LATEST_COMMIT_STEP_HELP)?;
         registry.register(Box::new(latest_commit_step.clone()))?;
 
+        let pruned_blocks = IntCounter::new(PRUNED_BLOCKS_NAME, PRUNED_BLOCKS_HELP)?;
+        registry.register(Box::new(pruned_blocks.clone()))?;
+
+        let pruned_deploys = IntCounter::new(PRUNED_DEPLOYS_NAME, PRUNED_DEPLOYS_HELP)?;
+        registry.register(Box::new(pruned_deploys.clone()))?;
+
+        let pruned_tries = IntCounter::new(PRUNED_TRIES_NAME, PRUNED_TRIES_HELP)?;
+        registry.register(Box::new(pruned_tries.clone()))?;
+
+        let pruned_trie_bytes = IntCounter::new(PRUNED_TRIE_BYTES_NAME, PRUNED_TRIE_BYTES_HELP)?;
+        registry.register(Box::new(pruned_trie_bytes.clone()))?;
+
+        let reexecuted_deploys = IntCounter::new(REEXECUTED_DEPLOYS_NAME, REEXECUTED_DEPLOYS_HELP)?;
+        registry.register(Box::new(reexecuted_deploys.clone()))?;
+
+        let module_cache_hits = IntCounter::new(MODULE_CACHE_HITS_NAME, MODULE_CACHE_HITS_HELP)?;
+        registry.register(Box::new(module_cache_hits.clone()))?;
+
+        let module_cache_misses =
+            IntCounter::new(MODULE_CACHE_MISSES_NAME, MODULE_CACHE_MISSES_HELP)?;
+        registry.register(Box::new(module_cache_misses.clone()))?;
+
+        let module_cache_entries =
+            IntGauge::new(MODULE_CACHE_ENTRIES_NAME, MODULE_CACHE_ENTRIES_HELP)?;
+        registry.register(Box::new(module_cache_entries.clone()))?;
+
         Ok(Metrics {
             run_execute: utils::register_histogram_metric(
                 registry,
@@ -184,9 +253,32 @@ impl Metrics {
                 common_buckets,
             )?,
             latest_commit_step,
+            pruned_blocks,
+            pruned_deploys,
+            pruned_tries,
+            pruned_trie_bytes,
+            prune_tries: utils::register_histogram_metric(
+                registry,
+                PRUNE_TRIES_NAME,
+                PRUNE_TRIES_HELP,
+                prometheus::exponential_buckets(1.0, 2.0, 12)?,
+            )?,
+            reexecuted_deploys,
+            module_cache_hits,
+            module_cache_misses,
+            module_cache_entries,
             registry: registry.clone(),
         })
     }
+
+    /// Brings the module cache metrics up to date with the statistics of the cache.
+    pub(super) fn record_module_cache_stats(&self, stats: ModuleCacheStats) {
+        self.module_cache_hits
+            .inc_by(stats.hits.saturating_sub(self.module_cache_hits.get()));
+        self.module_cache_misses
+            .inc_by(stats.misses.saturating_sub(self.module_cache_misses.get()));
+        self.module_cache_entries.set(stats.entries as i64);
+    }
 }
 
 impl Drop for Metrics {
@@ -206,5 +298,14 @@ impl Drop for Metrics {
         unregister_metric!(self.registry, self.chain_height);
         unregister_metric!(self.registry, self.exec_block);
         unregister_metric!(self.registry, self.latest_commit_step);
+        unregister_metric!(self.registry, self.pruned_blocks);
+        unregister_metric!(self.registry, self.pruned_deploys);
+        unregister_metric!(self.registry, self.pruned_tries);
+        unregister_metric!(self.registry, self.pruned_trie_bytes);
+        unregister_metric!(self.registry, self.prune_tries);
+        unregister_metric!(self.registry, self.reexecuted_deploys);
+        unregister_metric!(self.registry, self.module_cache_hits);
+        unregister_metric!(self.registry, self.module_cache_misses);
+        unregister_metric!(self.registry, self.module_cache_entries);
     }
 }
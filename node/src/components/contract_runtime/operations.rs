@@ -1,7 +1,7 @@
 use std::{collections::BTreeMap, sync::Arc, time::Instant};
 
 use itertools::Itertools;
-use tracing::{debug, trace, warn};
+use tracing::{debug, info_span, trace, warn};
 
 use casper_execution_engine::{
     core::engine_state::{
@@ -9,13 +9,19 @@ use casper_execution_engine::{
         ExecutionResult as EngineExecutionResult, GetEraValidatorsRequest, RewardItem, StepError,
         StepRequest, StepSuccess,
     },
-    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+    shared::{
+        additive_map::AdditiveMap,
+        execution_journal::ExecutionJournal,
+        gas_profile::{self, GasProfile},
+        newtypes::CorrelationId,
+        transform::Transform,
+    },
     storage::global_state::lmdb::LmdbGlobalState,
 };
 use casper_hashing::Digest;
 use casper_types::{
-    bytesrepr::ToBytes, CLValue, DeployHash, EraId, ExecutionResult, Key, ProtocolVersion,
-    PublicKey, U512,
+    bytesrepr::ToBytes, system::auction::SeigniorageRecipients, CLValue, DeployHash, EraId,
+    ExecutionResult, Key, ProtocolVersion, PublicKey, StoredValue, U512,
 };
 
 use crate::{
@@ -26,7 +32,10 @@ use crate::{
             BlockAndExecutionEffects, ExecutionPreState, Metrics,
         },
     },
-    types::{error::BlockCreationError, Block, Deploy, DeployHeader, FinalizedBlock},
+    types::{
+        error::BlockCreationError, Block, Deploy, DeployHeader, EraRewards, EraStakes,
+        FinalizedBlock,
+    },
 };
 use casper_execution_engine::{
     core::{engine_state::execution_result::ExecutionResults, execution},
@@ -40,6 +49,7 @@ use super::SpeculativeExecutionState;
 pub fn execute_finalized_block(
     engine_state: &EngineState<LmdbGlobalState>,
     metrics: Option<Arc<Metrics>>,
+    deploy_execution_threads: usize,
     protocol_version: ProtocolVersion,
     execution_pre_state: ExecutionPreState,
     finalized_block: FinalizedBlock,
@@ -52,6 +62,13 @@ pub fn execute_finalized_block(
             execution_pre_state: Box::new(execution_pre_state),
         });
     }
+    let _block_span = info_span!(
+        "execute_block",
+        height = finalized_block.height(),
+        era = %finalized_block.era_id(),
+        deploy_count = deploys.len() + transfers.len(),
+    )
+    .entered();
     let ExecutionPreState {
         pre_state_root_hash,
         parent_hash,
@@ -69,23 +86,47 @@ pub fn execute_finalized_block(
     // Create a new EngineState that reads from LMDB but only caches changes in memory.
     let scratch_state = engine_state.get_scratch_engine_state();
 
-    for deploy in deploys.into_iter().chain(transfers) {
-        let deploy_hash = *deploy.id();
-        let deploy_header = deploy.header().clone();
-        let execute_request = ExecuteRequest::new(
-            state_root_hash,
-            block_time,
-            vec![DeployItem::from(deploy)],
-            protocol_version,
-            *finalized_block.proposer(),
-        );
+    let (deploy_ids, execute_requests): (Vec<_>, Vec<_>) = deploys
+        .into_iter()
+        .chain(transfers)
+        .map(|deploy| {
+            let deploy_id = (*deploy.id(), deploy.header().clone());
+            let execute_request = ExecuteRequest::new(
+                state_root_hash,
+                block_time,
+                vec![DeployItem::from(deploy)],
+                protocol_version,
+                *finalized_block.proposer(),
+            );
+            (deploy_id, execute_request)
+        })
+        .unzip();
+
+    // With more than one thread, all deploys are executed speculatively right away, and only the
+    // ones depending on the effects of an earlier deploy are executed again as their turn comes.
+    let mut execution = scratch_state.execute_optimistically(
+        CorrelationId::new(),
+        execute_requests,
+        deploy_execution_threads,
+    );
+
+    for (deploy_hash, deploy_header) in deploy_ids {
+        let _deploy_span = info_span!("execute_deploy", %deploy_hash).entered();
 
         // TODO: this is currently working coincidentally because we are passing only one
         // deploy_item per exec. The execution results coming back from the EE lack the
         // mapping between deploy_hash and execution result, and this outer logic is
         // enriching it with the deploy hash. If we were passing multiple deploys per exec
         // the relation between the deploy and the execution results would be lost.
-        let result = execute(&scratch_state, metrics.clone(), execute_request)?;
+        let execute_start = Instant::now();
+        let result = execution
+            .next()
+            .expect("should have one execute request per deploy")?;
+        if let Some(metrics) = metrics.as_ref() {
+            metrics
+                .run_execute
+                .observe(execute_start.elapsed().as_secs_f64());
+        }
 
         trace!(?deploy_hash, ?result, "deploy execution result");
         // As for now a given state is expected to exist.
@@ -100,6 +141,21 @@ pub fn execute_finalized_block(
         state_root_hash = state_hash;
     }
 
+    if execution.reexecuted() > 0 {
+        debug!(
+            reexecuted = execution.reexecuted(),
+            "re-executed deploys conflicting with earlier deploys"
+        );
+        if let Some(metrics) = metrics.as_ref() {
+            metrics
+                .reexecuted_deploys
+                .inc_by(execution.reexecuted() as u64);
+        }
+    }
+    if let Some(metrics) = metrics.as_ref() {
+        metrics.record_module_cache_stats(engine_state.module_cache_stats());
+    }
+
     // Write the deploy approvals and execution results Merkle root hashes to global state if there
     // were any deploys.
     let block_height = finalized_block.height();
@@ -136,6 +192,15 @@ pub fn execute_finalized_block(
     // validators.
     let maybe_step_effect_and_upcoming_era_validators =
         if let Some(era_report) = finalized_block.era_report() {
+            // The step removes the ending era's stakes and delegation rates from the auction's
+            // snapshot, so they are read beforehand to account for the rewards it distributes.
+            let maybe_seigniorage_recipients = get_seigniorage_recipients(
+                engine_state,
+                pre_state_root_hash,
+                protocol_version,
+                finalized_block.era_id(),
+            );
+
             let StepSuccess {
                 post_state_hash: _, // ignore the post-state-hash returned from scratch
                 execution_journal: step_execution_journal,
@@ -161,9 +226,29 @@ pub fn execute_finalized_block(
                 system_contract_registry,
                 GetEraValidatorsRequest::new(state_root_hash, protocol_version),
             )?;
+            let era_rewards =
+                maybe_seigniorage_recipients
+                    .as_ref()
+                    .and_then(|seigniorage_recipients| {
+                        era_rewards_from_step(
+                            finalized_block.era_id(),
+                            &step_execution_journal,
+                            seigniorage_recipients,
+                        )
+                    });
+            let era_stakes = maybe_seigniorage_recipients.map(|seigniorage_recipients| {
+                EraStakes::new(
+                    finalized_block.era_id(),
+                    &seigniorage_recipients,
+                    &era_report.equivocators,
+                    &era_report.inactive_validators,
+                )
+            });
             Some(StepEffectAndUpcomingEraValidators {
                 step_execution_journal,
                 upcoming_era_validators,
+                era_rewards,
+                era_stakes,
             })
         } else {
             // Finally, the new state-root-hash from the cumulative changes to global state is
@@ -282,12 +367,13 @@ where
 /// Execute the transaction without commiting the effects.
 /// Intended to be used for discovery operations on read-only nodes.
 ///
-/// Returns effects of the execution.
+/// Returns effects of the execution, along with its gas profile if `profile_gas` is set.
 pub fn execute_only<S>(
     engine_state: &EngineState<S>,
     execution_state: SpeculativeExecutionState,
     deploy: DeployItem,
-) -> Result<Option<ExecutionResult>, engine_state::Error>
+    profile_gas: bool,
+) -> Result<Option<(ExecutionResult, Option<GasProfile>)>, engine_state::Error>
 where
     S: StateProvider + CommitProvider,
     S::Error: Into<execution::Error>,
@@ -305,7 +391,8 @@ where
         protocol_version,
         PublicKey::System,
     );
-    let results = execute(engine_state, None, execute_request);
+    let (results, maybe_gas_profile) =
+        gas_profile::profiled(profile_gas, || execute(engine_state, None, execute_request));
     results.map(|mut execution_results| {
         let len = execution_results.len();
         if len != 1 {
@@ -319,7 +406,9 @@ where
             // with `Some(_)` but `pop_front` already returns an `Option`.
             // We need to transform the `engine_state::ExecutionResult` into
             // `casper_types::ExecutionResult` as well.
-            execution_results.pop_front().map(Into::into)
+            execution_results
+                .pop_front()
+                .map(|execution_result| (execution_result.into(), maybe_gas_profile))
         }
     })
 }
@@ -344,6 +433,45 @@ where
     result
 }
 
+/// Returns the stakes and delegation rates of the validators and delegators of the given era, if
+/// available.
+fn get_seigniorage_recipients(
+    engine_state: &EngineState<LmdbGlobalState>,
+    state_root_hash: Digest,
+    protocol_version: ProtocolVersion,
+    era_id: EraId,
+) -> Option<SeigniorageRecipients> {
+    match engine_state.get_seigniorage_recipients_snapshot(
+        CorrelationId::new(),
+        None,
+        GetEraValidatorsRequest::new(state_root_hash, protocol_version),
+    ) {
+        Ok(mut snapshot) => snapshot.remove(&era_id),
+        Err(error) => {
+            warn!(%error, "failed to get seigniorage recipients; not recording era rewards");
+            None
+        }
+    }
+}
+
+/// Collects the rewards distributed by a step from the era info it wrote to global state.
+fn era_rewards_from_step(
+    era_id: EraId,
+    step_execution_journal: &ExecutionJournal,
+    seigniorage_recipients: &SeigniorageRecipients,
+) -> Option<EraRewards> {
+    step_execution_journal
+        .iter()
+        .find_map(|(key, transform)| match (key, transform) {
+            (Key::EraInfo(key_era_id), Transform::Write(StoredValue::EraInfo(era_info)))
+                if *key_era_id == era_id =>
+            {
+                Some(EraRewards::new(era_id, era_info, seigniorage_recipients))
+            }
+            _ => None,
+        })
+}
+
 fn commit_step<S>(
     engine_state: &EngineState<S>,
     maybe_metrics: Option<Arc<Metrics>>,
@@ -8,7 +8,7 @@ use casper_execution_engine::{
 use casper_hashing::Digest;
 use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey, U512};
 
-use crate::types::{Block, DeployHash, DeployHeader};
+use crate::types::{Block, DeployHash, DeployHeader, EraRewards, EraStakes};
 
 /// Request for validator weights for a specific era.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -90,6 +90,10 @@ pub struct StepEffectAndUpcomingEraValidators {
     pub upcoming_era_validators: BTreeMap<EraId, BTreeMap<PublicKey, U512>>,
     /// An [`ExecutionJournal`] created by an era ending.
     pub step_execution_journal: ExecutionJournal,
+    /// The rewards distributed to the validators and delegators of the ending era.
+    pub era_rewards: Option<EraRewards>,
+    /// The stakes held in the ending era and the validators evicted at its end.
+    pub era_stakes: Option<EraStakes>,
 }
 
 /// A [`Block`] that was the result of execution in the `ContractRuntime` along with any execution
@@ -1,5 +1,7 @@
+mod config;
 mod event;
 mod metrics;
+mod policy;
 mod tests;
 
 use std::fmt::Debug;
@@ -8,35 +10,45 @@ use datasize::DataSize;
 use prometheus::Registry;
 use serde::Serialize;
 use thiserror::Error;
-use tracing::{debug, error};
+use tracing::{debug, error, info_span};
 
-use casper_execution_engine::core::engine_state::{
-    executable_deploy_item::{
-        ContractIdentifier, ContractPackageIdentifier, ExecutableDeployItemIdentifier,
+use casper_execution_engine::{
+    core::engine_state::{
+        executable_deploy_item::{
+            ContractIdentifier, ContractPackageIdentifier, ExecutableDeployItemIdentifier,
+        },
+        ExecutableDeployItem, MAX_PAYMENT,
     },
-    ExecutableDeployItem, MAX_PAYMENT,
+    shared::{wasm_config::WasmConfig, wasm_prep},
 };
 use casper_hashing::Digest;
 use casper_types::{
     account::{Account, AccountHash},
     system::auction::ARG_AMOUNT,
     Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersion,
-    ContractVersionKey, Key, ProtocolVersion, Timestamp, U512,
+    ContractVersionKey, InvalidTransactionV2, Key, ProtocolVersion, PublicKey, TimeDiff, Timestamp,
+    TransactionTarget, TransactionV2, U512,
 };
 
 use crate::{
     components::Component,
     effect::{
         announcements::DeployAcceptorAnnouncement,
-        requests::{ContractRuntimeRequest, StorageRequest},
+        requests::{BlockProposerRequest, ContractRuntimeRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects, Responder,
     },
-    types::{chainspec::DeployConfig, BlockHeader, Chainspec, Deploy, DeployConfigurationFailure},
+    types::{
+        chainspec::{CoreConfig, DeployConfig},
+        transaction, BlockHeader, Chainspec, Deploy, DeployConfigurationFailure, DeployHash,
+    },
     utils::Source,
     NodeRng,
 };
 
+pub use config::Config;
 pub(crate) use event::{Event, EventMetadata};
+use policy::AcceptanceRule;
+pub(crate) use policy::PolicyViolation;
 
 const ARG_TARGET: &str = "target";
 
@@ -65,6 +77,148 @@ pub(crate) enum Error {
         /// The timestamp when the node validated the expiry timestamp.
         current_node_timestamp: Timestamp,
     },
+
+    /// The deploy's timestamp lies further in the future than the node is willing to hold it for.
+    #[error(
+        "deploy timestamp {deploy_timestamp} exceeds latest accepted {max_accepted_timestamp}"
+    )]
+    TimestampInFuture {
+        /// The timestamp of the deploy.
+        deploy_timestamp: Timestamp,
+        /// The latest deploy timestamp the node accepts.
+        max_accepted_timestamp: Timestamp,
+    },
+
+    /// The deploy's module bytes failed wasm preprocessing.
+    #[error("invalid {executable} wasm: {error}")]
+    InvalidWasm {
+        /// Either "payment" or "session".
+        executable: &'static str,
+        /// The preprocessing error.
+        error: String,
+    },
+
+    /// The deploy was rejected by one of the operator-configured acceptance rules.
+    #[error("deploy rejected by acceptance policy: {0}")]
+    PolicyViolation(PolicyViolation),
+
+    /// The deploy or its account is on the node operator's blocklist.
+    #[error("deploy {deploy_hash} or its account {account_hash} is blocked")]
+    Blocked {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// The hash of the account which signed the deploy.
+        account_hash: AccountHash,
+    },
+
+    /// The deploy's account already has the maximum number of deploys pending inclusion.
+    #[error("account {account} has reached its quota of pending deploys")]
+    ExceededPendingDeployQuota {
+        /// The account which signed the deploy.
+        account: PublicKey,
+    },
+
+    /// The deploy has the same replacement tag as a pending deploy, but fails to outbid it.
+    #[error("deploy fails to outbid pending deploy {replaced} with the same replacement tag")]
+    UnderpricedReplacement {
+        /// The hash of the pending deploy.
+        replaced: DeployHash,
+    },
+
+    /// The node is shutting down and no longer accepts deploys from clients.
+    #[error("node is shutting down")]
+    ShuttingDown,
+
+    /// The transaction in the versioned format failed validation.
+    #[error("invalid transaction: {0}")]
+    InvalidTransactionV2(InvalidTransactionV2),
+
+    /// Transactions in the versioned format are not yet gossiped or executed, so they are rejected
+    /// once validated.
+    #[error("transactions in the versioned format are not yet supported")]
+    TransactionV2NotSupported,
+}
+
+impl Error {
+    /// Returns a short, stable identifier of the cause of the rejection, suitable for clients to
+    /// match on.
+    pub(crate) fn rejection_reason(&self) -> &'static str {
+        match self {
+            Error::EmptyBlockchain => "empty_blockchain",
+            Error::InvalidDeployConfiguration(failure) => match failure {
+                DeployConfigurationFailure::InvalidChainName { .. } => "invalid_chain_name",
+                DeployConfigurationFailure::ExcessiveDependencies { .. } => {
+                    "excessive_dependencies"
+                }
+                DeployConfigurationFailure::ExcessiveSize(_) => "excessive_size",
+                DeployConfigurationFailure::ExcessiveTimeToLive { .. } => "excessive_ttl",
+                DeployConfigurationFailure::InvalidBodyHash => "invalid_body_hash",
+                DeployConfigurationFailure::InvalidDeployHash => "invalid_deploy_hash",
+                DeployConfigurationFailure::EmptyApprovals => "empty_approvals",
+                DeployConfigurationFailure::InvalidApproval { .. } => "invalid_approval",
+                DeployConfigurationFailure::ExcessiveSessionArgsLength { .. } => {
+                    "excessive_session_args_length"
+                }
+                DeployConfigurationFailure::ExcessivePaymentArgsLength { .. } => {
+                    "excessive_payment_args_length"
+                }
+                DeployConfigurationFailure::MissingPaymentAmount => "missing_payment_amount",
+                DeployConfigurationFailure::FailedToParsePaymentAmount => "invalid_payment_amount",
+                DeployConfigurationFailure::ExceededBlockGasLimit { .. } => {
+                    "exceeded_block_gas_limit"
+                }
+                DeployConfigurationFailure::MissingTransferAmount => "missing_transfer_amount",
+                DeployConfigurationFailure::FailedToParseTransferAmount => {
+                    "invalid_transfer_amount"
+                }
+                DeployConfigurationFailure::InsufficientTransferAmount { .. } => {
+                    "insufficient_transfer_amount"
+                }
+                DeployConfigurationFailure::ExcessiveApprovals { .. } => "excessive_approvals",
+                DeployConfigurationFailure::MissingSequenceNumber => "missing_sequence_number",
+                DeployConfigurationFailure::FailedToParseSequenceNumber => {
+                    "invalid_sequence_number"
+                }
+                DeployConfigurationFailure::FailedToParseReplacementTag => {
+                    "invalid_replacement_tag"
+                }
+            },
+            Error::InvalidDeployParameters { failure, .. } => match failure {
+                DeployParameterFailure::NonexistentAccount { .. } => "nonexistent_account",
+                DeployParameterFailure::NonexistentContractAtHash { .. } => "nonexistent_contract",
+                DeployParameterFailure::NonexistentContractEntryPoint { .. } => {
+                    "nonexistent_entry_point"
+                }
+                DeployParameterFailure::NonexistentContractPackageAtHash { .. } => {
+                    "nonexistent_contract_package"
+                }
+                DeployParameterFailure::InvalidContractAtVersion { .. } => {
+                    "invalid_contract_version"
+                }
+                DeployParameterFailure::InvalidAssociatedKeys => "invalid_associated_keys",
+                DeployParameterFailure::InsufficientDeploySignatureWeight => {
+                    "insufficient_signature_weight"
+                }
+                DeployParameterFailure::InsufficientBalance { .. } => "insufficient_balance",
+                DeployParameterFailure::UnknownBalance { .. } => "unknown_balance",
+                DeployParameterFailure::InvalidPaymentVariant => "invalid_payment_variant",
+                DeployParameterFailure::MissingPaymentAmount => "missing_payment_amount",
+                DeployParameterFailure::FailedToParsePaymentAmount => "invalid_payment_amount",
+                DeployParameterFailure::MissingTransferTarget => "missing_transfer_target",
+                DeployParameterFailure::MissingModuleBytes => "missing_module_bytes",
+            },
+            Error::ExpiredDeploy { .. } => "expired",
+            Error::TimestampInFuture { .. } => "timestamp_in_future",
+            Error::InvalidWasm { .. } => "invalid_wasm",
+            Error::PolicyViolation(_) => "policy_violation",
+            Error::Blocked { .. } => "blocked",
+            Error::ExceededPendingDeployQuota { .. } => "exceeded_pending_deploy_quota",
+            Error::UnderpricedReplacement { .. } => "underpriced_replacement",
+            Error::ShuttingDown => "shutting_down",
+            Error::InvalidTransactionV2(_) => "invalid_transaction",
+            Error::TransactionV2NotSupported => "transaction_v2_not_supported",
+        }
+    }
 }
 
 /// A representation of the way in which a deploy failed validation checks.
@@ -122,6 +276,7 @@ pub(crate) trait ReactorEventT:
     + From<DeployAcceptorAnnouncement>
     + From<StorageRequest>
     + From<ContractRuntimeRequest>
+    + From<BlockProposerRequest>
     + Send
 {
 }
@@ -131,6 +286,7 @@ impl<REv> ReactorEventT for REv where
         + From<DeployAcceptorAnnouncement>
         + From<StorageRequest>
         + From<ContractRuntimeRequest>
+        + From<BlockProposerRequest>
         + Send
 {
 }
@@ -145,12 +301,19 @@ pub struct DeployAcceptor {
     chain_name: String,
     protocol_version: ProtocolVersion,
     deploy_config: DeployConfig,
+    core_config: CoreConfig,
     max_associated_keys: u32,
+    wasm_config: WasmConfig,
+    max_future_timestamp_window: TimeDiff,
+    acceptance_rules: Vec<Box<dyn AcceptanceRule>>,
     metrics: metrics::Metrics,
+    /// Whether deploys from clients are rejected, as the node is shutting down.
+    is_draining: bool,
 }
 
 impl DeployAcceptor {
     pub(crate) fn new(
+        config: Config,
         chainspec: &Chainspec,
         registry: &Registry,
     ) -> Result<Self, prometheus::Error> {
@@ -158,23 +321,48 @@ impl DeployAcceptor {
             chain_name: chainspec.network_config.name.clone(),
             protocol_version: chainspec.protocol_version(),
             deploy_config: chainspec.deploy_config,
+            core_config: chainspec.core_config,
             max_associated_keys: chainspec.core_config.max_associated_keys,
+            wasm_config: chainspec.wasm_config,
+            max_future_timestamp_window: config.max_future_timestamp_window,
+            acceptance_rules: policy::rules_from_config(config.acceptance_policy),
             metrics: metrics::Metrics::new(registry)?,
+            is_draining: false,
         })
     }
 
+    /// Stops accepting deploys from clients ahead of a shutdown.
+    ///
+    /// Deploys from peers are still accepted, as they may be required to validate blocks.
+    pub(crate) fn stop_accepting(&mut self) {
+        self.is_draining = true;
+    }
+
     /// Handles receiving a new `Deploy` from a peer or client.
     /// In the case of a peer, there should be no responder and the variant should be `None`
     /// In the case of a client, there should be a responder to communicate the validity of the
     /// deploy and the variant will be `Some`
+    ///
+    /// If `is_precheck` is true, the deploy is neither stored nor announced, whether valid or not.
     fn accept<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
         deploy: Box<Deploy>,
         source: Source,
         maybe_responder: Option<Responder<Result<(), Error>>>,
+        is_precheck: bool,
     ) -> Effects<Event> {
         let verification_start_timestamp = Timestamp::now();
+        if self.is_draining && source.is_client() {
+            debug!(%deploy, "rejecting deploy while shutting down");
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                EventMetadata::new(deploy, source, maybe_responder, is_precheck),
+                Error::ShuttingDown,
+                verification_start_timestamp,
+            );
+        }
+
         let acceptable_result = deploy.is_config_compliant(
             &self.chain_name,
             &self.deploy_config,
@@ -185,12 +373,46 @@ impl DeployAcceptor {
             debug!(%deploy, %error, "deploy is incorrectly configured");
             return self.handle_invalid_deploy_result(
                 effect_builder,
-                EventMetadata::new(deploy, source, maybe_responder),
+                EventMetadata::new(deploy, source, maybe_responder, is_precheck),
                 Error::InvalidDeployConfiguration(error),
                 verification_start_timestamp,
             );
         }
 
+        // Deploys dated slightly in the future are held by the block proposer until they become
+        // valid, but we don't hold on to client deploys dated beyond the configured window.  As
+        // with expiry, deploys from peers are not checked, as they may already be included in
+        // blocks proposed by validators with clocks ahead of ours.
+        let max_accepted_timestamp =
+            verification_start_timestamp.saturating_add(self.max_future_timestamp_window);
+        if source.is_client() && deploy.header().timestamp() > max_accepted_timestamp {
+            let deploy_timestamp = deploy.header().timestamp();
+            debug!(%deploy, %max_accepted_timestamp, "deploy timestamp is too far in the future");
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                EventMetadata::new(deploy, source, maybe_responder, is_precheck),
+                Error::TimestampInFuture {
+                    deploy_timestamp,
+                    max_accepted_timestamp,
+                },
+                verification_start_timestamp,
+            );
+        }
+
+        // We only apply the operator's acceptance rules to deploys received from the client, as
+        // deploys from peers may already be included in blocks we need to validate.
+        if source.is_client() {
+            if let Err(violation) = self.check_acceptance_rules(&deploy) {
+                debug!(%deploy, %violation, "deploy violates acceptance policy");
+                return self.handle_invalid_deploy_result(
+                    effect_builder,
+                    EventMetadata::new(deploy, source, maybe_responder, is_precheck),
+                    Error::PolicyViolation(violation),
+                    verification_start_timestamp,
+                );
+            }
+        }
+
         // We only perform expiry checks on deploys received from the client.
         if source.is_client() {
             let current_node_timestamp = Timestamp::now();
@@ -199,7 +421,7 @@ impl DeployAcceptor {
                 debug!(%deploy, "deploy has expired");
                 return self.handle_invalid_deploy_result(
                     effect_builder,
-                    EventMetadata::new(deploy, source, maybe_responder),
+                    EventMetadata::new(deploy, source, maybe_responder, is_precheck),
                     Error::ExpiredDeploy {
                         deploy_expiry_timestamp: time_of_expiry,
                         current_node_timestamp,
@@ -209,19 +431,245 @@ impl DeployAcceptor {
             }
         }
 
+        // Deploys from the client are run through wasm preprocessing, so that those which would
+        // certainly fail to execute are rejected before being gossiped rather than wasting a slot
+        // in a block and the account's payment.
+        if source.is_client() || is_precheck {
+            if let Err(error) = self.preprocess_wasm(&deploy) {
+                debug!(%deploy, %error, "deploy has invalid wasm");
+                return self.handle_invalid_deploy_result(
+                    effect_builder,
+                    EventMetadata::new(deploy, source, maybe_responder, is_precheck),
+                    error,
+                    verification_start_timestamp,
+                );
+            }
+        }
+
+        // The operator's blocklist applies to deploys from peers too, so blocked deploys are
+        // neither stored nor gossiped further.
+        let deploy_hash = *deploy.id();
+        let account_hash = deploy.header().account().to_account_hash();
+        let event_metadata = EventMetadata::new(deploy, source, maybe_responder, is_precheck);
+        effect_builder
+            .check_deploy_blocklist(deploy_hash, account_hash)
+            .event(move |is_blocked| Event::CheckDeployBlocklistResult {
+                event_metadata,
+                is_blocked,
+                verification_start_timestamp,
+            })
+    }
+
+    /// Handles receiving a new transaction in the versioned format from a client.
+    ///
+    /// Such transactions can be neither gossiped nor executed yet, so they are always rejected.  They
+    /// are validated first so that clients learn about any other problems with them.
+    fn accept_transaction_v2(
+        &mut self,
+        transaction: Box<TransactionV2>,
+        responder: Responder<Result<(), Error>>,
+    ) -> Effects<Event> {
+        let verification_start_timestamp = Timestamp::now();
+        let error = self
+            .check_transaction_v2(&transaction, verification_start_timestamp)
+            .err()
+            .unwrap_or(Error::TransactionV2NotSupported);
+        debug!(%transaction, %error, "rejecting transaction");
+        self.metrics.observe_rejected(verification_start_timestamp);
+        responder.respond(Err(error)).ignore()
+    }
+
+    /// Runs the checks on a transaction in the versioned format which need no other components.
+    fn check_transaction_v2(
+        &self,
+        transaction: &TransactionV2,
+        verification_start_timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        if self.is_draining {
+            return Err(Error::ShuttingDown);
+        }
+
+        transaction::is_config_compliant(
+            transaction,
+            &self.chain_name,
+            &self.deploy_config,
+            self.max_associated_keys,
+        )
+        .map_err(Error::InvalidDeployConfiguration)?;
+
+        let max_accepted_timestamp =
+            verification_start_timestamp.saturating_add(self.max_future_timestamp_window);
+        if transaction.header().timestamp() > max_accepted_timestamp {
+            return Err(Error::TimestampInFuture {
+                deploy_timestamp: transaction.header().timestamp(),
+                max_accepted_timestamp,
+            });
+        }
+
+        let current_node_timestamp = Timestamp::now();
+        if transaction.header().expired(current_node_timestamp) {
+            return Err(Error::ExpiredDeploy {
+                deploy_expiry_timestamp: transaction.header().expires(),
+                current_node_timestamp,
+            });
+        }
+
+        if let TransactionTarget::Session { module_bytes } = transaction.body().target() {
+            if let Err(error) = wasm_prep::preprocess(self.wasm_config, module_bytes) {
+                return Err(Error::InvalidWasm {
+                    executable: "session",
+                    error: error.to_string(),
+                });
+            }
+        }
+
+        transaction.verify().map_err(Error::InvalidTransactionV2)
+    }
+
+    fn handle_check_deploy_blocklist_result<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        event_metadata: EventMetadata,
+        is_blocked: bool,
+        verification_start_timestamp: Timestamp,
+    ) -> Effects<Event> {
+        if is_blocked {
+            let deploy_hash = *event_metadata.deploy.id();
+            let account_hash = event_metadata.deploy.header().account().to_account_hash();
+            debug!(deploy = %event_metadata.deploy, "deploy is blocked");
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                event_metadata,
+                Error::Blocked {
+                    deploy_hash,
+                    account_hash,
+                },
+                verification_start_timestamp,
+            );
+        }
+
+        // We only enforce the per-account quota of pending deploys on deploys received from the
+        // client.  Deploys from peers exceeding it are dropped by the block proposer instead.
+        if event_metadata.source.is_client() {
+            let account = event_metadata.deploy.header().account().clone();
+            return effect_builder
+                .check_pending_deploy_quota(account)
+                .event(move |within_quota| Event::CheckPendingDeployQuotaResult {
+                    event_metadata,
+                    within_quota,
+                    verification_start_timestamp,
+                });
+        }
+
+        self.get_highest_block_header(effect_builder, event_metadata, verification_start_timestamp)
+    }
+
+    fn handle_check_pending_deploy_quota_result<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        event_metadata: EventMetadata,
+        within_quota: bool,
+        verification_start_timestamp: Timestamp,
+    ) -> Effects<Event> {
+        if !within_quota {
+            let account = event_metadata.deploy.header().account().clone();
+            debug!(
+                deploy = %event_metadata.deploy, %account,
+                "account reached pending deploy quota"
+            );
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                event_metadata,
+                Error::ExceededPendingDeployQuota { account },
+                verification_start_timestamp,
+            );
+        }
+
+        // Like the quota, underpriced replacements are only rejected here if received from the
+        // client; the block proposer drops those from peers.
+        if let Ok(deploy_info) = event_metadata.deploy.deploy_info() {
+            let hash = event_metadata.deploy.deploy_or_transfer_hash();
+            return effect_builder
+                .check_deploy_replacement(hash, deploy_info)
+                .event(move |maybe_replaced| Event::CheckDeployReplacementResult {
+                    event_metadata,
+                    maybe_replaced,
+                    verification_start_timestamp,
+                });
+        }
+
+        self.get_highest_block_header(effect_builder, event_metadata, verification_start_timestamp)
+    }
+
+    fn handle_check_deploy_replacement_result<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        event_metadata: EventMetadata,
+        maybe_replaced: Option<DeployHash>,
+        verification_start_timestamp: Timestamp,
+    ) -> Effects<Event> {
+        if let Some(replaced) = maybe_replaced {
+            debug!(
+                deploy = %event_metadata.deploy, %replaced,
+                "deploy fails to outbid pending deploy with the same replacement tag"
+            );
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                event_metadata,
+                Error::UnderpricedReplacement { replaced },
+                verification_start_timestamp,
+            );
+        }
+
+        self.get_highest_block_header(effect_builder, event_metadata, verification_start_timestamp)
+    }
+
+    fn get_highest_block_header<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        event_metadata: EventMetadata,
+        verification_start_timestamp: Timestamp,
+    ) -> Effects<Event> {
         effect_builder
             .get_highest_block_header_from_storage()
             .event(move |maybe_block_header| Event::GetBlockHeaderResult {
-                event_metadata: EventMetadata::new(deploy, source, maybe_responder),
+                event_metadata,
                 maybe_block_header: Box::new(maybe_block_header),
                 verification_start_timestamp,
             })
     }
 
+    /// Checks the deploy against each of the configured acceptance rules.
+    fn check_acceptance_rules(&self, deploy: &Deploy) -> Result<(), PolicyViolation> {
+        self.acceptance_rules
+            .iter()
+            .try_for_each(|rule| rule.check(deploy))
+    }
+
+    /// Runs the non-empty module bytes of the deploy's payment and session through wasm
+    /// preprocessing.
+    fn preprocess_wasm(&self, deploy: &Deploy) -> Result<(), Error> {
+        let executables = [("payment", deploy.payment()), ("session", deploy.session())];
+        for (executable, item) in executables {
+            if let ExecutableDeployItem::ModuleBytes { module_bytes, .. } = item {
+                if module_bytes.is_empty() {
+                    continue;
+                }
+                if let Err(error) = wasm_prep::preprocess(self.wasm_config, module_bytes) {
+                    return Err(Error::InvalidWasm {
+                        executable,
+                        error: error.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn handle_get_block_result<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
-        event_metadata: EventMetadata,
+        mut event_metadata: EventMetadata,
         maybe_block: Option<BlockHeader>,
         verification_start_timestamp: Timestamp,
     ) -> Effects<Event> {
@@ -238,6 +686,8 @@ impl DeployAcceptor {
             }
         };
 
+        event_metadata.signing_context_active =
+            self.core_config.is_signing_context_active(block.era_id());
         let prestate_hash = *block.state_root_hash();
         let account_hash = event_metadata.deploy.header().account().to_account_hash();
         let account_key = account_hash.into();
@@ -765,6 +1215,7 @@ impl DeployAcceptor {
             deploy,
             source,
             maybe_responder,
+            ..
         } = event_metadata;
         self.metrics.observe_accepted(verification_start_timestamp);
         let mut effects = Effects::new();
@@ -789,7 +1240,10 @@ impl DeployAcceptor {
         event_metadata: EventMetadata,
         verification_start_timestamp: Timestamp,
     ) -> Effects<Event> {
-        if let Err(deploy_configuration_failure) = event_metadata.deploy.is_valid() {
+        if let Err(deploy_configuration_failure) = event_metadata
+            .deploy
+            .is_valid_with_signing_context(event_metadata.signing_context_active)
+        {
             // The client has submitted a deploy with one or more invalid signatures.
             // Return an error to the RPC component via the responder.
             debug!("deploy is cryptographically invalid");
@@ -801,6 +1255,14 @@ impl DeployAcceptor {
             );
         }
 
+        if event_metadata.is_precheck {
+            // The deploy passed all checks, but shouldn't be stored or announced.
+            if let Some(responder) = event_metadata.maybe_responder {
+                return responder.respond(Ok(())).ignore();
+            }
+            return Effects::new();
+        }
+
         effect_builder
             .put_deploy_to_storage(Box::new((*event_metadata.deploy).clone()))
             .event(move |is_new| Event::PutToStorageResult {
@@ -821,14 +1283,18 @@ impl DeployAcceptor {
             deploy,
             source,
             maybe_responder,
+            is_precheck,
         } = event_metadata;
-        self.metrics.observe_rejected(verification_start_timestamp);
         let mut effects = Effects::new();
         if let Some(responder) = maybe_responder {
             // The client has submitted an invalid deploy
             // Return an error to the RPC component via the responder.
             effects.extend(responder.respond(Err(error)).ignore());
         }
+        if is_precheck {
+            return effects;
+        }
+        self.metrics.observe_rejected(verification_start_timestamp);
         effects.extend(
             effect_builder
                 .announce_invalid_deploy(deploy, source)
@@ -848,13 +1314,59 @@ impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
         _rng: &mut NodeRng,
         event: Self::Event,
     ) -> Effects<Self::Event> {
+        // Every step of accepting a deploy is handled in a span carrying the deploy's hash, so
+        // that the steps can be followed together in exported traces.
+        let _entered = event
+            .deploy()
+            .map(|deploy| info_span!("accept_deploy", deploy_hash = %deploy.id()).entered());
         debug!(?event, "handling event");
         match event {
             Event::Accept {
                 deploy,
                 source,
                 maybe_responder: responder,
-            } => self.accept(effect_builder, deploy, source, responder),
+            } => self.accept(effect_builder, deploy, source, responder, false),
+            Event::Precheck { deploy, responder } => self.accept(
+                effect_builder,
+                deploy,
+                Source::Client,
+                Some(responder),
+                true,
+            ),
+            Event::AcceptTransactionV2 {
+                transaction,
+                responder,
+            } => self.accept_transaction_v2(transaction, responder),
+            Event::CheckDeployBlocklistResult {
+                event_metadata,
+                is_blocked,
+                verification_start_timestamp,
+            } => self.handle_check_deploy_blocklist_result(
+                effect_builder,
+                event_metadata,
+                is_blocked,
+                verification_start_timestamp,
+            ),
+            Event::CheckPendingDeployQuotaResult {
+                event_metadata,
+                within_quota,
+                verification_start_timestamp,
+            } => self.handle_check_pending_deploy_quota_result(
+                effect_builder,
+                event_metadata,
+                within_quota,
+                verification_start_timestamp,
+            ),
+            Event::CheckDeployReplacementResult {
+                event_metadata,
+                maybe_replaced,
+                verification_start_timestamp,
+            } => self.handle_check_deploy_replacement_result(
+                effect_builder,
+                event_metadata,
+                maybe_replaced,
+                verification_start_timestamp,
+            ),
             Event::GetBlockHeaderResult {
                 event_metadata,
                 maybe_block_header,
@@ -0,0 +1,34 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use casper_types::TimeDiff;
+
+use super::policy::AcceptancePolicyConfig;
+
+/// Deploy acceptor configuration.
+#[derive(DataSize, Debug, Deserialize, Serialize, Clone)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// How far into the future a deploy's timestamp may lie for it to be accepted.  Such deploys
+    /// are held by the block proposer until their timestamp is reached; deploys from clients dated
+    /// further in the future are rejected.
+    #[serde(default = "default_max_future_timestamp_window")]
+    pub max_future_timestamp_window: TimeDiff,
+    /// Additional acceptance rules applied to deploys received from clients.
+    #[serde(default)]
+    pub acceptance_policy: AcceptancePolicyConfig,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            max_future_timestamp_window: default_max_future_timestamp_window(),
+            acceptance_policy: AcceptancePolicyConfig::default(),
+        }
+    }
+}
+
+fn default_max_future_timestamp_window() -> TimeDiff {
+    "10min".parse().unwrap()
+}
@@ -6,13 +6,14 @@ use super::Source;
 use crate::{
     components::deploy_acceptor::Error,
     effect::{announcements::RpcServerAnnouncement, Responder},
-    types::{BlockHeader, Deploy},
+    types::{BlockHeader, Deploy, DeployHash},
 };
 
 use casper_hashing::Digest;
 use casper_types::{
     account::{Account, AccountHash},
-    Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersion, Timestamp, U512,
+    Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersion, Timestamp,
+    TransactionV2, U512,
 };
 
 /// A utility struct to hold duplicated information across events.
@@ -21,6 +22,11 @@ pub(crate) struct EventMetadata {
     pub(crate) deploy: Box<Deploy>,
     pub(crate) source: Source,
     pub(crate) maybe_responder: Option<Responder<Result<(), Error>>>,
+    /// Whether the deploy should only be validated, and neither stored nor announced.
+    pub(crate) is_precheck: bool,
+    /// Whether the deploy's approvals must be signed in their signing context, as of the era of
+    /// the highest block.  Only known once the highest block header has been read.
+    pub(crate) signing_context_active: bool,
 }
 
 impl EventMetadata {
@@ -28,11 +34,14 @@ impl EventMetadata {
         deploy: Box<Deploy>,
         source: Source,
         maybe_responder: Option<Responder<Result<(), Error>>>,
+        is_precheck: bool,
     ) -> Self {
         EventMetadata {
             deploy,
             source,
             maybe_responder,
+            is_precheck,
+            signing_context_active: false,
         }
     }
 }
@@ -46,12 +55,44 @@ pub(crate) enum Event {
         source: Source,
         maybe_responder: Option<Responder<Result<(), Error>>>,
     },
+    /// The initiating event to validate a `Deploy` received from a client without storing or
+    /// announcing it.
+    Precheck {
+        deploy: Box<Deploy>,
+        responder: Responder<Result<(), Error>>,
+    },
+    /// The initiating event to accept a new transaction in the versioned format from a client.
+    AcceptTransactionV2 {
+        transaction: Box<TransactionV2>,
+        responder: Responder<Result<(), Error>>,
+    },
     /// The result of the `DeployAcceptor` putting a `Deploy` to the storage component.
     PutToStorageResult {
         event_metadata: EventMetadata,
         is_new: bool,
         verification_start_timestamp: Timestamp,
     },
+    /// The result of asking the block proposer whether the `Deploy` or its account is on the
+    /// operator's blocklist.
+    CheckDeployBlocklistResult {
+        event_metadata: EventMetadata,
+        is_blocked: bool,
+        verification_start_timestamp: Timestamp,
+    },
+    /// The result of asking the block proposer whether the `Deploy`'s account is within its quota
+    /// of pending deploys.
+    CheckPendingDeployQuotaResult {
+        event_metadata: EventMetadata,
+        within_quota: bool,
+        verification_start_timestamp: Timestamp,
+    },
+    /// The result of asking the block proposer whether the `Deploy` fails to outbid the pending
+    /// deploy with the same replacement tag.
+    CheckDeployReplacementResult {
+        event_metadata: EventMetadata,
+        maybe_replaced: Option<DeployHash>,
+        verification_start_timestamp: Timestamp,
+    },
     /// The result of querying the highest available `BlockHeader` from the storage component.
     GetBlockHeaderResult {
         event_metadata: EventMetadata,
@@ -94,6 +135,27 @@ pub(crate) enum Event {
     },
 }
 
+impl Event {
+    /// Returns the deploy being accepted, if any.
+    pub(crate) fn deploy(&self) -> Option<&Deploy> {
+        match self {
+            Event::Accept { deploy, .. } | Event::Precheck { deploy, .. } => Some(deploy),
+            Event::PutToStorageResult { event_metadata, .. }
+            | Event::CheckDeployBlocklistResult { event_metadata, .. }
+            | Event::CheckPendingDeployQuotaResult { event_metadata, .. }
+            | Event::CheckDeployReplacementResult { event_metadata, .. }
+            | Event::GetBlockHeaderResult { event_metadata, .. }
+            | Event::GetAccountResult { event_metadata, .. }
+            | Event::GetBalanceResult { event_metadata, .. }
+            | Event::GetContractResult { event_metadata, .. }
+            | Event::GetContractPackageResult { event_metadata, .. } => {
+                Some(&event_metadata.deploy)
+            }
+            Event::AcceptTransactionV2 { .. } => None,
+        }
+    }
+}
+
 impl From<RpcServerAnnouncement> for Event {
     fn from(announcement: RpcServerAnnouncement) -> Self {
         match announcement {
@@ -102,6 +164,16 @@ impl From<RpcServerAnnouncement> for Event {
                 source: Source::Client,
                 maybe_responder: responder,
             },
+            RpcServerAnnouncement::DeployPrecheckRequested { deploy, responder } => {
+                Event::Precheck { deploy, responder }
+            }
+            RpcServerAnnouncement::TransactionV2Received {
+                transaction,
+                responder,
+            } => Event::AcceptTransactionV2 {
+                transaction,
+                responder,
+            },
         }
     }
 }
@@ -112,6 +184,12 @@ impl Display for Event {
             Event::Accept { deploy, source, .. } => {
                 write!(formatter, "accept {} from {}", deploy.id(), source)
             }
+            Event::Precheck { deploy, .. } => {
+                write!(formatter, "precheck {}", deploy.id())
+            }
+            Event::AcceptTransactionV2 { transaction, .. } => {
+                write!(formatter, "accept transaction {}", transaction.hash())
+            }
             Event::PutToStorageResult {
                 event_metadata,
                 is_new,
@@ -131,6 +209,27 @@ impl Display for Event {
                     )
                 }
             }
+            Event::CheckDeployBlocklistResult { event_metadata, .. } => {
+                write!(
+                    formatter,
+                    "checked deploy blocklist to validate deploy with hash: {}.",
+                    event_metadata.deploy.id()
+                )
+            }
+            Event::CheckPendingDeployQuotaResult { event_metadata, .. } => {
+                write!(
+                    formatter,
+                    "checked pending deploy quota to validate deploy with hash: {}.",
+                    event_metadata.deploy.id()
+                )
+            }
+            Event::CheckDeployReplacementResult { event_metadata, .. } => {
+                write!(
+                    formatter,
+                    "checked deploy replacement to validate deploy with hash: {}.",
+                    event_metadata.deploy.id()
+                )
+            }
             Event::GetBlockHeaderResult { event_metadata, .. } => {
                 write!(
                     formatter,
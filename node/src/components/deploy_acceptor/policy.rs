@@ -0,0 +1,168 @@
+//! Operator-configurable acceptance rules, evaluated in addition to the protocol's own validity
+//! checks.
+
+use std::fmt::Debug;
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_execution_engine::core::engine_state::ExecutableDeployItem;
+use casper_types::{
+    system::standard_payment::ARG_AMOUNT, ContractHash, ContractPackageHash, PublicKey, U512,
+};
+
+use crate::types::Deploy;
+
+/// Configuration of the additional acceptance rules applied to deploys received from clients.
+#[derive(Clone, DataSize, Debug, Default, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct AcceptancePolicyConfig {
+    /// The minimum payment amount in motes.  Only enforced on deploys whose payment code takes an
+    /// "amount" argument.  `0` means no minimum.
+    #[serde(default)]
+    pub min_payment_amount: u64,
+    /// Contracts which deploys must not call, either as payment or as session code.
+    #[serde(default)]
+    pub banned_contract_hashes: Vec<ContractHash>,
+    /// Contract packages which deploys must not call, either as payment or as session code.
+    #[serde(default)]
+    pub banned_contract_package_hashes: Vec<ContractPackageHash>,
+    /// The only accounts allowed to submit deploys.  Empty means all accounts are allowed.
+    #[serde(default)]
+    pub allowed_accounts: Vec<PublicKey>,
+}
+
+/// The reason a deploy was rejected by an acceptance rule.
+#[derive(Clone, Debug, Error, Serialize)]
+pub(crate) enum PolicyViolation {
+    /// The deploy's payment amount is below the configured minimum.
+    #[error("payment amount {payment_amount} is below the minimum of {min_payment_amount}")]
+    PaymentAmountTooLow {
+        payment_amount: U512,
+        min_payment_amount: U512,
+    },
+    /// The deploy calls a banned contract.
+    #[error("{executable} calls banned contract {contract_hash}")]
+    BannedContract {
+        /// Either "payment" or "session".
+        executable: &'static str,
+        contract_hash: ContractHash,
+    },
+    /// The deploy calls a banned contract package.
+    #[error("{executable} calls banned contract package {contract_package_hash}")]
+    BannedContractPackage {
+        /// Either "payment" or "session".
+        executable: &'static str,
+        contract_package_hash: ContractPackageHash,
+    },
+    /// The deploy's account is not on the allowlist.
+    #[error("account {account} is not allowed to submit deploys")]
+    AccountNotAllowed { account: PublicKey },
+}
+
+/// An acceptance rule which every deploy received from a client must pass.
+pub(crate) trait AcceptanceRule: Debug + Send {
+    /// Checks the deploy against the rule.
+    fn check(&self, deploy: &Deploy) -> Result<(), PolicyViolation>;
+}
+
+/// Rejects deploys paying less than a minimum amount.
+#[derive(Debug)]
+struct MinPaymentAmount(U512);
+
+impl AcceptanceRule for MinPaymentAmount {
+    fn check(&self, deploy: &Deploy) -> Result<(), PolicyViolation> {
+        let payment_amount = match deploy
+            .payment()
+            .args()
+            .get(ARG_AMOUNT)
+            .and_then(|value| value.clone().into_t::<U512>().ok())
+        {
+            Some(payment_amount) => payment_amount,
+            // A missing or malformed amount is caught by the regular payment checks.
+            None => return Ok(()),
+        };
+        if payment_amount < self.0 {
+            return Err(PolicyViolation::PaymentAmountTooLow {
+                payment_amount,
+                min_payment_amount: self.0,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Rejects deploys calling banned contracts or contract packages by hash.
+#[derive(Debug)]
+struct BannedContracts {
+    contract_hashes: Vec<ContractHash>,
+    contract_package_hashes: Vec<ContractPackageHash>,
+}
+
+impl AcceptanceRule for BannedContracts {
+    fn check(&self, deploy: &Deploy) -> Result<(), PolicyViolation> {
+        let executables = [("payment", deploy.payment()), ("session", deploy.session())];
+        for (executable, item) in executables {
+            match item {
+                ExecutableDeployItem::StoredContractByHash { hash, .. }
+                    if self.contract_hashes.contains(hash) =>
+                {
+                    return Err(PolicyViolation::BannedContract {
+                        executable,
+                        contract_hash: *hash,
+                    });
+                }
+                ExecutableDeployItem::StoredVersionedContractByHash { hash, .. }
+                    if self.contract_package_hashes.contains(hash) =>
+                {
+                    return Err(PolicyViolation::BannedContractPackage {
+                        executable,
+                        contract_package_hash: *hash,
+                    });
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rejects deploys from accounts not on an allowlist.
+#[derive(Debug)]
+struct AccountAllowlist(Vec<PublicKey>);
+
+impl AcceptanceRule for AccountAllowlist {
+    fn check(&self, deploy: &Deploy) -> Result<(), PolicyViolation> {
+        let account = deploy.header().account();
+        if !self.0.contains(account) {
+            return Err(PolicyViolation::AccountNotAllowed {
+                account: account.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Builds the acceptance rules enabled by the given config.
+pub(super) fn rules_from_config(config: AcceptancePolicyConfig) -> Vec<Box<dyn AcceptanceRule>> {
+    let mut rules: Vec<Box<dyn AcceptanceRule>> = vec![];
+    if config.min_payment_amount > 0 {
+        rules.push(Box::new(MinPaymentAmount(U512::from(
+            config.min_payment_amount,
+        ))));
+    }
+    if !config.banned_contract_hashes.is_empty()
+        || !config.banned_contract_package_hashes.is_empty()
+    {
+        rules.push(Box::new(BannedContracts {
+            contract_hashes: config.banned_contract_hashes,
+            contract_package_hashes: config.banned_contract_package_hashes,
+        }));
+    }
+    if !config.allowed_accounts.is_empty() {
+        rules.push(Box::new(AccountAllowlist(config.allowed_accounts)));
+    }
+    rules
+}
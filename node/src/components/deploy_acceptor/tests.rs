@@ -21,15 +21,16 @@ use casper_execution_engine::{
 };
 use casper_types::{
     account::{Account, ActionThresholds, AssociatedKeys, Weight},
-    CLValue, StoredValue, URef, U512,
+    CLValue, PricingMode, RuntimeArgs, SecretKey, StoredValue, TransactionEntryPoint,
+    TransactionInvocationTarget, TransactionV2Body, URef, U512,
 };
 
-use super::*;
+use super::{policy::AcceptancePolicyConfig, *};
 use crate::{
     components::storage::{self, Storage},
     effect::{
         announcements::{ControlAnnouncement, DeployAcceptorAnnouncement},
-        requests::{ContractRuntimeRequest, NetworkRequest},
+        requests::{BlockProposerRequest, ContractRuntimeRequest, NetworkRequest},
         Responder,
     },
     logging,
@@ -63,6 +64,8 @@ enum Event {
     StorageRequest(StorageRequest),
     #[from]
     NetworkRequest(NetworkRequest<Message>),
+    #[from]
+    BlockProposerRequest(#[serde(skip_serializing)] BlockProposerRequest),
 }
 
 impl ReactorEvent for Event {
@@ -98,6 +101,9 @@ impl Display for Event {
             }
             Event::StorageRequest(request) => write!(formatter, "storage request: {:?}", request),
             Event::NetworkRequest(request) => write!(formatter, "network request: {:?}", request),
+            Event::BlockProposerRequest(request) => {
+                write!(formatter, "block proposer request: {}", request)
+            }
         }
     }
 }
@@ -151,6 +157,7 @@ enum TestScenario {
     FromClientSessionContractPackage(ContractPackageScenario),
     DeployWithNativeTransferInPayment,
     DeployWithEmptySessionModuleBytes,
+    DeployWithInvalidSessionWasm,
     DeployWithoutPaymentAmount,
     DeployWithMangledPaymentAmount,
     DeployWithMangledTransferAmount,
@@ -159,6 +166,13 @@ enum TestScenario {
     BalanceCheckForDeploySentByPeer,
     ShouldNotAcceptExpiredDeploySentByClient,
     ShouldAcceptExpiredDeploySentByPeer,
+    FromClientExceedingPendingDeployQuota,
+    FromClientUnderpricedReplacement,
+    FromClientBlockedDeploy,
+    FromPeerBlockedDeploy,
+    FromClientFutureDatedDeployWithinWindow,
+    FromClientDeployTooFarInFuture,
+    FromPeerDeployTooFarInFuture,
 }
 
 impl TestScenario {
@@ -175,9 +189,9 @@ impl TestScenario {
             | TestScenario::FromPeerCustomPaymentContractPackage(_)
             | TestScenario::FromPeerSessionContract(_)
             | TestScenario::FromPeerSessionContractPackage(_)
-            | TestScenario::ShouldAcceptExpiredDeploySentByPeer => {
-                Source::Peer(NodeId::random(rng))
-            }
+            | TestScenario::ShouldAcceptExpiredDeploySentByPeer
+            | TestScenario::FromPeerBlockedDeploy
+            | TestScenario::FromPeerDeployTooFarInFuture => Source::Peer(NodeId::random(rng)),
             TestScenario::FromClientInvalidDeploy
             | TestScenario::FromClientMissingAccount
             | TestScenario::FromClientInsufficientBalance
@@ -196,8 +210,14 @@ impl TestScenario {
             | TestScenario::FromClientSessionContract(_)
             | TestScenario::FromClientSessionContractPackage(_)
             | TestScenario::DeployWithEmptySessionModuleBytes
+            | TestScenario::DeployWithInvalidSessionWasm
             | TestScenario::DeployWithNativeTransferInPayment
-            | TestScenario::ShouldNotAcceptExpiredDeploySentByClient => Source::Client,
+            | TestScenario::ShouldNotAcceptExpiredDeploySentByClient
+            | TestScenario::FromClientExceedingPendingDeployQuota
+            | TestScenario::FromClientUnderpricedReplacement
+            | TestScenario::FromClientBlockedDeploy
+            | TestScenario::FromClientFutureDatedDeployWithinWindow
+            | TestScenario::FromClientDeployTooFarInFuture => Source::Client,
         }
     }
 
@@ -220,9 +240,11 @@ impl TestScenario {
             | TestScenario::FromClientAccountWithInvalidAssociatedKeys
             | TestScenario::FromClientAccountWithInsufficientWeight
             | TestScenario::AccountWithUnknownBalance
-            | TestScenario::BalanceCheckForDeploySentByPeer => {
-                Deploy::random_valid_native_transfer(rng)
-            }
+            | TestScenario::BalanceCheckForDeploySentByPeer
+            | TestScenario::FromClientExceedingPendingDeployQuota
+            | TestScenario::FromClientUnderpricedReplacement
+            | TestScenario::FromClientBlockedDeploy
+            | TestScenario::FromPeerBlockedDeploy => Deploy::random_valid_native_transfer(rng),
             TestScenario::DeployWithoutPaymentAmount => Deploy::random_without_payment_amount(rng),
             TestScenario::DeployWithMangledPaymentAmount => {
                 Deploy::random_with_mangled_payment_amount(rng)
@@ -297,6 +319,9 @@ impl TestScenario {
             TestScenario::DeployWithEmptySessionModuleBytes => {
                 Deploy::random_with_empty_session_module_bytes(rng)
             }
+            TestScenario::DeployWithInvalidSessionWasm => {
+                Deploy::random_with_invalid_session_module_bytes(rng)
+            }
             TestScenario::DeployWithNativeTransferInPayment => {
                 Deploy::random_with_native_transfer_in_payment_logic(rng)
             }
@@ -304,6 +329,13 @@ impl TestScenario {
             | TestScenario::ShouldNotAcceptExpiredDeploySentByClient => {
                 Deploy::random_expired_deploy(rng)
             }
+            TestScenario::FromClientFutureDatedDeployWithinWindow => {
+                Deploy::random_future_dated_deploy(rng, TimeDiff::from_seconds(60))
+            }
+            TestScenario::FromClientDeployTooFarInFuture
+            | TestScenario::FromPeerDeployTooFarInFuture => {
+                Deploy::random_future_dated_deploy(rng, TimeDiff::from_seconds(3_600))
+            }
         }
     }
 
@@ -316,6 +348,8 @@ impl TestScenario {
             | TestScenario::FromPeerAccountWithInvalidAssociatedKeys // account check skipped if from peer
             | TestScenario::FromClientRepeatedValidDeploy
             | TestScenario::FromClientValidDeploy
+            | TestScenario::FromClientFutureDatedDeployWithinWindow
+            | TestScenario::FromPeerDeployTooFarInFuture
             | TestScenario::ShouldAcceptExpiredDeploySentByPeer=> true,
             TestScenario::FromPeerInvalidDeploy
             | TestScenario::FromClientInsufficientBalance
@@ -325,6 +359,7 @@ impl TestScenario {
             | TestScenario::FromClientAccountWithInvalidAssociatedKeys
             | TestScenario::AccountWithUnknownBalance
             | TestScenario::DeployWithEmptySessionModuleBytes
+            | TestScenario::DeployWithInvalidSessionWasm
             | TestScenario::DeployWithNativeTransferInPayment
             | TestScenario::DeployWithoutPaymentAmount
             | TestScenario::DeployWithMangledPaymentAmount
@@ -332,7 +367,12 @@ impl TestScenario {
             | TestScenario::DeployWithoutTransferAmount
             | TestScenario::DeployWithoutTransferTarget
             | TestScenario::BalanceCheckForDeploySentByPeer
-            | TestScenario::ShouldNotAcceptExpiredDeploySentByClient => false,
+            | TestScenario::ShouldNotAcceptExpiredDeploySentByClient
+            | TestScenario::FromClientExceedingPendingDeployQuota
+            | TestScenario::FromClientUnderpricedReplacement
+            | TestScenario::FromClientBlockedDeploy
+            | TestScenario::FromPeerBlockedDeploy
+            | TestScenario::FromClientDeployTooFarInFuture => false,
             TestScenario::FromPeerCustomPaymentContract(contract_scenario)
             | TestScenario::FromPeerSessionContract(contract_scenario)
             | TestScenario::FromClientCustomPaymentContract(contract_scenario)
@@ -411,7 +451,8 @@ impl reactor::Reactor for Reactor {
 
         let (chainspec, _) = <(Chainspec, ChainspecRawBytes)>::from_resources("local");
 
-        let deploy_acceptor = DeployAcceptor::new(&chainspec, registry).unwrap();
+        let deploy_acceptor =
+            DeployAcceptor::new(super::Config::default(), &chainspec, registry).unwrap();
 
         let storage = Storage::new(
             &storage_withdir,
@@ -460,6 +501,37 @@ impl reactor::Reactor for Reactor {
                 // We do not care about deploy acceptor announcements in the acceptor tests.
                 Effects::new()
             }
+            Event::BlockProposerRequest(BlockProposerRequest::CheckPendingDeployQuota {
+                responder,
+                ..
+            }) => {
+                let within_quota =
+                    self.test_scenario != TestScenario::FromClientExceedingPendingDeployQuota;
+                responder.respond(within_quota).ignore()
+            }
+            Event::BlockProposerRequest(BlockProposerRequest::CheckDeployReplacement {
+                hash,
+                responder,
+                ..
+            }) => {
+                let maybe_replaced = (self.test_scenario
+                    == TestScenario::FromClientUnderpricedReplacement)
+                    .then(|| *hash.deploy_hash());
+                responder.respond(maybe_replaced).ignore()
+            }
+            Event::BlockProposerRequest(BlockProposerRequest::CheckDeployBlocklist {
+                responder,
+                ..
+            }) => {
+                let is_blocked = matches!(
+                    self.test_scenario,
+                    TestScenario::FromClientBlockedDeploy | TestScenario::FromPeerBlockedDeploy
+                );
+                responder.respond(is_blocked).ignore()
+            }
+            Event::BlockProposerRequest(request) => {
+                panic!("should not receive {} in the acceptor tests", request)
+            }
             Event::ContractRuntime(event) => match event {
                 ContractRuntimeRequest::Query {
                     query_request,
@@ -656,13 +728,28 @@ fn schedule_accept_deploy(
     }
 }
 
+fn schedule_precheck_deploy(
+    deploy: Box<Deploy>,
+    responder: Responder<Result<(), super::Error>>,
+) -> impl FnOnce(EffectBuilder<Event>) -> Effects<Event> {
+    |effect_builder: EffectBuilder<Event>| {
+        effect_builder
+            .into_inner()
+            .schedule(
+                super::Event::Precheck { deploy, responder },
+                QueueKind::Regular,
+            )
+            .ignore()
+    }
+}
+
 fn inject_balance_check_for_peer(
     deploy: Box<Deploy>,
     source: Source,
     responder: Responder<Result<(), super::Error>>,
 ) -> impl FnOnce(EffectBuilder<Event>) -> Effects<Event> {
     |effect_builder: EffectBuilder<Event>| {
-        let event_metadata = EventMetadata::new(deploy, source, Some(responder));
+        let event_metadata = EventMetadata::new(deploy, source, Some(responder), false);
         effect_builder
             .into_inner()
             .schedule(
@@ -769,6 +856,7 @@ async fn run_deploy_acceptor_without_timeout(
             | TestScenario::FromClientAccountWithInvalidAssociatedKeys
             | TestScenario::FromClientAccountWithInsufficientWeight
             | TestScenario::DeployWithEmptySessionModuleBytes
+            | TestScenario::DeployWithInvalidSessionWasm
             | TestScenario::AccountWithUnknownBalance
             | TestScenario::DeployWithNativeTransferInPayment
             | TestScenario::DeployWithoutPaymentAmount
@@ -776,7 +864,11 @@ async fn run_deploy_acceptor_without_timeout(
             | TestScenario::DeployWithMangledTransferAmount
             | TestScenario::DeployWithoutTransferTarget
             | TestScenario::DeployWithoutTransferAmount
-            | TestScenario::ShouldNotAcceptExpiredDeploySentByClient => {
+            | TestScenario::ShouldNotAcceptExpiredDeploySentByClient
+            | TestScenario::FromClientExceedingPendingDeployQuota
+            | TestScenario::FromClientUnderpricedReplacement
+            | TestScenario::FromClientBlockedDeploy
+            | TestScenario::FromClientDeployTooFarInFuture => {
                 matches!(
                     event,
                     Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
@@ -833,7 +925,9 @@ async fn run_deploy_acceptor_without_timeout(
             }
             // Check that invalid deploys sent by a peer raise the `InvalidDeploy` announcement
             // with the appropriate source.
-            TestScenario::FromPeerInvalidDeploy | TestScenario::BalanceCheckForDeploySentByPeer => {
+            TestScenario::FromPeerInvalidDeploy
+            | TestScenario::BalanceCheckForDeploySentByPeer
+            | TestScenario::FromPeerBlockedDeploy => {
                 matches!(
                     event,
                     Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
@@ -848,6 +942,7 @@ async fn run_deploy_acceptor_without_timeout(
             | TestScenario::FromPeerMissingAccount
             | TestScenario::FromPeerAccountWithInvalidAssociatedKeys
             | TestScenario::FromPeerAccountWithInsufficientWeight
+            | TestScenario::FromPeerDeployTooFarInFuture
             | TestScenario::ShouldAcceptExpiredDeploySentByPeer => {
                 matches!(
                     event,
@@ -861,7 +956,8 @@ async fn run_deploy_acceptor_without_timeout(
             }
             // Check that a, new and valid, deploy sent by a client raises an `AcceptedNewDeploy`
             // announcement with the appropriate source.
-            TestScenario::FromClientValidDeploy => {
+            TestScenario::FromClientValidDeploy
+            | TestScenario::FromClientFutureDatedDeployWithinWindow => {
                 matches!(
                     event,
                     Event::DeployAcceptorAnnouncement(
@@ -923,6 +1019,64 @@ async fn run_deploy_acceptor(test_scenario: TestScenario) -> Result<(), super::E
         .unwrap()
 }
 
+/// Runs the deploy of a client test scenario through a precheck, asserting that the deploy is
+/// never stored.
+async fn run_deploy_precheck_without_timeout(
+    test_scenario: TestScenario,
+) -> Result<(), super::Error> {
+    let _ = logging::init();
+    let mut rng = crate::new_rng();
+
+    let mut runner: Runner<ConditionCheckReactor<Reactor>> =
+        Runner::new(test_scenario, &mut rng).await.unwrap();
+
+    let block = Box::new(Block::random(&mut rng));
+    let (block_sender, block_receiver) = oneshot::channel();
+    let block_responder = Responder::without_shutdown(block_sender);
+    runner
+        .process_injected_effects(put_block_to_storage(block, block_responder))
+        .await;
+    while runner.try_crank(&mut rng).await.is_none() {
+        time::sleep(POLL_INTERVAL).await;
+    }
+    assert!(block_receiver.await.unwrap());
+
+    let (deploy_sender, mut deploy_receiver) = oneshot::channel();
+    let deploy_responder = Responder::without_shutdown(deploy_sender);
+    let deploy = test_scenario.deploy(&mut rng);
+    runner
+        .process_injected_effects(schedule_precheck_deploy(
+            Box::new(deploy.clone()),
+            deploy_responder,
+        ))
+        .await;
+
+    let result = loop {
+        if let Some(result) = deploy_receiver.try_recv().unwrap() {
+            break result;
+        }
+        if runner.try_crank(&mut rng).await.is_none() {
+            time::sleep(POLL_INTERVAL).await;
+        }
+    };
+
+    let is_in_storage = runner
+        .reactor()
+        .inner()
+        .storage
+        .get_deploy_by_hash(*deploy.id())
+        .is_some();
+    assert!(!is_in_storage);
+
+    result
+}
+
+async fn run_deploy_precheck(test_scenario: TestScenario) -> Result<(), super::Error> {
+    time::timeout(TIMEOUT, run_deploy_precheck_without_timeout(test_scenario))
+        .await
+        .unwrap()
+}
+
 #[tokio::test]
 async fn should_accept_valid_deploy_from_peer() {
     let result = run_deploy_acceptor(TestScenario::FromPeerValidDeploy).await;
@@ -1020,6 +1174,36 @@ async fn should_reject_valid_deploy_from_client_for_insufficient_balance() {
     ))
 }
 
+#[tokio::test]
+async fn should_reject_deploy_from_client_exceeding_pending_deploy_quota() {
+    let result = run_deploy_acceptor(TestScenario::FromClientExceedingPendingDeployQuota).await;
+    assert!(matches!(
+        result,
+        Err(super::Error::ExceededPendingDeployQuota { .. })
+    ))
+}
+
+#[tokio::test]
+async fn should_reject_underpriced_replacement_from_client() {
+    let result = run_deploy_acceptor(TestScenario::FromClientUnderpricedReplacement).await;
+    assert!(matches!(
+        result,
+        Err(super::Error::UnderpricedReplacement { .. })
+    ))
+}
+
+#[tokio::test]
+async fn should_reject_blocked_deploy_from_client() {
+    let result = run_deploy_acceptor(TestScenario::FromClientBlockedDeploy).await;
+    assert!(matches!(result, Err(super::Error::Blocked { .. })))
+}
+
+#[tokio::test]
+async fn should_reject_blocked_deploy_from_peer() {
+    let result = run_deploy_acceptor(TestScenario::FromPeerBlockedDeploy).await;
+    assert!(matches!(result, Err(super::Error::Blocked { .. })))
+}
+
 #[tokio::test]
 async fn should_reject_valid_deploy_from_client_for_unknown_balance() {
     let result = run_deploy_acceptor(TestScenario::AccountWithUnknownBalance).await;
@@ -1401,6 +1585,19 @@ async fn should_reject_deploy_with_missing_version_in_session_contract_package_f
     ))
 }
 
+#[tokio::test]
+async fn should_reject_deploy_with_invalid_wasm_in_session() {
+    let test_scenario = TestScenario::DeployWithInvalidSessionWasm;
+    let result = run_deploy_acceptor(test_scenario).await;
+    assert!(matches!(
+        result,
+        Err(super::Error::InvalidWasm {
+            executable: "session",
+            ..
+        })
+    ))
+}
+
 #[tokio::test]
 async fn should_reject_deploy_with_empty_module_bytes_in_session() {
     let test_scenario = TestScenario::DeployWithEmptySessionModuleBytes;
@@ -1497,6 +1694,42 @@ async fn should_reject_expired_deploy_from_client() {
     assert!(matches!(result, Err(super::Error::ExpiredDeploy { .. })))
 }
 
+#[tokio::test]
+async fn should_report_specific_rejection_reason() {
+    let result = run_deploy_acceptor(TestScenario::ShouldNotAcceptExpiredDeploySentByClient).await;
+    assert_eq!(result.unwrap_err().rejection_reason(), "expired");
+
+    let result = run_deploy_acceptor(TestScenario::FromClientMissingAccount).await;
+    assert_eq!(
+        result.unwrap_err().rejection_reason(),
+        "nonexistent_account"
+    );
+}
+
+#[tokio::test]
+async fn should_accept_future_dated_deploy_within_window_from_client() {
+    let test_scenario = TestScenario::FromClientFutureDatedDeployWithinWindow;
+    let result = run_deploy_acceptor(test_scenario).await;
+    assert!(result.is_ok())
+}
+
+#[tokio::test]
+async fn should_reject_deploy_dated_too_far_in_future_from_client() {
+    let test_scenario = TestScenario::FromClientDeployTooFarInFuture;
+    let result = run_deploy_acceptor(test_scenario).await;
+    assert!(matches!(
+        result,
+        Err(super::Error::TimestampInFuture { .. })
+    ))
+}
+
+#[tokio::test]
+async fn should_accept_deploy_dated_too_far_in_future_from_peer() {
+    let test_scenario = TestScenario::FromPeerDeployTooFarInFuture;
+    let result = run_deploy_acceptor(test_scenario).await;
+    assert!(result.is_ok())
+}
+
 #[tokio::test]
 async fn should_accept_expired_deploy_from_peer() {
     let test_scenario = TestScenario::ShouldAcceptExpiredDeploySentByPeer;
@@ -1511,3 +1744,143 @@ async fn should_panic_when_balance_checking_for_deploy_sent_by_peer() {
     let result = run_deploy_acceptor(test_scenario).await;
     assert!(result.is_ok())
 }
+
+#[tokio::test]
+async fn should_pass_precheck_of_valid_deploy_without_storing_it() {
+    let result = run_deploy_precheck(TestScenario::FromClientValidDeploy).await;
+    assert!(result.is_ok())
+}
+
+#[tokio::test]
+async fn should_fail_precheck_of_invalid_deploy() {
+    let result = run_deploy_precheck(TestScenario::FromClientMissingAccount).await;
+    assert!(matches!(
+        result,
+        Err(super::Error::InvalidDeployParameters {
+            failure: DeployParameterFailure::NonexistentAccount { .. },
+            ..
+        })
+    ))
+}
+
+#[test]
+fn should_fail_wasm_preprocessing_of_invalid_session_module_bytes() {
+    let mut rng = crate::new_rng();
+    let deploy = Deploy::random_with_invalid_session_module_bytes(&mut rng);
+    let (chainspec, _) = <(Chainspec, ChainspecRawBytes)>::from_resources("local");
+    let deploy_acceptor =
+        DeployAcceptor::new(super::Config::default(), &chainspec, &Registry::new()).unwrap();
+    assert!(matches!(
+        deploy_acceptor.preprocess_wasm(&deploy),
+        Err(super::Error::InvalidWasm {
+            executable: "session",
+            ..
+        })
+    ))
+}
+
+fn deploy_acceptor_with_policy(acceptance_policy: AcceptancePolicyConfig) -> DeployAcceptor {
+    let (chainspec, _) = <(Chainspec, ChainspecRawBytes)>::from_resources("local");
+    let config = super::Config {
+        acceptance_policy,
+        ..Default::default()
+    };
+    DeployAcceptor::new(config, &chainspec, &Registry::new()).unwrap()
+}
+
+#[test]
+fn should_reject_deploy_below_minimum_payment_amount() {
+    let mut rng = crate::new_rng();
+    let deploy = Deploy::random_valid_native_transfer(&mut rng);
+    let deploy_acceptor = deploy_acceptor_with_policy(AcceptancePolicyConfig {
+        min_payment_amount: u64::MAX,
+        ..Default::default()
+    });
+    assert!(matches!(
+        deploy_acceptor.check_acceptance_rules(&deploy),
+        Err(PolicyViolation::PaymentAmountTooLow { .. })
+    ))
+}
+
+#[test]
+fn should_reject_deploy_calling_banned_contract() {
+    let mut rng = crate::new_rng();
+    let deploy = Deploy::random_with_missing_session_contract_by_hash(&mut rng);
+    let contract_hash = match deploy.session() {
+        ExecutableDeployItem::StoredContractByHash { hash, .. } => *hash,
+        _ => panic!("session should call a contract by hash"),
+    };
+    let deploy_acceptor = deploy_acceptor_with_policy(AcceptancePolicyConfig {
+        banned_contract_hashes: vec![contract_hash],
+        ..Default::default()
+    });
+    assert!(matches!(
+        deploy_acceptor.check_acceptance_rules(&deploy),
+        Err(PolicyViolation::BannedContract {
+            executable: "session",
+            ..
+        })
+    ))
+}
+
+#[test]
+fn should_only_accept_deploys_from_allowed_accounts() {
+    let mut rng = crate::new_rng();
+    let deploy = Deploy::random_valid_native_transfer(&mut rng);
+    let deploy_acceptor = deploy_acceptor_with_policy(AcceptancePolicyConfig {
+        allowed_accounts: vec![PublicKey::random(&mut rng)],
+        ..Default::default()
+    });
+    assert!(matches!(
+        deploy_acceptor.check_acceptance_rules(&deploy),
+        Err(PolicyViolation::AccountNotAllowed { .. })
+    ));
+
+    let deploy_acceptor = deploy_acceptor_with_policy(AcceptancePolicyConfig {
+        allowed_accounts: vec![deploy.header().account().clone()],
+        ..Default::default()
+    });
+    assert!(deploy_acceptor.check_acceptance_rules(&deploy).is_ok());
+}
+
+#[tokio::test]
+async fn should_reject_valid_transaction_v2() {
+    let mut rng = crate::new_rng();
+    let (chainspec, _) = <(Chainspec, ChainspecRawBytes)>::from_resources("local");
+    let mut deploy_acceptor =
+        DeployAcceptor::new(super::Config::default(), &chainspec, &Registry::new()).unwrap();
+    let transaction = TransactionV2::new(
+        chainspec.network_config.name.clone(),
+        Timestamp::now(),
+        TimeDiff::from(60_000),
+        PricingMode::Classic {
+            payment_amount: 1_000,
+            gas_price: 1,
+        },
+        TransactionV2Body::new(
+            RuntimeArgs::new(),
+            TransactionTarget::Stored {
+                id: TransactionInvocationTarget::ByName(String::from("contract")),
+            },
+            TransactionEntryPoint::Custom(String::from("call")),
+        ),
+        &SecretKey::random(&mut rng),
+        None,
+    );
+
+    let (sender, receiver) = oneshot::channel();
+    let effects = deploy_acceptor
+        .accept_transaction_v2(Box::new(transaction), Responder::without_shutdown(sender));
+    for effect in effects {
+        effect.await;
+    }
+    let result = receiver.await.unwrap();
+    assert!(matches!(
+        result,
+        Err(super::Error::TransactionV2NotSupported)
+    ));
+    assert_eq!(
+        result.unwrap_err().rejection_reason(),
+        "transaction_v2_not_supported"
+    );
+}
@@ -2,6 +2,9 @@
 //!
 //! The diagnostics port listens on a configurable unix socket for incoming connections and allows
 //! deep debug access to a running node via special commands.
+//!
+//! Optionally, the same commands are available via TCP to clients authenticating with a TLS
+//! certificate issued by a configured authority, allowing for remote administration.
 
 mod command;
 mod tasks;
@@ -10,24 +13,35 @@ mod util;
 use std::{
     fmt::{self, Display, Formatter},
     fs, io,
+    net::TcpListener as StdTcpListener,
     path::{Path, PathBuf},
 };
 
 use datasize::DataSize;
+use openssl::error::ErrorStack;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tokio::{net::UnixListener, sync::watch};
-use tracing::{debug, warn};
+use tokio::{
+    net::{TcpListener, UnixListener},
+    sync::watch,
+};
+use tracing::{debug, info, warn};
 
 use super::Component;
 use crate::{
     effect::{
-        announcements::ControlAnnouncement, diagnostics_port::DumpConsensusStateRequest,
+        announcements::ControlAnnouncement,
+        diagnostics_port::{
+            DumpConsensusStateRequest, DumpNetworkStateRequest, ReloadConfigRequest,
+            ShutdownPointRequest, ValidatorModeRequest,
+        },
+        requests::BlockProposerRequest,
         EffectBuilder, EffectExt, Effects,
     },
     reactor::EventQueueHandle,
+    tls::{self, MutualTlsConfig},
     types::NodeRng,
-    utils::umask,
+    utils::{self, umask, ResolveAddressError},
     WithDir,
 };
 pub use tasks::FileSerializer;
@@ -43,7 +57,9 @@ pub(crate) struct DiagnosticsPort {
 }
 
 /// Diagnostics port configuration.
-#[derive(Clone, DataSize, Debug, Deserialize)]
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
 pub(crate) struct Config {
     /// Whether or not the diagnostics port is enabled.
     enabled: bool,
@@ -51,6 +67,12 @@ pub(crate) struct Config {
     socket_path: PathBuf,
     /// `umask` to apply before creating the socket.
     socket_umask: u16,
+    /// Address to additionally listen on via TCP, requiring mutual TLS.
+    #[serde(default)]
+    tls_address: Option<String>,
+    /// TLS configuration of the TCP listener.
+    #[serde(default)]
+    tls: Option<MutualTlsConfig>,
 }
 
 impl Default for Config {
@@ -59,6 +81,8 @@ impl Default for Config {
             enabled: false,
             socket_path: "debug.socket".into(),
             socket_umask: 0o077,
+            tls_address: None,
+            tls: None,
         }
     }
 }
@@ -70,7 +94,14 @@ impl DiagnosticsPort {
         event_queue: EventQueueHandle<REv>,
     ) -> Result<(Self, Effects<Event>), Error>
     where
-        REv: From<DumpConsensusStateRequest> + From<ControlAnnouncement> + Send,
+        REv: From<DumpConsensusStateRequest>
+            + From<DumpNetworkStateRequest>
+            + From<ControlAnnouncement>
+            + From<BlockProposerRequest>
+            + From<ReloadConfigRequest>
+            + From<ShutdownPointRequest>
+            + From<ValidatorModeRequest>
+            + Send,
     {
         let config = cfg.value();
         let (shutdown_sender, shutdown_receiver) = watch::channel(());
@@ -90,17 +121,47 @@ impl DiagnosticsPort {
             #[allow(clippy::useless_conversion)]
             config.socket_umask.into(),
         )?;
+        let mut effects = match config.tls_address {
+            Some(ref address) => {
+                let tls_config = config.tls.clone().ok_or(Error::MissingTlsConfig)?;
+                let acceptor = tls::create_mutual_tls_acceptor(&cfg.map_ref(|_| tls_config))
+                    .map_err(Error::Tls)?;
+                let tcp_listener = setup_tcp_listener(address)?;
+                tasks::tls_server(
+                    EffectBuilder::new(event_queue),
+                    tcp_listener,
+                    acceptor,
+                    shutdown_receiver.clone(),
+                )
+                .ignore()
+            }
+            None => Effects::new(),
+        };
+
         let server = tasks::server(
             EffectBuilder::new(event_queue),
             socket_path,
             listener,
             shutdown_receiver,
         );
+        effects.extend(server.ignore());
 
-        Ok((DiagnosticsPort { shutdown_sender }, server.ignore()))
+        Ok((DiagnosticsPort { shutdown_sender }, effects))
     }
 }
 
+/// Sets up a TCP listener on the given address.
+fn setup_tcp_listener(address: &str) -> Result<TcpListener, Error> {
+    let address = utils::resolve_address(address)?;
+    let listener = StdTcpListener::bind(address)?;
+    listener.set_nonblocking(true)?;
+    let listener = TcpListener::from_std(listener)?;
+
+    info!(local_addr=%listener.local_addr()?, "diagnostics port listening via TLS");
+
+    Ok(listener)
+}
+
 /// Sets up a UNIX socket listener at the given path.
 ///
 /// If the socket already exists, an attempt to delete it is made. Errors during deletion are
@@ -146,6 +207,15 @@ pub(crate) enum Error {
     /// Error setting up the diagnostics port's unix socket listener.
     #[error("could not setup diagnostics port listener")]
     SetupListener(#[from] io::Error),
+    /// Error resolving the address of the diagnostics port's TCP listener.
+    #[error("could not resolve diagnostics port TLS address")]
+    ResolveAddress(#[from] ResolveAddressError),
+    /// A TLS address was configured without the TLS settings.
+    #[error("diagnostics port TLS address configured without TLS settings")]
+    MissingTlsConfig,
+    /// Error setting up TLS for the diagnostics port's TCP listener.
+    #[error("could not set up diagnostics port TLS: {0}")]
+    Tls(ErrorStack),
 }
 
 impl Display for Event {
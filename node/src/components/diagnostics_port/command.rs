@@ -7,6 +7,14 @@ use serde::Serialize;
 use structopt::StructOpt;
 use thiserror::Error;
 
+use casper_hashing::Digest;
+use casper_types::{account::AccountHash, TimeDiff};
+
+use crate::{
+    components::{block_proposer::DeployBlocklistUpdate, consensus::ValidatorMode},
+    types::DeployHash,
+};
+
 /// Command processing error.
 ///
 /// Failures that occur when trying to parse an incoming client message.
@@ -84,12 +92,156 @@ pub(super) enum Action {
         /// Era to dump. If omitted, dumps the latest era.
         era: Option<u64>,
     },
+    /// Dump the state of the networking component's connection manager.
+    ///
+    /// Shows the state of every outgoing connection including backoff timers, connection
+    /// symmetries, blocked addresses and when outgoing connections were last swept.
+    DumpNetwork,
+    /// Show the connected peers.
+    ///
+    /// For every peer, shows its validator status according to its consensus certificate and the
+    /// number of messages and bytes exchanged with it, by kind of message.
+    ShowPeers,
     /// Dump the event queues.
     DumpQueues,
+    /// Show the depth of each event queue and how many of its events are for each component.
+    QueueSummary {
+        /// Also show a one-line summary of every queued event.
+        #[structopt(short, long)]
+        events: bool,
+    },
+    /// Show the memory usage of the node and the estimated heap memory usage of each component.
+    DumpMemory,
+    /// Block a deploy or all deploys of an account.
+    ///
+    /// Blocked deploys are rejected when received and never proposed. The blocklist is persisted
+    /// across restarts.
+    Block {
+        #[structopt(subcommand)]
+        target: BlocklistTarget,
+    },
+    /// Remove a deploy or an account from the blocklist.
+    Unblock {
+        #[structopt(subcommand)]
+        target: BlocklistTarget,
+    },
+    /// Show the blocked deploys and accounts.
+    ShowBlocklist,
+    /// Reload the config file, applying the options which can be changed without a restart.
+    ///
+    /// Equivalent to sending `SIGHUP` to the node, except that the outcome is reported back.
+    ReloadConfig,
+    /// Show or change the block height or era after which the node shuts down.
+    ///
+    /// Without options, shows the shutdown point in effect.  Otherwise replaces it with the given
+    /// height and era; the node shuts down after storing the block at that height or the switch
+    /// block of that era, whichever comes first, and exits with exit code 104.
+    ShutdownAt {
+        /// Height of the last block to store before shutting down.
+        #[structopt(long)]
+        height: Option<u64>,
+        /// Era after whose switch block the node shuts down.
+        #[structopt(long)]
+        era: Option<u64>,
+        /// Clear the shutdown point, so that the node keeps running.
+        #[structopt(long, conflicts_with_all = &["height", "era"])]
+        clear: bool,
+    },
+    /// Show or change whether the node takes part in consensus as a validator.
+    ///
+    /// A change takes effect when the next era begins, so that redundant validator machines can
+    /// hand over at an era boundary without both voting in the same era.
+    ValidatorMode {
+        /// The mode from the next era on, one of `active` or `observer`.  If omitted, shows the
+        /// current mode.
+        mode: Option<ValidatorMode>,
+    },
+    /// Show or change the log filter without a restart.
+    LogFilter {
+        #[structopt(subcommand)]
+        action: LogFilterAction,
+    },
+    /// Shut down the node cleanly.
+    ///
+    /// Equivalent to sending `SIGTERM` to the node: the node finishes its work in progress and exits
+    /// with the same exit code.
+    Shutdown,
     /// Close connection server-side.
     Quit,
 }
 
+/// Deploy or account to add to or remove from the blocklist.
+#[derive(Clone, Copy, Debug, StructOpt)]
+pub(super) enum BlocklistTarget {
+    /// A single deploy.
+    Deploy {
+        /// Hex-encoded deploy hash.
+        #[structopt(parse(try_from_str = parse_deploy_hash))]
+        deploy_hash: DeployHash,
+    },
+    /// All deploys signed by an account.
+    Account {
+        /// Account hash, formatted as "account-hash-<hex>".
+        #[structopt(parse(try_from_str = parse_account_hash))]
+        account_hash: AccountHash,
+    },
+}
+
+/// Change to make to the log filter.
+#[derive(Clone, Debug, StructOpt)]
+pub(super) enum LogFilterAction {
+    /// Show the log filter in use.
+    Show,
+    /// Replace the log filter with the given directives, in the same format as `RUST_LOG`.
+    Set {
+        /// Filter directives, e.g. "info,casper_node::components::consensus=debug".
+        directives: String,
+        /// Revert to the configured log filter after this long, e.g. "10min".
+        #[structopt(short, long)]
+        duration: Option<TimeDiff>,
+    },
+    /// Add the given directives to the log filter in use, overriding it where they overlap.
+    Add {
+        /// Filter directives, e.g. "casper_node::components::small_network=trace".
+        directives: String,
+        /// Revert to the configured log filter after this long, e.g. "10min".
+        #[structopt(short, long)]
+        duration: Option<TimeDiff>,
+    },
+    /// Revert to the log filter from `RUST_LOG` or the config file.
+    Reset,
+}
+
+impl BlocklistTarget {
+    /// Returns the blocklist update adding the target or, if `block` is false, removing it.
+    pub(super) fn to_update(&self, block: bool) -> DeployBlocklistUpdate {
+        match (*self, block) {
+            (BlocklistTarget::Deploy { deploy_hash }, true) => {
+                DeployBlocklistUpdate::BlockDeploy(deploy_hash)
+            }
+            (BlocklistTarget::Deploy { deploy_hash }, false) => {
+                DeployBlocklistUpdate::UnblockDeploy(deploy_hash)
+            }
+            (BlocklistTarget::Account { account_hash }, true) => {
+                DeployBlocklistUpdate::BlockAccount(account_hash)
+            }
+            (BlocklistTarget::Account { account_hash }, false) => {
+                DeployBlocklistUpdate::UnblockAccount(account_hash)
+            }
+        }
+    }
+}
+
+fn parse_deploy_hash(input: &str) -> Result<DeployHash, String> {
+    Digest::from_hex(input)
+        .map(DeployHash::new)
+        .map_err(|err| err.to_string())
+}
+
+fn parse_account_hash(input: &str) -> Result<AccountHash, String> {
+    AccountHash::from_formatted_str(input).map_err(|err| err.to_string())
+}
+
 /// A command to be performed on the node's diagnostic port.
 #[derive(Debug, StructOpt)]
 pub(super) struct Command {
@@ -108,14 +260,80 @@ impl Command {
 
 #[cfg(test)]
 mod tests {
-    use crate::components::diagnostics_port::command::{Action, Command};
+    use casper_hashing::Digest;
+    use casper_types::TimeDiff;
+
+    use crate::components::diagnostics_port::command::{
+        Action, BlocklistTarget, Command, LogFilterAction,
+    };
 
     #[test]
     fn can_parse_simple_commands() {
         let cmd = Command::from_line("dump-consensus 123").expect("command parsing failed");
         assert!(matches!(cmd.action, Action::DumpConsensus { era } if era == Some(123)));
 
+        let cmd = Command::from_line("dump-network").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::DumpNetwork));
+
+        let cmd = Command::from_line("show-peers").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::ShowPeers));
+
+        let cmd = Command::from_line("dump-memory").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::DumpMemory));
+
+        let cmd = Command::from_line("shutdown").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::Shutdown));
+
         let cmd = Command::from_line("dump-queues").expect("command parsing failed");
         assert!(matches!(cmd.action, Action::DumpQueues));
+
+        let cmd = Command::from_line("queue-summary").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::QueueSummary { events: false }));
+
+        let cmd = Command::from_line("queue-summary --events").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::QueueSummary { events: true }));
+
+        let cmd = Command::from_line(
+            "log-filter add casper_node::components::small_network=trace -d 5min",
+        )
+        .expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::LogFilter {
+                action: LogFilterAction::Add { ref directives, duration: Some(duration) }
+            } if directives == "casper_node::components::small_network=trace"
+                && duration == TimeDiff::from_seconds(300)
+        ));
+
+        let cmd = Command::from_line("log-filter reset").expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::LogFilter {
+                action: LogFilterAction::Reset
+            }
+        ));
+    }
+
+    #[test]
+    fn can_parse_blocklist_commands() {
+        let deploy_hex = "01".repeat(32);
+        let cmd = Command::from_line(&format!("block deploy {}", deploy_hex))
+            .expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::Block { target: BlocklistTarget::Deploy { deploy_hash } }
+                if *deploy_hash.inner() == Digest::from([1; Digest::LENGTH])
+        ));
+
+        let account = format!("account-hash-{}", "02".repeat(32));
+        let cmd = Command::from_line(&format!("unblock account {}", account))
+            .expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::Unblock { target: BlocklistTarget::Account { account_hash } }
+                if account_hash.to_formatted_string() == account
+        ));
+
+        assert!(Command::from_line("block deploy not-a-hash").is_err());
     }
 }
@@ -3,7 +3,13 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
     fs::{self, File},
     io,
+    net::SocketAddr,
     path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
 use bincode::{
@@ -12,11 +18,13 @@ use bincode::{
 };
 use erased_serde::Serializer as ErasedSerializer;
 use futures::future::{self, Either};
+use openssl::ssl::SslAcceptor;
 use serde::Serialize;
+use signal_hook::consts::signal::SIGTERM;
 use thiserror::Error;
 use tokio::{
-    io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader},
-    net::{unix::OwnedWriteHalf, UnixListener, UnixStream},
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::{TcpListener, UnixListener, UnixStream},
     sync::watch,
 };
 use tracing::{debug, info, info_span, warn, Instrument};
@@ -24,19 +32,38 @@ use tracing::{debug, info, info_span, warn, Instrument};
 use casper_types::EraId;
 
 use super::{
-    command::{Action, Command, OutputFormat},
+    command::{Action, Command, LogFilterAction, OutputFormat},
     util::ShowUnixAddr,
 };
 use crate::{
-    components::consensus::EraDump,
+    audit_log,
+    components::{consensus::EraDump, linear_chain::ShutdownPoint},
     effect::{
         announcements::{ControlAnnouncement, QueueDumpFormat},
-        diagnostics_port::DumpConsensusStateRequest,
+        diagnostics_port::{
+            DumpConsensusStateRequest, DumpNetworkStateRequest, ReloadConfigRequest,
+            ShutdownPointRequest, ValidatorModeRequest,
+        },
+        requests::BlockProposerRequest,
         EffectBuilder,
     },
+    logging, tls,
     utils::display_error,
+    TERMINATION_REQUESTED,
 };
 
+/// Failure reason given if the deploy blocklist is accessed while no block proposer is running.
+const BLOCKLIST_UNAVAILABLE: &str = "blocklist unavailable until the node is participating";
+
+/// Time allowed for a client connecting via TCP to complete the TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Source of client IDs, shared by all listeners to keep IDs unique in logs and the audit log.
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The sending half of a client connection.
+type ClientWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
 /// Success or failure response.
 ///
 /// This response is sent back to clients after every operation (unless suppressed in quiet mode),
@@ -191,16 +218,30 @@ impl Session {
     async fn process_line<REv>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
-        writer: &mut OwnedWriteHalf,
+        writer: &mut ClientWriter,
+        audit_source: &audit_log::Source,
         line: &str,
     ) -> io::Result<bool>
     where
-        REv: From<DumpConsensusStateRequest> + From<ControlAnnouncement> + Send,
+        REv: From<DumpConsensusStateRequest>
+            + From<DumpNetworkStateRequest>
+            + From<ControlAnnouncement>
+            + From<BlockProposerRequest>
+            + From<ReloadConfigRequest>
+            + From<ShutdownPointRequest>
+            + From<ValidatorModeRequest>
+            + Send,
     {
         debug!(%line, "line received");
         match Command::from_line(line) {
             Ok(ref cmd) => {
                 info!(?cmd, "processing command");
+                audit_log::record(
+                    audit_source,
+                    audit_log::Action::DiagnosticsCommand {
+                        command: line.to_string(),
+                    },
+                );
                 match cmd.action {
                     Action::Session => {
                         self.send_outcome(writer, &Outcome::success("showing session info"))
@@ -250,6 +291,21 @@ impl Session {
                             }
                         }
                     }
+                    Action::DumpNetwork => {
+                        let dump = effect_builder.diagnostics_port_dump_network_state().await;
+                        self.send_outcome(writer, &Outcome::success("dumping network state"))
+                            .await?;
+                        self.send_to_client(writer, &dump).await?;
+                    }
+                    Action::ShowPeers => {
+                        let peers = effect_builder
+                            .diagnostics_port_dump_network_state()
+                            .await
+                            .into_peers();
+                        self.send_outcome(writer, &Outcome::success("showing peers"))
+                            .await?;
+                        self.send_to_client(writer, &peers).await?;
+                    }
                     Action::DumpQueues => {
                         // Note: The preferable approach would be to use a tempfile instead of a
                         //       named one in a temporary directory, and return it through the
@@ -276,6 +332,126 @@ impl Session {
                             }
                         };
                     }
+                    Action::QueueSummary { events } => {
+                        let summary = effect_builder
+                            .diagnostics_port_summarize_queue(events)
+                            .await;
+                        self.send_outcome(writer, &Outcome::success("showing queue summary"))
+                            .await?;
+                        self.send_to_client(writer, &summary).await?;
+                    }
+                    Action::DumpMemory => {
+                        let dump = effect_builder.diagnostics_port_dump_memory().await;
+                        self.send_outcome(writer, &Outcome::success("dumping memory usage"))
+                            .await?;
+                        self.send_to_client(writer, &dump).await?;
+                    }
+                    Action::Block { target } | Action::Unblock { target } => {
+                        let update = target.to_update(matches!(cmd.action, Action::Block { .. }));
+                        let outcome = match effect_builder.update_deploy_blocklist(update).await {
+                            Some(true) => {
+                                Outcome::success(format!("blocklist updated: {}", update))
+                            }
+                            Some(false) => {
+                                Outcome::success(format!("blocklist unchanged: {}", update))
+                            }
+                            None => Outcome::failed(BLOCKLIST_UNAVAILABLE),
+                        };
+                        self.send_outcome(writer, &outcome).await?;
+                    }
+                    Action::ShowBlocklist => match effect_builder.get_deploy_blocklist().await {
+                        Some(blocklist) => {
+                            self.send_outcome(writer, &Outcome::success("showing blocklist"))
+                                .await?;
+                            self.send_to_client(writer, &blocklist).await?;
+                        }
+                        None => {
+                            self.send_outcome(writer, &Outcome::failed(BLOCKLIST_UNAVAILABLE))
+                                .await?;
+                        }
+                    },
+                    Action::ReloadConfig => {
+                        let result = effect_builder.reload_config().await;
+                        audit_log::record(audit_source, audit_log::Action::config_reload(&result));
+                        match result {
+                            Ok(report) => {
+                                self.send_outcome(writer, &Outcome::success("reloaded config"))
+                                    .await?;
+                                self.send_to_client(writer, &report).await?;
+                            }
+                            Err(err) => {
+                                self.send_outcome(writer, &Outcome::failed(err)).await?;
+                            }
+                        }
+                    }
+                    Action::ShutdownAt { height, era, clear } => {
+                        let update =
+                            (clear || height.is_some() || era.is_some()).then(|| ShutdownPoint {
+                                height,
+                                era: era.map(EraId::new),
+                            });
+                        match effect_builder.shutdown_point(update).await {
+                            Ok(shutdown_point) => {
+                                let msg = if update.is_some() {
+                                    "shutdown point updated"
+                                } else {
+                                    "showing shutdown point"
+                                };
+                                self.send_outcome(writer, &Outcome::success(msg)).await?;
+                                self.send_to_client(writer, &shutdown_point).await?;
+                            }
+                            Err(err) => {
+                                self.send_outcome(writer, &Outcome::failed(err)).await?;
+                            }
+                        }
+                    }
+                    Action::ValidatorMode { mode } => {
+                        let result = effect_builder.validator_mode(mode).await;
+                        if let Some(mode) = mode {
+                            audit_log::record(
+                                audit_source,
+                                audit_log::Action::ValidatorModeChange {
+                                    mode,
+                                    error: result.as_ref().err().map(ToString::to_string),
+                                },
+                            );
+                        }
+                        match result {
+                            Ok(status) => {
+                                let msg = if mode.is_some() {
+                                    "validator mode updated"
+                                } else {
+                                    "showing validator mode"
+                                };
+                                self.send_outcome(writer, &Outcome::success(msg)).await?;
+                                self.send_to_client(writer, &status).await?;
+                            }
+                            Err(err) => {
+                                self.send_outcome(writer, &Outcome::failed(err)).await?;
+                            }
+                        }
+                    }
+                    Action::LogFilter { ref action } => {
+                        match apply_log_filter_action(action)
+                            .and_then(|msg| logging::filter_status().map(|status| (msg, status)))
+                        {
+                            Ok((msg, status)) => {
+                                self.send_outcome(writer, &Outcome::success(msg)).await?;
+                                self.send_to_client(writer, &status).await?;
+                            }
+                            Err(err) => {
+                                self.send_outcome(writer, &Outcome::failed(format!("{:#}", err)))
+                                    .await?;
+                            }
+                        }
+                    }
+                    Action::Shutdown => {
+                        info!("shutdown requested via diagnostics port");
+                        TERMINATION_REQUESTED.store(SIGTERM as usize, Ordering::SeqCst);
+                        self.send_outcome(writer, &Outcome::success("shutting down"))
+                            .await?;
+                        return Ok(false);
+                    }
                     Action::Quit => {
                         self.send_outcome(writer, &Outcome::success("goodbye!"))
                             .await?;
@@ -325,11 +501,7 @@ impl Session {
     /// Sends an operation outcome.
     ///
     /// The outcome will be silently dropped if the session is in quiet mode.
-    async fn send_outcome(
-        &self,
-        writer: &mut OwnedWriteHalf,
-        response: &Outcome,
-    ) -> io::Result<()> {
+    async fn send_outcome(&self, writer: &mut ClientWriter, response: &Outcome) -> io::Result<()> {
         if self.quiet {
             return Ok(());
         }
@@ -341,7 +513,7 @@ impl Session {
     ///
     /// Any type of message can be sent to a client, as long as it has a `Display` (use for
     /// `interactive` encoding) and `Serialize` (used for `bincode` and `json`) implementation.
-    async fn send_to_client<T>(&self, writer: &mut OwnedWriteHalf, response: &T) -> io::Result<()>
+    async fn send_to_client<T>(&self, writer: &mut ClientWriter, response: &T) -> io::Result<()>
     where
         T: Display + Serialize,
     {
@@ -371,13 +543,53 @@ impl Session {
     /// Returns the number of bytes sent.
     async fn stream_to_client<R: AsyncRead + Unpin + ?Sized>(
         &self,
-        writer: &mut OwnedWriteHalf,
+        writer: &mut ClientWriter,
         src: &mut R,
     ) -> io::Result<u64> {
         tokio::io::copy(src, writer).await
     }
 }
 
+/// Applies a change to the log filter, returning a description of the outcome.
+///
+/// If the change is temporary, a task is spawned to revert it, unless it has been superseded by
+/// then.
+fn apply_log_filter_action(action: &LogFilterAction) -> anyhow::Result<String> {
+    let (directives, duration, extend) = match action {
+        LogFilterAction::Show => return Ok("showing log filter".to_string()),
+        LogFilterAction::Reset => {
+            return Ok(if logging::reset_filter(None)? {
+                "log filter reset".to_string()
+            } else {
+                "log filter unchanged".to_string()
+            });
+        }
+        LogFilterAction::Set {
+            directives,
+            duration,
+        } => (directives, duration, false),
+        LogFilterAction::Add {
+            directives,
+            duration,
+        } => (directives, duration, true),
+    };
+
+    let change = logging::override_filter(directives, extend)?;
+    let duration = match duration {
+        Some(duration) => *duration,
+        None => return Ok("log filter updated".to_string()),
+    };
+    tokio::spawn(async move {
+        tokio::time::sleep(duration.into()).await;
+        match logging::reset_filter(Some(change)) {
+            Ok(true) => info!("temporary log filter expired, reverted to configured filter"),
+            Ok(false) => debug!("temporary log filter superseded, not reverting"),
+            Err(err) => warn!(%err, "failed to revert temporary log filter"),
+        }
+    });
+    Ok(format!("log filter updated for {}", duration))
+}
+
 /// Handler for client connection.
 ///
 /// The core loop for the diagnostics port; reads commands via unix socket and processes them.
@@ -387,17 +599,27 @@ impl Session {
 /// The handler itself will buffer an unlimited amount of data if no newline is encountered in the
 /// input stream. For this reason ensure that only trusted client connect to the socket producing
 /// the passed in `stream`.
-async fn handler<REv>(
+async fn handler<REv, S>(
     effect_builder: EffectBuilder<REv>,
-    stream: UnixStream,
+    stream: S,
+    audit_source: audit_log::Source,
     mut shutdown_receiver: watch::Receiver<()>,
 ) -> io::Result<()>
 where
-    REv: From<DumpConsensusStateRequest> + From<ControlAnnouncement> + Send,
+    S: AsyncRead + AsyncWrite + Send + 'static,
+    REv: From<DumpConsensusStateRequest>
+        + From<DumpNetworkStateRequest>
+        + From<ControlAnnouncement>
+        + From<BlockProposerRequest>
+        + From<ReloadConfigRequest>
+        + From<ShutdownPointRequest>
+        + From<ValidatorModeRequest>
+        + Send,
 {
     debug!("accepted new connection on diagnostics port");
 
-    let (reader, mut writer) = stream.into_split();
+    let (reader, writer) = tokio::io::split(stream);
+    let mut writer: ClientWriter = Box::new(writer);
     let mut lines = BufReader::new(reader).lines();
     let mut session = Session::default();
 
@@ -413,7 +635,7 @@ where
             Either::Right((line_result, _)) => {
                 if let Some(line) = line_result? {
                     keep_going = session
-                        .process_line(effect_builder, &mut writer, line.as_str())
+                        .process_line(effect_builder, &mut writer, &audit_source, line.as_str())
                         .await?;
                 } else {
                     info!("client closed diagnostics port connection");
@@ -426,6 +648,30 @@ where
     Ok(())
 }
 
+/// Identifies a diagnostics port client in the audit log by the credentials of its process.
+fn audit_source(client_id: u64, stream: &UnixStream) -> audit_log::Source {
+    match stream.peer_cred() {
+        Ok(cred) => {
+            info!(uid = cred.uid(), gid = cred.gid(), pid = ?cred.pid(), "client credentials");
+            audit_log::Source::DiagnosticsPort {
+                client_id,
+                uid: Some(cred.uid()),
+                gid: Some(cred.gid()),
+                pid: cred.pid(),
+            }
+        }
+        Err(err) => {
+            warn!(%err, "could not obtain client credentials");
+            audit_log::Source::DiagnosticsPort {
+                client_id,
+                uid: None,
+                gid: None,
+                pid: None,
+            }
+        }
+    }
+}
+
 /// Server task for diagnostics port.
 pub(super) async fn server<REv>(
     effect_builder: EffectBuilder<REv>,
@@ -433,27 +679,37 @@ pub(super) async fn server<REv>(
     listener: UnixListener,
     mut shutdown_receiver: watch::Receiver<()>,
 ) where
-    REv: From<DumpConsensusStateRequest> + From<ControlAnnouncement> + Send,
+    REv: From<DumpConsensusStateRequest>
+        + From<DumpNetworkStateRequest>
+        + From<ControlAnnouncement>
+        + From<BlockProposerRequest>
+        + From<ReloadConfigRequest>
+        + From<ShutdownPointRequest>
+        + From<ValidatorModeRequest>
+        + Send,
 {
     let handling_shutdown_receiver = shutdown_receiver.clone();
-    let mut next_client_id: u64 = 0;
     let accept_connections = async move {
         loop {
             match listener.accept().await {
                 Ok((stream, client_addr)) => {
-                    let client_id = next_client_id;
+                    let client_id = NEXT_CLIENT_ID.fetch_add(1, Ordering::SeqCst);
 
                     let span = info_span!("diagnostics_port", client_id,);
 
-                    span.in_scope(|| {
+                    let audit_source = span.in_scope(|| {
                         info!(client_addr = %ShowUnixAddr(&client_addr), "accepted connection");
+                        audit_source(client_id, &stream)
                     });
 
-                    next_client_id += 1;
-
                     tokio::spawn(
-                        handler(effect_builder, stream, handling_shutdown_receiver.clone())
-                            .instrument(span),
+                        handler(
+                            effect_builder,
+                            stream,
+                            audit_source,
+                            handling_shutdown_receiver.clone(),
+                        )
+                        .instrument(span),
                     );
                 }
                 Err(err) => {
@@ -483,6 +739,91 @@ pub(super) async fn server<REv>(
     }
 }
 
+/// Server task for the diagnostics port's TCP listener, requiring clients to authenticate via
+/// mutual TLS.
+pub(super) async fn tls_server<REv>(
+    effect_builder: EffectBuilder<REv>,
+    listener: TcpListener,
+    acceptor: SslAcceptor,
+    mut shutdown_receiver: watch::Receiver<()>,
+) where
+    REv: From<DumpConsensusStateRequest>
+        + From<DumpNetworkStateRequest>
+        + From<ControlAnnouncement>
+        + From<BlockProposerRequest>
+        + From<ReloadConfigRequest>
+        + From<ShutdownPointRequest>
+        + From<ValidatorModeRequest>
+        + Send,
+{
+    let acceptor = Arc::new(acceptor);
+    let handling_shutdown_receiver = shutdown_receiver.clone();
+    let accept_connections = async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, client_addr)) => {
+                    let client_id = NEXT_CLIENT_ID.fetch_add(1, Ordering::SeqCst);
+
+                    let span = info_span!("diagnostics_port", client_id,);
+                    span.in_scope(|| info!(%client_addr, "accepted TCP connection"));
+
+                    let acceptor = Arc::clone(&acceptor);
+                    let shutdown_receiver = handling_shutdown_receiver.clone();
+                    tokio::spawn(
+                        async move {
+                            let handshake = tls::accept_mutual_tls(&acceptor, stream);
+                            let (stream, client) = match tokio::time::timeout(
+                                TLS_HANDSHAKE_TIMEOUT,
+                                handshake,
+                            )
+                            .await
+                            {
+                                Ok(Ok(accepted)) => accepted,
+                                Ok(Err(err)) => {
+                                    warn!(%err, "rejected diagnostics port client");
+                                    return Ok(());
+                                }
+                                Err(_) => {
+                                    warn!("diagnostics port client timed out during handshake");
+                                    return Ok(());
+                                }
+                            };
+                            info!(%client, "client authenticated");
+                            let audit_source = remote_audit_source(client_id, client_addr, client);
+                            handler(effect_builder, stream, audit_source, shutdown_receiver).await
+                        }
+                        .instrument(span),
+                    );
+                }
+                Err(err) => {
+                    info!(%err, "failed to accept incoming TCP connection on diagnostics port");
+                }
+            }
+        }
+    };
+
+    let shutdown_messages = async move { while shutdown_receiver.changed().await.is_ok() {} };
+
+    match future::select(Box::pin(shutdown_messages), Box::pin(accept_connections)).await {
+        Either::Left(_) => info!("shutting down diagnostics port TCP listener"),
+        Either::Right(_) => unreachable!("server accept returns `!`"),
+    }
+}
+
+/// Identifies a diagnostics port client connected via mutual TLS by its certificate.
+fn remote_audit_source(
+    client_id: u64,
+    addr: SocketAddr,
+    client: tls::ClientIdentity,
+) -> audit_log::Source {
+    audit_log::Source::RemoteDiagnosticsPort {
+        client_id,
+        addr,
+        subject: client.subject,
+        fingerprint: client.fingerprint,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -493,7 +834,7 @@ mod tests {
 
     use crate::{
         components::{diagnostics_port::Config as DiagnosticsPortConfig, small_network},
-        reactor::{participating::ParticipatingEvent, QueueKind},
+        reactor::{participating::ParticipatingEvent, QueueKind, ReactorEvent},
         testing::{
             self,
             network::{Network, NetworkedReactor},
@@ -544,6 +885,11 @@ mod tests {
 
         requests: {
             DumpConsensusStateRequest -> !;
+            DumpNetworkStateRequest -> !;
+            BlockProposerRequest -> !;
+            ReloadConfigRequest -> !;
+            ShutdownPointRequest -> !;
+            ValidatorModeRequest -> !;
         }
 
         announcements: {}
@@ -655,4 +1001,26 @@ mod tests {
             })
             .await;
     }
+
+    #[tokio::test]
+    async fn can_summarize_events_from_scheduler() {
+        let scheduler = WeightedRoundRobin::new(QueueKind::weights());
+        for event in [
+            small_network::Event::SweepOutgoing,
+            small_network::Event::GossipOurAddress,
+        ] {
+            scheduler
+                .push(ParticipatingEvent::SmallNetwork(event), QueueKind::Network)
+                .await;
+        }
+
+        let summary = scheduler
+            .summarize(ParticipatingEvent::description, ToString::to_string, false)
+            .await;
+        let json = serde_json::to_value(&summary).expect("could not serialize summary");
+        assert_eq!(json["queues"]["Network"]["depth"], 2);
+        assert_eq!(json["queues"]["Network"]["counts"]["SmallNetwork"], 2);
+        assert!(json["queues"]["Network"]["items"].is_null());
+        assert_eq!(json["queues"]["Regular"]["depth"], 0);
+    }
 }
@@ -9,9 +9,16 @@
 //! a component implementation that interfaces with other components via being plugged into a
 //! reactor, and an external facing http server that manages SSE subscriptions on a single endpoint.
 //!
-//! This component is passive and receives announcements made by other components while never making
-//! a request of other components itself. The handled announcements are serialized to JSON and
-//! pushed to subscribers.
+//! Operators can configure additional endpoints, each serving a subset of the events with its own
+//! buffer and subscriber limit, so that heavyweight consumers can be isolated from each other.
+//!
+//! Clients interested in only a few events can instead connect to the WebSocket endpoint, which
+//! filters events server-side by type, deploy hash, account and era, see `ws_server`.
+//!
+//! This component receives announcements made by other components, which are serialized to JSON and
+//! pushed to subscribers. The only requests it makes of other components are global state queries
+//! resolving the main purses of accounts watched by subscribers, so that balance changes of those
+//! purses are included in the subscribers' filtered streams.
 //!
 //! This component uses a ring buffer for outbound events providing some robustness against
 //! unintended subscriber disconnects, if a disconnected subscriber re-subscribes before the buffer
@@ -27,31 +34,36 @@ mod http_server;
 mod sse_server;
 #[cfg(test)]
 mod tests;
+mod ws_server;
 
-use std::{convert::Infallible, fmt::Debug, net::SocketAddr, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashSet, convert::Infallible, fmt::Debug, net::SocketAddr, path::PathBuf,
+    sync::Arc,
+};
 
 use datasize::DataSize;
 use tokio::sync::{
     mpsc::{self, UnboundedSender},
     oneshot,
 };
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 use warp::Filter;
 
-use casper_types::ProtocolVersion;
+use casper_hashing::Digest;
+use casper_types::{Key, ProtocolVersion};
 
 use super::Component;
 use crate::{
-    effect::{EffectBuilder, Effects},
-    types::JsonBlock,
+    effect::{requests::ContractRuntimeRequest, EffectBuilder, EffectExt, Effects},
+    types::{BlockSignatures, JsonBlock, JsonProof},
     utils::{self, ListeningError},
     NodeRng,
 };
-pub use config::Config;
+pub use config::{Config, EndpointConfig};
 pub(crate) use event::Event;
 use event_indexer::{EventIndex, EventIndexer};
-use sse_server::ChannelsAndFilter;
 pub(crate) use sse_server::SseData;
+use sse_server::{ChannelsAndFilter, EventFilter, WatchedAccounts};
 
 /// This is used to define the number of events to buffer in the tokio broadcast channel to help
 /// slower clients to try to avoid missing events (See
@@ -65,9 +77,12 @@ const ADDITIONAL_PERCENT_FOR_BROADCAST_CHANNEL_SIZE: u32 = 20;
 
 /// A helper trait whose bounds represent the requirements for a reactor event that `run_server` can
 /// work with.
-pub trait ReactorEventT: From<Event> + Send {}
+pub trait ReactorEventT: From<Event> + From<ContractRuntimeRequest> + Send {}
 
-impl<REv> ReactorEventT for REv where REv: From<Event> + Send + 'static {}
+impl<REv> ReactorEventT for REv where
+    REv: From<Event> + From<ContractRuntimeRequest> + Send + 'static
+{
+}
 
 #[derive(DataSize, Debug)]
 struct InnerServer {
@@ -75,10 +90,27 @@ struct InnerServer {
     // TODO - this should not be skipped.  Awaiting support for `UnboundedSender` in datasize crate.
     #[data_size(skip)]
     sse_data_sender: UnboundedSender<(EventIndex, SseData)>,
+    /// Channel sender to pass a new event stream buffer length to the event-stream server.
+    #[data_size(skip)]
+    buffer_length_sender: UnboundedSender<u32>,
+    /// Channel senders to pass event-stream data to the additional endpoints.
+    #[data_size(skip)]
+    endpoints: Vec<EndpointSender>,
+    /// The accounts watched by subscribers, along with their main purses once resolved.
+    #[data_size(skip)]
+    watched_accounts: WatchedAccounts,
     event_indexer: EventIndexer,
     listening_address: SocketAddr,
 }
 
+/// The sending half of an additional endpoint's data channel.
+#[derive(Debug)]
+struct EndpointSender {
+    /// The events served by the endpoint.
+    event_filter: &'static [EventFilter],
+    sse_data_sender: UnboundedSender<(EventIndex, SseData)>,
+}
+
 #[derive(DataSize, Debug)]
 pub(crate) struct EventStreamServer {
     inner: Option<InnerServer>,
@@ -105,11 +137,13 @@ impl EventStreamServer {
 
         let event_indexer = EventIndexer::new(storage_path);
         let (sse_data_sender, sse_data_receiver) = mpsc::unbounded_channel();
+        let (buffer_length_sender, buffer_length_receiver) = mpsc::unbounded_channel();
 
         // Event stream channels and filter.
         let broadcast_channel_size = config.event_stream_buffer_length
             * (100 + ADDITIONAL_PERCENT_FOR_BROADCAST_CHANNEL_SIZE)
             / 100;
+        let watched_accounts = WatchedAccounts::default();
         let ChannelsAndFilter {
             event_broadcaster,
             new_subscriber_info_receiver,
@@ -117,8 +151,43 @@ impl EventStreamServer {
         } = ChannelsAndFilter::new(
             broadcast_channel_size as usize,
             config.max_concurrent_subscribers,
+            watched_accounts.clone(),
         );
 
+        // Additional endpoints, each with its own channels and buffer, tried before the built-in
+        // ones.
+        validate_endpoints(&config.endpoints)?;
+        let mut sse_filter = sse_filter;
+        let mut endpoints = Vec::with_capacity(config.endpoints.len());
+        for endpoint in &config.endpoints {
+            // The filter is required for as long as the server runs.
+            let event_filter: &'static [EventFilter] =
+                Box::leak(endpoint.event_kinds.clone().into_boxed_slice());
+            let broadcast_channel_size = endpoint.event_stream_buffer_length
+                * (100 + ADDITIONAL_PERCENT_FOR_BROADCAST_CHANNEL_SIZE)
+                / 100;
+            let endpoint_channels = ChannelsAndFilter::new_endpoint(
+                endpoint.name.clone(),
+                event_filter,
+                broadcast_channel_size as usize,
+                endpoint.max_concurrent_subscribers,
+            );
+            sse_filter = endpoint_channels.sse_filter.or(sse_filter).unify().boxed();
+
+            let (endpoint_data_sender, endpoint_data_receiver) = mpsc::unbounded_channel();
+            tokio::spawn(http_server::run_endpoint(
+                endpoint.event_stream_buffer_length,
+                api_version,
+                endpoint_data_receiver,
+                endpoint_channels.event_broadcaster,
+                endpoint_channels.new_subscriber_info_receiver,
+            ));
+            endpoints.push(EndpointSender {
+                event_filter,
+                sse_data_sender: endpoint_data_sender,
+            });
+        }
+
         let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
 
         let (listening_address, server_with_shutdown) =
@@ -140,25 +209,114 @@ impl EventStreamServer {
             sse_data_receiver,
             event_broadcaster,
             new_subscriber_info_receiver,
+            buffer_length_receiver,
         ));
 
         Ok(EventStreamServer {
             inner: Some(InnerServer {
                 sse_data_sender,
+                buffer_length_sender,
+                endpoints,
+                watched_accounts,
                 event_indexer,
                 listening_address,
             }),
         })
     }
 
+    /// Changes the number of events buffered for clients re-subscribing.
+    ///
+    /// The size of the broadcast channel is only changed on restart.
+    pub(crate) fn set_buffer_length(&self, buffer_length: u32) {
+        if let Some(server) = self.inner.as_ref() {
+            let _ = server.buffer_length_sender.send(buffer_length);
+        }
+    }
+
     /// Broadcasts the SSE data to all clients connected to the event stream.
     fn broadcast(&mut self, sse_data: SseData) -> Effects<Event> {
         if let Some(server) = self.inner.as_mut() {
             let event_index = server.event_indexer.next_index();
+            for endpoint in &server.endpoints {
+                if sse_data.should_include(endpoint.event_filter) {
+                    let _ = endpoint
+                        .sse_data_sender
+                        .send((event_index, sse_data.clone()));
+                }
+            }
             let _ = server.sse_data_sender.send((event_index, sse_data));
         }
         Effects::new()
     }
+
+    /// Resolves the main purses of the watched accounts not yet resolved, against the given state
+    /// root hash.
+    fn resolve_watched_accounts<REv>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        state_root_hash: Digest,
+    ) -> Effects<Event>
+    where
+        REv: ReactorEventT,
+    {
+        let server = match self.inner.as_ref() {
+            Some(server) => server,
+            None => return Effects::new(),
+        };
+        server
+            .watched_accounts
+            .unresolved()
+            .into_iter()
+            .flat_map(|account_hash| {
+                let watched_accounts = server.watched_accounts.clone();
+                async move {
+                    match effect_builder
+                        .get_account_from_global_state(state_root_hash, Key::Account(account_hash))
+                        .await
+                    {
+                        Some(account) => {
+                            watched_accounts.resolve(account_hash, account.main_purse())
+                        }
+                        None => debug!(%account_hash, "watched account not found in global state"),
+                    }
+                }
+                .ignore()
+            })
+            .collect()
+    }
+}
+
+/// Checks that the additional endpoints have unique, URL-safe names not used by the built-in
+/// endpoints.
+fn validate_endpoints(endpoints: &[EndpointConfig]) -> Result<(), ListeningError> {
+    let mut names = HashSet::new();
+    for endpoint in endpoints {
+        let reason = if endpoint.name.is_empty()
+            || !endpoint
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            "name must be non-empty and consist of alphanumeric characters, '-' and '_'"
+        } else if sse_server::get_filter(&endpoint.name).is_some()
+            || endpoint.name == ws_server::WS_API_PATH
+        {
+            "name is used by a built-in endpoint"
+        } else if !names.insert(endpoint.name.as_str()) {
+            "name is used by another endpoint"
+        } else if endpoint.event_kinds.is_empty() {
+            "no event kinds given"
+        } else if endpoint.event_stream_buffer_length == 0 {
+            "buffer length must be positive"
+        } else {
+            continue;
+        };
+        return Err(ListeningError::InvalidEndpoint {
+            name: endpoint.name.clone(),
+            reason,
+        });
+    }
+    Ok(())
 }
 
 impl Drop for EventStreamServer {
@@ -176,15 +334,20 @@ where
 
     fn handle_event(
         &mut self,
-        _effect_builder: EffectBuilder<REv>,
+        effect_builder: EffectBuilder<REv>,
         _rng: &mut NodeRng,
         event: Self::Event,
     ) -> Effects<Self::Event> {
         match event {
-            Event::BlockAdded(block) => self.broadcast(SseData::BlockAdded {
-                block_hash: *block.hash(),
-                block: Box::new(JsonBlock::new(*block, None)),
-            }),
+            Event::BlockAdded(block) => {
+                let mut effects =
+                    self.resolve_watched_accounts(effect_builder, *block.state_root_hash());
+                effects.extend(self.broadcast(SseData::BlockAdded {
+                    block_hash: *block.hash(),
+                    block: Box::new(JsonBlock::new(*block, None)),
+                }));
+                effects
+            }
             Event::DeployAccepted(deploy) => self.broadcast(SseData::DeployAccepted {
                 deploy: Arc::new(*deploy),
             }),
@@ -216,6 +379,18 @@ where
                 timestamp,
             }),
             Event::FinalitySignature(fs) => self.broadcast(SseData::FinalitySignature(fs)),
+            Event::Finalized(signatures) => {
+                let BlockSignatures {
+                    block_hash,
+                    era_id,
+                    proofs,
+                } = *signatures;
+                self.broadcast(SseData::Finalized {
+                    block_hash,
+                    era_id,
+                    finality_signatures: proofs.into_iter().map(JsonProof::from).collect(),
+                })
+            }
             Event::Step {
                 era_id,
                 execution_effect,
@@ -223,6 +398,8 @@ where
                 era_id,
                 execution_effect,
             }),
+            Event::SyncProgress(sync_status) => self.broadcast(SseData::SyncProgress(sync_status)),
+            Event::FinalityAlert(alert) => self.broadcast(SseData::FinalityAlert(alert)),
         }
     }
 }
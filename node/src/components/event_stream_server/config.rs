@@ -1,6 +1,8 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use super::sse_server::EventFilter;
+
 /// Default binding address for the SSE HTTP server.
 ///
 /// Uses a fixed port per node, but binds on any interface.
@@ -28,6 +30,28 @@ pub struct Config {
 
     /// Default maximum number of subscribers across all event streams permitted at any one time.
     pub max_concurrent_subscribers: u32,
+
+    /// Additional endpoints, each serving a subset of the events with its own buffer and limit.
+    #[serde(default)]
+    pub endpoints: Vec<EndpointConfig>,
+}
+
+/// Configuration of an additional SSE endpoint.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct EndpointConfig {
+    /// Name of the endpoint, served at `/events/<name>`.
+    pub name: String,
+
+    /// Kinds of events sent to subscribers of the endpoint.
+    pub event_kinds: Vec<EventFilter>,
+
+    /// Number of SSEs to buffer for subscribers of the endpoint.
+    pub event_stream_buffer_length: u32,
+
+    /// Maximum number of subscribers to the endpoint permitted at any one time.
+    pub max_concurrent_subscribers: u32,
 }
 
 impl Config {
@@ -38,6 +62,7 @@ impl Config {
             address: DEFAULT_ADDRESS.to_string(),
             event_stream_buffer_length: DEFAULT_EVENT_STREAM_BUFFER_LENGTH,
             max_concurrent_subscribers: DEFAULT_MAX_CONCURRENT_SUBSCRIBERS,
+            endpoints: Vec::new(),
         }
     }
 }
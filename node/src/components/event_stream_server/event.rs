@@ -3,7 +3,12 @@ use std::fmt::{self, Display, Formatter};
 use casper_types::{EraId, ExecutionEffect, ExecutionResult, PublicKey, Timestamp};
 use itertools::Itertools;
 
-use crate::types::{Block, BlockHash, Deploy, DeployHash, DeployHeader, FinalitySignature};
+use crate::{
+    components::{chain_synchronizer::SyncStatus, finality_watchdog::FinalityAlert},
+    types::{
+        Block, BlockHash, BlockSignatures, Deploy, DeployHash, DeployHeader, FinalitySignature,
+    },
+};
 
 #[derive(Debug)]
 pub enum Event {
@@ -22,10 +27,13 @@ pub enum Event {
         timestamp: Timestamp,
     },
     FinalitySignature(Box<FinalitySignature>),
+    Finalized(Box<BlockSignatures>),
     Step {
         era_id: EraId,
         execution_effect: ExecutionEffect,
     },
+    SyncProgress(SyncStatus),
+    FinalityAlert(FinalityAlert),
 }
 
 impl Display for Event {
@@ -55,7 +63,14 @@ impl Display for Event {
                 public_key, timestamp, era_id,
             ),
             Event::FinalitySignature(fs) => write!(formatter, "finality signature {}", fs),
+            Event::Finalized(signatures) => {
+                write!(formatter, "block finalized {}", signatures.block_hash)
+            }
             Event::Step { era_id, .. } => write!(formatter, "step committed for {}", era_id),
+            Event::SyncProgress(sync_status) => {
+                write!(formatter, "sync progress {:?}", sync_status.phase)
+            }
+            Event::FinalityAlert(alert) => write!(formatter, "finality {}", alert.status),
         }
     }
 }
@@ -25,6 +25,7 @@ use super::{
 /// * `new_subscriber_info_receiver` is used to notify the server of the details of a new client
 ///   having subscribed to the event stream.  It allows the server to populate that client's stream
 ///   with the requested number of historical events.
+/// * `buffer_length_receiver` is used to change the number of buffered events while running.
 pub(super) async fn run(
     config: Config,
     api_version: ProtocolVersion,
@@ -33,6 +34,7 @@ pub(super) async fn run(
     mut data_receiver: mpsc::UnboundedReceiver<(EventIndex, SseData)>,
     broadcaster: broadcast::Sender<BroadcastChannelMessage>,
     mut new_subscriber_info_receiver: mpsc::UnboundedReceiver<NewSubscriberInfo>,
+    mut buffer_length_receiver: mpsc::UnboundedReceiver<u32>,
 ) {
     let server_joiner = task::spawn(server_with_shutdown);
 
@@ -49,43 +51,21 @@ pub(super) async fn run(
             select! {
                 maybe_new_subscriber = new_subscriber_info_receiver.recv() => {
                     if let Some(subscriber) = maybe_new_subscriber {
-                        // First send the client the `ApiVersion` event.  We don't care if this
-                        // errors - the client may have disconnected already.
-                        let _ = subscriber
-                            .initial_events_sender
-                            .send(ServerSentEvent::initial_event(api_version));
-                        // If the client supplied a "start_from" index, provide the buffered events.
-                        // If they requested more than is buffered, just provide the whole buffer.
-                        if let Some(start_index) = subscriber.start_from {
-                            // If the buffer's first event ID is in the range [0, buffer size) or
-                            // (Id::MAX - buffer size, Id::MAX], then the events in the buffer are
-                            // considered to have their IDs wrapping round, or that was recently the
-                            // case.  In this case, we add `buffer.capacity()` to `start_index` and
-                            // the buffered events' IDs when considering which events to include in
-                            // the requested initial events, effectively shifting all the IDs past
-                            // the wrapping transition.
-                            let buffer_size = buffer.capacity() as Id;
-                            let in_wraparound_zone = buffer
-                                .iter()
-                                .next()
-                                .map(|event| {
-                                    let id = event.id.unwrap();
-                                    id > Id::MAX - buffer_size || id < buffer_size
-                                })
-                                .unwrap_or_default();
-                            for event in buffer.iter().skip_while(|event| {
-                                if in_wraparound_zone {
-                                    event.id.unwrap().wrapping_add(buffer_size)
-                                        < start_index.wrapping_add(buffer_size)
-                                } else {
-                                    event.id.unwrap() < start_index
-                                }
-                            }) {
-                                // As per sending `SSE_INITIAL_EVENT`, we don't care if this errors.
-                                let _ = subscriber.initial_events_sender.send(event.clone());
-                            }
-                        }
+                        send_initial_events(&buffer, subscriber, api_version);
+                    }
+                }
+
+                Some(buffer_length) = buffer_length_receiver.recv() => {
+                    // Rebuild the buffer with the new capacity, keeping the most recent events.
+                    let buffered_events: Vec<_> = buffer.iter().cloned().collect();
+                    buffer = WheelBuf::new(vec![
+                        ServerSentEvent::initial_event(api_version);
+                        buffer_length as usize
+                    ]);
+                    for event in buffered_events {
+                        buffer.push(event);
                     }
+                    info!(%buffer_length, "changed event stream buffer length");
                 }
 
                 maybe_data = data_receiver.recv() => {
@@ -121,3 +101,83 @@ pub(super) async fn run(
 
     trace!("Event stream server stopped");
 }
+
+/// Runs the buffer of an additional endpoint configured via `Config::endpoints`.
+///
+/// Only the events served by the endpoint are received via `data_receiver`, so they don't compete
+/// for buffer space with events served by other endpoints.
+pub(super) async fn run_endpoint(
+    buffer_length: u32,
+    api_version: ProtocolVersion,
+    mut data_receiver: mpsc::UnboundedReceiver<(EventIndex, SseData)>,
+    broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    mut new_subscriber_info_receiver: mpsc::UnboundedReceiver<NewSubscriberInfo>,
+) {
+    let mut buffer = WheelBuf::new(vec![
+        ServerSentEvent::initial_event(api_version);
+        buffer_length as usize
+    ]);
+
+    loop {
+        select! {
+            Some(subscriber) = new_subscriber_info_receiver.recv() => {
+                send_initial_events(&buffer, subscriber, api_version);
+            }
+
+            maybe_data = data_receiver.recv() => {
+                match maybe_data {
+                    Some((event_index, data)) => {
+                        let event = ServerSentEvent { id: Some(event_index), data };
+                        buffer.push(event.clone());
+                        let _ = broadcaster.send(BroadcastChannelMessage::ServerSentEvent(event));
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+
+    let _ = broadcaster.send(BroadcastChannelMessage::Shutdown);
+}
+
+/// Sends a new subscriber the `ApiVersion` event, followed by the buffered events it requested.
+fn send_initial_events(
+    buffer: &WheelBuf<Vec<ServerSentEvent>, ServerSentEvent>,
+    subscriber: NewSubscriberInfo,
+    api_version: ProtocolVersion,
+) {
+    // First send the client the `ApiVersion` event.  We don't care if this errors - the client may
+    // have disconnected already.
+    let _ = subscriber
+        .initial_events_sender
+        .send(ServerSentEvent::initial_event(api_version));
+    // If the client supplied a "start_from" index, provide the buffered events.  If they requested
+    // more than is buffered, just provide the whole buffer.
+    if let Some(start_index) = subscriber.start_from {
+        // If the buffer's first event ID is in the range [0, buffer size) or
+        // (Id::MAX - buffer size, Id::MAX], then the events in the buffer are considered to have
+        // their IDs wrapping round, or that was recently the case.  In this case, we add
+        // `buffer.capacity()` to `start_index` and the buffered events' IDs when considering which
+        // events to include in the requested initial events, effectively shifting all the IDs past
+        // the wrapping transition.
+        let buffer_size = buffer.capacity() as Id;
+        let in_wraparound_zone = buffer
+            .iter()
+            .next()
+            .map(|event| {
+                let id = event.id.unwrap();
+                id > Id::MAX - buffer_size || id < buffer_size
+            })
+            .unwrap_or_default();
+        for event in buffer.iter().skip_while(|event| {
+            if in_wraparound_zone {
+                event.id.unwrap().wrapping_add(buffer_size) < start_index.wrapping_add(buffer_size)
+            } else {
+                event.id.unwrap() < start_index
+            }
+        }) {
+            // As per sending `SSE_INITIAL_EVENT`, we don't care if this errors.
+            let _ = subscriber.initial_events_sender.send(event.clone());
+        }
+    }
+}
@@ -35,10 +35,15 @@ use warp::{
 #[cfg(test)]
 use casper_types::testing::TestRng;
 use casper_types::{
-    EraId, ExecutionEffect, ExecutionResult, ProtocolVersion, PublicKey, TimeDiff, Timestamp,
+    account::AccountHash, EraId, ExecutionEffect, ExecutionResult, Key, ProtocolVersion, PublicKey,
+    TimeDiff, Timestamp, URef,
 };
 
-use crate::types::{BlockHash, Deploy, DeployHash, FinalitySignature, JsonBlock};
+use super::ws_server;
+use crate::{
+    components::{chain_synchronizer::SyncStatus, finality_watchdog::FinalityAlert},
+    types::{BlockHash, Deploy, DeployHash, FinalitySignature, JsonBlock, JsonProof},
+};
 #[cfg(test)]
 use crate::{testing, types::Block};
 
@@ -49,23 +54,121 @@ pub const SSE_API_ROOT_PATH: &str = "events";
 pub const SSE_API_MAIN_PATH: &str = "main";
 /// The URL path part to subscribe to only `DeployAccepted` events.
 pub const SSE_API_DEPLOYS_PATH: &str = "deploys";
-/// The URL path part to subscribe to only `FinalitySignature` events.
+/// The URL path part to subscribe to only `FinalitySignature` and `Finalized` events.
 pub const SSE_API_SIGNATURES_PATH: &str = "sigs";
+/// The URL path part to subscribe to only `DeployProcessed` and `Step` events which modified one
+/// of a given set of global state keys.
+pub const SSE_API_STATE_PATH: &str = "state";
 /// The URL query string field name.
 pub const QUERY_FIELD: &str = "start_from";
+/// The URL query string field name for the comma-separated list of watched global state keys.
+pub const KEYS_QUERY_FIELD: &str = "keys";
+/// The maximum number of global state keys a single client can watch.
+pub const MAX_WATCHED_KEYS: usize = 100;
+
+/// The main purses of the accounts watched by clients of the state path.
+///
+/// Shared with the event stream server component, which resolves the main purses from global state
+/// as blocks are added.
+#[derive(Clone, Debug, Default)]
+pub(super) struct WatchedAccounts(Arc<RwLock<HashMap<AccountHash, Option<URef>>>>);
+
+impl WatchedAccounts {
+    /// Registers the given accounts, to have their main purses resolved if not yet known.
+    fn watch(&self, account_hashes: &[AccountHash]) {
+        let mut main_purses = self.0.write().unwrap();
+        for account_hash in account_hashes {
+            let _ = main_purses.entry(*account_hash).or_insert(None);
+        }
+    }
+
+    /// Returns the watched accounts whose main purses are not yet known.
+    pub(super) fn unresolved(&self) -> Vec<AccountHash> {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, maybe_main_purse)| maybe_main_purse.is_none())
+            .map(|(account_hash, _)| *account_hash)
+            .collect()
+    }
+
+    /// Records the main purse of a watched account.
+    pub(super) fn resolve(&self, account_hash: AccountHash, main_purse: URef) {
+        let _ = self
+            .0
+            .write()
+            .unwrap()
+            .insert(account_hash, Some(main_purse));
+    }
+
+    /// Returns the formatted balance keys of the known main purses of the given accounts.
+    fn balance_keys(&self, account_hashes: &[AccountHash]) -> HashSet<String> {
+        let main_purses = self.0.read().unwrap();
+        account_hashes
+            .iter()
+            .filter_map(|account_hash| main_purses.get(account_hash).copied().flatten())
+            .map(|main_purse| Key::Balance(main_purse.addr()).to_formatted_string())
+            .collect()
+    }
+}
+
+/// The global state keys watched by a client of the state path.
+#[derive(Debug)]
+pub(super) struct WatchedKeys {
+    /// The formatted keys.
+    keys: HashSet<String>,
+    /// The accounts among the keys, the balances of whose main purses are watched as well.
+    accounts: Vec<AccountHash>,
+    watched_accounts: WatchedAccounts,
+}
+
+impl WatchedKeys {
+    fn new(keys: HashSet<String>, watched_accounts: WatchedAccounts) -> Self {
+        let accounts: Vec<AccountHash> = keys
+            .iter()
+            .filter_map(|key| match Key::from_formatted_str(key) {
+                Ok(Key::Account(account_hash)) => Some(account_hash),
+                _ => None,
+            })
+            .collect();
+        watched_accounts.watch(&accounts);
+        WatchedKeys {
+            keys,
+            accounts,
+            watched_accounts,
+        }
+    }
+
+    /// Returns `true` if the event should be sent to the client, i.e. if it is not a
+    /// state-modifying event, or it modified any of the keys or the balance of the main purse of
+    /// any of the accounts.
+    fn matches(&self, data: &SseData) -> bool {
+        if data.touches_any_key(&self.keys) {
+            return true;
+        }
+        !self.accounts.is_empty()
+            && data.touches_any_key(&self.watched_accounts.balance_keys(&self.accounts))
+    }
+}
 
 /// The filter associated with `/events/main` path.
-const MAIN_FILTER: [EventFilter; 5] = [
+const MAIN_FILTER: [EventFilter; 7] = [
     EventFilter::BlockAdded,
     EventFilter::DeployProcessed,
     EventFilter::DeployExpired,
     EventFilter::Fault,
     EventFilter::Step,
+    EventFilter::SyncProgress,
+    EventFilter::FinalityAlert,
 ];
 /// The filter associated with `/events/deploys` path.
 const DEPLOYS_FILTER: [EventFilter; 1] = [EventFilter::DeployAccepted];
 /// The filter associated with `/events/sigs` path.
-const SIGNATURES_FILTER: [EventFilter; 1] = [EventFilter::FinalitySignature];
+const SIGNATURES_FILTER: [EventFilter; 2] =
+    [EventFilter::FinalitySignature, EventFilter::Finalized];
+/// The filter associated with `/events/state` path.
+const STATE_FILTER: [EventFilter; 2] = [EventFilter::DeployProcessed, EventFilter::Step];
 
 /// The "id" field of the events sent on the event stream to clients.
 pub type Id = u32;
@@ -109,12 +212,23 @@ pub enum SseData {
     },
     /// New finality signature received.
     FinalitySignature(Box<FinalitySignature>),
+    /// The given block has been finalized, as proven by the given finality signatures from
+    /// validators exceeding the finality threshold by weight.
+    Finalized {
+        block_hash: BlockHash,
+        era_id: EraId,
+        finality_signatures: Vec<JsonProof>,
+    },
     /// The execution effects produced by a `StepRequest`.
     Step {
         era_id: EraId,
         #[data_size(skip)]
         execution_effect: ExecutionEffect,
     },
+    /// The current progress of the node's chain synchronization, sent periodically while syncing.
+    SyncProgress(SyncStatus),
+    /// The node's view of whether the network is finalizing blocks has changed.
+    FinalityAlert(FinalityAlert),
     /// The node is about to shut down.
     Shutdown,
 }
@@ -129,9 +243,41 @@ impl SseData {
             SseData::DeployExpired { .. } => filter.contains(&EventFilter::DeployExpired),
             SseData::Fault { .. } => filter.contains(&EventFilter::Fault),
             SseData::FinalitySignature(_) => filter.contains(&EventFilter::FinalitySignature),
+            SseData::Finalized { .. } => filter.contains(&EventFilter::Finalized),
             SseData::Step { .. } => filter.contains(&EventFilter::Step),
+            SseData::SyncProgress(_) => filter.contains(&EventFilter::SyncProgress),
+            SseData::FinalityAlert(_) => filter.contains(&EventFilter::FinalityAlert),
         }
     }
+
+    /// Returns `true` if the event should be sent to a client watching the given formatted global
+    /// state keys, i.e. if it is not a state-modifying event or it modified any of the keys.
+    pub(super) fn touches_any_key(&self, watched_keys: &HashSet<String>) -> bool {
+        let effect = match self {
+            SseData::ApiVersion(_) | SseData::Shutdown => return true,
+            SseData::DeployProcessed {
+                execution_result, ..
+            } => match &**execution_result {
+                ExecutionResult::Success { effect, .. }
+                | ExecutionResult::Failure { effect, .. } => effect,
+            },
+            SseData::Step {
+                execution_effect, ..
+            } => execution_effect,
+            SseData::BlockAdded { .. }
+            | SseData::DeployAccepted { .. }
+            | SseData::DeployExpired { .. }
+            | SseData::Fault { .. }
+            | SseData::FinalitySignature(_)
+            | SseData::Finalized { .. }
+            | SseData::SyncProgress(_)
+            | SseData::FinalityAlert(_) => return false,
+        };
+        effect
+            .transforms
+            .iter()
+            .any(|transform_entry| watched_keys.contains(&transform_entry.key))
+    }
 }
 
 #[cfg(test)]
@@ -203,6 +349,21 @@ impl SseData {
         )))
     }
 
+    /// Returns a random `SseData::Finalized`.
+    pub(super) fn random_finalized(rng: &mut TestRng) -> Self {
+        let block_hash = BlockHash::random(rng);
+        let era_id = EraId::new(rng.gen());
+        let finality_signature = FinalitySignature::random_for_block(block_hash, era_id.value());
+        SseData::Finalized {
+            block_hash,
+            era_id,
+            finality_signatures: vec![JsonProof::from((
+                finality_signature.public_key,
+                finality_signature.signature,
+            ))],
+        }
+    }
+
     /// Returns a random `SseData::Step`.
     pub(super) fn random_step(rng: &mut TestRng) -> Self {
         let execution_effect = match rng.gen::<ExecutionResult>() {
@@ -265,15 +426,21 @@ pub(super) struct NewSubscriberInfo {
 }
 
 /// A filter for event types a client has subscribed to receive.
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
-pub(super) enum EventFilter {
+///
+/// Also used to configure the event kinds served by additional endpoints.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventFilter {
     BlockAdded,
     DeployAccepted,
     DeployProcessed,
     DeployExpired,
     Fault,
     FinalitySignature,
+    Finalized,
     Step,
+    SyncProgress,
+    FinalityAlert,
 }
 
 /// Filters the `event`, mapping it to a warp event, or `None` if it should be filtered out.
@@ -316,6 +483,9 @@ async fn filter_map_server_sent_event(
         | &SseData::Fault { .. }
         | &SseData::Step { .. }
         | &SseData::FinalitySignature(_)
+        | &SseData::Finalized { .. }
+        | &SseData::SyncProgress(_)
+        | &SseData::FinalityAlert(_)
         | &SseData::Shutdown => Some(Ok(WarpServerSentEvent::default()
             .json_data(&event.data)
             .unwrap_or_else(|error| {
@@ -342,6 +512,7 @@ pub(super) fn get_filter(path_param: &str) -> Option<&'static [EventFilter]> {
         SSE_API_MAIN_PATH => Some(&MAIN_FILTER[..]),
         SSE_API_DEPLOYS_PATH => Some(&DEPLOYS_FILTER[..]),
         SSE_API_SIGNATURES_PATH => Some(&SIGNATURES_FILTER[..]),
+        SSE_API_STATE_PATH => Some(&STATE_FILTER[..]),
         _ => None,
     }
 }
@@ -368,14 +539,47 @@ fn parse_query(query: HashMap<String, String>) -> Result<Option<Id>, Response> {
     }
 }
 
+/// Extracts and removes the watched global state keys from the provided query, or `None` if the
+/// query doesn't contain the keys field.
+///
+/// Returns a 422 response if the keys field is present but doesn't hold a comma-separated list of
+/// between one and `MAX_WATCHED_KEYS` formatted global state keys.
+fn parse_watched_keys(
+    query: &mut HashMap<String, String>,
+) -> Result<Option<HashSet<String>>, Response> {
+    let keys_str = match query.remove(KEYS_QUERY_FIELD) {
+        Some(keys_str) => keys_str,
+        None => return Ok(None),
+    };
+
+    let mut watched_keys = HashSet::new();
+    for key_str in keys_str.split(',') {
+        match Key::from_formatted_str(key_str) {
+            // Normalize the key to match the formatting used in the execution effects.
+            Ok(key) => {
+                let _ = watched_keys.insert(key.to_formatted_string());
+            }
+            Err(_) => return Err(create_keys_422()),
+        }
+    }
+
+    if watched_keys.len() > MAX_WATCHED_KEYS {
+        return Err(create_keys_422());
+    }
+
+    Ok(Some(watched_keys))
+}
+
 /// Creates a 404 response with a useful error message in the body.
 fn create_404() -> Response {
     let mut response = Response::new(Body::from(format!(
-        "invalid path: expected '/{root}/{main}', '/{root}/{deploys}' or '/{root}/{sigs}'\n",
+        "invalid path: expected '/{root}/{main}', '/{root}/{deploys}', '/{root}/{sigs}' or \
+        '/{root}/{state}'\n",
         root = SSE_API_ROOT_PATH,
         main = SSE_API_MAIN_PATH,
         deploys = SSE_API_DEPLOYS_PATH,
-        sigs = SSE_API_SIGNATURES_PATH
+        sigs = SSE_API_SIGNATURES_PATH,
+        state = SSE_API_STATE_PATH
     )));
     *response.status_mut() = StatusCode::NOT_FOUND;
     response
@@ -392,9 +596,21 @@ fn create_422() -> Response {
     response
 }
 
+/// Creates a 422 response with a useful error message in the body for use in case of a missing or
+/// bad list of watched keys.
+fn create_keys_422() -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid query: expected field '{}=<KEY>[,<KEY>...]' with at most {} formatted keys and \
+        optional field '{}=<EVENT ID>'\n",
+        KEYS_QUERY_FIELD, MAX_WATCHED_KEYS, QUERY_FIELD
+    )));
+    *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+    response
+}
+
 /// Creates a 503 response (Service Unavailable) to be returned if the server has too many
 /// subscribers.
-fn create_503() -> Response {
+pub(super) fn create_503() -> Response {
     let mut response = Response::new(Body::from("server has reached limit of subscribers"));
     *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
     response
@@ -409,7 +625,11 @@ pub(super) struct ChannelsAndFilter {
 impl ChannelsAndFilter {
     /// Creates the message-passing channels required to run the event-stream server and the warp
     /// filter for the event-stream server.
-    pub(super) fn new(broadcast_channel_size: usize, max_concurrent_subscribers: u32) -> Self {
+    pub(super) fn new(
+        broadcast_channel_size: usize,
+        max_concurrent_subscribers: u32,
+        watched_accounts: WatchedAccounts,
+    ) -> Self {
         // Create a channel to broadcast new events to all subscribed clients' streams.
         let (event_broadcaster, _) = broadcast::channel(broadcast_channel_size);
         let cloned_broadcaster = event_broadcaster.clone();
@@ -418,8 +638,15 @@ impl ChannelsAndFilter {
         // new client subscription.
         let (new_subscriber_info_sender, new_subscriber_info_receiver) = mpsc::unbounded_channel();
 
+        // The WebSocket endpoint shares the channels of the built-in SSE endpoints.
+        let ws_filter = ws_server::ws_filter(
+            event_broadcaster.clone(),
+            new_subscriber_info_sender.clone(),
+            max_concurrent_subscribers,
+        );
+
         let serve = move |path_param: String,
-                          query: HashMap<String, String>,
+                          mut query: HashMap<String, String>,
                           maybe_remote_address: Option<SocketAddr>| {
             let remote_address = match maybe_remote_address {
                 Some(address) => address.to_string(),
@@ -442,47 +669,100 @@ impl ChannelsAndFilter {
                 None => return create_404(),
             };
 
+            // Only the state path requires, and accepts, a list of watched keys.
+            let watched_keys = match parse_watched_keys(&mut query) {
+                Ok(Some(_)) if path_param != SSE_API_STATE_PATH => return create_422(),
+                Ok(None) if path_param == SSE_API_STATE_PATH => return create_keys_422(),
+                Ok(maybe_keys) => maybe_keys
+                    .map(|keys| Arc::new(WatchedKeys::new(keys, watched_accounts.clone()))),
+                Err(error_response) => return error_response,
+            };
+
             let start_from = match parse_query(query) {
                 Ok(maybe_id) => maybe_id,
+                Err(_) if watched_keys.is_some() => return create_keys_422(),
                 Err(error_response) => return error_response,
             };
 
-            // Create a channel for the client's handler to receive the stream of initial events.
-            let (initial_events_sender, initial_events_receiver) = mpsc::unbounded_channel();
-
-            // Supply the server with the sender part of the channel along with the client's
-            // requested starting point.
-            let new_subscriber_info = NewSubscriberInfo {
+            subscribe(
+                &cloned_broadcaster,
+                &new_subscriber_info_sender,
+                event_filter,
+                watched_keys,
                 start_from,
-                initial_events_sender,
+                remote_address,
+            )
+        };
+
+        let sse_filter = warp::get()
+            .and(path(SSE_API_ROOT_PATH))
+            .and(path::param::<String>())
+            .and(path::end())
+            .and(warp::query())
+            .and(addr::remote())
+            .map(serve)
+            .or_else(|_| async move { Ok::<_, Rejection>((create_404(),)) });
+        let sse_filter = ws_filter.or(sse_filter).unify().boxed();
+
+        ChannelsAndFilter {
+            event_broadcaster,
+            new_subscriber_info_receiver,
+            sse_filter,
+        }
+    }
+
+    /// Creates the message-passing channels and the warp filter for an additional endpoint at
+    /// `/events/<name>`, serving only the events matching `event_filter`.
+    pub(super) fn new_endpoint(
+        name: String,
+        event_filter: &'static [EventFilter],
+        broadcast_channel_size: usize,
+        max_concurrent_subscribers: u32,
+    ) -> Self {
+        let (event_broadcaster, _) = broadcast::channel(broadcast_channel_size);
+        let cloned_broadcaster = event_broadcaster.clone();
+        let (new_subscriber_info_sender, new_subscriber_info_receiver) = mpsc::unbounded_channel();
+
+        let endpoint_name = name.clone();
+        let serve = move |query: HashMap<String, String>,
+                          maybe_remote_address: Option<SocketAddr>| {
+            let remote_address = match maybe_remote_address {
+                Some(address) => address.to_string(),
+                None => "unknown".to_string(),
             };
-            if new_subscriber_info_sender
-                .send(new_subscriber_info)
-                .is_err()
-            {
-                error!("failed to send new subscriber info");
+
+            if cloned_broadcaster.receiver_count() >= max_concurrent_subscribers as usize {
+                info!(
+                    %remote_address,
+                    endpoint = %endpoint_name,
+                    %max_concurrent_subscribers,
+                    "event stream endpoint has max subscribers: rejecting new one"
+                );
+                return create_503();
             }
 
-            // Create a channel for the client's handler to receive the stream of ongoing events.
-            let ongoing_events_receiver = cloned_broadcaster.subscribe();
+            let start_from = match parse_query(query) {
+                Ok(maybe_id) => maybe_id,
+                Err(error_response) => return error_response,
+            };
 
-            sse::reply(sse::keep_alive().stream(stream_to_client(
-                initial_events_receiver,
-                ongoing_events_receiver,
+            subscribe(
+                &cloned_broadcaster,
+                &new_subscriber_info_sender,
                 event_filter,
+                None,
+                start_from,
                 remote_address,
-            )))
-            .into_response()
+            )
         };
 
         let sse_filter = warp::get()
             .and(path(SSE_API_ROOT_PATH))
-            .and(path::param::<String>())
+            .and(path(name))
             .and(path::end())
             .and(warp::query())
             .and(addr::remote())
             .map(serve)
-            .or_else(|_| async move { Ok::<_, Rejection>((create_404(),)) })
             .boxed();
 
         ChannelsAndFilter {
@@ -493,7 +773,45 @@ impl ChannelsAndFilter {
     }
 }
 
-/// This takes the two channel receivers and turns them into a stream of SSEs to the subscribed
+/// Subscribes a new client, returning the response streaming the events to it.
+fn subscribe(
+    broadcaster: &broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: &mpsc::UnboundedSender<NewSubscriberInfo>,
+    event_filter: &'static [EventFilter],
+    watched_keys: Option<Arc<WatchedKeys>>,
+    start_from: Option<Id>,
+    remote_address: String,
+) -> Response {
+    // Create a channel for the client's handler to receive the stream of initial events.
+    let (initial_events_sender, initial_events_receiver) = mpsc::unbounded_channel();
+
+    // Supply the server with the sender part of the channel along with the client's requested
+    // starting point.
+    let new_subscriber_info = NewSubscriberInfo {
+        start_from,
+        initial_events_sender,
+    };
+    if new_subscriber_info_sender
+        .send(new_subscriber_info)
+        .is_err()
+    {
+        error!("failed to send new subscriber info");
+    }
+
+    // Create a channel for the client's handler to receive the stream of ongoing events.
+    let ongoing_events_receiver = broadcaster.subscribe();
+
+    sse::reply(sse::keep_alive().stream(stream_to_client(
+        initial_events_receiver,
+        ongoing_events_receiver,
+        event_filter,
+        watched_keys,
+        remote_address,
+    )))
+    .into_response()
+}
+
+/// This takes the two channel receivers and turns them into a stream of events to the subscribed
 /// client.
 ///
 /// The initial events receiver (an mpsc receiver) is exhausted first, and contains an initial
@@ -504,15 +822,11 @@ impl ChannelsAndFilter {
 /// either the client disconnects, or the server shuts down (indicated by sending a `Shutdown`
 /// variant via the channel).  This channel will receive all SSEs created from the moment the client
 /// subscribed to the server's event stream.
-///
-/// It also takes an `EventFilter` which causes events to which the client didn't subscribe to be
-/// skipped.
-fn stream_to_client(
+pub(super) fn event_stream(
     initial_events: mpsc::UnboundedReceiver<ServerSentEvent>,
     ongoing_events: broadcast::Receiver<BroadcastChannelMessage>,
-    event_filter: &'static [EventFilter],
     remote_address: String,
-) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + 'static {
+) -> impl Stream<Item = Result<ServerSentEvent, RecvError>> + 'static {
     // Keep a record of the IDs of the events delivered via the `initial_events` receiver.
     let initial_stream_ids = Arc::new(RwLock::new(HashSet::new()));
     let cloned_initial_ids = Arc::clone(&initial_stream_ids);
@@ -548,8 +862,7 @@ fn stream_to_client(
         })
         .take_while(|result| future::ready(!matches!(result, Err(RecvError::Closed))));
 
-    // Serve the initial events followed by the ongoing ones, filtering as dictated by the
-    // `event_filter`.
+    // Serve the initial events followed by the ongoing ones.
     UnboundedReceiverStream::new(initial_events)
         .map(move |event| {
             if let Some(id) = event.id {
@@ -558,19 +871,43 @@ fn stream_to_client(
             Ok(event)
         })
         .chain(ongoing_stream)
-        .filter_map(move |result| async move {
+}
+
+/// Turns the stream of events to a subscribed client into a stream of SSEs, see `event_stream`.
+///
+/// It takes an `EventFilter` which causes events to which the client didn't subscribe to be
+/// skipped, and optionally a set of watched global state keys, which causes events not modifying
+/// any of those keys, nor the balance of the main purse of any watched account, to be skipped.
+fn stream_to_client(
+    initial_events: mpsc::UnboundedReceiver<ServerSentEvent>,
+    ongoing_events: broadcast::Receiver<BroadcastChannelMessage>,
+    event_filter: &'static [EventFilter],
+    watched_keys: Option<Arc<WatchedKeys>>,
+    remote_address: String,
+) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + 'static {
+    event_stream(initial_events, ongoing_events, remote_address).filter_map(move |result| {
+        let watched_keys = watched_keys.clone();
+        async move {
             match result {
-                Ok(event) => filter_map_server_sent_event(&event, event_filter).await,
+                Ok(event) => {
+                    if let Some(keys) = watched_keys {
+                        if !keys.matches(&event.data) {
+                            return None;
+                        }
+                    }
+                    filter_map_server_sent_event(&event, event_filter).await
+                }
                 Err(error) => Some(Err(error)),
             }
-        })
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use std::iter;
 
-    use casper_types::testing::TestRng;
+    use casper_types::{testing::TestRng, AccessRights, Transform, TransformEntry, U512};
 
     use super::*;
     use crate::logging;
@@ -631,6 +968,10 @@ mod tests {
             id: Some(rng.gen()),
             data: SseData::random_finality_signature(&mut rng),
         };
+        let finalized = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::random_finalized(&mut rng),
+        };
         let step = ServerSentEvent {
             id: Some(rng.gen()),
             data: SseData::random_step(&mut rng),
@@ -640,7 +981,8 @@ mod tests {
             data: SseData::Shutdown,
         };
 
-        // `EventFilter::Main` should only filter out `DeployAccepted`s and `FinalitySignature`s.
+        // `EventFilter::Main` should only filter out `DeployAccepted`s, `FinalitySignature`s and
+        // `Finalized`s.
         should_not_filter_out(&api_version, &MAIN_FILTER[..]).await;
         should_not_filter_out(&block_added, &MAIN_FILTER[..]).await;
         should_not_filter_out(&deploy_processed, &MAIN_FILTER[..]).await;
@@ -651,6 +993,7 @@ mod tests {
 
         should_filter_out(&deploy_accepted, &MAIN_FILTER[..]).await;
         should_filter_out(&finality_signature, &MAIN_FILTER[..]).await;
+        should_filter_out(&finalized, &MAIN_FILTER[..]).await;
 
         // `EventFilter::DeployAccepted` should filter out everything except `ApiVersion`s and
         // `DeployAccepted`s.
@@ -663,12 +1006,14 @@ mod tests {
         should_filter_out(&deploy_expired, &DEPLOYS_FILTER[..]).await;
         should_filter_out(&fault, &DEPLOYS_FILTER[..]).await;
         should_filter_out(&finality_signature, &DEPLOYS_FILTER[..]).await;
+        should_filter_out(&finalized, &DEPLOYS_FILTER[..]).await;
         should_filter_out(&step, &DEPLOYS_FILTER[..]).await;
 
-        // `EventFilter::Signatures` should filter out everything except `ApiVersion`s and
-        // `FinalitySignature`s.
+        // `EventFilter::Signatures` should filter out everything except `ApiVersion`s,
+        // `FinalitySignature`s and `Finalized`s.
         should_not_filter_out(&api_version, &SIGNATURES_FILTER[..]).await;
         should_not_filter_out(&finality_signature, &SIGNATURES_FILTER[..]).await;
+        should_not_filter_out(&finalized, &SIGNATURES_FILTER[..]).await;
         should_not_filter_out(&shutdown, &SIGNATURES_FILTER[..]).await;
 
         should_filter_out(&block_added, &SIGNATURES_FILTER[..]).await;
@@ -717,6 +1062,10 @@ mod tests {
             id: None,
             data: SseData::random_finality_signature(&mut rng),
         };
+        let malformed_finalized = ServerSentEvent {
+            id: None,
+            data: SseData::random_finalized(&mut rng),
+        };
         let malformed_step = ServerSentEvent {
             id: None,
             data: SseData::random_step(&mut rng),
@@ -738,11 +1087,107 @@ mod tests {
             should_filter_out(&malformed_deploy_expired, filter).await;
             should_filter_out(&malformed_fault, filter).await;
             should_filter_out(&malformed_finality_signature, filter).await;
+            should_filter_out(&malformed_finalized, filter).await;
             should_filter_out(&malformed_step, filter).await;
             should_filter_out(&malformed_shutdown, filter).await;
         }
     }
 
+    /// This test checks that only state-modifying events which touch one of the watched keys are
+    /// passed to clients of the state path.
+    #[test]
+    fn should_filter_events_by_watched_keys() {
+        let mut rng = crate::new_rng();
+
+        let touched_key = Key::Hash(rng.gen()).to_formatted_string();
+        let untouched_key = Key::Hash(rng.gen()).to_formatted_string();
+        let main_purse = URef::new(rng.gen(), AccessRights::READ_ADD_WRITE);
+        let mut deploy_processed = SseData::random_deploy_processed(&mut rng);
+        match &mut deploy_processed {
+            SseData::DeployProcessed {
+                execution_result, ..
+            } => match &mut **execution_result {
+                ExecutionResult::Success { effect, .. }
+                | ExecutionResult::Failure { effect, .. } => {
+                    effect.transforms = vec![
+                        TransformEntry {
+                            key: touched_key.clone(),
+                            transform: Transform::Identity,
+                        },
+                        TransformEntry {
+                            key: Key::Balance(main_purse.addr()).to_formatted_string(),
+                            transform: Transform::AddUInt512(U512::one()),
+                        },
+                    ]
+                }
+            },
+            _ => unreachable!(),
+        }
+
+        let unrelated_keys = iter::once(untouched_key.clone()).collect();
+        assert!(!deploy_processed.touches_any_key(&unrelated_keys));
+        assert!(SseData::Shutdown.touches_any_key(&unrelated_keys));
+        assert!(SseData::random_api_version(&mut rng).touches_any_key(&unrelated_keys));
+        assert!(!SseData::random_block_added(&mut rng).touches_any_key(&unrelated_keys));
+
+        let watched_keys = vec![touched_key, untouched_key].into_iter().collect();
+        assert!(deploy_processed.touches_any_key(&watched_keys));
+    }
+
+    /// This test checks that watching an account also watches the balance of its main purse, once
+    /// the latter is resolved.
+    #[test]
+    fn should_watch_main_purse_balance_of_watched_account() {
+        let mut rng = crate::new_rng();
+
+        let account_hash = AccountHash::new(rng.gen());
+        let main_purse = URef::new(rng.gen(), AccessRights::READ_ADD_WRITE);
+        let mut deploy_processed = SseData::random_deploy_processed(&mut rng);
+        if let SseData::DeployProcessed {
+            execution_result, ..
+        } = &mut deploy_processed
+        {
+            match &mut **execution_result {
+                ExecutionResult::Success { effect, .. }
+                | ExecutionResult::Failure { effect, .. } => {
+                    effect.transforms = vec![TransformEntry {
+                        key: Key::Balance(main_purse.addr()).to_formatted_string(),
+                        transform: Transform::AddUInt512(U512::one()),
+                    }]
+                }
+            }
+        }
+
+        let watched_accounts = WatchedAccounts::default();
+        let keys = iter::once(Key::Account(account_hash).to_formatted_string()).collect();
+        let watched_keys = WatchedKeys::new(keys, watched_accounts.clone());
+        assert_eq!(watched_accounts.unresolved(), vec![account_hash]);
+        assert!(!watched_keys.matches(&deploy_processed));
+
+        watched_accounts.resolve(account_hash, main_purse);
+        assert!(watched_accounts.unresolved().is_empty());
+        assert!(watched_keys.matches(&deploy_processed));
+    }
+
+    #[test]
+    fn should_parse_watched_keys() {
+        let account_key =
+            "account-hash-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb";
+        let mut query = HashMap::new();
+        let _ = query.insert(KEYS_QUERY_FIELD.to_string(), account_key.to_string());
+        let _ = query.insert(QUERY_FIELD.to_string(), "1".to_string());
+        let watched_keys = parse_watched_keys(&mut query).unwrap().unwrap();
+        assert!(watched_keys.contains(account_key));
+        assert_eq!(parse_query(query).unwrap(), Some(1));
+
+        let mut query = HashMap::new();
+        let _ = query.insert(KEYS_QUERY_FIELD.to_string(), "not-a-key".to_string());
+        assert!(parse_watched_keys(&mut query).is_err());
+
+        let mut query = HashMap::new();
+        assert!(parse_watched_keys(&mut query).unwrap().is_none());
+    }
+
     async fn should_filter_duplicate_events(path_filter: &str) {
         // Returns `count` random SSE events, all of a single variant defined by `path_filter`.  The
         // events will have sequential IDs starting from `start_id`, and if the path filter
@@ -856,6 +1301,7 @@ mod tests {
                 initial_events_receiver,
                 ongoing_events_receiver,
                 get_filter(path_filter).unwrap(),
+                None,
                 "127.0.0.1:3456".to_string(),
             )
             .collect()
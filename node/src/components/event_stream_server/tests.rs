@@ -28,7 +28,7 @@ use crate::{logging, testing::assert_schema};
 use sse_server::{
     DeployAccepted, Id, QUERY_FIELD, SSE_API_DEPLOYS_PATH as DEPLOYS_PATH,
     SSE_API_MAIN_PATH as MAIN_PATH, SSE_API_ROOT_PATH as ROOT_PATH,
-    SSE_API_SIGNATURES_PATH as SIGS_PATH,
+    SSE_API_SIGNATURES_PATH as SIGS_PATH, SSE_API_STATE_PATH as STATE_PATH,
 };
 
 /// The total number of random events each `EventStreamServer` will emit by default, excluding the
@@ -899,8 +899,8 @@ async fn should_handle_bad_url_path() {
     ];
 
     let expected_body = format!(
-        "invalid path: expected '/{0}/{1}', '/{0}/{2}' or '/{0}/{3}'",
-        ROOT_PATH, MAIN_PATH, DEPLOYS_PATH, SIGS_PATH
+        "invalid path: expected '/{0}/{1}', '/{0}/{2}', '/{0}/{3}' or '/{0}/{4}'",
+        ROOT_PATH, MAIN_PATH, DEPLOYS_PATH, SIGS_PATH, STATE_PATH
     );
     for url in &urls {
         let response = reqwest::get(url).await.unwrap();
@@ -1174,6 +1174,39 @@ async fn should_limit_concurrent_subscribers() {
     fixture.stop_server().await;
 }
 
+#[test]
+fn should_validate_endpoints() {
+    let endpoint = |name: &str| EndpointConfig {
+        name: name.to_string(),
+        event_kinds: vec![EventFilter::FinalitySignature, EventFilter::BlockAdded],
+        event_stream_buffer_length: 100,
+        max_concurrent_subscribers: 10,
+    };
+
+    assert!(validate_endpoints(&[endpoint("finality"), endpoint("blocks_2")]).is_ok());
+
+    let invalid = [
+        vec![endpoint("")],
+        vec![endpoint("a/b")],
+        vec![endpoint(MAIN_PATH)],
+        vec![endpoint("finality"), endpoint("finality")],
+        vec![EndpointConfig {
+            event_kinds: vec![],
+            ..endpoint("finality")
+        }],
+    ];
+    for endpoints in invalid.iter() {
+        assert!(
+            matches!(
+                validate_endpoints(endpoints),
+                Err(ListeningError::InvalidEndpoint { .. })
+            ),
+            "{:?} should be invalid",
+            endpoints
+        );
+    }
+}
+
 /// Rather than being a test proper, this is more a means to easily determine differences between
 /// versions of the events emitted by the SSE server by comparing the contents of
 /// `resources/test/sse_data_schema.json` across different versions of the codebase.
@@ -0,0 +1,462 @@
+//! WebSocket endpoint of the event stream server, filtering events server-side.
+//!
+//! Rather than receiving every event of an SSE stream and filtering them itself, a client connects
+//! to `/events/ws` and sends a [`Subscription`] as its first text message.  Only events matching
+//! the subscription are then sent to it, each as a text message holding a JSON object with the
+//! event's `id` and `data`, the latter in the same form as the data of the equivalent SSE.  Further
+//! subscriptions sent by the client replace the current one.
+//!
+//! The endpoint is served from the same buffer as the built-in SSE endpoints, so clients can resume
+//! from an event ID via the subscription's `start_from` field.
+
+use std::{collections::HashSet, net::SocketAddr, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, error, info};
+use warp::{
+    addr,
+    filters::BoxedFilter,
+    path,
+    reply::Response,
+    ws::{Message, WebSocket, Ws},
+    Filter, Reply,
+};
+
+use casper_types::{EraId, PublicKey};
+
+use super::sse_server::{
+    self, BroadcastChannelMessage, DeployAccepted, EventFilter, Id, NewSubscriberInfo, SseData,
+    SSE_API_ROOT_PATH,
+};
+use crate::types::DeployHash;
+
+/// The URL path part of the WebSocket endpoint.
+pub const WS_API_PATH: &str = "ws";
+/// The maximum number of values of each kind a subscription can filter on.
+pub const MAX_FILTER_VALUES: usize = 100;
+/// How long a client has to send its subscription after connecting.
+const SUBSCRIPTION_TIMEOUT: Duration = Duration::from_secs(10);
+/// The close code sent to clients sending an invalid subscription, i.e. "policy violation".
+const INVALID_SUBSCRIPTION_CLOSE_CODE: u16 = 1008;
+
+/// The filter sent by a client to subscribe to events.
+///
+/// Each non-empty list restricts the events sent to those matching at least one of its values;
+/// events which don't carry the kind of value filtered on don't match.  The `ApiVersion` and
+/// `Shutdown` events are always sent.
+#[derive(Clone, Default, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Subscription {
+    /// The event types to send.
+    #[serde(default)]
+    pub event_types: Vec<EventFilter>,
+    /// The deploy hashes events should relate to.
+    #[serde(default)]
+    pub deploy_hashes: Vec<DeployHash>,
+    /// The public keys of the accounts or validators events should relate to.
+    #[serde(default)]
+    pub accounts: Vec<PublicKey>,
+    /// The eras events should relate to.
+    #[serde(default)]
+    pub era_ids: Vec<EraId>,
+    /// The ID of the buffered event from which to start sending events.  Only used by the first
+    /// subscription of a connection.
+    #[serde(default)]
+    pub start_from: Option<Id>,
+}
+
+/// A subscription, prepared for matching events against.
+#[derive(Debug)]
+struct SubscriptionFilter {
+    event_types: Vec<EventFilter>,
+    deploy_hashes: HashSet<DeployHash>,
+    accounts: HashSet<PublicKey>,
+    era_ids: HashSet<EraId>,
+}
+
+impl SubscriptionFilter {
+    /// Validates the subscription and prepares it for matching.
+    fn new(subscription: Subscription) -> Result<Self, String> {
+        if subscription.event_types.len() > MAX_FILTER_VALUES
+            || subscription.deploy_hashes.len() > MAX_FILTER_VALUES
+            || subscription.accounts.len() > MAX_FILTER_VALUES
+            || subscription.era_ids.len() > MAX_FILTER_VALUES
+        {
+            return Err(format!(
+                "subscription must not filter on more than {} values of each kind",
+                MAX_FILTER_VALUES
+            ));
+        }
+        Ok(SubscriptionFilter {
+            event_types: subscription.event_types,
+            deploy_hashes: subscription.deploy_hashes.into_iter().collect(),
+            accounts: subscription.accounts.into_iter().collect(),
+            era_ids: subscription.era_ids.into_iter().collect(),
+        })
+    }
+
+    /// Returns `true` if the event should be sent to the subscribed client.
+    fn matches(&self, data: &SseData) -> bool {
+        if matches!(data, SseData::ApiVersion(_) | SseData::Shutdown) {
+            return true;
+        }
+        if !self.event_types.is_empty() && !data.should_include(&self.event_types) {
+            return false;
+        }
+        if !self.deploy_hashes.is_empty()
+            && !deploy_hashes(data)
+                .iter()
+                .any(|deploy_hash| self.deploy_hashes.contains(deploy_hash))
+        {
+            return false;
+        }
+        if !self.accounts.is_empty()
+            && !accounts(data)
+                .iter()
+                .any(|account| self.accounts.contains(account))
+        {
+            return false;
+        }
+        if !self.era_ids.is_empty() && !era_id(data).map_or(false, |id| self.era_ids.contains(&id))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Returns the hashes of the deploys the event relates to.
+fn deploy_hashes(data: &SseData) -> Vec<&DeployHash> {
+    match data {
+        SseData::BlockAdded { block, .. } => block
+            .body
+            .deploy_hashes()
+            .iter()
+            .chain(block.body.transfer_hashes())
+            .collect(),
+        SseData::DeployAccepted { deploy } => vec![deploy.id()],
+        SseData::DeployProcessed { deploy_hash, .. } => vec![&**deploy_hash],
+        SseData::DeployExpired { deploy_hash } => vec![deploy_hash],
+        SseData::ApiVersion(_)
+        | SseData::Fault { .. }
+        | SseData::FinalitySignature(_)
+        | SseData::Finalized { .. }
+        | SseData::Step { .. }
+        | SseData::SyncProgress(_)
+        | SseData::FinalityAlert(_)
+        | SseData::Shutdown => vec![],
+    }
+}
+
+/// Returns the public keys of the accounts or validators the event relates to.
+fn accounts(data: &SseData) -> Vec<&PublicKey> {
+    match data {
+        SseData::BlockAdded { block, .. } => vec![block.body.proposer()],
+        SseData::DeployAccepted { deploy } => vec![deploy.header().account()],
+        SseData::DeployProcessed { account, .. } => vec![&**account],
+        SseData::Fault { public_key, .. } => vec![public_key],
+        SseData::FinalitySignature(signature) => vec![&signature.public_key],
+        SseData::ApiVersion(_)
+        | SseData::DeployExpired { .. }
+        | SseData::Finalized { .. }
+        | SseData::Step { .. }
+        | SseData::SyncProgress(_)
+        | SseData::FinalityAlert(_)
+        | SseData::Shutdown => vec![],
+    }
+}
+
+/// Returns the era the event relates to, if any.
+fn era_id(data: &SseData) -> Option<EraId> {
+    match data {
+        SseData::BlockAdded { block, .. } => Some(block.header.era_id),
+        SseData::Fault { era_id, .. } | SseData::Step { era_id, .. } => Some(*era_id),
+        SseData::FinalitySignature(signature) => Some(signature.era_id),
+        SseData::Finalized { era_id, .. } => Some(*era_id),
+        SseData::ApiVersion(_)
+        | SseData::DeployAccepted { .. }
+        | SseData::DeployProcessed { .. }
+        | SseData::DeployExpired { .. }
+        | SseData::SyncProgress(_)
+        | SseData::FinalityAlert(_)
+        | SseData::Shutdown => None,
+    }
+}
+
+/// Encodes an event as the JSON text sent to clients.
+fn encode_event(id: Option<Id>, data: &SseData) -> serde_json::Result<String> {
+    let data = match data {
+        SseData::DeployAccepted { deploy } => serde_json::to_value(&DeployAccepted {
+            deploy_accepted: deploy.clone(),
+        })?,
+        _ => serde_json::to_value(data)?,
+    };
+    serde_json::to_string(&json!({ "id": id, "data": data }))
+}
+
+/// Parses and validates a subscription sent by a client.
+fn parse_subscription(text: &str) -> Result<(SubscriptionFilter, Option<Id>), String> {
+    let subscription: Subscription = serde_json::from_str(text)
+        .map_err(|error| format!("failed to parse subscription: {}", error))?;
+    let start_from = subscription.start_from;
+    Ok((SubscriptionFilter::new(subscription)?, start_from))
+}
+
+/// Creates the warp filter for the WebSocket endpoint at `/events/ws`.
+///
+/// Subscribers count towards the same `max_concurrent_subscribers` limit as the SSE subscribers of
+/// `broadcaster`.
+pub(super) fn ws_filter(
+    broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: mpsc::UnboundedSender<NewSubscriberInfo>,
+    max_concurrent_subscribers: u32,
+) -> BoxedFilter<(Response,)> {
+    let serve = move |ws: Ws, maybe_remote_address: Option<SocketAddr>| {
+        let remote_address = match maybe_remote_address {
+            Some(address) => address.to_string(),
+            None => "unknown".to_string(),
+        };
+
+        if broadcaster.receiver_count() >= max_concurrent_subscribers as usize {
+            info!(
+                %remote_address,
+                %max_concurrent_subscribers,
+                "event stream server has max subscribers: rejecting new websocket client"
+            );
+            return sse_server::create_503();
+        }
+
+        let broadcaster = broadcaster.clone();
+        let new_subscriber_info_sender = new_subscriber_info_sender.clone();
+        ws.on_upgrade(move |socket| {
+            serve_client(
+                socket,
+                broadcaster,
+                new_subscriber_info_sender,
+                remote_address,
+            )
+        })
+        .into_response()
+    };
+
+    warp::get()
+        .and(path(SSE_API_ROOT_PATH))
+        .and(path(WS_API_PATH))
+        .and(path::end())
+        .and(warp::ws())
+        .and(addr::remote())
+        .map(serve)
+        .boxed()
+}
+
+/// Closes the connection to a client which sent an invalid subscription, explaining why.
+async fn reject_subscription(mut socket: WebSocket, reason: String, remote_address: &str) {
+    debug!(%remote_address, %reason, "rejecting websocket subscription");
+    let _ = socket
+        .send(Message::text(json!({ "error": reason }).to_string()))
+        .await;
+    let _ = socket
+        .send(Message::close_with(
+            INVALID_SUBSCRIPTION_CLOSE_CODE,
+            "invalid subscription",
+        ))
+        .await;
+}
+
+/// Waits for the client's subscription, then sends it the matching events until either side
+/// closes the connection.
+async fn serve_client(
+    mut socket: WebSocket,
+    broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: mpsc::UnboundedSender<NewSubscriberInfo>,
+    remote_address: String,
+) {
+    let subscription = match tokio::time::timeout(SUBSCRIPTION_TIMEOUT, socket.next()).await {
+        Ok(Some(Ok(message))) => match message.to_str() {
+            Ok(text) => parse_subscription(text),
+            Err(()) => Err("expected a subscription as text message".to_string()),
+        },
+        Ok(Some(Err(_))) | Ok(None) => return,
+        Err(_) => Err("timed out waiting for subscription".to_string()),
+    };
+    let (mut filter, start_from) = match subscription {
+        Ok(subscription) => subscription,
+        Err(reason) => return reject_subscription(socket, reason, &remote_address).await,
+    };
+
+    // Subscribe to the ongoing events before requesting the buffered ones, so that no event falls
+    // between the two.
+    let ongoing_events_receiver = broadcaster.subscribe();
+    let (initial_events_sender, initial_events_receiver) = mpsc::unbounded_channel();
+    let new_subscriber_info = NewSubscriberInfo {
+        start_from,
+        initial_events_sender,
+    };
+    if new_subscriber_info_sender
+        .send(new_subscriber_info)
+        .is_err()
+    {
+        error!("failed to send new subscriber info");
+    }
+
+    let events = sse_server::event_stream(
+        initial_events_receiver,
+        ongoing_events_receiver,
+        remote_address.clone(),
+    );
+    futures::pin_mut!(events);
+    let (mut sink, mut stream) = socket.split();
+
+    loop {
+        tokio::select! {
+            maybe_event = events.next() => {
+                let event = match maybe_event {
+                    Some(Ok(event)) => event,
+                    // The client lagged or the server is shutting down.
+                    Some(Err(_)) | None => break,
+                };
+                if !filter.matches(&event.data) {
+                    continue;
+                }
+                let text = match encode_event(event.id, &event.data) {
+                    Ok(text) => text,
+                    Err(error) => {
+                        error!(%error, ?event, "failed to jsonify websocket event");
+                        continue;
+                    }
+                };
+                if sink.send(Message::text(text)).await.is_err() {
+                    break;
+                }
+            }
+
+            maybe_message = stream.next() => {
+                let message = match maybe_message {
+                    Some(Ok(message)) => message,
+                    Some(Err(_)) | None => break,
+                };
+                if message.is_close() {
+                    break;
+                }
+                // Pings are answered by the WebSocket implementation itself.
+                if let Ok(text) = message.to_str() {
+                    match parse_subscription(text) {
+                        Ok((new_filter, _)) => filter = new_filter,
+                        Err(reason) => {
+                            if let Ok(socket) = sink.reunite(stream) {
+                                reject_subscription(socket, reason, &remote_address).await;
+                            }
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = sink.close().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    fn filter(subscription: Subscription) -> SubscriptionFilter {
+        SubscriptionFilter::new(subscription).expect("valid subscription")
+    }
+
+    #[test]
+    fn empty_subscription_should_match_all_events() {
+        let mut rng = TestRng::new();
+        let filter = filter(Subscription::default());
+        assert!(filter.matches(&SseData::random_block_added(&mut rng)));
+        assert!(filter.matches(&SseData::random_deploy_accepted(&mut rng).0));
+        assert!(filter.matches(&SseData::random_deploy_processed(&mut rng)));
+        assert!(filter.matches(&SseData::random_finality_signature(&mut rng)));
+        assert!(filter.matches(&SseData::random_step(&mut rng)));
+    }
+
+    #[test]
+    fn should_filter_by_event_type() {
+        let mut rng = TestRng::new();
+        let filter = filter(Subscription {
+            event_types: vec![EventFilter::DeployAccepted],
+            ..Default::default()
+        });
+        assert!(filter.matches(&SseData::random_deploy_accepted(&mut rng).0));
+        assert!(!filter.matches(&SseData::random_block_added(&mut rng)));
+        assert!(filter.matches(&SseData::random_api_version(&mut rng)));
+        assert!(filter.matches(&SseData::Shutdown));
+    }
+
+    #[test]
+    fn should_filter_by_deploy_hash_and_account() {
+        let mut rng = TestRng::new();
+        let (deploy_accepted, deploy) = SseData::random_deploy_accepted(&mut rng);
+        let (other_deploy_accepted, _) = SseData::random_deploy_accepted(&mut rng);
+
+        let by_hash = filter(Subscription {
+            deploy_hashes: vec![*deploy.id()],
+            ..Default::default()
+        });
+        assert!(by_hash.matches(&deploy_accepted));
+        assert!(!by_hash.matches(&other_deploy_accepted));
+        assert!(!by_hash.matches(&SseData::random_step(&mut rng)));
+
+        let by_account = filter(Subscription {
+            accounts: vec![deploy.header().account().clone()],
+            ..Default::default()
+        });
+        assert!(by_account.matches(&deploy_accepted));
+        assert!(!by_account.matches(&other_deploy_accepted));
+    }
+
+    #[test]
+    fn should_filter_by_era_id() {
+        let mut rng = TestRng::new();
+        let step = SseData::random_step(&mut rng);
+        let era_id = match &step {
+            SseData::Step { era_id, .. } => *era_id,
+            _ => unreachable!(),
+        };
+
+        let matching = filter(Subscription {
+            era_ids: vec![era_id],
+            ..Default::default()
+        });
+        assert!(matching.matches(&step));
+        assert!(!matching.matches(&SseData::random_deploy_processed(&mut rng)));
+
+        let other = filter(Subscription {
+            era_ids: vec![era_id.successor()],
+            ..Default::default()
+        });
+        assert!(!other.matches(&step));
+    }
+
+    #[test]
+    fn should_parse_subscription() {
+        let (filter, start_from) = parse_subscription(
+            r#"{"event_types": ["deploy_processed", "block_added"], "era_ids": [3], "start_from": 12}"#,
+        )
+        .expect("valid subscription");
+        assert_eq!(
+            filter.event_types,
+            vec![EventFilter::DeployProcessed, EventFilter::BlockAdded]
+        );
+        assert!(filter.era_ids.contains(&EraId::from(3)));
+        assert_eq!(start_from, Some(12));
+
+        assert!(parse_subscription(r#"{"unknown": []}"#).is_err());
+        assert!(parse_subscription("not json").is_err());
+
+        let too_many_eras: Vec<u64> = (0..=MAX_FILTER_VALUES as u64).collect();
+        let text = json!({ "era_ids": too_many_eras }).to_string();
+        assert!(parse_subscription(&text).is_err());
+    }
+}
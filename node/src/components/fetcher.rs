@@ -26,7 +26,7 @@ use crate::{
     protocol::Message,
     types::{
         Block, BlockAndDeploys, BlockHash, BlockHeader, BlockHeaderWithMetadata, BlockHeadersBatch,
-        BlockHeadersBatchId, BlockSignatures, BlockWithMetadata, Deploy, DeployHash,
+        BlockHeadersBatchId, BlockHeight, BlockSignatures, BlockWithMetadata, Deploy, DeployHash,
         DeployWithFinalizedApprovals, FinalizedApprovals, FinalizedApprovalsWithId, Item, NodeId,
     },
     utils::Source,
@@ -479,7 +479,7 @@ impl ItemFetcher<BlockWithMetadata> for Fetcher<BlockWithMetadata> {
         let fault_tolerance_fraction = self.fault_tolerance_fraction;
         async move {
             let block_with_metadata = effect_builder
-                .get_block_with_metadata_from_storage_by_height(id, false)
+                .get_block_with_metadata_from_storage_by_height(BlockHeight::new(id), false)
                 .await?;
             has_enough_block_signatures(
                 effect_builder,
@@ -526,7 +526,7 @@ impl ItemFetcher<BlockHeaderWithMetadata> for Fetcher<BlockHeaderWithMetadata> {
         let fault_tolerance_fraction = self.fault_tolerance_fraction;
         async move {
             let block_header_with_metadata = effect_builder
-                .get_block_header_with_metadata_from_storage_by_height(id, false)
+                .get_block_header_with_metadata_from_storage_by_height(BlockHeight::new(id), false)
                 .await?;
             has_enough_block_signatures(
                 effect_builder,
@@ -769,7 +769,16 @@ where
                 self.signal(id, Err(FetcherError::Absent { id, peer }), peer)
             }
             Event::TimeoutPeer { id, peer } => {
-                self.signal(id, Err(FetcherError::TimedOut { id, peer }), peer)
+                // Only a request still awaiting a response counts against the peer's reputation.
+                let is_pending = self
+                    .responders()
+                    .get(&id)
+                    .map_or(false, |responders| responders.contains_key(&peer));
+                let mut effects = self.signal(id, Err(FetcherError::TimedOut { id, peer }), peer);
+                if is_pending {
+                    effects.extend(effect_builder.announce_slow_response(peer).ignore());
+                }
+                effects
             }
         }
     }
@@ -5,11 +5,16 @@ use serde::{Deserialize, Serialize};
 
 use casper_types::TimeDiff;
 
+use crate::utils::deserialize_nonzero_time_diff;
+
 const DEFAULT_GET_FROM_PEER_TIMEOUT: &str = "3sec";
 
 /// Configuration options for fetching.
 #[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
 pub struct Config {
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
     get_from_peer_timeout: TimeDiff,
 }
 
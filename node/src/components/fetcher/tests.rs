@@ -118,6 +118,8 @@ reactor!(Reactor {
         // reactor, outside of routing.
         NetRequestIncoming -> [storage];
         NetResponseIncoming -> [fn handle_net_response];
+        // Deploy responses are never subject to flow control on the in-memory network.
+        DeployResponseIncoming -> [!];
 
         // There is no deploy gossiping going on.
         GossiperIncoming<Deploy> -> [!];
@@ -132,7 +134,9 @@ reactor!(Reactor {
         // No consensus component.
         ConsensusMessageIncoming -> [!];
         FinalitySignatureIncoming -> [!];
-        BlocklistAnnouncement -> [!];
+        FinalitySignaturesIncoming -> [!];
+        // Peers timing out on requests are reported, but there is no network to act on it.
+        BlocklistAnnouncement -> [#];
     }
 });
 
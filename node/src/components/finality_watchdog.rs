@@ -0,0 +1,429 @@
+//! Finality watchdog component.
+//!
+//! The finality watchdog periodically checks when the node last saw a new block being added to
+//! the linear chain.  If that was longer ago than the configured maximum, it looks at the node's
+//! peers to tell whether the node itself is cut off from the network, or the network as a whole
+//! has stopped finalizing blocks.  Every change of that status is reported via the
+//! `finality_status` metric, an announcement which is forwarded to event stream clients and, if
+//! configured, a JSON `POST` request to a webhook URL.  Alerts are also reported as incidents to
+//! the configured incident reporters.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+use datasize::DataSize;
+use prometheus::{IntGauge, Registry};
+use reqwest::header::CONTENT_TYPE;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use casper_types::{TimeDiff, Timestamp};
+
+use super::Component;
+use crate::{
+    effect::{
+        announcements::FinalityWatchdogAnnouncement, requests::NetworkInfoRequest, EffectBuilder,
+        EffectExt, Effects,
+    },
+    incident::{self, Incident, IncidentKind},
+    reactor::supervisor,
+    types::NodeRng,
+    unregister_metric,
+    utils::deserialize_nonzero_time_diff,
+};
+
+/// Finality watchdog configuration.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Whether or not the finality watchdog is enabled.
+    pub(crate) enabled: bool,
+    /// How often the time since the last finalized block is checked.
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
+    pub(crate) check_interval: TimeDiff,
+    /// How long the node may go without a new finalized block before an alert is raised.
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
+    pub(crate) max_time_without_finality: TimeDiff,
+    /// The minimum number of connected, non-syncing peers for the node not to be considered
+    /// partitioned from the network.
+    pub(crate) min_peers: u64,
+    /// URL to send alerts to as JSON `POST` requests, if any.
+    pub(crate) webhook_url: Option<String>,
+    /// Timeout for requests to the webhook URL.
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
+    pub(crate) webhook_timeout: TimeDiff,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: true,
+            check_interval: TimeDiff::from_seconds(10),
+            max_time_without_finality: TimeDiff::from_seconds(300),
+            min_peers: 3,
+            webhook_url: None,
+            webhook_timeout: TimeDiff::from_seconds(10),
+        }
+    }
+}
+
+/// The node's view of whether the network is finalizing blocks.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DataSize, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalityStatus {
+    /// New blocks are being finalized.
+    Healthy,
+    /// No block has been finalized for too long, and the node is connected to too few non-syncing
+    /// peers, so it is likely cut off from the rest of the network.
+    Partitioned,
+    /// No block has been finalized for too long, although the node is connected to enough
+    /// non-syncing peers, so the network as a whole is likely not finalizing blocks.
+    NetworkStalled,
+}
+
+impl FinalityStatus {
+    /// Returns the value of the `finality_status` metric for this status.
+    fn metric_value(self) -> i64 {
+        match self {
+            FinalityStatus::Healthy => 0,
+            FinalityStatus::Partitioned => 1,
+            FinalityStatus::NetworkStalled => 2,
+        }
+    }
+}
+
+impl Display for FinalityStatus {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FinalityStatus::Healthy => write!(formatter, "healthy"),
+            FinalityStatus::Partitioned => write!(formatter, "partitioned"),
+            FinalityStatus::NetworkStalled => write!(formatter, "network stalled"),
+        }
+    }
+}
+
+/// A change of the node's finality status, raised by the finality watchdog.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, DataSize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FinalityAlert {
+    /// The new finality status.
+    pub status: FinalityStatus,
+    /// The height of the last block finalized since the node started, if any.
+    pub last_finalized_block_height: Option<u64>,
+    /// The time since the last finalized block, or since the node started if there was none.
+    pub time_since_last_finalized_block: TimeDiff,
+    /// The number of fully connected peers.
+    pub connected_peers: u64,
+    /// The number of fully connected peers which are not syncing.
+    pub non_syncing_peers: u64,
+}
+
+/// Finality watchdog event.
+#[derive(Debug, Serialize)]
+pub(crate) enum Event {
+    /// A block was added to the linear chain.
+    BlockAdded {
+        /// The height of the added block.
+        height: u64,
+    },
+    /// The timer for the next check has fired.
+    Check,
+    /// The peers were counted for a check which found no recent finalized block.
+    GotPeerCounts {
+        /// The number of fully connected peers.
+        connected_peers: u64,
+        /// The number of fully connected peers which are not syncing.
+        non_syncing_peers: u64,
+    },
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::BlockAdded { height } => write!(formatter, "block added at height {}", height),
+            Event::Check => write!(formatter, "check finality"),
+            Event::GotPeerCounts {
+                connected_peers,
+                non_syncing_peers,
+            } => write!(
+                formatter,
+                "got peer counts: {} connected, {} non-syncing",
+                connected_peers, non_syncing_peers
+            ),
+        }
+    }
+}
+
+/// Metrics for the finality watchdog.
+#[derive(Debug)]
+struct Metrics {
+    /// Seconds since the last finalized block.
+    time_since_last_finalized_block: IntGauge,
+    /// The current finality status: 0 if healthy, 1 if partitioned and 2 if the network stalled.
+    finality_status: IntGauge,
+    /// Prometheus registry used to publish metrics.
+    registry: Registry,
+}
+
+impl Metrics {
+    fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let time_since_last_finalized_block = IntGauge::new(
+            "time_since_last_finalized_block",
+            "time in seconds since the last block was finalized, or since the node started",
+        )?;
+        let finality_status = IntGauge::new(
+            "finality_status",
+            "finality status: 0 if healthy, 1 if the node is partitioned, 2 if the network stalled",
+        )?;
+        registry.register(Box::new(time_since_last_finalized_block.clone()))?;
+        registry.register(Box::new(finality_status.clone()))?;
+        Ok(Metrics {
+            time_since_last_finalized_block,
+            finality_status,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl Drop for Metrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.time_since_last_finalized_block);
+        unregister_metric!(self.registry, self.finality_status);
+    }
+}
+
+/// Finality watchdog component.
+#[derive(DataSize, Debug)]
+pub(crate) struct FinalityWatchdog {
+    config: Config,
+    /// The height of the last block added since the node started, if any.
+    last_finalized_block_height: Option<u64>,
+    /// When the last block was added, or when the node started.
+    last_finalized_block_time: Timestamp,
+    /// The status last reported.
+    status: FinalityStatus,
+    #[data_size(skip)]
+    metrics: Metrics,
+}
+
+impl FinalityWatchdog {
+    /// Creates a new finality watchdog, returning the effect scheduling its first check.
+    pub(crate) fn new<REv: Send>(
+        config: Config,
+        effect_builder: EffectBuilder<REv>,
+        registry: &Registry,
+    ) -> Result<(Self, Effects<Event>), prometheus::Error> {
+        let metrics = Metrics::new(registry)?;
+        let effects = if config.enabled {
+            schedule_check(effect_builder, config.check_interval)
+        } else {
+            Effects::new()
+        };
+        let watchdog = FinalityWatchdog {
+            config,
+            last_finalized_block_height: None,
+            last_finalized_block_time: Timestamp::now(),
+            status: FinalityStatus::Healthy,
+            metrics,
+        };
+        Ok((watchdog, effects))
+    }
+
+    /// Returns the alert for the given status and peer counts.
+    fn alert(
+        &self,
+        status: FinalityStatus,
+        connected_peers: u64,
+        non_syncing_peers: u64,
+    ) -> FinalityAlert {
+        FinalityAlert {
+            status,
+            last_finalized_block_height: self.last_finalized_block_height,
+            time_since_last_finalized_block: self.last_finalized_block_time.elapsed(),
+            connected_peers,
+            non_syncing_peers,
+        }
+    }
+
+    /// Records the new status and, if it changed, raises an alert.
+    fn update_status<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        alert: FinalityAlert,
+    ) -> Effects<Event>
+    where
+        REv: From<FinalityWatchdogAnnouncement> + Send,
+    {
+        if alert.status == self.status {
+            return Effects::new();
+        }
+        self.status = alert.status;
+        self.metrics
+            .finality_status
+            .set(alert.status.metric_value());
+        match alert.status {
+            FinalityStatus::Healthy => info!(
+                last_finalized_block_height = ?alert.last_finalized_block_height,
+                "finality restored"
+            ),
+            FinalityStatus::Partitioned | FinalityStatus::NetworkStalled => {
+                warn!(
+                    status = %alert.status,
+                    last_finalized_block_height = ?alert.last_finalized_block_height,
+                    time_since_last_finalized_block = %alert.time_since_last_finalized_block,
+                    connected_peers = alert.connected_peers,
+                    non_syncing_peers = alert.non_syncing_peers,
+                    "no block finalized for too long"
+                );
+                let incident = Incident::new(
+                    IncidentKind::FinalityAlert,
+                    format!(
+                        "no block finalized for {}, {}",
+                        alert.time_since_last_finalized_block, alert.status
+                    ),
+                    supervisor::incident_context(),
+                )
+                .with_component("FinalityWatchdog")
+                .with_details(&alert);
+                incident::report(incident);
+            }
+        }
+
+        let mut effects = Effects::new();
+        if let Some(url) = self.config.webhook_url.clone() {
+            effects.extend(
+                call_webhook(url, self.config.webhook_timeout.into(), alert.clone()).ignore(),
+            );
+        }
+        effects.extend(effect_builder.announce_finality_alert(alert).ignore());
+        effects
+    }
+}
+
+impl<REv> Component<REv> for FinalityWatchdog
+where
+    REv: From<NetworkInfoRequest> + From<FinalityWatchdogAnnouncement> + Send,
+{
+    type Event = Event;
+    type ConstructionError = prometheus::Error;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        if !self.config.enabled {
+            return Effects::new();
+        }
+        match event {
+            Event::BlockAdded { height } => {
+                self.last_finalized_block_height = Some(height);
+                self.last_finalized_block_time = Timestamp::now();
+                self.metrics.time_since_last_finalized_block.set(0);
+                let alert = self.alert(FinalityStatus::Healthy, 0, 0);
+                self.update_status(effect_builder, alert)
+            }
+            Event::Check => {
+                let elapsed = self.last_finalized_block_time.elapsed();
+                self.metrics
+                    .time_since_last_finalized_block
+                    .set((elapsed.millis() / 1000) as i64);
+                let mut effects = schedule_check(effect_builder, self.config.check_interval);
+                if elapsed > self.config.max_time_without_finality {
+                    effects.extend(
+                        async move {
+                            let connected_peers =
+                                effect_builder.get_fully_connected_peers().await.len();
+                            let non_syncing_peers = effect_builder
+                                .get_fully_connected_non_syncing_peers()
+                                .await
+                                .len();
+                            (connected_peers as u64, non_syncing_peers as u64)
+                        }
+                        .event(|(connected_peers, non_syncing_peers)| {
+                            Event::GotPeerCounts {
+                                connected_peers,
+                                non_syncing_peers,
+                            }
+                        }),
+                    );
+                }
+                effects
+            }
+            Event::GotPeerCounts {
+                connected_peers,
+                non_syncing_peers,
+            } => {
+                // A block might have been added while the peers were being counted.
+                if self.last_finalized_block_time.elapsed() <= self.config.max_time_without_finality
+                {
+                    return Effects::new();
+                }
+                let status = classify(non_syncing_peers, self.config.min_peers);
+                let alert = self.alert(status, connected_peers, non_syncing_peers);
+                self.update_status(effect_builder, alert)
+            }
+        }
+    }
+}
+
+/// Returns the status of a node which has not seen a finalized block for too long.
+///
+/// With fewer than `min_peers` connected non-syncing peers, the node most likely lost contact with
+/// the network.  Otherwise its peers are not finalizing blocks either.
+fn classify(non_syncing_peers: u64, min_peers: u64) -> FinalityStatus {
+    if non_syncing_peers < min_peers {
+        FinalityStatus::Partitioned
+    } else {
+        FinalityStatus::NetworkStalled
+    }
+}
+
+fn schedule_check<REv: Send>(
+    effect_builder: EffectBuilder<REv>,
+    check_interval: TimeDiff,
+) -> Effects<Event> {
+    effect_builder
+        .set_timeout(check_interval.into())
+        .event(|_| Event::Check)
+}
+
+/// Sends the alert as JSON to the webhook URL, logging any failure.
+async fn call_webhook(url: String, timeout: Duration, alert: FinalityAlert) {
+    let body = match serde_json::to_vec(&alert) {
+        Ok(body) => body,
+        Err(error) => {
+            warn!(%error, "failed to serialize finality alert");
+            return;
+        }
+    };
+    let result = reqwest::Client::new()
+        .post(&url)
+        .header(CONTENT_TYPE, "application/json")
+        .timeout(timeout)
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    if let Err(error) = result {
+        warn!(%error, %url, "failed to send finality alert to webhook");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_tell_partition_from_stalled_network() {
+        assert_eq!(classify(0, 3), FinalityStatus::Partitioned);
+        assert_eq!(classify(2, 3), FinalityStatus::Partitioned);
+        assert_eq!(classify(3, 3), FinalityStatus::NetworkStalled);
+        assert_eq!(classify(0, 0), FinalityStatus::NetworkStalled);
+    }
+}
@@ -9,20 +9,23 @@ mod tests;
 
 use datasize::DataSize;
 use prometheus::Registry;
+use serde::Serialize;
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     convert::Infallible,
     fmt::{self, Debug, Formatter},
     time::Duration,
 };
 use tracing::{debug, error, warn};
 
+use casper_hashing::Digest;
+
 use crate::{
     components::{fetcher::FetchedOrNotFound, Component},
     effect::{
-        announcements::GossiperAnnouncement,
+        announcements::{BlocklistAnnouncement, GossiperAnnouncement},
         incoming::GossiperIncoming,
-        requests::{BeginGossipRequest, NetworkRequest, StorageRequest},
+        requests::{BeginGossipRequest, NetworkRequest, StateStoreRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
     },
     protocol::Message as NodeMessage,
@@ -36,6 +39,13 @@ use gossip_table::{GossipAction, GossipTable};
 pub(crate) use message::Message;
 use metrics::Metrics;
 
+/// Interval after which the recently gossiped item IDs are persisted to storage, if they changed
+/// since the last snapshot.
+const PERSIST_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The maximum number of item IDs sent in response to an anti-entropy digest.
+const MAX_ANTI_ENTROPY_ITEMS: usize = 4096;
+
 /// A helper trait whose bounds represent the requirements for a reactor event that `Gossiper` can
 /// work with.
 pub(crate) trait ReactorEventT<T>:
@@ -43,7 +53,9 @@ pub(crate) trait ReactorEventT<T>:
     + From<NetworkRequest<Message<T>>>
     + From<NetworkRequest<NodeMessage>>
     + From<StorageRequest>
+    + From<StateStoreRequest>
     + From<GossiperAnnouncement<T>>
+    + From<BlocklistAnnouncement>
     + Send
     + 'static
 where
@@ -60,7 +72,9 @@ where
         + From<NetworkRequest<Message<T>>>
         + From<NetworkRequest<NodeMessage>>
         + From<StorageRequest>
+        + From<StateStoreRequest>
         + From<GossiperAnnouncement<T>>
+        + From<BlocklistAnnouncement>
         + Send
         + 'static,
 {
@@ -93,6 +107,16 @@ pub(crate) fn get_deploy_from_storage<T: Item + 'static, REv: ReactorEventT<T>>(
         })
 }
 
+/// Returns a digest of the given item IDs, independent of their order.
+fn recent_items_digest<Id: Serialize>(item_ids: &[Id]) -> Digest {
+    let mut serialized_ids: Vec<Vec<u8>> = item_ids
+        .iter()
+        .filter_map(|item_id| bincode::serialize(item_id).ok())
+        .collect();
+    serialized_ids.sort_unstable();
+    Digest::hash(serialized_ids.concat())
+}
+
 /// The component which gossips to peers and handles incoming gossip messages from peers.
 #[allow(clippy::type_complexity)]
 #[derive(DataSize)]
@@ -104,6 +128,18 @@ where
     table: GossipTable<T::Id>,
     gossip_timeout: Duration,
     get_from_peer_timeout: Duration,
+    max_batch_size: usize,
+    batch_interval: Duration,
+    /// Item IDs waiting to be gossiped in a batch, keyed by the number of peers to gossip them to.
+    /// Each item ID is paired with the peers to exclude when gossiping it.
+    pending_batches: BTreeMap<usize, Vec<(T::Id, HashSet<NodeId>)>>,
+    /// Whether a timer to flush the pending batches is currently running.
+    is_flush_scheduled: bool,
+    anti_entropy_interval: Duration,
+    /// The key under which the recently gossiped item IDs are persisted.
+    state_key: Vec<u8>,
+    /// The digest of the recently gossiped item IDs last persisted.
+    persisted_digest: Option<Digest>,
     #[data_size(skip)] // Not well supported by datasize.
     get_from_holder:
         Box<dyn Fn(EffectBuilder<REv>, T::Id, NodeId) -> Effects<Event<T>> + Send + 'static>,
@@ -123,26 +159,37 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
     /// `gossiper::get_deploy_from_store()` which is used by `Gossiper<Deploy>`.
     ///
     /// Must be supplied with a name, which should be a snake-case identifier to disambiguate the
-    /// specific gossiper from other potentially present gossipers.
+    /// specific gossiper from other potentially present gossipers.  The name is also used as the
+    /// key under which the recently gossiped item IDs are persisted across restarts.
     pub(crate) fn new_for_partial_items(
         name: &str,
         config: Config,
+        effect_builder: EffectBuilder<REv>,
         get_from_holder: impl Fn(EffectBuilder<REv>, T::Id, NodeId) -> Effects<Event<T>>
             + Send
             + 'static,
         registry: &Registry,
-    ) -> Result<Self, prometheus::Error> {
+    ) -> Result<(Self, Effects<Event<T>>), prometheus::Error> {
         assert!(
             !T::ID_IS_COMPLETE_ITEM,
             "this should only be called for types where T::ID_IS_COMPLETE_ITEM is false"
         );
-        Ok(Gossiper {
+        let gossiper = Gossiper {
             table: GossipTable::new(config),
             gossip_timeout: config.gossip_request_timeout().into(),
             get_from_peer_timeout: config.get_remainder_timeout().into(),
+            max_batch_size: config.max_batch_size() as usize,
+            batch_interval: config.batch_interval().into(),
+            pending_batches: BTreeMap::new(),
+            is_flush_scheduled: false,
+            anti_entropy_interval: config.anti_entropy_interval().into(),
+            state_key: format!("gossiper {}", name).into_bytes(),
+            persisted_digest: None,
             get_from_holder: Box::new(get_from_holder),
             metrics: Metrics::new(name, registry)?,
-        })
+        };
+        let effects = gossiper.start(effect_builder);
+        Ok((gossiper, effects))
     }
 
     /// Constructs a new gossiper component for use where `T::ID_IS_COMPLETE_ITEM == true`, i.e.
@@ -153,21 +200,62 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
     pub(crate) fn new_for_complete_items(
         name: &str,
         config: Config,
+        effect_builder: EffectBuilder<REv>,
         registry: &Registry,
-    ) -> Result<Self, prometheus::Error> {
+    ) -> Result<(Self, Effects<Event<T>>), prometheus::Error> {
         assert!(
             T::ID_IS_COMPLETE_ITEM,
             "this should only be called for types where T::ID_IS_COMPLETE_ITEM is true"
         );
-        Ok(Gossiper {
+        let gossiper = Gossiper {
             table: GossipTable::new(config),
             gossip_timeout: config.gossip_request_timeout().into(),
             get_from_peer_timeout: config.get_remainder_timeout().into(),
+            max_batch_size: config.max_batch_size() as usize,
+            batch_interval: config.batch_interval().into(),
+            pending_batches: BTreeMap::new(),
+            is_flush_scheduled: false,
+            anti_entropy_interval: config.anti_entropy_interval().into(),
+            state_key: format!("gossiper {}", name).into_bytes(),
+            persisted_digest: None,
             get_from_holder: Box::new(|_, item, _| {
                 panic!("gossiper should never try to get {}", item)
             }),
             metrics: Metrics::new(name, registry)?,
-        })
+        };
+        let effects = gossiper.start(effect_builder);
+        Ok((gossiper, effects))
+    }
+
+    /// Applies the gossip timeouts and batch interval of `config`.  Gossip already in flight keeps
+    /// the previous values.
+    pub(crate) fn update_timeouts(&mut self, config: &Config) {
+        self.gossip_timeout = config.gossip_request_timeout().into();
+        self.get_from_peer_timeout = config.get_remainder_timeout().into();
+        self.batch_interval = config.batch_interval().into();
+    }
+
+    /// Returns the effects loading the recently gossiped item IDs persisted before a restart and
+    /// starting the timers for persisting them and, unless disabled, for anti-entropy rounds.
+    fn start(&self, effect_builder: EffectBuilder<REv>) -> Effects<Event<T>> {
+        let mut effects = effect_builder
+            .load_state::<Vec<T::Id>>(self.state_key.clone().into())
+            .event(|maybe_item_ids| Event::Loaded {
+                item_ids: maybe_item_ids.unwrap_or_default(),
+            });
+        effects.extend(
+            effect_builder
+                .set_timeout(PERSIST_INTERVAL)
+                .event(|_| Event::PersistState),
+        );
+        if !self.anti_entropy_interval.is_zero() {
+            effects.extend(
+                effect_builder
+                    .set_timeout(self.anti_entropy_interval)
+                    .event(|_| Event::AntiEntropyRound),
+            );
+        }
+        effects
     }
 
     /// Handles a new item received from a peer or client for which we should begin gossiping.
@@ -183,12 +271,17 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         match self.table.new_complete_data(&item_id, source.node_id()) {
             GossipAction::ShouldGossip(should_gossip) => {
                 self.metrics.items_received.inc();
-                self.gossip(
+                let mut effects = self.gossip(
                     effect_builder,
                     item_id,
                     should_gossip.count,
                     should_gossip.exclude_peers,
-                )
+                );
+                // Items are only received once accepted as new, so the sending peer was useful.
+                if let Source::Peer(peer) = source {
+                    effects.extend(effect_builder.announce_useful_gossip(peer).ignore());
+                }
+                effects
             }
             GossipAction::Noop => Effects::new(),
             GossipAction::AnnounceFinished => {
@@ -202,12 +295,89 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
     }
 
     /// Gossips the given item ID to `count` random peers excluding the indicated ones.
+    ///
+    /// Unless batching is disabled, the item ID is held back to be gossiped in a batch together
+    /// with other item IDs due to be gossiped to the same number of peers.
     fn gossip(
         &mut self,
         effect_builder: EffectBuilder<REv>,
         item_id: T::Id,
         count: usize,
         exclude_peers: HashSet<NodeId>,
+    ) -> Effects<Event<T>> {
+        if self.max_batch_size == 0 {
+            return self.gossip_single(effect_builder, item_id, count, exclude_peers);
+        }
+
+        let batch = self.pending_batches.entry(count).or_default();
+        batch.push((item_id, exclude_peers));
+        if batch.len() >= self.max_batch_size {
+            return self.flush_batch(effect_builder, count);
+        }
+
+        if self.is_flush_scheduled {
+            return Effects::new();
+        }
+        self.is_flush_scheduled = true;
+        effect_builder
+            .set_timeout(self.batch_interval)
+            .event(|_| Event::FlushGossipBatches)
+    }
+
+    /// Gossips all pending batches.
+    fn flush_batches(&mut self, effect_builder: EffectBuilder<REv>) -> Effects<Event<T>> {
+        self.is_flush_scheduled = false;
+        let counts: Vec<usize> = self.pending_batches.keys().copied().collect();
+        let mut effects = Effects::new();
+        for count in counts {
+            effects.extend(self.flush_batch(effect_builder, count));
+        }
+        effects
+    }
+
+    /// Gossips the pending batch of item IDs due to be gossiped to `count` peers.
+    ///
+    /// The batch is only sent to peers which support batched gossip, and only those which are not
+    /// to be excluded for any of the items in the batch.  Any shortfall is made up for once the
+    /// network component reports which peers it gossiped to.
+    fn flush_batch(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        count: usize,
+    ) -> Effects<Event<T>> {
+        let mut items = match self.pending_batches.remove(&count) {
+            Some(items) => items,
+            None => return Effects::new(),
+        };
+
+        // There's no point in batching a single item.
+        if items.len() == 1 {
+            let (item_id, exclude_peers) = items.pop().unwrap();
+            return self.gossip_single(effect_builder, item_id, count, exclude_peers);
+        }
+
+        let item_ids = items.iter().map(|(item_id, _)| *item_id).collect();
+        let exclude = items
+            .iter()
+            .flat_map(|(_, exclude_peers)| exclude_peers.iter().copied())
+            .collect();
+        effect_builder
+            .gossip_message(Message::GossipBatch(item_ids), count, exclude)
+            .event(move |peers| Event::BatchGossipedTo {
+                items,
+                requested_count: count,
+                peers,
+            })
+    }
+
+    /// Gossips the given item ID to `count` random peers excluding the indicated ones in a message
+    /// of its own.
+    fn gossip_single(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        item_id: T::Id,
+        count: usize,
+        exclude_peers: HashSet<NodeId>,
     ) -> Effects<Event<T>> {
         let message = Message::Gossip(item_id);
         effect_builder
@@ -257,6 +427,41 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         effects
     }
 
+    /// Handles the response from the network component detailing which peers it gossiped a batch
+    /// to.
+    ///
+    /// If the batch reached fewer peers than requested, e.g. since some peers don't support batched
+    /// gossip, the items are gossiped individually to make up for the shortfall.
+    fn batch_gossiped_to(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        items: Vec<(T::Id, HashSet<NodeId>)>,
+        requested_count: usize,
+        peers: HashSet<NodeId>,
+    ) -> Effects<Event<T>> {
+        let mut effects = Effects::new();
+        for (item_id, mut exclude_peers) in items {
+            if !peers.is_empty() {
+                effects.extend(self.gossiped_to(
+                    effect_builder,
+                    item_id,
+                    peers.len(),
+                    peers.clone(),
+                ));
+            }
+            if peers.len() < requested_count {
+                exclude_peers.extend(peers.iter().copied());
+                effects.extend(self.gossip_single(
+                    effect_builder,
+                    item_id,
+                    requested_count - peers.len(),
+                    exclude_peers,
+                ));
+            }
+        }
+        effects
+    }
+
     /// Checks that the given peer has responded to a previous gossip request we sent it.
     fn check_gossip_timeout(
         &mut self,
@@ -341,6 +546,59 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         item_id: T::Id,
         sender: NodeId,
     ) -> Effects<Event<T>> {
+        let (mut effects, is_already_held) =
+            self.process_gossiped_item(effect_builder, item_id, sender);
+
+        // Send a response to the sender indicating whether we already hold the item.
+        let reply = Message::GossipResponse {
+            item_id,
+            is_already_held,
+        };
+        effects.extend(effect_builder.send_message(sender, reply).ignore());
+        effects
+    }
+
+    /// Handles an incoming batched gossip request from a peer on the network.
+    ///
+    /// A single response is sent, listing which of the items we want the sender to send us.
+    fn handle_gossip_batch(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        item_ids: Vec<T::Id>,
+        sender: NodeId,
+    ) -> Effects<Event<T>> {
+        let mut effects = Effects::new();
+        let mut already_held = vec![];
+        let mut wanted = vec![];
+        for item_id in item_ids {
+            let (item_effects, is_already_held) =
+                self.process_gossiped_item(effect_builder, item_id, sender);
+            effects.extend(item_effects);
+            if is_already_held {
+                already_held.push(item_id);
+            } else {
+                wanted.push(item_id);
+            }
+        }
+
+        let reply = Message::GossipBatchResponse {
+            already_held,
+            wanted,
+        };
+        effects.extend(effect_builder.send_message(sender, reply).ignore());
+        effects
+    }
+
+    /// Updates the gossip table with an item ID gossiped to us by `sender`.
+    ///
+    /// Returns the resulting effects, and whether we already hold the item, i.e. whether the
+    /// response to the sender should indicate that we don't need the item from them.
+    fn process_gossiped_item(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        item_id: T::Id,
+        sender: NodeId,
+    ) -> (Effects<Event<T>>, bool) {
         let action = if T::ID_IS_COMPLETE_ITEM {
             self.table.new_complete_data(&item_id, Some(sender))
         } else {
@@ -368,50 +626,30 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
                             .announce_complete_item_received_via_gossip(item_id)
                             .ignore(),
                     );
+                    effects.extend(effect_builder.announce_useful_gossip(sender).ignore());
                 }
 
-                // Send a response to the sender indicating whether we already hold the item.
-                let reply = Message::GossipResponse {
-                    item_id,
-                    is_already_held: should_gossip.is_already_held,
-                };
-                effects.extend(effect_builder.send_message(sender, reply).ignore());
-                effects
+                (effects, should_gossip.is_already_held)
             }
             GossipAction::GetRemainder { .. } => {
                 self.metrics.items_received.inc();
-                // Send a response to the sender indicating we want the full item from them, and set
-                // a timeout for this response.
-                let reply = Message::GossipResponse {
-                    item_id,
-                    is_already_held: false,
-                };
-                let mut effects = effect_builder.send_message(sender, reply).ignore();
-                effects.extend(
-                    effect_builder
-                        .set_timeout(self.get_from_peer_timeout)
-                        .event(move |_| Event::CheckGetFromPeerTimeout {
-                            item_id,
-                            peer: sender,
-                        }),
-                );
-                effects
+                // We want the full item from the sender, so set a timeout for its response.
+                let effects = effect_builder
+                    .set_timeout(self.get_from_peer_timeout)
+                    .event(move |_| Event::CheckGetFromPeerTimeout {
+                        item_id,
+                        peer: sender,
+                    });
+                (effects, false)
             }
             GossipAction::Noop
             | GossipAction::AwaitingRemainder
             | GossipAction::AnnounceFinished => {
-                // Send a response to the sender indicating we already hold the item.
-                let reply = Message::GossipResponse {
-                    item_id,
-                    is_already_held: true,
-                };
-                let mut effects = effect_builder.send_message(sender, reply).ignore();
-
+                let mut effects = Effects::new();
                 if action == GossipAction::AnnounceFinished {
                     effects.extend(effect_builder.announce_finished_gossiping(item_id).ignore());
                 }
-
-                effects
+                (effects, true)
             }
         }
     }
@@ -461,6 +699,24 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         effects
     }
 
+    /// Handles an incoming response to a batched gossip request from a peer on the network.
+    fn handle_gossip_batch_response(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        already_held: Vec<T::Id>,
+        wanted: Vec<T::Id>,
+        sender: NodeId,
+    ) -> Effects<Event<T>> {
+        let mut effects = Effects::new();
+        for item_id in already_held {
+            effects.extend(self.handle_gossip_response(effect_builder, item_id, true, sender));
+        }
+        for item_id in wanted {
+            effects.extend(self.handle_gossip_response(effect_builder, item_id, false, sender));
+        }
+        effects
+    }
+
     /// Handles the `Ok` case for a `Result` of attempting to get the item from the component
     /// responsible for holding it, in order to send it to the requester.
     fn got_from_holder(
@@ -503,6 +759,110 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         Effects::new()
     }
 
+    /// Persists the IDs of the recently gossiped items, if they changed since the last snapshot.
+    fn persist_state(&mut self, effect_builder: EffectBuilder<REv>) -> Effects<Event<T>> {
+        // Re-trigger timer after `PERSIST_INTERVAL`.
+        let mut effects = effect_builder
+            .set_timeout(PERSIST_INTERVAL)
+            .event(|_| Event::PersistState);
+
+        let item_ids = self.table.recent_items();
+        let digest = recent_items_digest(&item_ids);
+        if self.persisted_digest != Some(digest) {
+            self.persisted_digest = Some(digest);
+            effects.extend(
+                effect_builder
+                    .save_state(self.state_key.clone().into(), item_ids)
+                    .ignore(),
+            );
+        }
+        effects
+    }
+
+    /// Sends the digest of the recently gossiped item IDs to a random peer.
+    fn anti_entropy_round(&mut self, effect_builder: EffectBuilder<REv>) -> Effects<Event<T>> {
+        // Re-trigger timer after the anti-entropy interval.
+        let mut effects = effect_builder
+            .set_timeout(self.anti_entropy_interval)
+            .event(|_| Event::AntiEntropyRound);
+
+        self.metrics.anti_entropy_rounds.inc();
+        let digest = recent_items_digest(&self.table.recent_items());
+        effects.extend(
+            effect_builder
+                .gossip_message(Message::AntiEntropyDigest(digest), 1, HashSet::new())
+                .ignore(),
+        );
+        effects
+    }
+
+    /// Handles an incoming anti-entropy digest from a peer on the network.
+    ///
+    /// If it doesn't match the digest of our recently gossiped item IDs, we send the peer our list
+    /// so it can determine which items are missing on either side.
+    fn handle_anti_entropy_digest(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        digest: Digest,
+        sender: NodeId,
+    ) -> Effects<Event<T>> {
+        let mut item_ids = self.table.recent_items();
+        if recent_items_digest(&item_ids) == digest {
+            debug!(%sender, "anti-entropy digest matches");
+            return Effects::new();
+        }
+        item_ids.truncate(MAX_ANTI_ENTROPY_ITEMS);
+        effect_builder
+            .send_message(sender, Message::AntiEntropyItems(item_ids))
+            .ignore()
+    }
+
+    /// Handles an incoming list of item IDs recently gossiped by a peer in response to our
+    /// anti-entropy digest.
+    ///
+    /// The items unknown to us are treated as if the peer had gossiped them to us in a batch, and
+    /// the items we recently gossiped which the peer didn't list are gossiped to it in a batch.
+    fn handle_anti_entropy_items(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        item_ids: Vec<T::Id>,
+        sender: NodeId,
+    ) -> Effects<Event<T>> {
+        let theirs: HashSet<T::Id> = item_ids.iter().copied().collect();
+        let unknown: Vec<T::Id> = item_ids
+            .into_iter()
+            .filter(|item_id| !self.table.contains(item_id))
+            .collect();
+        let missing_at_peer: Vec<T::Id> = self
+            .table
+            .recent_items()
+            .into_iter()
+            .filter(|item_id| !theirs.contains(item_id))
+            .collect();
+        debug!(
+            %sender,
+            unknown = unknown.len(),
+            missing_at_peer = missing_at_peer.len(),
+            "anti-entropy exchange"
+        );
+        self.metrics
+            .anti_entropy_items_exchanged
+            .inc_by((unknown.len() + missing_at_peer.len()) as u64);
+
+        let mut effects = Effects::new();
+        if !unknown.is_empty() {
+            effects.extend(self.handle_gossip_batch(effect_builder, unknown, sender));
+        }
+        if !missing_at_peer.is_empty() {
+            effects.extend(
+                effect_builder
+                    .send_message(sender, Message::GossipBatch(missing_at_peer))
+                    .ignore(),
+            );
+        }
+        effects
+    }
+
     /// Updates the gossiper metrics from the state of the gossip table.
     fn update_gossip_table_metrics(&self) {
         self.metrics
@@ -512,6 +872,17 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
             .table_items_finished
             .set(self.table.items_finished() as i64);
     }
+
+    /// Returns the estimated heap memory usage of the gossip table and the pending batches.
+    pub(crate) fn estimate_heap_size_breakdown(&self) -> Vec<(String, usize)> {
+        vec![
+            ("table".to_string(), self.table.estimate_heap_size()),
+            (
+                "pending_batches".to_string(),
+                self.pending_batches.estimate_heap_size(),
+            ),
+        ]
+    }
 }
 
 impl<T, REv> Component<REv> for Gossiper<T, REv>
@@ -547,6 +918,19 @@ where
                 requested_count,
                 peers,
             } => self.gossiped_to(effect_builder, item_id, requested_count, peers),
+            Event::BatchGossipedTo {
+                items,
+                requested_count,
+                peers,
+            } => self.batch_gossiped_to(effect_builder, items, requested_count, peers),
+            Event::FlushGossipBatches => self.flush_batches(effect_builder),
+            Event::Loaded { item_ids } => {
+                debug!(count = item_ids.len(), "restoring recently gossiped items");
+                self.table.restore_finished(item_ids);
+                Effects::new()
+            }
+            Event::PersistState => self.persist_state(effect_builder),
+            Event::AntiEntropyRound => self.anti_entropy_round(effect_builder),
             Event::CheckGossipTimeout { item_id, peer } => {
                 self.check_gossip_timeout(effect_builder, item_id, peer)
             }
@@ -559,6 +943,21 @@ where
                     item_id,
                     is_already_held,
                 } => self.handle_gossip_response(effect_builder, item_id, is_already_held, sender),
+                Message::GossipBatch(item_ids) => {
+                    self.handle_gossip_batch(effect_builder, item_ids, sender)
+                }
+                Message::GossipBatchResponse {
+                    already_held,
+                    wanted,
+                } => {
+                    self.handle_gossip_batch_response(effect_builder, already_held, wanted, sender)
+                }
+                Message::AntiEntropyDigest(digest) => {
+                    self.handle_anti_entropy_digest(effect_builder, digest, sender)
+                }
+                Message::AntiEntropyItems(item_ids) => {
+                    self.handle_anti_entropy_items(effect_builder, item_ids, sender)
+                }
             },
             Event::GetFromHolderResult {
                 item_id,
@@ -581,6 +980,9 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Debug for Gossiper<T, REv> {
             .field("table", &self.table)
             .field("gossip_timeout", &self.gossip_timeout)
             .field("get_from_peer_timeout", &self.get_from_peer_timeout)
+            .field("max_batch_size", &self.max_batch_size)
+            .field("batch_interval", &self.batch_interval)
+            .field("anti_entropy_interval", &self.anti_entropy_interval)
             .finish()
     }
 }
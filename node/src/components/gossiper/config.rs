@@ -9,6 +9,8 @@ use tracing::error;
 
 use casper_types::TimeDiff;
 
+use crate::utils::deserialize_nonzero_time_diff;
+
 #[cfg(test)]
 use super::error::Error;
 
@@ -18,6 +20,9 @@ pub(super) const MAX_SATURATION_LIMIT_PERCENT: u8 = 99;
 pub(super) const DEFAULT_FINISHED_ENTRY_DURATION: &str = "60sec";
 const DEFAULT_GOSSIP_REQUEST_TIMEOUT: &str = "10sec";
 const DEFAULT_GET_REMAINDER_TIMEOUT: &str = "60sec";
+const DEFAULT_MAX_BATCH_SIZE: u16 = 100;
+const DEFAULT_BATCH_INTERVAL: &str = "50ms";
+const DEFAULT_ANTI_ENTROPY_INTERVAL: &str = "30sec";
 #[cfg(test)]
 const SMALL_TIMEOUTS_FINISHED_ENTRY_DURATION: &str = "2sec";
 #[cfg(test)]
@@ -27,6 +32,8 @@ const SMALL_TIMEOUTS_GET_REMAINDER_TIMEOUT: &str = "1sec";
 
 /// Configuration options for gossiping.
 #[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
 pub struct Config {
     /// Target number of peers to infect with a given piece of data.
     infection_target: u8,
@@ -46,10 +53,24 @@ pub struct Config {
     /// The timeout duration in seconds for a single gossip request, i.e. for a single gossip
     /// message sent from this node, it will be considered timed out if the expected response from
     /// that peer is not received within this specified duration.
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
     gossip_request_timeout: TimeDiff,
     /// The timeout duration in seconds for retrieving the remaining part(s) of newly-discovered
     /// data from a peer which gossiped information about that data to this node.
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
     get_remainder_timeout: TimeDiff,
+    /// The maximum number of item IDs announced to a peer in a single batched gossip message.  `0`
+    /// disables batching, i.e. every item ID is gossiped in a message of its own.
+    #[serde(default = "default_max_batch_size")]
+    max_batch_size: u16,
+    /// The maximum duration for which item IDs are held back to be gossiped together in a batch.
+    #[serde(default = "default_batch_interval")]
+    batch_interval: TimeDiff,
+    /// The interval between anti-entropy rounds, in which the recently gossiped item IDs are
+    /// compared with a random peer to recover items missed e.g. due to a transient partition.  `0`
+    /// disables anti-entropy rounds.
+    #[serde(default = "default_anti_entropy_interval")]
+    anti_entropy_interval: TimeDiff,
 }
 
 impl Config {
@@ -70,6 +91,9 @@ impl Config {
             finished_entry_duration,
             gossip_request_timeout,
             get_remainder_timeout,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            batch_interval: default_batch_interval(),
+            anti_entropy_interval: default_anti_entropy_interval(),
         })
     }
 
@@ -105,6 +129,18 @@ impl Config {
     pub(crate) fn get_remainder_timeout(&self) -> TimeDiff {
         self.get_remainder_timeout
     }
+
+    pub(crate) fn max_batch_size(&self) -> u16 {
+        self.max_batch_size
+    }
+
+    pub(crate) fn batch_interval(&self) -> TimeDiff {
+        self.batch_interval
+    }
+
+    pub(crate) fn anti_entropy_interval(&self) -> TimeDiff {
+        self.anti_entropy_interval
+    }
 }
 
 impl Default for Config {
@@ -115,10 +151,25 @@ impl Default for Config {
             finished_entry_duration: TimeDiff::from_str(DEFAULT_FINISHED_ENTRY_DURATION).unwrap(),
             gossip_request_timeout: TimeDiff::from_str(DEFAULT_GOSSIP_REQUEST_TIMEOUT).unwrap(),
             get_remainder_timeout: TimeDiff::from_str(DEFAULT_GET_REMAINDER_TIMEOUT).unwrap(),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            batch_interval: default_batch_interval(),
+            anti_entropy_interval: default_anti_entropy_interval(),
         }
     }
 }
 
+fn default_max_batch_size() -> u16 {
+    DEFAULT_MAX_BATCH_SIZE
+}
+
+fn default_batch_interval() -> TimeDiff {
+    TimeDiff::from_str(DEFAULT_BATCH_INTERVAL).unwrap()
+}
+
+fn default_anti_entropy_interval() -> TimeDiff {
+    TimeDiff::from_str(DEFAULT_ANTI_ENTROPY_INTERVAL).unwrap()
+}
+
 /// Deserializes a `usize` but fails if it's not in the range 0..100.
 fn deserialize_saturation_limit_percent<'de, D>(deserializer: D) -> Result<u8, D::Error>
 where
@@ -152,6 +203,9 @@ mod tests {
             finished_entry_duration: TimeDiff::from_str(DEFAULT_FINISHED_ENTRY_DURATION).unwrap(),
             gossip_request_timeout: TimeDiff::from_str(DEFAULT_GOSSIP_REQUEST_TIMEOUT).unwrap(),
             get_remainder_timeout: TimeDiff::from_str(DEFAULT_GET_REMAINDER_TIMEOUT).unwrap(),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            batch_interval: TimeDiff::from_str(DEFAULT_BATCH_INTERVAL).unwrap(),
+            anti_entropy_interval: TimeDiff::from_str(DEFAULT_ANTI_ENTROPY_INTERVAL).unwrap(),
         };
 
         // Parsing should fail.
@@ -27,6 +27,24 @@ pub(crate) enum Event<T: Item> {
         requested_count: usize,
         peers: HashSet<NodeId>,
     },
+    /// The network component gossiped a batch of item IDs to the included peers.
+    ///
+    /// Each item is paired with the peers which were to be excluded when gossiping it.
+    BatchGossipedTo {
+        items: Vec<(T::Id, HashSet<NodeId>)>,
+        requested_count: usize,
+        peers: HashSet<NodeId>,
+    },
+    /// The interval for collecting item IDs into batches has elapsed and the pending batches
+    /// should be gossiped.
+    FlushGossipBatches,
+    /// The IDs of the recently gossiped items have been loaded from storage.
+    Loaded { item_ids: Vec<T::Id> },
+    /// The IDs of the recently gossiped items should be persisted, if they changed.
+    PersistState,
+    /// The anti-entropy interval has elapsed and the recently gossiped items should be compared
+    /// with a random peer.
+    AntiEntropyRound,
     /// The timeout for waiting for a gossip response has elapsed and we should check the response
     /// arrived.
     CheckGossipTimeout { item_id: T::Id, peer: NodeId },
@@ -66,6 +84,22 @@ impl<T: Item> Display for Event<T> {
                 item_id,
                 DisplayIter::new(peers)
             ),
+            Event::BatchGossipedTo { items, peers, .. } => write!(
+                formatter,
+                "gossiped batch of {} items to {}",
+                items.len(),
+                DisplayIter::new(peers)
+            ),
+            Event::FlushGossipBatches => write!(formatter, "flush gossip batches"),
+            Event::Loaded { item_ids } => {
+                write!(
+                    formatter,
+                    "loaded {} recently gossiped items",
+                    item_ids.len()
+                )
+            }
+            Event::PersistState => write!(formatter, "persist gossip table"),
+            Event::AntiEntropyRound => write!(formatter, "anti-entropy round"),
             Event::CheckGossipTimeout { item_id, peer } => write!(
                 formatter,
                 "check gossip timeout for {} with {}",
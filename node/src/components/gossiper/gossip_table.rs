@@ -432,6 +432,32 @@ impl<T: Copy + Eq + Hash + Display> GossipTable<T> {
         false
     }
 
+    /// Returns whether the data is either currently being gossiped or finished.
+    pub(crate) fn contains(&self, data_id: &T) -> bool {
+        self.current.contains_key(data_id) || self.finished.contains(data_id)
+    }
+
+    /// Returns the IDs of the data we hold which is currently being gossiped or finished, i.e.
+    /// which we recently gossiped.
+    pub(crate) fn recent_items(&self) -> Vec<T> {
+        self.current
+            .iter()
+            .filter(|(_, state)| state.held_by_us)
+            .map(|(data_id, _)| *data_id)
+            .chain(self.finished.iter().copied())
+            .collect()
+    }
+
+    /// Restores data IDs recently gossiped before a restart as finished entries, unless they are
+    /// already known.
+    pub(crate) fn restore_finished(&mut self, data_ids: Vec<T>) {
+        for data_id in data_ids {
+            if !self.contains(&data_id) {
+                self.insert_to_finished(&data_id);
+            }
+        }
+    }
+
     /// Updates the entry under `data_id` in `self.current` and returns the action we should now
     /// take, or `None` if the entry does not exist.
     ///
@@ -978,6 +1004,38 @@ mod tests {
         assert!(!gossip_table.finished.contains(&data_id));
     }
 
+    #[test]
+    fn should_restore_finished_and_list_recent_items() {
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let node_ids = random_node_ids(&mut rng);
+        let held_id: u64 = rng.gen();
+        let partial_id: u64 = rng.gen();
+        let restored_id: u64 = rng.gen();
+
+        let mut gossip_table = GossipTable::new(Config::default());
+        let _ = gossip_table.new_complete_data(&held_id, None);
+        let _ = gossip_table.new_partial_data(&partial_id, node_ids[0]);
+
+        // Restoring a current entry should leave it untouched.
+        gossip_table.restore_finished(vec![held_id, restored_id]);
+        assert!(gossip_table.current.contains_key(&held_id));
+        assert!(gossip_table.finished.contains(&restored_id));
+        assert!(gossip_table.contains(&partial_id));
+
+        // Partial data is not held by us, so isn't listed as recently gossiped.
+        let recent: BTreeSet<_> = gossip_table.recent_items().into_iter().collect();
+        assert_eq!(recent, [held_id, restored_id].iter().copied().collect());
+
+        // Restored entries are purged like any other finished entry.
+        let millis = TimeDiff::from_str(DEFAULT_FINISHED_ENTRY_DURATION)
+            .unwrap()
+            .millis();
+        Instant::advance_time(millis + 1);
+        gossip_table.purge_finished();
+        assert!(!gossip_table.contains(&restored_id));
+    }
+
     #[test]
     fn timeouts_purge_in_order() {
         let mut timeouts = Timeouts::new();
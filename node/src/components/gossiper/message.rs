@@ -2,7 +2,10 @@ use std::fmt::{self, Display, Formatter};
 
 use serde::{Deserialize, Serialize};
 
+use casper_hashing::Digest;
+
 use super::Item;
+use crate::utils::DisplayIter;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(bound = "for<'a> T: Deserialize<'a>")]
@@ -15,6 +18,36 @@ pub(crate) enum Message<T: Item> {
         item_id: T::Id,
         is_already_held: bool,
     },
+    /// Gossiped out to random peers to notify them of several items we hold at once.
+    ///
+    /// Only sent to peers which advertised support for batched gossip during the handshake.
+    GossipBatch(Vec<T::Id>),
+    /// Response to a `GossipBatch` message.  The recipient should treat the `wanted` items as
+    /// `GetRequest`s and send a `GetResponse` for each of them.
+    GossipBatchResponse {
+        already_held: Vec<T::Id>,
+        wanted: Vec<T::Id>,
+    },
+    /// Sent to a random peer in an anti-entropy round, containing a digest of the IDs of the items
+    /// recently gossiped by us.
+    AntiEntropyDigest(Digest),
+    /// Response to an `AntiEntropyDigest` message whose digest didn't match the recipient's,
+    /// listing the IDs of the items recently gossiped by the responder.
+    AntiEntropyItems(Vec<T::Id>),
+}
+
+impl<T: Item> Message<T> {
+    /// Returns whether this is one of the batched gossip or anti-entropy messages, which can only
+    /// be sent to peers advertising support for batched gossip.
+    pub(crate) fn is_batched(&self) -> bool {
+        match self {
+            Message::Gossip(_) | Message::GossipResponse { .. } => false,
+            Message::GossipBatch(_)
+            | Message::GossipBatchResponse { .. }
+            | Message::AntiEntropyDigest(_)
+            | Message::AntiEntropyItems(_) => true,
+        }
+    }
 }
 
 impl<T: Item> Display for Message<T> {
@@ -29,6 +62,28 @@ impl<T: Item> Display for Message<T> {
                 "gossip-response({}, {})",
                 item_id, is_already_held
             ),
+            Message::GossipBatch(item_ids) => {
+                write!(formatter, "gossip-batch({})", DisplayIter::new(item_ids))
+            }
+            Message::GossipBatchResponse {
+                already_held,
+                wanted,
+            } => write!(
+                formatter,
+                "gossip-batch-response(already held: {}, wanted: {})",
+                DisplayIter::new(already_held),
+                DisplayIter::new(wanted)
+            ),
+            Message::AntiEntropyDigest(digest) => {
+                write!(formatter, "anti-entropy-digest({})", digest)
+            }
+            Message::AntiEntropyItems(item_ids) => {
+                write!(
+                    formatter,
+                    "anti-entropy-items({})",
+                    DisplayIter::new(item_ids)
+                )
+            }
         }
     }
 }
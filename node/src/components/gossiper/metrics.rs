@@ -15,6 +15,10 @@ pub(super) struct Metrics {
     pub(super) table_items_current: IntGauge,
     /// Number of items in the gossip table that are finished.
     pub(super) table_items_finished: IntGauge,
+    /// Total number of anti-entropy rounds started with a peer.
+    pub(super) anti_entropy_rounds: IntCounter,
+    /// Total number of items found missing on either side in anti-entropy exchanges.
+    pub(super) anti_entropy_items_exchanged: IntCounter,
     /// Reference to the registry for unregistering.
     registry: Registry,
 }
@@ -51,12 +55,25 @@ impl Metrics {
                 name
             ),
         )?;
+        let anti_entropy_rounds = IntCounter::new(
+            format!("{}_anti_entropy_rounds", name),
+            format!("number of anti-entropy rounds started by the {}", name),
+        )?;
+        let anti_entropy_items_exchanged = IntCounter::new(
+            format!("{}_anti_entropy_items_exchanged", name),
+            format!(
+                "number of items found missing on either side in anti-entropy exchanges of the {}",
+                name
+            ),
+        )?;
 
         registry.register(Box::new(items_received.clone()))?;
         registry.register(Box::new(times_gossiped.clone()))?;
         registry.register(Box::new(times_ran_out_of_peers.clone()))?;
         registry.register(Box::new(table_items_current.clone()))?;
         registry.register(Box::new(table_items_finished.clone()))?;
+        registry.register(Box::new(anti_entropy_rounds.clone()))?;
+        registry.register(Box::new(anti_entropy_items_exchanged.clone()))?;
 
         Ok(Metrics {
             items_received,
@@ -64,6 +81,8 @@ impl Metrics {
             times_ran_out_of_peers,
             table_items_current,
             table_items_finished,
+            anti_entropy_rounds,
+            anti_entropy_items_exchanged,
             registry: registry.clone(),
         })
     }
@@ -76,5 +95,7 @@ impl Drop for Metrics {
         unregister_metric!(self.registry, self.times_ran_out_of_peers);
         unregister_metric!(self.registry, self.table_items_current);
         unregister_metric!(self.registry, self.table_items_finished);
+        unregister_metric!(self.registry, self.anti_entropy_rounds);
+        unregister_metric!(self.registry, self.anti_entropy_items_exchanged);
     }
 }
@@ -41,12 +41,13 @@ use crate::{
     },
     effect::{
         announcements::{
-            ContractRuntimeAnnouncement, ControlAnnouncement, DeployAcceptorAnnouncement,
-            GossiperAnnouncement, RpcServerAnnouncement,
+            BlocklistAnnouncement, ContractRuntimeAnnouncement, ControlAnnouncement,
+            DeployAcceptorAnnouncement, GossiperAnnouncement, RpcServerAnnouncement,
         },
         incoming::{
-            ConsensusMessageIncoming, FinalitySignatureIncoming, NetRequestIncoming, NetResponse,
-            NetResponseIncoming, TrieDemand, TrieRequestIncoming, TrieResponseIncoming,
+            ConsensusMessageIncoming, DeployResponseIncoming, FinalitySignatureIncoming,
+            FinalitySignaturesIncoming, NetRequestIncoming, NetResponse, NetResponseIncoming,
+            TrieDemand, TrieRequestIncoming, TrieResponseIncoming,
         },
         requests::{ConsensusRequest, ContractRuntimeRequest, MarkBlockCompletedRequest},
         Responder,
@@ -57,6 +58,7 @@ use crate::{
     testing::{
         self,
         network::{Network, NetworkedReactor},
+        network_faults::NetworkFaults,
         ConditionCheckReactor,
     },
     types::{Deploy, NodeId},
@@ -83,6 +85,8 @@ enum Event {
     #[from]
     StorageRequest(StorageRequest),
     #[from]
+    StateStoreRequest(StateStoreRequest),
+    #[from]
     MarkBlockCompletedRequest(MarkBlockCompletedRequest),
     #[from]
     ControlAnnouncement(ControlAnnouncement),
@@ -93,6 +97,8 @@ enum Event {
     #[from]
     DeployGossiperAnnouncement(#[serde(skip_serializing)] GossiperAnnouncement<Deploy>),
     #[from]
+    BlocklistAnnouncement(BlocklistAnnouncement),
+    #[from]
     ContractRuntime(contract_runtime::Event),
     #[from]
     ContractRuntimeRequest(ContractRuntimeRequest),
@@ -107,6 +113,8 @@ enum Event {
     #[from]
     NetResponseIncoming(NetResponseIncoming),
     #[from]
+    DeployResponseIncoming(DeployResponseIncoming),
+    #[from]
     TrieRequestIncoming(TrieRequestIncoming),
     #[from]
     TrieDemand(TrieDemand),
@@ -114,6 +122,8 @@ enum Event {
     TrieResponseIncoming(TrieResponseIncoming),
     #[from]
     FinalitySignatureIncoming(FinalitySignatureIncoming),
+    #[from]
+    FinalitySignaturesIncoming(FinalitySignaturesIncoming),
 }
 
 impl ReactorEvent for Event {
@@ -160,6 +170,7 @@ impl Display for Event {
             Event::DeployAcceptor(event) => write!(formatter, "deploy acceptor: {}", event),
             Event::DeployGossiper(event) => write!(formatter, "deploy gossiper: {}", event),
             Event::StorageRequest(req) => write!(formatter, "storage request: {}", req),
+            Event::StateStoreRequest(req) => write!(formatter, "state store request: {}", req),
             Event::MarkBlockCompletedRequest(req) => {
                 write!(formatter, "mark block completed: {}", req)
             }
@@ -175,6 +186,9 @@ impl Display for Event {
             Event::DeployGossiperAnnouncement(ann) => {
                 write!(formatter, "deploy-gossiper announcement: {}", ann)
             }
+            Event::BlocklistAnnouncement(ann) => {
+                write!(formatter, "blocklist announcement: {}", ann)
+            }
             Event::ContractRuntime(event) => {
                 write!(formatter, "contract-runtime event: {:?}", event)
             }
@@ -183,10 +197,12 @@ impl Display for Event {
             Event::AddressGossiperIncoming(inner) => write!(formatter, "incoming: {}", inner),
             Event::NetRequestIncoming(inner) => write!(formatter, "incoming: {}", inner),
             Event::NetResponseIncoming(inner) => write!(formatter, "incoming: {}", inner),
+            Event::DeployResponseIncoming(inner) => write!(formatter, "incoming: {}", inner),
             Event::TrieRequestIncoming(inner) => write!(formatter, "incoming: {}", inner),
             Event::TrieDemand(inner) => write!(formatter, "demand: {}", inner),
             Event::TrieResponseIncoming(inner) => write!(formatter, "incoming: {}", inner),
             Event::FinalitySignatureIncoming(inner) => write!(formatter, "incoming: {}", inner),
+            Event::FinalitySignaturesIncoming(inner) => write!(formatter, "incoming: {}", inner),
         }
     }
 }
@@ -239,7 +255,7 @@ impl reactor::Reactor for Reactor {
         let contract_runtime_config = contract_runtime::Config::default();
         let contract_runtime = ContractRuntime::new(
             ProtocolVersion::from_parts(1, 0, 0),
-            storage.root_path(),
+            storage.global_state_path(),
             &contract_runtime_config,
             WasmConfig::default(),
             SystemConfig::default(),
@@ -247,15 +263,18 @@ impl reactor::Reactor for Reactor {
             DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
             DEFAULT_MINIMUM_DELEGATION_AMOUNT,
             DEFAULT_STRICT_ARGUMENT_CHECKING,
+            false,
+            false,
             DEFAULT_VESTING_SCHEDULE_LENGTH_MILLIS,
             registry,
         )
         .unwrap();
 
         let fake_deploy_acceptor = FakeDeployAcceptor::new();
-        let deploy_gossiper = Gossiper::new_for_partial_items(
+        let (deploy_gossiper, deploy_gossiper_effects) = Gossiper::new_for_partial_items(
             "deploy_gossiper",
             config,
+            EffectBuilder::new(event_queue),
             get_deploy_from_storage,
             registry,
         )?;
@@ -269,7 +288,7 @@ impl reactor::Reactor for Reactor {
             _storage_tempdir: storage_tempdir,
         };
 
-        let effects = Effects::new();
+        let effects = reactor::wrap_effects(Event::DeployGossiper, deploy_gossiper_effects);
 
         Ok((reactor, effects))
     }
@@ -305,6 +324,11 @@ impl reactor::Reactor for Reactor {
                 self.storage
                     .handle_event(effect_builder, rng, request.into()),
             ),
+            Event::StateStoreRequest(request) => reactor::wrap_effects(
+                Event::Storage,
+                self.storage
+                    .handle_event(effect_builder, rng, request.into()),
+            ),
             Event::MarkBlockCompletedRequest(_) => {
                 panic!("gossiper tests should never mark blocks completed")
             }
@@ -322,6 +346,23 @@ impl reactor::Reactor for Reactor {
                 };
                 self.dispatch_event(effect_builder, rng, Event::DeployAcceptor(event))
             }
+            Event::RpcServerAnnouncement(RpcServerAnnouncement::DeployPrecheckRequested {
+                deploy,
+                responder,
+            }) => {
+                let event = deploy_acceptor::Event::Precheck { deploy, responder };
+                self.dispatch_event(effect_builder, rng, Event::DeployAcceptor(event))
+            }
+            Event::RpcServerAnnouncement(RpcServerAnnouncement::TransactionV2Received {
+                transaction,
+                responder,
+            }) => {
+                let event = deploy_acceptor::Event::AcceptTransactionV2 {
+                    transaction,
+                    responder,
+                };
+                self.dispatch_event(effect_builder, rng, Event::DeployAcceptor(event))
+            }
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::AcceptedNewDeploy {
                 deploy,
                 source,
@@ -340,6 +381,10 @@ impl reactor::Reactor for Reactor {
                 // We do not care about deploy gossiper announcements in the gossiper test.
                 Effects::new()
             }
+            Event::BlocklistAnnouncement(_ann) => {
+                // There is no reputation tracking on the in-memory network.
+                Effects::new()
+            }
             Event::Network(event) => reactor::wrap_effects(
                 Event::Network,
                 self.network.handle_event(effect_builder, rng, event),
@@ -416,10 +461,12 @@ impl reactor::Reactor for Reactor {
             },
             other @ (Event::ConsensusMessageIncoming(_)
             | Event::FinalitySignatureIncoming(_)
+            | Event::FinalitySignaturesIncoming(_)
             | Event::AddressGossiperIncoming(_)
             | Event::TrieRequestIncoming(_)
             | Event::TrieDemand(_)
-            | Event::TrieResponseIncoming(_)) => {
+            | Event::TrieResponseIncoming(_)
+            | Event::DeployResponseIncoming(_)) => {
                 fatal!(effect_builder, "should not receive {:?}", other).ignore()
             }
         }
@@ -502,6 +549,117 @@ async fn should_gossip() {
     }
 }
 
+#[tokio::test]
+async fn should_gossip_batch_of_deploys() {
+    const NETWORK_SIZE: usize = 5;
+    const DEPLOY_COUNT: usize = 10;
+    const TIMEOUT: Duration = Duration::from_secs(20);
+    const QUIET_FOR: Duration = Duration::from_millis(50);
+
+    NetworkController::<NodeMessage>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = crate::new_rng();
+
+    let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+
+    // Give all deploys to node 0 to be gossiped.
+    let (all_deploy_hashes, deploys): (BTreeSet<_>, Vec<_>) = iter::repeat_with(|| {
+        let deploy = Box::new(Deploy::random_valid_native_transfer(&mut rng));
+        (*deploy.id(), deploy)
+    })
+    .take(DEPLOY_COUNT)
+    .unzip();
+    for deploy in deploys {
+        network
+            .process_injected_effect_on(&node_ids[0], announce_deploy_received(deploy, None))
+            .await;
+    }
+
+    // Run node 0 until it has gossiped the deploys in a batch.
+    let made_batched_gossip_request = |event: &Event| -> bool {
+        match event {
+            Event::NetworkRequest(NetworkRequest::Gossip { payload, .. }) => {
+                matches!(
+                    **payload,
+                    NodeMessage::DeployGossiper(Message::GossipBatch(_))
+                )
+            }
+            _ => false,
+        }
+    };
+    network
+        .crank_until(&node_ids[0], &mut rng, made_batched_gossip_request, TIMEOUT)
+        .await;
+
+    // Check every node has every deploy stored locally.
+    let all_deploys_held = |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        nodes.values().all(|runner| {
+            let hashes = runner.reactor().inner().storage.get_all_deploy_hashes();
+            all_deploy_hashes == hashes
+        })
+    };
+    network.settle_on(&mut rng, all_deploys_held, TIMEOUT).await;
+
+    // Ensure all responders are called before dropping the network.
+    network.settle(&mut rng, QUIET_FOR, TIMEOUT).await;
+
+    NetworkController::<NodeMessage>::remove_active();
+}
+
+#[tokio::test]
+async fn should_not_gossip_across_partition() {
+    const NETWORK_SIZE: usize = 5;
+    const DEPLOY_COUNT: usize = 5;
+    const TIMEOUT: Duration = Duration::from_secs(20);
+    const QUIET_FOR: Duration = Duration::from_millis(50);
+
+    let mut rng = crate::new_rng();
+    let faults = NetworkFaults::new(&mut rng);
+    faults.set_latency(Duration::from_millis(10), Duration::from_millis(20));
+    NetworkController::<NodeMessage>::create_active_with_faults(faults.clone());
+    let mut network = Network::<Reactor>::new();
+
+    let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+    let isolated = node_ids[NETWORK_SIZE - 1];
+    faults.partition(iter::once(isolated));
+
+    // Give all deploys to node 0 to be gossiped.
+    let (all_deploy_hashes, deploys): (BTreeSet<_>, Vec<_>) = iter::repeat_with(|| {
+        let deploy = Box::new(Deploy::random_valid_native_transfer(&mut rng));
+        (*deploy.id(), deploy)
+    })
+    .take(DEPLOY_COUNT)
+    .unzip();
+    for deploy in deploys {
+        network
+            .process_injected_effect_on(&node_ids[0], announce_deploy_received(deploy, None))
+            .await;
+    }
+
+    // Check every node on the majority side has every deploy stored locally.
+    let all_deploys_held = |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        nodes
+            .iter()
+            .filter(|(node_id, _)| **node_id != isolated)
+            .all(|(_, runner)| {
+                let hashes = runner.reactor().inner().storage.get_all_deploy_hashes();
+                all_deploy_hashes == hashes
+            })
+    };
+    network.settle_on(&mut rng, all_deploys_held, TIMEOUT).await;
+    network.settle(&mut rng, QUIET_FOR, TIMEOUT).await;
+
+    // The isolated node never heard of any of them.
+    let isolated_hashes = network.nodes()[&isolated]
+        .reactor()
+        .inner()
+        .storage
+        .get_all_deploy_hashes();
+    assert!(isolated_hashes.is_empty());
+
+    NetworkController::<NodeMessage>::remove_active();
+}
+
 #[tokio::test]
 async fn should_get_from_alternate_source() {
     const NETWORK_SIZE: usize = 3;
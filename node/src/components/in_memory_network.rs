@@ -1,7 +1,8 @@
 //! Very fast networking component used for testing and simulations.
 //!
 //! The `InMemoryNetwork` represents a full virtual network with flawless connectivity and delivery
-//! by default.
+//! by default. Latency, packet loss and partitions can be injected by creating the network with
+//! `NetworkController::create_active_with_faults`.
 //!
 //! # Setup
 //!
@@ -296,6 +297,7 @@ use crate::{
     effect::{requests::NetworkRequest, EffectBuilder, EffectExt, Effects},
     logging,
     reactor::{EventQueueHandle, QueueKind},
+    testing::network_faults::NetworkFaults,
     types::NodeId,
     NodeRng,
 };
@@ -334,6 +336,8 @@ thread_local! {
 pub(crate) struct NetworkController<P> {
     /// Channels for network communication.
     nodes: Network<P>,
+    /// Faults applied to all messages sent.
+    faults: NetworkFaults,
 }
 
 impl<P> NetworkController<P>
@@ -341,10 +345,11 @@ where
     P: 'static + Send,
 {
     /// Create a new, empty network.
-    fn new() -> Self {
+    fn new(faults: NetworkFaults) -> Self {
         let _ = logging::init();
         NetworkController {
             nodes: Default::default(),
+            faults,
         }
     }
 
@@ -354,9 +359,20 @@ where
     ///
     /// Panics if the internal lock has been poisoned.
     pub(crate) fn create_active() {
+        Self::create_active_with_faults(NetworkFaults::default())
+    }
+
+    /// Creates a new, empty network controller applying `faults` to all messages and sets it as
+    /// active.
+    ///
+    /// The faults can be changed through any clone of `faults` while the network is running.
+    pub(crate) fn create_active_with_faults(faults: NetworkFaults) {
         let _ = logging::init();
-        ACTIVE_NETWORK
-            .with(|active_network| active_network.borrow_mut().replace(Box::new(Self::new())));
+        ACTIVE_NETWORK.with(|active_network| {
+            active_network
+                .borrow_mut()
+                .replace(Box::new(Self::new(faults)))
+        });
     }
 
     /// Removes the active network.
@@ -434,7 +450,12 @@ where
     where
         REv: Send + FromIncoming<P>,
     {
-        InMemoryNetwork::new_with_data(event_queue, NodeId::random(rng), self.nodes.clone())
+        InMemoryNetwork::new_with_data(
+            event_queue,
+            NodeId::random(rng),
+            self.nodes.clone(),
+            self.faults.clone(),
+        )
     }
 }
 
@@ -446,6 +467,9 @@ pub(crate) struct InMemoryNetwork<P> {
 
     /// The nodes map, contains the incoming channel for each virtual node.
     nodes: Network<P>,
+
+    /// Faults applied to all messages sent.
+    faults: NetworkFaults,
 }
 
 impl<P> InMemoryNetwork<P>
@@ -467,6 +491,7 @@ where
         event_queue: EventQueueHandle<REv>,
         node_id: NodeId,
         nodes: Network<P>,
+        faults: NetworkFaults,
     ) -> Self
     where
         REv: Send + FromIncoming<P>,
@@ -482,7 +507,11 @@ where
 
         tokio::spawn(receiver_task(event_queue, receiver));
 
-        InMemoryNetwork { node_id, nodes }
+        InMemoryNetwork {
+            node_id,
+            nodes,
+            faults,
+        }
     }
 
     /// Returns this node's ID.
@@ -494,7 +523,7 @@ where
 
 impl<P> InMemoryNetwork<P>
 where
-    P: Display,
+    P: 'static + Send + Display,
 {
     /// Internal helper, sends a payload to a node, ignoring but logging all errors.
     fn send(
@@ -507,22 +536,41 @@ where
             panic!("can't send message to self");
         }
 
-        match nodes.get(&dest) {
-            Some(sender) => {
-                if let Err(SendError((_, msg))) = sender.send((self.node_id, payload)) {
-                    warn!(%dest, %msg, "could not send message (send error)");
+        let sender = match nodes.get(&dest) {
+            Some(sender) => sender.clone(),
+            None => {
+                info!(%dest, %payload, "dropping message to non-existent recipient");
+                return;
+            }
+        };
+
+        let delay = match self.faults.delivery_delay(self.node_id, dest) {
+            Some(delay) => delay,
+            None => return,
+        };
 
-                    // We do nothing else, the message is just dropped.
-                }
+        let node_id = self.node_id;
+        let deliver = move || {
+            if let Err(SendError((_, msg))) = sender.send((node_id, payload)) {
+                warn!(%dest, %msg, "could not send message (send error)");
+
+                // We do nothing else, the message is just dropped.
             }
-            None => info!(%dest, %payload, "dropping message to non-existent recipient"),
+        };
+        if delay.is_zero() {
+            deliver();
+        } else {
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                deliver();
+            });
         }
     }
 }
 
 impl<P, REv> Component<REv> for InMemoryNetwork<P>
 where
-    P: Display + Clone,
+    P: 'static + Send + Display + Clone,
 {
     type Event = Event<P>;
     type ConstructionError = Infallible;
@@ -7,19 +7,27 @@ mod signature_cache;
 mod state;
 mod utils;
 
-use std::convert::Infallible;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    mem,
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use datasize::DataSize;
 use itertools::Itertools;
 use num::rational::Ratio;
 use prometheus::Registry;
+use serde::Serialize;
 use tracing::{error, info};
 
 use casper_execution_engine::core::engine_state::GetEraValidatorsError;
 use casper_types::{
     system::auction::{EraValidators, ValidatorWeights},
-    EraId, ProtocolVersion,
+    EraId, ProtocolVersion, TimeDiff, Timestamp,
 };
 
 use self::{
@@ -30,13 +38,14 @@ use crate::{
     components::{contract_runtime::EraValidatorsRequest, Component},
     effect::{
         announcements::LinearChainAnnouncement,
+        diagnostics_port::ShutdownPointRequest,
         requests::{
             ChainspecLoaderRequest, ContractRuntimeRequest, NetworkRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, EffectResultExt, Effects,
     },
     protocol::Message,
-    types::{ActivationPoint, BlockHeader},
+    types::{ActivationPoint, Block, BlockHash, BlockHeader, BlockSignatures, FinalitySignature},
     NodeRng,
 };
 pub(crate) use error::{BlockSignatureError, Error};
@@ -46,6 +55,54 @@ pub(crate) use utils::{
     validate_block_signatures,
 };
 
+/// How long finality signatures are collected before being gossiped as per-block batches.
+const GOSSIP_BATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The number of peers each batch of finality signatures is gossiped to.
+const GOSSIP_FANOUT: usize = 10;
+
+/// The number of recent blocks whose time to finality is still to be recorded.  Older blocks are
+/// dropped, e.g. if the node never collects enough signatures for them.
+const MAX_UNFINALIZED_BLOCKS: usize = 100;
+
+/// The point after which the node shuts down, so that e.g. an emergency upgrade can be applied or
+/// a snapshot of its data taken at the same block on every node.
+#[derive(Clone, Copy, Debug, Default, DataSize, PartialEq, Eq, Serialize)]
+pub(crate) struct ShutdownPoint {
+    /// Shut down once the block at this height has been stored.
+    pub(crate) height: Option<u64>,
+    /// Shut down once the switch block of this era has been stored.
+    pub(crate) era: Option<EraId>,
+}
+
+impl ShutdownPoint {
+    /// Returns `true` if the node should shut down having stored the block with the given header.
+    fn is_reached_by(&self, header: &BlockHeader) -> bool {
+        self.height
+            .map_or(false, |height| header.height() >= height)
+            || self.era.map_or(false, |era_id| {
+                header.era_id() > era_id || (header.era_id() == era_id && header.is_switch_block())
+            })
+    }
+}
+
+impl Display for ShutdownPoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.height, self.era) {
+            (None, None) => write!(f, "none"),
+            (Some(height), None) => write!(f, "after block {}", height),
+            (None, Some(era_id)) => write!(f, "after era {}", era_id),
+            (Some(height), Some(era_id)) => {
+                write!(
+                    f,
+                    "after block {} or era {}, whichever is first",
+                    height, era_id
+                )
+            }
+        }
+    }
+}
+
 #[derive(DataSize, Debug)]
 pub(crate) struct LinearChainComponent {
     linear_chain_state: LinearChain,
@@ -53,6 +110,15 @@ pub(crate) struct LinearChainComponent {
     metrics: Metrics,
     /// If true, the process should stop execution to allow an upgrade to proceed.
     stop_for_upgrade: bool,
+    /// The point after which the node shuts down.
+    shutdown_point: ShutdownPoint,
+    /// If true, the shutdown point has been reached and the process should stop execution.
+    reached_shutdown_point: bool,
+    /// Finality signatures waiting to be gossiped, batched by the block they sign.
+    pending_gossip: BTreeMap<BlockHash, BlockSignatures>,
+    /// The hashes and timestamps of recent blocks by height, until signatures proving their
+    /// finality are stored.
+    unfinalized_blocks: BTreeMap<u64, (BlockHash, Timestamp)>,
 }
 
 impl LinearChainComponent {
@@ -62,7 +128,9 @@ impl LinearChainComponent {
         auction_delay: u64,
         unbonding_delay: u64,
         finality_threshold_fraction: Ratio<u64>,
+        signing_context_activation_era: Option<EraId>,
         next_upgrade_activation_point: Option<ActivationPoint>,
+        shutdown_point: ShutdownPoint,
     ) -> Result<Self, prometheus::Error> {
         let metrics = Metrics::new(registry)?;
         let linear_chain_state = LinearChain::new(
@@ -70,81 +138,228 @@ impl LinearChainComponent {
             auction_delay,
             unbonding_delay,
             finality_threshold_fraction,
+            signing_context_activation_era,
             next_upgrade_activation_point,
         );
         Ok(LinearChainComponent {
             linear_chain_state,
             metrics,
             stop_for_upgrade: false,
+            shutdown_point,
+            reached_shutdown_point: false,
+            pending_gossip: BTreeMap::new(),
+            unfinalized_blocks: BTreeMap::new(),
         })
     }
 
     pub(crate) fn stop_for_upgrade(&self) -> bool {
         self.stop_for_upgrade
     }
-}
 
-fn outcomes_to_effects<REv>(
-    effect_builder: EffectBuilder<REv>,
-    outcomes: Outcomes,
-) -> Effects<Event>
-where
-    REv: From<StorageRequest>
-        + From<NetworkRequest<Message>>
-        + From<LinearChainAnnouncement>
-        + From<ContractRuntimeRequest>
-        + From<ChainspecLoaderRequest>
-        + Send,
-{
-    outcomes
-        .into_iter()
-        .map(|outcome| match outcome {
-            Outcome::StoreBlockSignatures(block_signatures, should_upgrade) => effect_builder
-                .put_signatures_to_storage(block_signatures)
-                .events(move |_| should_upgrade.then(|| Event::Upgrade).into_iter()),
-            Outcome::StoreBlock(block, execution_results) => async move {
-                let block_hash = *block.hash();
-                effect_builder.put_block_to_storage(block.clone()).await;
-                effect_builder
-                    .put_execution_results_to_storage(block_hash, execution_results)
-                    .await;
-                block
+    pub(crate) fn reached_shutdown_point(&self) -> bool {
+        self.reached_shutdown_point
+    }
+
+    /// Returns the latest block stored, if any.
+    pub(crate) fn latest_block(&self) -> Option<&Block> {
+        self.linear_chain_state.latest_block().as_ref()
+    }
+
+    /// Replaces the shutdown point, unless the node has already stored blocks beyond it.
+    fn set_shutdown_point(
+        &mut self,
+        shutdown_point: ShutdownPoint,
+    ) -> Result<ShutdownPoint, Cow<'static, str>> {
+        if let Some(latest_block) = self.linear_chain_state.latest_block() {
+            if shutdown_point.is_reached_by(latest_block.header()) {
+                return Err(Cow::Owned(format!(
+                    "shutdown point {} already passed at block {}",
+                    shutdown_point,
+                    latest_block.height()
+                )));
             }
-            .event(|block| Event::PutBlockResult { block }),
-            Outcome::Gossip(fs) => {
-                let message = Message::FinalitySignature(fs);
-                effect_builder.broadcast_message(message).ignore()
+        }
+        info!(%shutdown_point, "shutdown point updated");
+        self.shutdown_point = shutdown_point;
+        Ok(shutdown_point)
+    }
+
+    /// Records the interval since the previous block, and starts timing the block's finality.
+    ///
+    /// Must be called before the block is added to the linear chain state.
+    fn record_block_added(&mut self, block: &Block) {
+        if let Some(previous) = self.linear_chain_state.latest_block() {
+            if previous.height() + 1 == block.height() {
+                let interval = block
+                    .header()
+                    .timestamp()
+                    .saturating_diff(previous.header().timestamp());
+                self.metrics.block_interval.observe(as_secs(interval));
             }
-            Outcome::AnnounceSignature(fs) => {
-                effect_builder.announce_finality_signature(fs).ignore()
+        }
+        self.unfinalized_blocks
+            .insert(block.height(), (*block.hash(), block.header().timestamp()));
+        while self.unfinalized_blocks.len() > MAX_UNFINALIZED_BLOCKS {
+            let lowest_height = *self
+                .unfinalized_blocks
+                .keys()
+                .next()
+                .expect("should not be empty");
+            self.unfinalized_blocks.remove(&lowest_height);
+        }
+    }
+
+    /// Records the time to finality of the signed block if the signatures prove its finality for
+    /// the first time.
+    ///
+    /// Returns `true` if they do, i.e. if the block has just been finalized.
+    fn record_signatures_stored(&mut self, signatures: &BlockSignatures) -> bool {
+        let height = match self
+            .unfinalized_blocks
+            .iter()
+            .find(|(_, (block_hash, _))| *block_hash == signatures.block_hash)
+        {
+            Some((height, _)) => *height,
+            None => return false,
+        };
+        if !self.linear_chain_state.is_finalized_by(signatures) {
+            return false;
+        }
+        match self.unfinalized_blocks.remove(&height) {
+            Some((_, timestamp)) => {
+                self.metrics
+                    .time_to_finality
+                    .observe(as_secs(timestamp.elapsed()));
+                true
             }
-            Outcome::AnnounceBlock(block) => effect_builder.announce_block_added(block).ignore(),
-            Outcome::LoadSignatures(fs) => effect_builder
-                .get_signatures_from_storage(fs.block_hash)
-                .event(move |maybe_signatures| {
-                    Event::GetStoredFinalitySignaturesResult(fs, maybe_signatures.map(Box::new))
-                }),
-            Outcome::VerifyIfBonded {
-                new_fs,
-                known_fs,
-                protocol_version,
-                latest_state_root_hash,
-            } => effect_builder
-                .is_bonded_validator(
-                    new_fs.public_key.clone(),
-                    new_fs.era_id,
-                    latest_state_root_hash,
+            None => false,
+        }
+    }
+
+    /// Updates the time since the proposal of the latest stored block.
+    fn update_linear_chain_lag(&self) {
+        if let Some(latest_block) = self.linear_chain_state.latest_block() {
+            self.metrics
+                .linear_chain_lag
+                .set(as_secs(latest_block.header().timestamp().elapsed()));
+        }
+    }
+
+    /// Queues the finality signature to be gossiped with the next batch, scheduling the batch if
+    /// none is pending yet.
+    fn queue_gossip<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        fs: Box<FinalitySignature>,
+    ) -> Effects<Event>
+    where
+        REv: Send,
+    {
+        let effects = if self.pending_gossip.is_empty() {
+            effect_builder
+                .set_timeout(GOSSIP_BATCH_INTERVAL)
+                .event(|_| Event::GossipFinalitySignatures)
+        } else {
+            Effects::new()
+        };
+        self.pending_gossip
+            .entry(fs.block_hash)
+            .or_insert_with(|| BlockSignatures::new(fs.block_hash, fs.era_id))
+            .insert_proof(fs.public_key, fs.signature);
+        effects
+    }
+
+    /// Gossips all queued finality signatures, one message per block.
+    fn gossip_pending<REv>(&mut self, effect_builder: EffectBuilder<REv>) -> Effects<Event>
+    where
+        REv: From<NetworkRequest<Message>> + Send,
+    {
+        mem::take(&mut self.pending_gossip)
+            .into_values()
+            .map(|signatures| {
+                let message = Message::FinalitySignatures(Box::new(signatures));
+                effect_builder
+                    .gossip_message(message, GOSSIP_FANOUT, HashSet::new())
+                    .ignore()
+            })
+            .concat()
+    }
+
+    fn outcomes_to_effects<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        outcomes: Outcomes,
+    ) -> Effects<Event>
+    where
+        REv: From<StorageRequest>
+            + From<NetworkRequest<Message>>
+            + From<LinearChainAnnouncement>
+            + From<ContractRuntimeRequest>
+            + From<ChainspecLoaderRequest>
+            + Send,
+    {
+        outcomes
+            .into_iter()
+            .map(|outcome| match outcome {
+                Outcome::StoreBlockSignatures(block_signatures, should_upgrade) => {
+                    let mut effects = Effects::new();
+                    if self.record_signatures_stored(&block_signatures) {
+                        effects.extend(
+                            effect_builder
+                                .announce_block_finalized(Box::new(block_signatures.clone()))
+                                .ignore(),
+                        );
+                    }
+                    effects.extend(
+                        effect_builder
+                            .put_signatures_to_storage(block_signatures)
+                            .events(move |_| should_upgrade.then(|| Event::Upgrade).into_iter()),
+                    );
+                    effects
+                }
+                Outcome::StoreBlock(block, execution_results) => async move {
+                    let block_hash = *block.hash();
+                    effect_builder.put_block_to_storage(block.clone()).await;
+                    effect_builder
+                        .put_execution_results_to_storage(block_hash, execution_results)
+                        .await;
+                    block
+                }
+                .event(|block| Event::PutBlockResult { block }),
+                Outcome::Gossip(fs) => self.queue_gossip(effect_builder, fs),
+                Outcome::AnnounceSignature(fs) => {
+                    effect_builder.announce_finality_signature(fs).ignore()
+                }
+                Outcome::AnnounceBlock(block) => {
+                    effect_builder.announce_block_added(block).ignore()
+                }
+                Outcome::LoadSignatures(fs) => effect_builder
+                    .get_signatures_from_storage(fs.block_hash)
+                    .event(move |maybe_signatures| {
+                        Event::GetStoredFinalitySignaturesResult(fs, maybe_signatures.map(Box::new))
+                    }),
+                Outcome::VerifyIfBonded {
+                    new_fs,
+                    known_fs,
                     protocol_version,
-                )
-                .result(
-                    |is_bonded| Event::IsBonded(known_fs, new_fs, is_bonded),
-                    |error| {
-                        error!(%error, "checking in future eras returned an error.");
-                        panic!("couldn't check if validator is bonded")
-                    },
-                ),
-        })
-        .concat()
+                    latest_state_root_hash,
+                } => effect_builder
+                    .is_bonded_validator(
+                        new_fs.public_key.clone(),
+                        new_fs.era_id,
+                        latest_state_root_hash,
+                        protocol_version,
+                    )
+                    .result(
+                        |is_bonded| Event::IsBonded(known_fs, new_fs, is_bonded),
+                        |error| {
+                            error!(%error, "checking in future eras returned an error.");
+                            panic!("couldn't check if validator is bonded")
+                        },
+                    ),
+            })
+            .concat()
+    }
 }
 
 impl<REv> Component<REv> for LinearChainComponent
@@ -165,6 +380,7 @@ where
         _rng: &mut NodeRng,
         event: Self::Event,
     ) -> Effects<Self::Event> {
+        self.update_linear_chain_lag();
         match event {
             Event::NewLinearChainBlock {
                 block,
@@ -173,27 +389,43 @@ where
                 let outcomes = self
                     .linear_chain_state
                     .handle_new_block(block, execution_results);
-                outcomes_to_effects(effect_builder, outcomes)
+                self.outcomes_to_effects(effect_builder, outcomes)
             }
             Event::PutBlockResult { block } => {
                 let completion_duration = block.header().timestamp().elapsed().millis();
                 self.metrics
                     .block_completion_duration
                     .set(completion_duration as i64);
+                if self.shutdown_point.is_reached_by(block.header()) {
+                    info!(
+                        shutdown_point = %self.shutdown_point,
+                        height = block.height(),
+                        "stored block at shutdown point, shutting down"
+                    );
+                    self.reached_shutdown_point = true;
+                }
+                self.record_block_added(&block);
                 let outcomes = self.linear_chain_state.handle_put_block(block);
-                outcomes_to_effects(effect_builder, outcomes)
+                self.outcomes_to_effects(effect_builder, outcomes)
             }
             Event::FinalitySignatureReceived(fs, gossiped) => {
                 let outcomes = self
                     .linear_chain_state
                     .handle_finality_signature(fs, gossiped);
-                outcomes_to_effects(effect_builder, outcomes)
+                self.outcomes_to_effects(effect_builder, outcomes)
             }
+            Event::FinalitySignaturesReceived(signatures) => {
+                let outcomes = self
+                    .linear_chain_state
+                    .handle_finality_signatures(signatures);
+                self.outcomes_to_effects(effect_builder, outcomes)
+            }
+            Event::GossipFinalitySignatures => self.gossip_pending(effect_builder),
             Event::GetStoredFinalitySignaturesResult(fs, maybe_signatures) => {
                 let outcomes = self
                     .linear_chain_state
                     .handle_cached_signatures(maybe_signatures, fs);
-                outcomes_to_effects(effect_builder, outcomes)
+                self.outcomes_to_effects(effect_builder, outcomes)
             }
             Event::IsBonded(maybe_known_signatures, new_fs, is_bonded) => {
                 let outcomes = self.linear_chain_state.handle_is_bonded(
@@ -201,7 +433,7 @@ where
                     new_fs,
                     is_bonded,
                 );
-                outcomes_to_effects(effect_builder, outcomes)
+                self.outcomes_to_effects(effect_builder, outcomes)
             }
             Event::Upgrade => {
                 self.stop_for_upgrade = true;
@@ -212,10 +444,22 @@ where
                     .got_upgrade_activation_point(activation_point);
                 Effects::new()
             }
+            Event::ShutdownPointRequest(ShutdownPointRequest { update, responder }) => {
+                let result = match update {
+                    Some(shutdown_point) => self.set_shutdown_point(shutdown_point),
+                    None => Ok(self.shutdown_point),
+                };
+                responder.respond(result).ignore()
+            }
         }
     }
 }
 
+/// Converts the time difference to seconds, as reported by metrics.
+fn as_secs(time_diff: TimeDiff) -> f64 {
+    Duration::from(time_diff).as_secs_f64()
+}
+
 /// A trait to allow an `EffectBuilder` to be replaced in testing by a mock.
 #[async_trait]
 pub(crate) trait EraValidatorsGetter: Copy {
@@ -654,4 +898,29 @@ mod tests {
         check_validators(&fixture, 10, ERA_5);
         check_validators(&fixture, 11, ERA_5);
     }
+
+    #[test]
+    fn should_reach_shutdown_point() {
+        let mut rng = TestRng::new();
+        let mut header = |era_id, height, is_switch| {
+            Block::random_with_specifics(&mut rng, era_id, height, VERSION_1, is_switch, None)
+                .take_header()
+        };
+        let at_height = ShutdownPoint {
+            height: Some(10),
+            era: None,
+        };
+        assert!(!at_height.is_reached_by(&header(ERA_1, 9, false)));
+        assert!(at_height.is_reached_by(&header(ERA_1, 10, false)));
+
+        let at_era = ShutdownPoint {
+            height: None,
+            era: Some(ERA_1),
+        };
+        assert!(!at_era.is_reached_by(&header(ERA_1, 9, false)));
+        assert!(at_era.is_reached_by(&header(ERA_1, 10, true)));
+        assert!(at_era.is_reached_by(&header(ERA_2, 11, false)));
+
+        assert!(!ShutdownPoint::default().is_reached_by(&header(ERA_6, 100, true)));
+    }
 }
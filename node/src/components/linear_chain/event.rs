@@ -6,7 +6,10 @@ use std::{
 use casper_types::ExecutionResult;
 
 use crate::{
-    effect::incoming::FinalitySignatureIncoming,
+    effect::{
+        diagnostics_port::ShutdownPointRequest,
+        incoming::{FinalitySignatureIncoming, FinalitySignaturesIncoming},
+    },
     types::{ActivationPoint, Block, BlockSignatures, DeployHash, FinalitySignature},
 };
 
@@ -22,6 +25,10 @@ pub(crate) enum Event {
     /// Finality signature received.
     /// Not necessarily _new_ finality signature.
     FinalitySignatureReceived(Box<FinalitySignature>, bool),
+    /// A batch of finality signatures for a single block received from a peer.
+    FinalitySignaturesReceived(Box<BlockSignatures>),
+    /// Time to gossip the finality signatures queued since the last flush.
+    GossipFinalitySignatures,
     /// The result of putting a block to storage.
     PutBlockResult {
         /// The block.
@@ -35,6 +42,14 @@ pub(crate) enum Event {
     Upgrade,
     /// Got the result of checking for an upgrade activation point.
     GotUpgradeActivationPoint(ActivationPoint),
+    /// A request to show or change the shutdown point.
+    ShutdownPointRequest(ShutdownPointRequest),
+}
+
+impl From<ShutdownPointRequest> for Event {
+    fn from(request: ShutdownPointRequest) -> Self {
+        Event::ShutdownPointRequest(request)
+    }
 }
 
 impl From<FinalitySignatureIncoming> for Event {
@@ -43,6 +58,12 @@ impl From<FinalitySignatureIncoming> for Event {
     }
 }
 
+impl From<FinalitySignaturesIncoming> for Event {
+    fn from(incoming: FinalitySignaturesIncoming) -> Self {
+        Event::FinalitySignaturesReceived(incoming.message)
+    }
+}
+
 impl Display for Event {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -54,6 +75,15 @@ impl Display for Event {
                 "linear-chain new finality signature for block: {}, from: {}, external: {}",
                 fs.block_hash, fs.public_key, gossiped
             ),
+            Event::FinalitySignaturesReceived(signatures) => write!(
+                f,
+                "linear-chain new batch of {} finality signatures for block: {}",
+                signatures.proofs.len(),
+                signatures.block_hash
+            ),
+            Event::GossipFinalitySignatures => {
+                write!(f, "linear-chain gossip queued finality signatures")
+            }
             Event::PutBlockResult { .. } => write!(f, "linear-chain put-block result"),
             Event::GetStoredFinalitySignaturesResult(finality_signature, maybe_signatures) => {
                 write!(
@@ -76,6 +106,7 @@ impl Display for Event {
                 "linear chain got upgrade activation point {}",
                 activation_point
             ),
+            Event::ShutdownPointRequest(request) => write!(f, "linear chain: {}", request),
         }
     }
 }
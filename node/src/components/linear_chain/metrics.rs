@@ -1,10 +1,21 @@
-use prometheus::{IntGauge, Registry};
+use prometheus::{Gauge, Histogram, IntGauge, Registry};
 
-use crate::unregister_metric;
+use crate::{unregister_metric, utils};
+
+/// Buckets of the time from proposal to finality, from half a second to about 17 minutes.
+const TIME_TO_FINALITY_BUCKETS: (f64, f64, usize) = (0.5, 2.0, 12);
+/// Buckets of the interval between blocks, from one second to about 34 minutes.
+const BLOCK_INTERVAL_BUCKETS: (f64, f64, usize) = (1.0, 2.0, 12);
 
 #[derive(Debug)]
 pub(super) struct Metrics {
     pub(super) block_completion_duration: IntGauge,
+    /// Time from the proposal of a block until this node holds finality signatures proving it.
+    pub(super) time_to_finality: Histogram,
+    /// Interval between the timestamps of consecutive blocks.
+    pub(super) block_interval: Histogram,
+    /// Time since the proposal of the latest block stored by this node.
+    pub(super) linear_chain_lag: Gauge,
     /// Prometheus registry used to publish metrics.
     registry: Registry,
 }
@@ -16,8 +27,35 @@ impl Metrics {
             "time in milliseconds to execute a block, from finalizing it until stored locally",
         )?;
         registry.register(Box::new(block_completion_duration.clone()))?;
+
+        let (start, factor, count) = TIME_TO_FINALITY_BUCKETS;
+        let time_to_finality = utils::register_histogram_metric(
+            registry,
+            "block_time_to_finality_seconds",
+            "time in seconds from the proposal of a block until the node holds finality signatures \
+             from validators exceeding the finality threshold",
+            prometheus::exponential_buckets(start, factor, count)?,
+        )?;
+
+        let (start, factor, count) = BLOCK_INTERVAL_BUCKETS;
+        let block_interval = utils::register_histogram_metric(
+            registry,
+            "block_interval_seconds",
+            "time in seconds between the proposals of consecutive blocks",
+            prometheus::exponential_buckets(start, factor, count)?,
+        )?;
+
+        let linear_chain_lag = Gauge::new(
+            "linear_chain_lag_seconds",
+            "time in seconds since the proposal of the latest block stored locally",
+        )?;
+        registry.register(Box::new(linear_chain_lag.clone()))?;
+
         Ok(Self {
             block_completion_duration,
+            time_to_finality,
+            block_interval,
+            linear_chain_lag,
             registry: registry.clone(),
         })
     }
@@ -26,5 +64,8 @@ impl Metrics {
 impl Drop for Metrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.block_completion_duration);
+        unregister_metric!(self.registry, self.time_to_finality);
+        unregister_metric!(self.registry, self.block_interval);
+        unregister_metric!(self.registry, self.linear_chain_lag);
     }
 }
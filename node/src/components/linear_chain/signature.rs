@@ -34,8 +34,4 @@ impl Signature {
             Signature::Local(fs) | Signature::External(fs) => fs,
         }
     }
-
-    pub(super) fn is_local(&self) -> bool {
-        matches!(self, Signature::Local(_))
-    }
 }
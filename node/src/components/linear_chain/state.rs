@@ -35,6 +35,8 @@ pub(crate) struct LinearChain {
     /// The fraction of validators, by weight, that have to sign a block to prove its finality.
     #[data_size(skip)]
     finality_threshold_fraction: Ratio<u64>,
+    /// The era from which finality signatures must be signed in their signing context.
+    signing_context_activation_era: Option<EraId>,
     /// The next upgrade activation point. When the key block for this era is fully signed, the
     /// linear chain component indicates that the node should restart for an upgrade.
     next_upgrade_activation_point: Option<ActivationPoint>,
@@ -49,7 +51,7 @@ pub(super) enum Outcome {
     StoreBlock(Box<Block>, HashMap<DeployHash, ExecutionResult>),
     // Read finality signatures for the block from storage.
     LoadSignatures(Box<FinalitySignature>),
-    // Gossip finality signature to peers.
+    // Queue finality signature for batched gossip to peers.
     Gossip(Box<FinalitySignature>),
     // Create a reactor announcement about new (valid) finality signatures.
     AnnounceSignature(Box<FinalitySignature>),
@@ -73,6 +75,7 @@ impl LinearChain {
         auction_delay: u64,
         unbonding_delay: u64,
         finality_threshold_fraction: Ratio<u64>,
+        signing_context_activation_era: Option<EraId>,
         next_upgrade_activation_point: Option<ActivationPoint>,
     ) -> Self {
         LinearChain {
@@ -85,9 +88,17 @@ impl LinearChain {
             unbonding_delay,
             next_upgrade_activation_point,
             finality_threshold_fraction,
+            signing_context_activation_era,
         }
     }
 
+    /// Returns whether finality signatures in the given era must be signed in their signing
+    /// context.
+    fn is_signing_context_active(&self, era_id: EraId) -> bool {
+        self.signing_context_activation_era
+            .map_or(false, |activation_era| era_id >= activation_era)
+    }
+
     /// Handles registering an upgrade activation point.
     pub(super) fn got_upgrade_activation_point(&mut self, activation_point: ActivationPoint) {
         debug!(?activation_point, "got an activation point");
@@ -132,16 +143,30 @@ impl LinearChain {
     /// Tries to add the finality signature to the collection of pending finality signatures.
     /// Returns true if added successfully, otherwise false.
     fn add_pending_finality_signature(&mut self, fs: FinalitySignature, gossiped: bool) -> bool {
+        if !self.is_acceptable(&fs) {
+            return false;
+        }
+        if let Err(err) = fs.verify(self.is_signing_context_active(fs.era_id)) {
+            warn!(block_hash=%fs.block_hash, public_key=%fs.public_key, %err,
+                "received invalid finality signature");
+            return false;
+        }
+        self.insert_pending_finality_signature(fs, gossiped)
+    }
+
+    /// Returns whether the finality signature is for an acceptable era and neither pending nor
+    /// already known. Does not verify the signature itself.
+    fn is_acceptable(&self, fs: &FinalitySignature) -> bool {
         let FinalitySignature {
             block_hash,
             public_key,
             era_id,
             ..
-        } = fs.clone();
+        } = fs;
         if let Some(latest_block) = self.latest_block.as_ref() {
             let current_era = latest_block.header().next_block_era_id();
-            if era_id < self.lowest_acceptable_era_id(current_era)
-                || era_id > self.highest_acceptable_era_id(current_era)
+            if *era_id < self.lowest_acceptable_era_id(current_era)
+                || *era_id > self.highest_acceptable_era_id(current_era)
             {
                 warn!(
                     era_id=%era_id.value(),
@@ -152,21 +177,22 @@ impl LinearChain {
                 return false;
             }
         }
-        if self.is_pending(&fs) {
-            debug!(block_hash=%fs.block_hash, public_key=%fs.public_key,
-                "finality signature already pending");
+        if self.is_pending(fs) {
+            debug!(%block_hash, %public_key, "finality signature already pending");
             return false;
         }
-        if !self.is_new(&fs) {
-            debug!(block_hash=%fs.block_hash, public_key=%fs.public_key,
-                "finality signature is already known");
+        if !self.is_new(fs) {
+            debug!(%block_hash, %public_key, "finality signature is already known");
             return false;
         }
-        if let Err(err) = fs.verify() {
-            warn!(%block_hash, %public_key, %err, "received invalid finality signature");
-            return false;
-        }
-        debug!(%block_hash, %public_key, "received new finality signature");
+        true
+    }
+
+    /// Adds an already verified finality signature to the collection of pending finality
+    /// signatures. Returns true if added successfully, otherwise false.
+    fn insert_pending_finality_signature(&mut self, fs: FinalitySignature, gossiped: bool) -> bool {
+        debug!(block_hash=%fs.block_hash, public_key=%fs.public_key,
+            "received new finality signature");
         let signature = if gossiped {
             Signature::External(Box::new(fs))
         } else {
@@ -210,7 +236,7 @@ impl LinearChain {
         self.protocol_version
     }
 
-    fn latest_block(&self) -> &Option<Block> {
+    pub(super) fn latest_block(&self) -> &Option<Block> {
         &self.latest_block
     }
 
@@ -268,9 +294,7 @@ impl LinearChain {
                 should_upgrade,
             ));
             for signature in signatures {
-                if signature.is_local() {
-                    outcomes.push(Outcome::Gossip(Box::new(signature.to_inner().clone())));
-                }
+                outcomes.push(Outcome::Gossip(Box::new(signature.to_inner().clone())));
                 outcomes.push(Outcome::AnnounceSignature(signature.take()));
             }
         };
@@ -287,26 +311,39 @@ impl LinearChain {
             None => return false, // The signed block is not a key block.
             Some(signed_kb_info) => signed_kb_info,
         };
-        if self.next_upgrade_activation_point
-            != Some(ActivationPoint::EraId(signed_kb_info.era_id()))
-        {
+        let is_activation_point =
+            self.next_upgrade_activation_point
+                .map_or(false, |activation_point| {
+                    activation_point
+                        .is_activated_by(signed_kb_info.era_id(), signed_kb_info.era_start())
+                });
+        if !is_activation_point {
             return false; // This is not the next activation point.
         }
-        let era_kb_info = match self.key_block_info.get(&signatures.era_id) {
-            None => {
-                warn!(?signed_kb_info, "missing previous key block info");
-                return false;
-            }
-            Some(era_kb_info) => era_kb_info,
-        };
-        matches!(
-            linear_chain::check_sufficient_block_signatures(
-                era_kb_info.validator_weights(),
-                self.finality_threshold_fraction,
-                Some(signatures),
-            ),
-            Ok(()) | Err(BlockSignatureError::TooManySignatures { .. })
-        )
+        if !self.key_block_info.contains_key(&signatures.era_id) {
+            warn!(?signed_kb_info, "missing previous key block info");
+            return false;
+        }
+        self.is_finalized_by(signatures)
+    }
+
+    /// Returns whether the signatures prove the finality of the signed block, i.e. whether they
+    /// are from validators exceeding the finality threshold by weight in the block's era.
+    ///
+    /// Returns `false` if the validators of the era are not known.
+    pub(super) fn is_finalized_by(&self, signatures: &BlockSignatures) -> bool {
+        self.key_block_info
+            .get(&signatures.era_id)
+            .map_or(false, |era_kb_info| {
+                matches!(
+                    linear_chain::check_sufficient_block_signatures(
+                        era_kb_info.validator_weights(),
+                        self.finality_threshold_fraction,
+                        Some(signatures),
+                    ),
+                    Ok(()) | Err(BlockSignatureError::TooManySignatures { .. })
+                )
+            })
     }
 
     pub(super) fn handle_finality_signature(
@@ -314,13 +351,61 @@ impl LinearChain {
         fs: Box<FinalitySignature>,
         gossiped: bool,
     ) -> Outcomes {
-        let FinalitySignature { block_hash, .. } = *fs;
         if !self.add_pending_finality_signature(*fs.clone(), gossiped) {
             // If we did not add the signature it means it's either incorrect or we already
             // know it.
             return vec![];
         }
-        match self.get_signatures(&block_hash) {
+        self.handle_pending_finality_signature(fs)
+    }
+
+    /// Handles a batch of finality signatures for a single block received from a peer.
+    ///
+    /// Signatures which are already pending or known are discarded before any are verified, so
+    /// the overlap between batches relayed by different peers costs no signature checks. Invalid
+    /// signatures are dropped individually without affecting the rest of the batch.
+    pub(super) fn handle_finality_signatures(
+        &mut self,
+        signatures: Box<BlockSignatures>,
+    ) -> Outcomes {
+        let BlockSignatures {
+            block_hash,
+            era_id,
+            proofs,
+        } = *signatures;
+        let verified = proofs
+            .into_iter()
+            .map(|(public_key, signature)| FinalitySignature {
+                block_hash,
+                era_id,
+                signature,
+                public_key,
+            })
+            .filter(|fs| self.is_acceptable(fs))
+            .filter(
+                |fs| match fs.verify(self.is_signing_context_active(era_id)) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        warn!(%block_hash, public_key=%fs.public_key, %err,
+                        "received invalid finality signature in batch");
+                        false
+                    }
+                },
+            )
+            .collect_vec();
+        let mut outcomes = vec![];
+        for fs in verified {
+            if self.insert_pending_finality_signature(fs.clone(), true) {
+                outcomes.extend(self.handle_pending_finality_signature(Box::new(fs)));
+            }
+        }
+        outcomes
+    }
+
+    /// Continues handling a finality signature which has just been added to the pending
+    /// collection.
+    fn handle_pending_finality_signature(&mut self, fs: Box<FinalitySignature>) -> Outcomes {
+        match self.get_signatures(&fs.block_hash) {
             // Not found in the cache, look in the storage.
             None => vec![Outcome::LoadSignatures(fs)],
             // We know about the block but we haven't seen any signatures for it yet.
@@ -423,14 +508,12 @@ impl LinearChain {
                 debug!(hash = %known_signatures.block_hash, "storing finality signatures");
                 // Announce new finality signatures for other components to pick up.
                 let mut outcomes = vec![Outcome::AnnounceSignature(new_fs.clone())];
-                if let Some(signature) = self.remove_from_pending_fs(&*new_fs) {
+                if self.remove_from_pending_fs(&*new_fs).is_some() {
                     // This shouldn't return `None` as we added the `fs` to the pending collection
                     // when we received it. If it _is_ `None` then a concurrent
-                    // flow must have already removed it. If it's a signature
-                    // created by this node, gossip it.
-                    if signature.is_local() {
-                        outcomes.push(Outcome::Gossip(new_fs.clone()));
-                    }
+                    // flow must have already removed it. Otherwise gossip it on, regardless of
+                    // whether it was created by this node or learned from a peer.
+                    outcomes.push(Outcome::Gossip(new_fs.clone()));
                 };
                 let should_upgrade = self.should_upgrade(&*known_signatures);
                 outcomes.push(Outcome::StoreBlockSignatures(
@@ -461,7 +544,14 @@ mod tests {
     fn new_block_no_sigs() {
         let mut rng = TestRng::new();
         let protocol_version = ProtocolVersion::V1_0_0;
-        let mut lc = LinearChain::new(protocol_version, 1u64, 1u64, Ratio::new(1, 3), None);
+        let mut lc = LinearChain::new(
+            protocol_version,
+            1u64,
+            1u64,
+            Ratio::new(1, 3),
+            Some(EraId::new(0)),
+            None,
+        );
         let block = Block::random(&mut rng);
         let execution_results = HashMap::new();
         let new_block_outcomes =
@@ -511,7 +601,14 @@ mod tests {
     fn new_block_unvalidated_pending_sigs() {
         let mut rng = TestRng::new();
         let protocol_version = ProtocolVersion::V1_0_0;
-        let mut lc = LinearChain::new(protocol_version, 1u64, 1u64, Ratio::new(1, 3), None);
+        let mut lc = LinearChain::new(
+            protocol_version,
+            1u64,
+            1u64,
+            Ratio::new(1, 3),
+            Some(EraId::new(0)),
+            None,
+        );
         let block = Block::random(&mut rng);
         let block_hash = *block.hash();
         let block_era = block.header().era_id();
@@ -550,7 +647,14 @@ mod tests {
     fn new_block_bonded_pending_sigs() {
         let mut rng = TestRng::new();
         let protocol_version = ProtocolVersion::V1_0_0;
-        let mut lc = LinearChain::new(protocol_version, 1u64, 1u64, Ratio::new(1, 3), None);
+        let mut lc = LinearChain::new(
+            protocol_version,
+            1u64,
+            1u64,
+            Ratio::new(1, 3),
+            Some(EraId::new(0)),
+            None,
+        );
         let block = Box::new(Block::random(&mut rng));
         let block_hash = *block.hash();
         let block_era = block.header().era_id();
@@ -576,9 +680,10 @@ mod tests {
             block_signatures.insert_proof(sig_a.public_key.clone(), sig_a.signature);
             block_signatures.insert_proof(sig_b.public_key.clone(), sig_b.signature);
             tmp.push(Outcome::StoreBlockSignatures(block_signatures, false));
-            // Only `sig_a` was created locally and we don't "regossip" incoming signatures.
+            // Both local and incoming signatures are queued to be gossiped on.
             tmp.push(Outcome::Gossip(Box::new(sig_a.clone())));
             tmp.push(Outcome::AnnounceSignature(Box::new(sig_a.clone())));
+            tmp.push(Outcome::Gossip(Box::new(sig_b.clone())));
             tmp.push(Outcome::AnnounceSignature(Box::new(sig_b.clone())));
             tmp.push(Outcome::AnnounceBlock(block));
             tmp
@@ -607,7 +712,14 @@ mod tests {
     fn pending_sig_rejected() {
         let mut rng = TestRng::new();
         let protocol_version = ProtocolVersion::V1_0_0;
-        let mut lc = LinearChain::new(protocol_version, 1u64, 1u64, Ratio::new(1, 3), None);
+        let mut lc = LinearChain::new(
+            protocol_version,
+            1u64,
+            1u64,
+            Ratio::new(1, 3),
+            Some(EraId::new(0)),
+            None,
+        );
         let block_hash = BlockHash::random(&mut rng);
         let valid_sig = FinalitySignature::random_for_block(block_hash, 0);
         let handle_sig_outcomes = lc.handle_finality_signature(Box::new(valid_sig.clone()), false);
@@ -660,7 +772,14 @@ mod tests {
         let _ = logging::init();
         let mut rng = TestRng::new();
         let protocol_version = ProtocolVersion::V1_0_0;
-        let mut lc = LinearChain::new(protocol_version, 1u64, 1u64, Ratio::new(1, 3), None);
+        let mut lc = LinearChain::new(
+            protocol_version,
+            1u64,
+            1u64,
+            Ratio::new(1, 3),
+            Some(EraId::new(0)),
+            None,
+        );
         let block = Block::random(&mut rng);
         let valid_sig =
             FinalitySignature::random_for_block(*block.hash(), block.header().era_id().value());
@@ -684,6 +803,7 @@ mod tests {
             auction_delay,
             unbonding_delay,
             Ratio::new(1, 3),
+            Some(EraId::new(0)),
             None,
         );
 
@@ -724,6 +844,49 @@ mod tests {
         assert!(outcomes.is_empty())
     }
 
+    #[test]
+    fn batched_sigs_filtered_before_verification() {
+        let _ = logging::init();
+        let mut rng = TestRng::new();
+        let protocol_version = ProtocolVersion::V1_0_0;
+        let mut lc = LinearChain::new(
+            protocol_version,
+            1u64,
+            1u64,
+            Ratio::new(1, 3),
+            Some(EraId::new(0)),
+            None,
+        );
+        let block = Box::new(Block::random(&mut rng));
+        let block_hash = *block.hash();
+        let block_era = block.header().era_id();
+        lc.handle_put_block(block);
+
+        // One of the signatures in the batch is already pending.
+        let pending_sig = add_pending(&mut lc, block_hash, block_era, false);
+        let sig_a = FinalitySignature::random_for_block(block_hash, block_era.value());
+        let sig_b = FinalitySignature::random_for_block(block_hash, block_era.value());
+        let (_, other_pub_key) = generate_ed25519_keypair();
+        let mut invalid_sig = FinalitySignature::random_for_block(block_hash, block_era.value());
+        invalid_sig.public_key = other_pub_key;
+
+        let mut batch = BlockSignatures::new(block_hash, block_era);
+        for sig in [&pending_sig, &sig_a, &sig_b, &invalid_sig] {
+            batch.insert_proof(sig.public_key.clone(), sig.signature);
+        }
+        let outcomes = lc.handle_finality_signatures(Box::new(batch.clone()));
+        // Only the new, valid signatures should be processed further.
+        assert_equal(
+            vec![
+                Outcome::LoadSignatures(Box::new(sig_a)),
+                Outcome::LoadSignatures(Box::new(sig_b)),
+            ],
+            outcomes,
+        );
+        // Receiving the same batch again is a no-op.
+        assert!(lc.handle_finality_signatures(Box::new(batch)).is_empty());
+    }
+
     #[test]
     fn new_block_then_own_sig() {
         let _ = logging::init();
@@ -736,6 +899,7 @@ mod tests {
             auction_delay,
             unbonding_delay,
             Ratio::new(1, 3),
+            Some(EraId::new(0)),
             None,
         );
 
@@ -794,6 +958,7 @@ mod tests {
             auction_delay,
             unbonding_delay,
             Ratio::new(1, 3),
+            Some(EraId::new(0)),
             Some(ActivationPoint::EraId(3.into())),
         );
 
@@ -842,7 +1007,7 @@ mod tests {
             .iter()
             .map(|sk| {
                 let pk = PublicKey::from(sk);
-                Box::new(FinalitySignature::new(*block.hash(), era_id, sk, pk))
+                Box::new(FinalitySignature::new(*block.hash(), era_id, sk, pk, true))
             })
             .collect();
 
@@ -872,6 +1037,7 @@ mod tests {
         let outcomes =
             lc.handle_cached_signatures(Some(stored_sigs.clone()), signatures[1].clone());
         stored_sigs.insert_proof(signatures[1].public_key.clone(), signatures[1].signature);
+        assert!(!lc.is_finalized_by(&stored_sigs));
         assert_equal(
             vec![
                 Outcome::AnnounceSignature(signatures[1].clone()),
@@ -884,6 +1050,7 @@ mod tests {
         let outcomes =
             lc.handle_cached_signatures(Some(stored_sigs.clone()), signatures[2].clone());
         stored_sigs.insert_proof(signatures[2].public_key.clone(), signatures[2].signature);
+        assert!(lc.is_finalized_by(&stored_sigs));
         assert_equal(
             vec![
                 Outcome::AnnounceSignature(signatures[2].clone()),
@@ -907,6 +1074,7 @@ mod tests {
             auction_delay,
             unbonding_delay,
             Ratio::new(1, 3),
+            Some(EraId::new(0)),
             Some(ActivationPoint::EraId(3.into())),
         );
 
@@ -954,7 +1122,7 @@ mod tests {
             .iter()
             .map(|sk| {
                 let pk = PublicKey::from(sk);
-                Box::new(FinalitySignature::new(*block.hash(), era_id, sk, pk))
+                Box::new(FinalitySignature::new(*block.hash(), era_id, sk, pk, true))
             })
             .collect();
 
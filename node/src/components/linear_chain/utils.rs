@@ -203,7 +203,7 @@ mod tests {
     use casper_types::{crypto, testing::TestRng, EraId, SecretKey};
 
     use super::*;
-    use crate::types::BlockHash;
+    use crate::types::{BlockHash, FinalitySignature};
 
     const TEST_VALIDATOR_WEIGHT: usize = 1;
 
@@ -231,11 +231,12 @@ mod tests {
 
         let block_hash = BlockHash::random(rng);
 
-        let mut sigs = BlockSignatures::new(block_hash, EraId::from(era));
+        let era_id = EraId::from(era);
+        let mut sigs = BlockSignatures::new(block_hash, era_id);
 
         for (pub_key, secret_key) in validators.iter().take(n_sigs) {
-            let sig = crypto::sign(block_hash, secret_key, pub_key);
-            sigs.insert_proof(pub_key.clone(), sig);
+            let sig = FinalitySignature::new(block_hash, era_id, secret_key, pub_key.clone(), true);
+            sigs.insert_proof(pub_key.clone(), sig.signature);
         }
 
         sigs
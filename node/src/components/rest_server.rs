@@ -17,8 +17,12 @@
 //!     example: curl -X GET 'http://<ip>:8888/status'
 //! /metrics : time series data collected from the internals of the node being queried.
 //!     example: curl -X GET 'http://<ip>:8888/metrics'
+//! /dry-run : if enabled in the config, previews the effects of an unsigned deploy posted as JSON.
+//!     example: curl -X POST -H 'Content-Type: application/json' -d @deploy.json \
+//!     'http://<ip>:8888/dry-run'
 
 mod config;
+mod dry_run;
 mod event;
 mod filters;
 mod http_server;
@@ -27,6 +31,7 @@ use std::{convert::Infallible, fmt::Debug, time::Instant};
 
 use datasize::DataSize;
 use futures::{future::BoxFuture, join, FutureExt};
+use hyper::Server;
 use tokio::{sync::oneshot, task::JoinHandle};
 use tracing::{debug, error, info, warn};
 
@@ -37,14 +42,15 @@ use crate::{
     components::rpc_server::rpcs::docs::OPEN_RPC_SCHEMA,
     effect::{
         requests::{
-            ChainspecLoaderRequest, ConsensusRequest, MetricsRequest, NetworkInfoRequest,
-            NodeStateRequest, RestRequest, StorageRequest,
+            ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, MetricsRequest,
+            NetworkInfoRequest, NodeStateRequest, RestRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
     reactor::Finalize,
+    tls,
     types::StatusFeed,
-    utils::{self, ListeningError},
+    utils::{self, ListeningError, WithDir},
     NodeRng,
 };
 pub use config::Config;
@@ -58,6 +64,7 @@ pub(crate) trait ReactorEventT:
     + From<StorageRequest>
     + From<ChainspecLoaderRequest>
     + From<ConsensusRequest>
+    + From<ContractRuntimeRequest>
     + From<MetricsRequest>
     + From<NodeStateRequest>
     + Send
@@ -71,6 +78,7 @@ impl<REv> ReactorEventT for REv where
         + From<StorageRequest>
         + From<ChainspecLoaderRequest>
         + From<ConsensusRequest>
+        + From<ContractRuntimeRequest>
         + From<MetricsRequest>
         + From<NodeStateRequest>
         + Send
@@ -98,7 +106,7 @@ pub(crate) struct RestServer {
 
 impl RestServer {
     pub(crate) fn new<REv>(
-        config: Config,
+        cfg: &WithDir<Config>,
         effect_builder: EffectBuilder<REv>,
         api_version: ProtocolVersion,
         node_startup_instant: Instant,
@@ -106,20 +114,41 @@ impl RestServer {
     where
         REv: ReactorEventT,
     {
+        let config = cfg.value();
         if !config.enable_server {
             return Ok(RestServer { inner_rest: None });
         }
 
         let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
 
-        let builder = utils::start_listening(&config.address)?;
-        let server_join_handle = Some(tokio::spawn(http_server::run(
-            builder,
-            effect_builder,
-            api_version,
-            shutdown_receiver,
-            config.qps_limit,
-        )));
+        let incoming = utils::bind_incoming(&config.address)?;
+        let local_addr = incoming.local_addr();
+        let server_join_handle = match config.tls {
+            None => tokio::spawn(http_server::run(
+                Server::builder(incoming),
+                local_addr,
+                effect_builder,
+                api_version,
+                shutdown_receiver,
+                config.qps_limit,
+                config.enable_dry_run,
+            )),
+            Some(ref tls_config) => {
+                let acceptor =
+                    tls::create_mutual_tls_acceptor(&cfg.map_ref(|_| tls_config.clone()))
+                        .map_err(ListeningError::Tls)?;
+                tokio::spawn(http_server::run(
+                    Server::builder(utils::mutual_tls_incoming(incoming, acceptor)),
+                    local_addr,
+                    effect_builder,
+                    api_version,
+                    shutdown_receiver,
+                    config.qps_limit,
+                    config.enable_dry_run,
+                ))
+            }
+        };
+        let server_join_handle = Some(server_join_handle);
 
         Ok(RestServer {
             inner_rest: Some(InnerRestServer {
@@ -158,12 +187,14 @@ where
                     let (
                         last_added_block,
                         peers,
+                        peer_reputations,
                         chainspec_info,
                         consensus_status,
-                        node_state,
+                        (node_state, sync_status),
                     ) = join!(
                         effect_builder.get_highest_block_from_storage(),
                         effect_builder.network_peers(),
+                        effect_builder.network_peer_reputations(),
                         effect_builder.get_chainspec_info(),
                         effect_builder.consensus_status(),
                         effect_builder.get_node_state()
@@ -172,10 +203,12 @@ where
                     let status_feed = StatusFeed::new(
                         last_added_block,
                         peers,
+                        peer_reputations,
                         chainspec_info,
                         consensus_status,
                         node_uptime,
                         node_state,
+                        sync_status,
                     );
                     responder.respond(status_feed).await;
                 }
@@ -1,6 +1,8 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::tls::MutualTlsConfig;
+
 /// Default binding address for the REST HTTP server.
 ///
 /// Uses a fixed port per node, but binds on any interface.
@@ -21,6 +23,15 @@ pub struct Config {
 
     /// Max rate limit in qps.
     pub qps_limit: u64,
+
+    /// If set, the server only accepts TLS connections from clients presenting a certificate
+    /// issued by the configured authority.
+    #[serde(default)]
+    pub tls: Option<MutualTlsConfig>,
+
+    /// Setting to enable the `/dry-run` endpoint, which speculatively executes unsigned deploys.
+    #[serde(default)]
+    pub enable_dry_run: bool,
 }
 
 impl Config {
@@ -30,6 +41,8 @@ impl Config {
             enable_server: true,
             address: DEFAULT_ADDRESS.to_string(),
             qps_limit: DEFAULT_QPS_LIMIT,
+            tls: None,
+            enable_dry_run: false,
         }
     }
 }
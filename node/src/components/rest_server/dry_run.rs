@@ -0,0 +1,244 @@
+//! Dry runs of unsigned deploys, for wallets to preview the effects of a deploy before signing it.
+//!
+//! The deploy is approved by its account with a placeholder signature and executed speculatively
+//! on top of a block's global state.  Unlike the `speculative_exec` JSON-RPC, the raw execution
+//! result is condensed into the purse balances it changes, the transfers and contract messages it
+//! emits, and its cost.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use warp::http::StatusCode;
+
+use casper_execution_engine::core::engine_state;
+use casper_types::{
+    contract_messages::Message, AccessRights, ExecutableDeployItem, ExecutionResult, Key,
+    ProtocolVersion, PublicKey, TimeDiff, Timestamp, TransferV2, Transform, URef, URefAddr, U512,
+};
+
+use super::ReactorEventT;
+use crate::{
+    components::contract_runtime::SpeculativeExecutionState,
+    effect::EffectBuilder,
+    rpcs::chain::BlockIdentifier,
+    types::{Block, BlockHash, Deploy, Transaction},
+};
+
+/// The time-to-live of the deploy if not given.
+const DEFAULT_TTL: TimeDiff = TimeDiff::from_seconds(30 * 60);
+
+/// The gas price of the deploy if not given.
+const DEFAULT_GAS_PRICE: u64 = 1;
+
+/// An unsigned deploy to dry-run.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct DryRunRequest {
+    /// The block on top of whose global state the deploy is executed, or the highest block if not
+    /// given.
+    #[serde(default)]
+    block_identifier: Option<BlockIdentifier>,
+    /// The account the deploy is executed as.
+    account: PublicKey,
+    /// The deploy's timestamp, or the current time if not given.
+    #[serde(default = "Timestamp::now")]
+    timestamp: Timestamp,
+    /// The deploy's time-to-live.
+    #[serde(default = "default_ttl")]
+    ttl: TimeDiff,
+    /// The deploy's gas price.
+    #[serde(default = "default_gas_price")]
+    gas_price: u64,
+    /// The name of the chain the deploy is intended for.
+    #[serde(default)]
+    chain_name: String,
+    /// The payment code.
+    payment: ExecutableDeployItem,
+    /// The session code.
+    session: ExecutableDeployItem,
+}
+
+fn default_ttl() -> TimeDiff {
+    DEFAULT_TTL
+}
+
+fn default_gas_price() -> u64 {
+    DEFAULT_GAS_PRICE
+}
+
+/// The predicted effects of a deploy.
+#[derive(Debug, Serialize)]
+pub(super) struct DryRunResult {
+    /// The REST API version.
+    api_version: ProtocolVersion,
+    /// Hash of the block on top of which the deploy was executed.
+    block_hash: BlockHash,
+    /// The error the deploy would fail with, if any.  The effects of the payment code persist even
+    /// if the deploy fails.
+    error_message: Option<String>,
+    /// The estimated cost of the deploy.
+    cost: U512,
+    /// The balances of all purses changed by the deploy.
+    balance_changes: Vec<BalanceChange>,
+    /// The transfers the deploy would make.
+    transfers: Vec<TransferV2>,
+    /// The messages contracts would emit, along with the keys they would be stored under.
+    messages: Vec<EmittedMessage>,
+}
+
+/// The balance of a purse before and after a deploy.
+#[derive(Debug, Serialize)]
+struct BalanceChange {
+    /// The purse's balance key.
+    balance_key: Key,
+    /// The balance before the deploy, in motes.
+    before: U512,
+    /// The balance after the deploy, in motes.
+    after: U512,
+}
+
+/// A message emitted by a contract.
+#[derive(Debug, Serialize)]
+struct EmittedMessage {
+    /// The key the message is stored under.
+    key: Key,
+    /// The message's payload and its checksum.
+    message: Message,
+}
+
+/// Error dry-running a deploy.
+#[derive(Debug, Error)]
+pub(super) enum DryRunError {
+    /// The requested block is not available on this node.
+    #[error("block not available on this node")]
+    NoSuchBlock,
+    /// The execution engine failed to execute the deploy.
+    #[error("failed to execute deploy: {0}")]
+    Execution(#[from] engine_state::Error),
+    /// The execution engine returned no result.
+    #[error("execution returned no result")]
+    NoResult,
+}
+
+impl DryRunError {
+    /// Returns the HTTP status code to respond with.
+    pub(super) fn status_code(&self) -> StatusCode {
+        match self {
+            DryRunError::NoSuchBlock => StatusCode::NOT_FOUND,
+            DryRunError::Execution(engine_state::Error::RootNotFound(_)) => StatusCode::NOT_FOUND,
+            DryRunError::Execution(
+                engine_state::Error::WasmPreprocessing(_)
+                | engine_state::Error::InvalidDeployItemVariant(_)
+                | engine_state::Error::Deploy,
+            ) => StatusCode::UNPROCESSABLE_ENTITY,
+            DryRunError::Execution(_) | DryRunError::NoResult => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Executes the deploy speculatively and condenses its execution result.
+pub(super) async fn dry_run<REv: ReactorEventT>(
+    effect_builder: EffectBuilder<REv>,
+    api_version: ProtocolVersion,
+    request: DryRunRequest,
+) -> Result<DryRunResult, DryRunError> {
+    // This request is restricted by the block availability index.
+    let only_from_available_block_range = true;
+
+    let maybe_block: Option<Block> = match request.block_identifier {
+        None => effect_builder.get_highest_block_from_storage().await,
+        Some(BlockIdentifier::Hash(block_hash)) => effect_builder
+            .get_block_with_metadata_from_storage(block_hash, only_from_available_block_range)
+            .await
+            .map(|block_with_metadata| block_with_metadata.block),
+        Some(BlockIdentifier::Height(block_height)) => effect_builder
+            .get_block_at_height_with_metadata_from_storage(
+                block_height,
+                only_from_available_block_range,
+            )
+            .await
+            .map(|block_with_metadata| block_with_metadata.block),
+    };
+    let block = maybe_block.ok_or(DryRunError::NoSuchBlock)?;
+    let block_hash = *block.hash();
+    let state_root_hash = *block.state_root_hash();
+
+    let deploy = Deploy::new_with_dummy_approval(
+        request.timestamp,
+        request.ttl,
+        request.gas_price,
+        request.chain_name,
+        request.payment,
+        request.session,
+        request.account,
+    );
+    let execution_prestate = SpeculativeExecutionState {
+        state_root_hash,
+        block_time: block.header().timestamp(),
+        protocol_version: block.protocol_version(),
+    };
+    let (execution_result, _) = effect_builder
+        .speculative_execute_transaction(execution_prestate, Transaction::Deploy(deploy), false)
+        .await?
+        .ok_or(DryRunError::NoResult)?;
+
+    let (effect, cost, error_message) = match execution_result {
+        ExecutionResult::Success { effect, cost, .. } => (effect, cost, None),
+        ExecutionResult::Failure {
+            effect,
+            cost,
+            error_message,
+            ..
+        } => (effect, cost, Some(error_message)),
+    };
+
+    let mut balance_transforms: BTreeMap<URefAddr, Vec<Transform>> = BTreeMap::new();
+    let mut transfers = Vec::new();
+    let mut messages = Vec::new();
+    for entry in effect.transforms {
+        match (Key::from_formatted_str(&entry.key), entry.transform) {
+            (Ok(Key::Balance(purse_addr)), transform) => balance_transforms
+                .entry(purse_addr)
+                .or_default()
+                .push(transform),
+            (_, Transform::WriteTransfer(transfer)) => transfers.push(TransferV2::from(transfer)),
+            (_, Transform::WriteTransferV2(transfer)) => transfers.push(transfer),
+            (Ok(key), Transform::WriteMessage(message)) => {
+                messages.push(EmittedMessage { key, message })
+            }
+            _ => {}
+        }
+    }
+
+    let mut balance_changes = Vec::with_capacity(balance_transforms.len());
+    for (purse_addr, transforms) in balance_transforms {
+        // A purse created by the deploy has no balance yet.
+        let before = effect_builder
+            .check_purse_balance(state_root_hash, URef::new(purse_addr, AccessRights::NONE))
+            .await
+            .unwrap_or_default();
+        let after = transforms
+            .into_iter()
+            .fold(before, |balance, transform| match transform {
+                Transform::WriteCLValue(cl_value) => cl_value.into_t().unwrap_or(balance),
+                Transform::AddUInt512(amount) => balance.saturating_add(amount),
+                _ => balance,
+            });
+        balance_changes.push(BalanceChange {
+            balance_key: Key::Balance(purse_addr),
+            before,
+            after,
+        });
+    }
+
+    Ok(DryRunResult {
+        api_version,
+        block_hash,
+        error_message,
+        cost,
+        balance_changes,
+        transfers,
+        messages,
+    })
+}
@@ -1,7 +1,7 @@
 use futures::FutureExt;
 use http::Response;
 use hyper::Body;
-use tracing::warn;
+use tracing::{info, warn};
 use warp::{
     filters::BoxedFilter,
     http::StatusCode,
@@ -12,7 +12,10 @@ use warp::{
 
 use casper_types::ProtocolVersion;
 
-use super::ReactorEventT;
+use super::{
+    dry_run::{self, DryRunRequest},
+    ReactorEventT,
+};
 use crate::{
     effect::{requests::RestRequest, EffectBuilder},
     reactor::QueueKind,
@@ -35,6 +38,12 @@ pub const VALIDATOR_CHANGES_API_PATH: &str = "validator-changes";
 /// The chainspec file URL path.
 pub const CHAINSPEC_API_PATH: &str = "chainspec";
 
+/// The deploy dry-run URL path.
+pub const DRY_RUN_API_PATH: &str = "dry-run";
+
+/// The maximum size of a dry-run request body.
+const MAX_DRY_RUN_BODY_SIZE: u64 = 1024 * 1024;
+
 pub(super) fn create_status_filter<REv: ReactorEventT>(
     effect_builder: EffectBuilder<REv>,
     api_version: ProtocolVersion,
@@ -134,3 +143,32 @@ pub(super) fn create_chainspec_filter<REv: ReactorEventT>(
         })
         .boxed()
 }
+
+pub(super) fn create_dry_run_filter<REv: ReactorEventT>(
+    effect_builder: EffectBuilder<REv>,
+    api_version: ProtocolVersion,
+    enabled: bool,
+) -> BoxedFilter<(Response<Body>,)> {
+    warp::post()
+        .and(warp::path(DRY_RUN_API_PATH))
+        .and_then(move || async move {
+            if enabled {
+                Ok::<_, Rejection>(())
+            } else {
+                Err(warp::reject::not_found())
+            }
+        })
+        .untuple_one()
+        .and(warp::body::content_length_limit(MAX_DRY_RUN_BODY_SIZE))
+        .and(warp::body::json())
+        .and_then(move |request: DryRunRequest| {
+            dry_run::dry_run(effect_builder, api_version, request).map(|result| match result {
+                Ok(result) => Ok::<_, Rejection>(reply::json(&result).into_response()),
+                Err(error) => {
+                    info!(%error, "dry run failed");
+                    Ok(reply::with_status(error.to_string(), error.status_code()).into_response())
+                }
+            })
+        })
+        .boxed()
+}
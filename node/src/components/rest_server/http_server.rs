@@ -1,7 +1,8 @@
-use std::{convert::Infallible, time::Duration};
+use std::{convert::Infallible, error::Error as StdError, net::SocketAddr, time::Duration};
 
 use futures::{future, TryFutureExt};
-use hyper::server::{conn::AddrIncoming, Builder};
+use hyper::server::{accept::Accept, Builder};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::sync::oneshot;
 use tower::builder::ServiceBuilder;
 use tracing::{info, warn};
@@ -15,13 +16,20 @@ use crate::effect::EffectBuilder;
 /// Run the REST HTTP server.
 ///
 /// A message received on `shutdown_receiver` will cause the server to exit cleanly.
-pub(super) async fn run<REv: ReactorEventT>(
-    builder: Builder<AddrIncoming>,
+pub(super) async fn run<REv, I>(
+    builder: Builder<I>,
+    local_addr: SocketAddr,
     effect_builder: EffectBuilder<REv>,
     api_version: ProtocolVersion,
     shutdown_receiver: oneshot::Receiver<()>,
     qps_limit: u64,
-) {
+    enable_dry_run: bool,
+) where
+    REv: ReactorEventT,
+    I: Accept,
+    I::Conn: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    I::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
     // REST filters.
     let rest_status = filters::create_status_filter(effect_builder, api_version);
     let rest_metrics = filters::create_metrics_filter(effect_builder);
@@ -29,6 +37,7 @@ pub(super) async fn run<REv: ReactorEventT>(
     let rest_validator_changes =
         filters::create_validator_changes_filter(effect_builder, api_version);
     let rest_chainspec_filter = filters::create_chainspec_filter(effect_builder, api_version);
+    let rest_dry_run = filters::create_dry_run_filter(effect_builder, api_version, enable_dry_run);
 
     let service = warp::service(
         rest_status
@@ -36,6 +45,7 @@ pub(super) async fn run<REv: ReactorEventT>(
             .or(rest_open_rpc)
             .or(rest_validator_changes)
             .or(rest_chainspec_filter)
+            .or(rest_dry_run)
             .with(warp::cors().allow_any_origin()),
     );
 
@@ -48,7 +58,7 @@ pub(super) async fn run<REv: ReactorEventT>(
         .service(make_svc);
 
     let server = builder.serve(rate_limited_service);
-    info!(address = %server.local_addr(), "started REST server");
+    info!(address = %local_addr, "started REST server");
 
     // Shutdown the server gracefully.
     let _ = server
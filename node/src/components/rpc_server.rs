@@ -14,6 +14,8 @@
 //! For the list of supported RPC methods, see:
 //! <https://github.com/CasperLabs/ceps/blob/master/text/0009-client-api.md#rpcs>
 
+mod archive_config;
+mod archive_server;
 mod config;
 mod event;
 mod http_server;
@@ -21,20 +23,23 @@ pub mod rpcs;
 mod speculative_exec_config;
 mod speculative_exec_server;
 
-use std::{convert::Infallible, fmt::Debug, time::Instant};
+use std::{convert::Infallible, fmt::Debug, sync::Arc, time::Instant};
 
 use datasize::DataSize;
 use futures::join;
 use tracing::error;
 
-use casper_execution_engine::core::engine_state::{
-    self, BalanceRequest, BalanceResult, GetBidsRequest, GetEraValidatorsError, QueryRequest,
-    QueryResult,
+use casper_execution_engine::{
+    core::engine_state::{
+        self, BalanceRequest, BalanceResult, GetBidsRequest, GetEraValidatorsError, QueryRequest,
+        QueryResult,
+    },
+    shared::gas_profile::GasProfile,
 };
 use casper_hashing::Digest;
 use casper_types::{system::auction::EraValidators, ExecutionResult, Key, ProtocolVersion, URef};
 
-use self::rpcs::chain::BlockIdentifier;
+use self::rpcs::{archive::ArchiveLimits, chain::BlockIdentifier, RpcLimits};
 use super::Component;
 use crate::{
     components::contract_runtime::EraValidatorsRequest,
@@ -42,15 +47,16 @@ use crate::{
     effect::{
         announcements::RpcServerAnnouncement,
         requests::{
-            ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, MetricsRequest,
-            NetworkInfoRequest, NodeStateRequest, RpcRequest, StorageRequest,
+            BlockProposerRequest, ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest,
+            MetricsRequest, NetworkInfoRequest, NodeStateRequest, RpcRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects, Responder,
     },
-    types::{BlockHeader, Deploy, StatusFeed},
+    types::{BlockHeader, StatusFeed, Transaction},
     utils::{self, ListeningError},
     NodeRng,
 };
+pub use archive_config::Config as ArchiveServerConfig;
 pub use config::Config;
 pub(crate) use event::Event;
 pub use speculative_exec_config::Config as SpeculativeExecConfig;
@@ -67,6 +73,7 @@ pub(crate) trait ReactorEventT:
     + From<NetworkInfoRequest>
     + From<StorageRequest>
     + From<NodeStateRequest>
+    + From<BlockProposerRequest>
     + Send
 {
 }
@@ -82,6 +89,7 @@ impl<REv> ReactorEventT for REv where
         + From<NetworkInfoRequest>
         + From<StorageRequest>
         + From<NodeStateRequest>
+        + From<BlockProposerRequest>
         + Send
         + 'static
 {
@@ -110,12 +118,19 @@ pub(crate) struct RpcServer {
     /// no fields and no methods because all that is needed to operate it is the
     /// spawned tokio task, so a unit struct will suffice here.
     speculative_exec: Option<()>,
+    /// The limits of the JSON-RPC server, if enabled.
+    #[data_size(skip)]
+    rpc_limits: Option<Arc<RpcLimits>>,
+    /// The limits of the speculative execution JSON-RPC server, if enabled.
+    #[data_size(skip)]
+    speculative_exec_limits: Option<Arc<RpcLimits>>,
 }
 
 impl RpcServer {
     pub(crate) fn new<REv>(
         config: Config,
         speculative_exec_config: SpeculativeExecConfig,
+        archive_config: ArchiveServerConfig,
         effect_builder: EffectBuilder<REv>,
         api_version: ProtocolVersion,
         node_startup_instant: Instant,
@@ -126,34 +141,62 @@ impl RpcServer {
         // Set the speculative execution HTTP server up first. The speculative
         // execution server can operate independently from the JSON-RPC server,
         // so we save its state before we construct the `RpcServer`.
-        let speculative_exec = if speculative_exec_config.enable_server {
+        let (speculative_exec, speculative_exec_limits) = if speculative_exec_config.enable_server {
             let builder = utils::start_listening(&speculative_exec_config.address)?;
+            let limits = Arc::new(RpcLimits::new(
+                speculative_exec_config.qps_limit,
+                speculative_exec_config.max_body_bytes,
+            ));
             tokio::spawn(speculative_exec_server::run(
                 builder,
                 effect_builder,
                 api_version,
-                speculative_exec_config.qps_limit,
-                speculative_exec_config.max_body_bytes,
+                Arc::clone(&limits),
             ));
-            Some(())
+            (Some(()), Some(limits))
         } else {
-            None
+            (None, None)
         };
 
+        // The archive server is likewise independent from the JSON-RPC server.
+        if archive_config.enable_server {
+            let builder = utils::start_listening(&archive_config.address)?;
+            let limits = Arc::new(RpcLimits::new(
+                archive_config.qps_limit,
+                archive_config.max_body_bytes,
+            ));
+            let archive_limits = ArchiveLimits::new(
+                archive_config.max_queries_per_request,
+                archive_config.max_lineage_length,
+            );
+            tokio::spawn(archive_server::run(
+                builder,
+                effect_builder,
+                api_version,
+                limits,
+                archive_limits,
+            ));
+        }
+
         if !config.enable_server {
             return Ok(RpcServer {
                 inner_rpc: None,
                 speculative_exec,
+                rpc_limits: None,
+                speculative_exec_limits,
             });
         }
 
         let builder = utils::start_listening(&config.address)?;
+        let rpc_limits = Arc::new(
+            RpcLimits::new(config.qps_limit, config.max_body_bytes)
+                .with_batch_limits(config.max_batch_size, config.max_batch_concurrency),
+        );
         tokio::spawn(http_server::run(
             builder,
             effect_builder,
             api_version,
-            config.qps_limit,
-            config.max_body_bytes,
+            Arc::clone(&rpc_limits),
         ));
 
         let inner_rpc = Some(InnerRpcServer {
@@ -163,8 +206,30 @@ impl RpcServer {
         Ok(RpcServer {
             inner_rpc,
             speculative_exec,
+            rpc_limits: Some(rpc_limits),
+            speculative_exec_limits,
         })
     }
+
+    /// Applies the rate, request size and batch limits of the given configs to the running servers.
+    ///
+    /// Servers which aren't running are unaffected, i.e. enabling a server requires a restart.
+    pub(crate) fn update_limits(
+        &self,
+        config: &Config,
+        speculative_exec_config: &SpeculativeExecConfig,
+    ) {
+        if let Some(limits) = &self.rpc_limits {
+            limits.update(config.qps_limit, config.max_body_bytes);
+            limits.update_batch_limits(config.max_batch_size, config.max_batch_concurrency);
+        }
+        if let Some(limits) = &self.speculative_exec_limits {
+            limits.update(
+                speculative_exec_config.qps_limit,
+                speculative_exec_config.max_body_bytes,
+            );
+        }
+    }
 }
 
 impl RpcServer {
@@ -221,8 +286,11 @@ impl RpcServer {
         &mut self,
         effect_builder: EffectBuilder<REv>,
         block_header: BlockHeader,
-        deploy: Deploy,
-        responder: Responder<Result<Option<ExecutionResult>, engine_state::Error>>,
+        transaction: Transaction,
+        profile_gas: bool,
+        responder: Responder<
+            Result<Option<(ExecutionResult, Option<GasProfile>)>, engine_state::Error>,
+        >,
     ) -> Effects<Event> {
         async move {
             let execution_prestate = SpeculativeExecutionState {
@@ -231,7 +299,7 @@ impl RpcServer {
                 protocol_version: block_header.protocol_version(),
             };
             let result = effect_builder
-                .speculative_execute_deploy(execution_prestate, deploy)
+                .speculative_execute_transaction(execution_prestate, transaction, profile_gas)
                 .await;
             responder.respond(result).await
         }
@@ -256,7 +324,8 @@ where
         // the speculative execution JSON-RPC server.
         if let Event::RpcRequest(RpcRequest::SpeculativeDeployExecute {
             block_header,
-            deploy,
+            transaction,
+            profile_gas,
             responder,
         }) = event
         {
@@ -265,7 +334,8 @@ where
                     return self.handle_execute_deploy(
                         effect_builder,
                         block_header,
-                        *deploy,
+                        *transaction,
+                        profile_gas,
                         responder,
                     );
                 }
@@ -288,6 +358,15 @@ where
             Event::RpcRequest(RpcRequest::SubmitDeploy { deploy, responder }) => effect_builder
                 .announce_deploy_received(deploy, Some(responder))
                 .ignore(),
+            Event::RpcRequest(RpcRequest::SubmitTransactionV2 {
+                transaction,
+                responder,
+            }) => effect_builder
+                .announce_transaction_v2_received(transaction, responder)
+                .ignore(),
+            Event::RpcRequest(RpcRequest::PrecheckDeploy { deploy, responder }) => effect_builder
+                .announce_deploy_precheck_requested(deploy, responder)
+                .ignore(),
             Event::RpcRequest(RpcRequest::GetBlock {
                 maybe_id: Some(BlockIdentifier::Hash(hash)),
                 only_from_available_block_range,
@@ -399,9 +478,17 @@ where
             Event::RpcRequest(RpcRequest::GetStatus { responder }) => {
                 let node_uptime = rpc_server.node_startup_instant().elapsed();
                 async move {
-                    let (last_added_block, peers, chainspec_info, consensus_status, node_state) = join!(
+                    let (
+                        last_added_block,
+                        peers,
+                        peer_reputations,
+                        chainspec_info,
+                        consensus_status,
+                        (node_state, sync_status),
+                    ) = join!(
                         effect_builder.get_highest_block_from_storage(),
                         effect_builder.network_peers(),
+                        effect_builder.network_peer_reputations(),
                         effect_builder.get_chainspec_info(),
                         effect_builder.consensus_status(),
                         effect_builder.get_node_state()
@@ -409,10 +496,12 @@ where
                     let status_feed = StatusFeed::new(
                         last_added_block,
                         peers,
+                        peer_reputations,
                         chainspec_info,
                         consensus_status,
                         node_uptime,
                         node_state,
+                        sync_status,
                     );
                     responder.respond(status_feed).await;
                 }
@@ -0,0 +1,54 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+/// Default binding address for the archive JSON-RPC HTTP server.
+///
+/// Uses a fixed port per node, but binds on any interface.
+const DEFAULT_ADDRESS: &str = "0.0.0.0:2";
+/// Default rate limit in qps.
+const DEFAULT_QPS_LIMIT: u64 = 10;
+/// Default max body bytes (64kB).
+const DEFAULT_MAX_BODY_BYTES: u32 = 65_536;
+/// Default maximum number of values proven by a single request.
+const DEFAULT_MAX_QUERIES_PER_REQUEST: u32 = 100;
+/// Default maximum number of switch blocks in the validator lineage of a single request.
+const DEFAULT_MAX_LINEAGE_LENGTH: u64 = 1_000;
+
+/// Archive JSON-RPC HTTP server configuration.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Setting to enable the HTTP server.
+    pub enable_server: bool,
+    /// Address to bind the archive JSON-RPC HTTP server to.
+    pub address: String,
+    /// Maximum rate limit in queries per second.
+    pub qps_limit: u64,
+    /// Maximum number of bytes to accept in a single request body.
+    pub max_body_bytes: u32,
+    /// Maximum number of values proven by a single request.
+    pub max_queries_per_request: u32,
+    /// Maximum number of switch blocks in the validator lineage returned by a single request.
+    pub max_lineage_length: u64,
+}
+
+impl Config {
+    /// Creates a default instance for `RpcServer`.
+    pub fn new() -> Self {
+        Config {
+            enable_server: false,
+            address: DEFAULT_ADDRESS.to_string(),
+            qps_limit: DEFAULT_QPS_LIMIT,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            max_queries_per_request: DEFAULT_MAX_QUERIES_PER_REQUEST,
+            max_lineage_length: DEFAULT_MAX_LINEAGE_LENGTH,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use hyper::server::{conn::AddrIncoming, Builder};
+
+use casper_json_rpc::RequestHandlersBuilder;
+use casper_types::ProtocolVersion;
+
+use super::ReactorEventT;
+use crate::{
+    effect::EffectBuilder,
+    rpcs::{
+        archive::{ArchiveLimits, GetProofBundles},
+        RpcLimits,
+    },
+};
+
+/// The URL path for all JSON-RPC requests.
+pub const ARCHIVE_API_PATH: &str = "rpc";
+
+pub const ARCHIVE_SERVER_NAME: &str = "archive";
+
+/// Run the archive server.
+pub(super) async fn run<REv: ReactorEventT>(
+    builder: Builder<AddrIncoming>,
+    effect_builder: EffectBuilder<REv>,
+    api_version: ProtocolVersion,
+    limits: Arc<RpcLimits>,
+    archive_limits: ArchiveLimits,
+) {
+    let mut handlers = RequestHandlersBuilder::new();
+    GetProofBundles::register_as_handler(
+        effect_builder,
+        api_version,
+        archive_limits,
+        &mut handlers,
+    );
+    let handlers = handlers.build();
+
+    super::rpcs::run(
+        builder,
+        handlers,
+        limits,
+        ARCHIVE_API_PATH,
+        ARCHIVE_SERVER_NAME,
+    )
+    .await;
+}
@@ -10,6 +10,10 @@ const DEFAULT_QPS_LIMIT: u64 = 100;
 /// Default max body bytes.  This is 2.5MB which should be able to accommodate the largest valid
 /// JSON-RPC request, which would be an "account_put_deploy".
 const DEFAULT_MAX_BODY_BYTES: u32 = 2_621_440;
+/// Default maximum number of requests in a batch request.
+const DEFAULT_MAX_BATCH_SIZE: u32 = 20;
+/// Default maximum number of requests of a single batch handled concurrently.
+const DEFAULT_MAX_BATCH_CONCURRENCY: u32 = 4;
 
 /// JSON-RPC HTTP server configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
@@ -24,6 +28,20 @@ pub struct Config {
     pub qps_limit: u64,
     /// Maximum number of bytes to accept in a single request body.
     pub max_body_bytes: u32,
+    /// Maximum number of requests in a batch request.  Batch requests are rejected if `0`.
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: u32,
+    /// Maximum number of requests of a single batch request handled concurrently.
+    #[serde(default = "default_max_batch_concurrency")]
+    pub max_batch_concurrency: u32,
+}
+
+fn default_max_batch_size() -> u32 {
+    DEFAULT_MAX_BATCH_SIZE
+}
+
+fn default_max_batch_concurrency() -> u32 {
+    DEFAULT_MAX_BATCH_CONCURRENCY
 }
 
 impl Config {
@@ -34,6 +52,8 @@ impl Config {
             address: DEFAULT_ADDRESS.to_string(),
             qps_limit: DEFAULT_QPS_LIMIT,
             max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            max_batch_concurrency: DEFAULT_MAX_BATCH_CONCURRENCY,
         }
     }
 }
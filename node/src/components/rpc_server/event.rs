@@ -8,7 +8,7 @@ use derive_more::From;
 use casper_execution_engine::core::engine_state::{
     self, BalanceResult, GetBidsResult, GetEraValidatorsError, QueryResult,
 };
-use casper_types::{system::auction::EraValidators, Transfer};
+use casper_types::{system::auction::EraValidators, TransferV2};
 
 use crate::{
     effect::{requests::RpcRequest, Responder},
@@ -27,8 +27,8 @@ pub(crate) enum Event {
     },
     GetBlockTransfersResult {
         block_hash: BlockHash,
-        result: Box<Option<Vec<Transfer>>>,
-        main_responder: Responder<Option<Vec<Transfer>>>,
+        result: Box<Option<Vec<TransferV2>>>,
+        main_responder: Responder<Option<Vec<TransferV2>>>,
     },
     QueryGlobalStateResult {
         result: Result<QueryResult, engine_state::Error>,
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use hyper::server::{conn::AddrIncoming, Builder};
 
 use casper_json_rpc::RequestHandlersBuilder;
@@ -5,15 +7,21 @@ use casper_types::ProtocolVersion;
 
 use super::{
     rpcs::{
-        account::PutDeploy,
-        chain::{GetBlock, GetBlockTransfers, GetEraInfoBySwitchBlock, GetStateRootHash},
+        account::{PrecheckDeploy, PutDeploy, PutTransaction},
+        chain::{
+            GetBlock, GetBlockFinality, GetBlockSummaries, GetBlockTransfers, GetDelegatorRewards,
+            GetEraInfoBySwitchBlock, GetSignalTally, GetStateRootHash, GetValidatorRewards,
+        },
         docs::ListRpcs,
-        info::{GetChainspec, GetDeploy, GetPeers, GetStatus, GetValidatorChanges},
+        info::{
+            GetChainspec, GetDelegatorHistory, GetDeploy, GetEraRewards, GetPeers, GetStatus,
+            GetTransaction, GetValidatorChanges,
+        },
         state::{
-            GetAccountInfo, GetAuctionInfo, GetBalance, GetDictionaryItem, GetItem, GetTrie,
-            QueryBalance, QueryGlobalState,
+            GetAccountInfo, GetAuctionInfo, GetBalance, GetDictionaryItem, GetItem, GetProofBundle,
+            GetTrie, QueryBalance, QueryGlobalState, QueryGlobalStateBulk,
         },
-        RpcWithOptionalParams, RpcWithParams, RpcWithoutParams,
+        RpcLimits, RpcWithOptionalParams, RpcWithParams, RpcWithoutParams,
     },
     ReactorEventT,
 };
@@ -29,11 +37,12 @@ pub(super) async fn run<REv: ReactorEventT>(
     builder: Builder<AddrIncoming>,
     effect_builder: EffectBuilder<REv>,
     api_version: ProtocolVersion,
-    qps_limit: u64,
-    max_body_bytes: u32,
+    limits: Arc<RpcLimits>,
 ) {
     let mut handlers = RequestHandlersBuilder::new();
     PutDeploy::register_as_handler(effect_builder, api_version, &mut handlers);
+    PrecheckDeploy::register_as_handler(effect_builder, api_version, &mut handlers);
+    PutTransaction::register_as_handler(effect_builder, api_version, &mut handlers);
     GetBlock::register_as_handler(effect_builder, api_version, &mut handlers);
     GetBlockTransfers::register_as_handler(effect_builder, api_version, &mut handlers);
     GetStateRootHash::register_as_handler(effect_builder, api_version, &mut handlers);
@@ -42,6 +51,7 @@ pub(super) async fn run<REv: ReactorEventT>(
     GetBalance::register_as_handler(effect_builder, api_version, &mut handlers);
     GetAccountInfo::register_as_handler(effect_builder, api_version, &mut handlers);
     GetDeploy::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetTransaction::register_as_handler(effect_builder, api_version, &mut handlers);
     GetPeers::register_as_handler(effect_builder, api_version, &mut handlers);
     GetStatus::register_as_handler(effect_builder, api_version, &mut handlers);
     GetEraInfoBySwitchBlock::register_as_handler(effect_builder, api_version, &mut handlers);
@@ -52,15 +62,16 @@ pub(super) async fn run<REv: ReactorEventT>(
     GetDictionaryItem::register_as_handler(effect_builder, api_version, &mut handlers);
     GetChainspec::register_as_handler(effect_builder, api_version, &mut handlers);
     QueryBalance::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetProofBundle::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetBlockSummaries::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetValidatorRewards::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetDelegatorRewards::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetSignalTally::register_as_handler(effect_builder, api_version, &mut handlers);
+    QueryGlobalStateBulk::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetBlockFinality::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetEraRewards::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetDelegatorHistory::register_as_handler(effect_builder, api_version, &mut handlers);
     let handlers = handlers.build();
 
-    super::rpcs::run(
-        builder,
-        handlers,
-        qps_limit,
-        max_body_bytes,
-        RPC_API_PATH,
-        RPC_API_SERVER_NAME,
-    )
-    .await;
+    super::rpcs::run(builder, handlers, limits, RPC_API_PATH, RPC_API_SERVER_NAME).await;
 }
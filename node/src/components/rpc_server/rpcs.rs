@@ -3,6 +3,7 @@
 //! See <https://github.com/CasperLabs/ceps/blob/master/text/0009-client-api.md#rpcs> for info.
 
 pub mod account;
+pub mod archive;
 pub mod chain;
 mod common;
 pub mod docs;
@@ -11,7 +12,15 @@ pub mod info;
 pub mod speculative_exec;
 pub mod state;
 
-use std::{convert::Infallible, str, sync::Arc, time::Duration};
+use std::{
+    convert::Infallible,
+    str,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
 use http::header::ACCEPT_ENCODING;
@@ -20,11 +29,12 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::sync::oneshot;
-use tower::ServiceBuilder;
-use tracing::info;
+use tracing::{info, info_span, Instrument};
 use warp::Filter;
 
-use casper_json_rpc::{Error, Params, RequestHandlers, RequestHandlersBuilder, ReservedErrorCode};
+use casper_json_rpc::{
+    BatchLimits, Error, Params, RequestHandlers, RequestHandlersBuilder, ReservedErrorCode,
+};
 use casper_types::ProtocolVersion;
 
 use super::{ReactorEventT, RpcRequest};
@@ -88,9 +98,12 @@ pub(super) trait RpcWithParams {
         api_version: ProtocolVersion,
         handlers_builder: &mut RequestHandlersBuilder,
     ) {
-        let handler = move |maybe_params| async move {
-            let params = Self::try_parse_params(maybe_params)?;
-            Self::do_handle_request(effect_builder, api_version, params).await
+        let handler = move |maybe_params| {
+            async move {
+                let params = Self::try_parse_params(maybe_params)?;
+                Self::do_handle_request(effect_builder, api_version, params).await
+            }
+            .instrument(info_span!("rpc", method = Self::METHOD))
         };
         handlers_builder.register_handler(Self::METHOD, Arc::new(handler))
     }
@@ -147,9 +160,12 @@ pub(super) trait RpcWithoutParams {
         api_version: ProtocolVersion,
         handlers_builder: &mut RequestHandlersBuilder,
     ) {
-        let handler = move |maybe_params| async move {
-            Self::check_no_params(maybe_params)?;
-            Self::do_handle_request(effect_builder, api_version).await
+        let handler = move |maybe_params| {
+            async move {
+                Self::check_no_params(maybe_params)?;
+                Self::do_handle_request(effect_builder, api_version).await
+            }
+            .instrument(info_span!("rpc", method = Self::METHOD))
         };
         handlers_builder.register_handler(Self::METHOD, Arc::new(handler))
     }
@@ -227,9 +243,12 @@ pub(super) trait RpcWithOptionalParams {
         api_version: ProtocolVersion,
         handlers_builder: &mut RequestHandlersBuilder,
     ) {
-        let handler = move |maybe_params| async move {
-            let params = Self::try_parse_params(maybe_params)?;
-            Self::do_handle_request(effect_builder, api_version, params).await
+        let handler = move |maybe_params| {
+            async move {
+                let params = Self::try_parse_params(maybe_params)?;
+                Self::do_handle_request(effect_builder, api_version, params).await
+            }
+            .instrument(info_span!("rpc", method = Self::METHOD))
         };
         handlers_builder.register_handler(Self::METHOD, Arc::new(handler))
     }
@@ -252,21 +271,104 @@ pub(super) trait RpcWithOptionalParams {
     ) -> Result<Self::ResponseResult, Error>;
 }
 
+/// The period over which the rate limit of a JSON-RPC server applies.
+const RATE_LIMIT_PERIOD: Duration = Duration::from_secs(1);
+
+/// The limits of a JSON-RPC server, which can be changed while it is running.
+#[derive(Debug)]
+pub(crate) struct RpcLimits {
+    /// Maximum number of connections accepted per `RATE_LIMIT_PERIOD`.
+    qps_limit: AtomicU64,
+    /// Maximum number of bytes to accept in a single request body.
+    max_body_bytes: AtomicU32,
+    /// Maximum number of requests in a batch request.  Batch requests are rejected if `0`.
+    max_batch_size: AtomicU32,
+    /// Maximum number of requests of a single batch handled concurrently.
+    max_batch_concurrency: AtomicU32,
+    /// The start of the current rate limit period and the number of connections accepted in it.
+    current_period: Mutex<(Instant, u64)>,
+}
+
+impl RpcLimits {
+    /// Creates new limits, with batch requests disabled.
+    pub(crate) fn new(qps_limit: u64, max_body_bytes: u32) -> Self {
+        RpcLimits {
+            qps_limit: AtomicU64::new(qps_limit),
+            max_body_bytes: AtomicU32::new(max_body_bytes),
+            max_batch_size: AtomicU32::new(0),
+            max_batch_concurrency: AtomicU32::new(1),
+            current_period: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Enables batch requests of up to `max_batch_size` requests, of which up to
+    /// `max_batch_concurrency` are handled concurrently.
+    pub(crate) fn with_batch_limits(self, max_batch_size: u32, max_batch_concurrency: u32) -> Self {
+        self.update_batch_limits(max_batch_size, max_batch_concurrency);
+        self
+    }
+
+    /// Sets new limits, which apply to connections accepted from now on.
+    pub(crate) fn update(&self, qps_limit: u64, max_body_bytes: u32) {
+        self.qps_limit.store(qps_limit, Ordering::SeqCst);
+        self.max_body_bytes.store(max_body_bytes, Ordering::SeqCst);
+    }
+
+    /// Sets new batch request limits, which apply to connections accepted from now on.
+    pub(crate) fn update_batch_limits(&self, max_batch_size: u32, max_batch_concurrency: u32) {
+        self.max_batch_size.store(max_batch_size, Ordering::SeqCst);
+        self.max_batch_concurrency
+            .store(max_batch_concurrency, Ordering::SeqCst);
+    }
+
+    /// Returns the current batch request limits.
+    fn batch_limits(&self) -> BatchLimits {
+        BatchLimits::new(
+            self.max_batch_size.load(Ordering::SeqCst),
+            self.max_batch_concurrency.load(Ordering::SeqCst),
+        )
+    }
+
+    /// Waits until accepting another connection doesn't exceed the rate limit.
+    async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut current_period = self
+                    .current_period
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                let (ref mut period_start, ref mut count) = *current_period;
+                let now = Instant::now();
+                if now.duration_since(*period_start) >= RATE_LIMIT_PERIOD {
+                    *period_start = now;
+                    *count = 0;
+                }
+                if *count < self.qps_limit.load(Ordering::SeqCst) {
+                    *count += 1;
+                    return;
+                }
+                RATE_LIMIT_PERIOD - now.duration_since(*period_start)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
 /// Start JSON RPC server in a background.
 pub(super) async fn run(
     builder: Builder<AddrIncoming>,
     handlers: RequestHandlers,
-    qps_limit: u64,
-    max_body_bytes: u32,
+    limits: Arc<RpcLimits>,
     api_path: &'static str,
     server_name: &'static str,
 ) {
     let make_svc = hyper::service::make_service_fn(move |_| {
         let service_routes = casper_json_rpc::route(
             api_path,
-            max_body_bytes,
+            limits.max_body_bytes.load(Ordering::SeqCst),
             handlers.clone(),
             ALLOW_UNKNOWN_FIELDS_IN_JSON_RPC_REQUEST,
+            limits.batch_limits(),
         );
 
         // Supports content negotiation for gzip responses. This is an interim fix until
@@ -276,13 +378,13 @@ pub(super) async fn run(
             .with(warp::compression::gzip());
 
         let service = warp::service(service_routes_gzip.or(service_routes));
-        async move { Ok::<_, Infallible>(service.clone()) }
+        let limits = Arc::clone(&limits);
+        async move {
+            limits.acquire().await;
+            Ok::<_, Infallible>(service.clone())
+        }
     });
 
-    let make_svc = ServiceBuilder::new()
-        .rate_limit(qps_limit, Duration::from_secs(1))
-        .service(make_svc);
-
     let server = builder.serve(make_svc);
     info!(address = %server.local_addr(), "started {} server", server_name);
 
@@ -344,9 +446,13 @@ mod tests {
             GetDeploy::register_as_test_handler(&mut handlers);
             let handlers = handlers.build();
 
-            filters::main_filter(handlers, ALLOW_UNKNOWN_FIELDS_IN_JSON_RPC_REQUEST)
-                .recover(filters::handle_rejection)
-                .boxed()
+            filters::main_filter(
+                handlers,
+                ALLOW_UNKNOWN_FIELDS_IN_JSON_RPC_REQUEST,
+                BatchLimits::new(0, 1),
+            )
+            .recover(filters::handle_rejection)
+            .boxed()
         }
 
         #[tokio::test]
@@ -411,9 +517,13 @@ mod tests {
             GetPeers::register_as_test_handler(&mut handlers);
             let handlers = handlers.build();
 
-            filters::main_filter(handlers, ALLOW_UNKNOWN_FIELDS_IN_JSON_RPC_REQUEST)
-                .recover(filters::handle_rejection)
-                .boxed()
+            filters::main_filter(
+                handlers,
+                ALLOW_UNKNOWN_FIELDS_IN_JSON_RPC_REQUEST,
+                BatchLimits::new(0, 1),
+            )
+            .recover(filters::handle_rejection)
+            .boxed()
         }
 
         #[tokio::test]
@@ -456,8 +566,11 @@ mod tests {
 
     mod rpc_with_optional_params {
         use super::*;
-        use crate::components::rpc_server::rpcs::chain::{
-            BlockIdentifier, GetBlock, GetBlockParams, GetBlockResult,
+        use crate::{
+            components::rpc_server::rpcs::chain::{
+                BlockIdentifier, GetBlock, GetBlockParams, GetBlockResult,
+            },
+            types::BlockHeight,
         };
 
         fn main_filter_with_recovery() -> BoxedFilter<(impl Reply,)> {
@@ -465,9 +578,13 @@ mod tests {
             GetBlock::register_as_test_handler(&mut handlers);
             let handlers = handlers.build();
 
-            filters::main_filter(handlers, ALLOW_UNKNOWN_FIELDS_IN_JSON_RPC_REQUEST)
-                .recover(filters::handle_rejection)
-                .boxed()
+            filters::main_filter(
+                handlers,
+                ALLOW_UNKNOWN_FIELDS_IN_JSON_RPC_REQUEST,
+                BatchLimits::new(0, 1),
+            )
+            .recover(filters::handle_rejection)
+            .boxed()
         }
 
         #[tokio::test]
@@ -498,7 +615,7 @@ mod tests {
             let filter = main_filter_with_recovery();
 
             let params = serde_json::to_string(&GetBlockParams {
-                block_identifier: BlockIdentifier::Height(1),
+                block_identifier: BlockIdentifier::Height(BlockHeight::new(1)),
             })
             .unwrap();
             let params = Some(params.as_str());
@@ -11,17 +11,17 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use casper_types::ProtocolVersion;
+use casper_types::{ProtocolVersion, TimeDiff, Timestamp};
 
 use super::{
     docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
     Error, ReactorEventT, RpcRequest, RpcWithParams,
 };
 use crate::{
-    components::rpc_server::rpcs::ErrorCode,
+    components::{deploy_acceptor, rpc_server::rpcs::ErrorCode},
     effect::EffectBuilder,
     reactor::QueueKind,
-    types::{Deploy, DeployHash},
+    types::{Deploy, DeployHash, Transaction, TransactionHash},
 };
 
 static PUT_DEPLOY_PARAMS: Lazy<PutDeployParams> = Lazy::new(|| PutDeployParams {
@@ -31,6 +31,24 @@ static PUT_DEPLOY_RESULT: Lazy<PutDeployResult> = Lazy::new(|| PutDeployResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     deploy_hash: *Deploy::doc_example().id(),
 });
+static PUT_TRANSACTION_PARAMS: Lazy<PutTransactionParams> = Lazy::new(|| PutTransactionParams {
+    transaction: Transaction::Deploy(Deploy::doc_example().clone()),
+});
+static PUT_TRANSACTION_RESULT: Lazy<PutTransactionResult> = Lazy::new(|| PutTransactionResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    transaction_hash: TransactionHash::Deploy(*Deploy::doc_example().id()),
+});
+static PRECHECK_DEPLOY_PARAMS: Lazy<PrecheckDeployParams> = Lazy::new(|| PrecheckDeployParams {
+    deploy: Deploy::doc_example().clone(),
+});
+static PRECHECK_DEPLOY_RESULT: Lazy<PrecheckDeployResult> = Lazy::new(|| PrecheckDeployResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    deploy_hash: *Deploy::doc_example().id(),
+    warnings: vec![],
+});
+
+/// If a prechecked deploy expires within this period, a warning is returned.
+const EXPIRY_WARNING_THRESHOLD: TimeDiff = TimeDiff::from_seconds(60);
 
 /// Params for "account_put_deploy" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -105,8 +123,255 @@ impl RpcWithParams for PutDeploy {
                     %error,
                     "the deploy submitted by the client was invalid",
                 );
-                Err(Error::new(ErrorCode::InvalidDeploy, error.to_string()))
+                Err(Error::new(
+                    ErrorCode::InvalidDeploy,
+                    PutDeployErrorData {
+                        reason: error.rejection_reason(),
+                        message: error.to_string(),
+                        error,
+                    },
+                ))
+            }
+        }
+    }
+}
+
+/// The `data` field of a failed "account_put_deploy" response.
+#[derive(Serialize)]
+struct PutDeployErrorData {
+    /// Short identifier of the cause of the rejection, e.g. "excessive_ttl".
+    reason: &'static str,
+    /// Human-readable description of the failure.
+    message: String,
+    /// The structured failure.
+    error: deploy_acceptor::Error,
+}
+
+/// Params for "account_put_transaction" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PutTransactionParams {
+    /// The `Transaction`, either a deploy or in the versioned format.
+    pub transaction: Transaction,
+}
+
+impl DocExample for PutTransactionParams {
+    fn doc_example() -> &'static Self {
+        &*PUT_TRANSACTION_PARAMS
+    }
+}
+
+/// Result for "account_put_transaction" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PutTransactionResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The transaction hash.
+    pub transaction_hash: TransactionHash,
+}
+
+impl DocExample for PutTransactionResult {
+    fn doc_example() -> &'static Self {
+        &*PUT_TRANSACTION_RESULT
+    }
+}
+
+/// "account_put_transaction" RPC
+///
+/// Deploys are handled exactly as by "account_put_deploy".  Transactions in the versioned format
+/// are validated, but then rejected, as they can't be executed yet.
+pub struct PutTransaction {}
+
+#[async_trait]
+impl RpcWithParams for PutTransaction {
+    const METHOD: &'static str = "account_put_transaction";
+    type RequestParams = PutTransactionParams;
+    type ResponseResult = PutTransactionResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let transaction_hash = params.transaction.hash();
+
+        let put_transaction_result = match params.transaction {
+            Transaction::Deploy(deploy) => {
+                effect_builder
+                    .make_request(
+                        |responder| RpcRequest::SubmitDeploy {
+                            deploy: Box::new(deploy),
+                            responder,
+                        },
+                        QueueKind::Api,
+                    )
+                    .await
+            }
+            Transaction::V2(transaction) => {
+                effect_builder
+                    .make_request(
+                        |responder| RpcRequest::SubmitTransactionV2 {
+                            transaction: Box::new(transaction),
+                            responder,
+                        },
+                        QueueKind::Api,
+                    )
+                    .await
+            }
+        };
+
+        match put_transaction_result {
+            Ok(_) => {
+                info!(%transaction_hash, "transaction was stored");
+                Ok(Self::ResponseResult {
+                    api_version,
+                    transaction_hash,
+                })
+            }
+            Err(error) => {
+                info!(
+                    %transaction_hash,
+                    %error,
+                    "the transaction submitted by the client was invalid",
+                );
+                Err(Error::new(
+                    ErrorCode::InvalidDeploy,
+                    PutDeployErrorData {
+                        reason: error.rejection_reason(),
+                        message: error.to_string(),
+                        error,
+                    },
+                ))
             }
         }
     }
 }
+
+/// Params for "account_precheck_deploy" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PrecheckDeployParams {
+    /// The `Deploy`.
+    pub deploy: Deploy,
+}
+
+impl DocExample for PrecheckDeployParams {
+    fn doc_example() -> &'static Self {
+        &*PRECHECK_DEPLOY_PARAMS
+    }
+}
+
+/// Result for "account_precheck_deploy" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PrecheckDeployResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+    /// Conditions which don't make the deploy invalid, but may prevent it from being executed as
+    /// expected.
+    pub warnings: Vec<String>,
+}
+
+impl DocExample for PrecheckDeployResult {
+    fn doc_example() -> &'static Self {
+        &*PRECHECK_DEPLOY_RESULT
+    }
+}
+
+/// "account_precheck_deploy" RPC
+///
+/// Runs the deploy through the same validation as "account_put_deploy", including wasm
+/// preprocessing, but neither stores nor gossips it.
+pub struct PrecheckDeploy {}
+
+#[async_trait]
+impl RpcWithParams for PrecheckDeploy {
+    const METHOD: &'static str = "account_precheck_deploy";
+    type RequestParams = PrecheckDeployParams;
+    type ResponseResult = PrecheckDeployResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let deploy_hash = *params.deploy.id();
+        let warnings = precheck_warnings(&params.deploy, Timestamp::now());
+
+        let precheck_result = effect_builder
+            .make_request(
+                |responder| RpcRequest::PrecheckDeploy {
+                    deploy: Box::new(params.deploy),
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await;
+
+        match precheck_result {
+            Ok(_) => {
+                info!(%deploy_hash, "deploy passed precheck");
+                let result = Self::ResponseResult {
+                    api_version,
+                    deploy_hash,
+                    warnings,
+                };
+                Ok(result)
+            }
+            Err(error) => {
+                info!(%deploy_hash, %error, "deploy failed precheck");
+                Err(Error::new(
+                    ErrorCode::InvalidDeploy,
+                    PrecheckErrorData {
+                        reason: error.rejection_reason(),
+                        message: error.to_string(),
+                        error,
+                        warnings,
+                    },
+                ))
+            }
+        }
+    }
+}
+
+/// The `data` field of a failed "account_precheck_deploy" response.
+#[derive(Serialize)]
+struct PrecheckErrorData {
+    /// Short identifier of the cause of the rejection, e.g. "excessive_ttl".
+    reason: &'static str,
+    /// Human-readable description of the failure.
+    message: String,
+    /// The structured failure.
+    error: deploy_acceptor::Error,
+    /// Any warnings which also apply to the deploy.
+    warnings: Vec<String>,
+}
+
+/// Returns warnings about conditions which don't invalidate the deploy at `now`.
+fn precheck_warnings(deploy: &Deploy, now: Timestamp) -> Vec<String> {
+    let header = deploy.header();
+    let mut warnings = vec![];
+    if header.timestamp() > now {
+        warnings.push(format!(
+            "deploy timestamp {} is in the future and it cannot be included in a block before then",
+            header.timestamp()
+        ));
+    }
+    if header.expires() < now.saturating_add(EXPIRY_WARNING_THRESHOLD) {
+        warnings.push(format!(
+            "deploy expires at {}, which may be before it can be included in a block",
+            header.expires()
+        ));
+    }
+    if !header.dependencies().is_empty() {
+        warnings.push(
+            "deploy has dependencies and will only be executed after all of them".to_string(),
+        );
+    }
+    warnings
+}
@@ -0,0 +1,238 @@
+//! RPCs served by archive nodes.
+//!
+//! Unlike the proofs returned by the main JSON-RPC server, the proofs served here are not restricted
+//! to the node's available block range: an archive node syncs to genesis and retains the global
+//! state of every block, so it can prove values at any historical block.
+
+use std::sync::Arc;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, info_span, Instrument};
+
+use casper_json_rpc::{Params, RequestHandlersBuilder, ReservedErrorCode};
+use casper_types::{Key, ProtocolVersion};
+
+use super::{
+    chain::{self, BlockIdentifier},
+    common,
+    state::{self, SignedSwitchBlockHeader},
+    Error, ErrorCode, ReactorEventT,
+};
+use crate::{
+    effect::EffectBuilder,
+    types::{
+        json_compatibility::StoredValue, BlockHash, BlockWithMetadata, JsonBlockHeader, JsonProof,
+    },
+};
+
+/// The limits on the cost of a single request to the archive server.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ArchiveLimits {
+    /// Maximum number of values proven by a single request.
+    max_queries_per_request: u32,
+    /// Maximum number of switch blocks in the validator lineage returned by a single request.
+    max_lineage_length: u64,
+}
+
+impl ArchiveLimits {
+    pub(crate) fn new(max_queries_per_request: u32, max_lineage_length: u64) -> Self {
+        ArchiveLimits {
+            max_queries_per_request,
+            max_lineage_length,
+        }
+    }
+}
+
+/// A value to prove, given by a key and a path.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProofQuery {
+    /// `casper_types::Key` as formatted string.
+    pub key: String,
+    /// The path components starting from the key as base.
+    #[serde(default)]
+    pub path: Vec<String>,
+}
+
+/// Params for "archive_get_proof_bundles" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetProofBundlesParams {
+    /// The identifier of the block against whose global state the values are proven.
+    pub block_identifier: BlockIdentifier,
+    /// The hash of a switch block already trusted by the caller, from which the era validator
+    /// weights lineage starts.  If not given, no lineage is returned.
+    #[serde(default)]
+    pub trusted_switch_block_hash: Option<BlockHash>,
+    /// The values to prove.
+    pub queries: Vec<ProofQuery>,
+}
+
+/// A value along with its Merkle proof.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProvenValue {
+    /// The queried key.
+    pub key: String,
+    /// The queried path.
+    pub path: Vec<String>,
+    /// The stored value.
+    pub stored_value: StoredValue,
+    /// The merkle proof of the stored value against the block's state root hash.
+    pub merkle_proof: String,
+}
+
+/// Result for "archive_get_proof_bundles" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetProofBundlesResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The hash of the block.
+    pub block_hash: BlockHash,
+    /// The header of the block.
+    pub block_header: JsonBlockHeader,
+    /// The finality signatures of the block.
+    pub finality_signatures: Vec<JsonProof>,
+    /// The signed switch blocks of every era after the trusted switch block's era and before the
+    /// block's era, in ascending order.  Empty if no trusted switch block was given.
+    pub era_validator_lineage: Vec<SignedSwitchBlockHeader>,
+    /// The queried values, in the order of the queries.
+    pub values: Vec<ProvenValue>,
+}
+
+/// "archive_get_proof_bundles" RPC.
+///
+/// This doesn't implement `RpcWithParams`, as the handler depends on the archive server's limits.
+pub struct GetProofBundles {}
+
+impl GetProofBundles {
+    /// The JSON-RPC "method" name.
+    pub const METHOD: &'static str = "archive_get_proof_bundles";
+
+    /// Registers this RPC as the handler for JSON-RPC requests whose "method" field is the same as
+    /// `Self::METHOD`.
+    pub(crate) fn register_as_handler<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        limits: ArchiveLimits,
+        handlers_builder: &mut RequestHandlersBuilder,
+    ) {
+        let handler = move |maybe_params| {
+            async move {
+                let params = Self::try_parse_params(maybe_params)?;
+                Self::do_handle_request(effect_builder, api_version, limits, params).await
+            }
+            .instrument(info_span!("rpc", method = Self::METHOD))
+        };
+        handlers_builder.register_handler(Self::METHOD, Arc::new(handler))
+    }
+
+    fn try_parse_params(maybe_params: Option<Params>) -> Result<GetProofBundlesParams, Error> {
+        let params = match maybe_params {
+            Some(params) => Value::from(params),
+            None => {
+                return Err(Error::new(
+                    ReservedErrorCode::InvalidParams,
+                    "Missing 'params' field",
+                ))
+            }
+        };
+        serde_json::from_value(params).map_err(|error| {
+            Error::new(
+                ReservedErrorCode::InvalidParams,
+                format!("Failed to parse 'params' field: {}", error),
+            )
+        })
+    }
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        limits: ArchiveLimits,
+        params: GetProofBundlesParams,
+    ) -> Result<GetProofBundlesResult, Error> {
+        // Archive nodes retain the global state of all blocks, so this RPC is not restricted by the
+        // block availability index.
+        let only_from_available_block_range = false;
+
+        if params.queries.len() > limits.max_queries_per_request as usize {
+            let error_msg = format!(
+                "request contains {} queries, exceeding the limit of {}",
+                params.queries.len(),
+                limits.max_queries_per_request
+            );
+            info!("{}", error_msg);
+            return Err(Error::new(ErrorCode::TooManyQueries, error_msg));
+        }
+
+        let mut base_keys = Vec::with_capacity(params.queries.len());
+        for query in &params.queries {
+            match Key::from_formatted_str(&query.key) {
+                Ok(key) => base_keys.push(key),
+                Err(error) => {
+                    let error_msg = format!("failed to parse key {}: {}", query.key, error);
+                    info!("{}", error_msg);
+                    return Err(Error::new(ErrorCode::FailedToParseQueryKey, error_msg));
+                }
+            }
+        }
+
+        let BlockWithMetadata {
+            block,
+            block_signatures,
+        } = chain::get_block_with_metadata(
+            Some(params.block_identifier),
+            only_from_available_block_range,
+            effect_builder,
+        )
+        .await?;
+
+        let era_validator_lineage = match params.trusted_switch_block_hash {
+            Some(trusted_switch_block_hash) => {
+                state::get_era_validator_lineage(
+                    effect_builder,
+                    trusted_switch_block_hash,
+                    block.header().era_id(),
+                    limits.max_lineage_length,
+                )
+                .await?
+            }
+            None => vec![],
+        };
+
+        let state_root_hash = *block.header().state_root_hash();
+        let mut values = Vec::with_capacity(params.queries.len());
+        for (query, base_key) in params.queries.into_iter().zip(base_keys) {
+            let (stored_value, merkle_proof) = common::run_query_and_encode(
+                effect_builder,
+                state_root_hash,
+                base_key,
+                query.path.clone(),
+            )
+            .await?;
+            values.push(ProvenValue {
+                key: query.key,
+                path: query.path,
+                stored_value,
+                merkle_proof,
+            });
+        }
+
+        Ok(GetProofBundlesResult {
+            api_version,
+            block_hash: *block.hash(),
+            block_header: JsonBlockHeader::from(block.take_header()),
+            finality_signatures: block_signatures
+                .proofs
+                .into_iter()
+                .map(JsonProof::from)
+                .collect(),
+            era_validator_lineage,
+            values,
+        })
+    }
+}
@@ -5,25 +5,33 @@
 
 mod era_summary;
 
-use std::{num::ParseIntError, str};
+use std::{collections::BTreeMap, num::ParseIntError, ops::RangeInclusive, str};
 
 use async_trait::async_trait;
 use once_cell::sync::Lazy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use tracing::info;
 
 use casper_hashing::Digest;
-use casper_types::{Key, ProtocolVersion, Transfer};
+use casper_types::{
+    AsymmetricType, EraId, Key, ProtocolVersion, PublicKey, Transfer, TransferV2, U512,
+};
 
 use super::{
     docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
     Error, ErrorCode, ReactorEventT, ReservedErrorCode, RpcRequest, RpcWithOptionalParams,
+    RpcWithParams,
 };
 use crate::{
+    components::linear_chain,
     effect::EffectBuilder,
     reactor::QueueKind,
     rpcs::common,
-    types::{Block, BlockHash, BlockWithMetadata, JsonBlock},
+    types::{
+        Block, BlockHash, BlockHeight, BlockSignals, BlockSummary, BlockWithMetadata,
+        DelegatorReward, EraRewards, JsonBlock, JsonProof, ValidatorRewards,
+    },
 };
 pub use era_summary::EraSummary;
 use era_summary::ERA_SUMMARY;
@@ -43,11 +51,110 @@ static GET_BLOCK_TRANSFERS_RESULT: Lazy<GetBlockTransfersResult> =
     Lazy::new(|| GetBlockTransfersResult {
         api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
         block_hash: Some(*Block::doc_example().hash()),
-        transfers: Some(vec![Transfer::default()]),
+        transfers: Some(vec![TransferV2::from(Transfer::default())]),
+    });
+static GET_BLOCK_SUMMARIES_PARAMS: Lazy<GetBlockSummariesParams> =
+    Lazy::new(|| GetBlockSummariesParams {
+        blocks: BlockSummariesIdentifier::HeightRange {
+            low: Block::doc_example().height(),
+            high: Block::doc_example().height(),
+        },
     });
+static GET_BLOCK_SUMMARIES_RESULT: Lazy<GetBlockSummariesResult> =
+    Lazy::new(|| GetBlockSummariesResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        block_summaries: vec![BlockSummary::new(
+            Block::doc_example(),
+            U512::from(123_456),
+            U512::from(2_500_000_000u64),
+        )],
+    });
+static GET_BLOCK_FINALITY_PARAMS: Lazy<GetBlockFinalityParams> =
+    Lazy::new(|| GetBlockFinalityParams {
+        block_identifier: BlockIdentifier::Hash(*Block::doc_example().hash()),
+    });
+static GET_BLOCK_FINALITY_RESULT: Lazy<GetBlockFinalityResult> =
+    Lazy::new(|| GetBlockFinalityResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        block_hash: *Block::doc_example().hash(),
+        era_id: Block::doc_example().header().era_id(),
+        finality_signatures: JsonBlock::doc_example().proofs.clone(),
+        validator_weights: vec![EraValidatorWeight {
+            public_key: VALIDATOR_PUBLIC_KEY.clone(),
+            weight: U512::from(1_000_000_000u64),
+        }],
+    });
+static VALIDATOR_PUBLIC_KEY: Lazy<PublicKey> = Lazy::new(|| {
+    PublicKey::from_hex("012a1732addc639ea43a89e25d3ad912e40232156dcaa4b9edfc709f43d2fb0876")
+        .unwrap()
+});
+static DELEGATOR_PUBLIC_KEY: Lazy<PublicKey> = Lazy::new(|| {
+    PublicKey::from_hex("01e1b46a25baa8a5c28beb3c9cfb79b572effa04076f00befa57eb70b016153f18")
+        .unwrap()
+});
+static ERA_RANGE: Lazy<EraRange> = Lazy::new(|| EraRange {
+    low: EraId::from(40),
+    high: EraId::from(42),
+});
+static GET_VALIDATOR_REWARDS_PARAMS: Lazy<GetValidatorRewardsParams> =
+    Lazy::new(|| GetValidatorRewardsParams {
+        validator_public_key: VALIDATOR_PUBLIC_KEY.clone(),
+        eras: *ERA_RANGE,
+    });
+static GET_VALIDATOR_REWARDS_RESULT: Lazy<GetValidatorRewardsResult> =
+    Lazy::new(|| GetValidatorRewardsResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        rewards: vec![ValidatorEraRewards {
+            era_id: EraId::from(42),
+            rewards: ValidatorRewards {
+                validator_public_key: VALIDATOR_PUBLIC_KEY.clone(),
+                amount: U512::from(2100),
+                commission: U512::from(100),
+                delegation_rate: 10,
+                delegators: vec![DelegatorReward {
+                    delegator_public_key: DELEGATOR_PUBLIC_KEY.clone(),
+                    amount: U512::from(900),
+                }],
+            },
+        }],
+    });
+static GET_DELEGATOR_REWARDS_PARAMS: Lazy<GetDelegatorRewardsParams> =
+    Lazy::new(|| GetDelegatorRewardsParams {
+        delegator_public_key: DELEGATOR_PUBLIC_KEY.clone(),
+        eras: *ERA_RANGE,
+    });
+static GET_DELEGATOR_REWARDS_RESULT: Lazy<GetDelegatorRewardsResult> =
+    Lazy::new(|| GetDelegatorRewardsResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        rewards: vec![DelegatorEraReward {
+            era_id: EraId::from(42),
+            validator_public_key: VALIDATOR_PUBLIC_KEY.clone(),
+            amount: U512::from(900),
+        }],
+    });
+static GET_SIGNAL_TALLY_PARAMS: Lazy<GetSignalTallyParams> = Lazy::new(|| GetSignalTallyParams {
+    block_count: MAX_SIGNAL_TALLY_BLOCKS,
+});
+static GET_SIGNAL_TALLY_RESULT: Lazy<GetSignalTallyResult> = Lazy::new(|| {
+    let high = Block::doc_example().height();
+    GetSignalTallyResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        low: high.saturating_sub(MAX_SIGNAL_TALLY_BLOCKS - 1),
+        high,
+        block_count: 800,
+        bit_counts: vec![650, 0, 120, 0, 0, 0, 0, 0],
+        proposers: vec![ProposerSignals {
+            public_key: VALIDATOR_PUBLIC_KEY.clone(),
+            signals: 0b0000_0101,
+            block_count: 120,
+        }],
+    }
+});
 static GET_STATE_ROOT_HASH_PARAMS: Lazy<GetStateRootHashParams> =
     Lazy::new(|| GetStateRootHashParams {
-        block_identifier: BlockIdentifier::Height(Block::doc_example().header().height()),
+        block_identifier: BlockIdentifier::Height(BlockHeight::new(
+            Block::doc_example().header().height(),
+        )),
     });
 static GET_STATE_ROOT_HASH_RESULT: Lazy<GetStateRootHashResult> =
     Lazy::new(|| GetStateRootHashResult {
@@ -69,7 +176,7 @@ pub enum BlockIdentifier {
     /// Identify and retrieve the block with its hash.
     Hash(BlockHash),
     /// Identify and retrieve the block with its height.
-    Height(u64),
+    Height(BlockHeight),
 }
 
 impl str::FromStr for BlockIdentifier {
@@ -201,7 +308,7 @@ pub struct GetBlockTransfersResult {
     /// The block hash, if found.
     pub block_hash: Option<BlockHash>,
     /// The block's transfers, if found.
-    pub transfers: Option<Vec<Transfer>>,
+    pub transfers: Option<Vec<TransferV2>>,
 }
 
 impl GetBlockTransfersResult {
@@ -209,7 +316,7 @@ impl GetBlockTransfersResult {
     pub fn new(
         api_version: ProtocolVersion,
         block_hash: Option<BlockHash>,
-        transfers: Option<Vec<Transfer>>,
+        transfers: Option<Vec<TransferV2>>,
     ) -> Self {
         GetBlockTransfersResult {
             api_version,
@@ -268,6 +375,544 @@ impl RpcWithOptionalParams for GetBlockTransfers {
     }
 }
 
+/// The maximum number of blocks which can be summarized in a single "chain_get_block_summaries"
+/// request.
+const MAX_BLOCK_SUMMARIES: u64 = 100;
+
+/// Identifier for the blocks to summarize.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum BlockSummariesIdentifier {
+    /// Summarize a single block.
+    Block(BlockIdentifier),
+    /// Summarize the blocks with heights in the given inclusive range.
+    HeightRange {
+        /// The lowest block height.
+        low: u64,
+        /// The highest block height.
+        high: u64,
+    },
+}
+
+/// Params for "chain_get_block_summaries" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockSummariesParams {
+    /// The blocks to summarize.
+    pub blocks: BlockSummariesIdentifier,
+}
+
+impl DocExample for GetBlockSummariesParams {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCK_SUMMARIES_PARAMS
+    }
+}
+
+/// Result for "chain_get_block_summaries" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockSummariesResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The summaries of the blocks stored on this node, in ascending order of height.
+    pub block_summaries: Vec<BlockSummary>,
+}
+
+impl DocExample for GetBlockSummariesResult {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCK_SUMMARIES_RESULT
+    }
+}
+
+/// "chain_get_block_summaries" RPC.
+pub struct GetBlockSummaries {}
+
+#[async_trait]
+impl RpcWithOptionalParams for GetBlockSummaries {
+    const METHOD: &'static str = "chain_get_block_summaries";
+    type OptionalRequestParams = GetBlockSummariesParams;
+    type ResponseResult = GetBlockSummariesResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        maybe_params: Option<Self::OptionalRequestParams>,
+    ) -> Result<Self::ResponseResult, Error> {
+        // This RPC request is restricted by the block availability index.
+        let only_from_available_block_range = true;
+
+        let block_heights = match maybe_params.map(|params| params.blocks) {
+            Some(BlockSummariesIdentifier::HeightRange { low, high }) => {
+                if low > high || high - low >= MAX_BLOCK_SUMMARIES {
+                    let error_msg = format!(
+                        "height range {}..={} must be non-empty and span at most {} blocks",
+                        low, high, MAX_BLOCK_SUMMARIES
+                    );
+                    info!("{}", error_msg);
+                    return Err(Error::new(ErrorCode::InvalidBlockRange, error_msg));
+                }
+                low..=high
+            }
+            maybe_identifier => {
+                let maybe_block_id = match maybe_identifier {
+                    Some(BlockSummariesIdentifier::Block(block_identifier)) => {
+                        Some(block_identifier)
+                    }
+                    _ => None,
+                };
+                let height = common::get_block(
+                    maybe_block_id,
+                    only_from_available_block_range,
+                    effect_builder,
+                )
+                .await?
+                .height();
+                height..=height
+            }
+        };
+
+        let block_summaries = effect_builder
+            .get_block_summaries_from_storage(block_heights, only_from_available_block_range)
+            .await;
+
+        // Return the result.
+        let result = Self::ResponseResult {
+            api_version,
+            block_summaries,
+        };
+        Ok(result)
+    }
+}
+
+/// Params for "chain_get_block_finality" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockFinalityParams {
+    /// The block identifier.
+    pub block_identifier: BlockIdentifier,
+}
+
+impl DocExample for GetBlockFinalityParams {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCK_FINALITY_PARAMS
+    }
+}
+
+/// A validator's weight in an era.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EraValidatorWeight {
+    /// The validator's public key.
+    pub public_key: PublicKey,
+    /// The validator's weight.
+    pub weight: U512,
+}
+
+/// Result for "chain_get_block_finality" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockFinalityResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The hash of the block.
+    pub block_hash: BlockHash,
+    /// The era of the block.
+    pub era_id: EraId,
+    /// The finality signatures of the block stored on this node.
+    pub finality_signatures: Vec<JsonProof>,
+    /// The weights of the validators expected to sign the block, ordered by public key.  The block
+    /// is finalized if the signatures are from validators exceeding the chainspec's finality
+    /// threshold fraction of the total weight.
+    pub validator_weights: Vec<EraValidatorWeight>,
+}
+
+impl DocExample for GetBlockFinalityResult {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCK_FINALITY_RESULT
+    }
+}
+
+/// "chain_get_block_finality" RPC.
+pub struct GetBlockFinality {}
+
+#[async_trait]
+impl RpcWithOptionalParams for GetBlockFinality {
+    const METHOD: &'static str = "chain_get_block_finality";
+    type OptionalRequestParams = GetBlockFinalityParams;
+    type ResponseResult = GetBlockFinalityResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        maybe_params: Option<Self::OptionalRequestParams>,
+    ) -> Result<Self::ResponseResult, Error> {
+        // This RPC request is restricted by the block availability index.
+        let only_from_available_block_range = true;
+
+        let maybe_block_id = maybe_params.map(|params| params.block_identifier);
+        let BlockWithMetadata {
+            block,
+            block_signatures,
+        } = get_block_with_metadata(
+            maybe_block_id,
+            only_from_available_block_range,
+            effect_builder,
+        )
+        .await?;
+
+        let validator_weights =
+            match linear_chain::era_validator_weights_for_block(block.header(), effect_builder)
+                .await
+            {
+                Ok((_, validator_weights)) => validator_weights,
+                Err(error) => {
+                    let error_msg = format!(
+                        "failed to get the validator weights for block {}: {}",
+                        block.hash(),
+                        error
+                    );
+                    info!("{}", error_msg);
+                    return Err(Error::new(ErrorCode::NoSuchSwitchBlock, error_msg));
+                }
+            };
+
+        let result = Self::ResponseResult {
+            api_version,
+            block_hash: *block.hash(),
+            era_id: block.header().era_id(),
+            finality_signatures: block_signatures
+                .proofs
+                .into_iter()
+                .map(JsonProof::from)
+                .collect(),
+            validator_weights: validator_weights
+                .into_iter()
+                .map(|(public_key, weight)| EraValidatorWeight { public_key, weight })
+                .collect(),
+        };
+        Ok(result)
+    }
+}
+
+/// The maximum number of eras whose rewards can be retrieved in a single
+/// "chain_get_validator_rewards" or "chain_get_delegator_rewards" request.
+const MAX_REWARDS_ERAS: u64 = 100;
+
+/// An inclusive range of eras.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EraRange {
+    /// The lowest era ID.
+    pub low: EraId,
+    /// The highest era ID.
+    pub high: EraId,
+}
+
+impl EraRange {
+    /// Returns the range of era IDs, or an error if it is empty or too long.
+    fn era_ids(&self) -> Result<RangeInclusive<u64>, Error> {
+        let (low, high) = (self.low.value(), self.high.value());
+        if low > high || high - low >= MAX_REWARDS_ERAS {
+            let error_msg = format!(
+                "era range {}..={} must be non-empty and span at most {} eras",
+                low, high, MAX_REWARDS_ERAS
+            );
+            info!("{}", error_msg);
+            return Err(Error::new(ErrorCode::InvalidEraRange, error_msg));
+        }
+        Ok(low..=high)
+    }
+}
+
+/// Params for "chain_get_validator_rewards" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetValidatorRewardsParams {
+    /// The validator's public key.
+    pub validator_public_key: PublicKey,
+    /// The eras to get the rewards for.
+    pub eras: EraRange,
+}
+
+impl DocExample for GetValidatorRewardsParams {
+    fn doc_example() -> &'static Self {
+        &*GET_VALIDATOR_REWARDS_PARAMS
+    }
+}
+
+/// The rewards a validator and its delegators received for an era.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorEraRewards {
+    /// The era the rewards were distributed for.
+    pub era_id: EraId,
+    /// The rewards.
+    pub rewards: ValidatorRewards,
+}
+
+/// Result for "chain_get_validator_rewards" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetValidatorRewardsResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The validator's rewards for the eras recorded on this node, in ascending order of era ID.
+    pub rewards: Vec<ValidatorEraRewards>,
+}
+
+impl DocExample for GetValidatorRewardsResult {
+    fn doc_example() -> &'static Self {
+        &*GET_VALIDATOR_REWARDS_RESULT
+    }
+}
+
+/// "chain_get_validator_rewards" RPC.
+pub struct GetValidatorRewards {}
+
+#[async_trait]
+impl RpcWithParams for GetValidatorRewards {
+    const METHOD: &'static str = "chain_get_validator_rewards";
+    type RequestParams = GetValidatorRewardsParams;
+    type ResponseResult = GetValidatorRewardsResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let era_ids = params.eras.era_ids()?;
+        let rewards = effect_builder
+            .get_era_rewards_from_storage(era_ids)
+            .await
+            .into_iter()
+            .filter_map(|era_rewards: EraRewards| {
+                let rewards = era_rewards.validator(&params.validator_public_key)?.clone();
+                Some(ValidatorEraRewards {
+                    era_id: era_rewards.era_id,
+                    rewards,
+                })
+            })
+            .collect();
+
+        let result = Self::ResponseResult {
+            api_version,
+            rewards,
+        };
+        Ok(result)
+    }
+}
+
+/// Params for "chain_get_delegator_rewards" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDelegatorRewardsParams {
+    /// The delegator's public key.
+    pub delegator_public_key: PublicKey,
+    /// The eras to get the rewards for.
+    pub eras: EraRange,
+}
+
+impl DocExample for GetDelegatorRewardsParams {
+    fn doc_example() -> &'static Self {
+        &*GET_DELEGATOR_REWARDS_PARAMS
+    }
+}
+
+/// The reward a delegator received through one of its validators for an era.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DelegatorEraReward {
+    /// The era the reward was distributed for.
+    pub era_id: EraId,
+    /// The public key of the validator the delegator delegated to.
+    pub validator_public_key: PublicKey,
+    /// The delegator's reward in motes.
+    pub amount: U512,
+}
+
+/// Result for "chain_get_delegator_rewards" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDelegatorRewardsResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The delegator's rewards for the eras recorded on this node, in ascending order of era ID.
+    pub rewards: Vec<DelegatorEraReward>,
+}
+
+impl DocExample for GetDelegatorRewardsResult {
+    fn doc_example() -> &'static Self {
+        &*GET_DELEGATOR_REWARDS_RESULT
+    }
+}
+
+/// "chain_get_delegator_rewards" RPC.
+pub struct GetDelegatorRewards {}
+
+#[async_trait]
+impl RpcWithParams for GetDelegatorRewards {
+    const METHOD: &'static str = "chain_get_delegator_rewards";
+    type RequestParams = GetDelegatorRewardsParams;
+    type ResponseResult = GetDelegatorRewardsResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let era_ids = params.eras.era_ids()?;
+        let mut rewards = Vec::new();
+        for era_rewards in effect_builder.get_era_rewards_from_storage(era_ids).await {
+            rewards.extend(era_rewards.delegator(&params.delegator_public_key).map(
+                |(validator_public_key, reward)| DelegatorEraReward {
+                    era_id: era_rewards.era_id,
+                    validator_public_key: validator_public_key.clone(),
+                    amount: reward.amount,
+                },
+            ));
+        }
+
+        let result = Self::ResponseResult {
+            api_version,
+            rewards,
+        };
+        Ok(result)
+    }
+}
+
+/// The maximum number of blocks whose signals can be tallied in a single "chain_get_signal_tally"
+/// request.
+const MAX_SIGNAL_TALLY_BLOCKS: u64 = 1000;
+
+/// Params for "chain_get_signal_tally" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetSignalTallyParams {
+    /// The number of most recent blocks to tally the signals of.
+    pub block_count: u64,
+}
+
+impl DocExample for GetSignalTallyParams {
+    fn doc_example() -> &'static Self {
+        &*GET_SIGNAL_TALLY_PARAMS
+    }
+}
+
+/// The signals of a validator over the tallied blocks.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProposerSignals {
+    /// The validator's public key.
+    pub public_key: PublicKey,
+    /// The signaling bits of the most recent block the validator proposed.
+    pub signals: u8,
+    /// The number of tallied blocks the validator proposed.
+    pub block_count: u64,
+}
+
+/// Result for "chain_get_signal_tally" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetSignalTallyResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The lowest height of the tallied range of blocks.
+    pub low: u64,
+    /// The highest height of the tallied range of blocks.
+    pub high: u64,
+    /// The number of blocks in the range whose signals were recorded by this node.
+    pub block_count: u64,
+    /// For each of the 8 signaling bits, starting with the least significant one, the number of
+    /// blocks that set it.
+    pub bit_counts: Vec<u64>,
+    /// The signals of each validator that proposed any of the blocks, ordered by public key.
+    pub proposers: Vec<ProposerSignals>,
+}
+
+impl DocExample for GetSignalTallyResult {
+    fn doc_example() -> &'static Self {
+        &*GET_SIGNAL_TALLY_RESULT
+    }
+}
+
+/// "chain_get_signal_tally" RPC.
+pub struct GetSignalTally {}
+
+#[async_trait]
+impl RpcWithOptionalParams for GetSignalTally {
+    const METHOD: &'static str = "chain_get_signal_tally";
+    type OptionalRequestParams = GetSignalTallyParams;
+    type ResponseResult = GetSignalTallyResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        maybe_params: Option<Self::OptionalRequestParams>,
+    ) -> Result<Self::ResponseResult, Error> {
+        let block_count = maybe_params.map_or(MAX_SIGNAL_TALLY_BLOCKS, |params| params.block_count);
+        if block_count == 0 || block_count > MAX_SIGNAL_TALLY_BLOCKS {
+            let error_msg = format!(
+                "block count {} must be between 1 and {}",
+                block_count, MAX_SIGNAL_TALLY_BLOCKS
+            );
+            info!("{}", error_msg);
+            return Err(Error::new(ErrorCode::InvalidBlockRange, error_msg));
+        }
+
+        let high = match effect_builder.get_highest_block_header_from_storage().await {
+            Some(block_header) => block_header.height(),
+            None => {
+                let error_msg = "no blocks stored on this node".to_string();
+                info!("{}", error_msg);
+                return Err(Error::new(ErrorCode::NoSuchBlock, error_msg));
+            }
+        };
+        let low = high.saturating_sub(block_count - 1);
+
+        let mut bit_counts = vec![0; u8::BITS as usize];
+        let mut proposers: BTreeMap<PublicKey, ProposerSignals> = BTreeMap::new();
+        let block_signals = effect_builder
+            .get_block_signals_from_storage(low..=high)
+            .await;
+        for BlockSignals {
+            proposer, signals, ..
+        } in &block_signals
+        {
+            for (bit, count) in bit_counts.iter_mut().enumerate() {
+                if signals & (1 << bit) != 0 {
+                    *count += 1;
+                }
+            }
+            // Blocks are in ascending order of height, so the last one seen is the most recent.
+            let proposer_signals =
+                proposers
+                    .entry(proposer.clone())
+                    .or_insert_with(|| ProposerSignals {
+                        public_key: proposer.clone(),
+                        signals: 0,
+                        block_count: 0,
+                    });
+            proposer_signals.signals = *signals;
+            proposer_signals.block_count += 1;
+        }
+
+        let result = Self::ResponseResult {
+            api_version,
+            low,
+            high,
+            block_count: block_signals.len() as u64,
+            bit_counts,
+            proposers: proposers.into_values().collect(),
+        };
+        Ok(result)
+    }
+}
+
 /// Params for "chain_get_state_root_hash" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -5,9 +5,10 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
+use casper_execution_engine::storage::trie::merkle_proof::TrieMerkleProof;
 use casper_hashing::Digest;
 use casper_json_rpc::{ErrorCodeT, ReservedErrorCode};
-use casper_types::{bytesrepr::ToBytes, Key};
+use casper_types::{bytesrepr::ToBytes, Key, StoredValue as DomainStoredValue};
 
 use super::{
     chain::{self, BlockIdentifier},
@@ -47,7 +48,14 @@ pub(super) async fn run_query_and_encode<REv: ReactorEventT>(
     path: Vec<String>,
 ) -> Result<(StoredValue, String), Error> {
     let (value, proofs) = state::run_query(effect_builder, state_root_hash, base_key, path).await?;
+    encode_query_success(value, proofs)
+}
 
+/// Converts a queried value into its JSON-compatible form and hex-encodes its Merkle proof.
+pub(super) fn encode_query_success(
+    value: DomainStoredValue,
+    proofs: Vec<TrieMerkleProof<Key, DomainStoredValue>>,
+) -> Result<(StoredValue, String), Error> {
     let value_compat = match StoredValue::try_from(value) {
         Ok(value_compat) => value_compat,
         Err(error) => {
@@ -122,3 +130,54 @@ pub(super) async fn get_block<REv: ReactorEventT>(
         .await
         .map(|block_with_metadata| block_with_metadata.block)
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::{
+        core::{
+            engine_state::{EngineConfig, EngineState, QueryRequest, QueryResult},
+            validate_query_proof,
+        },
+        shared::newtypes::CorrelationId,
+        storage::global_state::in_memory::InMemoryGlobalState,
+    };
+    use casper_types::{bytesrepr, CLValue};
+
+    use super::*;
+    use crate::rpcs::{docs::DocExample, state::GetProofBundleResult};
+
+    #[test]
+    fn should_round_trip_proof_bundle() {
+        let key = Key::Hash([1; 32]);
+        let value = DomainStoredValue::CLValue(CLValue::from_t(1u64).unwrap());
+        let other_value = DomainStoredValue::CLValue(CLValue::from_t("other".to_string()).unwrap());
+        let (global_state, state_root_hash) = InMemoryGlobalState::from_pairs(
+            CorrelationId::new(),
+            &[(key, value.clone()), (Key::Hash([2; 32]), other_value)],
+        )
+        .unwrap();
+        let engine_state = EngineState::new(global_state, EngineConfig::default());
+
+        let query_request = QueryRequest::new(state_root_hash, key, vec![]);
+        let (queried_value, proofs) =
+            match engine_state.run_query(CorrelationId::new(), query_request) {
+                Ok(QueryResult::Success { value, proofs }) => (*value, proofs),
+                result => panic!("unexpected query result: {:?}", result),
+            };
+        assert_eq!(queried_value, value);
+
+        // The value and proof as sent to a client decode to a valid proof of the value.
+        let (stored_value, merkle_proof) = encode_query_success(queried_value, proofs).unwrap();
+        assert_eq!(stored_value, StoredValue::try_from(value.clone()).unwrap());
+        let proof_bytes = base16::decode(&merkle_proof).unwrap();
+        let decoded_proofs: Vec<TrieMerkleProof<Key, DomainStoredValue>> =
+            bytesrepr::deserialize(proof_bytes).unwrap();
+        validate_query_proof(&state_root_hash, &decoded_proofs, &key, &[], &value).unwrap();
+
+        // The bundle survives a round trip through JSON.
+        let bundle = GetProofBundleResult::doc_example();
+        let json = serde_json::to_string(bundle).unwrap();
+        let decoded_bundle: GetProofBundleResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(&decoded_bundle, bundle);
+    }
+}
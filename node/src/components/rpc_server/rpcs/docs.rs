@@ -16,12 +16,18 @@ use serde_json::{json, Value};
 use casper_types::ProtocolVersion;
 
 use super::{
-    account::PutDeploy,
-    chain::{GetBlock, GetBlockTransfers, GetEraInfoBySwitchBlock, GetStateRootHash},
-    info::{GetChainspec, GetDeploy, GetPeers, GetStatus, GetValidatorChanges},
+    account::{PrecheckDeploy, PutDeploy, PutTransaction},
+    chain::{
+        GetBlock, GetBlockFinality, GetBlockSummaries, GetBlockTransfers, GetDelegatorRewards,
+        GetEraInfoBySwitchBlock, GetSignalTally, GetStateRootHash, GetValidatorRewards,
+    },
+    info::{
+        GetChainspec, GetDelegatorHistory, GetDeploy, GetEraRewards, GetPeers, GetStatus,
+        GetTransaction, GetValidatorChanges,
+    },
     state::{
-        GetAccountInfo, GetAuctionInfo, GetBalance, GetDictionaryItem, GetItem, QueryBalance,
-        QueryGlobalState,
+        GetAccountInfo, GetAuctionInfo, GetBalance, GetDictionaryItem, GetItem, GetProofBundle,
+        QueryBalance, QueryGlobalState, QueryGlobalStateBulk,
     },
     Error, ReactorEventT, RpcWithOptionalParams, RpcWithParams, RpcWithoutParams,
 };
@@ -67,12 +73,25 @@ pub(crate) static OPEN_RPC_SCHEMA: Lazy<OpenRpcSchema> = Lazy::new(|| {
     };
 
     schema.push_with_params::<PutDeploy>("receives a Deploy to be executed by the network");
+    schema.push_with_params::<PutTransaction>(
+        "receives a Transaction, either a Deploy or in the versioned format, to be executed by the \
+        network",
+    );
+    schema.push_with_params::<PrecheckDeploy>(
+        "validates a Deploy as the network would on receipt, without storing or gossiping it",
+    );
     schema.push_with_params::<GetDeploy>("returns a Deploy from the network");
+    schema.push_with_params::<GetTransaction>(
+        "returns a Transaction, either a Deploy or in the versioned format, from the network",
+    );
     schema.push_with_params::<GetAccountInfo>("returns an Account from the network");
     schema.push_with_params::<GetDictionaryItem>("returns an item from a Dictionary");
     schema.push_with_params::<QueryGlobalState>(
         "a query to global state using either a Block hash or state root hash",
     );
+    schema.push_with_params::<QueryGlobalStateBulk>(
+        "queries multiple values in the same global state, each with a merkle proof",
+    );
     schema.push_with_params::<QueryBalance>(
         "query for a balance using a purse identifier and a state identifier",
     );
@@ -84,7 +103,20 @@ pub(crate) static OPEN_RPC_SCHEMA: Lazy<OpenRpcSchema> = Lazy::new(|| {
         "returns the raw bytes of the chainspec.toml, genesis accounts.toml, and \
         global_state.toml files",
     );
+    schema.push_with_params::<GetEraRewards>(
+        "returns the rewards and stakes of the validators and delegators of an era",
+    );
+    schema.push_with_params::<GetDelegatorHistory>(
+        "returns the stakes and rewards of a delegator over a range of eras",
+    );
     schema.push_with_optional_params::<GetBlock>("returns a Block from the network");
+    schema.push_with_optional_params::<GetBlockSummaries>(
+        "returns summaries of a single Block or of a range of Blocks by height",
+    );
+    schema.push_with_optional_params::<GetBlockFinality>(
+        "returns the finality signatures of a Block along with the weights of the validators \
+        expected to sign it",
+    );
     schema.push_with_optional_params::<GetBlockTransfers>(
         "returns all transfers for a Block from the network",
     );
@@ -103,6 +135,19 @@ pub(crate) static OPEN_RPC_SCHEMA: Lazy<OpenRpcSchema> = Lazy::new(|| {
         "returns the bids and validators as of either a specific block (by height or hash), or \
         the most recently added block",
     );
+    schema.push_with_params::<GetValidatorRewards>(
+        "returns the rewards of a validator and its delegators over a range of eras",
+    );
+    schema.push_with_params::<GetDelegatorRewards>(
+        "returns the rewards of a delegator over a range of eras",
+    );
+    schema.push_with_optional_params::<GetSignalTally>(
+        "returns the tally of the signaling bits set by the proposers of the most recent Blocks",
+    );
+    schema.push_with_params::<GetProofBundle>(
+        "returns a stored value with its merkle proof, the signed Block header it is proven \
+        against, and the signed switch Blocks linking that Block to a trusted switch Block",
+    );
 
     schema
 });
@@ -568,4 +613,22 @@ mod tests {
         let incorrect_optional_params = check_optional_params_fields::<GetAuctionInfo>();
         assert!(incorrect_optional_params.is_empty())
     }
+
+    #[test]
+    fn check_chain_get_block_summaries_required_fields() {
+        let incorrect_optional_params = check_optional_params_fields::<GetBlockSummaries>();
+        assert!(incorrect_optional_params.is_empty())
+    }
+
+    #[test]
+    fn check_chain_get_block_finality_required_fields() {
+        let incorrect_optional_params = check_optional_params_fields::<GetBlockFinality>();
+        assert!(incorrect_optional_params.is_empty())
+    }
+
+    #[test]
+    fn check_chain_get_signal_tally_required_fields() {
+        let incorrect_optional_params = check_optional_params_fields::<GetSignalTally>();
+        assert!(incorrect_optional_params.is_empty())
+    }
 }
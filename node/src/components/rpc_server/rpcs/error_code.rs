@@ -35,6 +35,18 @@ pub enum ErrorCode {
     FailedToGetTrie = -32011,
     /// The requested state root hash was not found.
     NoSuchStateRoot = -32012,
+    /// The requested switch block was not found.
+    NoSuchSwitchBlock = -32013,
+    /// The given trusted block cannot anchor a proof bundle for the requested block.
+    InvalidTrustedBlock = -32014,
+    /// The requested range of blocks is empty or too long.
+    InvalidBlockRange = -32015,
+    /// The requested range of eras is empty or too long.
+    InvalidEraRange = -32016,
+    /// The request exceeds the server's limits on the number of queried values.
+    TooManyQueries = -32017,
+    /// No rewards or stakes are recorded for the requested era.
+    NoEraRewards = -32018,
 }
 
 impl From<ErrorCode> for (i64, &'static str) {
@@ -59,6 +71,12 @@ impl From<ErrorCode> for (i64, &'static str) {
             }
             ErrorCode::FailedToGetTrie => (error_code as i64, "Failed to get trie"),
             ErrorCode::NoSuchStateRoot => (error_code as i64, "No such state root"),
+            ErrorCode::NoSuchSwitchBlock => (error_code as i64, "No such switch block"),
+            ErrorCode::InvalidTrustedBlock => (error_code as i64, "Invalid trusted block"),
+            ErrorCode::InvalidBlockRange => (error_code as i64, "Invalid block range"),
+            ErrorCode::InvalidEraRange => (error_code as i64, "Invalid era range"),
+            ErrorCode::TooManyQueries => (error_code as i64, "Too many queries"),
+            ErrorCode::NoEraRewards => (error_code as i64, "No rewards recorded for era"),
         }
     }
 }
@@ -3,7 +3,10 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
-use std::{collections::BTreeMap, str};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str,
+};
 
 use async_trait::async_trait;
 use once_cell::sync::Lazy;
@@ -11,7 +14,9 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey};
+use casper_types::{
+    system::auction::DelegationRate, EraId, ExecutionResult, ProtocolVersion, PublicKey, U512,
+};
 
 use super::{
     docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
@@ -23,7 +28,8 @@ use crate::{
     reactor::QueueKind,
     types::{
         Block, BlockHash, BlockHashAndHeight, ChainspecRawBytes, Deploy, DeployHash,
-        DeployMetadataExt, GetStatusResult, PeersMap,
+        DeployMetadataExt, EraRewards, EraStakes, GetStatusResult, PeersMap, Transaction,
+        TransactionHash,
     },
 };
 
@@ -39,6 +45,14 @@ static GET_DEPLOY_RESULT: Lazy<GetDeployResult> = Lazy::new(|| GetDeployResult {
         result: ExecutionResult::example().clone(),
     }],
     block_hash_and_height: None,
+    unresolvable_dependencies: None,
+});
+static GET_TRANSACTION_PARAMS: Lazy<GetTransactionParams> = Lazy::new(|| GetTransactionParams {
+    transaction_hash: Transaction::doc_example().hash(),
+});
+static GET_TRANSACTION_RESULT: Lazy<GetTransactionResult> = Lazy::new(|| GetTransactionResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    transaction: Transaction::doc_example().clone(),
 });
 static GET_PEERS_RESULT: Lazy<GetPeersResult> = Lazy::new(|| GetPeersResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
@@ -57,6 +71,55 @@ static GET_CHAINSPEC_RESULT: Lazy<GetChainspecResult> = Lazy::new(|| GetChainspe
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     chainspec_bytes: ChainspecRawBytes::new(vec![42, 42].into(), None, None),
 });
+static VALIDATOR_PUBLIC_KEY: Lazy<PublicKey> = Lazy::new(|| {
+    PublicKey::from_hex("012a1732addc639ea43a89e25d3ad912e40232156dcaa4b9edfc709f43d2fb0876")
+        .unwrap()
+});
+static DELEGATOR_PUBLIC_KEY: Lazy<PublicKey> = Lazy::new(|| {
+    PublicKey::from_hex("01e1b46a25baa8a5c28beb3c9cfb79b572effa04076f00befa57eb70b016153f18")
+        .unwrap()
+});
+static GET_ERA_REWARDS_PARAMS: Lazy<GetEraRewardsParams> = Lazy::new(|| GetEraRewardsParams {
+    era_id: EraId::from(42),
+    offset: 0,
+    limit: Some(MAX_ERA_REWARDS_PAGE_SIZE),
+});
+static GET_ERA_REWARDS_RESULT: Lazy<GetEraRewardsResult> = Lazy::new(|| GetEraRewardsResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    era_id: EraId::from(42),
+    validator_count: 1,
+    validators: vec![ValidatorEraOutcome {
+        validator_public_key: VALIDATOR_PUBLIC_KEY.clone(),
+        stake: Some(U512::from(100_000)),
+        reward: Some(U512::from(2100)),
+        commission: Some(U512::from(100)),
+        delegation_rate: Some(10),
+        delegators: vec![DelegatorEraOutcome {
+            delegator_public_key: DELEGATOR_PUBLIC_KEY.clone(),
+            stake: Some(U512::from(50_000)),
+            reward: Some(U512::from(900)),
+        }],
+    }],
+    equivocators: vec![],
+    inactive_validators: vec![],
+});
+static GET_DELEGATOR_HISTORY_PARAMS: Lazy<GetDelegatorHistoryParams> =
+    Lazy::new(|| GetDelegatorHistoryParams {
+        delegator_public_key: DELEGATOR_PUBLIC_KEY.clone(),
+        start_era_id: Some(EraId::from(42)),
+        limit: Some(MAX_DELEGATOR_HISTORY_ERAS),
+    });
+static GET_DELEGATOR_HISTORY_RESULT: Lazy<GetDelegatorHistoryResult> =
+    Lazy::new(|| GetDelegatorHistoryResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        history: vec![DelegatorHistoryEntry {
+            era_id: EraId::from(42),
+            validator_public_key: VALIDATOR_PUBLIC_KEY.clone(),
+            stake: Some(U512::from(50_000)),
+            reward: Some(U512::from(900)),
+        }],
+        next_era_id: None,
+    });
 
 /// Params for "info_get_deploy" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -106,6 +169,11 @@ pub struct GetDeployResult {
     /// only provided if the full execution results are not know on this node.
     #[serde(skip_serializing_if = "Option::is_none", flatten)]
     pub block_hash_and_height: Option<BlockHashAndHeight>,
+    /// The dependencies of this deploy which are neither executed nor known to this node, only
+    /// provided if the deploy is still pending and has any such dependencies. The deploy can't
+    /// be included in a block before these are executed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unresolvable_dependencies: Option<Vec<DeployHash>>,
 }
 
 impl DocExample for GetDeployResult {
@@ -167,16 +235,104 @@ impl RpcWithParams for GetDeploy {
             DeployMetadataExt::Empty => (Vec::new(), None),
         };
 
+        // Only deploys which are not yet executed can have unresolvable dependencies.
+        let unresolvable_dependencies =
+            if execution_results.is_empty() && block_hash_and_height.is_none() {
+                effect_builder
+                    .get_unresolvable_deploy_dependencies(*deploy.id())
+                    .await
+                    .filter(|dependencies| !dependencies.is_empty())
+            } else {
+                None
+            };
+
         let result = Self::ResponseResult {
             api_version,
             deploy,
             execution_results,
             block_hash_and_height,
+            unresolvable_dependencies,
         };
         Ok(result)
     }
 }
 
+/// Params for "info_get_transaction" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetTransactionParams {
+    /// The transaction hash.
+    pub transaction_hash: TransactionHash,
+}
+
+impl DocExample for GetTransactionParams {
+    fn doc_example() -> &'static Self {
+        &*GET_TRANSACTION_PARAMS
+    }
+}
+
+/// Result for "info_get_transaction" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetTransactionResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The transaction, with the approvals originally received by the node.
+    pub transaction: Transaction,
+}
+
+impl DocExample for GetTransactionResult {
+    fn doc_example() -> &'static Self {
+        &*GET_TRANSACTION_RESULT
+    }
+}
+
+/// "info_get_transaction" RPC.
+///
+/// Returns either a deploy or a transaction in the versioned format.  Execution results are only
+/// available for deploys, via "info_get_deploy".
+pub struct GetTransaction {}
+
+#[async_trait]
+impl RpcWithParams for GetTransaction {
+    const METHOD: &'static str = "info_get_transaction";
+    type RequestParams = GetTransactionParams;
+    type ResponseResult = GetTransactionResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let maybe_transaction = match params.transaction_hash {
+            TransactionHash::Deploy(deploy_hash) => effect_builder
+                .get_deploys_from_storage(vec![deploy_hash])
+                .await
+                .into_iter()
+                .next()
+                .flatten()
+                .map(|deploy| Transaction::Deploy(deploy.discard_finalized_approvals())),
+            TransactionHash::V2(transaction_hash) => effect_builder
+                .get_transaction_v2_from_storage(transaction_hash)
+                .await
+                .map(Transaction::V2),
+        };
+
+        match maybe_transaction {
+            Some(transaction) => Ok(Self::ResponseResult {
+                api_version,
+                transaction,
+            }),
+            None => {
+                let message = format!("failed to get {} from storage", params.transaction_hash);
+                info!("{}", message);
+                Err(Error::new(ErrorCode::NoSuchDeploy, message))
+            }
+        }
+    }
+}
+
 /// Result for "info_get_peers" RPC response.
 #[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -390,3 +546,359 @@ impl RpcWithoutParams for GetChainspec {
         Ok(result)
     }
 }
+
+/// The maximum number of validators returned by a single "info_get_era_rewards" request.
+const MAX_ERA_REWARDS_PAGE_SIZE: u32 = 100;
+
+/// The maximum number of eras covered by a single "info_get_delegator_history" request.
+const MAX_DELEGATOR_HISTORY_ERAS: u64 = 100;
+
+/// The stake and reward of a delegator of a validator in an era.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DelegatorEraOutcome {
+    /// The delegator's public key.
+    pub delegator_public_key: PublicKey,
+    /// The delegator's stake in motes, if recorded.
+    pub stake: Option<U512>,
+    /// The delegator's reward in motes, if recorded.
+    pub reward: Option<U512>,
+}
+
+/// The stake and rewards of a validator and its delegators in an era.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorEraOutcome {
+    /// The validator's public key.
+    pub validator_public_key: PublicKey,
+    /// The validator's own stake in motes, if recorded.
+    pub stake: Option<U512>,
+    /// The validator's reward in motes, including the commission, if recorded.
+    pub reward: Option<U512>,
+    /// The commission in motes the validator charged its delegators, if recorded.
+    pub commission: Option<U512>,
+    /// The delegation rate the commission was charged at, in percent, if recorded.
+    pub delegation_rate: Option<DelegationRate>,
+    /// The validator's delegators, ordered by public key.
+    pub delegators: Vec<DelegatorEraOutcome>,
+}
+
+impl ValidatorEraOutcome {
+    fn new(validator_public_key: PublicKey) -> Self {
+        ValidatorEraOutcome {
+            validator_public_key,
+            stake: None,
+            reward: None,
+            commission: None,
+            delegation_rate: None,
+            delegators: vec![],
+        }
+    }
+}
+
+/// Joins the rewards and stakes recorded for an era by validator, ordered by public key.
+///
+/// Stakes have been recorded since a later version than rewards, so either might be missing.
+fn validator_era_outcomes(
+    maybe_era_rewards: Option<&EraRewards>,
+    maybe_era_stakes: Option<&EraStakes>,
+) -> Vec<ValidatorEraOutcome> {
+    let mut validators: BTreeMap<PublicKey, ValidatorEraOutcome> = BTreeMap::new();
+    let mut delegators: BTreeMap<(PublicKey, PublicKey), DelegatorEraOutcome> = BTreeMap::new();
+    let new_delegator = |delegator_public_key: &PublicKey| DelegatorEraOutcome {
+        delegator_public_key: delegator_public_key.clone(),
+        stake: None,
+        reward: None,
+    };
+
+    for validator_stake in maybe_era_stakes
+        .into_iter()
+        .flat_map(|era_stakes| &era_stakes.validators)
+    {
+        let validator_public_key = &validator_stake.validator_public_key;
+        validators
+            .entry(validator_public_key.clone())
+            .or_insert_with(|| ValidatorEraOutcome::new(validator_public_key.clone()))
+            .stake = Some(validator_stake.stake);
+        for delegator_stake in &validator_stake.delegators {
+            let delegator_public_key = &delegator_stake.delegator_public_key;
+            delegators
+                .entry((validator_public_key.clone(), delegator_public_key.clone()))
+                .or_insert_with(|| new_delegator(delegator_public_key))
+                .stake = Some(delegator_stake.stake);
+        }
+    }
+
+    for validator_rewards in maybe_era_rewards
+        .into_iter()
+        .flat_map(|era_rewards| &era_rewards.validators)
+    {
+        let validator_public_key = &validator_rewards.validator_public_key;
+        let validator = validators
+            .entry(validator_public_key.clone())
+            .or_insert_with(|| ValidatorEraOutcome::new(validator_public_key.clone()));
+        validator.reward = Some(validator_rewards.amount);
+        validator.commission = Some(validator_rewards.commission);
+        validator.delegation_rate = Some(validator_rewards.delegation_rate);
+        for delegator_reward in &validator_rewards.delegators {
+            let delegator_public_key = &delegator_reward.delegator_public_key;
+            delegators
+                .entry((validator_public_key.clone(), delegator_public_key.clone()))
+                .or_insert_with(|| new_delegator(delegator_public_key))
+                .reward = Some(delegator_reward.amount);
+        }
+    }
+
+    // Delegators are ordered by validator first, then by their own public key.
+    for ((validator_public_key, _), delegator) in delegators {
+        if let Some(validator) = validators.get_mut(&validator_public_key) {
+            validator.delegators.push(delegator);
+        }
+    }
+    validators.into_values().collect()
+}
+
+/// Params for "info_get_era_rewards" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetEraRewardsParams {
+    /// The era to get the rewards for.
+    pub era_id: EraId,
+    /// The number of validators to skip, in order of public key.
+    #[serde(default)]
+    pub offset: u32,
+    /// The maximum number of validators to return.  Defaults to and is capped at 100.
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+impl DocExample for GetEraRewardsParams {
+    fn doc_example() -> &'static Self {
+        &*GET_ERA_REWARDS_PARAMS
+    }
+}
+
+/// Result for "info_get_era_rewards" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetEraRewardsResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The era the rewards were distributed for.
+    pub era_id: EraId,
+    /// The total number of validators recorded for the era, across all pages.
+    pub validator_count: u32,
+    /// The requested page of validators, ordered by public key.
+    pub validators: Vec<ValidatorEraOutcome>,
+    /// The validators evicted at the end of the era for equivocating, if recorded.
+    pub equivocators: Vec<PublicKey>,
+    /// The validators evicted at the end of the era for being inactive, if recorded.
+    pub inactive_validators: Vec<PublicKey>,
+}
+
+impl DocExample for GetEraRewardsResult {
+    fn doc_example() -> &'static Self {
+        &*GET_ERA_REWARDS_RESULT
+    }
+}
+
+/// "info_get_era_rewards" RPC.
+pub struct GetEraRewards {}
+
+#[async_trait]
+impl RpcWithParams for GetEraRewards {
+    const METHOD: &'static str = "info_get_era_rewards";
+    type RequestParams = GetEraRewardsParams;
+    type ResponseResult = GetEraRewardsResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let limit = params
+            .limit
+            .unwrap_or(MAX_ERA_REWARDS_PAGE_SIZE)
+            .min(MAX_ERA_REWARDS_PAGE_SIZE);
+        let era_ids = params.era_id.value()..=params.era_id.value();
+        let maybe_era_rewards = effect_builder
+            .get_era_rewards_from_storage(era_ids.clone())
+            .await
+            .pop();
+        let maybe_era_stakes = effect_builder
+            .get_era_stakes_from_storage(era_ids)
+            .await
+            .pop();
+        if maybe_era_rewards.is_none() && maybe_era_stakes.is_none() {
+            let error_msg = format!("no rewards recorded for {}", params.era_id);
+            info!("{}", error_msg);
+            return Err(Error::new(ErrorCode::NoEraRewards, error_msg));
+        }
+
+        let validators =
+            validator_era_outcomes(maybe_era_rewards.as_ref(), maybe_era_stakes.as_ref());
+        let validator_count = validators.len() as u32;
+        let validators = validators
+            .into_iter()
+            .skip(params.offset as usize)
+            .take(limit as usize)
+            .collect();
+        let (equivocators, inactive_validators) = maybe_era_stakes
+            .map(|era_stakes| (era_stakes.equivocators, era_stakes.inactive_validators))
+            .unwrap_or_default();
+
+        let result = Self::ResponseResult {
+            api_version,
+            era_id: params.era_id,
+            validator_count,
+            validators,
+            equivocators,
+            inactive_validators,
+        };
+        Ok(result)
+    }
+}
+
+/// Params for "info_get_delegator_history" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDelegatorHistoryParams {
+    /// The delegator's public key.
+    pub delegator_public_key: PublicKey,
+    /// The first era to get the history for.  Defaults to the genesis era.
+    #[serde(default)]
+    pub start_era_id: Option<EraId>,
+    /// The maximum number of eras to get the history for.  Defaults to and is capped at 100.
+    #[serde(default)]
+    pub limit: Option<u64>,
+}
+
+impl DocExample for GetDelegatorHistoryParams {
+    fn doc_example() -> &'static Self {
+        &*GET_DELEGATOR_HISTORY_PARAMS
+    }
+}
+
+/// The stake and reward of a delegator through one of its validators in an era.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DelegatorHistoryEntry {
+    /// The era.
+    pub era_id: EraId,
+    /// The public key of the validator the delegator delegated to.
+    pub validator_public_key: PublicKey,
+    /// The delegator's stake in motes, if recorded.
+    pub stake: Option<U512>,
+    /// The delegator's reward in motes, if recorded.
+    pub reward: Option<U512>,
+}
+
+/// Result for "info_get_delegator_history" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDelegatorHistoryResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The delegator's stakes and rewards in the requested eras, ordered by era ID and then by
+    /// validator public key.
+    pub history: Vec<DelegatorHistoryEntry>,
+    /// The era to request the next page of history from, or `None` if the requested eras reach the
+    /// current era.
+    pub next_era_id: Option<EraId>,
+}
+
+impl DocExample for GetDelegatorHistoryResult {
+    fn doc_example() -> &'static Self {
+        &*GET_DELEGATOR_HISTORY_RESULT
+    }
+}
+
+/// "info_get_delegator_history" RPC.
+pub struct GetDelegatorHistory {}
+
+#[async_trait]
+impl RpcWithParams for GetDelegatorHistory {
+    const METHOD: &'static str = "info_get_delegator_history";
+    type RequestParams = GetDelegatorHistoryParams;
+    type ResponseResult = GetDelegatorHistoryResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let limit = params
+            .limit
+            .unwrap_or(MAX_DELEGATOR_HISTORY_ERAS)
+            .min(MAX_DELEGATOR_HISTORY_ERAS);
+        if limit == 0 {
+            let error_msg = "limit must be at least 1".to_string();
+            info!("{}", error_msg);
+            return Err(Error::new(ErrorCode::InvalidEraRange, error_msg));
+        }
+        let start = params.start_era_id.unwrap_or_default().value();
+        let current_era = match effect_builder.get_highest_block_header_from_storage().await {
+            Some(block_header) => block_header.era_id().value(),
+            None => {
+                return Ok(Self::ResponseResult {
+                    api_version,
+                    history: vec![],
+                    next_era_id: None,
+                })
+            }
+        };
+        let end = start.saturating_add(limit - 1).min(current_era);
+        let next_era_id = (end < current_era).then(|| EraId::new(end + 1));
+
+        let mut era_rewards: BTreeMap<EraId, EraRewards> = effect_builder
+            .get_era_rewards_from_storage(start..=end)
+            .await
+            .into_iter()
+            .map(|era_rewards| (era_rewards.era_id, era_rewards))
+            .collect();
+        let mut era_stakes: BTreeMap<EraId, EraStakes> = effect_builder
+            .get_era_stakes_from_storage(start..=end)
+            .await
+            .into_iter()
+            .map(|era_stakes| (era_stakes.era_id, era_stakes))
+            .collect();
+        let era_ids: BTreeSet<EraId> = era_rewards
+            .keys()
+            .chain(era_stakes.keys())
+            .copied()
+            .collect();
+
+        let mut history = Vec::new();
+        for era_id in era_ids {
+            let validators = validator_era_outcomes(
+                era_rewards.remove(&era_id).as_ref(),
+                era_stakes.remove(&era_id).as_ref(),
+            );
+            for validator in validators {
+                history.extend(
+                    validator
+                        .delegators
+                        .into_iter()
+                        .filter(|delegator| {
+                            delegator.delegator_public_key == params.delegator_public_key
+                        })
+                        .map(|delegator| DelegatorHistoryEntry {
+                            era_id,
+                            validator_public_key: validator.validator_public_key.clone(),
+                            stake: delegator.stake,
+                            reward: delegator.reward,
+                        }),
+                );
+            }
+        }
+
+        let result = Self::ResponseResult {
+            api_version,
+            history,
+            next_era_id,
+        };
+        Ok(result)
+    }
+}
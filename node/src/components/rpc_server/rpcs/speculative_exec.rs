@@ -3,16 +3,18 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
-use std::str;
+use std::{convert::TryFrom, str};
 
 use async_trait::async_trait;
-use casper_execution_engine::core::engine_state::Error as EngineStateError;
+use casper_execution_engine::{
+    core::engine_state::Error as EngineStateError, shared::gas_profile::GasProfile,
+};
 use casper_json_rpc::ReservedErrorCode;
 use once_cell::sync::Lazy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use casper_types::{ExecutionResult, ProtocolVersion};
+use casper_types::{ExecutionResult, ProtocolVersion, U512};
 
 use super::{
     chain::BlockIdentifier,
@@ -23,17 +25,25 @@ use super::{
 use crate::{
     effect::{requests::RpcRequest, EffectBuilder},
     reactor::QueueKind,
-    types::{Block, BlockHash, Deploy},
+    types::{Block, BlockHash, Deploy, Transaction},
 };
 
 static SPECULATIVE_EXEC_PARAMS: Lazy<SpeculativeExecParams> = Lazy::new(|| SpeculativeExecParams {
     block_identifier: Some(BlockIdentifier::Hash(*Block::doc_example().hash())),
     deploy: Deploy::doc_example().clone(),
+    profile_gas: false,
 });
+static SPECULATIVE_EXEC_TRANSACTION_PARAMS: Lazy<SpeculativeExecTransactionParams> =
+    Lazy::new(|| SpeculativeExecTransactionParams {
+        block_identifier: Some(BlockIdentifier::Hash(*Block::doc_example().hash())),
+        transaction: Transaction::doc_example().clone(),
+        profile_gas: false,
+    });
 static SPECULATIVE_EXEC_RESULT: Lazy<SpeculativeExecResult> = Lazy::new(|| SpeculativeExecResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     block_hash: *Block::doc_example().hash(),
     execution_result: ExecutionResult::example().clone(),
+    gas_profile: None,
 });
 
 /// Params for "speculative_exec" RPC request.
@@ -44,6 +54,10 @@ pub struct SpeculativeExecParams {
     pub block_identifier: Option<BlockIdentifier>,
     /// Deploy to execute.
     pub deploy: Deploy,
+    /// Whether to return a breakdown of the gas used by host function.  Profiling slows down
+    /// execution considerably.
+    #[serde(default)]
+    pub profile_gas: bool,
 }
 
 impl DocExample for SpeculativeExecParams {
@@ -63,6 +77,9 @@ pub struct SpeculativeExecResult {
     pub block_hash: BlockHash,
     /// Result of the execution.
     pub execution_result: ExecutionResult,
+    /// Breakdown of the gas used by the execution, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_profile: Option<JsonGasProfile>,
 }
 
 impl DocExample for SpeculativeExecResult {
@@ -71,6 +88,55 @@ impl DocExample for SpeculativeExecResult {
     }
 }
 
+/// The calls to a host function made during speculative execution.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct HostFunctionProfile {
+    /// The name of the host function.
+    pub name: String,
+    /// The number of calls.
+    pub calls: u64,
+    /// The gas charged by the calls, excluding that charged by any Wasm they executed.
+    pub gas: U512,
+    /// The wall-clock time spent in the calls in nanoseconds, excluding that spent in nested host
+    /// function calls.
+    pub time_ns: u64,
+}
+
+/// A breakdown of the gas used during speculative execution, covering payment, session and
+/// finalization.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonGasProfile {
+    /// The total gas charged by Wasm metering and host functions.
+    pub total_gas: U512,
+    /// The gas charged by the metering of Wasm, for executing opcodes and growing memory.
+    pub wasm_gas: U512,
+    /// The host functions called, in descending order of the gas charged by them.
+    pub host_functions: Vec<HostFunctionProfile>,
+}
+
+impl From<GasProfile> for JsonGasProfile {
+    fn from(gas_profile: GasProfile) -> Self {
+        let mut host_functions: Vec<_> = gas_profile
+            .host_functions
+            .iter()
+            .map(|(name, usage)| HostFunctionProfile {
+                name: name.to_string(),
+                calls: usage.calls,
+                gas: usage.gas.value(),
+                time_ns: u64::try_from(usage.time.as_nanos()).unwrap_or(u64::MAX),
+            })
+            .collect();
+        host_functions.sort_by(|a, b| b.gas.cmp(&a.gas).then_with(|| a.name.cmp(&b.name)));
+        JsonGasProfile {
+            total_gas: gas_profile.total().value(),
+            wasm_gas: gas_profile.wasm.value(),
+            host_functions,
+        }
+    }
+}
+
 /// "speculative_exec" RPC
 pub struct SpeculativeExec {}
 
@@ -88,85 +154,157 @@ impl RpcWithParams for SpeculativeExec {
         let SpeculativeExecParams {
             block_identifier: maybe_block_id,
             deploy,
+            profile_gas,
         } = params;
-        // This RPC request is restricted by the block availability index.
-        let only_from_available_block_range = true;
-
-        let block = common::get_block(
+        execute(
+            effect_builder,
+            api_version,
             maybe_block_id,
-            only_from_available_block_range,
+            Transaction::Deploy(deploy),
+            profile_gas,
+        )
+        .await
+    }
+}
+
+/// Params for "speculative_exec_transaction" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SpeculativeExecTransactionParams {
+    /// Block hash on top of which to execute the transaction.
+    pub block_identifier: Option<BlockIdentifier>,
+    /// Deploy or transaction in the versioned format to execute.
+    pub transaction: Transaction,
+    /// Whether to return a breakdown of the gas used by host function.  Profiling slows down
+    /// execution considerably.
+    #[serde(default)]
+    pub profile_gas: bool,
+}
+
+impl DocExample for SpeculativeExecTransactionParams {
+    fn doc_example() -> &'static Self {
+        &*SPECULATIVE_EXEC_TRANSACTION_PARAMS
+    }
+}
+
+/// "speculative_exec_transaction" RPC
+pub struct SpeculativeExecTransaction {}
+
+#[async_trait]
+impl RpcWithParams for SpeculativeExecTransaction {
+    const METHOD: &'static str = "speculative_exec_transaction";
+    type RequestParams = SpeculativeExecTransactionParams;
+    type ResponseResult = SpeculativeExecResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let SpeculativeExecTransactionParams {
+            block_identifier: maybe_block_id,
+            transaction,
+            profile_gas,
+        } = params;
+        execute(
             effect_builder,
+            api_version,
+            maybe_block_id,
+            transaction,
+            profile_gas,
         )
-        .await?;
-        let block_hash = *block.hash();
-        let result = effect_builder
-            .make_request(
-                |responder| RpcRequest::SpeculativeDeployExecute {
-                    block_header: block.take_header(),
-                    deploy: Box::new(deploy),
-                    responder,
-                },
-                QueueKind::Api,
-            )
-            .await;
-
-        match result {
-            Ok(Some(execution_result)) => {
-                let result = Self::ResponseResult {
-                    api_version,
-                    block_hash,
-                    execution_result,
-                };
-                Ok(result)
-            }
-            Ok(None) => Err(Error::new(
-                ErrorCode::NoSuchBlock,
-                "block hash not found".to_string(),
-            )),
-            Err(error) => {
-                let rpc_error = match error {
-                    EngineStateError::RootNotFound(_) => Error::new(ErrorCode::NoSuchStateRoot, ""),
-                    EngineStateError::WasmPreprocessing(error) => {
-                        Error::new(ErrorCode::InvalidDeploy, &format!("{}", error))
-                    }
-                    EngineStateError::InvalidDeployItemVariant(error) => {
-                        Error::new(ErrorCode::InvalidDeploy, &error)
-                    }
-                    EngineStateError::InvalidProtocolVersion(_) => Error::new(
-                        ErrorCode::InvalidDeploy,
-                        &format!("deploy used invalid protocol version {}", error),
-                    ),
-                    EngineStateError::Deploy => Error::new(ErrorCode::InvalidDeploy, ""),
-                    EngineStateError::Genesis(_)
-                    | EngineStateError::WasmSerialization(_)
-                    | EngineStateError::Exec(_)
-                    | EngineStateError::Storage(_)
-                    | EngineStateError::Authorization
-                    | EngineStateError::InsufficientPayment
-                    | EngineStateError::GasConversionOverflow
-                    | EngineStateError::Finalization
-                    | EngineStateError::Bytesrepr(_)
-                    | EngineStateError::Mint(_)
-                    | EngineStateError::InvalidKeyVariant
-                    | EngineStateError::ProtocolUpgrade(_)
-                    | EngineStateError::CommitError(_)
-                    | EngineStateError::MissingSystemContractRegistry
-                    | EngineStateError::MissingSystemContractHash(_)
-                    | EngineStateError::RuntimeStackOverflow
-                    | EngineStateError::FailedToGetWithdrawKeys
-                    | EngineStateError::FailedToGetStoredWithdraws
-                    | EngineStateError::FailedToGetWithdrawPurses
-                    | EngineStateError::FailedToRetrieveUnbondingDelay
-                    | EngineStateError::FailedToRetrieveEraId => {
-                        Error::new(ReservedErrorCode::InternalError, &format!("{}", error))
-                    }
-                    _ => Error::new(
-                        ReservedErrorCode::InternalError,
-                        &format!("Unhandled engine state error: {}", error),
-                    ),
-                };
-                Err(rpc_error)
-            }
+        .await
+    }
+}
+
+/// Executes the deploy or transaction on top of the given block, or the highest block if none,
+/// profiling its gas usage if `profile_gas` is set.
+async fn execute<REv: ReactorEventT>(
+    effect_builder: EffectBuilder<REv>,
+    api_version: ProtocolVersion,
+    maybe_block_id: Option<BlockIdentifier>,
+    transaction: Transaction,
+    profile_gas: bool,
+) -> Result<SpeculativeExecResult, Error> {
+    // This RPC request is restricted by the block availability index.
+    let only_from_available_block_range = true;
+
+    let block = common::get_block(
+        maybe_block_id,
+        only_from_available_block_range,
+        effect_builder,
+    )
+    .await?;
+    let block_hash = *block.hash();
+    let result = effect_builder
+        .make_request(
+            |responder| RpcRequest::SpeculativeDeployExecute {
+                block_header: block.take_header(),
+                transaction: Box::new(transaction),
+                profile_gas,
+                responder,
+            },
+            QueueKind::Api,
+        )
+        .await;
+
+    match result {
+        Ok(Some((execution_result, maybe_gas_profile))) => {
+            let result = SpeculativeExecResult {
+                api_version,
+                block_hash,
+                execution_result,
+                gas_profile: maybe_gas_profile.map(JsonGasProfile::from),
+            };
+            Ok(result)
+        }
+        Ok(None) => Err(Error::new(
+            ErrorCode::NoSuchBlock,
+            "block hash not found".to_string(),
+        )),
+        Err(error) => {
+            let rpc_error = match error {
+                EngineStateError::RootNotFound(_) => Error::new(ErrorCode::NoSuchStateRoot, ""),
+                EngineStateError::WasmPreprocessing(error) => {
+                    Error::new(ErrorCode::InvalidDeploy, &format!("{}", error))
+                }
+                EngineStateError::InvalidDeployItemVariant(error) => {
+                    Error::new(ErrorCode::InvalidDeploy, &error)
+                }
+                EngineStateError::InvalidProtocolVersion(_) => Error::new(
+                    ErrorCode::InvalidDeploy,
+                    &format!("deploy used invalid protocol version {}", error),
+                ),
+                EngineStateError::Deploy => Error::new(ErrorCode::InvalidDeploy, ""),
+                EngineStateError::Genesis(_)
+                | EngineStateError::WasmSerialization(_)
+                | EngineStateError::Exec(_)
+                | EngineStateError::Storage(_)
+                | EngineStateError::Authorization
+                | EngineStateError::InsufficientPayment
+                | EngineStateError::GasConversionOverflow
+                | EngineStateError::Finalization
+                | EngineStateError::Bytesrepr(_)
+                | EngineStateError::Mint(_)
+                | EngineStateError::InvalidKeyVariant
+                | EngineStateError::ProtocolUpgrade(_)
+                | EngineStateError::CommitError(_)
+                | EngineStateError::MissingSystemContractRegistry
+                | EngineStateError::MissingSystemContractHash(_)
+                | EngineStateError::RuntimeStackOverflow
+                | EngineStateError::FailedToGetWithdrawKeys
+                | EngineStateError::FailedToGetStoredWithdraws
+                | EngineStateError::FailedToGetWithdrawPurses
+                | EngineStateError::FailedToRetrieveUnbondingDelay
+                | EngineStateError::FailedToRetrieveEraId => {
+                    Error::new(ReservedErrorCode::InternalError, &format!("{}", error))
+                }
+                _ => Error::new(
+                    ReservedErrorCode::InternalError,
+                    &format!("Unhandled engine state error: {}", error),
+                ),
+            };
+            Err(rpc_error)
         }
     }
 }
@@ -20,22 +20,24 @@ use casper_json_rpc::ReservedErrorCode;
 use casper_types::{
     account::AccountHash,
     bytesrepr::{Bytes, ToBytes},
-    CLValue, Key, ProtocolVersion, PublicKey, SecretKey, StoredValue as DomainStoredValue, URef,
-    U512,
+    CLValue, EraId, Key, ProtocolVersion, PublicKey, SecretKey, StoredValue as DomainStoredValue,
+    URef, U512,
 };
 
 use crate::{
     effect::EffectBuilder,
     reactor::QueueKind,
     rpcs::{
-        chain::BlockIdentifier,
+        archive::ProofQuery,
+        chain::{self, BlockIdentifier},
         common::{self, MERKLE_PROOF},
         docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
         Error, ErrorCode, ReactorEventT, RpcRequest, RpcWithOptionalParams, RpcWithParams,
     },
     types::{
         json_compatibility::{Account as JsonAccount, AuctionState, StoredValue},
-        Block, BlockHash, JsonBlockHeader,
+        Block, BlockHash, BlockHeader, BlockHeight, BlockSignatures, BlockWithMetadata,
+        JsonBlockHeader, JsonProof,
     },
 };
 
@@ -43,11 +45,13 @@ static GET_ITEM_PARAMS: Lazy<GetItemParams> = Lazy::new(|| GetItemParams {
     state_root_hash: *Block::doc_example().header().state_root_hash(),
     key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1".to_string(),
     path: vec!["inner".to_string()],
+    named_keys_page: None,
 });
 static GET_ITEM_RESULT: Lazy<GetItemResult> = Lazy::new(|| GetItemResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     stored_value: StoredValue::CLValue(CLValue::from_t(1u64).unwrap()),
     merkle_proof: MERKLE_PROOF.clone(),
+    named_keys_total: None,
 });
 static GET_BALANCE_PARAMS: Lazy<GetBalanceParams> = Lazy::new(|| GetBalanceParams {
     state_root_hash: *Block::doc_example().header().state_root_hash(),
@@ -102,6 +106,7 @@ static QUERY_GLOBAL_STATE_PARAMS: Lazy<QueryGlobalStateParams> =
         state_identifier: GlobalStateIdentifier::BlockHash(*Block::doc_example().hash()),
         key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1".to_string(),
         path: vec![],
+        named_keys_page: None,
     });
 static QUERY_GLOBAL_STATE_RESULT: Lazy<QueryGlobalStateResult> =
     Lazy::new(|| QueryGlobalStateResult {
@@ -109,7 +114,46 @@ static QUERY_GLOBAL_STATE_RESULT: Lazy<QueryGlobalStateResult> =
         block_header: Some(JsonBlockHeader::doc_example().clone()),
         stored_value: StoredValue::Account(JsonAccount::doc_example().clone()),
         merkle_proof: MERKLE_PROOF.clone(),
+        named_keys_total: None,
     });
+static QUERY_GLOBAL_STATE_BULK_PARAMS: Lazy<QueryGlobalStateBulkParams> =
+    Lazy::new(|| QueryGlobalStateBulkParams {
+        state_identifier: GlobalStateIdentifier::BlockHash(*Block::doc_example().hash()),
+        queries: vec![ProofQuery {
+            key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1"
+                .to_string(),
+            path: vec![],
+        }],
+    });
+static QUERY_GLOBAL_STATE_BULK_RESULT: Lazy<QueryGlobalStateBulkResult> =
+    Lazy::new(|| QueryGlobalStateBulkResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        block_header: Some(JsonBlockHeader::doc_example().clone()),
+        state_root_hash: *Block::doc_example().header().state_root_hash(),
+        results: vec![BulkQueryResult {
+            key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1"
+                .to_string(),
+            path: vec![],
+            stored_value: Some(StoredValue::CLValue(CLValue::from_t(1u64).unwrap())),
+            merkle_proof: Some(MERKLE_PROOF.clone()),
+            error: None,
+        }],
+    });
+static GET_PROOF_BUNDLE_PARAMS: Lazy<GetProofBundleParams> = Lazy::new(|| GetProofBundleParams {
+    block_identifier: BlockIdentifier::Hash(*Block::doc_example().hash()),
+    trusted_switch_block_hash: *Block::doc_example().header().parent_hash(),
+    key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1".to_string(),
+    path: vec![],
+});
+static GET_PROOF_BUNDLE_RESULT: Lazy<GetProofBundleResult> = Lazy::new(|| GetProofBundleResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    block_hash: *Block::doc_example().hash(),
+    block_header: JsonBlockHeader::doc_example().clone(),
+    finality_signatures: vec![],
+    era_validator_lineage: vec![],
+    stored_value: StoredValue::CLValue(CLValue::from_t(1u64).unwrap()),
+    merkle_proof: MERKLE_PROOF.clone(),
+});
 static GET_TRIE_PARAMS: Lazy<GetTrieParams> = Lazy::new(|| GetTrieParams {
     trie_key: *Block::doc_example().header().state_root_hash(),
 });
@@ -128,6 +172,16 @@ static QUERY_BALANCE_RESULT: Lazy<QueryBalanceResult> = Lazy::new(|| QueryBalanc
     balance: U512::from(123_456),
 });
 
+/// A page of the named keys of an account or contract, in name order.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NamedKeysPage {
+    /// The number of named keys to skip before the start of the page.
+    pub offset: u32,
+    /// The maximum number of named keys in the page.
+    pub limit: u32,
+}
+
 /// Params for "state_get_item" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -139,6 +193,10 @@ pub struct GetItemParams {
     /// The path components starting from the key as base.
     #[serde(default)]
     pub path: Vec<String>,
+    /// If the stored value is an account or contract, only this page of its named keys is
+    /// returned.  All of them are returned if not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_keys_page: Option<NamedKeysPage>,
 }
 
 impl DocExample for GetItemParams {
@@ -158,6 +216,10 @@ pub struct GetItemResult {
     pub stored_value: StoredValue,
     /// The merkle proof.
     pub merkle_proof: String,
+    /// The total number of named keys of the account or contract, if a page of its named keys
+    /// was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_keys_total: Option<u32>,
 }
 
 impl DocExample for GetItemResult {
@@ -192,18 +254,22 @@ impl RpcWithParams for GetItem {
         };
 
         // Run the query.
-        let (stored_value, merkle_proof) = common::run_query_and_encode(
+        let (mut stored_value, merkle_proof) = common::run_query_and_encode(
             effect_builder,
             params.state_root_hash,
             base_key,
             params.path,
         )
         .await?;
+        let named_keys_total = params
+            .named_keys_page
+            .and_then(|page| stored_value.retain_named_keys_page(page.offset, page.limit));
 
         let result = Self::ResponseResult {
             api_version,
             stored_value,
             merkle_proof,
+            named_keys_total,
         };
         Ok(result)
     }
@@ -384,7 +450,7 @@ impl RpcWithOptionalParams for GetAuctionInfo {
         // the global state hash of the last block
         let state_root_hash = *block.header().state_root_hash();
         // the block height of the last added block
-        let block_height = block.header().height();
+        let block_height = BlockHeight::new(block.header().height());
 
         let get_bids_result = effect_builder
             .make_request(
@@ -764,7 +830,7 @@ pub enum GlobalStateIdentifier {
     /// Query using a block hash.
     BlockHash(BlockHash),
     /// Query using a block height.
-    BlockHeight(u64),
+    BlockHeight(BlockHeight),
     /// Query using the state root hash.
     StateRootHash(Digest),
 }
@@ -780,6 +846,10 @@ pub struct QueryGlobalStateParams {
     /// The path components starting from the key as base.
     #[serde(default)]
     pub path: Vec<String>,
+    /// If the stored value is an account or contract, only this page of its named keys is
+    /// returned.  All of them are returned if not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_keys_page: Option<NamedKeysPage>,
 }
 
 impl DocExample for QueryGlobalStateParams {
@@ -801,6 +871,10 @@ pub struct QueryGlobalStateResult {
     pub stored_value: StoredValue,
     /// The merkle proof.
     pub merkle_proof: String,
+    /// The total number of named keys of the account or contract, if a page of its named keys
+    /// was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_keys_total: Option<u32>,
 }
 
 impl DocExample for QueryGlobalStateResult {
@@ -837,20 +911,419 @@ impl RpcWithParams for QueryGlobalState {
             }
         };
 
-        let (stored_value, merkle_proof) =
+        let (mut stored_value, merkle_proof) =
             common::run_query_and_encode(effect_builder, state_root_hash, base_key, params.path)
                 .await?;
+        let named_keys_total = params
+            .named_keys_page
+            .and_then(|page| stored_value.retain_named_keys_page(page.offset, page.limit));
 
         let result = Self::ResponseResult {
             api_version,
             block_header: maybe_block_header,
             stored_value,
             merkle_proof,
+            named_keys_total,
+        };
+        Ok(result)
+    }
+}
+
+/// The maximum number of queries in a single "query_global_state_bulk" request.
+const MAX_BULK_QUERIES: usize = 100;
+
+/// Params for "query_global_state_bulk" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QueryGlobalStateBulkParams {
+    /// The identifier of the global state all values are queried in.
+    pub state_identifier: GlobalStateIdentifier,
+    /// The values to query, at most 100.
+    pub queries: Vec<ProofQuery>,
+}
+
+impl DocExample for QueryGlobalStateBulkParams {
+    fn doc_example() -> &'static Self {
+        &*QUERY_GLOBAL_STATE_BULK_PARAMS
+    }
+}
+
+/// The outcome of a single query of a "query_global_state_bulk" RPC request.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BulkQueryResult {
+    /// The queried key.
+    pub key: String,
+    /// The queried path.
+    pub path: Vec<String>,
+    /// The stored value, if the query succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stored_value: Option<StoredValue>,
+    /// The merkle proof of the stored value against the state root hash, if the query succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merkle_proof: Option<String>,
+    /// Why the query failed, e.g. because there is no value under the key, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BulkQueryResult {
+    fn failed(query: ProofQuery, error: String) -> Self {
+        BulkQueryResult {
+            key: query.key,
+            path: query.path,
+            stored_value: None,
+            merkle_proof: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Result for "query_global_state_bulk" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QueryGlobalStateBulkResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The block header if a Block hash or height was provided.
+    pub block_header: Option<JsonBlockHeader>,
+    /// The state root hash all values were queried in, and are proven against.
+    pub state_root_hash: Digest,
+    /// The outcomes of the queries, in the order of the queries.
+    pub results: Vec<BulkQueryResult>,
+}
+
+impl DocExample for QueryGlobalStateBulkResult {
+    fn doc_example() -> &'static Self {
+        &*QUERY_GLOBAL_STATE_BULK_RESULT
+    }
+}
+
+/// "query_global_state_bulk" RPC.
+pub struct QueryGlobalStateBulk {}
+
+#[async_trait]
+impl RpcWithParams for QueryGlobalStateBulk {
+    const METHOD: &'static str = "query_global_state_bulk";
+    type RequestParams = QueryGlobalStateBulkParams;
+    type ResponseResult = QueryGlobalStateBulkResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        if params.queries.len() > MAX_BULK_QUERIES {
+            let error_msg = format!(
+                "request contains {} queries, exceeding the limit of {}",
+                params.queries.len(),
+                MAX_BULK_QUERIES
+            );
+            info!("{}", error_msg);
+            return Err(Error::new(ErrorCode::TooManyQueries, error_msg));
+        }
+
+        let (state_root_hash, maybe_block_header) =
+            get_state_root_hash_and_optional_header(effect_builder, params.state_identifier)
+                .await?;
+
+        let mut results = Vec::with_capacity(params.queries.len());
+        for query in params.queries {
+            let base_key = match Key::from_formatted_str(&query.key) {
+                Ok(key) => key,
+                Err(error) => {
+                    let error_msg = format!("failed to parse key: {}", error);
+                    results.push(BulkQueryResult::failed(query, error_msg));
+                    continue;
+                }
+            };
+
+            let query_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::QueryGlobalState {
+                        state_root_hash,
+                        base_key,
+                        path: query.path.clone(),
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            // Only failures of individual queries are reported per query; if the state root is
+            // missing or the engine fails, the whole request fails.
+            let (value, proofs) = match query_result {
+                Ok(QueryResult::Success { value, proofs }) => (*value, proofs),
+                Ok(QueryResult::RootNotFound) => {
+                    info!("bulk query failed: root not found");
+                    let error = common::missing_block_or_state_root_error(
+                        effect_builder,
+                        ErrorCode::NoSuchStateRoot,
+                        format!("failed to get state root at {:?}", state_root_hash),
+                    )
+                    .await;
+                    return Err(error);
+                }
+                Ok(query_result) => {
+                    results.push(BulkQueryResult::failed(
+                        query,
+                        format!("{:?}", query_result),
+                    ));
+                    continue;
+                }
+                Err(error) => {
+                    info!(?error, "bulk query failed to execute");
+                    return Err(Error::new(
+                        ErrorCode::QueryFailedToExecute,
+                        format!("{:?}", error),
+                    ));
+                }
+            };
+
+            let (stored_value, merkle_proof) = common::encode_query_success(value, proofs)?;
+            results.push(BulkQueryResult {
+                key: query.key,
+                path: query.path,
+                stored_value: Some(stored_value),
+                merkle_proof: Some(merkle_proof),
+                error: None,
+            });
+        }
+
+        Ok(Self::ResponseResult {
+            api_version,
+            block_header: maybe_block_header,
+            state_root_hash,
+            results,
+        })
+    }
+}
+
+/// The maximum number of switch blocks which can be included in the validator lineage of a proof
+/// bundle.
+const MAX_PROOF_BUNDLE_LINEAGE_LENGTH: u64 = 100;
+
+/// Params for "state_get_proof_bundle" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetProofBundleParams {
+    /// The identifier of the block against whose global state the value is proven.
+    pub block_identifier: BlockIdentifier,
+    /// The hash of a switch block already trusted by the caller, from which the era validator
+    /// weights lineage starts.
+    pub trusted_switch_block_hash: BlockHash,
+    /// `casper_types::Key` as formatted string.
+    pub key: String,
+    /// The path components starting from the key as base.
+    #[serde(default)]
+    pub path: Vec<String>,
+}
+
+impl DocExample for GetProofBundleParams {
+    fn doc_example() -> &'static Self {
+        &*GET_PROOF_BUNDLE_PARAMS
+    }
+}
+
+/// A switch block header along with the finality signatures proving it.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SignedSwitchBlockHeader {
+    /// The switch block hash.
+    pub block_hash: BlockHash,
+    /// The switch block header, including the validator weights of the following era.
+    pub block_header: JsonBlockHeader,
+    /// The finality signatures of the switch block.
+    pub finality_signatures: Vec<JsonProof>,
+}
+
+impl SignedSwitchBlockHeader {
+    fn new(block_header: BlockHeader, block_signatures: BlockSignatures) -> Self {
+        SignedSwitchBlockHeader {
+            block_hash: block_header.hash(),
+            block_header: JsonBlockHeader::from(block_header),
+            finality_signatures: block_signatures
+                .proofs
+                .into_iter()
+                .map(JsonProof::from)
+                .collect(),
+        }
+    }
+}
+
+/// Result for "state_get_proof_bundle" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetProofBundleResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The hash of the block.
+    pub block_hash: BlockHash,
+    /// The header of the block.
+    pub block_header: JsonBlockHeader,
+    /// The finality signatures of the block.
+    pub finality_signatures: Vec<JsonProof>,
+    /// The signed switch blocks of every era after the trusted switch block's era and before the
+    /// block's era, in ascending order.  Each one carries the validator weights needed to verify
+    /// the signatures of the next entry, or of the block itself for the last entry.
+    pub era_validator_lineage: Vec<SignedSwitchBlockHeader>,
+    /// The stored value.
+    pub stored_value: StoredValue,
+    /// The merkle proof of the stored value against the block's state root hash.
+    pub merkle_proof: String,
+}
+
+impl DocExample for GetProofBundleResult {
+    fn doc_example() -> &'static Self {
+        &*GET_PROOF_BUNDLE_RESULT
+    }
+}
+
+/// "state_get_proof_bundle" RPC.
+pub struct GetProofBundle {}
+
+#[async_trait]
+impl RpcWithParams for GetProofBundle {
+    const METHOD: &'static str = "state_get_proof_bundle";
+    type RequestParams = GetProofBundleParams;
+    type ResponseResult = GetProofBundleResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        // This RPC request is restricted by the block availability index.
+        let only_from_available_block_range = true;
+
+        let base_key = match Key::from_formatted_str(&params.key)
+            .map_err(|error| format!("failed to parse key: {}", error))
+        {
+            Ok(key) => key,
+            Err(error_msg) => {
+                info!("{}", error_msg);
+                return Err(Error::new(ErrorCode::FailedToParseQueryKey, error_msg));
+            }
+        };
+
+        let BlockWithMetadata {
+            block,
+            block_signatures,
+        } = chain::get_block_with_metadata(
+            Some(params.block_identifier),
+            only_from_available_block_range,
+            effect_builder,
+        )
+        .await?;
+
+        let era_validator_lineage = get_era_validator_lineage(
+            effect_builder,
+            params.trusted_switch_block_hash,
+            block.header().era_id(),
+            MAX_PROOF_BUNDLE_LINEAGE_LENGTH,
+        )
+        .await?;
+
+        let (stored_value, merkle_proof) = common::run_query_and_encode(
+            effect_builder,
+            *block.header().state_root_hash(),
+            base_key,
+            params.path,
+        )
+        .await?;
+
+        let block_hash = *block.hash();
+        let result = Self::ResponseResult {
+            api_version,
+            block_hash,
+            block_header: JsonBlockHeader::from(block.take_header()),
+            finality_signatures: block_signatures
+                .proofs
+                .into_iter()
+                .map(JsonProof::from)
+                .collect(),
+            era_validator_lineage,
+            stored_value,
+            merkle_proof,
         };
         Ok(result)
     }
 }
 
+/// Returns the signed switch blocks of every era after the trusted switch block's era and before
+/// `block_era_id`, in ascending order, failing if there are more than `max_length` of them.
+pub(super) async fn get_era_validator_lineage<REv: ReactorEventT>(
+    effect_builder: EffectBuilder<REv>,
+    trusted_switch_block_hash: BlockHash,
+    block_era_id: EraId,
+    max_length: u64,
+) -> Result<Vec<SignedSwitchBlockHeader>, Error> {
+    let trusted_header = match effect_builder
+        .get_block_header_from_storage(trusted_switch_block_hash, false)
+        .await
+    {
+        Some(block_header) => block_header,
+        None => {
+            let error_msg = format!(
+                "trusted switch block {} not stored on this node",
+                trusted_switch_block_hash
+            );
+            return Err(Error::new(ErrorCode::NoSuchBlock, error_msg));
+        }
+    };
+
+    if !trusted_header.is_switch_block() || trusted_header.era_id() >= block_era_id {
+        let error_msg = format!(
+            "block {} is not a switch block of an era preceding era {}",
+            trusted_switch_block_hash, block_era_id
+        );
+        info!("{}", error_msg);
+        return Err(Error::new(ErrorCode::InvalidTrustedBlock, error_msg));
+    }
+
+    let lineage_length = block_era_id.value() - trusted_header.era_id().value() - 1;
+    if lineage_length > max_length {
+        let error_msg = format!(
+            "lineage from trusted switch block {} spans {} eras, exceeding the limit of {}",
+            trusted_switch_block_hash, lineage_length, max_length
+        );
+        info!("{}", error_msg);
+        return Err(Error::new(ErrorCode::InvalidTrustedBlock, error_msg));
+    }
+
+    let mut era_validator_lineage = Vec::with_capacity(lineage_length as usize);
+    let mut era_id = trusted_header.era_id().successor();
+    while era_id < block_era_id {
+        let maybe_switch_block_header = effect_builder
+            .get_switch_block_header_at_era_id_from_storage(era_id)
+            .await;
+        let maybe_signatures = match &maybe_switch_block_header {
+            Some(block_header) => {
+                effect_builder
+                    .get_signatures_from_storage(block_header.hash())
+                    .await
+            }
+            None => None,
+        };
+        match (maybe_switch_block_header, maybe_signatures) {
+            (Some(block_header), Some(block_signatures)) => era_validator_lineage
+                .push(SignedSwitchBlockHeader::new(block_header, block_signatures)),
+            _ => {
+                let error_msg = format!(
+                    "signed switch block of era {} not stored on this node",
+                    era_id
+                );
+                info!("{}", error_msg);
+                return Err(Error::new(ErrorCode::NoSuchSwitchBlock, error_msg));
+            }
+        }
+        era_id = era_id.successor();
+    }
+    Ok(era_validator_lineage)
+}
+
 /// Identifier of a purse.
 #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
@@ -1009,7 +1482,8 @@ pub struct GetTrieResult {
     /// The RPC API version.
     #[schemars(with = "String")]
     pub api_version: ProtocolVersion,
-    /// A list of keys read under the specified prefix.
+    /// The trie under the requested key, if present.  The key is the hash of these bytes, so
+    /// they prove themselves against it.
     #[schemars(
         with = "Option<String>",
         description = "A trie from global state storage, bytesrepr serialized and hex-encoded."
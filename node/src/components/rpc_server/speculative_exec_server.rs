@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use hyper::server::{conn::AddrIncoming, Builder};
 
 use casper_json_rpc::RequestHandlersBuilder;
@@ -6,7 +8,10 @@ use casper_types::ProtocolVersion;
 use super::ReactorEventT;
 use crate::{
     effect::EffectBuilder,
-    rpcs::{speculative_exec::SpeculativeExec, RpcWithParams},
+    rpcs::{
+        speculative_exec::{SpeculativeExec, SpeculativeExecTransaction},
+        RpcLimits, RpcWithParams,
+    },
 };
 
 /// The URL path for all JSON-RPC requests.
@@ -19,18 +24,17 @@ pub(super) async fn run<REv: ReactorEventT>(
     builder: Builder<AddrIncoming>,
     effect_builder: EffectBuilder<REv>,
     api_version: ProtocolVersion,
-    qps_limit: u64,
-    max_body_bytes: u32,
+    limits: Arc<RpcLimits>,
 ) {
     let mut handlers = RequestHandlersBuilder::new();
     SpeculativeExec::register_as_handler(effect_builder, api_version, &mut handlers);
+    SpeculativeExecTransaction::register_as_handler(effect_builder, api_version, &mut handlers);
     let handlers = handlers.build();
 
     super::rpcs::run(
         builder,
         handlers,
-        qps_limit,
-        max_body_bytes,
+        limits,
         SPECULATIVE_EXEC_API_PATH,
         SPECULATIVE_EXEC_SERVER_NAME,
     )
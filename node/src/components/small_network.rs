@@ -23,33 +23,43 @@
 //! Nodes gossip their public listening addresses periodically, and will try to establish and
 //! maintain an outgoing connection to any new address learned.
 
+mod admission;
 mod bincode_format;
 mod chain_info;
+mod compression;
 mod config;
 mod counting_format;
+mod debug;
+mod encoding_version;
 mod error;
 mod event;
+mod features;
 mod gossiped_address;
 mod limiter;
 mod message;
 mod message_pack_format;
 mod metrics;
+mod network_key;
 mod outgoing;
+mod peer_store;
+mod reputation;
+mod shaper;
 mod symmetry;
 pub(crate) mod tasks;
 #[cfg(test)]
 mod tests;
+mod traffic;
 
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
     convert::Infallible,
     fmt::{self, Debug, Display, Formatter},
-    io,
+    io, mem,
     net::{SocketAddr, TcpListener},
     result,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, Weak,
+        Arc, RwLock, Weak,
     },
     time::{Duration, Instant},
 };
@@ -66,7 +76,7 @@ use tokio::{
     net::TcpStream,
     sync::{
         mpsc::{self, UnboundedSender},
-        watch,
+        oneshot, watch,
     },
     task::JoinHandle,
 };
@@ -74,37 +84,55 @@ use tokio_openssl::SslStream;
 use tokio_util::codec::LengthDelimitedCodec;
 use tracing::{debug, error, info, trace, warn, Instrument, Span};
 
-use casper_types::{EraId, PublicKey};
+use casper_types::{EraId, PublicKey, SecretKey, TimeDiff, Timestamp};
 
-pub(crate) use self::{
-    bincode_format::BincodeFormat,
-    config::Config,
-    error::Error,
-    event::Event,
-    gossiped_address::GossipedAddress,
-    message::{EstimatorWeights, FromIncoming, Message, MessageKind, Payload},
-};
+#[cfg(fuzzing)]
+pub(crate) use self::message_pack_format::MessagePackFormat;
 use self::{
+    admission::Admission,
     chain_info::ChainInfo,
-    config::IdentityConfig,
+    compression::CompressionFormat,
+    config::{IdentityConfig, NetworkKeyConfig, NodeRole},
     counting_format::{ConnectionId, CountingFormat, Role},
+    debug::{PeerDump, PeersDump, SymmetryDump, ValidatorStatus},
     error::{ConnectionError, Result},
     event::{IncomingConnection, OutgoingConnection},
+    features::Features,
     limiter::Limiter,
-    message::ConsensusKeyPair,
+    message::{ConsensusKeyPair, NetworkKeyPair},
     metrics::Metrics,
     outgoing::{DialOutcome, DialRequest, OutgoingConfig, OutgoingManager},
+    peer_store::{PeerRecord, PeerStore, PEER_STORE_STATE_KEY},
+    reputation::{Ban, Behavior, ReputationRegistry},
+    shaper::TrafficShaper,
     symmetry::ConnectionSymmetry,
     tasks::{MessageQueueItem, NetworkContext},
+    traffic::{PeerTraffic, TrafficRegistry},
+};
+pub(crate) use self::{
+    bincode_format::BincodeFormat,
+    config::Config,
+    debug::NetworkDump,
+    error::Error,
+    event::Event,
+    gossiped_address::GossipedAddress,
+    message::{EstimatorWeights, FromIncoming, Message, MessageKind, Payload},
+    network_key::NetworkKeyEndorsement,
 };
 
 use crate::{
+    audit_log,
     components::{consensus, Component},
     effect::{
         announcements::{
-            BlocklistAnnouncement, ChainSynchronizerAnnouncement, ContractRuntimeAnnouncement,
+            BlocklistAnnouncement, ChainSynchronizerAnnouncement, ChainspecLoaderAnnouncement,
+            ContractRuntimeAnnouncement,
+        },
+        diagnostics_port::DumpNetworkStateRequest,
+        requests::{
+            BeginGossipRequest, NetworkInfoRequest, NetworkRequest, StateStoreRequest,
+            StorageRequest,
         },
-        requests::{BeginGossipRequest, NetworkInfoRequest, NetworkRequest, StorageRequest},
         AutoClosingResponder, EffectBuilder, EffectExt, Effects,
     },
     reactor::{EventQueueHandle, Finalize, ReactorEvent},
@@ -113,7 +141,7 @@ use crate::{
         ValidationError,
     },
     types::NodeId,
-    utils::{self, display_error, Source, WithDir},
+    utils::{self, display_error, Loadable, Source, WithDir},
     NodeRng,
 };
 
@@ -132,6 +160,9 @@ const BASE_RECONNECTION_TIMEOUT: Duration = Duration::from_secs(1);
 /// Interval during which to perform outgoing manager housekeeping.
 const OUTGOING_MANAGER_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
 
+/// Interval after which the peer store is persisted, if it changed.
+const PEER_STORE_PERSIST_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Clone, DataSize, Debug)]
 pub(crate) struct OutgoingHandle<P> {
     #[data_size(skip)] // Unfortunately, there is no way to inspect an `UnboundedSender`.
@@ -164,6 +195,14 @@ where
     /// Tracks nodes that have announced themselves as nodes that are syncing.
     syncing_nodes: HashSet<NodeId>,
 
+    /// Tracks nodes that have announced support for batched gossip messages.
+    batched_gossip_nodes: HashSet<NodeId>,
+
+    /// Tracks nodes that have announced they are shutting down.
+    ///
+    /// These are not selected for gossiping or fetching until they reconnect.
+    departing_nodes: HashSet<NodeId>,
+
     /// Channel signaling a shutdown of the small network.
     // Note: This channel is closed when `SmallNetwork` is dropped, signalling the receivers that
     // they should cease operation.
@@ -200,6 +239,31 @@ where
 
     /// The era that is considered the active era by the small network component.
     active_era: EraId,
+
+    /// Validators of the active era.
+    active_validators: HashSet<PublicKey>,
+    /// Validators of the upcoming era.
+    upcoming_validators: HashSet<PublicKey>,
+    /// Consensus keys proven by the consensus certificates of connected peers.
+    peer_consensus_keys: HashMap<NodeId, PublicKey>,
+
+    /// Reputation of peers, as scored by their behavior.
+    #[data_size(skip)]
+    reputation: ReputationRegistry,
+    /// Handles closing each incoming connection when dropped, by the address it comes from.
+    #[data_size(skip)]
+    incoming_closers: HashMap<SocketAddr, oneshot::Sender<()>>,
+
+    /// When the outgoing connections were last swept.
+    #[data_size(skip)]
+    last_outgoing_sweep: Option<Instant>,
+
+    /// Address book of the peers we were connected to, persisted across restarts.
+    #[data_size(skip)]
+    peer_store: PeerStore,
+    /// When the dials currently in progress were started, by address.
+    #[data_size(skip)]
+    dial_started: HashMap<SocketAddr, Instant>,
 }
 
 impl<REv, P> SmallNetwork<REv, P>
@@ -209,6 +273,7 @@ where
         + From<Event<P>>
         + FromIncoming<P>
         + From<StorageRequest>
+        + From<StateStoreRequest>
         + From<NetworkRequest<P>>,
 {
     /// Creates a new small network component instance.
@@ -221,19 +286,7 @@ where
         small_network_identity: SmallNetworkIdentity,
         chain_info_source: C,
     ) -> Result<(SmallNetwork<REv, P>, Effects<Event<P>>)> {
-        let mut known_addresses = HashSet::new();
-        for address in &cfg.known_addresses {
-            match utils::resolve_address(address) {
-                Ok(known_address) => {
-                    if !known_addresses.insert(known_address) {
-                        warn!(%address, resolved=%known_address, "ignoring duplicated known address");
-                    };
-                }
-                Err(ref err) => {
-                    warn!(%address, err=display_error(err), "failed to resolve known address");
-                }
-            }
-        }
+        let known_addresses = resolve_known_addresses(&cfg.known_addresses);
 
         // Assert we have at least one known address in the config.
         if known_addresses.is_empty() {
@@ -291,15 +344,26 @@ where
             public_addr.set_port(local_addr.port());
         }
 
-        // If given consensus key configuration, load it for handshake signing.
-        let consensus_keys = consensus_cfg
-            .map(|cfg| {
-                let root = cfg.dir();
-                cfg.value().load_keys(root)
-            })
-            .transpose()
-            .map_err(Error::LoadConsensusKeys)?
-            .map(|(secret_key, public_key)| ConsensusKeyPair::new(secret_key, public_key));
+        // If given a network key, sign handshakes with it so the validator key is not needed for
+        // networking.  Otherwise, if given consensus key configuration, load it for handshake
+        // signing.
+        let network_keys = cfg
+            .network_key
+            .as_ref()
+            .map(load_network_keys)
+            .transpose()?;
+        let consensus_keys = if network_keys.is_some() {
+            None
+        } else {
+            consensus_cfg
+                .map(|cfg| {
+                    let root = cfg.dir();
+                    cfg.value().load_keys(root)
+                })
+                .transpose()
+                .map_err(Error::LoadConsensusKeys)?
+                .map(|(secret_key, public_key)| ConsensusKeyPair::new(secret_key, public_key))
+        };
 
         // Set the demand max from configuration, regarding `0` as "unlimited".
         let demand_max = if cfg.max_in_flight_demands == 0 {
@@ -308,6 +372,13 @@ where
             cfg.max_in_flight_demands as usize
         };
 
+        // Likewise for the maximum of acknowledged messages in flight per peer.
+        let acknowledged_max = if cfg.max_in_flight_deploys == 0 {
+            usize::MAX
+        } else {
+            cfg.max_in_flight_deploys as usize
+        };
+
         // Load a ca certificate (if present)
         let ca_certificate = match &cfg.identity {
             Some(identity) => {
@@ -340,13 +411,19 @@ where
             chain_info,
             public_addr,
             consensus_keys,
+            network_keys,
             handshake_timeout: cfg.handshake_timeout,
             payload_weights: cfg.estimator_weights.clone(),
             tarpit_version_threshold: cfg.tarpit_version_threshold,
             tarpit_duration: cfg.tarpit_duration,
             tarpit_chance: cfg.tarpit_chance,
             max_in_flight_demands: demand_max,
+            max_in_flight_acknowledged: acknowledged_max,
             is_syncing: AtomicBool::new(true),
+            staged_protocol_version: RwLock::new(None),
+            admission: Arc::new(Admission::new(cfg.incoming_limits.clone())),
+            features: Features::supported(cfg.enable_compression),
+            peer_traffic: TrafficRegistry::default(),
         });
 
         // Run the server task.
@@ -366,12 +443,20 @@ where
             .in_current_span(),
         );
 
+        let peer_store = PeerStore::new(cfg.peer_store.clone());
+        let reputation = ReputationRegistry::new(
+            cfg.reputation.clone(),
+            net_metrics.create_reputation_metrics(),
+        );
+
         let mut component = SmallNetwork {
             cfg,
             context,
             outgoing_manager,
             connection_symmetries: HashMap::new(),
             syncing_nodes: HashSet::new(),
+            batched_gossip_nodes: HashSet::new(),
+            departing_nodes: HashSet::new(),
             shutdown_sender: Some(server_shutdown_sender),
             close_incoming_sender: Some(close_incoming_sender),
             close_incoming_receiver,
@@ -381,6 +466,14 @@ where
             incoming_limiter,
             // We start with an empty set of validators for era 0 and expect to be updated.
             active_era: EraId::new(0),
+            active_validators: HashSet::new(),
+            upcoming_validators: HashSet::new(),
+            peer_consensus_keys: HashMap::new(),
+            reputation,
+            incoming_closers: HashMap::new(),
+            last_outgoing_sweep: None,
+            peer_store,
+            dial_started: HashMap::new(),
         };
 
         let effect_builder = EffectBuilder::new(event_queue);
@@ -394,12 +487,27 @@ where
 
         let mut effects = component.process_dial_requests(dial_requests);
 
-        // Start broadcasting our public listening address.
-        effects.extend(
-            effect_builder
-                .set_timeout(component.cfg.initial_gossip_delay.into())
-                .event(|_| Event::GossipOurAddress),
-        );
+        // Load the peers we knew before the restart, to dial the best of them as well.
+        if !component.peer_store.is_disabled() {
+            effects.extend(
+                effect_builder
+                    .load_state::<Vec<PeerRecord>>(PEER_STORE_STATE_KEY.into())
+                    .event(|maybe_records| {
+                        Event::PeerStoreLoaded(maybe_records.unwrap_or_default())
+                    }),
+            );
+        }
+
+        // Start broadcasting our public listening address, unless our role keeps it private.
+        if component.cfg.role.gossips_own_address() {
+            effects.extend(
+                effect_builder
+                    .set_timeout(component.cfg.initial_gossip_delay.into())
+                    .event(|_| Event::GossipOurAddress),
+            );
+        } else {
+            info!(role = ?component.cfg.role, "not gossiping our address");
+        }
 
         // Start regular housekeeping of the outgoing connections.
         effects.extend(
@@ -411,6 +519,19 @@ where
         Ok((component, effects))
     }
 
+    /// Resolves the given known addresses and dials those not known yet.
+    ///
+    /// Known addresses are never forgotten, so addresses no longer given remain known until the
+    /// node is restarted.
+    pub(crate) fn learn_known_addresses(&mut self, addresses: &[String]) -> Effects<Event<P>> {
+        let now = Instant::now();
+        let dial_requests: Vec<_> = resolve_known_addresses(addresses)
+            .into_iter()
+            .filter_map(|addr| self.outgoing_manager.learn_addr(addr, true, now))
+            .collect();
+        self.process_dial_requests(dial_requests)
+    }
+
     fn close_incoming_connections(&mut self) {
         info!("disconnecting incoming connections");
         let (close_incoming_sender, close_incoming_receiver) = watch::channel(());
@@ -426,6 +547,12 @@ where
         }
     }
 
+    /// Notifies all connected peers that we are shutting down.
+    pub(crate) fn announce_going_away(&self) {
+        info!("notifying peers of shutdown");
+        self.broadcast_message(Arc::new(Message::GoingAway));
+    }
+
     /// Queues a message to `count` random nodes on the network.
     fn gossip_message(
         &self,
@@ -434,10 +561,15 @@ where
         count: usize,
         exclude: HashSet<NodeId>,
     ) -> HashSet<NodeId> {
+        let requires_batched_gossip = msg.payload_requires_batched_gossip_support();
         let peer_ids = self
             .outgoing_manager
             .connected_peers()
             .filter(|peer_id| !exclude.contains(peer_id))
+            .filter(|peer_id| !self.departing_nodes.contains(peer_id))
+            .filter(|peer_id| {
+                !requires_batched_gossip || self.batched_gossip_nodes.contains(peer_id)
+            })
             .choose_multiple(rng, count);
 
         if peer_ids.len() != count {
@@ -466,6 +598,15 @@ where
         msg: Arc<Message<P>>,
         opt_responder: Option<AutoClosingResponder<()>>,
     ) {
+        #[cfg(any(feature = "fault-injection", test))]
+        if crate::fault_injection::message_dropped(
+            msg.classify(),
+            crate::fault_injection::Direction::Outgoing,
+        ) {
+            debug!(our_id=%self.context.our_id, %dest, ?msg, "dropped outgoing message, fault injected");
+            return;
+        }
+
         // Try to send the message.
         if let Some(connection) = self.outgoing_manager.get_route(dest) {
             if msg.payload_is_unsafe_for_syncing_nodes() && self.syncing_nodes.contains(&dest) {
@@ -503,16 +644,18 @@ where
             }
             IncomingConnection::Failed {
                 peer_addr: _,
-                peer_id: _,
+                peer_id,
                 ref error,
             } => {
-                // TODO: At this point, we could consider blocking peers by [`PeerID`], but this
-                //       feature is not implemented yet.
                 debug!(
                     err = display_error(error),
                     "incoming connection failed after TLS setup"
                 );
-                Effects::new()
+                if is_handshake_failure_by_peer(error) {
+                    self.record_behavior(peer_id, Behavior::HandshakeFailure)
+                } else {
+                    Effects::new()
+                }
             }
             IncomingConnection::Loopback => {
                 // Loopback connections are closed immediately, but will be marked as such by the
@@ -527,7 +670,14 @@ where
                 peer_id,
                 peer_consensus_public_key,
                 stream,
+                permit,
             } => {
+                let now = Instant::now();
+                if self.reputation.is_banned(&peer_id, now) {
+                    info!(%public_addr, %peer_id, "rejecting new incoming connection, peer is banned");
+                    return Effects::new();
+                }
+
                 if self.cfg.max_incoming_peer_connections != 0 {
                     if let Some(symmetries) = self.connection_symmetries.get(&peer_id) {
                         let incoming_count = symmetries
@@ -547,13 +697,32 @@ where
                     }
                 }
 
+                if !self.make_room_for_incoming(peer_id, peer_consensus_public_key.as_ref(), now) {
+                    info!(
+                        %public_addr,
+                        %peer_id,
+                        score = self.reputation.score(&peer_id, now),
+                        "rejecting new incoming connection, no slot left for peer's reputation"
+                    );
+                    return Effects::new();
+                }
+
                 info!(%public_addr, "new incoming connection established");
 
-                // Learn the address the peer gave us.
-                let dial_requests =
-                    self.outgoing_manager
-                        .learn_addr(public_addr, false, Instant::now());
-                let mut effects = self.process_dial_requests(dial_requests);
+                if let Some(ref public_key) = peer_consensus_public_key {
+                    self.peer_consensus_keys.insert(peer_id, public_key.clone());
+                }
+                self.record_seen(public_addr, peer_consensus_public_key.clone(), None);
+
+                // Learn the address the peer gave us, unless we only dial our known addresses.
+                let mut effects = if self.cfg.role.dials_unknown_addresses() {
+                    let dial_requests =
+                        self.outgoing_manager
+                            .learn_addr(public_addr, false, Instant::now());
+                    self.process_dial_requests(dial_requests)
+                } else {
+                    Effects::new()
+                };
 
                 // Update connection symmetries.
                 if self
@@ -577,6 +746,8 @@ where
 
                 // Now we can start the message reader.
                 let boxed_span = Box::new(span.clone());
+                let (closer, evicted) = oneshot::channel();
+                self.incoming_closers.insert(peer_addr, closer);
                 effects.extend(
                     tasks::message_reader(
                         self.context.clone(),
@@ -584,15 +755,20 @@ where
                         self.incoming_limiter
                             .create_handle(peer_id, peer_consensus_public_key),
                         self.close_incoming_receiver.clone(),
+                        evicted,
                         peer_id,
                         span.clone(),
                     )
                     .instrument(span)
-                    .event(move |result| Event::IncomingClosed {
-                        result,
-                        peer_id: Box::new(peer_id),
-                        peer_addr,
-                        span: boxed_span,
+                    .event(move |result| {
+                        // The connection no longer counts towards the per-address limits.
+                        drop(permit);
+                        Event::IncomingClosed {
+                            result,
+                            peer_id: Box::new(peer_id),
+                            peer_addr,
+                            span: boxed_span,
+                        }
                     }),
                 );
 
@@ -620,15 +796,162 @@ where
             }
 
             // Update the connection symmetries.
+            self.incoming_closers.remove(&peer_addr);
             self.connection_symmetries
                 .entry(*peer_id)
                 .or_default()
                 .remove_incoming(peer_addr, Instant::now());
 
-            Effects::new()
+            // Messages which cannot be decoded count against the peer.
+            match result {
+                Err(ref err) if err.kind() == io::ErrorKind::InvalidData => {
+                    self.record_behavior(*peer_id, Behavior::InvalidMessage)
+                }
+                _ => Effects::new(),
+            }
         })
     }
 
+    /// Records a behavior of a peer affecting its reputation, banning the peer if called for.
+    fn record_behavior(&mut self, peer_id: NodeId, behavior: Behavior) -> Effects<Event<P>> {
+        let now = Instant::now();
+        match self.reputation.record(peer_id, behavior, now) {
+            Some(ban) => self.ban_peer(peer_id, ban, now),
+            None => Effects::new(),
+        }
+    }
+
+    /// Bans a peer, blocking its outgoing address and closing its incoming connections.
+    ///
+    /// New incoming connections from the peer are rejected until the ban ends.
+    fn ban_peer(&mut self, peer_id: NodeId, ban: Ban, now: Instant) -> Effects<Event<P>> {
+        warn!(%peer_id, reason = %ban.reason, duration = ?ban.duration, "banning peer");
+
+        let outgoing_addr = self.outgoing_manager.get_addr(peer_id);
+        let incoming_addr = self
+            .connection_symmetries
+            .get(&peer_id)
+            .and_then(ConnectionSymmetry::incoming_addrs)
+            .and_then(|addrs| addrs.iter().next().copied());
+        if let Some(addr) = outgoing_addr.or(incoming_addr) {
+            audit_log::record(
+                &audit_log::Source::Node,
+                audit_log::Action::PeerBan {
+                    peer_id: Some(peer_id),
+                    addr,
+                    reason: ban.reason.to_string(),
+                },
+            );
+        }
+
+        self.close_incoming(peer_id);
+        let requests = outgoing_addr
+            .and_then(|addr| {
+                self.outgoing_manager
+                    .block_addr_for(addr, now, ban.duration)
+            })
+            .into_iter();
+        self.process_dial_requests(requests)
+    }
+
+    /// Closes all incoming connections of a peer.
+    fn close_incoming(&mut self, peer_id: NodeId) {
+        if let Some(addrs) = self
+            .connection_symmetries
+            .get(&peer_id)
+            .and_then(ConnectionSymmetry::incoming_addrs)
+        {
+            for addr in addrs {
+                // Dropping the closer ends the connection's message reader.
+                self.incoming_closers.remove(addr);
+            }
+        }
+    }
+
+    /// Records the peer at `addr` in the peer store as seen now.
+    ///
+    /// Its validator status is only recorded once the validators are known.
+    fn record_seen(
+        &mut self,
+        addr: SocketAddr,
+        consensus_key: Option<PublicKey>,
+        latency: Option<TimeDiff>,
+    ) {
+        let is_validator = (!self.active_validators.is_empty()).then(|| {
+            consensus_key
+                .as_ref()
+                .map_or(false, |key| self.is_validator_key(key))
+        });
+        self.peer_store
+            .record_seen(addr, consensus_key, is_validator, latency, Timestamp::now());
+    }
+
+    /// Returns whether the consensus key belongs to an active or upcoming validator.
+    fn is_validator_key(&self, consensus_key: &PublicKey) -> bool {
+        self.active_validators.contains(consensus_key)
+            || self.upcoming_validators.contains(consensus_key)
+    }
+
+    /// Checks whether an incoming connection from the peer fits within the configured maximum
+    /// number of peers with incoming connections, evicting the lowest-scoring peer if the new one
+    /// has a higher score.
+    ///
+    /// Validators and peers which already have an incoming connection are always admitted, and
+    /// validators are never evicted.
+    fn make_room_for_incoming(
+        &mut self,
+        peer_id: NodeId,
+        consensus_key: Option<&PublicKey>,
+        now: Instant,
+    ) -> bool {
+        let max_incoming_peers = self.cfg.reputation.max_incoming_peers as usize;
+        if max_incoming_peers == 0
+            || self.cfg.role == NodeRole::Relay
+            || consensus_key.map_or(false, |key| self.is_validator_key(key))
+        {
+            return true;
+        }
+
+        let incoming_peers: Vec<NodeId> = self
+            .connection_symmetries
+            .iter()
+            .filter(|(_, symmetry)| {
+                symmetry
+                    .incoming_addrs()
+                    .map_or(false, |addrs| !addrs.is_empty())
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        if incoming_peers.len() < max_incoming_peers || incoming_peers.contains(&peer_id) {
+            return true;
+        }
+
+        let lowest = incoming_peers
+            .into_iter()
+            .filter(|id| {
+                !self
+                    .peer_consensus_keys
+                    .get(id)
+                    .map_or(false, |key| self.is_validator_key(key))
+            })
+            .min_by_key(|id| self.reputation.score(id, now));
+        match lowest {
+            Some(lowest)
+                if self.reputation.score(&lowest, now) < self.reputation.score(&peer_id, now) =>
+            {
+                info!(
+                    evicted = %lowest,
+                    score = self.reputation.score(&lowest, now),
+                    "evicting lowest-scoring incoming peer to make room"
+                );
+                self.close_incoming(lowest);
+                self.net_metrics.incoming_peers_evicted.inc();
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Determines whether an outgoing peer should be blocked based on the connection error.
     fn is_blockable_offense_for_outgoing(&self, error: &ConnectionError) -> bool {
         match error {
@@ -654,7 +977,8 @@ where
             | ConnectionError::PeerCertificateInvalid(_)
             | ConnectionError::DidNotSendHandshake
             | ConnectionError::InvalidRemoteHandshakeMessage(_)
-            | ConnectionError::InvalidConsensusCertificate(_) => false,
+            | ConnectionError::InvalidConsensusCertificate(_)
+            | ConnectionError::InvalidNetworkCertificate(_) => false,
 
             // Definitely something we want to avoid.
             ConnectionError::WrongNetwork(_)
@@ -663,6 +987,53 @@ where
         }
     }
 
+    /// Handles a failed outgoing connection, blocking the address or penalizing the peer if the
+    /// failure is its fault.
+    fn handle_failed_outgoing(
+        &mut self,
+        peer_addr: SocketAddr,
+        peer_id: Option<NodeId>,
+        error: ConnectionError,
+        now: Instant,
+    ) -> Effects<Event<P>> {
+        debug!(err=%display_error(&error), "outgoing connection failed");
+        // We perform blocking first, to not trigger a reconnection before blocking.
+        let mut requests = Vec::new();
+
+        if self.is_blockable_offense_for_outgoing(&error) {
+            audit_log::record(
+                &audit_log::Source::Node,
+                audit_log::Action::PeerBan {
+                    peer_id: None,
+                    addr: peer_addr,
+                    reason: error.to_string(),
+                },
+            );
+            requests.extend(self.outgoing_manager.block_addr(peer_addr, now).into_iter());
+        }
+
+        let mut effects = match peer_id {
+            Some(peer_id) if is_handshake_failure_by_peer(&error) => {
+                self.record_behavior(peer_id, Behavior::HandshakeFailure)
+            }
+            _ => Effects::new(),
+        };
+
+        // Now we can proceed with the regular updates.
+        requests.extend(
+            self.outgoing_manager
+                .handle_dial_outcome(DialOutcome::Failed {
+                    addr: peer_addr,
+                    error,
+                    when: now,
+                })
+                .into_iter(),
+        );
+
+        effects.extend(self.process_dial_requests(requests));
+        effects
+    }
+
     /// Sets up an established outgoing connection.
     ///
     /// Initiates sending of the handshake as soon as the connection is established.
@@ -674,34 +1045,20 @@ where
     ) -> Effects<Event<P>> {
         let now = Instant::now();
         span.clone().in_scope(|| match outgoing {
-            OutgoingConnection::FailedEarly { peer_addr, error }
-            | OutgoingConnection::Failed {
+            OutgoingConnection::FailedEarly { peer_addr, error } => {
+                self.dial_started.remove(&peer_addr);
+                self.handle_failed_outgoing(peer_addr, None, error, now)
+            }
+            OutgoingConnection::Failed {
                 peer_addr,
-                peer_id: _,
+                peer_id,
                 error,
             } => {
-                debug!(err=%display_error(&error), "outgoing connection failed");
-                // We perform blocking first, to not trigger a reconnection before blocking.
-                let mut requests = Vec::new();
-
-                if self.is_blockable_offense_for_outgoing(&error) {
-                    requests.extend(self.outgoing_manager.block_addr(peer_addr, now).into_iter());
-                }
-
-                // Now we can proceed with the regular updates.
-                requests.extend(
-                    self.outgoing_manager
-                        .handle_dial_outcome(DialOutcome::Failed {
-                            addr: peer_addr,
-                            error,
-                            when: now,
-                        })
-                        .into_iter(),
-                );
-
-                self.process_dial_requests(requests)
+                self.dial_started.remove(&peer_addr);
+                self.handle_failed_outgoing(peer_addr, Some(peer_id), error, now)
             }
             OutgoingConnection::Loopback { peer_addr } => {
+                self.dial_started.remove(&peer_addr);
                 // Loopback connections are marked, but closed.
                 info!("successful outgoing loopback connection, will be dropped");
                 let request = self
@@ -715,9 +1072,19 @@ where
                 peer_consensus_public_key,
                 sink,
                 is_syncing,
+                supports_batched_gossip,
             } => {
                 info!("new outgoing connection established");
 
+                if let Some(ref public_key) = peer_consensus_public_key {
+                    self.peer_consensus_keys.insert(peer_id, public_key.clone());
+                }
+                let latency = self
+                    .dial_started
+                    .remove(&peer_addr)
+                    .map(|started| now.saturating_duration_since(started).into());
+                self.record_seen(peer_addr, peer_consensus_public_key.clone(), latency);
+
                 let (sender, receiver) = mpsc::unbounded_channel();
                 let handle = OutgoingHandle { sender, peer_addr };
 
@@ -739,7 +1106,9 @@ where
                     .mark_outgoing(now)
                 {
                     self.connection_completed(peer_id);
+                    self.departing_nodes.remove(&peer_id);
                     self.update_syncing_nodes_set(peer_id, is_syncing);
+                    self.update_batched_gossip_nodes_set(peer_id, supports_batched_gossip);
                 }
 
                 effects.extend(
@@ -748,6 +1117,11 @@ where
                         sink,
                         self.outgoing_limiter
                             .create_handle(peer_id, peer_consensus_public_key),
+                        TrafficShaper::new(
+                            &self.cfg.traffic_shaping,
+                            self.net_metrics.create_shaper_metrics(),
+                            now,
+                        ),
                         self.net_metrics.queued_messages.clone(),
                     )
                     .instrument(span)
@@ -789,14 +1163,17 @@ where
         for request in requests.into_iter() {
             trace!(%request, "processing dial request");
             match request {
-                DialRequest::Dial { addr, span } => effects.extend(
-                    tasks::connect_outgoing(self.context.clone(), addr)
-                        .instrument(span.clone())
-                        .event(|outgoing| Event::OutgoingConnection {
-                            outgoing: Box::new(outgoing),
-                            span,
-                        }),
-                ),
+                DialRequest::Dial { addr, span } => {
+                    self.dial_started.insert(addr, Instant::now());
+                    effects.extend(
+                        tasks::connect_outgoing(self.context.clone(), addr)
+                            .instrument(span.clone())
+                            .event(|outgoing| Event::OutgoingConnection {
+                                outgoing: Box::new(outgoing),
+                                span,
+                            }),
+                    )
+                }
                 DialRequest::Disconnect { handle: _, span } => {
                     // Dropping the `handle` is enough to signal the connection to shutdown.
                     span.in_scope(|| {
@@ -820,6 +1197,15 @@ where
     where
         REv: FromIncoming<P>,
     {
+        #[cfg(any(feature = "fault-injection", test))]
+        if crate::fault_injection::message_dropped(
+            msg.classify(),
+            crate::fault_injection::Direction::Incoming,
+        ) {
+            span.in_scope(|| debug!(?msg, "dropped incoming message, fault injected"));
+            return Effects::new();
+        }
+
         span.in_scope(|| match msg {
             Message::Handshake { .. } => {
                 // We should never receive a handshake message on an established connection. Simply
@@ -831,6 +1217,11 @@ where
             Message::Payload(payload) => {
                 effect_builder.announce_incoming(peer_id, payload).ignore()
             }
+            Message::GoingAway => {
+                info!("peer is shutting down");
+                self.departing_nodes.insert(peer_id);
+                Effects::new()
+            }
         })
     }
 
@@ -853,6 +1244,106 @@ where
         }
     }
 
+    /// Updates the set of nodes known to support batched gossip messages.
+    fn update_batched_gossip_nodes_set(&mut self, peer_id: NodeId, supports_batched_gossip: bool) {
+        if supports_batched_gossip {
+            self.batched_gossip_nodes.insert(peer_id);
+        } else {
+            self.batched_gossip_nodes.remove(&peer_id);
+        }
+    }
+
+    /// Returns the estimated heap memory usage of the connection state.
+    pub(crate) fn estimate_heap_size_breakdown(&self) -> Vec<(String, usize)> {
+        vec![
+            (
+                "outgoing_manager".to_string(),
+                self.outgoing_manager.estimate_heap_size(),
+            ),
+            (
+                "connection_symmetries".to_string(),
+                self.connection_symmetries.estimate_heap_size(),
+            ),
+            (
+                "peer_sets".to_string(),
+                self.syncing_nodes.estimate_heap_size()
+                    + self.batched_gossip_nodes.estimate_heap_size()
+                    + self.departing_nodes.estimate_heap_size(),
+            ),
+        ]
+    }
+
+    /// Returns a snapshot of the connection manager state for the diagnostics port.
+    fn dump_state(&self) -> NetworkDump {
+        let now = Instant::now();
+        let mut symmetries: Vec<_> = self
+            .connection_symmetries
+            .iter()
+            .map(|(peer_id, symmetry)| {
+                let (state, in_state_for) = symmetry.describe(now);
+                SymmetryDump {
+                    peer_id: *peer_id,
+                    state,
+                    in_state_for: in_state_for.map(Into::into),
+                    incoming_addrs: symmetry
+                        .incoming_addrs()
+                        .map(|addrs| addrs.iter().copied().collect())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        symmetries.sort_by_key(|symmetry| symmetry.peer_id);
+        let mut blocked_addrs: Vec<_> = self.outgoing_manager.blocked_addrs().collect();
+        blocked_addrs.sort();
+        let mut syncing_peers: Vec<_> = self.syncing_nodes.iter().copied().collect();
+        syncing_peers.sort();
+        let mut departing_peers: Vec<_> = self.departing_nodes.iter().copied().collect();
+        departing_peers.sort();
+        let peers = self
+            .peers()
+            .into_iter()
+            .map(|(peer_id, addr)| {
+                let consensus_key = self.peer_consensus_keys.get(&peer_id).cloned();
+                let validator_status = match consensus_key {
+                    None => ValidatorStatus::NoCertificate,
+                    Some(ref key) if self.active_validators.contains(key) => {
+                        ValidatorStatus::Active
+                    }
+                    Some(ref key) if self.upcoming_validators.contains(key) => {
+                        ValidatorStatus::Upcoming
+                    }
+                    Some(_) => ValidatorStatus::NotValidator,
+                };
+                PeerDump {
+                    peer_id,
+                    addr,
+                    consensus_key,
+                    validator_status,
+                    score: self.reputation.score(&peer_id, now),
+                    traffic: self.context.peer_traffic.snapshot(&peer_id),
+                }
+            })
+            .collect();
+
+        NetworkDump {
+            our_id: self.context.our_id,
+            public_addr: self.context.public_addr,
+            active_era: self.active_era,
+            is_syncing: self.context.is_syncing.load(Ordering::SeqCst),
+            sweep_interval: OUTGOING_MANAGER_SWEEP_INTERVAL.into(),
+            since_last_sweep: self
+                .last_outgoing_sweep
+                .map(|last_sweep| now.saturating_duration_since(last_sweep).into()),
+            outgoing: self.outgoing_manager.dump(now),
+            symmetries,
+            blocked_addrs,
+            syncing_peers,
+            departing_peers,
+            reputations: self.reputation.snapshot(now),
+            peers: PeersDump { peers },
+        }
+    }
+
     /// Returns the set of connected nodes.
     pub(crate) fn peers(&self) -> BTreeMap<NodeId, String> {
         let mut ret = BTreeMap::new();
@@ -918,6 +1409,7 @@ where
         + From<BeginGossipRequest<GossipedAddress>>
         + FromIncoming<P>
         + From<StorageRequest>
+        + From<StateStoreRequest>
         + From<NetworkRequest<P>>,
     P: Payload,
 {
@@ -1010,6 +1502,7 @@ where
                         .filter_map(|(node_id, sym)| {
                             matches!(sym, ConnectionSymmetry::Symmetric { .. }).then(|| *node_id)
                         })
+                        .filter(|node_id| !self.departing_nodes.contains(node_id))
                         .collect();
 
                     symmetric_peers.shuffle(rng);
@@ -1024,13 +1517,20 @@ where
                             matches!(sym, ConnectionSymmetry::Symmetric { .. }).then(|| *node_id)
                         })
                         .filter(|node_id| !self.syncing_nodes.contains(node_id))
+                        .filter(|node_id| !self.departing_nodes.contains(node_id))
                         .collect();
 
                     symmetric_validator_peers.shuffle(rng);
 
                     responder.respond(symmetric_validator_peers).ignore()
                 }
+                NetworkInfoRequest::PeerReputations { responder } => responder
+                    .respond(self.reputation.snapshot(Instant::now()))
+                    .ignore(),
             },
+            Event::PeerAddressReceived(_) if !self.cfg.role.dials_unknown_addresses() => {
+                Effects::new()
+            }
             Event::PeerAddressReceived(gossiped_address) => {
                 let requests = self.outgoing_manager.learn_addr(
                     gossiped_address.into(),
@@ -1040,17 +1540,14 @@ where
                 self.process_dial_requests(requests)
             }
             Event::BlocklistAnnouncement(BlocklistAnnouncement::OffenseCommitted(peer_id)) => {
-                // TODO: We do not have a proper by-node-ID blocklist, but rather only block the
-                // current outgoing address of a peer.
                 warn!(%peer_id, "adding peer to blocklist after transgression");
-
-                if let Some(addr) = self.outgoing_manager.get_addr(*peer_id) {
-                    let requests = self.outgoing_manager.block_addr(addr, Instant::now());
-                    self.process_dial_requests(requests)
-                } else {
-                    // Peer got away with it, no longer an outgoing connection.
-                    Effects::new()
-                }
+                self.record_behavior(*peer_id, Behavior::Offense)
+            }
+            Event::BlocklistAnnouncement(BlocklistAnnouncement::SlowResponse(peer_id)) => {
+                self.record_behavior(*peer_id, Behavior::SlowResponse)
+            }
+            Event::BlocklistAnnouncement(BlocklistAnnouncement::UsefulGossip(peer_id)) => {
+                self.record_behavior(*peer_id, Behavior::UsefulGossip)
             }
             Event::ContractRuntimeAnnouncement(
                 ContractRuntimeAnnouncement::LinearChainBlock { .. }
@@ -1094,7 +1591,9 @@ where
                     self.incoming_limiter
                         .update_validators(active_validators.clone(), upcoming_validators.clone());
                     self.outgoing_limiter
-                        .update_validators(active_validators, upcoming_validators);
+                        .update_validators(active_validators.clone(), upcoming_validators.clone());
+                    self.active_validators = active_validators;
+                    self.upcoming_validators = upcoming_validators;
                 }
 
                 Effects::new()
@@ -1115,8 +1614,16 @@ where
             }
             Event::SweepOutgoing => {
                 let now = Instant::now();
+                self.last_outgoing_sweep = Some(now);
                 let requests = self.outgoing_manager.perform_housekeeping(now);
 
+                // Forget what we know about peers we are no longer connected to.
+                let peers = self.peers();
+                self.peer_consensus_keys
+                    .retain(|peer_id, _| peers.contains_key(peer_id));
+                self.context.peer_traffic.prune();
+                self.reputation.prune(now);
+
                 let mut effects = self.process_dial_requests(requests);
 
                 effects.extend(
@@ -1127,13 +1634,158 @@ where
 
                 effects
             }
+            Event::PeerStoreLoaded(records) => {
+                let addrs = self.peer_store.load(records, Timestamp::now());
+                let mut effects = if self.cfg.role.dials_unknown_addresses() {
+                    info!(
+                        count = addrs.len(),
+                        stored = self.peer_store.len(),
+                        "dialing stored peers"
+                    );
+                    let now = Instant::now();
+                    let requests: Vec<_> = addrs
+                        .into_iter()
+                        .filter_map(|addr| self.outgoing_manager.learn_addr(addr, false, now))
+                        .collect();
+                    self.process_dial_requests(requests)
+                } else {
+                    Effects::new()
+                };
+
+                // Only start persisting once loaded, to not overwrite the loaded records.
+                effects.extend(
+                    effect_builder
+                        .set_timeout(PEER_STORE_PERSIST_INTERVAL)
+                        .event(|_| Event::PersistPeerStore),
+                );
+                effects
+            }
+            Event::PersistPeerStore => {
+                let now = Timestamp::now();
+                let connected_addrs: Vec<SocketAddr> = self
+                    .outgoing_manager
+                    .connected_peers()
+                    .filter_map(|peer_id| self.outgoing_manager.get_addr(peer_id))
+                    .collect();
+                for addr in connected_addrs {
+                    self.peer_store.touch(addr, now);
+                }
+
+                let mut effects = effect_builder
+                    .set_timeout(PEER_STORE_PERSIST_INTERVAL)
+                    .event(|_| Event::PersistPeerStore);
+                if let Some(records) = self.peer_store.take_changes(now) {
+                    effects.extend(
+                        effect_builder
+                            .save_state(PEER_STORE_STATE_KEY.into(), records)
+                            .ignore(),
+                    );
+                }
+                effects
+            }
+            Event::DumpState(DumpNetworkStateRequest { responder }) => {
+                responder.respond(self.dump_state()).ignore()
+            }
             Event::ChainSynchronizerAnnouncement(ChainSynchronizerAnnouncement::SyncFinished) => {
                 self.context.is_syncing.store(false, Ordering::SeqCst);
                 self.close_incoming_connections();
                 Effects::new()
             }
+            Event::ChainSynchronizerAnnouncement(ChainSynchronizerAnnouncement::SyncProgress(
+                _,
+            )) => Effects::new(),
+            Event::ChainspecLoaderAnnouncement(
+                ChainspecLoaderAnnouncement::UpgradeActivationPointRead(next_upgrade),
+            ) => {
+                let staged_protocol_version = Some(next_upgrade.protocol_version());
+                let changed = match self.context.staged_protocol_version.write() {
+                    Ok(mut current) => {
+                        mem::replace(&mut *current, staged_protocol_version)
+                            != staged_protocol_version
+                    }
+                    Err(_) => {
+                        debug!("could not update staged protocol version, lock poisoned");
+                        false
+                    }
+                };
+                // Peers only learn about the staged upgrade during the handshake, so we have them
+                // reconnect, as when we finished syncing.
+                if changed {
+                    info!(%next_upgrade, "announcing staged upgrade to peers");
+                    self.close_incoming_connections();
+                }
+                Effects::new()
+            }
+        }
+    }
+}
+
+/// Loads the network key and its endorsement, checking the endorsement is for this key.
+fn load_network_keys(cfg: &NetworkKeyConfig) -> Result<NetworkKeyPair> {
+    let secret_key = <Arc<SecretKey>>::from_path(&cfg.secret_key).map_err(Error::LoadNetworkKey)?;
+    let endorsement = NetworkKeyEndorsement::from_path(&cfg.endorsement)
+        .map_err(Error::LoadNetworkKeyEndorsement)?;
+    if endorsement.network_public_key != PublicKey::from(secret_key.as_ref()) {
+        return Err(Error::NetworkKeyNotEndorsed);
+    }
+    endorsement
+        .verify()
+        .map_err(Error::InvalidNetworkKeyEndorsement)?;
+    info!(
+        validator_public_key = %endorsement.validator_public_key,
+        network_public_key = %endorsement.network_public_key,
+        "using endorsed network key for handshakes"
+    );
+    Ok(NetworkKeyPair::new(secret_key, endorsement))
+}
+
+/// Resolves the given known addresses, skipping any which fail to resolve or are duplicated.
+fn resolve_known_addresses(addresses: &[String]) -> HashSet<SocketAddr> {
+    let mut known_addresses = HashSet::new();
+    for address in addresses {
+        match utils::resolve_address(address) {
+            Ok(known_address) => {
+                if !known_addresses.insert(known_address) {
+                    warn!(%address, resolved=%known_address, "ignoring duplicated known address");
+                };
+            }
+            Err(ref err) => {
+                warn!(%address, err=display_error(err), "failed to resolve known address");
+            }
         }
     }
+    known_addresses
+}
+
+/// Returns whether a connection failing with the given error after the peer identified itself
+/// counts against the peer's reputation.
+fn is_handshake_failure_by_peer(error: &ConnectionError) -> bool {
+    match error {
+        // Potentially transient failures, including incompatible versions during upgrades.
+        ConnectionError::TlsInitialization(_)
+        | ConnectionError::TcpConnection(_)
+        | ConnectionError::TcpNoDelay(_)
+        | ConnectionError::TlsHandshake(_)
+        | ConnectionError::HandshakeSend(_)
+        | ConnectionError::HandshakeRecv(_)
+        | ConnectionError::IncompatibleVersion(_) => false,
+
+        // These errors are potential bugs on our side.
+        ConnectionError::HandshakeSenderCrashed(_)
+        | ConnectionError::FailedToReuniteHandshakeSinkAndStream
+        | ConnectionError::CouldNotEncodeOurHandshake(_) => false,
+
+        // The peer sent something it should not have.
+        ConnectionError::NoPeerCertificate
+        | ConnectionError::PeerCertificateInvalid(_)
+        | ConnectionError::DidNotSendHandshake
+        | ConnectionError::InvalidRemoteHandshakeMessage(_)
+        | ConnectionError::InvalidConsensusCertificate(_)
+        | ConnectionError::InvalidNetworkCertificate(_)
+        | ConnectionError::WrongNetwork(_)
+        | ConnectionError::WrongChainspecHash(_)
+        | ConnectionError::MissingChainspecHash => true,
+    }
 }
 
 #[derive(Debug, Error)]
@@ -1220,7 +1872,7 @@ pub(crate) type FullTransport<P> = tokio_serde::Framed<
     FramedTransport,
     Message<P>,
     Arc<Message<P>>,
-    CountingFormat<BincodeFormat>,
+    CountingFormat<CompressionFormat<BincodeFormat>>,
 >;
 
 pub(crate) type FramedTransport = tokio_util::codec::Framed<Transport, LengthDelimitedCodec>;
@@ -1228,11 +1880,15 @@ pub(crate) type FramedTransport = tokio_util::codec::Framed<Transport, LengthDel
 /// Constructs a new full transport on a stream.
 ///
 /// A full transport contains the framing as well as the encoding scheme used to send messages.
+/// Payloads are compressed if `compression` was negotiated during the handshake.
 fn full_transport<P>(
     metrics: Weak<Metrics>,
+    traffic: Arc<PeerTraffic>,
     connection_id: ConnectionId,
     framed: FramedTransport,
     role: Role,
+    compression: bool,
+    maximum_net_message_size: u32,
 ) -> FullTransport<P>
 where
     for<'de> P: Serialize + Deserialize<'de>,
@@ -1240,7 +1896,17 @@ where
 {
     tokio_serde::Framed::new(
         framed,
-        CountingFormat::new(metrics, connection_id, role, BincodeFormat::default()),
+        CountingFormat::new(
+            metrics,
+            traffic,
+            connection_id,
+            role,
+            CompressionFormat::new(
+                compression,
+                maximum_net_message_size,
+                BincodeFormat::default(),
+            ),
+        ),
     )
 }
 
@@ -0,0 +1,301 @@
+//! Admission control for incoming connections.
+//!
+//! Before the comparatively expensive TLS and protocol handshakes are performed, incoming
+//! connections are checked against caps on the number of concurrent connections per IP address and
+//! per subnet, and on the number of handshake attempts per minute per IP address. An address
+//! exceeding any of them is greylisted, i.e. all of its connections are rejected for a configured
+//! duration.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+use tracing::info;
+
+use super::config::IncomingLimitsConfig;
+
+/// Window over which handshake attempts are counted.
+const HANDSHAKE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Admission control for incoming connections, shared by the acceptor and all connections.
+#[derive(Debug)]
+pub(super) struct Admission {
+    /// The configured limits.
+    config: IncomingLimitsConfig,
+    /// How long violators are greylisted for.
+    greylist_duration: Duration,
+    /// Bookkeeping of connections and handshakes.
+    state: Mutex<State>,
+}
+
+/// Bookkeeping of connections and handshakes per address.
+#[derive(Debug, Default)]
+struct State {
+    /// Number of open connections per IP address.
+    connections_per_ip: HashMap<IpAddr, u32>,
+    /// Number of open connections per subnet, keyed by the subnet's network address.
+    connections_per_subnet: HashMap<IpAddr, u32>,
+    /// Handshake attempts per IP address in the current window.
+    handshakes: HashMap<IpAddr, HandshakeWindow>,
+    /// Greylisted IP addresses, with the time they are released.
+    greylist: HashMap<IpAddr, Instant>,
+    /// When expired entries were last removed.
+    last_pruned: Option<Instant>,
+}
+
+/// Handshake attempts counted since the start of a window.
+#[derive(Debug)]
+struct HandshakeWindow {
+    started: Instant,
+    attempts: u32,
+}
+
+/// Reason for rejecting an incoming connection.
+#[derive(Debug, Error)]
+pub(super) enum Rejection {
+    /// The address has previously exceeded a limit.
+    #[error("address is greylisted")]
+    Greylisted,
+    /// Too many concurrent connections from the address.
+    #[error("exceeded limit of {0} connections per address")]
+    TooManyConnectionsFromIp(u32),
+    /// Too many concurrent connections from the address's subnet.
+    #[error("exceeded limit of {limit} connections from subnet {subnet}")]
+    TooManyConnectionsFromSubnet { subnet: IpAddr, limit: u32 },
+    /// Too many handshake attempts from the address within a minute.
+    #[error("exceeded limit of {0} handshakes per minute")]
+    HandshakeRateExceeded(u32),
+}
+
+/// Permission for an admitted connection to stay open.
+///
+/// The connection is no longer counted towards the limits once the permit is dropped.
+#[derive(Debug)]
+pub(crate) struct AdmissionPermit {
+    admission: Arc<Admission>,
+    ip: IpAddr,
+    subnet: IpAddr,
+}
+
+impl Admission {
+    /// Creates a new admission control with the given limits.
+    pub(super) fn new(config: IncomingLimitsConfig) -> Self {
+        Admission {
+            greylist_duration: config.greylist_duration.into(),
+            config,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Checks whether a new connection from `ip` is admitted, greylisting the address if it
+    /// exceeds a limit.
+    pub(super) fn admit(
+        self: &Arc<Self>,
+        ip: IpAddr,
+        now: Instant,
+    ) -> Result<AdmissionPermit, Rejection> {
+        let mut state = self.state.lock().expect("admission lock poisoned");
+        state.prune(now);
+
+        if state.greylist.contains_key(&ip) {
+            return Err(Rejection::Greylisted);
+        }
+
+        let subnet = self.subnet(ip);
+        if let Err(rejection) = self.check(&mut state, ip, subnet, now) {
+            info!(%ip, %rejection, greylist_duration=?self.greylist_duration, "greylisting address");
+            state.greylist.insert(ip, now + self.greylist_duration);
+            return Err(rejection);
+        }
+
+        *state.connections_per_ip.entry(ip).or_default() += 1;
+        *state.connections_per_subnet.entry(subnet).or_default() += 1;
+        Ok(AdmissionPermit {
+            admission: Arc::clone(self),
+            ip,
+            subnet,
+        })
+    }
+
+    /// Counts a handshake attempt and checks all limits.
+    fn check(
+        &self,
+        state: &mut State,
+        ip: IpAddr,
+        subnet: IpAddr,
+        now: Instant,
+    ) -> Result<(), Rejection> {
+        let window = state.handshakes.entry(ip).or_insert(HandshakeWindow {
+            started: now,
+            attempts: 0,
+        });
+        if now.saturating_duration_since(window.started) >= HANDSHAKE_WINDOW {
+            *window = HandshakeWindow {
+                started: now,
+                attempts: 0,
+            };
+        }
+        window.attempts += 1;
+
+        let limit = self.config.max_handshakes_per_minute_per_ip;
+        if limit != 0 && window.attempts > limit {
+            return Err(Rejection::HandshakeRateExceeded(limit));
+        }
+
+        let limit = self.config.max_connections_per_ip;
+        let count = state
+            .connections_per_ip
+            .get(&ip)
+            .copied()
+            .unwrap_or_default();
+        if limit != 0 && count >= limit {
+            return Err(Rejection::TooManyConnectionsFromIp(limit));
+        }
+
+        let limit = self.config.max_connections_per_subnet;
+        let count = state
+            .connections_per_subnet
+            .get(&subnet)
+            .copied()
+            .unwrap_or_default();
+        if limit != 0 && count >= limit {
+            return Err(Rejection::TooManyConnectionsFromSubnet { subnet, limit });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the network address of the subnet `ip` belongs to.
+    fn subnet(&self, ip: IpAddr) -> IpAddr {
+        match ip {
+            IpAddr::V4(ip) => {
+                let prefix = u32::from(self.config.ipv4_subnet_prefix.min(32));
+                let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or_default();
+                IpAddr::V4(Ipv4Addr::from(u32::from(ip) & mask))
+            }
+            IpAddr::V6(ip) => {
+                let prefix = u32::from(self.config.ipv6_subnet_prefix.min(128));
+                let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or_default();
+                IpAddr::V6(Ipv6Addr::from(u128::from(ip) & mask))
+            }
+        }
+    }
+
+    /// Stops counting a closed connection.
+    fn release(&self, ip: IpAddr, subnet: IpAddr) {
+        let mut state = self.state.lock().expect("admission lock poisoned");
+        decrement(&mut state.connections_per_ip, ip);
+        decrement(&mut state.connections_per_subnet, subnet);
+    }
+}
+
+impl State {
+    /// Removes expired greylist entries, and expired handshake windows at most once per window.
+    fn prune(&mut self, now: Instant) {
+        self.greylist.retain(|_, until| *until > now);
+
+        let window_passed = self.last_pruned.map_or(true, |last_pruned| {
+            now.saturating_duration_since(last_pruned) >= HANDSHAKE_WINDOW
+        });
+        if window_passed {
+            self.handshakes.retain(|_, window| {
+                now.saturating_duration_since(window.started) < HANDSHAKE_WINDOW
+            });
+            self.last_pruned = Some(now);
+        }
+    }
+}
+
+/// Decrements a connection count, removing it once it reaches zero.
+fn decrement(counts: &mut HashMap<IpAddr, u32>, key: IpAddr) {
+    if let Some(count) = counts.get_mut(&key) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            counts.remove(&key);
+        }
+    }
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        self.admission.release(self.ip, self.subnet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::TimeDiff;
+
+    use super::*;
+
+    fn admission(config: IncomingLimitsConfig) -> Arc<Admission> {
+        Arc::new(Admission::new(IncomingLimitsConfig {
+            greylist_duration: TimeDiff::from_seconds(300),
+            ..config
+        }))
+    }
+
+    #[test]
+    fn should_limit_concurrent_connections_per_ip_and_subnet() {
+        let admission = admission(IncomingLimitsConfig {
+            max_connections_per_ip: 2,
+            max_connections_per_subnet: 3,
+            ..Default::default()
+        });
+        let now = Instant::now();
+        let ip = |last: u8| IpAddr::V4(Ipv4Addr::new(192, 0, 2, last));
+
+        let first = admission.admit(ip(1), now).unwrap();
+        let _second = admission.admit(ip(1), now).unwrap();
+        assert!(matches!(
+            admission.admit(ip(1), now),
+            Err(Rejection::TooManyConnectionsFromIp(2))
+        ));
+        // The violating address is now greylisted, even once a connection closes.
+        drop(first);
+        assert!(matches!(
+            admission.admit(ip(1), now),
+            Err(Rejection::Greylisted)
+        ));
+
+        let _third = admission.admit(ip(2), now).unwrap();
+        let _fourth = admission.admit(ip(3), now).unwrap();
+        assert!(matches!(
+            admission.admit(ip(4), now),
+            Err(Rejection::TooManyConnectionsFromSubnet { limit: 3, .. })
+        ));
+        // Other subnets are unaffected.
+        let _other = admission
+            .admit(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1)), now)
+            .unwrap();
+    }
+
+    #[test]
+    fn should_limit_handshake_rate_and_release_greylisted_addresses() {
+        let admission = admission(IncomingLimitsConfig {
+            max_handshakes_per_minute_per_ip: 3,
+            ..Default::default()
+        });
+        let start = Instant::now();
+        let ip = IpAddr::V6(Ipv6Addr::LOCALHOST);
+
+        for _ in 0..3 {
+            drop(admission.admit(ip, start).unwrap());
+        }
+        assert!(matches!(
+            admission.admit(ip, start),
+            Err(Rejection::HandshakeRateExceeded(3))
+        ));
+        assert!(matches!(
+            admission.admit(ip, start + Duration::from_secs(299)),
+            Err(Rejection::Greylisted)
+        ));
+        assert!(admission
+            .admit(ip, start + Duration::from_secs(300))
+            .is_ok());
+    }
+}
@@ -11,7 +11,9 @@ use datasize::DataSize;
 
 use super::{
     counting_format::ConnectionId,
-    message::{ConsensusCertificate, ConsensusKeyPair},
+    encoding_version::EncodingVersion,
+    features::Features,
+    message::{ConsensusCertificate, ConsensusKeyPair, NetworkCertificate, NetworkKeyPair},
     Message,
 };
 use crate::types::Chainspec;
@@ -50,8 +52,11 @@ impl ChainInfo {
         &self,
         public_addr: SocketAddr,
         consensus_keys: Option<&ConsensusKeyPair>,
+        network_keys: Option<&NetworkKeyPair>,
         connection_id: ConnectionId,
         is_syncing: bool,
+        staged_protocol_version: Option<ProtocolVersion>,
+        features: Features,
     ) -> Message<P> {
         Message::Handshake {
             network_name: self.network_name.clone(),
@@ -61,6 +66,11 @@ impl ChainInfo {
                 .map(|key_pair| ConsensusCertificate::create(connection_id, key_pair)),
             is_syncing,
             chainspec_hash: Some(self.chainspec_hash),
+            staged_protocol_version,
+            network_certificate: network_keys
+                .map(|key_pair| NetworkCertificate::create(connection_id, key_pair)),
+            encoding_versions: EncodingVersion::supported_ids(),
+            features,
         }
     }
 }
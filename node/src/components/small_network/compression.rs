@@ -0,0 +1,208 @@
+//! Optional compression of payloads.
+//!
+//! Nodes with compression enabled advertise it in their handshake.  If both ends of a connection
+//! do, every message sent over it after the handshake is prefixed with a tag byte indicating whether
+//! the remainder is zstd-compressed.  Only payloads large enough to be worth it are compressed, and
+//! only if compressing actually shrinks them.  Connections involving a peer without compression
+//! support encode messages exactly as before.
+
+use std::{io, pin::Pin, sync::Arc};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use pin_project::pin_project;
+use tokio_serde::{Deserializer, Serializer};
+
+use super::Message;
+
+/// Tag of a message sent as is.
+const TAG_UNCOMPRESSED: u8 = 0;
+
+/// Tag of a zstd-compressed message.
+const TAG_ZSTD: u8 = 1;
+
+/// Serialized payloads smaller than this are not compressed.
+const MIN_COMPRESSED_SIZE: usize = 1024;
+
+/// The zstd compression level, trading compression ratio for speed.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// A serializer/deserializer wrapper compressing payloads, if enabled for the connection.
+#[pin_project]
+#[derive(Debug)]
+pub struct CompressionFormat<F> {
+    /// The actual serializer performing the work.
+    #[pin]
+    inner: F,
+    /// Whether compression was negotiated for the connection.
+    enabled: bool,
+    /// The maximum size of a decompressed message.
+    max_decompressed_size: usize,
+}
+
+impl<F> CompressionFormat<F> {
+    /// Creates a new compression formatter.
+    ///
+    /// `enabled` must only be set if both peers advertised compression support in their handshakes.
+    pub(super) fn new(enabled: bool, maximum_net_message_size: u32, inner: F) -> Self {
+        CompressionFormat {
+            inner,
+            enabled,
+            max_decompressed_size: maximum_net_message_size as usize,
+        }
+    }
+}
+
+/// Prefixes `data` with `tag`.
+fn tagged(tag: u8, data: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(data.len() + 1);
+    buf.put_u8(tag);
+    buf.put_slice(data);
+    buf.freeze()
+}
+
+impl<F, P> Serializer<Arc<Message<P>>> for CompressionFormat<F>
+where
+    F: Serializer<Arc<Message<P>>, Error = io::Error>,
+{
+    type Error = io::Error;
+
+    fn serialize(self: Pin<&mut Self>, item: &Arc<Message<P>>) -> Result<Bytes, Self::Error> {
+        let this = self.project();
+        let serialized = F::serialize(this.inner, item)?;
+
+        if !*this.enabled {
+            return Ok(serialized);
+        }
+
+        if matches!(**item, Message::Payload(_)) && serialized.len() >= MIN_COMPRESSED_SIZE {
+            let compressed = zstd::bulk::compress(&serialized, COMPRESSION_LEVEL)?;
+            if compressed.len() < serialized.len() {
+                return Ok(tagged(TAG_ZSTD, &compressed));
+            }
+        }
+
+        Ok(tagged(TAG_UNCOMPRESSED, &serialized))
+    }
+}
+
+impl<F, P> Deserializer<Message<P>> for CompressionFormat<F>
+where
+    F: Deserializer<Message<P>, Error = io::Error>,
+{
+    type Error = io::Error;
+
+    fn deserialize(self: Pin<&mut Self>, src: &BytesMut) -> Result<Message<P>, Self::Error> {
+        let this = self.project();
+
+        if !*this.enabled {
+            return F::deserialize(this.inner, src);
+        }
+
+        let (tag, data) = src
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing compression tag"))?;
+
+        let decompressed = match *tag {
+            TAG_UNCOMPRESSED => BytesMut::from(data),
+            TAG_ZSTD => {
+                // Bounding the output prevents a peer from exhausting our memory with a small
+                // message decompressing to an arbitrarily large one.
+                let decompressed = zstd::bulk::decompress(data, *this.max_decompressed_size)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                BytesMut::from(&decompressed[..])
+            }
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown compression tag {}", tag),
+                ))
+            }
+        };
+
+        F::deserialize(this.inner, &decompressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, pin::Pin, sync::Arc};
+
+    use bytes::{Bytes, BytesMut};
+    use tokio_serde::{Deserializer, Serializer};
+
+    use super::{CompressionFormat, TAG_UNCOMPRESSED, TAG_ZSTD};
+    use crate::components::small_network::{BincodeFormat, Message};
+
+    const MAX_SIZE: u32 = 1024 * 1024;
+
+    fn serialize(format: &mut CompressionFormat<BincodeFormat>, msg: Message<Vec<u8>>) -> Bytes {
+        Pin::new(format)
+            .serialize(&Arc::new(msg))
+            .expect("serialization failed")
+    }
+
+    fn deserialize(
+        format: &mut CompressionFormat<BincodeFormat>,
+        raw: &[u8],
+    ) -> io::Result<Message<Vec<u8>>> {
+        Pin::new(format).deserialize(&BytesMut::from(raw))
+    }
+
+    fn payload(msg: Message<Vec<u8>>) -> Vec<u8> {
+        match msg {
+            Message::Payload(payload) => payload,
+            other => panic!("expected payload, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn large_payloads_are_compressed() {
+        let mut format = CompressionFormat::new(true, MAX_SIZE, BincodeFormat::default());
+        let data = vec![7u8; 64 * 1024];
+
+        let raw = serialize(&mut format, Message::Payload(data.clone()));
+        assert_eq!(raw[0], TAG_ZSTD);
+        assert!(raw.len() < data.len() / 10);
+
+        let decoded = deserialize(&mut format, &raw).expect("deserialization failed");
+        assert_eq!(payload(decoded), data);
+    }
+
+    #[test]
+    fn small_and_protocol_messages_are_not_compressed() {
+        let mut format = CompressionFormat::new(true, MAX_SIZE, BincodeFormat::default());
+
+        let raw = serialize(&mut format, Message::Payload(vec![7u8; 16]));
+        assert_eq!(raw[0], TAG_UNCOMPRESSED);
+        let decoded = deserialize(&mut format, &raw).expect("deserialization failed");
+        assert_eq!(payload(decoded), vec![7u8; 16]);
+
+        let raw = serialize(&mut format, Message::GoingAway);
+        assert_eq!(raw[0], TAG_UNCOMPRESSED);
+        assert!(matches!(
+            deserialize(&mut format, &raw).expect("deserialization failed"),
+            Message::GoingAway
+        ));
+    }
+
+    #[test]
+    fn disabled_compression_does_not_change_encoding() {
+        let mut format = CompressionFormat::new(false, MAX_SIZE, BincodeFormat::default());
+        let msg = Message::Payload(vec![7u8; 64 * 1024]);
+
+        let raw = serialize(&mut format, msg.clone());
+        let expected = Pin::new(&mut BincodeFormat::default())
+            .serialize(&Arc::new(msg))
+            .expect("serialization failed");
+        assert_eq!(raw, expected);
+    }
+
+    #[test]
+    fn oversized_decompressed_messages_are_rejected() {
+        let mut sender = CompressionFormat::new(true, MAX_SIZE, BincodeFormat::default());
+        let raw = serialize(&mut sender, Message::Payload(vec![7u8; 64 * 1024]));
+
+        let mut receiver = CompressionFormat::new(true, 1024, BincodeFormat::default());
+        assert!(deserialize(&mut receiver, &raw).is_err());
+    }
+}
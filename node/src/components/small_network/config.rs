@@ -6,7 +6,7 @@ use casper_types::{ProtocolVersion, TimeDiff};
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
-use super::EstimatorWeights;
+use super::{EstimatorWeights, MessageKind};
 
 /// Default binding address.
 ///
@@ -30,6 +30,33 @@ const DEFAULT_MAX_ADDR_PENDING_TIME: TimeDiff = TimeDiff::from_seconds(60);
 /// Default timeout during which the handshake needs to be completed.
 const DEFAULT_HANDSHAKE_TIMEOUT: TimeDiff = TimeDiff::from_seconds(20);
 
+/// Default maximum number of deploys from a single peer being processed at once.
+const DEFAULT_MAX_IN_FLIGHT_DEPLOYS: u32 = 50;
+
+/// Default prefix length of the IPv4 subnets incoming connections are counted by.
+const DEFAULT_IPV4_SUBNET_PREFIX: u8 = 24;
+
+/// Default prefix length of the IPv6 subnets incoming connections are counted by.
+const DEFAULT_IPV6_SUBNET_PREFIX: u8 = 64;
+
+/// Default duration for which addresses exceeding an incoming connection limit are greylisted.
+const DEFAULT_GREYLIST_DURATION: TimeDiff = TimeDiff::from_seconds(300);
+
+/// Default duration of the first ban of a peer whose reputation dropped too low.
+const DEFAULT_BASE_BAN_DURATION: TimeDiff = TimeDiff::from_seconds(60);
+
+/// Default maximum duration of a single ban.
+const DEFAULT_MAX_BAN_DURATION: TimeDiff = TimeDiff::from_seconds(24 * 60 * 60);
+
+/// Default maximum number of peers recorded in the peer store.
+const DEFAULT_MAX_STORED_PEERS: u32 = 1000;
+
+/// Default duration after which a peer not seen is forgotten by the peer store.
+const DEFAULT_MAX_STORED_PEER_AGE: TimeDiff = TimeDiff::from_seconds(7 * 24 * 60 * 60);
+
+/// Default maximum number of stored peers dialed on startup.
+const DEFAULT_MAX_STORED_PEERS_DIALED: u32 = 50;
+
 // Default values for networking configuration:
 impl Default for Config {
     fn default() -> Self {
@@ -49,8 +76,77 @@ impl Default for Config {
             tarpit_duration: TimeDiff::from_seconds(600),
             tarpit_chance: 0.2,
             max_in_flight_demands: 50,
+            max_in_flight_deploys: DEFAULT_MAX_IN_FLIGHT_DEPLOYS,
             blocklist_retain_duration: TimeDiff::from_seconds(600),
             identity: None,
+            network_key: None,
+            incoming_limits: IncomingLimitsConfig::default(),
+            traffic_shaping: TrafficShapingConfig::default(),
+            enable_compression: false,
+            reputation: ReputationConfig::default(),
+            peer_store: PeerStoreConfig::default(),
+            role: NodeRole::default(),
+        }
+    }
+}
+
+impl Default for PeerStoreConfig {
+    fn default() -> Self {
+        PeerStoreConfig {
+            max_peers: DEFAULT_MAX_STORED_PEERS,
+            max_age: DEFAULT_MAX_STORED_PEER_AGE,
+            max_dialed_on_startup: DEFAULT_MAX_STORED_PEERS_DIALED,
+        }
+    }
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        ReputationConfig {
+            invalid_message_penalty: 100,
+            handshake_failure_penalty: 20,
+            slow_response_penalty: 5,
+            useful_gossip_reward: 1,
+            max_score: 100,
+            ban_threshold: -200,
+            recovery_per_minute: 10,
+            base_ban_duration: DEFAULT_BASE_BAN_DURATION,
+            max_ban_duration: DEFAULT_MAX_BAN_DURATION,
+            max_incoming_peers: 0,
+        }
+    }
+}
+
+impl Default for IncomingLimitsConfig {
+    fn default() -> Self {
+        IncomingLimitsConfig {
+            max_connections_per_ip: 0,
+            max_connections_per_subnet: 0,
+            ipv4_subnet_prefix: DEFAULT_IPV4_SUBNET_PREFIX,
+            ipv6_subnet_prefix: DEFAULT_IPV6_SUBNET_PREFIX,
+            max_handshakes_per_minute_per_ip: 0,
+            greylist_duration: DEFAULT_GREYLIST_DURATION,
+        }
+    }
+}
+
+impl Default for TrafficShapingConfig {
+    fn default() -> Self {
+        let unlimited = |priority| KindShapingConfig {
+            priority,
+            max_bytes_per_sec: 0,
+            max_queue_length: 0,
+        };
+        TrafficShapingConfig {
+            protocol: unlimited(0),
+            consensus: unlimited(1),
+            deploy_gossip: unlimited(2),
+            address_gossip: unlimited(2),
+            deploy_transfer: unlimited(2),
+            finalized_approvals_transfer: unlimited(2),
+            block_transfer: unlimited(2),
+            trie_transfer: unlimited(3),
+            other: unlimited(2),
         }
     }
 }
@@ -68,6 +164,171 @@ pub struct IdentityConfig {
     pub ca_certificate: PathBuf,
 }
 
+/// Network key configuration.
+#[derive(DataSize, Debug, Clone, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct NetworkKeyConfig {
+    /// Path to the secret key of the network identity.
+    pub secret_key: PathBuf,
+    /// Path to the endorsement of the network public key by the validator's secret key.
+    pub endorsement: PathBuf,
+}
+
+/// Limits on incoming connections per source address, enforced before any handshake.
+#[derive(DataSize, Debug, Clone, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(default, deny_unknown_fields)]
+pub struct IncomingLimitsConfig {
+    /// Maximum number of concurrent incoming connections from a single IP address. Unlimited if
+    /// 0.
+    pub max_connections_per_ip: u32,
+    /// Maximum number of concurrent incoming connections from a single subnet. Unlimited if 0.
+    pub max_connections_per_subnet: u32,
+    /// Prefix length of the IPv4 subnets connections are counted by.
+    pub ipv4_subnet_prefix: u8,
+    /// Prefix length of the IPv6 subnets connections are counted by.
+    pub ipv6_subnet_prefix: u8,
+    /// Maximum number of handshake attempts per minute from a single IP address. Unlimited if 0.
+    pub max_handshakes_per_minute_per_ip: u32,
+    /// Duration for which an address exceeding any of the limits has all connections rejected.
+    pub greylist_duration: TimeDiff,
+}
+
+/// Scoring of peers by their behavior, and the consequences of a low score.
+#[derive(DataSize, Debug, Clone, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(default, deny_unknown_fields)]
+pub struct ReputationConfig {
+    /// Score deducted for a message which could not be decoded or an item which failed validation.
+    pub invalid_message_penalty: u32,
+    /// Score deducted for a handshake which failed after the peer's identity was established.
+    pub handshake_failure_penalty: u32,
+    /// Score deducted for a fetch request the peer did not answer in time.
+    pub slow_response_penalty: u32,
+    /// Score added for gossip about an item we did not hold yet.
+    pub useful_gossip_reward: u32,
+    /// Highest score a peer can reach.
+    pub max_score: i64,
+    /// Score at or below which a peer is banned.
+    pub ban_threshold: i64,
+    /// Points per minute by which scores recover towards zero.
+    pub recovery_per_minute: u32,
+    /// Duration of the first ban of a peer, doubled with every further ban.
+    pub base_ban_duration: TimeDiff,
+    /// Maximum duration of a single ban.
+    pub max_ban_duration: TimeDiff,
+    /// Maximum number of peers with incoming connections. When full, a connection from a peer
+    /// with a higher score than the lowest-scoring connected peer evicts that peer, any other is
+    /// rejected. Validators are exempt. Unlimited if 0.
+    pub max_incoming_peers: u32,
+}
+
+/// Persisted address book of peers, redialed after a restart.
+#[derive(DataSize, Debug, Clone, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(default, deny_unknown_fields)]
+pub struct PeerStoreConfig {
+    /// Maximum number of peers recorded, the least recently seen ones being forgotten first.
+    /// Peers are neither recorded nor redialed if 0.
+    pub max_peers: u32,
+    /// Duration after which a peer we were not connected to is forgotten.
+    pub max_age: TimeDiff,
+    /// Maximum number of recorded peers dialed on startup, in addition to the known addresses.
+    /// Validators are dialed first, then the peers with the lowest latency.
+    pub max_dialed_on_startup: u32,
+}
+
+/// The role of the node in the network, determining whom it dials and whether it advertises
+/// itself.
+#[derive(DataSize, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeRole {
+    /// Dials the known, recorded and gossiped addresses, and gossips its own address.
+    Standard,
+    /// Like a standard node, but accepts incoming connections regardless of
+    /// `reputation.max_incoming_peers` and doesn't gossip its own address.
+    Relay,
+    /// Only dials the known addresses, i.e. its sentry nodes.  Neither gossips its own address nor
+    /// learns the addresses of other peers.
+    PrivateValidator,
+}
+
+impl NodeRole {
+    /// Returns whether the node gossips its own address.
+    pub(super) fn gossips_own_address(self) -> bool {
+        self == NodeRole::Standard
+    }
+
+    /// Returns whether the node dials addresses other than the known addresses.
+    pub(super) fn dials_unknown_addresses(self) -> bool {
+        self != NodeRole::PrivateValidator
+    }
+}
+
+impl Default for NodeRole {
+    fn default() -> Self {
+        NodeRole::Standard
+    }
+}
+
+/// Outgoing traffic shaping of a single kind of message, applied to each peer separately.
+#[derive(DataSize, Debug, Clone, Copy, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct KindShapingConfig {
+    /// Priority of the kind of message.  Queued messages of a kind with a lower value are always
+    /// sent before those of a kind with a higher value, unless their bandwidth limit is reached.
+    pub priority: u8,
+    /// Maximum number of bytes per second sent to a single peer. Unlimited if 0.
+    pub max_bytes_per_sec: u32,
+    /// Maximum number of messages queued for a single peer, any further messages being dropped.
+    /// Unlimited if 0.
+    pub max_queue_length: u32,
+}
+
+/// Outgoing traffic shaping configuration, per kind of message.
+#[derive(DataSize, Debug, Clone, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(default, deny_unknown_fields)]
+pub struct TrafficShapingConfig {
+    /// Shaping of non-payload messages.
+    pub protocol: KindShapingConfig,
+    /// Shaping of consensus messages.
+    pub consensus: KindShapingConfig,
+    /// Shaping of deploy gossip messages.
+    pub deploy_gossip: KindShapingConfig,
+    /// Shaping of address gossip messages.
+    pub address_gossip: KindShapingConfig,
+    /// Shaping of deploy requests and responses.
+    pub deploy_transfer: KindShapingConfig,
+    /// Shaping of finalized approvals requests and responses.
+    pub finalized_approvals_transfer: KindShapingConfig,
+    /// Shaping of block requests and responses.
+    pub block_transfer: KindShapingConfig,
+    /// Shaping of trie requests and responses.
+    pub trie_transfer: KindShapingConfig,
+    /// Shaping of any other messages.
+    pub other: KindShapingConfig,
+}
+
+impl TrafficShapingConfig {
+    /// Returns the shaping of the given kind of message.
+    pub(super) fn for_kind(&self, kind: MessageKind) -> KindShapingConfig {
+        match kind {
+            MessageKind::Protocol => self.protocol,
+            MessageKind::Consensus => self.consensus,
+            MessageKind::DeployGossip => self.deploy_gossip,
+            MessageKind::AddressGossip => self.address_gossip,
+            MessageKind::DeployTransfer => self.deploy_transfer,
+            MessageKind::FinalizedApprovalsTransfer => self.finalized_approvals_transfer,
+            MessageKind::BlockTransfer => self.block_transfer,
+            MessageKind::TrieTransfer => self.trie_transfer,
+            MessageKind::Other => self.other,
+        }
+    }
+}
+
 /// Small network configuration.
 #[derive(DataSize, Debug, Clone, Deserialize, Serialize)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
@@ -105,6 +366,9 @@ pub struct Config {
     pub tarpit_chance: f32,
     /// Maximum number of demands for objects that can be in-flight.
     pub max_in_flight_demands: u32,
+    /// Maximum number of deploys received from a single peer that can be processed at once.
+    #[serde(default = "default_max_in_flight_deploys")]
+    pub max_in_flight_deploys: u32,
     /// Duration peers are kept on the block list, before being redeemed.
     pub blocklist_retain_duration: TimeDiff,
     /// Small network identity configuration option.
@@ -112,6 +376,35 @@ pub struct Config {
     /// An identity will be automatically generated when starting up a node if this option is
     /// unspecified.
     pub identity: Option<IdentityConfig>,
+    /// Network key configuration option.
+    ///
+    /// If specified, handshakes are signed with this key and carry its endorsement by the validator
+    /// key, rather than being signed with the validator key directly.
+    #[serde(default)]
+    pub network_key: Option<NetworkKeyConfig>,
+    /// Limits on incoming connections per source address.
+    #[serde(default)]
+    pub incoming_limits: IncomingLimitsConfig,
+    /// Shaping of outgoing traffic per kind of message.
+    #[serde(default)]
+    pub traffic_shaping: TrafficShapingConfig,
+    /// Whether to compress large payloads sent to, and accept compressed payloads from, peers which
+    /// also have compression enabled.
+    #[serde(default)]
+    pub enable_compression: bool,
+    /// Scoring of peers and banning of those with a low score.
+    #[serde(default)]
+    pub reputation: ReputationConfig,
+    /// Persisted address book of peers.
+    #[serde(default)]
+    pub peer_store: PeerStoreConfig,
+    /// The role of the node in the network.
+    #[serde(default)]
+    pub role: NodeRole,
+}
+
+fn default_max_in_flight_deploys() -> u32 {
+    DEFAULT_MAX_IN_FLIGHT_DEPLOYS
 }
 
 #[cfg(test)]
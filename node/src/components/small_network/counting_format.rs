@@ -25,7 +25,7 @@ use tracing::{trace, warn};
 
 use casper_hashing::Digest;
 
-use super::{tls::KeyFingerprint, Message, Metrics, Payload};
+use super::{tls::KeyFingerprint, traffic::PeerTraffic, Message, Metrics, Payload};
 use crate::{types::NodeId, utils};
 
 /// Lazily-evaluated network message ID generator.
@@ -42,9 +42,9 @@ impl Display for TraceId {
 
 /// A metric-updating serializer/deserializer wrapper for network messages.
 ///
-/// Classifies each message given and updates the `NetworkingMetrics` accordingly. Also emits a
-/// TRACE-level message to the `net_out` and `net_in` target with a per-message unique hash when
-/// a message is sent or received.
+/// Classifies each message given and updates the `NetworkingMetrics` and the traffic counters of
+/// the peer accordingly. Also emits a TRACE-level message to the `net_out` and `net_in` target with
+/// a per-message unique hash when a message is sent or received.
 #[pin_project]
 #[derive(Debug)]
 pub struct CountingFormat<F> {
@@ -61,6 +61,8 @@ pub struct CountingFormat<F> {
     role: Role,
     /// Metrics to update.
     metrics: Weak<Metrics>,
+    /// Traffic counters of the peer to update.
+    traffic: Arc<PeerTraffic>,
 }
 
 impl<F> CountingFormat<F> {
@@ -68,12 +70,14 @@ impl<F> CountingFormat<F> {
     #[inline]
     pub(super) fn new(
         metrics: Weak<Metrics>,
+        traffic: Arc<PeerTraffic>,
         connection_id: ConnectionId,
         role: Role,
         inner: F,
     ) -> Self {
         Self {
             metrics,
+            traffic,
             connection_id,
             out_count: 0,
             in_count: 0,
@@ -99,6 +103,7 @@ where
         let msg_size = serialized.len() as u64;
         let msg_kind = item.classify();
         Metrics::record_payload_out(this.metrics, msg_kind, msg_size);
+        this.traffic.record_out(msg_kind, msg_size);
 
         let trace_id = this
             .connection_id
@@ -131,6 +136,7 @@ where
         let deserialized = F::deserialize(projection, src)?;
         let msg_kind = deserialized.classify();
         Metrics::record_payload_in(this.metrics, msg_kind, msg_size);
+        this.traffic.record_in(msg_kind, msg_size);
 
         let trace_id = this
             .connection_id
@@ -0,0 +1,215 @@
+//! Diagnostic dumps of the networking state.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+};
+
+use serde::Serialize;
+
+use casper_types::{EraId, PublicKey, TimeDiff};
+
+use super::{outgoing::OutgoingDump, traffic::KindTraffic};
+use crate::types::{peers_map::PeerReputation, NodeId};
+
+/// A snapshot of the state of the connection manager, for diagnosing connectivity problems.
+#[derive(Debug, Serialize)]
+pub(crate) struct NetworkDump {
+    /// Our node ID.
+    pub(super) our_id: NodeId,
+    /// Our public listening address.
+    pub(super) public_addr: SocketAddr,
+    /// The era considered active by the networking component.
+    pub(super) active_era: EraId,
+    /// Whether we are still announcing ourselves as syncing.
+    pub(super) is_syncing: bool,
+    /// Interval at which outgoing connections are swept.
+    pub(super) sweep_interval: TimeDiff,
+    /// Time since outgoing connections were last swept, if they have been swept yet.
+    pub(super) since_last_sweep: Option<TimeDiff>,
+    /// State of every known outgoing address.
+    pub(super) outgoing: Vec<OutgoingDump>,
+    /// Connection symmetry of every peer with a connection in either direction.
+    pub(super) symmetries: Vec<SymmetryDump>,
+    /// Outgoing addresses which are currently blocked.
+    pub(super) blocked_addrs: Vec<SocketAddr>,
+    /// Peers which announced they are syncing.
+    pub(super) syncing_peers: Vec<NodeId>,
+    /// Peers which announced they are shutting down.
+    pub(super) departing_peers: Vec<NodeId>,
+    /// Reputation of every peer with a score other than zero or recent bans, connected or not.
+    pub(super) reputations: Vec<PeerReputation>,
+    /// Every connected peer.
+    pub(super) peers: PeersDump,
+}
+
+impl NetworkDump {
+    /// Returns only the connected peers.
+    pub(crate) fn into_peers(self) -> PeersDump {
+        self.peers
+    }
+}
+
+/// The connected peers, with their validator status and traffic.
+#[derive(Debug, Serialize)]
+pub(crate) struct PeersDump {
+    pub(super) peers: Vec<PeerDump>,
+}
+
+/// A single connected peer.
+#[derive(Debug, Serialize)]
+pub(crate) struct PeerDump {
+    /// The peer.
+    pub(super) peer_id: NodeId,
+    /// The address of one of the peer's connections.
+    pub(super) addr: String,
+    /// The consensus key proven by the peer's consensus certificate, if it sent one.
+    pub(super) consensus_key: Option<PublicKey>,
+    /// Whether the consensus key belongs to a validator.
+    pub(super) validator_status: ValidatorStatus,
+    /// The peer's reputation score.
+    pub(super) score: i64,
+    /// Messages and bytes exchanged with the peer, by kind of message.
+    pub(super) traffic: BTreeMap<String, KindTraffic>,
+}
+
+/// The validator status of a peer, as established by its consensus certificate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ValidatorStatus {
+    /// The peer did not send a consensus certificate.
+    NoCertificate,
+    /// The peer is a validator in the active era.
+    Active,
+    /// The peer is a validator in the upcoming era only.
+    Upcoming,
+    /// The peer's consensus key is not among the active or upcoming validators.
+    NotValidator,
+}
+
+impl Display for ValidatorStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidatorStatus::NoCertificate => f.write_str("no certificate"),
+            ValidatorStatus::Active => f.write_str("active validator"),
+            ValidatorStatus::Upcoming => f.write_str("upcoming validator"),
+            ValidatorStatus::NotValidator => f.write_str("not a validator"),
+        }
+    }
+}
+
+impl Display for PeerDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}, {}, score {}",
+            self.peer_id, self.addr, self.validator_status, self.score
+        )?;
+        if let Some(ref consensus_key) = self.consensus_key {
+            write!(f, " ({})", consensus_key)?;
+        }
+        for (kind, traffic) in &self.traffic {
+            write!(f, "\n    {}: {}", kind, traffic)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for PeersDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "peers ({}):", self.peers.len())?;
+        for peer in &self.peers {
+            writeln!(f, "  {}", peer)?;
+        }
+        Ok(())
+    }
+}
+
+/// The connection symmetry of a single peer.
+#[derive(Debug, Serialize)]
+pub(crate) struct SymmetryDump {
+    /// The peer.
+    pub(super) peer_id: NodeId,
+    /// The symmetry state, e.g. `incoming_only`.
+    pub(super) state: &'static str,
+    /// Time the connection has been in its current state, if tracked.
+    pub(super) in_state_for: Option<TimeDiff>,
+    /// Addresses the peer is connected to us from.
+    pub(super) incoming_addrs: Vec<SocketAddr>,
+}
+
+impl Display for SymmetryDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.peer_id, self.state)?;
+        if let Some(in_state_for) = self.in_state_for {
+            write!(f, " for {}", in_state_for)?;
+        }
+        if !self.incoming_addrs.is_empty() {
+            write!(f, ", incoming from")?;
+            for addr in &self.incoming_addrs {
+                write!(f, " {}", addr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for NetworkDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "our id: {}, public address: {}",
+            self.our_id, self.public_addr
+        )?;
+        writeln!(
+            f,
+            "active era: {}, syncing: {}",
+            self.active_era, self.is_syncing
+        )?;
+        match self.since_last_sweep {
+            Some(since_last_sweep) => writeln!(
+                f,
+                "outgoing swept {} ago, every {}",
+                since_last_sweep, self.sweep_interval
+            )?,
+            None => writeln!(f, "outgoing not swept yet, every {}", self.sweep_interval)?,
+        }
+
+        writeln!(f, "outgoing ({}):", self.outgoing.len())?;
+        for outgoing in &self.outgoing {
+            writeln!(f, "  {}", outgoing)?;
+        }
+        writeln!(f, "symmetries ({}):", self.symmetries.len())?;
+        for symmetry in &self.symmetries {
+            writeln!(f, "  {}", symmetry)?;
+        }
+        writeln!(f, "blocked addresses ({}):", self.blocked_addrs.len())?;
+        for addr in &self.blocked_addrs {
+            writeln!(f, "  {}", addr)?;
+        }
+        writeln!(f, "syncing peers ({}):", self.syncing_peers.len())?;
+        for peer_id in &self.syncing_peers {
+            writeln!(f, "  {}", peer_id)?;
+        }
+        writeln!(f, "departing peers ({}):", self.departing_peers.len())?;
+        for peer_id in &self.departing_peers {
+            writeln!(f, "  {}", peer_id)?;
+        }
+        writeln!(f, "reputations ({}):", self.reputations.len())?;
+        for reputation in &self.reputations {
+            write!(f, "  {} score {}", reputation.node_id, reputation.score)?;
+            if let Some(banned_for) = reputation.banned_for {
+                write!(f, ", banned for {}", banned_for)?;
+            }
+            if reputation.bans > 0 {
+                write!(f, ", {} recent bans", reputation.bans)?;
+            }
+            if let Some(ref last_penalty) = reputation.last_penalty {
+                write!(f, ", last penalty: {}", last_penalty)?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "{}", self.peers)
+    }
+}
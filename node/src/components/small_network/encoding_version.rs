@@ -0,0 +1,173 @@
+//! Versioning of the human-readable encodings of keys and signatures exchanged during handshakes.
+//!
+//! Handshakes are encoded using a human-readable MessagePack encoder, so the human-readable forms
+//! of `PublicKey` and `Signature` are part of the networking protocol.  Checksummed-hex encoding,
+//! introduced in 1.4.2 and changed again in 1.4.3, broke handshaking between these versions, which
+//! was worked around by always encoding the consensus certificate as all-lowercase hex.
+//!
+//! Rather than adding another hand-written serde shim for every future change to these encodings,
+//! each encoding is identified by an [`EncodingVersion`].  Nodes advertise the versions they
+//! support in their handshake and agree per connection on the highest version both support, see
+//! [`EncodingVersion::negotiate`].  Since the handshake itself is sent before any negotiation has
+//! taken place, it is always encoded using [`EncodingVersion::HANDSHAKE`], while decoding accepts
+//! any supported version.
+
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
+
+use datasize::DataSize;
+
+use casper_types::{crypto, AsymmetricType, PublicKey, Signature};
+
+/// A version of the human-readable encoding of `PublicKey` and `Signature`.
+#[derive(Clone, Copy, DataSize, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[repr(u8)]
+pub(crate) enum EncodingVersion {
+    /// All-lowercase hex, i.e. without checksummed-hex encoding.  Decoding is case-insensitive.
+    V1 = 1,
+}
+
+impl EncodingVersion {
+    /// All versions supported by this node, in ascending order.
+    pub(super) const SUPPORTED: [EncodingVersion; 1] = [EncodingVersion::V1];
+
+    /// The version used for encoding handshakes and assumed for peers which don't advertise any
+    /// supported versions, i.e. peers running versions prior to the introduction of negotiation.
+    pub(super) const HANDSHAKE: EncodingVersion = EncodingVersion::V1;
+
+    /// Returns the IDs of all supported versions, as advertised in our handshake.
+    pub(super) fn supported_ids() -> Vec<u8> {
+        Self::SUPPORTED
+            .iter()
+            .map(|version| *version as u8)
+            .collect()
+    }
+
+    /// Returns the highest version supported by both us and a peer advertising the given IDs.
+    ///
+    /// IDs unknown to us are ignored, and [`EncodingVersion::HANDSHAKE`] is returned if there is no
+    /// common version.
+    pub(super) fn negotiate(peer_ids: &[u8]) -> Self {
+        Self::SUPPORTED
+            .iter()
+            .rev()
+            .find(|version| peer_ids.contains(&(**version as u8)))
+            .copied()
+            .unwrap_or(Self::HANDSHAKE)
+    }
+
+    /// Encodes a public key.
+    pub(super) fn encode_public_key(self, public_key: &PublicKey) -> String {
+        match self {
+            EncodingVersion::V1 => public_key.to_hex().to_lowercase(),
+        }
+    }
+
+    /// Encodes a signature.
+    pub(super) fn encode_signature(self, signature: &Signature) -> String {
+        match self {
+            EncodingVersion::V1 => signature.to_hex().to_lowercase(),
+        }
+    }
+
+    /// Decodes a public key which was encoded using any supported version.
+    pub(super) fn decode_public_key(encoded: &str) -> Result<PublicKey, crypto::Error> {
+        Self::decode_any(encoded, |version, encoded| match version {
+            EncodingVersion::V1 => PublicKey::from_hex(encoded.to_lowercase()),
+        })
+    }
+
+    /// Decodes a signature which was encoded using any supported version.
+    pub(super) fn decode_signature(encoded: &str) -> Result<Signature, crypto::Error> {
+        Self::decode_any(encoded, |version, encoded| match version {
+            EncodingVersion::V1 => Signature::from_hex(encoded.to_lowercase()),
+        })
+    }
+
+    /// Tries decoding using the handshake version, then each other supported version in turn,
+    /// returning the first success or otherwise the last error.
+    fn decode_any<T, F>(encoded: &str, decode: F) -> Result<T, crypto::Error>
+    where
+        F: Fn(EncodingVersion, &str) -> Result<T, crypto::Error>,
+    {
+        Self::SUPPORTED
+            .iter()
+            .filter(|version| **version != Self::HANDSHAKE)
+            .fold(decode(Self::HANDSHAKE, encoded), |result, version| {
+                result.or_else(|_| decode(*version, encoded))
+            })
+    }
+}
+
+impl TryFrom<u8> for EncodingVersion {
+    type Error = u8;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        Self::SUPPORTED
+            .iter()
+            .find(|version| **version as u8 == id)
+            .copied()
+            .ok_or(id)
+    }
+}
+
+impl Display for EncodingVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", *self as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{testing::TestRng, SecretKey};
+
+    use super::*;
+
+    #[test]
+    fn should_negotiate_highest_common_version() {
+        assert_eq!(
+            EncodingVersion::negotiate(&EncodingVersion::supported_ids()),
+            *EncodingVersion::SUPPORTED.last().unwrap()
+        );
+        assert_eq!(
+            EncodingVersion::negotiate(&[EncodingVersion::V1 as u8, u8::MAX]),
+            EncodingVersion::V1
+        );
+        // Legacy peers don't advertise any versions.
+        assert_eq!(EncodingVersion::negotiate(&[]), EncodingVersion::HANDSHAKE);
+        assert_eq!(EncodingVersion::try_from(u8::MAX), Err(u8::MAX));
+    }
+
+    #[test]
+    fn should_roundtrip_all_supported_versions() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let public_key = PublicKey::from(&secret_key);
+        let signature = crypto::sign(b"connection id", &secret_key, &public_key);
+
+        for version in EncodingVersion::SUPPORTED.iter() {
+            let encoded_key = version.encode_public_key(&public_key);
+            let encoded_signature = version.encode_signature(&signature);
+            assert_eq!(
+                EncodingVersion::decode_public_key(&encoded_key).unwrap(),
+                public_key
+            );
+            assert_eq!(
+                EncodingVersion::decode_signature(&encoded_signature).unwrap(),
+                signature
+            );
+        }
+    }
+
+    #[test]
+    fn should_decode_checksummed_hex() {
+        // As sent by a 1.4.2 node.
+        let checksummed = "020283c0D687933Eb20a541C8540478877861edE4AFfAf04a68ea194B7A40046424e";
+        assert_eq!(
+            EncodingVersion::decode_public_key(checksummed).unwrap(),
+            PublicKey::from_hex(checksummed.to_lowercase()).unwrap()
+        );
+    }
+}
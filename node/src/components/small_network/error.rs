@@ -1,15 +1,17 @@
-use std::{error, io, net::SocketAddr, result, sync::Arc};
+use std::{error, io, net::SocketAddr, result};
 
 use casper_hashing::Digest;
-use casper_types::{crypto, ProtocolVersion, SecretKey};
+use casper_types::{crypto, ProtocolVersion};
 use datasize::DataSize;
 use openssl::{error::ErrorStack, ssl};
 use serde::Serialize;
 use thiserror::Error;
 
+use super::network_key::LoadEndorsementError;
 use crate::{
+    components::consensus::LoadKeysError,
     tls::{LoadCertError, ValidationError},
-    utils::{LoadError, Loadable, ResolveAddressError},
+    utils::ResolveAddressError,
 };
 
 pub(super) type Result<T> = result::Result<T, Error>;
@@ -25,8 +27,32 @@ pub enum Error {
     LoadConsensusKeys(
         #[serde(skip_serializing)]
         #[source]
-        LoadError<<Arc<SecretKey> as Loadable>::Error>,
+        LoadKeysError,
     ),
+    /// A network key was configured, but could not be loaded.
+    #[error("network key provided, but could not be loaded: {0}")]
+    LoadNetworkKey(
+        #[serde(skip_serializing)]
+        #[source]
+        crypto::ErrorExt,
+    ),
+    /// A network key was configured, but its endorsement could not be loaded.
+    #[error("network key endorsement could not be loaded: {0}")]
+    LoadNetworkKeyEndorsement(
+        #[serde(skip_serializing)]
+        #[source]
+        LoadEndorsementError,
+    ),
+    /// The network key endorsement is not a valid signature by the validator key.
+    #[error("invalid network key endorsement: {0}")]
+    InvalidNetworkKeyEndorsement(
+        #[serde(skip_serializing)]
+        #[source]
+        crypto::Error,
+    ),
+    /// The network key endorsement is for a different network key.
+    #[error("network key endorsement does not match the configured network key")]
+    NetworkKeyNotEndorsed,
     /// Failed to create a TCP listener.
     #[error("failed to create listener on {1}")]
     ListenerCreation(
@@ -205,6 +231,13 @@ pub enum ConnectionError {
         #[source]
         crypto::Error,
     ),
+    /// The peer sent a network certificate, but it was invalid.
+    #[error("invalid network certificate")]
+    InvalidNetworkCertificate(
+        #[serde(skip_serializing)]
+        #[source]
+        crypto::Error,
+    ),
     /// Failed to reunite handshake sink/stream.
     ///
     /// This is usually a bug.
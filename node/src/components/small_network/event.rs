@@ -12,12 +12,17 @@ use serde::Serialize;
 use static_assertions::const_assert;
 use tracing::Span;
 
-use super::{error::ConnectionError, FullTransport, GossipedAddress, Message, NodeId};
+use super::{
+    admission::AdmissionPermit, error::ConnectionError, peer_store::PeerRecord, FullTransport,
+    GossipedAddress, Message, NodeId,
+};
 use crate::{
     effect::{
         announcements::{
-            BlocklistAnnouncement, ChainSynchronizerAnnouncement, ContractRuntimeAnnouncement,
+            BlocklistAnnouncement, ChainSynchronizerAnnouncement, ChainspecLoaderAnnouncement,
+            ContractRuntimeAnnouncement,
         },
+        diagnostics_port::DumpNetworkStateRequest,
         requests::{NetworkInfoRequest, NetworkRequest},
     },
     protocol::Message as ProtocolMessage,
@@ -90,6 +95,16 @@ pub(crate) enum Event<P> {
     /// Housekeeping for the outgoing manager.
     SweepOutgoing,
 
+    /// The peer records persisted before a restart were loaded.
+    PeerStoreLoaded(#[serde(skip_serializing)] Vec<PeerRecord>),
+
+    /// The peer records should be persisted, if they changed.
+    PersistPeerStore,
+
+    /// Diagnostics port request to dump the connection manager state.
+    #[from]
+    DumpState(#[serde(skip_serializing)] DumpNetworkStateRequest),
+
     /// Blocklist announcement.
     #[from]
     BlocklistAnnouncement(BlocklistAnnouncement),
@@ -101,6 +116,10 @@ pub(crate) enum Event<P> {
     /// Chain synchronizer announcement.
     #[from]
     ChainSynchronizerAnnouncement(ChainSynchronizerAnnouncement),
+
+    /// Chainspec loader announcement.
+    #[from]
+    ChainspecLoaderAnnouncement(ChainspecLoaderAnnouncement),
 }
 
 impl From<NetworkRequest<ProtocolMessage>> for Event<ProtocolMessage> {
@@ -150,9 +169,17 @@ impl<P: Display> Display for Event<P> {
             Event::SweepOutgoing => {
                 write!(f, "sweep outgoing connections")
             }
+            Event::PeerStoreLoaded(records) => {
+                write!(f, "loaded {} peer records", records.len())
+            }
+            Event::PersistPeerStore => write!(f, "persist peer store"),
+            Event::DumpState(req) => write!(f, "{}", req),
             Event::ChainSynchronizerAnnouncement(ann) => {
                 write!(f, "handling chain synchronizer announcement: {}", ann)
             }
+            Event::ChainspecLoaderAnnouncement(ann) => {
+                write!(f, "handling chainspec loader announcement: {}", ann)
+            }
         }
     }
 }
@@ -191,6 +218,9 @@ pub(crate) enum IncomingConnection<P> {
         /// Stream of incoming messages. for incoming connections.
         #[serde(skip_serializing)]
         stream: SplitStream<FullTransport<P>>,
+        /// Permit counting the connection towards the per-address limits while it is open.
+        #[serde(skip_serializing)]
+        permit: AdmissionPermit,
     },
 }
 
@@ -212,6 +242,7 @@ impl<P> Display for IncomingConnection<P> {
                 peer_id,
                 peer_consensus_public_key,
                 stream: _,
+                permit: _,
             } => {
                 write!(
                     f,
@@ -263,6 +294,8 @@ pub(crate) enum OutgoingConnection<P> {
         sink: SplitSink<FullTransport<P>, Arc<Message<P>>>,
         /// Holds the information whether the remote node is syncing.
         is_syncing: bool,
+        /// Holds the information whether the remote node understands batched gossip messages.
+        supports_batched_gossip: bool,
     },
 }
 
@@ -284,6 +317,7 @@ impl<P> Display for OutgoingConnection<P> {
                 peer_consensus_public_key,
                 sink: _,
                 is_syncing,
+                supports_batched_gossip: _,
             } => {
                 write!(
                     f,
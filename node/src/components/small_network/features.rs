@@ -0,0 +1,131 @@
+//! Optional wire features negotiated during handshakes.
+//!
+//! Every optional feature of the networking protocol, such as a new payload variant or a change to
+//! the framing of messages, is assigned a bit in a [`Features`] bitmap.  Nodes advertise the
+//! features they support in their handshake, and a feature is only used on a connection if both
+//! ends advertised it, see [`Features::negotiate`].  Rolling out a new wire feature, e.g. chunked
+//! transfers of large payloads, thus only requires assigning it the next free bit, rather than
+//! adding another dedicated handshake field.
+//!
+//! Bits unknown to a node are ignored by it, so they never survive negotiation.  Peers running
+//! versions which predate the bitmap advertise no features.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::BitOr,
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+/// A set of optional wire features.
+#[derive(Clone, Copy, DataSize, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub(crate) struct Features(u64);
+
+impl Features {
+    /// The empty set of features.
+    pub(super) const NONE: Features = Features(0);
+
+    /// The node understands batched gossip messages, e.g. `Message::FinalitySignatures`.
+    pub(super) const BATCHED_GOSSIP: Features = Features(1 << 0);
+
+    /// The node accepts compressed payloads, see `compression`.
+    pub(super) const COMPRESSION: Features = Features(1 << 1);
+
+    /// All features known to this node, along with their names.
+    const KNOWN: [(Features, &'static str); 2] = [
+        (Features::BATCHED_GOSSIP, "batched_gossip"),
+        (Features::COMPRESSION, "compression"),
+    ];
+
+    /// Returns the features supported by this node, as advertised in its handshakes.
+    ///
+    /// Compression is only supported if enabled in the config.
+    pub(super) fn supported(enable_compression: bool) -> Self {
+        let compression = if enable_compression {
+            Features::COMPRESSION
+        } else {
+            Features::NONE
+        };
+        Features::BATCHED_GOSSIP | compression
+    }
+
+    /// Returns `true` if all features in `other` are contained in `self`.
+    pub(super) fn contains(self, other: Features) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the features to use on a connection between us, supporting `self`, and a peer
+    /// advertising `peer_features`, i.e. the features known to and supported by both.
+    pub(super) fn negotiate(self, peer_features: Features) -> Self {
+        Features(self.0 & peer_features.0)
+    }
+}
+
+impl BitOr for Features {
+    type Output = Features;
+
+    fn bitor(self, rhs: Features) -> Self::Output {
+        Features(self.0 | rhs.0)
+    }
+}
+
+impl Display for Features {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if *self == Features::NONE {
+            return write!(f, "none");
+        }
+        let mut names: Vec<String> = Features::KNOWN
+            .iter()
+            .filter(|(feature, _)| self.contains(*feature))
+            .map(|(_, name)| name.to_string())
+            .collect();
+        let known = Features::KNOWN
+            .iter()
+            .fold(Features::NONE, |known, (feature, _)| known | *feature);
+        let unknown = self.0 & !known.0;
+        if unknown != 0 {
+            names.push(format!("unknown({:#x})", unknown));
+        }
+        write!(f, "{}", names.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_negotiate_common_features() {
+        let ours = Features::supported(true);
+        assert!(ours.contains(Features::BATCHED_GOSSIP));
+        assert!(ours.contains(Features::COMPRESSION));
+
+        let peer = Features::BATCHED_GOSSIP | Features(1 << 63);
+        let negotiated = ours.negotiate(peer);
+        assert_eq!(negotiated, Features::BATCHED_GOSSIP);
+        assert!(!negotiated.contains(Features::COMPRESSION));
+
+        // Compression is only used if enabled on both ends.
+        let negotiated = Features::supported(false).negotiate(Features::supported(true));
+        assert!(!negotiated.contains(Features::COMPRESSION));
+        assert_eq!(
+            Features::supported(true).negotiate(Features::NONE),
+            Features::NONE
+        );
+    }
+
+    #[test]
+    fn should_display_features() {
+        assert_eq!(Features::NONE.to_string(), "none");
+        assert_eq!(
+            Features::supported(true).to_string(),
+            "batched_gossip,compression"
+        );
+        assert_eq!(
+            (Features::COMPRESSION | Features(1 << 8)).to_string(),
+            "compression,unknown(0x100)"
+        );
+    }
+}
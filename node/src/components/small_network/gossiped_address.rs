@@ -1,12 +1,18 @@
 use std::{
     convert::Infallible,
     fmt::{self, Display, Formatter},
-    net::SocketAddr,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
 };
 
 use datasize::DataSize;
+#[cfg(any(feature = "testing", test))]
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+use casper_types::bytesrepr::{self, FromBytes, ToBytes};
+#[cfg(any(feature = "testing", test))]
+use casper_types::testing::TestRng;
+
 use crate::types::{Item, Tag};
 
 /// Used to gossip our public listening address to peers.
@@ -15,10 +21,71 @@ use crate::types::{Item, Tag};
 )]
 pub struct GossipedAddress(SocketAddr);
 
+/// Tag of an IPv4 address in the bytesrepr encoding.
+const IPV4_TAG: u8 = 0;
+/// Tag of an IPv6 address in the bytesrepr encoding.
+const IPV6_TAG: u8 = 1;
+
 impl GossipedAddress {
     pub(super) fn new(address: SocketAddr) -> Self {
         GossipedAddress(address)
     }
+
+    /// Returns a random `GossipedAddress`.
+    #[cfg(any(feature = "testing", test))]
+    pub(crate) fn random(rng: &mut TestRng) -> Self {
+        let ip = if rng.gen() {
+            IpAddr::V4(Ipv4Addr::from(rng.gen::<[u8; 4]>()))
+        } else {
+            IpAddr::V6(Ipv6Addr::from(rng.gen::<[u8; 16]>()))
+        };
+        GossipedAddress(SocketAddr::new(ip, rng.gen()))
+    }
+}
+
+impl ToBytes for GossipedAddress {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        match self.0.ip() {
+            IpAddr::V4(ip) => {
+                buffer.push(IPV4_TAG);
+                buffer.extend(ip.octets().to_bytes()?);
+            }
+            IpAddr::V6(ip) => {
+                buffer.push(IPV6_TAG);
+                buffer.extend(ip.octets().to_bytes()?);
+            }
+        }
+        buffer.extend(self.0.port().to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        let ip_length = match self.0.ip() {
+            IpAddr::V4(ip) => ip.octets().serialized_length(),
+            IpAddr::V6(ip) => ip.octets().serialized_length(),
+        };
+        bytesrepr::U8_SERIALIZED_LENGTH + ip_length + self.0.port().serialized_length()
+    }
+}
+
+impl FromBytes for GossipedAddress {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, bytes) = u8::from_bytes(bytes)?;
+        let (ip, bytes) = match tag {
+            IPV4_TAG => {
+                let (octets, bytes) = <[u8; 4]>::from_bytes(bytes)?;
+                (IpAddr::V4(Ipv4Addr::from(octets)), bytes)
+            }
+            IPV6_TAG => {
+                let (octets, bytes) = <[u8; 16]>::from_bytes(bytes)?;
+                (IpAddr::V6(Ipv6Addr::from(octets)), bytes)
+            }
+            _ => return Err(bytesrepr::Error::Formatting),
+        };
+        let (port, bytes) = u16::from_bytes(bytes)?;
+        Ok((GossipedAddress(SocketAddr::new(ip, port)), bytes))
+    }
 }
 
 impl Display for GossipedAddress {
@@ -7,7 +7,7 @@ use std::{
 use casper_hashing::Digest;
 #[cfg(test)]
 use casper_types::testing::TestRng;
-use casper_types::{crypto, AsymmetricType, ProtocolVersion, PublicKey, SecretKey, Signature};
+use casper_types::{crypto, ProtocolVersion, PublicKey, SecretKey, Signature};
 use datasize::DataSize;
 use futures::future::BoxFuture;
 use serde::{
@@ -17,7 +17,10 @@ use serde::{
 
 use crate::{effect::EffectBuilder, types::NodeId, utils::opt_display::OptDisplay};
 
-use super::counting_format::ConnectionId;
+use super::{
+    counting_format::ConnectionId, encoding_version::EncodingVersion, features::Features,
+    network_key::NetworkKeyEndorsement,
+};
 
 /// The default protocol version to use in absence of one in the protocol version field.
 #[inline]
@@ -45,8 +48,22 @@ pub(crate) enum Message<P> {
         /// Hash of the chainspec the node is running.
         #[serde(default)]
         chainspec_hash: Option<Digest>,
+        /// The protocol version of an upgrade the node has staged and is ready to activate.
+        #[serde(default)]
+        staged_protocol_version: Option<ProtocolVersion>,
+        /// A certificate indicating validator status via a network key endorsed by the validator.
+        #[serde(default)]
+        network_certificate: Option<NetworkCertificate>,
+        /// IDs of the human-readable encoding versions the node supports, see `EncodingVersion`.
+        #[serde(default)]
+        encoding_versions: Vec<u8>,
+        /// The optional wire features the node supports, see `Features`.
+        #[serde(default)]
+        features: Features,
     },
     Payload(P),
+    /// The sender is shutting down and should no longer be relied upon.
+    GoingAway,
 }
 
 impl<P: Payload> Message<P> {
@@ -54,7 +71,7 @@ impl<P: Payload> Message<P> {
     #[inline]
     pub(super) fn classify(&self) -> MessageKind {
         match self {
-            Message::Handshake { .. } => MessageKind::Protocol,
+            Message::Handshake { .. } | Message::GoingAway => MessageKind::Protocol,
             Message::Payload(payload) => payload.classify(),
         }
     }
@@ -63,16 +80,25 @@ impl<P: Payload> Message<P> {
     #[inline]
     pub(super) fn is_low_priority(&self) -> bool {
         match self {
-            Message::Handshake { .. } => false,
+            Message::Handshake { .. } | Message::GoingAway => false,
             Message::Payload(payload) => payload.is_low_priority(),
         }
     }
 
+    /// Determines whether or not a message is high priority.
+    #[inline]
+    pub(super) fn is_high_priority(&self) -> bool {
+        match self {
+            Message::Handshake { .. } | Message::GoingAway => false,
+            Message::Payload(payload) => payload.is_high_priority(),
+        }
+    }
+
     /// Returns the incoming resource estimate of the payload.
     #[inline]
     pub(super) fn payload_incoming_resource_estimate(&self, weights: &EstimatorWeights) -> u32 {
         match self {
-            Message::Handshake { .. } => 0,
+            Message::Handshake { .. } | Message::GoingAway => 0,
             Message::Payload(payload) => payload.incoming_resource_estimate(weights),
         }
     }
@@ -81,11 +107,20 @@ impl<P: Payload> Message<P> {
     #[inline]
     pub(super) fn payload_is_unsafe_for_syncing_nodes(&self) -> bool {
         match self {
-            Message::Handshake { .. } => false,
+            Message::Handshake { .. } | Message::GoingAway => false,
             Message::Payload(payload) => payload.is_unsafe_for_syncing_peers(),
         }
     }
 
+    /// Returns whether or not the payload can only be sent to peers supporting batched gossip.
+    #[inline]
+    pub(super) fn payload_requires_batched_gossip_support(&self) -> bool {
+        match self {
+            Message::Handshake { .. } | Message::GoingAway => false,
+            Message::Payload(payload) => payload.requires_batched_gossip_support(),
+        }
+    }
+
     /// Attempts to create a demand-event from this message.
     ///
     /// Succeeds if the outer message contains a payload that can be converd into a demand.
@@ -98,7 +133,7 @@ impl<P: Payload> Message<P> {
         REv: FromIncoming<P> + Send,
     {
         match self {
-            Message::Handshake { .. } => Err(self),
+            Message::Handshake { .. } | Message::GoingAway => Err(self),
             Message::Payload(payload) => {
                 // Note: For now, the wrapping/unwrapp of the payload is a bit unfortunate here.
                 REv::try_demand_from_incoming(effect_builder, sender, payload)
@@ -106,6 +141,26 @@ impl<P: Payload> Message<P> {
             }
         }
     }
+
+    /// Attempts to create an acknowledged event from this message.
+    ///
+    /// Succeeds if the outer message contains a payload that is subject to flow control.
+    pub(super) fn try_into_acknowledged<REv>(
+        self,
+        effect_builder: EffectBuilder<REv>,
+        sender: NodeId,
+    ) -> Result<(REv, BoxFuture<'static, ()>), Self>
+    where
+        REv: FromIncoming<P> + Send,
+    {
+        match self {
+            Message::Handshake { .. } | Message::GoingAway => Err(self),
+            Message::Payload(payload) => {
+                REv::try_acknowledged_from_incoming(effect_builder, sender, payload)
+                    .map_err(Message::Payload)
+            }
+        }
+    }
 }
 
 /// A pair of secret keys used by consensus.
@@ -131,9 +186,10 @@ impl ConsensusKeyPair {
 
 /// Certificate used to indicate that the peer is a validator using the specified public key.
 ///
-/// Note that this type has custom `Serialize` and `Deserialize` implementations to allow the
-/// `public_key` and `signature` fields to be encoded to all-lowercase hex, hence circumventing the
-/// checksummed-hex encoding used by `PublicKey` and `Signature` in versions 1.4.2 and 1.4.3.
+/// Note that this type has custom `Serialize` and `Deserialize` implementations which encode the
+/// `public_key` and `signature` fields using `EncodingVersion::HANDSHAKE` in the human-readable
+/// case, hence circumventing the checksummed-hex encoding used by `PublicKey` and `Signature` in
+/// versions 1.4.2 and 1.4.3.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) struct ConsensusCertificate {
     public_key: PublicKey,
@@ -174,6 +230,88 @@ impl Display for ConsensusCertificate {
     }
 }
 
+/// A network key and its endorsement by the validator key, used instead of the validator key to
+/// sign handshakes.
+pub(super) struct NetworkKeyPair {
+    secret_key: Arc<SecretKey>,
+    endorsement: NetworkKeyEndorsement,
+}
+
+impl NetworkKeyPair {
+    /// Creates a new network key pair.
+    ///
+    /// The endorsement is expected to have been checked to be valid and for the given key.
+    pub(super) fn new(secret_key: Arc<SecretKey>, endorsement: NetworkKeyEndorsement) -> Self {
+        Self {
+            secret_key,
+            endorsement,
+        }
+    }
+
+    /// Sign a value using the network key.
+    fn sign<T: AsRef<[u8]>>(&self, value: T) -> Signature {
+        crypto::sign(
+            value,
+            &self.secret_key,
+            &self.endorsement.network_public_key,
+        )
+    }
+}
+
+/// Certificate used to indicate that the peer is a validator, by presenting a network key endorsed
+/// by the validator key along with the network key's signature over the connection ID.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct NetworkCertificate {
+    endorsement: NetworkKeyEndorsement,
+    signature: Signature,
+}
+
+impl NetworkCertificate {
+    /// Creates a new network certificate from a connection ID and network key pair.
+    pub(super) fn create(connection_id: ConnectionId, key_pair: &NetworkKeyPair) -> Self {
+        NetworkCertificate {
+            endorsement: key_pair.endorsement.clone(),
+            signature: key_pair.sign(connection_id.as_bytes()),
+        }
+    }
+
+    /// Creates a random `NetworkCertificate`.
+    #[cfg(test)]
+    fn random(rng: &mut TestRng) -> Self {
+        let validator_secret_key = SecretKey::random(rng);
+        let network_secret_key = SecretKey::random(rng);
+        let endorsement = NetworkKeyEndorsement::create(
+            PublicKey::from(&network_secret_key),
+            &validator_secret_key,
+        );
+        NetworkCertificate::create(
+            ConnectionId::random(rng),
+            &NetworkKeyPair::new(Arc::new(network_secret_key), endorsement),
+        )
+    }
+
+    /// Validates a certificate, returning the validator's `PublicKey` if valid.
+    pub(super) fn validate(self, connection_id: ConnectionId) -> Result<PublicKey, crypto::Error> {
+        self.endorsement.verify()?;
+        crypto::verify(
+            connection_id.as_bytes(),
+            &self.signature,
+            &self.endorsement.network_public_key,
+        )?;
+        Ok(self.endorsement.validator_public_key)
+    }
+}
+
+impl Display for NetworkCertificate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "key:{}, network key:{}",
+            self.endorsement.validator_public_key, self.endorsement.network_public_key
+        )
+    }
+}
+
 /// This type and the `NonHumanReadableCertificate` are helper structs only used in the `Serialize`
 /// and `Deserialize` implementations of `ConsensusCertificate` to allow handshaking between nodes
 /// running the casper-node v1.4.2 and v1.4.3 software versions.
@@ -187,8 +325,9 @@ impl Display for ConsensusCertificate {
 /// encoding, we need to use an all-lowercase form of hex encoding for the `PublicKey` and
 /// `Signature` types.
 ///
-/// The `HumanReadableCertificate` enables that by explicitly being constructed from all-lowercase
-/// hex encoded types, while the `NonHumanReadableCertificate` is a simple mirror of
+/// The `HumanReadableCertificate` enables that by holding the fields encoded via
+/// `EncodingVersion`, so that further changes to these encodings only require a new version rather
+/// than another shim, while the `NonHumanReadableCertificate` is a simple mirror of
 /// `ConsensusCertificate` to allow us to derive `Serialize` and `Deserialize`, avoiding complex
 /// hand-written implementations for the non-human-readable case.
 #[derive(Serialize, Deserialize)]
@@ -207,8 +346,8 @@ impl Serialize for ConsensusCertificate {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         if serializer.is_human_readable() {
             let human_readable_certificate = HumanReadableCertificate {
-                public_key: self.public_key.to_hex().to_lowercase(),
-                signature: self.signature.to_hex().to_lowercase(),
+                public_key: EncodingVersion::HANDSHAKE.encode_public_key(&self.public_key),
+                signature: EncodingVersion::HANDSHAKE.encode_signature(&self.signature),
             };
 
             return human_readable_certificate.serialize(serializer);
@@ -226,20 +365,12 @@ impl<'de> Deserialize<'de> for ConsensusCertificate {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         if deserializer.is_human_readable() {
             let human_readable_certificate = HumanReadableCertificate::deserialize(deserializer)?;
-            let public_key = PublicKey::from_hex(
-                human_readable_certificate
-                    .public_key
-                    .to_lowercase()
-                    .as_bytes(),
-            )
-            .map_err(D::Error::custom)?;
-            let signature = Signature::from_hex(
-                human_readable_certificate
-                    .signature
-                    .to_lowercase()
-                    .as_bytes(),
-            )
-            .map_err(D::Error::custom)?;
+            let public_key =
+                EncodingVersion::decode_public_key(&human_readable_certificate.public_key)
+                    .map_err(D::Error::custom)?;
+            let signature =
+                EncodingVersion::decode_signature(&human_readable_certificate.signature)
+                    .map_err(D::Error::custom)?;
             return Ok(ConsensusCertificate {
                 public_key,
                 signature,
@@ -265,26 +396,35 @@ impl<P: Display> Display for Message<P> {
                 consensus_certificate,
                 is_syncing,
                 chainspec_hash,
+                staged_protocol_version,
+                network_certificate,
+                encoding_versions,
+                features,
             } => {
                 write!(
                     f,
-                    "handshake: {}, public addr: {}, protocol_version: {}, consensus_certificate: {}, is_syncing: {}, chainspec_hash: {}",
+                    "handshake: {}, public addr: {}, protocol_version: {}, consensus_certificate: {}, is_syncing: {}, chainspec_hash: {}, staged_protocol_version: {}, network_certificate: {}, encoding_versions: {:?}, features: {}",
                     network_name,
                     public_addr,
                     protocol_version,
                     OptDisplay::new(consensus_certificate.as_ref(), "none"),
                     is_syncing,
-                    OptDisplay::new(chainspec_hash.as_ref(), "none")
+                    OptDisplay::new(chainspec_hash.as_ref(), "none"),
+                    OptDisplay::new(staged_protocol_version.as_ref(), "none"),
+                    OptDisplay::new(network_certificate.as_ref(), "none"),
+                    encoding_versions,
+                    features
                 )
             }
             Message::Payload(payload) => write!(f, "payload: {}", payload),
+            Message::GoingAway => write!(f, "going away"),
         }
     }
 }
 
 /// A classification system for networking messages.
-#[derive(Copy, Clone, Debug)]
-pub(crate) enum MessageKind {
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MessageKind {
     /// Non-payload messages, like handshakes.
     Protocol,
     /// Messages directly related to consensus.
@@ -339,10 +479,22 @@ pub(crate) trait Payload:
         false
     }
 
+    /// Determines if the payload should be considered high priority, i.e. handled ahead of other
+    /// incoming messages.
+    fn is_high_priority(&self) -> bool {
+        false
+    }
+
     /// Indicates a message is not safe to send to a syncing node.
     ///
     /// This functionality should be removed once multiplexed networking lands.
     fn is_unsafe_for_syncing_peers(&self) -> bool;
+
+    /// Indicates a message can only be understood by peers which announced support for batched
+    /// gossip during the handshake.
+    fn requires_batched_gossip_support(&self) -> bool {
+        false
+    }
 }
 
 /// Network message conversion support.
@@ -367,6 +519,22 @@ pub(crate) trait FromIncoming<P> {
     {
         Err(payload)
     }
+
+    /// Tries to convert a payload into an event whose processing is acknowledged.
+    ///
+    /// On success, the returned future completes once the event has been fully processed. Until
+    /// then, the event counts towards the sending peer's allowance of in-flight messages, and no
+    /// further messages are read from the peer while that allowance is exhausted.
+    fn try_acknowledged_from_incoming(
+        _effect_builder: EffectBuilder<Self>,
+        _sender: NodeId,
+        payload: P,
+    ) -> Result<(Self, BoxFuture<'static, ()>), P>
+    where
+        Self: Sized + Send,
+    {
+        Err(payload)
+    }
 }
 /// A generic configuration for payload weights.
 ///
@@ -406,7 +574,7 @@ mod tests {
     use std::{net::SocketAddr, pin::Pin};
 
     use bytes::BytesMut;
-    use casper_types::ProtocolVersion;
+    use casper_types::{AsymmetricType, ProtocolVersion};
     use serde::{de::DeserializeOwned, Deserialize, Serialize};
     use tokio_serde::{Deserializer, Serializer};
 
@@ -562,6 +730,10 @@ mod tests {
             consensus_certificate: Some(ConsensusCertificate::random(&mut rng)),
             is_syncing: false,
             chainspec_hash: Some(Digest::hash("example-chainspec")),
+            staged_protocol_version: Some(ProtocolVersion::from_parts(5, 6, 8)),
+            network_certificate: Some(NetworkCertificate::random(&mut rng)),
+            encoding_versions: EncodingVersion::supported_ids(),
+            features: Features::supported(true),
         };
 
         let legacy_handshake: V1_0_0_Message = roundtrip_message(&modern_handshake);
@@ -597,15 +769,23 @@ mod tests {
                 consensus_certificate,
                 is_syncing,
                 chainspec_hash,
+                staged_protocol_version,
+                network_certificate,
+                encoding_versions,
+                features,
             } => {
                 assert_eq!(network_name, "example-handshake");
                 assert_eq!(public_addr, ([12, 34, 56, 78], 12346).into());
                 assert_eq!(protocol_version, ProtocolVersion::V1_0_0);
                 assert!(consensus_certificate.is_none());
                 assert!(!is_syncing);
-                assert!(chainspec_hash.is_none())
+                assert!(chainspec_hash.is_none());
+                assert!(staged_protocol_version.is_none());
+                assert!(network_certificate.is_none());
+                assert!(encoding_versions.is_empty());
+                assert_eq!(features, Features::NONE);
             }
-            Message::Payload(_) => {
+            Message::Payload(_) | Message::GoingAway => {
                 panic!("did not expect modern handshake to deserialize to payload")
             }
         }
@@ -623,6 +803,10 @@ mod tests {
                 consensus_certificate,
                 is_syncing,
                 chainspec_hash,
+                staged_protocol_version,
+                network_certificate,
+                encoding_versions,
+                features,
             } => {
                 assert!(!is_syncing);
                 assert_eq!(network_name, "serialization-test");
@@ -630,9 +814,13 @@ mod tests {
                 assert_eq!(protocol_version, ProtocolVersion::V1_0_0);
                 assert!(consensus_certificate.is_none());
                 assert!(!is_syncing);
-                assert!(chainspec_hash.is_none())
+                assert!(chainspec_hash.is_none());
+                assert!(staged_protocol_version.is_none());
+                assert!(network_certificate.is_none());
+                assert!(encoding_versions.is_empty());
+                assert_eq!(features, Features::NONE);
             }
-            Message::Payload(_) => {
+            Message::Payload(_) | Message::GoingAway => {
                 panic!("did not expect modern handshake to deserialize to payload")
             }
         }
@@ -650,6 +838,10 @@ mod tests {
                 consensus_certificate,
                 is_syncing,
                 chainspec_hash,
+                staged_protocol_version,
+                network_certificate,
+                encoding_versions,
+                features,
             } => {
                 assert_eq!(network_name, "example-handshake");
                 assert_eq!(public_addr, ([12, 34, 56, 78], 12346).into());
@@ -676,9 +868,13 @@ mod tests {
                     .unwrap()
                 );
                 assert!(!is_syncing);
-                assert!(chainspec_hash.is_none())
+                assert!(chainspec_hash.is_none());
+                assert!(staged_protocol_version.is_none());
+                assert!(network_certificate.is_none());
+                assert!(encoding_versions.is_empty());
+                assert_eq!(features, Features::NONE);
             }
-            Message::Payload(_) => {
+            Message::Payload(_) | Message::GoingAway => {
                 panic!("did not expect modern handshake to deserialize to payload")
             }
         }
@@ -696,6 +892,10 @@ mod tests {
                 consensus_certificate,
                 is_syncing,
                 chainspec_hash,
+                staged_protocol_version,
+                network_certificate,
+                encoding_versions,
+                features,
             } => {
                 assert!(!is_syncing);
                 assert_eq!(network_name, "example-handshake");
@@ -722,14 +922,25 @@ mod tests {
                     .unwrap()
                 );
                 assert!(!is_syncing);
-                assert!(chainspec_hash.is_none())
+                assert!(chainspec_hash.is_none());
+                assert!(staged_protocol_version.is_none());
+                assert!(network_certificate.is_none());
+                assert!(encoding_versions.is_empty());
+                assert_eq!(features, Features::NONE);
             }
-            Message::Payload(_) => {
+            Message::Payload(_) | Message::GoingAway => {
                 panic!("did not expect modern handshake to deserialize to payload")
             }
         }
     }
 
+    #[test]
+    fn going_away_roundtrips() {
+        let going_away: Message<protocol::Message> =
+            roundtrip_message(&Message::<protocol::Message>::GoingAway);
+        assert!(matches!(going_away, Message::GoingAway));
+    }
+
     fn roundtrip_certificate(use_human_readable: bool) {
         let mut rng = crate::new_rng();
         let certificate = ConsensusCertificate::random(&mut rng);
@@ -753,4 +964,48 @@ mod tests {
     fn bincode_roundtrip_certificate() {
         roundtrip_certificate(false)
     }
+
+    #[test]
+    fn should_validate_secp256r1_consensus_certificate() {
+        let mut rng = crate::new_rng();
+        let secret_key = SecretKey::random_secp256r1(&mut rng);
+        let public_key = PublicKey::from(&secret_key);
+        let key_pair = ConsensusKeyPair::new(Arc::new(secret_key), public_key.clone());
+
+        let connection_id = ConnectionId::random(&mut rng);
+        let certificate: ConsensusCertificate =
+            roundtrip_message(&ConsensusCertificate::create(connection_id, &key_pair));
+        assert_eq!(
+            certificate.clone().validate(connection_id).unwrap(),
+            public_key
+        );
+        assert!(certificate
+            .validate(ConnectionId::random(&mut rng))
+            .is_err());
+    }
+
+    #[test]
+    fn should_validate_network_certificate() {
+        let mut rng = crate::new_rng();
+        let validator_secret_key = SecretKey::random(&mut rng);
+        let network_secret_key = SecretKey::random(&mut rng);
+        let endorsement = NetworkKeyEndorsement::create(
+            PublicKey::from(&network_secret_key),
+            &validator_secret_key,
+        );
+        let key_pair = NetworkKeyPair::new(Arc::new(network_secret_key), endorsement);
+
+        let connection_id = ConnectionId::random(&mut rng);
+        let certificate: NetworkCertificate =
+            roundtrip_message(&NetworkCertificate::create(connection_id, &key_pair));
+        assert_eq!(
+            certificate.clone().validate(connection_id).unwrap(),
+            PublicKey::from(&validator_secret_key)
+        );
+
+        // The certificate is only valid for the connection it was created for.
+        assert!(certificate
+            .validate(ConnectionId::random(&mut rng))
+            .is_err());
+    }
 }
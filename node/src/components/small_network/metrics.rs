@@ -1,9 +1,11 @@
 use std::sync::Weak;
 
-use prometheus::{Counter, IntCounter, IntGauge, Registry};
+use prometheus::{Counter, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry};
 use tracing::debug;
 
-use super::{outgoing::OutgoingMetrics, MessageKind};
+use super::{
+    outgoing::OutgoingMetrics, reputation::ReputationMetrics, shaper::ShaperMetrics, MessageKind,
+};
 use crate::unregister_metric;
 
 /// Network-type agnostic networking metrics.
@@ -116,6 +118,27 @@ pub(super) struct Metrics {
     pub(super) accumulated_outgoing_limiter_delay: Counter,
     /// Total time spent delaying incoming traffic from non-validators due to limiter, in seconds.
     pub(super) accumulated_incoming_limiter_delay: Counter,
+    /// Number of incoming connections rejected due to per-IP limits or greylisting.
+    pub(super) incoming_connections_rejected: IntCounter,
+
+    /// Number of outgoing messages queued by the traffic shapers, per kind of message.
+    pub(super) shaper_queue_depth: IntGaugeVec,
+    /// Number of outgoing messages dropped by the traffic shapers, per kind of message.
+    pub(super) shaper_dropped_messages: IntCounterVec,
+    /// Number of outgoing messages delayed by the bandwidth limits of the traffic shapers, per kind
+    /// of message.
+    pub(super) shaper_delayed_messages: IntCounterVec,
+
+    /// Number of behaviors affecting the reputation of peers, per kind of behavior.
+    pub(super) reputation_events: IntCounterVec,
+    /// Current reputation score of each tracked peer.
+    pub(super) peer_scores: IntGaugeVec,
+    /// Number of bans of peers.
+    pub(super) peer_bans: IntCounter,
+    /// Number of peers currently banned.
+    pub(super) banned_peers: IntGauge,
+    /// Number of peers whose incoming connections were closed to make room for a better peer.
+    pub(super) incoming_peers_evicted: IntCounter,
 
     /// Registry instance.
     registry: Registry,
@@ -324,6 +347,53 @@ impl Metrics {
             "accumulated_incoming_limiter_delay",
             "seconds spent delaying incoming traffic from non-validators due to limiter, in seconds."
         )?;
+        let incoming_connections_rejected = IntCounter::new(
+            "net_incoming_connections_rejected",
+            "number of incoming connections rejected due to per-IP limits or greylisting",
+        )?;
+
+        let shaper_queue_depth = IntGaugeVec::new(
+            Opts::new(
+                "net_shaper_queue_depth",
+                "number of outgoing messages queued by the traffic shapers",
+            ),
+            &["kind"],
+        )?;
+        let shaper_dropped_messages = IntCounterVec::new(
+            Opts::new(
+                "net_shaper_dropped_messages",
+                "number of outgoing messages dropped due to full traffic shaper queues",
+            ),
+            &["kind"],
+        )?;
+        let shaper_delayed_messages = IntCounterVec::new(
+            Opts::new(
+                "net_shaper_delayed_messages",
+                "number of outgoing messages delayed due to traffic shaper bandwidth limits",
+            ),
+            &["kind"],
+        )?;
+
+        let reputation_events = IntCounterVec::new(
+            Opts::new(
+                "net_reputation_events",
+                "number of behaviors of peers affecting their reputation",
+            ),
+            &["behavior"],
+        )?;
+        let peer_scores = IntGaugeVec::new(
+            Opts::new("net_peer_score", "current reputation score of a peer"),
+            &["peer"],
+        )?;
+        let peer_bans = IntCounter::new(
+            "net_peer_bans",
+            "number of times a peer was banned for a low reputation or an offense",
+        )?;
+        let banned_peers = IntGauge::new("net_banned_peers", "number of peers currently banned")?;
+        let incoming_peers_evicted = IntCounter::new(
+            "net_incoming_peers_evicted",
+            "number of peers disconnected to make room for a peer with a better reputation",
+        )?;
 
         registry.register(Box::new(broadcast_requests.clone()))?;
         registry.register(Box::new(direct_message_requests.clone()))?;
@@ -382,6 +452,15 @@ impl Metrics {
 
         registry.register(Box::new(accumulated_outgoing_limiter_delay.clone()))?;
         registry.register(Box::new(accumulated_incoming_limiter_delay.clone()))?;
+        registry.register(Box::new(incoming_connections_rejected.clone()))?;
+        registry.register(Box::new(shaper_queue_depth.clone()))?;
+        registry.register(Box::new(shaper_dropped_messages.clone()))?;
+        registry.register(Box::new(shaper_delayed_messages.clone()))?;
+        registry.register(Box::new(reputation_events.clone()))?;
+        registry.register(Box::new(peer_scores.clone()))?;
+        registry.register(Box::new(peer_bans.clone()))?;
+        registry.register(Box::new(banned_peers.clone()))?;
+        registry.register(Box::new(incoming_peers_evicted.clone()))?;
 
         Ok(Metrics {
             broadcast_requests,
@@ -434,6 +513,15 @@ impl Metrics {
             requests_for_trie_finished,
             accumulated_outgoing_limiter_delay,
             accumulated_incoming_limiter_delay,
+            incoming_connections_rejected,
+            shaper_queue_depth,
+            shaper_dropped_messages,
+            shaper_delayed_messages,
+            reputation_events,
+            peer_scores,
+            peer_bans,
+            banned_peers,
+            incoming_peers_evicted,
             registry: registry.clone(),
         })
     }
@@ -541,6 +629,25 @@ impl Metrics {
         }
     }
 
+    /// Creates a set of metrics for a traffic shaper.
+    pub(super) fn create_shaper_metrics(&self) -> ShaperMetrics {
+        ShaperMetrics {
+            queue_depth: self.shaper_queue_depth.clone(),
+            dropped_messages: self.shaper_dropped_messages.clone(),
+            delayed_messages: self.shaper_delayed_messages.clone(),
+        }
+    }
+
+    /// Creates the metrics of the peer reputation registry.
+    pub(super) fn create_reputation_metrics(&self) -> ReputationMetrics {
+        ReputationMetrics {
+            events: self.reputation_events.clone(),
+            scores: self.peer_scores.clone(),
+            bans: self.peer_bans.clone(),
+            banned_peers: self.banned_peers.clone(),
+        }
+    }
+
     /// Records that a trie request has been started.
     pub(super) fn record_trie_request_start(this: &Weak<Self>) {
         if let Some(metrics) = this.upgrade() {
@@ -619,5 +726,14 @@ impl Drop for Metrics {
 
         unregister_metric!(self.registry, self.accumulated_outgoing_limiter_delay);
         unregister_metric!(self.registry, self.accumulated_incoming_limiter_delay);
+        unregister_metric!(self.registry, self.incoming_connections_rejected);
+        unregister_metric!(self.registry, self.shaper_queue_depth);
+        unregister_metric!(self.registry, self.shaper_dropped_messages);
+        unregister_metric!(self.registry, self.shaper_delayed_messages);
+        unregister_metric!(self.registry, self.reputation_events);
+        unregister_metric!(self.registry, self.peer_scores);
+        unregister_metric!(self.registry, self.peer_bans);
+        unregister_metric!(self.registry, self.banned_peers);
+        unregister_metric!(self.registry, self.incoming_peers_evicted);
     }
 }
@@ -0,0 +1,142 @@
+//! Endorsement of a node's network key by its validator key.
+//!
+//! A validator may run its node with a distinct, long-lived network key, so that the validator key
+//! does not need to be used for networking.  The validator key signs the network public key once,
+//! offline, producing a `NetworkKeyEndorsement` which the node presents together with a signature
+//! of the connection ID by the network key in each handshake.
+
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_types::{
+    bytesrepr::ToBytes,
+    crypto,
+    file_utils::{read_file, ReadFileError},
+    PublicKey, SecretKey, Signature,
+};
+
+use crate::utils::Loadable;
+
+/// Prefix of the message signed by the validator key, to avoid the endorsement being mistaken for
+/// a signature over anything else.
+const ENDORSEMENT_PREFIX: &[u8] = b"casper-network-key-endorsement";
+
+/// Error loading a network key endorsement from a file.
+#[derive(Debug, Error)]
+pub(crate) enum LoadEndorsementError {
+    /// The file could not be read.
+    #[error(transparent)]
+    ReadFile(#[from] ReadFileError),
+    /// The file does not contain a valid endorsement.
+    #[error("failed to parse endorsement: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Error writing a network key endorsement to a file.
+#[derive(Debug, Error)]
+pub(crate) enum WriteEndorsementError {
+    /// The endorsement could not be encoded.
+    #[error("failed to encode endorsement: {0}")]
+    Encode(#[from] serde_json::Error),
+    /// The file could not be written.
+    #[error("failed to write endorsement: {0}")]
+    WriteFile(#[from] io::Error),
+}
+
+/// A signature by a validator key over a network public key, allowing the holder of the network
+/// key to prove it is operated by the validator.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct NetworkKeyEndorsement {
+    /// The public key of the endorsing validator.
+    pub(super) validator_public_key: PublicKey,
+    /// The endorsed network public key.
+    pub(super) network_public_key: PublicKey,
+    /// The validator's signature over the network public key.
+    pub(super) signature: Signature,
+}
+
+impl NetworkKeyEndorsement {
+    /// Creates an endorsement of the given network public key using the validator's secret key.
+    pub(crate) fn create(network_public_key: PublicKey, validator_secret_key: &SecretKey) -> Self {
+        let validator_public_key = PublicKey::from(validator_secret_key);
+        let signature = crypto::sign(
+            endorsement_message(&network_public_key),
+            validator_secret_key,
+            &validator_public_key,
+        );
+        NetworkKeyEndorsement {
+            validator_public_key,
+            network_public_key,
+            signature,
+        }
+    }
+
+    /// Verifies that the signature is the validator's signature over the network public key.
+    pub(super) fn verify(&self) -> Result<(), crypto::Error> {
+        crypto::verify(
+            endorsement_message(&self.network_public_key),
+            &self.signature,
+            &self.validator_public_key,
+        )
+    }
+
+    /// Writes the endorsement to the given file as JSON.
+    pub(crate) fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), WriteEndorsementError> {
+        let encoded = serde_json::to_vec_pretty(self)?;
+        fs::write(path, encoded)?;
+        Ok(())
+    }
+}
+
+impl Loadable for NetworkKeyEndorsement {
+    type Error = LoadEndorsementError;
+
+    fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Self::Error> {
+        let contents = read_file(path)?;
+        Ok(serde_json::from_slice(&contents)?)
+    }
+}
+
+/// Returns the message signed by the validator key to endorse the network public key.
+fn endorsement_message(network_public_key: &PublicKey) -> Vec<u8> {
+    let mut message = ENDORSEMENT_PREFIX.to_vec();
+    message.extend(
+        network_public_key
+            .to_bytes()
+            .expect("should serialize public key"),
+    );
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn should_verify_endorsement() {
+        let mut rng = TestRng::new();
+        let validator_secret_key = SecretKey::random(&mut rng);
+        let network_public_key = PublicKey::from(&SecretKey::random(&mut rng));
+
+        let endorsement =
+            NetworkKeyEndorsement::create(network_public_key.clone(), &validator_secret_key);
+        assert!(endorsement.verify().is_ok());
+
+        let json = serde_json::to_vec(&endorsement).unwrap();
+        let decoded: NetworkKeyEndorsement = serde_json::from_slice(&json).unwrap();
+        assert_eq!(decoded, endorsement);
+
+        // The endorsement must not be transferable to another network key.
+        let other_network_public_key = PublicKey::from(&SecretKey::random(&mut rng));
+        let forged = NetworkKeyEndorsement {
+            network_public_key: other_network_public_key,
+            ..endorsement
+        };
+        assert!(forged.verify().is_err());
+    }
+}
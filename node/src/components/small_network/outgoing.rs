@@ -104,8 +104,11 @@ use std::{
 use datasize::DataSize;
 
 use prometheus::IntGauge;
+use serde::Serialize;
 use tracing::{debug, error_span, field::Empty, info, trace, warn, Span};
 
+use casper_types::TimeDiff;
+
 use super::{display_error, NodeId};
 
 /// An outgoing connection/address in various states.
@@ -155,8 +158,8 @@ where
         /// Can be a channel to decouple sending, or even a direct connection handle.
         handle: H,
     },
-    /// The address was blocked and will not be retried.
-    Blocked { since: Instant },
+    /// The address was blocked and will not be retried until it is unblocked.
+    Blocked { since: Instant, until: Instant },
     /// The address is owned by ourselves and will not be tried again.
     Loopback,
 }
@@ -257,6 +260,47 @@ where
     }
 }
 
+/// A snapshot of the state of an outgoing connection/address, for diagnostics.
+#[derive(Debug, Serialize)]
+pub(crate) struct OutgoingDump {
+    /// The outgoing address.
+    addr: SocketAddr,
+    /// Whether or not the address is unforgettable.
+    is_unforgettable: bool,
+    /// The current state, e.g. `waiting(3)`.
+    state: String,
+    /// The peer connected via this address, if any.
+    peer_id: Option<NodeId>,
+    /// Time the address has been in its current state, if tracked.
+    in_state_for: Option<TimeDiff>,
+    /// Time until the next reconnection attempt, unblocking or sweep timeout, if any.
+    next_action_in: Option<TimeDiff>,
+    /// The most recent connection error, if waiting to reconnect after a failure.
+    last_error: Option<String>,
+}
+
+impl Display for OutgoingDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.addr, self.state)?;
+        if self.is_unforgettable {
+            write!(f, " unforgettable")?;
+        }
+        if let Some(peer_id) = self.peer_id {
+            write!(f, " peer {}", peer_id)?;
+        }
+        if let Some(in_state_for) = self.in_state_for {
+            write!(f, " for {}", in_state_for)?;
+        }
+        if let Some(next_action_in) = self.next_action_in {
+            write!(f, ", next action in {}", next_action_in)?;
+        }
+        if let Some(ref last_error) = self.last_error {
+            write!(f, ", last error: {}", last_error)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(DataSize, Debug)]
 /// Connection settings for the outgoing connection manager.
 pub struct OutgoingConfig {
@@ -541,24 +585,47 @@ where
             })
     }
 
-    /// Blocks an address.
+    /// Blocks an address for the configured duration.
     ///
     /// Causes any current connection to the address to be terminated and future ones prohibited.
     pub(crate) fn block_addr(&mut self, addr: SocketAddr, now: Instant) -> Option<DialRequest<H>> {
+        self.block_addr_for(addr, now, self.config.unblock_after)
+    }
+
+    /// Blocks an address for the given duration.
+    ///
+    /// An address which is already blocked stays blocked until the later of the two unblocking
+    /// times.
+    pub(crate) fn block_addr_for(
+        &mut self,
+        addr: SocketAddr,
+        now: Instant,
+        duration: Duration,
+    ) -> Option<DialRequest<H>> {
+        let until = now + duration;
         let span = make_span(addr, self.outgoing.get(&addr));
 
         span.clone()
             .in_scope(move || match self.outgoing.entry(addr) {
                 Entry::Vacant(_vacant) => {
                     info!("unknown address blocked");
-                    self.change_outgoing_state(addr, OutgoingState::Blocked { since: now });
+                    self.change_outgoing_state(addr, OutgoingState::Blocked { since: now, until });
                     None
                 }
                 // TODO: Check what happens on close on our end, i.e. can we distinguish in logs
                 // between a closed connection on our end vs one that failed?
                 Entry::Occupied(occupied) => match occupied.get().state {
-                    OutgoingState::Blocked { .. } => {
+                    OutgoingState::Blocked {
+                        since,
+                        until: current_until,
+                    } => {
                         debug!("address already blocked");
+                        if until > current_until {
+                            self.change_outgoing_state(
+                                addr,
+                                OutgoingState::Blocked { since, until },
+                            );
+                        }
                         None
                     }
                     OutgoingState::Loopback => {
@@ -568,12 +635,18 @@ where
                     OutgoingState::Connected { ref handle, .. } => {
                         info!("connected address blocked, disconnecting");
                         let handle = handle.clone();
-                        self.change_outgoing_state(addr, OutgoingState::Blocked { since: now });
+                        self.change_outgoing_state(
+                            addr,
+                            OutgoingState::Blocked { since: now, until },
+                        );
                         Some(DialRequest::Disconnect { span, handle })
                     }
                     OutgoingState::Waiting { .. } | OutgoingState::Connecting { .. } => {
                         info!("address blocked");
-                        self.change_outgoing_state(addr, OutgoingState::Blocked { since: now });
+                        self.change_outgoing_state(
+                            addr,
+                            OutgoingState::Blocked { since: now, until },
+                        );
                         None
                     }
                 },
@@ -621,6 +694,60 @@ where
             })
     }
 
+    /// Returns a snapshot of the state of all outgoing connections/addresses, ordered by address.
+    pub(crate) fn dump(&self, now: Instant) -> Vec<OutgoingDump> {
+        let elapsed = |since: Instant| Some(now.saturating_duration_since(since).into());
+        let remaining = |due: Instant| Some(due.saturating_duration_since(now).into());
+
+        let mut dump: Vec<_> = self
+            .outgoing
+            .iter()
+            .map(|(&addr, outgoing)| {
+                let (peer_id, in_state_for, next_action_in, last_error) = match outgoing.state {
+                    OutgoingState::Connecting { since, .. } => (
+                        None,
+                        elapsed(since),
+                        remaining(since + self.config.sweep_timeout),
+                        None,
+                    ),
+                    OutgoingState::Waiting {
+                        failures_so_far,
+                        ref error,
+                        last_failure,
+                    } => (
+                        None,
+                        elapsed(last_failure),
+                        remaining(last_failure + self.config.calc_backoff(failures_so_far)),
+                        error.as_ref().map(ToString::to_string),
+                    ),
+                    OutgoingState::Connected { peer_id, .. } => (Some(peer_id), None, None, None),
+                    OutgoingState::Blocked { since, until } => {
+                        (None, elapsed(since), remaining(until), None)
+                    }
+                    OutgoingState::Loopback => (None, None, None, None),
+                };
+                OutgoingDump {
+                    addr,
+                    is_unforgettable: outgoing.is_unforgettable,
+                    state: outgoing.state.to_string(),
+                    peer_id,
+                    in_state_for,
+                    next_action_in,
+                    last_error,
+                }
+            })
+            .collect();
+        dump.sort_by_key(|outgoing| outgoing.addr);
+        dump
+    }
+
+    /// Returns the addresses which are currently blocked.
+    pub(crate) fn blocked_addrs(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.outgoing.iter().filter_map(|(&addr, outgoing)| {
+            matches!(outgoing.state, OutgoingState::Blocked { .. }).then(|| addr)
+        })
+    }
+
     /// Performs housekeeping like reconnection or unblocking peers.
     ///
     /// This function must periodically be called. A good interval is every second.
@@ -663,8 +790,8 @@ where
                     }
                 }
 
-                OutgoingState::Blocked { since } => {
-                    if now >= since + self.config.unblock_after {
+                OutgoingState::Blocked { until, .. } => {
+                    if now >= until {
                         info!("address unblocked");
 
                         to_reconnect.push((addr, 0));
@@ -1422,4 +1549,77 @@ mod tests {
         assert!(manager.perform_housekeeping(clock.now()).is_empty());
         assert!(manager.is_blocked(addr_a));
     }
+
+    #[test]
+    fn dump_shows_state_and_timers() {
+        init_logging();
+
+        let mut rng = crate::new_rng();
+        let mut clock = TestClock::new();
+
+        let addr_a: SocketAddr = "1.2.3.4:1234".parse().unwrap();
+        let addr_b: SocketAddr = "5.6.7.8:5678".parse().unwrap();
+        let addr_c: SocketAddr = "9.0.1.2:9012".parse().unwrap();
+        let id_c = NodeId::random(&mut rng);
+
+        let mut manager = OutgoingManager::<u32, TestDialerError>::new(test_config());
+
+        // `addr_a` fails once, `addr_b` is blocked and `addr_c` connects.
+        assert!(dials(
+            addr_a,
+            &manager.learn_addr(addr_a, true, clock.now())
+        ));
+        assert!(manager
+            .handle_dial_outcome(DialOutcome::Failed {
+                addr: addr_a,
+                error: TestDialerError { id: 1 },
+                when: clock.now(),
+            })
+            .is_none());
+        assert!(manager.block_addr(addr_b, clock.now()).is_none());
+        assert!(dials(
+            addr_c,
+            &manager.learn_addr(addr_c, false, clock.now())
+        ));
+        assert!(manager
+            .handle_dial_outcome(DialOutcome::Successful {
+                addr: addr_c,
+                handle: 99,
+                node_id: id_c,
+            })
+            .is_none());
+
+        clock.advance_time(500);
+        let dump = manager.dump(clock.now());
+        assert_eq!(
+            dump.iter()
+                .map(|outgoing| outgoing.addr)
+                .collect::<Vec<_>>(),
+            vec![addr_a, addr_b, addr_c]
+        );
+
+        assert_eq!(dump[0].state, "waiting(1)");
+        assert!(dump[0].is_unforgettable);
+        assert_eq!(
+            dump[0].in_state_for,
+            Some(Duration::from_millis(500).into())
+        );
+        assert_eq!(
+            dump[0].next_action_in,
+            Some(Duration::from_millis(1_500).into())
+        );
+        assert_eq!(dump[0].last_error.as_deref(), Some("test dialer error(1)"));
+
+        assert_eq!(dump[1].state, "blocked");
+        assert_eq!(
+            dump[1].next_action_in,
+            Some(Duration::from_millis(59_500).into())
+        );
+
+        assert_eq!(dump[2].state, "connected");
+        assert_eq!(dump[2].peer_id, Some(id_c));
+        assert!(dump[2].next_action_in.is_none());
+
+        assert_eq!(manager.blocked_addrs().collect::<Vec<_>>(), vec![addr_b]);
+    }
 }
@@ -0,0 +1,299 @@
+//! Persisted address book of peers.
+//!
+//! Every peer we complete a handshake with is recorded by its public address, along with when we
+//! were last connected to it, how long it took to establish the last outgoing connection to it and
+//! whether it proved to be a validator through its consensus certificate. The records are
+//! persisted via the state store, so after a restart the node redials the peers it knew rather than
+//! rediscovering the network from its known addresses alone.
+//!
+//! On startup, validators are dialed first, then the peers with the lowest latency.  Records of
+//! peers not seen for the configured maximum age are forgotten, as are the least recently seen
+//! ones once the configured maximum number of records is exceeded.
+
+use std::{cmp::Reverse, collections::BTreeMap, net::SocketAddr};
+
+use serde::{Deserialize, Serialize};
+
+use casper_types::{PublicKey, TimeDiff, Timestamp};
+
+use super::config::PeerStoreConfig;
+
+/// The key under which the peer records are persisted.
+pub(super) const PEER_STORE_STATE_KEY: &[u8] = b"small_network peer_store";
+
+/// What we know about a peer from past connections.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(super) struct PeerRecord {
+    /// The peer's public address.
+    pub(super) addr: SocketAddr,
+    /// When we were last connected to the peer.
+    pub(super) last_seen: Timestamp,
+    /// How long it took to establish the last outgoing connection to the peer, including the
+    /// handshake.
+    pub(super) latency: Option<TimeDiff>,
+    /// The consensus key proven by the peer's consensus certificate, if any.
+    pub(super) consensus_key: Option<PublicKey>,
+    /// Whether the consensus key belonged to an active or upcoming validator when last seen.
+    pub(super) is_validator: bool,
+}
+
+/// The address book of peers.
+#[derive(Debug)]
+pub(super) struct PeerStore {
+    config: PeerStoreConfig,
+    records: BTreeMap<SocketAddr, PeerRecord>,
+    /// Whether the records changed since they were last persisted.
+    changed: bool,
+}
+
+impl PeerStore {
+    /// Creates an empty peer store.
+    pub(super) fn new(config: PeerStoreConfig) -> Self {
+        PeerStore {
+            config,
+            records: BTreeMap::new(),
+            changed: false,
+        }
+    }
+
+    /// Returns `true` if peers are neither recorded nor redialed after a restart.
+    pub(super) fn is_disabled(&self) -> bool {
+        self.config.max_peers == 0
+    }
+
+    /// Adds the persisted records, keeping any recorded since startup, and returns the addresses of
+    /// the peers to dial, best first.
+    pub(super) fn load(&mut self, records: Vec<PeerRecord>, now: Timestamp) -> Vec<SocketAddr> {
+        for record in records {
+            self.records.entry(record.addr).or_insert(record);
+        }
+        self.prune(now);
+
+        let mut records: Vec<&PeerRecord> = self.records.values().collect();
+        records.sort_by_key(|record| {
+            (
+                Reverse(record.is_validator),
+                record.latency.is_none(),
+                record.latency,
+                Reverse(record.last_seen),
+            )
+        });
+        records
+            .into_iter()
+            .take(self.config.max_dialed_on_startup as usize)
+            .map(|record| record.addr)
+            .collect()
+    }
+
+    /// Records that we are connected to the peer at `addr`.
+    ///
+    /// If `latency` is `None`, e.g. because the peer connected to us, the previously recorded
+    /// latency is kept.  If `is_validator` is `None`, i.e. the validators are not known yet, the
+    /// previously recorded status is kept as long as the consensus key is unchanged.
+    pub(super) fn record_seen(
+        &mut self,
+        addr: SocketAddr,
+        consensus_key: Option<PublicKey>,
+        is_validator: Option<bool>,
+        latency: Option<TimeDiff>,
+        now: Timestamp,
+    ) {
+        if self.is_disabled() {
+            return;
+        }
+        let previous = self.records.get(&addr);
+        let latency = latency.or_else(|| previous.and_then(|record| record.latency));
+        let is_validator = is_validator.unwrap_or_else(|| {
+            previous.map_or(false, |record| {
+                record.is_validator && record.consensus_key == consensus_key
+            })
+        });
+        self.records.insert(
+            addr,
+            PeerRecord {
+                addr,
+                last_seen: now,
+                latency,
+                consensus_key,
+                is_validator,
+            },
+        );
+        self.changed = true;
+    }
+
+    /// Updates when we were last connected to the peer at `addr`, if it is recorded.
+    pub(super) fn touch(&mut self, addr: SocketAddr, now: Timestamp) {
+        if let Some(record) = self.records.get_mut(&addr) {
+            record.last_seen = now;
+            self.changed = true;
+        }
+    }
+
+    /// Forgets the peers not seen for the maximum age, and the least recently seen ones beyond the
+    /// maximum number of records.
+    fn prune(&mut self, now: Timestamp) {
+        let count = self.records.len();
+        let max_age = self.config.max_age;
+        self.records
+            .retain(|_, record| now.saturating_diff(record.last_seen) <= max_age);
+
+        let max_peers = self.config.max_peers as usize;
+        if self.records.len() > max_peers {
+            let mut last_seen: Vec<Timestamp> = self
+                .records
+                .values()
+                .map(|record| record.last_seen)
+                .collect();
+            last_seen.sort_unstable_by_key(|timestamp| Reverse(*timestamp));
+            let cutoff = last_seen[max_peers];
+            self.records.retain(|_, record| record.last_seen > cutoff);
+        }
+
+        if self.records.len() != count {
+            self.changed = true;
+        }
+    }
+
+    /// Prunes the records and returns them if they changed since they were last persisted.
+    pub(super) fn take_changes(&mut self, now: Timestamp) -> Option<Vec<PeerRecord>> {
+        self.prune(now);
+        if !self.changed {
+            return None;
+        }
+        self.changed = false;
+        Some(self.records.values().cloned().collect())
+    }
+
+    /// Returns the number of recorded peers.
+    pub(super) fn len(&self) -> usize {
+        self.records.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{testing::TestRng, SecretKey};
+
+    use super::*;
+
+    fn new_store(max_peers: u32, max_dialed_on_startup: u32) -> PeerStore {
+        PeerStore::new(PeerStoreConfig {
+            max_peers,
+            max_age: TimeDiff::from_seconds(3600),
+            max_dialed_on_startup,
+        })
+    }
+
+    fn record(
+        port: u16,
+        last_seen: u64,
+        latency_ms: Option<u64>,
+        is_validator: bool,
+    ) -> PeerRecord {
+        PeerRecord {
+            addr: ([127, 0, 0, 1], port).into(),
+            last_seen: Timestamp::from(last_seen),
+            latency: latency_ms.map(TimeDiff::from),
+            consensus_key: None,
+            is_validator,
+        }
+    }
+
+    #[test]
+    fn should_dial_validators_then_fastest_peers_first() {
+        let mut store = new_store(100, 3);
+        let now = Timestamp::from(10_000);
+        let addrs = store.load(
+            vec![
+                record(1, 9_000, Some(50), false),
+                record(2, 9_000, None, true),
+                record(3, 9_500, None, false),
+                record(4, 9_000, Some(10), false),
+                record(5, 9_000, Some(500), false),
+            ],
+            now,
+        );
+        let ports: Vec<u16> = addrs.iter().map(SocketAddr::port).collect();
+        assert_eq!(ports, vec![2, 4, 1]);
+    }
+
+    #[test]
+    fn should_forget_old_and_least_recently_seen_peers() {
+        let mut store = new_store(2, 10);
+        let now = Timestamp::from(10_000_000);
+        let addrs = store.load(
+            vec![
+                record(1, 1, Some(1), true),
+                record(2, 9_000_000, None, false),
+                record(3, 9_500_000, None, false),
+                record(4, 8_000_000, None, false),
+            ],
+            now,
+        );
+        let mut ports: Vec<u16> = addrs.iter().map(SocketAddr::port).collect();
+        ports.sort_unstable();
+        assert_eq!(ports, vec![2, 3]);
+        assert_eq!(
+            store.take_changes(now).map(|records| records.len()),
+            Some(2)
+        );
+        assert_eq!(store.take_changes(now), None);
+    }
+
+    #[test]
+    fn should_keep_latency_and_validator_status_if_unknown() {
+        let mut rng = TestRng::new();
+        let consensus_key = PublicKey::from(&SecretKey::random(&mut rng));
+        let mut store = new_store(100, 10);
+        let addr: SocketAddr = ([127, 0, 0, 1], 1).into();
+        let latency = Some(TimeDiff::from(20));
+        store.record_seen(
+            addr,
+            Some(consensus_key.clone()),
+            Some(true),
+            latency,
+            Timestamp::from(1),
+        );
+        store.record_seen(
+            addr,
+            Some(consensus_key.clone()),
+            None,
+            None,
+            Timestamp::from(2),
+        );
+
+        let records = store.take_changes(Timestamp::from(3)).unwrap();
+        assert_eq!(
+            records,
+            vec![PeerRecord {
+                addr,
+                last_seen: Timestamp::from(2),
+                latency,
+                consensus_key: Some(consensus_key),
+                is_validator: true,
+            }]
+        );
+        assert_eq!(store.len(), 1);
+
+        store.touch(addr, Timestamp::from(4));
+        assert_eq!(
+            store.take_changes(Timestamp::from(5)).unwrap()[0].last_seen,
+            Timestamp::from(4)
+        );
+    }
+
+    #[test]
+    fn should_record_nothing_if_disabled() {
+        let mut store = new_store(0, 10);
+        assert!(store.is_disabled());
+        store.record_seen(
+            ([127, 0, 0, 1], 1).into(),
+            None,
+            Some(false),
+            None,
+            Timestamp::from(1),
+        );
+        assert_eq!(store.len(), 0);
+        assert_eq!(store.take_changes(Timestamp::from(2)), None);
+    }
+}
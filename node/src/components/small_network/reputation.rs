@@ -0,0 +1,395 @@
+//! Peer reputation.
+//!
+//! Every peer is scored by its behavior: messages which cannot be decoded, handshakes failing after
+//! the peer identified itself and fetch requests left unanswered lower its score, while gossip about
+//! items we did not hold yet raises it. Scores recover towards zero over time.
+//!
+//! A peer whose score drops to the ban threshold, or which commits an offense, is banned. The first
+//! ban lasts the base ban duration, every further one twice as long as the one before, up to the
+//! maximum ban duration. The count of bans is forgotten once a peer has gone the maximum ban
+//! duration without being banned again.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    time::{Duration, Instant},
+};
+
+use prometheus::{IntCounter, IntCounterVec, IntGauge, IntGaugeVec};
+use tracing::debug;
+
+use super::config::ReputationConfig;
+use crate::types::{peers_map::PeerReputation, NodeId};
+
+/// A behavior of a peer affecting its reputation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Behavior {
+    /// The peer sent a message which could not be decoded.
+    InvalidMessage,
+    /// A handshake with the peer failed after it identified itself.
+    HandshakeFailure,
+    /// The peer did not answer a fetch request in time.
+    SlowResponse,
+    /// The peer gossiped an item we did not hold yet.
+    UsefulGossip,
+    /// The peer committed an offense, such as sending an invalid item, and is banned outright.
+    Offense,
+}
+
+impl Behavior {
+    /// Returns the label of the behavior in the metrics.
+    fn label(self) -> &'static str {
+        match self {
+            Behavior::InvalidMessage => "invalid_message",
+            Behavior::HandshakeFailure => "handshake_failure",
+            Behavior::SlowResponse => "slow_response",
+            Behavior::UsefulGossip => "useful_gossip",
+            Behavior::Offense => "offense",
+        }
+    }
+}
+
+impl Display for Behavior {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Behavior::InvalidMessage => f.write_str("invalid message"),
+            Behavior::HandshakeFailure => f.write_str("handshake failure"),
+            Behavior::SlowResponse => f.write_str("slow response"),
+            Behavior::UsefulGossip => f.write_str("useful gossip"),
+            Behavior::Offense => f.write_str("offense committed"),
+        }
+    }
+}
+
+/// The metrics of the reputation registry.
+#[derive(Clone, Debug)]
+pub(super) struct ReputationMetrics {
+    /// Number of recorded behaviors, labelled by behavior.
+    pub(super) events: IntCounterVec,
+    /// Current score, labelled by peer.
+    pub(super) scores: IntGaugeVec,
+    /// Number of bans.
+    pub(super) bans: IntCounter,
+    /// Number of peers currently banned.
+    pub(super) banned_peers: IntGauge,
+}
+
+/// A ban imposed on a peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct Ban {
+    /// How long the ban lasts.
+    pub(super) duration: Duration,
+    /// The behavior which triggered the ban.
+    pub(super) reason: Behavior,
+}
+
+/// The reputation of a single peer.
+#[derive(Debug)]
+struct PeerRecord {
+    /// The score as of `last_updated`.
+    score: f64,
+    /// When the score was last brought up to date.
+    last_updated: Instant,
+    /// Number of bans since the count was last forgotten.
+    bans: u32,
+    /// When the latest ban ends or ended.
+    banned_until: Option<Instant>,
+    /// The most recent behavior which lowered the score.
+    last_penalty: Option<Behavior>,
+}
+
+impl PeerRecord {
+    fn new(now: Instant) -> Self {
+        PeerRecord {
+            score: 0.0,
+            last_updated: now,
+            bans: 0,
+            banned_until: None,
+            last_penalty: None,
+        }
+    }
+
+    /// Returns the score at `now`, after recovering towards zero since the last update.
+    fn score_at(&self, now: Instant, recovery_per_minute: u32) -> f64 {
+        let elapsed = now.saturating_duration_since(self.last_updated);
+        let recovery = f64::from(recovery_per_minute) * elapsed.as_secs_f64() / 60.0;
+        if self.score < 0.0 {
+            (self.score + recovery).min(0.0)
+        } else {
+            (self.score - recovery).max(0.0)
+        }
+    }
+
+    fn is_banned(&self, now: Instant) -> bool {
+        self.banned_until.map_or(false, |until| now < until)
+    }
+
+    /// Returns whether the count of bans has been forgotten by `now`.
+    fn bans_forgotten(&self, now: Instant, max_ban_duration: Duration) -> bool {
+        self.banned_until
+            .map_or(true, |until| now >= until + max_ban_duration)
+    }
+}
+
+/// The reputation of all peers which recently affected theirs.
+#[derive(Debug)]
+pub(super) struct ReputationRegistry {
+    config: ReputationConfig,
+    peers: HashMap<NodeId, PeerRecord>,
+    metrics: ReputationMetrics,
+}
+
+impl ReputationRegistry {
+    /// Creates a new registry in which all peers start out with a score of zero.
+    pub(super) fn new(config: ReputationConfig, metrics: ReputationMetrics) -> Self {
+        ReputationRegistry {
+            config,
+            peers: HashMap::new(),
+            metrics,
+        }
+    }
+
+    /// Records a behavior of a peer, returning the ban it incurs, if any.
+    ///
+    /// A peer which is already banned is not banned again, but its score is still updated.
+    pub(super) fn record(
+        &mut self,
+        peer_id: NodeId,
+        behavior: Behavior,
+        now: Instant,
+    ) -> Option<Ban> {
+        self.metrics
+            .events
+            .with_label_values(&[behavior.label()])
+            .inc();
+
+        let config = &self.config;
+        let record = self
+            .peers
+            .entry(peer_id)
+            .or_insert_with(|| PeerRecord::new(now));
+        record.score = record.score_at(now, config.recovery_per_minute);
+        record.last_updated = now;
+
+        let penalty = match behavior {
+            Behavior::InvalidMessage => config.invalid_message_penalty,
+            Behavior::HandshakeFailure => config.handshake_failure_penalty,
+            Behavior::SlowResponse => config.slow_response_penalty,
+            Behavior::Offense => 0,
+            Behavior::UsefulGossip => {
+                record.score = (record.score + f64::from(config.useful_gossip_reward))
+                    .min(config.max_score as f64);
+                0
+            }
+        };
+        if behavior != Behavior::UsefulGossip {
+            record.score -= f64::from(penalty);
+            record.last_penalty = Some(behavior);
+        }
+        self.metrics
+            .scores
+            .with_label_values(&[&peer_id.to_string()])
+            .set(record.score.round() as i64);
+
+        let should_ban =
+            behavior == Behavior::Offense || record.score <= config.ban_threshold as f64;
+        if !should_ban || record.is_banned(now) {
+            return None;
+        }
+
+        let max_ban_duration: Duration = config.max_ban_duration.into();
+        if record.bans_forgotten(now, max_ban_duration) {
+            record.bans = 0;
+        }
+        let duration = 2u32
+            .checked_pow(record.bans)
+            .and_then(|factor| Duration::from(config.base_ban_duration).checked_mul(factor))
+            .map_or(max_ban_duration, |duration| duration.min(max_ban_duration));
+        record.bans += 1;
+        record.banned_until = Some(now + duration);
+        debug!(%peer_id, bans = record.bans, ?duration, reason = %behavior, "banning peer");
+
+        self.metrics.bans.inc();
+        self.update_banned_peers(now);
+        Some(Ban {
+            duration,
+            reason: behavior,
+        })
+    }
+
+    /// Returns whether the peer is currently banned.
+    pub(super) fn is_banned(&self, peer_id: &NodeId, now: Instant) -> bool {
+        self.peers
+            .get(peer_id)
+            .map_or(false, |record| record.is_banned(now))
+    }
+
+    /// Returns the current score of the peer.
+    pub(super) fn score(&self, peer_id: &NodeId, now: Instant) -> i64 {
+        self.peers.get(peer_id).map_or(0, |record| {
+            record
+                .score_at(now, self.config.recovery_per_minute)
+                .round() as i64
+        })
+    }
+
+    /// Returns the reputation of the peer, if it has one.
+    pub(super) fn reputation(&self, peer_id: &NodeId, now: Instant) -> Option<PeerReputation> {
+        self.peers
+            .get(peer_id)
+            .map(|record| self.describe(peer_id, record, now))
+    }
+
+    /// Returns the reputation of all tracked peers, ordered by node ID.
+    pub(super) fn snapshot(&self, now: Instant) -> Vec<PeerReputation> {
+        let mut peer_ids: Vec<_> = self.peers.keys().collect();
+        peer_ids.sort();
+        peer_ids
+            .into_iter()
+            .map(|peer_id| self.describe(peer_id, &self.peers[peer_id], now))
+            .collect()
+    }
+
+    /// Forgets peers which are back to a neutral reputation, and updates the metrics.
+    pub(super) fn prune(&mut self, now: Instant) {
+        let recovery_per_minute = self.config.recovery_per_minute;
+        let max_ban_duration = self.config.max_ban_duration.into();
+        let scores = &self.metrics.scores;
+        self.peers.retain(|peer_id, record| {
+            let score = record.score_at(now, recovery_per_minute);
+            let keep = score != 0.0 || !record.bans_forgotten(now, max_ban_duration);
+            if keep {
+                scores
+                    .with_label_values(&[&peer_id.to_string()])
+                    .set(score.round() as i64);
+            } else {
+                let _ = scores.remove_label_values(&[&peer_id.to_string()]);
+            }
+            keep
+        });
+        self.update_banned_peers(now);
+    }
+
+    fn describe(&self, peer_id: &NodeId, record: &PeerRecord, now: Instant) -> PeerReputation {
+        PeerReputation {
+            node_id: peer_id.to_string(),
+            score: record
+                .score_at(now, self.config.recovery_per_minute)
+                .round() as i64,
+            bans: record.bans,
+            banned_for: record
+                .banned_until
+                .filter(|until| now < *until)
+                .map(|until| until.saturating_duration_since(now).into()),
+            last_penalty: record.last_penalty.map(|behavior| behavior.to_string()),
+        }
+    }
+
+    fn update_banned_peers(&self, now: Instant) {
+        let banned = self
+            .peers
+            .values()
+            .filter(|record| record.is_banned(now))
+            .count();
+        self.metrics.banned_peers.set(banned as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{testing::TestRng, TimeDiff};
+    use prometheus::Opts;
+
+    use super::*;
+
+    fn new_registry(config: ReputationConfig) -> ReputationRegistry {
+        let metrics = ReputationMetrics {
+            events: IntCounterVec::new(Opts::new("events", "events"), &["behavior"]).unwrap(),
+            scores: IntGaugeVec::new(Opts::new("scores", "scores"), &["peer"]).unwrap(),
+            bans: IntCounter::new("bans", "bans").unwrap(),
+            banned_peers: IntGauge::new("banned", "banned").unwrap(),
+        };
+        ReputationRegistry::new(config, metrics)
+    }
+
+    #[test]
+    fn should_ban_with_exponential_backoff() {
+        let mut rng = TestRng::new();
+        let peer_id = NodeId::random(&mut rng);
+        let mut registry = new_registry(ReputationConfig {
+            invalid_message_penalty: 100,
+            ban_threshold: -200,
+            recovery_per_minute: 0,
+            base_ban_duration: TimeDiff::from_seconds(60),
+            max_ban_duration: TimeDiff::from_seconds(200),
+            ..Default::default()
+        });
+        let mut now = Instant::now();
+
+        assert!(registry
+            .record(peer_id, Behavior::InvalidMessage, now)
+            .is_none());
+        assert_eq!(registry.score(&peer_id, now), -100);
+        let ban = registry
+            .record(peer_id, Behavior::InvalidMessage, now)
+            .expect("should ban");
+        assert_eq!(ban.duration, Duration::from_secs(60));
+        assert_eq!(ban.reason, Behavior::InvalidMessage);
+        assert!(registry.is_banned(&peer_id, now));
+
+        // No further ban is imposed while the peer is banned.
+        assert!(registry
+            .record(peer_id, Behavior::InvalidMessage, now)
+            .is_none());
+
+        now += Duration::from_secs(60);
+        assert!(!registry.is_banned(&peer_id, now));
+        let ban = registry.record(peer_id, Behavior::Offense, now).unwrap();
+        assert_eq!(ban.duration, Duration::from_secs(120));
+
+        now += Duration::from_secs(120);
+        let ban = registry.record(peer_id, Behavior::Offense, now).unwrap();
+        assert_eq!(ban.duration, Duration::from_secs(200));
+
+        // After the maximum ban duration without a ban, the count of bans is forgotten.
+        now += Duration::from_secs(400);
+        let ban = registry.record(peer_id, Behavior::Offense, now).unwrap();
+        assert_eq!(ban.duration, Duration::from_secs(60));
+        assert_eq!(
+            registry.reputation(&peer_id, now).unwrap().last_penalty,
+            Some("offense committed".to_string())
+        );
+    }
+
+    #[test]
+    fn should_recover_and_prune_scores() {
+        let mut rng = TestRng::new();
+        let good_peer = NodeId::random(&mut rng);
+        let bad_peer = NodeId::random(&mut rng);
+        let mut registry = new_registry(ReputationConfig {
+            slow_response_penalty: 30,
+            useful_gossip_reward: 10,
+            max_score: 15,
+            recovery_per_minute: 10,
+            ..Default::default()
+        });
+        let now = Instant::now();
+
+        registry.record(good_peer, Behavior::UsefulGossip, now);
+        registry.record(good_peer, Behavior::UsefulGossip, now);
+        registry.record(bad_peer, Behavior::SlowResponse, now);
+        assert_eq!(registry.score(&good_peer, now), 15);
+        assert_eq!(registry.score(&bad_peer, now), -30);
+
+        let later = now + Duration::from_secs(120);
+        assert_eq!(registry.score(&good_peer, later), 0);
+        assert_eq!(registry.score(&bad_peer, later), -10);
+
+        registry.prune(later);
+        assert!(registry.reputation(&good_peer, later).is_none());
+        let snapshot = registry.snapshot(later);
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].node_id, bad_peer.to_string());
+        assert_eq!(snapshot[0].last_penalty, Some("slow response".to_string()));
+    }
+}
@@ -0,0 +1,373 @@
+//! Outgoing traffic shaping.
+//!
+//! Every outgoing connection has a traffic shaper, which queues the messages to be sent per kind of
+//! message and decides which of them is sent next: the oldest message of the kind with the highest
+//! priority, among the kinds which have not exceeded their bandwidth limit.  Messages of a kind
+//! whose queue is full are dropped.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use prometheus::{IntCounterVec, IntGaugeVec};
+
+use super::{
+    config::{KindShapingConfig, TrafficShapingConfig},
+    MessageKind,
+};
+
+/// All kinds of messages, in the order their queues are kept.
+const KINDS: [MessageKind; 9] = [
+    MessageKind::Protocol,
+    MessageKind::Consensus,
+    MessageKind::DeployGossip,
+    MessageKind::AddressGossip,
+    MessageKind::DeployTransfer,
+    MessageKind::FinalizedApprovalsTransfer,
+    MessageKind::BlockTransfer,
+    MessageKind::TrieTransfer,
+    MessageKind::Other,
+];
+
+/// The metrics of the traffic shapers, labelled by kind of message.
+#[derive(Clone, Debug)]
+pub(super) struct ShaperMetrics {
+    /// Number of queued messages.
+    pub(super) queue_depth: IntGaugeVec,
+    /// Number of messages dropped due to a full queue.
+    pub(super) dropped_messages: IntCounterVec,
+    /// Number of messages delayed due to a bandwidth limit.
+    pub(super) delayed_messages: IntCounterVec,
+}
+
+/// The outcome of asking a traffic shaper for the next message to send.
+#[derive(Debug)]
+pub(super) enum Dequeued<T> {
+    /// The message to send now.
+    Ready(T),
+    /// All queued messages are held back by bandwidth limits until the given instant.
+    Throttled(Instant),
+    /// No message is queued.
+    Empty,
+}
+
+/// A message waiting in a queue.
+#[derive(Debug)]
+struct Entry<T> {
+    /// Position of the message in the order messages were queued across all kinds.
+    sequence: u64,
+    /// Estimated size of the message in bytes.
+    size: u32,
+    /// Whether the message has already been counted as delayed.
+    delayed: bool,
+    item: T,
+}
+
+/// The queue of a single kind of message.
+#[derive(Debug)]
+struct KindQueue<T> {
+    kind: MessageKind,
+    config: KindShapingConfig,
+    entries: VecDeque<Entry<T>>,
+    /// Bytes which can be sent before the bandwidth limit is reached, negative if it was exceeded.
+    allowance: f64,
+    /// When `allowance` was last updated.
+    last_refill: Instant,
+}
+
+impl<T> KindQueue<T> {
+    /// Adds the allowance gained since the last refill, up to one second worth of bandwidth.
+    fn refill(&mut self, now: Instant) {
+        let rate = f64::from(self.config.max_bytes_per_sec);
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.allowance = (self.allowance + elapsed * rate).min(rate);
+        self.last_refill = now;
+    }
+
+    /// Returns when the queue can send again, or `None` if it can send now.
+    fn throttled_until(&self, now: Instant) -> Option<Instant> {
+        if self.config.max_bytes_per_sec == 0 || self.allowance > 0.0 {
+            return None;
+        }
+        let deficit = -self.allowance + 1.0;
+        Some(now + Duration::from_secs_f64(deficit / f64::from(self.config.max_bytes_per_sec)))
+    }
+}
+
+/// A traffic shaper for the outgoing messages of a single connection.
+#[derive(Debug)]
+pub(super) struct TrafficShaper<T> {
+    queues: Vec<KindQueue<T>>,
+    next_sequence: u64,
+    metrics: ShaperMetrics,
+}
+
+impl<T> TrafficShaper<T> {
+    /// Creates a new traffic shaper.
+    pub(super) fn new(config: &TrafficShapingConfig, metrics: ShaperMetrics, now: Instant) -> Self {
+        let queues = KINDS
+            .iter()
+            .map(|&kind| {
+                let config = config.for_kind(kind);
+                KindQueue {
+                    kind,
+                    config,
+                    entries: VecDeque::new(),
+                    allowance: f64::from(config.max_bytes_per_sec),
+                    last_refill: now,
+                }
+            })
+            .collect();
+        TrafficShaper {
+            queues,
+            next_sequence: 0,
+            metrics,
+        }
+    }
+
+    /// Returns the number of queued messages.
+    pub(super) fn len(&self) -> usize {
+        self.queues.iter().map(|queue| queue.entries.len()).sum()
+    }
+
+    /// Queues a message of the given kind and estimated size.
+    ///
+    /// Returns the message back if the queue of its kind is full.
+    pub(super) fn enqueue(&mut self, kind: MessageKind, size: u32, item: T) -> Result<(), T> {
+        let queue = self.queue_mut(kind);
+        let max_queue_length = queue.config.max_queue_length as usize;
+        if max_queue_length != 0 && queue.entries.len() >= max_queue_length {
+            self.metrics
+                .dropped_messages
+                .with_label_values(&[&kind.to_string()])
+                .inc();
+            return Err(item);
+        }
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.queue_mut(kind).entries.push_back(Entry {
+            sequence,
+            size,
+            delayed: false,
+            item,
+        });
+        self.metrics
+            .queue_depth
+            .with_label_values(&[&kind.to_string()])
+            .inc();
+        Ok(())
+    }
+
+    /// Removes the next message to send, if any message can be sent at `now`.
+    pub(super) fn dequeue(&mut self, now: Instant) -> Dequeued<T> {
+        // The index of the queue to send from, keyed by its priority and the position of its oldest
+        // message.
+        let mut best: Option<((u8, u64), usize)> = None;
+        let mut throttled_until: Option<Instant> = None;
+
+        for (index, queue) in self.queues.iter_mut().enumerate() {
+            if queue.entries.is_empty() {
+                continue;
+            }
+            queue.refill(now);
+            if let Some(until) = queue.throttled_until(now) {
+                if let Some(head) = queue.entries.front_mut() {
+                    if !head.delayed {
+                        head.delayed = true;
+                        self.metrics
+                            .delayed_messages
+                            .with_label_values(&[&queue.kind.to_string()])
+                            .inc();
+                    }
+                }
+                throttled_until = Some(throttled_until.map_or(until, |other| other.min(until)));
+                continue;
+            }
+            let key = (
+                queue.config.priority,
+                queue.entries.front().map_or(u64::MAX, |head| head.sequence),
+            );
+            if best.map_or(true, |(best_key, _)| key < best_key) {
+                best = Some((key, index));
+            }
+        }
+
+        match (best, throttled_until) {
+            (Some((_, index)), _) => {
+                let queue = &mut self.queues[index];
+                let entry = queue
+                    .entries
+                    .pop_front()
+                    .expect("queue should not be empty");
+                if queue.config.max_bytes_per_sec != 0 {
+                    queue.allowance -= f64::from(entry.size);
+                }
+                self.metrics
+                    .queue_depth
+                    .with_label_values(&[&queue.kind.to_string()])
+                    .dec();
+                Dequeued::Ready(entry.item)
+            }
+            (None, Some(until)) => Dequeued::Throttled(until),
+            (None, None) => Dequeued::Empty,
+        }
+    }
+
+    fn queue_mut(&mut self, kind: MessageKind) -> &mut KindQueue<T> {
+        self.queues
+            .iter_mut()
+            .find(|queue| queue.kind == kind)
+            .expect("should have a queue for every kind of message")
+    }
+}
+
+impl<T> Drop for TrafficShaper<T> {
+    fn drop(&mut self) {
+        // Messages still queued are discarded along with the shaper.
+        for queue in &self.queues {
+            if !queue.entries.is_empty() {
+                self.metrics
+                    .queue_depth
+                    .with_label_values(&[&queue.kind.to_string()])
+                    .sub(queue.entries.len() as i64);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Opts;
+
+    use super::*;
+
+    fn new_metrics() -> ShaperMetrics {
+        ShaperMetrics {
+            queue_depth: IntGaugeVec::new(Opts::new("queue_depth", "queue depth"), &["kind"])
+                .unwrap(),
+            dropped_messages: IntCounterVec::new(Opts::new("dropped", "dropped"), &["kind"])
+                .unwrap(),
+            delayed_messages: IntCounterVec::new(Opts::new("delayed", "delayed"), &["kind"])
+                .unwrap(),
+        }
+    }
+
+    fn config(
+        kind: MessageKind,
+        priority: u8,
+        max_bytes_per_sec: u32,
+        max_queue_length: u32,
+    ) -> TrafficShapingConfig {
+        let mut config = TrafficShapingConfig::default();
+        let kind_config = KindShapingConfig {
+            priority,
+            max_bytes_per_sec,
+            max_queue_length,
+        };
+        match kind {
+            MessageKind::DeployGossip => config.deploy_gossip = kind_config,
+            MessageKind::TrieTransfer => config.trie_transfer = kind_config,
+            _ => unimplemented!("not used in tests"),
+        }
+        config
+    }
+
+    fn assert_ready<T: PartialEq + std::fmt::Debug>(dequeued: Dequeued<T>, expected: T) {
+        match dequeued {
+            Dequeued::Ready(item) => assert_eq!(item, expected),
+            other => panic!("expected {:?} to be ready, got {:?}", expected, other),
+        }
+    }
+
+    #[test]
+    fn higher_priority_preempts_lower_priority() {
+        let now = Instant::now();
+        let mut shaper = TrafficShaper::new(&TrafficShapingConfig::default(), new_metrics(), now);
+
+        shaper.enqueue(MessageKind::TrieTransfer, 100, 1).unwrap();
+        shaper.enqueue(MessageKind::DeployGossip, 100, 2).unwrap();
+        shaper.enqueue(MessageKind::TrieTransfer, 100, 3).unwrap();
+        shaper.enqueue(MessageKind::Consensus, 100, 4).unwrap();
+        shaper.enqueue(MessageKind::BlockTransfer, 100, 5).unwrap();
+        assert_eq!(shaper.len(), 5);
+
+        // Consensus first, then the kinds of equal priority in the order they were queued.
+        assert_ready(shaper.dequeue(now), 4);
+        assert_ready(shaper.dequeue(now), 2);
+        assert_ready(shaper.dequeue(now), 5);
+        assert_ready(shaper.dequeue(now), 1);
+        assert_ready(shaper.dequeue(now), 3);
+        assert!(matches!(shaper.dequeue(now), Dequeued::Empty));
+    }
+
+    #[test]
+    fn bandwidth_limit_delays_messages() {
+        let now = Instant::now();
+        let metrics = new_metrics();
+        let mut shaper = TrafficShaper::new(
+            &config(MessageKind::TrieTransfer, 0, 1_000, 0),
+            metrics.clone(),
+            now,
+        );
+
+        shaper.enqueue(MessageKind::TrieTransfer, 1_500, 1).unwrap();
+        shaper.enqueue(MessageKind::TrieTransfer, 500, 2).unwrap();
+        shaper.enqueue(MessageKind::DeployGossip, 10, 3).unwrap();
+
+        // The first message exhausts the allowance, letting lower priority messages through.
+        assert_ready(shaper.dequeue(now), 1);
+        assert_ready(shaper.dequeue(now), 3);
+        let until = match shaper.dequeue(now) {
+            Dequeued::Throttled(until) => until,
+            other => panic!("expected to be throttled, got {:?}", other),
+        };
+        assert!(until > now + Duration::from_millis(500));
+        assert!(until <= now + Duration::from_millis(502));
+        assert_eq!(
+            metrics
+                .delayed_messages
+                .with_label_values(&["trie_transfer"])
+                .get(),
+            1
+        );
+
+        assert_ready(shaper.dequeue(until), 2);
+        assert!(matches!(shaper.dequeue(until), Dequeued::Empty));
+    }
+
+    #[test]
+    fn full_queue_drops_messages() {
+        let now = Instant::now();
+        let metrics = new_metrics();
+        let mut shaper = TrafficShaper::new(
+            &config(MessageKind::DeployGossip, 2, 0, 2),
+            metrics.clone(),
+            now,
+        );
+
+        shaper.enqueue(MessageKind::DeployGossip, 10, 1).unwrap();
+        shaper.enqueue(MessageKind::DeployGossip, 10, 2).unwrap();
+        assert_eq!(shaper.enqueue(MessageKind::DeployGossip, 10, 3), Err(3));
+        shaper.enqueue(MessageKind::Consensus, 10, 4).unwrap();
+
+        let depth = |kind| metrics.queue_depth.with_label_values(&[kind]).get();
+        assert_eq!(depth("deploy_gossip"), 2);
+        assert_eq!(
+            metrics
+                .dropped_messages
+                .with_label_values(&["deploy_gossip"])
+                .get(),
+            1
+        );
+
+        assert_ready(shaper.dequeue(now), 4);
+        assert_eq!(depth("consensus"), 0);
+
+        drop(shaper);
+        assert_eq!(depth("deploy_gossip"), 0);
+    }
+}
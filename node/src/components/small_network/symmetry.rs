@@ -3,7 +3,12 @@
 //! Tracks the state of connections, which may be uni- or bi-directional, depending on whether a
 //! peer has connected back to us. Asymmetric connections are usually removed periodically.
 
-use std::{collections::BTreeSet, mem, net::SocketAddr, time::Instant};
+use std::{
+    collections::BTreeSet,
+    mem,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 
 use datasize::DataSize;
 use tracing::{debug, warn};
@@ -191,6 +196,20 @@ impl ConnectionSymmetry {
         }
     }
 
+    /// Returns a short name of the state, and the time it has been in that state if tracked.
+    pub(super) fn describe(&self, now: Instant) -> (&'static str, Option<Duration>) {
+        match self {
+            ConnectionSymmetry::IncomingOnly { since, .. } => {
+                ("incoming_only", Some(now.saturating_duration_since(*since)))
+            }
+            ConnectionSymmetry::OutgoingOnly { since } => {
+                ("outgoing_only", Some(now.saturating_duration_since(*since)))
+            }
+            ConnectionSymmetry::Symmetric { .. } => ("symmetric", None),
+            ConnectionSymmetry::Gone => ("gone", None),
+        }
+    }
+
     /// Returns the set of incoming addresses, if any.
     pub(super) fn incoming_addrs(&self) -> Option<&BTreeSet<SocketAddr>> {
         match self {
@@ -8,9 +8,9 @@ use std::{
     pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, Weak,
+        Arc, RwLock, Weak,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use bincode::Options;
@@ -29,7 +29,10 @@ use rand::Rng;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::{
     net::TcpStream,
-    sync::{mpsc::UnboundedReceiver, watch, Semaphore},
+    sync::{
+        mpsc::{error::TryRecvError, UnboundedReceiver},
+        oneshot, watch, Semaphore,
+    },
 };
 use tokio_openssl::SslStream;
 use tokio_serde::{Deserializer, Serializer};
@@ -39,17 +42,22 @@ use tracing::{
     info, trace, warn, Instrument, Span,
 };
 
-use casper_types::{ProtocolVersion, PublicKey, TimeDiff};
+use casper_types::{ProtocolVersion, ProtocolVersionRange, PublicKey, TimeDiff};
 
 use super::{
+    admission::{Admission, AdmissionPermit},
     chain_info::ChainInfo,
     counting_format::{ConnectionId, Role},
+    encoding_version::EncodingVersion,
     error::{ConnectionError, IoError},
     event::{IncomingConnection, OutgoingConnection},
+    features::Features,
     full_transport,
     limiter::LimiterHandle,
-    message::ConsensusKeyPair,
+    message::{ConsensusKeyPair, NetworkKeyPair},
     message_pack_format::MessagePackFormat,
+    shaper::{Dequeued, TrafficShaper},
+    traffic::TrafficRegistry,
     EstimatorWeights, Event, FramedTransport, FullTransport, Message, Metrics, Payload, Transport,
 };
 use crate::{
@@ -61,6 +69,10 @@ use crate::{
     utils::display_error,
 };
 
+/// Maximum time an acknowledged incoming message is counted towards its sender's allowance of
+/// in-flight messages, in case its processing is never acknowledged.
+const ACKNOWLEDGEMENT_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// An item on the internal outgoing message queue.
 ///
 /// Contains a reference counted message and an optional responder to call once the message has been
@@ -77,6 +89,10 @@ struct HandshakeOutcome {
     peer_consensus_public_key: Option<PublicKey>,
     /// Holds the information whether the remote node is syncing.
     is_peer_syncing: bool,
+    /// The human-readable encoding version negotiated for this connection.
+    encoding_version: EncodingVersion,
+    /// The wire features negotiated for this connection.
+    features: Features,
 }
 
 /// Low-level TLS connection function.
@@ -159,6 +175,8 @@ where
             public_addr,
             peer_consensus_public_key,
             is_peer_syncing: is_syncing,
+            encoding_version: _,
+            features,
         }) => {
             if let Some(ref public_key) = peer_consensus_public_key {
                 Span::current().record("validator_id", &field::display(public_key));
@@ -172,9 +190,12 @@ where
             // Setup full framed transport, then close down receiving end of the transport.
             let full_transport = full_transport::<P>(
                 context.net_metrics.clone(),
+                context.peer_traffic.for_peer(peer_id),
                 connection_id,
                 framed_transport,
                 Role::Dialer,
+                features.contains(Features::COMPRESSION),
+                context.chain_info.maximum_net_message_size,
             );
             let (sink, _stream) = full_transport.split();
 
@@ -184,6 +205,7 @@ where
                 peer_consensus_public_key,
                 sink,
                 is_syncing,
+                supports_batched_gossip: features.contains(Features::BATCHED_GOSSIP),
             }
         }
         Err(error) => OutgoingConnection::Failed {
@@ -217,6 +239,8 @@ where
     pub(super) public_addr: SocketAddr,
     /// Optional set of consensus keys, to identify as a validator during handshake.
     pub(super) consensus_keys: Option<ConsensusKeyPair>,
+    /// Our network key and its endorsement, if configured, used instead of the consensus keys.
+    pub(super) network_keys: Option<NetworkKeyPair>,
     /// Timeout for handshake completion.
     pub(super) handshake_timeout: TimeDiff,
     /// Weights to estimate payloads with.
@@ -229,8 +253,18 @@ where
     pub(super) tarpit_chance: f32,
     /// Maximum number of demands allowed to be running at once. If 0, no limit is enforced.
     pub(super) max_in_flight_demands: usize,
+    /// Maximum number of acknowledged messages from a single peer allowed to be processed at once.
+    pub(super) max_in_flight_acknowledged: usize,
     /// Flag indicating whether this node is syncing.
     pub(super) is_syncing: AtomicBool,
+    /// The protocol version of the upgrade this node has staged, if any.
+    pub(super) staged_protocol_version: RwLock<Option<ProtocolVersion>>,
+    /// Admission control for incoming connections.
+    pub(super) admission: Arc<Admission>,
+    /// The wire features we support and advertise in our handshakes.
+    pub(super) features: Features,
+    /// Traffic exchanged with each peer, by kind of message.
+    pub(super) peer_traffic: TrafficRegistry,
 }
 
 impl<REv> NetworkContext<REv> {
@@ -249,6 +283,7 @@ async fn handle_incoming<P, REv>(
     context: Arc<NetworkContext<REv>>,
     stream: TcpStream,
     peer_addr: SocketAddr,
+    permit: AdmissionPermit,
 ) -> IncomingConnection<P>
 where
     REv: From<Event<P>> + 'static,
@@ -284,6 +319,8 @@ where
             public_addr,
             peer_consensus_public_key,
             is_peer_syncing: _,
+            encoding_version: _,
+            features,
         }) => {
             if let Some(ref public_key) = peer_consensus_public_key {
                 Span::current().record("validator_id", &field::display(public_key));
@@ -292,9 +329,12 @@ where
             // Establish full transport and close the receiving end.
             let full_transport = full_transport::<P>(
                 context.net_metrics.clone(),
+                context.peer_traffic.for_peer(peer_id),
                 connection_id,
                 framed_transport,
                 Role::Listener,
+                features.contains(Features::COMPRESSION),
+                context.chain_info.maximum_net_message_size,
             );
 
             let (_sink, stream) = full_transport.split();
@@ -305,6 +345,7 @@ where
                 peer_id,
                 peer_consensus_public_key,
                 stream,
+                permit,
             }
         }
         Err(error) => IncomingConnection::Failed {
@@ -394,8 +435,15 @@ where
     let handshake_message = context.chain_info.create_handshake::<P>(
         context.public_addr,
         context.consensus_keys.as_ref(),
+        context.network_keys.as_ref(),
         connection_id,
         context.is_syncing.load(Ordering::SeqCst),
+        context
+            .staged_protocol_version
+            .read()
+            .map(|staged_protocol_version| *staged_protocol_version)
+            .unwrap_or_default(),
+        context.features,
     );
 
     let serialized_handshake_message = Pin::new(&mut encoder)
@@ -435,6 +483,10 @@ where
         consensus_certificate,
         is_syncing,
         chainspec_hash,
+        staged_protocol_version,
+        network_certificate,
+        encoding_versions,
+        features: peer_features,
     } = remote_message
     {
         debug!(%protocol_version, "handshake received");
@@ -452,7 +504,7 @@ where
         // compatibility, so we allow only exact version matches.
         if protocol_version != context.chain_info.protocol_version {
             if let Some(threshold) = context.tarpit_version_threshold {
-                if protocol_version <= threshold {
+                if ProtocolVersionRange::at_most(threshold).contains(&protocol_version) {
                     let mut rng = crate::new_rng();
 
                     if rng.gen_bool(context.tarpit_chance as f64) {
@@ -477,12 +529,29 @@ where
             return Err(ConnectionError::WrongChainspecHash(peer_chainspec_hash));
         }
 
-        let peer_consensus_public_key = consensus_certificate
-            .map(|cert| {
+        // A network certificate, if present, takes precedence over a consensus certificate.
+        let peer_consensus_public_key = match network_certificate {
+            Some(cert) => Some(
                 cert.validate(connection_id)
-                    .map_err(ConnectionError::InvalidConsensusCertificate)
-            })
-            .transpose()?;
+                    .map_err(ConnectionError::InvalidNetworkCertificate)?,
+            ),
+            None => consensus_certificate
+                .map(|cert| {
+                    cert.validate(connection_id)
+                        .map_err(ConnectionError::InvalidConsensusCertificate)
+                })
+                .transpose()?,
+        };
+
+        if let Some(staged_protocol_version) = staged_protocol_version {
+            info!(%staged_protocol_version, "peer is ready to upgrade");
+        }
+
+        let encoding_version = EncodingVersion::negotiate(&encoding_versions);
+        debug!(%encoding_version, "negotiated encoding version");
+
+        let features = context.features.negotiate(peer_features);
+        debug!(%features, "negotiated wire features");
 
         let framed_transport = sink
             .reunite(stream)
@@ -493,6 +562,8 @@ where
             public_addr,
             peer_consensus_public_key,
             is_peer_syncing: is_syncing,
+            encoding_version,
+            features,
         })
     } else {
         // Received a non-handshake, this is an error.
@@ -521,6 +592,18 @@ pub(super) async fn server<P, REv>(
             // the queue.
             match listener.accept().await {
                 Ok((stream, peer_addr)) => {
+                    // Check the per-address limits before spending any effort on the handshakes.
+                    let permit = match context.admission.admit(peer_addr.ip(), Instant::now()) {
+                        Ok(permit) => permit,
+                        Err(rejection) => {
+                            debug!(%peer_addr, %rejection, "rejecting incoming connection");
+                            if let Some(net_metrics) = context.net_metrics.upgrade() {
+                                net_metrics.incoming_connections_rejected.inc();
+                            }
+                            continue;
+                        }
+                    };
+
                     // The span setup here is used throughout the entire lifetime of the connection.
                     let span =
                         error_span!("incoming", %peer_addr, peer_id=Empty, validator_id=Empty);
@@ -530,7 +613,7 @@ pub(super) async fn server<P, REv>(
                     tokio::spawn(
                         async move {
                             let incoming =
-                                handle_incoming(context.clone(), stream, peer_addr).await;
+                                handle_incoming(context.clone(), stream, peer_addr, permit).await;
                             context
                                 .event_queue
                                 .schedule(
@@ -575,12 +658,17 @@ pub(super) async fn server<P, REv>(
 
 /// Network message reader.
 ///
-/// Schedules all received messages until the stream is closed or an error occurs.
+/// Schedules all received messages until the stream is closed or an error occurs. The reader also
+/// stops once `evicted` resolves, which happens when its sender is fired or dropped, e.g. because
+/// the peer has been banned or evicted to make room for another peer.
+///
+/// Returns the error that caused reading to fail, if any.
 pub(super) async fn message_reader<REv, P>(
     context: Arc<NetworkContext<REv>>,
     mut stream: SplitStream<FullTransport<P>>,
     limiter: Box<dyn LimiterHandle>,
     mut close_incoming_receiver: watch::Receiver<()>,
+    evicted: oneshot::Receiver<()>,
     peer_id: NodeId,
     span: Span,
 ) -> io::Result<()>
@@ -589,6 +677,7 @@ where
     REv: From<Event<P>> + FromIncoming<P> + From<NetworkRequest<P>> + Send,
 {
     let demands_in_flight = Arc::new(Semaphore::new(context.max_in_flight_demands));
+    let acknowledged_in_flight = Arc::new(Semaphore::new(context.max_in_flight_acknowledged));
 
     let read_messages = async move {
         while let Some(msg_result) = stream.next().await {
@@ -666,23 +755,64 @@ where
                                 )
                                 .await;
 
-                            let queue_kind = if msg.is_low_priority() {
+                            let queue_kind = if msg.is_high_priority() {
+                                QueueKind::Consensus
+                            } else if msg.is_low_priority() {
                                 QueueKind::NetworkLowPriority
                             } else {
                                 QueueKind::NetworkIncoming
                             };
 
-                            context
-                                .event_queue
-                                .schedule(
-                                    Event::IncomingMessage {
-                                        peer_id: Box::new(peer_id),
-                                        msg: Box::new(msg),
-                                        span: span.clone(),
-                                    },
-                                    queue_kind,
-                                )
-                                .await;
+                            match msg.try_into_acknowledged(effect_builder, peer_id) {
+                                Ok((event, acknowledged)) => {
+                                    // Acquire a permit, which is only released once the message
+                                    // has been fully processed. If too many messages from this
+                                    // peer are still being processed, this will block, halting
+                                    // the reading of further messages until one is acknowledged.
+                                    let in_flight = acknowledged_in_flight
+                                        .clone()
+                                        .acquire_owned()
+                                        .await
+                                        // Note: As with demands, the semaphore is never closed.
+                                        .map_err(|_| {
+                                            io::Error::new(
+                                                io::ErrorKind::Other,
+                                                "acknowledgement semaphore closed unexpectedly",
+                                            )
+                                        })?;
+
+                                    tokio::spawn(async move {
+                                        if tokio::time::timeout(
+                                            ACKNOWLEDGEMENT_TIMEOUT,
+                                            acknowledged,
+                                        )
+                                        .await
+                                        .is_err()
+                                        {
+                                            debug!(
+                                                %peer_id,
+                                                "incoming message not acknowledged in time"
+                                            );
+                                        }
+                                        drop(in_flight);
+                                    });
+
+                                    context.event_queue.schedule::<REv>(event, queue_kind).await;
+                                }
+                                Err(msg) => {
+                                    context
+                                        .event_queue
+                                        .schedule(
+                                            Event::IncomingMessage {
+                                                peer_id: Box::new(peer_id),
+                                                msg: Box::new(msg),
+                                                span: span.clone(),
+                                            },
+                                            queue_kind,
+                                        )
+                                        .await;
+                                }
+                            }
                         }
                     }
                 }
@@ -699,12 +829,18 @@ where
     };
 
     let shutdown_messages = async move { while close_incoming_receiver.changed().await.is_ok() {} };
+    let shutdown_or_evicted = future::select(Box::pin(shutdown_messages), evicted);
 
-    // Now we can wait for either the `shutdown` channel's remote end to do be dropped or the
-    // while loop to terminate.
-    match future::select(Box::pin(shutdown_messages), Box::pin(read_messages)).await {
-        Either::Left(_) => info!("shutting down incoming connection message reader"),
-        Either::Right(_) => (),
+    // Now we can wait for either the `shutdown` channel's remote end to do be dropped, the peer to
+    // be evicted or the while loop to terminate.
+    match future::select(shutdown_or_evicted, Box::pin(read_messages)).await {
+        Either::Left((Either::Left(_), _)) => {
+            info!("shutting down incoming connection message reader")
+        }
+        Either::Left((Either::Right(_), _)) => {
+            info!("peer evicted, closing incoming connection message reader")
+        }
+        Either::Right((result, _)) => return result,
     }
 
     Ok(())
@@ -712,28 +848,75 @@ where
 
 /// Network message sender.
 ///
-/// Reads from a channel and sends all messages, until the stream is closed or an error occurs.
+/// Reads from a channel and sends all messages in the order decided by the traffic shaper, until
+/// the stream is closed or an error occurs.
 pub(super) async fn message_sender<P>(
     mut queue: UnboundedReceiver<MessageQueueItem<P>>,
     mut sink: SplitSink<FullTransport<P>, Arc<Message<P>>>,
     limiter: Box<dyn LimiterHandle>,
+    mut shaper: TrafficShaper<(MessageQueueItem<P>, u32)>,
     counter: IntGauge,
 ) where
     P: Payload,
 {
-    while let Some((message, opt_responder)) = queue.recv().await {
-        counter.dec();
+    let mut queue_open = true;
+
+    'send: loop {
+        // Hand every message queued so far to the shaper before picking the next one to send.
+        while queue_open {
+            match queue.try_recv() {
+                Ok(item) => {
+                    if enqueue_message(&mut shaper, item, &counter).is_err() {
+                        break 'send;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => queue_open = false,
+            }
+        }
 
-        let estimated_wire_size = match BincodeFormat::default().0.serialized_size(&*message) {
-            Ok(size) => size as u32,
-            Err(error) => {
-                error!(
-                    error = display_error(&error),
-                    "failed to get serialized size of outgoing message, closing outgoing connection"
-                );
-                break;
+        let ((message, opt_responder), estimated_wire_size) = match shaper.dequeue(Instant::now()) {
+            Dequeued::Ready(item) => item,
+            Dequeued::Throttled(until) => {
+                // Wait for the bandwidth limit, unless a message which could be sent earlier
+                // arrives in the meantime.
+                let maybe_item = if queue_open {
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(until.into()) => None,
+                        maybe_item = queue.recv() => Some(maybe_item),
+                    }
+                } else {
+                    tokio::time::sleep_until(until.into()).await;
+                    None
+                };
+                match maybe_item {
+                    Some(Some(item)) => {
+                        if enqueue_message(&mut shaper, item, &counter).is_err() {
+                            break;
+                        }
+                    }
+                    Some(None) => queue_open = false,
+                    None => (),
+                }
+                continue;
+            }
+            Dequeued::Empty => {
+                if !queue_open {
+                    break;
+                }
+                match queue.recv().await {
+                    Some(item) => {
+                        if enqueue_message(&mut shaper, item, &counter).is_err() {
+                            break;
+                        }
+                    }
+                    None => queue_open = false,
+                }
+                continue;
             }
         };
+        counter.dec();
+
         limiter.request_allowance(estimated_wire_size).await;
 
         let mut outcome = sink.send(message).await;
@@ -751,14 +934,49 @@ pub(super) async fn message_sender<P>(
                 err = display_error(err),
                 "message send failed, closing outgoing connection"
             );
-
-            // To ensure, metrics are up to date, we close the queue and drain it.
-            queue.close();
-            while queue.recv().await.is_some() {
-                counter.dec();
-            }
-
             break;
         };
     }
+
+    // To ensure, metrics are up to date, we close the queue and drain it, discarding the messages
+    // still held by the shaper.
+    queue.close();
+    while queue.recv().await.is_some() {
+        counter.dec();
+    }
+    counter.sub(shaper.len() as i64);
+}
+
+/// Hands an outgoing message to the traffic shaper, dropping it if the queue of its kind is full.
+///
+/// Returns an error if the size of the message cannot be estimated, in which case the connection
+/// should be closed.
+fn enqueue_message<P>(
+    shaper: &mut TrafficShaper<(MessageQueueItem<P>, u32)>,
+    item: MessageQueueItem<P>,
+    counter: &IntGauge,
+) -> Result<(), ()>
+where
+    P: Payload,
+{
+    let estimated_wire_size = match BincodeFormat::default().0.serialized_size(&*item.0) {
+        Ok(size) => size as u32,
+        Err(error) => {
+            error!(
+                error = display_error(&error),
+                "failed to get serialized size of outgoing message, closing outgoing connection"
+            );
+            counter.dec();
+            return Err(());
+        }
+    };
+    let kind = item.0.classify();
+    if shaper
+        .enqueue(kind, estimated_wire_size, (item, estimated_wire_size))
+        .is_err()
+    {
+        debug!(%kind, "dropped outgoing message, traffic shaper queue full");
+        counter.dec();
+    }
+    Ok(())
 }
@@ -26,13 +26,13 @@ use crate::{
         Component,
     },
     effect::{
-        announcements::{ControlAnnouncement, GossiperAnnouncement},
+        announcements::{BlocklistAnnouncement, ControlAnnouncement, GossiperAnnouncement},
         incoming::GossiperIncoming,
         requests::{
             BeginGossipRequest, ChainspecLoaderRequest, ContractRuntimeRequest, NetworkRequest,
-            StorageRequest,
+            StateStoreRequest, StorageRequest,
         },
-        EffectBuilder, Effects,
+        EffectBuilder, EffectExt, Effects,
     },
     protocol,
     reactor::{self, EventQueueHandle, Finalize, Reactor, Runner},
@@ -60,6 +60,10 @@ enum Event {
     AddressGossiperAnnouncement(#[serde(skip_serializing)] GossiperAnnouncement<GossipedAddress>),
     #[from]
     BeginAddressGossipRequest(BeginGossipRequest<GossipedAddress>),
+    #[from]
+    BlocklistAnnouncement(#[serde(skip_serializing)] BlocklistAnnouncement),
+    #[from]
+    StateStoreRequest(StateStoreRequest),
     /// An incoming network message with an address gossiper protocol message.
     AddressGossiperIncoming(GossiperIncoming<GossipedAddress>),
 }
@@ -172,6 +176,8 @@ impl Payload for Message {
 struct TestReactor {
     net: SmallNetwork<Event, Message>,
     address_gossiper: Gossiper<GossipedAddress, Event>,
+    /// In-memory replacement of the state store.
+    state: HashMap<Vec<u8>, Vec<u8>>,
 }
 
 impl Reactor for TestReactor {
@@ -195,13 +201,19 @@ impl Reactor for TestReactor {
             ChainInfo::create_for_testing(),
         )?;
         let gossiper_config = gossiper::Config::new_with_small_timeouts();
-        let address_gossiper =
-            Gossiper::new_for_complete_items("address_gossiper", gossiper_config, registry)?;
+        // The gossiper's effects load and persist its table, which is not needed in these tests.
+        let (address_gossiper, _) = Gossiper::new_for_complete_items(
+            "address_gossiper",
+            gossiper_config,
+            EffectBuilder::new(event_queue),
+            registry,
+        )?;
 
         Ok((
             TestReactor {
                 net,
                 address_gossiper,
+                state: HashMap::new(),
             },
             reactor::wrap_effects(Event::SmallNet, effects),
         ))
@@ -250,11 +262,30 @@ impl Reactor for TestReactor {
                 self.address_gossiper
                     .handle_event(effect_builder, rng, ev.into()),
             ),
+            Event::BlocklistAnnouncement(ann) => reactor::wrap_effects(
+                Event::SmallNet,
+                self.net.handle_event(effect_builder, rng, ann.into()),
+            ),
             Event::AddressGossiperIncoming(incoming) => reactor::wrap_effects(
                 Event::AddressGossiper,
                 self.address_gossiper
                     .handle_event(effect_builder, rng, incoming.into()),
             ),
+            Event::StateStoreRequest(StateStoreRequest::Save {
+                key,
+                data,
+                responder,
+            }) => {
+                self.state.insert(key.into_owned(), data);
+                responder.respond(()).ignore()
+            }
+            Event::StateStoreRequest(StateStoreRequest::Load { key, responder }) => {
+                responder.respond(self.state.get(&*key).cloned()).ignore()
+            }
+            Event::StateStoreRequest(StateStoreRequest::Delete { key, responder }) => {
+                self.state.remove(&*key);
+                responder.respond(()).ignore()
+            }
         }
     }
 
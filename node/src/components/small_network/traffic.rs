@@ -0,0 +1,151 @@
+//! Per-peer traffic accounting.
+//!
+//! Unlike the networking metrics, which aggregate over all connections, the counters kept here are
+//! broken down by peer, so that operators can see which peers a node is exchanging what with.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+    sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+
+use super::MessageKind;
+use crate::types::NodeId;
+
+/// Messages and bytes exchanged with a single peer for one kind of message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct KindTraffic {
+    /// Number of messages received.
+    pub(crate) messages_in: u64,
+    /// Number of bytes received.
+    pub(crate) bytes_in: u64,
+    /// Number of messages sent.
+    pub(crate) messages_out: u64,
+    /// Number of bytes sent.
+    pub(crate) bytes_out: u64,
+}
+
+impl Display for KindTraffic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "in {} msgs / {} bytes, out {} msgs / {} bytes",
+            self.messages_in, self.bytes_in, self.messages_out, self.bytes_out
+        )
+    }
+}
+
+/// Traffic exchanged with a single peer over all of its connections.
+#[derive(Debug, Default)]
+pub(super) struct PeerTraffic {
+    by_kind: Mutex<HashMap<MessageKind, KindTraffic>>,
+}
+
+impl PeerTraffic {
+    /// Records a message received from the peer.
+    pub(super) fn record_in(&self, kind: MessageKind, size: u64) {
+        let mut by_kind = self.by_kind.lock().expect("peer traffic lock poisoned");
+        let traffic = by_kind.entry(kind).or_default();
+        traffic.messages_in += 1;
+        traffic.bytes_in += size;
+    }
+
+    /// Records a message sent to the peer.
+    pub(super) fn record_out(&self, kind: MessageKind, size: u64) {
+        let mut by_kind = self.by_kind.lock().expect("peer traffic lock poisoned");
+        let traffic = by_kind.entry(kind).or_default();
+        traffic.messages_out += 1;
+        traffic.bytes_out += size;
+    }
+
+    /// Returns the traffic so far, keyed by the name of the kind of message.
+    pub(super) fn snapshot(&self) -> BTreeMap<String, KindTraffic> {
+        self.by_kind
+            .lock()
+            .expect("peer traffic lock poisoned")
+            .iter()
+            .map(|(kind, traffic)| (kind.to_string(), *traffic))
+            .collect()
+    }
+}
+
+/// The traffic counters of all peers, shared between the connection tasks.
+#[derive(Debug, Default)]
+pub(super) struct TrafficRegistry {
+    peers: Mutex<HashMap<NodeId, Arc<PeerTraffic>>>,
+}
+
+impl TrafficRegistry {
+    /// Returns the counters of the given peer, creating them if the peer is new.
+    pub(super) fn for_peer(&self, peer_id: NodeId) -> Arc<PeerTraffic> {
+        self.peers
+            .lock()
+            .expect("traffic registry lock poisoned")
+            .entry(peer_id)
+            .or_default()
+            .clone()
+    }
+
+    /// Returns the traffic exchanged with the given peer so far, if any.
+    pub(super) fn snapshot(&self, peer_id: &NodeId) -> BTreeMap<String, KindTraffic> {
+        self.peers
+            .lock()
+            .expect("traffic registry lock poisoned")
+            .get(peer_id)
+            .map(|traffic| traffic.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// Forgets the counters of peers which no longer have any connection holding on to them.
+    pub(super) fn prune(&self) {
+        self.peers
+            .lock()
+            .expect("traffic registry lock poisoned")
+            .retain(|_, traffic| Arc::strong_count(traffic) > 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn should_count_traffic_per_peer_and_kind() {
+        let mut rng = TestRng::new();
+        let registry = TrafficRegistry::default();
+        let peer_a = NodeId::random(&mut rng);
+        let peer_b = NodeId::random(&mut rng);
+
+        let connection_a = registry.for_peer(peer_a);
+        connection_a.record_in(MessageKind::Consensus, 100);
+        connection_a.record_out(MessageKind::Consensus, 50);
+        registry
+            .for_peer(peer_a)
+            .record_out(MessageKind::TrieTransfer, 1000);
+        let connection_b = registry.for_peer(peer_b);
+        connection_b.record_in(MessageKind::Protocol, 10);
+
+        let traffic = registry.snapshot(&peer_a);
+        assert_eq!(traffic.len(), 2);
+        assert_eq!(
+            traffic["consensus"],
+            KindTraffic {
+                messages_in: 1,
+                bytes_in: 100,
+                messages_out: 1,
+                bytes_out: 50,
+            }
+        );
+        assert_eq!(traffic["trie_transfer"].bytes_out, 1000);
+
+        // Only counters still referenced by a connection survive pruning.
+        drop(connection_a);
+        registry.prune();
+        assert!(registry.snapshot(&peer_a).is_empty());
+        assert_eq!(registry.snapshot(&peer_b)["protocol"].messages_in, 1);
+    }
+}
@@ -7,7 +7,8 @@
 //! * storing and loading deploys,
 //! * [temporary until refactored] holding `DeployMetadata` for each deploy,
 //! * keeping an index of blocks by height and
-//! * [unimplemented] managing disk usage by pruning blocks and deploys from storage.
+//! * managing disk usage by pruning the deploys and execution results of blocks outside the
+//!   configured retention window.
 //!
 //! Any I/O performed by the component is done on the event handling thread, this is on purpose as
 //! the assumption is that caching by LMDB will offset any gains from offloading it onto a separate
@@ -30,6 +31,7 @@
 //! The storage component itself is panic free and in general reports three classes of errors:
 //! Corruption, temporary resource exhaustion and potential bugs.
 
+pub(crate) mod check;
 pub(crate) mod disjoint_sequences;
 mod error;
 mod lmdb_ext;
@@ -65,7 +67,8 @@ use tracing::{debug, error, info, warn};
 use casper_hashing::Digest;
 use casper_types::{
     bytesrepr::{FromBytes, ToBytes},
-    EraId, ExecutionResult, ProtocolVersion, TimeDiff, Transfer, Transform,
+    EraId, ExecutionResult, ProtocolVersion, TimeDiff, TransactionV2, TransactionV2Hash,
+    TransferV2, Transform, U512,
 };
 
 // The reactor! macro needs this in the fetcher tests
@@ -82,9 +85,10 @@ use crate::{
     reactor::ReactorEvent,
     types::{
         AvailableBlockRange, Block, BlockAndDeploys, BlockBody, BlockHash, BlockHashAndHeight,
-        BlockHeader, BlockHeaderWithMetadata, BlockHeadersBatch, BlockHeadersBatchId,
-        BlockSignatures, BlockWithMetadata, Deploy, DeployHash, DeployMetadata, DeployMetadataExt,
-        DeployWithFinalizedApprovals, FinalizedApprovals, FinalizedApprovalsWithId, Item, NodeId,
+        BlockHeader, BlockHeaderWithMetadata, BlockHeadersBatch, BlockHeadersBatchId, BlockSignals,
+        BlockSignatures, BlockSummary, BlockWithMetadata, Deploy, DeployHash, DeployMetadata,
+        DeployMetadataExt, DeployWithFinalizedApprovals, EraRewards, FinalizedApprovals,
+        FinalizedApprovalsWithId, Item, NodeId,
     },
     utils::{display_error, WithDir},
     NodeRng,
@@ -113,10 +117,14 @@ const DEFAULT_MAX_DEPLOY_STORE_SIZE: usize = 300 * GIB;
 const DEFAULT_MAX_DEPLOY_METADATA_STORE_SIZE: usize = 300 * GIB;
 /// Default max state store size.
 const DEFAULT_MAX_STATE_STORE_SIZE: usize = 10 * GIB;
+/// Default interval between pruning history outside the retention window.
+const DEFAULT_PRUNING_INTERVAL_SECS: u32 = 3600;
 /// Maximum number of allowed dbs.
-const MAX_DB_COUNT: u32 = 8;
+const MAX_DB_COUNT: u32 = 12;
 /// Key under which completed blocks are to be stored.
 const COMPLETED_BLOCKS_STORAGE_KEY: &[u8] = b"completed_blocks_disjoint_sequences";
+/// Key under which the height below which all blocks have been pruned is to be stored.
+const PRUNED_BELOW_HEIGHT_STORAGE_KEY: &[u8] = b"pruned_below_height";
 
 /// OS-specific lmdb flags.
 #[cfg(not(target_os = "macos"))]
@@ -130,6 +138,9 @@ const OS_FLAGS: EnvironmentFlags = EnvironmentFlags::empty();
 const _STORAGE_EVENT_SIZE: usize = mem::size_of::<Event>();
 const_assert!(_STORAGE_EVENT_SIZE <= 96);
 
+/// The files of the global state, stored in `Config::global_state_path` if set.
+const GLOBAL_STATE_FILES: [&str; 2] = ["data.lmdb", "data.lmdb-lock"];
+
 const STORAGE_FILES: [&str; 5] = [
     "data.lmdb",
     "data.lmdb-lock",
@@ -143,6 +154,8 @@ const STORAGE_FILES: [&str; 5] = [
 pub struct Storage {
     /// Storage location.
     root: PathBuf,
+    /// Location of the global state, which is `root` unless configured otherwise.
+    global_state_root: PathBuf,
     /// Environment holding LMDB databases.
     #[data_size(skip)]
     env: Environment,
@@ -170,6 +183,19 @@ pub struct Storage {
     /// The finalized approvals database.
     #[data_size(skip)]
     finalized_approvals_db: Database,
+    /// The database of transactions in the versioned format.
+    #[data_size(skip)]
+    transaction_v2_db: Database,
+    /// The database of rewards distributed at the end of each era, keyed by era ID.
+    #[data_size(skip)]
+    era_rewards_db: Database,
+    /// The database of the stakes held in each era and the validators evicted at its end, keyed by
+    /// era ID.
+    #[data_size(skip)]
+    era_stakes_db: Database,
+    /// The database of signaling bits set by the proposers of finalized blocks, keyed by height.
+    #[data_size(skip)]
+    block_signals_db: Database,
     /// A map of block height to block ID.
     block_height_index: BTreeMap<u64, BlockHash>,
     /// A map of era ID to switch block ID.
@@ -184,6 +210,23 @@ pub struct Storage {
     ///
     /// Keyed by serialized item ID, contains the serialized item.
     serialized_item_pool: ObjectPool<Box<[u8]>>,
+    /// How much history is retained.
+    retention: Retention,
+    /// How often history outside the retention window is pruned.
+    pruning_interval: TimeDiff,
+    /// The height below which the deploys and execution results of all blocks have been pruned.
+    pruned_below_height: u64,
+}
+
+/// The outcome of pruning a batch of blocks outside the retention window.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct PrunedHistory {
+    /// The number of blocks whose deploys and execution results were pruned.
+    pub(crate) pruned_blocks: u64,
+    /// The number of deploys pruned, along with their execution results.
+    pub(crate) pruned_deploys: u64,
+    /// The state root hashes of all retained blocks, if no further blocks remain to be pruned.
+    pub(crate) retained_state_roots: Option<Vec<Digest>>,
 }
 
 /// A storage component event.
@@ -295,6 +338,7 @@ impl Storage {
         }
 
         root = network_subdir;
+        let global_state_root = global_state_root(cfg, network_name)?;
 
         // Calculate the upper bound for the memory map that is potentially used.
         let total_size = config
@@ -327,6 +371,10 @@ impl Storage {
         let finalized_approvals_db =
             env.create_db(Some("finalized_approvals"), DatabaseFlags::empty())?;
         let block_body_db = env.create_db(Some("block_body"), DatabaseFlags::empty())?;
+        let transaction_v2_db = env.create_db(Some("transactions_v2"), DatabaseFlags::empty())?;
+        let era_rewards_db = env.create_db(Some("era_rewards"), DatabaseFlags::empty())?;
+        let era_stakes_db = env.create_db(Some("era_stakes"), DatabaseFlags::empty())?;
+        let block_signals_db = env.create_db(Some("block_signals"), DatabaseFlags::empty())?;
 
         // We now need to restore the block-height index. Log messages allow timing here.
         info!("indexing block store");
@@ -404,6 +452,7 @@ impl Storage {
 
         let mut component = Self {
             root,
+            global_state_root,
             env,
             block_header_db,
             block_body_db,
@@ -413,14 +462,29 @@ impl Storage {
             transfer_db,
             state_store_db,
             finalized_approvals_db,
+            transaction_v2_db,
+            era_rewards_db,
+            era_stakes_db,
+            block_signals_db,
             block_height_index,
             switch_block_era_id_index,
             deploy_hash_index,
             completed_blocks: Default::default(),
             enable_mem_deduplication: config.enable_mem_deduplication,
             serialized_item_pool: ObjectPool::new(config.mem_pool_prune_interval),
+            retention: config.retention,
+            pruning_interval: config.pruning_interval,
+            pruned_below_height: 0,
         };
 
+        if let Some(raw) =
+            component.read_state_store(&Cow::Borrowed(PRUNED_BELOW_HEIGHT_STORAGE_KEY))?
+        {
+            let (pruned_below_height, _) = u64::from_bytes(&raw)
+                .map_err(FatalStorageError::UnexpectedDeserializationFailure)?;
+            component.pruned_below_height = pruned_below_height;
+        }
+
         match component.read_state_store(&Cow::Borrowed(COMPLETED_BLOCKS_STORAGE_KEY))? {
             Some(raw) => {
                 let (mut sequences, _) = DisjointSequences::from_vec(raw)
@@ -478,6 +542,10 @@ impl Storage {
                 let bytes = self.read_state_store(&key)?;
                 Ok(responder.respond(bytes).ignore())
             }
+            StateStoreRequest::Delete { key, responder } => {
+                self.delete_state_store(key)?;
+                Ok(responder.respond(()).ignore())
+            }
         }
     }
 
@@ -516,11 +584,30 @@ impl Storage {
         Ok(())
     }
 
+    /// Deletes a key from the state storage database, if present.
+    fn delete_state_store(&self, key: Cow<'static, [u8]>) -> Result<(), FatalStorageError> {
+        let mut txn = self.env.begin_rw_txn()?;
+        del_if_exists(&mut txn, self.state_store_db, &key)?;
+        txn.commit()?;
+
+        Ok(())
+    }
+
     /// Returns the path to the storage folder.
     pub(crate) fn root_path(&self) -> &Path {
         &self.root
     }
 
+    /// Returns the path to the global state folder.
+    pub(crate) fn global_state_path(&self) -> &Path {
+        &self.global_state_root
+    }
+
+    /// Flushes the database to disk.
+    pub(crate) fn flush(&self) -> Result<(), lmdb::Error> {
+        self.env.sync(true)
+    }
+
     fn handle_net_request_incoming<REv>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -752,9 +839,115 @@ impl Storage {
                     .respond(self.get_transfers(&mut txn, &block_hash)?)
                     .ignore()
             }
+            StorageRequest::GetBlockSummaries {
+                block_heights,
+                only_from_available_block_range,
+                responder,
+            } => {
+                let mut txn = self.env.begin_ro_txn()?;
+                let mut block_summaries = Vec::new();
+                for block_height in block_heights {
+                    if !(self.should_return_block(block_height, only_from_available_block_range)?) {
+                        continue;
+                    }
+                    if let Some(block) = self.get_block_by_height(&mut txn, block_height)? {
+                        block_summaries.push(self.get_block_summary(&mut txn, &block)?);
+                    }
+                }
+                responder.respond(block_summaries).ignore()
+            }
+            StorageRequest::PutBlockSignals {
+                block_signals,
+                responder,
+            } => {
+                let mut txn = self.env.begin_rw_txn()?;
+                txn.put_value(
+                    self.block_signals_db,
+                    &block_signals.height.to_le_bytes(),
+                    &*block_signals,
+                    true,
+                )?;
+                txn.commit()?;
+                responder.respond(()).ignore()
+            }
+            StorageRequest::GetBlockSignals {
+                block_heights,
+                responder,
+            } => {
+                let mut txn = self.env.begin_ro_txn()?;
+                let mut block_signals = Vec::new();
+                for block_height in block_heights {
+                    if let Some(signals) =
+                        txn.get_value(self.block_signals_db, &block_height.to_le_bytes())?
+                    {
+                        block_signals.push(signals);
+                    }
+                }
+                responder.respond(block_signals).ignore()
+            }
+            StorageRequest::PutEraRewards {
+                era_rewards,
+                responder,
+            } => {
+                let mut txn = self.env.begin_rw_txn()?;
+                let era_id = era_rewards.era_id.value();
+                txn.put_value(
+                    self.era_rewards_db,
+                    &era_id.to_le_bytes(),
+                    &*era_rewards,
+                    true,
+                )?;
+                txn.commit()?;
+                responder.respond(()).ignore()
+            }
+            StorageRequest::GetEraRewards { era_ids, responder } => {
+                let mut txn = self.env.begin_ro_txn()?;
+                let mut era_rewards = Vec::new();
+                for era_id in era_ids {
+                    if let Some(rewards) =
+                        txn.get_value(self.era_rewards_db, &era_id.to_le_bytes())?
+                    {
+                        era_rewards.push(rewards);
+                    }
+                }
+                responder.respond(era_rewards).ignore()
+            }
+            StorageRequest::PutEraStakes {
+                era_stakes,
+                responder,
+            } => {
+                let mut txn = self.env.begin_rw_txn()?;
+                let era_id = era_stakes.era_id.value();
+                txn.put_value(
+                    self.era_stakes_db,
+                    &era_id.to_le_bytes(),
+                    &*era_stakes,
+                    true,
+                )?;
+                txn.commit()?;
+                responder.respond(()).ignore()
+            }
+            StorageRequest::GetEraStakes { era_ids, responder } => {
+                let mut txn = self.env.begin_ro_txn()?;
+                let mut era_stakes = Vec::new();
+                for era_id in era_ids {
+                    if let Some(stakes) =
+                        txn.get_value(self.era_stakes_db, &era_id.to_le_bytes())?
+                    {
+                        era_stakes.push(stakes);
+                    }
+                }
+                responder.respond(era_stakes).ignore()
+            }
             StorageRequest::PutDeploy { deploy, responder } => {
                 responder.respond(self.put_deploy(&*deploy)?).ignore()
             }
+            StorageRequest::GetTransactionV2 {
+                transaction_hash,
+                responder,
+            } => responder
+                .respond(self.read_transaction_v2_by_hash(transaction_hash)?)
+                .ignore(),
             StorageRequest::GetDeploys {
                 deploy_hashes,
                 responder,
@@ -776,7 +969,7 @@ impl Storage {
             } => {
                 let mut txn = self.env.begin_rw_txn()?;
 
-                let mut transfers: Vec<Transfer> = vec![];
+                let mut transfers: Vec<TransferV2> = vec![];
 
                 for (deploy_hash, execution_result) in execution_results {
                     let mut metadata = self
@@ -794,8 +987,12 @@ impl Storage {
 
                     if let ExecutionResult::Success { effect, .. } = execution_result.clone() {
                         for transform_entry in effect.transforms {
-                            if let Transform::WriteTransfer(transfer) = transform_entry.transform {
-                                transfers.push(transfer);
+                            match transform_entry.transform {
+                                Transform::WriteTransfer(transfer) => {
+                                    transfers.push(TransferV2::from(transfer))
+                                }
+                                Transform::WriteTransferV2(transfer) => transfers.push(transfer),
+                                _ => (),
                             }
                         }
                     }
@@ -891,9 +1088,11 @@ impl Storage {
                     Some(signatures) => signatures,
                     None => BlockSignatures::new(block_hash, block.header().era_id()),
                 };
-                if block_signatures.verify().is_err() {
+                // Stored signatures were checked against the signing context when added, so only
+                // check they are valid signatures at all.
+                if block_signatures.verify(false).is_err() {
                     error!(?block, "invalid block signatures for block");
-                    debug_assert!(block_signatures.verify().is_ok());
+                    debug_assert!(block_signatures.verify(false).is_ok());
                     return Ok(responder.respond(None).ignore());
                 }
                 responder
@@ -937,6 +1136,7 @@ impl Storage {
                 only_from_available_block_range,
                 responder,
             } => {
+                let block_height = block_height.value();
                 if !(self.should_return_block(block_height, only_from_available_block_range)?) {
                     return Ok(responder.respond(None).ignore());
                 }
@@ -968,6 +1168,7 @@ impl Storage {
                 only_from_available_block_range,
                 responder,
             } => {
+                let block_height = block_height.value();
                 if !(self.should_return_block(block_height, only_from_available_block_range)?) {
                     return Ok(responder.respond(None).ignore());
                 }
@@ -1076,7 +1277,7 @@ impl Storage {
                 let mut txn = self.env.begin_ro_txn()?;
                 let result = self.get_block_header_by_height_restricted(
                     &mut txn,
-                    block_height,
+                    block_height.value(),
                     only_from_available_block_range,
                 )?;
                 responder.respond(result).ignore()
@@ -1125,6 +1326,10 @@ impl Storage {
             } => responder
                 .respond(self.read_block_headers_batch(&block_headers_id)?)
                 .ignore(),
+            StorageRequest::PruneHistory {
+                max_blocks,
+                responder,
+            } => responder.respond(self.prune_history(max_blocks)?).ignore(),
         })
     }
 
@@ -1142,6 +1347,124 @@ impl Storage {
         Ok(responder.respond(()).ignore())
     }
 
+    /// Returns how often history outside the retention window should be pruned, or `None` if all
+    /// history is retained.
+    pub(crate) fn pruning_interval(&self) -> Option<TimeDiff> {
+        match self.retention {
+            Retention::Archive => None,
+            Retention::RecentEras(_) => Some(self.pruning_interval),
+        }
+    }
+
+    /// Prunes the deploys and execution results of up to `max_blocks` blocks outside the
+    /// retention window, keeping their headers and bodies.
+    ///
+    /// Returns `None` if all history is retained.
+    fn prune_history(
+        &mut self,
+        max_blocks: u64,
+    ) -> Result<Option<PrunedHistory>, FatalStorageError> {
+        let recent_eras = match self.retention {
+            Retention::Archive => return Ok(None),
+            Retention::RecentEras(recent_eras) => recent_eras.max(1),
+        };
+        let cutoff_height = self.retention_cutoff_height(recent_eras)?;
+
+        let to_prune: Vec<(u64, BlockHash)> = self
+            .block_height_index
+            .range(self.pruned_below_height..cutoff_height.max(self.pruned_below_height))
+            .take(max_blocks as usize)
+            .map(|(height, block_hash)| (*height, *block_hash))
+            .collect();
+
+        let mut pruned_history = PrunedHistory::default();
+        let mut txn = self.env.begin_rw_txn()?;
+        for (_, block_hash) in &to_prune {
+            let block_header = match self.get_single_block_header(&mut txn, block_hash)? {
+                Some(block_header) => block_header,
+                None => continue,
+            };
+            if let Some(block_body) =
+                get_body_for_block_header(&mut txn, &block_header, self.block_body_db)?
+            {
+                for deploy_hash in block_body
+                    .deploy_hashes()
+                    .iter()
+                    .chain(block_body.transfer_hashes())
+                {
+                    // The block containing each deploy is still indexed, only the deploy itself and
+                    // its execution results are deleted.
+                    del_if_exists(&mut txn, self.deploy_db, deploy_hash)?;
+                    del_if_exists(&mut txn, self.deploy_metadata_db, deploy_hash)?;
+                    del_if_exists(&mut txn, self.finalized_approvals_db, deploy_hash)?;
+                    pruned_history.pruned_deploys += 1;
+                }
+            }
+            del_if_exists(&mut txn, self.transfer_db, block_hash)?;
+            pruned_history.pruned_blocks += 1;
+        }
+        txn.commit()?;
+
+        self.pruned_below_height = match to_prune.last() {
+            Some((height, _)) if (to_prune.len() as u64) == max_blocks => height + 1,
+            _ => cutoff_height.max(self.pruned_below_height),
+        };
+        self.write_state_store(
+            Cow::Borrowed(PRUNED_BELOW_HEIGHT_STORAGE_KEY),
+            &self
+                .pruned_below_height
+                .to_bytes()
+                .map_err(FatalStorageError::UnexpectedSerializationFailure)?,
+        )?;
+        self.completed_blocks.remove_below(self.pruned_below_height);
+        self.persist_completed_blocks()?;
+
+        if self.pruned_below_height >= cutoff_height {
+            let mut txn = self.env.begin_ro_txn()?;
+            let mut retained_state_roots = Vec::new();
+            for block_hash in self
+                .block_height_index
+                .range(cutoff_height..)
+                .map(|(_, hash)| hash)
+            {
+                if let Some(block_header) = self.get_single_block_header(&mut txn, block_hash)? {
+                    retained_state_roots.push(*block_header.state_root_hash());
+                }
+            }
+            pruned_history.retained_state_roots = Some(retained_state_roots);
+        }
+
+        if pruned_history.pruned_blocks > 0 {
+            info!(
+                pruned_blocks = pruned_history.pruned_blocks,
+                pruned_deploys = pruned_history.pruned_deploys,
+                pruned_below_height = self.pruned_below_height,
+                "pruned history outside the retention window"
+            );
+        }
+
+        Ok(Some(pruned_history))
+    }
+
+    /// Returns the height of the lowest block within the `recent_eras` most recent eras.
+    ///
+    /// Returns 0 if the switch block of the era preceding the retention window is not known.
+    fn retention_cutoff_height(&self, recent_eras: u64) -> Result<u64, FatalStorageError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let highest_era_id = match self.get_highest_block_header(&mut txn)? {
+            Some(block_header) => block_header.era_id(),
+            None => return Ok(0),
+        };
+        let first_retained_era_id = highest_era_id.saturating_sub(recent_eras - 1);
+        let last_pruned_era_id = match first_retained_era_id.checked_sub(1) {
+            Some(era_id) => era_id,
+            None => return Ok(0),
+        };
+        Ok(self
+            .get_switch_block_header_by_era_id(&mut txn, last_pruned_era_id)?
+            .map_or(0, |block_header| block_header.height() + 1))
+    }
+
     /// Persists the completed blocks disjoint sequences state to the database.
     fn persist_completed_blocks(&mut self) -> Result<(), FatalStorageError> {
         let serialized = self
@@ -1606,7 +1929,7 @@ impl Storage {
     }
 
     /// Retrieves a block header by hash.
-    fn read_block_header_by_hash(
+    pub(crate) fn read_block_header_by_hash(
         &self,
         block_hash: &BlockHash,
     ) -> Result<Option<BlockHeader>, FatalStorageError> {
@@ -1692,10 +2015,40 @@ impl Storage {
         &self,
         txn: &mut Tx,
         block_hash: &BlockHash,
-    ) -> Result<Option<Vec<Transfer>>, FatalStorageError> {
+    ) -> Result<Option<Vec<TransferV2>>, FatalStorageError> {
         Ok(txn.get_value(self.transfer_db, block_hash)?)
     }
 
+    /// Computes the summary of a block from its stored execution results and transfers.
+    fn get_block_summary<Tx: Transaction>(
+        &self,
+        txn: &mut Tx,
+        block: &Block,
+    ) -> Result<BlockSummary, FatalStorageError> {
+        let block_hash = block.hash();
+        let mut total_gas = U512::zero();
+        for deploy_hash in block
+            .deploy_hashes()
+            .iter()
+            .chain(block.transfer_hashes().iter())
+        {
+            let execution_result = self
+                .get_deploy_metadata(txn, deploy_hash)?
+                .and_then(|mut metadata| metadata.execution_results.remove(block_hash));
+            match execution_result {
+                Some(ExecutionResult::Success { cost, .. })
+                | Some(ExecutionResult::Failure { cost, .. }) => total_gas += cost,
+                None => {}
+            }
+        }
+        let total_transferred = self
+            .get_transfers(txn, block_hash)?
+            .unwrap_or_default()
+            .iter()
+            .fold(U512::zero(), |total, transfer| total + transfer.amount);
+        Ok(BlockSummary::new(block, total_gas, total_transferred))
+    }
+
     /// Retrieves block signatures for a block with a given block hash.
     fn get_block_signatures<Tx: Transaction>(
         &self,
@@ -1723,6 +2076,15 @@ impl Storage {
         Ok(txn.get_value(self.deploy_db, &deploy_hash)?)
     }
 
+    /// Directly returns a transaction in the versioned format from internal store.
+    pub fn read_transaction_v2_by_hash(
+        &self,
+        transaction_hash: TransactionV2Hash,
+    ) -> Result<Option<TransactionV2>, FatalStorageError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        Ok(txn.get_value_bytesrepr(self.transaction_v2_db, &transaction_hash)?)
+    }
+
     /// Directly returns all deploys or None if any is missing.
     fn read_deploys<'a, I: Iterator<Item = &'a DeployHash> + 'a>(
         &self,
@@ -1997,6 +2359,87 @@ fn move_storage_files_to_network_subdir(
     Ok(())
 }
 
+/// Returns the folder of the global state for the given network, creating it if necessary.
+///
+/// Fails if a separate global state folder is configured, but the global state is still in the
+/// storage folder, as a new empty global state would be created otherwise.
+fn global_state_root(
+    cfg: &WithDir<Config>,
+    network_name: &str,
+) -> Result<PathBuf, FatalStorageError> {
+    let storage_root = cfg.with_dir(cfg.value().path.clone()).join(network_name);
+    let global_state_root = match cfg.value().global_state_path {
+        Some(ref path) => cfg.with_dir(path.clone()).join(network_name),
+        None => return Ok(storage_root),
+    };
+    if global_state_root == storage_root {
+        return Ok(global_state_root);
+    }
+
+    let data_file = GLOBAL_STATE_FILES[0];
+    if storage_root.join(data_file).exists() {
+        return Err(FatalStorageError::GlobalStateNotMigrated {
+            found: storage_root,
+            configured: global_state_root,
+        });
+    }
+    if !global_state_root.exists() {
+        fs::create_dir_all(&global_state_root).map_err(|err| {
+            FatalStorageError::CreateDatabaseDirectory(global_state_root.clone(), err)
+        })?;
+    }
+    Ok(global_state_root)
+}
+
+/// Moves the global state from `source` to the global state folder configured for the given
+/// network, or to the storage folder if none is configured.
+///
+/// The files are copied if they cannot be renamed, e.g. as the folders are on different
+/// filesystems.  The node must not be running.  Returns the folder the global state was moved to.
+pub(crate) fn migrate_global_state(
+    cfg: &WithDir<Config>,
+    network_name: &str,
+    source: &Path,
+) -> Result<PathBuf, FatalStorageError> {
+    let dest = match cfg.value().global_state_path {
+        Some(ref path) => cfg.with_dir(path.clone()).join(network_name),
+        None => cfg.with_dir(cfg.value().path.clone()).join(network_name),
+    };
+    if !source.join(GLOBAL_STATE_FILES[0]).exists() {
+        return Err(FatalStorageError::MissingStorageFiles {
+            missing_files: vec![source.join(GLOBAL_STATE_FILES[0])],
+        });
+    }
+    if dest.join(GLOBAL_STATE_FILES[0]).exists() {
+        return Err(FatalStorageError::GlobalStateAlreadyExists(dest));
+    }
+    fs::create_dir_all(&dest)
+        .map_err(|err| FatalStorageError::CreateDatabaseDirectory(dest.clone(), err))?;
+
+    for file_name in GLOBAL_STATE_FILES.iter() {
+        let source_path = source.join(file_name);
+        if !source_path.exists() {
+            continue;
+        }
+        let dest_path = dest.join(file_name);
+        let moved = fs::rename(&source_path, &dest_path).or_else(|_| {
+            info!(?source_path, ?dest_path, "cannot rename, copying instead");
+            fs::copy(&source_path, &dest_path)?;
+            fs::remove_file(&source_path)
+        });
+        if let Err(original_error) = moved {
+            return Err(FatalStorageError::UnableToMoveFile {
+                source_path,
+                dest_path,
+                original_error,
+            });
+        }
+    }
+
+    info!(?source, ?dest, "moved global state");
+    Ok(dest)
+}
+
 /// On-disk storage configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
@@ -2005,6 +2448,12 @@ pub struct Config {
     ///
     /// If the folder doesn't exist, it and any required parents will be created.
     pub path: PathBuf,
+    /// The path to the folder where the global state is stored, if different from `path`.
+    ///
+    /// This allows putting the large global state on a different disk than the block and deploy
+    /// databases.  If the folder doesn't exist, it and any required parents will be created.
+    #[serde(default)]
+    pub global_state_path: Option<PathBuf>,
     /// The maximum size of the database to use for the block store.
     ///
     /// The size should be a multiple of the OS page size.
@@ -2025,6 +2474,35 @@ pub struct Config {
     enable_mem_deduplication: bool,
     /// How many loads before memory duplication checks for dead references.
     mem_pool_prune_interval: u16,
+    /// How much history to retain.
+    #[serde(default)]
+    retention: Retention,
+    /// How often to prune history outside the retention window.
+    #[serde(default = "default_pruning_interval")]
+    pruning_interval: TimeDiff,
+}
+
+/// How much history the node retains.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Retention {
+    /// All blocks, deploys, execution results and global state are retained.
+    Archive,
+    /// Only the given number of most recent eras are retained in full.
+    ///
+    /// Older blocks keep their headers and bodies, but their deploys and execution results are
+    /// deleted, as is all global state unreachable from the state roots of retained blocks.
+    RecentEras(u64),
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention::Archive
+    }
+}
+
+fn default_pruning_interval() -> TimeDiff {
+    TimeDiff::from_seconds(DEFAULT_PRUNING_INTERVAL_SECS)
 }
 
 impl Default for Config {
@@ -2032,12 +2510,15 @@ impl Default for Config {
         Config {
             // No one should be instantiating a config with storage set to default.
             path: "/dev/null".into(),
+            global_state_path: None,
             max_block_store_size: DEFAULT_MAX_BLOCK_STORE_SIZE,
             max_deploy_store_size: DEFAULT_MAX_DEPLOY_STORE_SIZE,
             max_deploy_metadata_store_size: DEFAULT_MAX_DEPLOY_METADATA_STORE_SIZE,
             max_state_store_size: DEFAULT_MAX_STATE_STORE_SIZE,
             enable_mem_deduplication: true,
             mem_pool_prune_interval: 4096,
+            retention: Retention::default(),
+            pruning_interval: default_pruning_interval(),
         }
     }
 }
@@ -2247,6 +2728,18 @@ fn get_body_for_block_header<Tx: Transaction>(
     txn.get_value(block_body_db, block_header.body_hash())
 }
 
+/// Deletes the entry under `key` from `db`, if any.
+fn del_if_exists<K: AsRef<[u8]>>(
+    txn: &mut RwTransaction,
+    db: Database,
+    key: &K,
+) -> Result<(), FatalStorageError> {
+    match txn.del(db, key, None) {
+        Ok(()) | Err(lmdb::Error::NotFound) => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
 /// Purges stale entries from the block metadata database.
 fn initialize_block_metadata_db(
     env: &Environment,
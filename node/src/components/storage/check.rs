@@ -0,0 +1,359 @@
+//! Offline consistency check of the node's storage.
+//!
+//! Opens the block store and global state read-only and verifies that the stored block headers
+//! are keyed by their hash and form an unbroken chain, that completed blocks have their bodies,
+//! deploys and global state, and that stored deploys are keyed by their valid hash.  Everything
+//! found is collected into a [`StorageCheckReport`] rather than aborting the check.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use lmdb::{Cursor, Database, Environment, EnvironmentFlags, Transaction};
+use serde::Serialize;
+
+use casper_hashing::Digest;
+use casper_types::bytesrepr::FromBytes;
+
+use super::{
+    disjoint_sequences::{DisjointSequences, Sequence},
+    lmdb_ext, Config, FatalStorageError, COMPLETED_BLOCKS_STORAGE_KEY, MAX_DB_COUNT,
+    STORAGE_DB_FILENAME,
+};
+use crate::{
+    types::{BlockBody, BlockHash, BlockHeader, Deploy, DeployHash},
+    utils::WithDir,
+};
+
+/// Filename of the execution engine's global state database.
+const GLOBAL_STATE_DB_FILENAME: &str = "data.lmdb";
+/// Name of the trie store within the global state database.
+const TRIE_STORE_DB_NAME: &str = "TRIE_STORE";
+
+/// The result of checking the node's storage.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct StorageCheckReport {
+    /// The directory checked.
+    pub(crate) path: PathBuf,
+    /// Number of stored block headers.
+    pub(crate) block_headers: u64,
+    /// Lowest and highest stored block height.
+    pub(crate) height_range: Option<(u64, u64)>,
+    /// Ranges of heights without a stored block header, inclusive.
+    pub(crate) height_gaps: Vec<(u64, u64)>,
+    /// Number of blocks marked as complete, i.e. with body, deploys and global state.
+    pub(crate) complete_blocks: u64,
+    /// Number of deploys referenced by complete blocks which were checked.
+    pub(crate) deploys_checked: u64,
+    /// Whether the global state database could be opened to check state roots.
+    pub(crate) global_state_checked: bool,
+    /// Problems found.
+    pub(crate) problems: Vec<Problem>,
+}
+
+impl StorageCheckReport {
+    /// Returns `true` if no problems were found.
+    pub(crate) fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// A problem found in the node's storage.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Problem {
+    /// A stored value could not be decoded.
+    Undecodable {
+        database: &'static str,
+        key: String,
+        error: String,
+    },
+    /// A block header is stored under a key other than its hash.
+    BlockHashMismatch { key: String, block_hash: BlockHash },
+    /// Two different block headers are stored at the same height.
+    DuplicateHeight {
+        height: u64,
+        first: BlockHash,
+        second: BlockHash,
+    },
+    /// A block's parent hash doesn't match the hash of the block below it.
+    BrokenParentLink {
+        height: u64,
+        parent_hash: BlockHash,
+        block_hash_below: BlockHash,
+    },
+    /// A complete block's body is missing.
+    MissingBlockBody { height: u64, block_hash: BlockHash },
+    /// A deploy of a complete block is missing.
+    MissingDeploy {
+        height: u64,
+        block_hash: BlockHash,
+        deploy_hash: DeployHash,
+    },
+    /// A stored deploy's hash doesn't match its contents.
+    InvalidDeployHash { deploy_hash: DeployHash },
+    /// The global state of a complete block is missing.
+    MissingStateRoot {
+        height: u64,
+        block_hash: BlockHash,
+        state_root_hash: Digest,
+    },
+}
+
+/// Checks the storage configured in `cfg` for the given network, without modifying it.
+///
+/// Returns an error only if the storage cannot be opened at all.
+pub(crate) fn check_storage(
+    cfg: &WithDir<Config>,
+    network_name: &str,
+) -> Result<StorageCheckReport, FatalStorageError> {
+    let root = cfg.with_dir(cfg.value().path.clone());
+    // Storage files are moved into the network subdirectory on the first start of a node.
+    let network_subdir = root.join(network_name);
+    let path = if network_subdir.join(STORAGE_DB_FILENAME).exists() {
+        network_subdir
+    } else {
+        root
+    };
+
+    let env = open_read_only(&path.join(STORAGE_DB_FILENAME), MAX_DB_COUNT)?;
+    let block_header_db = env.open_db(Some("block_header"))?;
+    let block_body_db = env.open_db(Some("block_body"))?;
+    let deploy_db = env.open_db(Some("deploys"))?;
+    let state_store_db = env.open_db(Some("state_store"))?;
+
+    let mut report = StorageCheckReport {
+        path: path.clone(),
+        ..Default::default()
+    };
+    let txn = env.begin_ro_txn()?;
+
+    let headers = read_block_headers(&txn, block_header_db, &mut report)?;
+    check_chain(&headers, &mut report);
+
+    // Without a record of completed blocks, all blocks are complete (see `Storage::new`).
+    let completed = match txn.get(state_store_db, &COMPLETED_BLOCKS_STORAGE_KEY) {
+        Ok(raw) => {
+            DisjointSequences::from_vec(raw.to_vec())
+                .map_err(FatalStorageError::UnexpectedDeserializationFailure)?
+                .0
+        }
+        Err(lmdb::Error::NotFound) => match report.height_range {
+            Some((_, highest)) => DisjointSequences::new(Sequence::new(0, highest)),
+            None => DisjointSequences::default(),
+        },
+        Err(error) => return Err(error.into()),
+    };
+
+    let global_state_path = match cfg.value().global_state_path {
+        Some(ref global_state_path) => cfg.with_dir(global_state_path.clone()).join(network_name),
+        None => path.clone(),
+    };
+    let trie_store = open_trie_store(&global_state_path);
+    report.global_state_checked = trie_store.is_some();
+    let trie_txn = trie_store
+        .as_ref()
+        .map(|(env, _)| env.begin_ro_txn())
+        .transpose()?;
+
+    for (height, header) in &headers {
+        if !completed.contains(*height) {
+            continue;
+        }
+        report.complete_blocks += 1;
+        let block_hash = header.hash();
+
+        let body: BlockBody = match txn.get(block_body_db, header.body_hash()) {
+            Ok(raw) => match lmdb_ext::deserialize(raw) {
+                Ok(body) => body,
+                Err(error) => {
+                    report.problems.push(Problem::Undecodable {
+                        database: "block_body",
+                        key: header.body_hash().to_string(),
+                        error: error.to_string(),
+                    });
+                    continue;
+                }
+            },
+            Err(lmdb::Error::NotFound) => {
+                report.problems.push(Problem::MissingBlockBody {
+                    height: *height,
+                    block_hash,
+                });
+                continue;
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        for deploy_hash in body.deploy_hashes().iter().chain(body.transfer_hashes()) {
+            report.deploys_checked += 1;
+            match txn.get(deploy_db, deploy_hash) {
+                Ok(raw) => match lmdb_ext::deserialize::<Deploy>(raw) {
+                    Ok(deploy) => {
+                        if deploy.id() != deploy_hash || deploy.has_valid_hash().is_err() {
+                            report.problems.push(Problem::InvalidDeployHash {
+                                deploy_hash: *deploy_hash,
+                            });
+                        }
+                    }
+                    Err(error) => report.problems.push(Problem::Undecodable {
+                        database: "deploys",
+                        key: deploy_hash.to_string(),
+                        error: error.to_string(),
+                    }),
+                },
+                Err(lmdb::Error::NotFound) => report.problems.push(Problem::MissingDeploy {
+                    height: *height,
+                    block_hash,
+                    deploy_hash: *deploy_hash,
+                }),
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        if let (Some((_, trie_db)), Some(trie_txn)) = (&trie_store, &trie_txn) {
+            let state_root_hash = *header.state_root_hash();
+            match trie_txn.get(*trie_db, &state_root_hash) {
+                Ok(_) => (),
+                Err(lmdb::Error::NotFound) => report.problems.push(Problem::MissingStateRoot {
+                    height: *height,
+                    block_hash,
+                    state_root_hash,
+                }),
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Opens an LMDB environment without allowing writes.
+fn open_read_only(path: &Path, max_dbs: u32) -> Result<Environment, lmdb::Error> {
+    Environment::new()
+        .set_flags(
+            EnvironmentFlags::READ_ONLY
+                | EnvironmentFlags::NO_SUB_DIR
+                | EnvironmentFlags::NO_TLS
+                | EnvironmentFlags::NO_READAHEAD,
+        )
+        .set_max_dbs(max_dbs)
+        .open(path)
+}
+
+/// Opens the global state's trie store, if present.
+fn open_trie_store(path: &Path) -> Option<(Environment, Database)> {
+    let env = open_read_only(&path.join(GLOBAL_STATE_DB_FILENAME), 2).ok()?;
+    let db = env.open_db(Some(TRIE_STORE_DB_NAME)).ok()?;
+    Some((env, db))
+}
+
+/// Reads all block headers, keyed by height.
+fn read_block_headers<Tx: Transaction>(
+    txn: &Tx,
+    block_header_db: Database,
+    report: &mut StorageCheckReport,
+) -> Result<BTreeMap<u64, BlockHeader>, FatalStorageError> {
+    let mut headers: BTreeMap<u64, BlockHeader> = BTreeMap::new();
+    let mut cursor = txn.open_ro_cursor(block_header_db)?;
+    // Note: `iter_start` has an undocumented panic if called on an empty database.
+    for (raw_key, raw_val) in cursor.iter() {
+        let key = base16::encode_lower(raw_key);
+        report.block_headers += 1;
+        let header: BlockHeader = match lmdb_ext::deserialize(raw_val) {
+            Ok(header) => header,
+            Err(error) => {
+                report.problems.push(Problem::Undecodable {
+                    database: "block_header",
+                    key,
+                    error: error.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let block_hash = header.hash();
+        if block_hash.as_ref() != raw_key {
+            report
+                .problems
+                .push(Problem::BlockHashMismatch { key, block_hash });
+        }
+
+        if let Some(first) = headers.get(&header.height()) {
+            report.problems.push(Problem::DuplicateHeight {
+                height: header.height(),
+                first: first.hash(),
+                second: block_hash,
+            });
+            continue;
+        }
+        headers.insert(header.height(), header);
+    }
+    Ok(headers)
+}
+
+/// Checks that the headers link up via their parent hashes, and records gaps in the heights.
+fn check_chain(headers: &BTreeMap<u64, BlockHeader>, report: &mut StorageCheckReport) {
+    let mut below: Option<(u64, BlockHash)> = None;
+    for (height, header) in headers {
+        match below {
+            Some((height_below, block_hash_below)) if height_below + 1 == *height => {
+                if *header.parent_hash() != block_hash_below {
+                    report.problems.push(Problem::BrokenParentLink {
+                        height: *height,
+                        parent_hash: *header.parent_hash(),
+                        block_hash_below,
+                    });
+                }
+            }
+            Some((height_below, _)) => report.height_gaps.push((height_below + 1, height - 1)),
+            None => (),
+        }
+        below = Some((*height, header.hash()));
+    }
+    report.height_range = headers
+        .keys()
+        .next()
+        .copied()
+        .zip(headers.keys().next_back().copied());
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{testing::TestRng, EraId, ProtocolVersion};
+
+    use super::*;
+    use crate::types::Block;
+
+    #[test]
+    fn should_report_broken_links_and_gaps() {
+        let mut rng = TestRng::new();
+        let headers: BTreeMap<u64, BlockHeader> = [0, 1, 2, 5]
+            .iter()
+            .map(|height| {
+                let block = Block::random_with_specifics(
+                    &mut rng,
+                    EraId::from(0),
+                    *height,
+                    ProtocolVersion::V1_0_0,
+                    false,
+                    None,
+                );
+                (*height, block.header().clone())
+            })
+            .collect();
+
+        let mut report = StorageCheckReport::default();
+        check_chain(&headers, &mut report);
+
+        assert_eq!(report.height_range, Some((0, 5)));
+        assert_eq!(report.height_gaps, vec![(3, 4)]);
+        // Random blocks don't link up.
+        assert_eq!(report.problems.len(), 2);
+        assert!(report
+            .problems
+            .iter()
+            .all(|problem| matches!(problem, Problem::BrokenParentLink { .. })));
+    }
+}
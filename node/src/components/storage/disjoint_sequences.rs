@@ -76,7 +76,6 @@ impl Sequence {
     }
 
     /// Returns `true` if a sequence contains the value.
-    #[cfg(test)]
     pub(super) fn contains(&self, value: u64) -> bool {
         value >= self.low && value <= self.high
     }
@@ -200,6 +199,36 @@ impl DisjointSequences {
         })
     }
 
+    /// Reduces the sequence(s), keeping all entries above and including `min_value`.  If
+    /// `min_value` is not already included in a sequence, it will not be added.
+    ///
+    /// If the current lowest value is greater than `min_value`, or if there are no sequences, this
+    /// has no effect.
+    pub(super) fn remove_below(&mut self, min_value: u64) {
+        self.sequences.retain_mut(|sequence| {
+            if sequence.low >= min_value {
+                // Keep this sequence unchanged.
+                return true;
+            }
+
+            if sequence.high < min_value {
+                // Delete this entire sequence.
+                return false;
+            }
+
+            // This sequence contains `min_value`, so keep the sequence, but raise its low value.
+            sequence.low = min_value;
+            true
+        })
+    }
+
+    /// Returns `true` if any of the sequences contains the value.
+    pub(super) fn contains(&self, value: u64) -> bool {
+        self.sequences
+            .iter()
+            .any(|sequence| sequence.contains(value))
+    }
+
     /// Inserts multiple values produced by the given iterator.
     #[cfg(test)]
     pub(super) fn extend<T: IntoIterator<Item = u64>>(&mut self, iter: T) {
@@ -544,6 +573,62 @@ mod tests {
         assert!(disjoint_sequences.sequences.is_empty());
     }
 
+    #[test]
+    fn should_remove_below() {
+        const SEQ_HIGH: Sequence = Sequence { high: 11, low: 9 };
+        const SEQ_MID: Sequence = Sequence { high: 6, low: 6 };
+        const SEQ_LOW: Sequence = Sequence { high: 3, low: 1 };
+        let initial_sequences = DisjointSequences {
+            sequences: vec![SEQ_HIGH, SEQ_MID, SEQ_LOW],
+        };
+
+        // Removing below a `min_value` less or equal to current lowest value should be a no-op.
+        let mut disjoint_sequences = initial_sequences.clone();
+        disjoint_sequences.remove_below(0);
+        assert_eq!(disjoint_sequences.sequences, initial_sequences.sequences);
+        disjoint_sequences.remove_below(1);
+        assert_eq!(disjoint_sequences.sequences, initial_sequences.sequences);
+
+        // Removing below a `min_value` between two sequences should cause the lower sequences to
+        // get removed and the higher ones retained unchanged.
+        disjoint_sequences = initial_sequences.clone();
+        disjoint_sequences.remove_below(SEQ_MID.low);
+        assert_eq!(disjoint_sequences.sequences, vec![SEQ_HIGH, SEQ_MID]);
+
+        disjoint_sequences = initial_sequences.clone();
+        disjoint_sequences.remove_below(SEQ_MID.high + 1);
+        assert_eq!(disjoint_sequences.sequences, vec![SEQ_HIGH]);
+
+        // Removing below a `min_value` within a sequence should cause that sequence to get
+        // updated, any lower sequences to get removed, and any higher ones retained unchanged.
+        disjoint_sequences = initial_sequences.clone();
+        let min_value = SEQ_HIGH.low + 1;
+        disjoint_sequences.remove_below(min_value);
+        assert_eq!(
+            disjoint_sequences.sequences,
+            vec![Sequence::new(SEQ_HIGH.high, min_value)]
+        );
+
+        disjoint_sequences = initial_sequences.clone();
+        let min_value = SEQ_LOW.high;
+        disjoint_sequences.remove_below(min_value);
+        assert_eq!(
+            disjoint_sequences.sequences,
+            vec![SEQ_HIGH, SEQ_MID, Sequence::new(SEQ_LOW.high, min_value)]
+        );
+
+        // Removing below a `min_value` greater than the highest value should cause all sequences
+        // to get removed.
+        disjoint_sequences = initial_sequences;
+        disjoint_sequences.remove_below(SEQ_HIGH.high + 1);
+        assert!(disjoint_sequences.sequences.is_empty());
+
+        // Removing on an empty set of sequences should have no effect.
+        disjoint_sequences = DisjointSequences::default();
+        disjoint_sequences.remove_below(100);
+        assert!(disjoint_sequences.sequences.is_empty());
+    }
+
     #[test]
     fn roundtrip_to_bytes() {
         let mut disjoint_sequences = DisjointSequences::default();
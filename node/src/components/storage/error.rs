@@ -66,6 +66,22 @@ pub enum FatalStorageError {
         /// The original `io::Error` from `fs::rename`.
         original_error: io::Error,
     },
+    /// The global state is in the storage folder, although a separate folder is configured.
+    #[error(
+        "found global state in {} although storage.global_state_path is set to {}, move it \
+        using the migrate-global-state subcommand",
+        .found.display(),
+        .configured.display()
+    )]
+    GlobalStateNotMigrated {
+        /// The folder the global state was found in.
+        found: PathBuf,
+        /// The configured global state folder.
+        configured: PathBuf,
+    },
+    /// There already is a global state where it should be moved to.
+    #[error("global state already exists in {}", .0.display())]
+    GlobalStateAlreadyExists(PathBuf),
     /// Mix of missing and found storage files.
     #[error("expected files to exist: {missing_files:?}.")]
     MissingStorageFiles {
@@ -19,9 +19,11 @@ use thiserror::Error;
 use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
     system::auction::UnbondingPurse,
+    Transfer, TransferV2,
 };
 
 const UNBONDING_PURSE_V2_MAGIC_BYTES: &[u8] = &[121, 17, 133, 179, 91, 63, 69, 222];
+const TRANSFERS_V2_MAGIC_BYTES: &[u8] = &[246, 58, 212, 9, 187, 120, 31, 254];
 
 /// Error wrapper for lower-level storage errors.
 ///
@@ -165,14 +167,16 @@ where
 }
 
 /// Serializes `value` into the buffer.
-/// In case the `value` is of the `UnbondingPurse` type it uses the specialized
-/// function to provide compatibility with the legacy version of the `UnbondingPurse` struct.
-/// See [`serialize_unbonding_purse`] for more details.
+/// In case the `value` is of the `UnbondingPurse` or `Vec<TransferV2>` type it uses the specialized
+/// functions to provide compatibility with the legacy versions of these structs.
+/// See [`serialize_unbonding_purse`] and [`serialize_transfers`] for more details.
 pub(crate) fn serialize_internal<V: 'static + Serialize>(
     value: &V,
 ) -> Result<Vec<u8>, LmdbExtError> {
     let buffer = if TypeId::of::<UnbondingPurse>() == TypeId::of::<V>() {
         serialize_unbonding_purse(value)?
+    } else if TypeId::of::<Vec<TransferV2>>() == TypeId::of::<V>() {
+        serialize_transfers(value)?
     } else {
         serialize(value)?
     };
@@ -180,14 +184,16 @@ pub(crate) fn serialize_internal<V: 'static + Serialize>(
 }
 
 /// Deserializes an object from the raw bytes.
-/// In case the expected object is of the `UnbondingPurse` type it uses the specialized
-/// function to provide compatibility with the legacy version of the `UnbondingPurse` struct.
-/// See [`deserialize_unbonding_purse`] for more details.
+/// In case the expected object is of the `UnbondingPurse` or `Vec<TransferV2>` type it uses the
+/// specialized functions to provide compatibility with the legacy versions of these structs.
+/// See [`deserialize_unbonding_purse`] and [`deserialize_transfers`] for more details.
 pub(crate) fn deserialize_internal<V: 'static + DeserializeOwned>(
     raw: &[u8],
 ) -> Result<Option<V>, LmdbExtError> {
     if TypeId::of::<UnbondingPurse>() == TypeId::of::<V>() {
         deserialize_unbonding_purse(raw).map(Some)
+    } else if TypeId::of::<Vec<TransferV2>>() == TypeId::of::<V>() {
+        deserialize_transfers(raw).map(Some)
     } else {
         deserialize(raw).map(Some)
     }
@@ -201,6 +207,11 @@ impl WriteTransactionExt for RwTransaction<'_> {
         value: &V,
         overwrite: bool,
     ) -> Result<bool, LmdbExtError> {
+        #[cfg(any(feature = "fault-injection", test))]
+        if crate::fault_injection::storage_write_fails::<V>() {
+            return Err(lmdb::Error::Other(libc::EIO).into());
+        }
+
         let buffer = serialize_internal(value)?;
 
         let flags = if overwrite {
@@ -224,6 +235,11 @@ impl WriteTransactionExt for RwTransaction<'_> {
         value: &V,
         overwrite: bool,
     ) -> Result<bool, LmdbExtError> {
+        #[cfg(any(feature = "fault-injection", test))]
+        if crate::fault_injection::storage_write_fails::<V>() {
+            return Err(lmdb::Error::Other(libc::EIO).into());
+        }
+
         let buffer = serialize_bytesrepr(value)?;
 
         let flags = if overwrite {
@@ -271,6 +287,20 @@ pub(super) fn deserialize_unbonding_purse<T: DeserializeOwned>(
     }
 }
 
+/// Deserializes a `Vec<TransferV2>` from a buffer.
+/// To provide backward compatibility with blocks whose transfers were stored as `Vec<Transfer>`,
+/// it checks if the raw bytes stream begins with "magic bytes". If yes, the magic bytes are
+/// stripped and the transfers are deserialized as `TransferV2`s. Otherwise, the raw bytes are
+/// deserialized as `Transfer`s which are then converted via `TransferV2::from`.
+pub(super) fn deserialize_transfers<T: DeserializeOwned>(raw: &[u8]) -> Result<T, LmdbExtError> {
+    if let Some(current) = raw.strip_prefix(TRANSFERS_V2_MAGIC_BYTES) {
+        return deserialize(current);
+    }
+    let legacy_transfers: Vec<Transfer> = deserialize(raw)?;
+    let transfers: Vec<TransferV2> = legacy_transfers.into_iter().map(TransferV2::from).collect();
+    deserialize(&serialize(&transfers)?)
+}
+
 /// Serializes into a buffer.
 #[inline(always)]
 pub(super) fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, LmdbExtError> {
@@ -288,6 +318,16 @@ pub(super) fn serialize_unbonding_purse<T: Serialize>(value: &T) -> Result<Vec<u
     Ok(serialized)
 }
 
+/// Serializes a `Vec<TransferV2>` into a buffer.
+/// The serialized bytes are prefixed with the "magic bytes", which will be used by the
+/// deserialization routine to tell them apart from a legacy `Vec<Transfer>`.
+#[inline(always)]
+pub(super) fn serialize_transfers<T: Serialize>(value: &T) -> Result<Vec<u8>, LmdbExtError> {
+    let mut serialized = TRANSFERS_V2_MAGIC_BYTES.to_vec();
+    serialized.extend(serialize(value)?);
+    Ok(serialized)
+}
+
 /// Deserializes from a buffer.
 #[inline(always)]
 pub(super) fn deserialize_bytesrepr<T: FromBytes>(raw: &[u8]) -> Result<T, LmdbExtError> {
@@ -11,21 +11,25 @@ use serde::{Deserialize, Serialize};
 use smallvec::smallvec;
 
 use casper_types::{
-    system::auction::UnbondingPurse, testing::TestRng, AccessRights, EraId, ExecutionResult,
-    ProtocolVersion, PublicKey, SecretKey, URef, U512,
+    account::AccountHash, system::auction::UnbondingPurse, testing::TestRng, AccessRights, EraId,
+    ExecutionResult, Phase, ProtocolVersion, PublicKey, SecretKey, TimeDiff, Transfer, TransferV2,
+    URef, U512,
 };
 
 use super::{
-    move_storage_files_to_network_subdir, should_move_storage_files_to_network_subdir, Config,
-    Storage,
+    migrate_global_state, move_storage_files_to_network_subdir,
+    should_move_storage_files_to_network_subdir, Config, FatalStorageError, PrunedHistory,
+    Retention, Storage,
 };
 use crate::{
     effect::{requests::StorageRequest, Multiple},
     storage::lmdb_ext::{deserialize_internal, serialize_internal},
     testing::{ComponentHarness, UnitTestEvent},
     types::{
-        Block, BlockHash, BlockHashAndHeight, BlockHeader, BlockSignatures, Deploy, DeployHash,
-        DeployMetadata, DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature,
+        Block, BlockHash, BlockHashAndHeight, BlockHeader, BlockHeight, BlockSignals,
+        BlockSignatures, BlockSummary, DelegatorReward, DelegatorStake, Deploy, DeployHash,
+        DeployMetadata, DeployMetadataExt, DeployWithFinalizedApprovals, EraRewards, EraStakes,
+        FinalitySignature, ValidatorRewards, ValidatorStake,
     },
     utils::WithDir,
 };
@@ -36,12 +40,15 @@ fn new_config(harness: &ComponentHarness<UnitTestEvent>) -> Config {
     // Restrict all stores to 50 mibibytes, to catch issues before filling up the entire disk.
     Config {
         path: harness.tmp.path().join("storage"),
+        global_state_path: None,
         max_block_store_size: 50 * MIB,
         max_deploy_store_size: 50 * MIB,
         max_deploy_metadata_store_size: 50 * MIB,
         max_state_store_size: 50 * MIB,
         enable_mem_deduplication: true,
         mem_pool_prune_interval: 4,
+        retention: Retention::Archive,
+        pruning_interval: TimeDiff::from_seconds(60),
     }
 }
 
@@ -96,6 +103,7 @@ fn random_signatures(rng: &mut TestRng, block: &Block) -> BlockSignatures {
             era_id,
             &secret_key,
             PublicKey::from(&secret_key),
+            true,
         );
         block_signatures.insert_proof(signature.public_key, signature.signature);
     }
@@ -143,7 +151,7 @@ fn get_block_header_by_height(
 ) -> Option<BlockHeader> {
     let response = harness.send_request(storage, move |responder| {
         StorageRequest::GetBlockHeaderByHeight {
-            block_height,
+            block_height: BlockHeight::new(block_height),
             only_from_available_block_range: false,
             responder,
         }
@@ -1045,6 +1053,104 @@ fn should_hard_reset() {
     check(0);
 }
 
+#[test]
+fn should_prune_history_outside_retention_window() {
+    let blocks_count = 8_usize;
+    let blocks_per_era = 3;
+    let mut harness = ComponentHarness::default();
+    let mut storage = {
+        let cfg = Config {
+            retention: Retention::RecentEras(1),
+            ..new_config(&harness)
+        };
+        Storage::new(
+            &WithDir::new(harness.tmp.path(), cfg),
+            None,
+            ProtocolVersion::from_parts(1, 0, 0),
+            "test",
+        )
+        .expect("could not create storage component fixture")
+    };
+
+    // Create and store 8 blocks, 0-2 in era 0, 3-5 in era 1, and 6,7 in era 2, each containing one
+    // executed deploy.
+    let mut blocks = vec![];
+    let mut deploys = vec![];
+    for height in 0..blocks_count {
+        let deploy = Deploy::random(&mut harness.rng);
+        let is_switch = height % blocks_per_era == blocks_per_era - 1;
+        let block = Block::random_with_specifics(
+            &mut harness.rng,
+            EraId::from((height / blocks_per_era) as u64),
+            height as u64,
+            ProtocolVersion::V1_0_0,
+            is_switch,
+            iter::once(&deploy),
+        );
+        assert!(put_block(
+            &mut harness,
+            &mut storage,
+            Box::new(block.clone())
+        ));
+        put_deploy(&mut harness, &mut storage, Box::new(deploy.clone()));
+        let mut exec_results = HashMap::new();
+        exec_results.insert(*deploy.id(), harness.rng.gen());
+        put_execution_results(&mut harness, &mut storage, *block.hash(), exec_results);
+        storage.completed_blocks.insert(height as u64);
+        blocks.push(block);
+        deploys.push(deploy);
+    }
+
+    // Blocks below height 6 are outside the retention window.  Prune them in two batches.
+    let pruned_history = storage.prune_history(4).unwrap().unwrap();
+    assert_eq!(pruned_history.pruned_blocks, 4);
+    assert_eq!(pruned_history.pruned_deploys, 4);
+    assert!(pruned_history.retained_state_roots.is_none());
+
+    let pruned_history = storage.prune_history(4).unwrap().unwrap();
+    let expected_state_roots = blocks[6..]
+        .iter()
+        .map(|block| *block.header().state_root_hash())
+        .collect();
+    assert_eq!(
+        pruned_history,
+        PrunedHistory {
+            pruned_blocks: 2,
+            pruned_deploys: 2,
+            retained_state_roots: Some(expected_state_roots),
+        }
+    );
+
+    for (index, (block, deploy)) in blocks.iter().zip(&deploys).enumerate() {
+        let retained = index >= 6;
+        assert!(get_block_header_at_height(&mut storage, index as u64).is_some());
+        assert_eq!(storage.get_deploy_by_hash(*deploy.id()).is_some(), retained);
+        assert_eq!(
+            storage.get_deploy_metadata_by_hash(deploy.id()).is_some(),
+            retained
+        );
+        assert!(get_block(&mut harness, &mut storage, *block.hash()).is_some());
+    }
+    assert_eq!(storage.get_available_block_range().low(), 6);
+
+    // Nothing is left to prune until the next era ends.
+    let pruned_history = storage.prune_history(4).unwrap().unwrap();
+    assert_eq!(pruned_history.pruned_blocks, 0);
+    assert!(pruned_history.retained_state_roots.is_some());
+}
+
+#[test]
+fn should_not_prune_archive() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let block = Block::random(&mut harness.rng);
+    assert!(put_block(&mut harness, &mut storage, Box::new(block)));
+
+    assert!(storage.prune_history(100).unwrap().is_none());
+    assert!(storage.pruning_interval().is_none());
+}
+
 #[test]
 fn should_create_subdir_named_after_network() {
     let harness = ComponentHarness::default();
@@ -1065,6 +1171,47 @@ fn should_create_subdir_named_after_network() {
     assert_eq!(expected_path, storage.root_path());
 }
 
+#[test]
+fn should_require_migration_of_global_state_to_configured_path() {
+    let harness = ComponentHarness::default();
+    let mut cfg = new_config(&harness);
+    let network_name = "test";
+
+    // Create a global state in the storage folder.
+    let storage_subdir = cfg.path.join(network_name);
+    fs::create_dir_all(&storage_subdir).unwrap();
+    File::create(storage_subdir.join("data.lmdb")).unwrap();
+
+    cfg.global_state_path = Some(harness.tmp.path().join("global_state"));
+    let cfg = WithDir::new(harness.tmp.path(), cfg);
+    let result = Storage::new(
+        &cfg,
+        None,
+        ProtocolVersion::from_parts(1, 0, 0),
+        network_name,
+    );
+    assert!(matches!(
+        result,
+        Err(FatalStorageError::GlobalStateNotMigrated { .. })
+    ));
+
+    let expected_path = harness.tmp.path().join("global_state").join(network_name);
+    let dest = migrate_global_state(&cfg, network_name, &storage_subdir).unwrap();
+    assert_eq!(expected_path, dest);
+    assert!(expected_path.join("data.lmdb").exists());
+    assert!(!storage_subdir.join("data.lmdb").exists());
+
+    let storage = Storage::new(
+        &cfg,
+        None,
+        ProtocolVersion::from_parts(1, 0, 0),
+        network_name,
+    )
+    .unwrap();
+    assert_eq!(expected_path, storage.global_state_path());
+    assert_eq!(storage_subdir, storage.root_path());
+}
+
 #[test]
 fn should_not_try_to_move_nonexistent_files() {
     let harness = ComponentHarness::default();
@@ -1192,6 +1339,200 @@ fn can_put_and_get_block() {
     assert_eq!(response.as_ref(), Some(block.header()));
 }
 
+#[test]
+fn should_store_block_signals() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let block_signals: Vec<_> = [2, 3, 5]
+        .iter()
+        .map(|&height| BlockSignals {
+            height,
+            era_id: EraId::new(1),
+            proposer: PublicKey::random(&mut harness.rng),
+            signals: harness.rng.gen(),
+        })
+        .collect();
+    for signals in &block_signals {
+        let block_signals = Box::new(signals.clone());
+        harness.send_request(&mut storage, move |responder| {
+            StorageRequest::PutBlockSignals {
+                block_signals,
+                responder,
+            }
+            .into()
+        });
+    }
+
+    // Heights without recorded signals are omitted.
+    let response = harness.send_request(&mut storage, move |responder| {
+        StorageRequest::GetBlockSignals {
+            block_heights: 0..=4,
+            responder,
+        }
+        .into()
+    });
+    assert_eq!(response, block_signals[..2]);
+}
+
+#[test]
+fn should_store_era_rewards() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let mut random_era_rewards = |era_id: u64| EraRewards {
+        era_id: EraId::new(era_id),
+        validators: vec![ValidatorRewards {
+            validator_public_key: PublicKey::random(&mut harness.rng),
+            amount: U512::from(harness.rng.gen::<u64>()),
+            commission: U512::from(harness.rng.gen::<u64>()),
+            delegation_rate: harness.rng.gen_range(0..=100),
+            delegators: vec![DelegatorReward {
+                delegator_public_key: PublicKey::random(&mut harness.rng),
+                amount: U512::from(harness.rng.gen::<u64>()),
+            }],
+        }],
+    };
+    let era_rewards_3 = random_era_rewards(3);
+    let era_rewards_5 = random_era_rewards(5);
+
+    for era_rewards in [era_rewards_5.clone(), era_rewards_3.clone()] {
+        let era_rewards = Box::new(era_rewards);
+        harness.send_request(&mut storage, move |responder| {
+            StorageRequest::PutEraRewards {
+                era_rewards,
+                responder,
+            }
+            .into()
+        });
+    }
+
+    // Eras without recorded rewards are omitted, and the rest are ordered by era ID.
+    let response = harness.send_request(&mut storage, move |responder| {
+        StorageRequest::GetEraRewards {
+            era_ids: 2..=6,
+            responder,
+        }
+        .into()
+    });
+    assert_eq!(response, vec![era_rewards_3, era_rewards_5]);
+
+    let response = harness.send_request(&mut storage, move |responder| {
+        StorageRequest::GetEraRewards {
+            era_ids: 4..=4,
+            responder,
+        }
+        .into()
+    });
+    assert!(response.is_empty());
+}
+
+#[test]
+fn should_store_era_stakes() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let mut random_era_stakes = |era_id: u64| {
+        let validator_public_key = PublicKey::random(&mut harness.rng);
+        EraStakes {
+            era_id: EraId::new(era_id),
+            validators: vec![ValidatorStake {
+                validator_public_key: validator_public_key.clone(),
+                stake: U512::from(harness.rng.gen::<u64>()),
+                delegators: vec![DelegatorStake {
+                    delegator_public_key: PublicKey::random(&mut harness.rng),
+                    stake: U512::from(harness.rng.gen::<u64>()),
+                }],
+            }],
+            equivocators: vec![],
+            inactive_validators: vec![validator_public_key],
+        }
+    };
+    let era_stakes_1 = random_era_stakes(1);
+    let era_stakes_2 = random_era_stakes(2);
+
+    for era_stakes in [era_stakes_2.clone(), era_stakes_1.clone()] {
+        let era_stakes = Box::new(era_stakes);
+        harness.send_request(&mut storage, move |responder| {
+            StorageRequest::PutEraStakes {
+                era_stakes,
+                responder,
+            }
+            .into()
+        });
+    }
+
+    let response = harness.send_request(&mut storage, move |responder| {
+        StorageRequest::GetEraStakes {
+            era_ids: 0..=2,
+            responder,
+        }
+        .into()
+    });
+    assert_eq!(response, vec![era_stakes_1, era_stakes_2]);
+}
+
+#[test]
+fn should_summarize_blocks() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    // This test is not restricted by the block availability index.
+    let only_from_available_block_range = false;
+
+    let block = Block::random(&mut harness.rng);
+    put_block(&mut harness, &mut storage, Box::new(block.clone()));
+
+    let mut execution_results = HashMap::new();
+    let mut total_gas = U512::zero();
+    for deploy_hash in block
+        .deploy_hashes()
+        .iter()
+        .chain(block.transfer_hashes().iter())
+    {
+        let execution_result: ExecutionResult = harness.rng.gen();
+        match &execution_result {
+            ExecutionResult::Success { cost, .. } | ExecutionResult::Failure { cost, .. } => {
+                total_gas += *cost
+            }
+        }
+        execution_results.insert(*deploy_hash, execution_result);
+    }
+    put_execution_results(&mut harness, &mut storage, *block.hash(), execution_results);
+
+    let block_hash = *block.hash();
+    let transfers = harness.send_request(&mut storage, move |responder| {
+        StorageRequest::GetBlockTransfers {
+            block_hash,
+            responder,
+        }
+        .into()
+    });
+    let total_transferred = transfers
+        .unwrap_or_default()
+        .iter()
+        .fold(U512::zero(), |total, transfer| total + transfer.amount);
+
+    // Heights without a stored block are omitted.
+    let height = block.height();
+    let response = harness.send_request(&mut storage, move |responder| {
+        StorageRequest::GetBlockSummaries {
+            block_heights: height.saturating_sub(1)..=height.saturating_add(1),
+            only_from_available_block_range,
+            responder,
+        }
+        .into()
+    });
+    assert_eq!(
+        response,
+        vec![BlockSummary::new(&block, total_gas, total_transferred)]
+    );
+    let summary = &response[0];
+    assert_eq!(summary.proposer, *block.body().proposer());
+    assert_eq!(summary.deploy_count, block.deploy_hashes().len() as u64);
+    assert_eq!(summary.transfer_count, block.transfer_hashes().len() as u64);
+}
+
 #[test]
 fn should_restrict_returned_blocks() {
     let mut harness = ComponentHarness::default();
@@ -1332,3 +1673,53 @@ fn unbonding_purse_serialization_roundtrip() {
     // Explicitly assert that the `new_validator` is not `None`
     assert!(deserialized.new_validator().is_some())
 }
+
+#[test]
+fn should_read_legacy_transfers() {
+    let mut rng = TestRng::new();
+    let legacy = Transfer::new(
+        casper_types::DeployHash::new(rng.gen()),
+        AccountHash::new(rng.gen()),
+        Some(AccountHash::new(rng.gen())),
+        rng.gen(),
+        rng.gen(),
+        U512::from(rng.gen::<u64>()),
+        U512::zero(),
+        Some(rng.gen()),
+    );
+    let serialized = bincode::serialize(&vec![legacy]).expect("serialization");
+
+    let deserialized: Vec<TransferV2> = deserialize_internal(&serialized)
+        .expect("should deserialize w/o error")
+        .expect("should be Some");
+
+    assert_eq!(deserialized, vec![TransferV2::from(legacy)]);
+    assert_eq!(deserialized[0].initiator, Some(legacy.from));
+    assert_eq!(deserialized[0].memo, None);
+    assert_eq!(deserialized[0].phase, Phase::Session);
+}
+
+#[test]
+fn transfers_serialization_roundtrip() {
+    let mut rng = TestRng::new();
+    let original = vec![TransferV2::new(
+        casper_types::DeployHash::new(rng.gen()),
+        AccountHash::new(rng.gen()),
+        None,
+        rng.gen(),
+        rng.gen(),
+        U512::from(rng.gen::<u64>()),
+        U512::zero(),
+        None,
+        None,
+        Some("invoice 42".to_string()),
+        Phase::Session,
+    )];
+
+    let serialized = serialize_internal(&original).expect("serialization");
+    let deserialized: Vec<TransferV2> = deserialize_internal(&serialized)
+        .expect("should deserialize w/o error")
+        .expect("should be Some");
+
+    assert_eq!(original, deserialized);
+}
@@ -1,4 +1,4 @@
-use std::{env, fs, io, path::PathBuf, sync::Arc};
+use std::{env, fs, io, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -9,9 +9,10 @@ use casper_hashing::Digest;
 use casper_types::{crypto, ProtocolVersion, PublicKey, SecretKey, Signature};
 
 use crate::{
+    components::consensus::LoadKeysError,
     reactor::participating::Config,
     types::{chainspec, Chainspec, ChainspecRawBytes},
-    utils::{LoadError, Loadable, WithDir},
+    utils::{Loadable, WithDir},
 };
 
 /// The name of the file for recording the new global state hash after a data migration.
@@ -76,7 +77,7 @@ pub(crate) enum Error {
 
     /// Error loading the secret key.
     #[error("error loading secret key: {0}")]
-    LoadSecretKey(LoadError<crypto::ErrorExt>),
+    LoadSecretKey(LoadKeysError),
 
     /// Error loading the chainspec.
     #[error("error loading chainspec: {0}")]
@@ -204,10 +205,9 @@ pub(crate) fn migrate_data(
         .0
         .protocol_config
         .version;
-    let secret_key: Arc<SecretKey> = new_config
+    let (secret_key, _) = new_config
         .consensus
-        .secret_key_path
-        .load(&new_root)
+        .load_keys(&new_root)
         .map_err(Error::LoadSecretKey)?;
 
     // Get this by actually migrating the global state data.
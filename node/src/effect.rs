@@ -102,6 +102,7 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
     future::Future,
     mem,
+    ops::RangeInclusive,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -120,47 +121,59 @@ use casper_execution_engine::{
         BalanceResult, GetBidsRequest, GetBidsResult, QueryRequest, QueryResult, UpgradeConfig,
         UpgradeSuccess,
     },
-    shared::execution_journal::ExecutionJournal,
+    shared::{execution_journal::ExecutionJournal, gas_profile::GasProfile},
     storage::trie::{TrieOrChunk, TrieOrChunkId},
 };
 use casper_hashing::Digest;
 use casper_types::{
-    account::Account, bytesrepr::Bytes, system::auction::EraValidators, Contract, ContractPackage,
-    EraId, ExecutionEffect, ExecutionResult, Key, ProtocolVersion, PublicKey, TimeDiff, Timestamp,
-    Transfer, URef, U512,
+    account::{Account, AccountHash},
+    bytesrepr::Bytes,
+    system::auction::EraValidators,
+    Contract, ContractPackage, EraId, ExecutionEffect, ExecutionResult, Key, ProtocolVersion,
+    PublicKey, TimeDiff, Timestamp, TransactionV2, TransactionV2Hash, TransferV2, URef, U512,
 };
 
 use crate::{
     components::{
+        block_proposer::{DeployBlocklist, DeployBlocklistUpdate, DeployInfo},
         block_validator::ValidatingBlock,
+        chain_synchronizer::SyncStatus,
         chainspec_loader::NextUpgrade,
-        consensus::{BlockContext, ClContext, EraDump, ValidatorChange},
+        consensus::{BlockContext, ClContext, EraDump, ValidatorChange, ValidatorMode},
         contract_runtime::{
             BlockAndExecutionEffects, BlockExecutionError, EraValidatorsRequest, ExecutionPreState,
         },
         deploy_acceptor,
         fetcher::FetchResult,
-        small_network::FromIncoming,
+        finality_watchdog::FinalityAlert,
+        linear_chain::ShutdownPoint,
+        small_network::{FromIncoming, NetworkDump},
+        storage::PrunedHistory,
     },
     contract_runtime::SpeculativeExecutionState,
     effect::announcements::ChainSynchronizerAnnouncement,
-    reactor::{EventQueueHandle, QueueKind},
+    reactor::{EventQueueHandle, MemoryDump, QueueKind},
     types::{
-        AvailableBlockRange, Block, BlockAndDeploys, BlockHash, BlockHeader,
-        BlockHeaderWithMetadata, BlockHeadersBatch, BlockHeadersBatchId, BlockPayload,
-        BlockSignatures, BlockWithMetadata, Chainspec, ChainspecInfo, ChainspecRawBytes, Deploy,
-        DeployHash, DeployHeader, DeployMetadataExt, DeployWithFinalizedApprovals,
+        peers_map::PeerReputation, AvailableBlockRange, Block, BlockAndDeploys, BlockHash,
+        BlockHeader, BlockHeaderWithMetadata, BlockHeadersBatch, BlockHeadersBatchId, BlockHeight,
+        BlockPayload, BlockSignals, BlockSignatures, BlockSummary, BlockWithMetadata, Chainspec,
+        ChainspecInfo, ChainspecRawBytes, Deploy, DeployHash, DeployHeader, DeployMetadataExt,
+        DeployOrTransferHash, DeployWithFinalizedApprovals, EraRewards, EraStakes,
         FinalitySignature, FinalizedApprovals, FinalizedBlock, Item, NodeId, NodeState,
+        Transaction,
     },
-    utils::{fmt_limit::FmtLimit, SharedFlag, Source},
+    utils::{fmt_limit::FmtLimit, round_robin::QueueSummary, SharedFlag, Source},
 };
 use announcements::{
     BlockProposerAnnouncement, BlocklistAnnouncement, ChainspecLoaderAnnouncement,
     ConsensusAnnouncement, ContractRuntimeAnnouncement, ControlAnnouncement,
-    DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement, QueueDumpFormat,
-    RpcServerAnnouncement,
+    DeployAcceptorAnnouncement, FinalityWatchdogAnnouncement, GossiperAnnouncement,
+    LinearChainAnnouncement, QueueDumpFormat, RpcServerAnnouncement,
+};
+use diagnostics_port::{
+    ConfigReloadReport, DumpConsensusStateRequest, DumpNetworkStateRequest, ReloadConfigRequest,
+    ShutdownPointRequest, ValidatorModeRequest, ValidatorModeStatus,
 };
-use diagnostics_port::DumpConsensusStateRequest;
 use requests::{
     BeginGossipRequest, BlockPayloadRequest, BlockProposerRequest, BlockValidationRequest,
     ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, FetcherRequest,
@@ -754,6 +767,18 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the reputation of the network peers with a score or recent bans.
+    pub(crate) async fn network_peer_reputations(self) -> Vec<PeerReputation>
+    where
+        REv: From<NetworkInfoRequest>,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::PeerReputations { responder },
+            QueueKind::Api,
+        )
+        .await
+    }
+
     /// Gets the current network peers in random order.
     pub async fn get_fully_connected_peers(self) -> Vec<NodeId>
     where
@@ -857,6 +882,41 @@ impl<REv> EffectBuilder<REv> {
             .await;
     }
 
+    /// Announces that a client has requested a precheck of a deploy.
+    pub(crate) async fn announce_deploy_precheck_requested(
+        self,
+        deploy: Box<Deploy>,
+        responder: Responder<Result<(), deploy_acceptor::Error>>,
+    ) where
+        REv: From<RpcServerAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                RpcServerAnnouncement::DeployPrecheckRequested { deploy, responder },
+                QueueKind::Api,
+            )
+            .await;
+    }
+
+    /// Announces that a transaction in the versioned format has been received by the RPC server.
+    pub(crate) async fn announce_transaction_v2_received(
+        self,
+        transaction: Box<TransactionV2>,
+        responder: Responder<Result<(), deploy_acceptor::Error>>,
+    ) where
+        REv: From<RpcServerAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                RpcServerAnnouncement::TransactionV2Received {
+                    transaction,
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await;
+    }
+
     /// Announces that a deploy not previously stored has now been accepted and stored.
     pub(crate) fn announce_new_deploy_accepted(
         self,
@@ -919,6 +979,8 @@ impl<REv> EffectBuilder<REv> {
         self,
         era_id: EraId,
         execution_journal: ExecutionJournal,
+        era_rewards: Option<EraRewards>,
+        era_stakes: Option<EraStakes>,
     ) where
         REv: From<ContractRuntimeAnnouncement>,
     {
@@ -927,6 +989,8 @@ impl<REv> EffectBuilder<REv> {
                 ContractRuntimeAnnouncement::CommitStepSuccess {
                     era_id,
                     execution_effect: ExecutionEffect::from(&execution_journal),
+                    era_rewards: era_rewards.map(Box::new),
+                    era_stakes: era_stakes.map(Box::new),
                 },
                 QueueKind::Regular,
             )
@@ -1067,7 +1131,7 @@ impl<REv> EffectBuilder<REv> {
 
     pub(crate) async fn get_block_header_at_height_from_storage(
         self,
-        block_height: u64,
+        block_height: BlockHeight,
         only_from_available_block_range: bool,
     ) -> Option<BlockHeader>
     where
@@ -1150,11 +1214,125 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the summaries of the blocks with heights in the given range from storage.
+    pub(crate) async fn get_block_summaries_from_storage(
+        self,
+        block_heights: RangeInclusive<u64>,
+        only_from_available_block_range: bool,
+    ) -> Vec<BlockSummary>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlockSummaries {
+                block_heights,
+                only_from_available_block_range,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Puts the signaling bits of a finalized block into storage.
+    pub(crate) async fn put_block_signals_to_storage(self, block_signals: Box<BlockSignals>)
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutBlockSignals {
+                block_signals,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the signaling bits of the finalized blocks with heights in the given range from
+    /// storage.
+    pub(crate) async fn get_block_signals_from_storage(
+        self,
+        block_heights: RangeInclusive<u64>,
+    ) -> Vec<BlockSignals>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlockSignals {
+                block_heights,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Puts the rewards distributed at the end of an era into storage.
+    pub(crate) async fn put_era_rewards_to_storage(self, era_rewards: Box<EraRewards>)
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutEraRewards {
+                era_rewards,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the rewards distributed at the end of the eras in the given range from storage.
+    pub(crate) async fn get_era_rewards_from_storage(
+        self,
+        era_ids: RangeInclusive<u64>,
+    ) -> Vec<EraRewards>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetEraRewards { era_ids, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Puts the stakes held in an era and the validators evicted at its end into storage.
+    pub(crate) async fn put_era_stakes_to_storage(self, era_stakes: Box<EraStakes>)
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutEraStakes {
+                era_stakes,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the stakes held in the eras in the given range from storage.
+    pub(crate) async fn get_era_stakes_from_storage(
+        self,
+        era_ids: RangeInclusive<u64>,
+    ) -> Vec<EraStakes>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetEraStakes { era_ids, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested block's transfers from storage.
     pub(crate) async fn get_block_transfers_from_storage(
         self,
         block_hash: BlockHash,
-    ) -> Option<Vec<Transfer>>
+    ) -> Option<Vec<TransferV2>>
     where
         REv: From<StorageRequest>,
     {
@@ -1237,6 +1415,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Prunes the deploys and execution results of up to `max_blocks` blocks outside the
+    /// retention window.
+    ///
+    /// Returns `None` if all history is retained.
+    pub(crate) async fn prune_history(self, max_blocks: u64) -> Option<PrunedHistory>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PruneHistory {
+                max_blocks,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Get a trie or chunk by its ID.
     pub(crate) async fn get_trie(
         self,
@@ -1321,6 +1517,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the requested transaction in the versioned format from the transaction store.
+    pub(crate) async fn get_transaction_v2_from_storage(
+        self,
+        transaction_hash: TransactionV2Hash,
+    ) -> Option<TransactionV2>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetTransactionV2 {
+                transaction_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested deploys from the deploy store.
     ///
     /// Returns the "original" deploys, which are the first received by the node, along with a
@@ -1383,7 +1597,7 @@ impl<REv> EffectBuilder<REv> {
     /// Gets the requested block and its finality signatures.
     pub(crate) async fn get_block_at_height_with_metadata_from_storage(
         self,
-        block_height: u64,
+        block_height: BlockHeight,
         only_from_available_block_range: bool,
     ) -> Option<BlockWithMetadata>
     where
@@ -1443,7 +1657,7 @@ impl<REv> EffectBuilder<REv> {
     /// Gets the requested block by height with its associated metadata.
     pub(crate) async fn get_block_with_metadata_from_storage_by_height(
         self,
-        block_height: u64,
+        block_height: BlockHeight,
         only_from_available_block_range: bool,
     ) -> Option<BlockWithMetadata>
     where
@@ -1463,7 +1677,7 @@ impl<REv> EffectBuilder<REv> {
     /// Gets the requested block header by height with its associated metadata.
     pub(crate) async fn get_block_header_with_metadata_from_storage_by_height(
         self,
-        block_height: u64,
+        block_height: BlockHeight,
         only_from_available_block_range: bool,
     ) -> Option<BlockHeaderWithMetadata>
     where
@@ -1537,6 +1751,105 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the dependencies of a pending deploy which the block proposer can't resolve, as they
+    /// are neither executed nor pending.  Returns `None` if the deploy is not pending.
+    pub(crate) async fn get_unresolvable_deploy_dependencies(
+        self,
+        deploy_hash: DeployHash,
+    ) -> Option<Vec<DeployHash>>
+    where
+        REv: From<BlockProposerRequest>,
+    {
+        self.make_request(
+            |responder| BlockProposerRequest::GetUnresolvableDependencies {
+                deploy_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Checks whether the block proposer would buffer another deploy from the given account
+    /// without exceeding its per-account quota of pending deploys.
+    pub(crate) async fn check_pending_deploy_quota(self, account: PublicKey) -> bool
+    where
+        REv: From<BlockProposerRequest>,
+    {
+        self.make_request(
+            |responder| BlockProposerRequest::CheckPendingDeployQuota { account, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Checks whether a deploy fails to outbid the pending deploy with the same replacement tag,
+    /// returning the latter's hash if so.
+    pub(crate) async fn check_deploy_replacement(
+        self,
+        hash: DeployOrTransferHash,
+        deploy_info: DeployInfo,
+    ) -> Option<DeployHash>
+    where
+        REv: From<BlockProposerRequest>,
+    {
+        self.make_request(
+            |responder| BlockProposerRequest::CheckDeployReplacement {
+                hash,
+                deploy_info: Box::new(deploy_info),
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Checks whether a deploy or the account which signed it is on the operator's blocklist.
+    pub(crate) async fn check_deploy_blocklist(
+        self,
+        deploy_hash: DeployHash,
+        account_hash: AccountHash,
+    ) -> bool
+    where
+        REv: From<BlockProposerRequest>,
+    {
+        self.make_request(
+            |responder| BlockProposerRequest::CheckDeployBlocklist {
+                deploy_hash,
+                account_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Applies a change to the operator's blocklist, returning whether it changed.  Returns `None`
+    /// if there is no block proposer running to maintain the blocklist.
+    pub(crate) async fn update_deploy_blocklist(self, update: DeployBlocklistUpdate) -> Option<bool>
+    where
+        REv: From<BlockProposerRequest>,
+    {
+        self.make_request(
+            |responder| BlockProposerRequest::UpdateDeployBlocklist { update, responder },
+            QueueKind::Control,
+        )
+        .await
+    }
+
+    /// Gets the operator's blocklist.  Returns `None` if there is no block proposer running to
+    /// maintain the blocklist.
+    pub(crate) async fn get_deploy_blocklist(self) -> Option<DeployBlocklist>
+    where
+        REv: From<BlockProposerRequest>,
+    {
+        self.make_request(
+            |responder| BlockProposerRequest::GetDeployBlocklist { responder },
+            QueueKind::Control,
+        )
+        .await
+    }
+
     /// Executes a finalized block.
     pub(crate) async fn execute_finalized_block(
         self,
@@ -1657,6 +1970,25 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that deploys proposed in the given era were never finalized.
+    pub(crate) async fn announce_orphaned_deploys(
+        self,
+        era_id: EraId,
+        deploy_hashes: Vec<DeployHash>,
+    ) where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ConsensusAnnouncement::OrphanedDeploys {
+                    era_id,
+                    deploy_hashes,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Announce the intent to disconnect from a specific peer, which consensus thinks is faulty.
     pub(crate) async fn announce_disconnect_from_peer(self, peer: NodeId)
     where
@@ -1670,6 +2002,32 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announce that a peer did not respond to a request in time.
+    pub(crate) async fn announce_slow_response(self, peer: NodeId)
+    where
+        REv: From<BlocklistAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                BlocklistAnnouncement::SlowResponse(Box::new(peer)),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announce that a peer gossiped an item which was new to us.
+    pub(crate) async fn announce_useful_gossip(self, peer: NodeId)
+    where
+        REv: From<BlocklistAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                BlocklistAnnouncement::UsefulGossip(Box::new(peer)),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Announce that the sync process has finished.
     pub(crate) async fn announce_finished_chain_syncing(self)
     where
@@ -1683,6 +2041,32 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces the current progress of chain synchronization.
+    pub(crate) async fn announce_sync_progress(self, sync_status: SyncStatus)
+    where
+        REv: From<ChainSynchronizerAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ChainSynchronizerAnnouncement::SyncProgress(sync_status),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces a change of the node's finality status.
+    pub(crate) async fn announce_finality_alert(self, alert: FinalityAlert)
+    where
+        REv: From<FinalityWatchdogAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                FinalityWatchdogAnnouncement::StatusChanged(alert),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// The linear chain has stored a newly-created block.
     pub(crate) async fn announce_block_added(self, block: Box<Block>)
     where
@@ -1709,6 +2093,19 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// The linear chain has stored finality signatures proving the finality of a block.
+    pub(crate) async fn announce_block_finalized(self, signatures: Box<BlockSignatures>)
+    where
+        REv: From<LinearChainAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                LinearChainAnnouncement::BlockFinalized(signatures),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Runs the genesis process on the contract runtime.
     pub(crate) async fn commit_genesis(
         self,
@@ -1756,7 +2153,8 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
-    pub(crate) async fn get_node_state(self) -> NodeState
+    /// Gets the node state and, while the node is syncing, a summary of the sync progress.
+    pub(crate) async fn get_node_state(self) -> (NodeState, Option<SyncStatus>)
     where
         REv: From<NodeStateRequest> + Send,
     {
@@ -1836,6 +2234,20 @@ impl<REv> EffectBuilder<REv> {
         }
     }
 
+    /// Removes previously saved state from storage.
+    ///
+    /// Removing a key which is not present is not an error.
+    pub(crate) async fn delete_state(self, key: Cow<'static, [u8]>)
+    where
+        REv: From<StateStoreRequest>,
+    {
+        self.make_request(
+            move |responder| StateStoreRequest::Delete { key, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Requests a query be executed on the Contract Runtime component.
     pub(crate) async fn query_global_state(
         self,
@@ -2088,6 +2500,63 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Dump the state of the networking component's connection manager.
+    pub(crate) async fn diagnostics_port_dump_network_state(self) -> NetworkDump
+    where
+        REv: From<DumpNetworkStateRequest>,
+    {
+        self.make_request(
+            |responder| DumpNetworkStateRequest { responder },
+            QueueKind::Control,
+        )
+        .await
+    }
+
+    /// Reloads the reloadable subset of the config file, returning which changed keys were applied
+    /// and which require a restart.
+    pub(crate) async fn reload_config(self) -> Result<ConfigReloadReport, Cow<'static, str>>
+    where
+        REv: From<ReloadConfigRequest>,
+    {
+        self.make_request(
+            |responder| ReloadConfigRequest { responder },
+            QueueKind::Control,
+        )
+        .await
+    }
+
+    /// Replaces the point after which the node shuts down if `update` is given, returning the
+    /// shutdown point in effect.
+    pub(crate) async fn shutdown_point(
+        self,
+        update: Option<ShutdownPoint>,
+    ) -> Result<ShutdownPoint, Cow<'static, str>>
+    where
+        REv: From<ShutdownPointRequest>,
+    {
+        self.make_request(
+            |responder| ShutdownPointRequest { update, responder },
+            QueueKind::Control,
+        )
+        .await
+    }
+
+    /// Sets the validator mode to switch to at the start of the next era if `update` is given,
+    /// returning the resulting validator mode status.
+    pub(crate) async fn validator_mode(
+        self,
+        update: Option<ValidatorMode>,
+    ) -> Result<ValidatorModeStatus, Cow<'static, str>>
+    where
+        REv: From<ValidatorModeRequest>,
+    {
+        self.make_request(
+            |responder| ValidatorModeRequest { update, responder },
+            QueueKind::Control,
+        )
+        .await
+    }
+
     /// Dump the event queue contents to the diagnostics port, using the given serializer.
     pub(crate) async fn diagnostics_port_dump_queue(self, dump_format: QueueDumpFormat)
     where
@@ -2103,6 +2572,36 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Summarize the event queues for the diagnostics port.
+    pub(crate) async fn diagnostics_port_summarize_queue(
+        self,
+        include_events: bool,
+    ) -> QueueSummary<QueueKind>
+    where
+        REv: From<ControlAnnouncement>,
+    {
+        self.make_request(
+            |responder| ControlAnnouncement::QueueSummaryRequest {
+                include_events,
+                responder,
+            },
+            QueueKind::Control,
+        )
+        .await
+    }
+
+    /// Obtains a breakdown of the node's memory usage for the diagnostics port.
+    pub(crate) async fn diagnostics_port_dump_memory(self) -> MemoryDump
+    where
+        REv: From<ControlAnnouncement>,
+    {
+        self.make_request(
+            |responder| ControlAnnouncement::MemoryDumpRequest { responder },
+            QueueKind::Control,
+        )
+        .await
+    }
+
     /// Get the bytes for the chainspec file and genesis_accounts
     /// and global_state bytes if the files are present.
     pub(crate) async fn get_chainspec_raw_bytes(self) -> Arc<ChainspecRawBytes>
@@ -2175,20 +2674,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
-    /// Requests execution of a single deploy, without commiting its effects.
+    /// Requests execution of a single deploy or transaction, without commiting its effects.
     /// Inteded to be used for debugging & discovery purposes.
-    pub(crate) async fn speculative_execute_deploy(
+    ///
+    /// If `profile_gas` is set, the gas profile of the execution is returned as well.
+    pub(crate) async fn speculative_execute_transaction(
         self,
         execution_prestate: SpeculativeExecutionState,
-        deploy: Deploy,
-    ) -> Result<Option<ExecutionResult>, engine_state::Error>
+        transaction: Transaction,
+        profile_gas: bool,
+    ) -> Result<Option<(ExecutionResult, Option<GasProfile>)>, engine_state::Error>
     where
         REv: From<ContractRuntimeRequest>,
     {
         self.make_request(
             |responder| ContractRuntimeRequest::SpeculativeDeployExecution {
                 execution_prestate,
-                deploy: Box::new(deploy),
+                transaction: Box::new(transaction),
+                profile_gas,
                 responder,
             },
             QueueKind::Regular,
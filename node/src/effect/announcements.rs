@@ -12,17 +12,22 @@ use std::{
 use itertools::Itertools;
 use serde::Serialize;
 
-use casper_types::{EraId, ExecutionEffect, ExecutionResult, PublicKey, Timestamp, U512};
+use casper_types::{
+    EraId, ExecutionEffect, ExecutionResult, PublicKey, Timestamp, TransactionV2, U512,
+};
 
 use crate::{
     components::{
-        chainspec_loader::NextUpgrade, deploy_acceptor::Error, diagnostics_port::FileSerializer,
+        chain_synchronizer::SyncStatus, chainspec_loader::NextUpgrade, deploy_acceptor::Error,
+        diagnostics_port::FileSerializer, finality_watchdog::FinalityAlert,
     },
     effect::Responder,
+    reactor::{MemoryDump, QueueKind},
     types::{
-        Block, Deploy, DeployHash, DeployHeader, FinalitySignature, FinalizedBlock, Item, NodeId,
+        Block, BlockSignatures, Deploy, DeployHash, DeployHeader, EraRewards, EraStakes,
+        FinalitySignature, FinalizedBlock, Item, NodeId,
     },
-    utils::Source,
+    utils::{round_robin::QueueSummary, Source},
 };
 
 /// Control announcements are special announcements handled directly by the runtime/runner.
@@ -55,6 +60,18 @@ pub(crate) enum ControlAnnouncement {
         /// Responder called when the dump has been finished.
         finished: Responder<()>,
     },
+    /// A summary of the event queues has been requested.
+    QueueSummaryRequest {
+        /// Whether to include a summary of every queued event.
+        include_events: bool,
+        /// Responder to call with the summary.
+        responder: Responder<QueueSummary<QueueKind>>,
+    },
+    /// A breakdown of the node's memory usage has been requested.
+    MemoryDumpRequest {
+        /// Responder to call with the breakdown.
+        responder: Responder<MemoryDump>,
+    },
 }
 
 /// Queue dump format with handler.
@@ -88,6 +105,11 @@ impl Debug for ControlAnnouncement {
                 .field("msg", msg)
                 .finish(),
             Self::QueueDumpRequest { .. } => f.debug_struct("QueueDump").finish_non_exhaustive(),
+            Self::QueueSummaryRequest { include_events, .. } => f
+                .debug_struct("QueueSummary")
+                .field("include_events", include_events)
+                .finish_non_exhaustive(),
+            Self::MemoryDumpRequest { .. } => f.debug_struct("MemoryDump").finish_non_exhaustive(),
         }
     }
 }
@@ -101,6 +123,12 @@ impl Display for ControlAnnouncement {
             ControlAnnouncement::QueueDumpRequest { .. } => {
                 write!(f, "dump event queue")
             }
+            ControlAnnouncement::QueueSummaryRequest { .. } => {
+                write!(f, "summarize event queue")
+            }
+            ControlAnnouncement::MemoryDumpRequest { .. } => {
+                write!(f, "dump memory usage")
+            }
         }
     }
 }
@@ -116,6 +144,20 @@ pub(crate) enum RpcServerAnnouncement {
         /// A client responder in the case where a client submits a deploy.
         responder: Option<Responder<Result<(), Error>>>,
     },
+    /// A deploy received from a client which should only be validated, never stored or gossiped.
+    DeployPrecheckRequested {
+        /// The received deploy.
+        deploy: Box<Deploy>,
+        /// The client responder.
+        responder: Responder<Result<(), Error>>,
+    },
+    /// A new transaction in the versioned format received from a client.
+    TransactionV2Received {
+        /// The received transaction.
+        transaction: Box<TransactionV2>,
+        /// The client responder.
+        responder: Responder<Result<(), Error>>,
+    },
 }
 
 impl Display for RpcServerAnnouncement {
@@ -124,6 +166,16 @@ impl Display for RpcServerAnnouncement {
             RpcServerAnnouncement::DeployReceived { deploy, .. } => {
                 write!(formatter, "api server received {}", deploy.id())
             }
+            RpcServerAnnouncement::DeployPrecheckRequested { deploy, .. } => {
+                write!(
+                    formatter,
+                    "api server received {} for precheck",
+                    deploy.id()
+                )
+            }
+            RpcServerAnnouncement::TransactionV2Received { transaction, .. } => {
+                write!(formatter, "api server received {}", transaction.hash())
+            }
         }
     }
 }
@@ -197,6 +249,13 @@ pub(crate) enum ConsensusAnnouncement {
         /// The timestamp when the evidence of the equivocation was detected.
         timestamp: Timestamp,
     },
+    /// Deploys that were proposed in an era which ended without finalizing them.
+    OrphanedDeploys {
+        /// The era in which the deploys were proposed.
+        era_id: EraId,
+        /// The hashes of the orphaned deploys and transfers.
+        deploy_hashes: Vec<DeployHash>,
+    },
 }
 
 impl Display for ConsensusAnnouncement {
@@ -217,15 +276,28 @@ impl Display for ConsensusAnnouncement {
                 "Validator fault with public key: {} has been identified at time: {} in era: {}",
                 public_key, timestamp, era_id,
             ),
+            ConsensusAnnouncement::OrphanedDeploys {
+                era_id,
+                deploy_hashes,
+            } => write!(
+                formatter,
+                "{} deploys orphaned at the end of era {}",
+                deploy_hashes.len(),
+                era_id
+            ),
         }
     }
 }
 
-/// A block-list related announcement.
+/// A block-list related announcement, reporting behavior which affects a peer's reputation.
 #[derive(Debug, Serialize)]
 pub(crate) enum BlocklistAnnouncement {
     /// A given peer committed a blockable offense.
     OffenseCommitted(Box<NodeId>),
+    /// A given peer did not respond to a request in time.
+    SlowResponse(Box<NodeId>),
+    /// A given peer gossiped an item which was new to us.
+    UsefulGossip(Box<NodeId>),
 }
 
 impl Display for BlocklistAnnouncement {
@@ -234,6 +306,12 @@ impl Display for BlocklistAnnouncement {
             BlocklistAnnouncement::OffenseCommitted(peer) => {
                 write!(f, "peer {} committed offense", peer)
             }
+            BlocklistAnnouncement::SlowResponse(peer) => {
+                write!(f, "peer {} responded too slowly", peer)
+            }
+            BlocklistAnnouncement::UsefulGossip(peer) => {
+                write!(f, "peer {} gossiped a new item", peer)
+            }
         }
     }
 }
@@ -266,6 +344,8 @@ pub(crate) enum LinearChainAnnouncement {
     BlockAdded(Box<Block>),
     /// New finality signature received.
     NewFinalitySignature(Box<FinalitySignature>),
+    /// The stored finality signatures of a block have crossed the finality threshold.
+    BlockFinalized(Box<BlockSignatures>),
 }
 
 impl Display for LinearChainAnnouncement {
@@ -277,6 +357,9 @@ impl Display for LinearChainAnnouncement {
             LinearChainAnnouncement::NewFinalitySignature(fs) => {
                 write!(f, "new finality signature {}", fs.block_hash)
             }
+            LinearChainAnnouncement::BlockFinalized(signatures) => {
+                write!(f, "block finalized {}", signatures.block_hash)
+            }
         }
     }
 }
@@ -315,6 +398,10 @@ pub(crate) enum ContractRuntimeAnnouncement {
         era_id: EraId,
         /// The operations and transforms committed to global state.
         execution_effect: ExecutionEffect,
+        /// The rewards distributed to the validators and delegators of the era.
+        era_rewards: Option<Box<EraRewards>>,
+        /// The stakes held in the era and the validators evicted at its end.
+        era_stakes: Option<Box<EraStakes>>,
     },
     /// New era validators.
     UpcomingEraValidators {
@@ -355,6 +442,8 @@ pub(crate) enum ChainSynchronizerAnnouncement {
     /// synchronizing. Once this message is received, the only way for the peer to signal it's in
     /// the syncing process is to reconnect.
     SyncFinished,
+    /// A periodic report of the progress of the ongoing synchronization.
+    SyncProgress(SyncStatus),
 }
 
 impl Display for ChainSynchronizerAnnouncement {
@@ -363,6 +452,26 @@ impl Display for ChainSynchronizerAnnouncement {
             ChainSynchronizerAnnouncement::SyncFinished => {
                 write!(f, "synchronization finished")
             }
+            ChainSynchronizerAnnouncement::SyncProgress(sync_status) => {
+                write!(f, "synchronization progress: {:?}", sync_status.phase)
+            }
+        }
+    }
+}
+
+/// A finality watchdog announcement.
+#[derive(Debug, Serialize)]
+pub(crate) enum FinalityWatchdogAnnouncement {
+    /// The node's finality status has changed.
+    StatusChanged(FinalityAlert),
+}
+
+impl Display for FinalityWatchdogAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FinalityWatchdogAnnouncement::StatusChanged(alert) => {
+                write!(f, "finality status changed to {}", alert.status)
+            }
         }
     }
 }
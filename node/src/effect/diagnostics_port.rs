@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    fmt::{Debug, Display},
+    fmt::{self, Debug, Display, Formatter},
 };
 
 use casper_types::EraId;
@@ -9,7 +9,11 @@ use futures::Future;
 use serde::Serialize;
 
 use super::Responder;
-use crate::components::consensus::EraDump;
+use crate::components::{
+    consensus::{EraDump, ValidatorMode},
+    linear_chain::ShutdownPoint,
+    small_network::NetworkDump,
+};
 
 /// A request to dump the internal consensus state of a specific era.
 #[derive(DataSize, Serialize)]
@@ -58,3 +62,150 @@ impl Debug for DumpConsensusStateRequest {
             .finish_non_exhaustive()
     }
 }
+
+/// A request to dump the state of the networking component's connection manager.
+#[derive(DataSize, Serialize)]
+pub(crate) struct DumpNetworkStateRequest {
+    /// Responder to send the dump into.
+    pub(crate) responder: Responder<NetworkDump>,
+}
+
+impl Display for DumpNetworkStateRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "dump network state")
+    }
+}
+
+impl Debug for DumpNetworkStateRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DumpNetworkStateRequest")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A request to reload the reloadable subset of the node's config file.
+#[derive(DataSize, Serialize)]
+pub(crate) struct ReloadConfigRequest {
+    /// Responder to send the outcome of the reload into.
+    pub(crate) responder: Responder<Result<ConfigReloadReport, Cow<'static, str>>>,
+}
+
+impl Display for ReloadConfigRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "reload config")
+    }
+}
+
+impl Debug for ReloadConfigRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReloadConfigRequest")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A request to show or replace the point after which the node shuts down.
+#[derive(DataSize, Serialize)]
+pub(crate) struct ShutdownPointRequest {
+    /// The new shutdown point, or `None` to only show the current one.
+    pub(crate) update: Option<ShutdownPoint>,
+    /// Responder to send the shutdown point in effect into.
+    pub(crate) responder: Responder<Result<ShutdownPoint, Cow<'static, str>>>,
+}
+
+impl Display for ShutdownPointRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.update {
+            Some(shutdown_point) => write!(f, "set shutdown point: {}", shutdown_point),
+            None => write!(f, "get shutdown point"),
+        }
+    }
+}
+
+impl Debug for ShutdownPointRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShutdownPointRequest")
+            .field("update", &self.update)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A request to show or change whether the node takes part in consensus as a validator.
+#[derive(DataSize, Serialize)]
+pub(crate) struct ValidatorModeRequest {
+    /// The mode to switch to when the next era begins, or `None` to only show the current mode.
+    pub(crate) update: Option<ValidatorMode>,
+    /// Responder to send the resulting validator mode status into.
+    pub(crate) responder: Responder<Result<ValidatorModeStatus, Cow<'static, str>>>,
+}
+
+impl Display for ValidatorModeRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.update {
+            Some(mode) => write!(f, "set validator mode from next era: {}", mode),
+            None => write!(f, "get validator mode"),
+        }
+    }
+}
+
+impl Debug for ValidatorModeRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidatorModeRequest")
+            .field("update", &self.update)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The validator mode of the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct ValidatorModeStatus {
+    /// The current era.
+    pub(crate) current_era: EraId,
+    /// Whether this node is a validator in the current era.
+    pub(crate) is_validator: bool,
+    /// The mode in the current era.
+    pub(crate) mode: ValidatorMode,
+    /// The mode from the next era on.
+    pub(crate) next_era_mode: ValidatorMode,
+}
+
+impl Display for ValidatorModeStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} in {} ({}), {} from the next era",
+            self.mode,
+            self.current_era,
+            if self.is_validator {
+                "validator"
+            } else {
+                "not a validator"
+            },
+            self.next_era_mode
+        )
+    }
+}
+
+/// The outcome of reloading the config file.
+///
+/// Keys are given as `<section>.<key>`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct ConfigReloadReport {
+    /// Changed keys whose new values were applied to the running node.
+    pub(crate) applied: Vec<String>,
+    /// Changed keys which only take effect once the node is restarted.
+    pub(crate) requires_restart: Vec<String>,
+}
+
+impl Display for ConfigReloadReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.applied.is_empty() && self.requires_restart.is_empty() {
+            return write!(f, "no config changes");
+        }
+        write!(
+            f,
+            "applied: [{}], requires restart: [{}]",
+            self.applied.join(", "),
+            self.requires_restart.join(", ")
+        )
+    }
+}
@@ -9,12 +9,12 @@ use datasize::DataSize;
 use serde::Serialize;
 
 use crate::{
-    components::{consensus, gossiper},
+    components::{consensus, deploy_acceptor, gossiper},
     protocol::Message,
-    types::{FinalitySignature, NodeId, Tag},
+    types::{BlockSignatures, FinalitySignature, NodeId, Tag},
 };
 
-use super::AutoClosingResponder;
+use super::{AutoClosingResponder, Responder};
 
 /// An envelope for an incoming message, attaching a sender address.
 #[derive(DataSize, Debug, Serialize)]
@@ -52,6 +52,27 @@ where
     }
 }
 
+/// An envelope for an incoming message whose processing is acknowledged to the networking layer,
+/// attaching a sender address and the responder to acknowledge through.
+#[derive(DataSize, Debug, Serialize)]
+pub struct AcknowledgedIncoming<M, T> {
+    /// The sender from which the message originated.
+    pub(crate) sender: NodeId,
+    /// The wrapped message.
+    pub(crate) message: M,
+    /// Responder to acknowledge the message through once it has been processed.
+    pub(crate) responder: Responder<T>,
+}
+
+impl<M, T> Display for AcknowledgedIncoming<M, T>
+where
+    M: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "acknowledged from {}: {}", self.sender, self.message)
+    }
+}
+
 /// A new consensus message arrived.
 pub(crate) type ConsensusMessageIncoming = MessageIncoming<consensus::ConsensusMessage>;
 
@@ -73,9 +94,17 @@ pub(crate) type NetResponseIncoming = MessageIncoming<NetResponse>;
 /// A new message responding to a trie request arrived.
 pub(crate) type TrieResponseIncoming = MessageIncoming<TrieResponse>;
 
+/// A new message responding to a deploy request arrived, to be acknowledged once the deploy has
+/// been accepted or rejected.
+pub(crate) type DeployResponseIncoming =
+    AcknowledgedIncoming<DeployResponse, Result<(), deploy_acceptor::Error>>;
+
 /// A new finality signature arrived over the network.
 pub(crate) type FinalitySignatureIncoming = MessageIncoming<Box<FinalitySignature>>;
 
+/// A new batch of finality signatures for a single block arrived over the network.
+pub(crate) type FinalitySignaturesIncoming = MessageIncoming<Box<BlockSignatures>>;
+
 /// A request for an object out of storage arrived.
 ///
 /// Note: The variants here are grouped under a common enum, since they are usually handled by the
@@ -252,6 +281,16 @@ impl Display for TrieResponse {
     }
 }
 
+/// A response to a request for a deploy, subject to flow control.
+#[derive(Debug, Serialize)]
+pub(crate) struct DeployResponse(pub(crate) Arc<[u8]>);
+
+impl Display for DeployResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("response, deploy")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::NetRequest;
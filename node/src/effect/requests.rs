@@ -8,6 +8,7 @@ use std::{
     collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     mem,
+    ops::RangeInclusive,
     sync::Arc,
 };
 
@@ -26,40 +27,45 @@ use casper_execution_engine::{
         query::{QueryRequest, QueryResult},
         UpgradeConfig, UpgradeSuccess,
     },
+    shared::gas_profile::GasProfile,
     storage::trie::{TrieOrChunk, TrieOrChunkId},
 };
 use casper_hashing::Digest;
 use casper_types::{
-    bytesrepr::Bytes, system::auction::EraValidators, EraId, ExecutionResult, Key, ProtocolVersion,
-    PublicKey, TimeDiff, Transfer, URef,
+    account::AccountHash, bytesrepr::Bytes, system::auction::EraValidators, EraId, ExecutionResult,
+    Key, ProtocolVersion, PublicKey, TimeDiff, TransactionV2, TransactionV2Hash, TransferV2, URef,
 };
 
 use crate::{
     components::{
+        block_proposer::{DeployBlocklist, DeployBlocklistUpdate, DeployInfo},
         block_validator::ValidatingBlock,
+        chain_synchronizer::SyncStatus,
         consensus::{BlockContext, ClContext, ValidatorChange},
         contract_runtime::{
             BlockAndExecutionEffects, BlockExecutionError, EraValidatorsRequest, ExecutionPreState,
         },
         deploy_acceptor::Error,
         fetcher::FetchResult,
+        storage::PrunedHistory,
     },
     contract_runtime::SpeculativeExecutionState,
     effect::{AutoClosingResponder, Responder},
     rpcs::{chain::BlockIdentifier, docs::OpenRpcSchema},
     types::{
-        AvailableBlockRange, Block, BlockAndDeploys, BlockHash, BlockHeader,
-        BlockHeaderWithMetadata, BlockHeadersBatch, BlockHeadersBatchId, BlockPayload,
-        BlockSignatures, BlockWithMetadata, Chainspec, ChainspecInfo, ChainspecRawBytes, Deploy,
-        DeployHash, DeployMetadataExt, DeployWithFinalizedApprovals, FinalizedApprovals,
-        FinalizedBlock, Item, NodeId, NodeState, StatusFeed,
+        peers_map::PeerReputation, AvailableBlockRange, Block, BlockAndDeploys, BlockHash,
+        BlockHeader, BlockHeaderWithMetadata, BlockHeadersBatch, BlockHeadersBatchId, BlockHeight,
+        BlockPayload, BlockSignals, BlockSignatures, BlockSummary, BlockWithMetadata, Chainspec,
+        ChainspecInfo, ChainspecRawBytes, Deploy, DeployHash, DeployMetadataExt,
+        DeployOrTransferHash, DeployWithFinalizedApprovals, EraRewards, EraStakes,
+        FinalizedApprovals, FinalizedBlock, Item, NodeId, NodeState, StatusFeed, Transaction,
     },
     utils::{DisplayIter, Source},
 };
 
 // Redirection for reactor macro.
 #[allow(unused_imports)]
-pub(crate) use super::diagnostics_port::DumpConsensusStateRequest;
+pub(crate) use super::diagnostics_port::{DumpConsensusStateRequest, ReloadConfigRequest};
 
 const _STORAGE_REQUEST_SIZE: usize = mem::size_of::<StorageRequest>();
 const_assert!(_STORAGE_REQUEST_SIZE < 89);
@@ -205,6 +211,11 @@ pub(crate) enum NetworkInfoRequest {
         /// Responder to be called with all connected non-syncing peers in random order.
         responder: Responder<Vec<NodeId>>,
     },
+    /// Get the reputation of all peers with a score or recent bans.
+    PeerReputations {
+        /// Responder to be called with the reputations, ordered by node ID.
+        responder: Responder<Vec<PeerReputation>>,
+    },
 }
 
 impl Display for NetworkInfoRequest {
@@ -219,6 +230,9 @@ impl Display for NetworkInfoRequest {
             NetworkInfoRequest::FullyConnectedNonSyncingPeers { responder: _ } => {
                 write!(formatter, "get fully connected non-syncing peers")
             }
+            NetworkInfoRequest::PeerReputations { responder: _ } => {
+                write!(formatter, "get peer reputations")
+            }
         }
     }
 }
@@ -324,7 +338,7 @@ pub(crate) enum StorageRequest {
     },
     GetBlockHeaderByHeight {
         /// Height of block to get header of.
-        block_height: u64,
+        block_height: BlockHeight,
         /// Flag indicating whether storage should check the block availability before trying to
         /// retrieve it.
         only_from_available_block_range: bool,
@@ -345,7 +359,59 @@ pub(crate) enum StorageRequest {
         block_hash: BlockHash,
         /// Responder to call with the result.  Returns `None` is the transfers do not exist in
         /// local storage under the block_hash provided.
-        responder: Responder<Option<Vec<Transfer>>>,
+        responder: Responder<Option<Vec<TransferV2>>>,
+    },
+    /// Retrieve summaries of the blocks with heights in the given range.
+    GetBlockSummaries {
+        /// The inclusive range of block heights.
+        block_heights: RangeInclusive<u64>,
+        /// Flag indicating whether storage should check the block availability before trying to
+        /// retrieve the blocks.
+        only_from_available_block_range: bool,
+        /// Responder to call with the result.  Blocks not present in local storage are omitted.
+        responder: Responder<Vec<BlockSummary>>,
+    },
+    /// Store the signaling bits of a finalized block.
+    PutBlockSignals {
+        /// The block's signals.
+        block_signals: Box<BlockSignals>,
+        /// Responder to call when the signals have been stored.
+        responder: Responder<()>,
+    },
+    /// Retrieve the signaling bits of the finalized blocks with heights in the given range.
+    GetBlockSignals {
+        /// The inclusive range of block heights.
+        block_heights: RangeInclusive<u64>,
+        /// Responder to call with the result.  Blocks without stored signals are omitted.
+        responder: Responder<Vec<BlockSignals>>,
+    },
+    /// Store the rewards distributed at the end of an era.
+    PutEraRewards {
+        /// The era's rewards.
+        era_rewards: Box<EraRewards>,
+        /// Responder to call when the rewards have been stored.
+        responder: Responder<()>,
+    },
+    /// Retrieve the rewards distributed at the end of the eras in the given range.
+    GetEraRewards {
+        /// The inclusive range of era IDs.
+        era_ids: RangeInclusive<u64>,
+        /// Responder to call with the result.  Eras without stored rewards are omitted.
+        responder: Responder<Vec<EraRewards>>,
+    },
+    /// Store the stakes held in an era and the validators evicted at its end.
+    PutEraStakes {
+        /// The era's stakes.
+        era_stakes: Box<EraStakes>,
+        /// Responder to call when the stakes have been stored.
+        responder: Responder<()>,
+    },
+    /// Retrieve the stakes held in the eras in the given range.
+    GetEraStakes {
+        /// The inclusive range of era IDs.
+        era_ids: RangeInclusive<u64>,
+        /// Responder to call with the result.  Eras without stored stakes are omitted.
+        responder: Responder<Vec<EraStakes>>,
     },
     /// Store given deploy.
     PutDeploy {
@@ -355,6 +421,13 @@ pub(crate) enum StorageRequest {
         /// attempt or false if it was previously stored.
         responder: Responder<bool>,
     },
+    /// Retrieve the transaction in the versioned format with the given hash.
+    GetTransactionV2 {
+        /// Hash of the transaction to be retrieved.
+        transaction_hash: TransactionV2Hash,
+        /// Responder to call with the result.
+        responder: Responder<Option<TransactionV2>>,
+    },
     /// Retrieve deploys with given hashes.
     GetDeploys {
         /// Hashes of deploys to be retrieved.
@@ -488,6 +561,13 @@ pub(crate) enum StorageRequest {
         /// Responder, responded to once the approvals are written.
         responder: Responder<()>,
     },
+    /// Prune the deploys and execution results of blocks outside the retention window.
+    PruneHistory {
+        /// The maximum number of blocks to prune.
+        max_blocks: u64,
+        /// Responder to call with the outcome, or `None` if all history is retained.
+        responder: Responder<Option<PrunedHistory>>,
+    },
 }
 
 impl Display for StorageRequest {
@@ -530,7 +610,43 @@ impl Display for StorageRequest {
             StorageRequest::GetBlockTransfers { block_hash, .. } => {
                 write!(formatter, "get transfers for {}", block_hash)
             }
+            StorageRequest::GetBlockSummaries { block_heights, .. } => write!(
+                formatter,
+                "get block summaries for heights {}..={}",
+                block_heights.start(),
+                block_heights.end()
+            ),
+            StorageRequest::PutBlockSignals { block_signals, .. } => {
+                write!(formatter, "put signals for height {}", block_signals.height)
+            }
+            StorageRequest::GetBlockSignals { block_heights, .. } => write!(
+                formatter,
+                "get signals for heights {}..={}",
+                block_heights.start(),
+                block_heights.end()
+            ),
+            StorageRequest::PutEraRewards { era_rewards, .. } => {
+                write!(formatter, "put rewards for {}", era_rewards.era_id)
+            }
+            StorageRequest::GetEraRewards { era_ids, .. } => write!(
+                formatter,
+                "get rewards for eras {}..={}",
+                era_ids.start(),
+                era_ids.end()
+            ),
+            StorageRequest::PutEraStakes { era_stakes, .. } => {
+                write!(formatter, "put stakes for {}", era_stakes.era_id)
+            }
+            StorageRequest::GetEraStakes { era_ids, .. } => write!(
+                formatter,
+                "get stakes for eras {}..={}",
+                era_ids.start(),
+                era_ids.end()
+            ),
             StorageRequest::PutDeploy { deploy, .. } => write!(formatter, "put {}", deploy),
+            StorageRequest::GetTransactionV2 {
+                transaction_hash, ..
+            } => write!(formatter, "get transaction {}", transaction_hash),
             StorageRequest::GetDeploys { deploy_hashes, .. } => {
                 write!(formatter, "get {}", DisplayIter::new(deploy_hashes.iter()))
             }
@@ -601,6 +717,9 @@ impl Display for StorageRequest {
             } => {
                 write!(formatter, "get block headers batch: {}", block_headers_id)
             }
+            StorageRequest::PruneHistory { max_blocks, .. } => {
+                write!(formatter, "prune history of up to {} blocks", max_blocks)
+            }
         }
     }
 }
@@ -651,6 +770,13 @@ pub(crate) enum StateStoreRequest {
         /// Responder for value, if found, returning the previously passed in serialization form.
         responder: Responder<Option<Vec<u8>>>,
     },
+    /// Removes a piece of state from storage.
+    Delete {
+        /// Key to remove.
+        key: Cow<'static, [u8]>,
+        /// Notification when removing is complete.
+        responder: Responder<()>,
+    },
 }
 
 impl Display for StateStoreRequest {
@@ -667,6 +793,9 @@ impl Display for StateStoreRequest {
             StateStoreRequest::Load { key, .. } => {
                 write!(f, "load data from key {}", base16::encode_lower(key))
             }
+            StateStoreRequest::Delete { key, .. } => {
+                write!(f, "delete data under key {}", base16::encode_lower(key))
+            }
         }
     }
 }
@@ -695,6 +824,55 @@ pub(crate) struct BlockPayloadRequest {
 pub(crate) enum BlockProposerRequest {
     /// Request a list of deploys to propose in a new block.
     RequestBlockPayload(BlockPayloadRequest),
+    /// Request the dependencies of a pending deploy which are neither executed nor pending.
+    GetUnresolvableDependencies {
+        /// The hash of the pending deploy.
+        deploy_hash: DeployHash,
+        /// Responder to call with the unresolvable dependencies, or `None` if the deploy is not
+        /// pending.
+        responder: Responder<Option<Vec<DeployHash>>>,
+    },
+    /// Check whether another deploy from the given account can be buffered without exceeding the
+    /// per-account quota of pending deploys.
+    CheckPendingDeployQuota {
+        /// The account which signed the deploy.
+        account: PublicKey,
+        /// Responder to call with `true` if the account has not yet reached its quota.
+        responder: Responder<bool>,
+    },
+    /// Check whether a deploy fails to outbid the pending deploy with the same replacement tag.
+    CheckDeployReplacement {
+        /// The hash of the deploy.
+        hash: DeployOrTransferHash,
+        /// The deploy's information.
+        deploy_info: Box<DeployInfo>,
+        /// Responder to call with the hash of the pending deploy with the same replacement tag
+        /// which the deploy fails to outbid, if any.
+        responder: Responder<Option<DeployHash>>,
+    },
+    /// Check whether a deploy or its account is on the operator's blocklist.
+    CheckDeployBlocklist {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// The hash of the account which signed the deploy.
+        account_hash: AccountHash,
+        /// Responder to call with `true` if the deploy is blocked.
+        responder: Responder<bool>,
+    },
+    /// Add a deploy or an account to, or remove it from the operator's blocklist.
+    UpdateDeployBlocklist {
+        /// The change to apply.
+        update: DeployBlocklistUpdate,
+        /// Responder to call with `true` if the blocklist changed, or `None` if there is no block
+        /// proposer running to maintain the blocklist.
+        responder: Responder<Option<bool>>,
+    },
+    /// Get the operator's blocklist.
+    GetDeployBlocklist {
+        /// Responder to call with the blocklist, or `None` if there is no block proposer running to
+        /// maintain the blocklist.
+        responder: Responder<Option<DeployBlocklist>>,
+    },
 }
 
 impl Display for BlockProposerRequest {
@@ -713,6 +891,26 @@ impl Display for BlockProposerRequest {
                 context.height(),
                 next_finalized
             ),
+            BlockProposerRequest::GetUnresolvableDependencies { deploy_hash, .. } => {
+                write!(
+                    formatter,
+                    "get unresolvable dependencies of {}",
+                    deploy_hash
+                )
+            }
+            BlockProposerRequest::CheckPendingDeployQuota { account, .. } => {
+                write!(formatter, "check pending deploy quota for {}", account)
+            }
+            BlockProposerRequest::CheckDeployReplacement { hash, .. } => {
+                write!(formatter, "check replacement by {}", hash)
+            }
+            BlockProposerRequest::CheckDeployBlocklist { deploy_hash, .. } => {
+                write!(formatter, "check blocklist for {}", deploy_hash)
+            }
+            BlockProposerRequest::UpdateDeployBlocklist { update, .. } => {
+                write!(formatter, "update blocklist: {}", update)
+            }
+            BlockProposerRequest::GetDeployBlocklist { .. } => write!(formatter, "get blocklist"),
         }
     }
 }
@@ -731,6 +929,20 @@ pub(crate) enum RpcRequest {
         /// Responder to call.
         responder: Responder<Result<(), Error>>,
     },
+    /// Submit a transaction in the versioned format to be validated and stored.
+    SubmitTransactionV2 {
+        /// The transaction to be submitted.
+        transaction: Box<TransactionV2>,
+        /// Responder to call.
+        responder: Responder<Result<(), Error>>,
+    },
+    /// Submit a deploy to be validated without being stored or gossiped.
+    PrecheckDeploy {
+        /// The deploy to be validated.
+        deploy: Box<Deploy>,
+        /// Responder to call.
+        responder: Responder<Result<(), Error>>,
+    },
     /// If `maybe_identifier` is `Some`, return the specified block if it exists, else `None`.  If
     /// `maybe_identifier` is `None`, return the latest block.
     GetBlock {
@@ -747,7 +959,7 @@ pub(crate) enum RpcRequest {
         /// The hash of the block to retrieve transfers for.
         block_hash: BlockHash,
         /// Responder to call with the result.
-        responder: Responder<Option<Vec<Transfer>>>,
+        responder: Responder<Option<Vec<TransferV2>>>,
     },
     /// Query the global state at the given root hash.
     QueryGlobalState {
@@ -810,15 +1022,18 @@ pub(crate) enum RpcRequest {
         /// Responder to call with the result.
         responder: Responder<AvailableBlockRange>,
     },
-    /// Executs a deploy against a specified block, returning the effects.
+    /// Executs a deploy or transaction against a specified block, returning the effects.
     /// Does not commit the effects. This is a "read-only" action.
     SpeculativeDeployExecute {
         /// Block header representing the state on top of which we will run the deploy.
         block_header: BlockHeader,
-        /// Deploy to execute.
-        deploy: Box<Deploy>,
+        /// Deploy or transaction to execute.
+        transaction: Box<Transaction>,
+        /// Whether to profile the gas usage of the execution.
+        profile_gas: bool,
         /// Responder.
-        responder: Responder<Result<Option<ExecutionResult>, engine_state::Error>>,
+        responder:
+            Responder<Result<Option<(ExecutionResult, Option<GasProfile>)>, engine_state::Error>>,
     },
 }
 
@@ -826,6 +1041,12 @@ impl Display for RpcRequest {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
             RpcRequest::SubmitDeploy { deploy, .. } => write!(formatter, "submit {}", *deploy),
+            RpcRequest::SubmitTransactionV2 { transaction, .. } => {
+                write!(formatter, "submit {}", *transaction)
+            }
+            RpcRequest::PrecheckDeploy { deploy, .. } => {
+                write!(formatter, "precheck {}", *deploy)
+            }
             RpcRequest::GetBlock {
                 maybe_id: Some(BlockIdentifier::Hash(hash)),
                 ..
@@ -1045,10 +1266,13 @@ pub(crate) enum ContractRuntimeRequest {
     SpeculativeDeployExecution {
         /// Hash of a block on top of which to execute the deploy.
         execution_prestate: SpeculativeExecutionState,
-        /// Deploy to execute.
-        deploy: Box<Deploy>,
-        /// Results
-        responder: Responder<Result<Option<ExecutionResult>, engine_state::Error>>,
+        /// Deploy or transaction to execute.
+        transaction: Box<Transaction>,
+        /// Whether to profile the gas usage of the execution.
+        profile_gas: bool,
+        /// Results, along with the gas profile if requested.
+        responder:
+            Responder<Result<Option<(ExecutionResult, Option<GasProfile>)>, engine_state::Error>>,
     },
 }
 
@@ -1119,13 +1343,13 @@ impl Display for ContractRuntimeRequest {
             }
             ContractRuntimeRequest::SpeculativeDeployExecution {
                 execution_prestate,
-                deploy,
+                transaction,
                 ..
             } => {
                 write!(
                     formatter,
                     "Execute {} on {}",
-                    deploy.id(),
+                    transaction.hash(),
                     execution_prestate.state_root_hash
                 )
             }
@@ -1175,8 +1399,6 @@ impl Display for BlockValidationRequest {
     }
 }
 
-type BlockHeight = u64;
-
 #[derive(DataSize, Debug)]
 #[must_use]
 /// Consensus component requests.
@@ -1207,9 +1429,9 @@ impl Display for ChainspecLoaderRequest {
     }
 }
 
-/// ChainSynchronizer component request.
+/// ChainSynchronizer component request for the node state and, while syncing, the sync status.
 #[derive(Debug, Serialize)]
-pub(crate) struct NodeStateRequest(pub(crate) Responder<NodeState>);
+pub(crate) struct NodeStateRequest(pub(crate) Responder<(NodeState, Option<SyncStatus>)>);
 
 impl Display for NodeStateRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
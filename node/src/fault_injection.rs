@@ -0,0 +1,239 @@
+//! Hooks for injecting faults into storage and networking.
+//!
+//! These allow crash-recovery and retry logic to be exercised in tests, by making writes of
+//! specific types to storage fail, delaying reads of tries from global state, or dropping specific
+//! kinds of messages at the networking layer. They are only compiled with the `fault-injection`
+//! feature enabled, or in tests.
+//!
+//! Faults are injected process-wide, i.e. they affect every node running in the process, and stay
+//! in effect until the [`FaultGuard`] returned when injecting them is dropped.
+
+use std::{
+    any,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    thread,
+    time::Duration,
+};
+
+use once_cell::sync::Lazy;
+use tracing::debug;
+
+pub use crate::components::small_network::MessageKind;
+
+/// The faults currently in effect.
+static FAULTS: Lazy<RwLock<Vec<InjectedFault>>> = Lazy::new(Default::default);
+
+/// The ID of the next injected fault.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The direction of messages dropped by the networking layer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Messages sent to peers.
+    Outgoing,
+    /// Messages received from peers.
+    Incoming,
+    /// Messages both sent to and received from peers.
+    Both,
+}
+
+impl Direction {
+    fn includes(self, direction: Direction) -> bool {
+        self == Direction::Both || self == direction
+    }
+}
+
+#[derive(Debug)]
+enum Fault {
+    /// Writes of values with the given type name fail, `remaining` more times if limited.
+    FailStorageWrites {
+        type_name: &'static str,
+        remaining: Option<AtomicUsize>,
+    },
+    /// Reads of tries from global state are delayed.
+    DelayTrieReads(Duration),
+    /// Messages of the given kind are dropped.
+    DropMessages {
+        kind: MessageKind,
+        direction: Direction,
+    },
+}
+
+#[derive(Debug)]
+struct InjectedFault {
+    id: u64,
+    fault: Fault,
+    triggered: Arc<AtomicUsize>,
+}
+
+/// A fault in effect, which is removed when this guard is dropped.
+#[derive(Debug)]
+#[must_use = "the fault is removed when the guard is dropped"]
+pub struct FaultGuard {
+    id: u64,
+    triggered: Arc<AtomicUsize>,
+}
+
+impl FaultGuard {
+    /// Returns the number of times the fault has been triggered so far.
+    pub fn triggered(&self) -> usize {
+        self.triggered.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for FaultGuard {
+    fn drop(&mut self) {
+        write_faults().retain(|injected| injected.id != self.id);
+    }
+}
+
+fn inject(fault: Fault) -> FaultGuard {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let triggered = Arc::new(AtomicUsize::new(0));
+    debug!(id, ?fault, "injecting fault");
+    write_faults().push(InjectedFault {
+        id,
+        fault,
+        triggered: Arc::clone(&triggered),
+    });
+    FaultGuard { id, triggered }
+}
+
+fn read_faults() -> RwLockReadGuard<'static, Vec<InjectedFault>> {
+    // A panic while holding the lock cannot leave the faults inconsistent, so ignore poisoning.
+    FAULTS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn write_faults() -> RwLockWriteGuard<'static, Vec<InjectedFault>> {
+    FAULTS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Makes writes of values of type `T` to storage fail with an I/O error.
+///
+/// If `count` is given, only the next `count` writes fail.
+pub fn fail_storage_writes<T: ?Sized>(count: Option<usize>) -> FaultGuard {
+    inject(Fault::FailStorageWrites {
+        type_name: any::type_name::<T>(),
+        remaining: count.map(AtomicUsize::new),
+    })
+}
+
+/// Delays every read of a trie (or trie chunk) from global state by `delay`.
+///
+/// The delay blocks the reading thread, as a slow disk would.
+pub fn delay_trie_reads(delay: Duration) -> FaultGuard {
+    inject(Fault::DelayTrieReads(delay))
+}
+
+/// Drops messages of the given `kind` travelling in the given `direction`.
+pub fn drop_messages(kind: MessageKind, direction: Direction) -> FaultGuard {
+    inject(Fault::DropMessages { kind, direction })
+}
+
+/// Returns whether a write of a value of type `T` to storage should fail.
+pub(crate) fn storage_write_fails<T: ?Sized>() -> bool {
+    let type_name = any::type_name::<T>();
+    read_faults().iter().any(|injected| match &injected.fault {
+        Fault::FailStorageWrites {
+            type_name: faulty_type_name,
+            remaining,
+        } if *faulty_type_name == type_name => {
+            let fails = match remaining {
+                Some(remaining) => remaining
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+                        remaining.checked_sub(1)
+                    })
+                    .is_ok(),
+                None => true,
+            };
+            if fails {
+                injected.triggered.fetch_add(1, Ordering::SeqCst);
+            }
+            fails
+        }
+        _ => false,
+    })
+}
+
+/// Blocks for the longest injected trie read delay, if any.
+pub(crate) fn delay_trie_read() {
+    let delay = read_faults()
+        .iter()
+        .filter_map(|injected| match injected.fault {
+            Fault::DelayTrieReads(delay) => {
+                injected.triggered.fetch_add(1, Ordering::SeqCst);
+                Some(delay)
+            }
+            _ => None,
+        })
+        .max();
+    if let Some(delay) = delay {
+        thread::sleep(delay);
+    }
+}
+
+/// Returns whether a message of the given `kind` travelling in the given `direction` should be
+/// dropped.
+pub(crate) fn message_dropped(kind: MessageKind, direction: Direction) -> bool {
+    read_faults().iter().any(|injected| match injected.fault {
+        Fault::DropMessages {
+            kind: faulty_kind,
+            direction: faulty_direction,
+        } if faulty_kind == kind && faulty_direction.includes(direction) => {
+            injected.triggered.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test uses a type or message kind not faulted by any other test, as faults are
+    // process-wide.
+    struct FaultyValue;
+    struct OtherValue;
+
+    #[test]
+    fn should_fail_limited_number_of_storage_writes() {
+        let guard = fail_storage_writes::<FaultyValue>(Some(2));
+        assert!(storage_write_fails::<FaultyValue>());
+        assert!(!storage_write_fails::<OtherValue>());
+        assert!(storage_write_fails::<FaultyValue>());
+        assert!(!storage_write_fails::<FaultyValue>());
+        assert_eq!(guard.triggered(), 2);
+
+        let guard = fail_storage_writes::<FaultyValue>(None);
+        assert!(storage_write_fails::<FaultyValue>());
+        drop(guard);
+        assert!(!storage_write_fails::<FaultyValue>());
+    }
+
+    #[test]
+    fn should_drop_messages_in_given_direction() {
+        let guard = drop_messages(MessageKind::FinalizedApprovalsTransfer, Direction::Incoming);
+        assert!(message_dropped(
+            MessageKind::FinalizedApprovalsTransfer,
+            Direction::Incoming
+        ));
+        assert!(!message_dropped(
+            MessageKind::FinalizedApprovalsTransfer,
+            Direction::Outgoing
+        ));
+        assert_eq!(guard.triggered(), 1);
+        drop(guard);
+        assert!(!message_dropped(
+            MessageKind::FinalizedApprovalsTransfer,
+            Direction::Incoming
+        ));
+    }
+}
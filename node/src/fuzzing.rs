@@ -0,0 +1,104 @@
+//! Entry points for fuzzing the decoding of untrusted input.
+//!
+//! Each function mirrors the way the node decodes a particular kind of data received from peers,
+//! discarding the result. They are only compiled when fuzzing (`cargo fuzz` sets `--cfg fuzzing`)
+//! and are driven by the targets in the `fuzz` directory of this crate.
+
+use std::pin::Pin;
+
+use bincode::Options;
+use bytes::BytesMut;
+use tokio_serde::{Deserializer, Serializer};
+
+use casper_execution_engine::storage::trie::TrieOrChunk;
+use casper_types::bytesrepr::{FromBytes, ToBytes};
+
+use crate::{
+    components::{
+        fetcher::FetchedOrNotFound,
+        small_network::{BincodeFormat, GossipedAddress, Message, MessagePackFormat},
+    },
+    protocol,
+    types::{
+        Block, BlockAndDeploys, BlockHeader, BlockHeaderWithMetadata, BlockHeadersBatch,
+        BlockSignatures, BlockWithMetadata, Deploy, FinalizedApprovalsWithId, Item, Tag,
+    },
+};
+
+/// Decodes a handshake as received when establishing a connection, which is encoded as msgpack.
+///
+/// Successfully decoded messages are encoded again.
+pub fn decode_handshake(data: &[u8]) {
+    let mut format = MessagePackFormat;
+    let decoded: Result<Message<protocol::Message>, _> =
+        Pin::new(&mut format).deserialize(&BytesMut::from(data));
+    if let Ok(message) = decoded {
+        let _ = Pin::new(&mut format).serialize(&message);
+    }
+}
+
+/// Decodes a message as received on an established connection, which is encoded as bincode.
+///
+/// The items contained in fetch responses are decoded as well.
+pub fn decode_message(data: &[u8]) {
+    let decoded: Result<Message<protocol::Message>, _> =
+        BincodeFormat::default().0.deserialize(data);
+    if let Ok(Message::Payload(protocol::Message::GetResponse {
+        tag,
+        serialized_item,
+    })) = decoded
+    {
+        decode_tagged_fetch_response(tag, &serialized_item);
+    }
+}
+
+/// Decodes the serialized item of a fetch response, where the first byte is the item's tag.
+pub fn decode_fetch_response(data: &[u8]) {
+    if let Some((tag_byte, serialized_item)) = data.split_first() {
+        if let Ok(tag) = bincode::deserialize::<Tag>(&[*tag_byte]) {
+            decode_tagged_fetch_response(tag, serialized_item);
+        }
+    }
+}
+
+fn decode_tagged_fetch_response(tag: Tag, serialized_item: &[u8]) {
+    match tag {
+        Tag::Deploy => decode_fetched::<Deploy>(serialized_item),
+        Tag::FinalizedApprovals => decode_fetched::<FinalizedApprovalsWithId>(serialized_item),
+        Tag::Block => decode_fetched::<Block>(serialized_item),
+        Tag::GossipedAddress => decode_fetched::<GossipedAddress>(serialized_item),
+        Tag::BlockAndMetadataByHeight => decode_fetched::<BlockWithMetadata>(serialized_item),
+        Tag::BlockHeaderByHash => decode_fetched::<BlockHeader>(serialized_item),
+        Tag::BlockHeaderAndFinalitySignaturesByHeight => {
+            decode_fetched::<BlockHeaderWithMetadata>(serialized_item)
+        }
+        Tag::TrieOrChunk => decode_fetched::<TrieOrChunk>(serialized_item),
+        Tag::BlockAndDeploysByHash => decode_fetched::<BlockAndDeploys>(serialized_item),
+        Tag::BlockHeaderBatch => decode_fetched::<BlockHeadersBatch>(serialized_item),
+        Tag::FinalitySignaturesByHash => decode_fetched::<BlockSignatures>(serialized_item),
+    }
+}
+
+/// Decodes a fetched item the way the fetcher does, and validates it if found.
+fn decode_fetched<T: Item>(serialized_item: &[u8]) {
+    if let Ok(FetchedOrNotFound::Fetched(item)) =
+        bincode::deserialize::<FetchedOrNotFound<T, T::Id>>(serialized_item)
+    {
+        let _ = item.validate();
+        let _ = item.id();
+    }
+}
+
+/// Decodes a deploy both from bincode, as received from peers, and from its `bytesrepr` encoding.
+///
+/// Successfully decoded deploys are validated, and encoded again.
+pub fn decode_deploy(data: &[u8]) {
+    if let Ok(deploy) = bincode::deserialize::<Deploy>(data) {
+        let _ = deploy.validate();
+        let _ = bincode::serialize(&deploy);
+    }
+    if let Ok((deploy, _remainder)) = Deploy::from_bytes(data) {
+        let _ = deploy.validate();
+        let _ = deploy.to_bytes();
+    }
+}
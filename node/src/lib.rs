@@ -21,13 +21,22 @@
     unused_qualifications
 )]
 
+mod audit_log;
+mod chainspec_check;
 pub(crate) mod components;
 mod config_migration;
 mod data_migration;
 pub(crate) mod effect;
+#[cfg(any(feature = "fault-injection", test))]
+pub mod fault_injection;
+#[cfg(fuzzing)]
+pub mod fuzzing;
+mod incident;
 pub(crate) mod logging;
 pub(crate) mod protocol;
 pub(crate) mod reactor;
+mod snapshot_export;
+mod snapshot_import;
 #[cfg(test)]
 pub(crate) mod testing;
 pub(crate) mod tls;
@@ -43,24 +52,38 @@ pub use components::{
 };
 pub use utils::WithDir;
 
-use std::sync::{atomic::AtomicUsize, Arc};
+use std::{
+    panic::PanicInfo,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc,
+    },
+};
 
 use ansi_term::Color::Red;
 use once_cell::sync::Lazy;
 #[cfg(not(test))]
 use rand::SeedableRng;
-use signal_hook::{consts::TERM_SIGNALS, flag};
+use signal_hook::{
+    consts::{SIGHUP, TERM_SIGNALS},
+    flag,
+};
+
+use incident::{Incident, IncidentKind};
 
 pub(crate) use components::{
     block_proposer::Config as BlockProposerConfig,
+    clock_skew::Config as ClockSkewConfig,
     consensus::Config as ConsensusConfig,
     contract_runtime::Config as ContractRuntimeConfig,
+    deploy_acceptor::Config as DeployAcceptorConfig,
     diagnostics_port::Config as DiagnosticsPortConfig,
     event_stream_server::Config as EventStreamServerConfig,
     fetcher::Config as FetcherConfig,
+    finality_watchdog::Config as FinalityWatchdogConfig,
     gossiper::Config as GossipConfig,
     rest_server::Config as RestServerConfig,
-    rpc_server::{Config as RpcServerConfig, SpeculativeExecConfig},
+    rpc_server::{ArchiveServerConfig, Config as RpcServerConfig, SpeculativeExecConfig},
     small_network::Config as SmallNetworkConfig,
 };
 pub(crate) use types::NodeRng;
@@ -100,6 +123,10 @@ pub(crate) static VERSION_STRING: Lazy<String> = Lazy::new(|| version_string(fal
 pub(crate) static TERMINATION_REQUESTED: Lazy<Arc<AtomicUsize>> =
     Lazy::new(|| Arc::new(AtomicUsize::new(0)));
 
+/// Global value that indicates the config should be reloaded if it is set.
+pub(crate) static RELOAD_REQUESTED: Lazy<Arc<AtomicBool>> =
+    Lazy::new(|| Arc::new(AtomicBool::new(false)));
+
 /// Setup UNIX signal hooks for current application.
 pub(crate) fn setup_signal_hooks() {
     for signal in TERM_SIGNALS {
@@ -110,6 +137,39 @@ pub(crate) fn setup_signal_hooks() {
         )
         .unwrap_or_else(|error| panic!("failed to register signal {}: {}", signal, error));
     }
+    flag::register(SIGHUP, Arc::clone(&*RELOAD_REQUESTED))
+        .unwrap_or_else(|error| panic!("failed to register signal {}: {}", SIGHUP, error));
+}
+
+/// Writes a summary of the reactor's state to the configured crash dump file, if any.
+///
+/// Called when the node panics, so that the summary can be attached to bug reports.
+pub fn write_crash_dump(reason: &str) {
+    reactor::supervisor::write_crash_dump(reason)
+}
+
+/// Reports a panic to the configured incident reporters, if any.
+///
+/// Called when the node panics, waiting until the panic has been reported before returning.
+pub fn report_panic(info: &PanicInfo) {
+    let payload = info.payload();
+    let message = match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(message), _) => message.to_string(),
+        (None, Some(message)) => message.clone(),
+        (None, None) => info.to_string(),
+    };
+    let mut incident = Incident::new(
+        IncidentKind::Panic,
+        message,
+        reactor::supervisor::incident_context(),
+    );
+    if let Some(location) = info.location() {
+        incident = incident.with_location(location.to_string());
+    }
+    incident::report_and_wait(incident);
 }
 
 /// Constructs a new `NodeRng`.
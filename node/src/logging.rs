@@ -1,11 +1,27 @@
 //! Logging via the tracing crate.
 
-use std::{env, fmt, io};
+use std::{
+    env,
+    fmt::{self, Display, Formatter},
+    io,
+    sync::Mutex,
+};
 
 use ansi_term::{Color, Style};
 use anyhow::anyhow;
 use datasize::DataSize;
+use once_cell::sync::OnceCell;
+use opentelemetry::{
+    global,
+    sdk::{
+        trace::{self, Sampler, Tracer},
+        Resource,
+    },
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use smallvec::SmallVec;
 use tracing::{
     field::{Field, Visit},
@@ -13,21 +29,37 @@ use tracing::{
 };
 use tracing_subscriber::{
     fmt::{
-        format::{self, Writer},
+        format::{self, JsonFields, Writer},
         time::{FormatTime, SystemTime},
         FmtContext, FormatEvent, FormatFields, FormattedFields,
     },
+    layer::SubscriberExt,
     registry::LookupSpan,
+    util::SubscriberInitExt,
     EnvFilter,
 };
 
 const LOG_CONFIGURATION_ENVVAR: &str = "RUST_LOG";
 
+pub(crate) const DEFAULT_LOG_FILTER: &str = "warn,casper_node=info";
+
 const LOG_FIELD_MESSAGE: &str = "message";
 const LOG_FIELD_TARGET: &str = "log.target";
 const LOG_FIELD_MODULE: &str = "log.module_path";
 const LOG_FIELD_FILE: &str = "log.file";
 const LOG_FIELD_LINE: &str = "log.line";
+const LOG_FIELD_PREFIX: &str = "log.";
+
+/// Name of the span the reactor processes each event in.
+const DISPATCH_SPAN_NAME: &str = "dispatch";
+
+/// Fields of the dispatch span, and the top-level keys they are copied to in JSON log lines.
+const DISPATCH_SPAN_FIELDS: [(&str, &str); 4] = [
+    ("component", "component"),
+    ("ev", "event_id"),
+    ("a", "parent_event_id"),
+    ("c", "correlation_id"),
+];
 
 /// Logging configuration.
 #[derive(DataSize, Debug, Default, Deserialize, Serialize)]
@@ -47,6 +79,17 @@ pub struct LoggingConfig {
     /// If set, human-readable formats will abbreviate module names, `foo::bar::baz::bizz` will
     /// turn into `f:b:b:bizz`.
     abbreviate_modules: bool,
+
+    /// Log filter directives, in the same format as the `RUST_LOG` environment variable.
+    ///
+    /// Ignored at startup if `RUST_LOG` is set.  Can be changed by reloading the config without
+    /// restarting the node.
+    #[serde(default)]
+    level: Option<String>,
+
+    /// Export of tracing spans via OpenTelemetry, disabled if not set.
+    #[serde(default)]
+    opentelemetry: Option<OpenTelemetryConfig>,
 }
 
 impl LoggingConfig {
@@ -57,8 +100,67 @@ impl LoggingConfig {
             format,
             color,
             abbreviate_modules,
+            level: None,
+            opentelemetry: None,
         }
     }
+
+    /// Returns the configured log filter directives, if any.
+    pub(crate) fn level(&self) -> Option<&str> {
+        self.level.as_deref()
+    }
+}
+
+/// Configuration of the export of tracing spans via OpenTelemetry.
+///
+/// Spans are exported over OTLP/gRPC, e.g. to Jaeger or Tempo, subject to the same filter as log
+/// output.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct OpenTelemetryConfig {
+    /// Endpoint of the OTLP/gRPC trace collector, e.g. "http://localhost:4317".
+    endpoint: String,
+
+    /// Service name the exported spans are reported under.
+    #[serde(default = "default_service_name")]
+    service_name: String,
+
+    /// Ratio of traces sampled, between 0 and 1.
+    #[serde(default = "default_sampling_ratio")]
+    sampling_ratio: f64,
+}
+
+fn default_service_name() -> String {
+    "casper-node".to_string()
+}
+
+fn default_sampling_ratio() -> f64 {
+    1.0
+}
+
+impl OpenTelemetryConfig {
+    /// Installs a batch exporter of spans to the configured endpoint, returning its tracer.
+    ///
+    /// The exporter runs on the Tokio runtime, so this must be called from within one.
+    fn install_tracer(&self) -> anyhow::Result<Tracer> {
+        let exporter = opentelemetry_otlp::new_exporter()
+            .tonic()
+            .with_endpoint(self.endpoint.clone());
+        let trace_config = trace::config()
+            .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                self.sampling_ratio,
+            ))))
+            .with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                self.service_name.clone(),
+            )]));
+        opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(exporter)
+            .with_trace_config(trace_config)
+            .install_batch(opentelemetry::runtime::Tokio)
+            .map_err(|error| anyhow!("failed to install OpenTelemetry exporter: {}", error))
+    }
 }
 
 /// Logging output format.
@@ -70,6 +172,10 @@ pub enum LoggingFormat {
     /// Text format.
     Text,
     /// JSON format.
+    ///
+    /// Every line logged while the reactor processes an event carries the event's `component`,
+    /// `event_id`, `parent_event_id` and `correlation_id` as top-level keys.  The correlation ID is
+    /// shared by all events derived from the same external input, e.g. an RPC request.
     Json,
 }
 
@@ -252,6 +358,103 @@ where
     }
 }
 
+/// Formats tracing events as JSON objects, one per line.
+///
+/// The output follows the format of `tracing_subscriber`'s own JSON formatter, with the fields of
+/// the innermost dispatch span additionally copied to the top level.
+struct JsonFmtEvent;
+
+/// Collects the fields of a tracing event into a JSON object.
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl JsonVisitor {
+    fn insert<V: Into<Value>>(&mut self, field: &Field, value: V) {
+        if !field.name().starts_with(LOG_FIELD_PREFIX) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value)
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value)
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value)
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value)
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{:?}", value))
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFmtEvent
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let mut timestamp = String::new();
+        SystemTime.format_time(&mut Writer::new(&mut timestamp))?;
+        let mut fields = JsonVisitor::default();
+        event.record(&mut fields);
+
+        let mut line = Map::new();
+        line.insert("timestamp".to_string(), timestamp.into());
+        line.insert("level".to_string(), meta.level().to_string().into());
+        line.insert("fields".to_string(), Value::Object(fields.0));
+        line.insert("target".to_string(), meta.target().into());
+
+        // The span fields were formatted as JSON objects by `JsonFields`.
+        let mut spans = Vec::new();
+        for span in ctx
+            .event_scope()
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+        {
+            let mut span_fields = span
+                .extensions()
+                .get::<FormattedFields<N>>()
+                .and_then(|fields| serde_json::from_str::<Map<String, Value>>(&fields.fields).ok())
+                .unwrap_or_default();
+            if span.name() == DISPATCH_SPAN_NAME {
+                for (field, key) in DISPATCH_SPAN_FIELDS {
+                    if let Some(value) = span_fields.get(field) {
+                        line.insert(key.to_string(), value.clone());
+                    }
+                }
+            }
+            span_fields.insert("name".to_string(), span.name().into());
+            spans.push(Value::Object(span_fields));
+        }
+        if let Some(current) = spans.last() {
+            line.insert("span".to_string(), current.clone());
+        }
+        if !spans.is_empty() {
+            line.insert("spans".to_string(), Value::Array(spans));
+        }
+
+        let json = serde_json::to_string(&line).map_err(|_| fmt::Error)?;
+        writeln!(writer, "{}", json)
+    }
+}
+
 /// Initializes the logging system with the default parameters.
 ///
 /// See `init_params` for details.
@@ -273,26 +476,324 @@ pub fn init_with_config(config: &LoggingConfig) -> anyhow::Result<()> {
         _ => write!(writer, "; {}={:?}", field, value),
     });
 
-    let filter = EnvFilter::new(
-        env::var(LOG_CONFIGURATION_ENVVAR)
-            .as_deref()
-            .unwrap_or("warn,casper_node=info"),
-    );
+    let directives = env::var(LOG_CONFIGURATION_ENVVAR)
+        .ok()
+        .or_else(|| config.level().map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_LOG_FILTER.to_string());
+    let filter = EnvFilter::new(&directives);
 
-    match config.format {
+    let filter_reloader: FilterReloader = match config.format {
         // Setup a new tracing-subscriber writing to `stdout` for logging.
-        LoggingFormat::Text => tracing_subscriber::fmt()
-            .with_writer(io::stdout)
-            .with_env_filter(filter)
-            .fmt_fields(formatter)
-            .event_format(FmtEvent::new(config.color, config.abbreviate_modules))
-            .try_init(),
+        LoggingFormat::Text => {
+            let builder = tracing_subscriber::fmt()
+                .with_writer(io::stdout)
+                .with_env_filter(filter)
+                .fmt_fields(formatter)
+                .event_format(FmtEvent::new(config.color, config.abbreviate_modules))
+                .with_filter_reloading();
+            let handle = builder.reload_handle();
+            try_init_subscriber(builder.finish(), config.opentelemetry.as_ref())?;
+            Box::new(move |filter| handle.reload(filter).map_err(|error| anyhow!(error)))
+        }
         // JSON logging writes to `stdout` as well but uses the JSON format.
-        LoggingFormat::Json => tracing_subscriber::fmt()
-            .with_writer(io::stdout)
-            .with_env_filter(filter)
-            .json()
-            .try_init(),
+        LoggingFormat::Json => {
+            let builder = tracing_subscriber::fmt()
+                .with_writer(io::stdout)
+                .with_env_filter(filter)
+                .fmt_fields(JsonFields::new())
+                .event_format(JsonFmtEvent)
+                .with_filter_reloading();
+            let handle = builder.reload_handle();
+            try_init_subscriber(builder.finish(), config.opentelemetry.as_ref())?;
+            Box::new(move |filter| handle.reload(filter).map_err(|error| anyhow!(error)))
+        }
+    };
+
+    // Only the first installed logger is global, so there is nothing to do if this is already set.
+    let _ = FILTER_CONTROL.set(Mutex::new(FilterControl::new(filter_reloader, directives)));
+    Ok(())
+}
+
+/// Installs `subscriber` as the global default, also exporting spans via OpenTelemetry if
+/// configured.
+fn try_init_subscriber<S>(
+    subscriber: S,
+    maybe_opentelemetry: Option<&OpenTelemetryConfig>,
+) -> anyhow::Result<()>
+where
+    S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync + 'static,
+{
+    match maybe_opentelemetry {
+        Some(opentelemetry) => {
+            let tracer = opentelemetry.install_tracer()?;
+            subscriber
+                .with(tracing_opentelemetry::layer().with_tracer(tracer))
+                .try_init()
+        }
+        None => subscriber.try_init(),
     }
     .map_err(|error| anyhow!(error))
 }
+
+/// Flushes any spans buffered for export via OpenTelemetry, and stops the exporter.
+///
+/// Blocks until the spans are exported, so must not be called on the async runtime's threads.
+pub(crate) fn shutdown() {
+    global::shutdown_tracer_provider();
+}
+
+/// Replaces the filter of the installed logger.
+type FilterReloader = Box<dyn Fn(EnvFilter) -> anyhow::Result<()> + Send + Sync>;
+
+/// The filter control of the installed logger, set by `init_with_config`.
+static FILTER_CONTROL: OnceCell<Mutex<FilterControl>> = OnceCell::new();
+
+/// The log filter of the installed logger, which can be temporarily overridden at runtime.
+struct FilterControl {
+    reloader: FilterReloader,
+    /// The directives from `RUST_LOG` or the config file, restored when an override is reset.
+    base: String,
+    /// The directives of the active override, if any.
+    override_directives: Option<String>,
+    /// Incremented on every change, so that an expiring override does not undo a later change.
+    generation: u64,
+}
+
+impl FilterControl {
+    fn new(reloader: FilterReloader, base: String) -> Self {
+        FilterControl {
+            reloader,
+            base,
+            override_directives: None,
+            generation: 0,
+        }
+    }
+
+    /// Returns the directives currently in use.
+    fn current(&self) -> &str {
+        self.override_directives.as_deref().unwrap_or(&self.base)
+    }
+
+    /// Applies the given directives and records the change, returning its generation.
+    fn apply(&mut self, directives: &str, is_override: bool) -> anyhow::Result<u64> {
+        let filter = EnvFilter::try_new(directives).map_err(|error| anyhow!(error))?;
+        (self.reloader)(filter)?;
+        if is_override {
+            self.override_directives = Some(directives.to_string());
+        } else {
+            self.base = directives.to_string();
+            self.override_directives = None;
+        }
+        self.generation += 1;
+        Ok(self.generation)
+    }
+
+    /// Reverts to the base directives if `generation` is `None` or the generation of the latest
+    /// change.  Returns whether an override was reset.
+    fn reset(&mut self, generation: Option<u64>) -> anyhow::Result<bool> {
+        if self.override_directives.is_none()
+            || generation.map_or(false, |generation| generation != self.generation)
+        {
+            return Ok(false);
+        }
+        let base = self.base.clone();
+        self.apply(&base, false)?;
+        Ok(true)
+    }
+
+    fn status(&self) -> LogFilterStatus {
+        LogFilterStatus {
+            directives: self.current().to_string(),
+            base_directives: self.base.clone(),
+            overridden: self.override_directives.is_some(),
+        }
+    }
+}
+
+/// The log filter in use by the running node.
+#[derive(Debug, Serialize)]
+pub(crate) struct LogFilterStatus {
+    /// The directives currently in use.
+    directives: String,
+    /// The directives from `RUST_LOG` or the config file.
+    base_directives: String,
+    /// Whether the directives in use were set at runtime and differ from the base directives.
+    overridden: bool,
+}
+
+impl Display for LogFilterStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "log filter: {}", self.directives)?;
+        if self.overridden {
+            write!(f, " (overriding {})", self.base_directives)?;
+        }
+        Ok(())
+    }
+}
+
+fn with_filter_control<T>(
+    f: impl FnOnce(&mut FilterControl) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let control = FILTER_CONTROL
+        .get()
+        .ok_or_else(|| anyhow!("logging has not been initialized"))?;
+    let mut guard = control
+        .lock()
+        .map_err(|_| anyhow!("log filter lock poisoned"))?;
+    f(&mut guard)
+}
+
+/// Replaces the log filter of the running node with the given directives, in the same format as the
+/// `RUST_LOG` environment variable.
+///
+/// The directives become the base directives, replacing any override.
+pub(crate) fn reload_filter(directives: &str) -> anyhow::Result<()> {
+    with_filter_control(|control| control.apply(directives, false).map(drop))
+}
+
+/// Overrides the log filter of the running node with the given directives, or if `extend` is true,
+/// with the current directives extended by the given ones.
+///
+/// Returns an identifier of the change, which can be passed to `reset_filter` to revert it only if
+/// the filter has not been changed again since.
+pub(crate) fn override_filter(directives: &str, extend: bool) -> anyhow::Result<u64> {
+    with_filter_control(|control| {
+        let directives = if extend {
+            format!("{},{}", control.current(), directives)
+        } else {
+            directives.to_string()
+        };
+        control.apply(&directives, true)
+    })
+}
+
+/// Reverts an override of the log filter, restoring the base directives.
+///
+/// If `change` is given, the override is only reverted if it is still the latest change.  Returns
+/// whether an override was reverted.
+pub(crate) fn reset_filter(change: Option<u64>) -> anyhow::Result<bool> {
+    with_filter_control(|control| control.reset(change))
+}
+
+/// Returns the log filter in use by the running node.
+pub(crate) fn filter_status() -> anyhow::Result<LogFilterStatus> {
+    with_filter_control(|control| Ok(control.status()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tracing::{debug_span, info};
+
+    use super::*;
+
+    /// A writer appending to a shared buffer.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_lines_should_carry_dispatch_span_fields() {
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_max_level(Level::TRACE)
+            .fmt_fields(JsonFields::new())
+            .event_format(JsonFmtEvent)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let dispatch = debug_span!(
+                "dispatch",
+                a = 3,
+                c = 1,
+                component = "DeployAcceptor",
+                ev = 7
+            );
+            let _dispatch = dispatch.enter();
+            let inner = debug_span!("inner", peer = "abc");
+            let _inner = inner.enter();
+            info!(count = 2, "deploy accepted");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "deploy accepted");
+        assert_eq!(line["fields"]["count"], 2);
+        assert_eq!(line["component"], "DeployAcceptor");
+        assert_eq!(line["event_id"], 7);
+        assert_eq!(line["parent_event_id"], 3);
+        assert_eq!(line["correlation_id"], 1);
+        assert_eq!(line["span"]["name"], "inner");
+        assert_eq!(line["span"]["peer"], "abc");
+        assert_eq!(line["spans"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn should_override_and_reset_filter() {
+        let reloads = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reloads.clone();
+        let reloader: FilterReloader = Box::new(move |filter| {
+            recorded.lock().unwrap().push(filter.to_string());
+            Ok(())
+        });
+        let mut control = FilterControl::new(reloader, DEFAULT_LOG_FILTER.to_string());
+
+        let first = control
+            .apply("casper_node::components::small_network=trace", true)
+            .unwrap();
+        assert!(control.status().overridden);
+        assert_eq!(
+            control.current(),
+            "casper_node::components::small_network=trace"
+        );
+
+        // A later change is not undone by an earlier override expiring.
+        let second = control.apply("debug", true).unwrap();
+        assert!(!control.reset(Some(first)).unwrap());
+        assert_eq!(control.current(), "debug");
+        assert!(control.reset(Some(second)).unwrap());
+        assert_eq!(control.current(), DEFAULT_LOG_FILTER);
+        assert!(!control.status().overridden);
+        assert!(!control.reset(None).unwrap());
+
+        // Invalid directives leave the filter unchanged.
+        assert!(control.apply("casper_node=notalevel", true).is_err());
+        assert_eq!(control.current(), DEFAULT_LOG_FILTER);
+        assert_eq!(reloads.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn should_parse_opentelemetry_config_with_defaults() {
+        let config: LoggingConfig = toml::from_str(
+            r#"
+            format = 'json'
+            color = false
+            abbreviate_modules = false
+
+            [opentelemetry]
+            endpoint = 'http://localhost:4317'
+            "#,
+        )
+        .unwrap();
+        let opentelemetry = config
+            .opentelemetry
+            .expect("should have opentelemetry config");
+        assert_eq!(opentelemetry.endpoint, "http://localhost:4317");
+        assert_eq!(opentelemetry.service_name, "casper-node");
+        assert!((opentelemetry.sampling_ratio - 1.0).abs() < f64::EPSILON);
+    }
+}
@@ -20,13 +20,14 @@ use crate::{
     },
     effect::{
         incoming::{
-            ConsensusMessageIncoming, FinalitySignatureIncoming, GossiperIncoming, NetRequest,
+            ConsensusMessageIncoming, DeployResponse, DeployResponseIncoming,
+            FinalitySignatureIncoming, FinalitySignaturesIncoming, GossiperIncoming, NetRequest,
             NetRequestIncoming, NetResponse, NetResponseIncoming, TrieDemand, TrieRequest,
             TrieRequestIncoming, TrieResponse, TrieResponseIncoming,
         },
         AutoClosingResponder, EffectBuilder,
     },
-    types::{Deploy, FinalitySignature, Item, NodeId, Tag},
+    types::{BlockSignatures, Deploy, FinalitySignature, Item, NodeId, Tag},
 };
 
 /// Reactor message.
@@ -58,6 +59,8 @@ pub(crate) enum Message {
     /// Finality signature.
     #[from]
     FinalitySignature(Box<FinalitySignature>),
+    /// A batch of finality signatures for a single block.
+    FinalitySignatures(Box<BlockSignatures>),
 }
 
 impl Payload for Message {
@@ -83,7 +86,9 @@ impl Payload for Message {
                     Tag::FinalitySignaturesByHash => MessageKind::BlockTransfer,
                 }
             }
-            Message::FinalitySignature(_) => MessageKind::Consensus,
+            Message::FinalitySignature(_) | Message::FinalitySignatures(_) => {
+                MessageKind::Consensus
+            }
         }
     }
 
@@ -98,6 +103,20 @@ impl Payload for Message {
             Message::GetRequest { .. } => false,
             Message::GetResponse { .. } => false,
             Message::FinalitySignature(_) => false,
+            Message::FinalitySignatures(_) => false,
+        }
+    }
+
+    fn is_high_priority(&self) -> bool {
+        // Consensus messages and finality signatures are time-critical for validators.
+        match self {
+            Message::Consensus(_) => true,
+            Message::DeployGossiper(_) => false,
+            Message::AddressGossiper(_) => false,
+            Message::GetRequest { .. } => false,
+            Message::GetResponse { .. } => false,
+            Message::FinalitySignature(_) => true,
+            Message::FinalitySignatures(_) => true,
         }
     }
 
@@ -134,6 +153,9 @@ impl Payload for Message {
                 Tag::FinalitySignaturesByHash => weights.block_responses,
             },
             Message::FinalitySignature(_) => weights.finality_signatures,
+            Message::FinalitySignatures(signatures) => weights
+                .finality_signatures
+                .saturating_mul(signatures.proofs.len() as u32),
         }
     }
 
@@ -147,6 +169,19 @@ impl Payload for Message {
             Message::GetRequest { .. } => false,
             Message::GetResponse { .. } => false,
             Message::FinalitySignature(_) => false,
+            Message::FinalitySignatures(_) => false,
+        }
+    }
+
+    fn requires_batched_gossip_support(&self) -> bool {
+        match self {
+            Message::DeployGossiper(message) => message.is_batched(),
+            Message::AddressGossiper(message) => message.is_batched(),
+            Message::FinalitySignatures(_) => true,
+            Message::Consensus(_)
+            | Message::GetRequest { .. }
+            | Message::GetResponse { .. }
+            | Message::FinalitySignature(_) => false,
         }
     }
 }
@@ -202,6 +237,10 @@ impl Debug for Message {
             Message::FinalitySignature(fs) => {
                 f.debug_tuple("FinalitySignature").field(&fs).finish()
             }
+            Message::FinalitySignatures(signatures) => f
+                .debug_tuple("FinalitySignatures")
+                .field(&signatures)
+                .finish(),
         }
     }
 }
@@ -224,6 +263,9 @@ impl Display for Message {
             Message::FinalitySignature(fs) => {
                 write!(f, "FinalitySignature::({})", fs)
             }
+            Message::FinalitySignatures(signatures) => {
+                write!(f, "FinalitySignatures::({})", signatures)
+            }
         }
     }
 }
@@ -238,7 +280,9 @@ where
         + From<TrieRequestIncoming>
         + From<TrieDemand>
         + From<TrieResponseIncoming>
-        + From<FinalitySignatureIncoming>,
+        + From<FinalitySignatureIncoming>
+        + From<FinalitySignaturesIncoming>
+        + From<DeployResponseIncoming>,
 {
     // fn from_incoming(sender: NodeId, payload: Message, effect_builder: EffectBuilder<REv>) ->
     // Self {
@@ -367,6 +411,9 @@ where
             Message::FinalitySignature(message) => {
                 FinalitySignatureIncoming { sender, message }.into()
             }
+            Message::FinalitySignatures(message) => {
+                FinalitySignaturesIncoming { sender, message }.into()
+            }
         }
     }
 
@@ -391,4 +438,30 @@ where
             _ => Err(payload),
         }
     }
+
+    fn try_acknowledged_from_incoming(
+        effect_builder: EffectBuilder<REv>,
+        sender: NodeId,
+        payload: Message,
+    ) -> Result<(Self, BoxFuture<'static, ()>), Message>
+    where
+        Self: Sized + Send,
+    {
+        match payload {
+            Message::GetResponse {
+                tag: Tag::Deploy,
+                serialized_item,
+            } => {
+                let (ev, fut) =
+                    effect_builder.create_request_parts(move |responder| DeployResponseIncoming {
+                        sender,
+                        message: DeployResponse(serialized_item),
+                        responder,
+                    });
+
+                Ok((ev, fut.map(|_| ()).boxed()))
+            }
+            _ => Err(payload),
+        }
+    }
 }
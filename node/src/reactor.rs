@@ -32,14 +32,15 @@ pub(crate) mod initializer;
 pub(crate) mod joiner;
 pub(crate) mod participating;
 mod queue_kind;
+pub(crate) mod supervisor;
 
 #[cfg(test)]
 use std::sync::Arc;
 use std::{
     any,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     env,
-    fmt::{Debug, Display},
+    fmt::{self, Debug, Display, Formatter},
     io::Write,
     mem,
     num::NonZeroU64,
@@ -51,7 +52,7 @@ use datasize::DataSize;
 use erased_serde::Serialize as ErasedSerialize;
 use futures::{future::BoxFuture, FutureExt};
 use once_cell::sync::Lazy;
-use prometheus::{self, Histogram, HistogramOpts, IntCounter, IntGauge, Registry};
+use prometheus::{self, Histogram, HistogramOpts, HistogramVec, IntCounter, IntGauge, Registry};
 use quanta::{Clock, IntoNanoseconds};
 use serde::Serialize;
 use signal_hook::consts::signal::{SIGINT, SIGQUIT, SIGTERM};
@@ -65,8 +66,9 @@ use crate::{
     effect::{
         announcements::{BlocklistAnnouncement, ControlAnnouncement, QueueDumpFormat},
         incoming::NetResponse,
-        Effect, EffectBuilder, EffectExt, Effects,
+        Effect, EffectBuilder, EffectExt, Effects, Responder,
     },
+    incident::{self, Incident, IncidentKind},
     types::{
         Block, BlockAndDeploys, BlockHeader, BlockHeaderWithMetadata, BlockHeadersBatch,
         BlockSignatures, BlockWithMetadata, Deploy, DeployHash, ExitCode, FinalizedApprovalsWithId,
@@ -86,6 +88,7 @@ use crate::{
     types::{Chainspec, ChainspecRawBytes},
 };
 pub(crate) use queue_kind::QueueKind;
+pub(crate) use supervisor::{ReactorSummary, Supervisor};
 
 /// Default threshold for when an event is considered slow.  Can be overridden by setting the env
 /// var `CL_EVENT_MAX_MICROSECS=<MICROSECONDS>`.
@@ -109,6 +112,17 @@ static DISPATCH_EVENT_THRESHOLD: Lazy<Duration> = Lazy::new(|| {
 /// The desired limit for open files.
 const TARGET_OPEN_FILES_LIMIT: Limit = 64_000;
 
+/// Reports a fatal error as an incident, waiting until it has been reported.
+fn report_fatal_error(file: &str, line: u32, msg: &str) {
+    let incident = Incident::new(
+        IncidentKind::FatalError,
+        msg.to_string(),
+        supervisor::incident_context(),
+    )
+    .with_location(format!("{}:{}", file, line));
+    incident::report_and_wait(incident);
+}
+
 /// Adjusts the maximum number of open file handles upwards towards the hard limit.
 fn adjust_open_files_limit() {
     // Ensure we have reasonable ulimits.
@@ -163,9 +177,43 @@ pub(crate) enum ReactorExit {
 ///
 /// Components rarely use this, but use a bound `EventQueueHandle` instead.
 ///
-/// Schedule tuples contain an optional ancestor ID and the actual event. The ancestor ID indicates
-/// which potential previous event resulted in the event being created.
-pub(crate) type Scheduler<Ev> = WeightedRoundRobin<(Option<NonZeroU64>, Ev), QueueKind>;
+/// Schedule tuples contain the event's metadata and the actual event.
+pub(crate) type Scheduler<Ev> = WeightedRoundRobin<(EventMetadata, Ev), QueueKind>;
+
+/// Metadata attached to an event while it is waiting in the scheduler.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub(crate) struct EventMetadata {
+    /// The optional ancestry, indicating which potential previous event resulted in the event being
+    /// created.
+    pub(crate) ancestry: Option<Ancestry>,
+    /// The time the event was scheduled, used to measure how long it spent in the queue.
+    #[serde(skip)]
+    pub(crate) scheduled_at: Instant,
+}
+
+impl EventMetadata {
+    /// Creates metadata for an event scheduled now.
+    fn new(ancestry: Option<Ancestry>) -> Self {
+        EventMetadata {
+            ancestry,
+            scheduled_at: Instant::now(),
+        }
+    }
+}
+
+/// The events an event was derived from.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub(crate) struct Ancestry {
+    /// ID of the event whose effects resulted in the event being created.
+    pub(crate) parent: NonZeroU64,
+    /// ID of the event which started the chain of events the event belongs to, i.e. the earliest
+    /// ancestor which was scheduled from outside the reactor, e.g. by an incoming RPC request or
+    /// network message.
+    ///
+    /// It is logged with every event's processing to allow following e.g. a deploy from its
+    /// submission through acceptance to gossiping.
+    pub(crate) correlation_id: NonZeroU64,
+}
 
 /// Event queue handle
 ///
@@ -224,14 +272,14 @@ impl<REv> EventQueueHandle<REv> {
     /// Schedule an event on a specific queue.
     pub(crate) async fn schedule_with_ancestor<Ev>(
         self,
-        ancestor: Option<NonZeroU64>,
+        ancestor: Option<Ancestry>,
         event: Ev,
         queue_kind: QueueKind,
     ) where
         REv: From<Ev>,
     {
         self.scheduler
-            .push((ancestor, event.into()), queue_kind)
+            .push((EventMetadata::new(ancestor), event.into()), queue_kind)
             .await
     }
 
@@ -295,6 +343,36 @@ pub(crate) trait Reactor: Sized {
 
     /// Instructs the reactor to update performance metrics, if any.
     fn update_metrics(&mut self, _event_queue_handle: EventQueueHandle<Self::Event>) {}
+
+    /// Returns a summary of the reactor's state to be included in crash dumps.
+    fn summary(&self) -> ReactorSummary {
+        ReactorSummary::default()
+    }
+
+    /// Estimates the heap memory usage of each of the reactor's components, in bytes.
+    fn estimate_component_memory(&self) -> BTreeMap<&'static str, u64> {
+        BTreeMap::new()
+    }
+
+    /// Starts draining the reactor ahead of a graceful shutdown.
+    ///
+    /// Returns the maximum duration to wait for the reactor to be drained, along with any effects.
+    /// By default, reactors aren't drained and shut down immediately.
+    fn begin_drain(
+        &mut self,
+        _effect_builder: EffectBuilder<Self::Event>,
+    ) -> (Duration, Effects<Self::Event>) {
+        (Duration::ZERO, Effects::new())
+    }
+
+    /// Returns `true` once the work in flight when draining began has completed.
+    fn is_drained(&self) -> bool {
+        true
+    }
+
+    /// Runs the final steps of a graceful shutdown, once the reactor has been drained or the drain
+    /// timed out.
+    fn finish_drain(&mut self) {}
 }
 
 /// A reactor event type.
@@ -340,6 +418,37 @@ struct AllocatedMem {
     total: u64,
 }
 
+/// A breakdown of the node's memory usage, as requested via the diagnostics port.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct MemoryDump {
+    /// Memory allocated by the node in bytes, if known.
+    allocated: Option<u64>,
+    /// Memory consumed on the system in bytes, if known.
+    consumed: Option<u64>,
+    /// Total system memory in bytes, if known.
+    total: Option<u64>,
+    /// Estimated heap memory usage of each component in bytes.
+    components: BTreeMap<&'static str, u64>,
+}
+
+impl Display for MemoryDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.allocated, self.consumed, self.total) {
+            (Some(allocated), Some(consumed), Some(total)) => writeln!(
+                f,
+                "allocated: {} bytes, system: {} of {} bytes consumed",
+                allocated, consumed, total
+            )?,
+            _ => writeln!(f, "allocated: unknown")?,
+        }
+        writeln!(f, "components ({}):", self.components.len())?;
+        for (component, size) in &self.components {
+            writeln!(f, "  {}: {} bytes", component, size)?;
+        }
+        Ok(())
+    }
+}
+
 /// A runner for a reactor.
 ///
 /// The runner manages a reactor's event queue and reactor itself and can run it either continuously
@@ -375,6 +484,9 @@ where
 
     /// Flag indicating the reactor is being shut down.
     is_shutting_down: SharedFlag,
+
+    /// Supervisor watching the reactor for stalls, if any.
+    supervisor: Option<Supervisor>,
 }
 
 /// Metric data for the Runner
@@ -384,6 +496,10 @@ struct RunnerMetrics {
     events: IntCounter,
     /// Histogram of how long it took to dispatch an event.
     event_dispatch_duration: Histogram,
+    /// Histograms of how long events spent in the queue before being dispatched, per component.
+    event_queue_time: HistogramVec,
+    /// Histograms of how long it took to handle an event, per component.
+    event_handling_time: HistogramVec,
     /// Total allocated RAM in bytes, as reported by stats_alloc.
     allocated_ram_bytes: IntGauge,
     /// Total consumed RAM in bytes, as reported by sys-info.
@@ -431,6 +547,28 @@ impl RunnerMetrics {
             ]),
         )?;
 
+        // Per-component histograms are in seconds, following Prometheus conventions.
+        let event_queue_time = HistogramVec::new(
+            HistogramOpts::new(
+                "event_queue_time_seconds",
+                "time in seconds events spent in the queue before being dispatched, by component",
+            )
+            .buckets(vec![
+                0.000_01, 0.000_1, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0,
+            ]),
+            &["component"],
+        )?;
+        let event_handling_time = HistogramVec::new(
+            HistogramOpts::new(
+                "event_handling_time_seconds",
+                "time in seconds taken to handle an event, by component",
+            )
+            .buckets(vec![
+                0.000_001, 0.000_01, 0.000_05, 0.000_1, 0.000_5, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5,
+            ]),
+            &["component"],
+        )?;
+
         let allocated_ram_bytes =
             IntGauge::new("allocated_ram_bytes", "total allocated ram in bytes")?;
         let consumed_ram_bytes =
@@ -439,6 +577,8 @@ impl RunnerMetrics {
 
         registry.register(Box::new(events.clone()))?;
         registry.register(Box::new(event_dispatch_duration.clone()))?;
+        registry.register(Box::new(event_queue_time.clone()))?;
+        registry.register(Box::new(event_handling_time.clone()))?;
         registry.register(Box::new(allocated_ram_bytes.clone()))?;
         registry.register(Box::new(consumed_ram_bytes.clone()))?;
         registry.register(Box::new(total_ram_bytes.clone()))?;
@@ -446,6 +586,8 @@ impl RunnerMetrics {
         Ok(RunnerMetrics {
             events,
             event_dispatch_duration,
+            event_queue_time,
+            event_handling_time,
             registry: registry.clone(),
             allocated_ram_bytes,
             consumed_ram_bytes,
@@ -458,6 +600,8 @@ impl Drop for RunnerMetrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.events);
         unregister_metric!(self.registry, self.event_dispatch_duration);
+        unregister_metric!(self.registry, self.event_queue_time);
+        unregister_metric!(self.registry, self.event_handling_time);
         unregister_metric!(self.registry, self.allocated_ram_bytes);
         unregister_metric!(self.registry, self.consumed_ram_bytes);
         unregister_metric!(self.registry, self.total_ram_bytes);
@@ -498,7 +642,7 @@ where
         let (reactor, initial_effects) = R::new(cfg, registry, event_queue, rng)?;
 
         // Run all effects from component instantiation.
-        process_effects(None, scheduler, initial_effects)
+        process_effects(None, QueueKind::default(), scheduler, initial_effects)
             .instrument(debug_span!("process initial effects"))
             .await;
 
@@ -514,13 +658,32 @@ where
             event_metrics_threshold: 1000,
             clock: Clock::new(),
             is_shutting_down,
+            supervisor: None,
         })
     }
 
+    /// Places the reactor under the supervision of the given supervisor, if any.
+    pub(crate) fn set_supervisor(&mut self, supervisor: Option<Supervisor>) {
+        if let Some(ref supervisor) = supervisor {
+            let scheduler = self.scheduler;
+            supervisor.watch_queues(
+                any::type_name::<R>(),
+                Box::new(move || scheduler.event_queues_counts()),
+            );
+            supervisor.update_summary(self.reactor.summary());
+        }
+        self.supervisor = supervisor;
+    }
+
     /// Processes a single event on the event queue.
     ///
     /// Returns `false` if processing should stop.
-    #[instrument("dispatch", level = "debug", fields(a, ev = self.current_event_id), skip(self, rng))]
+    #[instrument(
+        "dispatch",
+        level = "info",
+        fields(a, c, component, ev = self.current_event_id),
+        skip(self, rng)
+    )]
     pub(crate) async fn crank(&mut self, rng: &mut NodeRng) -> bool {
         self.metrics.events.inc();
 
@@ -532,6 +695,9 @@ where
             // We update metrics on the first very event as well to get a good baseline.
             if self.last_metrics.elapsed() >= self.event_metrics_min_delay {
                 self.reactor.update_metrics(event_queue);
+                if let Some(ref supervisor) = self.supervisor {
+                    supervisor.update_summary(self.reactor.summary());
+                }
 
                 // Use a fresh timestamp. This skews the metrics collection interval a little bit,
                 // but ensures that if metrics collection time explodes, we are guaranteed a full
@@ -552,16 +718,36 @@ where
             }
         }
 
-        let ((ancestor, event), queue) = self.scheduler.pop().await;
+        let ((metadata, event), queue) = self.scheduler.pop().await;
         trace!(%event, %queue, "current");
         let event_desc = event.description();
+        let ancestry = metadata.ancestry;
+
+        let queue_time = metadata.scheduled_at.elapsed();
+        self.metrics
+            .event_queue_time
+            .with_label_values(&[event_desc])
+            .observe(queue_time.as_secs_f64());
+        if let Some(ref supervisor) = self.supervisor {
+            supervisor.dispatch_started(event_desc, self.current_event_id, queue_time);
+        }
 
         // Create another span for tracing the processing of one event.
         Span::current().record("ev", &self.current_event_id);
-
-        // If we know the ancestor of an event, record it.
-        if let Some(ancestor) = ancestor {
-            Span::current().record("a", &ancestor.get());
+        Span::current().record("component", &event_desc);
+
+        // If we know the ancestor of an event, record it.  Events without an ancestor start a new
+        // chain of events, correlated by this event's ID.
+        let event_id = NonZeroU64::new(self.current_event_id);
+        let correlation_id = match ancestry {
+            Some(ancestry) => {
+                Span::current().record("a", &ancestry.parent.get());
+                Some(ancestry.correlation_id)
+            }
+            None => event_id,
+        };
+        if let Some(correlation_id) = correlation_id {
+            Span::current().record("c", &correlation_id.get());
         }
 
         // Dispatch the event, then execute the resulting effect.
@@ -582,6 +768,13 @@ where
                 }
                 Some(ControlAnnouncement::FatalError { file, line, msg }) => {
                     error!(%file, %line, %msg, "fatal error via control announcement");
+                    if let Some(ref supervisor) = self.supervisor {
+                        supervisor.write_crash_dump(&format!(
+                            "fatal error at {}:{}: {}",
+                            file, line, msg
+                        ));
+                    }
+                    report_fatal_error(file, line, &msg);
                     (Default::default(), false)
                 }
                 Some(ControlAnnouncement::QueueDumpRequest {
@@ -631,6 +824,34 @@ where
                     // Do nothing on queue dump otherwise.
                     (Default::default(), true)
                 }
+                Some(ControlAnnouncement::QueueSummaryRequest {
+                    include_events,
+                    responder,
+                }) => {
+                    let summary = self
+                        .scheduler
+                        .summarize(
+                            |(_, event)| event.description(),
+                            |(_, event)| event.to_string(),
+                            include_events,
+                        )
+                        .await;
+                    responder.respond(summary).await;
+
+                    (Default::default(), true)
+                }
+                Some(ControlAnnouncement::MemoryDumpRequest { responder }) => {
+                    let allocated_mem = Self::get_allocated_memory();
+                    let dump = MemoryDump {
+                        allocated: allocated_mem.as_ref().map(|mem| mem.allocated),
+                        consumed: allocated_mem.as_ref().map(|mem| mem.consumed),
+                        total: allocated_mem.as_ref().map(|mem| mem.total),
+                        components: self.reactor.estimate_component_memory(),
+                    };
+                    responder.respond(dump).await;
+
+                    (Default::default(), true)
+                }
             }
         } else {
             (
@@ -640,6 +861,9 @@ where
         };
 
         let end = self.clock.end();
+        if let Some(ref supervisor) = self.supervisor {
+            supervisor.dispatch_finished();
+        }
 
         // Warn if processing took a long time, record to histogram.
         let delta = self.clock.delta(start, end);
@@ -649,10 +873,21 @@ where
         self.metrics
             .event_dispatch_duration
             .observe(delta.into_nanos() as f64);
+        self.metrics
+            .event_handling_time
+            .with_label_values(&[event_desc])
+            .observe(delta.into_nanos() as f64 / 1_000_000_000.0);
 
         // Run effects, with the current event ID as the ancestor for resulting set of events.
+        let ancestry = event_id
+            .zip(correlation_id)
+            .map(|(parent, correlation_id)| Ancestry {
+                parent,
+                correlation_id,
+            });
         process_effects(
-            NonZeroU64::new(self.current_event_id),
+            ancestry,
+            queue.inherited_by_effects(),
             self.scheduler,
             effects,
         )
@@ -713,26 +948,42 @@ where
                         // since that workaround of making two attempts with the first wrapped in a
                         // timeout should no longer be required.
 
-                        for (ancestor, event) in
+                        for (metadata, event) in
                             self.scheduler.drain_queue(QueueKind::Control).await
                         {
                             if let Some(ctrl_ann) = event.as_control() {
                                 match ctrl_ann {
                                     ControlAnnouncement::FatalError { file, line, msg } => {
                                         warn!(%file, line=*line, %msg, "exiting due to fatal error scheduled before reactor completion");
+                                        if let Some(ref supervisor) = self.supervisor {
+                                            supervisor.write_crash_dump(&format!(
+                                                "fatal error at {}:{}: {}",
+                                                file, line, msg
+                                            ));
+                                        }
+                                        report_fatal_error(file, *line, msg);
                                         return ReactorExit::ProcessShouldExit(ExitCode::Abort);
                                     }
-                                    ControlAnnouncement::QueueDumpRequest { .. } => {
+                                    ControlAnnouncement::QueueDumpRequest { .. }
+                                    | ControlAnnouncement::QueueSummaryRequest { .. }
+                                    | ControlAnnouncement::MemoryDumpRequest { .. } => {
                                         // Queue dumps are not handled when shutting down. TODO:
                                         // Maybe return an error instead, something like "reactor is
                                         // shutting down"?
                                     }
                                 }
                             } else {
-                                debug!(?ancestor, %event, "found non-control announcement while draining queue")
+                                debug!(ancestor = ?metadata.ancestry, %event, "found non-control announcement while draining queue")
                             }
                         }
 
+                        if reactor_exit
+                            == ReactorExit::ProcessShouldExit(ExitCode::ReachedShutdownPoint)
+                        {
+                            // Not drained, as that could store blocks beyond the shutdown point,
+                            // but everything stored so far is flushed to disk.
+                            self.reactor.finish_drain();
+                        }
                         break reactor_exit;
                     }
                     if !self.crank(rng).await {
@@ -741,6 +992,7 @@ where
                     }
                 }
                 SIGINT => {
+                    self.drain(rng).await;
                     self.is_shutting_down.set();
                     break ReactorExit::ProcessShouldExit(ExitCode::SigInt);
                 }
@@ -749,6 +1001,7 @@ where
                     break ReactorExit::ProcessShouldExit(ExitCode::SigQuit);
                 }
                 SIGTERM => {
+                    self.drain(rng).await;
                     self.is_shutting_down.set();
                     break ReactorExit::ProcessShouldExit(ExitCode::SigTerm);
                 }
@@ -757,12 +1010,51 @@ where
         }
     }
 
+    /// Drains the reactor ahead of a graceful shutdown, waiting at most for its drain timeout.
+    ///
+    /// Once the reactor reports it is drained, the events queued at that point are still processed,
+    /// e.g. to store a block which has just been executed.
+    async fn drain(&mut self, rng: &mut NodeRng) {
+        let event_queue = EventQueueHandle::new(self.scheduler, self.is_shutting_down);
+        let (timeout, effects) = self.reactor.begin_drain(EffectBuilder::new(event_queue));
+        if timeout.is_zero() {
+            self.reactor.finish_drain();
+            return;
+        }
+        info!(?timeout, "draining reactor before shutting down");
+        process_effects(None, QueueKind::default(), self.scheduler, effects).await;
+
+        let deadline = Instant::now() + timeout;
+        let mut remaining_events = None;
+        loop {
+            if remaining_events.is_none() && self.reactor.is_drained() {
+                remaining_events = Some(self.scheduler.item_count());
+            }
+            if remaining_events == Some(0) {
+                info!("finished draining reactor");
+                break;
+            }
+            match tokio::time::timeout_at(deadline, self.crank(rng)).await {
+                Ok(true) => (),
+                Ok(false) => break,
+                Err(_) => {
+                    warn!("timed out draining reactor");
+                    break;
+                }
+            }
+            if let Some(count) = remaining_events.as_mut() {
+                *count -= 1;
+            }
+        }
+        self.reactor.finish_drain();
+    }
+
     /// Shuts down a reactor, sealing and draining the entire queue before returning it.
     pub(crate) async fn drain_into_inner(self) -> R {
         self.is_shutting_down.set();
         self.scheduler.seal();
-        for (ancestor, event) in self.scheduler.drain_queues().await {
-            debug!(?ancestor, %event, "drained event");
+        for (metadata, event) in self.scheduler.drain_queues().await {
+            debug!(ancestor = ?metadata.ancestry, %event, "drained event");
         }
         self.reactor
     }
@@ -796,7 +1088,7 @@ where
 
         let effects = create_effects(effect_builder);
 
-        process_effects(None, self.scheduler, effects)
+        process_effects(None, QueueKind::default(), self.scheduler, effects)
             .instrument(debug_span!(
                 "process injected effects",
                 ev = self.current_event_id
@@ -846,7 +1138,7 @@ impl Runner<InitializerReactor> {
 
         // Run all effects from component instantiation.
         let span = debug_span!("process initial effects");
-        process_effects(None, scheduler, initial_effects)
+        process_effects(None, QueueKind::default(), scheduler, initial_effects)
             .instrument(span)
             .await;
 
@@ -874,21 +1166,22 @@ impl Runner<InitializerReactor> {
 
 /// Spawns tasks that will process the given effects.
 ///
-/// Result events from processing the events will be scheduled with the given ancestor.
+/// Result events from processing the events will be scheduled with the given ancestry on the given
+/// queue.
 async fn process_effects<Ev>(
-    ancestor: Option<NonZeroU64>,
+    ancestor: Option<Ancestry>,
+    queue_kind: QueueKind,
     scheduler: &'static Scheduler<Ev>,
     effects: Effects<Ev>,
 ) where
     Ev: Send + 'static,
 {
-    // TODO: Properly carry around priorities.
-    let queue_kind = QueueKind::default();
-
     for effect in effects {
         tokio::spawn(async move {
             for event in effect.await {
-                scheduler.push((ancestor, event), queue_kind).await
+                scheduler
+                    .push((EventMetadata::new(ancestor), event), queue_kind)
+                    .await
             }
         });
     }
@@ -951,6 +1244,65 @@ where
     }
 }
 
+/// Routes an incoming deploy response to the `DeployAcceptor`.
+///
+/// If a responder is given, it is answered once the deploy has been accepted or rejected, or
+/// straight away if the response did not contain a deploy.
+fn handle_deploy_response<R>(
+    reactor: &mut R,
+    effect_builder: EffectBuilder<<R as Reactor>::Event>,
+    rng: &mut NodeRng,
+    sender: NodeId,
+    serialized_item: &[u8],
+    maybe_responder: Option<Responder<Result<(), deploy_acceptor::Error>>>,
+) -> Effects<<R as Reactor>::Event>
+where
+    R: Reactor,
+    <R as Reactor>::Event:
+        From<deploy_acceptor::Event> + From<fetcher::Event<Deploy>> + From<BlocklistAnnouncement>,
+{
+    // Incoming Deploys should be routed to the `DeployAcceptor` rather than directly to the
+    // `DeployFetcher`.
+    let (event, maybe_responder) =
+        match bincode::deserialize::<FetchedOrNotFound<Deploy, DeployHash>>(serialized_item) {
+            Ok(FetchedOrNotFound::Fetched(deploy)) => {
+                let event = <R as Reactor>::Event::from(deploy_acceptor::Event::Accept {
+                    deploy: Box::new(deploy),
+                    source: Source::Peer(sender),
+                    maybe_responder,
+                });
+                (event, None)
+            }
+            Ok(FetchedOrNotFound::NotFound(deploy_hash)) => {
+                info!(%sender, ?deploy_hash, "peer did not have deploy",);
+                let event = <R as Reactor>::Event::from(fetcher::Event::<Deploy>::AbsentRemotely {
+                    id: deploy_hash,
+                    peer: sender,
+                });
+                (event, maybe_responder)
+            }
+            Err(error) => {
+                warn!(
+                    %sender,
+                    %error,
+                    "received a deploy item we couldn't parse, banning peer",
+                );
+                let mut effects = effect_builder
+                    .announce_disconnect_from_peer(sender)
+                    .ignore();
+                if let Some(responder) = maybe_responder {
+                    effects.extend(responder.respond(Ok(())).ignore());
+                }
+                return effects;
+            }
+        };
+    let mut effects = <R as Reactor>::dispatch_event(reactor, effect_builder, rng, event);
+    if let Some(responder) = maybe_responder {
+        effects.extend(responder.respond(Ok(())).ignore());
+    }
+    effects
+}
+
 fn handle_get_response<R>(
     reactor: &mut R,
     effect_builder: EffectBuilder<<R as Reactor>::Event>,
@@ -974,37 +1326,7 @@ where
 {
     match message {
         NetResponse::Deploy(ref serialized_item) => {
-            // Incoming Deploys should be routed to the `DeployAcceptor` rather than directly to the
-            // `DeployFetcher`.
-            let event = match bincode::deserialize::<FetchedOrNotFound<Deploy, DeployHash>>(
-                serialized_item,
-            ) {
-                Ok(FetchedOrNotFound::Fetched(deploy)) => {
-                    <R as Reactor>::Event::from(deploy_acceptor::Event::Accept {
-                        deploy: Box::new(deploy),
-                        source: Source::Peer(sender),
-                        maybe_responder: None,
-                    })
-                }
-                Ok(FetchedOrNotFound::NotFound(deploy_hash)) => {
-                    info!(%sender, ?deploy_hash, "peer did not have deploy",);
-                    <R as Reactor>::Event::from(fetcher::Event::<Deploy>::AbsentRemotely {
-                        id: deploy_hash,
-                        peer: sender,
-                    })
-                }
-                Err(error) => {
-                    warn!(
-                        %sender,
-                        %error,
-                        "received a deploy item we couldn't parse, banning peer",
-                    );
-                    return effect_builder
-                        .announce_disconnect_from_peer(sender)
-                        .ignore();
-                }
-            };
-            <R as Reactor>::dispatch_event(reactor, effect_builder, rng, event)
+            handle_deploy_response(reactor, effect_builder, rng, sender, serialized_item, None)
         }
         NetResponse::FinalizedApprovals(ref serialized_item) => {
             handle_fetch_response::<R, FinalizedApprovalsWithId>(
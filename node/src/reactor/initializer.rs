@@ -205,7 +205,7 @@ impl Reactor {
 
         let contract_runtime = ContractRuntime::new(
             chainspec_loader.chainspec().protocol_config.version,
-            storage.root_path(),
+            storage.global_state_path(),
             &config.value().contract_runtime,
             chainspec_loader.chainspec().wasm_config,
             chainspec_loader.chainspec().system_costs_config,
@@ -222,6 +222,14 @@ impl Reactor {
                 .chainspec()
                 .core_config
                 .strict_argument_checking,
+            chainspec_loader
+                .chainspec()
+                .core_config
+                .record_transfer_metadata,
+            chainspec_loader
+                .chainspec()
+                .core_config
+                .allow_account_hash_v2,
             chainspec_loader
                 .chainspec()
                 .core_config
@@ -273,8 +281,11 @@ impl reactor::Reactor for Reactor {
         let effect_builder = EffectBuilder::new(event_queue);
 
         // Construct the `ChainspecLoader` first so we fail fast if the chainspec is invalid.
-        let (chainspec_loader, chainspec_effects) =
-            ChainspecLoader::new(config.dir(), effect_builder)?;
+        let (chainspec_loader, chainspec_effects) = ChainspecLoader::new(
+            config.dir(),
+            config.value().node.trusted_release_keys.clone(),
+            effect_builder,
+        )?;
         Self::new_with_chainspec_loader(config, registry, chainspec_loader, chainspec_effects)
     }
 
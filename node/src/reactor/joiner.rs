@@ -4,7 +4,7 @@ mod memory_metrics;
 
 use std::{
     borrow::Cow,
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     fmt::{self, Display, Formatter},
     path::PathBuf,
     sync::Arc,
@@ -20,9 +20,11 @@ use serde::Serialize;
 use tracing::{debug, error, warn};
 
 use casper_execution_engine::storage::trie::TrieOrChunk;
+use casper_types::{PublicKey, Timestamp};
 
 use crate::{
     components::{
+        block_proposer,
         chain_synchronizer::{self, ChainSynchronizer, JoiningOutcome},
         chainspec_loader::{self, ChainspecLoader},
         contract_runtime::ContractRuntime,
@@ -45,16 +47,20 @@ use crate::{
             ContractRuntimeAnnouncement, ControlAnnouncement, DeployAcceptorAnnouncement,
             GossiperAnnouncement, LinearChainAnnouncement,
         },
-        diagnostics_port::DumpConsensusStateRequest,
+        diagnostics_port::{
+            DumpConsensusStateRequest, DumpNetworkStateRequest, ReloadConfigRequest,
+            ShutdownPointRequest, ValidatorModeRequest,
+        },
         incoming::{
-            ConsensusMessageIncoming, FinalitySignatureIncoming, GossiperIncoming,
-            NetRequestIncoming, NetResponseIncoming, TrieDemand, TrieRequestIncoming,
-            TrieResponseIncoming,
+            ConsensusMessageIncoming, DeployResponseIncoming, FinalitySignatureIncoming,
+            FinalitySignaturesIncoming, GossiperIncoming, NetRequestIncoming, NetResponseIncoming,
+            TrieDemand, TrieRequestIncoming, TrieResponseIncoming,
         },
         requests::{
-            BeginGossipRequest, ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest,
-            FetcherRequest, MarkBlockCompletedRequest, MetricsRequest, NetworkInfoRequest,
-            NetworkRequest, NodeStateRequest, RestRequest, StorageRequest,
+            BeginGossipRequest, BlockProposerRequest, ChainspecLoaderRequest, ConsensusRequest,
+            ContractRuntimeRequest, FetcherRequest, MarkBlockCompletedRequest, MetricsRequest,
+            NetworkInfoRequest, NetworkRequest, NodeStateRequest, RestRequest, StateStoreRequest,
+            StorageRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
@@ -64,7 +70,7 @@ use crate::{
         event_queue_metrics::EventQueueMetrics,
         initializer,
         participating::{self, Error, ParticipatingInitConfig},
-        EventQueueHandle, Finalize, ReactorExit,
+        EventQueueHandle, Finalize, ReactorExit, ReactorSummary,
     },
     types::{
         Block, BlockAndDeploys, BlockHeader, BlockHeaderWithMetadata, BlockHeadersBatch,
@@ -130,6 +136,8 @@ pub(crate) enum JoinerEvent {
     #[from]
     StorageRequest(StorageRequest),
     #[from]
+    StateStoreRequest(StateStoreRequest),
+    #[from]
     MarkBlockCompletedRequest(MarkBlockCompletedRequest),
     #[from]
     DiagnosticsPort(diagnostics_port::Event),
@@ -184,6 +192,8 @@ pub(crate) enum JoinerEvent {
     #[from]
     ConsensusRequest(#[serde(skip_serializing)] ConsensusRequest),
     #[from]
+    BlockProposerRequest(#[serde(skip_serializing)] BlockProposerRequest),
+    #[from]
     ConsensusMessageIncoming(ConsensusMessageIncoming),
     #[from]
     DeployGossiperIncoming(GossiperIncoming<Deploy>),
@@ -194,6 +204,8 @@ pub(crate) enum JoinerEvent {
     #[from]
     NetResponseIncoming(NetResponseIncoming),
     #[from]
+    DeployResponseIncoming(DeployResponseIncoming),
+    #[from]
     TrieRequestIncoming(TrieRequestIncoming),
     #[from]
     TrieDemand(TrieDemand),
@@ -202,7 +214,17 @@ pub(crate) enum JoinerEvent {
     #[from]
     FinalitySignatureIncoming(FinalitySignatureIncoming),
     #[from]
+    FinalitySignaturesIncoming(FinalitySignaturesIncoming),
+    #[from]
     DumpConsensusStateRequest(DumpConsensusStateRequest),
+    #[from]
+    DumpNetworkStateRequest(DumpNetworkStateRequest),
+    #[from]
+    ReloadConfigRequest(ReloadConfigRequest),
+    #[from]
+    ShutdownPointRequest(ShutdownPointRequest),
+    #[from]
+    ValidatorModeRequest(ValidatorModeRequest),
 }
 
 impl ReactorEvent for JoinerEvent {
@@ -251,6 +273,10 @@ impl ReactorEvent for JoinerEvent {
             JoinerEvent::FinalizedApprovalsFetcherRequest(_) => "FinalizedApprovalsFetcherRequest",
             JoinerEvent::TrieOrChunkFetcherRequest(_) => "TrieOrChunkFetcherRequest",
             JoinerEvent::DumpConsensusStateRequest(_) => "DumpConsensusStateRequest",
+            JoinerEvent::DumpNetworkStateRequest(_) => "DumpNetworkStateRequest",
+            JoinerEvent::ReloadConfigRequest(_) => "ReloadConfigRequest",
+            JoinerEvent::ShutdownPointRequest(_) => "ShutdownPointRequest",
+            JoinerEvent::ValidatorModeRequest(_) => "ValidatorModeRequest",
             JoinerEvent::ControlAnnouncement(_) => "ControlAnnouncement",
             JoinerEvent::ContractRuntimeAnnouncement(_) => "ContractRuntimeAnnouncement",
             JoinerEvent::AddressGossiperAnnouncement(_) => "AddressGossiperAnnouncement",
@@ -258,6 +284,7 @@ impl ReactorEvent for JoinerEvent {
             JoinerEvent::LinearChainAnnouncement(_) => "LinearChainAnnouncement",
             JoinerEvent::ChainspecLoaderAnnouncement(_) => "ChainspecLoaderAnnouncement",
             JoinerEvent::ConsensusRequest(_) => "ConsensusRequest",
+            JoinerEvent::BlockProposerRequest(_) => "BlockProposerRequest",
             JoinerEvent::BlockHeaderFetcher(_) => "BlockHeaderFetcher",
             JoinerEvent::BlockHeaderByHeightFetcher(_) => "BlockHeaderByHeightFetcher",
             JoinerEvent::BlockHeaderFetcherRequest(_) => "BlockHeaderFetcherRequest",
@@ -268,6 +295,7 @@ impl ReactorEvent for JoinerEvent {
             JoinerEvent::BlockAndDeploysFetcherRequest(_) => "BlockAndDeploysFetcherRequest",
             JoinerEvent::BlocklistAnnouncement(_) => "BlocklistAnnouncement",
             JoinerEvent::StorageRequest(_) => "StorageRequest",
+            JoinerEvent::StateStoreRequest(_) => "StateStoreRequest",
             JoinerEvent::MarkBlockCompletedRequest(_) => "MarkBlockCompletedRequest",
             JoinerEvent::BeginAddressGossipRequest(_) => "BeginAddressGossipRequest",
             JoinerEvent::ConsensusMessageIncoming(_) => "ConsensusMessageIncoming",
@@ -275,10 +303,12 @@ impl ReactorEvent for JoinerEvent {
             JoinerEvent::AddressGossiperIncoming(_) => "AddressGossiperIncoming",
             JoinerEvent::NetRequestIncoming(_) => "NetRequestIncoming",
             JoinerEvent::NetResponseIncoming(_) => "NetResponseIncoming",
+            JoinerEvent::DeployResponseIncoming(_) => "DeployResponseIncoming",
             JoinerEvent::TrieRequestIncoming(_) => "TrieRequestIncoming",
             JoinerEvent::TrieDemand(_) => "TrieDemand",
             JoinerEvent::TrieResponseIncoming(_) => "TrieResponseIncoming",
             JoinerEvent::FinalitySignatureIncoming(_) => "FinalitySignatureIncoming",
+            JoinerEvent::FinalitySignaturesIncoming(_) => "FinalitySignaturesIncoming",
             JoinerEvent::ContractRuntimeRequest(_) => "ContractRuntimeRequest",
             JoinerEvent::DeployGossiper(_) => "DeployGossiper",
             JoinerEvent::DeployGossiperAnnouncement(_) => "DeployGossiperAnnouncement",
@@ -339,6 +369,7 @@ impl Display for JoinerEvent {
                 write!(f, "chain synchronizer request: {}", req)
             }
             JoinerEvent::StorageRequest(req) => write!(f, "storage request: {}", req),
+            JoinerEvent::StateStoreRequest(req) => write!(f, "state store request: {}", req),
             JoinerEvent::MarkBlockCompletedRequest(req) => {
                 write!(f, "mark block as completed request: {}", req)
             }
@@ -393,6 +424,7 @@ impl Display for JoinerEvent {
                 write!(f, "chainspec loader announcement: {}", ann)
             }
             JoinerEvent::ConsensusRequest(req) => write!(f, "consensus request: {:?}", req),
+            JoinerEvent::BlockProposerRequest(req) => write!(f, "block proposer request: {}", req),
             JoinerEvent::BlockHeaderFetcher(block_header) => {
                 write!(f, "block header fetcher event: {}", block_header)
             }
@@ -414,16 +446,22 @@ impl Display for JoinerEvent {
             JoinerEvent::AddressGossiperIncoming(inner) => write!(f, "incoming: {}", inner),
             JoinerEvent::NetRequestIncoming(inner) => write!(f, "incoming: {}", inner),
             JoinerEvent::NetResponseIncoming(inner) => write!(f, "incoming: {}", inner),
+            JoinerEvent::DeployResponseIncoming(inner) => write!(f, "incoming: {}", inner),
             JoinerEvent::TrieRequestIncoming(inner) => write!(f, "incoming: {}", inner),
             JoinerEvent::TrieDemand(inner) => write!(f, "demand: {}", inner),
             JoinerEvent::TrieResponseIncoming(inner) => write!(f, "incoming: {}", inner),
             JoinerEvent::FinalitySignatureIncoming(inner) => write!(f, "incoming: {}", inner),
+            JoinerEvent::FinalitySignaturesIncoming(inner) => write!(f, "incoming: {}", inner),
             JoinerEvent::ContractRuntimeRequest(req) => {
                 write!(f, "contract runtime request: {}", req)
             }
             JoinerEvent::DumpConsensusStateRequest(req) => {
                 write!(f, "consensus dump request: {}", req)
             }
+            JoinerEvent::DumpNetworkStateRequest(req) => write!(f, "{}", req),
+            JoinerEvent::ReloadConfigRequest(req) => write!(f, "{}", req),
+            JoinerEvent::ShutdownPointRequest(req) => write!(f, "{}", req),
+            JoinerEvent::ValidatorModeRequest(req) => write!(f, "{}", req),
             JoinerEvent::DeployGossiper(event) => write!(f, "deploy gossiper: {}", event),
             JoinerEvent::DeployGossiperAnnouncement(ann) => {
                 write!(f, "deploy gossiper announcement: {}", ann)
@@ -489,6 +527,10 @@ pub(crate) struct Reactor {
     memory_metrics: MemoryMetrics,
     node_startup_instant: Instant,
     deploy_gossiper: Gossiper<Deploy, JoinerEvent>,
+    /// The expiry times of the deploys signed by the account's own key and accepted from clients
+    /// while joining, by account.  These count towards the accounts' pending deploy quota, as no
+    /// block proposer buffers them yet.
+    client_deploy_expiries: HashMap<PublicKey, Vec<Timestamp>>,
 }
 
 impl reactor::Reactor for Reactor {
@@ -548,10 +590,18 @@ impl reactor::Reactor for Reactor {
             diagnostics_port_effects,
         ));
 
-        let address_gossiper =
-            Gossiper::new_for_complete_items("address_gossiper", config.gossip, registry)?;
-
         let effect_builder = EffectBuilder::new(event_queue);
+        let (address_gossiper, address_gossiper_effects) = Gossiper::new_for_complete_items(
+            "address_gossiper",
+            config.gossip,
+            effect_builder,
+            registry,
+        )?;
+        effects.extend(reactor::wrap_effects(
+            JoinerEvent::AddressGossiper,
+            address_gossiper_effects,
+        ));
+
         let (chain_synchronizer, sync_effects) =
             ChainSynchronizer::<JoinerEvent>::new_for_fast_sync(
                 Arc::clone(chainspec_loader.chainspec()),
@@ -567,7 +617,7 @@ impl reactor::Reactor for Reactor {
 
         let protocol_version = &chainspec_loader.chainspec().protocol_config.version;
         let rest_server = RestServer::new(
-            config.rest_server.clone(),
+            &WithDir::new(&root, config.rest_server.clone()),
             effect_builder,
             *protocol_version,
             node_startup_instant,
@@ -575,7 +625,7 @@ impl reactor::Reactor for Reactor {
 
         let event_stream_server = EventStreamServer::new(
             config.event_stream_server.clone(),
-            storage.root_path().to_path_buf(),
+            storage.global_state_path().to_path_buf(),
             *protocol_version,
         )?;
 
@@ -598,14 +648,23 @@ impl reactor::Reactor for Reactor {
 
         let trie_or_chunk_fetcher = fetcher_builder.build("trie_or_chunk")?;
 
-        let deploy_acceptor = DeployAcceptor::new(chainspec_loader.chainspec(), registry)?;
+        let deploy_acceptor = DeployAcceptor::new(
+            config.deploy_acceptor.clone(),
+            chainspec_loader.chainspec(),
+            registry,
+        )?;
 
-        let deploy_gossiper = Gossiper::new_for_partial_items(
+        let (deploy_gossiper, deploy_gossiper_effects) = Gossiper::new_for_partial_items(
             "deploy_gossiper",
             config.gossip,
+            effect_builder,
             gossiper::get_deploy_from_storage::<Deploy, JoinerEvent>,
             registry,
         )?;
+        effects.extend(reactor::wrap_effects(
+            JoinerEvent::DeployGossiper,
+            deploy_gossiper_effects,
+        ));
 
         effects.extend(reactor::wrap_effects(
             JoinerEvent::ChainspecLoader,
@@ -641,6 +700,7 @@ impl reactor::Reactor for Reactor {
                 node_startup_instant,
                 diagnostics_port,
                 deploy_gossiper,
+                client_deploy_expiries: HashMap::new(),
             },
             effects,
         ))
@@ -672,11 +732,22 @@ impl reactor::Reactor for Reactor {
             JoinerEvent::DeployAcceptorAnnouncement(
                 DeployAcceptorAnnouncement::AcceptedNewDeploy { deploy, source },
             ) => {
-                if let Err(error) = deploy.deploy_info() {
-                    error!(%error, "invalid deploy");
-                    return Effects::new();
+                let deploy_info = match deploy.deploy_info() {
+                    Ok(deploy_info) => deploy_info,
+                    Err(error) => {
+                        error!(%error, "invalid deploy");
+                        return Effects::new();
+                    }
                 };
 
+                // Like the block proposer, only count deploys signed by the account's own key.
+                if source.is_client() && deploy_info.signed_by_account {
+                    self.client_deploy_expiries
+                        .entry(deploy.header().account().clone())
+                        .or_default()
+                        .push(deploy.header().expires());
+                }
+
                 let event = event_stream_server::Event::DeployAccepted(deploy.clone());
                 let mut effects =
                     self.dispatch_event(effect_builder, rng, JoinerEvent::EventStreamServer(event));
@@ -773,6 +844,10 @@ impl reactor::Reactor for Reactor {
                 JoinerEvent::Storage,
                 self.storage.handle_event(effect_builder, rng, req.into()),
             ),
+            JoinerEvent::StateStoreRequest(req) => reactor::wrap_effects(
+                JoinerEvent::Storage,
+                self.storage.handle_event(effect_builder, rng, req.into()),
+            ),
             JoinerEvent::MarkBlockCompletedRequest(req) => reactor::wrap_effects(
                 JoinerEvent::Storage,
                 self.storage.handle_event(effect_builder, rng, req.into()),
@@ -860,12 +935,28 @@ impl reactor::Reactor for Reactor {
                 );
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            JoinerEvent::LinearChainAnnouncement(LinearChainAnnouncement::BlockFinalized(
+                signatures,
+            )) => {
+                let reactor_event = JoinerEvent::EventStreamServer(
+                    event_stream_server::Event::Finalized(signatures),
+                );
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
             JoinerEvent::ChainSynchronizerAnnouncement(
                 ChainSynchronizerAnnouncement::SyncFinished,
             ) => {
                 warn!("unexpected sync finished announcement in the joiner");
                 Effects::new()
             }
+            JoinerEvent::ChainSynchronizerAnnouncement(
+                ChainSynchronizerAnnouncement::SyncProgress(sync_status),
+            ) => {
+                let reactor_event = JoinerEvent::EventStreamServer(
+                    event_stream_server::Event::SyncProgress(sync_status),
+                );
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
             JoinerEvent::RestServer(event) => reactor::wrap_effects(
                 JoinerEvent::RestServer,
                 self.rest_server.handle_event(effect_builder, rng, event),
@@ -917,6 +1008,63 @@ impl reactor::Reactor for Reactor {
                 // no consensus, respond with empty map
                 responder.respond(BTreeMap::new()).ignore()
             }
+            JoinerEvent::BlockProposerRequest(BlockProposerRequest::CheckPendingDeployQuota {
+                account,
+                responder,
+            }) => {
+                let max_pending = self.config.block_proposer.max_pending_deploys_per_account;
+                if max_pending == 0 {
+                    return responder.respond(true).ignore();
+                }
+                // No block proposer is running, so count the deploys it saved before the node
+                // restarted along with those accepted from clients since.
+                let accepted_count = self.unexpired_client_deploy_count(&account);
+                async move {
+                    let saved_count =
+                        block_proposer::load_pending_count_for_account(effect_builder, &account)
+                            .await;
+                    responder
+                        .respond(saved_count + accepted_count < max_pending as usize)
+                        .await
+                }
+                .ignore()
+            }
+            JoinerEvent::BlockProposerRequest(
+                BlockProposerRequest::GetUnresolvableDependencies { responder, .. },
+            ) => responder.respond(None).ignore(),
+            JoinerEvent::BlockProposerRequest(BlockProposerRequest::CheckDeployReplacement {
+                responder,
+                ..
+            }) => {
+                // no block proposer, so no pending deploy can be replaced
+                responder.respond(None).ignore()
+            }
+            JoinerEvent::BlockProposerRequest(BlockProposerRequest::CheckDeployBlocklist {
+                deploy_hash,
+                account_hash,
+                responder,
+            }) => async move {
+                // No block proposer is running, so check the blocklist it saved.
+                let blocked = block_proposer::load_blocklist_contains(
+                    effect_builder,
+                    &deploy_hash,
+                    &account_hash,
+                )
+                .await;
+                responder.respond(blocked).await
+            }
+            .ignore(),
+            JoinerEvent::BlockProposerRequest(BlockProposerRequest::UpdateDeployBlocklist {
+                responder,
+                ..
+            }) => responder.respond(None).ignore(),
+            JoinerEvent::BlockProposerRequest(BlockProposerRequest::GetDeployBlocklist {
+                responder,
+            }) => responder.respond(None).ignore(),
+            JoinerEvent::BlockProposerRequest(BlockProposerRequest::RequestBlockPayload(_)) => {
+                error!("joiner reactor does not propose blocks");
+                Effects::new()
+            }
             JoinerEvent::BlockHeaderByHeightFetcher(event) => reactor::wrap_effects(
                 JoinerEvent::BlockHeaderByHeightFetcher,
                 self.block_header_and_finality_signatures_by_height_fetcher
@@ -954,6 +1102,18 @@ impl reactor::Reactor for Reactor {
             JoinerEvent::NetResponseIncoming(NetResponseIncoming { sender, message }) => {
                 reactor::handle_get_response(self, effect_builder, rng, sender, message)
             }
+            JoinerEvent::DeployResponseIncoming(DeployResponseIncoming {
+                sender,
+                message,
+                responder,
+            }) => reactor::handle_deploy_response(
+                self,
+                effect_builder,
+                rng,
+                sender,
+                &message.0,
+                Some(responder),
+            ),
             JoinerEvent::TrieRequestIncoming(incoming) => reactor::wrap_effects(
                 JoinerEvent::ContractRuntime,
                 self.contract_runtime
@@ -980,11 +1140,48 @@ impl reactor::Reactor for Reactor {
                 debug!(%sender, "finality signatures not handled in joiner reactor");
                 Effects::new()
             }
+            JoinerEvent::FinalitySignaturesIncoming(FinalitySignaturesIncoming {
+                sender, ..
+            }) => {
+                debug!(%sender, "finality signatures not handled in joiner reactor");
+                Effects::new()
+            }
             JoinerEvent::DumpConsensusStateRequest(req) => {
                 // We have no consensus running in the joiner, so we answer with `None`.
                 req.answer(Err(Cow::Borrowed("node is joining, no running consensus")))
                     .ignore()
             }
+            JoinerEvent::DumpNetworkStateRequest(req) => reactor::wrap_effects(
+                JoinerEvent::SmallNetwork,
+                self.small_network
+                    .handle_event(effect_builder, rng, req.into()),
+            ),
+            JoinerEvent::ReloadConfigRequest(ReloadConfigRequest { responder }) => {
+                // The components affected by a reload only run once the node is participating.
+                responder
+                    .respond(Err(Cow::Borrowed(
+                        "node is joining, config can be reloaded once participating",
+                    )))
+                    .ignore()
+            }
+            JoinerEvent::ShutdownPointRequest(ShutdownPointRequest { responder, .. }) => {
+                // The shutdown point is only tracked once the node is participating.
+                responder
+                    .respond(Err(Cow::Borrowed(
+                        "node is joining, shutdown point can be set once participating or via \
+                        the config file",
+                    )))
+                    .ignore()
+            }
+            JoinerEvent::ValidatorModeRequest(ValidatorModeRequest { responder, .. }) => {
+                // Consensus only runs once the node is participating.
+                responder
+                    .respond(Err(Cow::Borrowed(
+                        "node is joining, validator mode can be set once participating or via the \
+                        config file",
+                    )))
+                    .ignore()
+            }
             JoinerEvent::DeployGossiper(event) => reactor::wrap_effects(
                 JoinerEvent::DeployGossiper,
                 self.deploy_gossiper
@@ -1026,14 +1223,44 @@ impl reactor::Reactor for Reactor {
             })
     }
 
+    fn estimate_component_memory(&self) -> BTreeMap<&'static str, u64> {
+        self.memory_metrics.estimate(self);
+        self.memory_metrics.by_component()
+    }
+
     fn update_metrics(&mut self, event_queue_handle: EventQueueHandle<Self::Event>) {
         self.memory_metrics.estimate(self);
         self.event_queue_metrics
             .record_event_queue_counts(&event_queue_handle);
     }
+
+    fn summary(&self) -> ReactorSummary {
+        ReactorSummary {
+            current_era: None,
+            peer_count: Some(self.small_network.peers().len()),
+            last_block_height: None,
+            last_block_hash: None,
+        }
+    }
 }
 
 impl Reactor {
+    /// Returns the number of unexpired deploys accepted from the given account's clients while
+    /// joining, forgetting about the expired ones.
+    fn unexpired_client_deploy_count(&mut self, account: &PublicKey) -> usize {
+        let now = Timestamp::now();
+        let expiries = match self.client_deploy_expiries.get_mut(account) {
+            Some(expiries) => expiries,
+            None => return 0,
+        };
+        expiries.retain(|expiry| *expiry >= now);
+        let count = expiries.len();
+        if count == 0 {
+            self.client_deploy_expiries.remove(account);
+        }
+        count
+    }
+
     /// Deconstructs the reactor into config useful for creating a Validator reactor. Shuts down
     /// the network, closing all incoming and outgoing connections, and frees up the listening
     /// socket.
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use datasize::DataSize;
 use prometheus::{self, Histogram, HistogramOpts, IntGauge, Registry};
 use tracing::debug;
@@ -143,6 +145,24 @@ impl MemoryMetrics {
         %deploy_fetcher,
         "Collected new set of memory metrics for the joiner");
     }
+
+    /// Returns the most recent estimates of each component's heap memory usage, in bytes.
+    pub(super) fn by_component(&self) -> BTreeMap<&'static str, u64> {
+        [
+            ("metrics", &self.mem_metrics),
+            ("small_network", &self.mem_small_network),
+            ("address_gossiper", &self.mem_address_gossiper),
+            ("config", &self.mem_config),
+            ("chainspec_loader", &self.mem_chainspec_loader),
+            ("storage", &self.mem_storage),
+            ("contract_runtime", &self.mem_contract_runtime),
+            ("block_fetcher", &self.mem_block_fetcher),
+            ("deploy_fetcher", &self.mem_deploy_fetcher),
+        ]
+        .iter()
+        .map(|(component, gauge)| (*component, gauge.get() as u64))
+        .collect()
+    }
 }
 
 impl Drop for MemoryMetrics {
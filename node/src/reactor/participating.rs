@@ -3,12 +3,14 @@
 //! Participating nodes join the participating-only network upon startup.
 
 mod config;
+mod config_reload;
 mod error;
 mod memory_metrics;
 #[cfg(test)]
 mod tests;
 
 use std::{
+    collections::BTreeMap,
     fmt::{self, Debug, Display, Formatter},
     path::PathBuf,
     sync::Arc,
@@ -20,24 +22,27 @@ use derive_more::From;
 use prometheus::Registry;
 use reactor::ReactorEvent;
 use serde::Serialize;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use casper_execution_engine::storage::trie::TrieOrChunk;
 
 use crate::{
+    cli,
     components::{
         block_proposer::{self, BlockProposer},
         block_validator::{self, BlockValidator},
         chain_synchronizer::{self, ChainSynchronizer, JoiningOutcome},
         chainspec_loader::{self, ChainspecLoader},
+        clock_skew::{self, ClockSkewMonitor},
         consensus::{self, EraSupervisor, HighwayProtocol},
         contract_runtime::{BlockAndExecutionEffects, ContractRuntime, ExecutionPreState},
         deploy_acceptor::{self, DeployAcceptor},
         diagnostics_port::{self, DiagnosticsPort},
         event_stream_server::{self, EventStreamServer},
         fetcher::{self, Fetcher, FetcherBuilder},
+        finality_watchdog::{self, FinalityWatchdog},
         gossiper::{self, Gossiper},
-        linear_chain::{self, LinearChainComponent},
+        linear_chain::{self, LinearChainComponent, ShutdownPoint},
         metrics::Metrics,
         rest_server::{self, RestServer},
         rpc_server::{self, RpcServer},
@@ -50,14 +55,17 @@ use crate::{
         announcements::{
             BlockProposerAnnouncement, BlocklistAnnouncement, ChainSynchronizerAnnouncement,
             ChainspecLoaderAnnouncement, ConsensusAnnouncement, ContractRuntimeAnnouncement,
-            ControlAnnouncement, DeployAcceptorAnnouncement, GossiperAnnouncement,
-            LinearChainAnnouncement, RpcServerAnnouncement,
+            ControlAnnouncement, DeployAcceptorAnnouncement, FinalityWatchdogAnnouncement,
+            GossiperAnnouncement, LinearChainAnnouncement, RpcServerAnnouncement,
+        },
+        diagnostics_port::{
+            ConfigReloadReport, DumpConsensusStateRequest, DumpNetworkStateRequest,
+            ReloadConfigRequest, ShutdownPointRequest, ValidatorModeRequest,
         },
-        diagnostics_port::DumpConsensusStateRequest,
         incoming::{
-            ConsensusMessageIncoming, FinalitySignatureIncoming, GossiperIncoming,
-            NetRequestIncoming, NetResponseIncoming, TrieDemand, TrieRequestIncoming,
-            TrieResponseIncoming,
+            ConsensusMessageIncoming, DeployResponseIncoming, FinalitySignatureIncoming,
+            FinalitySignaturesIncoming, GossiperIncoming, NetRequestIncoming, NetResponseIncoming,
+            TrieDemand, TrieRequestIncoming, TrieResponseIncoming,
         },
         requests::{
             BeginGossipRequest, BlockProposerRequest, BlockValidationRequest,
@@ -67,19 +75,24 @@ use crate::{
         },
         EffectBuilder, EffectExt, Effects,
     },
-    fatal,
+    fatal, logging,
     protocol::Message,
-    reactor::{self, event_queue_metrics::EventQueueMetrics, EventQueueHandle, ReactorExit},
+    reactor::{
+        self, event_queue_metrics::EventQueueMetrics, EventQueueHandle, ReactorExit, ReactorSummary,
+    },
     types::{
         Block, BlockAndDeploys, BlockHeader, BlockHeaderWithMetadata, BlockHeadersBatch,
-        BlockSignatures, BlockWithMetadata, Deploy, ExitCode, FinalitySignature,
+        BlockSignals, BlockSignatures, BlockWithMetadata, Deploy, ExitCode, FinalitySignature,
         FinalizedApprovalsWithId,
     },
     utils::{Source, WithDir},
     NodeRng,
 };
 #[cfg(test)]
-use crate::{testing::network::NetworkedReactor, types::NodeId};
+use crate::{
+    testing::network::NetworkedReactor,
+    types::{Chainspec, NodeId},
+};
 pub(crate) use config::Config;
 pub(crate) use error::Error;
 use memory_metrics::MemoryMetrics;
@@ -125,6 +138,10 @@ pub(crate) enum ParticipatingEvent {
     #[from]
     DiagnosticsPort(diagnostics_port::Event),
     #[from]
+    FinalityWatchdog(finality_watchdog::Event),
+    #[from]
+    ClockSkewMonitor(clock_skew::Event),
+    #[from]
     ContractRuntime(contract_runtime::Event),
     #[from]
     BlockFetcher(#[serde(skip_serializing)] fetcher::Event<Block>),
@@ -196,6 +213,14 @@ pub(crate) enum ParticipatingEvent {
     StateStoreRequest(StateStoreRequest),
     #[from]
     DumpConsensusStateRequest(DumpConsensusStateRequest),
+    #[from]
+    DumpNetworkStateRequest(DumpNetworkStateRequest),
+    #[from]
+    ReloadConfigRequest(ReloadConfigRequest),
+    #[from]
+    ShutdownPointRequest(ShutdownPointRequest),
+    #[from]
+    ValidatorModeRequest(ValidatorModeRequest),
 
     // Announcements
     #[from]
@@ -219,6 +244,8 @@ pub(crate) enum ParticipatingEvent {
     #[from]
     ChainSynchronizerAnnouncement(#[serde(skip_serializing)] ChainSynchronizerAnnouncement),
     #[from]
+    FinalityWatchdogAnnouncement(FinalityWatchdogAnnouncement),
+    #[from]
     BlocklistAnnouncement(BlocklistAnnouncement),
     #[from]
     ConsensusMessageIncoming(ConsensusMessageIncoming),
@@ -231,6 +258,8 @@ pub(crate) enum ParticipatingEvent {
     #[from]
     NetResponseIncoming(NetResponseIncoming),
     #[from]
+    DeployResponseIncoming(DeployResponseIncoming),
+    #[from]
     TrieRequestIncoming(TrieRequestIncoming),
     #[from]
     TrieDemand(TrieDemand),
@@ -239,6 +268,8 @@ pub(crate) enum ParticipatingEvent {
     #[from]
     FinalitySignatureIncoming(FinalitySignatureIncoming),
     #[from]
+    FinalitySignaturesIncoming(FinalitySignaturesIncoming),
+    #[from]
     BlockProposerAnnouncement(#[serde(skip_serializing)] BlockProposerAnnouncement),
 }
 
@@ -289,6 +320,8 @@ impl ReactorEvent for ParticipatingEvent {
             ParticipatingEvent::BlockHeadersBatchFetcher(_) => "BlockHeadersBatchFetcher",
             ParticipatingEvent::FinalitySignaturesFetcher(_) => "FinalitySignaturesFetcher",
             ParticipatingEvent::DiagnosticsPort(_) => "DiagnosticsPort",
+            ParticipatingEvent::FinalityWatchdog(_) => "FinalityWatchdog",
+            ParticipatingEvent::ClockSkewMonitor(_) => "ClockSkewMonitor",
             ParticipatingEvent::NetworkRequest(_) => "NetworkRequest",
             ParticipatingEvent::NetworkInfoRequest(_) => "NetworkInfoRequest",
             ParticipatingEvent::BlockFetcherRequest(_) => "BlockFetcherRequest",
@@ -317,6 +350,10 @@ impl ReactorEvent for ParticipatingEvent {
             ParticipatingEvent::MarkBlockCompletedRequest(_) => "MarkBlockCompletedRequest",
             ParticipatingEvent::StateStoreRequest(_) => "StateStoreRequest",
             ParticipatingEvent::DumpConsensusStateRequest(_) => "DumpConsensusStateRequest",
+            ParticipatingEvent::DumpNetworkStateRequest(_) => "DumpNetworkStateRequest",
+            ParticipatingEvent::ReloadConfigRequest(_) => "ReloadConfigRequest",
+            ParticipatingEvent::ShutdownPointRequest(_) => "ShutdownPointRequest",
+            ParticipatingEvent::ValidatorModeRequest(_) => "ValidatorModeRequest",
             ParticipatingEvent::ControlAnnouncement(_) => "ControlAnnouncement",
             ParticipatingEvent::RpcServerAnnouncement(_) => "RpcServerAnnouncement",
             ParticipatingEvent::DeployAcceptorAnnouncement(_) => "DeployAcceptorAnnouncement",
@@ -334,12 +371,15 @@ impl ReactorEvent for ParticipatingEvent {
             ParticipatingEvent::AddressGossiperIncoming(_) => "AddressGossiperIncoming",
             ParticipatingEvent::NetRequestIncoming(_) => "NetRequestIncoming",
             ParticipatingEvent::NetResponseIncoming(_) => "NetResponseIncoming",
+            ParticipatingEvent::DeployResponseIncoming(_) => "DeployResponseIncoming",
             ParticipatingEvent::TrieRequestIncoming(_) => "TrieRequestIncoming",
             ParticipatingEvent::TrieDemand(_) => "TrieDemand",
             ParticipatingEvent::TrieResponseIncoming(_) => "TrieResponseIncoming",
             ParticipatingEvent::FinalitySignatureIncoming(_) => "FinalitySignatureIncoming",
+            ParticipatingEvent::FinalitySignaturesIncoming(_) => "FinalitySignaturesIncoming",
             ParticipatingEvent::ContractRuntime(_) => "ContractRuntime",
             ParticipatingEvent::ChainSynchronizerAnnouncement(_) => "ChainSynchronizerAnnouncement",
+            ParticipatingEvent::FinalityWatchdogAnnouncement(_) => "FinalityWatchdogAnnouncement",
         }
     }
 }
@@ -431,6 +471,12 @@ impl Display for ParticipatingEvent {
                 write!(f, "finality signatures fetcher: {}", event)
             }
             ParticipatingEvent::DiagnosticsPort(event) => write!(f, "diagnostics port: {}", event),
+            ParticipatingEvent::FinalityWatchdog(event) => {
+                write!(f, "finality watchdog: {}", event)
+            }
+            ParticipatingEvent::ClockSkewMonitor(event) => {
+                write!(f, "clock skew monitor: {}", event)
+            }
             ParticipatingEvent::ChainSynchronizerRequest(req) => {
                 write!(f, "chain synchronizer request: {}", req)
             }
@@ -490,6 +536,10 @@ impl Display for ParticipatingEvent {
             ParticipatingEvent::DumpConsensusStateRequest(req) => {
                 write!(f, "dump consensus state: {}", req)
             }
+            ParticipatingEvent::DumpNetworkStateRequest(req) => write!(f, "{}", req),
+            ParticipatingEvent::ReloadConfigRequest(req) => write!(f, "reload config: {}", req),
+            ParticipatingEvent::ShutdownPointRequest(req) => write!(f, "{}", req),
+            ParticipatingEvent::ValidatorModeRequest(req) => write!(f, "{}", req),
             ParticipatingEvent::RpcServerAnnouncement(ann) => {
                 write!(f, "api server announcement: {}", ann)
             }
@@ -523,15 +573,20 @@ impl Display for ParticipatingEvent {
             ParticipatingEvent::ChainSynchronizerAnnouncement(ann) => {
                 write!(f, "chain synchronizer announcement: {}", ann)
             }
+            ParticipatingEvent::FinalityWatchdogAnnouncement(ann) => {
+                write!(f, "finality watchdog announcement: {}", ann)
+            }
             ParticipatingEvent::ConsensusMessageIncoming(inner) => Display::fmt(inner, f),
             ParticipatingEvent::DeployGossiperIncoming(inner) => Display::fmt(inner, f),
             ParticipatingEvent::AddressGossiperIncoming(inner) => Display::fmt(inner, f),
             ParticipatingEvent::NetRequestIncoming(inner) => Display::fmt(inner, f),
             ParticipatingEvent::NetResponseIncoming(inner) => Display::fmt(inner, f),
+            ParticipatingEvent::DeployResponseIncoming(inner) => Display::fmt(inner, f),
             ParticipatingEvent::TrieRequestIncoming(inner) => Display::fmt(inner, f),
             ParticipatingEvent::TrieDemand(inner) => Display::fmt(inner, f),
             ParticipatingEvent::TrieResponseIncoming(inner) => Display::fmt(inner, f),
             ParticipatingEvent::FinalitySignatureIncoming(inner) => Display::fmt(inner, f),
+            ParticipatingEvent::FinalitySignaturesIncoming(inner) => Display::fmt(inner, f),
             ParticipatingEvent::ContractRuntime(inner) => Display::fmt(inner, f),
         }
     }
@@ -601,7 +656,15 @@ pub(crate) struct Reactor {
     block_headers_batch_fetcher: Fetcher<BlockHeadersBatch>,
     finality_signatures_fetcher: Fetcher<BlockSignatures>,
     diagnostics_port: DiagnosticsPort,
+    finality_watchdog: FinalityWatchdog,
+    clock_skew_monitor: ClockSkewMonitor,
     // Non-components.
+    /// The config table the node is running with, if it was started from a config file.
+    #[data_size(skip)]
+    config_table: Option<toml::Value>,
+    /// The maximum duration to wait for the reactor to be drained on shutdown.
+    #[data_size(skip)]
+    shutdown_drain_timeout: Duration,
     #[data_size(skip)] // Never allocates heap data.
     memory_metrics: MemoryMetrics,
     #[data_size(skip)]
@@ -624,6 +687,66 @@ impl Reactor {
     pub(crate) fn contract_runtime(&self) -> &ContractRuntime {
         &self.contract_runtime
     }
+
+    /// Inspect the chainspec.
+    pub(crate) fn chainspec(&self) -> &Arc<Chainspec> {
+        self.chainspec_loader.chainspec()
+    }
+}
+
+impl Reactor {
+    /// Reads the config file again and applies the changes which don't require a restart.
+    fn reload_config(
+        &mut self,
+    ) -> Result<(ConfigReloadReport, Effects<ParticipatingEvent>), String> {
+        let running_table = self
+            .config_table
+            .as_mut()
+            .ok_or("node was not started from a config file")?;
+        let reloaded_table = cli::reload_config_table().map_err(|error| error.to_string())?;
+        let config: Config = reloaded_table
+            .clone()
+            .try_into()
+            .map_err(|error| format!("invalid config: {}", error))?;
+
+        let report = config_reload::compare(running_table, &reloaded_table);
+        let mut effects = Effects::new();
+        if report.applied.iter().any(|key| key == "logging.level") {
+            let directives = config
+                .logging
+                .level()
+                .unwrap_or(logging::DEFAULT_LOG_FILTER);
+            logging::reload_filter(directives)
+                .map_err(|error| format!("invalid logging level: {}", error))?;
+        }
+        for key in &report.applied {
+            match key.as_str() {
+                "rpc_server.qps_limit"
+                | "rpc_server.max_body_bytes"
+                | "speculative_exec_server.qps_limit"
+                | "speculative_exec_server.max_body_bytes" => self
+                    .rpc_server
+                    .update_limits(&config.rpc_server, &config.speculative_exec_server),
+                "gossip.gossip_request_timeout"
+                | "gossip.get_remainder_timeout"
+                | "gossip.batch_interval" => {
+                    self.address_gossiper.update_timeouts(&config.gossip);
+                    self.deploy_gossiper.update_timeouts(&config.gossip);
+                }
+                "network.known_addresses" => effects.extend(reactor::wrap_effects(
+                    ParticipatingEvent::SmallNetwork,
+                    self.small_network
+                        .learn_known_addresses(&config.network.known_addresses),
+                )),
+                "event_stream_server.event_stream_buffer_length" => self
+                    .event_stream_server
+                    .set_buffer_length(config.event_stream_server.event_stream_buffer_length),
+                _ => (),
+            }
+        }
+        config_reload::update_running(running_table, &reloaded_table, &report.applied);
+        Ok((report, effects))
+    }
 }
 
 impl reactor::Reactor for Reactor {
@@ -654,6 +777,7 @@ impl reactor::Reactor for Reactor {
         } = config;
 
         let (our_secret_key, our_public_key) = config.consensus.load_keys(&root)?;
+        let shutdown_drain_timeout = config.node.shutdown_drain_timeout.into();
 
         let effect_builder = EffectBuilder::new(event_queue);
         let mut effects = Effects::new();
@@ -685,6 +809,10 @@ impl reactor::Reactor for Reactor {
                     );
 
                     let current_era_id = block.header().era_id();
+                    let signing_context_active = chainspec_loader
+                        .chainspec()
+                        .core_config
+                        .is_signing_context_active(current_era_id);
                     if let Some(step_effect_and_upcoming_era_validators) =
                         maybe_step_effect_and_upcoming_era_validators
                     {
@@ -693,6 +821,8 @@ impl reactor::Reactor for Reactor {
                                 .announce_commit_step_success(
                                     current_era_id,
                                     step_effect_and_upcoming_era_validators.step_execution_journal,
+                                    step_effect_and_upcoming_era_validators.era_rewards,
+                                    step_effect_and_upcoming_era_validators.era_stakes,
                                 )
                                 .ignore(),
                         );
@@ -737,6 +867,7 @@ impl reactor::Reactor for Reactor {
                                     current_era_id,
                                     &secret_key,
                                     public_key.clone(),
+                                    signing_context_active,
                                 );
 
                                 effect_builder
@@ -772,21 +903,36 @@ impl reactor::Reactor for Reactor {
 
         let effect_builder = EffectBuilder::new(event_queue);
 
-        let address_gossiper =
-            Gossiper::new_for_complete_items("address_gossiper", config.gossip, registry)?;
+        let (address_gossiper, address_gossiper_effects) = Gossiper::new_for_complete_items(
+            "address_gossiper",
+            config.gossip,
+            effect_builder,
+            registry,
+        )?;
+        effects.extend(reactor::wrap_effects(
+            ParticipatingEvent::AddressGossiper,
+            address_gossiper_effects,
+        ));
 
         let chainspec = chainspec_loader.chainspec();
 
         let protocol_version = chainspec.protocol_config.version;
+        if config.archive_server.enable_server && !config.node.sync_to_genesis {
+            warn!(
+                "the archive server is enabled without node.sync_to_genesis; proofs are only \
+                available for blocks whose global state is stored on this node"
+            );
+        }
         let rpc_server = RpcServer::new(
             config.rpc_server.clone(),
             config.speculative_exec_server.clone(),
+            config.archive_server.clone(),
             effect_builder,
             protocol_version,
             node_startup_instant,
         )?;
         let rest_server = RestServer::new(
-            config.rest_server.clone(),
+            &WithDir::new(&root, config.rest_server.clone()),
             effect_builder,
             protocol_version,
             node_startup_instant,
@@ -798,14 +944,23 @@ impl reactor::Reactor for Reactor {
             registry,
         );
 
-        let deploy_acceptor = DeployAcceptor::new(chainspec_loader.chainspec(), registry)?;
+        let deploy_acceptor = DeployAcceptor::new(
+            config.deploy_acceptor,
+            chainspec_loader.chainspec(),
+            registry,
+        )?;
         let deploy_fetcher = fetcher_builder.build("deploy")?;
-        let deploy_gossiper = Gossiper::new_for_partial_items(
+        let (deploy_gossiper, deploy_gossiper_effects) = Gossiper::new_for_partial_items(
             "deploy_gossiper",
             config.gossip,
+            effect_builder,
             gossiper::get_deploy_from_storage::<Deploy, ParticipatingEvent>,
             registry,
         )?;
+        effects.extend(reactor::wrap_effects(
+            ParticipatingEvent::DeployGossiper,
+            deploy_gossiper_effects,
+        ));
 
         let (block_proposer, block_proposer_effects) = BlockProposer::new(
             registry.clone(),
@@ -836,7 +991,7 @@ impl reactor::Reactor for Reactor {
         let next_upgrade_activation_point = chainspec_loader.next_upgrade_activation_point();
         let (consensus, init_consensus_effects) = EraSupervisor::new(
             highest_block_header.next_block_era_id(),
-            storage.root_path(),
+            storage.global_state_path(),
             our_secret_key,
             our_public_key,
             config.consensus,
@@ -857,6 +1012,20 @@ impl reactor::Reactor for Reactor {
         contract_runtime
             .set_initial_state(ExecutionPreState::from_block_header(&highest_block_header))?;
 
+        if let Some(pruning_interval) = storage.pruning_interval() {
+            if config.node.sync_to_genesis {
+                warn!(
+                    "history is not pruned while node.sync_to_genesis is enabled; set it to false \
+                    to apply storage.retention"
+                );
+            } else {
+                effects.extend(reactor::wrap_effects(
+                    ParticipatingEvent::ContractRuntime,
+                    contract_runtime.start_pruning_history(effect_builder, pruning_interval),
+                ));
+            }
+        }
+
         let block_validator = BlockValidator::new(Arc::clone(chainspec));
         let linear_chain = LinearChainComponent::new(
             registry,
@@ -864,7 +1033,12 @@ impl reactor::Reactor for Reactor {
             chainspec.core_config.auction_delay,
             chainspec.core_config.unbonding_delay,
             chainspec.highway_config.finality_threshold_fraction,
+            chainspec.core_config.signing_context_activation_era,
             next_upgrade_activation_point,
+            ShutdownPoint {
+                height: config.node.shutdown_at_height,
+                era: config.node.shutdown_at_era,
+            },
         )?;
 
         let (chain_synchronizer, chain_synchronizer_effects) =
@@ -900,6 +1074,25 @@ impl reactor::Reactor for Reactor {
             chainspec_loader.start_checking_for_upgrades(effect_builder),
         ));
 
+        let (finality_watchdog, finality_watchdog_effects) =
+            FinalityWatchdog::new(config.finality_watchdog, effect_builder, registry)?;
+        effects.extend(reactor::wrap_effects(
+            ParticipatingEvent::FinalityWatchdog,
+            finality_watchdog_effects,
+        ));
+
+        let (clock_skew_monitor, clock_skew_effects) =
+            ClockSkewMonitor::new(config.clock_skew, registry)?;
+        effects.extend(reactor::wrap_effects(
+            ParticipatingEvent::ClockSkewMonitor,
+            clock_skew_effects,
+        ));
+
+        let config_table = cli::initial_config_table();
+        if config_table.is_some() {
+            effects.extend(config_reload::reload_on_signal(effect_builder).ignore());
+        }
+
         Ok((
             Reactor {
                 metrics,
@@ -929,6 +1122,10 @@ impl reactor::Reactor for Reactor {
                 block_headers_batch_fetcher,
                 finality_signatures_fetcher,
                 diagnostics_port,
+                finality_watchdog,
+                clock_skew_monitor,
+                config_table,
+                shutdown_drain_timeout,
                 memory_metrics,
                 event_queue_metrics,
             },
@@ -1065,6 +1262,16 @@ impl reactor::Reactor for Reactor {
                 self.diagnostics_port
                     .handle_event(effect_builder, rng, event),
             ),
+            ParticipatingEvent::FinalityWatchdog(event) => reactor::wrap_effects(
+                ParticipatingEvent::FinalityWatchdog,
+                self.finality_watchdog
+                    .handle_event(effect_builder, rng, event),
+            ),
+            ParticipatingEvent::ClockSkewMonitor(event) => reactor::wrap_effects(
+                ParticipatingEvent::ClockSkewMonitor,
+                self.clock_skew_monitor
+                    .handle_event(effect_builder, rng, event),
+            ),
 
             // Requests:
             ParticipatingEvent::ChainSynchronizerRequest(request) => reactor::wrap_effects(
@@ -1172,6 +1379,33 @@ impl reactor::Reactor for Reactor {
                 ParticipatingEvent::Consensus,
                 self.consensus.handle_event(effect_builder, rng, req.into()),
             ),
+            ParticipatingEvent::DumpNetworkStateRequest(req) => reactor::wrap_effects(
+                ParticipatingEvent::SmallNetwork,
+                self.small_network
+                    .handle_event(effect_builder, rng, req.into()),
+            ),
+            ParticipatingEvent::ValidatorModeRequest(req) => reactor::wrap_effects(
+                ParticipatingEvent::Consensus,
+                self.consensus.handle_event(effect_builder, rng, req.into()),
+            ),
+            ParticipatingEvent::ShutdownPointRequest(req) => reactor::wrap_effects(
+                ParticipatingEvent::LinearChain,
+                self.linear_chain
+                    .handle_event(effect_builder, rng, req.into()),
+            ),
+            ParticipatingEvent::ReloadConfigRequest(ReloadConfigRequest { responder }) => {
+                match self.reload_config() {
+                    Ok((report, mut effects)) => {
+                        info!(%report, "reloaded config");
+                        effects.extend(responder.respond(Ok(report)).ignore());
+                        effects
+                    }
+                    Err(error) => {
+                        warn!(%error, "failed to reload config");
+                        responder.respond(Err(error.into())).ignore()
+                    }
+                }
+            }
 
             // Announcements:
             ParticipatingEvent::ControlAnnouncement(ctrl_ann) => {
@@ -1192,6 +1426,32 @@ impl reactor::Reactor for Reactor {
                     ParticipatingEvent::DeployAcceptor(event),
                 )
             }
+            ParticipatingEvent::RpcServerAnnouncement(
+                RpcServerAnnouncement::DeployPrecheckRequested { deploy, responder },
+            ) => {
+                let event = deploy_acceptor::Event::Precheck { deploy, responder };
+                self.dispatch_event(
+                    effect_builder,
+                    rng,
+                    ParticipatingEvent::DeployAcceptor(event),
+                )
+            }
+            ParticipatingEvent::RpcServerAnnouncement(
+                RpcServerAnnouncement::TransactionV2Received {
+                    transaction,
+                    responder,
+                },
+            ) => {
+                let event = deploy_acceptor::Event::AcceptTransactionV2 {
+                    transaction,
+                    responder,
+                };
+                self.dispatch_event(
+                    effect_builder,
+                    rng,
+                    ParticipatingEvent::DeployAcceptor(event),
+                )
+            }
             ParticipatingEvent::DeployAcceptorAnnouncement(
                 DeployAcceptorAnnouncement::AcceptedNewDeploy { deploy, source },
             ) => {
@@ -1252,10 +1512,14 @@ impl reactor::Reactor for Reactor {
             ParticipatingEvent::ConsensusAnnouncement(consensus_announcement) => {
                 match consensus_announcement {
                     ConsensusAnnouncement::Finalized(block) => {
+                        let mut effects = effect_builder
+                            .put_block_signals_to_storage(Box::new(BlockSignals::from(&*block)))
+                            .ignore();
                         let reactor_event = ParticipatingEvent::BlockProposer(
                             block_proposer::Event::FinalizedBlock(block),
                         );
-                        self.dispatch_event(effect_builder, rng, reactor_event)
+                        effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                        effects
                     }
                     ConsensusAnnouncement::CreatedFinalitySignature(fs) => self.dispatch_event(
                         effect_builder,
@@ -1278,6 +1542,15 @@ impl reactor::Reactor for Reactor {
                         );
                         self.dispatch_event(effect_builder, rng, reactor_event)
                     }
+                    ConsensusAnnouncement::OrphanedDeploys {
+                        era_id: _,
+                        deploy_hashes,
+                    } => {
+                        let reactor_event = ParticipatingEvent::BlockProposer(
+                            block_proposer::Event::OrphanedDeploys(deploy_hashes),
+                        );
+                        self.dispatch_event(effect_builder, rng, reactor_event)
+                    }
                 }
             }
             ParticipatingEvent::ContractRuntimeAnnouncement(
@@ -1319,14 +1592,32 @@ impl reactor::Reactor for Reactor {
                 ContractRuntimeAnnouncement::CommitStepSuccess {
                     era_id,
                     execution_effect,
+                    era_rewards,
+                    era_stakes,
                 },
             ) => {
+                let mut effects = Effects::new();
+                if let Some(era_rewards) = era_rewards {
+                    effects.extend(
+                        effect_builder
+                            .put_era_rewards_to_storage(era_rewards)
+                            .ignore(),
+                    );
+                }
+                if let Some(era_stakes) = era_stakes {
+                    effects.extend(
+                        effect_builder
+                            .put_era_stakes_to_storage(era_stakes)
+                            .ignore(),
+                    );
+                }
                 let reactor_event =
                     ParticipatingEvent::EventStreamServer(event_stream_server::Event::Step {
                         era_id,
                         execution_effect,
                     });
-                self.dispatch_event(effect_builder, rng, reactor_event)
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                effects
             }
             ParticipatingEvent::DeployGossiperAnnouncement(
                 GossiperAnnouncement::NewCompleteItem(gossiped_deploy_id),
@@ -1365,11 +1656,20 @@ impl reactor::Reactor for Reactor {
                         header: Box::new(block.header().clone()),
                         header_hash: *block.hash(),
                     });
+                let reactor_event_bp = ParticipatingEvent::BlockProposer(
+                    block_proposer::Event::BlockExecuted(block.clone()),
+                );
+                let reactor_event_fw =
+                    ParticipatingEvent::FinalityWatchdog(finality_watchdog::Event::BlockAdded {
+                        height: block.height(),
+                    });
                 let reactor_event_es = ParticipatingEvent::EventStreamServer(
                     event_stream_server::Event::BlockAdded(block),
                 );
                 let mut effects = self.dispatch_event(effect_builder, rng, reactor_event_es);
                 effects.extend(self.dispatch_event(effect_builder, rng, reactor_event_consensus));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event_bp));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event_fw));
 
                 effects
             }
@@ -1389,6 +1689,14 @@ impl reactor::Reactor for Reactor {
                 );
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            ParticipatingEvent::LinearChainAnnouncement(
+                LinearChainAnnouncement::BlockFinalized(signatures),
+            ) => {
+                let reactor_event = ParticipatingEvent::EventStreamServer(
+                    event_stream_server::Event::Finalized(signatures),
+                );
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
             ParticipatingEvent::ChainSynchronizerAnnouncement(
                 ChainSynchronizerAnnouncement::SyncFinished,
             ) => self.dispatch_event(
@@ -1400,6 +1708,24 @@ impl reactor::Reactor for Reactor {
                     ),
                 ),
             ),
+            ParticipatingEvent::ChainSynchronizerAnnouncement(
+                ChainSynchronizerAnnouncement::SyncProgress(sync_status),
+            ) => self.dispatch_event(
+                effect_builder,
+                rng,
+                ParticipatingEvent::EventStreamServer(event_stream_server::Event::SyncProgress(
+                    sync_status,
+                )),
+            ),
+            ParticipatingEvent::FinalityWatchdogAnnouncement(
+                FinalityWatchdogAnnouncement::StatusChanged(alert),
+            ) => self.dispatch_event(
+                effect_builder,
+                rng,
+                ParticipatingEvent::EventStreamServer(event_stream_server::Event::FinalityAlert(
+                    alert,
+                )),
+            ),
             ParticipatingEvent::ChainspecLoaderAnnouncement(
                 ChainspecLoaderAnnouncement::UpgradeActivationPointRead(next_upgrade),
             ) => {
@@ -1416,6 +1742,12 @@ impl reactor::Reactor for Reactor {
                     linear_chain::Event::GotUpgradeActivationPoint(next_upgrade.activation_point()),
                 );
                 effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                let reactor_event = ParticipatingEvent::SmallNetwork(
+                    small_network::Event::ChainspecLoaderAnnouncement(
+                        ChainspecLoaderAnnouncement::UpgradeActivationPointRead(next_upgrade),
+                    ),
+                );
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
                 effects
             }
             ParticipatingEvent::BlocklistAnnouncement(ann) => self.dispatch_event(
@@ -1446,6 +1778,18 @@ impl reactor::Reactor for Reactor {
             ParticipatingEvent::NetResponseIncoming(NetResponseIncoming { sender, message }) => {
                 reactor::handle_get_response(self, effect_builder, rng, sender, message)
             }
+            ParticipatingEvent::DeployResponseIncoming(DeployResponseIncoming {
+                sender,
+                message,
+                responder,
+            }) => reactor::handle_deploy_response(
+                self,
+                effect_builder,
+                rng,
+                sender,
+                &message.0,
+                Some(responder),
+            ),
             ParticipatingEvent::TrieRequestIncoming(req) => reactor::wrap_effects(
                 ParticipatingEvent::ContractRuntime,
                 self.contract_runtime
@@ -1470,6 +1814,11 @@ impl reactor::Reactor for Reactor {
                 self.linear_chain
                     .handle_event(effect_builder, rng, incoming.into()),
             ),
+            ParticipatingEvent::FinalitySignaturesIncoming(incoming) => reactor::wrap_effects(
+                ParticipatingEvent::LinearChain,
+                self.linear_chain
+                    .handle_event(effect_builder, rng, incoming.into()),
+            ),
             ParticipatingEvent::ContractRuntimeAnnouncement(ann) => self.dispatch_event(
                 effect_builder,
                 rng,
@@ -1483,16 +1832,59 @@ impl reactor::Reactor for Reactor {
         }
     }
 
+    fn estimate_component_memory(&self) -> BTreeMap<&'static str, u64> {
+        self.memory_metrics.estimate(self);
+        self.memory_metrics.by_component()
+    }
+
     fn update_metrics(&mut self, event_queue_handle: EventQueueHandle<Self::Event>) {
         self.memory_metrics.estimate(self);
         self.event_queue_metrics
             .record_event_queue_counts(&event_queue_handle)
     }
 
+    fn summary(&self) -> ReactorSummary {
+        ReactorSummary {
+            current_era: Some(self.consensus.current_era()),
+            peer_count: Some(self.small_network.peers().len()),
+            last_block_height: self.linear_chain.latest_block().map(Block::height),
+            last_block_hash: self.linear_chain.latest_block().map(|block| *block.hash()),
+        }
+    }
+
     fn maybe_exit(&self) -> Option<ReactorExit> {
-        self.linear_chain
-            .stop_for_upgrade()
-            .then(|| ReactorExit::ProcessShouldExit(ExitCode::Success))
+        if self.linear_chain.stop_for_upgrade() {
+            Some(ReactorExit::ProcessShouldExit(ExitCode::Success))
+        } else if self.linear_chain.reached_shutdown_point() {
+            Some(ReactorExit::ProcessShouldExit(
+                ExitCode::ReachedShutdownPoint,
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn begin_drain(
+        &mut self,
+        _effect_builder: EffectBuilder<Self::Event>,
+    ) -> (Duration, Effects<Self::Event>) {
+        self.deploy_acceptor.stop_accepting();
+        self.contract_runtime.begin_drain();
+        self.small_network.announce_going_away();
+        (self.shutdown_drain_timeout, Effects::new())
+    }
+
+    fn is_drained(&self) -> bool {
+        self.contract_runtime.is_drained()
+    }
+
+    fn finish_drain(&mut self) {
+        if let Err(error) = self.storage.flush() {
+            error!(%error, "failed to flush storage");
+        }
+        if let Err(error) = self.contract_runtime.flush() {
+            error!(%error, "failed to flush global state");
+        }
     }
 }
 
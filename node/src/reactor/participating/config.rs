@@ -1,15 +1,17 @@
 use datasize::DataSize;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    logging::LoggingConfig, types::NodeConfig, BlockProposerConfig, ConsensusConfig,
-    ContractRuntimeConfig, DiagnosticsPortConfig, EventStreamServerConfig, FetcherConfig,
-    GossipConfig, RestServerConfig, RpcServerConfig, SmallNetworkConfig, SpeculativeExecConfig,
-    StorageConfig,
+    audit_log::AuditLogConfig, incident::Config as IncidentReportingConfig, logging::LoggingConfig,
+    reactor::supervisor::Config as SupervisorConfig, types::NodeConfig, ArchiveServerConfig,
+    BlockProposerConfig, ClockSkewConfig, ConsensusConfig, ContractRuntimeConfig,
+    DeployAcceptorConfig, DiagnosticsPortConfig, EventStreamServerConfig, FetcherConfig,
+    FinalityWatchdogConfig, GossipConfig, RestServerConfig, RpcServerConfig, SmallNetworkConfig,
+    SpeculativeExecConfig, StorageConfig,
 };
 
 /// Root configuration.
-#[derive(DataSize, Debug, Default, Deserialize)]
+#[derive(DataSize, Debug, Default, Deserialize, Serialize)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
 pub(crate) struct Config {
@@ -29,6 +31,9 @@ pub(crate) struct Config {
     pub(crate) rpc_server: RpcServerConfig,
     /// Speculative execution server configuration.
     pub(crate) speculative_exec_server: SpeculativeExecConfig,
+    /// Archive server configuration.
+    #[serde(default)]
+    pub(crate) archive_server: ArchiveServerConfig,
     /// On-disk storage configuration.
     pub(crate) storage: StorageConfig,
     /// Gossip protocol configuration.
@@ -40,6 +45,24 @@ pub(crate) struct Config {
     /// Block proposer configuration.
     #[serde(default)]
     pub(crate) block_proposer: BlockProposerConfig,
+    /// Deploy acceptor configuration.
+    #[serde(default)]
+    pub(crate) deploy_acceptor: DeployAcceptorConfig,
     /// Diagnostics port configuration.
     pub(crate) diagnostics_port: DiagnosticsPortConfig,
+    /// Reactor supervisor configuration.
+    #[serde(default)]
+    pub(crate) supervisor: SupervisorConfig,
+    /// Finality watchdog configuration.
+    #[serde(default)]
+    pub(crate) finality_watchdog: FinalityWatchdogConfig,
+    /// Clock skew monitor configuration.
+    #[serde(default)]
+    pub(crate) clock_skew: ClockSkewConfig,
+    /// Audit log configuration.
+    #[serde(default)]
+    pub(crate) audit_log: AuditLogConfig,
+    /// Incident reporting configuration.
+    #[serde(default)]
+    pub(crate) incident_reporting: IncidentReportingConfig,
 }
@@ -0,0 +1,196 @@
+//! Reloading of the node's config while it is running.
+//!
+//! A reload is requested either by sending `SIGHUP` to the node or via the diagnostics port.  The
+//! config file is read again and compared with the config the node is running with.  Changes to
+//! the keys in `RELOADABLE_KEYS` are applied immediately, while all other changes only take effect
+//! after a restart.
+
+use std::{collections::BTreeSet, sync::atomic::Ordering, time::Duration};
+
+use toml::Value;
+use tracing::{info, warn};
+
+use crate::{
+    audit_log,
+    effect::{
+        diagnostics_port::{ConfigReloadReport, ReloadConfigRequest},
+        EffectBuilder,
+    },
+    RELOAD_REQUESTED,
+};
+
+/// The config keys which can be changed without restarting the node.
+pub(super) const RELOADABLE_KEYS: &[&str] = &[
+    "logging.level",
+    "rpc_server.qps_limit",
+    "rpc_server.max_body_bytes",
+    "speculative_exec_server.qps_limit",
+    "speculative_exec_server.max_body_bytes",
+    "gossip.gossip_request_timeout",
+    "gossip.get_remainder_timeout",
+    "gossip.batch_interval",
+    "network.known_addresses",
+    "event_stream_server.event_stream_buffer_length",
+];
+
+/// How often to check whether a reload has been requested via `SIGHUP`.
+const RELOAD_SIGNAL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Returns the keys, as `section.key`, whose values differ between the two config tables.
+fn changed_keys(old: &Value, new: &Value) -> Vec<String> {
+    let empty = toml::value::Table::new();
+    let old_sections = old.as_table().unwrap_or(&empty);
+    let new_sections = new.as_table().unwrap_or(&empty);
+    let section_names: BTreeSet<_> = old_sections.keys().chain(new_sections.keys()).collect();
+
+    let mut changed = vec![];
+    for section_name in section_names {
+        match (
+            old_sections.get(section_name).and_then(Value::as_table),
+            new_sections.get(section_name).and_then(Value::as_table),
+        ) {
+            (Some(old_section), Some(new_section)) => {
+                let keys: BTreeSet<_> = old_section.keys().chain(new_section.keys()).collect();
+                changed.extend(
+                    keys.into_iter()
+                        .filter(|key| old_section.get(*key) != new_section.get(*key))
+                        .map(|key| format!("{}.{}", section_name, key)),
+                );
+            }
+            _ => {
+                if old_sections.get(section_name) != new_sections.get(section_name) {
+                    changed.push(section_name.clone());
+                }
+            }
+        }
+    }
+    changed
+}
+
+/// Compares the running config table with the reloaded one, classifying the changed keys into
+/// those which can be applied and those which require a restart.
+pub(super) fn compare(running: &Value, reloaded: &Value) -> ConfigReloadReport {
+    let (applied, requires_restart) = changed_keys(running, reloaded)
+        .into_iter()
+        .partition(|key| RELOADABLE_KEYS.contains(&key.as_str()));
+    ConfigReloadReport {
+        applied,
+        requires_restart,
+    }
+}
+
+/// Copies the values of the given `section.key` keys from the reloaded table into the running one.
+pub(super) fn update_running(running: &mut Value, reloaded: &Value, keys: &[String]) {
+    for key in keys {
+        let (section_name, key) = match key.split_once('.') {
+            Some(split) => split,
+            None => continue,
+        };
+        let maybe_value = reloaded
+            .get(section_name)
+            .and_then(|section| section.get(key))
+            .cloned();
+        let running_section = match running.as_table_mut().map(|sections| {
+            sections
+                .entry(section_name)
+                .or_insert_with(|| Value::Table(toml::value::Table::new()))
+        }) {
+            Some(Value::Table(section)) => section,
+            _ => continue,
+        };
+        match maybe_value {
+            Some(value) => {
+                running_section.insert(key.to_string(), value);
+            }
+            None => {
+                running_section.remove(key);
+            }
+        }
+    }
+}
+
+/// Requests a config reload whenever the node receives `SIGHUP`.
+pub(super) async fn reload_on_signal<REv>(effect_builder: EffectBuilder<REv>)
+where
+    REv: From<ReloadConfigRequest> + Send,
+{
+    loop {
+        effect_builder
+            .set_timeout(RELOAD_SIGNAL_POLL_INTERVAL)
+            .await;
+        if !RELOAD_REQUESTED.swap(false, Ordering::SeqCst) {
+            continue;
+        }
+        let result = effect_builder.reload_config().await;
+        audit_log::record(
+            &audit_log::Source::Signal { signal: "SIGHUP" },
+            audit_log::Action::config_reload(&result),
+        );
+        match result {
+            Ok(report) => info!(%report, "reloaded config on SIGHUP"),
+            Err(error) => warn!(%error, "failed to reload config on SIGHUP"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(toml: &str) -> Value {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn should_classify_changed_keys() {
+        let running = table(
+            r#"
+            [logging]
+            format = 'text'
+
+            [rpc_server]
+            qps_limit = 100
+            address = '0.0.0.0:7777'
+
+            [network]
+            known_addresses = ['127.0.0.1:34553']
+            "#,
+        );
+        let reloaded = table(
+            r#"
+            [logging]
+            format = 'text'
+            level = 'debug'
+
+            [rpc_server]
+            qps_limit = 50
+            address = '0.0.0.0:7778'
+
+            [network]
+            known_addresses = ['127.0.0.1:34553']
+            "#,
+        );
+
+        let report = compare(&running, &reloaded);
+        assert_eq!(
+            report.applied,
+            vec!["logging.level", "rpc_server.qps_limit"]
+        );
+        assert_eq!(report.requires_restart, vec!["rpc_server.address"]);
+        assert_eq!(compare(&running, &running), ConfigReloadReport::default());
+    }
+
+    #[test]
+    fn should_only_update_applied_keys() {
+        let mut running = table("[rpc_server]\nqps_limit = 100\naddress = '0.0.0.0:7777'");
+        let reloaded = table("[rpc_server]\nqps_limit = 50\naddress = '0.0.0.0:7778'");
+
+        let report = compare(&running, &reloaded);
+        update_running(&mut running, &reloaded, &report.applied);
+
+        // The key requiring a restart is still reported on the next reload.
+        let report = compare(&running, &reloaded);
+        assert!(report.applied.is_empty());
+        assert_eq!(report.requires_restart, vec!["rpc_server.address"]);
+    }
+}
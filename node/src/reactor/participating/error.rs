@@ -2,13 +2,13 @@ use thiserror::Error;
 
 use crate::{
     components::{
-        chain_synchronizer, contract_runtime, contract_runtime::BlockExecutionError,
-        diagnostics_port, small_network, storage,
+        chain_synchronizer, consensus::LoadKeysError, contract_runtime,
+        contract_runtime::BlockExecutionError, diagnostics_port, small_network, storage,
     },
-    utils::{ListeningError, LoadError},
+    utils::ListeningError,
 };
 use casper_execution_engine::core::engine_state;
-use casper_types::{bytesrepr, crypto::ErrorExt as CryptoError};
+use casper_types::bytesrepr;
 
 /// Error type returned by the validator reactor.
 #[derive(Debug, Error)]
@@ -63,7 +63,7 @@ pub(crate) enum Error {
 
     /// Error while loading the signing key pair.
     #[error("signing key pair load error: {0}")]
-    LoadSigningKeyPair(#[from] LoadError<CryptoError>),
+    LoadSigningKeyPair(#[from] LoadKeysError),
 }
 
 impl From<bytesrepr::Error> for Error {
@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
+
 use datasize::DataSize;
-use prometheus::{self, Histogram, HistogramOpts, IntGauge, Registry};
+use prometheus::{self, Histogram, HistogramOpts, IntGauge, IntGaugeVec, Opts, Registry};
 use tracing::debug;
 
 use super::Reactor;
@@ -24,6 +26,13 @@ pub(super) struct MemoryMetrics {
     mem_block_proposer: IntGauge,
     mem_block_validator: IntGauge,
     mem_linear_chain: IntGauge,
+    mem_chain_synchronizer: IntGauge,
+    mem_trie_or_chunk_fetcher: IntGauge,
+    /// Estimated heap memory usage of the block, block header and finality signature fetchers.
+    mem_block_fetchers: IntGauge,
+    /// Estimated heap memory usage of parts of the largest components, labelled by component and
+    /// part, e.g. the gossip table of the deploy gossiper or each open consensus era.
+    mem_component_part: IntGaugeVec,
     /// Histogram detailing how long it took to measure memory usage.
     mem_estimator_runtime_s: Histogram,
     registry: Registry,
@@ -70,6 +79,25 @@ impl MemoryMetrics {
         )?;
         let mem_linear_chain =
             IntGauge::new("mem_linear_chain", "linear chain memory usage in bytes")?;
+        let mem_chain_synchronizer = IntGauge::new(
+            "mem_chain_synchronizer",
+            "chain synchronizer memory usage in bytes",
+        )?;
+        let mem_trie_or_chunk_fetcher = IntGauge::new(
+            "mem_trie_or_chunk_fetcher",
+            "trie or chunk fetcher memory usage in bytes",
+        )?;
+        let mem_block_fetchers = IntGauge::new(
+            "mem_block_fetchers",
+            "block, block header and finality signature fetchers memory usage in bytes",
+        )?;
+        let mem_component_part = IntGaugeVec::new(
+            Opts::new(
+                "mem_component_part",
+                "memory usage in bytes of parts of a component",
+            ),
+            &["component", "part"],
+        )?;
 
         let mem_estimator_runtime_s = Histogram::with_opts(
             HistogramOpts::new(
@@ -96,6 +124,10 @@ impl MemoryMetrics {
         registry.register(Box::new(mem_block_proposer.clone()))?;
         registry.register(Box::new(mem_block_validator.clone()))?;
         registry.register(Box::new(mem_linear_chain.clone()))?;
+        registry.register(Box::new(mem_chain_synchronizer.clone()))?;
+        registry.register(Box::new(mem_trie_or_chunk_fetcher.clone()))?;
+        registry.register(Box::new(mem_block_fetchers.clone()))?;
+        registry.register(Box::new(mem_component_part.clone()))?;
         registry.register(Box::new(mem_estimator_runtime_s.clone()))?;
 
         Ok(MemoryMetrics {
@@ -115,6 +147,10 @@ impl MemoryMetrics {
             mem_block_proposer,
             mem_block_validator,
             mem_linear_chain,
+            mem_chain_synchronizer,
+            mem_trie_or_chunk_fetcher,
+            mem_block_fetchers,
+            mem_component_part,
             mem_estimator_runtime_s,
             registry,
         })
@@ -139,6 +175,19 @@ impl MemoryMetrics {
         let block_proposer = reactor.block_proposer.estimate_heap_size() as i64;
         let block_validator = reactor.block_validator.estimate_heap_size() as i64;
         let linear_chain = reactor.linear_chain.estimate_heap_size() as i64;
+        let chain_synchronizer = reactor.chain_synchronizer.estimate_heap_size() as i64;
+        let trie_or_chunk_fetcher = reactor.trie_or_chunk_fetcher.estimate_heap_size() as i64;
+        let block_fetchers = (reactor.block_by_hash_fetcher.estimate_heap_size()
+            + reactor.block_header_by_hash_fetcher.estimate_heap_size()
+            + reactor.block_by_height_fetcher.estimate_heap_size()
+            + reactor
+                .block_header_and_finality_signatures_by_height_fetcher
+                .estimate_heap_size()
+            + reactor.block_and_deploys_fetcher.estimate_heap_size()
+            + reactor.finalized_approvals_fetcher.estimate_heap_size()
+            + reactor.block_headers_batch_fetcher.estimate_heap_size()
+            + reactor.finality_signatures_fetcher.estimate_heap_size())
+            as i64;
 
         let total = metrics
             + net
@@ -154,7 +203,39 @@ impl MemoryMetrics {
             + deploy_gossiper
             + block_proposer
             + block_validator
-            + linear_chain;
+            + linear_chain
+            + chain_synchronizer
+            + trie_or_chunk_fetcher
+            + block_fetchers;
+
+        // Parts are reset first, as e.g. consensus eras come and go.
+        self.mem_component_part.reset();
+        let parts = [
+            ("net", reactor.small_network.estimate_heap_size_breakdown()),
+            (
+                "consensus",
+                reactor.consensus.estimate_heap_size_breakdown(),
+            ),
+            (
+                "deploy_gossiper",
+                reactor.deploy_gossiper.estimate_heap_size_breakdown(),
+            ),
+            (
+                "address_gossiper",
+                reactor.address_gossiper.estimate_heap_size_breakdown(),
+            ),
+            (
+                "block_proposer",
+                reactor.block_proposer.estimate_heap_size_breakdown(),
+            ),
+        ];
+        for (component, breakdown) in &parts {
+            for (part, size) in breakdown {
+                self.mem_component_part
+                    .with_label_values(&[*component, part.as_str()])
+                    .set(*size as i64);
+            }
+        }
 
         self.mem_total.set(total);
         self.mem_metrics.set(metrics);
@@ -172,6 +253,9 @@ impl MemoryMetrics {
         self.mem_block_proposer.set(block_proposer);
         self.mem_block_validator.set(block_validator);
         self.mem_linear_chain.set(linear_chain);
+        self.mem_chain_synchronizer.set(chain_synchronizer);
+        self.mem_trie_or_chunk_fetcher.set(trie_or_chunk_fetcher);
+        self.mem_block_fetchers.set(block_fetchers);
 
         // Stop the timer explicitly, don't count logging.
         let duration_s = timer.stop_and_record();
@@ -193,8 +277,38 @@ impl MemoryMetrics {
                %block_proposer,
                %block_validator,
                %linear_chain,
+               %chain_synchronizer,
+               %trie_or_chunk_fetcher,
+               %block_fetchers,
                "Collected new set of memory metrics.");
     }
+
+    /// Returns the most recent estimates of each component's heap memory usage, in bytes.
+    pub(super) fn by_component(&self) -> BTreeMap<&'static str, u64> {
+        [
+            ("metrics", &self.mem_metrics),
+            ("net", &self.mem_net),
+            ("address_gossiper", &self.mem_address_gossiper),
+            ("storage", &self.mem_storage),
+            ("contract_runtime", &self.mem_contract_runtime),
+            ("rpc_server", &self.mem_rpc_server),
+            ("rest_server", &self.mem_rest_server),
+            ("event_stream_server", &self.mem_event_stream_server),
+            ("chainspec_loader", &self.mem_chainspec_loader),
+            ("consensus", &self.mem_consensus),
+            ("deploy_fetcher", &self.mem_deploy_fetcher),
+            ("deploy_gossiper", &self.mem_deploy_gossiper),
+            ("block_proposer", &self.mem_block_proposer),
+            ("block_validator", &self.mem_block_validator),
+            ("linear_chain", &self.mem_linear_chain),
+            ("chain_synchronizer", &self.mem_chain_synchronizer),
+            ("trie_or_chunk_fetcher", &self.mem_trie_or_chunk_fetcher),
+            ("block_fetchers", &self.mem_block_fetchers),
+        ]
+        .iter()
+        .map(|(component, gauge)| (*component, gauge.get() as u64))
+        .collect()
+    }
 }
 
 impl Drop for MemoryMetrics {
@@ -215,6 +329,10 @@ impl Drop for MemoryMetrics {
         unregister_metric!(self.registry, self.mem_block_proposer);
         unregister_metric!(self.registry, self.mem_block_validator);
         unregister_metric!(self.registry, self.mem_linear_chain);
+        unregister_metric!(self.registry, self.mem_chain_synchronizer);
+        unregister_metric!(self.registry, self.mem_trie_or_chunk_fetcher);
+        unregister_metric!(self.registry, self.mem_block_fetchers);
+        unregister_metric!(self.registry, self.mem_component_part);
         unregister_metric!(self.registry, self.mem_estimator_runtime_s);
     }
 }
@@ -9,11 +9,18 @@ use tempfile::TempDir;
 use tokio::time;
 use tracing::{error, info};
 
-use casper_execution_engine::core::engine_state::GetBidsRequest;
+use casper_execution_engine::{
+    core::engine_state::{executable_deploy_item::ExecutableDeployItem, GetBidsRequest},
+    shared::newtypes::CorrelationId,
+};
 use casper_types::{
-    system::auction::{Bids, DelegationRate},
+    runtime_args,
+    system::{
+        auction::{self, Bids, DelegationRate},
+        standard_payment::ARG_AMOUNT,
+    },
     testing::TestRng,
-    EraId, Motes, ProtocolVersion, PublicKey, SecretKey, Timestamp, U512,
+    EraId, Motes, ProtocolVersion, PublicKey, RuntimeArgs, SecretKey, Timestamp, U512,
 };
 
 use crate::{
@@ -30,10 +37,17 @@ use crate::{
         participating::{self, ParticipatingEvent},
         Reactor, ReactorExit, Runner,
     },
-    testing::{self, filter_reactor::FilterReactor, network::Network, ConditionCheckReactor},
+    testing::{
+        self,
+        filter_reactor::FilterReactor,
+        network::Network,
+        network_faults::{IncomingMessage, NetworkFaults},
+        ConditionCheckReactor,
+    },
     types::{
         chainspec::{AccountConfig, AccountsConfig, ValidatorConfig},
-        ActivationPoint, BlockHeader, Chainspec, ChainspecRawBytes, Deploy, ExitCode,
+        ActivationPoint, BlockHeader, Chainspec, ChainspecRawBytes, Deploy, DeployHash, ExitCode,
+        NodeId,
     },
     utils::{External, Loadable, Source, WithDir, RESOURCES_PATH},
     NodeRng,
@@ -55,6 +69,24 @@ impl Runner<ConditionCheckReactor<FilterReactor<participating::Reactor>>> {
     }
 }
 
+impl IncomingMessage for ParticipatingEvent {
+    fn incoming_sender(&self) -> Option<NodeId> {
+        match self {
+            ParticipatingEvent::ConsensusMessageIncoming(incoming) => Some(incoming.sender),
+            ParticipatingEvent::DeployGossiperIncoming(incoming) => Some(incoming.sender),
+            ParticipatingEvent::AddressGossiperIncoming(incoming) => Some(incoming.sender),
+            ParticipatingEvent::NetRequestIncoming(incoming) => Some(incoming.sender),
+            ParticipatingEvent::NetResponseIncoming(incoming) => Some(incoming.sender),
+            ParticipatingEvent::TrieRequestIncoming(incoming) => Some(incoming.sender),
+            ParticipatingEvent::TrieResponseIncoming(incoming) => Some(incoming.sender),
+            ParticipatingEvent::FinalitySignatureIncoming(incoming) => Some(incoming.sender),
+            ParticipatingEvent::FinalitySignaturesIncoming(incoming) => Some(incoming.sender),
+            // Messages carrying a responder are left alone: the networking layer awaits them.
+            _ => None,
+        }
+    }
+}
+
 impl TestChain {
     /// Instantiates a new test chain configuration.
     ///
@@ -129,6 +161,42 @@ impl TestChain {
         Arc::get_mut(&mut self.chainspec).unwrap()
     }
 
+    /// Makes every era end as soon as `blocks` blocks have been finalized in it, regardless of
+    /// its duration.
+    fn with_era_length(&mut self, blocks: u64) -> &mut Self {
+        let core_config = &mut self.chainspec_mut().core_config;
+        core_config.minimum_era_height = blocks;
+        core_config.era_duration = 0.into();
+        self
+    }
+
+    /// Sets the genesis balance of the account of `public_key` to `balance` motes, adding the
+    /// account if it doesn't exist yet, e.g. to pay for injected bids.
+    fn fund_account(&mut self, public_key: PublicKey, balance: U512) -> &mut Self {
+        let accounts_config = &mut self.chainspec_mut().network_config.accounts_config;
+        let mut validator = None;
+        let mut accounts: Vec<AccountConfig> = accounts_config
+            .accounts()
+            .iter()
+            .filter(|account| {
+                if account.public_key() != public_key {
+                    return true;
+                }
+                validator = account.validator().copied();
+                false
+            })
+            .cloned()
+            .collect();
+        accounts.push(AccountConfig::new(
+            public_key,
+            Motes::new(balance),
+            validator,
+        ));
+        let delegators = accounts_config.delegators().to_vec();
+        *accounts_config = AccountsConfig::new(accounts, delegators);
+        self
+    }
+
     /// Creates an initializer/validator configuration for the `idx`th validator.
     fn create_node_config(&mut self, idx: usize, first_node_port: u16) -> participating::Config {
         // Set the network configuration.
@@ -218,6 +286,48 @@ fn is_in_era(era_id: EraId) -> impl Fn(&Nodes) -> bool {
     }
 }
 
+/// Returns the height of the highest block stored by the given node, if any.
+fn highest_block_height(
+    runner: &Runner<ConditionCheckReactor<FilterReactor<participating::Reactor>>>,
+) -> Option<u64> {
+    runner
+        .participating()
+        .storage()
+        .read_highest_block_header()
+        .expect("failed to read from storage")
+        .map(|header| header.height())
+}
+
+/// Asserts that all nodes agree on every block up to the highest one finalized by all of them, and
+/// returns the height of that block.
+fn assert_finalized_chains_agree(nodes: &Nodes) -> u64 {
+    let common_height = nodes
+        .values()
+        .map(|runner| highest_block_height(runner).expect("node has no blocks"))
+        .min()
+        .expect("network has no nodes");
+    for height in 0..=common_height {
+        let mut header_iter = nodes.iter().map(|(node_id, runner)| {
+            let header = runner
+                .participating()
+                .storage()
+                .read_block_header_by_height(height)
+                .expect("failed to read from storage")
+                .unwrap_or_else(|| panic!("node {} is missing block {}", node_id, height));
+            (node_id, header)
+        });
+        let (first_node_id, header) = header_iter.next().unwrap();
+        for (node_id, other_header) in header_iter {
+            assert_eq!(
+                header, other_header,
+                "nodes {} and {} disagree on block {}",
+                first_node_id, node_id, height
+            );
+        }
+    }
+    common_height
+}
+
 /// A set of consecutive switch blocks.
 struct SwitchBlocks {
     headers: Vec<BlockHeader>,
@@ -285,6 +395,171 @@ impl SwitchBlocks {
     }
 }
 
+/// The payment for deploys injected into the network, in motes.
+const INJECTED_DEPLOY_PAYMENT: u64 = 10_000_000_000;
+
+/// Helpers to progress the network through eras and change its validator set.
+impl Network<FilterReactor<participating::Reactor>> {
+    /// Runs the network until all nodes are in era `era_id` or a later one.
+    async fn run_until_era(&mut self, rng: &mut TestRng, era_id: EraId, within: Duration) {
+        self.settle_on(
+            rng,
+            move |nodes: &Nodes| {
+                nodes
+                    .values()
+                    .all(|runner| runner.participating().consensus().current_era() >= era_id)
+            },
+            within,
+        )
+        .await;
+    }
+
+    /// Runs the network until the era after the latest one any node is in has started on all
+    /// nodes, i.e. until the current era has ended with a switch block, and returns that era.
+    async fn run_until_next_era(&mut self, rng: &mut TestRng, within: Duration) -> EraId {
+        let next_era_id = self
+            .nodes()
+            .values()
+            .map(|runner| runner.participating().consensus().current_era())
+            .max()
+            .expect("network has no nodes")
+            .successor();
+        self.run_until_era(rng, next_era_id, within).await;
+        next_era_id
+    }
+
+    /// Runs the network until all nodes have stored the block at `height`.
+    async fn run_until_block_height(&mut self, rng: &mut TestRng, height: u64, within: Duration) {
+        self.settle_on(
+            rng,
+            move |nodes: &Nodes| {
+                nodes
+                    .values()
+                    .all(|runner| highest_block_height(runner).map_or(false, |h| h >= height))
+            },
+            within,
+        )
+        .await;
+    }
+
+    /// Runs the network until all nodes have executed the deploy with the given hash.
+    async fn run_until_deploy_executed(
+        &mut self,
+        rng: &mut TestRng,
+        deploy_hash: DeployHash,
+        within: Duration,
+    ) {
+        self.settle_on(
+            rng,
+            move |nodes: &Nodes| {
+                nodes.values().all(|runner| {
+                    runner
+                        .participating()
+                        .storage()
+                        .get_deploy_metadata_by_hash(&deploy_hash)
+                        .is_some()
+                })
+            },
+            within,
+        )
+        .await;
+    }
+
+    /// Hands `deploy` to every node, as if a client had submitted it to each of them.
+    async fn inject_deploy(&mut self, deploy: Deploy) -> DeployHash {
+        let deploy_hash = *deploy.id();
+        for runner in self.runners_mut() {
+            runner
+                .process_injected_effects(|effect_builder| {
+                    effect_builder
+                        .put_deploy_to_storage(Box::new(deploy.clone()))
+                        .ignore()
+                })
+                .await;
+            runner
+                .process_injected_effects(|effect_builder| {
+                    effect_builder
+                        .announce_new_deploy_accepted(Box::new(deploy.clone()), Source::Client)
+                        .ignore()
+                })
+                .await;
+        }
+        deploy_hash
+    }
+
+    /// Injects a deploy calling the given entry point of the auction contract on behalf of the
+    /// account of `secret_key`, and returns its hash.
+    async fn inject_auction_call(
+        &mut self,
+        secret_key: &SecretKey,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) -> DeployHash {
+        let runner = self.nodes().values().next().expect("network has no nodes");
+        let participating = runner.participating();
+        let state_root_hash = *participating
+            .storage()
+            .read_highest_block_header()
+            .expect("failed to read from storage")
+            .expect("node has no blocks")
+            .state_root_hash();
+        let auction_hash = participating
+            .contract_runtime()
+            .engine_state()
+            .get_system_auction_hash(CorrelationId::new(), state_root_hash)
+            .expect("failed to get auction contract hash");
+        let chainspec = participating.chainspec();
+
+        let payment = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Default::default(),
+            args: runtime_args! { ARG_AMOUNT => U512::from(INJECTED_DEPLOY_PAYMENT) },
+        };
+        let session = ExecutableDeployItem::StoredContractByHash {
+            hash: auction_hash,
+            entry_point: entry_point.to_string(),
+            args,
+        };
+        let deploy = Deploy::new(
+            Timestamp::now(),
+            chainspec.deploy_config.max_ttl,
+            1,
+            vec![],
+            chainspec.network_config.name.clone(),
+            payment,
+            session,
+            secret_key,
+            None,
+        );
+        self.inject_deploy(deploy).await
+    }
+
+    /// Injects a bid of `amount` motes by the account of `secret_key`, adding it to the validator
+    /// set or increasing its stake once the auction has run.
+    ///
+    /// The account must be funded, e.g. via `TestChain::fund_account`.  Unless the account belongs
+    /// to a node in the network, its stake must not prevent the others from finalizing blocks.
+    async fn inject_bid(&mut self, secret_key: &SecretKey, amount: U512) -> DeployHash {
+        let args = runtime_args! {
+            auction::ARG_PUBLIC_KEY => PublicKey::from(secret_key),
+            auction::ARG_AMOUNT => amount,
+            auction::ARG_DELEGATION_RATE => DelegationRate::zero(),
+        };
+        self.inject_auction_call(secret_key, auction::METHOD_ADD_BID, args)
+            .await
+    }
+
+    /// Injects a withdrawal of `amount` motes from the bid of the account of `secret_key`,
+    /// reducing its stake or removing it from the validator set once the auction has run.
+    async fn inject_withdraw_bid(&mut self, secret_key: &SecretKey, amount: U512) -> DeployHash {
+        let args = runtime_args! {
+            auction::ARG_PUBLIC_KEY => PublicKey::from(secret_key),
+            auction::ARG_AMOUNT => amount,
+        };
+        self.inject_auction_call(secret_key, auction::METHOD_WITHDRAW_BID, args)
+            .await
+    }
+}
+
 #[tokio::test]
 async fn run_participating_network() {
     testing::init_logging();
@@ -316,6 +591,70 @@ async fn run_participating_network() {
     .await;
 }
 
+#[tokio::test]
+async fn run_network_with_faults() {
+    testing::init_logging();
+
+    let mut rng = crate::new_rng();
+
+    // Equal stakes, so that consensus can progress without any single validator.
+    const NETWORK_SIZE: usize = 5;
+    let keys: Vec<Arc<SecretKey>> = (0..NETWORK_SIZE)
+        .map(|_| Arc::new(SecretKey::random(&mut rng)))
+        .collect();
+    let stakes = keys
+        .iter()
+        .map(|secret_key| (PublicKey::from(&**secret_key), U512::from(100)))
+        .collect();
+    let mut chain = TestChain::new_with_keys(&mut rng, keys, stakes);
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    let faults = NetworkFaults::new(&mut rng);
+    faults.set_latency(Duration::from_millis(50), Duration::from_millis(100));
+    faults.set_packet_loss(0.02);
+    net.inject_faults(&faults);
+
+    net.settle_on(
+        &mut rng,
+        is_in_era(EraId::from(1)),
+        Duration::from_secs(300),
+    )
+    .await;
+
+    // Cut one node off until the others have moved on to the next era.
+    let isolated = *net.nodes().keys().next().unwrap();
+    faults.partition(iter::once(isolated));
+    net.settle_on(
+        &mut rng,
+        |nodes| {
+            nodes
+                .iter()
+                .filter(|(node_id, _)| **node_id != isolated)
+                .all(|(_, runner)| {
+                    runner.participating().consensus().current_era() >= EraId::from(2)
+                })
+        },
+        Duration::from_secs(300),
+    )
+    .await;
+    faults.heal();
+
+    // The isolated node must catch up, and all nodes must have finalized the same chain.
+    net.settle_on(
+        &mut rng,
+        is_in_era(EraId::from(3)),
+        Duration::from_secs(300),
+    )
+    .await;
+    assert!(faults.dropped() > 0);
+    let common_height = assert_finalized_chains_agree(net.nodes());
+    assert!(common_height >= 3);
+}
+
 #[tokio::test]
 async fn run_equivocator_network() {
     testing::init_logging();
@@ -431,8 +770,7 @@ async fn dont_upgrade_without_switch_block() {
 
     // Eras have exactly two blocks each, and there is one block per second.
     let mut chain = TestChain::new_with_keys(&mut rng, keys, stakes.clone());
-    chain.chainspec_mut().core_config.minimum_era_height = 2;
-    chain.chainspec_mut().core_config.era_duration = 0.into();
+    chain.with_era_length(2);
     chain.chainspec_mut().highway_config.minimum_round_exponent = 10;
 
     let mut net = chain
@@ -528,8 +866,7 @@ async fn should_store_finalized_approvals() {
 
     // Eras have exactly two blocks each, and there is one block per second.
     let mut chain = TestChain::new_with_keys(&mut rng, keys, stakes.clone());
-    chain.chainspec_mut().core_config.minimum_era_height = 2;
-    chain.chainspec_mut().core_config.era_duration = 0.into();
+    chain.with_era_length(2);
     chain.chainspec_mut().highway_config.minimum_round_exponent = 10;
 
     let mut net = chain
@@ -616,20 +953,8 @@ async fn should_store_finalized_approvals() {
 
     // Run until the deploy gets executed.
     let timeout = Duration::from_secs(90);
-    net.settle_on(
-        &mut rng,
-        |nodes| {
-            nodes.values().all(|runner| {
-                runner
-                    .participating()
-                    .storage()
-                    .get_deploy_metadata_by_hash(&deploy_hash)
-                    .is_some()
-            })
-        },
-        timeout,
-    )
-    .await;
+    net.run_until_deploy_executed(&mut rng, deploy_hash, timeout)
+        .await;
 
     // Check if the approvals agree.
     for runner in net.nodes().values() {
@@ -664,6 +989,68 @@ async fn should_store_finalized_approvals() {
     }
 }
 
+#[tokio::test]
+async fn should_change_validator_weights_via_injected_bids() {
+    testing::init_logging();
+
+    let mut rng = crate::new_rng();
+
+    const STAKE: u64 = 1_000_000_000_000;
+    const BID_AMOUNT: u64 = 500_000_000_000;
+    const WITHDRAWAL_AMOUNT: u64 = 300_000_000_000;
+
+    // Set up a network with two validators of equal stake.
+    let alice_sk = Arc::new(SecretKey::random(&mut rng));
+    let alice_pk = PublicKey::from(&*alice_sk);
+    let bob_sk = Arc::new(SecretKey::random(&mut rng));
+    let bob_pk = PublicKey::from(&*bob_sk);
+    let keys: Vec<Arc<SecretKey>> = vec![alice_sk.clone(), bob_sk.clone()];
+    let stakes: BTreeMap<PublicKey, U512> = keys
+        .iter()
+        .map(|secret_key| (PublicKey::from(&**secret_key), U512::from(STAKE)))
+        .collect();
+
+    // Eras have exactly two blocks each, and there is one block per second.
+    let mut chain = TestChain::new_with_keys(&mut rng, keys, stakes);
+    chain
+        .with_era_length(2)
+        .fund_account(alice_pk.clone(), U512::from(10 * STAKE))
+        .fund_account(bob_pk.clone(), U512::from(10 * STAKE));
+    chain.chainspec_mut().highway_config.minimum_round_exponent = 10;
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+    let timeout = Duration::from_secs(90);
+    net.run_until_block_height(&mut rng, 1, timeout).await;
+
+    // Alice increases her stake, and Bob decreases his.
+    let bid_hash = net.inject_bid(&alice_sk, U512::from(BID_AMOUNT)).await;
+    let withdrawal_hash = net
+        .inject_withdraw_bid(&bob_sk, U512::from(WITHDRAWAL_AMOUNT))
+        .await;
+    net.run_until_deploy_executed(&mut rng, bid_hash, timeout)
+        .await;
+    net.run_until_deploy_executed(&mut rng, withdrawal_hash, timeout)
+        .await;
+
+    // The bids were changed at the latest in the current era. The auction at its end already uses
+    // them, but only for the era after the next one, given an auction delay of one.
+    let mut era_id = EraId::from(0);
+    for _ in 0..3 {
+        era_id = net.run_until_next_era(&mut rng, timeout).await;
+    }
+    let last_switch_block_era = era_id.value() - 1;
+    let switch_blocks = SwitchBlocks::collect(net.nodes(), last_switch_block_era + 1);
+    let validators = switch_blocks.next_era_validators(last_switch_block_era);
+
+    // Both stakes also grow by the rewards paid out so far, which are far lower than the changes.
+    assert!(validators[&alice_pk] >= U512::from(STAKE + BID_AMOUNT));
+    assert!(validators[&bob_pk] >= U512::from(STAKE - WITHDRAWAL_AMOUNT));
+    assert!(validators[&bob_pk] < U512::from(STAKE));
+}
+
 #[tokio::test]
 async fn empty_block_validation_regression() {
     testing::init_logging();
@@ -30,6 +30,14 @@ pub enum QueueKind {
     ///
     /// This is the default queue.
     Regular,
+    /// Consensus messages and finality signatures, as well as the events resulting from handling
+    /// them.
+    ///
+    /// These are time-critical, as a validator falling behind on them under load may miss rounds.
+    /// They are given a large weight, so they are dispatched well ahead of bulk gossip and trie
+    /// transfers, but as with any other queue their share is bounded, so that other queues keep
+    /// making progress even if consensus traffic is heavy.
+    Consensus,
     /// Reporting events on the local node.
     ///
     /// Metric events take precedence over most other events since missing a request for metrics
@@ -46,6 +54,7 @@ impl Display for QueueKind {
             QueueKind::NetworkDemand => "NetworkDemand",
             QueueKind::Network => "Network",
             QueueKind::Regular => "Regular",
+            QueueKind::Consensus => "Consensus",
             QueueKind::Api => "Api",
         };
         write!(f, "{}", str_value)
@@ -72,11 +81,24 @@ impl QueueKind {
             QueueKind::NetworkDemand => 2,
             QueueKind::Network => 4,
             QueueKind::Regular => 8,
+            QueueKind::Consensus => 16,
             QueueKind::Api => 16,
         })
         .expect("weight must be positive")
     }
 
+    /// Returns the queue on which events resulting from the effects of an event taken from this queue
+    /// should be scheduled.
+    ///
+    /// Only consensus events pass their priority on, so that e.g. timers set or responses received
+    /// by the consensus component are not delayed behind regular events.
+    pub(crate) fn inherited_by_effects(self) -> Self {
+        match self {
+            QueueKind::Consensus => QueueKind::Consensus,
+            _ => QueueKind::default(),
+        }
+    }
+
     /// Return weights of all possible `Queue`s.
     pub(crate) fn weights() -> Vec<(Self, NonZeroUsize)> {
         QueueKind::into_enum_iter()
@@ -92,6 +114,7 @@ impl QueueKind {
             QueueKind::NetworkLowPriority => "network_low_priority",
             QueueKind::Network => "network",
             QueueKind::Regular => "regular",
+            QueueKind::Consensus => "consensus",
             QueueKind::Api => "api",
         }
     }
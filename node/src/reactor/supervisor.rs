@@ -0,0 +1,691 @@
+//! Reactor health supervision.
+//!
+//! The supervisor watches the reactor's event loop from a dedicated thread, so that it keeps
+//! running even if the event loop itself is blocked.  It tracks which component is currently
+//! handling an event, when each component last handled an event and how long its events waited in
+//! the queue, as well as the depth of each event queue.
+//!
+//! If a single event is being handled for longer than the configured stall timeout, the reactor is
+//! considered stalled: an error is logged, the `reactor_stalls` metric is incremented and, if
+//! configured, the process exits with [`ExitCode::Stalled`] so that it can be restarted.  Queues
+//! which keep growing and components whose events wait for too long are reported as warnings.
+//!
+//! If a crash dump path is configured, a bounded summary of the tracked state is written to it as
+//! JSON when the node panics or exits due to a fatal error, see [`write_crash_dump`].  The tracked
+//! state also provides the context of incidents reported to external services, see
+//! [`incident_context`].
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug, Formatter},
+    fs, io,
+    path::PathBuf,
+    process,
+    sync::{Arc, Mutex, MutexGuard, TryLockError, Weak},
+    thread,
+    time::{Duration, Instant},
+};
+
+use datasize::DataSize;
+use once_cell::sync::OnceCell;
+use prometheus::{IntCounter, IntGauge, Registry};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+use casper_types::{EraId, TimeDiff, Timestamp};
+
+use super::QueueKind;
+use crate::{
+    incident::{self, Incident, IncidentContext, IncidentKind},
+    types::{BlockHash, ExitCode},
+    unregister_metric,
+    utils::{deserialize_nonzero_time_diff, WithDir},
+};
+
+/// Minimum number of events in a queue before its growth is reported.
+const MIN_REPORTED_QUEUE_DEPTH: usize = 1_000;
+
+/// The supervisor writing crash dumps and providing the context of incidents, if any.
+static GLOBAL_SUPERVISOR: OnceCell<Weak<Shared>> = OnceCell::new();
+
+/// Supervisor configuration.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Whether or not the supervisor is enabled.
+    pub(crate) enabled: bool,
+    /// How often the health of the reactor is checked.
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
+    pub(crate) check_interval: TimeDiff,
+    /// How long a single event may be handled, or a component's event may wait in the queue,
+    /// before it is reported as stalled.
+    #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
+    pub(crate) stall_timeout: TimeDiff,
+    /// Number of consecutive checks a queue has to grow for before it is reported.
+    pub(crate) queue_growth_checks: u32,
+    /// Whether to exit the process with a dedicated exit code if the reactor stalls, so that it can
+    /// be restarted.
+    pub(crate) restart_on_stall: bool,
+    /// Path to write a summary of the reactor's state to when the node crashes, if any.
+    #[serde(default)]
+    pub(crate) crash_dump_path: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: true,
+            check_interval: TimeDiff::from_seconds(10),
+            stall_timeout: TimeDiff::from_seconds(120),
+            queue_growth_checks: 6,
+            restart_on_stall: false,
+            crash_dump_path: None,
+        }
+    }
+}
+
+/// Error starting the supervisor.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// The supervisor's metrics could not be registered.
+    #[error(transparent)]
+    Metrics(#[from] prometheus::Error),
+    /// The supervisor thread could not be spawned.
+    #[error("could not spawn supervisor thread: {0}")]
+    Spawn(#[from] io::Error),
+}
+
+/// Returns the current depth of each event queue.
+type QueueCounts = Box<dyn Fn() -> HashMap<QueueKind, usize> + Send>;
+
+/// Handle to the supervisor, shared with the runners it supervises.
+///
+/// The supervisor thread exits once all handles have been dropped.
+#[derive(Clone)]
+pub(crate) struct Supervisor {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    config: Config,
+    /// The absolute path to write crash dumps to, if any.
+    crash_dump_path: Option<PathBuf>,
+    state: Mutex<State>,
+    metrics: Metrics,
+}
+
+/// The event currently being handled.
+#[derive(Clone, Copy, Debug)]
+struct Dispatch {
+    component: &'static str,
+    event_id: u64,
+    started: Instant,
+}
+
+/// Responsiveness of a single component.
+#[derive(Debug)]
+struct ComponentHealth {
+    /// When the component last finished handling an event.
+    last_handled: Instant,
+    /// The ID of the last event handled by the component.
+    last_event_id: u64,
+    /// The longest time one of the component's events waited in the queue since the last check.
+    max_queue_time: Duration,
+}
+
+/// Parts of the reactor's state included in crash dumps, provided by the reactor itself.
+#[derive(Clone, Debug, Default, Serialize)]
+pub(crate) struct ReactorSummary {
+    /// The current era, if known.
+    pub(crate) current_era: Option<EraId>,
+    /// The number of connected peers, if known.
+    pub(crate) peer_count: Option<usize>,
+    /// The height of the latest block stored, if known.
+    pub(crate) last_block_height: Option<u64>,
+    /// The hash of the latest block stored, if known.
+    pub(crate) last_block_hash: Option<BlockHash>,
+}
+
+/// A summary of the reactor's state at the time of a crash.
+#[derive(Debug, Serialize)]
+struct CrashDump<'a> {
+    /// What caused the crash.
+    reason: &'a str,
+    timestamp: Timestamp,
+    /// The type of reactor running.
+    reactor: &'static str,
+    #[serde(flatten)]
+    summary: &'a ReactorSummary,
+    /// The event being handled when the crash happened, if any.
+    current_event: Option<CrashDumpEvent>,
+    /// The number of events in each queue.
+    queue_depths: BTreeMap<QueueKind, usize>,
+    /// The last event handled by each component.
+    last_events: BTreeMap<&'static str, CrashDumpEvent>,
+}
+
+/// An event in a crash dump.
+#[derive(Debug, Serialize)]
+struct CrashDumpEvent {
+    /// The component handling the event.
+    component: &'static str,
+    /// The event's ID, as used in the logs.
+    event_id: u64,
+    /// How long ago the event started (if current) or finished (otherwise) being handled.
+    millis_ago: u64,
+}
+
+/// Growth of a single event queue across checks.
+#[derive(Debug, Default)]
+struct QueueGrowth {
+    depth: usize,
+    consecutive: u32,
+}
+
+#[derive(Default)]
+struct State {
+    current: Option<Dispatch>,
+    components: HashMap<&'static str, ComponentHealth>,
+    /// The type of reactor running.
+    reactor: &'static str,
+    summary: ReactorSummary,
+    queue_counts: Option<QueueCounts>,
+    queue_growth: HashMap<QueueKind, QueueGrowth>,
+    /// Whether the current stall has already been reported.
+    stall_reported: bool,
+}
+
+/// The outcome of a single health check.
+#[derive(Debug, Default, PartialEq)]
+struct Report {
+    /// The component whose event handling has stalled, and for how long.
+    stalled: Option<(&'static str, Duration)>,
+    /// Components whose events waited longer than the stall timeout, with the longest wait.
+    lagging: Vec<(&'static str, Duration)>,
+    /// Queues which grew over the configured number of checks, with their depth.
+    growing: Vec<(QueueKind, usize)>,
+}
+
+impl State {
+    fn check(
+        &mut self,
+        config: &Config,
+        now: Instant,
+        queue_counts: HashMap<QueueKind, usize>,
+    ) -> Report {
+        let stall_timeout = Duration::from(config.stall_timeout);
+        let mut report = Report::default();
+
+        match self.current {
+            Some(dispatch) if now.saturating_duration_since(dispatch.started) > stall_timeout => {
+                report.stalled = Some((
+                    dispatch.component,
+                    now.saturating_duration_since(dispatch.started),
+                ));
+            }
+            _ => self.stall_reported = false,
+        }
+
+        for (component, health) in self.components.iter_mut() {
+            if health.max_queue_time > stall_timeout {
+                report.lagging.push((*component, health.max_queue_time));
+            }
+            health.max_queue_time = Duration::ZERO;
+        }
+        report.lagging.sort_unstable();
+
+        for (queue, depth) in queue_counts {
+            let growth = self.queue_growth.entry(queue).or_default();
+            if depth > growth.depth {
+                growth.consecutive += 1;
+            } else {
+                growth.consecutive = 0;
+            }
+            growth.depth = depth;
+            if growth.consecutive >= config.queue_growth_checks && depth >= MIN_REPORTED_QUEUE_DEPTH
+            {
+                report.growing.push((queue, depth));
+            }
+        }
+        report.growing.sort_unstable();
+
+        report
+    }
+
+    /// Returns the context of an incident happening now.
+    fn incident_context(&self) -> IncidentContext {
+        IncidentContext {
+            era: self.summary.current_era,
+            last_block_height: self.summary.last_block_height,
+            last_block_hash: self.summary.last_block_hash,
+            component: self.current.map(|dispatch| dispatch.component),
+        }
+    }
+
+    /// Returns a summary of the state for a crash dump.
+    fn crash_dump<'a>(&'a self, reason: &'a str, now: Instant) -> CrashDump<'a> {
+        let current_event = self.current.map(|dispatch| CrashDumpEvent {
+            component: dispatch.component,
+            event_id: dispatch.event_id,
+            millis_ago: now.saturating_duration_since(dispatch.started).as_millis() as u64,
+        });
+        let last_events = self
+            .components
+            .iter()
+            .map(|(component, health)| {
+                let event = CrashDumpEvent {
+                    component: *component,
+                    event_id: health.last_event_id,
+                    millis_ago: now
+                        .saturating_duration_since(health.last_handled)
+                        .as_millis() as u64,
+                };
+                (*component, event)
+            })
+            .collect();
+        CrashDump {
+            reason,
+            timestamp: Timestamp::now(),
+            reactor: self.reactor,
+            summary: &self.summary,
+            current_event,
+            queue_depths: self
+                .queue_counts
+                .as_ref()
+                .map(|queue_counts| queue_counts().into_iter().collect())
+                .unwrap_or_default(),
+            last_events,
+        }
+    }
+}
+
+impl Supervisor {
+    /// Starts the supervisor thread, unless the supervisor is disabled.
+    pub(crate) fn spawn(cfg: &WithDir<Config>, registry: &Registry) -> Result<Option<Self>, Error> {
+        let config = cfg.value();
+        if !config.enabled {
+            info!("reactor supervisor disabled");
+            return Ok(None);
+        }
+
+        let shared = Arc::new(Shared {
+            config: config.clone(),
+            crash_dump_path: config
+                .crash_dump_path
+                .as_ref()
+                .map(|path| cfg.with_dir(path.clone())),
+            state: Mutex::new(State::default()),
+            metrics: Metrics::new(registry)?,
+        });
+
+        let weak = Arc::downgrade(&shared);
+        if GLOBAL_SUPERVISOR.set(weak.clone()).is_err() {
+            warn!("crash dumps and incidents are already handled by another supervisor");
+        }
+        let check_interval = Duration::from(config.check_interval);
+        thread::Builder::new()
+            .name("supervisor".to_string())
+            .spawn(move || supervise(weak, check_interval))?;
+
+        Ok(Some(Supervisor { shared }))
+    }
+
+    /// Sets the function used to determine the depth of the event queues.
+    ///
+    /// Called whenever a new runner starts, replacing the previous runner's queues.
+    pub(super) fn watch_queues(&self, reactor: &'static str, queue_counts: QueueCounts) {
+        let mut state = self.lock();
+        state.reactor = reactor;
+        state.queue_counts = Some(queue_counts);
+        state.queue_growth.clear();
+    }
+
+    /// Updates the reactor's summary included in crash dumps.
+    pub(super) fn update_summary(&self, summary: ReactorSummary) {
+        self.lock().summary = summary;
+    }
+
+    /// Records that `component` started handling the event with the given ID, which waited
+    /// `queue_time` in the queue.
+    pub(super) fn dispatch_started(
+        &self,
+        component: &'static str,
+        event_id: u64,
+        queue_time: Duration,
+    ) {
+        let now = Instant::now();
+        let mut state = self.lock();
+        state.current = Some(Dispatch {
+            component,
+            event_id,
+            started: now,
+        });
+        let health = state
+            .components
+            .entry(component)
+            .or_insert(ComponentHealth {
+                last_handled: now,
+                last_event_id: event_id,
+                max_queue_time: Duration::ZERO,
+            });
+        health.last_event_id = event_id;
+        health.max_queue_time = health.max_queue_time.max(queue_time);
+    }
+
+    /// Records that the event currently being handled has been handled.
+    pub(super) fn dispatch_finished(&self) {
+        let now = Instant::now();
+        let mut state = self.lock();
+        if let Some(dispatch) = state.current.take() {
+            if let Some(health) = state.components.get_mut(dispatch.component) {
+                health.last_handled = now;
+            }
+        }
+    }
+
+    /// Writes a crash dump with the given reason, if a crash dump path is configured.
+    pub(super) fn write_crash_dump(&self, reason: &str) {
+        let path = match self.shared.crash_dump_path {
+            Some(ref path) => path,
+            None => return,
+        };
+        // The crash may have happened while the state was locked, possibly on this very thread.
+        let state = match self.shared.state.try_lock() {
+            Ok(state) => state,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                error!("could not write crash dump, supervisor state is locked");
+                return;
+            }
+        };
+        let result = serde_json::to_vec_pretty(&state.crash_dump(reason, Instant::now()))
+            .map_err(io::Error::from)
+            .and_then(|dump| fs::write(path, dump));
+        match result {
+            Ok(()) => error!(path = %path.display(), "wrote crash dump"),
+            Err(error) => error!(%error, path = %path.display(), "could not write crash dump"),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // A poisoned lock only means a panic elsewhere; the state itself remains usable.
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Returns the context of an incident happening now, as tracked by the running supervisor.
+///
+/// The context is empty if no supervisor is running or its state is locked, as the incident may
+/// have happened while it was locked on this very thread.
+pub(crate) fn incident_context() -> IncidentContext {
+    let shared = match GLOBAL_SUPERVISOR.get().and_then(Weak::upgrade) {
+        Some(shared) => shared,
+        None => return IncidentContext::default(),
+    };
+    let context = match shared.state.try_lock() {
+        Ok(state) => state.incident_context(),
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner().incident_context(),
+        Err(TryLockError::WouldBlock) => IncidentContext::default(),
+    };
+    context
+}
+
+/// Writes a crash dump with the given reason, if a supervisor with a crash dump path is running.
+///
+/// Intended to be called from the panic hook.
+pub(crate) fn write_crash_dump(reason: &str) {
+    if let Some(shared) = GLOBAL_SUPERVISOR.get().and_then(Weak::upgrade) {
+        Supervisor { shared }.write_crash_dump(reason);
+    }
+}
+
+impl Debug for Supervisor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Supervisor")
+            .field("config", &self.shared.config)
+            .finish()
+    }
+}
+
+/// Runs the health checks until all supervisor handles have been dropped.
+fn supervise(shared: Weak<Shared>, check_interval: Duration) {
+    loop {
+        thread::sleep(check_interval);
+        let shared = match shared.upgrade() {
+            Some(shared) => shared,
+            None => return,
+        };
+
+        let supervisor = Supervisor { shared };
+        let now = Instant::now();
+        let mut state = supervisor.lock();
+        let queue_counts = state
+            .queue_counts
+            .as_ref()
+            .map(|queue_counts| queue_counts())
+            .unwrap_or_default();
+        let report = state.check(&supervisor.shared.config, now, queue_counts);
+
+        for (component, max_queue_time) in &report.lagging {
+            let last_handled = state
+                .components
+                .get(component)
+                .map(|health| now.saturating_duration_since(health.last_handled));
+            warn!(
+                %component,
+                ?max_queue_time,
+                ?last_handled,
+                "component's events are waiting too long to be handled"
+            );
+        }
+        for (queue, depth) in &report.growing {
+            warn!(%queue, %depth, "event queue keeps growing");
+        }
+
+        let metrics = &supervisor.shared.metrics;
+        metrics
+            .growing_event_queues
+            .set(report.growing.len() as i64);
+        match report.stalled {
+            Some((component, duration)) => {
+                metrics.reactor_stalled.set(1);
+                if !state.stall_reported {
+                    state.stall_reported = true;
+                    metrics.reactor_stalls.inc();
+                    error!(
+                        %component,
+                        ?duration,
+                        "reactor stalled: component has not finished handling an event"
+                    );
+                    let incident = Incident::new(
+                        IncidentKind::ReactorStall,
+                        format!(
+                            "{} has not finished handling an event for {:?}",
+                            component, duration
+                        ),
+                        state.incident_context(),
+                    )
+                    .with_component(component);
+                    if supervisor.shared.config.restart_on_stall {
+                        incident::report_and_wait(incident);
+                    } else {
+                        incident::report(incident);
+                    }
+                }
+                if supervisor.shared.config.restart_on_stall {
+                    error!(%component, "exiting to allow restart after reactor stall");
+                    process::exit(ExitCode::Stalled as i32);
+                }
+            }
+            None => metrics.reactor_stalled.set(0),
+        }
+    }
+}
+
+/// Metrics for the supervisor.
+#[derive(Debug)]
+struct Metrics {
+    /// Number of times the reactor was detected as stalled.
+    reactor_stalls: IntCounter,
+    /// Whether the reactor is currently stalled.
+    reactor_stalled: IntGauge,
+    /// Number of event queues which keep growing.
+    growing_event_queues: IntGauge,
+    /// Handle to the metrics registry, in case we need to unregister.
+    registry: Registry,
+}
+
+impl Metrics {
+    fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let reactor_stalls = IntCounter::new(
+            "reactor_stalls",
+            "number of times a component took longer than the stall timeout to handle an event",
+        )?;
+        let reactor_stalled = IntGauge::new(
+            "reactor_stalled",
+            "1 if a component is currently taking longer than the stall timeout to handle an \
+             event, 0 otherwise",
+        )?;
+        let growing_event_queues = IntGauge::new(
+            "growing_event_queues",
+            "number of event queues which kept growing over the last checks",
+        )?;
+
+        registry.register(Box::new(reactor_stalls.clone()))?;
+        registry.register(Box::new(reactor_stalled.clone()))?;
+        registry.register(Box::new(growing_event_queues.clone()))?;
+
+        Ok(Metrics {
+            reactor_stalls,
+            reactor_stalled,
+            growing_event_queues,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl Drop for Metrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.reactor_stalls);
+        unregister_metric!(self.registry, self.reactor_stalled);
+        unregister_metric!(self.registry, self.growing_event_queues);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            stall_timeout: TimeDiff::from_seconds(60),
+            queue_growth_checks: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_detect_stalled_dispatch() {
+        let config = config();
+        let start = Instant::now();
+        let mut state = State {
+            current: Some(Dispatch {
+                component: "storage",
+                event_id: 1,
+                started: start,
+            }),
+            ..Default::default()
+        };
+
+        let report = state.check(&config, start + Duration::from_secs(30), HashMap::new());
+        assert_eq!(report, Report::default());
+
+        let report = state.check(&config, start + Duration::from_secs(90), HashMap::new());
+        assert_eq!(report.stalled, Some(("storage", Duration::from_secs(90))));
+    }
+
+    #[test]
+    fn should_report_lagging_components_and_growing_queues() {
+        let config = config();
+        let now = Instant::now();
+        let mut state = State::default();
+        state.components.insert(
+            "gossiper",
+            ComponentHealth {
+                last_handled: now,
+                last_event_id: 1,
+                max_queue_time: Duration::from_secs(61),
+            },
+        );
+        state.components.insert(
+            "consensus",
+            ComponentHealth {
+                last_handled: now,
+                last_event_id: 2,
+                max_queue_time: Duration::from_secs(1),
+            },
+        );
+
+        let counts = |depth| vec![(QueueKind::Regular, depth)].into_iter().collect();
+        let report = state.check(&config, now, counts(1_000));
+        assert_eq!(report.lagging, vec![("gossiper", Duration::from_secs(61))]);
+        assert!(report.growing.is_empty());
+
+        // Lag is only reported once per occurrence, growth only after consecutive checks.
+        let report = state.check(&config, now, counts(2_000));
+        assert!(report.lagging.is_empty());
+        assert_eq!(report.growing, vec![(QueueKind::Regular, 2_000)]);
+
+        let report = state.check(&config, now, counts(1_500));
+        assert!(report.growing.is_empty());
+    }
+
+    #[test]
+    fn should_summarize_state_in_crash_dump() {
+        let start = Instant::now();
+        let mut state = State {
+            current: Some(Dispatch {
+                component: "Consensus",
+                event_id: 7,
+                started: start,
+            }),
+            reactor: "participating",
+            summary: ReactorSummary {
+                current_era: Some(EraId::from(3)),
+                peer_count: Some(5),
+                last_block_height: Some(42),
+                last_block_hash: None,
+            },
+            queue_counts: Some(Box::new(|| {
+                vec![(QueueKind::Regular, 12)].into_iter().collect()
+            })),
+            ..Default::default()
+        };
+        state.components.insert(
+            "Storage",
+            ComponentHealth {
+                last_handled: start,
+                last_event_id: 6,
+                max_queue_time: Duration::ZERO,
+            },
+        );
+
+        let dump = state.crash_dump("test", start + Duration::from_secs(2));
+        let json = serde_json::to_value(&dump).unwrap();
+        assert_eq!(json["reason"], "test");
+        assert_eq!(json["reactor"], "participating");
+        assert_eq!(json["current_era"], 3);
+        assert_eq!(json["peer_count"], 5);
+        assert_eq!(json["last_block_height"], 42);
+        assert_eq!(json["current_event"]["component"], "Consensus");
+        assert_eq!(json["current_event"]["event_id"], 7);
+        assert_eq!(json["current_event"]["millis_ago"], 2_000);
+        assert_eq!(json["queue_depths"]["Regular"], 12);
+        assert_eq!(json["last_events"]["Storage"]["event_id"], 6);
+    }
+}
@@ -0,0 +1,386 @@
+//! Exporting of global state snapshots.
+//!
+//! Writes the global state under the state root hash of a single finalized block, along with the
+//! block's header, its finality signatures and the headers linking it back to the switch block of
+//! the previous era, to a snapshot file in the format described in [`crate::snapshot_import`].
+//! Other nodes can then bootstrap from that block by importing the snapshot, rather than
+//! downloading its global state from peers.
+//!
+//! All tries are read within a single read transaction, so the exported global state is consistent
+//! even if the node is running and committing new state while the snapshot is being written.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use num::rational::Ratio;
+use thiserror::Error;
+use tracing::info;
+
+use casper_execution_engine::{
+    shared::newtypes::CorrelationId,
+    storage::{error::Error as GlobalStateError, global_state::StateProvider},
+};
+use casper_hashing::Digest;
+use casper_types::EraId;
+
+use crate::{
+    components::{
+        contract_runtime::{self, ConfigError},
+        storage::{FatalStorageError, Storage},
+    },
+    reactor::participating::Config,
+    snapshot_import::{self, SnapshotHeader},
+    types::{chainspec, BlockHash, BlockHeader, Chainspec, ChainspecRawBytes},
+    utils::{Loadable, WithDir},
+};
+
+/// How many exported tries to log progress after.
+const EXPORT_PROGRESS_INTERVAL: u64 = 100_000;
+
+/// Error returned when exporting a snapshot.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// Error loading the chainspec.
+    #[error("error loading chainspec: {0}")]
+    LoadChainspec(chainspec::Error),
+
+    /// Error creating the snapshot file.
+    #[error("error creating snapshot {path}: {error}")]
+    CreateSnapshot {
+        /// The file path.
+        path: String,
+        /// The IO error.
+        error: io::Error,
+    },
+
+    /// Error writing the snapshot file.
+    #[error("error writing snapshot: {0}")]
+    WriteSnapshot(bincode::Error),
+
+    /// Error accessing storage.
+    #[error("error accessing storage: {0}")]
+    Storage(#[from] FatalStorageError),
+
+    /// Storage doesn't hold any blocks.
+    #[error("storage does not hold any blocks")]
+    NoBlocks,
+
+    /// The requested block is not stored.
+    #[error("block {block_hash} is not stored")]
+    NoSuchBlock {
+        /// The requested block hash.
+        block_hash: BlockHash,
+    },
+
+    /// No finality signatures are stored for the block.
+    #[error("no finality signatures are stored for block {block_hash}")]
+    MissingSignatures {
+        /// The hash of the block.
+        block_hash: BlockHash,
+    },
+
+    /// The switch block of the previous era is not stored.
+    #[error("the switch block preceding era {era_id} is not stored")]
+    MissingSwitchBlock {
+        /// The era of the block.
+        era_id: EraId,
+    },
+
+    /// The header of a block between the previous era's switch block and the block is not stored.
+    #[error("the block header at height {height} is not stored")]
+    MissingAncestor {
+        /// The height of the missing block header.
+        height: u64,
+    },
+
+    /// The block is not finalized by the stored finality signatures, so the snapshot would be
+    /// refused on import.
+    #[error("block {block_hash} cannot be verified: {error}")]
+    UnverifiableBlock {
+        /// The hash of the block.
+        block_hash: BlockHash,
+        /// The error verifying the snapshot header.
+        error: Box<snapshot_import::Error>,
+    },
+
+    /// No block of the latest era has sufficient stored finality signatures.
+    #[error("no block of era {era_id} has sufficient finality signatures stored")]
+    NoFinalizedBlock {
+        /// The latest era.
+        era_id: EraId,
+    },
+
+    /// Error opening global state.
+    #[error("error opening global state: {0}")]
+    OpenGlobalState(ConfigError),
+
+    /// Error accessing global state.
+    #[error("error accessing global state: {0}")]
+    GlobalState(#[from] GlobalStateError),
+
+    /// The global state under the block's state root hash is not fully stored.
+    #[error("global state is missing {missing} tries under state root hash {state_root_hash}")]
+    IncompleteGlobalState {
+        /// The state root hash of the block.
+        state_root_hash: Digest,
+        /// The number of tries found to be missing.
+        missing: usize,
+    },
+}
+
+/// Reads the headers of the switch block of the era preceding `block_header`'s era and of all
+/// blocks since, up to `block_header`'s parent, in ascending order of height.
+fn read_ancestors(
+    storage: &Storage,
+    block_header: &BlockHeader,
+) -> Result<Vec<BlockHeader>, Error> {
+    let era_id = block_header.era_id();
+    let switch_block_header = era_id
+        .checked_sub(1)
+        .map(|previous_era_id| storage.read_switch_block_header_by_era_id(previous_era_id))
+        .transpose()?
+        .flatten()
+        .ok_or(Error::MissingSwitchBlock { era_id })?;
+
+    let mut ancestors = vec![switch_block_header];
+    for height in (ancestors[0].height() + 1)..block_header.height() {
+        let ancestor = storage
+            .read_block_header_by_height(height)?
+            .ok_or(Error::MissingAncestor { height })?;
+        ancestors.push(ancestor);
+    }
+    Ok(ancestors)
+}
+
+/// Reads the snapshot header for the given block, and checks that it would pass verification on
+/// import.
+fn read_snapshot_header(
+    storage: &Storage,
+    block_header: BlockHeader,
+    finality_threshold_fraction: Ratio<u64>,
+    signing_context_activation_era: Option<EraId>,
+) -> Result<SnapshotHeader, Error> {
+    let block_hash = block_header.hash();
+    let block_signatures = storage
+        .read_block_header_and_metadata_by_height(block_header.height())?
+        .filter(|metadata| metadata.block_header.hash() == block_hash)
+        .ok_or(Error::MissingSignatures { block_hash })?
+        .block_signatures;
+    let ancestors = read_ancestors(storage, &block_header)?;
+
+    let header = SnapshotHeader {
+        block_header,
+        block_signatures,
+        ancestors,
+    };
+    snapshot_import::verify_header(
+        &header,
+        block_hash,
+        finality_threshold_fraction,
+        signing_context_activation_era,
+    )
+    .map_err(|error| Error::UnverifiableBlock {
+        block_hash,
+        error: Box::new(error),
+    })?;
+    Ok(header)
+}
+
+/// Returns the snapshot header for the highest block of the latest era which has sufficient
+/// finality signatures stored.
+fn read_latest_snapshot_header(
+    storage: &Storage,
+    finality_threshold_fraction: Ratio<u64>,
+    signing_context_activation_era: Option<EraId>,
+) -> Result<SnapshotHeader, Error> {
+    let highest_block_header = storage
+        .read_highest_block_header()?
+        .ok_or(Error::NoBlocks)?;
+    let era_id = highest_block_header.era_id();
+    let mut headers = read_ancestors(storage, &highest_block_header)?;
+    headers.push(highest_block_header);
+
+    // The most recent blocks might not have collected sufficient finality signatures yet.
+    for index in (1..headers.len()).rev() {
+        let block_header = headers[index].clone();
+        let block_hash = block_header.hash();
+        let block_signatures = match storage
+            .read_block_header_and_metadata_by_height(block_header.height())?
+            .filter(|metadata| metadata.block_header.hash() == block_hash)
+        {
+            Some(metadata) => metadata.block_signatures,
+            None => continue,
+        };
+        let header = SnapshotHeader {
+            block_header,
+            block_signatures,
+            ancestors: headers[..index].to_vec(),
+        };
+        if snapshot_import::verify_header(
+            &header,
+            block_hash,
+            finality_threshold_fraction,
+            signing_context_activation_era,
+        )
+        .is_ok()
+        {
+            return Ok(header);
+        }
+    }
+    Err(Error::NoFinalizedBlock { era_id })
+}
+
+/// Writes the snapshot header, followed by every trie under the block's state root hash.
+///
+/// Returns the number of tries written.
+fn write_snapshot<W, F>(
+    writer: &mut W,
+    header: &SnapshotHeader,
+    visit_tries: F,
+) -> Result<u64, Error>
+where
+    W: Write,
+    F: FnOnce(&mut dyn FnMut(&[u8]) -> Result<(), Error>) -> Result<u64, Error>,
+{
+    bincode::serialize_into(&mut *writer, header).map_err(Error::WriteSnapshot)?;
+    let mut written = 0;
+    let trie_count = visit_tries(&mut |trie_bytes| {
+        bincode::serialize_into(&mut *writer, trie_bytes).map_err(Error::WriteSnapshot)?;
+        written += 1;
+        if written % EXPORT_PROGRESS_INTERVAL == 0 {
+            info!(trie_count = written, "exporting snapshot global state");
+        }
+        Ok(())
+    })?;
+    writer
+        .flush()
+        .map_err(|error| Error::WriteSnapshot(error.into()))?;
+    Ok(trie_count)
+}
+
+/// Exports a snapshot of the node's global state at the block with hash `maybe_block_hash` or, if
+/// not given, at the highest block of the latest era with sufficient finality signatures, to a new
+/// file at `snapshot_path`.
+///
+/// Returns the hash of the block the snapshot was taken at and the number of tries exported.
+pub(crate) fn export_snapshot(
+    config: WithDir<Config>,
+    snapshot_path: &Path,
+    maybe_block_hash: Option<BlockHash>,
+) -> Result<(BlockHash, u64), Error> {
+    let (root, config) = config.into_parts();
+    let chainspec = <(Chainspec, ChainspecRawBytes)>::from_path(&root)
+        .map_err(Error::LoadChainspec)?
+        .0;
+    let finality_threshold_fraction = chainspec.highway_config.finality_threshold_fraction;
+    let signing_context_activation_era = chainspec.core_config.signing_context_activation_era;
+
+    let storage = Storage::new(
+        &WithDir::new(&root, config.storage.clone()),
+        None,
+        chainspec.protocol_config.version,
+        &chainspec.network_config.name,
+    )?;
+    let header = match maybe_block_hash {
+        Some(block_hash) => {
+            let block_header = storage
+                .read_block_header_by_hash(&block_hash)?
+                .ok_or(Error::NoSuchBlock { block_hash })?;
+            read_snapshot_header(
+                &storage,
+                block_header,
+                finality_threshold_fraction,
+                signing_context_activation_era,
+            )?
+        }
+        None => read_latest_snapshot_header(
+            &storage,
+            finality_threshold_fraction,
+            signing_context_activation_era,
+        )?,
+    };
+    let block_hash = header.block_header.hash();
+    info!(%block_hash, height = header.block_header.height(), "exporting snapshot of block");
+
+    let global_state =
+        contract_runtime::open_global_state(storage.global_state_path(), &config.contract_runtime)
+            .map_err(Error::OpenGlobalState)?;
+    let state_root_hash = *header.block_header.state_root_hash();
+    let missing = global_state
+        .missing_trie_keys(CorrelationId::new(), vec![state_root_hash])?
+        .len();
+    if missing != 0 {
+        return Err(Error::IncompleteGlobalState {
+            state_root_hash,
+            missing,
+        });
+    }
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(snapshot_path)
+        .map_err(|error| Error::CreateSnapshot {
+            path: snapshot_path.display().to_string(),
+            error,
+        })?;
+    let result = write_snapshot(&mut BufWriter::new(file), &header, |visit| {
+        global_state.visit_tries(state_root_hash, visit)
+    });
+    let trie_count = match result {
+        Ok(trie_count) => trie_count,
+        Err(error) => {
+            // Don't leave a truncated snapshot behind.
+            let _ = fs::remove_file(snapshot_path);
+            return Err(error);
+        }
+    };
+    info!(trie_count, %state_root_hash, "exported snapshot global state");
+
+    Ok((block_hash, trie_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Cursor};
+
+    use casper_types::testing::TestRng;
+
+    use super::*;
+    use crate::types::{Block, BlockSignatures};
+
+    #[test]
+    fn should_write_snapshot_readable_on_import() {
+        let mut rng = TestRng::new();
+        let block = Block::random(&mut rng);
+        let header = SnapshotHeader {
+            block_header: block.header().clone(),
+            block_signatures: BlockSignatures::new(*block.hash(), block.header().era_id()),
+            ancestors: vec![Block::random(&mut rng).take_header()],
+        };
+        let tries = vec![vec![1, 2, 3], vec![], vec![4; 1000]];
+
+        let mut snapshot = vec![];
+        let trie_count = write_snapshot(&mut snapshot, &header, |visit| {
+            for trie in &tries {
+                visit(trie.as_slice())?;
+            }
+            Ok(tries.len() as u64)
+        })
+        .unwrap();
+        assert_eq!(trie_count, 3);
+
+        let mut reader = BufReader::new(Cursor::new(snapshot));
+        let read_header: SnapshotHeader = bincode::deserialize_from(&mut reader).unwrap();
+        assert_eq!(read_header.block_header, header.block_header);
+        assert_eq!(read_header.ancestors, header.ancestors);
+        for trie in &tries {
+            let read_trie: Vec<u8> = bincode::deserialize_from(&mut reader).unwrap();
+            assert_eq!(&read_trie, trie);
+        }
+        assert!(reader.fill_buf().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,443 @@
+//! Importing of global state snapshots.
+//!
+//! A snapshot holds the global state under the state root hash of a single block, along with the
+//! block's header, its finality signatures and the headers linking it back to the switch block of
+//! the previous era.  Importing one lets a new node join the network from that block without
+//! downloading its global state from peers: during joining, tries already present locally are not
+//! fetched again.
+//!
+//! A snapshot file consists of a bincode-encoded [`SnapshotHeader`], followed by the serialized
+//! tries of global state, each bincode-encoded as a byte vector.  The tries may appear in any
+//! order.  Snapshots are written by [`crate::snapshot_export`].
+//!
+//! Tries are not stored under hashes given by the snapshot, but under the hashes of their contents,
+//! so a snapshot whose tries don't hash to the block's state root hash is refused.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use num::rational::Ratio;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use casper_execution_engine::{
+    shared::newtypes::CorrelationId,
+    storage::{error::Error as GlobalStateError, global_state::StateProvider},
+};
+use casper_hashing::Digest;
+use casper_types::{crypto, EraId};
+
+use crate::{
+    components::{
+        contract_runtime::{self, ConfigError},
+        linear_chain::{self, BlockSignatureError},
+        storage::{FatalStorageError, Storage},
+    },
+    reactor::participating::Config,
+    types::{chainspec, BlockHash, BlockHeader, BlockSignatures, Chainspec, ChainspecRawBytes},
+    utils::{Loadable, WithDir},
+};
+
+/// How many imported tries to log progress after.
+const IMPORT_PROGRESS_INTERVAL: u64 = 100_000;
+
+/// How many tries to write to global state within a single transaction.
+const IMPORT_BATCH_SIZE: usize = 1_000;
+
+/// Error returned when importing a snapshot.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// Error loading the chainspec.
+    #[error("error loading chainspec: {0}")]
+    LoadChainspec(chainspec::Error),
+
+    /// No trusted hash was given.
+    #[error("a trusted hash is required to import a snapshot")]
+    NoTrustedHash,
+
+    /// Error opening the snapshot file.
+    #[error("error opening snapshot {path}: {error}")]
+    OpenSnapshot {
+        /// The file path.
+        path: String,
+        /// The IO error.
+        error: io::Error,
+    },
+
+    /// Error reading the snapshot file.
+    #[error("error reading snapshot: {0}")]
+    ReadSnapshot(bincode::Error),
+
+    /// The snapshot is not of the trusted block.
+    #[error("snapshot is of block {block_hash}, but the trusted hash is {trusted_hash}")]
+    UntrustedBlock {
+        /// The trusted block hash.
+        trusted_hash: BlockHash,
+        /// The hash of the block in the snapshot.
+        block_hash: BlockHash,
+    },
+
+    /// The snapshot doesn't start its ancestors with the switch block of the previous era.
+    #[error("snapshot does not include the switch block preceding era {era_id}")]
+    MissingSwitchBlock {
+        /// The era of the block in the snapshot.
+        era_id: EraId,
+    },
+
+    /// The parent hash of a block header doesn't match the hash of the preceding header.
+    #[error("parent of block {block_hash} is missing from snapshot")]
+    BrokenAncestry {
+        /// The block whose parent is missing.
+        block_hash: BlockHash,
+    },
+
+    /// The finality signatures are not for the block in the snapshot.
+    #[error("finality signatures are not for block {block_hash} in era {era_id}")]
+    SignaturesForWrongBlock {
+        /// The hash of the block in the snapshot.
+        block_hash: BlockHash,
+        /// The era of the block in the snapshot.
+        era_id: EraId,
+    },
+
+    /// A finality signature is invalid.
+    #[error("invalid finality signature: {0}")]
+    InvalidSignature(crypto::Error),
+
+    /// The finality signatures are insufficient.
+    #[error(transparent)]
+    InsufficientSignatures(Box<BlockSignatureError>),
+
+    /// Error opening storage.
+    #[error("error opening storage: {0}")]
+    Storage(FatalStorageError),
+
+    /// Error opening global state.
+    #[error("error opening global state: {0}")]
+    OpenGlobalState(ConfigError),
+
+    /// Error accessing global state.
+    #[error("error accessing global state: {0}")]
+    GlobalState(GlobalStateError),
+
+    /// Error flushing global state to disk.
+    #[error("error flushing global state: {0}")]
+    FlushGlobalState(lmdb::Error),
+
+    /// None of the snapshot's tries hashes to the block's state root hash.
+    #[error("snapshot does not contain the root trie for state root hash {state_root_hash}")]
+    StateRootHashMismatch {
+        /// The state root hash of the block in the snapshot.
+        state_root_hash: Digest,
+    },
+
+    /// The snapshot doesn't hold the complete global state under the block's state root hash.
+    #[error("snapshot is missing {missing} tries under state root hash {state_root_hash}")]
+    IncompleteGlobalState {
+        /// The state root hash of the block in the snapshot.
+        state_root_hash: Digest,
+        /// The number of tries found to be missing.
+        missing: usize,
+    },
+}
+
+/// The part of a snapshot preceding its tries.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SnapshotHeader {
+    /// The header of the block whose global state the snapshot holds.
+    pub(crate) block_header: BlockHeader,
+    /// The block's finality signatures.
+    pub(crate) block_signatures: BlockSignatures,
+    /// The headers of the switch block of the previous era and of all blocks since, up to the
+    /// block's parent, in ascending order of height.
+    pub(crate) ancestors: Vec<BlockHeader>,
+}
+
+/// Checks that the snapshot is of the trusted block, and that the block's finality signatures are
+/// valid and have sufficient weight among the validators set in the previous era's switch block.
+///
+/// From `signing_context_activation_era` on, the signatures must be made in the finality signature
+/// signing context.
+pub(crate) fn verify_header(
+    header: &SnapshotHeader,
+    trusted_hash: BlockHash,
+    finality_threshold_fraction: Ratio<u64>,
+    signing_context_activation_era: Option<EraId>,
+) -> Result<(), Error> {
+    let block_hash = header.block_header.hash();
+    if block_hash != trusted_hash {
+        return Err(Error::UntrustedBlock {
+            trusted_hash,
+            block_hash,
+        });
+    }
+
+    let era_id = header.block_header.era_id();
+    let validator_weights = header
+        .ancestors
+        .first()
+        .filter(|switch_block_header| switch_block_header.era_id().successor() == era_id)
+        .and_then(BlockHeader::next_era_validator_weights)
+        .ok_or(Error::MissingSwitchBlock { era_id })?;
+
+    let mut child = &header.block_header;
+    for parent in header.ancestors.iter().rev() {
+        if *child.parent_hash() != parent.hash() {
+            return Err(Error::BrokenAncestry {
+                block_hash: child.hash(),
+            });
+        }
+        child = parent;
+    }
+
+    let signatures = &header.block_signatures;
+    if signatures.block_hash != block_hash || signatures.era_id != era_id {
+        return Err(Error::SignaturesForWrongBlock { block_hash, era_id });
+    }
+    let signing_context_active =
+        signing_context_activation_era.map_or(false, |activation_era| era_id >= activation_era);
+    signatures
+        .verify(signing_context_active)
+        .map_err(Error::InvalidSignature)?;
+    linear_chain::check_sufficient_block_signatures(
+        validator_weights,
+        finality_threshold_fraction,
+        Some(signatures),
+    )
+    .map_err(|error| Error::InsufficientSignatures(Box::new(error)))
+}
+
+/// Reads the tries following the snapshot header, passing them to `put_tries` in batches of
+/// `IMPORT_BATCH_SIZE`, and returns how many were read.
+///
+/// Returns an error if none of the tries hashes to `state_root_hash`.
+fn import_tries<R, F>(
+    reader: &mut R,
+    state_root_hash: Digest,
+    mut put_tries: F,
+) -> Result<u64, Error>
+where
+    R: BufRead,
+    F: FnMut(&[Vec<u8>]) -> Result<Vec<Digest>, GlobalStateError>,
+{
+    let mut trie_count = 0;
+    let mut found_root = false;
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    loop {
+        let at_end = reader
+            .fill_buf()
+            .map_err(|error| Error::ReadSnapshot(error.into()))?
+            .is_empty();
+        if !at_end {
+            let trie_bytes: Vec<u8> =
+                bincode::deserialize_from(&mut *reader).map_err(Error::ReadSnapshot)?;
+            batch.push(trie_bytes);
+        }
+        if batch.len() == IMPORT_BATCH_SIZE || (at_end && !batch.is_empty()) {
+            let trie_hashes = put_tries(&batch).map_err(Error::GlobalState)?;
+            found_root |= trie_hashes.contains(&state_root_hash);
+            let previous_count = trie_count;
+            trie_count += batch.len() as u64;
+            if trie_count / IMPORT_PROGRESS_INTERVAL != previous_count / IMPORT_PROGRESS_INTERVAL {
+                info!(trie_count, "importing snapshot global state");
+            }
+            batch.clear();
+        }
+        if at_end {
+            break;
+        }
+    }
+    if !found_root {
+        return Err(Error::StateRootHashMismatch { state_root_hash });
+    }
+    Ok(trie_count)
+}
+
+/// Imports the snapshot at `snapshot_path` into the node's global state, after verifying it
+/// against `maybe_trusted_hash` or, if not given, the config's `node.trusted_hash`.
+///
+/// Returns the hash of the block the snapshot was taken at.
+pub(crate) fn import_snapshot(
+    config: WithDir<Config>,
+    snapshot_path: &Path,
+    maybe_trusted_hash: Option<BlockHash>,
+) -> Result<BlockHash, Error> {
+    let (root, config) = config.into_parts();
+    let chainspec = <(Chainspec, ChainspecRawBytes)>::from_path(&root)
+        .map_err(Error::LoadChainspec)?
+        .0;
+    let trusted_hash = maybe_trusted_hash
+        .or(config.node.trusted_hash)
+        .ok_or(Error::NoTrustedHash)?;
+
+    let file = File::open(snapshot_path).map_err(|error| Error::OpenSnapshot {
+        path: snapshot_path.display().to_string(),
+        error,
+    })?;
+    let mut reader = BufReader::new(file);
+    let header: SnapshotHeader =
+        bincode::deserialize_from(&mut reader).map_err(Error::ReadSnapshot)?;
+    verify_header(
+        &header,
+        trusted_hash,
+        chainspec.highway_config.finality_threshold_fraction,
+        chainspec.core_config.signing_context_activation_era,
+    )?;
+    info!(%trusted_hash, "verified snapshot block");
+
+    let storage = Storage::new(
+        &WithDir::new(&root, config.storage.clone()),
+        None,
+        chainspec.protocol_config.version,
+        &chainspec.network_config.name,
+    )
+    .map_err(Error::Storage)?;
+    let global_state =
+        contract_runtime::open_global_state(storage.global_state_path(), &config.contract_runtime)
+            .map_err(Error::OpenGlobalState)?;
+
+    let state_root_hash = *header.block_header.state_root_hash();
+    let trie_count = import_tries(&mut reader, state_root_hash, |tries| {
+        global_state.put_tries(CorrelationId::new(), tries)
+    })?;
+    global_state
+        .environment()
+        .sync()
+        .map_err(Error::FlushGlobalState)?;
+
+    let missing = global_state
+        .missing_trie_keys(CorrelationId::new(), vec![state_root_hash])
+        .map_err(Error::GlobalState)?
+        .len();
+    if missing != 0 {
+        return Err(Error::IncompleteGlobalState {
+            state_root_hash,
+            missing,
+        });
+    }
+    info!(trie_count, %state_root_hash, "imported snapshot global state");
+
+    Ok(trusted_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, io::Cursor};
+
+    use casper_types::{testing::TestRng, PublicKey, SecretKey, U512};
+
+    use super::*;
+    use crate::types::{Block, FinalitySignature, FinalizedBlock};
+
+    /// Returns a snapshot header for a block in era 1, signed by the only validator.
+    fn signed_snapshot_header(rng: &mut TestRng) -> SnapshotHeader {
+        let secret_key = SecretKey::random(rng);
+        let public_key = PublicKey::from(&secret_key);
+        let protocol_version = Default::default();
+
+        let switch_block = Block::new(
+            BlockHash::random(rng),
+            Digest::hash(b"parent seed"),
+            Digest::hash(b"switch block state"),
+            FinalizedBlock::random_with_specifics(rng, EraId::new(0), 10, true, None),
+            Some(BTreeMap::from([(public_key.clone(), U512::from(100))])),
+            protocol_version,
+        )
+        .unwrap();
+        let block = Block::new(
+            *switch_block.hash(),
+            Digest::hash(b"parent seed"),
+            Digest::hash(b"block state"),
+            FinalizedBlock::random_with_specifics(rng, EraId::new(1), 11, false, None),
+            None,
+            protocol_version,
+        )
+        .unwrap();
+
+        let signature = FinalitySignature::new(
+            *block.hash(),
+            EraId::new(1),
+            &secret_key,
+            public_key.clone(),
+            true,
+        );
+        let mut block_signatures = BlockSignatures::new(*block.hash(), EraId::new(1));
+        block_signatures.insert_proof(public_key, signature.signature);
+
+        SnapshotHeader {
+            block_header: block.take_header(),
+            block_signatures,
+            ancestors: vec![switch_block.take_header()],
+        }
+    }
+
+    #[test]
+    fn should_verify_snapshot_header() {
+        let mut rng = TestRng::new();
+        let header = signed_snapshot_header(&mut rng);
+        let trusted_hash = header.block_header.hash();
+        let fraction = Ratio::new(1, 3);
+        let activation_era = Some(EraId::new(0));
+
+        verify_header(&header, trusted_hash, fraction, activation_era).unwrap();
+
+        let untrusted_hash = BlockHash::random(&mut rng);
+        assert!(matches!(
+            verify_header(&header, untrusted_hash, fraction, activation_era),
+            Err(Error::UntrustedBlock { .. })
+        ));
+
+        let mut unsigned = signed_snapshot_header(&mut rng);
+        unsigned.block_signatures.proofs.clear();
+        let unsigned_hash = unsigned.block_header.hash();
+        assert!(matches!(
+            verify_header(&unsigned, unsigned_hash, fraction, activation_era),
+            Err(Error::InsufficientSignatures(_))
+        ));
+
+        let mut unlinked = signed_snapshot_header(&mut rng);
+        unlinked.ancestors = header.ancestors;
+        let unlinked_hash = unlinked.block_header.hash();
+        assert!(matches!(
+            verify_header(&unlinked, unlinked_hash, fraction, activation_era),
+            Err(Error::BrokenAncestry { .. })
+        ));
+    }
+
+    #[test]
+    fn should_read_all_tries() {
+        let tries = vec![vec![1, 2, 3], vec![], vec![4; 1000]];
+        let mut snapshot = vec![];
+        for trie in &tries {
+            bincode::serialize_into(&mut snapshot, trie).unwrap();
+        }
+
+        let put_tries = |imported: &mut Vec<Vec<u8>>, tries: &[Vec<u8>]| {
+            imported.extend_from_slice(tries);
+            Ok::<_, GlobalStateError>(tries.iter().map(Digest::hash).collect())
+        };
+
+        let mut imported = vec![];
+        let state_root_hash = Digest::hash(&tries[2]);
+        let trie_count = import_tries(
+            &mut Cursor::new(snapshot.clone()),
+            state_root_hash,
+            |batch| put_tries(&mut imported, batch),
+        )
+        .unwrap();
+        assert_eq!(trie_count, 3);
+        assert_eq!(imported, tries);
+
+        let unrelated_hash = Digest::hash(b"unrelated state");
+        assert!(matches!(
+            import_tries(&mut Cursor::new(snapshot), unrelated_hash, |batch| {
+                put_tries(&mut vec![], batch)
+            }),
+            Err(Error::StateRootHashMismatch { .. })
+        ));
+    }
+}
@@ -4,10 +4,12 @@
 //! `casper-node` library.
 
 mod condition_check_reactor;
+pub(crate) mod deterministic_runner;
 pub(crate) mod fake_deploy_acceptor;
 pub(crate) mod filter_reactor;
 mod multi_stage_test_reactor;
 pub(crate) mod network;
+pub(crate) mod network_faults;
 pub(crate) mod test_clock;
 mod test_rng;
 
@@ -34,6 +36,10 @@ use tempfile::TempDir;
 use tokio::runtime::{self, Runtime};
 use tracing::{debug, warn};
 
+use casper_execution_engine::core::{
+    engine_state::{self, BalanceRequest, BalanceResult, QueryRequest, QueryResult},
+    validate_balance_proof, validate_query_proof,
+};
 use casper_types::{TimeDiff, Timestamp};
 
 use crate::{
@@ -272,7 +278,7 @@ impl<REv: 'static> ComponentHarness<REv> {
 
             // Iterate over all events that currently are inside the queue and fish out any fatal.
             for _ in 0..(self.scheduler.item_count()) {
-                let ((_ancestor, ev), _queue_kind) = self.runtime.block_on(self.scheduler.pop());
+                let ((_metadata, ev), _queue_kind) = self.runtime.block_on(self.scheduler.pop());
 
                 if let Some(ctrl_ann) = ev.as_control() {
                     match ctrl_ann {
@@ -282,7 +288,9 @@ impl<REv: 'static> ComponentHarness<REv> {
                                 fatal
                             )
                         }
-                        ControlAnnouncement::QueueDumpRequest { .. } => {
+                        ControlAnnouncement::QueueDumpRequest { .. }
+                        | ControlAnnouncement::QueueSummaryRequest { .. }
+                        | ControlAnnouncement::MemoryDumpRequest { .. } => {
                             panic!("queue dumps are not supported in the test harness")
                         }
                     }
@@ -384,6 +392,48 @@ pub(crate) fn create_not_expired_deploy(now: Timestamp, test_rng: &mut TestRng)
     )
 }
 
+/// Asserts that the Merkle proofs of a successful query result prove its value to be the result of
+/// the query under the queried state root hash.
+pub(crate) fn assert_valid_query_proofs(
+    query_request: &QueryRequest,
+    result: &Result<QueryResult, engine_state::Error>,
+) {
+    if let Ok(QueryResult::Success { value, proofs }) = result {
+        if let Err(error) = validate_query_proof(
+            &query_request.state_hash(),
+            proofs,
+            &query_request.key(),
+            query_request.path(),
+            value,
+        ) {
+            panic!("invalid proofs of {:?}: {}", query_request, error);
+        }
+    }
+}
+
+/// Asserts that the Merkle proof of a successful balance result proves the balance of the purse
+/// under the requested state root hash.
+pub(crate) fn assert_valid_balance_proof(
+    balance_request: &BalanceRequest,
+    result: &Result<BalanceResult, engine_state::Error>,
+) {
+    if let Ok(BalanceResult::Success { motes, proof }) = result {
+        if let Err(error) = validate_balance_proof(
+            &balance_request.state_hash(),
+            proof,
+            balance_request.purse_uref().into(),
+            motes,
+        ) {
+            panic!(
+                "invalid proof of balance of {} under state root hash {}: {}",
+                balance_request.purse_uref(),
+                balance_request.state_hash(),
+                error
+            );
+        }
+    }
+}
+
 /// Assert that the file at `schema_path` matches the provided `RootSchema`, which can be derived
 /// from `schemars::schema_for!` or `schemars::schema_for_value!`, for example. This method will
 /// create a temporary file with the actual schema and print the location if it fails.
@@ -0,0 +1,373 @@
+//! Deterministic, step-by-step execution of a reactor against a virtual clock.
+//!
+//! The [`DeterministicRunner`] drives a single reactor on a single-threaded runtime whose clock is
+//! paused: time only passes when the test advances it. This allows testing timer-driven behavior
+//! such as gossip intervals or fetch timeouts event by event, without sleeping.
+//!
+//! # Limitations
+//!
+//! Timers based on the `tokio` clock (e.g. `EffectBuilder::set_timeout`) follow the virtual clock,
+//! and so does `Timestamp::now`, which is pinned to a fixed start time plus the virtual time
+//! passed. Code reading the wall clock in other ways, e.g. through `SystemTime::now`, or from other
+//! threads, such as blocking tasks, does not.
+//!
+//! Effects waiting on anything other than the event queue or the clock, such as blocking tasks,
+//! may not have completed when the runner considers the reactor idle.
+
+use std::time::Duration;
+
+use casper_types::{
+    testing::{PinnedClock, TestRng},
+    TimeDiff, Timestamp,
+};
+use serde::Serialize;
+use tokio::{
+    runtime::{self, Runtime},
+    task,
+    time::{self, Instant},
+};
+
+use crate::{
+    effect::{EffectBuilder, Effects},
+    reactor::{Reactor, Runner},
+};
+
+/// The default granularity with which the virtual clock is advanced.
+const DEFAULT_TICK: Duration = Duration::from_millis(1);
+
+/// The timestamp returned by `Timestamp::now` when the runner is created, in milliseconds since the
+/// Unix epoch.
+const START_TIMESTAMP_MILLIS: u64 = 1_600_000_000_000;
+
+/// The number of consecutive polls without any event on the queue after which the reactor is
+/// considered idle.
+const IDLE_POLLS: usize = 3;
+
+/// A runner executing a reactor deterministically against a virtual clock.
+pub(crate) struct DeterministicRunner<R: Reactor> {
+    /// The single-threaded runtime with a paused clock executing all effects.
+    runtime: Runtime,
+    /// The runner of the reactor under test.
+    runner: Runner<R>,
+    /// The random number generator passed to the reactor.
+    rng: TestRng,
+    /// The virtual time at which the runner was created.
+    start: Instant,
+    /// The granularity with which the virtual clock is advanced.
+    tick: Duration,
+    /// The clock pinning `Timestamp::now` to the virtual time.
+    clock: PinnedClock,
+}
+
+impl<R> DeterministicRunner<R>
+where
+    R: Reactor,
+    R::Event: Serialize,
+    R::Error: From<prometheus::Error>,
+{
+    /// Creates a new reactor from the given configuration, processing none of its events yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be built.
+    pub(crate) fn new(cfg: R::Config, mut rng: TestRng) -> Result<Self, R::Error> {
+        let runtime = runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .expect("failed to build runtime");
+        let clock = PinnedClock::pin(Timestamp::from(START_TIMESTAMP_MILLIS));
+        let runner = runtime.block_on(Runner::new(cfg, &mut rng))?;
+        let start = runtime.block_on(async { Instant::now() });
+
+        Ok(DeterministicRunner {
+            runtime,
+            runner,
+            rng,
+            start,
+            tick: DEFAULT_TICK,
+            clock,
+        })
+    }
+
+    /// Sets the granularity with which the virtual clock is advanced.
+    ///
+    /// Timers firing within the same tick are processed together, so a coarser tick speeds up
+    /// tests at the cost of precision.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tick` is zero.
+    pub(crate) fn set_tick(&mut self, tick: Duration) {
+        assert!(!tick.is_zero(), "tick must not be zero");
+        self.tick = tick;
+    }
+
+    /// Returns a reference to the reactor.
+    pub(crate) fn reactor(&self) -> &R {
+        self.runner.reactor()
+    }
+
+    /// Returns a mutable reference to the reactor.
+    pub(crate) fn reactor_mut(&mut self) -> &mut R {
+        self.runner.reactor_mut()
+    }
+
+    /// Returns the virtual time passed since the runner was created.
+    pub(crate) fn elapsed(&self) -> Duration {
+        let start = self.start;
+        self.runtime.block_on(async move { start.elapsed() })
+    }
+
+    /// Returns the current virtual time as returned by `Timestamp::now`.
+    pub(crate) fn now(&self) -> Timestamp {
+        self.clock.now()
+    }
+
+    /// Advances the virtual clock by `tick` without processing any events.
+    fn advance_clock(&mut self, tick: Duration) {
+        self.runtime.block_on(time::advance(tick));
+        let now = Timestamp::from(START_TIMESTAMP_MILLIS) + TimeDiff::from(self.elapsed());
+        self.clock.set(now);
+    }
+
+    /// Injects effects created via a call to `create_effects`, without processing their events.
+    pub(crate) fn inject<F>(&mut self, create_effects: F)
+    where
+        F: FnOnce(EffectBuilder<R::Event>) -> Effects<R::Event>,
+    {
+        self.runtime
+            .block_on(self.runner.process_injected_effects(create_effects))
+    }
+
+    /// Processes a single event, if there is one.
+    ///
+    /// Returns whether an event was processed. The virtual clock does not advance.
+    pub(crate) fn step(&mut self) -> bool {
+        let DeterministicRunner {
+            runtime,
+            runner,
+            rng,
+            ..
+        } = self;
+        runtime.block_on(async move {
+            // Give the effects spawned so far a chance to schedule their events.
+            task::yield_now().await;
+            runner.try_crank(rng).await.is_some()
+        })
+    }
+
+    /// Processes events until there are none left without advancing the virtual clock.
+    ///
+    /// Returns the number of events processed.
+    pub(crate) fn run_until_idle(&mut self) -> usize {
+        let mut processed = 0;
+        let mut idle_polls = 0;
+        while idle_polls < IDLE_POLLS {
+            if self.step() {
+                processed += 1;
+                idle_polls = 0;
+            } else {
+                idle_polls += 1;
+            }
+        }
+        processed
+    }
+
+    /// Advances the virtual clock by `duration`, processing all events along the way.
+    ///
+    /// Returns the number of events processed.
+    pub(crate) fn advance(&mut self, duration: Duration) -> usize {
+        let mut processed = self.run_until_idle();
+        let mut remaining = duration;
+        while !remaining.is_zero() {
+            let tick = remaining.min(self.tick);
+            self.advance_clock(tick);
+            remaining -= tick;
+            processed += self.run_until_idle();
+        }
+        processed
+    }
+
+    /// Advances the virtual clock until `condition` holds for the reactor.
+    ///
+    /// Returns the virtual time it took.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the condition does not hold within the given virtual time.
+    pub(crate) fn advance_until<F>(&mut self, condition: F, within: Duration) -> Duration
+    where
+        F: Fn(&R) -> bool,
+    {
+        let start = self.elapsed();
+        loop {
+            self.run_until_idle();
+            let waited = self.elapsed() - start;
+            if condition(self.reactor()) {
+                return waited;
+            }
+            if waited >= within {
+                panic!("reactor did not meet condition within {:?}", within);
+            }
+            self.advance_clock(self.tick);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{self, Display, Formatter};
+
+    use derive_more::From;
+    use prometheus::Registry;
+
+    use super::*;
+    use crate::{
+        effect::{announcements::ControlAnnouncement, EffectExt},
+        reactor::{EventQueueHandle, ReactorEvent, ReactorExit},
+        NodeRng,
+    };
+
+    const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// A reactor counting the ticks of a periodic timer.
+    #[derive(Debug)]
+    struct Ticker {
+        ticks: u32,
+    }
+
+    #[derive(Debug, From, Serialize)]
+    enum Event {
+        Tick,
+        #[from]
+        ControlAnnouncement(ControlAnnouncement),
+    }
+
+    impl Display for Event {
+        fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+            match self {
+                Event::Tick => write!(formatter, "tick"),
+                Event::ControlAnnouncement(ctrl_ann) => write!(formatter, "control: {}", ctrl_ann),
+            }
+        }
+    }
+
+    impl ReactorEvent for Event {
+        fn as_control(&self) -> Option<&ControlAnnouncement> {
+            if let Self::ControlAnnouncement(ref ctrl_ann) = self {
+                Some(ctrl_ann)
+            } else {
+                None
+            }
+        }
+
+        fn try_into_control(self) -> Option<ControlAnnouncement> {
+            if let Self::ControlAnnouncement(ctrl_ann) = self {
+                Some(ctrl_ann)
+            } else {
+                None
+            }
+        }
+    }
+
+    fn schedule_tick(effect_builder: EffectBuilder<Event>) -> Effects<Event> {
+        effect_builder
+            .set_timeout(TICK_INTERVAL)
+            .event(|_| Event::Tick)
+    }
+
+    impl Reactor for Ticker {
+        type Event = Event;
+        type Config = ();
+        type Error = prometheus::Error;
+
+        fn new(
+            _cfg: Self::Config,
+            _registry: &Registry,
+            event_queue: EventQueueHandle<Self::Event>,
+            _rng: &mut NodeRng,
+        ) -> Result<(Self, Effects<Self::Event>), Self::Error> {
+            let effects = schedule_tick(EffectBuilder::new(event_queue));
+            Ok((Ticker { ticks: 0 }, effects))
+        }
+
+        fn dispatch_event(
+            &mut self,
+            effect_builder: EffectBuilder<Self::Event>,
+            _rng: &mut NodeRng,
+            event: Self::Event,
+        ) -> Effects<Self::Event> {
+            match event {
+                Event::Tick => {
+                    self.ticks += 1;
+                    schedule_tick(effect_builder)
+                }
+                Event::ControlAnnouncement(ctrl_ann) => {
+                    panic!("unexpected control announcement: {}", ctrl_ann)
+                }
+            }
+        }
+
+        fn maybe_exit(&self) -> Option<ReactorExit> {
+            None
+        }
+    }
+
+    #[test]
+    fn should_only_fire_timers_when_clock_advances() {
+        let rng = crate::new_rng();
+        let mut runner = DeterministicRunner::<Ticker>::new((), rng).expect("should create");
+
+        assert_eq!(runner.run_until_idle(), 0);
+        assert!(!runner.step());
+        assert_eq!(runner.reactor().ticks, 0);
+
+        assert_eq!(runner.advance(TICK_INTERVAL - Duration::from_millis(1)), 0);
+        assert_eq!(runner.reactor().ticks, 0);
+
+        assert_eq!(runner.advance(Duration::from_millis(1)), 1);
+        assert_eq!(runner.reactor().ticks, 1);
+
+        assert_eq!(runner.advance(TICK_INTERVAL * 10), 10);
+        assert_eq!(runner.reactor().ticks, 11);
+        assert_eq!(runner.elapsed(), TICK_INTERVAL * 11);
+    }
+
+    #[test]
+    fn should_pin_timestamps_to_virtual_clock() {
+        let rng = crate::new_rng();
+        let mut runner = DeterministicRunner::<Ticker>::new((), rng).expect("should create");
+        let start = Timestamp::now();
+        assert_eq!(start, runner.now());
+
+        runner.advance(TICK_INTERVAL * 3);
+        assert_eq!(Timestamp::now(), start + TimeDiff::from(TICK_INTERVAL * 3));
+        assert_eq!(runner.now(), Timestamp::now());
+    }
+
+    #[test]
+    fn should_advance_until_condition_holds() {
+        let rng = crate::new_rng();
+        let mut runner = DeterministicRunner::<Ticker>::new((), rng).expect("should create");
+        runner.set_tick(Duration::from_millis(100));
+
+        let waited = runner.advance_until(|ticker| ticker.ticks == 3, Duration::from_secs(5));
+        assert_eq!(waited, TICK_INTERVAL * 3);
+
+        runner.reactor_mut().ticks = 0;
+        runner.inject(|effect_builder| effect_builder.immediately().event(|_| Event::Tick));
+        assert_eq!(runner.run_until_idle(), 1);
+        assert_eq!(runner.reactor().ticks, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "did not meet condition")]
+    fn should_panic_if_condition_does_not_hold_in_time() {
+        let rng = crate::new_rng();
+        let mut runner = DeterministicRunner::<Ticker>::new((), rng).expect("should create");
+        runner.set_tick(Duration::from_millis(100));
+
+        runner.advance_until(|ticker| ticker.ticks == 3, Duration::from_secs(2));
+    }
+}
@@ -58,7 +58,7 @@ impl FakeDeployAcceptor {
         maybe_responder: Option<Responder<Result<(), Error>>>,
     ) -> Effects<Event> {
         let verification_start_timestamp = Timestamp::now();
-        let event_metadata = EventMetadata::new(deploy.clone(), source, maybe_responder);
+        let event_metadata = EventMetadata::new(deploy.clone(), source, maybe_responder, false);
         effect_builder
             .put_deploy_to_storage(Box::new(*deploy))
             .event(move |is_new| Event::PutToStorageResult {
@@ -78,6 +78,7 @@ impl FakeDeployAcceptor {
             deploy,
             source,
             maybe_responder,
+            ..
         } = event_metadata;
         let mut effects = Effects::new();
         if is_new {
@@ -0,0 +1,312 @@
+//! Fault injection for networks of test reactors.
+//!
+//! A `NetworkFaults` instance describes the conditions of the links between nodes: latency,
+//! packet loss and partitions. It can be shared by an in-memory network (see
+//! `NetworkController::create_active_with_faults`) or applied to the incoming messages of
+//! networked reactors wrapped in a `FilterReactor` (see `Network::inject_faults`).
+//!
+//! All random decisions are drawn from an RNG seeded at construction, so a test run can be
+//! reproduced from the seed of its `TestRng`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use casper_types::testing::TestRng;
+use either::Either;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use tokio::time;
+use tracing::debug;
+
+use super::{
+    filter_reactor::{EventFilter, FilterReactor},
+    network::{Network, NetworkedReactor},
+};
+use crate::{
+    effect::{EffectExt, Effects},
+    reactor::Reactor,
+    types::NodeId,
+};
+
+/// An event which may be the arrival of a message from a peer.
+pub(crate) trait IncomingMessage {
+    /// Returns the sender if this event is an incoming message subject to network faults.
+    fn incoming_sender(&self) -> Option<NodeId>;
+}
+
+/// The conditions of the links between the nodes of a test network.
+///
+/// Cloning returns a handle to the same conditions, so they can be changed while a test is
+/// running.
+#[derive(Clone)]
+pub(crate) struct NetworkFaults {
+    inner: Arc<Mutex<Conditions>>,
+}
+
+struct Conditions {
+    rng: ChaCha8Rng,
+    latency: Duration,
+    jitter: Duration,
+    packet_loss: f64,
+    /// Each partition separates its nodes from all other nodes.
+    partitions: Vec<HashSet<NodeId>>,
+    dropped: u64,
+}
+
+impl NetworkFaults {
+    /// Creates flawless network conditions, with the RNG for future faults seeded from `rng`.
+    pub(crate) fn new(rng: &mut TestRng) -> Self {
+        Self::from_seed(rng.gen())
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        NetworkFaults {
+            inner: Arc::new(Mutex::new(Conditions {
+                rng: ChaCha8Rng::seed_from_u64(seed),
+                latency: Duration::ZERO,
+                jitter: Duration::ZERO,
+                packet_loss: 0.0,
+                partitions: Vec::new(),
+                dropped: 0,
+            })),
+        }
+    }
+
+    fn with_conditions<T>(&self, f: impl FnOnce(&mut Conditions) -> T) -> T {
+        f(&mut self.inner.lock().expect("network faults lock poisoned"))
+    }
+
+    /// Delays every message by `latency`, plus a uniformly random extra delay of up to `jitter`.
+    ///
+    /// A non-zero jitter can reorder the messages on a link.
+    pub(crate) fn set_latency(&self, latency: Duration, jitter: Duration) {
+        self.with_conditions(|conditions| {
+            conditions.latency = latency;
+            conditions.jitter = jitter;
+        })
+    }
+
+    /// Drops every message with the given probability.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not within `[0, 1]`.
+    pub(crate) fn set_packet_loss(&self, probability: f64) {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "packet loss probability {} out of range",
+            probability
+        );
+        self.with_conditions(|conditions| conditions.packet_loss = probability)
+    }
+
+    /// Separates the given nodes from all other nodes, dropping all messages between the two
+    /// sides.
+    ///
+    /// Partitions accumulate: two nodes can only communicate if they are on the same side of every
+    /// partition.
+    pub(crate) fn partition<I>(&self, nodes: I)
+    where
+        I: IntoIterator<Item = NodeId>,
+    {
+        let partition = nodes.into_iter().collect();
+        self.with_conditions(|conditions| conditions.partitions.push(partition))
+    }
+
+    /// Removes all partitions.
+    pub(crate) fn heal(&self) {
+        self.with_conditions(|conditions| conditions.partitions.clear())
+    }
+
+    /// Returns the number of messages dropped so far.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.with_conditions(|conditions| conditions.dropped)
+    }
+
+    /// Decides the fate of a message from `sender` to `receiver`.
+    ///
+    /// Returns the delay after which the message is to be delivered, or `None` if it is to be
+    /// dropped.
+    pub(crate) fn delivery_delay(&self, sender: NodeId, receiver: NodeId) -> Option<Duration> {
+        self.with_conditions(|conditions| {
+            let partitioned = conditions
+                .partitions
+                .iter()
+                .any(|partition| partition.contains(&sender) != partition.contains(&receiver));
+            if partitioned || conditions.rng.gen_bool(conditions.packet_loss) {
+                conditions.dropped += 1;
+                debug!(%sender, %receiver, partitioned, "network faults dropping message");
+                return None;
+            }
+            let jitter = if conditions.jitter.is_zero() {
+                Duration::ZERO
+            } else {
+                conditions.rng.gen_range(Duration::ZERO..=conditions.jitter)
+            };
+            Some(conditions.latency + jitter)
+        })
+    }
+
+    /// Returns an event filter applying these faults to the messages received by `receiver`.
+    ///
+    /// Delayed messages are dispatched again after their delay has elapsed. They are told apart
+    /// from fresh messages by counting the released messages per sender, so a fresh message
+    /// already queued when an earlier one is released may be delivered in its place.
+    pub(crate) fn incoming_filter<Ev>(&self, receiver: NodeId) -> impl EventFilter<Ev>
+    where
+        Ev: IncomingMessage + Send + 'static,
+    {
+        let faults = self.clone();
+        let released = Arc::new(Mutex::new(HashMap::<NodeId, usize>::new()));
+        move |event: Ev| {
+            let sender = match event.incoming_sender() {
+                Some(sender) => sender,
+                None => return Either::Right(event),
+            };
+            {
+                let mut released = released.lock().expect("released lock poisoned");
+                if let Some(count) = released.get_mut(&sender).filter(|count| **count > 0) {
+                    *count -= 1;
+                    return Either::Right(event);
+                }
+            }
+            match faults.delivery_delay(sender, receiver) {
+                None => Either::Left(Effects::new()),
+                Some(delay) if delay.is_zero() => Either::Right(event),
+                Some(delay) => {
+                    let released = Arc::clone(&released);
+                    Either::Left(
+                        async move {
+                            time::sleep(delay).await;
+                            *released
+                                .lock()
+                                .expect("released lock poisoned")
+                                .entry(sender)
+                                .or_default() += 1;
+                        }
+                        .event(move |_| event),
+                    )
+                }
+            }
+        }
+    }
+}
+
+impl Default for NetworkFaults {
+    fn default() -> Self {
+        Self::from_seed(0)
+    }
+}
+
+impl Debug for NetworkFaults {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.with_conditions(|conditions| {
+            formatter
+                .debug_struct("NetworkFaults")
+                .field("latency", &conditions.latency)
+                .field("jitter", &conditions.jitter)
+                .field("packet_loss", &conditions.packet_loss)
+                .field("partitions", &conditions.partitions)
+                .field("dropped", &conditions.dropped)
+                .finish()
+        })
+    }
+}
+
+impl<R> Network<FilterReactor<R>>
+where
+    R: Reactor + NetworkedReactor,
+    R::Event: IncomingMessage + Send + 'static,
+{
+    /// Applies `faults` to the messages received by every node currently in the network.
+    ///
+    /// This replaces any event filter previously set on the nodes.
+    pub(crate) fn inject_faults(&mut self, faults: &NetworkFaults) {
+        for reactor in self.reactors_mut() {
+            let node_id = reactor.node_id();
+            reactor.set_filter(faults.incoming_filter(node_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_drop_messages_across_partitions() {
+        let mut rng = crate::new_rng();
+        let nodes: Vec<NodeId> = (0..4).map(|_| NodeId::random(&mut rng)).collect();
+        let faults = NetworkFaults::new(&mut rng);
+
+        assert_eq!(
+            faults.delivery_delay(nodes[0], nodes[3]),
+            Some(Duration::ZERO)
+        );
+
+        faults.partition(vec![nodes[0], nodes[1]]);
+        faults.partition(vec![nodes[1]]);
+        assert_eq!(
+            faults.delivery_delay(nodes[2], nodes[3]),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(faults.delivery_delay(nodes[0], nodes[2]), None);
+        assert_eq!(faults.delivery_delay(nodes[3], nodes[0]), None);
+        assert_eq!(faults.delivery_delay(nodes[0], nodes[1]), None);
+        assert_eq!(faults.dropped(), 3);
+
+        faults.heal();
+        assert_eq!(
+            faults.delivery_delay(nodes[0], nodes[2]),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn should_apply_latency_and_packet_loss() {
+        let mut rng = crate::new_rng();
+        let sender = NodeId::random(&mut rng);
+        let receiver = NodeId::random(&mut rng);
+        let faults = NetworkFaults::new(&mut rng);
+
+        let latency = Duration::from_millis(100);
+        let jitter = Duration::from_millis(50);
+        faults.set_latency(latency, jitter);
+        for _ in 0..100 {
+            let delay = faults
+                .delivery_delay(sender, receiver)
+                .expect("should deliver");
+            assert!(delay >= latency && delay <= latency + jitter);
+        }
+
+        faults.set_packet_loss(1.0);
+        assert_eq!(faults.delivery_delay(sender, receiver), None);
+
+        faults.set_packet_loss(0.5);
+        let delivered = (0..1000)
+            .filter_map(|_| faults.delivery_delay(sender, receiver))
+            .count();
+        assert!(delivered > 350 && delivered < 650);
+    }
+
+    #[test]
+    fn should_be_reproducible_from_seed() {
+        let fates = |faults: NetworkFaults| {
+            let sender = NodeId::from([1; 64]);
+            let receiver = NodeId::from([2; 64]);
+            faults.set_latency(Duration::from_millis(10), Duration::from_millis(90));
+            faults.set_packet_loss(0.3);
+            (0..100)
+                .map(|_| faults.delivery_delay(sender, receiver))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            fates(NetworkFaults::from_seed(7)),
+            fates(NetworkFaults::from_seed(7))
+        );
+    }
+}
@@ -14,6 +14,9 @@
 //!   ([`create_tls_acceptor`](fn.create_tls_acceptor.html)),
 //! * construction of TLS connectors for outgoing TCP connections
 //!   ([`create_tls_connector`](fn.create_tls_connector.html)),
+//! * construction of TLS acceptors requiring clients of administrative endpoints to authenticate
+//!   with a certificate issued by a configured authority
+//!   ([`create_mutual_tls_acceptor`](fn.create_mutual_tls_acceptor.html)),
 //! * creation and validation of self-signed certificates
 //!   ([`generate_node_cert`](fn.generate_node_cert.html)),
 //! * signing and verification of arbitrary values using keys from certificates
@@ -26,7 +29,8 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
     hash::Hash,
     marker::PhantomData,
-    path::Path,
+    path::{Path, PathBuf},
+    pin::Pin,
     str,
     time::{SystemTime, UNIX_EPOCH},
 };
@@ -44,7 +48,10 @@ use openssl::{
     nid,
     pkey::{PKey, PKeyRef, Private, Public},
     sha,
-    ssl::{SslAcceptor, SslConnector, SslContextBuilder, SslMethod, SslVerifyMode, SslVersion},
+    ssl::{
+        self, Ssl, SslAcceptor, SslConnector, SslContextBuilder, SslFiletype, SslMethod,
+        SslVerifyMode, SslVersion,
+    },
     x509::{X509Builder, X509Name, X509NameBuilder, X509NameRef, X509Ref, X509},
 };
 #[cfg(test)]
@@ -54,6 +61,10 @@ use rand::{
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_openssl::SslStream;
+
+use crate::utils::WithDir;
 
 // This is inside a private module so that the generated `BigArray` does not form part of this
 // crate's public API, and hence also doesn't appear in the rustdocs.
@@ -364,6 +375,110 @@ fn set_context_options(
     Ok(())
 }
 
+/// Configuration of a listener requiring clients to authenticate via mutual TLS.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct MutualTlsConfig {
+    /// Path to the PEM-encoded certificate chain presented to clients.
+    pub certificate_path: PathBuf,
+    /// Path to the PEM-encoded secret key of the certificate.
+    pub secret_key_path: PathBuf,
+    /// Path to the PEM-encoded certificates of the authorities client certificates must be issued
+    /// by.
+    pub client_ca_path: PathBuf,
+}
+
+/// Creates a TLS acceptor requiring clients to present a certificate issued by one of the
+/// configured authorities.
+///
+/// Unlike the acceptors created using `create_tls_acceptor`, which are used between nodes, client
+/// certificates are verified by OpenSSL during the handshake, and TLS 1.2 is accepted for
+/// compatibility with common tools such as `curl`.
+pub(crate) fn create_mutual_tls_acceptor(cfg: &WithDir<MutualTlsConfig>) -> SslResult<SslAcceptor> {
+    let config = cfg.value();
+    let client_ca_path = cfg.with_dir(config.client_ca_path.clone());
+
+    let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server())?;
+    builder.set_certificate_chain_file(cfg.with_dir(config.certificate_path.clone()))?;
+    builder.set_private_key_file(
+        cfg.with_dir(config.secret_key_path.clone()),
+        SslFiletype::PEM,
+    )?;
+    builder.check_private_key()?;
+    builder.set_ca_file(&client_ca_path)?;
+    builder.set_client_ca_list(X509Name::load_client_ca_file(&client_ca_path)?);
+    builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+
+    Ok(builder.build())
+}
+
+/// A client authenticated via mutual TLS.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct ClientIdentity {
+    /// The subject of the client's certificate.
+    pub(crate) subject: String,
+    /// The hex-encoded SHA-256 fingerprint of the client's certificate.
+    pub(crate) fingerprint: String,
+}
+
+impl Display for ClientIdentity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.subject, self.fingerprint)
+    }
+}
+
+/// Error accepting a mutual TLS connection.
+#[derive(Debug, Error)]
+pub(crate) enum MutualTlsError {
+    /// The TLS session could not be set up.
+    #[error("failed to initialize TLS: {0}")]
+    Initialization(#[source] ErrorStack),
+    /// The handshake failed, e.g. because the client's certificate was not accepted.
+    #[error("TLS handshake failed: {0}")]
+    Handshake(#[source] ssl::Error),
+    /// The client did not present a certificate.
+    #[error("client did not present a certificate")]
+    NoClientCertificate,
+    /// The client's certificate could not be read.
+    #[error("could not read client certificate: {0}")]
+    InvalidClientCertificate(#[source] ErrorStack),
+}
+
+/// Performs the server side of a mutual TLS handshake, returning the client's identity.
+pub(crate) async fn accept_mutual_tls<S>(
+    acceptor: &SslAcceptor,
+    stream: S,
+) -> Result<(SslStream<S>, ClientIdentity), MutualTlsError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut tls_stream = Ssl::new(acceptor.context())
+        .and_then(|ssl| SslStream::new(ssl, stream))
+        .map_err(MutualTlsError::Initialization)?;
+
+    SslStream::accept(Pin::new(&mut tls_stream))
+        .await
+        .map_err(MutualTlsError::Handshake)?;
+
+    // OpenSSL has verified the certificate against the authorities during the handshake.
+    let cert = tls_stream
+        .ssl()
+        .peer_certificate()
+        .ok_or(MutualTlsError::NoClientCertificate)?;
+    let subject =
+        name_to_string(cert.subject_name()).map_err(MutualTlsError::InvalidClientCertificate)?;
+    let fingerprint = cert
+        .digest(MessageDigest::sha256())
+        .map_err(MutualTlsError::InvalidClientCertificate)?;
+
+    let identity = ClientIdentity {
+        subject: subject.trim_end().to_string(),
+        fingerprint: base16::encode_lower(&*fingerprint),
+    };
+    Ok((tls_stream, identity))
+}
+
 /// Error during certificate validation.
 #[derive(Debug, Error, Serialize)]
 pub enum ValidationError {
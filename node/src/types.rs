@@ -1,10 +1,15 @@
 //! Common types used across multiple components.
 
 pub(crate) mod appendable_block;
+#[cfg(any(feature = "arbitrary", test))]
+pub mod arbitrary;
 mod available_block_range;
 mod block;
+mod block_height;
 pub mod chainspec;
 mod deploy;
+mod deploy_builder;
+mod era_rewards;
 pub mod error;
 mod exit_code;
 mod item;
@@ -15,6 +20,7 @@ mod node_id;
 pub mod peers_map;
 mod shared_object;
 mod status_feed;
+pub(crate) mod transaction;
 
 use rand::{CryptoRng, RngCore};
 #[cfg(not(test))]
@@ -22,10 +28,11 @@ use rand_chacha::ChaCha20Rng;
 
 pub use available_block_range::AvailableBlockRange;
 pub use block::{
-    json_compatibility::{JsonBlock, JsonBlockHeader},
-    Block, BlockAndDeploys, BlockBody, BlockHash, BlockHeader, BlockSignatures, FinalitySignature,
-    FinalizedBlock,
+    json_compatibility::{JsonBlock, JsonBlockHeader, JsonProof},
+    Block, BlockAndDeploys, BlockBody, BlockHash, BlockHeader, BlockSignals, BlockSignatures,
+    BlockSummary, FinalitySignature, FinalizedBlock,
 };
+pub use block_height::BlockHeight;
 pub(crate) use block::{
     BlockHashAndHeight, BlockHeaderWithMetadata, BlockHeadersBatch, BlockHeadersBatchId,
     BlockPayload, BlockWithMetadata,
@@ -37,7 +44,11 @@ pub use deploy::{
     Approval, Deploy, DeployConfigurationFailure, DeployHash, DeployHeader, DeployMetadata,
     DeployMetadataExt, DeployOrTransferHash, DeployWithApprovals, DeployWithFinalizedApprovals,
     Error as DeployError, ExcessiveSizeError as ExcessiveSizeDeployError, FinalizedApprovals,
-    FinalizedApprovalsWithId,
+    FinalizedApprovalsWithId, ARG_REPLACEMENT_TAG, ARG_SEQUENCE_NUMBER,
+};
+pub use deploy_builder::{DeployBuilder, DeployBuilderError};
+pub use era_rewards::{
+    DelegatorReward, DelegatorStake, EraRewards, EraStakes, ValidatorRewards, ValidatorStake,
 };
 pub use error::BlockValidationError;
 pub use exit_code::ExitCode;
@@ -46,6 +57,7 @@ pub use node_config::NodeConfig;
 pub(crate) use node_id::NodeId;
 pub use peers_map::PeersMap;
 pub use status_feed::{ChainspecInfo, GetStatusResult, NodeState, StatusFeed};
+pub use transaction::{Transaction, TransactionHash};
 
 /// An object-safe RNG trait that requires a cryptographically strong random number generator.
 pub trait CryptoRngCore: CryptoRng + RngCore {}
@@ -20,6 +20,8 @@ pub(crate) enum AddError {
     ApprovalCount,
     #[error("would exceed maximum gas per block")]
     GasLimit,
+    #[error("would exceed maximum transfer gas per block")]
+    TransferGasLimit,
     #[error("would exceed maximum block size")]
     BlockSize,
     #[error("duplicate deploy")]
@@ -40,13 +42,22 @@ pub struct AppendableBlock {
     timestamp: Timestamp,
     #[data_size(skip)]
     total_gas: Gas,
+    /// The fixed cost of a single wasm-less transfer.
+    #[data_size(skip)]
+    transfer_cost: Gas,
+    #[data_size(skip)]
+    total_transfer_gas: Gas,
     total_size: usize,
     total_approvals: usize,
 }
 
 impl AppendableBlock {
-    /// Creates an empty `AppendableBlock`.
-    pub(crate) fn new(deploy_config: DeployConfig, timestamp: Timestamp) -> Self {
+    /// Creates an empty `AppendableBlock`, where each transfer costs `transfer_cost`.
+    pub(crate) fn new(
+        deploy_config: DeployConfig,
+        transfer_cost: Gas,
+        timestamp: Timestamp,
+    ) -> Self {
         AppendableBlock {
             deploy_config,
             deploys: Vec::new(),
@@ -54,6 +65,8 @@ impl AppendableBlock {
             timestamp,
             deploy_and_transfer_set: HashSet::new(),
             total_gas: Gas::zero(),
+            transfer_cost,
+            total_transfer_gas: Gas::zero(),
             total_size: 0,
             total_approvals: 0,
         }
@@ -64,6 +77,21 @@ impl AppendableBlock {
         self.total_size
     }
 
+    /// Returns the total gas of all deploys so far.
+    pub(crate) fn total_gas(&self) -> Gas {
+        self.total_gas
+    }
+
+    /// Returns the number of deploys added so far.
+    pub(crate) fn deploy_count(&self) -> usize {
+        self.deploys.len()
+    }
+
+    /// Returns the number of transfers added so far.
+    pub(crate) fn transfer_count(&self) -> usize {
+        self.transfers.len()
+    }
+
     /// Attempts to add a transfer to the block; returns an error if that would violate a validity
     /// condition.
     ///
@@ -92,6 +120,16 @@ impl AppendableBlock {
         if self.would_exceed_approval_limits(transfer.approvals().len()) {
             return Err(AddError::ApprovalCount);
         }
+        // Transfers only count towards their own gas limit, so they can't crowd out deploys.
+        let new_total_transfer_gas = self
+            .total_transfer_gas
+            .checked_add(self.transfer_cost)
+            .ok_or(AddError::TransferGasLimit)?;
+        let max_transfer_gas = self.deploy_config.block_max_transfer_gas;
+        if max_transfer_gas != 0 && new_total_transfer_gas > Gas::from(max_transfer_gas) {
+            return Err(AddError::TransferGasLimit);
+        }
+        self.total_transfer_gas = new_total_transfer_gas;
         self.deploy_and_transfer_set.insert(*transfer.deploy_hash());
         self.total_approvals += transfer.approvals().len();
         self.transfers.push(transfer);
@@ -0,0 +1,99 @@
+//! [`Arbitrary`] implementations for the node's serializable types, for use in fuzzing and
+//! property-based tests via [`Proptest`](https://crates.io/crates/proptest).
+
+use proptest::{
+    arbitrary::Arbitrary,
+    strategy::{BoxedStrategy, Strategy},
+};
+
+use casper_types::arbitrary::from_test_rng;
+
+use super::{
+    Approval, Block, BlockBody, BlockHash, BlockHeader, BlockSignals, BlockSignatures, Deploy,
+    DeployHash, DeployHeader, DeployMetadata, EraRewards, EraStakes, FinalizedApprovals,
+    FinalizedBlock,
+};
+use crate::components::small_network::GossipedAddress;
+
+macro_rules! impl_arbitrary {
+    ($($ty:ty => $strategy:expr),* $(,)?) => {
+        $(
+            impl Arbitrary for $ty {
+                type Parameters = ();
+                type Strategy = BoxedStrategy<Self>;
+
+                fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+                    $strategy.boxed()
+                }
+            }
+        )*
+    };
+}
+
+impl_arbitrary! {
+    Approval => from_test_rng(Approval::random),
+    Block => from_test_rng(Block::random),
+    BlockBody => from_test_rng(|rng| Block::random(rng).body().clone()),
+    BlockHash => from_test_rng(BlockHash::random),
+    BlockHeader => from_test_rng(|rng| Block::random(rng).take_header()),
+    BlockSignals => from_test_rng(BlockSignals::random),
+    BlockSignatures => from_test_rng(BlockSignatures::random),
+    Deploy => from_test_rng(Deploy::random),
+    DeployHash => from_test_rng(DeployHash::random),
+    DeployHeader => from_test_rng(|rng| Deploy::random(rng).header().clone()),
+    DeployMetadata => from_test_rng(DeployMetadata::random),
+    EraRewards => from_test_rng(EraRewards::random),
+    EraStakes => from_test_rng(EraStakes::random),
+    FinalizedApprovals => from_test_rng(FinalizedApprovals::random),
+    FinalizedBlock => from_test_rng(FinalizedBlock::random),
+    GossipedAddress => from_test_rng(GossipedAddress::random),
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use casper_types::test_bytesrepr_roundtrip;
+
+    use super::*;
+
+    test_bytesrepr_roundtrip!(
+        Approval,
+        Block,
+        BlockBody,
+        BlockHash,
+        BlockHeader,
+        BlockSignals,
+        BlockSignatures,
+        Deploy,
+        DeployHash,
+        DeployHeader,
+        DeployMetadata,
+        EraRewards,
+        EraStakes,
+        FinalizedApprovals,
+        GossipedAddress,
+    );
+
+    // The gossip tables are persisted as the IDs of the recently gossiped items.
+    test_bytesrepr_roundtrip!(
+        bytesrepr_roundtrip_deploy_gossip_table: Vec<DeployHash>,
+        bytesrepr_roundtrip_address_gossip_table: Vec<GossipedAddress>,
+    );
+
+    proptest! {
+        #[test]
+        fn bincode_roundtrip_deploy(deploy in any::<Deploy>()) {
+            let serialized = bincode::serialize(&deploy).unwrap();
+            let deserialized: Deploy = bincode::deserialize(&serialized).unwrap();
+            prop_assert_eq!(deploy, deserialized);
+        }
+
+        #[test]
+        fn json_roundtrip_deploy(deploy in any::<Deploy>()) {
+            let json = serde_json::to_string(&deploy).unwrap();
+            let deserialized: Deploy = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(deploy, deserialized);
+        }
+    }
+}
@@ -25,7 +25,8 @@ use casper_hashing::Digest;
 use casper_types::testing::TestRng;
 use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
-    crypto, EraId, ProtocolVersion, PublicKey, SecretKey, Signature, Timestamp, U512,
+    crypto, EraId, ProtocolVersion, PublicKey, SecretKey, Signature, SigningContext, Timestamp,
+    U512,
 };
 #[cfg(any(feature = "testing", test))]
 use casper_types::{crypto::generate_ed25519_keypair, system::auction::BLOCK_REWARD};
@@ -169,8 +170,14 @@ static JSON_BLOCK: Lazy<JsonBlock> = Lazy::new(|| {
     let secret_key = SecretKey::doc_example();
     let public_key = PublicKey::from(secret_key);
 
-    let signature = crypto::sign(block.hash.inner(), secret_key, &public_key);
-    block_signature.insert_proof(public_key, signature);
+    let finality_signature = FinalitySignature::new(
+        *block.hash(),
+        block.header().era_id,
+        secret_key,
+        public_key.clone(),
+        true,
+    );
+    block_signature.insert_proof(public_key, finality_signature.signature);
 
     JsonBlock::new(block, Some(block_signature))
 });
@@ -220,6 +227,7 @@ pub struct BlockPayload {
     transfers: Vec<DeployWithApprovals>,
     accusations: Vec<PublicKey>,
     random_bit: bool,
+    signals: u8,
 }
 
 impl BlockPayload {
@@ -234,9 +242,16 @@ impl BlockPayload {
             transfers,
             accusations,
             random_bit,
+            signals: 0,
         }
     }
 
+    /// Sets the signaling bits chosen by the proposer.
+    pub(crate) fn with_signals(mut self, signals: u8) -> Self {
+        self.signals = signals;
+        self
+    }
+
     /// Returns the set of validators that are reported as faulty in this block.
     pub(crate) fn accusations(&self) -> &Vec<PublicKey> {
         &self.accusations
@@ -281,11 +296,13 @@ impl Display for BlockPayload {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         write!(
             formatter,
-            "block payload: deploys {}, transfers {}, accusations {:?}, random bit {}",
+            "block payload: deploys {}, transfers {}, accusations {:?}, random bit {}, \
+            signals {:#010b}",
             HexList(self.deploy_hashes()),
             HexList(self.transfer_hashes()),
             self.accusations,
             self.random_bit,
+            self.signals,
         )
     }
 }
@@ -422,6 +439,7 @@ pub struct FinalizedBlock {
     era_id: EraId,
     height: u64,
     proposer: Box<PublicKey>,
+    signals: u8,
 }
 
 impl FinalizedBlock {
@@ -442,6 +460,7 @@ impl FinalizedBlock {
             era_id,
             height,
             proposer: Box::new(proposer),
+            signals: block_payload.signals,
         }
     }
 
@@ -470,6 +489,11 @@ impl FinalizedBlock {
         self.proposer.clone()
     }
 
+    /// Returns the signaling bits chosen by the proposer.
+    pub(crate) fn signals(&self) -> u8 {
+        self.signals
+    }
+
     /// Returns the WebAssembly-deploy hashes for the finalized block. These correspond to complex
     /// smart contract operations that require a WebAssembly VM in the execution engine.
     pub(crate) fn deploy_hashes(&self) -> &[DeployHash] {
@@ -577,6 +601,8 @@ impl From<Block> for FinalizedBlock {
             era_id: block.header.era_id,
             height: block.header.height,
             proposer: Box::new(block.body.proposer),
+            // Signals are not part of the executed block.
+            signals: 0,
         }
     }
 }
@@ -1329,7 +1355,10 @@ impl BlockSignatures {
     }
 
     /// Verify the signatures contained within.
-    pub(crate) fn verify(&self) -> Result<(), crypto::Error> {
+    ///
+    /// If `signing_context_active` is `false`, signatures created without the finality signature
+    /// signing context are accepted as well.
+    pub(crate) fn verify(&self, signing_context_active: bool) -> Result<(), crypto::Error> {
         for (public_key, signature) in self.proofs.iter() {
             let signature = FinalitySignature {
                 block_hash: self.block_hash,
@@ -1337,12 +1366,63 @@ impl BlockSignatures {
                 signature: *signature,
                 public_key: public_key.clone(),
             };
-            signature.verify()?;
+            signature.verify(signing_context_active)?;
         }
         Ok(())
     }
 }
 
+#[cfg(any(feature = "testing", test))]
+impl BlockSignatures {
+    /// Returns a random `BlockSignatures` with a few valid proofs.
+    pub fn random(rng: &mut TestRng) -> Self {
+        let mut block_signatures = BlockSignatures::new(BlockHash::random(rng), rng.gen());
+        for _ in 0..rng.gen_range(0..4) {
+            let secret_key = SecretKey::random(rng);
+            let public_key = PublicKey::from(&secret_key);
+            let finality_signature = FinalitySignature::new(
+                block_signatures.block_hash,
+                block_signatures.era_id,
+                &secret_key,
+                public_key.clone(),
+                true,
+            );
+            block_signatures.insert_proof(public_key, finality_signature.signature);
+        }
+        block_signatures
+    }
+}
+
+impl ToBytes for BlockSignatures {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.block_hash.to_bytes()?);
+        buffer.extend(self.era_id.to_bytes()?);
+        buffer.extend(self.proofs.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.block_hash.serialized_length()
+            + self.era_id.serialized_length()
+            + self.proofs.serialized_length()
+    }
+}
+
+impl FromBytes for BlockSignatures {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (block_hash, bytes) = BlockHash::from_bytes(bytes)?;
+        let (era_id, bytes) = EraId::from_bytes(bytes)?;
+        let (proofs, bytes) = BTreeMap::<PublicKey, Signature>::from_bytes(bytes)?;
+        let block_signatures = BlockSignatures {
+            block_hash,
+            era_id,
+            proofs,
+        };
+        Ok((block_signatures, bytes))
+    }
+}
+
 impl Display for BlockSignatures {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         write!(
@@ -1362,7 +1442,9 @@ impl Item for BlockSignatures {
     const ID_IS_COMPLETE_ITEM: bool = false;
 
     fn validate(&self) -> Result<(), Self::ValidationError> {
-        self.verify()
+        // Whether the signing context is required depends on the era and the chainspec, so it is
+        // enforced by the components using the signatures.
+        self.verify(false)
     }
 
     fn id(&self) -> Self::Id {
@@ -1782,8 +1864,118 @@ impl Item for BlockAndDeploys {
     }
 }
 
+/// A compact summary of a block and its execution, for listing blocks without their bodies.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BlockSummary {
+    /// The block hash.
+    pub hash: BlockHash,
+    /// The block height.
+    pub height: u64,
+    /// The era ID the block belongs to.
+    pub era_id: EraId,
+    /// The public key of the validator which proposed the block.
+    pub proposer: PublicKey,
+    /// The number of non-transfer deploys included in the block.
+    pub deploy_count: u64,
+    /// The number of native transfers included in the block.
+    pub transfer_count: u64,
+    /// The total gas cost of executing the block's deploys and transfers.
+    pub total_gas: U512,
+    /// The total amount of motes transferred by the block's deploys and transfers.
+    pub total_transferred: U512,
+}
+
+impl BlockSummary {
+    /// Creates a summary of `block`, given the totals of its execution results and transfers.
+    pub(crate) fn new(block: &Block, total_gas: U512, total_transferred: U512) -> Self {
+        BlockSummary {
+            hash: *block.hash(),
+            height: block.height(),
+            era_id: block.header().era_id(),
+            proposer: block.body().proposer().clone(),
+            deploy_count: block.deploy_hashes().len() as u64,
+            transfer_count: block.transfer_hashes().len() as u64,
+            total_gas,
+            total_transferred,
+        }
+    }
+}
+
+/// The signaling bits the proposer of a finalized block set in its payload.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockSignals {
+    /// The block height.
+    pub height: u64,
+    /// The era ID the block belongs to.
+    pub era_id: EraId,
+    /// The public key of the validator which proposed the block.
+    pub proposer: PublicKey,
+    /// The signaling bits.
+    pub signals: u8,
+}
+
+#[cfg(any(feature = "testing", test))]
+impl BlockSignals {
+    /// Returns a random `BlockSignals`.
+    pub fn random(rng: &mut TestRng) -> Self {
+        BlockSignals {
+            height: rng.gen(),
+            era_id: rng.gen(),
+            proposer: PublicKey::random(rng),
+            signals: rng.gen(),
+        }
+    }
+}
+
+impl ToBytes for BlockSignals {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.height.to_bytes()?);
+        buffer.extend(self.era_id.to_bytes()?);
+        buffer.extend(self.proposer.to_bytes()?);
+        buffer.extend(self.signals.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.height.serialized_length()
+            + self.era_id.serialized_length()
+            + self.proposer.serialized_length()
+            + self.signals.serialized_length()
+    }
+}
+
+impl FromBytes for BlockSignals {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (height, bytes) = u64::from_bytes(bytes)?;
+        let (era_id, bytes) = EraId::from_bytes(bytes)?;
+        let (proposer, bytes) = PublicKey::from_bytes(bytes)?;
+        let (signals, bytes) = u8::from_bytes(bytes)?;
+        let block_signals = BlockSignals {
+            height,
+            era_id,
+            proposer,
+            signals,
+        };
+        Ok((block_signals, bytes))
+    }
+}
+
+impl From<&FinalizedBlock> for BlockSignals {
+    fn from(finalized_block: &FinalizedBlock) -> Self {
+        BlockSignals {
+            height: finalized_block.height(),
+            era_id: finalized_block.era_id(),
+            proposer: *finalized_block.proposer(),
+            signals: finalized_block.signals(),
+        }
+    }
+}
+
 pub(crate) mod json_compatibility {
     use super::*;
+    use crate::types::BlockHeight;
 
     #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, PartialEq, Eq, DataSize)]
     #[serde(deny_unknown_fields)]
@@ -1899,7 +2091,7 @@ pub(crate) mod json_compatibility {
         /// The block era id.
         pub era_id: EraId,
         /// The block height.
-        pub height: u64,
+        pub height: BlockHeight,
         /// The protocol version.
         pub protocol_version: ProtocolVersion,
     }
@@ -1915,7 +2107,7 @@ pub(crate) mod json_compatibility {
                 era_end: block_header.era_end.map(JsonEraEnd::from),
                 timestamp: block_header.timestamp,
                 era_id: block_header.era_id,
-                height: block_header.height,
+                height: BlockHeight::new(block_header.height),
                 protocol_version: block_header.protocol_version,
             }
         }
@@ -1932,7 +2124,7 @@ pub(crate) mod json_compatibility {
                 era_end: block_header.era_end.map(EraEnd::from),
                 timestamp: block_header.timestamp,
                 era_id: block_header.era_id,
-                height: block_header.height,
+                height: block_header.height.value(),
                 protocol_version: block_header.protocol_version,
             }
         }
@@ -1973,6 +2165,23 @@ pub(crate) mod json_compatibility {
         }
     }
 
+    impl JsonBlockBody {
+        /// Returns the public key of the validator which proposed the block.
+        pub fn proposer(&self) -> &PublicKey {
+            &self.proposer
+        }
+
+        /// Returns the deploy hashes within the block.
+        pub fn deploy_hashes(&self) -> &Vec<DeployHash> {
+            &self.deploy_hashes
+        }
+
+        /// Returns the transfer hashes within the block.
+        pub fn transfer_hashes(&self) -> &Vec<DeployHash> {
+            &self.transfer_hashes
+        }
+    }
+
     /// A JSON-friendly representation of `Block`.
     #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, PartialEq, Eq, DataSize)]
     #[serde(deny_unknown_fields)]
@@ -2087,16 +2296,24 @@ pub struct FinalitySignature {
 }
 
 impl FinalitySignature {
-    /// Create an instance of `FinalitySignature`.
+    /// Create an instance of `FinalitySignature`, signed in the finality signature signing context
+    /// if `signing_context_active` is `true`.
     pub fn new(
         block_hash: BlockHash,
         era_id: EraId,
         secret_key: &SecretKey,
         public_key: PublicKey,
+        signing_context_active: bool,
     ) -> Self {
         let mut bytes = block_hash.inner().into_vec();
         bytes.extend_from_slice(&era_id.to_le_bytes());
-        let signature = crypto::sign(bytes, secret_key, &public_key);
+        let signature = crypto::sign_with_context_if_active(
+            bytes,
+            SigningContext::FinalitySignature,
+            signing_context_active,
+            secret_key,
+            &public_key,
+        );
         FinalitySignature {
             block_hash,
             era_id,
@@ -2106,18 +2323,27 @@ impl FinalitySignature {
     }
 
     /// Verifies whether the signature is correct.
-    pub fn verify(&self) -> Result<(), crypto::Error> {
+    ///
+    /// If `signing_context_active` is `false`, signatures created without the finality signature
+    /// signing context are accepted as well.
+    pub fn verify(&self, signing_context_active: bool) -> Result<(), crypto::Error> {
         // NOTE: This needs to be in sync with the `new` constructor.
         let mut bytes = self.block_hash.inner().into_vec();
         bytes.extend_from_slice(&self.era_id.to_le_bytes());
-        crypto::verify(bytes, &self.signature, &self.public_key)
+        crypto::verify_with_context_if_active(
+            bytes,
+            SigningContext::FinalitySignature,
+            signing_context_active,
+            &self.signature,
+            &self.public_key,
+        )
     }
 
     /// Returns a random `FinalitySignature` for the provided `block_hash` and `era_id`.
     #[cfg(any(feature = "testing", test))]
     pub fn random_for_block(block_hash: BlockHash, era_id: u64) -> Self {
         let (sec_key, pub_key) = generate_ed25519_keypair();
-        FinalitySignature::new(block_hash, EraId::new(era_id), &sec_key, pub_key)
+        FinalitySignature::new(block_hash, EraId::new(era_id), &sec_key, pub_key, true)
     }
 }
 
@@ -2253,8 +2479,9 @@ mod tests {
         let (secret_key, public_key) = generate_ed25519_keypair();
         let secret_rc = Rc::new(secret_key);
         let era_id = EraId::from(1);
-        let fs = FinalitySignature::new(*block.hash(), era_id, &secret_rc, public_key.clone());
-        assert!(fs.verify().is_ok());
+        let fs =
+            FinalitySignature::new(*block.hash(), era_id, &secret_rc, public_key.clone(), true);
+        assert!(fs.verify(true).is_ok());
         let signature = fs.signature;
         // Verify that signature includes era id.
         let fs_manufactured = FinalitySignature {
@@ -2264,7 +2491,28 @@ mod tests {
             public_key,
         };
         // Test should fail b/c `signature` is over `era_id=1` and here we're using `era_id=2`.
-        assert!(fs_manufactured.verify().is_err());
+        assert!(fs_manufactured.verify(true).is_err());
+    }
+
+    #[test]
+    fn finality_signature_without_context_only_valid_until_active() {
+        let mut rng = TestRng::new();
+        let block = Block::random(&mut rng);
+        let (secret_key, public_key) = generate_ed25519_keypair();
+        let era_id = EraId::from(1);
+        let legacy_fs = FinalitySignature::new(
+            *block.hash(),
+            era_id,
+            &secret_key,
+            public_key.clone(),
+            false,
+        );
+        assert!(legacy_fs.verify(false).is_ok());
+        assert!(legacy_fs.verify(true).is_err());
+
+        let fs = FinalitySignature::new(*block.hash(), era_id, &secret_key, public_key, true);
+        assert!(fs.verify(false).is_ok());
+        assert!(fs.verify(true).is_ok());
     }
 
     #[test]
@@ -0,0 +1,106 @@
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use std::{
+    fmt::{self, Display, Formatter},
+    num::ParseIntError,
+    str::FromStr,
+};
+
+use datasize::DataSize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The height of a block in the linear chain.
+///
+/// Serialized as a bare JSON number, so negative, fractional or overflowing heights are rejected on
+/// deserialization.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    DataSize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct BlockHeight(u64);
+
+impl BlockHeight {
+    /// Creates a new [`BlockHeight`].
+    pub const fn new(value: u64) -> BlockHeight {
+        BlockHeight(value)
+    }
+
+    /// Returns the inner value.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl FromStr for BlockHeight {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str(s).map(BlockHeight)
+    }
+}
+
+impl Display for BlockHeight {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+impl From<BlockHeight> for u64 {
+    fn from(block_height: BlockHeight) -> Self {
+        block_height.value()
+    }
+}
+
+impl From<u64> for BlockHeight {
+    fn from(block_height: u64) -> Self {
+        BlockHeight(block_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::EraId;
+
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_as_json_number() {
+        let block_height = BlockHeight::new(u64::MAX);
+        let json = serde_json::to_string(&block_height).unwrap();
+        assert_eq!(json, u64::MAX.to_string());
+        assert_eq!(
+            serde_json::from_str::<BlockHeight>(&json).unwrap(),
+            block_height
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_json_numbers() {
+        for invalid in ["-1", "1.5", "18446744073709551616", "\"1\""] {
+            assert!(
+                serde_json::from_str::<BlockHeight>(invalid).is_err(),
+                "{} should be rejected as a block height",
+                invalid
+            );
+            assert!(
+                serde_json::from_str::<EraId>(invalid).is_err(),
+                "{} should be rejected as an era id",
+                invalid
+            );
+        }
+    }
+}
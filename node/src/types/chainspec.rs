@@ -7,11 +7,14 @@ mod chainspec_raw_bytes;
 mod core_config;
 mod deploy_config;
 mod error;
+mod global_state_migrations;
 mod global_state_update;
 mod highway_config;
 mod network_config;
+mod overlay;
 mod parse_toml;
 mod protocol_config;
+mod upgrade_signature;
 
 use std::{fmt::Debug, path::Path};
 
@@ -39,8 +42,10 @@ pub use self::error::Error;
 pub(crate) use self::{
     accounts_config::AccountsConfig, activation_point::ActivationPoint,
     chainspec_raw_bytes::ChainspecRawBytes, core_config::CoreConfig, deploy_config::DeployConfig,
-    global_state_update::GlobalStateUpdate, highway_config::HighwayConfig,
-    network_config::NetworkConfig, protocol_config::ProtocolConfig,
+    global_state_migrations::GlobalStateMigrations, global_state_update::GlobalStateUpdate,
+    highway_config::HighwayConfig, network_config::NetworkConfig, overlay::apply_overlays,
+    parse_toml::validate_upgrade_files, protocol_config::ProtocolConfig,
+    upgrade_signature::verify_upgrade_signature,
 };
 use crate::utils::Loadable;
 
@@ -49,7 +54,7 @@ pub const CHAINSPEC_FILENAME: &str = "chainspec.toml";
 
 /// A collection of configuration settings describing the state of the system at genesis and after
 /// upgrades to basic system functionality occurring after genesis.
-#[derive(DataSize, PartialEq, Eq, Serialize, Debug)]
+#[derive(Clone, DataSize, PartialEq, Eq, Serialize, Debug)]
 pub struct Chainspec {
     #[serde(rename = "protocol")]
     pub(crate) protocol_config: ProtocolConfig,
@@ -282,6 +287,7 @@ mod tests {
             print: HostFunction::new(123, [0, 1]),
             blake2b: HostFunction::new(133, [0, 1, 2, 3]),
             random_bytes: HostFunction::new(123, [0, 1]),
+            emit_message: HostFunction::new(142, [0, 1, 2, 3]),
         });
     static EXPECTED_GENESIS_WASM_COSTS: Lazy<WasmConfig> = Lazy::new(|| {
         WasmConfig::new(
@@ -349,7 +355,7 @@ mod tests {
             );
             assert_eq!(
                 spec.protocol_config.activation_point.era_id(),
-                EraId::from(1)
+                Some(EraId::from(1))
             );
             assert!(spec.network_config.accounts_config.accounts().is_empty());
             assert!(spec.protocol_config.global_state_update.is_some());
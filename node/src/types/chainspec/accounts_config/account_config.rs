@@ -45,6 +45,10 @@ impl AccountConfig {
         }
     }
 
+    pub fn validator(&self) -> Option<&ValidatorConfig> {
+        self.validator.as_ref()
+    }
+
     pub fn is_genesis_validator(&self) -> bool {
         self.validator.is_some()
     }
@@ -18,6 +18,7 @@ use casper_types::{
 
 const ERA_ID_TAG: u8 = 0;
 const GENESIS_TAG: u8 = 1;
+const TIMESTAMP_TAG: u8 = 2;
 
 /// The first era to which the associated protocol version applies.
 #[derive(Copy, Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
@@ -25,30 +26,51 @@ const GENESIS_TAG: u8 = 1;
 pub enum ActivationPoint {
     EraId(EraId),
     Genesis(Timestamp),
+    /// The first era starting at or after the given timestamp, i.e. the era following the first
+    /// switch block with a timestamp at or after it.
+    Timestamp {
+        timestamp: Timestamp,
+    },
 }
 
 impl ActivationPoint {
     /// Returns whether we should upgrade the node due to the next era being at or after the upgrade
     /// activation point.
-    pub(crate) fn should_upgrade(&self, era_being_deactivated: &EraId) -> bool {
+    ///
+    /// `era_end` is the timestamp of the switch block of the era being deactivated, i.e. the start
+    /// of the next era.
+    pub(crate) fn should_upgrade(&self, era_being_deactivated: &EraId, era_end: Timestamp) -> bool {
         match self {
             ActivationPoint::EraId(era_id) => era_being_deactivated.successor() >= *era_id,
             ActivationPoint::Genesis(_) => false,
+            ActivationPoint::Timestamp { timestamp } => era_end >= *timestamp,
         }
     }
 
-    /// Returns the Era ID if `self` is of `EraId` variant, or else 0 if `Genesis`.
-    pub(crate) fn era_id(&self) -> EraId {
+    /// Returns whether the era `era_id`, starting at `era_start`, is the first era of the protocol
+    /// version activated by `self`, assuming it is preceded by an era of the previous version.
+    pub(crate) fn is_activated_by(&self, era_id: EraId, era_start: Timestamp) -> bool {
         match self {
-            ActivationPoint::EraId(era_id) => *era_id,
-            ActivationPoint::Genesis(_) => EraId::from(0),
+            ActivationPoint::EraId(activation_era_id) => era_id == *activation_era_id,
+            ActivationPoint::Genesis(_) => false,
+            ActivationPoint::Timestamp { timestamp } => era_start >= *timestamp,
+        }
+    }
+
+    /// Returns the Era ID if `self` is of `EraId` variant, 0 if `Genesis`, or `None` if
+    /// `Timestamp`, as then the era can only be determined from the chain itself.
+    pub(crate) fn era_id(&self) -> Option<EraId> {
+        match self {
+            ActivationPoint::EraId(era_id) => Some(*era_id),
+            ActivationPoint::Genesis(_) => Some(EraId::from(0)),
+            ActivationPoint::Timestamp { .. } => None,
         }
     }
 
     /// Returns the timestamp if `self` is of `Genesis` variant, or else `None`.
     pub(crate) fn genesis_timestamp(&self) -> Option<Timestamp> {
         match self {
-            ActivationPoint::EraId(_) => None,
+            ActivationPoint::EraId(_) | ActivationPoint::Timestamp { .. } => None,
             ActivationPoint::Genesis(timestamp) => Some(*timestamp),
         }
     }
@@ -56,10 +78,36 @@ impl ActivationPoint {
     /// Returns true if `self` is `Genesis`.
     pub(crate) fn is_genesis(&self) -> bool {
         match self {
-            ActivationPoint::EraId(_) => false,
+            ActivationPoint::EraId(_) | ActivationPoint::Timestamp { .. } => false,
             ActivationPoint::Genesis(_) => true,
         }
     }
+
+    /// Returns whether `self` is strictly after `previous`.
+    ///
+    /// Genesis can never follow another activation point.  An era ID and a timestamp can't be
+    /// compared without the chain, so such pairs are accepted.
+    pub(crate) fn is_after(&self, previous: &ActivationPoint) -> bool {
+        match (self, previous) {
+            (ActivationPoint::Genesis(_), _) => false,
+            (ActivationPoint::EraId(era_id), ActivationPoint::EraId(previous_era_id)) => {
+                era_id > previous_era_id
+            }
+            (ActivationPoint::EraId(era_id), ActivationPoint::Genesis(_)) => era_id.value() > 0,
+            (
+                ActivationPoint::Timestamp { timestamp },
+                ActivationPoint::Genesis(previous_timestamp),
+            )
+            | (
+                ActivationPoint::Timestamp { timestamp },
+                ActivationPoint::Timestamp {
+                    timestamp: previous_timestamp,
+                },
+            ) => timestamp > previous_timestamp,
+            (ActivationPoint::EraId(_), ActivationPoint::Timestamp { .. })
+            | (ActivationPoint::Timestamp { .. }, ActivationPoint::EraId(_)) => true,
+        }
+    }
 }
 
 impl Display for ActivationPoint {
@@ -69,6 +117,9 @@ impl Display for ActivationPoint {
             ActivationPoint::Genesis(timestamp) => {
                 write!(formatter, "activation point {}", timestamp)
             }
+            ActivationPoint::Timestamp { timestamp } => {
+                write!(formatter, "activation point first era from {}", timestamp)
+            }
         }
     }
 }
@@ -86,6 +137,11 @@ impl ToBytes for ActivationPoint {
                 buffer.extend(timestamp.to_bytes()?);
                 Ok(buffer)
             }
+            ActivationPoint::Timestamp { timestamp } => {
+                let mut buffer = vec![TIMESTAMP_TAG];
+                buffer.extend(timestamp.to_bytes()?);
+                Ok(buffer)
+            }
         }
     }
 
@@ -93,7 +149,9 @@ impl ToBytes for ActivationPoint {
         U8_SERIALIZED_LENGTH
             + match self {
                 ActivationPoint::EraId(era_id) => era_id.serialized_length(),
-                ActivationPoint::Genesis(timestamp) => timestamp.serialized_length(),
+                ActivationPoint::Genesis(timestamp) | ActivationPoint::Timestamp { timestamp } => {
+                    timestamp.serialized_length()
+                }
             }
     }
 }
@@ -110,6 +168,10 @@ impl FromBytes for ActivationPoint {
                 let (timestamp, remainder) = Timestamp::from_bytes(remainder)?;
                 Ok((ActivationPoint::Genesis(timestamp), remainder))
             }
+            TIMESTAMP_TAG => {
+                let (timestamp, remainder) = Timestamp::from_bytes(remainder)?;
+                Ok((ActivationPoint::Timestamp { timestamp }, remainder))
+            }
             _ => Err(bytesrepr::Error::Formatting),
         }
     }
@@ -119,10 +181,12 @@ impl FromBytes for ActivationPoint {
 impl ActivationPoint {
     /// Generates a random instance using a `TestRng`.
     pub fn random(rng: &mut TestRng) -> Self {
-        if rng.gen() {
-            ActivationPoint::EraId(rng.gen())
-        } else {
-            ActivationPoint::Genesis(Timestamp::random(rng))
+        match rng.gen_range(0..3) {
+            0 => ActivationPoint::EraId(rng.gen()),
+            1 => ActivationPoint::Genesis(Timestamp::random(rng)),
+            _ => ActivationPoint::Timestamp {
+                timestamp: Timestamp::random(rng),
+            },
         }
     }
 }
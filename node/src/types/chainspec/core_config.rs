@@ -1,15 +1,14 @@
-use datasize::DataSize;
-use num::rational::Ratio;
-#[cfg(test)]
-use rand::Rng;
-use serde::{Deserialize, Serialize};
-
 #[cfg(test)]
 use casper_types::testing::TestRng;
 use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
-    TimeDiff,
+    EraId, TimeDiff,
 };
+use datasize::DataSize;
+use num::rational::Ratio;
+#[cfg(test)]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
@@ -39,6 +38,29 @@ pub struct CoreConfig {
     pub(crate) minimum_delegation_amount: u64,
     /// Enables strict arguments checking when calling a contract.
     pub(crate) strict_argument_checking: bool,
+    /// The era from which consensus messages, finality signatures and deploy approvals must be
+    /// signed in their signing contexts.  Before it, validators sign without a context, and
+    /// signatures without a context are accepted as well.  If `None`, signing contexts are never
+    /// required.
+    #[serde(default)]
+    pub(crate) signing_context_activation_era: Option<EraId>,
+    /// Records transfers as `TransferV2`s, with their initiator, an optional memo and the deploy
+    /// phase.  Should only be enabled at genesis or by a protocol upgrade.
+    #[serde(default)]
+    pub(crate) record_transfer_metadata: bool,
+    /// Authorizes auction callers by account hashes derived via `AccountHashVersion::V2` as well
+    /// as `V1`.  Should only be enabled at genesis or by a protocol upgrade.
+    #[serde(default)]
+    pub(crate) allow_account_hash_v2: bool,
+}
+
+impl CoreConfig {
+    /// Returns whether signatures must be created and verified in their signing contexts in the
+    /// given era.
+    pub(crate) fn is_signing_context_active(&self, era_id: EraId) -> bool {
+        self.signing_context_activation_era
+            .map_or(false, |activation_era| era_id >= activation_era)
+    }
 }
 
 #[cfg(test)]
@@ -60,6 +82,13 @@ impl CoreConfig {
         let max_runtime_call_stack_height = rng.gen();
         let minimum_delegation_amount = rng.gen::<u32>() as u64;
         let strict_argument_checking = rng.gen();
+        let signing_context_activation_era = if rng.gen() {
+            Some(EraId::new(rng.gen_range(0..1000)))
+        } else {
+            None
+        };
+        let record_transfer_metadata = rng.gen();
+        let allow_account_hash_v2 = rng.gen();
 
         CoreConfig {
             era_duration,
@@ -74,6 +103,9 @@ impl CoreConfig {
             max_runtime_call_stack_height,
             minimum_delegation_amount,
             strict_argument_checking,
+            signing_context_activation_era,
+            record_transfer_metadata,
+            allow_account_hash_v2,
         }
     }
 }
@@ -93,6 +125,9 @@ impl ToBytes for CoreConfig {
         buffer.extend(self.max_runtime_call_stack_height.to_bytes()?);
         buffer.extend(self.minimum_delegation_amount.to_bytes()?);
         buffer.extend(self.strict_argument_checking.to_bytes()?);
+        buffer.extend(self.signing_context_activation_era.to_bytes()?);
+        buffer.extend(self.record_transfer_metadata.to_bytes()?);
+        buffer.extend(self.allow_account_hash_v2.to_bytes()?);
         Ok(buffer)
     }
 
@@ -109,6 +144,9 @@ impl ToBytes for CoreConfig {
             + self.max_runtime_call_stack_height.serialized_length()
             + self.minimum_delegation_amount.serialized_length()
             + self.strict_argument_checking.serialized_length()
+            + self.signing_context_activation_era.serialized_length()
+            + self.record_transfer_metadata.serialized_length()
+            + self.allow_account_hash_v2.serialized_length()
     }
 }
 
@@ -126,6 +164,9 @@ impl FromBytes for CoreConfig {
         let (max_runtime_call_stack_height, remainder) = u32::from_bytes(remainder)?;
         let (minimum_delegation_amount, remainder) = u64::from_bytes(remainder)?;
         let (strict_argument_checking, remainder) = bool::from_bytes(remainder)?;
+        let (signing_context_activation_era, remainder) = Option::<EraId>::from_bytes(remainder)?;
+        let (record_transfer_metadata, remainder) = bool::from_bytes(remainder)?;
+        let (allow_account_hash_v2, remainder) = bool::from_bytes(remainder)?;
         let config = CoreConfig {
             era_duration,
             minimum_era_height,
@@ -139,6 +180,9 @@ impl FromBytes for CoreConfig {
             max_runtime_call_stack_height,
             minimum_delegation_amount,
             strict_argument_checking,
+            signing_context_activation_era,
+            record_transfer_metadata,
+            allow_account_hash_v2,
         };
         Ok((config, remainder))
     }
@@ -30,9 +30,17 @@ pub struct DeployConfig {
     pub(crate) block_max_transfer_count: u32,
     pub(crate) block_max_approval_count: u32,
     pub(crate) block_gas_limit: u64,
+    /// The upper limit of total gas of all wasm-less transfers in a block.  0 means transfers are
+    /// only limited by `block_max_transfer_count`.
+    #[serde(default)]
+    pub(crate) block_max_transfer_gas: u64,
     pub(crate) payment_args_max_length: u32,
     pub(crate) session_args_max_length: u32,
     pub(crate) native_transfer_minimum_motes: u64,
+    /// Whether deploys have to carry a sequence number, and deploys of one account are proposed in
+    /// sequence number order.  Intended for private networks.
+    #[serde(default)]
+    pub(crate) strict_account_ordering: bool,
 }
 
 #[cfg(test)]
@@ -48,10 +56,12 @@ impl DeployConfig {
         let block_max_transfer_count = rng.gen();
         let block_max_approval_count = rng.gen();
         let block_gas_limit = rng.gen_range(100_000_000_000..1_000_000_000_000_000);
+        let block_max_transfer_gas = rng.gen();
         let payment_args_max_length = rng.gen();
         let session_args_max_length = rng.gen();
         let native_transfer_minimum_motes =
             rng.gen_range(MAX_PAYMENT_AMOUNT..1_000_000_000_000_000);
+        let strict_account_ordering = rng.gen();
 
         DeployConfig {
             max_payment_cost,
@@ -63,9 +73,11 @@ impl DeployConfig {
             block_max_transfer_count,
             block_max_approval_count,
             block_gas_limit,
+            block_max_transfer_gas,
             payment_args_max_length,
             session_args_max_length,
             native_transfer_minimum_motes,
+            strict_account_ordering,
         }
     }
 }
@@ -83,9 +95,11 @@ impl Default for DeployConfig {
             block_max_transfer_count: 1000,
             block_max_approval_count: 2600,
             block_gas_limit: 10_000_000_000_000,
+            block_max_transfer_gas: 0,
             payment_args_max_length: 1024,
             session_args_max_length: 1024,
             native_transfer_minimum_motes: MAX_PAYMENT_AMOUNT,
+            strict_account_ordering: false,
         }
     }
 }
@@ -102,9 +116,11 @@ impl ToBytes for DeployConfig {
         buffer.extend(self.block_max_transfer_count.to_bytes()?);
         buffer.extend(self.block_max_approval_count.to_bytes()?);
         buffer.extend(self.block_gas_limit.to_bytes()?);
+        buffer.extend(self.block_max_transfer_gas.to_bytes()?);
         buffer.extend(self.payment_args_max_length.to_bytes()?);
         buffer.extend(self.session_args_max_length.to_bytes()?);
         buffer.extend(self.native_transfer_minimum_motes.to_bytes()?);
+        buffer.extend(self.strict_account_ordering.to_bytes()?);
         Ok(buffer)
     }
 
@@ -118,9 +134,11 @@ impl ToBytes for DeployConfig {
             + self.block_max_transfer_count.serialized_length()
             + self.block_max_approval_count.serialized_length()
             + self.block_gas_limit.serialized_length()
+            + self.block_max_transfer_gas.serialized_length()
             + self.payment_args_max_length.serialized_length()
             + self.session_args_max_length.serialized_length()
             + self.native_transfer_minimum_motes.serialized_length()
+            + self.strict_account_ordering.serialized_length()
     }
 }
 
@@ -136,9 +154,11 @@ impl FromBytes for DeployConfig {
         let (block_max_transfer_count, remainder) = u32::from_bytes(remainder)?;
         let (block_max_approval_count, remainder) = u32::from_bytes(remainder)?;
         let (block_gas_limit, remainder) = u64::from_bytes(remainder)?;
+        let (block_max_transfer_gas, remainder) = u64::from_bytes(remainder)?;
         let (payment_args_max_length, remainder) = u32::from_bytes(remainder)?;
         let (session_args_max_length, remainder) = u32::from_bytes(remainder)?;
         let (native_transfer_minimum_motes, remainder) = u64::from_bytes(remainder)?;
+        let (strict_account_ordering, remainder) = bool::from_bytes(remainder)?;
         let config = DeployConfig {
             max_payment_cost,
             max_ttl,
@@ -149,9 +169,11 @@ impl FromBytes for DeployConfig {
             block_max_transfer_count,
             block_max_approval_count,
             block_gas_limit,
+            block_max_transfer_gas,
             payment_args_max_length,
             session_args_max_length,
             native_transfer_minimum_motes,
+            strict_account_ordering,
         };
         Ok((config, remainder))
     }
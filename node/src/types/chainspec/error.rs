@@ -31,6 +31,10 @@ pub enum Error {
     #[error("could not load the global state update: {0}")]
     LoadGlobalStateUpgrade(#[from] GlobalStateUpdateLoadError),
 
+    /// Error loading the global state migrations.
+    #[error("could not load the global state migrations: {0}")]
+    LoadGlobalStateMigrations(#[from] GlobalStateMigrationsLoadError),
+
     /// Failed to read the given directory.
     #[error("failed to read dir {}: {error}", dir.display())]
     ReadDir {
@@ -40,6 +44,31 @@ pub enum Error {
         error: io::Error,
     },
 
+    /// Error applying a chainspec overlay file.
+    #[error("could not apply chainspec overlay {}: {error}", path.display())]
+    ApplyOverlay {
+        /// The overlay file which could not be applied.
+        path: PathBuf,
+        /// The underlying error.
+        error: ChainspecOverlayError,
+    },
+
+    /// Error while encoding the chainspec with its overlays applied to TOML format.
+    #[error("encoding to TOML error: {0}")]
+    EncodingToToml(toml::ser::Error),
+
+    /// Error loading the detached signature over an upgrade.
+    #[error("could not load upgrade signature: {0}")]
+    LoadUpgradeSignature(ReadFileError),
+
+    /// Error decoding the detached signature over an upgrade.
+    #[error("decoding upgrade signature error: {0}")]
+    DecodingUpgradeSignature(casper_types::crypto::Error),
+
+    /// The upgrade is not signed by any of the trusted release keys.
+    #[error("upgrade is not signed by any of the trusted release keys")]
+    UntrustedUpgradeSignature,
+
     /// No subdirectory representing a semver version was found in the given directory.
     #[error("failed to get a valid version from subdirs in {}", dir.display())]
     NoVersionSubdirFound {
@@ -72,6 +101,50 @@ pub enum ChainspecAccountsLoadError {
     Crypto(#[from] casper_types::crypto::ErrorExt),
 }
 
+/// Error loading global state migrations file.
+#[derive(Debug, Error)]
+pub enum GlobalStateMigrationsLoadError {
+    /// Error loading the migrations file.
+    #[error("could not load the file: {0}")]
+    LoadFile(#[from] ReadFileError),
+
+    /// Error while decoding the migrations from TOML format.
+    #[error("decoding from TOML error: {0}")]
+    DecodingFromToml(#[from] toml::de::Error),
+
+    /// Error while decoding a serialized value from a base64 encoded string.
+    #[error("decoding from base64 error: {0}")]
+    DecodingFromBase64(#[from] base64::DecodeError),
+
+    /// Error while decoding a key from formatted string.
+    #[error("decoding from formatted string error: {0}")]
+    DecodingKeyFromStr(String),
+
+    /// The name of a key tag is not recognized.
+    #[error("unknown key tag: {0}")]
+    UnknownKeyTag(String),
+}
+
+/// Error applying a chainspec overlay file.
+#[derive(Debug, Error)]
+pub enum ChainspecOverlayError {
+    /// Error loading the overlay file.
+    #[error("could not load overlay: {0}")]
+    LoadOverlay(#[from] ReadFileError),
+
+    /// Error while decoding the overlay from TOML format.
+    #[error("decoding from TOML error: {0}")]
+    DecodingFromToml(#[from] toml::de::Error),
+
+    /// The overlay sets a key which is not present in the base chainspec.
+    #[error("unknown key {0}")]
+    UnknownKey(String),
+
+    /// The overlay replaces a table with a value or vice versa.
+    #[error("mismatched table {0}")]
+    MismatchedTable(String),
+}
+
 /// Error loading global state update file.
 #[derive(Debug, Error)]
 pub enum GlobalStateUpdateLoadError {
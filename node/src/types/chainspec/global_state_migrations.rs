@@ -0,0 +1,338 @@
+use std::{convert::TryFrom, path::Path};
+
+use datasize::DataSize;
+#[cfg(test)]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use casper_execution_engine::core::engine_state::Migration;
+#[cfg(test)]
+use casper_types::testing::TestRng;
+use casper_types::{
+    bytesrepr::{self, Bytes, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    file_utils, Key, KeyTag,
+};
+
+use super::error::GlobalStateMigrationsLoadError;
+
+const GLOBAL_STATE_MIGRATIONS_FILENAME: &str = "migrations.toml";
+
+const REWRITE_KEY_TAG: u8 = 0;
+const REENCODE_VALUES_TAG: u8 = 1;
+const SEED_VALUE_TAG: u8 = 2;
+
+/// A single migration as laid out in the TOML-encoded migrations file.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum GlobalStateMigrationEntry {
+    RewriteKey { from: String, to: String },
+    ReencodeValues { key_tag: String },
+    SeedValue { key: String, value: String },
+}
+
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GlobalStateMigrationsConfig {
+    migrations: Vec<GlobalStateMigrationEntry>,
+}
+
+impl GlobalStateMigrationsConfig {
+    /// Returns `Self` parsed from the migrations file in the given directory.
+    ///
+    /// If the file doesn't exist, returns `Ok(None)`.
+    pub(super) fn from_dir<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Option<Self>, GlobalStateMigrationsLoadError> {
+        let migrations_path = path.as_ref().join(GLOBAL_STATE_MIGRATIONS_FILENAME);
+        if !migrations_path.is_file() {
+            return Ok(None);
+        }
+        let bytes = file_utils::read_file(migrations_path)?;
+        let config: GlobalStateMigrationsConfig = toml::from_slice(&bytes)?;
+        Ok(Some(config))
+    }
+}
+
+/// A typed migration of the global state, run once at the activation point of an upgrade.
+///
+/// Values are stored as serialized `StoredValue`s, in the same way as for `GlobalStateUpdate`.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, DataSize, Debug)]
+pub enum GlobalStateMigration {
+    /// Writes the value stored under `from` to `to`.
+    RewriteKey { from: Key, to: Key },
+    /// Re-encodes all values stored under keys with the given tag.
+    ReencodeValues { key_tag: String },
+    /// Writes `value` under `key` unless a value is already stored there.
+    SeedValue { key: Key, value: Bytes },
+}
+
+impl GlobalStateMigration {
+    /// Converts `self` into a migration to be run by the execution engine.
+    pub(crate) fn to_migration(&self) -> Result<Migration, bytesrepr::Error> {
+        let migration = match self {
+            GlobalStateMigration::RewriteKey { from, to } => Migration::RewriteKey {
+                from: *from,
+                to: *to,
+            },
+            GlobalStateMigration::ReencodeValues { key_tag } => Migration::ReencodeValues {
+                key_tag: key_tag_from_name(key_tag).ok_or(bytesrepr::Error::Formatting)?,
+            },
+            GlobalStateMigration::SeedValue { key, value } => Migration::SeedValue {
+                key: *key,
+                value: bytesrepr::deserialize(value.clone().into())?,
+            },
+        };
+        Ok(migration)
+    }
+
+    #[cfg(test)]
+    fn random(rng: &mut TestRng) -> Self {
+        match rng.gen_range(0..3) {
+            0 => GlobalStateMigration::RewriteKey {
+                from: rng.gen(),
+                to: rng.gen(),
+            },
+            1 => GlobalStateMigration::ReencodeValues {
+                key_tag: "bid".to_string(),
+            },
+            _ => GlobalStateMigration::SeedValue {
+                key: rng.gen(),
+                value: rng.gen(),
+            },
+        }
+    }
+}
+
+/// Returns the key tag with the given name, which is the prefix of its formatted keys.
+fn key_tag_from_name(name: &str) -> Option<KeyTag> {
+    let key_tag = match name {
+        "account" => KeyTag::Account,
+        "hash" => KeyTag::Hash,
+        "uref" => KeyTag::URef,
+        "transfer" => KeyTag::Transfer,
+        "deploy" => KeyTag::DeployInfo,
+        "era" => KeyTag::EraInfo,
+        "balance" => KeyTag::Balance,
+        "bid" => KeyTag::Bid,
+        "withdraw" => KeyTag::Withdraw,
+        "dictionary" => KeyTag::Dictionary,
+        "system-contract-registry" => KeyTag::SystemContractRegistry,
+        "unbond" => KeyTag::Unbond,
+        "chainspec-registry" => KeyTag::ChainspecRegistry,
+        "message" => KeyTag::Message,
+        _ => return None,
+    };
+    Some(key_tag)
+}
+
+impl ToBytes for GlobalStateMigration {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        match self {
+            GlobalStateMigration::RewriteKey { from, to } => {
+                buffer.push(REWRITE_KEY_TAG);
+                buffer.extend(from.to_bytes()?);
+                buffer.extend(to.to_bytes()?);
+            }
+            GlobalStateMigration::ReencodeValues { key_tag } => {
+                buffer.push(REENCODE_VALUES_TAG);
+                buffer.extend(key_tag.to_bytes()?);
+            }
+            GlobalStateMigration::SeedValue { key, value } => {
+                buffer.push(SEED_VALUE_TAG);
+                buffer.extend(key.to_bytes()?);
+                buffer.extend(value.to_bytes()?);
+            }
+        }
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+            + match self {
+                GlobalStateMigration::RewriteKey { from, to } => {
+                    from.serialized_length() + to.serialized_length()
+                }
+                GlobalStateMigration::ReencodeValues { key_tag } => key_tag.serialized_length(),
+                GlobalStateMigration::SeedValue { key, value } => {
+                    key.serialized_length() + value.serialized_length()
+                }
+            }
+    }
+}
+
+impl FromBytes for GlobalStateMigration {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, remainder) = u8::from_bytes(bytes)?;
+        match tag {
+            REWRITE_KEY_TAG => {
+                let (from, remainder) = Key::from_bytes(remainder)?;
+                let (to, remainder) = Key::from_bytes(remainder)?;
+                Ok((GlobalStateMigration::RewriteKey { from, to }, remainder))
+            }
+            REENCODE_VALUES_TAG => {
+                let (key_tag, remainder) = String::from_bytes(remainder)?;
+                Ok((GlobalStateMigration::ReencodeValues { key_tag }, remainder))
+            }
+            SEED_VALUE_TAG => {
+                let (key, remainder) = Key::from_bytes(remainder)?;
+                let (value, remainder) = Bytes::from_bytes(remainder)?;
+                Ok((GlobalStateMigration::SeedValue { key, value }, remainder))
+            }
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+fn parse_key(key: &str) -> Result<Key, GlobalStateMigrationsLoadError> {
+    Key::from_formatted_str(key)
+        .map_err(|err| GlobalStateMigrationsLoadError::DecodingKeyFromStr(format!("{}", err)))
+}
+
+/// The migrations to run at the activation point of an upgrade, in order.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, DataSize, Debug)]
+pub struct GlobalStateMigrations(pub(crate) Vec<GlobalStateMigration>);
+
+#[cfg(test)]
+impl GlobalStateMigrations {
+    pub fn random(rng: &mut TestRng) -> Self {
+        let count = rng.gen_range(0..10);
+        GlobalStateMigrations(
+            (0..count)
+                .map(|_| GlobalStateMigration::random(rng))
+                .collect(),
+        )
+    }
+}
+
+impl ToBytes for GlobalStateMigrations {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for GlobalStateMigrations {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (migrations, remainder) = Vec::<GlobalStateMigration>::from_bytes(bytes)?;
+        Ok((GlobalStateMigrations(migrations), remainder))
+    }
+}
+
+impl TryFrom<GlobalStateMigrationsConfig> for GlobalStateMigrations {
+    type Error = GlobalStateMigrationsLoadError;
+
+    fn try_from(config: GlobalStateMigrationsConfig) -> Result<Self, Self::Error> {
+        config
+            .migrations
+            .into_iter()
+            .map(|entry| {
+                let migration = match entry {
+                    GlobalStateMigrationEntry::RewriteKey { from, to } => {
+                        GlobalStateMigration::RewriteKey {
+                            from: parse_key(&from)?,
+                            to: parse_key(&to)?,
+                        }
+                    }
+                    GlobalStateMigrationEntry::ReencodeValues { key_tag } => {
+                        if key_tag_from_name(&key_tag).is_none() {
+                            return Err(GlobalStateMigrationsLoadError::UnknownKeyTag(key_tag));
+                        }
+                        GlobalStateMigration::ReencodeValues { key_tag }
+                    }
+                    GlobalStateMigrationEntry::SeedValue { key, value } => {
+                        GlobalStateMigration::SeedValue {
+                            key: parse_key(&key)?,
+                            value: base64::decode(&value)?.into(),
+                        }
+                    }
+                };
+                Ok(migration)
+            })
+            .collect::<Result<_, _>>()
+            .map(GlobalStateMigrations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{CLValue, StoredValue};
+
+    use super::*;
+
+    #[test]
+    fn global_state_migrations_bytesrepr_roundtrip() {
+        let mut rng = crate::new_rng();
+        let migrations = GlobalStateMigrations::random(&mut rng);
+        bytesrepr::test_serialization_roundtrip(&migrations);
+    }
+
+    #[test]
+    fn should_parse_migrations_config() {
+        let value = StoredValue::CLValue(CLValue::from_t(1_u64).unwrap());
+        let toml = format!(
+            r#"
+            [[migrations]]
+            type = "seed_value"
+            key = "{key}"
+            value = "{value}"
+
+            [[migrations]]
+            type = "rewrite_key"
+            from = "{key}"
+            to = "{other_key}"
+
+            [[migrations]]
+            type = "reencode_values"
+            key_tag = "bid"
+            "#,
+            key = Key::Hash([1; 32]).to_formatted_string(),
+            other_key = Key::Hash([2; 32]).to_formatted_string(),
+            value = base64::encode(value.to_bytes().unwrap()),
+        );
+        let config: GlobalStateMigrationsConfig = toml::from_str(&toml).unwrap();
+        let migrations = GlobalStateMigrations::try_from(config).unwrap();
+
+        let migrations: Vec<_> = migrations
+            .0
+            .iter()
+            .map(|migration| migration.to_migration().unwrap())
+            .collect();
+        assert_eq!(
+            migrations,
+            vec![
+                Migration::SeedValue {
+                    key: Key::Hash([1; 32]),
+                    value,
+                },
+                Migration::RewriteKey {
+                    from: Key::Hash([1; 32]),
+                    to: Key::Hash([2; 32]),
+                },
+                Migration::ReencodeValues {
+                    key_tag: KeyTag::Bid
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_key_tag() {
+        let config: GlobalStateMigrationsConfig = toml::from_str(
+            r#"
+            [[migrations]]
+            type = "reencode_values"
+            key_tag = "bids"
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            GlobalStateMigrations::try_from(config),
+            Err(GlobalStateMigrationsLoadError::UnknownKeyTag(_))
+        ));
+    }
+}
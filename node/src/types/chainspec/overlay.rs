@@ -0,0 +1,198 @@
+//! Support for chainspec overlay files.
+//!
+//! Overlays are TOML files in the `chainspec.d` directory next to `chainspec.toml`, each of which
+//! overrides individual keys of the base chainspec.  They are applied in lexicographical order of
+//! their file names, so e.g. `10-testnet.toml` is applied before `20-local.toml`.  Overlays may
+//! only override keys which are present in the base chainspec, ensuring that a misspelled key is
+//! rejected rather than silently ignored.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml::{value::Table, Value};
+
+use casper_types::file_utils;
+
+use super::error::{ChainspecOverlayError, Error};
+
+/// The name of the directory holding the chainspec overlay files.
+const CHAINSPEC_OVERLAYS_DIRNAME: &str = "chainspec.d";
+
+/// Applies all overlays found in `dir_path` to the base chainspec given as `chainspec_bytes`.
+///
+/// Returns the bytes unchanged if there are no overlays, otherwise the merged chainspec serialized
+/// to TOML.  As TOML tables are serialized with their keys in sorted order, the result only depends
+/// on the merged values, not on the layout of the original files.
+pub(crate) fn apply_overlays<P: AsRef<Path>>(
+    dir_path: P,
+    chainspec_bytes: Vec<u8>,
+) -> Result<Vec<u8>, Error> {
+    let overlay_paths = overlay_paths(dir_path.as_ref().join(CHAINSPEC_OVERLAYS_DIRNAME))?;
+    if overlay_paths.is_empty() {
+        return Ok(chainspec_bytes);
+    }
+
+    let mut chainspec: Table = toml::from_slice(&chainspec_bytes)?;
+    for path in overlay_paths {
+        let mut apply = || -> Result<(), ChainspecOverlayError> {
+            let overlay: Table = toml::from_slice(&file_utils::read_file(&path)?)?;
+            merge(&mut chainspec, overlay, "")
+        };
+        apply().map_err(|error| Error::ApplyOverlay { path, error })?;
+    }
+
+    let merged = toml::to_string(&Value::Table(chainspec)).map_err(Error::EncodingToToml)?;
+    Ok(merged.into_bytes())
+}
+
+/// Returns the paths of all `.toml` files in `overlays_dir`, sorted by file name.
+///
+/// If the directory doesn't exist, returns an empty `Vec`.
+fn overlay_paths(overlays_dir: PathBuf) -> Result<Vec<PathBuf>, Error> {
+    if !overlays_dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let read_dir_error = |error| Error::ReadDir {
+        dir: overlays_dir.clone(),
+        error,
+    };
+    let mut paths = vec![];
+    for entry in fs::read_dir(&overlays_dir).map_err(read_dir_error)? {
+        let path = entry.map_err(read_dir_error)?.path();
+        if path.is_file() && path.extension().map_or(false, |ext| ext == "toml") {
+            paths.push(path);
+        }
+    }
+    paths.sort_by(|lhs, rhs| lhs.file_name().cmp(&rhs.file_name()));
+    Ok(paths)
+}
+
+/// Recursively overrides the values in `base` with the ones in `overlay`.
+///
+/// `prefix` is the dotted path of `base` within the chainspec, used for error reporting.
+fn merge(base: &mut Table, overlay: Table, prefix: &str) -> Result<(), ChainspecOverlayError> {
+    for (key, overlay_value) in overlay {
+        let key_path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        let base_value = base
+            .get_mut(&key)
+            .ok_or_else(|| ChainspecOverlayError::UnknownKey(key_path.clone()))?;
+        match (base_value, overlay_value) {
+            (Value::Table(base_table), Value::Table(overlay_table)) => {
+                merge(base_table, overlay_table, &key_path)?
+            }
+            (Value::Table(_), _) | (_, Value::Table(_)) => {
+                return Err(ChainspecOverlayError::MismatchedTable(key_path))
+            }
+            (base_value, overlay_value) => *base_value = overlay_value,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(toml: &str) -> Table {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn should_override_nested_keys() {
+        let mut base = table(
+            r#"
+            [core]
+            era_duration = '41 seconds'
+            validator_slots = 5
+
+            [highway]
+            maximum_round_exponent = 19
+            "#,
+        );
+        let overlay = table(
+            r#"
+            [core]
+            validator_slots = 100
+            "#,
+        );
+
+        merge(&mut base, overlay, "").unwrap();
+
+        let expected = table(
+            r#"
+            [core]
+            era_duration = '41 seconds'
+            validator_slots = 100
+
+            [highway]
+            maximum_round_exponent = 19
+            "#,
+        );
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn should_reject_unknown_keys() {
+        let mut base = table("[core]\nvalidator_slots = 5");
+        let overlay = table("[core]\nvalidator_slot = 100");
+
+        match merge(&mut base, overlay, "") {
+            Err(ChainspecOverlayError::UnknownKey(key)) => assert_eq!(key, "core.validator_slot"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_reject_replacing_tables() {
+        let mut base = table("[core]\nvalidator_slots = 5");
+        let overlay = table("core = 5");
+
+        match merge(&mut base, overlay, "") {
+            Err(ChainspecOverlayError::MismatchedTable(key)) => assert_eq!(key, "core"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_apply_overlays_in_order() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let overlays_dir = tempdir.path().join(CHAINSPEC_OVERLAYS_DIRNAME);
+        fs::create_dir(&overlays_dir).unwrap();
+        fs::write(
+            overlays_dir.join("20-second.toml"),
+            "[core]\nvalidator_slots = 3",
+        )
+        .unwrap();
+        fs::write(
+            overlays_dir.join("10-first.toml"),
+            "[core]\nvalidator_slots = 2\nauction_delay = 7",
+        )
+        .unwrap();
+        fs::write(overlays_dir.join("ignored.txt"), "[core]\nunknown = 1").unwrap();
+
+        let base = b"[core]\nvalidator_slots = 1\nauction_delay = 1\n".to_vec();
+        let merged = apply_overlays(tempdir.path(), base).unwrap();
+
+        assert_eq!(
+            toml::from_slice::<Table>(&merged).unwrap(),
+            table("[core]\nvalidator_slots = 3\nauction_delay = 7")
+        );
+    }
+
+    #[test]
+    fn should_leave_chainspec_unchanged_without_overlays() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let base = b"# comment\n[core]\nvalidator_slots = 1\n".to_vec();
+
+        let merged = apply_overlays(tempdir.path(), base.clone()).unwrap();
+
+        assert_eq!(merged, base);
+    }
+}
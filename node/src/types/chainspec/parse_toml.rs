@@ -14,9 +14,11 @@ use casper_execution_engine::shared::{system_config::SystemConfig, wasm_config::
 use casper_types::{bytesrepr::Bytes, file_utils, ProtocolVersion};
 
 use super::{
-    accounts_config::AccountsConfig, global_state_update::GlobalStateUpdateConfig, ActivationPoint,
-    Chainspec, ChainspecRawBytes, CoreConfig, DeployConfig, Error, GlobalStateUpdate,
-    HighwayConfig, NetworkConfig, ProtocolConfig,
+    accounts_config::AccountsConfig, apply_overlays,
+    global_state_migrations::GlobalStateMigrationsConfig,
+    global_state_update::GlobalStateUpdateConfig, ActivationPoint, Chainspec, ChainspecRawBytes,
+    CoreConfig, DeployConfig, Error, GlobalStateMigrations, GlobalStateUpdate, HighwayConfig,
+    NetworkConfig, ProtocolConfig,
 };
 
 #[derive(PartialEq, Eq, Serialize, Deserialize, Debug)]
@@ -82,15 +84,19 @@ impl From<&Chainspec> for TomlChainspec {
 pub(super) fn parse_toml<P: AsRef<Path>>(
     chainspec_path: P,
 ) -> Result<(Chainspec, ChainspecRawBytes), Error> {
-    let chainspec_bytes =
-        file_utils::read_file(chainspec_path.as_ref()).map_err(Error::LoadChainspec)?;
-    let toml_chainspec: TomlChainspec = toml::from_slice(&chainspec_bytes)?;
-
     let root = chainspec_path
         .as_ref()
         .parent()
         .unwrap_or_else(|| Path::new(""));
 
+    // Overlays in chainspec.d, if any, are merged into the base chainspec before parsing it, so
+    // that both the parsed chainspec and its raw bytes reflect the merged result.
+    let chainspec_bytes = apply_overlays(
+        root,
+        file_utils::read_file(chainspec_path.as_ref()).map_err(Error::LoadChainspec)?,
+    )?;
+    let toml_chainspec: TomlChainspec = toml::from_slice(&chainspec_bytes)?;
+
     // accounts.toml must live in the same directory as chainspec.toml.
     let (accounts_config, maybe_genesis_accounts_bytes) = AccountsConfig::from_dir(root)?;
 
@@ -107,11 +113,18 @@ pub(super) fn parse_toml<P: AsRef<Path>>(
             None => (None, None),
         };
 
+    // migrations.toml must live in the same directory as chainspec.toml.
+    let global_state_migrations = GlobalStateMigrationsConfig::from_dir(root)?
+        .map(GlobalStateMigrations::try_from)
+        .transpose()?;
+
     let protocol_config = ProtocolConfig {
         version: toml_chainspec.protocol.version,
         hard_reset: toml_chainspec.protocol.hard_reset,
         activation_point: toml_chainspec.protocol.activation_point,
         global_state_update,
+        global_state_migrations,
+        effective_activation_era: None,
     };
 
     let chainspec = Chainspec {
@@ -131,3 +144,15 @@ pub(super) fn parse_toml<P: AsRef<Path>>(
 
     Ok((chainspec, chainspec_raw_bytes))
 }
+
+/// Parses the global state update and migrations files in `dir_path`, if present, in the same way
+/// as when the upgrade is activated, so that a broken upgrade package is caught ahead of time.
+pub(crate) fn validate_upgrade_files<P: AsRef<Path>>(dir_path: P) -> Result<(), Error> {
+    if let Some((config, _)) = GlobalStateUpdateConfig::from_dir(dir_path.as_ref())? {
+        GlobalStateUpdate::try_from(config)?;
+    }
+    if let Some(config) = GlobalStateMigrationsConfig::from_dir(dir_path.as_ref())? {
+        GlobalStateMigrations::try_from(config)?;
+    }
+    Ok(())
+}
@@ -12,10 +12,12 @@ use serde::{Deserialize, Serialize};
 use casper_types::testing::TestRng;
 use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
-    Key, ProtocolVersion, StoredValue,
+    EraId, Key, ProtocolVersion, StoredValue,
 };
 
-use super::{ActivationPoint, GlobalStateUpdate};
+use casper_execution_engine::core::engine_state::Migration;
+
+use super::{ActivationPoint, GlobalStateMigrations, GlobalStateUpdate};
 use crate::types::BlockHeader;
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, DataSize, Debug)]
@@ -30,6 +32,16 @@ pub struct ProtocolConfig {
     /// Any arbitrary updates we might want to make to the global state at the start of the era
     /// specified in the activation point.
     pub(crate) global_state_update: Option<GlobalStateUpdate>,
+    /// Typed migrations of the global state to run, in order, at the start of the era specified
+    /// in the activation point.
+    pub(crate) global_state_migrations: Option<GlobalStateMigrations>,
+    /// The first era of this protocol version, if the activation point is given as a timestamp
+    /// and the era has already been determined from the chain.
+    ///
+    /// This is derived from local storage and hence is not part of the chainspec's serialized
+    /// form.
+    #[serde(skip)]
+    pub(crate) effective_activation_era: Option<EraId>,
 }
 
 impl ProtocolConfig {
@@ -50,12 +62,38 @@ impl ProtocolConfig {
         Ok(update_mapping)
     }
 
+    /// The migrations to run on the global state as part of the upgrade, in order.
+    pub(crate) fn get_migrations(&self) -> Result<Vec<Migration>, bytesrepr::Error> {
+        match &self.global_state_migrations {
+            Some(GlobalStateMigrations(migrations)) => migrations
+                .iter()
+                .map(|migration| migration.to_migration())
+                .collect(),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Returns the first era of this protocol version.
+    ///
+    /// If the activation point is given as a timestamp and the era has not been determined yet,
+    /// this returns era 0.
+    pub(crate) fn activation_era(&self) -> EraId {
+        self.activation_point
+            .era_id()
+            .or(self.effective_activation_era)
+            .unwrap_or_else(|| EraId::from(0))
+    }
+
     /// Returns whether the block header belongs to the last block before the upgrade to the
     /// current protocol version.
     pub(crate) fn is_last_block_before_activation(&self, block_header: &BlockHeader) -> bool {
         block_header.protocol_version() < self.version
             && block_header.is_switch_block()
-            && ActivationPoint::EraId(block_header.next_block_era_id()) == self.activation_point
+            && match self.activation_point {
+                ActivationPoint::EraId(era_id) => block_header.next_block_era_id() == era_id,
+                ActivationPoint::Genesis(_) => false,
+                ActivationPoint::Timestamp { timestamp } => block_header.timestamp() >= timestamp,
+            }
     }
 
     /// Checks whether the values set in the config make sense and returns `false` if they don't.
@@ -78,6 +116,8 @@ impl ProtocolConfig {
             hard_reset: rng.gen(),
             activation_point,
             global_state_update: None,
+            global_state_migrations: None,
+            effective_activation_era: None,
         }
     }
 }
@@ -89,6 +129,7 @@ impl ToBytes for ProtocolConfig {
         buffer.extend(self.hard_reset.to_bytes()?);
         buffer.extend(self.activation_point.to_bytes()?);
         buffer.extend(self.global_state_update.to_bytes()?);
+        buffer.extend(self.global_state_migrations.to_bytes()?);
         Ok(buffer)
     }
 
@@ -97,6 +138,7 @@ impl ToBytes for ProtocolConfig {
             + self.hard_reset.serialized_length()
             + self.activation_point.serialized_length()
             + self.global_state_update.serialized_length()
+            + self.global_state_migrations.serialized_length()
     }
 }
 
@@ -108,11 +150,15 @@ impl FromBytes for ProtocolConfig {
         let (hard_reset, remainder) = bool::from_bytes(remainder)?;
         let (activation_point, remainder) = ActivationPoint::from_bytes(remainder)?;
         let (global_state_update, remainder) = Option::<GlobalStateUpdate>::from_bytes(remainder)?;
+        let (global_state_migrations, remainder) =
+            Option::<GlobalStateMigrations>::from_bytes(remainder)?;
         let protocol_config = ProtocolConfig {
             version,
             hard_reset,
             activation_point,
             global_state_update,
+            global_state_migrations,
+            effective_activation_era: None,
         };
         Ok((protocol_config, remainder))
     }
@@ -133,6 +179,27 @@ mod tests {
         bytesrepr::test_serialization_roundtrip(&activation_point);
     }
 
+    #[test]
+    fn should_parse_timestamp_activation_point() {
+        #[derive(Deserialize)]
+        struct Protocol {
+            activation_point: ActivationPoint,
+        }
+
+        let protocol: Protocol =
+            toml::from_str("activation_point = { timestamp = '2022-01-01T00:00:00.000Z' }")
+                .unwrap();
+        assert_eq!(
+            protocol.activation_point,
+            ActivationPoint::Timestamp {
+                timestamp: "2022-01-01T00:00:00.000Z".parse().unwrap()
+            }
+        );
+
+        let protocol: Protocol = toml::from_str("activation_point = 3").unwrap();
+        assert_eq!(protocol.activation_point, ActivationPoint::EraId(3.into()));
+    }
+
     #[test]
     fn protocol_config_bytesrepr_roundtrip() {
         let mut rng = crate::new_rng();
@@ -171,6 +238,14 @@ mod tests {
         assert!(protocol_config.is_valid());
     }
 
+    #[test]
+    fn protocol_config_with_migrations_bytesrepr_roundtrip() {
+        let mut rng = crate::new_rng();
+        let mut protocol_config = ProtocolConfig::random(&mut rng);
+        protocol_config.global_state_migrations = Some(GlobalStateMigrations::random(&mut rng));
+        bytesrepr::test_serialization_roundtrip(&protocol_config);
+    }
+
     #[test]
     fn should_recognize_blocks_before_activation_point() {
         let past_version = ProtocolVersion::from_parts(1, 0, 0);
@@ -186,6 +261,8 @@ mod tests {
             hard_reset: false,
             activation_point: ActivationPoint::EraId(upgrade_era),
             global_state_update: None,
+            global_state_migrations: None,
+            effective_activation_era: None,
         };
 
         // The block before this protocol version: a switch block with previous era and version.
@@ -211,4 +288,36 @@ mod tests {
             Block::random_with_specifics(&mut rng, previous_era, 100, past_version, false, None);
         assert!(!protocol_config.is_last_block_before_activation(block.header()));
     }
+    #[test]
+    fn should_recognize_blocks_before_timestamp_activation_point() {
+        let past_version = ProtocolVersion::from_parts(1, 0, 0);
+        let current_version = ProtocolVersion::from_parts(2, 0, 0);
+
+        let mut rng = crate::new_rng();
+        let block =
+            Block::random_with_specifics(&mut rng, EraId::from(4), 100, past_version, true, None);
+        let timestamp = block.header().timestamp();
+        let mut protocol_config = ProtocolConfig {
+            version: current_version,
+            hard_reset: false,
+            activation_point: ActivationPoint::Timestamp { timestamp },
+            global_state_update: None,
+            global_state_migrations: None,
+            effective_activation_era: None,
+        };
+
+        // A switch block of the previous version at or after the activation timestamp.
+        assert!(protocol_config.is_last_block_before_activation(block.header()));
+
+        // Not the activation point: before the activation timestamp.
+        protocol_config.activation_point = ActivationPoint::Timestamp {
+            timestamp: timestamp + 1.into(),
+        };
+        assert!(!protocol_config.is_last_block_before_activation(block.header()));
+
+        // The activation era is unknown until it has been determined from the chain.
+        assert_eq!(protocol_config.activation_era(), EraId::from(0));
+        protocol_config.effective_activation_era = Some(EraId::from(5));
+        assert_eq!(protocol_config.activation_era(), EraId::from(5));
+    }
 }
@@ -0,0 +1,137 @@
+//! Support for verifying detached signatures over staged upgrades.
+//!
+//! A release may ship a `chainspec.sig` file alongside its `chainspec.toml`, holding the
+//! hex-encoded signature over the digest returned by [`upgrade_package_digest`], i.e. over the
+//! chainspec and the optional global state update.  If the operator configures trusted release
+//! keys, a staged upgrade is only activated if it is signed by one of them.
+
+use std::path::Path;
+
+use casper_hashing::Digest;
+use casper_types::{crypto, file_utils, AsymmetricType, PublicKey, Signature};
+
+use super::{error::Error, CHAINSPEC_FILENAME};
+
+/// The name of the file holding the detached signature over an upgrade package.
+const UPGRADE_SIGNATURE_FILENAME: &str = "chainspec.sig";
+/// The name of the global state update file, which is covered by the signature if present.
+const GLOBAL_STATE_FILENAME: &str = "global_state.toml";
+
+/// Returns the digest signed by the release keys for the upgrade package in `dir_path`.
+fn upgrade_package_digest<P: AsRef<Path>>(dir_path: P) -> Result<Digest, Error> {
+    let chainspec_bytes = file_utils::read_file(dir_path.as_ref().join(CHAINSPEC_FILENAME))
+        .map_err(Error::LoadUpgradePoint)?;
+    let global_state_path = dir_path.as_ref().join(GLOBAL_STATE_FILENAME);
+    let global_state_bytes = if global_state_path.is_file() {
+        file_utils::read_file(global_state_path).map_err(Error::LoadUpgradePoint)?
+    } else {
+        vec![]
+    };
+    Ok(Digest::hash_pair(
+        Digest::hash(chainspec_bytes),
+        Digest::hash(global_state_bytes),
+    ))
+}
+
+/// Verifies that the upgrade package in `dir_path` is signed by one of `trusted_release_keys`.
+///
+/// If no trusted release keys are configured, verification is disabled and this returns `Ok`.
+pub(crate) fn verify_upgrade_signature<P: AsRef<Path>>(
+    dir_path: P,
+    trusted_release_keys: &[PublicKey],
+) -> Result<(), Error> {
+    if trusted_release_keys.is_empty() {
+        return Ok(());
+    }
+
+    let signature_bytes = file_utils::read_file(dir_path.as_ref().join(UPGRADE_SIGNATURE_FILENAME))
+        .map_err(Error::LoadUpgradeSignature)?;
+    let signature = Signature::from_hex(String::from_utf8_lossy(&signature_bytes).trim())
+        .map_err(Error::DecodingUpgradeSignature)?;
+    let digest = upgrade_package_digest(dir_path)?;
+
+    if trusted_release_keys
+        .iter()
+        .any(|public_key| crypto::verify(digest, &signature, public_key).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(Error::UntrustedUpgradeSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use casper_types::SecretKey;
+
+    use super::*;
+
+    fn write_package(dir: &Path) {
+        fs::write(
+            dir.join(CHAINSPEC_FILENAME),
+            "[protocol]\nversion = '2.0.0'",
+        )
+        .unwrap();
+        fs::write(dir.join(GLOBAL_STATE_FILENAME), "[[entries]]").unwrap();
+    }
+
+    fn sign_package(dir: &Path, secret_key: &SecretKey) {
+        let digest = upgrade_package_digest(dir).unwrap();
+        let signature = crypto::sign(digest, secret_key, &PublicKey::from(secret_key));
+        fs::write(dir.join(UPGRADE_SIGNATURE_FILENAME), signature.to_hex()).unwrap();
+    }
+
+    #[test]
+    fn should_verify_signature_by_trusted_key() {
+        let mut rng = crate::new_rng();
+        let tempdir = tempfile::tempdir().unwrap();
+        write_package(tempdir.path());
+        let secret_key = SecretKey::random(&mut rng);
+        sign_package(tempdir.path(), &secret_key);
+
+        let other_key = PublicKey::from(&SecretKey::random(&mut rng));
+        let trusted_release_keys = vec![other_key, PublicKey::from(&secret_key)];
+        verify_upgrade_signature(tempdir.path(), &trusted_release_keys).unwrap();
+    }
+
+    #[test]
+    fn should_reject_untrusted_or_tampered_packages() {
+        let mut rng = crate::new_rng();
+        let tempdir = tempfile::tempdir().unwrap();
+        write_package(tempdir.path());
+        let secret_key = SecretKey::random(&mut rng);
+        sign_package(tempdir.path(), &secret_key);
+
+        // Signed by a key which isn't trusted.
+        let other_key = PublicKey::from(&SecretKey::random(&mut rng));
+        assert!(matches!(
+            verify_upgrade_signature(tempdir.path(), &[other_key]),
+            Err(Error::UntrustedUpgradeSignature)
+        ));
+
+        // Tampered global state update.
+        let trusted_release_keys = vec![PublicKey::from(&secret_key)];
+        fs::write(tempdir.path().join(GLOBAL_STATE_FILENAME), "").unwrap();
+        assert!(matches!(
+            verify_upgrade_signature(tempdir.path(), &trusted_release_keys),
+            Err(Error::UntrustedUpgradeSignature)
+        ));
+
+        // Missing signature.
+        fs::remove_file(tempdir.path().join(UPGRADE_SIGNATURE_FILENAME)).unwrap();
+        assert!(matches!(
+            verify_upgrade_signature(tempdir.path(), &trusted_release_keys),
+            Err(Error::LoadUpgradeSignature(_))
+        ));
+    }
+
+    #[test]
+    fn should_skip_verification_without_trusted_keys() {
+        let tempdir = tempfile::tempdir().unwrap();
+        write_package(tempdir.path());
+
+        verify_upgrade_signature(tempdir.path(), &[]).unwrap();
+    }
+}
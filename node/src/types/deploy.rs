@@ -4,7 +4,8 @@
 use std::{
     array::TryFromSliceError,
     cmp,
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    convert::TryInto,
     error::Error as StdError,
     fmt::{self, Debug, Display, Formatter},
     hash,
@@ -36,10 +37,12 @@ use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
     crypto, runtime_args,
     system::standard_payment::ARG_AMOUNT,
-    ExecutionResult, Motes, PublicKey, RuntimeArgs, SecretKey, Signature, TimeDiff, Timestamp,
-    U512,
+    ExecutionResult, Motes, PublicKey, RuntimeArgs, SecretKey, Signature, SigningContext, TimeDiff,
+    Timestamp, U512,
 };
 
+#[cfg(test)]
+use super::DeployBuilder;
 use super::{BlockHash, BlockHashAndHeight, Item, Tag};
 use crate::{
     components::block_proposer::DeployInfo,
@@ -48,6 +51,15 @@ use crate::{
     utils::{ds, DisplayIter},
 };
 
+/// The name of the payment code runtime argument holding a deploy's sequence number, which is
+/// required if the chainspec enables strict account ordering.
+pub const ARG_SEQUENCE_NUMBER: &str = "sequence_number";
+
+/// The name of the optional payment code runtime argument holding a deploy's replacement tag.  A
+/// pending deploy is replaced by a deploy from the same account with the same tag which offers a
+/// higher price.
+pub const ARG_REPLACEMENT_TAG: &str = "replacement_tag";
+
 static DEPLOY: Lazy<Deploy> = Lazy::new(|| {
     let payment_args = runtime_args! {
         "amount" => 1000
@@ -206,6 +218,18 @@ pub enum DeployConfigurationFailure {
         /// The chainspec limit for max_associated_keys.
         max_associated_keys: u32,
     },
+
+    /// Missing payment "sequence_number" runtime argument, required by strict account ordering.
+    #[error("missing payment 'sequence_number' runtime argument")]
+    MissingSequenceNumber,
+
+    /// Failed to parse payment "sequence_number" runtime argument.
+    #[error("failed to parse payment 'sequence_number' as u64")]
+    FailedToParseSequenceNumber,
+
+    /// Failed to parse payment "replacement_tag" runtime argument.
+    #[error("failed to parse payment 'replacement_tag' as u64")]
+    FailedToParseReplacementTag,
 }
 
 /// Error returned when a Deploy is too large.
@@ -532,10 +556,14 @@ pub struct Approval {
 }
 
 impl Approval {
-    /// Creates an approval for the given deploy hash using the given secret key.
+    /// Creates an approval for the given deploy hash using the given secret key, signed in the
+    /// deploy approval signing context.
+    ///
+    /// Such approvals are accepted both before and after signing contexts are activated.
     pub fn create(hash: &DeployHash, secret_key: &SecretKey) -> Self {
         let signer = PublicKey::from(secret_key);
-        let signature = crypto::sign(hash, secret_key, &signer);
+        let signature =
+            crypto::sign_with_context(hash, SigningContext::DeployApproval, secret_key, &signer);
         Self { signer, signature }
     }
 
@@ -650,6 +678,34 @@ impl AsRef<BTreeSet<Approval>> for FinalizedApprovals {
     }
 }
 
+#[cfg(any(feature = "testing", test))]
+impl FinalizedApprovals {
+    /// Returns a random `FinalizedApprovals`.
+    pub fn random(rng: &mut TestRng) -> Self {
+        let approvals = (0..rng.gen_range(1..4))
+            .map(|_| Approval::random(rng))
+            .collect();
+        FinalizedApprovals(approvals)
+    }
+}
+
+impl ToBytes for FinalizedApprovals {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for FinalizedApprovals {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (approvals, bytes) = BTreeSet::<Approval>::from_bytes(bytes)?;
+        Ok((FinalizedApprovals(approvals), bytes))
+    }
+}
+
 /// A set of finalized approvals together with data identifying the deploy.
 #[derive(DataSize, Debug, Deserialize, Eq, PartialEq, Serialize, Clone)]
 pub struct FinalizedApprovalsWithId {
@@ -669,7 +725,7 @@ impl FinalizedApprovalsWithId {
     }
 }
 
-/// Error type containing the error message passed from `crypto::verify`
+/// Error type containing the error message passed from `crypto::verify_with_context`
 #[derive(Debug, Error)]
 #[error("invalid approval from {signer}: {error}")]
 pub struct FinalizedApprovalsVerificationError {
@@ -685,12 +741,19 @@ impl Item for FinalizedApprovalsWithId {
     const ID_IS_COMPLETE_ITEM: bool = false;
 
     fn validate(&self) -> Result<(), Self::ValidationError> {
+        // Finalized approvals are validated without knowing the era of the block they were
+        // finalized in, so approvals without a signing context are accepted as well.
         for approval in &self.approvals.0 {
-            crypto::verify(&self.id, approval.signature(), approval.signer()).map_err(|err| {
-                FinalizedApprovalsVerificationError {
-                    signer: approval.signer().clone(),
-                    error: format!("{}", err),
-                }
+            crypto::verify_with_context_if_active(
+                &self.id,
+                SigningContext::DeployApproval,
+                false,
+                approval.signature(),
+                approval.signer(),
+            )
+            .map_err(|err| FinalizedApprovalsVerificationError {
+                signer: approval.signer().clone(),
+                error: format!("{}", err),
             })?;
         }
         Ok(())
@@ -721,9 +784,11 @@ pub struct Deploy {
     payment: ExecutableDeployItem,
     session: ExecutableDeployItem,
     approvals: BTreeSet<Approval>,
+    /// The cached result of validating the deploy: if valid, the index of the first approval not
+    /// signed in the deploy approval signing context, if any.
     #[serde(skip)]
     #[data_size(with = ds::once_cell)]
-    is_valid: OnceCell<Result<(), DeployConfigurationFailure>>,
+    is_valid: OnceCell<Result<Option<usize>, DeployConfigurationFailure>>,
 }
 
 impl hash::Hash for Deploy {
@@ -802,12 +867,68 @@ impl Deploy {
         session: ExecutableDeployItem,
         secret_key: &SecretKey,
         account: Option<PublicKey>,
+    ) -> Deploy {
+        let account = account.unwrap_or_else(|| PublicKey::from(secret_key));
+        let mut deploy = Deploy::new_unsigned(
+            timestamp,
+            ttl,
+            gas_price,
+            dependencies,
+            chain_name,
+            payment,
+            session,
+            account,
+        );
+        deploy.sign(secret_key);
+        deploy
+    }
+
+    /// Constructs a new `Deploy` approved by `account` with a placeholder signature.
+    ///
+    /// The approval is not valid, so such a deploy is only fit for speculative execution, and must
+    /// never be stored or gossiped.
+    pub(crate) fn new_with_dummy_approval(
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        gas_price: u64,
+        chain_name: String,
+        payment: ExecutableDeployItem,
+        session: ExecutableDeployItem,
+        account: PublicKey,
+    ) -> Deploy {
+        let mut deploy = Deploy::new_unsigned(
+            timestamp,
+            ttl,
+            gas_price,
+            vec![],
+            chain_name,
+            payment,
+            session,
+            account.clone(),
+        );
+        deploy.approvals.insert(Approval {
+            signer: account,
+            signature: Signature::ed25519([0; Signature::ED25519_LENGTH])
+                .expect("should create placeholder signature"),
+        });
+        deploy
+    }
+
+    /// Constructs a new `Deploy` without any approvals.
+    #[allow(clippy::too_many_arguments)]
+    fn new_unsigned(
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        gas_price: u64,
+        dependencies: Vec<DeployHash>,
+        chain_name: String,
+        payment: ExecutableDeployItem,
+        session: ExecutableDeployItem,
+        account: PublicKey,
     ) -> Deploy {
         let serialized_body = serialize_body(&payment, &session);
         let body_hash = Digest::hash(&serialized_body);
 
-        let account = account.unwrap_or_else(|| PublicKey::from(secret_key));
-
         // Remove duplicates.
         let dependencies = dependencies.into_iter().unique().collect();
         let header = DeployHeader {
@@ -822,17 +943,14 @@ impl Deploy {
         let serialized_header = serialize_header(&header);
         let hash = DeployHash::new(Digest::hash(&serialized_header));
 
-        let mut deploy = Deploy {
+        Deploy {
             hash,
             header,
             payment,
             session,
             approvals: BTreeSet::new(),
             is_valid: OnceCell::new(),
-        };
-
-        deploy.sign(secret_key);
-        deploy
+        }
     }
 
     /// Adds a signature of this deploy's hash to its approvals.
@@ -909,10 +1027,44 @@ impl Deploy {
                 .map_err(|_| Error::InvalidPayment)?;
             Motes::new(value)
         };
+        let session_hash = Digest::hash(
+            self.session()
+                .to_bytes()
+                .unwrap_or_else(|error| panic!("should serialize session code: {}", error)),
+        );
         Ok(DeployInfo {
             header,
             payment_amount,
             size,
+            session_hash,
+            sequence_number: self.sequence_number().and_then(Result::ok),
+            replacement_tag: self.replacement_tag().and_then(Result::ok),
+            signed_by_account: self
+                .approvals()
+                .iter()
+                .any(|approval| approval.signer() == self.header().account()),
+        })
+    }
+
+    /// Returns the sequence number given as the payment code's "sequence_number" runtime argument,
+    /// if any, or an error if it is not a `u64`.
+    pub fn sequence_number(&self) -> Option<Result<u64, DeployConfigurationFailure>> {
+        self.payment().args().get(ARG_SEQUENCE_NUMBER).map(|value| {
+            value
+                .clone()
+                .into_t::<u64>()
+                .map_err(|_| DeployConfigurationFailure::FailedToParseSequenceNumber)
+        })
+    }
+
+    /// Returns the replacement tag given as the payment code's "replacement_tag" runtime argument,
+    /// if any, or an error if it is not a `u64`.
+    pub fn replacement_tag(&self) -> Option<Result<u64, DeployConfigurationFailure>> {
+        self.payment().args().get(ARG_REPLACEMENT_TAG).map(|value| {
+            value
+                .clone()
+                .into_t::<u64>()
+                .map_err(|_| DeployConfigurationFailure::FailedToParseReplacementTag)
         })
     }
 
@@ -951,9 +1103,30 @@ impl Deploy {
     ///   * the deploy hash is correct (should be the hash of the header), and
     ///   * the body hash is correct (should be the hash of the body), and
     ///   * approvals are non empty, and
-    ///   * all approvals are valid signatures of the deploy hash
+    ///   * all approvals are valid signatures of the deploy hash, with or without the deploy
+    ///     approval signing context
     pub fn is_valid(&self) -> Result<(), DeployConfigurationFailure> {
-        self.is_valid.get_or_init(|| validate_deploy(self)).clone()
+        self.is_valid
+            .get_or_init(|| validate_deploy(self))
+            .clone()
+            .map(|_| ())
+    }
+
+    /// Returns `Ok(())` if the deploy is valid, and if `signing_context_active` is `true` all
+    /// approvals are signed in the deploy approval signing context.
+    pub(crate) fn is_valid_with_signing_context(
+        &self,
+        signing_context_active: bool,
+    ) -> Result<(), DeployConfigurationFailure> {
+        match self.is_valid.get_or_init(|| validate_deploy(self)) {
+            Ok(Some(index)) if signing_context_active => {
+                Err(DeployConfigurationFailure::InvalidApproval {
+                    index: *index,
+                    error_msg: String::from("not signed in the deploy approval signing context"),
+                })
+            }
+            result => result.clone().map(|_| ()),
+        }
     }
 
     /// Returns true if and only if:
@@ -1100,6 +1273,22 @@ impl Deploy {
             }
         }
 
+        if config.strict_account_ordering {
+            let sequence_number = self.sequence_number().ok_or_else(|| {
+                info!("missing payment 'sequence_number' runtime argument");
+                DeployConfigurationFailure::MissingSequenceNumber
+            })?;
+            if let Err(error) = sequence_number {
+                info!("failed to parse payment 'sequence_number' runtime argument as a u64");
+                return Err(error);
+            }
+        }
+
+        if let Some(Err(error)) = self.replacement_tag() {
+            info!("failed to parse payment 'replacement_tag' runtime argument as a u64");
+            return Err(error);
+        }
+
         Ok(())
     }
 }
@@ -1220,65 +1409,32 @@ impl Deploy {
     /// Returns a random deploy for a native transfer.
     pub(crate) fn random_valid_native_transfer(rng: &mut TestRng) -> Self {
         let deploy = Self::random(rng);
-        let transfer_args = runtime_args! {
-            "amount" => *MAX_PAYMENT,
-            "source" => PublicKey::random(rng).to_account_hash(),
-            "target" => PublicKey::random(rng).to_account_hash(),
-        };
-        let payment_args = runtime_args! {
-            "amount" => U512::from(10),
-        };
-        let session = ExecutableDeployItem::Transfer {
-            args: transfer_args,
-        };
-        let payment = ExecutableDeployItem::ModuleBytes {
-            module_bytes: Bytes::new(),
-            args: payment_args,
-        };
+        let target = PublicKey::random(rng).to_account_hash();
         let secret_key = SecretKey::random(rng);
-        Deploy::new(
-            Timestamp::now(),
-            deploy.header.ttl,
-            deploy.header.gas_price,
-            deploy.header.dependencies,
-            deploy.header.chain_name,
-            payment,
-            session,
-            &secret_key,
-            None,
-        )
+        DeployBuilder::new_transfer(*MAX_PAYMENT, target, None)
+            .with_chain_name(deploy.header.chain_name)
+            .with_ttl(deploy.header.ttl)
+            .with_gas_price(deploy.header.gas_price)
+            .with_dependencies(deploy.header.dependencies)
+            .with_standard_payment(10)
+            .with_secret_key(&secret_key)
+            .build()
+            .expect("should build transfer")
     }
 
     /// Returns a random deploy for a native transfer with no dependencies.
     pub(crate) fn random_valid_native_transfer_without_deps(rng: &mut TestRng) -> Self {
         let deploy = Self::random(rng);
-        let transfer_args = runtime_args! {
-            "amount" => *MAX_PAYMENT,
-            "source" => PublicKey::random(rng).to_account_hash(),
-            "target" => PublicKey::random(rng).to_account_hash(),
-        };
-        let payment_args = runtime_args! {
-            "amount" => U512::from(10),
-        };
-        let session = ExecutableDeployItem::Transfer {
-            args: transfer_args,
-        };
-        let payment = ExecutableDeployItem::ModuleBytes {
-            module_bytes: Bytes::new(),
-            args: payment_args,
-        };
+        let target = PublicKey::random(rng).to_account_hash();
         let secret_key = SecretKey::random(rng);
-        Deploy::new(
-            Timestamp::now(),
-            deploy.header.ttl,
-            deploy.header.gas_price,
-            vec![],
-            deploy.header.chain_name,
-            payment,
-            session,
-            &secret_key,
-            None,
-        )
+        DeployBuilder::new_transfer(*MAX_PAYMENT, target, None)
+            .with_chain_name(deploy.header.chain_name)
+            .with_ttl(deploy.header.ttl)
+            .with_gas_price(deploy.header.gas_price)
+            .with_standard_payment(10)
+            .with_secret_key(&secret_key)
+            .build()
+            .expect("should build transfer")
     }
 
     /// Returns a random invalid deploy without a payment amount specified.
@@ -1488,6 +1644,15 @@ impl Deploy {
         Self::random_transfer_with_session(rng, session)
     }
 
+    /// Returns a random deploy with session module bytes which are not valid wasm.
+    pub(crate) fn random_with_invalid_session_module_bytes(rng: &mut TestRng) -> Self {
+        let session = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::from(vec![0u8; 8]),
+            args: Default::default(),
+        };
+        Self::random_transfer_with_session(rng, session)
+    }
+
     /// Returns a random invalid deploy with an expired TTL.
     pub(crate) fn random_expired_deploy(rng: &mut TestRng) -> Self {
         let deploy = Self::random_valid_native_transfer(rng);
@@ -1506,6 +1671,24 @@ impl Deploy {
         )
     }
 
+    /// Returns a random deploy whose timestamp lies `time_ahead` in the future.
+    pub(crate) fn random_future_dated_deploy(rng: &mut TestRng, time_ahead: TimeDiff) -> Self {
+        let deploy = Self::random_valid_native_transfer(rng);
+        let secret_key = SecretKey::random(rng);
+
+        Deploy::new(
+            Timestamp::now() + time_ahead,
+            deploy.header.ttl,
+            deploy.header.gas_price,
+            deploy.header.dependencies,
+            deploy.header.chain_name,
+            deploy.payment,
+            deploy.session,
+            &secret_key,
+            None,
+        )
+    }
+
     /// Returns a random deploy with native transfer as payment code.
     pub(crate) fn random_with_native_transfer_in_payment_logic(rng: &mut TestRng) -> Self {
         let transfer_args = runtime_args! {
@@ -1579,8 +1762,9 @@ fn serialize_body(payment: &ExecutableDeployItem, session: &ExecutableDeployItem
 }
 
 // Computationally expensive validity check for a given deploy instance, including
-// asymmetric_key signing verification.
-fn validate_deploy(deploy: &Deploy) -> Result<(), DeployConfigurationFailure> {
+// asymmetric_key signing verification.  Returns the index of the first approval which is only
+// valid without the deploy approval signing context, if any.
+fn validate_deploy(deploy: &Deploy) -> Result<Option<usize>, DeployConfigurationFailure> {
     if deploy.approvals.is_empty() {
         warn!(?deploy, "deploy has no approvals");
         return Err(DeployConfigurationFailure::EmptyApprovals);
@@ -1588,7 +1772,18 @@ fn validate_deploy(deploy: &Deploy) -> Result<(), DeployConfigurationFailure> {
 
     deploy.has_valid_hash()?;
 
+    let mut first_without_context = None;
     for (index, approval) in deploy.approvals.iter().enumerate() {
+        if crypto::verify_with_context(
+            &deploy.hash,
+            SigningContext::DeployApproval,
+            &approval.signature,
+            &approval.signer,
+        )
+        .is_ok()
+        {
+            continue;
+        }
         if let Err(error) = crypto::verify(&deploy.hash, &approval.signature, &approval.signer) {
             warn!(?deploy, "failed to verify approval {}: {}", index, error);
             return Err(DeployConfigurationFailure::InvalidApproval {
@@ -1596,9 +1791,10 @@ fn validate_deploy(deploy: &Deploy) -> Result<(), DeployConfigurationFailure> {
                 error_msg: error.to_string(),
             });
         }
+        first_without_context.get_or_insert(index);
     }
 
-    Ok(())
+    Ok(first_without_context)
 }
 
 impl Item for Deploy {
@@ -1680,6 +1876,60 @@ impl Default for DeployMetadataExt {
     }
 }
 
+#[cfg(any(feature = "testing", test))]
+impl DeployMetadata {
+    /// Returns a random `DeployMetadata`.
+    pub fn random(rng: &mut TestRng) -> Self {
+        let execution_results = (0..rng.gen_range(0..3))
+            .map(|_| (BlockHash::random(rng), rng.gen()))
+            .collect();
+        DeployMetadata { execution_results }
+    }
+}
+
+impl ToBytes for DeployMetadata {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        // Encoded like a `BTreeMap`, i.e. ordered by block hash, so the encoding is deterministic.
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        let count: u32 = self
+            .execution_results
+            .len()
+            .try_into()
+            .map_err(|_| bytesrepr::Error::NotRepresentable)?;
+        buffer.extend(count.to_bytes()?);
+        for (block_hash, execution_result) in self
+            .execution_results
+            .iter()
+            .sorted_by_key(|(block_hash, _)| **block_hash)
+        {
+            buffer.extend(block_hash.to_bytes()?);
+            buffer.extend(execution_result.to_bytes()?);
+        }
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        bytesrepr::U32_SERIALIZED_LENGTH
+            + self
+                .execution_results
+                .iter()
+                .map(|(block_hash, execution_result)| {
+                    block_hash.serialized_length() + execution_result.serialized_length()
+                })
+                .sum::<usize>()
+    }
+}
+
+impl FromBytes for DeployMetadata {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (execution_results, bytes) = BTreeMap::<BlockHash, ExecutionResult>::from_bytes(bytes)?;
+        let deploy_metadata = DeployMetadata {
+            execution_results: execution_results.into_iter().collect(),
+        };
+        Ok((deploy_metadata, bytes))
+    }
+}
+
 impl From<DeployMetadata> for DeployMetadataExt {
     fn from(deploy_metadata: DeployMetadata) -> Self {
         Self::Metadata(deploy_metadata)
@@ -1824,6 +2074,30 @@ mod tests {
         )
     }
 
+    #[test]
+    fn deploy_with_dummy_approval_should_be_approved_by_account_but_invalid() {
+        let mut rng = crate::new_rng();
+        let signed = create_deploy(&mut rng, DeployConfig::default().max_ttl, 0, "net-1");
+        let header = signed.header();
+        let unsigned = Deploy::new_with_dummy_approval(
+            header.timestamp(),
+            header.ttl(),
+            header.gas_price(),
+            header.chain_name().to_string(),
+            signed.payment().clone(),
+            signed.session().clone(),
+            header.account().clone(),
+        );
+
+        assert_eq!(unsigned.id(), signed.id());
+        let signers: Vec<_> = unsigned.approvals().iter().map(Approval::signer).collect();
+        assert_eq!(signers, vec![header.account()]);
+        assert!(matches!(
+            unsigned.is_valid(),
+            Err(DeployConfigurationFailure::InvalidApproval { .. })
+        ));
+    }
+
     #[test]
     fn is_valid() {
         let mut rng = crate::new_rng();
@@ -1836,11 +2110,35 @@ mod tests {
         deploy.is_valid().expect("should be valid");
         assert_eq!(
             deploy.is_valid.get(),
-            Some(&Ok(())),
+            Some(&Ok(None)),
             "is valid should be true"
         );
     }
 
+    #[test]
+    fn should_require_approvals_in_signing_context_only_if_active() {
+        let mut rng = crate::new_rng();
+        let deploy = create_deploy(&mut rng, DeployConfig::default().max_ttl, 0, "net-1");
+        assert_eq!(deploy.is_valid_with_signing_context(false), Ok(()));
+        assert_eq!(deploy.is_valid_with_signing_context(true), Ok(()));
+
+        // An approval signed without a context is only valid until contexts are active.
+        let secret_key = SecretKey::random(&mut rng);
+        let signer = PublicKey::from(&secret_key);
+        let mut legacy_deploy = deploy.clone();
+        legacy_deploy.approvals.clear();
+        legacy_deploy.is_valid = OnceCell::new();
+        legacy_deploy.approvals.insert(Approval {
+            signature: crypto::sign(&deploy.hash, &secret_key, &signer),
+            signer,
+        });
+        assert_eq!(legacy_deploy.is_valid_with_signing_context(false), Ok(()));
+        assert!(matches!(
+            legacy_deploy.is_valid_with_signing_context(true),
+            Err(DeployConfigurationFailure::InvalidApproval { index: 0, .. })
+        ));
+    }
+
     fn check_is_not_valid(invalid_deploy: Deploy, expected_error: DeployConfigurationFailure) {
         assert!(
             invalid_deploy.is_valid.get().is_none(),
@@ -1951,6 +2249,28 @@ mod tests {
             .expect("should be acceptable");
     }
 
+    #[test]
+    fn not_acceptable_due_to_missing_sequence_number() {
+        let mut rng = crate::new_rng();
+        let chain_name = "net-1";
+        let deploy_config = DeployConfig {
+            strict_account_ordering: true,
+            ..DeployConfig::default()
+        };
+
+        let deploy = create_deploy(
+            &mut rng,
+            deploy_config.max_ttl,
+            deploy_config.max_dependencies.into(),
+            chain_name,
+        );
+
+        assert_eq!(
+            deploy.is_config_compliant(chain_name, &deploy_config, DEFAULT_MAX_ASSOCIATED_KEYS),
+            Err(DeployConfigurationFailure::MissingSequenceNumber)
+        );
+    }
+
     #[test]
     fn not_acceptable_due_to_invalid_chain_name() {
         let mut rng = crate::new_rng();
@@ -0,0 +1,230 @@
+use thiserror::Error;
+
+use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::Bytes,
+    runtime_args,
+    system::{mint, standard_payment},
+    PublicKey, RuntimeArgs, SecretKey, TimeDiff, Timestamp, U512,
+};
+
+use super::{Deploy, DeployHash};
+
+/// The way in which building a [`Deploy`] via a [`DeployBuilder`] failed.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Error)]
+pub enum DeployBuilderError {
+    /// No chain name was provided.
+    #[error("missing chain name")]
+    MissingChainName,
+    /// No payment code was provided.
+    #[error("missing payment code")]
+    MissingPayment,
+    /// No secret key was provided to sign the deploy.
+    #[error("missing secret key")]
+    MissingSecretKey,
+}
+
+/// A builder for constructing and signing a [`Deploy`].
+///
+/// The chain name, the payment code and at least one secret key must be provided.  The timestamp
+/// defaults to the current time, the TTL to [`DeployBuilder::DEFAULT_TTL`] and the gas price to
+/// [`DeployBuilder::DEFAULT_GAS_PRICE`].  Unless set explicitly, the account is that of the first
+/// secret key; every secret key adds an approval.
+#[derive(Clone, Debug)]
+pub struct DeployBuilder<'a> {
+    chain_name: Option<String>,
+    timestamp: Option<Timestamp>,
+    ttl: TimeDiff,
+    gas_price: u64,
+    dependencies: Vec<DeployHash>,
+    payment: Option<ExecutableDeployItem>,
+    session: ExecutableDeployItem,
+    account: Option<PublicKey>,
+    secret_keys: Vec<&'a SecretKey>,
+}
+
+impl<'a> DeployBuilder<'a> {
+    /// The default time-to-live for deploys, i.e. 30 minutes.
+    pub const DEFAULT_TTL: TimeDiff = TimeDiff::from_seconds(30 * 60);
+    /// The default gas price for deploys.
+    pub const DEFAULT_GAS_PRICE: u64 = 1;
+
+    /// Returns a new `DeployBuilder` for a deploy executing the given session code.
+    pub fn new(session: ExecutableDeployItem) -> Self {
+        DeployBuilder {
+            chain_name: None,
+            timestamp: None,
+            ttl: Self::DEFAULT_TTL,
+            gas_price: Self::DEFAULT_GAS_PRICE,
+            dependencies: vec![],
+            payment: None,
+            session,
+            account: None,
+            secret_keys: vec![],
+        }
+    }
+
+    /// Returns a new `DeployBuilder` for a native transfer of `amount` motes from the account's
+    /// main purse to the main purse of `target`.
+    pub fn new_transfer<A: Into<U512>>(amount: A, target: AccountHash, id: Option<u64>) -> Self {
+        let args = runtime_args! {
+            mint::ARG_AMOUNT => amount.into(),
+            mint::ARG_TARGET => target,
+            mint::ARG_ID => id,
+        };
+        Self::new(ExecutableDeployItem::Transfer { args })
+    }
+
+    /// Sets the name of the chain the deploy is supposed to be executed on.
+    pub fn with_chain_name<C: Into<String>>(mut self, chain_name: C) -> Self {
+        self.chain_name = Some(chain_name.into());
+        self
+    }
+
+    /// Sets the time at which the deploy was created.
+    pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Sets how long the deploy stays valid after its timestamp.
+    pub fn with_ttl(mut self, ttl: TimeDiff) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the price in motes per unit of gas.
+    pub fn with_gas_price(mut self, gas_price: u64) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+
+    /// Sets the deploys which must be executed before this one.
+    pub fn with_dependencies(mut self, dependencies: Vec<DeployHash>) -> Self {
+        self.dependencies = dependencies;
+        self
+    }
+
+    /// Sets the payment code.
+    pub fn with_payment(mut self, payment: ExecutableDeployItem) -> Self {
+        self.payment = Some(payment);
+        self
+    }
+
+    /// Sets the payment code to the standard payment, paying `amount` motes.
+    pub fn with_standard_payment<A: Into<U512>>(self, amount: A) -> Self {
+        let args = runtime_args! {
+            standard_payment::ARG_AMOUNT => amount.into(),
+        };
+        self.with_payment(ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args,
+        })
+    }
+
+    /// Sets the account on whose behalf the deploy is executed, if it differs from the first
+    /// signer.
+    pub fn with_account(mut self, account: PublicKey) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Adds a secret key with which to sign the deploy.
+    pub fn with_secret_key(mut self, secret_key: &'a SecretKey) -> Self {
+        self.secret_keys.push(secret_key);
+        self
+    }
+
+    /// Returns the signed deploy, or an error if a required field is missing.
+    pub fn build(self) -> Result<Deploy, DeployBuilderError> {
+        let chain_name = self
+            .chain_name
+            .ok_or(DeployBuilderError::MissingChainName)?;
+        let payment = self.payment.ok_or(DeployBuilderError::MissingPayment)?;
+        let (first_key, other_keys) = self
+            .secret_keys
+            .split_first()
+            .ok_or(DeployBuilderError::MissingSecretKey)?;
+
+        let mut deploy = Deploy::new(
+            self.timestamp.unwrap_or_else(Timestamp::now),
+            self.ttl,
+            self.gas_price,
+            self.dependencies,
+            chain_name,
+            payment,
+            self.session,
+            first_key,
+            self.account,
+        );
+        for secret_key in other_keys {
+            deploy.sign(secret_key);
+        }
+        Ok(deploy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn should_build_deploy_signed_by_all_keys() {
+        let mut rng = TestRng::new();
+        let account_key = SecretKey::random(&mut rng);
+        let cosigner_key = SecretKey::random(&mut rng);
+
+        let deploy =
+            DeployBuilder::new_transfer(1_000_000_000_u64, AccountHash::new([1; 32]), None)
+                .with_chain_name("casper-example")
+                .with_standard_payment(10)
+                .with_secret_key(&account_key)
+                .with_secret_key(&cosigner_key)
+                .build()
+                .expect("should build deploy");
+
+        assert_eq!(deploy.is_valid(), Ok(()));
+        assert!(deploy.session().is_transfer());
+        assert_eq!(deploy.header().account(), &PublicKey::from(&account_key));
+        assert_eq!(deploy.header().ttl(), DeployBuilder::DEFAULT_TTL);
+        assert_eq!(deploy.approvals().len(), 2);
+    }
+
+    #[test]
+    fn should_fail_to_build_incomplete_deploy() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let session = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args: RuntimeArgs::new(),
+        };
+
+        assert_eq!(
+            DeployBuilder::new(session.clone())
+                .with_standard_payment(10)
+                .with_secret_key(&secret_key)
+                .build()
+                .unwrap_err(),
+            DeployBuilderError::MissingChainName
+        );
+        assert_eq!(
+            DeployBuilder::new(session.clone())
+                .with_chain_name("casper-example")
+                .with_secret_key(&secret_key)
+                .build()
+                .unwrap_err(),
+            DeployBuilderError::MissingPayment
+        );
+        assert_eq!(
+            DeployBuilder::new(session)
+                .with_chain_name("casper-example")
+                .with_standard_payment(10)
+                .build()
+                .unwrap_err(),
+            DeployBuilderError::MissingSecretKey
+        );
+    }
+}
@@ -0,0 +1,591 @@
+//! Rewards distributed to validators and their delegators at the end of an era, along with the
+//! stakes they were earned on and the validators evicted at the era's end.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use datasize::DataSize;
+#[cfg(any(feature = "testing", test))]
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "testing", test))]
+use casper_types::testing::TestRng;
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    system::auction::{DelegationRate, EraInfo, SeigniorageAllocation, SeigniorageRecipients},
+    EraId, PublicKey, U512,
+};
+
+/// The rewards distributed by the auction at the end of an era.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EraRewards {
+    /// The era the rewards were distributed for.
+    pub era_id: EraId,
+    /// The rewards of each validator of the era, ordered by public key.
+    pub validators: Vec<ValidatorRewards>,
+}
+
+/// The rewards a validator and its delegators received for an era.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorRewards {
+    /// The validator's public key.
+    pub validator_public_key: PublicKey,
+    /// The validator's reward in motes, including the commission.
+    pub amount: U512,
+    /// The commission in motes the validator charged its delegators.
+    pub commission: U512,
+    /// The delegation rate the commission was charged at, in percent.
+    pub delegation_rate: DelegationRate,
+    /// The rewards of the validator's delegators, ordered by public key.
+    pub delegators: Vec<DelegatorReward>,
+}
+
+/// The reward a delegator received for an era.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DelegatorReward {
+    /// The delegator's public key.
+    pub delegator_public_key: PublicKey,
+    /// The delegator's reward in motes.
+    pub amount: U512,
+}
+
+/// The stakes of the validators and delegators of an era, and the validators evicted by the auction
+/// at its end.
+///
+/// The Casper Network doesn't slash stakes, so evicted validators keep their stakes, but stop
+/// earning rewards until they reactivate their bids.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EraStakes {
+    /// The era the stakes were held in.
+    pub era_id: EraId,
+    /// The stakes of each validator of the era and of its delegators, ordered by public key.
+    pub validators: Vec<ValidatorStake>,
+    /// The validators evicted at the end of the era for equivocating, ordered by public key.
+    pub equivocators: Vec<PublicKey>,
+    /// The validators evicted at the end of the era for being inactive, ordered by public key.
+    pub inactive_validators: Vec<PublicKey>,
+}
+
+/// The stake of a validator and of its delegators in an era.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorStake {
+    /// The validator's public key.
+    pub validator_public_key: PublicKey,
+    /// The validator's own stake in motes.
+    pub stake: U512,
+    /// The stakes of the validator's delegators, ordered by public key.
+    pub delegators: Vec<DelegatorStake>,
+}
+
+/// The stake a delegator delegated to a validator in an era.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DelegatorStake {
+    /// The delegator's public key.
+    pub delegator_public_key: PublicKey,
+    /// The delegator's stake in motes.
+    pub stake: U512,
+}
+
+impl EraRewards {
+    /// Collects the rewards from the auction's allocations for `era_id`, taking stakes and
+    /// delegation rates from the era's seigniorage recipients.
+    pub(crate) fn new(
+        era_id: EraId,
+        era_info: &EraInfo,
+        seigniorage_recipients: &SeigniorageRecipients,
+    ) -> Self {
+        let mut validators: BTreeMap<PublicKey, ValidatorRewards> = BTreeMap::new();
+        for allocation in era_info.seigniorage_allocations() {
+            let validator_public_key = match allocation {
+                SeigniorageAllocation::Validator {
+                    validator_public_key,
+                    ..
+                }
+                | SeigniorageAllocation::Delegator {
+                    validator_public_key,
+                    ..
+                } => validator_public_key,
+            };
+            let rewards = validators
+                .entry(validator_public_key.clone())
+                .or_insert_with(|| ValidatorRewards {
+                    validator_public_key: validator_public_key.clone(),
+                    amount: U512::zero(),
+                    commission: U512::zero(),
+                    delegation_rate: seigniorage_recipients
+                        .get(validator_public_key)
+                        .map_or(0, |recipient| *recipient.delegation_rate()),
+                    delegators: vec![],
+                });
+            match allocation {
+                SeigniorageAllocation::Validator { amount, .. } => {
+                    rewards.amount = rewards.amount.saturating_add(*amount);
+                }
+                SeigniorageAllocation::Delegator {
+                    delegator_public_key,
+                    amount,
+                    ..
+                } => rewards.delegators.push(DelegatorReward {
+                    delegator_public_key: delegator_public_key.clone(),
+                    amount: *amount,
+                }),
+            }
+        }
+
+        for (validator_public_key, rewards) in validators.iter_mut() {
+            rewards
+                .delegators
+                .sort_by(|a, b| a.delegator_public_key.cmp(&b.delegator_public_key));
+            rewards.commission = seigniorage_recipients
+                .get(validator_public_key)
+                .map_or_else(U512::zero, |recipient| {
+                    // The auction splits the total reward proportionally to the stakes, and
+                    // charges the commission on the delegators' share.
+                    let total_reward = rewards
+                        .total_delegator_amount()
+                        .saturating_add(rewards.amount);
+                    let delegator_stake = recipient.delegator_total_stake().unwrap_or_default();
+                    let total_stake = recipient.total_stake().unwrap_or_default();
+                    if total_stake.is_zero() {
+                        return U512::zero();
+                    }
+                    total_reward.saturating_mul(delegator_stake) / total_stake
+                        * U512::from(rewards.delegation_rate)
+                        / U512::from(100)
+                });
+        }
+
+        EraRewards {
+            era_id,
+            validators: validators.into_values().collect(),
+        }
+    }
+
+    /// Returns the rewards of the given validator, if it was a validator in this era.
+    pub fn validator(&self, validator_public_key: &PublicKey) -> Option<&ValidatorRewards> {
+        self.validators
+            .iter()
+            .find(|rewards| rewards.validator_public_key == *validator_public_key)
+    }
+
+    /// Returns the rewards of the given delegator, along with the validator they were received
+    /// through.
+    pub fn delegator<'a>(
+        &'a self,
+        delegator_public_key: &'a PublicKey,
+    ) -> impl Iterator<Item = (&'a PublicKey, &'a DelegatorReward)> + 'a {
+        self.validators.iter().flat_map(move |rewards| {
+            rewards
+                .delegators
+                .iter()
+                .filter(move |reward| reward.delegator_public_key == *delegator_public_key)
+                .map(move |reward| (&rewards.validator_public_key, reward))
+        })
+    }
+}
+
+impl EraStakes {
+    /// Collects the stakes from the era's seigniorage recipients, and the evicted validators from
+    /// the era's report.
+    pub(crate) fn new<'a>(
+        era_id: EraId,
+        seigniorage_recipients: &SeigniorageRecipients,
+        equivocators: impl IntoIterator<Item = &'a PublicKey>,
+        inactive_validators: impl IntoIterator<Item = &'a PublicKey>,
+    ) -> Self {
+        let validators = seigniorage_recipients
+            .iter()
+            .map(|(validator_public_key, recipient)| ValidatorStake {
+                validator_public_key: validator_public_key.clone(),
+                stake: *recipient.stake(),
+                delegators: recipient
+                    .delegator_stake()
+                    .iter()
+                    .map(|(delegator_public_key, stake)| DelegatorStake {
+                        delegator_public_key: delegator_public_key.clone(),
+                        stake: *stake,
+                    })
+                    .collect(),
+            })
+            .collect();
+        let sorted = |public_keys: BTreeSet<&PublicKey>| public_keys.into_iter().cloned().collect();
+        EraStakes {
+            era_id,
+            validators,
+            equivocators: sorted(equivocators.into_iter().collect()),
+            inactive_validators: sorted(inactive_validators.into_iter().collect()),
+        }
+    }
+
+    /// Returns the stakes of the given validator, if it was a validator in this era.
+    pub fn validator(&self, validator_public_key: &PublicKey) -> Option<&ValidatorStake> {
+        self.validators
+            .iter()
+            .find(|stake| stake.validator_public_key == *validator_public_key)
+    }
+
+    /// Returns the stakes of the given delegator, along with the validator they were delegated
+    /// to.
+    pub fn delegator<'a>(
+        &'a self,
+        delegator_public_key: &'a PublicKey,
+    ) -> impl Iterator<Item = (&'a PublicKey, &'a DelegatorStake)> + 'a {
+        self.validators.iter().flat_map(move |validator| {
+            validator
+                .delegators
+                .iter()
+                .filter(move |stake| stake.delegator_public_key == *delegator_public_key)
+                .map(move |stake| (&validator.validator_public_key, stake))
+        })
+    }
+}
+
+impl ValidatorRewards {
+    /// Returns the total rewards of the validator's delegators.
+    pub fn total_delegator_amount(&self) -> U512 {
+        self.delegators.iter().fold(U512::zero(), |total, reward| {
+            total.saturating_add(reward.amount)
+        })
+    }
+}
+
+#[cfg(any(feature = "testing", test))]
+impl EraRewards {
+    /// Returns a random `EraRewards`.
+    pub fn random(rng: &mut TestRng) -> Self {
+        let validators = (0..rng.gen_range(0..3))
+            .map(|_| ValidatorRewards {
+                validator_public_key: PublicKey::random(rng),
+                amount: U512::from(rng.gen::<u64>()),
+                commission: U512::from(rng.gen::<u64>()),
+                delegation_rate: rng.gen_range(0..=100),
+                delegators: (0..rng.gen_range(0..3))
+                    .map(|_| DelegatorReward {
+                        delegator_public_key: PublicKey::random(rng),
+                        amount: U512::from(rng.gen::<u64>()),
+                    })
+                    .collect(),
+            })
+            .collect();
+        EraRewards {
+            era_id: rng.gen(),
+            validators,
+        }
+    }
+}
+
+#[cfg(any(feature = "testing", test))]
+impl EraStakes {
+    /// Returns a random `EraStakes`.
+    pub fn random(rng: &mut TestRng) -> Self {
+        let validators = (0..rng.gen_range(0..3))
+            .map(|_| ValidatorStake {
+                validator_public_key: PublicKey::random(rng),
+                stake: U512::from(rng.gen::<u64>()),
+                delegators: (0..rng.gen_range(0..3))
+                    .map(|_| DelegatorStake {
+                        delegator_public_key: PublicKey::random(rng),
+                        stake: U512::from(rng.gen::<u64>()),
+                    })
+                    .collect(),
+            })
+            .collect();
+        let public_keys = |rng: &mut TestRng| {
+            (0..rng.gen_range(0..3))
+                .map(|_| PublicKey::random(rng))
+                .collect()
+        };
+        EraStakes {
+            era_id: rng.gen(),
+            validators,
+            equivocators: public_keys(rng),
+            inactive_validators: public_keys(rng),
+        }
+    }
+}
+
+impl ToBytes for EraRewards {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.era_id.to_bytes()?);
+        buffer.extend(self.validators.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.era_id.serialized_length() + self.validators.serialized_length()
+    }
+}
+
+impl FromBytes for EraRewards {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (era_id, bytes) = EraId::from_bytes(bytes)?;
+        let (validators, bytes) = Vec::<ValidatorRewards>::from_bytes(bytes)?;
+        let value = EraRewards { era_id, validators };
+        Ok((value, bytes))
+    }
+}
+
+impl ToBytes for ValidatorRewards {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.validator_public_key.to_bytes()?);
+        buffer.extend(self.amount.to_bytes()?);
+        buffer.extend(self.commission.to_bytes()?);
+        buffer.extend(self.delegation_rate.to_bytes()?);
+        buffer.extend(self.delegators.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.validator_public_key.serialized_length()
+            + self.amount.serialized_length()
+            + self.commission.serialized_length()
+            + self.delegation_rate.serialized_length()
+            + self.delegators.serialized_length()
+    }
+}
+
+impl FromBytes for ValidatorRewards {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (validator_public_key, bytes) = PublicKey::from_bytes(bytes)?;
+        let (amount, bytes) = U512::from_bytes(bytes)?;
+        let (commission, bytes) = U512::from_bytes(bytes)?;
+        let (delegation_rate, bytes) = DelegationRate::from_bytes(bytes)?;
+        let (delegators, bytes) = Vec::<DelegatorReward>::from_bytes(bytes)?;
+        let value = ValidatorRewards {
+            validator_public_key,
+            amount,
+            commission,
+            delegation_rate,
+            delegators,
+        };
+        Ok((value, bytes))
+    }
+}
+
+impl ToBytes for DelegatorReward {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.delegator_public_key.to_bytes()?);
+        buffer.extend(self.amount.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.delegator_public_key.serialized_length() + self.amount.serialized_length()
+    }
+}
+
+impl FromBytes for DelegatorReward {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (delegator_public_key, bytes) = PublicKey::from_bytes(bytes)?;
+        let (amount, bytes) = U512::from_bytes(bytes)?;
+        let value = DelegatorReward {
+            delegator_public_key,
+            amount,
+        };
+        Ok((value, bytes))
+    }
+}
+
+impl ToBytes for EraStakes {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.era_id.to_bytes()?);
+        buffer.extend(self.validators.to_bytes()?);
+        buffer.extend(self.equivocators.to_bytes()?);
+        buffer.extend(self.inactive_validators.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.era_id.serialized_length()
+            + self.validators.serialized_length()
+            + self.equivocators.serialized_length()
+            + self.inactive_validators.serialized_length()
+    }
+}
+
+impl FromBytes for EraStakes {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (era_id, bytes) = EraId::from_bytes(bytes)?;
+        let (validators, bytes) = Vec::<ValidatorStake>::from_bytes(bytes)?;
+        let (equivocators, bytes) = Vec::<PublicKey>::from_bytes(bytes)?;
+        let (inactive_validators, bytes) = Vec::<PublicKey>::from_bytes(bytes)?;
+        let value = EraStakes {
+            era_id,
+            validators,
+            equivocators,
+            inactive_validators,
+        };
+        Ok((value, bytes))
+    }
+}
+
+impl ToBytes for ValidatorStake {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.validator_public_key.to_bytes()?);
+        buffer.extend(self.stake.to_bytes()?);
+        buffer.extend(self.delegators.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.validator_public_key.serialized_length()
+            + self.stake.serialized_length()
+            + self.delegators.serialized_length()
+    }
+}
+
+impl FromBytes for ValidatorStake {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (validator_public_key, bytes) = PublicKey::from_bytes(bytes)?;
+        let (stake, bytes) = U512::from_bytes(bytes)?;
+        let (delegators, bytes) = Vec::<DelegatorStake>::from_bytes(bytes)?;
+        let value = ValidatorStake {
+            validator_public_key,
+            stake,
+            delegators,
+        };
+        Ok((value, bytes))
+    }
+}
+
+impl ToBytes for DelegatorStake {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.delegator_public_key.to_bytes()?);
+        buffer.extend(self.stake.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.delegator_public_key.serialized_length() + self.stake.serialized_length()
+    }
+}
+
+impl FromBytes for DelegatorStake {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (delegator_public_key, bytes) = PublicKey::from_bytes(bytes)?;
+        let (stake, bytes) = U512::from_bytes(bytes)?;
+        let value = DelegatorStake {
+            delegator_public_key,
+            stake,
+        };
+        Ok((value, bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{system::auction::SeigniorageRecipient, SecretKey};
+
+    use super::*;
+
+    fn public_key(byte: u8) -> PublicKey {
+        PublicKey::from(&SecretKey::ed25519_from_bytes([byte; SecretKey::ED25519_LENGTH]).unwrap())
+    }
+
+    #[test]
+    fn should_collect_rewards_and_commission() {
+        let validator = public_key(1);
+        let delegator_1 = public_key(2);
+        let delegator_2 = public_key(3);
+
+        // The validator and its delegators each stake a third of the total, so the delegators'
+        // share of a reward of 3000 is 2000, 10% of which is charged as commission.
+        let mut delegator_stake = BTreeMap::new();
+        delegator_stake.insert(delegator_1.clone(), U512::from(100));
+        delegator_stake.insert(delegator_2.clone(), U512::from(100));
+        let mut seigniorage_recipients = SeigniorageRecipients::new();
+        seigniorage_recipients.insert(
+            validator.clone(),
+            SeigniorageRecipient::new(U512::from(100), 10, delegator_stake),
+        );
+
+        let mut era_info = EraInfo::new();
+        era_info.seigniorage_allocations_mut().extend([
+            SeigniorageAllocation::delegator(
+                delegator_2.clone(),
+                validator.clone(),
+                U512::from(900),
+            ),
+            SeigniorageAllocation::validator(validator.clone(), U512::from(1200)),
+            SeigniorageAllocation::delegator(
+                delegator_1.clone(),
+                validator.clone(),
+                U512::from(900),
+            ),
+        ]);
+
+        let era_rewards = EraRewards::new(EraId::new(5), &era_info, &seigniorage_recipients);
+        let validator_rewards = era_rewards.validator(&validator).unwrap();
+        assert_eq!(validator_rewards.amount, U512::from(1200));
+        assert_eq!(validator_rewards.commission, U512::from(200));
+        assert_eq!(validator_rewards.delegation_rate, 10);
+        assert_eq!(validator_rewards.total_delegator_amount(), U512::from(1800));
+        assert!(validator_rewards
+            .delegators
+            .windows(2)
+            .all(|pair| pair[0].delegator_public_key < pair[1].delegator_public_key));
+
+        let delegator_rewards: Vec<_> = era_rewards.delegator(&delegator_2).collect();
+        assert_eq!(delegator_rewards.len(), 1);
+        assert_eq!(delegator_rewards[0].0, &validator);
+        assert_eq!(delegator_rewards[0].1.amount, U512::from(900));
+        assert!(era_rewards.validator(&delegator_1).is_none());
+    }
+
+    #[test]
+    fn should_collect_stakes_and_evictions() {
+        let validator_1 = public_key(1);
+        let validator_2 = public_key(2);
+        let delegator = public_key(3);
+
+        let mut seigniorage_recipients = SeigniorageRecipients::new();
+        seigniorage_recipients.insert(
+            validator_2.clone(),
+            SeigniorageRecipient::new(U512::from(300), 0, BTreeMap::new()),
+        );
+        seigniorage_recipients.insert(
+            validator_1.clone(),
+            SeigniorageRecipient::new(
+                U512::from(100),
+                10,
+                BTreeMap::from([(delegator.clone(), U512::from(50))]),
+            ),
+        );
+
+        let era_stakes = EraStakes::new(
+            EraId::new(5),
+            &seigniorage_recipients,
+            [&validator_2, &validator_2],
+            [&validator_2, &validator_1],
+        );
+        assert_eq!(era_stakes.validators.len(), 2);
+        assert_eq!(era_stakes.validators[0].validator_public_key, validator_1);
+        assert_eq!(
+            era_stakes.validator(&validator_2).unwrap().stake,
+            U512::from(300)
+        );
+        assert_eq!(era_stakes.equivocators, vec![validator_2.clone()]);
+        assert_eq!(
+            era_stakes.inactive_validators,
+            vec![validator_1.clone(), validator_2]
+        );
+
+        let delegator_stakes: Vec<_> = era_stakes.delegator(&delegator).collect();
+        assert_eq!(delegator_stakes.len(), 1);
+        assert_eq!(delegator_stakes[0].0, &validator_1);
+        assert_eq!(delegator_stakes[0].1.stake, U512::from(50));
+    }
+}
@@ -23,6 +23,13 @@ pub enum ExitCode {
     /// It is no longer used, but we keep it here to avoid it being reassigned to other features.
     #[doc(hidden)]
     DowngradeVersion = 102,
+    /// The process should exit with `103`, as the reactor stalled while handling an event and
+    /// `supervisor.restart_on_stall` is enabled.  The node should be restarted.
+    Stalled = 103,
+    /// The process should exit with `104`, as it stored the block at the shutdown point set via
+    /// `node.shutdown_at_height`, `node.shutdown_at_era` or the diagnostics port.  The node should
+    /// not be restarted until the shutdown point has been changed.
+    ReachedShutdownPoint = 104,
     /// The exit code Rust uses by default when interrupted via an `INT` signal.
     SigInt = SIGNAL_OFFSET + SIGINT as u8,
     /// The exit code Rust uses by default when interrupted via a `QUIT` signal.
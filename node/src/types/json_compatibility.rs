@@ -5,6 +5,8 @@ mod auction_state;
 mod contracts;
 mod stored_value;
 
+use std::convert::TryFrom;
+
 use casper_types::{contracts::NamedKeys, NamedKey};
 
 pub use account::Account;
@@ -23,3 +25,13 @@ pub fn vectorize(keys: &NamedKeys) -> Vec<NamedKey> {
         .collect();
     named_keys
 }
+
+/// Retains only the page of `named_keys` starting at index `offset` and holding at most `limit`
+/// entries, returning the original number of named keys.
+fn retain_page(named_keys: &mut Vec<NamedKey>, offset: u32, limit: u32) -> u32 {
+    let total = u32::try_from(named_keys.len()).unwrap_or(u32::MAX);
+    let start = named_keys.len().min(offset as usize);
+    named_keys.drain(..start);
+    named_keys.truncate(limit as usize);
+    total
+}
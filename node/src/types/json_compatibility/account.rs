@@ -6,7 +6,10 @@ use once_cell::sync::Lazy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{rpcs::docs::DocExample, types::json_compatibility::vectorize};
+use crate::{
+    rpcs::docs::DocExample,
+    types::json_compatibility::{retain_page, vectorize},
+};
 use casper_types::{
     account::{Account as ExecutionEngineAccount, AccountHash},
     NamedKey, PublicKey, SecretKey, URef,
@@ -68,6 +71,11 @@ impl Account {
     pub(crate) fn main_purse(&self) -> URef {
         self.main_purse
     }
+
+    /// Retains only the given page of the named keys, returning the total number of named keys.
+    pub(crate) fn retain_named_keys_page(&mut self, offset: u32, limit: u32) -> u32 {
+        retain_page(&mut self.named_keys, offset, limit)
+    }
 }
 
 impl From<&ExecutionEngineAccount> for Account {
@@ -14,7 +14,7 @@ use casper_types::{
     AccessRights, EraId, PublicKey, SecretKey, URef, U512,
 };
 
-use crate::rpcs::docs::DocExample;
+use crate::{rpcs::docs::DocExample, types::BlockHeight};
 
 static ERA_VALIDATORS: Lazy<EraValidators> = Lazy::new(|| {
     let secret_key_1 = SecretKey::ed25519_from_bytes([42; SecretKey::ED25519_LENGTH]).unwrap();
@@ -63,7 +63,7 @@ static BIDS: Lazy<Bids> = Lazy::new(|| {
 });
 static AUCTION_INFO: Lazy<AuctionState> = Lazy::new(|| {
     let state_root_hash = Digest::from([11; Digest::LENGTH]);
-    let height: u64 = 10;
+    let height = BlockHeight::new(10);
     let era_validators = EraValidators::doc_example().clone();
     let bids = Bids::doc_example().clone();
     AuctionState::new(state_root_hash, height, era_validators, bids)
@@ -147,7 +147,7 @@ pub struct AuctionState {
     /// Global state hash.
     pub state_root_hash: Digest,
     /// Block height.
-    pub block_height: u64,
+    pub block_height: BlockHeight,
     /// Era validators.
     pub era_validators: Vec<JsonEraValidators>,
     /// All bids contained within a vector.
@@ -158,7 +158,7 @@ impl AuctionState {
     /// Create new instance of `AuctionState`
     pub fn new(
         state_root_hash: Digest,
-        block_height: u64,
+        block_height: BlockHeight,
         era_validators: EraValidators,
         bids: Bids,
     ) -> Self {
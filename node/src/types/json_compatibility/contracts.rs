@@ -5,7 +5,7 @@ use datasize::DataSize;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::types::json_compatibility::vectorize;
+use crate::types::json_compatibility::{retain_page, vectorize};
 use casper_types::{
     contracts::ContractPackageStatus, Contract as DomainContract, ContractHash,
     ContractPackage as DomainContractPackage, ContractPackageHash, ContractWasmHash, EntryPoint,
@@ -49,6 +49,13 @@ pub struct Contract {
     protocol_version: ProtocolVersion,
 }
 
+impl Contract {
+    /// Retains only the given page of the named keys, returning the total number of named keys.
+    pub(crate) fn retain_named_keys_page(&mut self, offset: u32, limit: u32) -> u32 {
+        retain_page(&mut self.named_keys, offset, limit)
+    }
+}
+
 impl From<&DomainContract> for Contract {
     fn from(contract: &DomainContract) -> Self {
         let entry_points = contract.entry_points().clone().take_entry_points();
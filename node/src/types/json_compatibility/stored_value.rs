@@ -12,8 +12,9 @@ use serde::{Deserialize, Serialize};
 
 use casper_types::{
     bytesrepr::{self, ToBytes},
+    contract_messages::{MessageChecksum, MessageTopicSummary},
     system::auction::{Bid, EraInfo, UnbondingPurse, WithdrawPurse},
-    CLValue, DeployInfo, StoredValue as ExecutionEngineStoredValue, Transfer,
+    CLValue, DeployInfo, StoredValue as ExecutionEngineStoredValue, Transfer, TransferV2,
 };
 
 use super::{Account, Contract, ContractPackage};
@@ -47,6 +48,24 @@ pub enum StoredValue {
     Withdraw(Vec<WithdrawPurse>),
     /// A collection of unbonding purses
     Unbonding(Vec<UnbondingPurse>),
+    /// The summary of a message topic
+    MessageTopic(MessageTopicSummary),
+    /// The checksum of a message emitted by a contract
+    Message(MessageChecksum),
+    /// A record of a transfer, with its initiator, memo and phase
+    TransferV2(TransferV2),
+}
+
+impl StoredValue {
+    /// If `self` is an account or contract, retains only the given page of its named keys and
+    /// returns the total number of its named keys.  Otherwise returns `None`.
+    pub(crate) fn retain_named_keys_page(&mut self, offset: u32, limit: u32) -> Option<u32> {
+        match self {
+            StoredValue::Account(account) => Some(account.retain_named_keys_page(offset, limit)),
+            StoredValue::Contract(contract) => Some(contract.retain_named_keys_page(offset, limit)),
+            _ => None,
+        }
+    }
 }
 
 impl TryFrom<ExecutionEngineStoredValue> for StoredValue {
@@ -77,6 +96,9 @@ impl TryFrom<ExecutionEngineStoredValue> for StoredValue {
             ExecutionEngineStoredValue::Unbonding(unbonding_purses) => {
                 StoredValue::Unbonding(unbonding_purses)
             }
+            ExecutionEngineStoredValue::MessageTopic(summary) => StoredValue::MessageTopic(summary),
+            ExecutionEngineStoredValue::Message(checksum) => StoredValue::Message(checksum),
+            ExecutionEngineStoredValue::TransferV2(transfer) => StoredValue::TransferV2(transfer),
         };
 
         Ok(stored_value)
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::types::BlockHash;
 
-use casper_types::TimeDiff;
+use casper_types::{EraId, PublicKey, TimeDiff};
 
 /// Maximum number of fetch-deploy tasks to run in parallel during chain synchronization.
 const DEFAULT_MAX_PARALLEL_DEPLOY_FETCHES: u32 = 5000;
@@ -13,6 +13,37 @@ const DEFAULT_MAX_PARALLEL_BLOCK_FETCHES: u32 = 50;
 const DEFAULT_MAX_SYNC_FETCH_ATTEMPTS: u32 = 5;
 const DEFAULT_PEER_REDEMPTION_INTERVAL: u32 = 10_000;
 const DEFAULT_RETRY_INTERVAL: &str = "100ms";
+const DEFAULT_SHUTDOWN_DRAIN_TIMEOUT: &str = "30sec";
+/// Maximum number of trie fetch requests in flight to a single peer during chain synchronization.
+const DEFAULT_MAX_TRIE_FETCHES_PER_PEER: u32 = 10;
+const DEFAULT_TRIE_FETCH_TIMEOUT: &str = "10sec";
+const DEFAULT_MAX_TRIE_FETCH_ATTEMPTS: u32 = 50;
+const DEFAULT_TRIE_PEER_FAILURE_PENALTY: u32 = 10;
+const DEFAULT_TRIE_PEER_MIN_SCORE: i64 = -50;
+
+fn default_shutdown_drain_timeout() -> TimeDiff {
+    DEFAULT_SHUTDOWN_DRAIN_TIMEOUT.parse().unwrap()
+}
+
+fn default_max_trie_fetches_per_peer() -> u32 {
+    DEFAULT_MAX_TRIE_FETCHES_PER_PEER
+}
+
+fn default_trie_fetch_timeout() -> TimeDiff {
+    DEFAULT_TRIE_FETCH_TIMEOUT.parse().unwrap()
+}
+
+fn default_max_trie_fetch_attempts() -> u32 {
+    DEFAULT_MAX_TRIE_FETCH_ATTEMPTS
+}
+
+fn default_trie_peer_failure_penalty() -> u32 {
+    DEFAULT_TRIE_PEER_FAILURE_PENALTY
+}
+
+fn default_trie_peer_min_score() -> i64 {
+    DEFAULT_TRIE_PEER_MIN_SCORE
+}
 
 /// Node fast-sync configuration.
 #[derive(DataSize, Debug, Deserialize, Serialize, Clone)]
@@ -31,6 +62,26 @@ pub struct NodeConfig {
     /// Maximum number of trie nodes to fetch in parallel.
     pub max_parallel_trie_fetches: u32,
 
+    /// Maximum number of trie and trie chunk requests in flight to a single peer.
+    #[serde(default = "default_max_trie_fetches_per_peer")]
+    pub max_trie_fetches_per_peer: u32,
+
+    /// The maximum duration to wait for a peer to deliver a trie or trie chunk.
+    #[serde(default = "default_trie_fetch_timeout")]
+    pub trie_fetch_timeout: TimeDiff,
+
+    /// The maximum number of failed requests for a single trie or trie chunk before giving up.
+    #[serde(default = "default_max_trie_fetch_attempts")]
+    pub max_trie_fetch_attempts: u32,
+
+    /// The score a peer loses each time it fails to deliver a trie or trie chunk in time.
+    #[serde(default = "default_trie_peer_failure_penalty")]
+    pub trie_peer_failure_penalty: u32,
+
+    /// The score below which a peer is no longer asked for tries until all peers are exhausted.
+    #[serde(default = "default_trie_peer_min_score")]
+    pub trie_peer_min_score: i64,
+
     /// The maximum number of retries of fetch operations during the chain synchronization process.
     /// The retry limit is in effect only when the network component reports that enough peers
     /// are connected, until that happens, the retries are unbounded.
@@ -45,6 +96,37 @@ pub struct NodeConfig {
     /// Whether to run in sync-to-genesis mode which captures all data (blocks, deploys
     /// and global state) back to genesis.
     pub sync_to_genesis: bool,
+
+    /// Public keys of which one must have signed a staged upgrade for it to be activated.
+    ///
+    /// If empty, staged upgrades are not required to be signed.
+    #[serde(default)]
+    pub trusted_release_keys: Vec<PublicKey>,
+
+    /// The maximum duration to wait on shutdown for the block being executed to be stored and for
+    /// peers to be notified.
+    #[serde(default = "default_shutdown_drain_timeout")]
+    pub shutdown_drain_timeout: TimeDiff,
+
+    /// If set, the node shuts down with exit code 104 once it has stored the block at this height.
+    #[serde(default)]
+    pub shutdown_at_height: Option<u64>,
+
+    /// If set, the node shuts down with exit code 104 once it has stored the switch block of this
+    /// era.
+    #[serde(default)]
+    pub shutdown_at_era: Option<EraId>,
+
+    /// Whether account hashes and public keys are emitted in bech32m form rather than hex in the
+    /// JSON output of the RPC, REST and event stream servers.  Both forms are always accepted.
+    #[serde(default)]
+    pub bech32_addresses: bool,
+
+    /// Whether 64-bit integers, such as the `u64` and `i64` values in parsed `CLValue`s, are
+    /// emitted as decimal strings rather than numbers in the JSON output of the RPC, REST and event
+    /// stream servers.  Both forms are always accepted.
+    #[serde(default)]
+    pub json_decimal_integers: bool,
 }
 
 impl Default for NodeConfig {
@@ -54,10 +136,21 @@ impl Default for NodeConfig {
             max_parallel_deploy_fetches: DEFAULT_MAX_PARALLEL_DEPLOY_FETCHES,
             max_parallel_block_fetches: DEFAULT_MAX_PARALLEL_BLOCK_FETCHES,
             max_parallel_trie_fetches: DEFAULT_MAX_PARALLEL_TRIE_FETCHES,
+            max_trie_fetches_per_peer: DEFAULT_MAX_TRIE_FETCHES_PER_PEER,
+            trie_fetch_timeout: default_trie_fetch_timeout(),
+            max_trie_fetch_attempts: DEFAULT_MAX_TRIE_FETCH_ATTEMPTS,
+            trie_peer_failure_penalty: DEFAULT_TRIE_PEER_FAILURE_PENALTY,
+            trie_peer_min_score: DEFAULT_TRIE_PEER_MIN_SCORE,
             max_sync_fetch_attempts: DEFAULT_MAX_SYNC_FETCH_ATTEMPTS,
             retry_interval: DEFAULT_RETRY_INTERVAL.parse().unwrap(),
             sync_peer_redemption_interval: DEFAULT_PEER_REDEMPTION_INTERVAL,
             sync_to_genesis: false,
+            trusted_release_keys: vec![],
+            shutdown_drain_timeout: default_shutdown_drain_timeout(),
+            shutdown_at_height: None,
+            shutdown_at_era: None,
+            bech32_addresses: false,
+            json_decimal_integers: false,
         }
     }
 }
@@ -6,6 +6,8 @@ use std::collections::BTreeMap;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use casper_types::TimeDiff;
+
 use crate::types::NodeId;
 
 /// Node peer entry.
@@ -18,6 +20,23 @@ pub struct PeerEntry {
     pub address: String,
 }
 
+/// The reputation of a peer, as scored by its behavior.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PeerReputation {
+    /// Node id.
+    pub node_id: String,
+    /// Current score. Penalties lower it and useful gossip raises it, and it recovers towards zero
+    /// over time.
+    pub score: i64,
+    /// Number of times the peer was banned recently.
+    pub bans: u32,
+    /// Time left until the peer's current ban ends, if it is banned.
+    pub banned_for: Option<TimeDiff>,
+    /// The most recent behavior which lowered the peer's score.
+    pub last_penalty: Option<String>,
+}
+
 /// Map of peer IDs to network addresses.
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
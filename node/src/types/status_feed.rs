@@ -17,11 +17,13 @@ use casper_types::{EraId, ProtocolVersion, PublicKey, TimeDiff, Timestamp};
 
 use crate::{
     components::{
-        chain_synchronizer::Progress,
+        chain_synchronizer::{Progress, SyncStatus},
         chainspec_loader::NextUpgrade,
         rpc_server::rpcs::docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
     },
-    types::{ActivationPoint, Block, BlockHash, NodeId, PeersMap},
+    types::{
+        peers_map::PeerReputation, ActivationPoint, Block, BlockHash, BlockHeight, NodeId, PeersMap,
+    },
 };
 
 static CHAINSPEC_INFO: Lazy<ChainspecInfo> = Lazy::new(|| {
@@ -40,15 +42,24 @@ static GET_STATUS_RESULT: Lazy<GetStatusResult> = Lazy::new(|| {
     let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 54321);
     let mut peers = BTreeMap::new();
     peers.insert(*node_id, socket_addr.to_string());
+    let peer_reputations = vec![PeerReputation {
+        node_id: node_id.to_string(),
+        score: -35,
+        bans: 0,
+        banned_for: None,
+        last_penalty: Some(String::from("slow response")),
+    }];
     let status_feed = StatusFeed {
         last_added_block: Some(Block::doc_example().clone()),
         peers,
+        peer_reputations,
         chainspec_info: ChainspecInfo::doc_example().clone(),
         our_public_signing_key: Some(PublicKey::doc_example().clone()),
         round_length: Some(TimeDiff::from(1 << 16)),
         version: crate::VERSION_STRING.as_str(),
         node_uptime: Duration::from_secs(13),
         node_state: NodeState::Participating,
+        sync_status: None,
     };
     GetStatusResult::new(status_feed, DOCS_EXAMPLE_PROTOCOL_VERSION)
 });
@@ -98,6 +109,8 @@ pub struct StatusFeed {
     pub last_added_block: Option<Block>,
     /// The peer nodes which are connected to this node.
     pub peers: BTreeMap<NodeId, String>,
+    /// The reputation of peers with a score other than zero or recent bans.
+    pub peer_reputations: Vec<PeerReputation>,
     /// The chainspec info for this node.
     pub chainspec_info: ChainspecInfo,
     /// Our public signing key.
@@ -110,16 +123,21 @@ pub struct StatusFeed {
     pub node_uptime: Duration,
     /// The current state of node.
     pub node_state: NodeState,
+    /// The progress of chain synchronization, if the node is syncing.
+    pub sync_status: Option<SyncStatus>,
 }
 
 impl StatusFeed {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         last_added_block: Option<Block>,
         peers: BTreeMap<NodeId, String>,
+        peer_reputations: Vec<PeerReputation>,
         chainspec_info: ChainspecInfo,
         consensus_status: Option<(PublicKey, Option<TimeDiff>)>,
         node_uptime: Duration,
         node_state: NodeState,
+        sync_status: Option<SyncStatus>,
     ) -> Self {
         let (our_public_signing_key, round_length) = match consensus_status {
             Some((public_key, round_length)) => (Some(public_key), round_length),
@@ -128,12 +146,14 @@ impl StatusFeed {
         StatusFeed {
             last_added_block,
             peers,
+            peer_reputations,
             chainspec_info,
             our_public_signing_key,
             round_length,
             version: crate::VERSION_STRING.as_str(),
             node_uptime,
             node_state,
+            sync_status,
         }
     }
 }
@@ -145,7 +165,7 @@ pub struct MinimalBlockInfo {
     hash: BlockHash,
     timestamp: Timestamp,
     era_id: EraId,
-    height: u64,
+    height: BlockHeight,
     state_root_hash: Digest,
     creator: PublicKey,
 }
@@ -156,7 +176,7 @@ impl From<Block> for MinimalBlockInfo {
             hash: *block.hash(),
             timestamp: block.header().timestamp(),
             era_id: block.header().era_id(),
-            height: block.header().height(),
+            height: BlockHeight::new(block.header().height()),
             state_root_hash: *block.header().state_root_hash(),
             creator: block.body().proposer().clone(),
         }
@@ -177,6 +197,8 @@ pub struct GetStatusResult {
     pub starting_state_root_hash: Digest,
     /// The node ID and network address of each connected peer.
     pub peers: PeersMap,
+    /// The reputation of peers with a score other than zero or recent bans, connected or not.
+    pub peer_reputations: Vec<PeerReputation>,
     /// The minimal info of the last block from the linear chain.
     pub last_added_block_info: Option<MinimalBlockInfo>,
     /// Our public signing key.
@@ -191,6 +213,8 @@ pub struct GetStatusResult {
     pub uptime: TimeDiff,
     /// The current state of node.
     pub node_state: NodeState,
+    /// The progress of chain synchronization, if the node is syncing.
+    pub sync_status: Option<SyncStatus>,
 }
 
 impl GetStatusResult {
@@ -201,12 +225,14 @@ impl GetStatusResult {
             chainspec_name: status_feed.chainspec_info.name,
             starting_state_root_hash: Digest::from([0u8; 32]),
             peers: PeersMap::from(status_feed.peers),
+            peer_reputations: status_feed.peer_reputations,
             last_added_block_info: status_feed.last_added_block.map(Into::into),
             our_public_signing_key: status_feed.our_public_signing_key,
             round_length: status_feed.round_length,
             next_upgrade: status_feed.chainspec_info.next_upgrade,
             uptime: status_feed.node_uptime.into(),
             node_state: status_feed.node_state,
+            sync_status: status_feed.sync_status,
             #[cfg(not(test))]
             build_version: crate::VERSION_STRING.clone(),
 
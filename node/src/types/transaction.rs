@@ -0,0 +1,426 @@
+//! Node-side support for the versioned transaction format.
+//!
+//! Until block production is migrated, a [`TransactionV2`] is executed by translating it into the
+//! equivalent [`DeployItem`].
+
+use std::fmt::{self, Display, Formatter};
+
+use datasize::DataSize;
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use casper_execution_engine::core::engine_state::{
+    executable_deploy_item::ExecutableDeployItem, DeployItem,
+};
+use casper_types::{
+    bytesrepr::{self, Bytes, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    runtime_args,
+    system::standard_payment::ARG_AMOUNT,
+    PricingMode, RuntimeArgs, SecretKey, TimeDiff, Timestamp, TransactionEntryPoint,
+    TransactionInvocationTarget, TransactionTarget, TransactionV2, TransactionV2Body,
+    TransactionV2Hash, U512,
+};
+
+use super::{Deploy, DeployConfigurationFailure, DeployHash, ExcessiveSizeDeployError};
+use crate::{rpcs::docs::DocExample, types::chainspec::DeployConfig};
+
+const DEPLOY_TAG: u8 = 0;
+const V2_TAG: u8 = 1;
+
+static TRANSACTION: Lazy<Transaction> = Lazy::new(|| {
+    let body = TransactionV2Body::new(
+        runtime_args! { "amount" => 1000 },
+        TransactionTarget::Stored {
+            id: TransactionInvocationTarget::ByName(String::from("casper-example")),
+        },
+        TransactionEntryPoint::Custom(String::from("example-entry-point")),
+    );
+    let transaction = TransactionV2::new(
+        String::from("casper-example"),
+        *Timestamp::doc_example(),
+        TimeDiff::from(3_600_000),
+        PricingMode::Classic {
+            payment_amount: 1000,
+            gas_price: 1,
+        },
+        body,
+        SecretKey::doc_example(),
+        None,
+    );
+    Transaction::V2(transaction)
+});
+
+/// A transaction in either the legacy deploy format or the versioned format.
+#[derive(Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum Transaction {
+    /// A deploy.
+    Deploy(Deploy),
+    /// A transaction in the versioned format.
+    V2(TransactionV2),
+}
+
+impl Transaction {
+    /// Returns the hash identifying this transaction.
+    pub fn hash(&self) -> TransactionHash {
+        match self {
+            Transaction::Deploy(deploy) => TransactionHash::Deploy(*deploy.id()),
+            Transaction::V2(transaction) => TransactionHash::V2(*transaction.hash()),
+        }
+    }
+}
+
+impl DocExample for Transaction {
+    fn doc_example() -> &'static Self {
+        &*TRANSACTION
+    }
+}
+
+impl From<Deploy> for Transaction {
+    fn from(deploy: Deploy) -> Self {
+        Transaction::Deploy(deploy)
+    }
+}
+
+impl From<TransactionV2> for Transaction {
+    fn from(transaction: TransactionV2) -> Self {
+        Transaction::V2(transaction)
+    }
+}
+
+impl Display for Transaction {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Transaction::Deploy(deploy) => Display::fmt(deploy, formatter),
+            Transaction::V2(transaction) => Display::fmt(transaction, formatter),
+        }
+    }
+}
+
+impl ToBytes for Transaction {
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        match self {
+            Transaction::Deploy(deploy) => {
+                DEPLOY_TAG.write_bytes(writer)?;
+                deploy.write_bytes(writer)
+            }
+            Transaction::V2(transaction) => {
+                V2_TAG.write_bytes(writer)?;
+                transaction.write_bytes(writer)
+            }
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+            + match self {
+                Transaction::Deploy(deploy) => deploy.serialized_length(),
+                Transaction::V2(transaction) => transaction.serialized_length(),
+            }
+    }
+}
+
+impl FromBytes for Transaction {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, remainder) = u8::from_bytes(bytes)?;
+        match tag {
+            DEPLOY_TAG => {
+                let (deploy, remainder) = Deploy::from_bytes(remainder)?;
+                Ok((Transaction::Deploy(deploy), remainder))
+            }
+            V2_TAG => {
+                let (transaction, remainder) = TransactionV2::from_bytes(remainder)?;
+                Ok((Transaction::V2(transaction), remainder))
+            }
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+/// The hash of a [`Transaction`].
+#[derive(
+    Copy,
+    Clone,
+    DataSize,
+    Ord,
+    PartialOrd,
+    Eq,
+    PartialEq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Debug,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub enum TransactionHash {
+    /// The hash of a deploy.
+    Deploy(DeployHash),
+    /// The hash of a transaction in the versioned format.
+    V2(TransactionV2Hash),
+}
+
+impl Display for TransactionHash {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionHash::Deploy(hash) => Display::fmt(hash, formatter),
+            TransactionHash::V2(hash) => Display::fmt(hash, formatter),
+        }
+    }
+}
+
+/// Returns `Ok` if the given transaction is compliant with the deploy limits in the chainspec.
+///
+/// This mirrors `Deploy::is_config_compliant`, with the payment amount taken from the pricing mode
+/// rather than from payment code.
+pub(crate) fn is_config_compliant(
+    transaction: &TransactionV2,
+    chain_name: &str,
+    config: &DeployConfig,
+    max_associated_keys: u32,
+) -> Result<(), DeployConfigurationFailure> {
+    let size = transaction.serialized_length();
+    if size > config.max_deploy_size as usize {
+        info!(%size, max_deploy_size = %config.max_deploy_size, "transaction size too large");
+        return Err(ExcessiveSizeDeployError {
+            max_deploy_size: config.max_deploy_size,
+            actual_deploy_size: size,
+        }
+        .into());
+    }
+
+    let header = transaction.header();
+    if header.chain_name() != chain_name {
+        info!(
+            transaction_hash = %transaction.hash(),
+            chain_name = %header.chain_name(),
+            "invalid chain identifier"
+        );
+        return Err(DeployConfigurationFailure::InvalidChainName {
+            expected: chain_name.to_string(),
+            got: header.chain_name().to_string(),
+        });
+    }
+
+    if header.ttl() > config.max_ttl {
+        info!(
+            transaction_hash = %transaction.hash(),
+            max_ttl = %config.max_ttl,
+            "transaction ttl excessive"
+        );
+        return Err(DeployConfigurationFailure::ExcessiveTimeToLive {
+            max_ttl: config.max_ttl,
+            got: header.ttl(),
+        });
+    }
+
+    if transaction.approvals().len() > max_associated_keys as usize {
+        info!(
+            transaction_hash = %transaction.hash(),
+            number_of_associated_keys = %transaction.approvals().len(),
+            %max_associated_keys,
+            "number of associated keys exceeds the maximum limit"
+        );
+        return Err(DeployConfigurationFailure::ExcessiveApprovals {
+            got: transaction.approvals().len() as u32,
+            max_associated_keys,
+        });
+    }
+
+    let body = transaction.body();
+    let args_length = body.args().serialized_length();
+    if args_length > config.session_args_max_length as usize {
+        info!(
+            args_length,
+            session_args_max_length = config.session_args_max_length,
+            "transaction args excessive"
+        );
+        return Err(DeployConfigurationFailure::ExcessiveSessionArgsLength {
+            max_length: config.session_args_max_length as usize,
+            got: args_length,
+        });
+    }
+
+    match body.target() {
+        TransactionTarget::Native => {
+            let attempted = body
+                .args()
+                .get(ARG_AMOUNT)
+                .ok_or_else(|| {
+                    info!("missing transfer 'amount' runtime argument");
+                    DeployConfigurationFailure::MissingTransferAmount
+                })?
+                .clone()
+                .into_t::<U512>()
+                .map_err(|_| {
+                    info!("failed to parse transfer 'amount' runtime argument as a U512");
+                    DeployConfigurationFailure::FailedToParseTransferAmount
+                })?;
+            let minimum = U512::from(config.native_transfer_minimum_motes);
+            if attempted < minimum {
+                info!(
+                    minimum = %config.native_transfer_minimum_motes,
+                    amount = %attempted,
+                    "insufficient transfer amount"
+                );
+                return Err(DeployConfigurationFailure::InsufficientTransferAmount {
+                    minimum,
+                    attempted,
+                });
+            }
+        }
+        TransactionTarget::Stored { .. } | TransactionTarget::Session { .. } => {
+            let PricingMode::Classic { payment_amount, .. } = header.pricing_mode();
+            if *payment_amount > config.block_gas_limit {
+                info!(
+                    amount = %payment_amount,
+                    block_gas_limit = %config.block_gas_limit,
+                    "payment amount exceeds block gas limit"
+                );
+                return Err(DeployConfigurationFailure::ExceededBlockGasLimit {
+                    block_gas_limit: config.block_gas_limit,
+                    got: U512::from(*payment_amount),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl From<Transaction> for DeployItem {
+    fn from(transaction: Transaction) -> Self {
+        let transaction = match transaction {
+            Transaction::Deploy(deploy) => return deploy.into(),
+            Transaction::V2(transaction) => transaction,
+        };
+
+        let address = transaction.header().initiator().to_account_hash();
+        let authorization_keys = transaction
+            .approvals()
+            .iter()
+            .map(|approval| approval.signer().to_account_hash())
+            .collect();
+        let PricingMode::Classic {
+            payment_amount,
+            gas_price,
+        } = *transaction.header().pricing_mode();
+        let payment = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args: runtime_args! { ARG_AMOUNT => U512::from(payment_amount) },
+        };
+        let deploy_hash = casper_types::DeployHash::new(transaction.hash().value());
+
+        let body = transaction.body();
+        let args = body.args().clone();
+        let entry_point = match body.entry_point() {
+            TransactionEntryPoint::Custom(entry_point) => entry_point.clone(),
+            TransactionEntryPoint::Call | TransactionEntryPoint::Transfer => String::new(),
+        };
+        let session = match body.target() {
+            TransactionTarget::Native => ExecutableDeployItem::Transfer { args },
+            TransactionTarget::Stored { id } => match id {
+                TransactionInvocationTarget::ByHash(hash) => {
+                    ExecutableDeployItem::StoredContractByHash {
+                        hash: *hash,
+                        entry_point,
+                        args,
+                    }
+                }
+                TransactionInvocationTarget::ByName(name) => {
+                    ExecutableDeployItem::StoredContractByName {
+                        name: name.clone(),
+                        entry_point,
+                        args,
+                    }
+                }
+                TransactionInvocationTarget::ByPackageHash { hash, version } => {
+                    ExecutableDeployItem::StoredVersionedContractByHash {
+                        hash: *hash,
+                        version: *version,
+                        entry_point,
+                        args,
+                    }
+                }
+                TransactionInvocationTarget::ByPackageName { name, version } => {
+                    ExecutableDeployItem::StoredVersionedContractByName {
+                        name: name.clone(),
+                        version: *version,
+                        entry_point,
+                        args,
+                    }
+                }
+            },
+            TransactionTarget::Session { module_bytes } => ExecutableDeployItem::ModuleBytes {
+                module_bytes: module_bytes.clone(),
+                args,
+            },
+        };
+
+        DeployItem::new(
+            address,
+            session,
+            payment,
+            gas_price,
+            authorization_keys,
+            deploy_hash,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        let mut rng = TestRng::new();
+        let deploy = Transaction::Deploy(Deploy::random(&mut rng));
+        bytesrepr::test_serialization_roundtrip(&deploy);
+        let transaction = Transaction::V2(TransactionV2::random(&mut rng));
+        bytesrepr::test_serialization_roundtrip(&transaction);
+    }
+
+    #[test]
+    fn native_transfer_should_convert_to_transfer_deploy_item() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let body = TransactionV2Body::new(
+            runtime_args! { ARG_AMOUNT => U512::from(2_500_000_000u64) },
+            TransactionTarget::Native,
+            TransactionEntryPoint::Transfer,
+        );
+        let transaction = TransactionV2::new(
+            "casper-example".to_string(),
+            Timestamp::now(),
+            TimeDiff::from_seconds(60),
+            PricingMode::Classic {
+                payment_amount: 10_000,
+                gas_price: 1,
+            },
+            body,
+            &secret_key,
+            None,
+        );
+        let expected_hash = casper_types::DeployHash::new(transaction.hash().value());
+
+        let deploy_item = DeployItem::from(Transaction::V2(transaction));
+        assert!(deploy_item.session.is_transfer());
+        assert_eq!(deploy_item.gas_price, 1);
+        assert_eq!(deploy_item.deploy_hash, expected_hash);
+        assert_eq!(
+            deploy_item.address,
+            casper_types::PublicKey::from(&secret_key).to_account_hash()
+        );
+    }
+}
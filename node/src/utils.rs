@@ -19,6 +19,7 @@ use std::{
     net::{SocketAddr, ToSocketAddrs},
     ops::{Add, BitXorAssign, Div},
     path::{Path, PathBuf},
+    pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -27,13 +28,24 @@ use std::{
 };
 
 use datasize::DataSize;
-use hyper::server::{conn::AddrIncoming, Builder, Server};
+use futures::future;
+use hyper::server::{
+    accept::{self, Accept},
+    conn::{AddrIncoming, AddrStream},
+    Builder, Server,
+};
 #[cfg(test)]
 use once_cell::sync::Lazy;
+use openssl::{error::ErrorStack, ssl::SslAcceptor};
 use prometheus::{self, Histogram, HistogramOpts, Registry};
-use serde::Serialize;
+use serde::{
+    de::{Deserializer, Error as SerdeError, Unexpected},
+    Deserialize, Serialize,
+};
 use thiserror::Error;
-use tracing::{error, warn};
+use tokio::sync::mpsc;
+use tokio_openssl::SslStream;
+use tracing::{debug, error, warn};
 
 pub(crate) use display_error::display_error;
 pub(crate) use external::External;
@@ -42,7 +54,9 @@ pub(crate) use external::RESOURCES_PATH;
 pub use external::{LoadError, Loadable};
 pub(crate) use round_robin::WeightedRoundRobin;
 
-use crate::types::NodeId;
+use casper_types::TimeDiff;
+
+use crate::{tls, types::NodeId};
 
 /// DNS resolution error.
 #[derive(Debug, Error)]
@@ -131,15 +145,33 @@ pub(crate) enum ListeningError {
         /// The failure reason.
         error: Box<dyn std::error::Error + Send + Sync>,
     },
+
+    /// Failed to set up TLS.
+    #[error("failed to set up TLS: {0}")]
+    Tls(ErrorStack),
+
+    /// An additional endpoint is misconfigured.
+    #[error("invalid endpoint {name:?}: {reason}")]
+    InvalidEndpoint {
+        /// The name of the endpoint.
+        name: String,
+        /// The reason it is invalid.
+        reason: &'static str,
+    },
 }
 
 pub(crate) fn start_listening(address: &str) -> Result<Builder<AddrIncoming>, ListeningError> {
+    bind_incoming(address).map(Server::builder)
+}
+
+/// Binds a listener for an HTTP server to the given address.
+pub(crate) fn bind_incoming(address: &str) -> Result<AddrIncoming, ListeningError> {
     let address = resolve_address(address).map_err(|error| {
         warn!(%error, %address, "failed to start HTTP server, cannot parse address");
         ListeningError::ResolveAddress(error)
     })?;
 
-    Server::try_bind(&address).map_err(|error| {
+    AddrIncoming::bind(&address).map_err(|error| {
         warn!(%error, %address, "failed to start HTTP server");
         ListeningError::Listen {
             address,
@@ -148,6 +180,61 @@ pub(crate) fn start_listening(address: &str) -> Result<Builder<AddrIncoming>, Li
     })
 }
 
+/// Wraps the connections accepted by `incoming` in TLS, requiring clients to authenticate with a
+/// certificate accepted by `acceptor`.
+///
+/// Handshakes are performed in separate tasks, so a slow client does not hold up others.
+/// Connections failing the handshake are dropped without being passed on to the HTTP server.
+pub(crate) fn mutual_tls_incoming(
+    incoming: AddrIncoming,
+    acceptor: SslAcceptor,
+) -> impl Accept<Conn = SslStream<AddrStream>, Error = io::Error> {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    tokio::spawn(accept_mutual_tls_connections(
+        incoming,
+        Arc::new(acceptor),
+        sender,
+    ));
+    accept::poll_fn(move |cx| receiver.poll_recv(cx).map(|stream| stream.map(Ok)))
+}
+
+/// Accepts connections until the receiving HTTP server has been dropped.
+async fn accept_mutual_tls_connections(
+    mut incoming: AddrIncoming,
+    acceptor: Arc<SslAcceptor>,
+    sender: mpsc::UnboundedSender<SslStream<AddrStream>>,
+) {
+    loop {
+        let accepted = tokio::select! {
+            _ = sender.closed() => break,
+            accepted = future::poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx)) => accepted,
+        };
+        let stream = match accepted {
+            Some(Ok(stream)) => stream,
+            Some(Err(error)) => {
+                warn!(%error, "failed to accept connection");
+                continue;
+            }
+            None => break,
+        };
+
+        let acceptor = Arc::clone(&acceptor);
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            let peer_addr = stream.remote_addr();
+            match tls::accept_mutual_tls(&acceptor, stream).await {
+                Ok((tls_stream, client)) => {
+                    debug!(%peer_addr, %client, "accepted mutual TLS connection");
+                    let _ = sender.send(tls_stream);
+                }
+                Err(error) => {
+                    warn!(%peer_addr, %error, "rejected mutual TLS connection");
+                }
+            }
+        });
+    }
+}
+
 /// Moves a value to the heap and then forgets about, leaving only a static reference behind.
 #[inline]
 pub(crate) fn leak<T>(value: T) -> &'static T {
@@ -366,6 +453,23 @@ pub(crate) fn xor(lhs: &mut [u8], rhs: &[u8]) {
         .for_each(|(sb, &cb)| sb.bitxor_assign(cb));
 }
 
+/// Deserializes a `TimeDiff` but fails if it is zero.
+///
+/// Used for config values such as check intervals and timeouts, for which zero is never valid.
+pub(crate) fn deserialize_nonzero_time_diff<'de, D>(deserializer: D) -> Result<TimeDiff, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let time_diff = TimeDiff::deserialize(deserializer)?;
+    if time_diff.millis() == 0 {
+        return Err(SerdeError::invalid_value(
+            Unexpected::Str(&time_diff.to_string()),
+            &"a duration greater than zero",
+        ));
+    }
+    Ok(time_diff)
+}
+
 /// Wait until all strong references for a particular arc have been dropped.
 ///
 /// Downgrades and immediately drops the `Arc`, keeping only a weak reference. The reference will
@@ -409,9 +513,12 @@ pub(crate) async fn wait_for_arc_drop<T>(
 mod tests {
     use std::{sync::Arc, time::Duration};
 
+    use casper_types::TimeDiff;
+    use serde::Deserialize;
+
     use crate::utils::SharedFlag;
 
-    use super::{wait_for_arc_drop, xor};
+    use super::{deserialize_nonzero_time_diff, wait_for_arc_drop, xor};
 
     #[test]
     fn xor_works() {
@@ -468,6 +575,30 @@ mod tests {
         assert!(weak.upgrade().is_none());
     }
 
+    #[test]
+    fn zero_time_diff_should_be_rejected() {
+        #[derive(Debug, Deserialize)]
+        struct Section {
+            #[serde(deserialize_with = "deserialize_nonzero_time_diff")]
+            interval: TimeDiff,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Config {
+            section: Section,
+        }
+
+        let config: Config = toml::from_str("[section]\ninterval = '5sec'\n").unwrap();
+        assert_eq!(config.section.interval, TimeDiff::from_seconds(5));
+
+        let error = toml::from_str::<Config>("[section]\ninterval = '0ms'\n").unwrap_err();
+        assert!(
+            error.to_string().contains("section.interval"),
+            "error should point to the offending key: {}",
+            error
+        );
+    }
+
     #[test]
     fn shared_flag_sanity_check() {
         let flag = SharedFlag::new();
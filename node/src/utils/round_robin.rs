@@ -6,7 +6,7 @@
 
 use std::{
     collections::{BTreeMap, HashMap, VecDeque},
-    fmt::Debug,
+    fmt::{self, Debug, Display, Formatter},
     hash::Hash,
     num::NonZeroUsize,
     sync::atomic::{AtomicBool, AtomicUsize, Ordering},
@@ -126,6 +126,45 @@ where
     queues: BTreeMap<K, &'a VecDeque<I>>,
 }
 
+/// A summary of the internal queues, listing the depth of each and what its items are.
+#[derive(Debug, Serialize)]
+pub struct QueueSummary<K>
+where
+    K: Ord + Eq,
+{
+    /// Summaries of the individual queues, in the natural order defined by `Ord` on `K`.
+    queues: BTreeMap<K, QueueSummaryEntry>,
+}
+
+/// A summary of a single queue.
+#[derive(Debug, Default, Serialize)]
+struct QueueSummaryEntry {
+    /// Number of items in the queue.
+    depth: usize,
+    /// Number of items in the queue with each description.
+    counts: BTreeMap<&'static str, usize>,
+    /// Summaries of the individual items, in queue order, if requested.
+    items: Option<Vec<String>>,
+}
+
+impl<K> Display for QueueSummary<K>
+where
+    K: Ord + Eq + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (kind, entry) in &self.queues {
+            writeln!(f, "{:?}: {}", kind, entry.depth)?;
+            for (description, count) in &entry.counts {
+                writeln!(f, "  {}: {}", description, count)?;
+            }
+            for item in entry.items.iter().flatten() {
+                writeln!(f, "    {}", item)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<I, K> WeightedRoundRobin<I, K>
 where
     I: Debug,
@@ -147,6 +186,41 @@ where
         dumper(&queue_dump);
     }
 
+    /// Summarizes the queues, counting their items by the description returned by `describe`.
+    ///
+    /// If `include_items` is true, the summary also contains the result of `summarize_item` for
+    /// every queued item.
+    pub async fn summarize<D, S>(
+        &self,
+        describe: D,
+        summarize_item: S,
+        include_items: bool,
+    ) -> QueueSummary<K>
+    where
+        K: Ord,
+        D: Fn(&I) -> &'static str,
+        S: Fn(&I) -> String,
+    {
+        let locks = self.lock_queues().await;
+        let mut queues = BTreeMap::new();
+        for (kind, guard) in &locks {
+            let mut entry = QueueSummaryEntry {
+                depth: guard.len(),
+                items: include_items.then(Vec::new),
+                ..Default::default()
+            };
+            for item in guard.iter() {
+                *entry.counts.entry(describe(item)).or_default() += 1;
+                if let Some(items) = entry.items.as_mut() {
+                    items.push(summarize_item(item));
+                }
+            }
+            queues.insert(*kind, entry);
+        }
+
+        QueueSummary { queues }
+    }
+
     /// Lock all queues in a well-defined order to avoid deadlocks conditions.
     async fn lock_queues(&self) -> Vec<(K, MutexGuard<'_, VecDeque<I>>)> {
         let mut locks = Vec::new();
@@ -323,7 +397,7 @@ mod tests {
     use super::*;
 
     #[repr(usize)]
-    #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+    #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, IntoEnumIterator, PartialOrd, Ord)]
     enum QueueKind {
         One = 1,
         Two,
@@ -384,4 +458,45 @@ mod tests {
         assert_eq!(scheduler.item_count(), 0);
         assert!(scheduler.drain_queues().await.is_empty());
     }
+
+    #[tokio::test]
+    async fn should_summarize_queues() {
+        let scheduler = WeightedRoundRobin::<char, QueueKind>::new(weights());
+        for item in ['a', 'B', 'c'] {
+            scheduler.push(item, QueueKind::One).await;
+        }
+        scheduler.push('d', QueueKind::Two).await;
+
+        let describe = |item: &char| {
+            if item.is_uppercase() {
+                "upper"
+            } else {
+                "lower"
+            }
+        };
+        let summary = scheduler
+            .summarize(describe, |item| item.to_string(), false)
+            .await;
+        let one = &summary.queues[&QueueKind::One];
+        assert_eq!(one.depth, 3);
+        assert_eq!(one.counts["lower"], 2);
+        assert_eq!(one.counts["upper"], 1);
+        assert!(one.items.is_none());
+        assert_eq!(summary.queues[&QueueKind::Two].depth, 1);
+
+        let summary = scheduler
+            .summarize(describe, |item| item.to_string(), true)
+            .await;
+        assert_eq!(
+            summary.queues[&QueueKind::One].items,
+            Some(vec!["a".to_string(), "B".to_string(), "c".to_string()])
+        );
+        assert_eq!(
+            summary.to_string(),
+            "One: 3\n  lower: 2\n  upper: 1\n    a\n    B\n    c\nTwo: 1\n  lower: 1\n    d\n"
+        );
+
+        // Summarizing does not consume any items.
+        assert_eq!(scheduler.item_count(), 4);
+    }
 }
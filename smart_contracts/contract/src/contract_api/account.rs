@@ -5,8 +5,8 @@ use core::convert::TryFrom;
 
 use casper_types::{
     account::{
-        AccountHash, ActionType, AddKeyFailure, RemoveKeyFailure, SetThresholdFailure,
-        UpdateKeyFailure, Weight,
+        AccountHash, ActionType, AddKeyFailure, KeyManagementFailure, KeyManagementOperation,
+        RemoveKeyFailure, SetThresholdFailure, UpdateKeyFailure, Weight,
     },
     bytesrepr, URef, UREF_SERIALIZED_LENGTH,
 };
@@ -93,3 +93,21 @@ pub fn update_associated_key(
         Err(UpdateKeyFailure::try_from(result).unwrap_or_revert())
     }
 }
+
+/// Applies the given changes to the account's associated keys and action thresholds as a single
+/// unit.
+///
+/// Only the resulting associated keys and thresholds are required to be valid, so e.g. the key
+/// management threshold can be raised before the keys which satisfy it are added.  If any change
+/// fails, none are applied.
+pub fn manage_associated_keys(
+    operations: Vec<KeyManagementOperation>,
+) -> Result<(), KeyManagementFailure> {
+    let (operations_ptr, operations_size, _bytes) = to_ptr(operations);
+    let result = unsafe { ext_ffi::casper_manage_associated_keys(operations_ptr, operations_size) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(KeyManagementFailure::try_from(result).unwrap_or_revert())
+    }
+}
@@ -0,0 +1,50 @@
+//! Functions for emitting messages from a contract.
+//!
+//! Messages are emitted on named topics of the calling contract.  Each message is kept in global
+//! state as the checksum of its payload, and is listed by address and checksum in the execution
+//! results of the deploy which emitted it.
+
+use core::convert::TryFrom;
+
+use casper_types::{bytesrepr::ToBytes, contract_messages::MessageEmissionFailure};
+
+use crate::{ext_ffi, unwrap_or_revert::UnwrapOrRevert};
+
+/// A typed message which a contract emits on a topic of its own.
+///
+/// The payload of an event is its `bytesrepr` serialization, so event types can derive
+/// [`ToBytes`](casper_types::bytesrepr::ToBytes) and clients can decode the payload with the
+/// matching `FromBytes` implementation.
+pub trait Event: ToBytes {
+    /// The name of the topic on which events of this type are emitted.
+    const TOPIC_NAME: &'static str;
+}
+
+/// Emits `payload` as a message on the topic of the current contract named `topic_name`.
+///
+/// The topic is registered by the first message emitted on it.  Fails if called from session code,
+/// or if the topic name or payload exceed
+/// [`MAX_TOPIC_NAME_SIZE`](casper_types::contract_messages::MAX_TOPIC_NAME_SIZE) or
+/// [`MAX_MESSAGE_PAYLOAD_SIZE`](casper_types::contract_messages::MAX_MESSAGE_PAYLOAD_SIZE) bytes
+/// respectively.
+pub fn emit_message(topic_name: &str, payload: &[u8]) -> Result<(), MessageEmissionFailure> {
+    let result = unsafe {
+        ext_ffi::casper_emit_message(
+            topic_name.as_ptr(),
+            topic_name.len(),
+            payload.as_ptr(),
+            payload.len(),
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(MessageEmissionFailure::try_from(result).unwrap_or_revert())
+    }
+}
+
+/// Emits `event` on the topic named by its [`Event::TOPIC_NAME`].
+pub fn emit<E: Event>(event: &E) -> Result<(), MessageEmissionFailure> {
+    let payload = event.to_bytes().unwrap_or_revert();
+    emit_message(E::TOPIC_NAME, &payload)
+}
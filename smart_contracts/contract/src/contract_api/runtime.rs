@@ -9,8 +9,9 @@ use casper_types::{
     bytesrepr::{self, FromBytes},
     contracts::{ContractVersion, NamedKeys},
     system::CallStackElement,
-    ApiError, BlockTime, CLTyped, CLValue, ContractHash, ContractPackageHash, Key, Phase,
-    RuntimeArgs, URef, BLAKE2B_DIGEST_LENGTH, BLOCKTIME_SERIALIZED_LENGTH, PHASE_SERIALIZED_LENGTH,
+    AccessRights, ApiError, BlockTime, CLTyped, CLValue, ContractHash, ContractPackageHash, Key,
+    Phase, RuntimeArgs, URef, BLAKE2B_DIGEST_LENGTH, BLOCKTIME_SERIALIZED_LENGTH,
+    PHASE_SERIALIZED_LENGTH,
 };
 
 use crate::{contract_api, ext_ffi, unwrap_or_revert::UnwrapOrRevert};
@@ -324,6 +325,54 @@ pub fn list_named_keys() -> NamedKeys {
     bytesrepr::deserialize(bytes).unwrap_or_revert()
 }
 
+/// Returns at most `count` of the named keys of the current context, in name order, starting with
+/// the one at index `offset`, along with the total number of named keys.
+///
+/// Unlike [`list_named_keys`], this allows iterating over the named keys of a context holding too
+/// many of them to be loaded at once.
+pub fn list_named_keys_page(offset: u32, count: u32) -> (NamedKeys, usize) {
+    let (total_keys, result_size) = {
+        let mut total_keys = MaybeUninit::uninit();
+        let mut result_size = 0;
+        let ret = unsafe {
+            ext_ffi::casper_load_named_keys_page(
+                offset,
+                count,
+                total_keys.as_mut_ptr(),
+                &mut result_size as *mut usize,
+            )
+        };
+        api_error::result_from(ret).unwrap_or_revert();
+        let total_keys = unsafe { total_keys.assume_init() };
+        (total_keys, result_size)
+    };
+    if result_size == 0 {
+        return (NamedKeys::new(), total_keys);
+    }
+    let bytes = read_host_buffer(result_size).unwrap_or_revert();
+    let named_keys = bytesrepr::deserialize(bytes).unwrap_or_revert();
+    (named_keys, total_keys)
+}
+
+/// Restricts the access rights with which `uref` can be used by any context other than the current
+/// one to `max_rights`, e.g. demoting a previously shared `READ_ADD_WRITE` grant to `READ`.
+///
+/// The current context must hold `uref` with full access rights, and once restricted, only the
+/// current context may change the restriction again.  Restricting to
+/// [`AccessRights::READ_ADD_WRITE`] lifts the restriction.
+pub fn restrict_uref(uref: URef, max_rights: AccessRights) {
+    let (uref_ptr, uref_size, _bytes) = contract_api::to_ptr(uref);
+    let result = unsafe { ext_ffi::casper_restrict_uref(uref_ptr, uref_size, max_rights.bits()) };
+    api_error::result_from(result).unwrap_or_revert()
+}
+
+/// Revokes `uref` from every context other than the current one.
+///
+/// This is equivalent to restricting `uref` to [`AccessRights::NONE`]; see [`restrict_uref`].
+pub fn revoke_uref(uref: URef) {
+    restrict_uref(uref, AccessRights::NONE)
+}
+
 /// Validates uref against named keys.
 pub fn is_valid_uref(uref: URef) -> bool {
     let (uref_ptr, uref_size, _bytes) = contract_api::to_ptr(uref);
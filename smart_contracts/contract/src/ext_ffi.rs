@@ -790,4 +790,59 @@ extern "C" {
     /// * `out_ptr` - pointer to the location where argument bytes will be copied from the host side
     /// * `out_size` - size of output pointer
     pub fn casper_random_bytes(out_ptr: *mut u8, out_size: usize) -> i32;
+    /// This function loads a page of the named keys of the current context from the host, in
+    /// name order.  The data will be available through the host buffer and can be copied to Wasm
+    /// memory through [`casper_read_host_buffer`].
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - number of named keys to skip before the start of the page
+    /// * `count` - maximum number of named keys in the page
+    /// * `total_keys` - total number of named keys of the current context
+    /// * `result_size` - size of the data loaded in the host, which is zero if the page is empty
+    pub fn casper_load_named_keys_page(
+        offset: u32,
+        count: u32,
+        total_keys: *mut usize,
+        result_size: *mut usize,
+    ) -> i32;
+    /// This function restricts the access rights with which a [`URef`](casper_types::URef) held
+    /// by the current context with full access rights can be used by any other context.  Only the
+    /// context which first restricted the `URef` may change its restriction.
+    ///
+    /// # Arguments
+    ///
+    /// * `uref_ptr` - pointer to a serialized `URef`
+    /// * `uref_size` - size of the serialized `URef`
+    /// * `access_rights` - bits of the maximum access rights of the `URef` in other contexts
+    pub fn casper_restrict_uref(uref_ptr: *const u8, uref_size: usize, access_rights: u8) -> i32;
+    /// This function applies a list of changes to the associated keys and action thresholds of the
+    /// current account as a single unit, validating only the resulting account.
+    ///
+    /// Returns status code for applying the changes, where 0 represents success and non-zero
+    /// represents failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `operations_ptr` - pointer to a serialized `Vec<KeyManagementOperation>`
+    /// * `operations_size` - size of the serialized list
+    pub fn casper_manage_associated_keys(operations_ptr: *const u8, operations_size: usize) -> i32;
+    /// This function emits a message on a topic of the current contract, registering the topic if
+    /// it doesn't exist yet.  Only the checksum of the payload is written to global state.
+    ///
+    /// Returns status code for emitting the message, where 0 represents success and non-zero
+    /// represents failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name_ptr` - pointer to the UTF-8 bytes of the topic name
+    /// * `topic_name_size` - size of the topic name
+    /// * `payload_ptr` - pointer to the message payload
+    /// * `payload_size` - size of the message payload
+    pub fn casper_emit_message(
+        topic_name_ptr: *const u8,
+        topic_name_size: usize,
+        payload_ptr: *const u8,
+        payload_size: usize,
+    ) -> i32;
 }
@@ -10,6 +10,22 @@ use casper_types::contracts::NamedKeys;
 
 const ARG_INITIAL_NAMED_KEYS: &str = "initial_named_args";
 const ARG_NEW_NAMED_KEYS: &str = "new_named_keys";
+const PAGE_SIZE: u32 = 1;
+
+/// Collects all named keys by loading them one page at a time.
+fn list_named_keys_in_pages() -> NamedKeys {
+    let mut named_keys = NamedKeys::new();
+    loop {
+        let offset = named_keys.len() as u32;
+        let (page, total_keys) = runtime::list_named_keys_page(offset, PAGE_SIZE);
+        assert!(page.len() <= PAGE_SIZE as usize);
+        if page.is_empty() {
+            assert_eq!(named_keys.len(), total_keys);
+            return named_keys;
+        }
+        named_keys.extend(page);
+    }
+}
 
 #[no_mangle]
 pub extern "C" fn call() {
@@ -18,6 +34,10 @@ pub extern "C" fn call() {
 
     let actual_named_keys = runtime::list_named_keys();
     assert_eq!(expected_initial_named_keys, actual_named_keys);
+    assert_eq!(expected_initial_named_keys, list_named_keys_in_pages());
+    assert!(runtime::list_named_keys_page(u32::MAX, PAGE_SIZE)
+        .0
+        .is_empty());
 
     // Add further named keys and assert that each is returned in `list_named_keys()`.
     let new_named_keys: NamedKeys = runtime::get_named_arg(ARG_NEW_NAMED_KEYS);
@@ -28,6 +48,7 @@ pub extern "C" fn call() {
         assert!(expected_named_keys.insert(key, value).is_none());
         let actual_named_keys = runtime::list_named_keys();
         assert_eq!(expected_named_keys, actual_named_keys);
+        assert_eq!(expected_named_keys, list_named_keys_in_pages());
     }
 
     // Remove all named keys and check that removed keys aren't returned in `list_named_keys()`.
@@ -37,5 +58,6 @@ pub extern "C" fn call() {
         assert!(expected_named_keys.remove(&key).is_some());
         let actual_named_keys = runtime::list_named_keys();
         assert_eq!(expected_named_keys, actual_named_keys);
+        assert_eq!(expected_named_keys, list_named_keys_in_pages());
     }
 }
@@ -5,6 +5,7 @@ pub mod action_thresholds;
 mod action_type;
 pub mod associated_keys;
 mod error;
+mod key_management;
 mod weight;
 
 use serde::Serialize;
@@ -20,11 +21,15 @@ use core::{
 use datasize::DataSize;
 
 pub use self::{
-    account_hash::{AccountHash, ACCOUNT_HASH_FORMATTED_STRING_PREFIX, ACCOUNT_HASH_LENGTH},
+    account_hash::{
+        AccountHash, AccountHashVersion, VersionedAccountHash,
+        ACCOUNT_HASH_FORMATTED_STRING_PREFIX, ACCOUNT_HASH_LENGTH,
+    },
     action_thresholds::ActionThresholds,
     action_type::ActionType,
     associated_keys::AssociatedKeys,
     error::{FromStrError, SetThresholdFailure, TryFromIntError, TryFromSliceForAccountHashError},
+    key_management::{KeyManagementFailure, KeyManagementOperation},
     weight::{Weight, WEIGHT_SERIALIZED_LENGTH},
 };
 use crate::{
@@ -219,6 +224,55 @@ impl Account {
         self.action_thresholds.set_threshold(action_type, weight)
     }
 
+    /// Applies the given key management operations as a single unit.
+    ///
+    /// The operations are applied in order, but the action threshold constraints are only checked
+    /// against the resulting associated keys and thresholds, so intermediate states are allowed to
+    /// violate them.  If an error is returned, the account is left unchanged.
+    pub fn apply_key_management_operations(
+        &mut self,
+        operations: &[KeyManagementOperation],
+    ) -> Result<(), KeyManagementFailure> {
+        let mut associated_keys = self.associated_keys.clone();
+        let mut deployment = self.action_thresholds.deployment;
+        let mut key_management = self.action_thresholds.key_management;
+
+        for operation in operations {
+            match *operation {
+                KeyManagementOperation::AddKey {
+                    account_hash,
+                    weight,
+                } => associated_keys
+                    .add_key(account_hash, weight)
+                    .map_err(|_| KeyManagementFailure::DuplicateKey)?,
+                KeyManagementOperation::UpdateKey {
+                    account_hash,
+                    weight,
+                } => associated_keys
+                    .update_key(account_hash, weight)
+                    .map_err(|_| KeyManagementFailure::MissingKey)?,
+                KeyManagementOperation::RemoveKey { account_hash } => associated_keys
+                    .remove_key(&account_hash)
+                    .map_err(|_| KeyManagementFailure::MissingKey)?,
+                KeyManagementOperation::SetDeploymentThreshold(weight) => deployment = weight,
+                KeyManagementOperation::SetKeyManagementThreshold(weight) => {
+                    key_management = weight
+                }
+            }
+        }
+
+        let action_thresholds = ActionThresholds::new(deployment, key_management)
+            .map_err(|_| KeyManagementFailure::DeploymentThreshold)?;
+        let total_weight = associated_keys.total_keys_weight();
+        if total_weight < deployment || total_weight < key_management {
+            return Err(KeyManagementFailure::InsufficientTotalWeight);
+        }
+
+        self.associated_keys = associated_keys;
+        self.action_thresholds = action_thresholds;
+        Ok(())
+    }
+
     /// Verifies if user can set action threshold.
     pub fn can_set_threshold(&self, new_threshold: Weight) -> Result<(), SetThresholdFailure> {
         let total_weight = self.associated_keys.total_keys_weight();
@@ -495,11 +549,13 @@ pub mod gens {
 mod tests {
     use crate::{
         account::{
-            Account, AccountHash, ActionThresholds, ActionType, AssociatedKeys, RemoveKeyFailure,
-            SetThresholdFailure, UpdateKeyFailure, Weight,
+            Account, AccountHash, AccountHashVersion, ActionThresholds, ActionType, AssociatedKeys,
+            FromStrError, RemoveKeyFailure, SetThresholdFailure, UpdateKeyFailure,
+            VersionedAccountHash, Weight,
         },
+        bech32, bytesrepr,
         contracts::NamedKeys,
-        AccessRights, URef,
+        AccessRights, PublicKey, SecretKey, URef,
     };
     use std::{collections::BTreeSet, convert::TryFrom, iter::FromIterator, vec::Vec};
 
@@ -596,6 +652,36 @@ mod tests {
         assert!(AccountHash::from_formatted_str(invalid_hex).is_err());
     }
 
+    #[test]
+    fn account_hash_from_bech32_str() {
+        let account_hash = AccountHash([3; 32]);
+        let encoded = account_hash.to_bech32_string();
+        assert!(encoded.starts_with("casper1"));
+        assert_eq!(
+            AccountHash::from_formatted_str(&encoded).unwrap(),
+            account_hash
+        );
+        assert_eq!(
+            AccountHash::from_formatted_str(&encoded.to_uppercase()).unwrap(),
+            account_hash
+        );
+
+        let mut typo = encoded.into_bytes();
+        let last = typo.len() - 1;
+        typo[last] = if typo[last] == b'q' { b'p' } else { b'q' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert!(matches!(
+            AccountHash::from_formatted_str(&typo),
+            Err(FromStrError::Bech32(_))
+        ));
+
+        let short = bech32::encode(bech32::ACCOUNT_HASH_HRP, &[3; 31]);
+        assert!(matches!(
+            AccountHash::from_formatted_str(&short),
+            Err(FromStrError::Hash(_))
+        ));
+    }
+
     #[test]
     fn account_hash_serde_roundtrip() {
         let account_hash = AccountHash([255; 32]);
@@ -612,6 +698,76 @@ mod tests {
         assert_eq!(account_hash, decoded);
     }
 
+    #[test]
+    fn account_hash_versions_should_differ() {
+        let secret_key = SecretKey::ed25519_from_bytes([1; 32]).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+
+        let v1 = VersionedAccountHash::from_public_key(&public_key, AccountHashVersion::V1);
+        let v2 = VersionedAccountHash::from_public_key(&public_key, AccountHashVersion::V2);
+        assert_eq!(v1.account_hash(), public_key.to_account_hash());
+        assert_ne!(v1.account_hash(), v2.account_hash());
+        assert_eq!(v2.version(), AccountHashVersion::V2);
+    }
+
+    #[test]
+    fn versioned_account_hash_from_formatted_str() {
+        let versioned = VersionedAccountHash::new(AccountHashVersion::V2, AccountHash([7; 32]));
+        let formatted = versioned.to_formatted_string();
+        assert!(formatted.starts_with("account-hash-v2-"));
+        assert_eq!(
+            VersionedAccountHash::from_formatted_str(&formatted).unwrap(),
+            versioned
+        );
+        assert_eq!(
+            AccountHash::from_formatted_str(&formatted).unwrap(),
+            AccountHash([7; 32])
+        );
+
+        // Unversioned strings are version 1.
+        let account_hash = AccountHash([8; 32]);
+        for unversioned in [
+            account_hash.to_formatted_string(),
+            account_hash.to_bech32_string(),
+        ] {
+            assert_eq!(
+                VersionedAccountHash::from_formatted_str(&unversioned).unwrap(),
+                VersionedAccountHash::new(AccountHashVersion::V1, account_hash)
+            );
+        }
+
+        let hex = base16::encode_lower(&[7; 32]);
+        for invalid_version in ["v0", "v3", "vx", "v"] {
+            let invalid = format!("account-hash-{}-{}", invalid_version, hex);
+            assert!(matches!(
+                VersionedAccountHash::from_formatted_str(&invalid),
+                Err(FromStrError::InvalidVersion)
+            ));
+        }
+        assert!(matches!(
+            VersionedAccountHash::from_formatted_str(&format!("account-hash-v2{}", hex)),
+            Err(FromStrError::InvalidVersion)
+        ));
+    }
+
+    #[test]
+    fn versioned_account_hash_roundtrip() {
+        let versioned = VersionedAccountHash::new(AccountHashVersion::V2, AccountHash([9; 32]));
+        bytesrepr::test_serialization_roundtrip(&versioned);
+
+        let json_string = serde_json::to_string(&versioned).unwrap();
+        assert_eq!(
+            json_string,
+            format!("\"account-hash-v2-{}\"", base16::encode_lower(&[9; 32]))
+        );
+        let decoded: VersionedAccountHash = serde_json::from_str(&json_string).unwrap();
+        assert_eq!(versioned, decoded);
+
+        let serialized = bincode::serialize(&versioned).unwrap();
+        let decoded: VersionedAccountHash = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(versioned, decoded);
+    }
+
     #[test]
     fn associated_keys_can_authorize_keys() {
         let key_1 = AccountHash::new([0; 32]);
@@ -954,6 +1110,93 @@ mod tests {
             .expect("should work");
     }
 
+    #[test]
+    fn key_management_operations_should_only_validate_end_state() {
+        let identity_key = AccountHash::new([1u8; 32]);
+        let key_1 = AccountHash::new([2u8; 32]);
+        let key_2 = AccountHash::new([3u8; 32]);
+        let key_3 = AccountHash::new([4u8; 32]);
+        let mut account = Account::create(
+            identity_key,
+            NamedKeys::new(),
+            URef::new([0u8; 32], AccessRights::READ_ADD_WRITE),
+        );
+
+        // Raising the thresholds first and removing the identity key would each be rejected if
+        // applied one at a time.
+        let operations = [
+            KeyManagementOperation::SetKeyManagementThreshold(Weight::new(3)),
+            KeyManagementOperation::SetDeploymentThreshold(Weight::new(2)),
+            KeyManagementOperation::RemoveKey {
+                account_hash: identity_key,
+            },
+            KeyManagementOperation::AddKey {
+                account_hash: key_1,
+                weight: Weight::new(1),
+            },
+            KeyManagementOperation::AddKey {
+                account_hash: key_2,
+                weight: Weight::new(1),
+            },
+            KeyManagementOperation::AddKey {
+                account_hash: key_3,
+                weight: Weight::new(1),
+            },
+        ];
+        account
+            .apply_key_management_operations(&operations)
+            .expect("should apply operations");
+
+        assert!(!account.associated_keys().contains_key(&identity_key));
+        assert_eq!(account.associated_keys().len(), 3);
+        assert_eq!(
+            account.action_thresholds(),
+            &ActionThresholds::new(Weight::new(2), Weight::new(3)).unwrap()
+        );
+    }
+
+    #[test]
+    fn failed_key_management_operations_should_leave_account_unchanged() {
+        let identity_key = AccountHash::new([1u8; 32]);
+        let key_1 = AccountHash::new([2u8; 32]);
+        let account = Account::create(
+            identity_key,
+            NamedKeys::new(),
+            URef::new([0u8; 32], AccessRights::READ_ADD_WRITE),
+        );
+        let add_key_1 = KeyManagementOperation::AddKey {
+            account_hash: key_1,
+            weight: Weight::new(1),
+        };
+
+        let mut modified = account.clone();
+        assert_eq!(
+            modified.apply_key_management_operations(&[
+                add_key_1,
+                KeyManagementOperation::SetKeyManagementThreshold(Weight::new(3)),
+            ]),
+            Err(KeyManagementFailure::InsufficientTotalWeight)
+        );
+        assert_eq!(
+            modified.apply_key_management_operations(&[
+                add_key_1,
+                KeyManagementOperation::SetDeploymentThreshold(Weight::new(2)),
+            ]),
+            Err(KeyManagementFailure::DeploymentThreshold)
+        );
+        assert_eq!(
+            modified.apply_key_management_operations(&[add_key_1, add_key_1]),
+            Err(KeyManagementFailure::DuplicateKey)
+        );
+        assert_eq!(
+            modified.apply_key_management_operations(&[KeyManagementOperation::RemoveKey {
+                account_hash: key_1
+            }]),
+            Err(KeyManagementFailure::MissingKey)
+        );
+        assert_eq!(modified, account);
+    }
+
     #[test]
     fn should_extract_access_rights() {
         const MAIN_PURSE: URef = URef::new([2; 32], AccessRights::READ_ADD_WRITE);
@@ -15,8 +15,9 @@ use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Seria
 
 use super::FromStrError;
 use crate::{
-    bytesrepr::{Error, FromBytes, ToBytes},
-    checksummed_hex, crypto, CLType, CLTyped, PublicKey, BLAKE2B_DIGEST_LENGTH,
+    bech32,
+    bytesrepr::{Error, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    checksummed_hex, crypto, CLType, CLTyped, PublicKey, Tagged, BLAKE2B_DIGEST_LENGTH,
 };
 
 /// The length in bytes of a [`AccountHash`].
@@ -24,6 +25,8 @@ pub const ACCOUNT_HASH_LENGTH: usize = 32;
 /// The prefix applied to the hex-encoded `AccountHash` to produce a formatted string
 /// representation.
 pub const ACCOUNT_HASH_FORMATTED_STRING_PREFIX: &str = "account-hash-";
+/// The domain separator prefixed to the preimage of a version 2 `AccountHash`.
+const ACCOUNT_HASH_V2_DOMAIN_SEPARATOR: &[u8] = b"casper-account-hash-v2";
 
 /// A newtype wrapping an array which contains the raw bytes of
 /// the AccountHash, a hash of Public Key and Algorithm
@@ -56,43 +59,308 @@ impl AccountHash {
         )
     }
 
-    /// Parses a string formatted as per `Self::to_formatted_string()` into an `AccountHash`.
+    /// Parses a string formatted as per `Self::to_formatted_string()`,
+    /// `Self::to_bech32_string()` or `VersionedAccountHash::to_formatted_string()` into an
+    /// `AccountHash`, discarding any version.
     pub fn from_formatted_str(input: &str) -> Result<Self, FromStrError> {
-        let remainder = input
-            .strip_prefix(ACCOUNT_HASH_FORMATTED_STRING_PREFIX)
-            .ok_or(FromStrError::InvalidPrefix)?;
-        let bytes =
-            <[u8; ACCOUNT_HASH_LENGTH]>::try_from(checksummed_hex::decode(remainder)?.as_ref())?;
-        Ok(AccountHash(bytes))
+        VersionedAccountHash::from_formatted_str(input)
+            .map(|versioned_account_hash| versioned_account_hash.account_hash)
+    }
+
+    /// Formats the `AccountHash` as a bech32m string, e.g. "casper1...".
+    pub fn to_bech32_string(self) -> String {
+        bech32::encode(bech32::ACCOUNT_HASH_HRP, &self.0)
     }
 
-    /// Parses a `PublicKey` and outputs the corresponding account hash.
+    /// Parses a string formatted as per `Self::to_bech32_string()` into an `AccountHash`.
+    pub fn from_bech32_str(input: &str) -> Result<Self, FromStrError> {
+        let bytes = bech32::decode(input, bech32::ACCOUNT_HASH_HRP)?;
+        Ok(AccountHash(<[u8; ACCOUNT_HASH_LENGTH]>::try_from(
+            bytes.as_ref(),
+        )?))
+    }
+
+    /// Parses a `PublicKey` and outputs the corresponding account hash, derived via
+    /// [`AccountHashVersion::V1`].
     pub fn from_public_key(
         public_key: &PublicKey,
         blake2b_hash_fn: impl Fn(Vec<u8>) -> [u8; BLAKE2B_DIGEST_LENGTH],
     ) -> Self {
+        Self::from_public_key_with_version(public_key, AccountHashVersion::V1, blake2b_hash_fn)
+    }
+
+    /// Parses a `PublicKey` and outputs the corresponding account hash, derived via the given
+    /// version of the derivation scheme.
+    pub fn from_public_key_with_version(
+        public_key: &PublicKey,
+        version: AccountHashVersion,
+        blake2b_hash_fn: impl Fn(Vec<u8>) -> [u8; BLAKE2B_DIGEST_LENGTH],
+    ) -> Self {
+        let preimage = match version {
+            AccountHashVersion::V1 => Self::v1_preimage(public_key),
+            AccountHashVersion::V2 => Self::v2_preimage(public_key),
+        };
+        // Hash the preimage data using blake2b256 and return it.
+        let digest = blake2b_hash_fn(preimage);
+        Self::new(digest)
+    }
+
+    /// Returns the preimage of a version 1 account hash: the lowercase name of the key's algorithm,
+    /// a zero byte and the raw public key bytes.
+    fn v1_preimage(public_key: &PublicKey) -> Vec<u8> {
         const SYSTEM_LOWERCASE: &str = "system";
         const ED25519_LOWERCASE: &str = "ed25519";
         const SECP256K1_LOWERCASE: &str = "secp256k1";
+        const SECP256R1_LOWERCASE: &str = "secp256r1";
 
         let algorithm_name = match public_key {
             PublicKey::System => SYSTEM_LOWERCASE,
             PublicKey::Ed25519(_) => ED25519_LOWERCASE,
             PublicKey::Secp256k1(_) => SECP256K1_LOWERCASE,
+            PublicKey::Secp256r1(_) => SECP256R1_LOWERCASE,
         };
         let public_key_bytes: Vec<u8> = public_key.into();
 
-        // Prepare preimage based on the public key parameters.
-        let preimage = {
-            let mut data = Vec::with_capacity(algorithm_name.len() + public_key_bytes.len() + 1);
-            data.extend(algorithm_name.as_bytes());
-            data.push(0);
-            data.extend(public_key_bytes);
-            data
+        let mut data = Vec::with_capacity(algorithm_name.len() + public_key_bytes.len() + 1);
+        data.extend(algorithm_name.as_bytes());
+        data.push(0);
+        data.extend(public_key_bytes);
+        data
+    }
+
+    /// Returns the preimage of a version 2 account hash: a domain separator, the key's algorithm
+    /// tag and the raw public key bytes.
+    fn v2_preimage(public_key: &PublicKey) -> Vec<u8> {
+        let public_key_bytes: Vec<u8> = public_key.into();
+        let mut data =
+            Vec::with_capacity(ACCOUNT_HASH_V2_DOMAIN_SEPARATOR.len() + public_key_bytes.len() + 1);
+        data.extend(ACCOUNT_HASH_V2_DOMAIN_SEPARATOR);
+        data.push(public_key.tag());
+        data.extend(public_key_bytes);
+        data
+    }
+}
+
+/// The version of the scheme used to derive an [`AccountHash`] from a [`PublicKey`].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+pub enum AccountHashVersion {
+    /// The BLAKE2b-256 hash of the lowercase name of the key's algorithm, a zero byte and the raw
+    /// public key bytes.  Used for all existing accounts.
+    V1 = 1,
+    /// The BLAKE2b-256 hash of a domain separator, the key's algorithm tag and the raw public key
+    /// bytes.
+    V2 = 2,
+}
+
+impl AccountHashVersion {
+    /// All supported versions, in ascending order.
+    pub const ALL: [AccountHashVersion; 2] = [AccountHashVersion::V1, AccountHashVersion::V2];
+}
+
+impl Default for AccountHashVersion {
+    fn default() -> Self {
+        AccountHashVersion::V1
+    }
+}
+
+impl Display for AccountHashVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "v{}", *self as u8)
+    }
+}
+
+impl TryFrom<u8> for AccountHashVersion {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(AccountHashVersion::V1),
+            2 => Ok(AccountHashVersion::V2),
+            _ => Err(Error::Formatting),
+        }
+    }
+}
+
+impl ToBytes for AccountHashVersion {
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        (*self as u8).to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), Error> {
+        writer.push(*self as u8);
+        Ok(())
+    }
+}
+
+impl FromBytes for AccountHashVersion {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (version, remainder) = u8::from_bytes(bytes)?;
+        Ok((AccountHashVersion::try_from(version)?, remainder))
+    }
+}
+
+impl Distribution<AccountHashVersion> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> AccountHashVersion {
+        if rng.gen() {
+            AccountHashVersion::V1
+        } else {
+            AccountHashVersion::V2
+        }
+    }
+}
+
+/// An [`AccountHash`] along with the version of the scheme used to derive it.
+///
+/// Its formatted string representation carries the version, e.g. "account-hash-v2-..." for a
+/// version 2 account hash.  Formatted strings without a version, as produced by
+/// [`AccountHash::to_formatted_string`] or [`AccountHash::to_bech32_string`], are parsed as
+/// version 1.
+#[derive(Default, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+pub struct VersionedAccountHash {
+    version: AccountHashVersion,
+    account_hash: AccountHash,
+}
+
+impl VersionedAccountHash {
+    /// Constructs a new `VersionedAccountHash`.
+    pub const fn new(version: AccountHashVersion, account_hash: AccountHash) -> Self {
+        VersionedAccountHash {
+            version,
+            account_hash,
+        }
+    }
+
+    /// Derives the account hash of the given public key via the given version of the derivation
+    /// scheme.
+    pub fn from_public_key(public_key: &PublicKey, version: AccountHashVersion) -> Self {
+        let account_hash =
+            AccountHash::from_public_key_with_version(public_key, version, crypto::blake2b);
+        VersionedAccountHash::new(version, account_hash)
+    }
+
+    /// Returns the version of the derivation scheme.
+    pub fn version(&self) -> AccountHashVersion {
+        self.version
+    }
+
+    /// Returns the account hash.
+    pub fn account_hash(&self) -> AccountHash {
+        self.account_hash
+    }
+
+    /// Formats the `VersionedAccountHash` as "account-hash-v<version>-<hex-encoded hash>".
+    pub fn to_formatted_string(self) -> String {
+        format!(
+            "{}{}-{}",
+            ACCOUNT_HASH_FORMATTED_STRING_PREFIX,
+            self.version,
+            base16::encode_lower(&self.account_hash.0),
+        )
+    }
+
+    /// Parses a string formatted as per `Self::to_formatted_string()`,
+    /// `AccountHash::to_formatted_string()` or `AccountHash::to_bech32_string()` into a
+    /// `VersionedAccountHash`.  Strings without a version are taken to be version 1.
+    pub fn from_formatted_str(input: &str) -> Result<Self, FromStrError> {
+        if is_bech32(input) {
+            let account_hash = AccountHash::from_bech32_str(input)?;
+            return Ok(VersionedAccountHash::new(
+                AccountHashVersion::V1,
+                account_hash,
+            ));
+        }
+        let remainder = input
+            .strip_prefix(ACCOUNT_HASH_FORMATTED_STRING_PREFIX)
+            .ok_or(FromStrError::InvalidPrefix)?;
+        let (version, hex) = match remainder.strip_prefix('v') {
+            Some(versioned) => {
+                let (version, hex) = versioned
+                    .split_once('-')
+                    .ok_or(FromStrError::InvalidVersion)?;
+                let version = version
+                    .parse::<u8>()
+                    .ok()
+                    .and_then(|version| AccountHashVersion::try_from(version).ok())
+                    .ok_or(FromStrError::InvalidVersion)?;
+                (version, hex)
+            }
+            None => (AccountHashVersion::V1, remainder),
         };
-        // Hash the preimage data using blake2b256 and return it.
-        let digest = blake2b_hash_fn(preimage);
-        Self::new(digest)
+        let bytes = <[u8; ACCOUNT_HASH_LENGTH]>::try_from(checksummed_hex::decode(hex)?.as_ref())?;
+        Ok(VersionedAccountHash::new(version, AccountHash(bytes)))
+    }
+}
+
+impl Display for VersionedAccountHash {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}-{}", self.version, self.account_hash)
+    }
+}
+
+impl From<VersionedAccountHash> for AccountHash {
+    fn from(versioned_account_hash: VersionedAccountHash) -> Self {
+        versioned_account_hash.account_hash
+    }
+}
+
+impl Serialize for VersionedAccountHash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            self.to_formatted_string().serialize(serializer)
+        } else {
+            (self.version, self.account_hash).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionedAccountHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let formatted_string = String::deserialize(deserializer)?;
+            VersionedAccountHash::from_formatted_str(&formatted_string).map_err(SerdeError::custom)
+        } else {
+            let (version, account_hash) =
+                <(AccountHashVersion, AccountHash)>::deserialize(deserializer)?;
+            Ok(VersionedAccountHash::new(version, account_hash))
+        }
+    }
+}
+
+impl ToBytes for VersionedAccountHash {
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::with_capacity(self.serialized_length());
+        self.write_bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.version.serialized_length() + self.account_hash.serialized_length()
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), Error> {
+        self.version.write_bytes(writer)?;
+        self.account_hash.write_bytes(writer)
+    }
+}
+
+impl FromBytes for VersionedAccountHash {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (version, remainder) = AccountHashVersion::from_bytes(bytes)?;
+        let (account_hash, remainder) = AccountHash::from_bytes(remainder)?;
+        Ok((VersionedAccountHash::new(version, account_hash), remainder))
+    }
+}
+
+impl Distribution<VersionedAccountHash> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> VersionedAccountHash {
+        VersionedAccountHash::new(rng.gen(), rng.gen())
     }
 }
 
@@ -105,7 +373,8 @@ impl JsonSchema for AccountHash {
     fn json_schema(gen: &mut SchemaGenerator) -> Schema {
         let schema = gen.subschema_for::<String>();
         let mut schema_object = schema.into_object();
-        schema_object.metadata().description = Some("Hex-encoded account hash.".to_string());
+        schema_object.metadata().description =
+            Some("Hex-encoded or bech32m-encoded account hash.".to_string());
         schema_object.into()
     }
 }
@@ -113,6 +382,9 @@ impl JsonSchema for AccountHash {
 impl Serialize for AccountHash {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         if serializer.is_human_readable() {
+            if bech32::json_output() {
+                return self.to_bech32_string().serialize(serializer);
+            }
             self.to_formatted_string().serialize(serializer)
         } else {
             self.0.serialize(serializer)
@@ -132,6 +404,15 @@ impl<'de> Deserialize<'de> for AccountHash {
     }
 }
 
+/// Returns whether `input` looks like a bech32m-encoded account hash, ignoring case.
+fn is_bech32(input: &str) -> bool {
+    let prefix_length = bech32::ACCOUNT_HASH_HRP.len() + 1;
+    input.len() > prefix_length
+        && input.is_char_boundary(prefix_length)
+        && input[..prefix_length - 1].eq_ignore_ascii_case(bech32::ACCOUNT_HASH_HRP)
+        && input.as_bytes()[prefix_length - 1] == b'1'
+}
+
 impl TryFrom<&[u8]> for AccountHash {
     type Error = TryFromSliceForAccountHashError;
 
@@ -4,10 +4,12 @@ use core::{
     fmt::{self, Display, Formatter},
 };
 
+use crate::bech32;
+
 // This error type is not intended to be used by third party crates.
 #[doc(hidden)]
 #[derive(Debug, Eq, PartialEq)]
-pub struct TryFromIntError(pub(super) ());
+pub struct TryFromIntError(pub(crate) ());
 
 /// Error returned when decoding an `AccountHash` from a formatted string.
 #[derive(Debug)]
@@ -19,6 +21,16 @@ pub enum FromStrError {
     Hex(base16::DecodeError),
     /// The hash is the wrong length.
     Hash(TryFromSliceError),
+    /// The hash is not valid bech32m.
+    Bech32(bech32::Error),
+    /// The version of the account hash is malformed or unsupported.
+    InvalidVersion,
+}
+
+impl From<bech32::Error> for FromStrError {
+    fn from(error: bech32::Error) -> Self {
+        FromStrError::Bech32(error)
+    }
 }
 
 impl From<base16::DecodeError> for FromStrError {
@@ -41,6 +53,8 @@ impl Display for FromStrError {
                 write!(f, "failed to decode address portion from hex: {}", error)
             }
             FromStrError::Hash(error) => write!(f, "address portion is wrong length: {}", error),
+            FromStrError::Bech32(error) => write!(f, "failed to decode bech32m address: {}", error),
+            FromStrError::InvalidVersion => write!(f, "invalid account hash version"),
         }
     }
 }
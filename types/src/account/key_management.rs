@@ -0,0 +1,161 @@
+//! Batched changes to an account's associated keys and action thresholds.
+
+use core::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    account::{AccountHash, TryFromIntError, Weight},
+    bytesrepr::{FromBytes, ToBytes},
+};
+
+/// A single change to an account's associated keys or action thresholds.
+///
+/// A sequence of operations is applied as one unit via
+/// [`Account::apply_key_management_operations`](super::Account::apply_key_management_operations),
+/// with the threshold constraints only being checked against the resulting account.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ToBytes, FromBytes)]
+pub enum KeyManagementOperation {
+    /// Associates a new [`AccountHash`] with the given [`Weight`].
+    #[bytesrepr(tag = 0)]
+    AddKey {
+        /// The key to associate.
+        account_hash: AccountHash,
+        /// The weight of the key.
+        weight: Weight,
+    },
+    /// Sets the [`Weight`] of an already associated [`AccountHash`].
+    #[bytesrepr(tag = 1)]
+    UpdateKey {
+        /// The key to update.
+        account_hash: AccountHash,
+        /// The new weight of the key.
+        weight: Weight,
+    },
+    /// Disassociates an [`AccountHash`].
+    #[bytesrepr(tag = 2)]
+    RemoveKey {
+        /// The key to remove.
+        account_hash: AccountHash,
+    },
+    /// Sets the threshold for [`ActionType::Deployment`](super::ActionType::Deployment).
+    #[bytesrepr(tag = 3)]
+    SetDeploymentThreshold(Weight),
+    /// Sets the threshold for [`ActionType::KeyManagement`](super::ActionType::KeyManagement).
+    #[bytesrepr(tag = 4)]
+    SetKeyManagementThreshold(Weight),
+}
+
+/// Errors that can occur while applying a batch of [`KeyManagementOperation`]s to an account.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum KeyManagementFailure {
+    /// An operation updates or removes an [`AccountHash`] which is not associated with the account
+    /// at that point in the batch.
+    MissingKey = 1,
+    /// An operation adds an [`AccountHash`] which is already associated with the account at that
+    /// point in the batch.
+    DuplicateKey = 2,
+    /// Caller doesn't have sufficient permissions to manage the keys of the given account.
+    PermissionDenied = 3,
+    /// The resulting account would have more than the maximum number of associated keys.
+    MaxKeysLimit = 4,
+    /// The resulting deployment threshold would be greater than the key management threshold.
+    DeploymentThreshold = 5,
+    /// The resulting total weight of all associated keys would be below one of the action
+    /// thresholds.
+    InsufficientTotalWeight = 6,
+}
+
+impl Display for KeyManagementFailure {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            KeyManagementFailure::MissingKey => formatter
+                .write_str("Unable to manage associated keys because a given key does not exist"),
+            KeyManagementFailure::DuplicateKey => formatter
+                .write_str("Unable to manage associated keys because a given key already exists"),
+            KeyManagementFailure::PermissionDenied => formatter
+                .write_str("Unable to manage associated keys due to insufficient permissions"),
+            KeyManagementFailure::MaxKeysLimit => formatter.write_str(
+                "Unable to manage associated keys because maximum amount of keys would be exceeded",
+            ),
+            KeyManagementFailure::DeploymentThreshold => formatter.write_str(
+                "Unable to manage associated keys because deployment threshold would exceed key \
+                management threshold",
+            ),
+            KeyManagementFailure::InsufficientTotalWeight => formatter.write_str(
+                "Unable to manage associated keys because total weight would fall below action \
+                thresholds",
+            ),
+        }
+    }
+}
+
+// This conversion is not intended to be used by third party crates.
+#[doc(hidden)]
+impl TryFrom<i32> for KeyManagementFailure {
+    type Error = TryFromIntError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            d if d == KeyManagementFailure::MissingKey as i32 => {
+                Ok(KeyManagementFailure::MissingKey)
+            }
+            d if d == KeyManagementFailure::DuplicateKey as i32 => {
+                Ok(KeyManagementFailure::DuplicateKey)
+            }
+            d if d == KeyManagementFailure::PermissionDenied as i32 => {
+                Ok(KeyManagementFailure::PermissionDenied)
+            }
+            d if d == KeyManagementFailure::MaxKeysLimit as i32 => {
+                Ok(KeyManagementFailure::MaxKeysLimit)
+            }
+            d if d == KeyManagementFailure::DeploymentThreshold as i32 => {
+                Ok(KeyManagementFailure::DeploymentThreshold)
+            }
+            d if d == KeyManagementFailure::InsufficientTotalWeight as i32 => {
+                Ok(KeyManagementFailure::InsufficientTotalWeight)
+            }
+            _ => Err(TryFromIntError(())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytesrepr;
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        let account_hash = AccountHash::new([3; 32]);
+        for operation in [
+            KeyManagementOperation::AddKey {
+                account_hash,
+                weight: Weight::new(2),
+            },
+            KeyManagementOperation::UpdateKey {
+                account_hash,
+                weight: Weight::new(5),
+            },
+            KeyManagementOperation::RemoveKey { account_hash },
+            KeyManagementOperation::SetDeploymentThreshold(Weight::new(1)),
+            KeyManagementOperation::SetKeyManagementThreshold(Weight::new(7)),
+        ] {
+            bytesrepr::test_serialization_roundtrip(&operation);
+        }
+    }
+
+    #[test]
+    fn try_from_i32_for_key_management_failure() {
+        let max_valid_value_for_variant = KeyManagementFailure::InsufficientTotalWeight as i32;
+        assert_eq!(
+            Err(TryFromIntError(())),
+            KeyManagementFailure::try_from(max_valid_value_for_variant + 1),
+            "Did you forget to update `KeyManagementFailure::try_from` for a new variant of \
+                   `KeyManagementFailure`, or `max_valid_value_for_variant` in this test?"
+        );
+    }
+}
@@ -7,8 +7,8 @@ use core::{
 
 use crate::{
     account::{
-        AddKeyFailure, RemoveKeyFailure, SetThresholdFailure, TryFromIntError,
-        TryFromSliceForAccountHashError, UpdateKeyFailure,
+        AddKeyFailure, KeyManagementFailure, RemoveKeyFailure, SetThresholdFailure,
+        TryFromIntError, TryFromSliceForAccountHashError, UpdateKeyFailure,
     },
     bytesrepr, contracts,
     system::{auction, handle_payment, mint},
@@ -451,6 +451,19 @@ impl From<SetThresholdFailure> for ApiError {
     }
 }
 
+impl From<KeyManagementFailure> for ApiError {
+    fn from(error: KeyManagementFailure) -> Self {
+        match error {
+            KeyManagementFailure::MissingKey => ApiError::MissingKey,
+            KeyManagementFailure::DuplicateKey => ApiError::DuplicateKey,
+            KeyManagementFailure::PermissionDenied => ApiError::PermissionDenied,
+            KeyManagementFailure::MaxKeysLimit => ApiError::MaxKeysLimit,
+            KeyManagementFailure::DeploymentThreshold => ApiError::DeploymentThreshold,
+            KeyManagementFailure::InsufficientTotalWeight => ApiError::InsufficientTotalWeight,
+        }
+    }
+}
+
 impl From<CLValueError> for ApiError {
     fn from(error: CLValueError) -> Self {
         match error {
@@ -0,0 +1,283 @@
+//! [`Arbitrary`] implementations for the serializable types of this crate, for use in fuzzing and
+//! property-based tests via [`Proptest`](https://crates.io/crates/proptest).
+//!
+//! Types which already have a dedicated strategy in [`gens`](crate::gens) use it, while the
+//! remaining types are generated from their `Distribution` or `random` constructors, seeded by
+//! proptest so that failing cases can be shrunk and replayed.
+//!
+//! The [`test_bytesrepr_roundtrip`](crate::test_bytesrepr_roundtrip) macro generates property
+//! tests checking the `bytesrepr` encoding of any type implementing [`Arbitrary`].
+
+use core::fmt::Debug;
+
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection,
+    strategy::{BoxedStrategy, Strategy},
+};
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+
+use crate::{
+    account::{
+        action_thresholds::gens::action_thresholds_arb, associated_keys::gens::associated_keys_arb,
+        gens::account_arb, Account, AccountHash, ActionThresholds, AssociatedKeys, Weight,
+    },
+    bytesrepr::Bytes,
+    contract_messages::{MessageAddr, MessageChecksum, MessageTopicSummary},
+    crypto::gens::public_key_arb,
+    gens,
+    system::auction::{
+        gens::{era_info_arb, seigniorage_allocation_arb},
+        Bid, EraInfo, SeigniorageAllocation, UnbondingPurse, WithdrawPurse,
+    },
+    testing::TestRng,
+    AccessRights, BlockTime, CLType, CLValue, Contract, ContractPackage, ContractVersionKey,
+    ContractWasm, DeployHash, DeployInfo, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, EraId, ExecutionResult, ExecutionResultV2, Group, Key, NamedArg, Parameter, Phase,
+    PricingMode, ProtocolVersion, PublicKey, SemVer, StoredValue, Timestamp, TransactionEntryPoint,
+    TransactionInvocationTarget, TransactionTarget, TransactionV2, TransactionV2Hash, Transfer,
+    TransferAddr, TransferV2, Transform, URef, U128, U256, U512,
+};
+
+#[doc(hidden)]
+pub use paste;
+#[doc(hidden)]
+pub use proptest;
+
+/// The maximum length of the collections generated for the [`Arbitrary`] implementations.
+const MAX_COLLECTION_LENGTH: usize = 4;
+
+/// Returns a strategy generating values via `generate`, called with a [`TestRng`] seeded by
+/// proptest.
+pub fn from_test_rng<T>(generate: fn(&mut TestRng) -> T) -> BoxedStrategy<T>
+where
+    T: Debug + 'static,
+{
+    any::<[u8; 16]>()
+        .prop_map(move |seed| generate(&mut TestRng::for_strategy(seed)))
+        .boxed()
+}
+
+/// Returns a strategy generating values via their `Distribution` implementation, with an RNG
+/// seeded by proptest.
+pub fn from_distribution<T>() -> BoxedStrategy<T>
+where
+    T: Debug + 'static,
+    Standard: Distribution<T>,
+{
+    from_test_rng(|rng| rng.gen())
+}
+
+/// Generates a property test for each of the given types, checking that arbitrary values of the
+/// type survive a `bytesrepr` round trip and that `serialized_length` matches the length of the
+/// actual encoding.
+///
+/// The types must implement [`Arbitrary`] as well as `ToBytes`, `FromBytes`, `PartialEq` and
+/// `Debug`.  Each test is named `bytesrepr_roundtrip_` followed by the type name in snake case:
+///
+/// ```
+/// # use casper_types::{test_bytesrepr_roundtrip, Key, U512};
+/// test_bytesrepr_roundtrip!(Key, U512);
+/// ```
+///
+/// Types which are not a single identifier, e.g. generic types, need an explicit test name:
+///
+/// ```
+/// # use casper_types::{test_bytesrepr_roundtrip, Key};
+/// test_bytesrepr_roundtrip!(bytesrepr_roundtrip_key_vec: Vec<Key>);
+/// ```
+#[macro_export]
+macro_rules! test_bytesrepr_roundtrip {
+    ($($ty:ident),+ $(,)?) => {
+        $crate::arbitrary::paste::paste! {
+            $crate::test_bytesrepr_roundtrip!($([<bytesrepr_roundtrip_ $ty:snake>]: $ty),+);
+        }
+    };
+    ($($name:ident: $ty:ty),+ $(,)?) => {
+        $crate::arbitrary::proptest::proptest! {
+            $(
+                #[test]
+                fn $name(value in $crate::arbitrary::proptest::arbitrary::any::<$ty>()) {
+                    $crate::bytesrepr::test_serialization_roundtrip(&value);
+                }
+            )+
+        }
+    };
+}
+
+macro_rules! impl_arbitrary {
+    ($($ty:ty => $strategy:expr),* $(,)?) => {
+        $(
+            impl Arbitrary for $ty {
+                type Parameters = ();
+                type Strategy = BoxedStrategy<Self>;
+
+                fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+                    $strategy.boxed()
+                }
+            }
+        )*
+    };
+}
+
+impl_arbitrary! {
+    AccessRights => gens::access_rights_arb(),
+    Account => account_arb(),
+    AccountHash => gens::account_hash_arb(),
+    ActionThresholds => action_thresholds_arb(),
+    AssociatedKeys => associated_keys_arb(),
+    Bid => gens::bid_arb(0..MAX_COLLECTION_LENGTH),
+    BlockTime => any::<u64>().prop_map(BlockTime::new),
+    Bytes => collection::vec(any::<u8>(), 0..64).prop_map(Bytes::from),
+    CLType => gens::cl_type_arb(),
+    CLValue => gens::cl_value_arb(),
+    Contract => gens::contract_arb(),
+    ContractPackage => gens::contract_package_arb(),
+    ContractVersionKey => gens::contract_version_key_arb(),
+    ContractWasm => gens::contract_wasm_arb(),
+    DeployHash => from_distribution(),
+    DeployInfo => gens::deploy_info_arb(),
+    EntryPoint => gens::entry_point_arb(),
+    EntryPointAccess => gens::entry_point_access_arb(),
+    EntryPointType => gens::entry_point_type_arb(),
+    EntryPoints => gens::entry_points_arb(),
+    EraId => gens::era_id_arb(),
+    EraInfo => era_info_arb(0..MAX_COLLECTION_LENGTH),
+    ExecutionResult => from_distribution(),
+    ExecutionResultV2 => from_distribution(),
+    Group => gens::group_arb(),
+    Key => gens::key_arb(),
+    MessageAddr => gens::message_addr_arb(),
+    MessageChecksum => from_distribution(),
+    MessageTopicSummary => gens::message_topic_summary_arb(),
+    NamedArg => gens::named_args_arb(),
+    Parameter => gens::parameter_arb(),
+    Phase => gens::phase_arb(),
+    PricingMode => from_test_rng(PricingMode::random),
+    ProtocolVersion => gens::protocol_version_arb(),
+    PublicKey => public_key_arb(),
+    SeigniorageAllocation => seigniorage_allocation_arb(),
+    SemVer => gens::sem_ver_arb(),
+    StoredValue => gens::stored_value_arb(),
+    Timestamp => from_test_rng(Timestamp::random),
+    TransactionEntryPoint => from_test_rng(TransactionEntryPoint::random),
+    TransactionInvocationTarget => from_test_rng(TransactionInvocationTarget::random),
+    TransactionTarget => from_test_rng(TransactionTarget::random),
+    TransactionV2 => from_test_rng(TransactionV2::random),
+    TransactionV2Hash => from_distribution(),
+    Transfer => gens::transfer_arb(),
+    TransferAddr => from_distribution(),
+    TransferV2 => gens::transfer_v2_arb(),
+    Transform => from_distribution(),
+    U128 => gens::u128_arb(),
+    U256 => gens::u256_arb(),
+    U512 => gens::u512_arb(),
+    UnbondingPurse => gens::unbonding_arb(),
+    URef => gens::uref_arb(),
+    Weight => gens::weight_arb(),
+    WithdrawPurse => gens::withdraw_arb(),
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    macro_rules! json_roundtrip_tests {
+        ($($name:ident: $ty:ty),* $(,)?) => {
+            proptest! {
+                $(
+                    #[test]
+                    fn $name(value in any::<$ty>()) {
+                        let json = serde_json::to_string(&value).unwrap();
+                        let decoded: $ty = serde_json::from_str(&json).unwrap();
+                        prop_assert_eq!(value, decoded);
+                    }
+                )*
+            }
+        };
+    }
+
+    test_bytesrepr_roundtrip!(
+        AccessRights,
+        Account,
+        AccountHash,
+        ActionThresholds,
+        AssociatedKeys,
+        Bid,
+        BlockTime,
+        Bytes,
+        CLType,
+        CLValue,
+        Contract,
+        ContractPackage,
+        ContractVersionKey,
+        ContractWasm,
+        DeployHash,
+        DeployInfo,
+        EntryPoint,
+        EntryPointAccess,
+        EntryPointType,
+        EntryPoints,
+        EraId,
+        EraInfo,
+        ExecutionResult,
+        ExecutionResultV2,
+        Group,
+        Key,
+        MessageAddr,
+        MessageChecksum,
+        MessageTopicSummary,
+        NamedArg,
+        Parameter,
+        Phase,
+        PricingMode,
+        ProtocolVersion,
+        PublicKey,
+        SeigniorageAllocation,
+        SemVer,
+        StoredValue,
+        Timestamp,
+        TransactionEntryPoint,
+        TransactionInvocationTarget,
+        TransactionTarget,
+        TransactionV2,
+        TransactionV2Hash,
+        Transfer,
+        TransferAddr,
+        TransferV2,
+        Transform,
+        U128,
+        U256,
+        U512,
+        UnbondingPurse,
+        URef,
+        Weight,
+        WithdrawPurse,
+    );
+
+    json_roundtrip_tests! {
+        json_roundtrip_account_hash: AccountHash,
+        json_roundtrip_bid: Bid,
+        json_roundtrip_deploy_hash: DeployHash,
+        json_roundtrip_deploy_info: DeployInfo,
+        json_roundtrip_era_id: EraId,
+        json_roundtrip_era_info: EraInfo,
+        json_roundtrip_execution_result: ExecutionResult,
+        json_roundtrip_execution_result_v2: ExecutionResultV2,
+        json_roundtrip_key: Key,
+        json_roundtrip_protocol_version: ProtocolVersion,
+        json_roundtrip_public_key: PublicKey,
+        json_roundtrip_timestamp: Timestamp,
+        json_roundtrip_transaction_v2: TransactionV2,
+        json_roundtrip_transfer: Transfer,
+        json_roundtrip_transfer_addr: TransferAddr,
+        json_roundtrip_transfer_v2: TransferV2,
+        json_roundtrip_uref: URef,
+        json_roundtrip_u512: U512,
+    }
+}
@@ -0,0 +1,263 @@
+//! Human-readable encoding of account hashes and public keys following the [bech32m][1] scheme.
+//!
+//! Unlike the hex encodings, a bech32m string carries a checksum which detects any typo of up to
+//! four characters, and a human-readable prefix which identifies the kind of value encoded.
+//!
+//! Both encodings are always accepted when parsing account hashes and public keys.  Whether they
+//! are emitted in bech32m form when serialized to a human-readable format such as JSON is
+//! controlled process-wide via [`set_json_output`].
+//!
+//! [1]: https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::{self, Display, Formatter},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The human-readable part of a bech32m-encoded [`AccountHash`](crate::account::AccountHash).
+pub const ACCOUNT_HASH_HRP: &str = "casper";
+/// The human-readable part of a bech32m-encoded [`PublicKey`](crate::PublicKey).
+pub const PUBLIC_KEY_HRP: &str = "casperpk";
+
+const SEPARATOR: char = '1';
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const CHECKSUM_LENGTH: usize = 6;
+const BECH32M_CONSTANT: u32 = 0x2bc8_30a3;
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether account hashes and public keys are serialized in bech32m form rather than hex
+/// when using a human-readable serializer.
+pub fn set_json_output(enabled: bool) {
+    JSON_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether account hashes and public keys are serialized in bech32m form when using a
+/// human-readable serializer.
+///
+/// This is always `false` during [canonical JSON](crate::canonical_json) serialization.
+pub fn json_output() -> bool {
+    #[cfg(any(feature = "std", test))]
+    if crate::canonical_json::in_progress() {
+        return false;
+    }
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Error returned when decoding a bech32m string.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The string contains both upper and lower case characters.
+    MixedCase,
+    /// The string has no separator between the human-readable part and the data.
+    MissingSeparator,
+    /// The human-readable part is not the one expected for the decoded type.
+    UnexpectedHrp {
+        /// The expected human-readable part.
+        expected: &'static str,
+        /// The human-readable part of the string.
+        got: String,
+    },
+    /// The string contains a character outside the bech32 character set.
+    InvalidChar(char),
+    /// The data part is too short to contain a checksum.
+    TooShort,
+    /// The checksum doesn't match the data.
+    InvalidChecksum,
+    /// The data doesn't decode to a whole number of bytes.
+    InvalidPadding,
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::MixedCase => write!(formatter, "bech32 string has mixed case"),
+            Error::MissingSeparator => write!(formatter, "bech32 string has no separator"),
+            Error::UnexpectedHrp { expected, got } => write!(
+                formatter,
+                "bech32 string has prefix '{}' rather than '{}'",
+                got, expected
+            ),
+            Error::InvalidChar(char) => {
+                write!(formatter, "bech32 string has invalid character '{}'", char)
+            }
+            Error::TooShort => write!(formatter, "bech32 string is too short"),
+            Error::InvalidChecksum => write!(formatter, "bech32 string has invalid checksum"),
+            Error::InvalidPadding => write!(formatter, "bech32 string has invalid padding"),
+        }
+    }
+}
+
+/// Encodes `data` as a bech32m string with the given human-readable part.
+///
+/// `hrp` must consist of lowercase ASCII characters.
+pub fn encode(hrp: &str, data: &[u8]) -> String {
+    let values = convert_bits(data, 8, 5, true).unwrap_or_default();
+    let checksum = polymod(
+        hrp_expand(hrp)
+            .chain(values.iter().copied())
+            .chain([0; CHECKSUM_LENGTH]),
+    ) ^ BECH32M_CONSTANT;
+
+    let mut encoded = String::with_capacity(hrp.len() + 1 + values.len() + CHECKSUM_LENGTH);
+    encoded.push_str(hrp);
+    encoded.push(SEPARATOR);
+    encoded.extend(values.iter().map(|&value| CHARSET[value as usize] as char));
+    encoded.extend(
+        (0..CHECKSUM_LENGTH)
+            .map(|index| CHARSET[((checksum >> (5 * (5 - index))) & 0x1f) as usize] as char),
+    );
+    encoded
+}
+
+/// Decodes a bech32m string, verifying that its human-readable part is `expected_hrp`.
+pub fn decode(input: &str, expected_hrp: &'static str) -> Result<Vec<u8>, Error> {
+    let has_lower = input.bytes().any(|byte| byte.is_ascii_lowercase());
+    let has_upper = input.bytes().any(|byte| byte.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(Error::MixedCase);
+    }
+    let input = input.to_ascii_lowercase();
+
+    let (hrp, data) = input
+        .rsplit_once(SEPARATOR)
+        .ok_or(Error::MissingSeparator)?;
+    if hrp != expected_hrp {
+        return Err(Error::UnexpectedHrp {
+            expected: expected_hrp,
+            got: String::from(hrp),
+        });
+    }
+
+    let values = data
+        .chars()
+        .map(|char| {
+            CHARSET
+                .iter()
+                .position(|&charset_char| charset_char as char == char)
+                .map(|position| position as u8)
+                .ok_or(Error::InvalidChar(char))
+        })
+        .collect::<Result<Vec<u8>, Error>>()?;
+    if values.len() < CHECKSUM_LENGTH {
+        return Err(Error::TooShort);
+    }
+    if polymod(hrp_expand(hrp).chain(values.iter().copied())) != BECH32M_CONSTANT {
+        return Err(Error::InvalidChecksum);
+    }
+
+    convert_bits(&values[..values.len() - CHECKSUM_LENGTH], 5, 8, false)
+        .ok_or(Error::InvalidPadding)
+}
+
+fn hrp_expand(hrp: &str) -> impl Iterator<Item = u8> + '_ {
+    hrp.bytes()
+        .map(|byte| byte >> 5)
+        .chain([0])
+        .chain(hrp.bytes().map(|byte| byte & 0x1f))
+}
+
+fn polymod<I: IntoIterator<Item = u8>>(values: I) -> u32 {
+    values.into_iter().fold(1, |checksum, value| {
+        let top = checksum >> 25;
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| (top >> index) & 1 == 1)
+            .fold(
+                ((checksum & 0x01ff_ffff) << 5) ^ u32::from(value),
+                |checksum, (_, generator)| checksum ^ generator,
+            )
+    })
+}
+
+/// Regroups `data` from `from`-bit values into `to`-bit values.
+///
+/// Returns `None` if a value is out of range or, without `pad`, if non-zero bits are left over.
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let max_value = (1u32 << to) - 1;
+    let mut accumulator = 0u32;
+    let mut bits = 0u32;
+    let mut converted = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for &value in data {
+        if u32::from(value) >> from != 0 {
+            return None;
+        }
+        accumulator = (accumulator << from) | u32::from(value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            converted.push(((accumulator >> bits) & max_value) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            converted.push(((accumulator << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((accumulator << (to - bits)) & max_value) != 0 {
+        return None;
+    }
+    Some(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prop_assert_eq;
+    use proptest_attr_macro::proptest;
+
+    use super::*;
+
+    #[test]
+    fn should_decode_bip_350_test_vector() {
+        // Valid bech32m string from BIP-350 with an empty data part.
+        assert_eq!(decode("a1lqfn3a", "a"), Ok(Vec::new()));
+        assert_eq!(decode("A1LQFN3A", "a"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn should_reject_bech32_checksum() {
+        // Valid under the original bech32 scheme, but not bech32m.
+        assert_eq!(decode("a12uel5l", "a"), Err(Error::InvalidChecksum));
+    }
+
+    #[test]
+    fn should_detect_typo() {
+        let encoded = encode(ACCOUNT_HASH_HRP, &[7; 32]);
+        let typo_index = encoded.len() - 10;
+        let mut typo = encoded.clone().into_bytes();
+        typo[typo_index] = if typo[typo_index] == b'q' { b'p' } else { b'q' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert_eq!(decode(&typo, ACCOUNT_HASH_HRP), Err(Error::InvalidChecksum));
+    }
+
+    #[test]
+    fn should_reject_unexpected_hrp() {
+        let encoded = encode(PUBLIC_KEY_HRP, &[1; 33]);
+        assert!(matches!(
+            decode(&encoded, ACCOUNT_HASH_HRP),
+            Err(Error::UnexpectedHrp { .. })
+        ));
+    }
+
+    #[test]
+    fn should_reject_mixed_case() {
+        let mut encoded = encode(ACCOUNT_HASH_HRP, &[1; 32]);
+        encoded.replace_range(0..1, "C");
+        assert_eq!(decode(&encoded, ACCOUNT_HASH_HRP), Err(Error::MixedCase));
+    }
+
+    #[proptest]
+    fn bech32_roundtrip(input: Vec<u8>) {
+        let encoded = encode(ACCOUNT_HASH_HRP, &input);
+        prop_assert_eq!(decode(&encoded, ACCOUNT_HASH_HRP), Ok(input));
+    }
+}
@@ -25,6 +25,7 @@ use num_rational::Ratio;
 use serde::{Deserialize, Serialize};
 
 pub use bytes::Bytes;
+pub use casper_types_derive::{FromBytes, ToBytes};
 
 /// The number of bytes in a serialized `()`.
 pub const UNIT_SERIALIZED_LENGTH: usize = 0;
@@ -90,6 +91,13 @@ pub trait FromBytes: Sized {
     }
 }
 
+// Items referred to by the code generated by the `bytesrepr` derive macros, which can't assume
+// that `alloc` is available to the deriving crate.
+#[doc(hidden)]
+pub mod __derive {
+    pub use alloc::vec::Vec;
+}
+
 /// Returns a `Vec<u8>` initialized with sufficient capacity to hold `to_be_serialized` after
 /// serialization.
 pub fn unchecked_allocate_buffer<T: ToBytes>(to_be_serialized: &T) -> Vec<u8> {
@@ -0,0 +1,262 @@
+//! Canonical JSON serialization, for JSON which is hashed or signed by external systems.
+//!
+//! The canonical form of a value is the JSON produced by its `Serialize` implementation, written
+//! such that every value has exactly one encoding:
+//!
+//! * there is no insignificant whitespace
+//! * object members are sorted by the UTF-8 bytes of their names
+//! * numbers must be integers, and are written in decimal without a fraction, exponent, leading
+//!   zeros or a plus sign
+//! * strings escape only `"`, `\` and control characters, the latter as `\b`, `\f`, `\n`, `\r` and
+//!   `\t` where possible and otherwise as `\u00xx` with lowercase hex digits
+//! * account hashes and public keys are written in their lowercase hex form, regardless of
+//!   [`bech32::set_json_output`](crate::bech32::set_json_output)
+//!
+//! Values which round-trip through their `Serialize` and `Deserialize` implementations also
+//! round-trip through [`to_string`] and [`from_str`], and [`from_str`] rejects any input which is
+//! not in canonical form.
+
+use std::{cell::Cell, fmt::Write};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Number, Value};
+use thiserror::Error;
+
+std::thread_local! {
+    static IN_PROGRESS: Cell<bool> = Cell::new(false);
+}
+
+/// Error while converting to or from canonical JSON.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The value could not be converted to or from JSON.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// The value contains a number which is not an integer.
+    #[error("canonical JSON doesn't support non-integer number {0}")]
+    NonIntegerNumber(Number),
+    /// The input is valid JSON, but not in canonical form.
+    #[error("JSON is not in canonical form")]
+    NotCanonical,
+}
+
+/// Serializes `value` as canonical JSON.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let json_value = {
+        let _guard = InProgressGuard::new();
+        serde_json::to_value(value)?
+    };
+    let mut output = String::new();
+    write_value(&json_value, &mut output)?;
+    Ok(output)
+}
+
+/// Serializes `value` as canonical JSON, returning the UTF-8 bytes.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    to_string(value).map(String::into_bytes)
+}
+
+/// Deserializes a value from canonical JSON, failing if `json` is not in canonical form.
+pub fn from_str<T: DeserializeOwned>(json: &str) -> Result<T, Error> {
+    let json_value: Value = serde_json::from_str(json)?;
+    let mut canonical = String::with_capacity(json.len());
+    write_value(&json_value, &mut canonical)?;
+    if canonical != json {
+        return Err(Error::NotCanonical);
+    }
+    Ok(serde_json::from_value(json_value)?)
+}
+
+/// Returns whether canonical serialization is in progress on the current thread.
+pub(crate) fn in_progress() -> bool {
+    IN_PROGRESS.with(Cell::get)
+}
+
+/// Marks canonical serialization as in progress on the current thread until dropped.
+struct InProgressGuard {
+    was_in_progress: bool,
+}
+
+impl InProgressGuard {
+    fn new() -> Self {
+        InProgressGuard {
+            was_in_progress: IN_PROGRESS.with(|in_progress| in_progress.replace(true)),
+        }
+    }
+}
+
+impl Drop for InProgressGuard {
+    fn drop(&mut self) {
+        IN_PROGRESS.with(|in_progress| in_progress.set(self.was_in_progress));
+    }
+}
+
+fn write_value(value: &Value, output: &mut String) -> Result<(), Error> {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Bool(true) => output.push_str("true"),
+        Value::Bool(false) => output.push_str("false"),
+        Value::Number(number) => write_number(number, output)?,
+        Value::String(string) => write_string(string, output),
+        Value::Array(values) => {
+            output.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_value(value, output)?;
+            }
+            output.push(']');
+        }
+        Value::Object(map) => write_object(map, output)?,
+    }
+    Ok(())
+}
+
+fn write_number(number: &Number, output: &mut String) -> Result<(), Error> {
+    if let Some(unsigned) = number.as_u64() {
+        let _ = write!(output, "{}", unsigned);
+    } else if let Some(signed) = number.as_i64() {
+        let _ = write!(output, "{}", signed);
+    } else {
+        return Err(Error::NonIntegerNumber(number.clone()));
+    }
+    Ok(())
+}
+
+fn write_string(string: &str, output: &mut String) {
+    output.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            control if control < ' ' => {
+                let _ = write!(output, "\\u{:04x}", control as u32);
+            }
+            other => output.push(other),
+        }
+    }
+    output.push('"');
+}
+
+fn write_object(map: &Map<String, Value>, output: &mut String) -> Result<(), Error> {
+    // Sorted explicitly, as `Map` preserves insertion order if serde_json's "preserve_order"
+    // feature is enabled anywhere in the dependency graph.
+    let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+    entries.sort_unstable_by(|(name1, _), (name2, _)| name1.as_bytes().cmp(name2.as_bytes()));
+
+    output.push('{');
+    for (index, (name, value)) in entries.into_iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        write_string(name, output);
+        output.push(':');
+        write_value(value, output)?;
+    }
+    output.push('}');
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{account::AccountHash, AccessRights, TimeDiff, Timestamp, URef, U512};
+
+    #[derive(PartialEq, Debug, Serialize, Deserialize)]
+    struct Transfer {
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        target: AccountHash,
+        source: URef,
+        amount: U512,
+        id: Option<u64>,
+        memo: String,
+        counts: BTreeMap<String, i64>,
+    }
+
+    fn transfer() -> Transfer {
+        let mut counts = BTreeMap::new();
+        counts.insert(String::from("b"), 2);
+        counts.insert(String::from("a"), -1);
+        Transfer {
+            timestamp: Timestamp::from(1_609_459_200_000),
+            ttl: TimeDiff::from_seconds(30 * 60),
+            target: AccountHash::new([0xab; 32]),
+            source: URef::new([0x0f; 32], AccessRights::READ_ADD_WRITE),
+            amount: U512::from(10).pow(U512::from(21)),
+            id: None,
+            memo: String::from("tab\tquote\"bell\u{7}é"),
+            counts,
+        }
+    }
+
+    #[test]
+    fn should_match_test_vectors() {
+        let expected = format!(
+            concat!(
+                r#"{{"amount":"1000000000000000000000","counts":{{"a":-1,"b":2}},"id":null,"#,
+                r#""memo":"tab\tquote\"bell\u0007é","source":"uref-{}-007","#,
+                r#""target":"account-hash-{}","timestamp":"2021-01-01T00:00:00.000Z","#,
+                r#""ttl":"30m"}}"#
+            ),
+            "0f".repeat(32),
+            "ab".repeat(32)
+        );
+        assert_eq!(to_string(&transfer()).unwrap(), expected);
+
+        let value = json!({"z": [3, {"y": true, "x": null}], "": "", "A": -7});
+        assert_eq!(
+            to_string(&value).unwrap(),
+            r#"{"":"","A":-7,"z":[3,{"x":null,"y":true}]}"#
+        );
+    }
+
+    #[test]
+    fn should_roundtrip() {
+        let transfer = transfer();
+        let json = to_string(&transfer).unwrap();
+        assert_eq!(from_str::<Transfer>(&json).unwrap(), transfer);
+        assert_eq!(to_vec(&transfer).unwrap(), json.into_bytes());
+    }
+
+    #[test]
+    fn should_reject_non_canonical_input() {
+        for json in [
+            r#"{"b":1,"a":2}"#,
+            r#"{"a": 2}"#,
+            r#"{"a":2,"a":2}"#,
+            r#"{"a":"\u0041"}"#,
+            r#"{"a":1E2}"#,
+        ] {
+            assert!(
+                from_str::<Value>(json).is_err(),
+                "{} should be rejected",
+                json
+            );
+        }
+        assert_eq!(
+            from_str::<Value>(r#"{"a":2,"b":[1]}"#).unwrap(),
+            json!({"a": 2, "b": [1]})
+        );
+    }
+
+    #[test]
+    fn should_reject_non_integer_numbers() {
+        assert!(matches!(
+            to_string(&1.5_f64),
+            Err(Error::NonIntegerNumber(_))
+        ));
+    }
+}
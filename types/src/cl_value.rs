@@ -17,6 +17,9 @@ use crate::{
 };
 
 mod jsonrepr;
+mod view;
+
+pub use view::CLValueView;
 
 /// Error while converting a [`CLValue`] into a given type.
 #[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
@@ -132,6 +135,12 @@ impl CLValue {
         self.bytes.inner_bytes()
     }
 
+    /// Returns a borrowed view of `self`, allowing individual components to be extracted without
+    /// deserializing the whole value.
+    pub fn view(&self) -> CLValueView<'_> {
+        CLValueView::from(self)
+    }
+
     /// Returns the length of the `Vec<u8>` yielded after calling `self.to_bytes()`.
     ///
     /// Note, this method doesn't actually serialize `self`, and hence is relatively cheap.
@@ -1,4 +1,9 @@
-use alloc::{string::String, vec, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Display;
 
 use serde::Serialize;
 use serde_json::{json, Value};
@@ -6,7 +11,7 @@ use serde_json::{json, Value};
 use crate::{
     bytesrepr::{self, FromBytes, OPTION_NONE_TAG, OPTION_SOME_TAG, RESULT_ERR_TAG, RESULT_OK_TAG},
     cl_type::CL_TYPE_RECURSION_DEPTH,
-    CLType, CLValue, Key, PublicKey, URef, U128, U256, U512,
+    json_decimal, CLType, CLValue, Key, PublicKey, URef, U128, U256, U512,
 };
 
 /// Returns a best-effort attempt to convert the `CLValue` into a meaningful JSON value.
@@ -35,10 +40,10 @@ fn depth_limited_to_json<'a>(
     match cl_type {
         CLType::Bool => simple_type_to_json::<bool>(bytes),
         CLType::I32 => simple_type_to_json::<i32>(bytes),
-        CLType::I64 => simple_type_to_json::<i64>(bytes),
+        CLType::I64 => large_integer_to_json::<i64>(bytes),
         CLType::U8 => simple_type_to_json::<u8>(bytes),
         CLType::U32 => simple_type_to_json::<u32>(bytes),
-        CLType::U64 => simple_type_to_json::<u64>(bytes),
+        CLType::U64 => large_integer_to_json::<u64>(bytes),
         CLType::U128 => simple_type_to_json::<U128>(bytes),
         CLType::U256 => simple_type_to_json::<U256>(bytes),
         CLType::U512 => simple_type_to_json::<U512>(bytes),
@@ -119,6 +124,19 @@ fn simple_type_to_json<T: FromBytes + Serialize>(bytes: &[u8]) -> Option<(Value,
     Some((json!(value), remainder))
 }
 
+/// Converts an integer which may exceed the range exactly representable by a JavaScript number,
+/// emitting it as a decimal string if [`json_decimal::json_output`] is enabled.
+fn large_integer_to_json<T: FromBytes + Serialize + Display>(
+    bytes: &[u8],
+) -> Option<(Value, &[u8])> {
+    let (value, remainder) = T::from_bytes(bytes).ok()?;
+    if json_decimal::json_output() {
+        Some((json!(value.to_string()), remainder))
+    } else {
+        Some((json!(value), remainder))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
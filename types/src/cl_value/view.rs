@@ -0,0 +1,402 @@
+use alloc::{borrow::Cow, boxed::Box};
+
+use crate::{
+    bytesrepr::{
+        self, FromBytes, ToBytes, OPTION_NONE_TAG, OPTION_SOME_TAG, RESULT_ERR_TAG, RESULT_OK_TAG,
+    },
+    cl_type::CL_TYPE_RECURSION_DEPTH,
+    CLType, CLTypeMismatch, CLTyped, CLValue, CLValueError, Key, PublicKey, URef, U128, U256, U512,
+};
+
+/// A borrowed view of a [`CLValue`] which allows inspecting its type and extracting individual
+/// components without deserializing or copying the whole value.
+///
+/// A view can be taken of an existing `CLValue` via [`CLValue::view`], or directly of the
+/// serialized form of a `CLValue` via [`CLValueView::from_serialized`], in which case the
+/// underlying bytes are never copied.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CLValueView<'a> {
+    cl_type: Cow<'a, CLType>,
+    bytes: &'a [u8],
+}
+
+impl<'a> CLValueView<'a> {
+    /// Parses the header of a serialized `CLValue`, returning a view of it and the remainder of
+    /// `serialized`.
+    ///
+    /// Only the length prefix and the `CLType` are decoded; the value bytes are borrowed.
+    pub fn from_serialized(serialized: &'a [u8]) -> Result<(Self, &'a [u8]), bytesrepr::Error> {
+        let (length, remainder) = u32::from_bytes(serialized)?;
+        let (bytes, remainder) = bytesrepr::safe_split_at(remainder, length as usize)?;
+        let (cl_type, remainder) = CLType::from_bytes(remainder)?;
+        let view = CLValueView {
+            cl_type: Cow::Owned(cl_type),
+            bytes,
+        };
+        Ok((view, remainder))
+    }
+
+    /// The [`CLType`] of the underlying data.
+    pub fn cl_type(&self) -> &CLType {
+        &self.cl_type
+    }
+
+    /// Returns the serialized form of the underlying value.
+    pub fn inner_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the length in bytes of the serialized form of the underlying value.
+    pub fn value_length(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Deserializes the viewed value into its underlying type.
+    pub fn to_t<T: CLTyped + FromBytes>(&self) -> Result<T, CLValueError> {
+        let expected = T::cl_type();
+        if *self.cl_type != expected {
+            return Err(self.mismatch(expected));
+        }
+        Ok(bytesrepr::deserialize_from_slice(self.bytes)?)
+    }
+
+    /// Copies the viewed value into an owned `CLValue`.
+    pub fn to_cl_value(&self) -> CLValue {
+        CLValue::from_components(self.cl_type().clone(), self.bytes.to_vec())
+    }
+
+    /// Returns the number of elements in the viewed list or entries in the viewed map.
+    pub fn element_count(&self) -> Result<u32, CLValueError> {
+        match &*self.cl_type {
+            CLType::List(_) | CLType::Map { .. } => {
+                let (count, _) = u32::from_bytes(self.bytes)?;
+                Ok(count)
+            }
+            _ => Err(self.mismatch(CLType::List(Box::new(CLType::Any)))),
+        }
+    }
+
+    /// Returns a view of the element at `index` of the viewed list, or `None` if `index` is out of
+    /// bounds.
+    ///
+    /// Only the elements preceding `index` are traversed, and none are deserialized unless their
+    /// encoding is variable-length.
+    pub fn element(&self, index: u32) -> Result<Option<CLValueView<'_>>, CLValueError> {
+        let element_type = match &*self.cl_type {
+            CLType::List(element_type) => element_type,
+            _ => return Err(self.mismatch(CLType::List(Box::new(CLType::Any)))),
+        };
+        let (count, mut stream) = u32::from_bytes(self.bytes)?;
+        if index >= count {
+            return Ok(None);
+        }
+        for _ in 0..index {
+            stream = skip_value(0, element_type, stream)?;
+        }
+        Ok(Some(take_value(element_type, stream)?.0))
+    }
+
+    /// Returns a view of the field at `index` of the viewed tuple, or `None` if `index` is out of
+    /// bounds.
+    pub fn field(&self, index: usize) -> Result<Option<CLValueView<'_>>, CLValueError> {
+        let field_types: &[Box<CLType>] = match &*self.cl_type {
+            CLType::Tuple1(field_types) => field_types,
+            CLType::Tuple2(field_types) => field_types,
+            CLType::Tuple3(field_types) => field_types,
+            _ => return Err(self.mismatch(CLType::Tuple1([Box::new(CLType::Any)]))),
+        };
+        if index >= field_types.len() {
+            return Ok(None);
+        }
+        let mut stream = self.bytes;
+        for field_type in &field_types[..index] {
+            stream = skip_value(0, field_type, stream)?;
+        }
+        Ok(Some(take_value(&field_types[index], stream)?.0))
+    }
+
+    /// Returns a view of the value stored under `key` in the viewed map, or `None` if there is no
+    /// such entry.
+    ///
+    /// Keys are compared in their serialized form, so none are deserialized.
+    pub fn get<K: CLTyped + ToBytes>(
+        &self,
+        key: &K,
+    ) -> Result<Option<CLValueView<'_>>, CLValueError> {
+        let (key_type, value_type) = match &*self.cl_type {
+            CLType::Map { key, value } => (key, value),
+            _ => {
+                return Err(self.mismatch(CLType::Map {
+                    key: Box::new(K::cl_type()),
+                    value: Box::new(CLType::Any),
+                }))
+            }
+        };
+        if **key_type != K::cl_type() {
+            return Err(self.mismatch(CLType::Map {
+                key: Box::new(K::cl_type()),
+                value: value_type.clone(),
+            }));
+        }
+        let serialized_key = key.to_bytes()?;
+        let (count, mut stream) = u32::from_bytes(self.bytes)?;
+        for _ in 0..count {
+            let (entry_key, remainder) = take_value(key_type, stream)?;
+            let (entry_value, remainder) = take_value(value_type, remainder)?;
+            if entry_key.bytes == serialized_key.as_slice() {
+                return Ok(Some(entry_value));
+            }
+            stream = remainder;
+        }
+        Ok(None)
+    }
+
+    /// Returns a view of the value held by the viewed `Option`, or `None` if it is `None`.
+    pub fn option(&self) -> Result<Option<CLValueView<'_>>, CLValueError> {
+        let inner_type = match &*self.cl_type {
+            CLType::Option(inner_type) => inner_type,
+            _ => return Err(self.mismatch(CLType::Option(Box::new(CLType::Any)))),
+        };
+        let (tag, remainder) = u8::from_bytes(self.bytes)?;
+        match tag {
+            OPTION_NONE_TAG => Ok(None),
+            OPTION_SOME_TAG => Ok(Some(take_value(inner_type, remainder)?.0)),
+            _ => Err(bytesrepr::Error::Formatting.into()),
+        }
+    }
+
+    fn mismatch(&self, expected: CLType) -> CLValueError {
+        CLValueError::Type(CLTypeMismatch {
+            expected,
+            found: self.cl_type().clone(),
+        })
+    }
+}
+
+impl<'a> From<&'a CLValue> for CLValueView<'a> {
+    fn from(cl_value: &'a CLValue) -> Self {
+        CLValueView {
+            cl_type: Cow::Borrowed(cl_value.cl_type()),
+            bytes: cl_value.inner_bytes(),
+        }
+    }
+}
+
+/// Splits a single value of type `cl_type` off the front of `bytes`, returning a view of it and
+/// the remainder.
+fn take_value<'a>(
+    cl_type: &'a CLType,
+    bytes: &'a [u8],
+) -> Result<(CLValueView<'a>, &'a [u8]), bytesrepr::Error> {
+    let remainder = skip_value(0, cl_type, bytes)?;
+    let (value_bytes, remainder) = bytes.split_at(bytes.len() - remainder.len());
+    let view = CLValueView {
+        cl_type: Cow::Borrowed(cl_type),
+        bytes: value_bytes,
+    };
+    Ok((view, remainder))
+}
+
+/// Returns `bytes` with a single serialized value of type `cl_type` removed from the front.
+fn skip_value<'a>(
+    depth: u8,
+    cl_type: &CLType,
+    bytes: &'a [u8],
+) -> Result<&'a [u8], bytesrepr::Error> {
+    if depth >= CL_TYPE_RECURSION_DEPTH {
+        return Err(bytesrepr::Error::ExceededRecursionDepth);
+    }
+    let depth = depth + 1;
+
+    match cl_type {
+        CLType::Bool => skip_simple_type::<bool>(bytes),
+        CLType::I32 => skip_simple_type::<i32>(bytes),
+        CLType::I64 => skip_simple_type::<i64>(bytes),
+        CLType::U8 => skip_simple_type::<u8>(bytes),
+        CLType::U32 => skip_simple_type::<u32>(bytes),
+        CLType::U64 => skip_simple_type::<u64>(bytes),
+        CLType::U128 => skip_simple_type::<U128>(bytes),
+        CLType::U256 => skip_simple_type::<U256>(bytes),
+        CLType::U512 => skip_simple_type::<U512>(bytes),
+        CLType::Unit => Ok(bytes),
+        CLType::String => {
+            let (length, remainder) = u32::from_bytes(bytes)?;
+            let (string_bytes, remainder) = bytesrepr::safe_split_at(remainder, length as usize)?;
+            if core::str::from_utf8(string_bytes).is_err() {
+                return Err(bytesrepr::Error::Formatting);
+            }
+            Ok(remainder)
+        }
+        CLType::Key => skip_simple_type::<Key>(bytes),
+        CLType::URef => skip_simple_type::<URef>(bytes),
+        CLType::PublicKey => skip_simple_type::<PublicKey>(bytes),
+        CLType::Option(inner_cl_type) => {
+            let (tag, remainder) = u8::from_bytes(bytes)?;
+            match tag {
+                OPTION_NONE_TAG => Ok(remainder),
+                OPTION_SOME_TAG => skip_value(depth, inner_cl_type, remainder),
+                _ => Err(bytesrepr::Error::Formatting),
+            }
+        }
+        CLType::List(inner_cl_type) => {
+            let (count, mut stream) = u32::from_bytes(bytes)?;
+            for _ in 0..count {
+                stream = skip_value(depth, inner_cl_type, stream)?;
+            }
+            Ok(stream)
+        }
+        CLType::ByteArray(length) => Ok(bytesrepr::safe_split_at(bytes, *length as usize)?.1),
+        CLType::Result { ok, err } => {
+            let (tag, remainder) = u8::from_bytes(bytes)?;
+            match tag {
+                RESULT_ERR_TAG => skip_value(depth, err, remainder),
+                RESULT_OK_TAG => skip_value(depth, ok, remainder),
+                _ => Err(bytesrepr::Error::Formatting),
+            }
+        }
+        CLType::Map { key, value } => {
+            let (count, mut stream) = u32::from_bytes(bytes)?;
+            for _ in 0..count {
+                stream = skip_value(depth, key, stream)?;
+                stream = skip_value(depth, value, stream)?;
+            }
+            Ok(stream)
+        }
+        CLType::Tuple1(types) => skip_values(depth, types, bytes),
+        CLType::Tuple2(types) => skip_values(depth, types, bytes),
+        CLType::Tuple3(types) => skip_values(depth, types, bytes),
+        CLType::Any => Err(bytesrepr::Error::Formatting),
+    }
+}
+
+fn skip_values<'a>(
+    depth: u8,
+    cl_types: &[Box<CLType>],
+    mut bytes: &'a [u8],
+) -> Result<&'a [u8], bytesrepr::Error> {
+    for cl_type in cl_types {
+        bytes = skip_value(depth, cl_type, bytes)?;
+    }
+    Ok(bytes)
+}
+
+fn skip_simple_type<T: FromBytes>(bytes: &[u8]) -> Result<&[u8], bytesrepr::Error> {
+    let (_, remainder) = T::from_bytes(bytes)?;
+    Ok(remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        collections::BTreeMap,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use super::*;
+
+    #[test]
+    fn should_view_serialized_cl_value() {
+        let value = vec![1_u64, 2, 3];
+        let cl_value = CLValue::from_t(value.clone()).unwrap();
+        let mut serialized = cl_value.to_bytes().unwrap();
+        serialized.push(0xff);
+
+        let (view, remainder) = CLValueView::from_serialized(&serialized).unwrap();
+        assert_eq!(remainder, &[0xff]);
+        assert_eq!(view.cl_type(), &CLType::List(Box::new(CLType::U64)));
+        assert_eq!(view.value_length(), cl_value.inner_bytes().len());
+        assert_eq!(view.to_t::<Vec<u64>>().unwrap(), value);
+        assert_eq!(view.to_cl_value(), cl_value);
+    }
+
+    #[test]
+    fn should_extract_list_element() {
+        let value = vec!["a".to_string(), "bc".to_string(), "def".to_string()];
+        let cl_value = CLValue::from_t(value.clone()).unwrap();
+        let view = cl_value.view();
+
+        assert_eq!(view.element_count().unwrap(), 3);
+        for (index, expected) in value.iter().enumerate() {
+            let element = view.element(index as u32).unwrap().unwrap();
+            assert_eq!(&element.to_t::<String>().unwrap(), expected);
+        }
+        assert!(view.element(3).unwrap().is_none());
+    }
+
+    #[test]
+    fn should_extract_tuple_field() {
+        let value = (7_u8, vec![Some(1_i32), None], "tail".to_string());
+        let cl_value = CLValue::from_t(value.clone()).unwrap();
+        let view = cl_value.view();
+
+        assert_eq!(view.field(0).unwrap().unwrap().to_t::<u8>().unwrap(), 7);
+        let list = view.field(1).unwrap().unwrap();
+        assert_eq!(
+            list.element(0)
+                .unwrap()
+                .unwrap()
+                .option()
+                .unwrap()
+                .unwrap()
+                .to_t::<i32>()
+                .unwrap(),
+            1
+        );
+        assert!(list
+            .element(1)
+            .unwrap()
+            .unwrap()
+            .option()
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            view.field(2).unwrap().unwrap().to_t::<String>().unwrap(),
+            value.2
+        );
+        assert!(view.field(3).unwrap().is_none());
+    }
+
+    #[test]
+    fn should_look_up_map_entry() {
+        let mut value = BTreeMap::new();
+        value.insert("one".to_string(), 1_u64);
+        value.insert("two".to_string(), 2);
+        let cl_value = CLValue::from_t(value).unwrap();
+        let view = cl_value.view();
+
+        assert_eq!(view.element_count().unwrap(), 2);
+        let entry = view.get(&"two".to_string()).unwrap().unwrap();
+        assert_eq!(entry.to_t::<u64>().unwrap(), 2);
+        assert!(view.get(&"three".to_string()).unwrap().is_none());
+        assert!(matches!(view.get(&1_u64), Err(CLValueError::Type(_))));
+    }
+
+    #[test]
+    fn should_fail_on_type_mismatch() {
+        let cl_value = CLValue::from_t(1_u32).unwrap();
+        let view = cl_value.view();
+        assert!(matches!(view.element(0), Err(CLValueError::Type(_))));
+        assert!(matches!(view.field(0), Err(CLValueError::Type(_))));
+        assert!(matches!(view.option(), Err(CLValueError::Type(_))));
+        assert!(matches!(view.to_t::<u64>(), Err(CLValueError::Type(_))));
+    }
+
+    #[test]
+    fn should_fail_on_truncated_bytes() {
+        let cl_value = CLValue::from_t(vec![1_u64, 2, 3]).unwrap();
+        let bytes = cl_value.inner_bytes();
+        let truncated = CLValue::from_components(
+            cl_value.cl_type().clone(),
+            bytes[..bytes.len() - 1].to_vec(),
+        );
+        assert!(truncated.view().element(1).unwrap().is_some());
+        assert!(matches!(
+            truncated.view().element(2),
+            Err(CLValueError::Serialization(
+                bytesrepr::Error::EarlyEndOfStream
+            ))
+        ));
+    }
+}
@@ -0,0 +1,501 @@
+//! Types for the storage of messages emitted by contracts.
+//!
+//! Messages are grouped into topics, each registered by a contract under a name.  Every topic has
+//! a [`MessageTopicSummary`] stored in global state under a [`Key::Message`](crate::Key::Message)
+//! whose [`MessageAddr`] has no message index, and each message emitted on the topic has a
+//! [`MessageChecksum`] stored under the [`MessageAddr`] with the message's index within the topic.
+//! The payload of each message is not kept in global state; it is recorded as a [`Message`] in the
+//! execution effects of the deploy which emitted it.
+//!
+//! This keeps messages out of the contract's named keys and dictionaries, and lets clients query a
+//! topic and any of its messages directly.
+//!
+//! Contracts emit messages via the `casper_emit_message` host function; topics are registered
+//! implicitly by the first message emitted on them.
+
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use alloc::{string::String, vec, vec::Vec};
+use core::{
+    convert::TryFrom,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
+#[cfg(feature = "datasize")]
+use datasize::DataSize;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+#[cfg(feature = "json-schema")]
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    account::TryFromIntError,
+    bytesrepr::{self, Bytes, FromBytes, ToBytes},
+    checksummed_hex, BlockTime, HashAddr, BLAKE2B_DIGEST_LENGTH, KEY_HASH_LENGTH,
+};
+
+/// The number of bytes in a [`TopicNameHash`].
+pub const TOPIC_NAME_HASH_LENGTH: usize = 32;
+/// The number of bytes in a [`MessageChecksum`].
+pub const MESSAGE_CHECKSUM_LENGTH: usize = 32;
+/// The maximum number of bytes in the name of a message topic.
+pub const MAX_TOPIC_NAME_SIZE: usize = 256;
+/// The maximum number of bytes in the payload of a single message.
+pub const MAX_MESSAGE_PAYLOAD_SIZE: usize = 1024;
+
+/// The hash of the name of a message topic.
+pub type TopicNameHash = [u8; TOPIC_NAME_HASH_LENGTH];
+
+/// Returns the hash of the given topic name.
+pub fn topic_name_hash(topic_name: &str) -> TopicNameHash {
+    hash(topic_name.as_bytes())
+}
+
+fn hash(data: &[u8]) -> [u8; BLAKE2B_DIGEST_LENGTH] {
+    // NOTE: Expect below is safe because the length passed is supported.
+    let mut hasher = VarBlake2b::new(BLAKE2B_DIGEST_LENGTH).expect("should create hasher");
+    hasher.update(data);
+    let mut result = [0; BLAKE2B_DIGEST_LENGTH];
+    hasher.finalize_variable(|hash| result.clone_from_slice(hash));
+    result
+}
+
+/// The address of a message topic, or of a single message emitted on that topic.
+#[derive(PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct MessageAddr {
+    /// The address of the contract which registered the topic.
+    entity_addr: HashAddr,
+    /// The hash of the name of the topic.
+    topic_name_hash: TopicNameHash,
+    /// The index of the message within the topic, or `None` if this addresses the topic itself.
+    message_index: Option<u32>,
+}
+
+impl MessageAddr {
+    /// Returns the address of the summary of a message topic.
+    pub const fn new_topic_addr(entity_addr: HashAddr, topic_name_hash: TopicNameHash) -> Self {
+        MessageAddr {
+            entity_addr,
+            topic_name_hash,
+            message_index: None,
+        }
+    }
+
+    /// Returns the address of the message at `message_index` within a message topic.
+    pub const fn new_message_addr(
+        entity_addr: HashAddr,
+        topic_name_hash: TopicNameHash,
+        message_index: u32,
+    ) -> Self {
+        MessageAddr {
+            entity_addr,
+            topic_name_hash,
+            message_index: Some(message_index),
+        }
+    }
+
+    /// Returns the address of the contract which registered the topic.
+    pub fn entity_addr(&self) -> HashAddr {
+        self.entity_addr
+    }
+
+    /// Returns the hash of the name of the topic.
+    pub fn topic_name_hash(&self) -> TopicNameHash {
+        self.topic_name_hash
+    }
+
+    /// Returns the index of the message within the topic, or `None` if this addresses the topic
+    /// itself.
+    pub fn message_index(&self) -> Option<u32> {
+        self.message_index
+    }
+
+    /// Returns the address of the topic to which this address belongs.
+    #[must_use]
+    pub fn topic_addr(&self) -> Self {
+        MessageAddr::new_topic_addr(self.entity_addr, self.topic_name_hash)
+    }
+}
+
+impl Display for MessageAddr {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            base16::encode_lower(&self.entity_addr),
+            base16::encode_lower(&self.topic_name_hash)
+        )?;
+        if let Some(message_index) = self.message_index {
+            write!(f, "-{}", message_index)?;
+        }
+        Ok(())
+    }
+}
+
+impl ToBytes for MessageAddr {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        KEY_HASH_LENGTH + TOPIC_NAME_HASH_LENGTH + self.message_index.serialized_length()
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        self.entity_addr.write_bytes(writer)?;
+        self.topic_name_hash.write_bytes(writer)?;
+        self.message_index.write_bytes(writer)
+    }
+}
+
+impl FromBytes for MessageAddr {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (entity_addr, remainder) = HashAddr::from_bytes(bytes)?;
+        let (topic_name_hash, remainder) = TopicNameHash::from_bytes(remainder)?;
+        let (message_index, remainder) = Option::<u32>::from_bytes(remainder)?;
+        let message_addr = MessageAddr {
+            entity_addr,
+            topic_name_hash,
+            message_index,
+        };
+        Ok((message_addr, remainder))
+    }
+}
+
+impl Distribution<MessageAddr> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> MessageAddr {
+        MessageAddr {
+            entity_addr: rng.gen(),
+            topic_name_hash: rng.gen(),
+            message_index: rng.gen(),
+        }
+    }
+}
+
+/// The summary of a message topic, stored under the topic's [`MessageAddr`].
+#[derive(
+    Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize, FromBytes, ToBytes,
+)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct MessageTopicSummary {
+    /// The number of messages emitted on the topic, which is also the index of the next message.
+    message_count: u32,
+    /// The block time of the last message emitted on the topic.
+    blocktime: BlockTime,
+}
+
+impl MessageTopicSummary {
+    /// Returns a new `MessageTopicSummary`.
+    pub fn new(message_count: u32, blocktime: BlockTime) -> Self {
+        MessageTopicSummary {
+            message_count,
+            blocktime,
+        }
+    }
+
+    /// Returns the number of messages emitted on the topic.
+    pub fn message_count(&self) -> u32 {
+        self.message_count
+    }
+
+    /// Returns the block time of the last message emitted on the topic.
+    pub fn blocktime(&self) -> BlockTime {
+        self.blocktime
+    }
+}
+
+impl Distribution<MessageTopicSummary> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> MessageTopicSummary {
+        MessageTopicSummary::new(rng.gen(), BlockTime::new(rng.gen()))
+    }
+}
+
+/// The checksum of a message's payload, stored under the message's [`MessageAddr`].
+///
+/// Only the checksum is kept in global state; the payload itself is recorded as a [`Message`] in
+/// the execution effects of the deploy which emitted it.
+#[derive(Default, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+pub struct MessageChecksum([u8; MESSAGE_CHECKSUM_LENGTH]);
+
+impl MessageChecksum {
+    /// Constructs a new `MessageChecksum` from its raw bytes.
+    pub const fn new(value: [u8; MESSAGE_CHECKSUM_LENGTH]) -> Self {
+        MessageChecksum(value)
+    }
+
+    /// Returns the checksum of the given message payload.
+    pub fn from_payload(payload: &[u8]) -> Self {
+        MessageChecksum(hash(payload))
+    }
+
+    /// Returns the raw bytes of the checksum as an array.
+    pub fn value(&self) -> [u8; MESSAGE_CHECKSUM_LENGTH] {
+        self.0
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl JsonSchema for MessageChecksum {
+    fn schema_name() -> String {
+        String::from("MessageChecksum")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let schema = gen.subschema_for::<String>();
+        let mut schema_object = schema.into_object();
+        schema_object.metadata().description =
+            Some("Hex-encoded checksum of a message's payload.".to_string());
+        schema_object.into()
+    }
+}
+
+impl ToBytes for MessageChecksum {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        self.0.write_bytes(writer)
+    }
+}
+
+impl FromBytes for MessageChecksum {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        <[u8; MESSAGE_CHECKSUM_LENGTH]>::from_bytes(bytes)
+            .map(|(inner, remainder)| (MessageChecksum(inner), remainder))
+    }
+}
+
+impl Serialize for MessageChecksum {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            base16::encode_lower(&self.0).serialize(serializer)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageChecksum {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = if deserializer.is_human_readable() {
+            let hex_string = String::deserialize(deserializer)?;
+            let vec_bytes =
+                checksummed_hex::decode(hex_string.as_bytes()).map_err(SerdeError::custom)?;
+            <[u8; MESSAGE_CHECKSUM_LENGTH]>::try_from(vec_bytes.as_ref())
+                .map_err(SerdeError::custom)?
+        } else {
+            <[u8; MESSAGE_CHECKSUM_LENGTH]>::deserialize(deserializer)?
+        };
+        Ok(MessageChecksum(bytes))
+    }
+}
+
+impl Debug for MessageChecksum {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "MessageChecksum({})",
+            base16::encode_lower(&self.0)
+        )
+    }
+}
+
+impl Display for MessageChecksum {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}", base16::encode_lower(&self.0))
+    }
+}
+
+impl Distribution<MessageChecksum> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> MessageChecksum {
+        MessageChecksum::new(rng.gen())
+    }
+}
+
+/// A message emitted by a contract, as recorded in the execution effects of the deploy which
+/// emitted it.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize, FromBytes, ToBytes)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Message {
+    /// The payload of the message.
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "String", description = "Hex-encoded message payload.")
+    )]
+    payload: Bytes,
+    /// The checksum of the payload, as stored in global state.
+    checksum: MessageChecksum,
+}
+
+impl Message {
+    /// Returns a new `Message` with the given payload.
+    pub fn new(payload: Bytes) -> Self {
+        let checksum = MessageChecksum::from_payload(payload.as_slice());
+        Message { payload, checksum }
+    }
+
+    /// Returns the payload of the message.
+    pub fn payload(&self) -> &Bytes {
+        &self.payload
+    }
+
+    /// Returns the checksum of the payload, as stored in global state.
+    pub fn checksum(&self) -> MessageChecksum {
+        self.checksum
+    }
+}
+
+impl Distribution<Message> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Message {
+        let mut payload = vec![0; rng.gen_range(0..64)];
+        rng.fill(payload.as_mut_slice());
+        Message::new(Bytes::from(payload))
+    }
+}
+
+/// Errors that can occur while emitting a message.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum MessageEmissionFailure {
+    /// Messages can only be emitted by a stored contract.
+    InvalidContext = 1,
+    /// The topic name is empty or longer than [`MAX_TOPIC_NAME_SIZE`] bytes.
+    InvalidTopicName = 2,
+    /// The payload is longer than [`MAX_MESSAGE_PAYLOAD_SIZE`] bytes.
+    PayloadTooLarge = 3,
+    /// The maximum number of messages has already been emitted on the topic.
+    TopicFull = 4,
+}
+
+impl Display for MessageEmissionFailure {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            MessageEmissionFailure::InvalidContext => formatter
+                .write_str("Unable to emit message because the caller is not a stored contract"),
+            MessageEmissionFailure::InvalidTopicName => {
+                formatter.write_str("Unable to emit message because the topic name is invalid")
+            }
+            MessageEmissionFailure::PayloadTooLarge => {
+                formatter.write_str("Unable to emit message because the payload is too large")
+            }
+            MessageEmissionFailure::TopicFull => formatter.write_str(
+                "Unable to emit message because the maximum number of messages has been emitted \
+                on the topic",
+            ),
+        }
+    }
+}
+
+// This conversion is not intended to be used by third party crates.
+#[doc(hidden)]
+impl TryFrom<i32> for MessageEmissionFailure {
+    type Error = TryFromIntError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            d if d == MessageEmissionFailure::InvalidContext as i32 => {
+                Ok(MessageEmissionFailure::InvalidContext)
+            }
+            d if d == MessageEmissionFailure::InvalidTopicName as i32 => {
+                Ok(MessageEmissionFailure::InvalidTopicName)
+            }
+            d if d == MessageEmissionFailure::PayloadTooLarge as i32 => {
+                Ok(MessageEmissionFailure::PayloadTooLarge)
+            }
+            d if d == MessageEmissionFailure::TopicFull as i32 => {
+                Ok(MessageEmissionFailure::TopicFull)
+            }
+            _ => Err(TryFromIntError(())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        let mut rng = TestRng::new();
+        let topic_addr = MessageAddr::new_topic_addr(rng.gen(), topic_name_hash("events"));
+        let message_addr = MessageAddr::new_message_addr(rng.gen(), rng.gen(), u32::MAX);
+        bytesrepr::test_serialization_roundtrip(&topic_addr);
+        bytesrepr::test_serialization_roundtrip(&message_addr);
+        bytesrepr::test_serialization_roundtrip(&rng.gen::<MessageTopicSummary>());
+        bytesrepr::test_serialization_roundtrip(&MessageChecksum::from_payload(b"payload"));
+        bytesrepr::test_serialization_roundtrip(&rng.gen::<Message>());
+    }
+
+    #[test]
+    fn message_addr_should_address_its_topic() {
+        let topic_name_hash = topic_name_hash("events");
+        let message_addr = MessageAddr::new_message_addr([1; 32], topic_name_hash, 3);
+        assert_eq!(message_addr.message_index(), Some(3));
+        assert_eq!(
+            message_addr.topic_addr(),
+            MessageAddr::new_topic_addr([1; 32], topic_name_hash)
+        );
+        assert_eq!(message_addr.topic_addr().message_index(), None);
+    }
+
+    #[test]
+    fn checksum_serde_roundtrip() {
+        let checksum = MessageChecksum::from_payload(b"payload");
+        let json = serde_json::to_string(&checksum).unwrap();
+        assert_eq!(json, format!("\"{}\"", checksum));
+        assert_eq!(
+            serde_json::from_str::<MessageChecksum>(&json).unwrap(),
+            checksum
+        );
+    }
+
+    #[test]
+    fn message_should_carry_checksum_of_payload() {
+        let message = Message::new(Bytes::from(b"payload".as_ref()));
+        assert_eq!(message.payload().as_slice(), b"payload");
+        assert_eq!(
+            message.checksum(),
+            MessageChecksum::from_payload(b"payload")
+        );
+    }
+
+    #[test]
+    fn emission_failure_should_round_trip_through_i32() {
+        for failure in &[
+            MessageEmissionFailure::InvalidContext,
+            MessageEmissionFailure::InvalidTopicName,
+            MessageEmissionFailure::PayloadTooLarge,
+            MessageEmissionFailure::TopicFull,
+        ] {
+            assert_eq!(
+                MessageEmissionFailure::try_from(*failure as i32),
+                Ok(*failure)
+            );
+        }
+        assert!(MessageEmissionFailure::try_from(0).is_err());
+    }
+}
@@ -1170,7 +1170,7 @@ impl Contract {
 
     /// Determines if `Contract` is compatible with a given `ProtocolVersion`.
     pub fn is_compatible_protocol_version(&self, protocol_version: ProtocolVersion) -> bool {
-        self.protocol_version.value().major == protocol_version.value().major
+        self.protocol_version.is_compatible_with(&protocol_version)
     }
 
     /// Extracts the access rights from the named keys of the contract.
@@ -2,6 +2,7 @@
 
 mod asymmetric_key;
 mod error;
+mod signing_context;
 
 use blake2::{
     digest::{Update, VariableOutput},
@@ -14,12 +15,14 @@ pub use asymmetric_key::generate_ed25519_keypair;
 #[cfg(any(feature = "testing", test))]
 pub use asymmetric_key::gens;
 pub use asymmetric_key::{
-    sign, verify, AsymmetricType, PublicKey, SecretKey, Signature, ED25519_TAG, SECP256K1_TAG,
-    SYSTEM_ACCOUNT, SYSTEM_TAG,
+    sign, sign_with_context, sign_with_context_if_active, verify, verify_with_context,
+    verify_with_context_if_active, AsymmetricType, PublicKey, SecretKey, Signature, ED25519_TAG,
+    SECP256K1_TAG, SECP256R1_TAG, SYSTEM_ACCOUNT, SYSTEM_TAG,
 };
 pub use error::Error;
 #[cfg(any(feature = "std", test))]
 pub use error::ErrorExt;
+pub use signing_context::SigningContext;
 
 #[doc(hidden)]
 pub fn blake2b<T: AsRef<[u8]>>(data: T) -> [u8; BLAKE2B_DIGEST_LENGTH] {
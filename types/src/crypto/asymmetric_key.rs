@@ -33,13 +33,18 @@ use k256::ecdsa::{
 };
 #[cfg(any(feature = "std", test))]
 use once_cell::sync::Lazy;
+use p256::ecdsa::{
+    Signature as Secp256r1Signature, SigningKey as Secp256r1SecretKey,
+    VerifyingKey as Secp256r1PublicKey,
+};
 #[cfg(any(feature = "std", test))]
 use pem::Pem;
 #[cfg(any(all(feature = "std", feature = "testing"), test))]
 use rand::{Rng, RngCore};
 #[cfg(feature = "json-schema")]
 use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha512};
 #[cfg(any(feature = "std", test))]
 use untrusted::Input;
 
@@ -47,7 +52,7 @@ use untrusted::Input;
 use crate::testing::TestRng;
 use crate::{
     account::AccountHash,
-    bytesrepr,
+    bech32, bytesrepr,
     bytesrepr::{FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
     checksummed_hex,
     crypto::Error,
@@ -55,12 +60,16 @@ use crate::{
 };
 #[cfg(any(feature = "std", test))]
 use crate::{
-    crypto::ErrorExt,
+    crypto::{ErrorExt, SigningContext},
     file_utils::{read_file, write_file, write_private_file},
 };
 
+#[cfg(any(feature = "std", test))]
+mod encryption;
 #[cfg(any(feature = "testing", test))]
 pub mod gens;
+#[cfg(any(feature = "std", test))]
+mod mnemonic;
 #[cfg(test)]
 mod tests;
 
@@ -82,6 +91,14 @@ const SECP256K1_SECRET_KEY_LENGTH: usize = 32;
 const SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH: usize = 33;
 const SECP256K1_SIGNATURE_LENGTH: usize = 64;
 
+/// Tag for secp256r1 variant.
+pub const SECP256R1_TAG: u8 = 3;
+const SECP256R1: &str = "Secp256r1";
+
+const SECP256R1_SECRET_KEY_LENGTH: usize = 32;
+const SECP256R1_COMPRESSED_PUBLIC_KEY_LENGTH: usize = 33;
+const SECP256R1_SIGNATURE_LENGTH: usize = 64;
+
 /// Public key for system account.
 pub const SYSTEM_ACCOUNT: PublicKey = PublicKey::System;
 
@@ -105,6 +122,14 @@ const SECP256K1_PEM_SECRET_KEY_TAG: &str = "EC PRIVATE KEY";
 #[cfg(any(feature = "std", test))]
 const SECP256K1_PEM_PUBLIC_KEY_TAG: &str = "PUBLIC KEY";
 
+// See https://tools.ietf.org/html/rfc5480#section-2.1.1.1
+#[cfg(any(feature = "std", test))]
+const SECP256R1_OBJECT_IDENTIFIER: [u8; 8] = [42, 134, 72, 206, 61, 3, 1, 7];
+#[cfg(any(feature = "std", test))]
+const SECP256R1_PEM_SECRET_KEY_TAG: &str = "EC PRIVATE KEY";
+#[cfg(any(feature = "std", test))]
+const SECP256R1_PEM_PUBLIC_KEY_TAG: &str = "PUBLIC KEY";
+
 #[cfg(any(feature = "std", test))]
 static ED25519_SECRET_KEY: Lazy<SecretKey> = Lazy::new(|| {
     let bytes = [15u8; SecretKey::ED25519_LENGTH];
@@ -158,9 +183,10 @@ where
             }
             ED25519_TAG => Self::ed25519_from_bytes(&key_bytes),
             SECP256K1_TAG => Self::secp256k1_from_bytes(&key_bytes),
+            SECP256R1_TAG => Self::secp256r1_from_bytes(&key_bytes),
             _ => Err(Error::AsymmetricKey(format!(
-                "failed to decode from hex: invalid tag.  Expected {}, {} or {}, got {}",
-                SYSTEM_TAG, ED25519_TAG, SECP256K1_TAG, tag[0]
+                "failed to decode from hex: invalid tag.  Expected {}, {}, {} or {}, got {}",
+                SYSTEM_TAG, ED25519_TAG, SECP256K1_TAG, SECP256R1_TAG, tag[0]
             ))),
         }
     }
@@ -173,6 +199,9 @@ where
 
     /// Constructs a new secp256k1 variant from a byte slice.
     fn secp256k1_from_bytes<T: AsRef<[u8]>>(bytes: T) -> Result<Self, Error>;
+
+    /// Constructs a new secp256r1 variant from a byte slice.
+    fn secp256r1_from_bytes<T: AsRef<[u8]>>(bytes: T) -> Result<Self, Error>;
 }
 
 /// A secret or private asymmetric key.
@@ -188,6 +217,9 @@ pub enum SecretKey {
     /// secp256k1 secret key.
     #[cfg_attr(feature = "datasize", data_size(skip))]
     Secp256k1(Secp256k1SecretKey),
+    /// secp256r1 (NIST P-256) secret key.
+    #[cfg_attr(feature = "datasize", data_size(skip))]
+    Secp256r1(Secp256r1SecretKey),
 }
 
 impl SecretKey {
@@ -200,6 +232,9 @@ impl SecretKey {
     /// The length in bytes of a secp256k1 secret key.
     pub const SECP256K1_LENGTH: usize = SECP256K1_SECRET_KEY_LENGTH;
 
+    /// The length in bytes of a secp256r1 secret key.
+    pub const SECP256R1_LENGTH: usize = SECP256R1_SECRET_KEY_LENGTH;
+
     /// Constructs a new system variant.
     pub fn system() -> Self {
         SecretKey::System
@@ -219,11 +254,19 @@ impl SecretKey {
         )?))
     }
 
+    /// Constructs a new secp256r1 variant from a byte slice.
+    pub fn secp256r1_from_bytes<T: AsRef<[u8]>>(bytes: T) -> Result<Self, Error> {
+        Ok(SecretKey::Secp256r1(Secp256r1SecretKey::from_bytes(
+            bytes.as_ref(),
+        )?))
+    }
+
     fn variant_name(&self) -> &str {
         match self {
             SecretKey::System => SYSTEM,
             SecretKey::Ed25519(_) => ED25519,
             SecretKey::Secp256k1(_) => SECP256K1,
+            SecretKey::Secp256r1(_) => SECP256R1,
         }
     }
 }
@@ -244,6 +287,77 @@ impl SecretKey {
         SecretKey::secp256k1_from_bytes(bytes).map_err(Into::into)
     }
 
+    /// Generates a new secp256r1 variant using the system's secure random number generator.
+    pub fn generate_secp256r1() -> Result<Self, ErrorExt> {
+        let mut bytes = [0u8; Self::SECP256R1_LENGTH];
+        getrandom::getrandom(&mut bytes[..])?;
+        SecretKey::secp256r1_from_bytes(bytes).map_err(Into::into)
+    }
+
+    /// Generates a new ed25519 variant from a new BIP-39 mnemonic of `word_count` words and the
+    /// given passphrase, which may be empty.
+    ///
+    /// Returns the key along with the mnemonic, from which it can be restored using
+    /// [`SecretKey::ed25519_from_mnemonic`].
+    pub fn generate_ed25519_with_mnemonic(
+        word_count: usize,
+        passphrase: &str,
+    ) -> Result<(Self, String), ErrorExt> {
+        let mnemonic = mnemonic::generate(word_count)?;
+        let secret_key = Self::ed25519_from_mnemonic(&mnemonic, passphrase)?;
+        Ok((secret_key, mnemonic))
+    }
+
+    /// Generates a new secp256k1 variant from a new BIP-39 mnemonic of `word_count` words and the
+    /// given passphrase, which may be empty.
+    ///
+    /// Returns the key along with the mnemonic, from which it can be restored using
+    /// [`SecretKey::secp256k1_from_mnemonic`].
+    pub fn generate_secp256k1_with_mnemonic(
+        word_count: usize,
+        passphrase: &str,
+    ) -> Result<(Self, String), ErrorExt> {
+        let mnemonic = mnemonic::generate(word_count)?;
+        let secret_key = Self::secp256k1_from_mnemonic(&mnemonic, passphrase)?;
+        Ok((secret_key, mnemonic))
+    }
+
+    /// Generates a new secp256r1 variant from a new BIP-39 mnemonic of `word_count` words and the
+    /// given passphrase, which may be empty.
+    ///
+    /// Returns the key along with the mnemonic, from which it can be restored using
+    /// [`SecretKey::secp256r1_from_mnemonic`].
+    pub fn generate_secp256r1_with_mnemonic(
+        word_count: usize,
+        passphrase: &str,
+    ) -> Result<(Self, String), ErrorExt> {
+        let mnemonic = mnemonic::generate(word_count)?;
+        let secret_key = Self::secp256r1_from_mnemonic(&mnemonic, passphrase)?;
+        Ok((secret_key, mnemonic))
+    }
+
+    /// Restores an ed25519 variant from a BIP-39 mnemonic and the passphrase used with it.
+    pub fn ed25519_from_mnemonic(mnemonic: &str, passphrase: &str) -> Result<Self, ErrorExt> {
+        let key = mnemonic::master_key(mnemonic::ED25519_CURVE, mnemonic, passphrase, |_| true)?;
+        SecretKey::ed25519_from_bytes(key).map_err(Into::into)
+    }
+
+    /// Restores a secp256k1 variant from a BIP-39 mnemonic and the passphrase used with it.
+    pub fn secp256k1_from_mnemonic(mnemonic: &str, passphrase: &str) -> Result<Self, ErrorExt> {
+        let key = mnemonic::master_key(mnemonic::SECP256K1_CURVE, mnemonic, passphrase, |key| {
+            Secp256k1SecretKey::from_bytes(key).is_ok()
+        })?;
+        SecretKey::secp256k1_from_bytes(key).map_err(Into::into)
+    }
+
+    /// Restores a secp256r1 variant from a BIP-39 mnemonic and the passphrase used with it.
+    pub fn secp256r1_from_mnemonic(mnemonic: &str, passphrase: &str) -> Result<Self, ErrorExt> {
+        let key = mnemonic::master_key(mnemonic::SECP256R1_CURVE, mnemonic, passphrase, |key| {
+            Secp256r1SecretKey::from_bytes(key).is_ok()
+        })?;
+        SecretKey::secp256r1_from_bytes(key).map_err(Into::into)
+    }
+
     /// Attempts to write the key bytes to the configured file path.
     pub fn to_file<P: AsRef<Path>>(&self, file: P) -> Result<(), ErrorExt> {
         write_private_file(file, self.to_pem()?).map_err(ErrorExt::SecretKeySave)
@@ -255,6 +369,27 @@ impl SecretKey {
         Self::from_pem(data)
     }
 
+    /// Attempts to write the key bytes, encrypted under the given passphrase, to the configured
+    /// file path.
+    pub fn to_encrypted_file<P: AsRef<Path>>(
+        &self,
+        file: P,
+        passphrase: &str,
+    ) -> Result<(), ErrorExt> {
+        write_private_file(file, self.to_encrypted_pem(passphrase)?)
+            .map_err(ErrorExt::SecretKeySave)
+    }
+
+    /// Attempts to read the key bytes from configured file path, decrypting them with the given
+    /// passphrase.
+    pub fn from_encrypted_file<P: AsRef<Path>>(
+        file: P,
+        passphrase: &str,
+    ) -> Result<Self, ErrorExt> {
+        let data = read_file(file).map_err(ErrorExt::SecretKeyLoad)?;
+        Self::from_encrypted_pem(data, passphrase)
+    }
+
     /// DER encodes a key.
     pub fn to_der(&self) -> Result<Vec<u8>, ErrorExt> {
         match self {
@@ -280,6 +415,21 @@ impl SecretKey {
                 let mut der = Der::new(&mut oid_bytes);
                 der.oid(&SECP256K1_OBJECT_IDENTIFIER)?;
 
+                let mut encoded = vec![];
+                der = Der::new(&mut encoded);
+                der.sequence(|der| {
+                    der.integer(&[1])?;
+                    der.octet_string(secret_key.to_bytes().as_slice())?;
+                    der.element(Tag::ContextSpecificConstructed0, &oid_bytes)
+                })?;
+                Ok(encoded)
+            }
+            SecretKey::Secp256r1(secret_key) => {
+                // See https://www.secg.org/sec1-v2.pdf#subsection.C.4
+                let mut oid_bytes = vec![];
+                let mut der = Der::new(&mut oid_bytes);
+                der.oid(&SECP256R1_OBJECT_IDENTIFIER)?;
+
                 let mut encoded = vec![];
                 der = Der::new(&mut encoded);
                 der.sequence(|der| {
@@ -331,7 +481,7 @@ impl SecretKey {
 
                     return Ok((ED25519_TAG, raw_bytes));
                 } else if tag == Tag::OctetString as u8 {
-                    // Expecting a secp256k1 key.
+                    // Expecting a secp256k1 or secp256r1 key.
                     if version != 1 {
                         return Err(derp::Error::WrongValue);
                     }
@@ -345,16 +495,18 @@ impl SecretKey {
                     let object_identifier = parameter0.read_all(derp::Error::Read, |input| {
                         derp::expect_tag_and_get_value(input, Tag::Oid)
                     })?;
-                    if object_identifier.as_slice_less_safe() != SECP256K1_OBJECT_IDENTIFIER {
-                        return Err(derp::Error::WrongValue);
-                    }
+                    let key_type_tag = match object_identifier.as_slice_less_safe() {
+                        oid if oid == SECP256K1_OBJECT_IDENTIFIER => SECP256K1_TAG,
+                        oid if oid == SECP256R1_OBJECT_IDENTIFIER => SECP256R1_TAG,
+                        _ => return Err(derp::Error::WrongValue),
+                    };
 
                     // There might be an optional public key as the final value, but we're not
                     // interested in parsing that.  Read it to ensure `input.read_all` doesn't fail
                     // with unused bytes error.
                     let _ = derp::read_tag_and_get_value(input);
 
-                    return Ok((SECP256K1_TAG, raw_bytes));
+                    return Ok((key_type_tag, raw_bytes));
                 }
 
                 Err(derp::Error::WrongValue)
@@ -365,6 +517,7 @@ impl SecretKey {
             SYSTEM_TAG => Err(Error::AsymmetricKey("cannot construct variant".to_string()).into()),
             ED25519_TAG => SecretKey::ed25519_from_bytes(raw_bytes).map_err(Into::into),
             SECP256K1_TAG => SecretKey::secp256k1_from_bytes(raw_bytes).map_err(Into::into),
+            SECP256R1_TAG => SecretKey::secp256r1_from_bytes(raw_bytes).map_err(Into::into),
             _ => Err(Error::AsymmetricKey("unknown type tag".to_string()).into()),
         }
     }
@@ -375,6 +528,7 @@ impl SecretKey {
             SecretKey::System => return Err(Error::System(String::from("to_pem")).into()),
             SecretKey::Ed25519(_) => ED25519_PEM_SECRET_KEY_TAG.to_string(),
             SecretKey::Secp256k1(_) => SECP256K1_PEM_SECRET_KEY_TAG.to_string(),
+            SecretKey::Secp256r1(_) => SECP256R1_PEM_SECRET_KEY_TAG.to_string(),
         };
         let contents = self.to_der()?;
         let pem = Pem { tag, contents };
@@ -384,6 +538,11 @@ impl SecretKey {
     /// Decodes a key from a PEM-encoded slice.
     pub fn from_pem<T: AsRef<[u8]>>(input: T) -> Result<Self, ErrorExt> {
         let pem = pem::parse(input)?;
+        if pem.tag == encryption::PEM_TAG {
+            return Err(ErrorExt::FromPem(
+                "the key is encrypted and requires a passphrase".to_string(),
+            ));
+        }
 
         let secret_key = Self::from_der(&pem.contents)?;
 
@@ -406,18 +565,57 @@ impl SecretKey {
                     return Err(bad_tag(SECP256K1_PEM_SECRET_KEY_TAG));
                 }
             }
+            SecretKey::Secp256r1(_) => {
+                if pem.tag != SECP256R1_PEM_SECRET_KEY_TAG {
+                    return Err(bad_tag(SECP256R1_PEM_SECRET_KEY_TAG));
+                }
+            }
         }
 
         Ok(secret_key)
     }
 
+    /// PEM encodes a key as a PKCS#8 encrypted private key, encrypted under the given passphrase.
+    pub fn to_encrypted_pem(&self, passphrase: &str) -> Result<String, ErrorExt> {
+        let contents = encryption::encrypt(self, passphrase, encryption::PBKDF2_ITERATIONS)?;
+        let pem = Pem {
+            tag: encryption::PEM_TAG.to_string(),
+            contents,
+        };
+        Ok(pem::encode(&pem))
+    }
+
+    /// Decodes a key from a PEM-encoded PKCS#8 encrypted private key, decrypting it with the given
+    /// passphrase.
+    pub fn from_encrypted_pem<T: AsRef<[u8]>>(
+        input: T,
+        passphrase: &str,
+    ) -> Result<Self, ErrorExt> {
+        let pem = pem::parse(input)?;
+        if pem.tag != encryption::PEM_TAG {
+            return Err(ErrorExt::FromPem(format!(
+                "invalid tag: expected {}, got {}",
+                encryption::PEM_TAG,
+                pem.tag
+            )));
+        }
+        encryption::decrypt(&pem.contents, passphrase)
+    }
+
+    /// Returns `true` if the input is a PEM-encoded encrypted private key.
+    pub fn is_encrypted_pem<T: AsRef<[u8]>>(input: T) -> bool {
+        pem::parse(input)
+            .map(|pem| pem.tag == encryption::PEM_TAG)
+            .unwrap_or(false)
+    }
+
     /// Generates a random instance using a `TestRng`.
     #[cfg(any(feature = "testing", test))]
     pub fn random(rng: &mut TestRng) -> Self {
-        if rng.gen() {
-            Self::random_ed25519(rng)
-        } else {
-            Self::random_secp256k1(rng)
+        match rng.gen_range(0..3) {
+            0 => Self::random_ed25519(rng),
+            1 => Self::random_secp256k1(rng),
+            _ => Self::random_secp256r1(rng),
         }
     }
 
@@ -437,6 +635,14 @@ impl SecretKey {
         SecretKey::secp256k1_from_bytes(bytes).unwrap()
     }
 
+    /// Generates a random secp256r1 instance using a `TestRng`.
+    #[cfg(any(feature = "testing", test))]
+    pub fn random_secp256r1(rng: &mut TestRng) -> Self {
+        let mut bytes = [0u8; Self::SECP256R1_LENGTH];
+        rng.fill_bytes(&mut bytes[..]);
+        SecretKey::secp256r1_from_bytes(bytes).unwrap()
+    }
+
     /// Returns an example value for documentation purposes.
     pub fn doc_example() -> &'static Self {
         &*ED25519_SECRET_KEY
@@ -461,6 +667,7 @@ impl Tagged<u8> for SecretKey {
             SecretKey::System => SYSTEM_TAG,
             SecretKey::Ed25519(_) => ED25519_TAG,
             SecretKey::Secp256k1(_) => SECP256K1_TAG,
+            SecretKey::Secp256r1(_) => SECP256R1_TAG,
         }
     }
 }
@@ -478,6 +685,9 @@ pub enum PublicKey {
     /// secp256k1 public key.
     #[cfg_attr(feature = "datasize", data_size(skip))]
     Secp256k1(Secp256k1PublicKey),
+    /// secp256r1 (NIST P-256) public key.
+    #[cfg_attr(feature = "datasize", data_size(skip))]
+    Secp256r1(Secp256r1PublicKey),
 }
 
 impl PublicKey {
@@ -490,16 +700,58 @@ impl PublicKey {
     /// The length in bytes of a secp256k1 public key.
     pub const SECP256K1_LENGTH: usize = SECP256K1_COMPRESSED_PUBLIC_KEY_LENGTH;
 
+    /// The length in bytes of a secp256r1 public key.
+    pub const SECP256R1_LENGTH: usize = SECP256R1_COMPRESSED_PUBLIC_KEY_LENGTH;
+
     /// Creates an `AccountHash` from a given `PublicKey` instance.
     pub fn to_account_hash(&self) -> AccountHash {
         AccountHash::from(self)
     }
 
+    /// Formats the `PublicKey` as a bech32m string, e.g. "casperpk1...", where the first encoded
+    /// byte represents the algorithm tag.
+    pub fn to_bech32_string(&self) -> String {
+        let bytes = iter::once(self.tag())
+            .chain(Vec::<u8>::from(self))
+            .collect::<Vec<u8>>();
+        bech32::encode(bech32::PUBLIC_KEY_HRP, &bytes)
+    }
+
+    /// Parses a string formatted as per `Self::to_bech32_string()` into a `PublicKey`.
+    pub fn from_bech32_str(input: &str) -> Result<Self, Error> {
+        let bytes = bech32::decode(input, bech32::PUBLIC_KEY_HRP).map_err(|error| {
+            Error::AsymmetricKey(format!("failed to decode from bech32: {}", error))
+        })?;
+        let (tag, key_bytes) = bytes.split_first().ok_or_else(|| {
+            Error::AsymmetricKey("failed to decode from bech32: too short".to_string())
+        })?;
+        match *tag {
+            SYSTEM_TAG if key_bytes.is_empty() => Ok(PublicKey::System),
+            ED25519_TAG => Self::ed25519_from_bytes(key_bytes),
+            SECP256K1_TAG => Self::secp256k1_from_bytes(key_bytes),
+            SECP256R1_TAG => Self::secp256r1_from_bytes(key_bytes),
+            _ => Err(Error::AsymmetricKey(format!(
+                "failed to decode from bech32: invalid tag {}",
+                tag
+            ))),
+        }
+    }
+
+    /// Returns whether `input` looks like a bech32m-encoded public key, ignoring case.
+    fn is_bech32(input: &str) -> bool {
+        let prefix_length = bech32::PUBLIC_KEY_HRP.len() + 1;
+        input.len() > prefix_length
+            && input.is_char_boundary(prefix_length)
+            && input[..prefix_length - 1].eq_ignore_ascii_case(bech32::PUBLIC_KEY_HRP)
+            && input.as_bytes()[prefix_length - 1] == b'1'
+    }
+
     fn variant_name(&self) -> &str {
         match self {
             PublicKey::System => SYSTEM,
             PublicKey::Ed25519(_) => ED25519,
             PublicKey::Secp256k1(_) => SECP256K1,
+            PublicKey::Secp256r1(_) => SECP256R1,
         }
     }
 }
@@ -520,6 +772,13 @@ impl PublicKey {
         PublicKey::secp256k1_from_bytes(bytes).map_err(Into::into)
     }
 
+    /// Generates a new secp256r1 variant using the system's secure random number generator.
+    pub fn generate_secp256r1() -> Result<Self, ErrorExt> {
+        let mut bytes = [0u8; Self::SECP256R1_LENGTH];
+        getrandom::getrandom(&mut bytes[..]).expect("RNG failure!");
+        PublicKey::secp256r1_from_bytes(bytes).map_err(Into::into)
+    }
+
     /// Attempts to write the key bytes to the configured file path.
     pub fn to_file<P: AsRef<Path>>(&self, file: P) -> Result<(), ErrorExt> {
         write_file(file, self.to_pem()?).map_err(ErrorExt::PublicKeySave)
@@ -558,6 +817,19 @@ impl PublicKey {
                 })?;
                 Ok(encoded)
             }
+            PublicKey::Secp256r1(public_key) => {
+                // See https://tools.ietf.org/html/rfc5480#section-2
+                let mut encoded = vec![];
+                let mut der = Der::new(&mut encoded);
+                der.sequence(|der| {
+                    der.sequence(|der| {
+                        der.oid(&EC_PUBLIC_KEY_OBJECT_IDENTIFIER)?;
+                        der.oid(&SECP256R1_OBJECT_IDENTIFIER)
+                    })?;
+                    der.bit_string(0, public_key.to_encoded_point(true).as_bytes())
+                })?;
+                Ok(encoded)
+            }
         }
     }
 
@@ -576,14 +848,16 @@ impl PublicKey {
                         key_type_tag = ED25519_TAG;
                         Ok(())
                     } else if object_identifier == EC_PUBLIC_KEY_OBJECT_IDENTIFIER {
-                        // Assert the next object identifier is the secp256k1 ID.
+                        // Assert the next object identifier is the secp256k1 or secp256r1 ID.
                         let next_object_identifier =
                             derp::expect_tag_and_get_value(input, Tag::Oid)?.as_slice_less_safe();
-                        if next_object_identifier != SECP256K1_OBJECT_IDENTIFIER {
+                        key_type_tag = if next_object_identifier == SECP256K1_OBJECT_IDENTIFIER {
+                            SECP256K1_TAG
+                        } else if next_object_identifier == SECP256R1_OBJECT_IDENTIFIER {
+                            SECP256R1_TAG
+                        } else {
                             return Err(derp::Error::WrongValue);
-                        }
-
-                        key_type_tag = SECP256K1_TAG;
+                        };
                         Ok(())
                     } else {
                         Err(derp::Error::WrongValue)
@@ -596,6 +870,7 @@ impl PublicKey {
         match key_type_tag {
             ED25519_TAG => PublicKey::ed25519_from_bytes(raw_bytes).map_err(Into::into),
             SECP256K1_TAG => PublicKey::secp256k1_from_bytes(raw_bytes).map_err(Into::into),
+            SECP256R1_TAG => PublicKey::secp256r1_from_bytes(raw_bytes).map_err(Into::into),
             _ => unreachable!(),
         }
     }
@@ -606,6 +881,7 @@ impl PublicKey {
             PublicKey::System => return Err(Error::System(String::from("to_pem")).into()),
             PublicKey::Ed25519(_) => ED25519_PEM_PUBLIC_KEY_TAG.to_string(),
             PublicKey::Secp256k1(_) => SECP256K1_PEM_PUBLIC_KEY_TAG.to_string(),
+            PublicKey::Secp256r1(_) => SECP256R1_PEM_PUBLIC_KEY_TAG.to_string(),
         };
         let contents = self.to_der()?;
         let pem = Pem { tag, contents };
@@ -634,6 +910,11 @@ impl PublicKey {
                     return Err(bad_tag(SECP256K1_PEM_PUBLIC_KEY_TAG));
                 }
             }
+            PublicKey::Secp256r1(_) => {
+                if pem.tag != SECP256R1_PEM_PUBLIC_KEY_TAG {
+                    return Err(bad_tag(SECP256R1_PEM_PUBLIC_KEY_TAG));
+                }
+            }
         }
         Ok(public_key)
     }
@@ -659,6 +940,13 @@ impl PublicKey {
         PublicKey::from(&secret_key)
     }
 
+    /// Generates a random secp256r1 instance using a `TestRng`.
+    #[cfg(any(feature = "testing", test))]
+    pub fn random_secp256r1(rng: &mut TestRng) -> Self {
+        let secret_key = SecretKey::random_secp256r1(rng);
+        PublicKey::from(&secret_key)
+    }
+
     /// Returns an example value for documentation purposes.
     pub fn doc_example() -> &'static Self {
         &*ED25519_PUBLIC_KEY
@@ -681,6 +969,12 @@ impl AsymmetricType<'_> for PublicKey {
             bytes.as_ref(),
         )?))
     }
+
+    fn secp256r1_from_bytes<T: AsRef<[u8]>>(bytes: T) -> Result<Self, Error> {
+        Ok(PublicKey::Secp256r1(Secp256r1PublicKey::from_sec1_bytes(
+            bytes.as_ref(),
+        )?))
+    }
 }
 
 impl From<&SecretKey> for PublicKey {
@@ -689,6 +983,7 @@ impl From<&SecretKey> for PublicKey {
             SecretKey::System => PublicKey::System,
             SecretKey::Ed25519(secret_key) => PublicKey::Ed25519(secret_key.into()),
             SecretKey::Secp256k1(secret_key) => PublicKey::Secp256k1(secret_key.into()),
+            SecretKey::Secp256r1(secret_key) => PublicKey::Secp256r1(secret_key.into()),
         }
     }
 }
@@ -699,6 +994,7 @@ impl From<&PublicKey> for Vec<u8> {
             PublicKey::System => Vec::new(),
             PublicKey::Ed25519(key) => key.to_bytes().into(),
             PublicKey::Secp256k1(key) => key.to_bytes().into(),
+            PublicKey::Secp256r1(key) => key.to_encoded_point(true).as_bytes().into(),
         }
     }
 }
@@ -765,6 +1061,7 @@ impl Tagged<u8> for PublicKey {
             PublicKey::System => SYSTEM_TAG,
             PublicKey::Ed25519(_) => ED25519_TAG,
             PublicKey::Secp256k1(_) => SECP256K1_TAG,
+            PublicKey::Secp256r1(_) => SECP256R1_TAG,
         }
     }
 }
@@ -786,6 +1083,11 @@ impl ToBytes for PublicKey {
                 let secp256k1_bytes = public_key.to_bytes();
                 buffer.extend_from_slice(&secp256k1_bytes);
             }
+            PublicKey::Secp256r1(public_key) => {
+                buffer.insert(0, SECP256R1_TAG);
+                let secp256r1_point = public_key.to_encoded_point(true);
+                buffer.extend_from_slice(secp256r1_point.as_bytes());
+            }
         }
         Ok(buffer)
     }
@@ -796,6 +1098,7 @@ impl ToBytes for PublicKey {
                 PublicKey::System => Self::SYSTEM_LENGTH,
                 PublicKey::Ed25519(_) => Self::ED25519_LENGTH,
                 PublicKey::Secp256k1(_) => Self::SECP256K1_LENGTH,
+                PublicKey::Secp256r1(_) => Self::SECP256R1_LENGTH,
             }
     }
 
@@ -810,6 +1113,10 @@ impl ToBytes for PublicKey {
                 writer.push(SECP256K1_TAG);
                 writer.extend_from_slice(&pk.to_bytes());
             }
+            PublicKey::Secp256r1(pk) => {
+                writer.push(SECP256R1_TAG);
+                writer.extend_from_slice(pk.to_encoded_point(true).as_bytes());
+            }
         }
         Ok(())
     }
@@ -834,6 +1141,13 @@ impl FromBytes for PublicKey {
                     .map_err(|_error| bytesrepr::Error::Formatting)?;
                 Ok((public_key, remainder))
             }
+            SECP256R1_TAG => {
+                let (raw_bytes, remainder): ([u8; Self::SECP256R1_LENGTH], _) =
+                    FromBytes::from_bytes(remainder)?;
+                let public_key = Self::secp256r1_from_bytes(raw_bytes)
+                    .map_err(|_error| bytesrepr::Error::Formatting)?;
+                Ok((public_key, remainder))
+            }
             _ => Err(bytesrepr::Error::Formatting),
         }
     }
@@ -841,12 +1155,24 @@ impl FromBytes for PublicKey {
 
 impl Serialize for PublicKey {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() && bech32::json_output() {
+            return self.to_bech32_string().serialize(serializer);
+        }
         detail::serialize(self, serializer)
     }
 }
 
 impl<'de> Deserialize<'de> for PublicKey {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let string = String::deserialize(deserializer)?;
+            let result = if PublicKey::is_bech32(&string) {
+                PublicKey::from_bech32_str(&string)
+            } else {
+                PublicKey::from_hex(string.as_bytes())
+            };
+            return result.map_err(SerdeError::custom);
+        }
         detail::deserialize(deserializer)
     }
 }
@@ -861,7 +1187,9 @@ impl JsonSchema for PublicKey {
         let schema = gen.subschema_for::<String>();
         let mut schema_object = schema.into_object();
         schema_object.metadata().description = Some(
-            "Hex-encoded cryptographic public key, including the algorithm tag prefix.".to_string(),
+            "Hex-encoded or bech32m-encoded cryptographic public key, including the algorithm tag \
+            prefix."
+                .to_string(),
         );
         schema_object.into()
     }
@@ -886,6 +1214,9 @@ pub enum Signature {
     /// Secp256k1 signature.
     #[cfg_attr(feature = "datasize", data_size(skip))]
     Secp256k1(Secp256k1Signature),
+    /// Secp256r1 (NIST P-256) signature.
+    #[cfg_attr(feature = "datasize", data_size(skip))]
+    Secp256r1(Secp256r1Signature),
 }
 
 impl Signature {
@@ -898,6 +1229,9 @@ impl Signature {
     /// The length in bytes of a secp256k1 signature
     pub const SECP256K1_LENGTH: usize = SECP256K1_SIGNATURE_LENGTH;
 
+    /// The length in bytes of a secp256r1 signature
+    pub const SECP256R1_LENGTH: usize = SECP256R1_SIGNATURE_LENGTH;
+
     /// Constructs a new Ed25519 variant from a byte array.
     pub fn ed25519(bytes: [u8; Self::ED25519_LENGTH]) -> Result<Self, Error> {
         let signature = ed25519_dalek::Signature::from_bytes(&bytes).map_err(|_| {
@@ -922,11 +1256,24 @@ impl Signature {
         Ok(Signature::Secp256k1(signature))
     }
 
+    /// Constructs a new secp256r1 variant from a byte array.
+    pub fn secp256r1(bytes: [u8; Self::SECP256R1_LENGTH]) -> Result<Self, Error> {
+        let signature = Secp256r1Signature::try_from(&bytes[..]).map_err(|_| {
+            Error::AsymmetricKey(format!(
+                "failed to construct secp256r1 signature from {:?}",
+                &bytes[..]
+            ))
+        })?;
+
+        Ok(Signature::Secp256r1(signature))
+    }
+
     fn variant_name(&self) -> &str {
         match self {
             Signature::System => SYSTEM,
             Signature::Ed25519(_) => ED25519,
             Signature::Secp256k1(_) => SECP256K1,
+            Signature::Secp256r1(_) => SECP256R1,
         }
     }
 }
@@ -955,6 +1302,16 @@ impl AsymmetricType<'_> for Signature {
         })?;
         Ok(Signature::Secp256k1(signature))
     }
+
+    fn secp256r1_from_bytes<T: AsRef<[u8]>>(bytes: T) -> Result<Self, Error> {
+        let signature = Secp256r1Signature::try_from(bytes.as_ref()).map_err(|_| {
+            Error::AsymmetricKey(format!(
+                "failed to construct secp256r1 signature from {:?}",
+                bytes.as_ref()
+            ))
+        })?;
+        Ok(Signature::Secp256r1(signature))
+    }
 }
 
 impl Debug for Signature {
@@ -1018,6 +1375,7 @@ impl Tagged<u8> for Signature {
             Signature::System => SYSTEM_TAG,
             Signature::Ed25519(_) => ED25519_TAG,
             Signature::Secp256k1(_) => SECP256K1_TAG,
+            Signature::Secp256r1(_) => SECP256R1_TAG,
         }
     }
 }
@@ -1039,6 +1397,11 @@ impl ToBytes for Signature {
                 let secp256k1_bytes = signature.as_ref();
                 buffer.extend_from_slice(secp256k1_bytes);
             }
+            Signature::Secp256r1(signature) => {
+                buffer.insert(0, SECP256R1_TAG);
+                let secp256r1_bytes = signature.as_ref();
+                buffer.extend_from_slice(secp256r1_bytes);
+            }
         }
         Ok(buffer)
     }
@@ -1049,6 +1412,7 @@ impl ToBytes for Signature {
                 Signature::System => Self::SYSTEM_LENGTH,
                 Signature::Ed25519(_) => Self::ED25519_LENGTH,
                 Signature::Secp256k1(_) => Self::SECP256K1_LENGTH,
+                Signature::Secp256r1(_) => Self::SECP256R1_LENGTH,
             }
     }
 
@@ -1065,6 +1429,10 @@ impl ToBytes for Signature {
                 writer.push(SECP256K1_TAG);
                 writer.extend_from_slice(signature.as_ref());
             }
+            Signature::Secp256r1(signature) => {
+                writer.push(SECP256R1_TAG);
+                writer.extend_from_slice(signature.as_ref());
+            }
         }
         Ok(())
     }
@@ -1089,6 +1457,13 @@ impl FromBytes for Signature {
                     Self::secp256k1(raw_bytes).map_err(|_error| bytesrepr::Error::Formatting)?;
                 Ok((public_key, remainder))
             }
+            SECP256R1_TAG => {
+                let (raw_bytes, remainder): ([u8; Self::SECP256R1_LENGTH], _) =
+                    FromBytes::from_bytes(remainder)?;
+                let public_key =
+                    Self::secp256r1(raw_bytes).map_err(|_error| bytesrepr::Error::Formatting)?;
+                Ok((public_key, remainder))
+            }
             _ => Err(bytesrepr::Error::Formatting),
         }
     }
@@ -1112,6 +1487,7 @@ impl From<&Signature> for Vec<u8> {
             Signature::System => Vec::new(),
             Signature::Ed25519(signature) => signature.to_bytes().into(),
             Signature::Secp256k1(signature) => signature.as_ref().into(),
+            Signature::Secp256r1(signature) => signature.as_ref().into(),
         }
     }
 }
@@ -1160,6 +1536,12 @@ pub fn sign<T: AsRef<[u8]>>(
                 .expect("should create signature");
             Signature::Secp256k1(signature)
         }
+        (SecretKey::Secp256r1(secret_key), PublicKey::Secp256r1(_public_key)) => {
+            let signature: Secp256r1Signature = secret_key
+                .try_sign(message.as_ref())
+                .expect("should create signature");
+            Signature::Secp256r1(signature)
+        }
         _ => panic!("secret and public key types must match"),
     }
 }
@@ -1185,6 +1567,11 @@ pub fn verify<T: AsRef<[u8]>>(
                     Error::AsymmetricKey(format!("failed to verify secp256k1 signature: {}", error))
                 })
         }
+        (Signature::Secp256r1(signature), PublicKey::Secp256r1(public_key)) => public_key
+            .verify(message.as_ref(), signature)
+            .map_err(|error| {
+                Error::AsymmetricKey(format!("failed to verify secp256r1 signature: {}", error))
+            }),
         _ => Err(Error::AsymmetricKey(format!(
             "type mismatch between {} and {}",
             signature, public_key
@@ -1192,6 +1579,98 @@ pub fn verify<T: AsRef<[u8]>>(
     }
 }
 
+/// Signs the given message using the given key pair, binding the signature to the given context.
+///
+/// Ed25519 keys create an Ed25519ph signature using the context's domain separator as the context
+/// string, while ECDSA keys sign the message prefixed with the domain separator.  Such signatures
+/// can only be verified via [`verify_with_context`] using the same context.
+pub fn sign_with_context<T: AsRef<[u8]>>(
+    message: T,
+    context: SigningContext,
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+) -> Signature {
+    match (secret_key, public_key) {
+        (SecretKey::Ed25519(secret_key), PublicKey::Ed25519(public_key)) => {
+            let expanded_secret_key = ExpandedSecretKey::from(secret_key);
+            let prehashed_message = Sha512::new().chain(message.as_ref());
+            let signature = expanded_secret_key
+                .sign_prehashed(prehashed_message, public_key, Some(context.as_bytes()))
+                .expect("context should be at most 255 bytes");
+            Signature::Ed25519(signature)
+        }
+        _ => sign(
+            context.domain_separated(message.as_ref()),
+            secret_key,
+            public_key,
+        ),
+    }
+}
+
+/// Verifies the signature of the given message in the given context against the given public key.
+pub fn verify_with_context<T: AsRef<[u8]>>(
+    message: T,
+    context: SigningContext,
+    signature: &Signature,
+    public_key: &PublicKey,
+) -> Result<(), Error> {
+    match (signature, public_key) {
+        (Signature::Ed25519(signature), PublicKey::Ed25519(public_key)) => {
+            let prehashed_message = Sha512::new().chain(message.as_ref());
+            public_key
+                .verify_prehashed(prehashed_message, Some(context.as_bytes()), signature)
+                .map_err(|_| {
+                    Error::AsymmetricKey(format!(
+                        "failed to verify Ed25519ph signature in {} context",
+                        context
+                    ))
+                })
+        }
+        _ => verify(
+            context.domain_separated(message.as_ref()),
+            signature,
+            public_key,
+        ),
+    }
+}
+
+/// Signs the given message in the given context if `context_active` is `true`, or as by [`sign`]
+/// otherwise.
+///
+/// Signing contexts are activated by a protocol upgrade: until then, signatures must remain
+/// verifiable by nodes which only support signatures without a context.
+pub fn sign_with_context_if_active<T: AsRef<[u8]>>(
+    message: T,
+    context: SigningContext,
+    context_active: bool,
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+) -> Signature {
+    if context_active {
+        sign_with_context(message, context, secret_key, public_key)
+    } else {
+        sign(message, secret_key, public_key)
+    }
+}
+
+/// Verifies the signature of the given message against the given public key.
+///
+/// If `context_active` is `true`, only signatures in the given context are accepted.  Otherwise,
+/// signatures both with and without the context are accepted.
+pub fn verify_with_context_if_active<T: AsRef<[u8]>>(
+    message: T,
+    context: SigningContext,
+    context_active: bool,
+    signature: &Signature,
+    public_key: &PublicKey,
+) -> Result<(), Error> {
+    let result = verify_with_context(message.as_ref(), context, signature, public_key);
+    if context_active || result.is_ok() {
+        return result;
+    }
+    verify(message, signature, public_key)
+}
+
 /// Generates an Ed25519 keypair using the operating system's cryptographically secure random number
 /// generator.
 #[cfg(any(feature = "std", test))]
@@ -1218,6 +1697,7 @@ mod detail {
         System,
         Ed25519(Vec<u8>),
         Secp256k1(Vec<u8>),
+        Secp256r1(Vec<u8>),
     }
 
     impl From<&PublicKey> for AsymmetricTypeAsBytes {
@@ -1226,6 +1706,7 @@ mod detail {
                 PublicKey::System => AsymmetricTypeAsBytes::System,
                 key @ PublicKey::Ed25519(_) => AsymmetricTypeAsBytes::Ed25519(key.into()),
                 key @ PublicKey::Secp256k1(_) => AsymmetricTypeAsBytes::Secp256k1(key.into()),
+                key @ PublicKey::Secp256r1(_) => AsymmetricTypeAsBytes::Secp256r1(key.into()),
             }
         }
     }
@@ -1236,6 +1717,7 @@ mod detail {
                 Signature::System => AsymmetricTypeAsBytes::System,
                 key @ Signature::Ed25519(_) => AsymmetricTypeAsBytes::Ed25519(key.into()),
                 key @ Signature::Secp256k1(_) => AsymmetricTypeAsBytes::Secp256k1(key.into()),
+                key @ Signature::Secp256r1(_) => AsymmetricTypeAsBytes::Secp256r1(key.into()),
             }
         }
     }
@@ -1275,6 +1757,9 @@ mod detail {
             AsymmetricTypeAsBytes::Secp256k1(raw_bytes) => {
                 T::secp256k1_from_bytes(raw_bytes).map_err(D::Error::custom)
             }
+            AsymmetricTypeAsBytes::Secp256r1(raw_bytes) => {
+                T::secp256r1_from_bytes(raw_bytes).map_err(D::Error::custom)
+            }
         }
     }
 }
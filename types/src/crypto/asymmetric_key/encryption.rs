@@ -0,0 +1,320 @@
+//! Passphrase-based encryption of secret keys.
+//!
+//! Encrypted keys are stored as a PKCS#8 `EncryptedPrivateKeyInfo` as specified in RFC 5958,
+//! encrypted via PBES2 as specified in RFC 8018 using PBKDF2 with HMAC-SHA256 and AES-256-CBC.
+//! The encrypted data is the key's PKCS#8 `PrivateKeyInfo`, so files written here can be read by
+//! OpenSSL, and keys encrypted by OpenSSL with these algorithms can be read here.
+
+use alloc::{string::ToString, vec, vec::Vec};
+
+use aes::Aes256;
+use block_modes::{block_padding::Pkcs7, BlockMode, Cbc};
+use derp::{Der, Tag};
+use hmac::Hmac;
+use sha2::Sha256;
+use untrusted::{Input, Reader};
+
+use super::{
+    SecretKey, EC_PUBLIC_KEY_OBJECT_IDENTIFIER, ED25519_OBJECT_IDENTIFIER,
+    SECP256K1_OBJECT_IDENTIFIER, SECP256R1_OBJECT_IDENTIFIER,
+};
+use crate::crypto::{Error, ErrorExt};
+
+/// The PEM tag of an encrypted secret key.
+pub(super) const PEM_TAG: &str = "ENCRYPTED PRIVATE KEY";
+
+/// The number of PBKDF2 iterations used when encrypting a key.
+pub(super) const PBKDF2_ITERATIONS: u32 = 100_000;
+/// The upper bound on the number of PBKDF2 iterations accepted when decrypting a key.
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+const SALT_LENGTH: usize = 16;
+const IV_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+
+/// id-PBES2, 1.2.840.113549.1.5.13.
+const PBES2_OBJECT_IDENTIFIER: [u8; 9] = [42, 134, 72, 134, 247, 13, 1, 5, 13];
+/// id-PBKDF2, 1.2.840.113549.1.5.12.
+const PBKDF2_OBJECT_IDENTIFIER: [u8; 9] = [42, 134, 72, 134, 247, 13, 1, 5, 12];
+/// id-hmacWithSHA256, 1.2.840.113549.2.9.
+const HMAC_SHA256_OBJECT_IDENTIFIER: [u8; 8] = [42, 134, 72, 134, 247, 13, 2, 9];
+/// aes256-CBC-PAD, 2.16.840.1.101.3.4.1.42.
+const AES_256_CBC_OBJECT_IDENTIFIER: [u8; 9] = [96, 134, 72, 1, 101, 3, 4, 1, 42];
+
+type Aes256Cbc = Cbc<Aes256, Pkcs7>;
+
+/// Encrypts the secret key under the given passphrase, returning the DER-encoded
+/// `EncryptedPrivateKeyInfo`.
+pub(super) fn encrypt(
+    secret_key: &SecretKey,
+    passphrase: &str,
+    iterations: u32,
+) -> Result<Vec<u8>, ErrorExt> {
+    let private_key_info = to_private_key_info(secret_key)?;
+
+    let mut salt = [0u8; SALT_LENGTH];
+    getrandom::getrandom(&mut salt)?;
+    let mut iv = [0u8; IV_LENGTH];
+    getrandom::getrandom(&mut iv)?;
+
+    let key = derive_key(passphrase, &salt, iterations);
+    let encrypted_data = cipher(&key, &iv)?.encrypt_vec(&private_key_info);
+
+    let mut encoded = vec![];
+    let mut der = Der::new(&mut encoded);
+    der.sequence(|der| {
+        der.sequence(|der| {
+            der.oid(&PBES2_OBJECT_IDENTIFIER)?;
+            der.sequence(|der| {
+                der.sequence(|der| {
+                    der.oid(&PBKDF2_OBJECT_IDENTIFIER)?;
+                    der.sequence(|der| {
+                        der.octet_string(&salt)?;
+                        der.integer(&encode_integer(iterations))?;
+                        der.sequence(|der| {
+                            der.oid(&HMAC_SHA256_OBJECT_IDENTIFIER)?;
+                            der.element(Tag::Null, &[])
+                        })
+                    })
+                })?;
+                der.sequence(|der| {
+                    der.oid(&AES_256_CBC_OBJECT_IDENTIFIER)?;
+                    der.octet_string(&iv)
+                })
+            })
+        })?;
+        der.octet_string(&encrypted_data)
+    })?;
+    Ok(encoded)
+}
+
+/// Decrypts the DER-encoded `EncryptedPrivateKeyInfo` with the given passphrase.
+pub(super) fn decrypt(
+    encrypted_private_key_info: &[u8],
+    passphrase: &str,
+) -> Result<SecretKey, ErrorExt> {
+    let input = Input::from(encrypted_private_key_info);
+    let (parameters, encrypted_data) = input.read_all(derp::Error::Read, |input| {
+        derp::nested(input, Tag::Sequence, |input| {
+            let parameters = derp::nested(input, Tag::Sequence, |input| {
+                expect_oid(input, &PBES2_OBJECT_IDENTIFIER)?;
+                derp::nested(input, Tag::Sequence, read_pbes2_parameters)
+            })?;
+            let encrypted_data = derp::expect_tag_and_get_value(input, Tag::OctetString)?;
+            Ok((parameters, encrypted_data.as_slice_less_safe()))
+        })
+    })?;
+
+    let key = derive_key(passphrase, parameters.salt, parameters.iterations);
+    let wrong_passphrase = || ErrorExt::Decryption("wrong passphrase or corrupt key".to_string());
+    let private_key_info = cipher(&key, parameters.iv)?
+        .decrypt_vec(encrypted_data)
+        .map_err(|_| wrong_passphrase())?;
+    // A wrong passphrase occasionally yields valid padding, leaving garbage to be parsed.
+    from_private_key_info(&private_key_info).map_err(|_| wrong_passphrase())
+}
+
+/// Returns the DER-encoded PKCS#8 `PrivateKeyInfo` of the secret key.
+fn to_private_key_info(secret_key: &SecretKey) -> Result<Vec<u8>, ErrorExt> {
+    let curve_object_identifier: &[u8] = match secret_key {
+        // The DER encoding of an Ed25519 key is already a `PrivateKeyInfo`.
+        SecretKey::System | SecretKey::Ed25519(_) => return secret_key.to_der(),
+        SecretKey::Secp256k1(_) => &SECP256K1_OBJECT_IDENTIFIER,
+        SecretKey::Secp256r1(_) => &SECP256R1_OBJECT_IDENTIFIER,
+    };
+    // See https://tools.ietf.org/html/rfc5915#section-3
+    let ec_private_key = secret_key.to_der()?;
+    let mut encoded = vec![];
+    let mut der = Der::new(&mut encoded);
+    der.sequence(|der| {
+        der.integer(&[0])?;
+        der.sequence(|der| {
+            der.oid(&EC_PUBLIC_KEY_OBJECT_IDENTIFIER)?;
+            der.oid(curve_object_identifier)
+        })?;
+        der.octet_string(&ec_private_key)
+    })?;
+    Ok(encoded)
+}
+
+/// Decodes a secret key from its DER-encoded PKCS#8 `PrivateKeyInfo`.
+fn from_private_key_info(private_key_info: &[u8]) -> Result<SecretKey, ErrorExt> {
+    let input = Input::from(private_key_info);
+    let (algorithm, curve, private_key) = input.read_all(derp::Error::Read, |input| {
+        derp::nested(input, Tag::Sequence, |input| {
+            // Version 0 or 1; the latter may have the public key appended, which we ignore.
+            let version = derp::expect_tag_and_get_value(input, Tag::Integer)?;
+            if decode_integer(version.as_slice_less_safe())? > 1 {
+                return Err(derp::Error::WrongValue);
+            }
+            let (algorithm, curve) = derp::nested(input, Tag::Sequence, |input| {
+                let algorithm = derp::expect_tag_and_get_value(input, Tag::Oid)?;
+                let curve = if input.peek(Tag::Oid as u8) {
+                    Some(derp::expect_tag_and_get_value(input, Tag::Oid)?)
+                } else {
+                    None
+                };
+                Ok((algorithm.as_slice_less_safe(), curve))
+            })?;
+            let private_key = derp::expect_tag_and_get_value(input, Tag::OctetString)?;
+            // Skip any optional attributes and public key.
+            while !input.at_end() {
+                let _ = derp::read_tag_and_get_value(input)?;
+            }
+            Ok((algorithm, curve, private_key))
+        })
+    })?;
+
+    if algorithm == ED25519_OBJECT_IDENTIFIER {
+        let raw_bytes = private_key.read_all(derp::Error::Read, |input| {
+            derp::expect_tag_and_get_value(input, Tag::OctetString)
+        })?;
+        return SecretKey::ed25519_from_bytes(raw_bytes.as_slice_less_safe()).map_err(Into::into);
+    }
+    if algorithm != EC_PUBLIC_KEY_OBJECT_IDENTIFIER {
+        return Err(Error::AsymmetricKey("unsupported key algorithm".to_string()).into());
+    }
+
+    // The curve parameters may be omitted from the inner `ECPrivateKey`, so are taken from the
+    // algorithm identifier instead.
+    let raw_bytes = private_key.read_all(derp::Error::Read, |input| {
+        derp::nested(input, Tag::Sequence, |input| {
+            let version = derp::expect_tag_and_get_value(input, Tag::Integer)?;
+            if version.as_slice_less_safe() != [1] {
+                return Err(derp::Error::WrongValue);
+            }
+            let raw_bytes = derp::expect_tag_and_get_value(input, Tag::OctetString)?;
+            while !input.at_end() {
+                let _ = derp::read_tag_and_get_value(input)?;
+            }
+            Ok(raw_bytes)
+        })
+    })?;
+    match curve.map(|curve| curve.as_slice_less_safe()) {
+        Some(curve) if curve == SECP256K1_OBJECT_IDENTIFIER => {
+            SecretKey::secp256k1_from_bytes(raw_bytes.as_slice_less_safe()).map_err(Into::into)
+        }
+        Some(curve) if curve == SECP256R1_OBJECT_IDENTIFIER => {
+            SecretKey::secp256r1_from_bytes(raw_bytes.as_slice_less_safe()).map_err(Into::into)
+        }
+        _ => Err(Error::AsymmetricKey("unsupported elliptic curve".to_string()).into()),
+    }
+}
+
+/// The parameters of PBES2 with PBKDF2 and AES-256-CBC.
+struct Pbes2Parameters<'a> {
+    salt: &'a [u8],
+    iterations: u32,
+    iv: &'a [u8],
+}
+
+fn read_pbes2_parameters<'a>(input: &mut Reader<'a>) -> Result<Pbes2Parameters<'a>, derp::Error> {
+    let (salt, iterations) = derp::nested(input, Tag::Sequence, |input| {
+        expect_oid(input, &PBKDF2_OBJECT_IDENTIFIER)?;
+        derp::nested(input, Tag::Sequence, |input| {
+            let salt = derp::expect_tag_and_get_value(input, Tag::OctetString)?;
+            let iterations = derp::expect_tag_and_get_value(input, Tag::Integer)?;
+            let iterations = decode_integer(iterations.as_slice_less_safe())?;
+            if iterations == 0 || iterations > MAX_PBKDF2_ITERATIONS {
+                return Err(derp::Error::WrongValue);
+            }
+            // The optional key length must match that of AES-256.
+            if input.peek(Tag::Integer as u8) {
+                let key_length = derp::expect_tag_and_get_value(input, Tag::Integer)?;
+                if decode_integer(key_length.as_slice_less_safe())? != KEY_LENGTH as u32 {
+                    return Err(derp::Error::WrongValue);
+                }
+            }
+            // The PRF defaults to HMAC-SHA1, which isn't supported.
+            derp::nested(input, Tag::Sequence, |input| {
+                expect_oid(input, &HMAC_SHA256_OBJECT_IDENTIFIER)?;
+                if !input.at_end() {
+                    derp::expect_tag_and_get_value(input, Tag::Null)?;
+                }
+                Ok(())
+            })?;
+            Ok((salt.as_slice_less_safe(), iterations))
+        })
+    })?;
+    let iv = derp::nested(input, Tag::Sequence, |input| {
+        expect_oid(input, &AES_256_CBC_OBJECT_IDENTIFIER)?;
+        derp::expect_tag_and_get_value(input, Tag::OctetString)
+    })?
+    .as_slice_less_safe();
+    if iv.len() != IV_LENGTH {
+        return Err(derp::Error::WrongValue);
+    }
+    Ok(Pbes2Parameters {
+        salt,
+        iterations,
+        iv,
+    })
+}
+
+fn expect_oid(input: &mut Reader, expected: &[u8]) -> Result<(), derp::Error> {
+    let object_identifier = derp::expect_tag_and_get_value(input, Tag::Oid)?;
+    if object_identifier.as_slice_less_safe() != expected {
+        return Err(derp::Error::WrongValue);
+    }
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> [u8; KEY_LENGTH] {
+    let mut key = [0u8; KEY_LENGTH];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+fn cipher(key: &[u8], iv: &[u8]) -> Result<Aes256Cbc, ErrorExt> {
+    Aes256Cbc::new_var(key, iv).map_err(|_| {
+        Error::AsymmetricKey("invalid key or IV length for AES-256-CBC".to_string()).into()
+    })
+}
+
+/// Returns the contents of the minimal DER encoding of a non-negative integer.
+fn encode_integer(value: u32) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let first_significant = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len() - 1);
+    let mut encoded = bytes[first_significant..].to_vec();
+    if encoded[0] & 0x80 != 0 {
+        encoded.insert(0, 0);
+    }
+    encoded
+}
+
+/// Decodes the contents of the DER encoding of a non-negative integer which fits in a `u32`.
+fn decode_integer(bytes: &[u8]) -> Result<u32, derp::Error> {
+    let significant_bytes = match bytes {
+        // Empty, negative or non-minimal encodings are invalid.
+        [] => return Err(derp::Error::WrongValue),
+        [first, ..] if first & 0x80 != 0 => return Err(derp::Error::WrongValue),
+        [0, second, ..] if second & 0x80 == 0 => return Err(derp::Error::WrongValue),
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        _ => bytes,
+    };
+    if significant_bytes.len() > 4 {
+        return Err(derp::Error::WrongValue);
+    }
+    Ok(significant_bytes
+        .iter()
+        .fold(0, |value, byte| (value << 8) | u32::from(*byte)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_integers() {
+        for value in [0, 1, 127, 128, 255, 256, PBKDF2_ITERATIONS, u32::MAX] {
+            let encoded = encode_integer(value);
+            assert_eq!(decode_integer(&encoded).unwrap(), value);
+        }
+        assert_eq!(encode_integer(128), vec![0, 128]);
+        assert!(decode_integer(&[0, 1]).is_err());
+        assert!(decode_integer(&[128]).is_err());
+        assert!(decode_integer(&[1, 0, 0, 0, 0]).is_err());
+    }
+}
@@ -23,6 +23,11 @@ pub fn public_key_arb() -> impl Strategy<Value = PublicKey> {
             let bytes_array: [u8; SecretKey::SECP256K1_LENGTH] = bytes.try_into().unwrap();
             let secret_key = SecretKey::secp256k1_from_bytes(bytes_array).unwrap();
             PublicKey::from(&secret_key)
+        }),
+        collection::vec(<u8>::arbitrary(), SecretKey::SECP256R1_LENGTH).prop_map(|bytes| {
+            let bytes_array: [u8; SecretKey::SECP256R1_LENGTH] = bytes.try_into().unwrap();
+            let secret_key = SecretKey::secp256r1_from_bytes(bytes_array).unwrap();
+            PublicKey::from(&secret_key)
         })
     ]
 }
@@ -39,6 +44,11 @@ pub fn public_key_arb_no_system() -> impl Strategy<Value = PublicKey> {
             let bytes_array: [u8; SecretKey::SECP256K1_LENGTH] = bytes.try_into().unwrap();
             let secret_key = SecretKey::secp256k1_from_bytes(bytes_array).unwrap();
             PublicKey::from(&secret_key)
+        }),
+        collection::vec(<u8>::arbitrary(), SecretKey::SECP256R1_LENGTH).prop_map(|bytes| {
+            let bytes_array: [u8; SecretKey::SECP256R1_LENGTH] = bytes.try_into().unwrap();
+            let secret_key = SecretKey::secp256r1_from_bytes(bytes_array).unwrap();
+            PublicKey::from(&secret_key)
         })
     ]
 }
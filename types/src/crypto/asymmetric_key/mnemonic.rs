@@ -0,0 +1,72 @@
+//! Derivation of secret keys from BIP-39 mnemonics.
+//!
+//! The mnemonic and optional passphrase are turned into a 64 byte seed as specified in BIP-39, and
+//! the secret key is the master key derived from that seed as specified in SLIP-0010, so keys
+//! restored from a mnemonic match those of other wallets following the same standards.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha512;
+
+use crate::crypto::ErrorExt;
+
+/// The HMAC key used to derive an Ed25519 master key from a seed.
+pub(super) const ED25519_CURVE: &[u8] = b"ed25519 seed";
+/// The HMAC key used to derive a secp256k1 master key from a seed.
+pub(super) const SECP256K1_CURVE: &[u8] = b"Bitcoin seed";
+/// The HMAC key used to derive a secp256r1 master key from a seed.
+pub(super) const SECP256R1_CURVE: &[u8] = b"Nist256p1 seed";
+
+/// Generates a new mnemonic of `word_count` words using the system's secure random number
+/// generator.
+///
+/// `word_count` must be one of 12, 15, 18, 21 or 24.
+pub(super) fn generate(word_count: usize) -> Result<String, ErrorExt> {
+    if word_count % 3 != 0 {
+        return Err(ErrorExt::Mnemonic(format!(
+            "invalid word count {}",
+            word_count
+        )));
+    }
+    // Every three words encode 32 bits of entropy.
+    let mut entropy = vec![0u8; word_count / 3 * 4];
+    getrandom::getrandom(&mut entropy)?;
+    let mnemonic =
+        Mnemonic::from_entropy(&entropy).map_err(|error| ErrorExt::Mnemonic(error.to_string()))?;
+    Ok(mnemonic.to_string())
+}
+
+/// Returns the master key for the given curve derived from the mnemonic and passphrase, trying
+/// further candidates while `is_valid` rejects them.
+pub(super) fn master_key<F>(
+    curve: &[u8],
+    mnemonic: &str,
+    passphrase: &str,
+    is_valid: F,
+) -> Result<Vec<u8>, ErrorExt>
+where
+    F: Fn(&[u8]) -> bool,
+{
+    let mnemonic =
+        Mnemonic::parse(mnemonic).map_err(|error| ErrorExt::Mnemonic(error.to_string()))?;
+    let mut data = mnemonic.to_seed(passphrase).to_vec();
+    loop {
+        let mut mac = Hmac::<Sha512>::new_varkey(curve).expect("HMAC accepts keys of any size");
+        mac.update(&data);
+        let output = mac.finalize().into_bytes();
+        // The first half is the key, the second the chain code, which is not needed for the master
+        // key.  An invalid key is replaced by one derived from the whole output.
+        let (key, _chain_code) = output.split_at(32);
+        if is_valid(key) {
+            return Ok(key.to_vec());
+        }
+        data = output.to_vec();
+    }
+}
@@ -96,6 +96,14 @@ pub enum ErrorExt {
     /// Error in getting random bytes from the system's preferred random number source.
     #[error("failed to get random bytes: {0}")]
     GetRandomBytes(#[from] getrandom::Error),
+
+    /// Invalid BIP-39 mnemonic or mnemonic length.
+    #[error("mnemonic error: {0}")]
+    Mnemonic(String),
+
+    /// Error decrypting an encrypted secret key.
+    #[error("decryption error: {0}")]
+    Decryption(String),
 }
 
 #[cfg(any(feature = "std", test))]
@@ -0,0 +1,58 @@
+//! Domain separation of signatures.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// The purpose for which a message is signed.
+///
+/// Signing via [`sign_with_context`](crate::crypto::sign_with_context) binds the signature to its
+/// context, so that a signature created for one purpose can't be replayed as a valid signature for
+/// another, even if the signed bytes happen to coincide.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum SigningContext {
+    /// Consensus protocol messages, such as units and endorsements.
+    Consensus,
+    /// Finality signatures over blocks.
+    FinalitySignature,
+    /// Approvals of deploys by their signers.
+    DeployApproval,
+    /// Approvals of transactions in the `TransactionV2` format by their signers.
+    TransactionApproval,
+}
+
+impl SigningContext {
+    /// Returns the domain separator of the context.
+    ///
+    /// For Ed25519 keys this is used as the Ed25519ph context string, so must be at most 255 bytes
+    /// long.
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            SigningContext::Consensus => b"casper-consensus",
+            SigningContext::FinalitySignature => b"casper-finality-signature",
+            SigningContext::DeployApproval => b"casper-deploy-approval",
+            SigningContext::TransactionApproval => b"casper-transaction-approval",
+        }
+    }
+
+    /// Returns the message prefixed with the length of the domain separator and the domain
+    /// separator itself.
+    pub(super) fn domain_separated(&self, message: &[u8]) -> Vec<u8> {
+        let context = self.as_bytes();
+        let mut bytes = Vec::with_capacity(1 + context.len() + message.len());
+        bytes.push(context.len() as u8);
+        bytes.extend_from_slice(context);
+        bytes.extend_from_slice(message);
+        bytes
+    }
+}
+
+impl Display for SigningContext {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SigningContext::Consensus => write!(formatter, "consensus"),
+            SigningContext::FinalitySignature => write!(formatter, "finality signature"),
+            SigningContext::DeployApproval => write!(formatter, "deploy approval"),
+            SigningContext::TransactionApproval => write!(formatter, "transaction approval"),
+        }
+    }
+}
@@ -37,8 +37,9 @@ use crate::KEY_HASH_LENGTH;
 use crate::{
     account::AccountHash,
     bytesrepr::{self, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    contract_messages::{Message, MessageTopicSummary},
     system::auction::{Bid, EraInfo, UnbondingPurse},
-    CLValue, DeployInfo, NamedKey, Transfer, TransferAddr, U128, U256, U512,
+    CLValue, DeployInfo, NamedKey, Transfer, TransferAddr, TransferV2, U128, U256, U512,
 };
 
 #[derive(FromPrimitive, ToPrimitive, Debug)]
@@ -94,6 +95,9 @@ enum TransformTag {
     AddUInt512 = 15,
     AddKeys = 16,
     Failure = 17,
+    WriteMessageTopic = 18,
+    WriteMessage = 19,
+    WriteTransferV2 = 20,
 }
 
 impl TryFrom<u8> for TransformTag {
@@ -546,6 +550,13 @@ pub enum Transform {
     AddKeys(Vec<NamedKey>),
     /// A failed transformation, containing an error message.
     Failure(String),
+    /// Writes the given message topic summary to global state.
+    WriteMessageTopic(MessageTopicSummary),
+    /// Writes the checksum of the given message to global state.  The message's payload is only
+    /// recorded here, not in global state.
+    WriteMessage(Message),
+    /// Writes the given TransferV2 to global state.
+    WriteTransferV2(TransferV2),
 }
 
 impl Transform {
@@ -569,6 +580,9 @@ impl Transform {
             Transform::AddUInt512(_) => TransformTag::AddUInt512,
             Transform::AddKeys(_) => TransformTag::AddKeys,
             Transform::Failure(_) => TransformTag::Failure,
+            Transform::WriteMessageTopic(_) => TransformTag::WriteMessageTopic,
+            Transform::WriteMessage(_) => TransformTag::WriteMessage,
+            Transform::WriteTransferV2(_) => TransformTag::WriteTransferV2,
         }
     }
 }
@@ -625,6 +639,15 @@ impl ToBytes for Transform {
             Transform::Failure(value) => {
                 buffer.extend(value.to_bytes()?);
             }
+            Transform::WriteMessageTopic(summary) => {
+                buffer.extend(summary.to_bytes()?);
+            }
+            Transform::WriteMessage(message) => {
+                buffer.extend(message.to_bytes()?);
+            }
+            Transform::WriteTransferV2(transfer) => {
+                buffer.extend(transfer.to_bytes()?);
+            }
         }
         Ok(buffer)
     }
@@ -649,6 +672,9 @@ impl ToBytes for Transform {
             | Transform::WriteContractPackage => 0,
             Transform::WriteBid(value) => value.serialized_length(),
             Transform::WriteWithdraw(value) => value.serialized_length(),
+            Transform::WriteMessageTopic(value) => value.serialized_length(),
+            Transform::WriteMessage(value) => value.serialized_length(),
+            Transform::WriteTransferV2(value) => value.serialized_length(),
         };
         U8_SERIALIZED_LENGTH + body_len
     }
@@ -719,6 +745,18 @@ impl FromBytes for Transform {
                     <Vec<UnbondingPurse> as FromBytes>::from_bytes(remainder)?;
                 Ok((Transform::WriteWithdraw(unbonding_purses), remainder))
             }
+            TransformTag::WriteMessageTopic => {
+                let (summary, remainder) = MessageTopicSummary::from_bytes(remainder)?;
+                Ok((Transform::WriteMessageTopic(summary), remainder))
+            }
+            TransformTag::WriteMessage => {
+                let (message, remainder) = Message::from_bytes(remainder)?;
+                Ok((Transform::WriteMessage(message), remainder))
+            }
+            TransformTag::WriteTransferV2 => {
+                let (transfer, remainder) = TransferV2::from_bytes(remainder)?;
+                Ok((Transform::WriteTransferV2(transfer), remainder))
+            }
         }
     }
 }
@@ -726,7 +764,7 @@ impl FromBytes for Transform {
 impl Distribution<Transform> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Transform {
         // TODO - include WriteDeployInfo and WriteTransfer as options
-        match rng.gen_range(0..13) {
+        match rng.gen_range(0..15) {
             0 => Transform::Identity,
             1 => Transform::WriteCLValue(CLValue::from_t(true).unwrap()),
             2 => Transform::WriteAccount(AccountHash::new(rng.gen())),
@@ -749,6 +787,8 @@ impl Distribution<Transform> for Standard {
                 Transform::AddKeys(named_keys)
             }
             12 => Transform::Failure(rng.gen::<u64>().to_string()),
+            13 => Transform::WriteMessageTopic(rng.gen()),
+            14 => Transform::WriteMessage(rng.gen()),
             _ => unreachable!(),
         }
     }
@@ -759,6 +799,7 @@ mod tests {
     use rand::{rngs::SmallRng, Rng, SeedableRng};
 
     use super::*;
+    use crate::{AccessRights, DeployHash, Key, URef};
 
     fn get_rng() -> SmallRng {
         let mut seed = [0u8; 32];
@@ -779,4 +820,36 @@ mod tests {
         let execution_result: ExecutionResult = rng.gen();
         bytesrepr::test_serialization_roundtrip(&execution_result);
     }
+
+    #[test]
+    fn bytesrepr_test_transfers_followed_by_other_transforms() {
+        let mut rng = get_rng();
+        let transfer = Transfer::new(
+            DeployHash::new(rng.gen()),
+            AccountHash::new(rng.gen()),
+            None,
+            URef::new(rng.gen(), AccessRights::READ_ADD_WRITE),
+            URef::new(rng.gen(), AccessRights::READ_ADD_WRITE),
+            U512::from(rng.gen::<u64>()),
+            U512::zero(),
+            Some(rng.gen()),
+        );
+        let mut transfer_v2 = TransferV2::from(transfer);
+        transfer_v2.memo = Some("memo".to_string());
+        let transforms = [
+            Transform::WriteTransfer(transfer),
+            Transform::WriteTransferV2(transfer_v2),
+            Transform::AddUInt512(U512::one()),
+        ];
+        let effect = ExecutionEffect::new(
+            transforms
+                .iter()
+                .map(|transform| TransformEntry {
+                    key: Key::Hash(rng.gen()).to_formatted_string(),
+                    transform: transform.clone(),
+                })
+                .collect(),
+        );
+        bytesrepr::test_serialization_roundtrip(&effect);
+    }
 }
@@ -0,0 +1,353 @@
+//! The second version of the result of executing a single deploy.
+//!
+//! In addition to the data recorded by an [`ExecutionResult`], an [`ExecutionResultV2`] records
+//! the gas consumed by the payment and session phases separately, a structured description of why
+//! execution failed, and the contract messages emitted during execution.
+//!
+//! Results stored in the legacy format can be converted via `ExecutionResultV2::from`, leaving the
+//! details which the legacy format doesn't record unset.
+
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+#[cfg(feature = "json-schema")]
+use alloc::string::ToString;
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(feature = "datasize")]
+use datasize::DataSize;
+#[cfg(feature = "json-schema")]
+use once_cell::sync::Lazy;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bytesrepr::{Bytes, FromBytes, ToBytes},
+    contract_messages::{Message, MessageAddr, MessageChecksum},
+    ContractHash, ExecutionEffect, ExecutionResult, Key, TransferAddr, Transform, U512,
+};
+
+#[cfg(feature = "json-schema")]
+static EXECUTION_RESULT_V2: Lazy<ExecutionResultV2> = Lazy::new(|| {
+    let mut result = ExecutionResultV2::from(ExecutionResult::example().clone());
+    result.payment_gas = Some(U512::from(23_456));
+    result.session_gas = Some(U512::from(100_000));
+    result.failure = Some(ExecutionFailure {
+        reason: FailureReason::Revert { error_code: 65_537 },
+        error_message: "User error: 1".to_string(),
+        contract_hash: Some(ContractHash::new([34; 32])),
+        entry_point: Some("transfer".to_string()),
+    });
+    result.events.push(EmittedEvent {
+        message_addr: MessageAddr::new_message_addr([34; 32], [55; 32], 0),
+        checksum: MessageChecksum::from_payload(b"transferred"),
+        payload: Bytes::from(b"transferred".as_ref()),
+    });
+    result
+});
+
+/// The result of executing a single deploy, recording the gas consumed per phase, the reason for
+/// any failure and the events emitted during execution.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, ToBytes, FromBytes)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ExecutionResultV2 {
+    /// The effect of executing the deploy.
+    pub effect: ExecutionEffect,
+    /// A record of Transfers performed while executing the deploy.
+    pub transfers: Vec<TransferAddr>,
+    /// The total cost of executing the deploy.
+    pub cost: U512,
+    /// The gas consumed by the payment phase, if known.
+    pub payment_gas: Option<U512>,
+    /// The gas consumed by the session phase, if known.
+    pub session_gas: Option<U512>,
+    /// The reason execution failed, or `None` if it succeeded.
+    pub failure: Option<ExecutionFailure>,
+    /// The contract messages emitted while executing the deploy, in order of emission.
+    pub events: Vec<EmittedEvent>,
+}
+
+impl ExecutionResultV2 {
+    // This method is not intended to be used by third party crates.
+    #[doc(hidden)]
+    #[cfg(feature = "json-schema")]
+    pub fn example() -> &'static Self {
+        &*EXECUTION_RESULT_V2
+    }
+
+    /// Returns `true` if execution succeeded.
+    pub fn is_success(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+impl From<ExecutionResult> for ExecutionResultV2 {
+    fn from(execution_result: ExecutionResult) -> Self {
+        let (effect, transfers, cost, failure) = match execution_result {
+            ExecutionResult::Failure {
+                effect,
+                transfers,
+                cost,
+                error_message,
+            } => {
+                let failure = ExecutionFailure {
+                    reason: FailureReason::Other,
+                    error_message,
+                    contract_hash: None,
+                    entry_point: None,
+                };
+                (effect, transfers, cost, Some(failure))
+            }
+            ExecutionResult::Success {
+                effect,
+                transfers,
+                cost,
+            } => (effect, transfers, cost, None),
+        };
+        let events = EmittedEvent::from_effect(&effect);
+        ExecutionResultV2 {
+            effect,
+            transfers,
+            cost,
+            payment_gas: None,
+            session_gas: None,
+            failure,
+            events,
+        }
+    }
+}
+
+impl Distribution<ExecutionResultV2> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ExecutionResultV2 {
+        let mut result = ExecutionResultV2::from(rng.gen::<ExecutionResult>());
+        if rng.gen() {
+            result.payment_gas = Some(rng.gen::<u32>().into());
+            result.session_gas = Some(rng.gen::<u32>().into());
+        }
+        if let Some(failure) = result.failure.as_mut() {
+            *failure = rng.gen();
+        }
+        let event_count = rng.gen_range(0..4);
+        result.events = (0..event_count).map(|_| rng.gen()).collect();
+        result
+    }
+}
+
+/// The reason execution of a deploy failed.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, ToBytes, FromBytes)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ExecutionFailure {
+    /// The category of the failure.
+    pub reason: FailureReason,
+    /// The error message associated with the failure.
+    pub error_message: String,
+    /// The hash of the contract being executed when the failure occurred, if known.
+    pub contract_hash: Option<ContractHash>,
+    /// The entry point being executed when the failure occurred, if known.
+    pub entry_point: Option<String>,
+}
+
+impl Distribution<ExecutionFailure> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ExecutionFailure {
+        let reason = match rng.gen_range(0..5) {
+            0 => FailureReason::Revert {
+                error_code: rng.gen(),
+            },
+            1 => FailureReason::GasLimit,
+            2 => FailureReason::InsufficientPayment,
+            3 => FailureReason::Finalization,
+            _ => FailureReason::Other,
+        };
+        let (contract_hash, entry_point) = if rng.gen() {
+            (
+                Some(ContractHash::new(rng.gen())),
+                Some(format!("entry_point_{}", rng.gen::<u8>())),
+            )
+        } else {
+            (None, None)
+        };
+        ExecutionFailure {
+            reason,
+            error_message: format!("Error message {}", rng.gen::<u64>()),
+            contract_hash,
+            entry_point,
+        }
+    }
+}
+
+/// The category of an execution failure.
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Debug, ToBytes, FromBytes)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub enum FailureReason {
+    /// Execution was reverted by a contract or by a system contract.
+    #[bytesrepr(tag = 0)]
+    Revert {
+        /// The code of the [`ApiError`](crate::ApiError) execution was reverted with.
+        error_code: u32,
+    },
+    /// Execution ran out of gas.
+    #[bytesrepr(tag = 1)]
+    GasLimit,
+    /// The payment provided for the deploy was insufficient.
+    #[bytesrepr(tag = 2)]
+    InsufficientPayment,
+    /// Finalizing the payment of the deploy failed.
+    #[bytesrepr(tag = 3)]
+    Finalization,
+    /// Any other failure, including all failures converted from the legacy format.
+    #[bytesrepr(tag = 4)]
+    Other,
+}
+
+impl FailureReason {
+    /// Returns the error code of the failure, if it has one.
+    pub fn error_code(&self) -> Option<u32> {
+        match self {
+            FailureReason::Revert { error_code } => Some(*error_code),
+            FailureReason::GasLimit
+            | FailureReason::InsufficientPayment
+            | FailureReason::Finalization
+            | FailureReason::Other => None,
+        }
+    }
+}
+
+/// A contract message emitted during execution.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, ToBytes, FromBytes)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct EmittedEvent {
+    /// The address of the message in global state.
+    pub message_addr: MessageAddr,
+    /// The checksum of the message's payload.
+    pub checksum: MessageChecksum,
+    /// The payload of the message.
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "String", description = "Hex-encoded message payload.")
+    )]
+    pub payload: Bytes,
+}
+
+impl EmittedEvent {
+    /// Returns the messages written by the transforms of `effect`, in order.
+    fn from_effect(effect: &ExecutionEffect) -> Vec<EmittedEvent> {
+        effect
+            .transforms
+            .iter()
+            .filter_map(
+                |entry| match (&entry.transform, Key::from_formatted_str(&entry.key)) {
+                    (Transform::WriteMessage(message), Ok(Key::Message(message_addr))) => {
+                        Some(EmittedEvent {
+                            message_addr,
+                            checksum: message.checksum(),
+                            payload: message.payload().clone(),
+                        })
+                    }
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+}
+
+impl Distribution<EmittedEvent> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> EmittedEvent {
+        let message: Message = rng.gen();
+        EmittedEvent {
+            message_addr: MessageAddr::new_message_addr(rng.gen(), rng.gen(), rng.gen()),
+            checksum: message.checksum(),
+            payload: message.payload().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+    use crate::{bytesrepr, testing::TestRng, TransformEntry};
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        let mut rng = TestRng::new();
+        let execution_result: ExecutionResultV2 = rng.gen();
+        bytesrepr::test_serialization_roundtrip(&execution_result);
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let mut rng = TestRng::new();
+        let execution_result: ExecutionResultV2 = rng.gen();
+        let json = serde_json::to_string(&execution_result).unwrap();
+        let decoded: ExecutionResultV2 = serde_json::from_str(&json).unwrap();
+        assert_eq!(execution_result, decoded);
+    }
+
+    #[test]
+    fn should_convert_legacy_failure() {
+        let message_addr = MessageAddr::new_message_addr([1; 32], [2; 32], 3);
+        let message = Message::new(Bytes::from(b"payload".as_ref()));
+        let effect = ExecutionEffect::new(vec![
+            TransformEntry {
+                key: Key::Message(message_addr).to_formatted_string(),
+                transform: Transform::WriteMessage(message.clone()),
+            },
+            TransformEntry {
+                key: Key::Hash([4; 32]).to_formatted_string(),
+                transform: Transform::Identity,
+            },
+        ]);
+        let legacy = ExecutionResult::Failure {
+            effect: effect.clone(),
+            transfers: vec![TransferAddr::new([5; 32])],
+            cost: U512::from(10),
+            error_message: "Out of gas error".to_string(),
+        };
+
+        let converted = ExecutionResultV2::from(legacy);
+        assert!(!converted.is_success());
+        assert_eq!(converted.effect, effect);
+        assert_eq!(converted.transfers, vec![TransferAddr::new([5; 32])]);
+        assert_eq!(converted.cost, U512::from(10));
+        assert_eq!(converted.payment_gas, None);
+        assert_eq!(converted.session_gas, None);
+        let failure = converted.failure.unwrap();
+        assert_eq!(failure.reason, FailureReason::Other);
+        assert_eq!(failure.error_message, "Out of gas error");
+        assert_eq!(
+            converted.events,
+            vec![EmittedEvent {
+                message_addr,
+                checksum: message.checksum(),
+                payload: message.payload().clone(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_convert_legacy_success() {
+        let legacy = ExecutionResult::Success {
+            effect: ExecutionEffect::default(),
+            transfers: vec![],
+            cost: U512::from(7),
+        };
+        let converted = ExecutionResultV2::from(legacy);
+        assert!(converted.is_success());
+        assert_eq!(converted.cost, U512::from(7));
+        assert!(converted.events.is_empty());
+    }
+}
@@ -14,6 +14,7 @@ use proptest::{
 
 use crate::{
     account::{gens::account_arb, AccountHash, Weight},
+    contract_messages::{MessageAddr, MessageChecksum, MessageTopicSummary},
     contracts::{
         ContractPackageStatus, ContractVersions, DisabledVersions, Groups, NamedKeys, Parameters,
     },
@@ -23,13 +24,17 @@ use crate::{
         DELEGATION_RATE_DENOMINATOR,
     },
     transfer::TransferAddr,
-    AccessRights, CLType, CLValue, Contract, ContractHash, ContractPackage, ContractVersionKey,
-    ContractWasm, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, EraId, Group, Key,
-    NamedArg, Parameter, Phase, ProtocolVersion, SemVer, StoredValue, URef, U128, U256, U512,
+    AccessRights, BlockTime, CLType, CLValue, Contract, ContractHash, ContractPackage,
+    ContractVersionKey, ContractWasm, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints,
+    EraId, Group, Key, NamedArg, Parameter, Phase, ProtocolVersion, SemVer, StoredValue, URef,
+    U128, U256, U512,
 };
 
 use crate::deploy_info::gens::{deploy_hash_arb, transfer_addr_arb};
-pub use crate::{deploy_info::gens::deploy_info_arb, transfer::gens::transfer_arb};
+pub use crate::{
+    deploy_info::gens::deploy_info_arb,
+    transfer::gens::{transfer_arb, transfer_v2_arb},
+};
 
 pub fn u8_slice_32() -> impl Strategy<Value = [u8; 32]> {
     collection::vec(any::<u8>(), 32).prop_map(|b| {
@@ -94,9 +99,25 @@ pub fn key_arb() -> impl Strategy<Value = Key> {
         account_hash_arb().prop_map(Key::Bid),
         account_hash_arb().prop_map(Key::Withdraw),
         u8_slice_32().prop_map(Key::Dictionary),
+        message_addr_arb().prop_map(Key::Message),
     ]
 }
 
+pub fn message_addr_arb() -> impl Strategy<Value = MessageAddr> {
+    (u8_slice_32(), u8_slice_32(), option::of(any::<u32>())).prop_map(
+        |(entity_addr, topic_name_hash, message_index)| match message_index {
+            Some(index) => MessageAddr::new_message_addr(entity_addr, topic_name_hash, index),
+            None => MessageAddr::new_topic_addr(entity_addr, topic_name_hash),
+        },
+    )
+}
+
+pub fn message_topic_summary_arb() -> impl Strategy<Value = MessageTopicSummary> {
+    (any::<u32>(), any::<u64>()).prop_map(|(message_count, blocktime)| {
+        MessageTopicSummary::new(message_count, BlockTime::new(blocktime))
+    })
+}
+
 pub fn colliding_key_arb() -> impl Strategy<Value = Key> {
     prop_oneof![
         u2_slice_32().prop_map(|bytes| Key::Account(AccountHash::new(bytes))),
@@ -446,7 +467,7 @@ pub(crate) fn bid_arb(delegations_len: impl Into<SizeRange>) -> impl Strategy<Va
         )
 }
 
-fn withdraw_arb() -> impl Strategy<Value = WithdrawPurse> {
+pub(crate) fn withdraw_arb() -> impl Strategy<Value = WithdrawPurse> {
     (
         uref_arb(),
         public_key_arb_no_system(),
@@ -463,7 +484,7 @@ fn withdraws_arb(size: impl Into<SizeRange>) -> impl Strategy<Value = Vec<Withdr
     collection::vec(withdraw_arb(), size)
 }
 
-fn unbonding_arb() -> impl Strategy<Value = UnbondingPurse> {
+pub(crate) fn unbonding_arb() -> impl Strategy<Value = UnbondingPurse> {
     (
         uref_arb(),
         public_key_arb_no_system(),
@@ -509,7 +530,10 @@ pub fn stored_value_arb() -> impl Strategy<Value = StoredValue> {
         era_info_arb(1..10).prop_map(StoredValue::EraInfo),
         bid_arb(0..100).prop_map(|bid| StoredValue::Bid(Box::new(bid))),
         withdraws_arb(1..50).prop_map(StoredValue::Withdraw),
-        unbondings_arb(1..50).prop_map(StoredValue::Unbonding)
+        unbondings_arb(1..50).prop_map(StoredValue::Unbonding),
+        message_topic_summary_arb().prop_map(StoredValue::MessageTopic),
+        u8_slice_32().prop_map(|bytes| StoredValue::Message(MessageChecksum::new(bytes))),
+        transfer_v2_arb().prop_map(StoredValue::TransferV2)
     ]
     .prop_map(|stored_value|
         // The following match statement is here only to make sure
@@ -526,5 +550,8 @@ pub fn stored_value_arb() -> impl Strategy<Value = StoredValue> {
             StoredValue::Bid(_) => stored_value,
             StoredValue::Withdraw(_) => stored_value,
             StoredValue::Unbonding(_) => stored_value,
+            StoredValue::MessageTopic(_) => stored_value,
+            StoredValue::Message(_) => stored_value,
+            StoredValue::TransferV2(_) => stored_value,
         })
 }
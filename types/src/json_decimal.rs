@@ -0,0 +1,242 @@
+//! Representation of large integers as decimal strings in JSON.
+//!
+//! JavaScript parses all JSON numbers as doubles, silently corrupting integers above 2^53.  This
+//! module provides a serde mode which emits integers as plain decimal strings when using a
+//! human-readable serializer, and accepts either a decimal string or a JSON number when
+//! deserializing.  Apply it to a field via `#[serde(with = "casper_types::json_decimal")]`.
+//!
+//! [`U128`], [`U256`] and [`U512`] are always emitted as decimal strings.  Whether other integers
+//! which are emitted as JSON numbers by default, such as the `u64` and `i64` values in the parsed
+//! representation of a [`CLValue`](crate::CLValue), are emitted as decimal strings instead is
+//! controlled process-wide via [`set_json_output`].
+
+use alloc::{format, string::String};
+use core::{
+    fmt::{self, Display, Formatter},
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use serde::{
+    de::{Error as SerdeError, Unexpected, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{U128, U256, U512};
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether integers which are emitted as JSON numbers by default are emitted as decimal
+/// strings instead when using a human-readable serializer.
+pub fn set_json_output(enabled: bool) {
+    JSON_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether integers which are emitted as JSON numbers by default are emitted as decimal
+/// strings instead when using a human-readable serializer.
+///
+/// This is always `false` during [canonical JSON](crate::canonical_json) serialization.
+pub fn json_output() -> bool {
+    #[cfg(any(feature = "std", test))]
+    if crate::canonical_json::in_progress() {
+        return false;
+    }
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// An unsigned integer type which can be represented as a decimal string.
+pub trait DecimalString: Sized + Display {
+    /// Parses a non-empty string of ASCII digits, returning `None` if it is malformed or the value
+    /// is out of range.
+    fn from_decimal_str(input: &str) -> Option<Self>;
+
+    /// Converts from a `u64`, returning `None` if the value is out of range.
+    fn from_u64(value: u64) -> Option<Self>;
+}
+
+/// Returns whether `input` is a non-empty string of ASCII digits.
+fn is_decimal(input: &str) -> bool {
+    !input.is_empty() && input.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+impl DecimalString for u64 {
+    fn from_decimal_str(input: &str) -> Option<Self> {
+        if !is_decimal(input) {
+            return None;
+        }
+        input.parse().ok()
+    }
+
+    fn from_u64(value: u64) -> Option<Self> {
+        Some(value)
+    }
+}
+
+macro_rules! impl_decimal_string_for_uint {
+    ($type:ident) => {
+        impl DecimalString for $type {
+            fn from_decimal_str(input: &str) -> Option<Self> {
+                if !is_decimal(input) {
+                    return None;
+                }
+                $type::from_dec_str(input).ok()
+            }
+
+            fn from_u64(value: u64) -> Option<Self> {
+                Some($type::from(value))
+            }
+        }
+    };
+}
+
+impl_decimal_string_for_uint!(U128);
+impl_decimal_string_for_uint!(U256);
+impl_decimal_string_for_uint!(U512);
+
+/// Serializes the value as a decimal string when using a human-readable serializer, and via its
+/// own `Serialize` implementation otherwise.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: DecimalString + Serialize,
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        serializer.collect_str(value)
+    } else {
+        value.serialize(serializer)
+    }
+}
+
+/// Deserializes the value from either a decimal string or a number when using a human-readable
+/// deserializer, and via its own `Deserialize` implementation otherwise.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: DecimalString + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        deserialize_human_readable(deserializer)
+    } else {
+        T::deserialize(deserializer)
+    }
+}
+
+/// Deserializes the value from either a decimal string or a number.
+pub(crate) fn deserialize_human_readable<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: DecimalString,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(DecimalVisitor(PhantomData))
+}
+
+struct DecimalVisitor<T>(PhantomData<T>);
+
+impl<'de, T: DecimalString> Visitor<'de> for DecimalVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("an unsigned integer as a decimal string or a number")
+    }
+
+    fn visit_str<E: SerdeError>(self, value: &str) -> Result<T, E> {
+        T::from_decimal_str(value).ok_or_else(|| {
+            SerdeError::custom(format!("invalid unsigned decimal integer '{}'", value))
+        })
+    }
+
+    fn visit_string<E: SerdeError>(self, value: String) -> Result<T, E> {
+        self.visit_str(&value)
+    }
+
+    fn visit_u64<E: SerdeError>(self, value: u64) -> Result<T, E> {
+        T::from_u64(value)
+            .ok_or_else(|| SerdeError::invalid_value(Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_i64<E: SerdeError>(self, value: i64) -> Result<T, E> {
+        if value < 0 {
+            return Err(SerdeError::invalid_value(Unexpected::Signed(value), &self));
+        }
+        self.visit_u64(value as u64)
+    }
+
+    fn visit_f64<E: SerdeError>(self, value: f64) -> Result<T, E> {
+        // Integers too large for a `u64` are parsed as floats, having already lost precision.
+        Err(SerdeError::custom(format!(
+            "{} is not an exact unsigned integer; large integers must be given as decimal strings",
+            value
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Amounts {
+        #[serde(with = "super")]
+        small: u64,
+        #[serde(with = "super")]
+        large: U512,
+    }
+
+    #[test]
+    fn should_emit_decimal_strings() {
+        let amounts = Amounts {
+            small: u64::MAX,
+            large: U512::max_value(),
+        };
+        let json = serde_json::to_string(&amounts).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"small":"{}","large":"{}"}}"#,
+                u64::MAX,
+                U512::max_value()
+            )
+        );
+        assert_eq!(serde_json::from_str::<Amounts>(&json).unwrap(), amounts);
+
+        let encoded = bincode::serialize(&amounts).unwrap();
+        assert_eq!(bincode::deserialize::<Amounts>(&encoded).unwrap(), amounts);
+    }
+
+    #[test]
+    fn should_accept_numbers() {
+        let amounts: Amounts =
+            serde_json::from_str(r#"{"small":7,"large":9007199254740993}"#).unwrap();
+        assert_eq!(amounts.small, 7);
+        assert_eq!(amounts.large, U512::from(9_007_199_254_740_993_u64));
+    }
+
+    #[test]
+    fn should_reject_malformed_input() {
+        for invalid in [
+            r#""""#,
+            r#""+1""#,
+            r#""-1""#,
+            r#"" 1""#,
+            r#""0x1""#,
+            r#""1.5""#,
+            "-1",
+            "1.5",
+            "18446744073709551616",
+            "true",
+        ] {
+            let json = format!(r#"{{"small":{},"large":"1"}}"#, invalid);
+            assert!(
+                serde_json::from_str::<Amounts>(&json).is_err(),
+                "{} should be rejected",
+                invalid
+            );
+        }
+        assert!(
+            serde_json::from_str::<Amounts>(r#"{"small":"18446744073709551616","large":"1"}"#)
+                .is_err()
+        );
+    }
+}
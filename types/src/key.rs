@@ -26,8 +26,12 @@ use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Seria
 
 use crate::{
     account::{self, AccountHash, ACCOUNT_HASH_LENGTH},
-    bytesrepr::{self, Error, FromBytes, ToBytes, U64_SERIALIZED_LENGTH},
+    bytesrepr::{
+        self, Error, FromBytes, ToBytes, U32_SERIALIZED_LENGTH, U64_SERIALIZED_LENGTH,
+        U8_SERIALIZED_LENGTH,
+    },
     checksummed_hex,
+    contract_messages::{MessageAddr, TopicNameHash, TOPIC_NAME_HASH_LENGTH},
     contract_wasm::ContractWasmHash,
     contracts::{ContractHash, ContractPackageHash},
     uref::{self, URef, URefAddr, UREF_SERIALIZED_LENGTH},
@@ -47,6 +51,8 @@ const SYSTEM_CONTRACT_REGISTRY_PREFIX: &str = "system-contract-registry-";
 const CHAINSPEC_REGISTRY_PREFIX: &str = "chainspec-registry-";
 const BLOCK_EFFECTS_ROOT_HASH_PREFIX: &str = "block-effects-root-hash-";
 const DEPLOY_APPROVALS_ROOT_HASH_REGISTRY_PREFIX: &str = "deploy-approvals-root-hash-";
+const MESSAGE_TOPIC_PREFIX: &str = "message-topic-";
+const MESSAGE_PREFIX: &str = "message-";
 
 /// The number of bytes in a Blake2b hash
 pub const BLAKE2B_DIGEST_LENGTH: usize = 32;
@@ -82,6 +88,12 @@ const KEY_CHAINSPEC_REGISTRY_SERIALIZED_LENGTH: usize =
 const KEY_BLOCK_EFFECTS_ROOT_HASH_LENGTH: usize = KEY_ID_SERIALIZED_LENGTH + U64_SERIALIZED_LENGTH;
 const KEY_DEPLOY_APPROVALS_ROOT_HASH_SERIALIZED_LENGTH: usize =
     KEY_ID_SERIALIZED_LENGTH + U64_SERIALIZED_LENGTH;
+// A `Key::Message` addressing a single message, i.e. with `Some` message index.
+const KEY_MESSAGE_MAX_SERIALIZED_LENGTH: usize = KEY_ID_SERIALIZED_LENGTH
+    + KEY_HASH_LENGTH
+    + TOPIC_NAME_HASH_LENGTH
+    + U8_SERIALIZED_LENGTH
+    + U32_SERIALIZED_LENGTH;
 
 /// An alias for [`Key`]s hash variant.
 pub type HashAddr = [u8; KEY_HASH_LENGTH];
@@ -108,6 +120,7 @@ pub enum KeyTag {
     ChainspecRegistry = 12,
     BlockEffectsRootHash = 13,
     DeployApprovalsRootHash = 14,
+    Message = 15,
 }
 
 /// The type under which data (e.g. [`CLValue`](crate::CLValue)s, smart contracts, user accounts)
@@ -155,6 +168,8 @@ pub enum Key {
         /// The height of the block whose deploy approvals are hashed here.
         block_height: u64,
     },
+    /// A `Key` under which we store a message topic summary or a message emitted by a contract.
+    Message(MessageAddr),
 }
 
 /// Errors produced when converting a `String` into a `Key`.
@@ -191,6 +206,8 @@ pub enum FromStrError {
     BlockEffectsRootHash(String),
     /// DeployApprovalsRootHash parse error.
     DeployApprovalsRootHash(String),
+    /// Message parse error.
+    Message(String),
     /// Unknown prefix.
     UnknownPrefix,
 }
@@ -257,6 +274,9 @@ impl Display for FromStrError {
                     error
                 )
             }
+            FromStrError::Message(error) => {
+                write!(f, "message-key from string error: {}", error)
+            }
             FromStrError::UnknownPrefix => write!(f, "unknown prefix for key"),
         }
     }
@@ -282,12 +302,13 @@ impl Key {
             Key::ChainspecRegistry => String::from("Key::ChainspecRegistry"),
             Key::BlockEffectsRootHash { .. } => String::from("Key::BlockEffectsRootHash"),
             Key::DeployApprovalsRootHash { .. } => String::from("Key::DeployApprovalsRootHash"),
+            Key::Message(_) => String::from("Key::Message"),
         }
     }
 
     /// Returns the maximum size a [`Key`] can be serialized into.
     pub const fn max_serialized_length() -> usize {
-        KEY_UREF_SERIALIZED_LENGTH
+        KEY_MESSAGE_MAX_SERIALIZED_LENGTH
     }
 
     /// If `self` is of type [`Key::URef`], returns `self` with the
@@ -360,6 +381,14 @@ impl Key {
                     DEPLOY_APPROVALS_ROOT_HASH_REGISTRY_PREFIX, block_height
                 )
             }
+            Key::Message(message_addr) => {
+                let prefix = if message_addr.message_index().is_some() {
+                    MESSAGE_PREFIX
+                } else {
+                    MESSAGE_TOPIC_PREFIX
+                };
+                format!("{}{}", prefix, message_addr)
+            }
         }
     }
 
@@ -483,6 +512,23 @@ impl Key {
             return Ok(Key::BlockEffectsRootHash { block_height });
         }
 
+        // We try this variant before messages as MESSAGE_PREFIX is a valid prefix of
+        // MESSAGE_TOPIC_PREFIX.
+        if let Some(topic_str) = input.strip_prefix(MESSAGE_TOPIC_PREFIX) {
+            let (entity_addr, topic_name_hash) = parse_message_topic(topic_str)?;
+            return Ok(Key::message_topic(entity_addr, topic_name_hash));
+        }
+
+        if let Some(message_str) = input.strip_prefix(MESSAGE_PREFIX) {
+            let (topic_str, index_str) = message_str
+                .rsplit_once('-')
+                .ok_or_else(|| FromStrError::Message("missing message index".to_string()))?;
+            let (entity_addr, topic_name_hash) = parse_message_topic(topic_str)?;
+            let message_index = u32::from_str(index_str)
+                .map_err(|error| FromStrError::Message(error.to_string()))?;
+            return Ok(Key::message(entity_addr, topic_name_hash, message_index));
+        }
+
         Err(FromStrError::UnknownPrefix)
     }
 
@@ -584,6 +630,51 @@ impl Key {
         }
         false
     }
+
+    /// Creates a new [`Key::Message`] variant under which the summary of the topic registered by
+    /// the given contract is stored.
+    pub fn message_topic(entity_addr: HashAddr, topic_name_hash: TopicNameHash) -> Key {
+        Key::Message(MessageAddr::new_topic_addr(entity_addr, topic_name_hash))
+    }
+
+    /// Creates a new [`Key::Message`] variant under which the message at `message_index` within
+    /// the topic registered by the given contract is stored.
+    pub fn message(
+        entity_addr: HashAddr,
+        topic_name_hash: TopicNameHash,
+        message_index: u32,
+    ) -> Key {
+        Key::Message(MessageAddr::new_message_addr(
+            entity_addr,
+            topic_name_hash,
+            message_index,
+        ))
+    }
+
+    /// Returns a reference to the inner [`MessageAddr`] if `self` is of type [`Key::Message`],
+    /// otherwise returns `None`.
+    pub fn as_message_addr(&self) -> Option<&MessageAddr> {
+        match self {
+            Key::Message(message_addr) => Some(message_addr),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the `<entity address>-<topic name hash>` portion of a formatted [`Key::Message`].
+fn parse_message_topic(input: &str) -> Result<(HashAddr, TopicNameHash), FromStrError> {
+    let (entity_str, topic_str) = input
+        .split_once('-')
+        .ok_or_else(|| FromStrError::Message("missing topic name hash".to_string()))?;
+    let entity_bytes = checksummed_hex::decode(entity_str)
+        .map_err(|error| FromStrError::Message(error.to_string()))?;
+    let entity_addr = HashAddr::try_from(entity_bytes.as_ref())
+        .map_err(|error| FromStrError::Message(error.to_string()))?;
+    let topic_bytes = checksummed_hex::decode(topic_str)
+        .map_err(|error| FromStrError::Message(error.to_string()))?;
+    let topic_name_hash = TopicNameHash::try_from(topic_bytes.as_ref())
+        .map_err(|error| FromStrError::Message(error.to_string()))?;
+    Ok((entity_addr, topic_name_hash))
 }
 
 impl Display for Key {
@@ -624,6 +715,7 @@ impl Display for Key {
             Key::DeployApprovalsRootHash { block_height } => {
                 write!(f, "Key::DeployApprovalsRootHash({})", block_height)
             }
+            Key::Message(message_addr) => write!(f, "Key::Message({})", message_addr),
         }
     }
 }
@@ -652,6 +744,7 @@ impl Tagged<KeyTag> for Key {
             Key::ChainspecRegistry => KeyTag::ChainspecRegistry,
             Key::BlockEffectsRootHash { .. } => KeyTag::BlockEffectsRootHash,
             Key::DeployApprovalsRootHash { .. } => KeyTag::DeployApprovalsRootHash,
+            Key::Message(_) => KeyTag::Message,
         }
     }
 }
@@ -747,6 +840,7 @@ impl ToBytes for Key {
             Key::DeployApprovalsRootHash { block_height } => {
                 result.append(&mut block_height.to_bytes()?)
             }
+            Key::Message(message_addr) => result.append(&mut message_addr.to_bytes()?),
         }
         Ok(result)
     }
@@ -770,6 +864,9 @@ impl ToBytes for Key {
             Key::ChainspecRegistry => KEY_CHAINSPEC_REGISTRY_SERIALIZED_LENGTH,
             Key::BlockEffectsRootHash { .. } => KEY_BLOCK_EFFECTS_ROOT_HASH_LENGTH,
             Key::DeployApprovalsRootHash { .. } => KEY_DEPLOY_APPROVALS_ROOT_HASH_SERIALIZED_LENGTH,
+            Key::Message(message_addr) => {
+                KEY_ID_SERIALIZED_LENGTH + message_addr.serialized_length()
+            }
         }
     }
 
@@ -791,6 +888,7 @@ impl ToBytes for Key {
             Key::ChainspecRegistry => CHAINSPEC_REGISTRY_KEY_BYTES.write_bytes(writer),
             Key::BlockEffectsRootHash { block_height } => block_height.write_bytes(writer),
             Key::DeployApprovalsRootHash { block_height } => block_height.write_bytes(writer),
+            Key::Message(message_addr) => message_addr.write_bytes(writer),
         }
     }
 }
@@ -859,6 +957,10 @@ impl FromBytes for Key {
                 let (block_height, rem) = u64::from_bytes(remainder)?;
                 Ok((Key::DeployApprovalsRootHash { block_height }, rem))
             }
+            tag if tag == KeyTag::Message as u8 => {
+                let (message_addr, rem) = MessageAddr::from_bytes(remainder)?;
+                Ok((Key::Message(message_addr), rem))
+            }
             _ => Err(Error::Formatting),
         }
     }
@@ -884,12 +986,13 @@ fn please_add_to_distribution_impl(key: Key) {
         Key::ChainspecRegistry => unimplemented!(),
         Key::BlockEffectsRootHash { .. } => unimplemented!(),
         Key::DeployApprovalsRootHash { .. } => unimplemented!(),
+        Key::Message(_) => unimplemented!(),
     }
 }
 
 impl Distribution<Key> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Key {
-        match rng.gen_range(0..=14) {
+        match rng.gen_range(0..=15) {
             0 => Key::Account(rng.gen()),
             1 => Key::Hash(rng.gen()),
             2 => Key::URef(rng.gen()),
@@ -909,6 +1012,7 @@ impl Distribution<Key> for Standard {
             14 => Key::DeployApprovalsRootHash {
                 block_height: rng.gen(),
             },
+            15 => Key::Message(rng.gen()),
             _ => unreachable!(),
         }
     }
@@ -934,6 +1038,7 @@ mod serde_helpers {
         ChainspecRegistry(String),
         BlockEffectsRootHash(String),
         DeployApprovalsRootHash(String),
+        Message(String),
     }
 
     impl From<&Key> for HumanReadable {
@@ -961,6 +1066,7 @@ mod serde_helpers {
                 Key::DeployApprovalsRootHash { .. } => {
                     HumanReadable::DeployApprovalsRootHash(formatted_string)
                 }
+                Key::Message(_) => HumanReadable::Message(formatted_string),
             }
         }
     }
@@ -984,7 +1090,8 @@ mod serde_helpers {
                 | HumanReadable::SystemContractRegistry(formatted_string)
                 | HumanReadable::ChainspecRegistry(formatted_string)
                 | HumanReadable::BlockEffectsRootHash(formatted_string)
-                | HumanReadable::DeployApprovalsRootHash(formatted_string) => {
+                | HumanReadable::DeployApprovalsRootHash(formatted_string)
+                | HumanReadable::Message(formatted_string) => {
                     Key::from_formatted_str(&formatted_string)
                 }
             }
@@ -1008,6 +1115,7 @@ mod serde_helpers {
         ChainspecRegistry,
         BlockEffectsRootHash { block_height: u64 },
         DeployApprovalsRootHash { block_height: u64 },
+        Message(&'a MessageAddr),
     }
 
     impl<'a> From<&'a Key> for BinarySerHelper<'a> {
@@ -1036,6 +1144,7 @@ mod serde_helpers {
                         block_height: *block_height,
                     }
                 }
+                Key::Message(message_addr) => BinarySerHelper::Message(message_addr),
             }
         }
     }
@@ -1057,6 +1166,7 @@ mod serde_helpers {
         ChainspecRegistry,
         BlockEffectsRootHash { block_height: u64 },
         DeployApprovalsRootHash { block_height: u64 },
+        Message(MessageAddr),
     }
 
     impl From<BinaryDeserHelper> for Key {
@@ -1081,6 +1191,7 @@ mod serde_helpers {
                 BinaryDeserHelper::DeployApprovalsRootHash { block_height } => {
                     Key::DeployApprovalsRootHash { block_height }
                 }
+                BinaryDeserHelper::Message(message_addr) => Key::Message(message_addr),
             }
         }
     }
@@ -1142,7 +1253,9 @@ mod tests {
     const DEPLOY_APPROVALS_ROOT_HASH_KEY: Key = Key::DeployApprovalsRootHash {
         block_height: BLOCK_HEIGHT,
     };
-    const KEYS: [Key; 15] = [
+    const MESSAGE_TOPIC_KEY: Key = Key::Message(MessageAddr::new_topic_addr([42; 32], [43; 32]));
+    const MESSAGE_KEY: Key = Key::Message(MessageAddr::new_message_addr([42; 32], [43; 32], 7));
+    const KEYS: [Key; 17] = [
         ACCOUNT_KEY,
         HASH_KEY,
         UREF_KEY,
@@ -1158,8 +1271,12 @@ mod tests {
         UNBOND_KEY,
         BLOCK_EFFECTS_ROOT_HASH_KEY,
         DEPLOY_APPROVALS_ROOT_HASH_KEY,
+        MESSAGE_TOPIC_KEY,
+        MESSAGE_KEY,
     ];
     const HEX_STRING: &str = "2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a";
+    const TOPIC_HEX_STRING: &str =
+        "2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b";
 
     fn test_readable(right: AccessRights, is_true: bool) {
         assert_eq!(right.is_readable(), is_true)
@@ -1271,6 +1388,14 @@ mod tests {
             format!("{}", DEPLOY_APPROVALS_ROOT_HASH_KEY),
             format!("Key::DeployApprovalsRootHash({})", BLOCK_HEIGHT,)
         );
+        assert_eq!(
+            format!("{}", MESSAGE_TOPIC_KEY),
+            format!("Key::Message({}-{})", HEX_STRING, TOPIC_HEX_STRING)
+        );
+        assert_eq!(
+            format!("{}", MESSAGE_KEY),
+            format!("Key::Message({}-{}-7)", HEX_STRING, TOPIC_HEX_STRING)
+        );
     }
 
     #[test]
@@ -1399,6 +1524,15 @@ mod tests {
             .unwrap_err()
             .to_string()
             .starts_with("block-effects-root-hash-key from string error: "));
+        assert!(Key::from_formatted_str(MESSAGE_TOPIC_PREFIX)
+            .unwrap_err()
+            .to_string()
+            .starts_with("message-key from string error: "));
+        let missing_index = format!("{}{}-{}", MESSAGE_PREFIX, HEX_STRING, TOPIC_HEX_STRING);
+        assert!(Key::from_formatted_str(&missing_index)
+            .unwrap_err()
+            .to_string()
+            .starts_with("message-key from string error: "));
 
         let invalid_prefix = "a-0000000000000000000000000000000000000000000000000000000000000000";
         assert_eq!(
@@ -1454,6 +1588,14 @@ mod tests {
                 r#"{{"DeployApprovalsRootHash":"deploy-approvals-root-hash-{}"}}"#,
                 BLOCK_HEIGHT
             ),
+            format!(
+                r#"{{"Message":"message-topic-{}-{}"}}"#,
+                HEX_STRING, TOPIC_HEX_STRING
+            ),
+            format!(
+                r#"{{"Message":"message-{}-{}-7"}}"#,
+                HEX_STRING, TOPIC_HEX_STRING
+            ),
         ];
 
         assert_eq!(
@@ -1509,5 +1651,7 @@ mod tests {
         round_trip(&Key::DeployApprovalsRootHash {
             block_height: BLOCK_HEIGHT,
         });
+        round_trip(&Key::message_topic(zeros, zeros));
+        round_trip(&Key::message(zeros, zeros, 0));
     }
 }
@@ -20,26 +20,36 @@
 
 #[cfg_attr(not(test), macro_use)]
 extern crate alloc;
+// Allows the paths emitted by the `bytesrepr` derive macros to resolve within this crate.
+extern crate self as casper_types;
 
 mod access_rights;
 pub mod account;
 pub mod api_error;
+#[cfg(any(feature = "arbitrary", test))]
+pub mod arbitrary;
+pub mod bech32;
 mod block_time;
 pub mod bytesrepr;
+#[cfg(any(feature = "std", test))]
+pub mod canonical_json;
 pub mod checksummed_hex;
 mod cl_type;
 mod cl_value;
+pub mod contract_messages;
 mod contract_wasm;
 pub mod contracts;
 pub mod crypto;
 mod deploy_info;
 mod era_id;
 mod execution_result;
+mod execution_result_v2;
 #[cfg(any(feature = "std", test))]
 pub mod file_utils;
 mod gas;
 #[cfg(any(feature = "testing", test))]
 pub mod gens;
+pub mod json_decimal;
 mod json_pretty_printer;
 mod key;
 mod motes;
@@ -54,6 +64,7 @@ mod tagged;
 #[cfg(any(feature = "testing", test))]
 pub mod testing;
 mod timestamp;
+pub mod transaction;
 mod transfer;
 mod transfer_result;
 mod uint;
@@ -66,7 +77,7 @@ pub use access_rights::{
 pub use api_error::ApiError;
 pub use block_time::{BlockTime, BLOCKTIME_SERIALIZED_LENGTH};
 pub use cl_type::{named_key_type, CLType, CLTyped};
-pub use cl_value::{CLTypeMismatch, CLValue, CLValueError};
+pub use cl_value::{CLTypeMismatch, CLValue, CLValueError, CLValueView};
 pub use contract_wasm::{ContractWasm, ContractWasmHash};
 #[doc(inline)]
 pub use contracts::{
@@ -79,6 +90,7 @@ pub use deploy_info::DeployInfo;
 pub use execution_result::{
     ExecutionEffect, ExecutionResult, OpKind, Operation, Transform, TransformEntry,
 };
+pub use execution_result_v2::{EmittedEvent, ExecutionFailure, ExecutionResultV2, FailureReason};
 pub use gas::Gas;
 pub use json_pretty_printer::json_pretty_print;
 #[doc(inline)]
@@ -89,16 +101,23 @@ pub use key::{
 pub use motes::Motes;
 pub use named_key::NamedKey;
 pub use phase::{Phase, PHASE_SERIALIZED_LENGTH};
-pub use protocol_version::{ProtocolVersion, VersionCheckResult};
+pub use protocol_version::{ProtocolVersion, ProtocolVersionRange, VersionCheckResult};
 #[doc(inline)]
 pub use runtime_args::{NamedArg, RuntimeArgs};
 pub use semver::{ParseSemVerError, SemVer, SEM_VER_SERIALIZED_LENGTH};
 pub use stored_value::{StoredValue, TypeMismatch as StoredValueTypeMismatch};
 pub use tagged::Tagged;
 pub use timestamp::{TimeDiff, Timestamp};
+#[doc(inline)]
+pub use transaction::{
+    InvalidTransactionV2, PricingMode, TransactionEntryPoint, TransactionInvocationTarget,
+    TransactionTarget, TransactionV2, TransactionV2Approval, TransactionV2Body,
+    TransactionV2Builder, TransactionV2BuilderError, TransactionV2Hash, TransactionV2Header,
+    TRANSACTION_V2_HASH_LENGTH,
+};
 pub use transfer::{
-    DeployHash, FromStrError as TransferFromStrError, Transfer, TransferAddr, DEPLOY_HASH_LENGTH,
-    TRANSFER_ADDR_LENGTH,
+    DeployHash, FromStrError as TransferFromStrError, Transfer, TransferAddr, TransferV2,
+    DEPLOY_HASH_LENGTH, TRANSFER_ADDR_LENGTH, TRANSFER_MEMO_MAX_LENGTH,
 };
 pub use transfer_result::{TransferResult, TransferredTo};
 pub use uref::{
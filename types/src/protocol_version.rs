@@ -1,4 +1,4 @@
-use alloc::{format, string::String, vec::Vec};
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
 use core::{convert::TryFrom, fmt, str::FromStr};
 
 #[cfg(feature = "datasize")]
@@ -46,6 +46,47 @@ impl VersionCheckResult {
     }
 }
 
+/// An inclusive range of [`ProtocolVersion`]s.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+pub struct ProtocolVersionRange {
+    min: ProtocolVersion,
+    max: ProtocolVersion,
+}
+
+impl ProtocolVersionRange {
+    /// Constructs a new range containing all versions from `min` to `max`, both inclusive.
+    pub const fn new(min: ProtocolVersion, max: ProtocolVersion) -> Self {
+        ProtocolVersionRange { min, max }
+    }
+
+    /// Constructs a new range containing all versions up to and including `max`.
+    pub const fn at_most(max: ProtocolVersion) -> Self {
+        Self::new(ProtocolVersion::from_parts(0, 0, 0), max)
+    }
+
+    /// Returns the lowest version in the range.
+    pub fn min(&self) -> ProtocolVersion {
+        self.min
+    }
+
+    /// Returns the highest version in the range.
+    pub fn max(&self) -> ProtocolVersion {
+        self.max
+    }
+
+    /// Returns `true` if `version` lies within the range.
+    pub fn contains(&self, version: &ProtocolVersion) -> bool {
+        self.min <= *version && *version <= self.max
+    }
+}
+
+impl fmt::Display for ProtocolVersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.min, self.max)
+    }
+}
+
 impl ProtocolVersion {
     /// Version 1.0.0.
     pub const V1_0_0: ProtocolVersion = ProtocolVersion(SemVer {
@@ -115,7 +156,36 @@ impl ProtocolVersion {
     ///
     /// Two protocol versions with different major version are considered to be incompatible.
     pub fn is_compatible_with(&self, version: &ProtocolVersion) -> bool {
-        self.0.major == version.0.major
+        self.compatibility_range().contains(version)
+    }
+
+    /// Returns the range of all protocol versions compatible with this one, i.e. all versions
+    /// sharing its major version.
+    pub fn compatibility_range(&self) -> ProtocolVersionRange {
+        ProtocolVersionRange::new(
+            ProtocolVersion::from_parts(self.0.major, 0, 0),
+            ProtocolVersion::from_parts(self.0.major, u32::MAX, u32::MAX),
+        )
+    }
+
+    /// Returns `true` if this version is strictly newer than `previous`, i.e. it could replace
+    /// `previous` via an upgrade.
+    pub fn is_upgrade_of(&self, previous: &ProtocolVersion) -> bool {
+        *self > *previous
+    }
+
+    /// Returns an iterator over the given `candidates` which are upgrades of this version,
+    /// deduplicated and in ascending order.
+    pub fn ordered_upgrades<I>(&self, candidates: I) -> impl Iterator<Item = ProtocolVersion>
+    where
+        I: IntoIterator<Item = ProtocolVersion>,
+    {
+        let current = *self;
+        candidates
+            .into_iter()
+            .filter(move |candidate| candidate.is_upgrade_of(&current))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
     }
 }
 
@@ -524,6 +594,59 @@ mod tests {
         assert!(current.is_compatible_with(&other));
     }
 
+    #[test]
+    fn should_have_compatibility_range_spanning_major_version() {
+        let current = ProtocolVersion::from_parts(1, 2, 3);
+        let range = current.compatibility_range();
+        assert_eq!(range.min(), ProtocolVersion::from_parts(1, 0, 0));
+        assert_eq!(
+            range.max(),
+            ProtocolVersion::from_parts(1, u32::MAX, u32::MAX)
+        );
+        assert!(range.contains(&current));
+        assert!(!range.contains(&ProtocolVersion::from_parts(0, 9, 9)));
+        assert!(!range.contains(&ProtocolVersion::from_parts(2, 0, 0)));
+    }
+
+    #[test]
+    fn should_contain_bounds_of_range() {
+        let range = ProtocolVersionRange::at_most(ProtocolVersion::from_parts(1, 4, 0));
+        assert!(range.contains(&ProtocolVersion::from_parts(0, 0, 0)));
+        assert!(range.contains(&ProtocolVersion::from_parts(1, 4, 0)));
+        assert!(!range.contains(&ProtocolVersion::from_parts(1, 4, 1)));
+    }
+
+    #[test]
+    fn should_only_be_upgrade_of_older_version() {
+        let current = ProtocolVersion::from_parts(1, 2, 3);
+        assert!(current.is_upgrade_of(&ProtocolVersion::from_parts(1, 2, 2)));
+        assert!(current.is_upgrade_of(&ProtocolVersion::from_parts(0, 9, 9)));
+        assert!(!current.is_upgrade_of(&current));
+        assert!(!current.is_upgrade_of(&ProtocolVersion::from_parts(1, 3, 0)));
+    }
+
+    #[test]
+    fn should_iterate_upgrades_in_order() {
+        let current = ProtocolVersion::from_parts(1, 2, 0);
+        let candidates = vec![
+            ProtocolVersion::from_parts(2, 0, 0),
+            ProtocolVersion::from_parts(1, 0, 0),
+            ProtocolVersion::from_parts(1, 3, 0),
+            ProtocolVersion::from_parts(1, 2, 0),
+            ProtocolVersion::from_parts(1, 2, 1),
+            ProtocolVersion::from_parts(1, 3, 0),
+        ];
+        let upgrades: Vec<_> = current.ordered_upgrades(candidates).collect();
+        assert_eq!(
+            upgrades,
+            vec![
+                ProtocolVersion::from_parts(1, 2, 1),
+                ProtocolVersion::from_parts(1, 3, 0),
+                ProtocolVersion::from_parts(2, 0, 0),
+            ]
+        );
+    }
+
     #[test]
     fn should_serialize_to_json_properly() {
         let protocol_version = ProtocolVersion::from_parts(1, 1, 1);
@@ -15,9 +15,10 @@ use serde_bytes::ByteBuf;
 use crate::{
     account::Account,
     bytesrepr::{self, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    contract_messages::{MessageChecksum, MessageTopicSummary},
     contracts::ContractPackage,
     system::auction::{Bid, EraInfo, UnbondingPurse, WithdrawPurse},
-    CLValue, Contract, ContractWasm, DeployInfo, Transfer,
+    CLValue, Contract, ContractWasm, DeployInfo, Transfer, TransferV2,
 };
 pub use type_mismatch::TypeMismatch;
 
@@ -35,6 +36,9 @@ enum Tag {
     Bid = 8,
     Withdraw = 9,
     Unbonding = 10,
+    MessageTopic = 11,
+    Message = 12,
+    TransferV2 = 13,
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -64,6 +68,12 @@ pub enum StoredValue {
     Withdraw(Vec<WithdrawPurse>),
     /// Variant that stores unbonding information.
     Unbonding(Vec<UnbondingPurse>),
+    /// Variant that stores the summary of a message topic.
+    MessageTopic(MessageTopicSummary),
+    /// Variant that stores the checksum of a message emitted by a contract.
+    Message(MessageChecksum),
+    /// Variant that stores [`TransferV2`].
+    TransferV2(TransferV2),
 }
 
 impl StoredValue {
@@ -147,6 +157,30 @@ impl StoredValue {
         }
     }
 
+    /// Returns a wrapped [`MessageTopicSummary`] if this is a `MessageTopic` variant.
+    pub fn as_message_topic_summary(&self) -> Option<&MessageTopicSummary> {
+        match self {
+            StoredValue::MessageTopic(summary) => Some(summary),
+            _ => None,
+        }
+    }
+
+    /// Returns a wrapped [`MessageChecksum`] if this is a `Message` variant.
+    pub fn as_message_checksum(&self) -> Option<&MessageChecksum> {
+        match self {
+            StoredValue::Message(checksum) => Some(checksum),
+            _ => None,
+        }
+    }
+
+    /// Returns a wrapped [`TransferV2`] if this is a `TransferV2` variant.
+    pub fn as_transfer_v2(&self) -> Option<&TransferV2> {
+        match self {
+            StoredValue::TransferV2(transfer) => Some(transfer),
+            _ => None,
+        }
+    }
+
     /// Returns the type name of the [`StoredValue`] enum variant.
     ///
     /// For [`CLValue`] variants it will return the name of the [`CLType`](crate::cl_type::CLType)
@@ -163,6 +197,9 @@ impl StoredValue {
             StoredValue::Bid(_) => "Bid".to_string(),
             StoredValue::Withdraw(_) => "Withdraw".to_string(),
             StoredValue::Unbonding(_) => "Unbonding".to_string(),
+            StoredValue::MessageTopic(_) => "MessageTopic".to_string(),
+            StoredValue::Message(_) => "Message".to_string(),
+            StoredValue::TransferV2(_) => "TransferV2".to_string(),
         }
     }
 
@@ -179,6 +216,9 @@ impl StoredValue {
             StoredValue::Bid(_) => Tag::Bid,
             StoredValue::Withdraw(_) => Tag::Withdraw,
             StoredValue::Unbonding(_) => Tag::Unbonding,
+            StoredValue::MessageTopic(_) => Tag::MessageTopic,
+            StoredValue::Message(_) => Tag::Message,
+            StoredValue::TransferV2(_) => Tag::TransferV2,
         }
     }
 }
@@ -213,6 +253,16 @@ impl From<Bid> for StoredValue {
         StoredValue::Bid(Box::new(bid))
     }
 }
+impl From<MessageTopicSummary> for StoredValue {
+    fn from(summary: MessageTopicSummary) -> StoredValue {
+        StoredValue::MessageTopic(summary)
+    }
+}
+impl From<MessageChecksum> for StoredValue {
+    fn from(checksum: MessageChecksum) -> StoredValue {
+        StoredValue::Message(checksum)
+    }
+}
 
 impl TryFrom<StoredValue> for CLValue {
     type Error = TypeMismatch;
@@ -295,6 +345,20 @@ impl TryFrom<StoredValue> for Transfer {
     }
 }
 
+impl TryFrom<StoredValue> for TransferV2 {
+    type Error = TypeMismatch;
+
+    fn try_from(value: StoredValue) -> Result<Self, Self::Error> {
+        match value {
+            StoredValue::TransferV2(transfer) => Ok(transfer),
+            _ => Err(TypeMismatch::new(
+                "TransferV2".to_string(),
+                value.type_name(),
+            )),
+        }
+    }
+}
+
 impl TryFrom<StoredValue> for DeployInfo {
     type Error = TypeMismatch;
 
@@ -320,6 +384,31 @@ impl TryFrom<StoredValue> for EraInfo {
     }
 }
 
+impl TryFrom<StoredValue> for MessageTopicSummary {
+    type Error = TypeMismatch;
+
+    fn try_from(value: StoredValue) -> Result<Self, Self::Error> {
+        match value {
+            StoredValue::MessageTopic(summary) => Ok(summary),
+            _ => Err(TypeMismatch::new(
+                "MessageTopic".to_string(),
+                value.type_name(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<StoredValue> for MessageChecksum {
+    type Error = TypeMismatch;
+
+    fn try_from(value: StoredValue) -> Result<Self, Self::Error> {
+        match value {
+            StoredValue::Message(checksum) => Ok(checksum),
+            _ => Err(TypeMismatch::new("Message".to_string(), value.type_name())),
+        }
+    }
+}
+
 impl ToBytes for StoredValue {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut result = bytesrepr::allocate_buffer(self)?;
@@ -341,6 +430,9 @@ impl ToBytes for StoredValue {
             StoredValue::Unbonding(unbonding_purses) => {
                 (Tag::Unbonding, unbonding_purses.to_bytes()?)
             }
+            StoredValue::MessageTopic(summary) => (Tag::MessageTopic, summary.to_bytes()?),
+            StoredValue::Message(checksum) => (Tag::Message, checksum.to_bytes()?),
+            StoredValue::TransferV2(transfer) => (Tag::TransferV2, transfer.to_bytes()?),
         };
         result.push(tag as u8);
         result.append(&mut serialized_data);
@@ -363,6 +455,9 @@ impl ToBytes for StoredValue {
                 StoredValue::Bid(bid) => bid.serialized_length(),
                 StoredValue::Withdraw(withdraw_purses) => withdraw_purses.serialized_length(),
                 StoredValue::Unbonding(unbonding_purses) => unbonding_purses.serialized_length(),
+                StoredValue::MessageTopic(summary) => summary.serialized_length(),
+                StoredValue::Message(checksum) => checksum.serialized_length(),
+                StoredValue::TransferV2(transfer) => transfer.serialized_length(),
             }
     }
 
@@ -382,6 +477,9 @@ impl ToBytes for StoredValue {
             StoredValue::Bid(bid) => bid.write_bytes(writer)?,
             StoredValue::Withdraw(unbonding_purses) => unbonding_purses.write_bytes(writer)?,
             StoredValue::Unbonding(unbonding_purses) => unbonding_purses.write_bytes(writer)?,
+            StoredValue::MessageTopic(summary) => summary.write_bytes(writer)?,
+            StoredValue::Message(checksum) => checksum.write_bytes(writer)?,
+            StoredValue::TransferV2(transfer) => transfer.write_bytes(writer)?,
         };
         Ok(())
     }
@@ -425,6 +523,12 @@ impl FromBytes for StoredValue {
                     (StoredValue::Unbonding(unbonding_purses), remainder)
                 })
             }
+            tag if tag == Tag::MessageTopic as u8 => MessageTopicSummary::from_bytes(remainder)
+                .map(|(summary, remainder)| (StoredValue::MessageTopic(summary), remainder)),
+            tag if tag == Tag::Message as u8 => MessageChecksum::from_bytes(remainder)
+                .map(|(checksum, remainder)| (StoredValue::Message(checksum), remainder)),
+            tag if tag == Tag::TransferV2 as u8 => TransferV2::from_bytes(remainder)
+                .map(|(transfer, remainder)| (StoredValue::TransferV2(transfer), remainder)),
             _ => Err(bytesrepr::Error::Formatting),
         }
     }
@@ -4,6 +4,8 @@ pub const ARG_PURSE: &str = "purse";
 pub const ARG_AMOUNT: &str = "amount";
 /// Named constant for `id`.
 pub const ARG_ID: &str = "id";
+/// Named constant for `memo`.
+pub const ARG_MEMO: &str = "memo";
 /// Named constant for `to`.
 pub const ARG_TO: &str = "to";
 /// Named constant for `source`.
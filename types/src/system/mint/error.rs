@@ -148,6 +148,13 @@ pub enum Error {
     /// assert_eq!(21, Error::UnapprovedSpendingAmount as u8);
     UnapprovedSpendingAmount = 21,
 
+    /// The memo attached to a transfer exceeds the maximum allowed length.
+    /// ```
+    /// # use casper_types::system::mint::Error;
+    /// assert_eq!(22, Error::MemoTooLong as u8);
+    /// ```
+    MemoTooLong = 22,
+
     #[cfg(test)]
     #[doc(hidden)]
     Sentinel,
@@ -200,6 +207,7 @@ impl TryFrom<u8> for Error {
             d if d == Error::GasLimit as u8 => Ok(Error::GasLimit),
             d if d == Error::InvalidContext as u8 => Ok(Error::InvalidContext),
             d if d == Error::UnapprovedSpendingAmount as u8 => Ok(Error::UnapprovedSpendingAmount),
+            d if d == Error::MemoTooLong as u8 => Ok(Error::MemoTooLong),
             _ => Err(TryFromU8ForError(())),
         }
     }
@@ -257,6 +265,7 @@ impl Display for Error {
             Error::GasLimit => formatter.write_str("GasLimit"),
             Error::InvalidContext => formatter.write_str("Invalid context"),
             Error::UnapprovedSpendingAmount => formatter.write_str("Unapproved spending amount"),
+            Error::MemoTooLong => formatter.write_str("Transfer memo too long"),
             #[cfg(test)]
             Error::Sentinel => formatter.write_str("Sentinel error"),
         }
@@ -1,6 +1,6 @@
-//! An RNG for testing purposes.
+//! An RNG and a pinnable clock for testing purposes.
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     cmp, env,
     fmt::{self, Debug, Display, Formatter},
     thread,
@@ -9,10 +9,16 @@ use std::{
 use rand::{self, CryptoRng, Error, Rng, RngCore, SeedableRng};
 use rand_pcg::Pcg64Mcg;
 
+use crate::{TimeDiff, Timestamp};
+
 thread_local! {
     static THIS_THREAD_HAS_RNG: RefCell<bool> = RefCell::new(false);
+    static PINNED_NOW: Cell<Option<Timestamp>> = Cell::new(None);
 }
 
+/// The env var holding the seed of `TestRng`s, as printed when a test fails.
+const CASPER_TEST_SEED: &str = "CASPER_TEST_SEED";
+/// The deprecated name of `CASPER_TEST_SEED`, still read if the latter is not set.
 const CL_TEST_SEED: &str = "CL_TEST_SEED";
 
 type Seed = <Pcg64Mcg as SeedableRng>::Seed; // [u8; 16]
@@ -27,8 +33,8 @@ pub struct TestRng {
 }
 
 impl TestRng {
-    /// Constructs a new `TestRng` using a seed generated from the env var `CL_TEST_SEED` if set or
-    /// from cryptographically secure random data if not.
+    /// Constructs a new `TestRng` using a seed generated from the env var `CASPER_TEST_SEED` (or the
+    /// deprecated `CL_TEST_SEED`) if set or from cryptographically secure random data if not.
     ///
     /// Note that `new()` or `default()` should only be called once per test.  If a test needs to
     /// spawn multiple threads each with their own `TestRng`, then use `new()` to create a single,
@@ -42,7 +48,7 @@ impl TestRng {
         Self::set_flag_or_panic();
 
         let mut seed = Seed::default();
-        match env::var(CL_TEST_SEED) {
+        match env::var(CASPER_TEST_SEED).or_else(|_| env::var(CL_TEST_SEED)) {
             Ok(seed_as_hex) => {
                 base16::decode_slice(&seed_as_hex, &mut seed).unwrap_or_else(|error| {
                     THIS_THREAD_HAS_RNG.with(|flag| {
@@ -75,6 +81,20 @@ impl TestRng {
         TestRng { seed, rng }
     }
 
+    /// Constructs a new `TestRng` using `seed` without claiming this thread's single `TestRng`.
+    ///
+    /// This is intended for proptest strategies, which need many short-lived RNGs on the same
+    /// thread and which record the seeds of failing cases themselves.
+    pub fn for_strategy(seed: Seed) -> Self {
+        let rng = Pcg64Mcg::from_seed(seed);
+        TestRng { seed, rng }
+    }
+
+    /// Returns the seed of this `TestRng`, which can be passed to `from_seed` to replay it.
+    pub fn seed(&self) -> Seed {
+        self.seed
+    }
+
     fn set_flag_or_panic() {
         THIS_THREAD_HAS_RNG.with(|flag| {
             if *flag.borrow() {
@@ -112,7 +132,7 @@ impl Drop for TestRng {
         if thread::panicking() {
             let line_1 = format!("Thread: {}", thread::current().name().unwrap_or("unnamed"));
             let line_2 = "To reproduce failure, try running with env var:";
-            let line_3 = format!("{}={}", CL_TEST_SEED, base16::encode_lower(&self.seed));
+            let line_3 = format!("{}={}", CASPER_TEST_SEED, base16::encode_lower(&self.seed));
             let max_length = cmp::max(line_1.len(), line_2.len());
             let border = "=".repeat(max_length);
             println!(
@@ -151,6 +171,58 @@ impl RngCore for TestRng {
 
 impl CryptoRng for TestRng {}
 
+/// A clock pinning [`Timestamp::now`] on the current thread for as long as it is alive.
+///
+/// While a `PinnedClock` exists, `Timestamp::now()` called on the thread which created it returns
+/// the pinned timestamp rather than the wall-clock time, until the clock is explicitly set or
+/// advanced.  This makes time-dependent tests, e.g. of TTL expiry or era timing, reproducible.
+///
+/// Other threads, e.g. those of a multi-threaded runtime or of blocking tasks, are unaffected.
+/// Clocks can be nested, and dropping one restores the time pinned by the enclosing one, if any.
+pub struct PinnedClock {
+    previous: Option<Timestamp>,
+}
+
+impl PinnedClock {
+    /// Pins `Timestamp::now()` on the current thread to `now`.
+    pub fn pin(now: Timestamp) -> Self {
+        let previous = PINNED_NOW.with(|pinned| pinned.replace(Some(now)));
+        PinnedClock { previous }
+    }
+
+    /// Returns the pinned timestamp.
+    pub fn now(&self) -> Timestamp {
+        pinned_now().expect("pinned clock should be set while alive")
+    }
+
+    /// Sets the pinned timestamp to `now`.
+    pub fn set(&self, now: Timestamp) {
+        PINNED_NOW.with(|pinned| pinned.set(Some(now)));
+    }
+
+    /// Advances the pinned timestamp by `diff`.
+    pub fn advance(&self, diff: TimeDiff) {
+        self.set(self.now() + diff);
+    }
+}
+
+impl Debug for PinnedClock {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "PinnedClock({:?})", pinned_now())
+    }
+}
+
+impl Drop for PinnedClock {
+    fn drop(&mut self) {
+        PINNED_NOW.with(|pinned| pinned.set(self.previous));
+    }
+}
+
+/// Returns the timestamp pinned on the current thread by a [`PinnedClock`], if any.
+pub(crate) fn pinned_now() -> Option<Timestamp> {
+    PINNED_NOW.with(Cell::get)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,4 +234,28 @@ mod tests {
         let seed = [1; 16];
         let _test_rng2 = TestRng::from_seed(seed);
     }
+
+    #[test]
+    fn pinned_clock_should_pin_now_until_dropped() {
+        let pinned = Timestamp::from(1_000);
+        let clock = PinnedClock::pin(pinned);
+        assert_eq!(Timestamp::now(), pinned);
+        assert_eq!(Timestamp::now().elapsed(), TimeDiff::from(0));
+
+        clock.advance(TimeDiff::from(500));
+        assert_eq!(Timestamp::now(), Timestamp::from(1_500));
+
+        {
+            let nested_clock = PinnedClock::pin(Timestamp::from(7));
+            assert_eq!(nested_clock.now(), Timestamp::from(7));
+            assert_eq!(Timestamp::now(), Timestamp::from(7));
+        }
+        assert_eq!(Timestamp::now(), Timestamp::from(1_500));
+
+        let other_thread_now = thread::spawn(Timestamp::now).join().unwrap();
+        assert!(other_thread_now > Timestamp::from(1_500));
+
+        drop(clock);
+        assert!(Timestamp::now() > Timestamp::from(1_500));
+    }
 }
@@ -42,7 +42,13 @@ pub struct Timestamp(u64);
 impl Timestamp {
     #[cfg(any(feature = "std", test))]
     /// Returns the timestamp of the current moment.
+    ///
+    /// In tests, the current moment can be pinned via `testing::PinnedClock`.
     pub fn now() -> Self {
+        #[cfg(any(feature = "testing", test))]
+        if let Some(now) = crate::testing::pinned_now() {
+            return now;
+        }
         let millis = SystemTime::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
         Timestamp(millis)
     }
@@ -0,0 +1,19 @@
+//! Contains the versioned transaction format succeeding deploys.
+
+mod approval;
+mod error;
+mod pricing_mode;
+mod transaction_target;
+mod transaction_v2;
+mod transaction_v2_builder;
+mod transaction_v2_hash;
+
+pub use approval::TransactionV2Approval;
+pub use error::{InvalidTransactionV2, TransactionV2BuilderError};
+pub use pricing_mode::PricingMode;
+pub use transaction_target::{
+    TransactionEntryPoint, TransactionInvocationTarget, TransactionTarget,
+};
+pub use transaction_v2::{TransactionV2, TransactionV2Body, TransactionV2Header};
+pub use transaction_v2_builder::TransactionV2Builder;
+pub use transaction_v2_hash::{TransactionV2Hash, TRANSACTION_V2_HASH_LENGTH};
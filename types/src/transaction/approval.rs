@@ -0,0 +1,84 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "datasize")]
+use datasize::DataSize;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::TransactionV2Hash;
+use crate::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    crypto::{self, SigningContext},
+    PublicKey, SecretKey, Signature,
+};
+
+/// A signature of a [`TransactionV2`](super::TransactionV2)'s hash, with the signer's public key.
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct TransactionV2Approval {
+    signer: PublicKey,
+    signature: Signature,
+}
+
+impl TransactionV2Approval {
+    /// Creates an approval of the given transaction hash using the given secret key.
+    ///
+    /// Unlike deploy approvals, these are always signed in the transaction approval context, as no
+    /// transactions in this format predate signing contexts.
+    pub fn create(hash: &TransactionV2Hash, secret_key: &SecretKey) -> Self {
+        let signer = PublicKey::from(secret_key);
+        let signature = crypto::sign_with_context(
+            hash,
+            SigningContext::TransactionApproval,
+            secret_key,
+            &signer,
+        );
+        TransactionV2Approval { signer, signature }
+    }
+
+    /// Returns the public key of the approval's signer.
+    pub fn signer(&self) -> &PublicKey {
+        &self.signer
+    }
+
+    /// Returns the approval's signature.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+}
+
+impl Display for TransactionV2Approval {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "approval({})", self.signer)
+    }
+}
+
+impl ToBytes for TransactionV2Approval {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.signer.serialized_length() + self.signature.serialized_length()
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        self.signer.write_bytes(writer)?;
+        self.signature.write_bytes(writer)
+    }
+}
+
+impl FromBytes for TransactionV2Approval {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (signer, remainder) = PublicKey::from_bytes(bytes)?;
+        let (signature, remainder) = Signature::from_bytes(remainder)?;
+        let approval = TransactionV2Approval { signer, signature };
+        Ok((approval, remainder))
+    }
+}
@@ -0,0 +1,101 @@
+use alloc::string::String;
+use core::fmt::Debug;
+#[cfg(not(any(feature = "std", test)))]
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "datasize")]
+use datasize::DataSize;
+use serde::Serialize;
+#[cfg(any(feature = "std", test))]
+use thiserror::Error;
+
+use super::TransactionEntryPoint;
+
+/// The way in which a [`TransactionV2`](super::TransactionV2) failed verification.
+#[derive(Clone, Eq, PartialEq, Serialize, Debug)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(any(feature = "std", test), derive(Error))]
+#[non_exhaustive]
+pub enum InvalidTransactionV2 {
+    /// The provided body hash does not match the actual hash of the body.
+    #[cfg_attr(
+        any(feature = "std", test),
+        error("the provided body hash does not match the actual hash of the body")
+    )]
+    InvalidBodyHash,
+
+    /// The provided transaction hash does not match the actual hash of the transaction.
+    #[cfg_attr(
+        any(feature = "std", test),
+        error("the provided hash does not match the actual hash of the transaction")
+    )]
+    InvalidTransactionHash,
+
+    /// The transaction has no approvals.
+    #[cfg_attr(any(feature = "std", test), error("the transaction has no approvals"))]
+    EmptyApprovals,
+
+    /// Invalid approval.
+    #[cfg_attr(
+        any(feature = "std", test),
+        error("the approval at index {index} is invalid: {error_msg}")
+    )]
+    InvalidApproval {
+        /// The index of the approval at fault.
+        index: usize,
+        /// The approval verification error.
+        error_msg: String,
+    },
+
+    /// The entry point cannot be invoked on the transaction's target.
+    #[cfg_attr(
+        any(feature = "std", test),
+        error("entry point {0} cannot be invoked on the transaction's target")
+    )]
+    InvalidEntryPoint(TransactionEntryPoint),
+}
+
+#[cfg(not(any(feature = "std", test)))]
+impl Display for InvalidTransactionV2 {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, formatter)
+    }
+}
+
+/// The way in which building a [`TransactionV2`](super::TransactionV2) via a
+/// [`TransactionV2Builder`](super::TransactionV2Builder) failed.
+#[derive(Clone, Eq, PartialEq, Serialize, Debug)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(any(feature = "std", test), derive(Error))]
+#[non_exhaustive]
+pub enum TransactionV2BuilderError {
+    /// No chain name was provided.
+    #[cfg_attr(any(feature = "std", test), error("missing chain name"))]
+    MissingChainName,
+
+    /// No payment amount was provided.
+    #[cfg_attr(any(feature = "std", test), error("missing payment amount"))]
+    MissingPaymentAmount,
+
+    /// No timestamp was provided, and the current time is unavailable without the "std" feature.
+    #[cfg_attr(any(feature = "std", test), error("missing timestamp"))]
+    MissingTimestamp,
+
+    /// No secret key was provided to sign the transaction.
+    #[cfg_attr(any(feature = "std", test), error("missing secret key"))]
+    MissingSecretKey,
+
+    /// The entry point cannot be invoked on the transaction's target.
+    #[cfg_attr(
+        any(feature = "std", test),
+        error("entry point {0} cannot be invoked on the transaction's target")
+    )]
+    InvalidEntryPoint(TransactionEntryPoint),
+}
+
+#[cfg(not(any(feature = "std", test)))]
+impl Display for TransactionV2BuilderError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, formatter)
+    }
+}
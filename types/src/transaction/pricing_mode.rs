@@ -0,0 +1,109 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "datasize")]
+use datasize::DataSize;
+#[cfg(any(feature = "testing", test))]
+use rand::Rng;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::bytesrepr::{self, FromBytes, ToBytes, U8_SERIALIZED_LENGTH};
+#[cfg(any(feature = "testing", test))]
+use crate::testing::TestRng;
+
+const CLASSIC_TAG: u8 = 0;
+
+/// How the execution of a [`TransactionV2`](super::TransactionV2) is paid for.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub enum PricingMode {
+    /// The standard payment code is executed with the given amount, as for deploys without custom
+    /// payment code.
+    Classic {
+        /// The amount of motes to pay for execution.
+        payment_amount: u64,
+        /// The price in motes per unit of gas.
+        gas_price: u64,
+    },
+}
+
+impl PricingMode {
+    /// Returns a random `PricingMode`.
+    #[cfg(any(feature = "testing", test))]
+    pub fn random(rng: &mut TestRng) -> Self {
+        PricingMode::Classic {
+            payment_amount: rng.gen(),
+            gas_price: rng.gen_range(1..10),
+        }
+    }
+}
+
+impl Display for PricingMode {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            PricingMode::Classic {
+                payment_amount,
+                gas_price,
+            } => write!(
+                formatter,
+                "classic(payment amount {}, gas price {})",
+                payment_amount, gas_price
+            ),
+        }
+    }
+}
+
+impl ToBytes for PricingMode {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+            + match self {
+                PricingMode::Classic {
+                    payment_amount,
+                    gas_price,
+                } => payment_amount.serialized_length() + gas_price.serialized_length(),
+            }
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        match self {
+            PricingMode::Classic {
+                payment_amount,
+                gas_price,
+            } => {
+                writer.push(CLASSIC_TAG);
+                payment_amount.write_bytes(writer)?;
+                gas_price.write_bytes(writer)
+            }
+        }
+    }
+}
+
+impl FromBytes for PricingMode {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, remainder) = u8::from_bytes(bytes)?;
+        match tag {
+            CLASSIC_TAG => {
+                let (payment_amount, remainder) = u64::from_bytes(remainder)?;
+                let (gas_price, remainder) = u64::from_bytes(remainder)?;
+                Ok((
+                    PricingMode::Classic {
+                        payment_amount,
+                        gas_price,
+                    },
+                    remainder,
+                ))
+            }
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
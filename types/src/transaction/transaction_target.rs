@@ -0,0 +1,214 @@
+use alloc::string::String;
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "datasize")]
+use datasize::DataSize;
+#[cfg(any(feature = "testing", test))]
+use rand::Rng;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "testing", test))]
+use crate::testing::TestRng;
+use crate::{
+    bytesrepr::{Bytes, FromBytes, ToBytes},
+    ContractHash, ContractPackageHash, ContractVersion,
+};
+
+const NATIVE_TAG: u8 = 0;
+const STORED_TAG: u8 = 1;
+const SESSION_TAG: u8 = 2;
+
+const BY_HASH_TAG: u8 = 0;
+const BY_NAME_TAG: u8 = 1;
+const BY_PACKAGE_HASH_TAG: u8 = 2;
+const BY_PACKAGE_NAME_TAG: u8 = 3;
+
+const CALL_TAG: u8 = 0;
+const TRANSFER_TAG: u8 = 1;
+const CUSTOM_TAG: u8 = 2;
+
+/// The code executed by a [`TransactionV2`](super::TransactionV2).
+#[derive(
+    Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug, FromBytes, ToBytes,
+)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub enum TransactionTarget {
+    /// A native operation of the system, executed without Wasm, e.g. a transfer.
+    #[bytesrepr(tag = NATIVE_TAG)]
+    Native,
+    /// A contract or contract package stored in global state.
+    #[bytesrepr(tag = STORED_TAG)]
+    Stored {
+        /// The stored contract or contract package to invoke.
+        id: TransactionInvocationTarget,
+    },
+    /// Wasm session code passed as part of the transaction.
+    #[bytesrepr(tag = SESSION_TAG)]
+    Session {
+        /// The raw Wasm module bytes.
+        #[cfg_attr(
+            feature = "json-schema",
+            schemars(with = "String", description = "Hex-encoded raw Wasm bytes.")
+        )]
+        module_bytes: Bytes,
+    },
+}
+
+impl TransactionTarget {
+    /// Returns a random `TransactionTarget`.
+    #[cfg(any(feature = "testing", test))]
+    pub fn random(rng: &mut TestRng) -> Self {
+        match rng.gen_range(0..3) {
+            0 => TransactionTarget::Native,
+            1 => TransactionTarget::Stored {
+                id: TransactionInvocationTarget::random(rng),
+            },
+            _ => {
+                let mut module_bytes = vec![0; rng.gen_range(0..100)];
+                rng.fill(module_bytes.as_mut_slice());
+                TransactionTarget::Session {
+                    module_bytes: module_bytes.into(),
+                }
+            }
+        }
+    }
+}
+
+impl Display for TransactionTarget {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            TransactionTarget::Native => write!(formatter, "native"),
+            TransactionTarget::Stored { id } => write!(formatter, "stored({})", id),
+            TransactionTarget::Session { module_bytes } => {
+                write!(formatter, "session({} module bytes)", module_bytes.len())
+            }
+        }
+    }
+}
+
+/// Identifies the stored contract or contract package invoked by a
+/// [`TransactionV2`](super::TransactionV2).
+#[derive(
+    Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug, FromBytes, ToBytes,
+)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub enum TransactionInvocationTarget {
+    /// A contract identified by its hash.
+    #[bytesrepr(tag = BY_HASH_TAG)]
+    ByHash(ContractHash),
+    /// A contract identified by a named key in the initiator's account.
+    #[bytesrepr(tag = BY_NAME_TAG)]
+    ByName(String),
+    /// A contract package identified by its hash.
+    #[bytesrepr(tag = BY_PACKAGE_HASH_TAG)]
+    ByPackageHash {
+        /// The hash of the contract package.
+        hash: ContractPackageHash,
+        /// The version of the contract to invoke, or the latest one if `None`.
+        version: Option<ContractVersion>,
+    },
+    /// A contract package identified by a named key in the initiator's account.
+    #[bytesrepr(tag = BY_PACKAGE_NAME_TAG)]
+    ByPackageName {
+        /// The named key under which the contract package is stored.
+        name: String,
+        /// The version of the contract to invoke, or the latest one if `None`.
+        version: Option<ContractVersion>,
+    },
+}
+
+impl TransactionInvocationTarget {
+    /// Returns a random `TransactionInvocationTarget`.
+    #[cfg(any(feature = "testing", test))]
+    pub fn random(rng: &mut TestRng) -> Self {
+        match rng.gen_range(0..4) {
+            0 => TransactionInvocationTarget::ByHash(ContractHash::new(rng.gen())),
+            1 => TransactionInvocationTarget::ByName(format!("contract-{}", rng.gen::<u16>())),
+            2 => TransactionInvocationTarget::ByPackageHash {
+                hash: ContractPackageHash::new(rng.gen()),
+                version: rng.gen(),
+            },
+            _ => TransactionInvocationTarget::ByPackageName {
+                name: format!("package-{}", rng.gen::<u16>()),
+                version: rng.gen(),
+            },
+        }
+    }
+}
+
+impl Display for TransactionInvocationTarget {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            TransactionInvocationTarget::ByHash(hash) => write!(formatter, "{}", hash),
+            TransactionInvocationTarget::ByName(name) => write!(formatter, "name {}", name),
+            TransactionInvocationTarget::ByPackageHash { hash, version } => {
+                write!(formatter, "{} version {:?}", hash, version)
+            }
+            TransactionInvocationTarget::ByPackageName { name, version } => {
+                write!(formatter, "package name {} version {:?}", name, version)
+            }
+        }
+    }
+}
+
+/// The entry point invoked by a [`TransactionV2`](super::TransactionV2).
+#[derive(
+    Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug, FromBytes, ToBytes,
+)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum TransactionEntryPoint {
+    /// The `call` export of session code.
+    #[bytesrepr(tag = CALL_TAG)]
+    Call,
+    /// The native transfer operation.
+    #[bytesrepr(tag = TRANSFER_TAG)]
+    Transfer,
+    /// The named entry point of a stored contract.
+    #[bytesrepr(tag = CUSTOM_TAG)]
+    Custom(String),
+}
+
+impl TransactionEntryPoint {
+    /// Returns a random `TransactionEntryPoint`.
+    #[cfg(any(feature = "testing", test))]
+    pub fn random(rng: &mut TestRng) -> Self {
+        match rng.gen_range(0..3) {
+            0 => TransactionEntryPoint::Call,
+            1 => TransactionEntryPoint::Transfer,
+            _ => TransactionEntryPoint::Custom(format!("entry_point_{}", rng.gen::<u16>())),
+        }
+    }
+
+    /// Returns whether this entry point can be invoked on the given target.
+    pub fn is_valid_for(&self, target: &TransactionTarget) -> bool {
+        matches!(
+            (target, self),
+            (TransactionTarget::Native, TransactionEntryPoint::Transfer)
+                | (
+                    TransactionTarget::Stored { .. },
+                    TransactionEntryPoint::Custom(_)
+                )
+                | (
+                    TransactionTarget::Session { .. },
+                    TransactionEntryPoint::Call
+                )
+        )
+    }
+}
+
+impl Display for TransactionEntryPoint {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            TransactionEntryPoint::Call => write!(formatter, "call"),
+            TransactionEntryPoint::Transfer => write!(formatter, "transfer"),
+            TransactionEntryPoint::Custom(name) => write!(formatter, "custom({})", name),
+        }
+    }
+}
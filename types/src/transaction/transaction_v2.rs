@@ -0,0 +1,519 @@
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
+
+#[cfg(feature = "datasize")]
+use datasize::DataSize;
+#[cfg(any(feature = "testing", test))]
+use rand::Rng;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    InvalidTransactionV2, PricingMode, TransactionEntryPoint, TransactionTarget,
+    TransactionV2Approval, TransactionV2Hash,
+};
+#[cfg(any(feature = "testing", test))]
+use crate::testing::TestRng;
+use crate::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    checksummed_hex,
+    crypto::{self, SigningContext},
+    PublicKey, RuntimeArgs, SecretKey, TimeDiff, Timestamp, BLAKE2B_DIGEST_LENGTH,
+};
+
+/// The header of a [`TransactionV2`], which is hashed to form the transaction hash.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct TransactionV2Header {
+    chain_name: String,
+    timestamp: Timestamp,
+    ttl: TimeDiff,
+    initiator: PublicKey,
+    pricing_mode: PricingMode,
+    #[serde(with = "hex_digest")]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(
+            with = "String",
+            description = "Hex-encoded hash of the transaction body."
+        )
+    )]
+    body_hash: [u8; BLAKE2B_DIGEST_LENGTH],
+}
+
+impl TransactionV2Header {
+    /// Returns the name of the chain the transaction is supposed to be executed on.
+    pub fn chain_name(&self) -> &str {
+        &self.chain_name
+    }
+
+    /// Returns the time at which the transaction was created.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns how long the transaction stays valid after its timestamp.
+    pub fn ttl(&self) -> TimeDiff {
+        self.ttl
+    }
+
+    /// Returns the public key of the account initiating the transaction.
+    pub fn initiator(&self) -> &PublicKey {
+        &self.initiator
+    }
+
+    /// Returns how the execution of the transaction is paid for.
+    pub fn pricing_mode(&self) -> &PricingMode {
+        &self.pricing_mode
+    }
+
+    /// Returns the hash of the transaction body.
+    pub fn body_hash(&self) -> &[u8; BLAKE2B_DIGEST_LENGTH] {
+        &self.body_hash
+    }
+
+    /// Returns the timestamp of when the transaction expires, i.e. `self.timestamp + self.ttl`.
+    pub fn expires(&self) -> Timestamp {
+        self.timestamp.saturating_add(self.ttl)
+    }
+
+    /// Returns whether the transaction has expired at the given time.
+    pub fn expired(&self, current_instant: Timestamp) -> bool {
+        self.expires() < current_instant
+    }
+}
+
+impl ToBytes for TransactionV2Header {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.chain_name.serialized_length()
+            + self.timestamp.serialized_length()
+            + self.ttl.serialized_length()
+            + self.initiator.serialized_length()
+            + self.pricing_mode.serialized_length()
+            + self.body_hash.serialized_length()
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        self.chain_name.write_bytes(writer)?;
+        self.timestamp.write_bytes(writer)?;
+        self.ttl.write_bytes(writer)?;
+        self.initiator.write_bytes(writer)?;
+        self.pricing_mode.write_bytes(writer)?;
+        self.body_hash.write_bytes(writer)
+    }
+}
+
+impl FromBytes for TransactionV2Header {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (chain_name, remainder) = String::from_bytes(bytes)?;
+        let (timestamp, remainder) = Timestamp::from_bytes(remainder)?;
+        let (ttl, remainder) = TimeDiff::from_bytes(remainder)?;
+        let (initiator, remainder) = PublicKey::from_bytes(remainder)?;
+        let (pricing_mode, remainder) = PricingMode::from_bytes(remainder)?;
+        let (body_hash, remainder) = <[u8; BLAKE2B_DIGEST_LENGTH]>::from_bytes(remainder)?;
+        let header = TransactionV2Header {
+            chain_name,
+            timestamp,
+            ttl,
+            initiator,
+            pricing_mode,
+            body_hash,
+        };
+        Ok((header, remainder))
+    }
+}
+
+/// The body of a [`TransactionV2`], i.e. what is executed and with which arguments.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct TransactionV2Body {
+    args: RuntimeArgs,
+    target: TransactionTarget,
+    entry_point: TransactionEntryPoint,
+}
+
+impl TransactionV2Body {
+    /// Constructs a new `TransactionV2Body`.
+    pub fn new(
+        args: RuntimeArgs,
+        target: TransactionTarget,
+        entry_point: TransactionEntryPoint,
+    ) -> Self {
+        TransactionV2Body {
+            args,
+            target,
+            entry_point,
+        }
+    }
+
+    /// Returns the runtime arguments passed to the entry point.
+    pub fn args(&self) -> &RuntimeArgs {
+        &self.args
+    }
+
+    /// Returns the code executed by the transaction.
+    pub fn target(&self) -> &TransactionTarget {
+        &self.target
+    }
+
+    /// Returns the entry point invoked by the transaction.
+    pub fn entry_point(&self) -> &TransactionEntryPoint {
+        &self.entry_point
+    }
+
+    /// Returns a random `TransactionV2Body` with an entry point valid for its target.
+    #[cfg(any(feature = "testing", test))]
+    pub fn random(rng: &mut TestRng) -> Self {
+        let target = TransactionTarget::random(rng);
+        let entry_point = match target {
+            TransactionTarget::Native => TransactionEntryPoint::Transfer,
+            TransactionTarget::Stored { .. } => {
+                TransactionEntryPoint::Custom(format!("entry_point_{}", rng.gen::<u16>()))
+            }
+            TransactionTarget::Session { .. } => TransactionEntryPoint::Call,
+        };
+        TransactionV2Body::new(RuntimeArgs::new(), target, entry_point)
+    }
+}
+
+impl ToBytes for TransactionV2Body {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.args.serialized_length()
+            + self.target.serialized_length()
+            + self.entry_point.serialized_length()
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        self.args.write_bytes(writer)?;
+        self.target.write_bytes(writer)?;
+        self.entry_point.write_bytes(writer)
+    }
+}
+
+impl FromBytes for TransactionV2Body {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (args, remainder) = RuntimeArgs::from_bytes(bytes)?;
+        let (target, remainder) = TransactionTarget::from_bytes(remainder)?;
+        let (entry_point, remainder) = TransactionEntryPoint::from_bytes(remainder)?;
+        Ok((TransactionV2Body::new(args, target, entry_point), remainder))
+    }
+}
+
+/// A transaction in the versioned format succeeding deploys.
+///
+/// The header and the body are hashed separately, with the header containing the body hash, and
+/// the approvals are kept apart from both, so that the set of approvals can change without
+/// affecting the transaction hash.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct TransactionV2 {
+    hash: TransactionV2Hash,
+    header: TransactionV2Header,
+    body: TransactionV2Body,
+    approvals: BTreeSet<TransactionV2Approval>,
+}
+
+impl TransactionV2 {
+    /// Constructs a new `TransactionV2`, signed by `secret_key`.
+    ///
+    /// The initiator is the account of `secret_key` unless given explicitly.
+    pub fn new(
+        chain_name: String,
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        pricing_mode: PricingMode,
+        body: TransactionV2Body,
+        secret_key: &SecretKey,
+        initiator: Option<PublicKey>,
+    ) -> Self {
+        let header = TransactionV2Header {
+            chain_name,
+            timestamp,
+            ttl,
+            initiator: initiator.unwrap_or_else(|| PublicKey::from(secret_key)),
+            pricing_mode,
+            body_hash: hash_of(&body),
+        };
+        let mut transaction = TransactionV2 {
+            hash: TransactionV2Hash::new(hash_of(&header)),
+            header,
+            body,
+            approvals: BTreeSet::new(),
+        };
+        transaction.sign(secret_key);
+        transaction
+    }
+
+    /// Adds a signature of this transaction's hash to its approvals.
+    pub fn sign(&mut self, secret_key: &SecretKey) {
+        let approval = TransactionV2Approval::create(&self.hash, secret_key);
+        self.approvals.insert(approval);
+    }
+
+    /// Returns the hash identifying this transaction.
+    pub fn hash(&self) -> &TransactionV2Hash {
+        &self.hash
+    }
+
+    /// Returns the header of this transaction.
+    pub fn header(&self) -> &TransactionV2Header {
+        &self.header
+    }
+
+    /// Returns the body of this transaction.
+    pub fn body(&self) -> &TransactionV2Body {
+        &self.body
+    }
+
+    /// Returns the approvals of this transaction.
+    pub fn approvals(&self) -> &BTreeSet<TransactionV2Approval> {
+        &self.approvals
+    }
+
+    /// Verifies that the body and transaction hashes are correct, that the entry point is valid
+    /// for the target, and that there is at least one approval and all approvals are valid
+    /// signatures of the transaction hash.
+    pub fn verify(&self) -> Result<(), InvalidTransactionV2> {
+        if hash_of(&self.body) != self.header.body_hash {
+            return Err(InvalidTransactionV2::InvalidBodyHash);
+        }
+        if TransactionV2Hash::new(hash_of(&self.header)) != self.hash {
+            return Err(InvalidTransactionV2::InvalidTransactionHash);
+        }
+        if !self.body.entry_point.is_valid_for(&self.body.target) {
+            return Err(InvalidTransactionV2::InvalidEntryPoint(
+                self.body.entry_point.clone(),
+            ));
+        }
+        if self.approvals.is_empty() {
+            return Err(InvalidTransactionV2::EmptyApprovals);
+        }
+        for (index, approval) in self.approvals.iter().enumerate() {
+            if let Err(error) = crypto::verify_with_context(
+                &self.hash,
+                SigningContext::TransactionApproval,
+                approval.signature(),
+                approval.signer(),
+            ) {
+                return Err(InvalidTransactionV2::InvalidApproval {
+                    index,
+                    error_msg: error.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a random, valid `TransactionV2`.
+    #[cfg(any(all(feature = "std", feature = "testing"), test))]
+    pub fn random(rng: &mut TestRng) -> Self {
+        let secret_key = SecretKey::random(rng);
+        TransactionV2::new(
+            format!("chain-{}", rng.gen::<u16>()),
+            Timestamp::random(rng),
+            TimeDiff::from(rng.gen_range(60_000..3_600_000)),
+            PricingMode::random(rng),
+            TransactionV2Body::random(rng),
+            &secret_key,
+            None,
+        )
+    }
+}
+
+impl Display for TransactionV2 {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "transaction-v2[{}, initiator {}, {} {} on {}, {} approvals]",
+            self.hash,
+            self.header.initiator,
+            self.header.pricing_mode,
+            self.body.entry_point,
+            self.body.target,
+            self.approvals.len()
+        )
+    }
+}
+
+impl ToBytes for TransactionV2 {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.hash.serialized_length()
+            + self.header.serialized_length()
+            + self.body.serialized_length()
+            + self.approvals.serialized_length()
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        self.hash.write_bytes(writer)?;
+        self.header.write_bytes(writer)?;
+        self.body.write_bytes(writer)?;
+        self.approvals.write_bytes(writer)
+    }
+}
+
+impl FromBytes for TransactionV2 {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (hash, remainder) = TransactionV2Hash::from_bytes(bytes)?;
+        let (header, remainder) = TransactionV2Header::from_bytes(remainder)?;
+        let (body, remainder) = TransactionV2Body::from_bytes(remainder)?;
+        let (approvals, remainder) = BTreeSet::<TransactionV2Approval>::from_bytes(remainder)?;
+        let transaction = TransactionV2 {
+            hash,
+            header,
+            body,
+            approvals,
+        };
+        Ok((transaction, remainder))
+    }
+}
+
+/// Returns the hash of the serialized `value`.
+fn hash_of<T: ToBytes>(value: &T) -> [u8; BLAKE2B_DIGEST_LENGTH] {
+    let serialized = value
+        .to_bytes()
+        .unwrap_or_else(|error| panic!("should serialize transaction part: {}", error));
+    crypto::blake2b(serialized)
+}
+
+/// (De)serializes a digest as a hex string in human-readable formats.
+mod hex_digest {
+    use super::*;
+
+    pub(super) fn serialize<S: Serializer>(
+        digest: &[u8; BLAKE2B_DIGEST_LENGTH],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            base16::encode_lower(digest).serialize(serializer)
+        } else {
+            digest.serialize(serializer)
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u8; BLAKE2B_DIGEST_LENGTH], D::Error> {
+        if deserializer.is_human_readable() {
+            let hex_string = String::deserialize(deserializer)?;
+            let bytes =
+                checksummed_hex::decode(hex_string.as_bytes()).map_err(SerdeError::custom)?;
+            <[u8; BLAKE2B_DIGEST_LENGTH]>::try_from(bytes.as_ref()).map_err(SerdeError::custom)
+        } else {
+            <[u8; BLAKE2B_DIGEST_LENGTH]>::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        let mut rng = TestRng::new();
+        let transaction = TransactionV2::random(&mut rng);
+        bytesrepr::test_serialization_roundtrip(&transaction);
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let mut rng = TestRng::new();
+        let transaction = TransactionV2::random(&mut rng);
+        let json_string = serde_json::to_string_pretty(&transaction).unwrap();
+        let decoded = serde_json::from_str(&json_string).unwrap();
+        assert_eq!(transaction, decoded);
+    }
+
+    #[test]
+    fn should_verify_valid_transaction() {
+        let mut rng = TestRng::new();
+        let transaction = TransactionV2::random(&mut rng);
+        assert_eq!(transaction.verify(), Ok(()));
+    }
+
+    #[test]
+    fn should_fail_to_verify_tampered_transaction() {
+        let mut rng = TestRng::new();
+
+        let mut transaction = TransactionV2::random(&mut rng);
+        transaction.header.chain_name.push('x');
+        assert_eq!(
+            transaction.verify(),
+            Err(InvalidTransactionV2::InvalidTransactionHash)
+        );
+
+        let mut transaction = TransactionV2::random(&mut rng);
+        transaction.body = TransactionV2Body::random(&mut rng);
+        assert_eq!(
+            transaction.verify(),
+            Err(InvalidTransactionV2::InvalidBodyHash)
+        );
+
+        let mut transaction = TransactionV2::random(&mut rng);
+        transaction.approvals.clear();
+        assert_eq!(
+            transaction.verify(),
+            Err(InvalidTransactionV2::EmptyApprovals)
+        );
+    }
+
+    #[test]
+    fn should_fail_to_verify_invalid_entry_point() {
+        let mut rng = TestRng::new();
+        let body = TransactionV2Body::new(
+            RuntimeArgs::new(),
+            TransactionTarget::Native,
+            TransactionEntryPoint::Call,
+        );
+        let transaction = TransactionV2::new(
+            String::from("casper-example"),
+            Timestamp::random(&mut rng),
+            TimeDiff::from(60_000),
+            PricingMode::random(&mut rng),
+            body,
+            &SecretKey::random(&mut rng),
+            None,
+        );
+        assert_eq!(
+            transaction.verify(),
+            Err(InvalidTransactionV2::InvalidEntryPoint(
+                TransactionEntryPoint::Call
+            ))
+        );
+    }
+}
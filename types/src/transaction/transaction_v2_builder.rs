@@ -0,0 +1,290 @@
+use alloc::{string::String, vec::Vec};
+
+use super::{
+    PricingMode, TransactionEntryPoint, TransactionInvocationTarget, TransactionTarget,
+    TransactionV2, TransactionV2Body, TransactionV2BuilderError,
+};
+use crate::{
+    account::AccountHash,
+    bytesrepr::{Bytes, ToBytes},
+    system::mint,
+    CLTyped, PublicKey, RuntimeArgs, SecretKey, TimeDiff, Timestamp, U512,
+};
+
+/// A builder for constructing and signing a [`TransactionV2`].
+///
+/// The chain name, the payment amount and at least one secret key must be provided.  The
+/// timestamp defaults to the current time where the "std" feature is enabled, the TTL to
+/// [`TransactionV2Builder::DEFAULT_TTL`] and the gas price to
+/// [`TransactionV2Builder::DEFAULT_GAS_PRICE`].  Unless set explicitly, the initiator is the
+/// account of the first secret key; every secret key adds an approval.
+#[derive(Clone, Debug)]
+pub struct TransactionV2Builder<'a> {
+    chain_name: Option<String>,
+    timestamp: Option<Timestamp>,
+    ttl: TimeDiff,
+    payment_amount: Option<u64>,
+    gas_price: u64,
+    body: TransactionV2Body,
+    initiator: Option<PublicKey>,
+    secret_keys: Vec<&'a SecretKey>,
+}
+
+impl<'a> TransactionV2Builder<'a> {
+    /// The default time-to-live for transactions, i.e. 30 minutes.
+    pub const DEFAULT_TTL: TimeDiff = TimeDiff::from_seconds(30 * 60);
+    /// The default gas price for transactions.
+    pub const DEFAULT_GAS_PRICE: u64 = 1;
+
+    /// Returns a new `TransactionV2Builder` for a transaction with the given body.
+    pub fn new(body: TransactionV2Body) -> Self {
+        TransactionV2Builder {
+            chain_name: None,
+            timestamp: None,
+            ttl: Self::DEFAULT_TTL,
+            payment_amount: None,
+            gas_price: Self::DEFAULT_GAS_PRICE,
+            body,
+            initiator: None,
+            secret_keys: Vec::new(),
+        }
+    }
+
+    /// Returns a new `TransactionV2Builder` for a native transfer of `amount` motes from the
+    /// initiator's main purse to the main purse of `target`.
+    pub fn new_transfer<A: Into<U512>>(amount: A, target: AccountHash, id: Option<u64>) -> Self {
+        let mut args = RuntimeArgs::new();
+        insert_arg(&mut args, mint::ARG_AMOUNT, amount.into());
+        insert_arg(&mut args, mint::ARG_TARGET, target);
+        insert_arg(&mut args, mint::ARG_ID, id);
+        let body = TransactionV2Body::new(
+            args,
+            TransactionTarget::Native,
+            TransactionEntryPoint::Transfer,
+        );
+        Self::new(body)
+    }
+
+    /// Returns a new `TransactionV2Builder` for executing the given Wasm session code.
+    pub fn new_session(module_bytes: Bytes, args: RuntimeArgs) -> Self {
+        let body = TransactionV2Body::new(
+            args,
+            TransactionTarget::Session { module_bytes },
+            TransactionEntryPoint::Call,
+        );
+        Self::new(body)
+    }
+
+    /// Returns a new `TransactionV2Builder` for invoking the given entry point of a stored
+    /// contract.
+    pub fn new_targeting_stored<E: Into<String>>(
+        id: TransactionInvocationTarget,
+        entry_point: E,
+        args: RuntimeArgs,
+    ) -> Self {
+        let body = TransactionV2Body::new(
+            args,
+            TransactionTarget::Stored { id },
+            TransactionEntryPoint::Custom(entry_point.into()),
+        );
+        Self::new(body)
+    }
+
+    /// Sets the name of the chain the transaction is supposed to be executed on.
+    pub fn with_chain_name<C: Into<String>>(mut self, chain_name: C) -> Self {
+        self.chain_name = Some(chain_name.into());
+        self
+    }
+
+    /// Sets the time at which the transaction was created.
+    pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Sets how long the transaction stays valid after its timestamp.
+    pub fn with_ttl(mut self, ttl: TimeDiff) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the amount of motes paid via the standard payment code.
+    pub fn with_standard_payment(mut self, payment_amount: u64) -> Self {
+        self.payment_amount = Some(payment_amount);
+        self
+    }
+
+    /// Sets the price in motes per unit of gas.
+    pub fn with_gas_price(mut self, gas_price: u64) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+
+    /// Sets the account initiating the transaction, if it differs from the first signer.
+    pub fn with_initiator(mut self, initiator: PublicKey) -> Self {
+        self.initiator = Some(initiator);
+        self
+    }
+
+    /// Adds a secret key with which to sign the transaction.
+    pub fn with_secret_key(mut self, secret_key: &'a SecretKey) -> Self {
+        self.secret_keys.push(secret_key);
+        self
+    }
+
+    /// Returns the signed transaction, or an error if a required field is missing or the body is
+    /// invalid.
+    pub fn build(self) -> Result<TransactionV2, TransactionV2BuilderError> {
+        let chain_name = self
+            .chain_name
+            .ok_or(TransactionV2BuilderError::MissingChainName)?;
+        let payment_amount = self
+            .payment_amount
+            .ok_or(TransactionV2BuilderError::MissingPaymentAmount)?;
+        let timestamp = match self.timestamp {
+            Some(timestamp) => timestamp,
+            None => current_time()?,
+        };
+        if !self.body.entry_point().is_valid_for(self.body.target()) {
+            return Err(TransactionV2BuilderError::InvalidEntryPoint(
+                self.body.entry_point().clone(),
+            ));
+        }
+        let (first_key, other_keys) = self
+            .secret_keys
+            .split_first()
+            .ok_or(TransactionV2BuilderError::MissingSecretKey)?;
+
+        let pricing_mode = PricingMode::Classic {
+            payment_amount,
+            gas_price: self.gas_price,
+        };
+        let mut transaction = TransactionV2::new(
+            chain_name,
+            timestamp,
+            self.ttl,
+            pricing_mode,
+            self.body,
+            first_key,
+            self.initiator,
+        );
+        for secret_key in other_keys {
+            transaction.sign(secret_key);
+        }
+        Ok(transaction)
+    }
+}
+
+#[cfg(any(feature = "std", test))]
+fn current_time() -> Result<Timestamp, TransactionV2BuilderError> {
+    Ok(Timestamp::now())
+}
+
+#[cfg(not(any(feature = "std", test)))]
+fn current_time() -> Result<Timestamp, TransactionV2BuilderError> {
+    Err(TransactionV2BuilderError::MissingTimestamp)
+}
+
+fn insert_arg<T: CLTyped + ToBytes>(args: &mut RuntimeArgs, name: &str, value: T) {
+    args.insert(name, value)
+        .unwrap_or_else(|error| panic!("should serialize transfer arg '{}': {}", name, error));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_build_transaction_signed_by_all_keys() {
+        let mut rng = TestRng::new();
+        let initiator_key = SecretKey::random(&mut rng);
+        let cosigner_key = SecretKey::random(&mut rng);
+        let timestamp = Timestamp::random(&mut rng);
+
+        let transaction =
+            TransactionV2Builder::new_transfer(1_000, AccountHash::new([1; 32]), Some(7))
+                .with_chain_name("casper-example")
+                .with_timestamp(timestamp)
+                .with_standard_payment(10_000)
+                .with_gas_price(2)
+                .with_secret_key(&initiator_key)
+                .with_secret_key(&cosigner_key)
+                .build()
+                .expect("should build transaction");
+
+        assert_eq!(transaction.verify(), Ok(()));
+        assert_eq!(transaction.header().chain_name(), "casper-example");
+        assert_eq!(transaction.header().timestamp(), timestamp);
+        assert_eq!(
+            transaction.header().ttl(),
+            TransactionV2Builder::DEFAULT_TTL
+        );
+        assert_eq!(
+            transaction.header().initiator(),
+            &PublicKey::from(&initiator_key)
+        );
+        assert_eq!(
+            transaction.header().pricing_mode(),
+            &PricingMode::Classic {
+                payment_amount: 10_000,
+                gas_price: 2
+            }
+        );
+        assert_eq!(transaction.approvals().len(), 2);
+        assert_eq!(
+            transaction.body().args().get(mint::ARG_AMOUNT),
+            Some(&crate::CLValue::from_t(U512::from(1_000)).unwrap())
+        );
+    }
+
+    #[test]
+    fn should_fail_to_build_incomplete_transaction() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let builder = TransactionV2Builder::new_session(Bytes::from(vec![1]), RuntimeArgs::new())
+            .with_chain_name("casper-example")
+            .with_standard_payment(10_000);
+
+        assert_eq!(
+            builder.clone().build().unwrap_err(),
+            TransactionV2BuilderError::MissingSecretKey
+        );
+        assert_eq!(
+            TransactionV2Builder::new_session(Bytes::new(), RuntimeArgs::new())
+                .with_secret_key(&secret_key)
+                .build()
+                .unwrap_err(),
+            TransactionV2BuilderError::MissingChainName
+        );
+        assert_eq!(
+            TransactionV2Builder::new_session(Bytes::new(), RuntimeArgs::new())
+                .with_chain_name("casper-example")
+                .with_secret_key(&secret_key)
+                .build()
+                .unwrap_err(),
+            TransactionV2BuilderError::MissingPaymentAmount
+        );
+        assert!(builder.with_secret_key(&secret_key).build().is_ok());
+    }
+
+    #[test]
+    fn should_fail_to_build_invalid_entry_point() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let body = TransactionV2Body::new(
+            RuntimeArgs::new(),
+            TransactionTarget::Native,
+            TransactionEntryPoint::Call,
+        );
+        assert_eq!(
+            TransactionV2Builder::new(body)
+                .with_chain_name("casper-example")
+                .with_standard_payment(10_000)
+                .with_secret_key(&secret_key)
+                .build()
+                .unwrap_err(),
+            TransactionV2BuilderError::InvalidEntryPoint(TransactionEntryPoint::Call)
+        );
+    }
+}
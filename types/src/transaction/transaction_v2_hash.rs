@@ -0,0 +1,137 @@
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use alloc::{string::String, vec::Vec};
+use core::{
+    convert::TryFrom,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+#[cfg(feature = "datasize")]
+use datasize::DataSize;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+#[cfg(feature = "json-schema")]
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    checksummed_hex,
+};
+
+/// The length of a transaction hash.
+pub const TRANSACTION_V2_HASH_LENGTH: usize = 32;
+
+/// A newtype wrapping a <code>[u8; [TRANSACTION_V2_HASH_LENGTH]]</code> which is the raw bytes of
+/// the hash of a [`TransactionV2`](super::TransactionV2).
+#[derive(Default, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+pub struct TransactionV2Hash([u8; TRANSACTION_V2_HASH_LENGTH]);
+
+impl TransactionV2Hash {
+    /// Constructs a new `TransactionV2Hash` instance from the raw bytes of a transaction hash.
+    pub const fn new(value: [u8; TRANSACTION_V2_HASH_LENGTH]) -> TransactionV2Hash {
+        TransactionV2Hash(value)
+    }
+
+    /// Returns the raw bytes of the transaction hash as an array.
+    pub fn value(&self) -> [u8; TRANSACTION_V2_HASH_LENGTH] {
+        self.0
+    }
+
+    /// Returns the raw bytes of the transaction hash as a `slice`.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl JsonSchema for TransactionV2Hash {
+    fn schema_name() -> String {
+        String::from("TransactionV2Hash")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let schema = gen.subschema_for::<String>();
+        let mut schema_object = schema.into_object();
+        schema_object.metadata().description = Some("Hex-encoded transaction hash.".to_string());
+        schema_object.into()
+    }
+}
+
+impl ToBytes for TransactionV2Hash {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        self.0.write_bytes(writer)
+    }
+}
+
+impl FromBytes for TransactionV2Hash {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        <[u8; TRANSACTION_V2_HASH_LENGTH]>::from_bytes(bytes)
+            .map(|(inner, remainder)| (TransactionV2Hash(inner), remainder))
+    }
+}
+
+impl Serialize for TransactionV2Hash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            base16::encode_lower(&self.0).serialize(serializer)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TransactionV2Hash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = if deserializer.is_human_readable() {
+            let hex_string = String::deserialize(deserializer)?;
+            let vec_bytes =
+                checksummed_hex::decode(hex_string.as_bytes()).map_err(SerdeError::custom)?;
+            <[u8; TRANSACTION_V2_HASH_LENGTH]>::try_from(vec_bytes.as_ref())
+                .map_err(SerdeError::custom)?
+        } else {
+            <[u8; TRANSACTION_V2_HASH_LENGTH]>::deserialize(deserializer)?
+        };
+        Ok(TransactionV2Hash(bytes))
+    }
+}
+
+impl Debug for TransactionV2Hash {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "TransactionV2Hash({})",
+            base16::encode_lower(&self.0)
+        )
+    }
+}
+
+impl Display for TransactionV2Hash {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}", base16::encode_lower(&self.0))
+    }
+}
+
+impl AsRef<[u8]> for TransactionV2Hash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Distribution<TransactionV2Hash> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> TransactionV2Hash {
+        TransactionV2Hash::new(rng.gen())
+    }
+}
@@ -21,13 +21,15 @@ use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Seria
 use crate::{
     account::AccountHash,
     bytesrepr::{self, FromBytes, ToBytes},
-    checksummed_hex, CLType, CLTyped, URef, U512,
+    checksummed_hex, CLType, CLTyped, Phase, URef, U512,
 };
 
 /// The length of a deploy hash.
 pub const DEPLOY_HASH_LENGTH: usize = 32;
 /// The length of a transfer address.
 pub const TRANSFER_ADDR_LENGTH: usize = 32;
+/// The maximum length in bytes of the UTF-8 memo attached to a transfer.
+pub const TRANSFER_MEMO_MAX_LENGTH: usize = 256;
 pub(super) const TRANSFER_ADDR_FORMATTED_STRING_PREFIX: &str = "transfer-";
 
 /// A newtype wrapping a <code>[u8; [DEPLOY_HASH_LENGTH]]</code> which is the raw bytes of the
@@ -239,6 +241,161 @@ impl ToBytes for Transfer {
     }
 }
 
+/// Represents a transfer from one purse to another, along with the account which initiated it, an
+/// optional memo and the deploy phase in which it was made.
+///
+/// Recorded in place of a [`Transfer`] once enabled by the chainspec.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct TransferV2 {
+    /// Deploy that created the transfer
+    pub deploy_hash: DeployHash,
+    /// Account from which transfer was executed
+    pub from: AccountHash,
+    /// Account to which funds are transferred
+    pub to: Option<AccountHash>,
+    /// Source purse
+    pub source: URef,
+    /// Target purse
+    pub target: URef,
+    /// Transfer amount
+    pub amount: U512,
+    /// Gas
+    pub gas: U512,
+    /// User-defined id
+    pub id: Option<u64>,
+    /// Account whose session code called the mint, or `None` if the transfer was made by a stored
+    /// contract
+    pub initiator: Option<AccountHash>,
+    /// User-defined UTF-8 memo of at most [`TRANSFER_MEMO_MAX_LENGTH`] bytes
+    pub memo: Option<String>,
+    /// Deploy phase in which the transfer was made
+    pub phase: Phase,
+}
+
+impl TransferV2 {
+    /// Creates a [`TransferV2`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        deploy_hash: DeployHash,
+        from: AccountHash,
+        to: Option<AccountHash>,
+        source: URef,
+        target: URef,
+        amount: U512,
+        gas: U512,
+        id: Option<u64>,
+        initiator: Option<AccountHash>,
+        memo: Option<String>,
+        phase: Phase,
+    ) -> Self {
+        TransferV2 {
+            deploy_hash,
+            from,
+            to,
+            source,
+            target,
+            amount,
+            gas,
+            id,
+            initiator,
+            memo,
+            phase,
+        }
+    }
+}
+
+impl From<Transfer> for TransferV2 {
+    /// Converts a legacy transfer, which was always initiated by the deploy's account in the
+    /// session phase and had no memo.
+    fn from(transfer: Transfer) -> Self {
+        TransferV2 {
+            deploy_hash: transfer.deploy_hash,
+            from: transfer.from,
+            to: transfer.to,
+            source: transfer.source,
+            target: transfer.target,
+            amount: transfer.amount,
+            gas: transfer.gas,
+            id: transfer.id,
+            initiator: Some(transfer.from),
+            memo: None,
+            phase: Phase::Session,
+        }
+    }
+}
+
+impl FromBytes for TransferV2 {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (deploy_hash, rem) = FromBytes::from_bytes(bytes)?;
+        let (from, rem) = AccountHash::from_bytes(rem)?;
+        let (to, rem) = <Option<AccountHash>>::from_bytes(rem)?;
+        let (source, rem) = URef::from_bytes(rem)?;
+        let (target, rem) = URef::from_bytes(rem)?;
+        let (amount, rem) = U512::from_bytes(rem)?;
+        let (gas, rem) = U512::from_bytes(rem)?;
+        let (id, rem) = <Option<u64>>::from_bytes(rem)?;
+        let (initiator, rem) = <Option<AccountHash>>::from_bytes(rem)?;
+        let (memo, rem) = <Option<String>>::from_bytes(rem)?;
+        let (phase, rem) = Phase::from_bytes(rem)?;
+        Ok((
+            TransferV2 {
+                deploy_hash,
+                from,
+                to,
+                source,
+                target,
+                amount,
+                gas,
+                id,
+                initiator,
+                memo,
+                phase,
+            },
+            rem,
+        ))
+    }
+}
+
+impl ToBytes for TransferV2 {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut result = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut result)?;
+        Ok(result)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.deploy_hash.serialized_length()
+            + self.from.serialized_length()
+            + self.to.serialized_length()
+            + self.source.serialized_length()
+            + self.target.serialized_length()
+            + self.amount.serialized_length()
+            + self.gas.serialized_length()
+            + self.id.serialized_length()
+            + self.initiator.serialized_length()
+            + self.memo.serialized_length()
+            + self.phase.serialized_length()
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        self.deploy_hash.write_bytes(writer)?;
+        self.from.write_bytes(writer)?;
+        self.to.write_bytes(writer)?;
+        self.source.write_bytes(writer)?;
+        self.target.write_bytes(writer)?;
+        self.amount.write_bytes(writer)?;
+        self.gas.write_bytes(writer)?;
+        self.id.write_bytes(writer)?;
+        self.initiator.write_bytes(writer)?;
+        self.memo.write_bytes(writer)?;
+        self.phase.write_bytes(writer)?;
+        Ok(())
+    }
+}
+
 /// Error returned when decoding a `TransferAddr` from a formatted string.
 #[derive(Debug)]
 #[non_exhaustive]
@@ -408,15 +565,15 @@ impl Distribution<TransferAddr> for Standard {
     }
 }
 
-/// Generators for [`Transfer`]
+/// Generators for [`Transfer`] and [`TransferV2`]
 #[cfg(any(feature = "testing", test))]
 pub mod gens {
     use proptest::prelude::{prop::option, Arbitrary, Strategy};
 
     use crate::{
         deploy_info::gens::{account_hash_arb, deploy_hash_arb},
-        gens::{u512_arb, uref_arb},
-        Transfer,
+        gens::{phase_arb, u512_arb, uref_arb},
+        Transfer, TransferV2,
     };
 
     /// Creates an arbitrary [`Transfer`]
@@ -444,6 +601,22 @@ pub mod gens {
                 }
             })
     }
+
+    /// Creates an arbitrary [`TransferV2`]
+    pub fn transfer_v2_arb() -> impl Strategy<Value = TransferV2> {
+        (
+            transfer_arb(),
+            option::of(account_hash_arb()),
+            option::of("\\PC{0,64}"),
+            phase_arb(),
+        )
+            .prop_map(|(transfer, initiator, memo, phase)| TransferV2 {
+                initiator,
+                memo,
+                phase,
+                ..TransferV2::from(transfer)
+            })
+    }
 }
 
 #[cfg(test)]
@@ -459,6 +632,11 @@ mod tests {
         fn test_serialization_roundtrip(transfer in gens::transfer_arb()) {
             bytesrepr::test_serialization_roundtrip(&transfer)
         }
+
+        #[test]
+        fn test_v2_serialization_roundtrip(transfer in gens::transfer_v2_arb()) {
+            bytesrepr::test_serialization_roundtrip(&transfer)
+        }
     }
 
     #[test]
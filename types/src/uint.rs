@@ -23,7 +23,10 @@ use serde::{
     ser::{Serialize, SerializeStruct, Serializer},
 };
 
-use crate::bytesrepr::{self, Error, FromBytes, ToBytes, U8_SERIALIZED_LENGTH};
+use crate::{
+    bytesrepr::{self, Error, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    json_decimal,
+};
 
 #[allow(
     clippy::assign_op_pattern,
@@ -154,9 +157,7 @@ macro_rules! impl_traits_for_uint {
                 ];
 
                 if deserializer.is_human_readable() {
-                    let decimal_string = String::deserialize(deserializer)?;
-                    return Self::from_dec_str(&decimal_string)
-                        .map_err(|error| de::Error::custom(format!("{:?}", error)));
+                    return json_decimal::deserialize_human_readable(deserializer);
                 }
 
                 deserializer.deserialize_struct("bigint", FIELDS, BigNumVisitor)
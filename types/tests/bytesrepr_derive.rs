@@ -0,0 +1,98 @@
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    U512,
+};
+
+const FIRST_TAG: u8 = 3;
+
+#[derive(PartialEq, Debug, FromBytes, ToBytes)]
+struct Named {
+    #[bytesrepr(index = 0)]
+    flag: bool,
+    #[bytesrepr(index = 1)]
+    amount: U512,
+    #[bytesrepr(index = 2)]
+    names: Vec<String>,
+}
+
+#[derive(PartialEq, Debug, FromBytes, ToBytes)]
+struct Tuple(u32, Option<u8>);
+
+#[derive(PartialEq, Debug, FromBytes, ToBytes)]
+struct Unit;
+
+#[derive(PartialEq, Debug, FromBytes, ToBytes)]
+struct Generic<T> {
+    inner: T,
+}
+
+#[derive(PartialEq, Debug, FromBytes, ToBytes)]
+enum Tagged {
+    #[bytesrepr(tag = FIRST_TAG)]
+    First,
+    #[bytesrepr(tag = 7)]
+    Second(u64, String),
+    #[bytesrepr(tag = FIRST_TAG + 1)]
+    Third { named: Named, tuple: Tuple },
+}
+
+#[test]
+fn should_roundtrip_structs() {
+    bytesrepr::test_serialization_roundtrip(&Named {
+        flag: true,
+        amount: U512::from(123_456_789_u64),
+        names: vec!["a".to_string(), "bc".to_string()],
+    });
+    bytesrepr::test_serialization_roundtrip(&Tuple(9, Some(1)));
+    bytesrepr::test_serialization_roundtrip(&Unit);
+    bytesrepr::test_serialization_roundtrip(&Generic {
+        inner: Tuple(1, None),
+    });
+}
+
+#[test]
+fn should_roundtrip_enum() {
+    bytesrepr::test_serialization_roundtrip(&Tagged::First);
+    bytesrepr::test_serialization_roundtrip(&Tagged::Second(5, "five".to_string()));
+    bytesrepr::test_serialization_roundtrip(&Tagged::Third {
+        named: Named {
+            flag: false,
+            amount: U512::zero(),
+            names: vec![],
+        },
+        tuple: Tuple(0, Some(0)),
+    });
+}
+
+#[test]
+fn should_serialize_fields_in_declaration_order() {
+    let tuple = Tuple(1, Some(2));
+    let mut expected = 1_u32.to_bytes().unwrap();
+    expected.extend(Some(2_u8).to_bytes().unwrap());
+    assert_eq!(tuple.to_bytes().unwrap(), expected);
+    assert_eq!(tuple.serialized_length(), expected.len());
+    assert!(Unit.to_bytes().unwrap().is_empty());
+}
+
+#[test]
+fn should_prefix_variant_with_tag() {
+    let second = Tagged::Second(5, "five".to_string());
+    let mut expected = vec![7];
+    expected.extend(5_u64.to_bytes().unwrap());
+    expected.extend("five".to_string().to_bytes().unwrap());
+    assert_eq!(second.to_bytes().unwrap(), expected);
+    assert_eq!(second.serialized_length(), expected.len());
+    assert_eq!(Tagged::First.to_bytes().unwrap(), vec![FIRST_TAG]);
+}
+
+#[test]
+fn should_fail_to_deserialize_unknown_tag() {
+    assert_eq!(
+        Tagged::from_bytes(&[0]).unwrap_err(),
+        bytesrepr::Error::Formatting
+    );
+    assert_eq!(
+        Tagged::from_bytes(&[7, 1]).unwrap_err(),
+        bytesrepr::Error::EarlyEndOfStream
+    );
+}
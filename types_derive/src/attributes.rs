@@ -0,0 +1,97 @@
+//! Parsing of the `#[bytesrepr(...)]` helper attribute.
+
+use syn::{
+    parse::{Parse, ParseStream},
+    Attribute, Expr, Field, Ident, LitInt, Token, Variant,
+};
+
+const ATTRIBUTE_NAME: &str = "bytesrepr";
+
+/// A single `#[bytesrepr(...)]` argument.
+enum Argument {
+    /// The tag of an enum variant.
+    Tag(Expr),
+    /// The expected position of a field.
+    Index(LitInt),
+}
+
+impl Parse for Argument {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        if name == "tag" {
+            Ok(Argument::Tag(input.parse()?))
+        } else if name == "index" {
+            Ok(Argument::Index(input.parse()?))
+        } else {
+            Err(syn::Error::new(
+                name.span(),
+                "expected `tag = ...` or `index = ...`",
+            ))
+        }
+    }
+}
+
+fn parse_arguments(attributes: &[Attribute]) -> syn::Result<Vec<(&Attribute, Argument)>> {
+    attributes
+        .iter()
+        .filter(|attribute| attribute.path.is_ident(ATTRIBUTE_NAME))
+        .map(|attribute| attribute.parse_args().map(|argument| (attribute, argument)))
+        .collect()
+}
+
+/// Ensures no `#[bytesrepr(...)]` attribute is applied to the type itself.
+pub(crate) fn check_container(attributes: &[Attribute]) -> syn::Result<()> {
+    match parse_arguments(attributes)?.first() {
+        Some((attribute, _)) => Err(syn::Error::new_spanned(
+            attribute,
+            "`#[bytesrepr(...)]` is only supported on enum variants and fields",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Returns the tag declared via `#[bytesrepr(tag = ...)]` on the given enum variant.
+pub(crate) fn variant_tag(variant: &Variant) -> syn::Result<Expr> {
+    let mut tag = None;
+    for (attribute, argument) in parse_arguments(&variant.attrs)? {
+        match argument {
+            Argument::Tag(_) if tag.is_some() => {
+                return Err(syn::Error::new_spanned(attribute, "duplicate tag"));
+            }
+            Argument::Tag(expr) => tag = Some(expr),
+            Argument::Index(_) => {
+                return Err(syn::Error::new_spanned(
+                    attribute,
+                    "`index` is only supported on fields",
+                ));
+            }
+        }
+    }
+    tag.ok_or_else(|| {
+        syn::Error::new_spanned(
+            variant,
+            "enum variants must declare their tag via `#[bytesrepr(tag = ...)]`",
+        )
+    })
+}
+
+/// Returns the index declared via `#[bytesrepr(index = ...)]` on the given field, if any.
+pub(crate) fn field_index(field: &Field) -> syn::Result<Option<usize>> {
+    let mut index = None;
+    for (attribute, argument) in parse_arguments(&field.attrs)? {
+        match argument {
+            Argument::Index(_) if index.is_some() => {
+                return Err(syn::Error::new_spanned(attribute, "duplicate index"));
+            }
+            Argument::Index(literal) => index = Some(literal.base10_parse()?),
+            Argument::Tag(_) => {
+                return Err(syn::Error::new_spanned(
+                    attribute,
+                    "`tag` is only supported on enum variants",
+                ));
+            }
+        }
+    }
+    Ok(index)
+}
@@ -0,0 +1,271 @@
+//! Generation of the `ToBytes` and `FromBytes` implementations.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DataEnum, DeriveInput, Expr, Fields, GenericParam, Generics, Ident, Index, Member, Type,
+};
+
+use crate::attributes;
+
+/// Generates the `ToBytes` implementation for the given type.
+pub(crate) fn to_bytes(input: &DeriveInput) -> syn::Result<TokenStream> {
+    attributes::check_container(&input.attrs)?;
+    let bytesrepr = bytesrepr_path();
+    let name = &input.ident;
+    let generics = add_trait_bounds(input.generics.clone(), quote!(#bytesrepr::ToBytes));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (serialized_length, write_bytes, tag_check) = match &input.data {
+        Data::Struct(data) => {
+            check_field_order(&data.fields)?;
+            let members = members(&data.fields);
+            let serialized_length =
+                quote!(0 #(+ #bytesrepr::ToBytes::serialized_length(&self.#members))*);
+            let write_bytes = quote! {
+                #(#bytesrepr::ToBytes::write_bytes(&self.#members, writer)?;)*
+                Ok(())
+            };
+            (serialized_length, write_bytes, TokenStream::new())
+        }
+        Data::Enum(data) => {
+            let tags = variant_tags(input, data)?;
+            let mut length_arms = Vec::new();
+            let mut write_arms = Vec::new();
+            for (variant, tag) in data.variants.iter().zip(&tags) {
+                check_field_order(&variant.fields)?;
+                let bindings = bindings(&variant.fields);
+                let ident = &variant.ident;
+                let pattern = construct(quote!(Self::#ident), &variant.fields, &bindings);
+                length_arms.push(quote! {
+                    #pattern => 0 #(+ #bytesrepr::ToBytes::serialized_length(#bindings))*
+                });
+                write_arms.push(quote! {
+                    #pattern => {
+                        writer.push(#tag);
+                        #(#bytesrepr::ToBytes::write_bytes(#bindings, writer)?;)*
+                    }
+                });
+            }
+            let serialized_length = quote! {
+                #bytesrepr::U8_SERIALIZED_LENGTH + match self { #(#length_arms,)* }
+            };
+            let write_bytes = quote! {
+                match self { #(#write_arms)* }
+                Ok(())
+            };
+            (serialized_length, write_bytes, unique_tags_check(&tags))
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "`ToBytes` cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        #tag_check
+
+        #[automatically_derived]
+        impl #impl_generics #bytesrepr::ToBytes for #name #ty_generics #where_clause {
+            fn to_bytes(
+                &self,
+            ) -> ::core::result::Result<#bytesrepr::__derive::Vec<u8>, #bytesrepr::Error> {
+                let mut buffer = #bytesrepr::allocate_buffer(self)?;
+                #bytesrepr::ToBytes::write_bytes(self, &mut buffer)?;
+                Ok(buffer)
+            }
+
+            fn serialized_length(&self) -> usize {
+                #serialized_length
+            }
+
+            fn write_bytes(
+                &self,
+                writer: &mut #bytesrepr::__derive::Vec<u8>,
+            ) -> ::core::result::Result<(), #bytesrepr::Error> {
+                #write_bytes
+            }
+        }
+    })
+}
+
+/// Generates the `FromBytes` implementation for the given type.
+pub(crate) fn from_bytes(input: &DeriveInput) -> syn::Result<TokenStream> {
+    attributes::check_container(&input.attrs)?;
+    let bytesrepr = bytesrepr_path();
+    let name = &input.ident;
+    let generics = add_trait_bounds(input.generics.clone(), quote!(#bytesrepr::FromBytes));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            check_field_order(&data.fields)?;
+            let bindings = bindings(&data.fields);
+            let types = field_types(&data.fields);
+            let value = construct(quote!(Self), &data.fields, &bindings);
+            quote! {
+                let remainder = bytes;
+                #(let (#bindings, remainder) = <#types as #bytesrepr::FromBytes>::from_bytes(remainder)?;)*
+                Ok((#value, remainder))
+            }
+        }
+        Data::Enum(data) => {
+            let tags = variant_tags(input, data)?;
+            let mut branches = Vec::new();
+            for (variant, tag) in data.variants.iter().zip(&tags) {
+                check_field_order(&variant.fields)?;
+                let bindings = bindings(&variant.fields);
+                let types = field_types(&variant.fields);
+                let ident = &variant.ident;
+                let value = construct(quote!(Self::#ident), &variant.fields, &bindings);
+                branches.push(quote! {
+                    if __tag == #tag {
+                        #(let (#bindings, remainder) = <#types as #bytesrepr::FromBytes>::from_bytes(remainder)?;)*
+                        return Ok((#value, remainder));
+                    }
+                });
+            }
+            quote! {
+                let (__tag, remainder) = <u8 as #bytesrepr::FromBytes>::from_bytes(bytes)?;
+                #(#branches)*
+                Err(#bytesrepr::Error::Formatting)
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "`FromBytes` cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #bytesrepr::FromBytes for #name #ty_generics #where_clause {
+            fn from_bytes(
+                bytes: &[u8],
+            ) -> ::core::result::Result<(Self, &[u8]), #bytesrepr::Error> {
+                #body
+            }
+        }
+    })
+}
+
+fn bytesrepr_path() -> TokenStream {
+    quote!(casper_types::bytesrepr)
+}
+
+/// Adds `bound` to every type parameter of `generics`.
+fn add_trait_bounds(mut generics: Generics, bound: TokenStream) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(type_param) = param {
+            type_param.bounds.push(syn::parse_quote!(#bound));
+        }
+    }
+    generics
+}
+
+/// Returns the tags of all variants of the given enum.
+fn variant_tags(input: &DeriveInput, data: &DataEnum) -> syn::Result<Vec<Expr>> {
+    if data.variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            input,
+            "enums without variants are not supported",
+        ));
+    }
+    data.variants.iter().map(attributes::variant_tag).collect()
+}
+
+/// Generates a constant evaluation which fails compilation if any two tags are equal.
+///
+/// Tags may be arbitrary constant expressions, so they can't be compared by the macro itself.
+fn unique_tags_check(tags: &[Expr]) -> TokenStream {
+    quote! {
+        const _: () = {
+            let tags: &[u8] = &[#(#tags),*];
+            let mut index = 0;
+            while index < tags.len() {
+                let mut other_index = index + 1;
+                while other_index < tags.len() {
+                    if tags[index] == tags[other_index] {
+                        panic!("enum variants must have unique `bytesrepr` tags");
+                    }
+                    other_index += 1;
+                }
+                index += 1;
+            }
+        };
+    }
+}
+
+/// Ensures that, if any field declares its index via `#[bytesrepr(index = ...)]`, all fields do
+/// and each is declared at its stated index.
+fn check_field_order(fields: &Fields) -> syn::Result<()> {
+    let indices = fields
+        .iter()
+        .map(attributes::field_index)
+        .collect::<syn::Result<Vec<_>>>()?;
+    if indices.iter().all(Option::is_none) {
+        return Ok(());
+    }
+    for (position, (field, index)) in fields.iter().zip(indices).enumerate() {
+        match index {
+            None => {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "all fields must declare their index via `#[bytesrepr(index = ...)]` if any \
+                    field does",
+                ))
+            }
+            Some(index) if index != position => {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    format!(
+                        "field is declared at index {} but its `bytesrepr` index is {}; \
+                        reordering fields changes the serialized form",
+                        position, index
+                    ),
+                ))
+            }
+            Some(_) => (),
+        }
+    }
+    Ok(())
+}
+
+/// Returns the members via which the given fields are accessed on `self`.
+fn members(fields: &Fields) -> Vec<Member> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(index)),
+        })
+        .collect()
+}
+
+/// Returns the local variable names to which the given fields are bound.
+fn bindings(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len())
+        .map(|index| format_ident!("__field{}", index))
+        .collect()
+}
+
+fn field_types(fields: &Fields) -> Vec<&Type> {
+    fields.iter().map(|field| &field.ty).collect()
+}
+
+/// Generates a pattern or constructor expression for `path` with its fields bound to `bindings`.
+fn construct(path: TokenStream, fields: &Fields, bindings: &[Ident]) -> TokenStream {
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Unit => path,
+    }
+}
@@ -0,0 +1,54 @@
+//! Derive macros for the `ToBytes` and `FromBytes` traits of `casper_types::bytesrepr`.
+//!
+//! Fields are serialized in declaration order.  Enum variants are serialized as a `u8` tag
+//! followed by the variant's fields, and each variant must declare its tag explicitly via
+//! `#[bytesrepr(tag = ...)]`, where the tag is any constant `u8` expression:
+//!
+//! ```ignore
+//! use casper_types::bytesrepr::{FromBytes, ToBytes};
+//!
+//! #[derive(ToBytes, FromBytes)]
+//! enum Target {
+//!     #[bytesrepr(tag = 0)]
+//!     Native,
+//!     #[bytesrepr(tag = 1)]
+//!     Stored { name: String, version: Option<u32> },
+//! }
+//! ```
+//!
+//! Duplicate tags are rejected at compile time.  Since reordering the fields of a type silently
+//! changes its serialized form, fields may additionally be annotated with their expected position
+//! via `#[bytesrepr(index = ...)]`.  If any field of a struct or variant is annotated, all must be,
+//! and compilation fails if a field is not declared at its stated index.
+
+#![doc(html_root_url = "https://docs.rs/casper-types-derive/0.1.0")]
+#![doc(
+    html_favicon_url = "https://raw.githubusercontent.com/CasperLabs/casper-node/master/images/CasperLabs_Logo_Favicon_RGB_50px.png",
+    html_logo_url = "https://raw.githubusercontent.com/CasperLabs/casper-node/master/images/CasperLabs_Logo_Symbol_RGB.png",
+    test(attr(forbid(warnings)))
+)]
+#![warn(missing_docs, trivial_casts, trivial_numeric_casts)]
+
+mod attributes;
+mod gen;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Derives `casper_types::bytesrepr::ToBytes`.
+#[proc_macro_derive(ToBytes, attributes(bytesrepr))]
+pub fn derive_to_bytes(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    gen::to_bytes(&input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Derives `casper_types::bytesrepr::FromBytes`.
+#[proc_macro_derive(FromBytes, attributes(bytesrepr))]
+pub fn derive_from_bytes(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    gen::from_bytes(&input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
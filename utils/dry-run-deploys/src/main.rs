@@ -45,6 +45,13 @@ struct Opts {
         about = "Max LMDB database size, may be useful to set this when running under valgrind."
     )]
     max_db_size: Option<usize>,
+
+    #[structopt(
+        long,
+        default_value = "1",
+        about = "Number of threads executing the deploys of each block in parallel."
+    )]
+    deploy_threads: usize,
 }
 
 #[tokio::main]
@@ -129,6 +136,7 @@ async fn main() -> Result<(), anyhow::Error> {
         let block_and_execution_effects = execute_finalized_block(
             &engine_state,
             None,
+            opts.deploy_threads,
             protocol_version,
             execution_pre_state,
             finalized_block,
@@ -0,0 +1,92 @@
+use std::{fs, path::Path};
+
+use serde::Serialize;
+
+use casper_types::{AsymmetricType, PublicKey, U512};
+
+use crate::Error;
+
+/// The contents of a chainspec's `accounts.toml`.
+#[derive(Serialize, Debug, Default)]
+pub(crate) struct AccountsToml {
+    accounts: Vec<AccountEntry>,
+}
+
+#[derive(Serialize, Debug)]
+struct AccountEntry {
+    public_key: String,
+    balance: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validator: Option<ValidatorEntry>,
+}
+
+#[derive(Serialize, Debug)]
+struct ValidatorEntry {
+    bonded_amount: String,
+    delegation_rate: u8,
+}
+
+impl AccountsToml {
+    /// Adds an account with the given balance, bonded as a genesis validator if `bonded_amount` is
+    /// given.
+    pub(crate) fn push(
+        &mut self,
+        public_key: &PublicKey,
+        balance: U512,
+        bonded_amount: Option<U512>,
+    ) {
+        self.accounts.push(AccountEntry {
+            public_key: public_key.to_hex(),
+            balance: balance.to_string(),
+            validator: bonded_amount.map(|bonded_amount| ValidatorEntry {
+                bonded_amount: bonded_amount.to_string(),
+                delegation_rate: 0,
+            }),
+        });
+    }
+
+    /// Writes the accounts to `path`.
+    pub(crate) fn write(&self, path: &Path) -> Result<(), Error> {
+        let contents = toml::to_string(self)?;
+        fs::write(path, contents).map_err(|error| Error::Io {
+            context: "failed to write",
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::SecretKey;
+
+    use super::*;
+
+    #[test]
+    fn should_render_validators_and_accounts() {
+        let validator = PublicKey::from(&SecretKey::ed25519_from_bytes([1; 32]).unwrap());
+        let user = PublicKey::from(&SecretKey::ed25519_from_bytes([2; 32]).unwrap());
+
+        let mut accounts = AccountsToml::default();
+        accounts.push(&validator, U512::from(100), Some(U512::from(10)));
+        accounts.push(&user, U512::from(50), None);
+
+        let rendered: toml::Value = toml::from_str(&toml::to_string(&accounts).unwrap()).unwrap();
+        let entries = rendered["accounts"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0]["public_key"].as_str(),
+            Some(validator.to_hex().as_str())
+        );
+        assert_eq!(entries[0]["balance"].as_str(), Some("100"));
+        assert_eq!(
+            entries[0]["validator"]["bonded_amount"].as_str(),
+            Some("10")
+        );
+        assert_eq!(
+            entries[1]["public_key"].as_str(),
+            Some(user.to_hex().as_str())
+        );
+        assert!(entries[1].get("validator").is_none());
+    }
+}
@@ -0,0 +1,77 @@
+use std::{io, path::PathBuf};
+
+use thiserror::Error;
+
+use casper_types::{crypto::ErrorExt, ProtocolVersion};
+
+/// Errors returned while setting up or operating a local network.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Failed to read or write a file or directory.
+    #[error("{context} {path}: {error}")]
+    Io {
+        /// What was being done with the path.
+        context: &'static str,
+        /// The path involved.
+        path: PathBuf,
+        /// The underlying error.
+        #[source]
+        error: io::Error,
+    },
+
+    /// Failed to parse a TOML template.
+    #[error("failed to parse {path} as TOML: {error}")]
+    ParseToml {
+        /// The path of the template.
+        path: PathBuf,
+        /// The underlying error.
+        #[source]
+        error: toml::de::Error,
+    },
+
+    /// Failed to render a TOML file.
+    #[error("failed to render TOML: {0}")]
+    RenderToml(#[from] toml::ser::Error),
+
+    /// A dotted key of a template override does not refer to a table entry.
+    #[error("cannot set '{key}': '{segment}' is not a table")]
+    NotATable {
+        /// The dotted key being set.
+        key: String,
+        /// The segment of the key which is not a table.
+        segment: String,
+    },
+
+    /// Failed to generate or write node keys.
+    #[error("failed to create keys: {0}")]
+    Keys(#[from] ErrorExt),
+
+    /// Failed to spawn a node process.
+    #[error("failed to spawn node {index} from {binary}: {error}")]
+    Spawn {
+        /// The index of the node.
+        index: usize,
+        /// The node binary.
+        binary: PathBuf,
+        /// The underlying error.
+        #[source]
+        error: io::Error,
+    },
+
+    /// No node with the given index exists.
+    #[error("no node with index {0}")]
+    NoSuchNode(usize),
+
+    /// The node with the given index is already running.
+    #[error("node {0} is already running")]
+    AlreadyRunning(usize),
+
+    /// An upgrade was staged to a protocol version which is not newer than the latest one.
+    #[error("cannot stage upgrade to {version}, as it is not newer than {latest}")]
+    StaleUpgrade {
+        /// The version of the staged upgrade.
+        version: ProtocolVersion,
+        /// The latest installed version.
+        latest: ProtocolVersion,
+    },
+}
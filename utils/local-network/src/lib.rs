@@ -0,0 +1,48 @@
+//! A library for spawning, configuring, upgrading and tearing down local networks of
+//! `casper-node` processes.
+//!
+//! It covers the same ground as the bash-based NCTL tooling for a single network: generating keys
+//! for the faucet, nodes and users, templating the chainspec, accounts and node configs, running
+//! each node as a child process with its output collected under its `logs` directory, and staging
+//! protocol upgrades which nodes switch to when they exit at the upgrade point.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::{thread, time::Duration};
+//!
+//! use casper_local_network::{NetworkBuilder, Upgrade};
+//! use casper_types::{EraId, ProtocolVersion};
+//!
+//! # fn main() -> Result<(), casper_local_network::Error> {
+//! let mut network = NetworkBuilder::new("/tmp/local-network", "target/release/casper-node")
+//!     .with_node_count(3)
+//!     .with_chainspec_override("core.era_duration", "41seconds")
+//!     .build()?;
+//! network.stage_upgrade(Upgrade::new(ProtocolVersion::from_parts(1, 1, 0), EraId::new(2)))?;
+//! network.start()?;
+//!
+//! for _ in 0..600 {
+//!     for event in network.supervise()? {
+//!         println!("{:?}", event);
+//!     }
+//!     thread::sleep(Duration::from_secs(1));
+//! }
+//!
+//! network.collect_logs("/tmp/local-network-logs")?;
+//! network.teardown()
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+mod accounts;
+mod error;
+mod network;
+mod node;
+mod template;
+
+pub use error::Error;
+pub use network::{Network, NetworkBuilder, Upgrade};
+pub use node::{Node, NodeEvent, NodePorts, NodeStatus};
+pub use template::TomlTemplate;
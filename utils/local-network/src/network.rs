@@ -0,0 +1,598 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use toml::Value;
+use tracing::info;
+
+use casper_types::{
+    AsymmetricType, EraId, ProtocolVersion, PublicKey, SecretKey, TimeDiff, Timestamp, U512,
+};
+
+use crate::{
+    accounts::AccountsToml,
+    node::{Node, NodeEvent, NodePorts, NodeStatus},
+    template::TomlTemplate,
+    Error,
+};
+
+/// The default number of nodes.
+const DEFAULT_NODE_COUNT: usize = 5;
+/// The default number of user accounts.
+const DEFAULT_USER_COUNT: usize = 5;
+/// The default first port allocated to nodes.
+const DEFAULT_BASE_PORT: u16 = 40_000;
+/// The default delay between setting up the network and genesis.
+const DEFAULT_GENESIS_DELAY: Duration = Duration::from_secs(30);
+/// The default name of the network.
+const DEFAULT_CHAIN_NAME: &str = "casper-local";
+
+/// The balance of the faucet account at genesis.
+const FAUCET_BALANCE: u64 = u64::MAX;
+/// The balance of each node's account at genesis.
+const NODE_BALANCE: u64 = 1_000_000_000_000_000_000;
+/// The balance of each user account at genesis.
+const USER_BALANCE: u64 = 1_000_000_000_000_000_000;
+/// The bonded amount of the first genesis validator; each further one bonds one mote more.
+const BASE_BONDED_AMOUNT: u64 = 1_000_000_000_000_000;
+
+const CHAINSPEC_FILE_NAME: &str = "chainspec.toml";
+const ACCOUNTS_FILE_NAME: &str = "accounts.toml";
+const CONFIG_FILE_NAME: &str = "config.toml";
+const GLOBAL_STATE_FILE_NAME: &str = "global_state.toml";
+
+fn default_template_path(file_name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../resources/local")
+        .join(file_name)
+}
+
+fn create_dir(path: &Path) -> Result<(), Error> {
+    fs::create_dir_all(path).map_err(|error| Error::Io {
+        context: "failed to create",
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<(), Error> {
+    fs::copy(from, to).map(drop).map_err(|error| Error::Io {
+        context: "failed to copy to",
+        path: to.to_path_buf(),
+        error,
+    })
+}
+
+/// Generates a key pair, writing it in PEM format to `dir`, along with the hex-encoded public key.
+fn generate_keys(dir: &Path) -> Result<PublicKey, Error> {
+    create_dir(dir)?;
+    let secret_key = SecretKey::generate_ed25519()?;
+    let public_key = PublicKey::from(&secret_key);
+    secret_key.to_file(dir.join("secret_key.pem"))?;
+    public_key.to_file(dir.join("public_key.pem"))?;
+    let path = dir.join("public_key_hex");
+    fs::write(&path, public_key.to_hex()).map_err(|error| Error::Io {
+        context: "failed to write",
+        path,
+        error,
+    })?;
+    Ok(public_key)
+}
+
+/// Builds and sets up the assets of a [`Network`].
+#[derive(Debug)]
+pub struct NetworkBuilder {
+    root: PathBuf,
+    node_binary: PathBuf,
+    chainspec_template: PathBuf,
+    config_template: PathBuf,
+    node_count: usize,
+    genesis_validator_count: Option<usize>,
+    user_count: usize,
+    protocol_version: ProtocolVersion,
+    genesis_delay: Duration,
+    base_port: u16,
+    chain_name: String,
+    chainspec_overrides: Vec<(String, Value)>,
+    config_overrides: Vec<(String, Value)>,
+}
+
+impl NetworkBuilder {
+    /// Returns a builder for a network set up under `root`, running the given `casper-node`
+    /// binary.
+    ///
+    /// By default, the chainspec and node config are templated from `resources/local` of this
+    /// repository, and all nodes are genesis validators.
+    pub fn new<P: Into<PathBuf>, Q: Into<PathBuf>>(root: P, node_binary: Q) -> Self {
+        NetworkBuilder {
+            root: root.into(),
+            node_binary: node_binary.into(),
+            chainspec_template: default_template_path("chainspec.toml.in"),
+            config_template: default_template_path(CONFIG_FILE_NAME),
+            node_count: DEFAULT_NODE_COUNT,
+            genesis_validator_count: None,
+            user_count: DEFAULT_USER_COUNT,
+            protocol_version: ProtocolVersion::V1_0_0,
+            genesis_delay: DEFAULT_GENESIS_DELAY,
+            base_port: DEFAULT_BASE_PORT,
+            chain_name: DEFAULT_CHAIN_NAME.to_string(),
+            chainspec_overrides: Vec::new(),
+            config_overrides: Vec::new(),
+        }
+    }
+
+    /// Sets the chainspec template.
+    pub fn with_chainspec_template<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.chainspec_template = path.into();
+        self
+    }
+
+    /// Sets the node config template.
+    pub fn with_config_template<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config_template = path.into();
+        self
+    }
+
+    /// Sets the number of nodes.
+    pub fn with_node_count(mut self, node_count: usize) -> Self {
+        self.node_count = node_count;
+        self
+    }
+
+    /// Sets the number of nodes bonded at genesis, which defaults to all of them.
+    ///
+    /// The remaining nodes are given funded accounts, but are not bonded.
+    pub fn with_genesis_validator_count(mut self, genesis_validator_count: usize) -> Self {
+        self.genesis_validator_count = Some(genesis_validator_count);
+        self
+    }
+
+    /// Sets the number of funded user accounts.
+    pub fn with_user_count(mut self, user_count: usize) -> Self {
+        self.user_count = user_count;
+        self
+    }
+
+    /// Sets the protocol version at genesis.
+    pub fn with_protocol_version(mut self, protocol_version: ProtocolVersion) -> Self {
+        self.protocol_version = protocol_version;
+        self
+    }
+
+    /// Sets the delay between setting up the network and genesis.
+    pub fn with_genesis_delay(mut self, genesis_delay: Duration) -> Self {
+        self.genesis_delay = genesis_delay;
+        self
+    }
+
+    /// Sets the first port allocated to nodes.  Each node uses five consecutive ports.
+    pub fn with_base_port(mut self, base_port: u16) -> Self {
+        self.base_port = base_port;
+        self
+    }
+
+    /// Sets the name of the network.
+    pub fn with_chain_name<S: Into<String>>(mut self, chain_name: S) -> Self {
+        self.chain_name = chain_name.into();
+        self
+    }
+
+    /// Overrides the chainspec entry under the dotted `key`, e.g. `core.era_duration`.
+    pub fn with_chainspec_override<K: Into<String>, V: Into<Value>>(
+        mut self,
+        key: K,
+        value: V,
+    ) -> Self {
+        self.chainspec_overrides.push((key.into(), value.into()));
+        self
+    }
+
+    /// Overrides the entry under the dotted `key` in the config of every node, e.g.
+    /// `logging.format`.
+    pub fn with_config_override<K: Into<String>, V: Into<Value>>(
+        mut self,
+        key: K,
+        value: V,
+    ) -> Self {
+        self.config_overrides.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets up the network's keys, chainspec, accounts and node configs under the root directory,
+    /// replacing anything there before.  No node is started.
+    pub fn build(self) -> Result<Network, Error> {
+        if self.root.exists() {
+            fs::remove_dir_all(&self.root).map_err(|error| Error::Io {
+                context: "failed to remove",
+                path: self.root.clone(),
+                error,
+            })?;
+        }
+        let genesis_validator_count = self
+            .genesis_validator_count
+            .unwrap_or(self.node_count)
+            .min(self.node_count);
+
+        let mut accounts = AccountsToml::default();
+        let faucet_key = generate_keys(&self.root.join("faucet"))?;
+        accounts.push(&faucet_key, U512::from(FAUCET_BALANCE), None);
+
+        let mut nodes = Vec::with_capacity(self.node_count);
+        for index in 1..=self.node_count {
+            let dir = self.root.join("nodes").join(format!("node-{}", index));
+            let public_key = generate_keys(&dir.join("keys"))?;
+            let is_genesis_validator = index <= genesis_validator_count;
+            let bonded_amount = if is_genesis_validator {
+                Some(U512::from(BASE_BONDED_AMOUNT) + U512::from(index))
+            } else {
+                None
+            };
+            accounts.push(&public_key, U512::from(NODE_BALANCE), bonded_amount);
+            let node = Node::new(
+                index,
+                dir,
+                public_key,
+                NodePorts::new(self.base_port, index - 1),
+                is_genesis_validator,
+                self.protocol_version,
+                self.node_binary.clone(),
+            );
+            create_dir(&node.storage_dir())?;
+            create_dir(&node.logs_dir())?;
+            nodes.push(node);
+        }
+
+        for index in 1..=self.user_count {
+            let public_key =
+                generate_keys(&self.root.join("users").join(format!("user-{}", index)))?;
+            accounts.push(&public_key, U512::from(USER_BALANCE), None);
+        }
+
+        let genesis_timestamp = Timestamp::now() + TimeDiff::from(self.genesis_delay);
+        let mut chainspec = TomlTemplate::from_file(&self.chainspec_template)?;
+        chainspec.set("protocol.version", self.protocol_version.to_string())?;
+        chainspec.set("protocol.activation_point", genesis_timestamp.to_string())?;
+        chainspec.set("network.name", self.chain_name.clone())?;
+        for (key, value) in &self.chainspec_overrides {
+            chainspec.set(key, value.clone())?;
+        }
+
+        let chainspec_dir = self.root.join("chainspec");
+        create_dir(&chainspec_dir)?;
+        chainspec.write(chainspec_dir.join(CHAINSPEC_FILE_NAME))?;
+        accounts.write(&chainspec_dir.join(ACCOUNTS_FILE_NAME))?;
+
+        let network = Network {
+            root: self.root,
+            nodes,
+            user_count: self.user_count,
+            chainspec,
+            config_template: TomlTemplate::from_file(&self.config_template)?,
+            config_overrides: self.config_overrides,
+        };
+        for node in &network.nodes {
+            let config_dir = node.config_dir(self.protocol_version);
+            create_dir(&config_dir)?;
+            copy_file(
+                &chainspec_dir.join(CHAINSPEC_FILE_NAME),
+                &config_dir.join(CHAINSPEC_FILE_NAME),
+            )?;
+            copy_file(
+                &chainspec_dir.join(ACCOUNTS_FILE_NAME),
+                &config_dir.join(ACCOUNTS_FILE_NAME),
+            )?;
+            network
+                .node_config(node, &network.config_template, &[])?
+                .write(config_dir.join(CONFIG_FILE_NAME))?;
+        }
+
+        info!(root = %network.root.display(), %genesis_timestamp, "set up network");
+        Ok(network)
+    }
+}
+
+/// A protocol upgrade, staged on all nodes of a [`Network`] via [`Network::stage_upgrade`].
+#[derive(Clone, Debug)]
+pub struct Upgrade {
+    protocol_version: ProtocolVersion,
+    activation_point: EraId,
+    node_binary: Option<PathBuf>,
+    config_template: Option<PathBuf>,
+    global_state_update: Option<PathBuf>,
+    chainspec_overrides: Vec<(String, Value)>,
+    config_overrides: Vec<(String, Value)>,
+}
+
+impl Upgrade {
+    /// Returns an upgrade to `protocol_version`, activated at the start of `activation_point`.
+    ///
+    /// By default, the upgraded nodes run the same binary and config, and the chainspec of the
+    /// previous version is carried over.
+    pub fn new(protocol_version: ProtocolVersion, activation_point: EraId) -> Self {
+        Upgrade {
+            protocol_version,
+            activation_point,
+            node_binary: None,
+            config_template: None,
+            global_state_update: None,
+            chainspec_overrides: Vec::new(),
+            config_overrides: Vec::new(),
+        }
+    }
+
+    /// Sets the `casper-node` binary run after the upgrade.
+    pub fn with_node_binary<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.node_binary = Some(path.into());
+        self
+    }
+
+    /// Sets the node config template used after the upgrade.
+    pub fn with_config_template<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config_template = Some(path.into());
+        self
+    }
+
+    /// Sets the `global_state.toml` applied at the upgrade.
+    pub fn with_global_state_update<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.global_state_update = Some(path.into());
+        self
+    }
+
+    /// Overrides the chainspec entry under the dotted `key` from the upgrade onwards.
+    pub fn with_chainspec_override<K: Into<String>, V: Into<Value>>(
+        mut self,
+        key: K,
+        value: V,
+    ) -> Self {
+        self.chainspec_overrides.push((key.into(), value.into()));
+        self
+    }
+
+    /// Overrides the entry under the dotted `key` in the config of every node after the upgrade.
+    pub fn with_config_override<K: Into<String>, V: Into<Value>>(
+        mut self,
+        key: K,
+        value: V,
+    ) -> Self {
+        self.config_overrides.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// A local network of `casper-node` processes.
+///
+/// All node processes are killed when the network is dropped; their assets are only removed by
+/// [`Network::teardown`].
+#[derive(Debug)]
+pub struct Network {
+    root: PathBuf,
+    nodes: Vec<Node>,
+    user_count: usize,
+    /// The chainspec of the latest installed version.
+    chainspec: TomlTemplate,
+    /// The node config template of the latest installed version.
+    config_template: TomlTemplate,
+    /// The node config overrides of the latest installed version.
+    config_overrides: Vec<(String, Value)>,
+}
+
+impl Network {
+    /// Returns the directory holding the network's assets.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the nodes of the network.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the node with the given index, starting at 1.
+    pub fn node(&self, index: usize) -> Result<&Node, Error> {
+        index
+            .checked_sub(1)
+            .and_then(|position| self.nodes.get(position))
+            .ok_or(Error::NoSuchNode(index))
+    }
+
+    fn node_mut(&mut self, index: usize) -> Result<&mut Node, Error> {
+        index
+            .checked_sub(1)
+            .and_then(move |position| self.nodes.get_mut(position))
+            .ok_or(Error::NoSuchNode(index))
+    }
+
+    /// Returns the path of the faucet account's secret key.
+    pub fn faucet_secret_key_path(&self) -> PathBuf {
+        self.root.join("faucet").join("secret_key.pem")
+    }
+
+    /// Returns the paths of the user accounts' secret keys.
+    pub fn user_secret_key_paths(&self) -> Vec<PathBuf> {
+        (1..=self.user_count)
+            .map(|index| {
+                self.root
+                    .join("users")
+                    .join(format!("user-{}", index))
+                    .join("secret_key.pem")
+            })
+            .collect()
+    }
+
+    /// Starts all nodes which are not running.
+    pub fn start(&mut self) -> Result<(), Error> {
+        for node in &mut self.nodes {
+            if node.status() != NodeStatus::Running {
+                node.start()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts the node with the given index.
+    pub fn start_node(&mut self, index: usize) -> Result<(), Error> {
+        self.node_mut(index)?.start()
+    }
+
+    /// Stops the node with the given index.
+    pub fn stop_node(&mut self, index: usize) -> Result<(), Error> {
+        self.node_mut(index)?.stop();
+        Ok(())
+    }
+
+    /// Stops all nodes.
+    pub fn stop(&mut self) {
+        for node in &mut self.nodes {
+            node.stop();
+        }
+    }
+
+    /// Checks all running nodes for having exited.
+    ///
+    /// Like `casper-node-launcher`, nodes exiting successfully at an upgrade point are restarted
+    /// with the next staged version.  Should be called periodically while the network runs.
+    pub fn supervise(&mut self) -> Result<Vec<NodeEvent>, Error> {
+        let mut events = Vec::new();
+        for node in &mut self.nodes {
+            events.extend(node.poll()?);
+        }
+        Ok(events)
+    }
+
+    /// Stages `upgrade` on all nodes, installing its chainspec and configs alongside those of the
+    /// previous versions, where running nodes pick them up.
+    pub fn stage_upgrade(&mut self, upgrade: Upgrade) -> Result<(), Error> {
+        let version = upgrade.protocol_version;
+        if let Some(latest) = self
+            .nodes
+            .iter()
+            .flat_map(|node| node.installed_versions().copied())
+            .max()
+            .filter(|latest| *latest >= version)
+        {
+            return Err(Error::StaleUpgrade { version, latest });
+        }
+
+        let mut chainspec = self.chainspec.clone();
+        chainspec.set("protocol.version", version.to_string())?;
+        chainspec.set(
+            "protocol.activation_point",
+            upgrade.activation_point.value() as i64,
+        )?;
+        for (key, value) in &upgrade.chainspec_overrides {
+            chainspec.set(key, value.clone())?;
+        }
+        let config_template = match &upgrade.config_template {
+            Some(path) => TomlTemplate::from_file(path)?,
+            None => self.config_template.clone(),
+        };
+        let mut config_overrides = self.config_overrides.clone();
+        config_overrides.extend(upgrade.config_overrides.iter().cloned());
+
+        for node in &self.nodes {
+            let config_dir = node.config_dir(version);
+            create_dir(&config_dir)?;
+            chainspec.write(config_dir.join(CHAINSPEC_FILE_NAME))?;
+            if let Some(path) = &upgrade.global_state_update {
+                copy_file(path, &config_dir.join(GLOBAL_STATE_FILE_NAME))?;
+            }
+            self.node_config(node, &config_template, &upgrade.config_overrides)?
+                .write(config_dir.join(CONFIG_FILE_NAME))?;
+        }
+        for node in &mut self.nodes {
+            let binary = upgrade
+                .node_binary
+                .clone()
+                .unwrap_or_else(|| node.latest_binary().to_path_buf());
+            node.install(version, binary);
+        }
+
+        self.chainspec = chainspec;
+        self.config_template = config_template;
+        self.config_overrides = config_overrides;
+        info!(%version, activation_point = %upgrade.activation_point, "staged upgrade");
+        Ok(())
+    }
+
+    /// Copies the logs of all nodes to `dest`, into a subdirectory per node.
+    pub fn collect_logs<P: AsRef<Path>>(&self, dest: P) -> Result<(), Error> {
+        for node in &self.nodes {
+            let node_dest = dest.as_ref().join(format!("node-{}", node.index()));
+            create_dir(&node_dest)?;
+            let logs_dir = node.logs_dir();
+            let entries = fs::read_dir(&logs_dir).map_err(|error| Error::Io {
+                context: "failed to read",
+                path: logs_dir.clone(),
+                error,
+            })?;
+            for entry in entries {
+                let path = entry
+                    .map_err(|error| Error::Io {
+                        context: "failed to read",
+                        path: logs_dir.clone(),
+                        error,
+                    })?
+                    .path();
+                if let Some(file_name) = path.file_name() {
+                    copy_file(&path, &node_dest.join(file_name))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops all nodes and removes the network's assets.
+    pub fn teardown(mut self) -> Result<(), Error> {
+        self.stop();
+        fs::remove_dir_all(&self.root).map_err(|error| Error::Io {
+            context: "failed to remove",
+            path: self.root.clone(),
+            error,
+        })
+    }
+
+    /// Returns the config of `node`, rendered from `template` with the node's keys, storage and
+    /// ports, the network's config overrides and `extra_overrides` applied.
+    fn node_config(
+        &self,
+        node: &Node,
+        template: &TomlTemplate,
+        extra_overrides: &[(String, Value)],
+    ) -> Result<TomlTemplate, Error> {
+        let ports = node.ports();
+        let bootstrap_port = self.nodes[0].ports().network;
+        let path_string = |path: PathBuf| path.to_string_lossy().into_owned();
+
+        let mut config = template.clone();
+        config.set(
+            "consensus.secret_key_path",
+            path_string(node.secret_key_path()),
+        )?;
+        config.set("storage.path", path_string(node.storage_dir()))?;
+        config.set("network.bind_address", format!("0.0.0.0:{}", ports.network))?;
+        config.set(
+            "network.public_address",
+            format!("127.0.0.1:{}", ports.network),
+        )?;
+        config.set(
+            "network.known_addresses",
+            vec![format!("127.0.0.1:{}", bootstrap_port)],
+        )?;
+        config.set("rpc_server.address", format!("0.0.0.0:{}", ports.rpc))?;
+        config.set("rest_server.address", format!("0.0.0.0:{}", ports.rest))?;
+        config.set(
+            "event_stream_server.address",
+            format!("0.0.0.0:{}", ports.event_stream),
+        )?;
+        config.set(
+            "speculative_exec_server.address",
+            format!("0.0.0.0:{}", ports.speculative_exec),
+        )?;
+        for (key, value) in self.config_overrides.iter().chain(extra_overrides) {
+            config.set(key, value.clone())?;
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+};
+
+use tracing::{info, warn};
+
+use casper_types::{ProtocolVersion, PublicKey};
+
+use crate::Error;
+
+/// The name of the file a node's stdout is appended to, in its logs directory.
+const STDOUT_FILE_NAME: &str = "stdout.log";
+/// The name of the file a node's stderr is appended to, in its logs directory.
+const STDERR_FILE_NAME: &str = "stderr.log";
+
+/// The ports a node listens on, all bound on localhost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodePorts {
+    /// The port of the node-to-node networking.
+    pub network: u16,
+    /// The port of the JSON-RPC server.
+    pub rpc: u16,
+    /// The port of the REST server.
+    pub rest: u16,
+    /// The port of the SSE event stream server.
+    pub event_stream: u16,
+    /// The port of the speculative execution JSON-RPC server.
+    pub speculative_exec: u16,
+}
+
+impl NodePorts {
+    /// The number of ports used per node.
+    pub(crate) const COUNT: u16 = 5;
+
+    /// Returns the ports of the node with the given index, allocated consecutively from
+    /// `base_port`.
+    pub(crate) fn new(base_port: u16, index: usize) -> Self {
+        let first = base_port + index as u16 * Self::COUNT;
+        NodePorts {
+            network: first,
+            rpc: first + 1,
+            rest: first + 2,
+            event_stream: first + 3,
+            speculative_exec: first + 4,
+        }
+    }
+}
+
+/// The status of a node's process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeStatus {
+    /// The node has not been started, or has been stopped.
+    Stopped,
+    /// The node is running.
+    Running,
+    /// The node exited on its own with the given exit code, if any, and was not restarted.
+    Exited(Option<i32>),
+}
+
+/// A change in the status of a node observed by [`Network::supervise`](crate::Network::supervise).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeEvent {
+    /// The node exited at an upgrade point and was restarted running the next installed version.
+    Upgraded {
+        /// The index of the node.
+        index: usize,
+        /// The protocol version the node ran before.
+        from: ProtocolVersion,
+        /// The protocol version the node runs now.
+        to: ProtocolVersion,
+    },
+    /// The node exited and was not restarted.
+    Exited {
+        /// The index of the node.
+        index: usize,
+        /// The exit code, if the node was not terminated by a signal.
+        code: Option<i32>,
+    },
+}
+
+/// A node of a local network, run as a child process.
+#[derive(Debug)]
+pub struct Node {
+    index: usize,
+    dir: PathBuf,
+    public_key: PublicKey,
+    ports: NodePorts,
+    is_genesis_validator: bool,
+    /// The installed protocol versions and the binaries to run them with.
+    versions: BTreeMap<ProtocolVersion, PathBuf>,
+    current_version: ProtocolVersion,
+    process: Option<Child>,
+    status: NodeStatus,
+}
+
+impl Node {
+    pub(crate) fn new(
+        index: usize,
+        dir: PathBuf,
+        public_key: PublicKey,
+        ports: NodePorts,
+        is_genesis_validator: bool,
+        protocol_version: ProtocolVersion,
+        binary: PathBuf,
+    ) -> Self {
+        let mut versions = BTreeMap::new();
+        versions.insert(protocol_version, binary);
+        Node {
+            index,
+            dir,
+            public_key,
+            ports,
+            is_genesis_validator,
+            versions,
+            current_version: protocol_version,
+            process: None,
+            status: NodeStatus::Stopped,
+        }
+    }
+
+    /// Returns the index of the node within the network, starting at 1.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the directory holding the node's keys, configs, storage and logs.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the node's validator public key.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Returns the path of the node's validator secret key.
+    pub fn secret_key_path(&self) -> PathBuf {
+        self.dir.join("keys").join("secret_key.pem")
+    }
+
+    /// Returns the ports the node listens on.
+    pub fn ports(&self) -> NodePorts {
+        self.ports
+    }
+
+    /// Returns the address of the node's JSON-RPC endpoint.
+    pub fn rpc_address(&self) -> String {
+        format!("http://127.0.0.1:{}/rpc", self.ports.rpc)
+    }
+
+    /// Returns the address of the node's SSE event stream.
+    pub fn event_stream_address(&self) -> String {
+        format!("http://127.0.0.1:{}/events/main", self.ports.event_stream)
+    }
+
+    /// Returns whether the node was bonded at genesis.
+    pub fn is_genesis_validator(&self) -> bool {
+        self.is_genesis_validator
+    }
+
+    /// Returns the protocol version the node runs, or ran last.
+    pub fn current_version(&self) -> ProtocolVersion {
+        self.current_version
+    }
+
+    /// Returns the installed protocol versions, including staged upgrades.
+    pub fn installed_versions(&self) -> impl Iterator<Item = &ProtocolVersion> {
+        self.versions.keys()
+    }
+
+    /// Returns the status of the node's process, as of the last start, stop or supervision.
+    pub fn status(&self) -> NodeStatus {
+        self.status
+    }
+
+    /// Returns the directory holding the chainspec and config of the given protocol version.
+    pub fn config_dir(&self, protocol_version: ProtocolVersion) -> PathBuf {
+        self.dir
+            .join("config")
+            .join(protocol_version.to_string().replace('.', "_"))
+    }
+
+    /// Returns the directory holding the node's storage.
+    pub fn storage_dir(&self) -> PathBuf {
+        self.dir.join("storage")
+    }
+
+    /// Returns the directory holding the node's stdout and stderr logs.
+    pub fn logs_dir(&self) -> PathBuf {
+        self.dir.join("logs")
+    }
+
+    /// Returns the binary of the latest installed version.
+    pub(crate) fn latest_binary(&self) -> &Path {
+        // `versions` always holds at least the genesis version.
+        self.versions
+            .values()
+            .next_back()
+            .expect("should have a version")
+    }
+
+    pub(crate) fn install(&mut self, protocol_version: ProtocolVersion, binary: PathBuf) {
+        self.versions.insert(protocol_version, binary);
+    }
+
+    pub(crate) fn start(&mut self) -> Result<(), Error> {
+        if self.process.is_some() {
+            return Err(Error::AlreadyRunning(self.index));
+        }
+        let binary = self.versions[&self.current_version].clone();
+        let config_path = self.config_dir(self.current_version).join("config.toml");
+        let process = Command::new(&binary)
+            .arg("validator")
+            .arg(&config_path)
+            .stdin(Stdio::null())
+            .stdout(self.open_log(STDOUT_FILE_NAME)?)
+            .stderr(self.open_log(STDERR_FILE_NAME)?)
+            .spawn()
+            .map_err(|error| Error::Spawn {
+                index: self.index,
+                binary,
+                error,
+            })?;
+        info!(
+            index = self.index,
+            pid = process.id(),
+            version = %self.current_version,
+            "started node"
+        );
+        self.process = Some(process);
+        self.status = NodeStatus::Running;
+        Ok(())
+    }
+
+    pub(crate) fn stop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            if let Err(error) = process.kill() {
+                warn!(index = self.index, %error, "failed to kill node");
+            }
+            let _ = process.wait();
+            info!(index = self.index, "stopped node");
+        }
+        self.status = NodeStatus::Stopped;
+    }
+
+    /// Checks whether the node's process exited, restarting it with the next installed version if
+    /// it exited successfully at an upgrade point.
+    pub(crate) fn poll(&mut self) -> Result<Option<NodeEvent>, Error> {
+        let exit_status = match self.process.as_mut() {
+            Some(process) => match process.try_wait() {
+                Ok(Some(exit_status)) => exit_status,
+                Ok(None) => return Ok(None),
+                Err(error) => {
+                    warn!(index = self.index, %error, "failed to poll node");
+                    return Ok(None);
+                }
+            },
+            None => return Ok(None),
+        };
+        self.process = None;
+
+        let next_version = self
+            .versions
+            .keys()
+            .copied()
+            .find(|version| *version > self.current_version);
+        match next_version {
+            Some(next_version) if exit_status.success() => {
+                let from = self.current_version;
+                info!(index = self.index, %from, to = %next_version, "upgrading node");
+                self.current_version = next_version;
+                self.start()?;
+                Ok(Some(NodeEvent::Upgraded {
+                    index: self.index,
+                    from,
+                    to: next_version,
+                }))
+            }
+            _ => {
+                let code = exit_status.code();
+                warn!(index = self.index, ?code, "node exited");
+                self.status = NodeStatus::Exited(code);
+                Ok(Some(NodeEvent::Exited {
+                    index: self.index,
+                    code,
+                }))
+            }
+        }
+    }
+
+    fn open_log(&self, file_name: &str) -> Result<File, Error> {
+        let path = self.logs_dir().join(file_name);
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|error| Error::Io {
+                context: "failed to open",
+                path,
+                error,
+            })
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,110 @@
+use std::{fs, path::Path};
+
+use toml::{value::Table, Value};
+
+use crate::Error;
+
+/// A TOML file, such as a chainspec or node config, with entries overridden by dotted keys.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TomlTemplate {
+    root: Table,
+}
+
+impl TomlTemplate {
+    /// Loads the template from the file at `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|error| Error::Io {
+            context: "failed to read",
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let root = toml::from_str(&contents).map_err(|error| Error::ParseToml {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Ok(TomlTemplate { root })
+    }
+
+    /// Sets the entry under the dotted `key`, e.g. `core.validator_slots`, creating any missing
+    /// tables along the way.
+    pub fn set<V: Into<Value>>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        let mut segments: Vec<&str> = key.split('.').collect();
+        let last = segments.pop().unwrap_or_default();
+        let mut table = &mut self.root;
+        for segment in segments {
+            table = match table
+                .entry(segment.to_string())
+                .or_insert_with(|| Value::Table(Table::new()))
+            {
+                Value::Table(table) => table,
+                _ => {
+                    return Err(Error::NotATable {
+                        key: key.to_string(),
+                        segment: segment.to_string(),
+                    })
+                }
+            };
+        }
+        table.insert(last.to_string(), value.into());
+        Ok(())
+    }
+
+    /// Returns the entry under the dotted `key`, if any.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        let mut segments = key.split('.');
+        let mut value = self.root.get(segments.next()?)?;
+        for segment in segments {
+            value = value.as_table()?.get(segment)?;
+        }
+        Some(value)
+    }
+
+    /// Renders the template with all overrides applied and writes it to `path`.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        // Serialize via `Value`, which emits plain entries before tables as TOML requires.
+        let contents = toml::to_string(&Value::Table(self.root.clone()))?;
+        fs::write(path, contents).map_err(|error| Error::Io {
+            context: "failed to write",
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_override_dotted_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chainspec.toml");
+        fs::write(
+            &path,
+            "[protocol]\nversion = '1.0.0'\n\n[core]\nvalidator_slots = 5\n",
+        )
+        .unwrap();
+
+        let mut template = TomlTemplate::from_file(&path).unwrap();
+        template.set("protocol.version", "1.1.0").unwrap();
+        template.set("protocol.activation_point", 2).unwrap();
+        template.set("network.name", "local").unwrap();
+        template.write(&path).unwrap();
+
+        let written = TomlTemplate::from_file(&path).unwrap();
+        assert_eq!(written.get("protocol.version"), Some(&Value::from("1.1.0")));
+        assert_eq!(
+            written.get("protocol.activation_point"),
+            Some(&Value::from(2))
+        );
+        assert_eq!(written.get("network.name"), Some(&Value::from("local")));
+        assert_eq!(written.get("core.validator_slots"), Some(&Value::from(5)));
+
+        assert!(matches!(
+            template.set("protocol.version.major", 1),
+            Err(Error::NotATable { .. })
+        ));
+    }
+}
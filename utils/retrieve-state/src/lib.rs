@@ -28,7 +28,7 @@ use casper_node::{
         },
     },
     storage::Storage,
-    types::{Block, BlockHash, Deploy, DeployOrTransferHash, JsonBlock},
+    types::{Block, BlockHash, BlockHeight, Deploy, DeployOrTransferHash, JsonBlock},
     utils::work_queue::WorkQueue,
 };
 use casper_types::{bytesrepr, bytesrepr::Bytes};
@@ -122,7 +122,7 @@ pub async fn get_genesis_block(client: &Client, url: &str) -> Result<GetBlockRes
         url,
         "chain_get_block",
         Some(GetBlockParams {
-            block_identifier: BlockIdentifier::Height(0),
+            block_identifier: BlockIdentifier::Height(BlockHeight::new(0)),
         }),
     )
     .await
@@ -263,7 +263,7 @@ pub fn get_block_by_identifier(
 ) -> Result<Option<Block>, anyhow::Error> {
     match identifier {
         BlockIdentifier::Hash(ref block_hash) => Ok(storage.read_block(block_hash)?),
-        BlockIdentifier::Height(ref height) => Ok(storage.read_block_by_height(*height)?),
+        BlockIdentifier::Height(height) => Ok(storage.read_block_by_height(height.value())?),
     }
 }
 
@@ -309,7 +309,7 @@ async fn get_block_and_download(
     {
         let block_with_deploys = download_block_with_deploys(client, url, json_block.hash).await?;
         put_block_with_deploys(storage, &block_with_deploys)?;
-        if block_with_deploys.block.header.height != 0 {
+        if block_with_deploys.block.header.height.value() != 0 {
             Ok(Some(block_with_deploys.block.header.parent_hash))
         } else {
             Ok(None)